@@ -0,0 +1,349 @@
+//! Verification throughput for the V2 EIP-155 "exact" facilitator.
+//!
+//! Each benchmark runs a full `verify` call, from the raw request down to the
+//! simulated contract calls, against a mocked JSON-RPC transport. The mock answers
+//! with canned successful results, so the numbers measure the facilitator's own
+//! validation pipeline (parsing, signature classification and recovery, EIP-712
+//! hashing, call encoding) and not network latency.
+//!
+//! Run with `cargo bench -p x402-chain-eip155 --all-features`. To compare
+//! against an earlier run, save a baseline with `-- --save-baseline <name>` and
+//! pass `-- --baseline <name>` later.
+
+use alloy_network::Ethereum;
+use alloy_primitives::{Address, B256, Bytes, U256, address, aliases::U48, hex};
+use alloy_provider::RootProvider;
+use alloy_rpc_client::RpcClient;
+use alloy_rpc_types_eth::TransactionReceipt;
+use alloy_signer::SignerSync;
+use alloy_signer_local::PrivateKeySigner;
+use alloy_sol_types::{SolStruct, SolValue, eip712_domain};
+use alloy_transport::mock::Asserter;
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+use serde_json::json;
+use x402_types::chain::{ChainId, ChainProviderOps};
+use x402_types::proto;
+use x402_types::scheme::X402SchemeFacilitator;
+use x402_types::timestamp::UnixTimestamp;
+
+use x402_chain_eip155::chain::{
+    Eip155ChainReference, Eip155Contracts, Eip155MetaTransactionProvider, MetaTransaction,
+};
+use x402_chain_eip155::v1_eip155_exact::{
+    Eip155ExactError, PERMIT2_ADDRESS, PermitDetails, PermitSingle, PermitWitnessTransferFrom,
+    Sig6492, TokenPermissions, TransferWithAuthorization, Witness, assert_permit2_domain,
+    assert_permit2_witness_domain, x402_exact_permit2_proxy_address,
+};
+use x402_chain_eip155::v2_eip155_exact::V2Eip155ExactFacilitator;
+
+const CHAIN: u64 = 42793;
+const TOKEN: Address = address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6");
+const PAY_TO: Address = address!("0x3333333333333333333333333333333333333333");
+const FACILITATOR: Address = address!("0x4444444444444444444444444444444444444444");
+const AMOUNT: u64 = 10_000_000_000_000_000;
+const EIP6492_MAGIC_SUFFIX: [u8; 32] =
+    hex!("6492649264926492649264926492649264926492649264926492649264926492");
+
+/// Chain provider backed by a mocked RPC transport. Verification never sends
+/// transactions, so the send methods are left unimplemented.
+struct MockChainProvider {
+    chain: Eip155ChainReference,
+    contracts: Eip155Contracts,
+    inner: RootProvider<Ethereum>,
+}
+
+impl MockChainProvider {
+    fn new(asserter: Asserter) -> Self {
+        Self {
+            chain: Eip155ChainReference::new(CHAIN),
+            contracts: Eip155Contracts::default(),
+            inner: RootProvider::new(RpcClient::mocked(asserter)),
+        }
+    }
+}
+
+impl Eip155MetaTransactionProvider for MockChainProvider {
+    type Error = Eip155ExactError;
+    type Inner = RootProvider<Ethereum>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn chain(&self) -> &Eip155ChainReference {
+        &self.chain
+    }
+
+    fn contracts(&self) -> &Eip155Contracts {
+        &self.contracts
+    }
+
+    fn required_confirmations(&self) -> u64 {
+        1
+    }
+
+    async fn send_transaction(
+        &self,
+        _tx: MetaTransaction,
+    ) -> Result<TransactionReceipt, Self::Error> {
+        unimplemented!("benchmarks only verify")
+    }
+
+    async fn send_transaction_from(
+        &self,
+        _tx: MetaTransaction,
+        _from: Address,
+    ) -> Result<TransactionReceipt, Self::Error> {
+        unimplemented!("benchmarks only verify")
+    }
+}
+
+impl ChainProviderOps for MockChainProvider {
+    fn signer_addresses(&self) -> Vec<String> {
+        vec![FACILITATOR.to_string()]
+    }
+
+    fn chain_id(&self) -> ChainId {
+        self.chain.into()
+    }
+}
+
+/// A payload variant, with the RPC results its verification consumes, in order.
+struct Case {
+    name: &'static str,
+    request: proto::VerifyRequest,
+    responses: Vec<Bytes>,
+}
+
+fn requirements() -> serde_json::Value {
+    json!({
+        "scheme": "exact",
+        "network": format!("eip155:{CHAIN}"),
+        "amount": AMOUNT.to_string(),
+        "payTo": PAY_TO,
+        "maxTimeoutSeconds": 300,
+        "asset": TOKEN,
+        "extra": { "name": "BBT", "version": "1" }
+    })
+}
+
+fn verify_request(payload: serde_json::Value) -> proto::VerifyRequest {
+    json!({
+        "x402Version": 2,
+        "paymentPayload": {
+            "x402Version": 2,
+            "accepted": requirements(),
+            "payload": payload,
+        },
+        "paymentRequirements": requirements(),
+    })
+    .into()
+}
+
+fn abi<T: SolValue>(value: T) -> Bytes {
+    value.abi_encode().into()
+}
+
+/// Signs an ERC-3009 authorization and lets `wrap` turn the signature into the
+/// variant under test.
+fn eip3009_payload(
+    signer: &PrivateKeySigner,
+    from: Address,
+    wrap: impl FnOnce(Bytes) -> Bytes,
+) -> serde_json::Value {
+    let now = UnixTimestamp::now().as_secs();
+    let authorization = TransferWithAuthorization {
+        from,
+        to: PAY_TO,
+        value: U256::from(AMOUNT),
+        validAfter: U256::from(now - 60),
+        validBefore: U256::from(now + 3600),
+        nonce: B256::repeat_byte(0x11),
+    };
+    let domain = eip712_domain! {
+        name: "BBT",
+        version: "1",
+        chain_id: CHAIN,
+        verifying_contract: TOKEN,
+    };
+    let signature = signer
+        .sign_hash_sync(&authorization.eip712_signing_hash(&domain))
+        .unwrap();
+    json!({
+        "signature": wrap(Bytes::from(signature.as_bytes().to_vec())),
+        "authorization": {
+            "from": from,
+            "to": PAY_TO,
+            "value": AMOUNT.to_string(),
+            "validAfter": (now - 60).to_string(),
+            "validBefore": (now + 3600).to_string(),
+            "nonce": authorization.nonce,
+        }
+    })
+}
+
+fn cases() -> Vec<Case> {
+    let signer = PrivateKeySigner::random();
+    let owner = signer.address();
+    let smart_wallet = address!("0x5555555555555555555555555555555555555555");
+    let now = UnixTimestamp::now().as_secs();
+    // Balance (and Permit2 allowance) come back from one Multicall3 `aggregate3`.
+    let balance = abi(vec![(true, abi(U256::MAX))]);
+
+    let eoa = Case {
+        name: "eoa",
+        request: verify_request(eip3009_payload(&signer, owner, |sig| sig)),
+        responses: vec![balance.clone(), Bytes::new()],
+    };
+
+    // A contract wallet signature is anything that does not recover to `from`.
+    let eip1271 = Case {
+        name: "eip1271",
+        request: verify_request(eip3009_payload(&signer, smart_wallet, |sig| {
+            [sig.as_ref(), &[0u8; 31]].concat().into()
+        })),
+        responses: vec![balance.clone(), Bytes::new()],
+    };
+
+    let eip6492 = Case {
+        name: "eip6492",
+        request: verify_request(eip3009_payload(&signer, smart_wallet, |sig| {
+            let wrapper = Sig6492 {
+                factory: address!("0x6666666666666666666666666666666666666666"),
+                factoryCalldata: Bytes::from(vec![0xab; 68]),
+                innerSig: sig,
+            };
+            [wrapper.abi_encode_params(), EIP6492_MAGIC_SUFFIX.to_vec()]
+                .concat()
+                .into()
+        })),
+        responses: vec![
+            balance.clone(),
+            // Code at the EIP-6492 validator, so the signature is checked through it.
+            Bytes::from_static(&[0x60]),
+            abi(vec![(true, abi(true)), (true, Bytes::new())]),
+        ],
+    };
+
+    let permit_single = PermitSingle {
+        details: PermitDetails {
+            token: TOKEN,
+            amount: U256::from(AMOUNT).to(),
+            expiration: U48::from(now + 3600),
+            nonce: U48::ZERO,
+        },
+        spender: FACILITATOR,
+        sigDeadline: U256::from(now + 3600),
+    };
+    let permit_signature = signer
+        .sign_hash_sync(&permit_single.eip712_signing_hash(&assert_permit2_domain(
+            &Eip155ChainReference::new(CHAIN),
+            PERMIT2_ADDRESS,
+        )))
+        .unwrap();
+    let permit2_allowance = Case {
+        name: "permit2_allowance",
+        request: verify_request(json!({
+            "permit2": {
+                "owner": owner,
+                "permitSingle": {
+                    "details": {
+                        "token": TOKEN,
+                        "amount": AMOUNT.to_string(),
+                        "expiration": now + 3600,
+                        "nonce": 0,
+                    },
+                    "spender": FACILITATOR,
+                    "sigDeadline": now + 3600,
+                },
+                "signature": Bytes::from(permit_signature.as_bytes().to_vec()),
+            }
+        })),
+        responses: vec![balance.clone(), Bytes::new(), abi(U256::MAX), abi(true)],
+    };
+
+    let proxy = x402_exact_permit2_proxy_address();
+    let permit_witness = PermitWitnessTransferFrom {
+        permitted: TokenPermissions {
+            token: TOKEN,
+            amount: U256::from(AMOUNT),
+        },
+        spender: proxy,
+        nonce: U256::from(7),
+        deadline: U256::from(now + 120),
+        witness: Witness {
+            to: PAY_TO,
+            validAfter: U256::from(now - 60),
+            extra: Bytes::new(),
+        },
+    };
+    let witness_signature = signer
+        .sign_hash_sync(
+            &permit_witness.eip712_signing_hash(&assert_permit2_witness_domain(
+                &Eip155ChainReference::new(CHAIN),
+                PERMIT2_ADDRESS,
+            )),
+        )
+        .unwrap();
+    let permit2_witness = Case {
+        name: "permit2_witness",
+        request: verify_request(json!({
+            "signature": Bytes::from(witness_signature.as_bytes().to_vec()),
+            "permit2Authorization": {
+                "from": owner,
+                "permitted": { "token": TOKEN, "amount": AMOUNT.to_string() },
+                "spender": proxy,
+                "nonce": "7",
+                "deadline": (now + 120).to_string(),
+                "witness": {
+                    "to": PAY_TO,
+                    "validAfter": (now - 60).to_string(),
+                    "extra": "0x",
+                },
+            }
+        })),
+        responses: vec![
+            abi(vec![(true, abi(U256::MAX)), (true, abi(U256::MAX))]),
+            Bytes::new(),
+        ],
+    };
+
+    vec![eoa, eip1271, eip6492, permit2_allowance, permit2_witness]
+}
+
+fn bench_verify(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let asserter = Asserter::new();
+    let facilitator = V2Eip155ExactFacilitator::new(MockChainProvider::new(asserter.clone()));
+
+    let mut group = c.benchmark_group("v2_eip155_exact/verify");
+    group.throughput(Throughput::Elements(1));
+    for case in cases() {
+        // Fail loudly if the canned responses drift from what verification calls.
+        let push_responses = || {
+            for response in &case.responses {
+                asserter.push_success(response);
+            }
+        };
+        push_responses();
+        let response = runtime
+            .block_on(facilitator.verify(&case.request))
+            .unwrap_or_else(|e| panic!("{}: {e}", case.name));
+        assert_eq!(response.0["isValid"], true, "{}: {response:?}", case.name);
+
+        group.bench_function(case.name, |b| {
+            b.iter_batched(
+                push_responses,
+                |()| runtime.block_on(facilitator.verify(&case.request)),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_verify);
+criterion_main!(benches);
@@ -0,0 +1,16 @@
+//! Decodes arbitrary bytes behind the EIP-6492 magic suffix.
+
+#![no_main]
+
+use alloy_primitives::Bytes;
+use libfuzzer_sys::fuzz_target;
+use x402_chain_eip155::v1_eip155_exact::{EIP6492_MAGIC_SUFFIX, StructuredSignature};
+
+fuzz_target!(|data: &[u8]| {
+    let wrapped = Bytes::from([data, &EIP6492_MAGIC_SUFFIX[..]].concat());
+    match StructuredSignature::try_from(wrapped.clone()) {
+        Ok(StructuredSignature::EIP6492 { original, .. }) => assert_eq!(original, wrapped),
+        Ok(other) => panic!("wrapped signature classified as {other:?}"),
+        Err(_) => {}
+    }
+});
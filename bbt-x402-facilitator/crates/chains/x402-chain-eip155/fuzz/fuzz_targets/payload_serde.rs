@@ -0,0 +1,29 @@
+//! Parses arbitrary request bodies as the facilitator does.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use x402_chain_eip155::v1_eip155_exact::{ExactEvmPayload, types as v1_types};
+use x402_chain_eip155::v2_eip155_exact::types as v2_types;
+use x402_types::proto;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(request) = serde_json::from_slice::<proto::VerifyRequest>(data) else {
+        return;
+    };
+    let _ = request.scheme_handler_slug();
+    let _ = (
+        request.payer(),
+        request.payee(),
+        request.amount(),
+        request.asset(),
+    );
+    if let Ok(typed) = v1_types::VerifyRequest::from_proto(&request) {
+        let reencoded = serde_json::to_value(&typed.payment_payload.payload).unwrap();
+        serde_json::from_value::<ExactEvmPayload>(reencoded).unwrap();
+    }
+    if let Ok(typed) = v2_types::VerifyRequest::from_proto(&request) {
+        let reencoded = serde_json::to_value(&typed.payment_payload.payload).unwrap();
+        serde_json::from_value::<ExactEvmPayload>(reencoded).unwrap();
+    }
+});
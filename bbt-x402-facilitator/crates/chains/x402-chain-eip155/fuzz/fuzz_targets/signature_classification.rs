@@ -0,0 +1,25 @@
+//! Classifies arbitrary signature bytes against an arbitrary signer and digest.
+
+#![no_main]
+
+use alloy_primitives::{Address, B256, Bytes};
+use libfuzzer_sys::fuzz_target;
+use x402_chain_eip155::v1_eip155_exact::StructuredSignature;
+
+fuzz_target!(|data: &[u8]| {
+    // The first 52 bytes pick the claimed signer and the digest, the rest is the signature.
+    if data.len() < 52 {
+        return;
+    }
+    let signer = Address::from_slice(&data[..20]);
+    let prehash = B256::from_slice(&data[20..52]);
+    let bytes = Bytes::copy_from_slice(&data[52..]);
+    if let Ok(StructuredSignature::EOA(signature)) =
+        StructuredSignature::try_from_bytes(bytes, signer, &prehash)
+    {
+        assert_eq!(
+            signature.recover_address_from_prehash(&prehash).ok(),
+            Some(signer)
+        );
+    }
+});
@@ -0,0 +1,368 @@
+//! AWS KMS settlement signer.
+//!
+//! Signs with an asymmetric `ECC_SECG_P256K1` KMS key through the KMS JSON API,
+//! so the private key never leaves KMS. Requests are signed with AWS Signature
+//! Version 4 using static credentials from the environment:
+//!
+//! - `AWS_ACCESS_KEY_ID`
+//! - `AWS_SECRET_ACCESS_KEY`
+//! - `AWS_SESSION_TOKEN` (optional)
+//!
+//! The key policy must allow `kms:GetPublicKey` and `kms:Sign`.
+
+use alloy_primitives::{Address, B256, Signature, U256, hex, keccak256};
+use alloy_transport_http::reqwest;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use hmac::{Hmac, Mac};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+use crate::chain::signer::{SettlementSigner, SettlementSignerError, ensure_signed_by};
+
+/// Order of the secp256k1 group.
+const SECP256K1_N: U256 = U256::from_be_bytes(hex!(
+    "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"
+));
+
+/// Static AWS credentials.
+#[derive(Clone)]
+struct AwsCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl std::fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AwsCredentials {
+    fn from_env() -> Result<Self, SettlementSignerError> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Ok(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID").ok_or_else(|| {
+                SettlementSignerError::Config("AWS_ACCESS_KEY_ID is not set".to_string())
+            })?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY").ok_or_else(|| {
+                SettlementSignerError::Config("AWS_SECRET_ACCESS_KEY is not set".to_string())
+            })?,
+            session_token: var("AWS_SESSION_TOKEN"),
+        })
+    }
+}
+
+/// A secp256k1 key held in AWS KMS.
+#[derive(Debug)]
+pub struct AwsKmsSigner {
+    http: reqwest::Client,
+    endpoint: Url,
+    region: String,
+    key_id: String,
+    credentials: AwsCredentials,
+    address: Address,
+}
+
+impl AwsKmsSigner {
+    /// Fetches the public key of `key_id` and derives the signer address.
+    ///
+    /// `endpoint` defaults to `https://kms.<region>.amazonaws.com/`.
+    pub async fn connect(
+        key_id: &str,
+        region: &str,
+        endpoint: Option<Url>,
+    ) -> Result<Self, SettlementSignerError> {
+        let endpoint = match endpoint {
+            Some(endpoint) => endpoint,
+            None => format!("https://kms.{region}.amazonaws.com/")
+                .parse()
+                .map_err(|e: url::ParseError| SettlementSignerError::Config(e.to_string()))?,
+        };
+        let mut signer = Self {
+            http: reqwest::Client::new(),
+            endpoint,
+            region: region.to_string(),
+            key_id: key_id.to_string(),
+            credentials: AwsCredentials::from_env()?,
+            address: Address::ZERO,
+        };
+        let response = signer
+            .call("GetPublicKey", json!({ "KeyId": key_id }))
+            .await?;
+        let public_key = decode_base64_field(&response, "PublicKey")?;
+        signer.address = address_from_spki(&public_key)?;
+        Ok(signer)
+    }
+
+    /// Calls a KMS action with a SigV4-signed request.
+    async fn call(&self, action: &str, body: Value) -> Result<Value, SettlementSignerError> {
+        let body =
+            serde_json::to_vec(&body).map_err(|e| SettlementSignerError::Backend(e.to_string()))?;
+        let host = self
+            .endpoint
+            .host_str()
+            .ok_or_else(|| SettlementSignerError::Config("KMS endpoint has no host".into()))?;
+        let host = match self.endpoint.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        };
+        let amz_date = amz_date(SystemTime::now());
+        let target = format!("TrentService.{action}");
+        let authorization = sigv4_authorization(
+            &self.credentials,
+            &self.region,
+            &host,
+            &amz_date,
+            &target,
+            &body,
+        );
+
+        let mut request = self
+            .http
+            .post(self.endpoint.clone())
+            .header("content-type", "application/x-amz-json-1.1")
+            .header("x-amz-date", &amz_date)
+            .header("x-amz-target", &target)
+            .header("authorization", authorization);
+        if let Some(token) = &self.credentials.session_token {
+            request = request.header("x-amz-security-token", token);
+        }
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| SettlementSignerError::Backend(e.to_string()))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| SettlementSignerError::Backend(e.to_string()))?;
+        if !status.is_success() {
+            return Err(SettlementSignerError::Backend(format!(
+                "KMS {action} returned {status}: {text}"
+            )));
+        }
+        serde_json::from_str(&text).map_err(|e| SettlementSignerError::Backend(e.to_string()))
+    }
+}
+
+#[async_trait::async_trait]
+impl SettlementSigner for AwsKmsSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_hash(&self, hash: &B256) -> Result<Signature, SettlementSignerError> {
+        let response = self
+            .call(
+                "Sign",
+                json!({
+                    "KeyId": self.key_id,
+                    "Message": BASE64.encode(hash),
+                    "MessageType": "DIGEST",
+                    "SigningAlgorithm": "ECDSA_SHA_256",
+                }),
+            )
+            .await?;
+        let der = decode_base64_field(&response, "Signature")?;
+        let (r, s) = parse_der_signature(&der)?;
+        // KMS does not return the recovery id, so try both.
+        for y_parity in [false, true] {
+            let signature = Signature::new(r, s, y_parity);
+            if ensure_signed_by(&signature, hash, self.address).is_ok() {
+                return Ok(signature);
+            }
+        }
+        Err(SettlementSignerError::InvalidSignature(
+            "KMS signature does not recover to the key address".to_string(),
+        ))
+    }
+}
+
+fn decode_base64_field(response: &Value, field: &str) -> Result<Vec<u8>, SettlementSignerError> {
+    let encoded = response[field].as_str().ok_or_else(|| {
+        SettlementSignerError::Backend(format!("KMS response is missing {field}"))
+    })?;
+    BASE64
+        .decode(encoded)
+        .map_err(|e| SettlementSignerError::Backend(format!("invalid {field}: {e}")))
+}
+
+/// Derives the address from a DER `SubjectPublicKeyInfo`, which for secp256k1 ends
+/// with the 65-byte uncompressed point.
+fn address_from_spki(spki: &[u8]) -> Result<Address, SettlementSignerError> {
+    let point = spki
+        .len()
+        .checked_sub(65)
+        .map(|start| &spki[start..])
+        .filter(|point| point[0] == 0x04)
+        .ok_or_else(|| {
+            SettlementSignerError::Config("KMS key is not an uncompressed secp256k1 key".into())
+        })?;
+    Ok(Address::from_slice(&keccak256(&point[1..])[12..]))
+}
+
+/// Parses a DER `ECDSA-Sig-Value` into `(r, s)`, with `s` normalized to the lower half
+/// of the curve order as Ethereum requires.
+fn parse_der_signature(der: &[u8]) -> Result<(U256, U256), SettlementSignerError> {
+    let invalid = || SettlementSignerError::InvalidSignature("malformed DER signature".into());
+    let read_integer = |input: &[u8]| -> Result<(U256, usize), SettlementSignerError> {
+        if input.len() < 2 || input[0] != 0x02 {
+            return Err(invalid());
+        }
+        let len = input[1] as usize;
+        let bytes = input.get(2..2 + len).ok_or_else(invalid)?;
+        let bytes = match bytes {
+            [0, rest @ ..] => rest,
+            bytes => bytes,
+        };
+        if bytes.len() > 32 {
+            return Err(invalid());
+        }
+        Ok((U256::from_be_slice(bytes), 2 + len))
+    };
+    if der.len() < 2 || der[0] != 0x30 || der[1] as usize != der.len() - 2 {
+        return Err(invalid());
+    }
+    let (r, consumed) = read_integer(&der[2..])?;
+    let (s, _) = read_integer(&der[2 + consumed..])?;
+    let s = if s > SECP256K1_N >> 1 {
+        SECP256K1_N - s
+    } else {
+        s
+    };
+    Ok((r, s))
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Formats a time as the SigV4 `YYYYMMDD'T'HHMMSS'Z'` timestamp.
+fn amz_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+/// Derives the SigV4 signing key for `kms` in `region` on `date` (`YYYYMMDD`).
+fn signing_key(secret_access_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac_sha256(
+        format!("AWS4{secret_access_key}").as_bytes(),
+        date.as_bytes(),
+    );
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+/// Builds the SigV4 `Authorization` header of a KMS JSON API request.
+fn sigv4_authorization(
+    credentials: &AwsCredentials,
+    region: &str,
+    host: &str,
+    amz_date: &str,
+    target: &str,
+    body: &[u8],
+) -> String {
+    let date = &amz_date[..8];
+    let mut headers = vec![
+        ("content-type", "application/x-amz-json-1.1"),
+        ("host", host),
+        ("x-amz-date", amz_date),
+    ];
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token));
+    }
+    headers.push(("x-amz-target", target));
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "POST\n/\n\n{canonical_headers}\n{signed_headers}\n{}",
+        hex::encode(Sha256::digest(body))
+    );
+    let scope = format!("{date}/{region}/kms/aws4_request");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let key = signing_key(&credentials.secret_access_key, date, region, "kms");
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+        credentials.access_key_id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_sigv4_signing_key() {
+        // Example from the AWS Signature Version 4 documentation.
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20150830",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9"
+        );
+        assert_eq!(
+            amz_date(UNIX_EPOCH + Duration::from_secs(1_440_938_160)),
+            "20150830T123600Z"
+        );
+    }
+
+    #[test]
+    fn test_parse_der_signature() {
+        let high_s = SECP256K1_N - U256::from(5);
+        let der = [
+            &[0x30, 0x26, 0x02, 0x01, 0x07, 0x02, 0x21, 0x00][..],
+            &high_s.to_be_bytes::<32>()[..],
+        ]
+        .concat();
+        assert_eq!(
+            parse_der_signature(&der).unwrap(),
+            (U256::from(7), U256::from(5))
+        );
+        assert!(parse_der_signature(&der[..10]).is_err());
+    }
+}
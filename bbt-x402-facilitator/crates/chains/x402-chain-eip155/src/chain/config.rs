@@ -1,11 +1,12 @@
-use alloy_primitives::B256;
+use alloy_primitives::{Address, B256};
+use alloy_signer_local::PrivateKeySigner;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use url::Url;
 use x402_types::chain::ChainId;
 use x402_types::config::LiteralOrEnv;
 
-use crate::chain::Eip155ChainReference;
+use crate::chain::{Eip155ChainReference, TokenAmount};
 
 /// Configuration for an EVM-compatible chain in the x402 facilitator.
 ///
@@ -61,13 +62,90 @@ impl Eip155ChainConfig {
     }
 
     /// Returns the numeric chain reference.
+    /// RPC providers for historical reads, falling back to [`Self::rpc`].
+    pub fn archive_rpc(&self) -> &Vec<RpcConfig> {
+        if self.inner.archive_rpc.is_empty() {
+            &self.inner.rpc
+        } else {
+            &self.inner.archive_rpc
+        }
+    }
+
+    /// Whether dedicated [`Self::archive_rpc`] providers are configured.
+    pub fn has_archive_rpc(&self) -> bool {
+        !self.inner.archive_rpc.is_empty()
+    }
+
     pub fn chain_reference(&self) -> Eip155ChainReference {
         self.chain_reference
     }
+
+    /// Returns the native balance (in wei) below which a signer is reported as low.
+    pub fn low_balance_threshold(&self) -> Option<TokenAmount> {
+        self.inner.low_balance_threshold
+    }
+
+    /// Returns the fee bumping policy for stuck transactions, if enabled.
+    pub fn fee_bump(&self) -> Option<FeeBumpConfig> {
+        self.inner.fee_bump
+    }
+
+    /// Returns the contract address overrides for this chain.
+    pub fn contracts(&self) -> Eip155ContractsConfig {
+        self.inner.contracts
+    }
+
+    /// Returns the block confirmations a settlement waits for.
+    pub fn required_confirmations(&self) -> u64 {
+        self.inner.required_confirmations
+    }
+
+    /// Returns the failover and circuit breaking policy of the `rpc` endpoints.
+    pub fn rpc_failover(&self) -> RpcFailoverConfig {
+        self.inner.rpc_failover
+    }
+
+    /// Returns the signer refunds are paid from, if refunds are enabled.
+    pub fn refund_treasury(&self) -> Option<Address> {
+        self.inner.refund_treasury
+    }
+
+    /// Returns how long a pending signer nonce may stay unmined before it is
+    /// reported as stuck.
+    pub fn stuck_nonce_secs(&self) -> u64 {
+        self.inner.stuck_nonce_secs
+    }
+
+    /// Returns how long the EIP-712 domains read from token contracts are cached.
+    pub fn eip712_cache_ttl_secs(&self) -> u64 {
+        self.inner.eip712_cache_ttl_secs
+    }
+
+    /// Returns how long a payment must remain valid to be settled on this chain,
+    /// if overridden.
+    pub fn min_remaining_validity_secs(&self) -> Option<u64> {
+        self.inner.min_remaining_validity_secs
+    }
+
+    /// Returns the bound on concurrent settlement transactions, if any.
+    pub fn settlement_concurrency(&self) -> Option<SettlementConcurrencyConfig> {
+        self.inner.settlement_concurrency
+    }
+
+    /// Returns the ERC-4337 settlement configuration, if settlements go through a bundler.
+    pub fn user_operations(&self) -> Option<&UserOperationConfig> {
+        self.inner.user_operations.as_ref()
+    }
+
+    /// Returns the relay settlements are submitted through, if any.
+    pub fn relay(&self) -> Option<&RelayConfig> {
+        self.inner.relay.as_ref()
+    }
 }
 
 /// Configuration specific to EVM-compatible chains.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Eip155ChainConfigInner {
     /// Whether the chain supports EIP-1559 gas pricing.
     #[serde(default = "eip155_chain_config::default_eip1559")]
@@ -76,13 +154,281 @@ pub struct Eip155ChainConfigInner {
     #[serde(default = "eip155_chain_config::default_flashblocks")]
     pub flashblocks: bool,
     /// Signer configuration for this chain (required).
-    /// Array of private keys (hex format) or env var references.
+    /// Array of private keys (hex format), env var references, or signing backends.
     pub signers: Eip155SignersConfig,
     /// RPC provider configuration for this chain (required).
     pub rpc: Vec<RpcConfig>,
+    /// Archive RPC providers used to verify past settlements (optional).
+    /// Defaults to `rpc`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub archive_rpc: Vec<RpcConfig>,
     /// How long to wait till the transaction receipt is available (optional)
     #[serde(default = "eip155_chain_config::default_receipt_timeout_secs")]
     pub receipt_timeout_secs: u64,
+    /// Native balance, in wei, below which a signer is reported as low on
+    /// `/health/signers` (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_balance_threshold: Option<TokenAmount>,
+    /// Rebroadcast pending transactions with higher fees (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_bump: Option<FeeBumpConfig>,
+    /// Addresses of Permit2 and the EIP-6492 validator where they differ from the
+    /// canonical deployments (optional).
+    #[serde(default, skip_serializing_if = "Eip155ContractsConfig::is_empty")]
+    pub contracts: Eip155ContractsConfig,
+    /// Block confirmations a settlement waits for before `/settle` responds
+    /// (optional). Reorg-prone chains need more than one; the wait counts against
+    /// `receipt_timeout_secs`.
+    #[serde(default = "eip155_chain_config::default_required_confirmations")]
+    pub required_confirmations: u64,
+    /// Failover and circuit breaking between the `rpc` endpoints (optional).
+    #[serde(default)]
+    pub rpc_failover: RpcFailoverConfig,
+    /// Address of the signer refunds are sent from (optional). It must be one of
+    /// `signers` and hold the tokens refunded. Refunds are disabled without it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    pub refund_treasury: Option<Address>,
+    /// Seconds a signer's lowest pending nonce may stay unmined, or skipped nonces
+    /// may stay unfilled, before they are reported as stuck (optional).
+    #[serde(default = "eip155_chain_config::default_stuck_nonce_secs")]
+    pub stuck_nonce_secs: u64,
+    /// Bound on the settlement transactions in flight at once (optional).
+    /// Unbounded without it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settlement_concurrency: Option<SettlementConcurrencyConfig>,
+    /// Seconds the EIP-712 name and version read from a token contract are reused
+    /// for (optional). Zero reads them on every payment.
+    #[serde(default = "eip155_chain_config::default_eip712_cache_ttl_secs")]
+    pub eip712_cache_ttl_secs: u64,
+    /// Seconds a payment must remain valid past now to be settled on this chain
+    /// (optional). Overrides the scheme's `minRemainingValiditySecs`; slow chains
+    /// need more than the default to mine the settlement before it expires.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_remaining_validity_secs: Option<u64>,
+    /// Submit settlements as ERC-4337 UserOperations of a smart account through a
+    /// bundler instead of as transactions of the signers (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_operations: Option<UserOperationConfig>,
+    /// Submit settlements through a managed relay service instead of as
+    /// transactions of the signers (optional). Excludes `user_operations`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relay: Option<RelayConfig>,
+}
+
+impl Eip155ChainConfigInner {
+    /// Returns the settings the chain provider would refuse to be built with, as
+    /// pairs of the field and the problem. Unlike building the provider, this does
+    /// not connect to the RPC endpoints or signing backends.
+    pub fn problems(&self) -> Vec<(&'static str, String)> {
+        let mut problems = Vec::new();
+        if self.signers.is_empty() {
+            problems.push((
+                "signers",
+                "at least one signer should be provided".to_string(),
+            ));
+        }
+        if self.rpc.is_empty() {
+            problems.push((
+                "rpc",
+                "at least one RPC endpoint should be provided".to_string(),
+            ));
+        }
+        if self.user_operations.is_some() && self.relay.is_some() {
+            problems.push((
+                "relay",
+                "user_operations and relay are mutually exclusive".to_string(),
+            ));
+        }
+        let addresses: Option<Vec<Address>> = self
+            .signers
+            .iter()
+            .map(Eip155SignerConfig::address)
+            .collect();
+        if let Some(treasury) = self.refund_treasury
+            && addresses.is_some_and(|addresses| !addresses.contains(&treasury))
+        {
+            problems.push((
+                "refund_treasury",
+                format!("{treasury} is not one of the signers"),
+            ));
+        }
+        problems
+    }
+}
+
+/// How requests fail over between the RPC endpoints of a chain.
+///
+/// Endpoints are tried in the order they are listed. An endpoint that fails
+/// `failure_threshold` requests in a row (transport errors, HTTP errors or no
+/// response within `request_timeout_secs`) is skipped for `cooldown_secs`, then
+/// tried again; a success closes its circuit. Health probes close it as well.
+///
+/// ```json
+/// { "rpc_failover": { "failure_threshold": 3, "cooldown_secs": 30, "request_timeout_secs": 30 } }
+/// ```
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RpcFailoverConfig {
+    /// Consecutive failures after which an endpoint is skipped.
+    #[serde(default = "eip155_chain_config::default_rpc_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Seconds an endpoint is skipped for once its circuit opened.
+    #[serde(default = "eip155_chain_config::default_rpc_cooldown_secs")]
+    pub cooldown_secs: u64,
+    /// Seconds to wait for an endpoint's response before failing over.
+    #[serde(default = "eip155_chain_config::default_rpc_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+}
+
+impl Default for RpcFailoverConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: eip155_chain_config::default_rpc_failure_threshold(),
+            cooldown_secs: eip155_chain_config::default_rpc_cooldown_secs(),
+            request_timeout_secs: eip155_chain_config::default_rpc_request_timeout_secs(),
+        }
+    }
+}
+
+/// Per-chain overrides of the contracts payments are verified and settled through.
+///
+/// Both default to their canonical CREATE2 deployments. An overridden address must
+/// have code when the chain provider is built.
+///
+/// ```json
+/// { "contracts": { "permit2": "0x...", "eip6492_validator": "0x..." } }
+/// ```
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Eip155ContractsConfig {
+    /// Permit2 contract address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    pub permit2: Option<Address>,
+    /// EIP-6492 universal signature validator address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    pub eip6492_validator: Option<Address>,
+}
+
+impl Eip155ContractsConfig {
+    fn is_empty(&self) -> bool {
+        self.permit2.is_none() && self.eip6492_validator.is_none()
+    }
+}
+
+/// Bound on the settlement transactions of a chain, see
+/// [`crate::chain::settlement_limiter`].
+///
+/// At most `max_in_flight` transactions wait for their receipt at once. Up to
+/// `max_queued` more settlements wait for a slot, each for at most
+/// `queue_timeout_secs`; the others are rejected with a hint to retry after
+/// `retry_after_secs`.
+///
+/// ```json
+/// { "settlement_concurrency": { "max_in_flight": 8, "max_queued": 64 } }
+/// ```
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SettlementConcurrencyConfig {
+    /// Settlement transactions in flight at once.
+    pub max_in_flight: usize,
+    /// Settlements waiting for a slot at once.
+    #[serde(default = "eip155_chain_config::default_settlement_max_queued")]
+    pub max_queued: usize,
+    /// Seconds a settlement waits for a slot before it is rejected.
+    #[serde(default = "eip155_chain_config::default_settlement_queue_timeout_secs")]
+    pub queue_timeout_secs: u64,
+    /// Seconds rejected callers are told to wait before retrying.
+    #[serde(default = "eip155_chain_config::default_settlement_retry_after_secs")]
+    pub retry_after_secs: u64,
+}
+
+/// Settlement through an ERC-4337 bundler, see [`crate::chain::user_operation`].
+///
+/// Settlement calls are executed by the smart account `sender`, in EntryPoint v0.7
+/// UserOperations signed by the `signers`, which must be owners of the account and
+/// need no native balance. Gas is sponsored by the ERC-7677 paymaster service at
+/// `paymaster_url`, or paid from the account's EntryPoint deposit without one.
+///
+/// ```json
+/// { "user_operations": { "bundler_url": "https://bundler.example/rpc", "sender": "0x...", "paymaster_url": "https://paymaster.example/rpc" } }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct UserOperationConfig {
+    /// JSON-RPC endpoint of the bundler.
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub bundler_url: Url,
+    /// EntryPoint contract the bundler serves.
+    #[serde(default = "eip155_chain_config::default_entry_point")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub entry_point: Address,
+    /// Deployed smart account settlements are executed by. It must expose
+    /// `execute(address,uint256,bytes)` and accept EIP-191 signatures of its owners.
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub sender: Address,
+    /// ERC-7677 paymaster service sponsoring the UserOperations (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    pub paymaster_url: Option<Url>,
+    /// Context passed to the paymaster service, e.g. a sponsorship policy ID (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_context: Option<serde_json::Value>,
+}
+
+/// Settlement through a managed relay service, see [`crate::chain::relay`].
+///
+/// Settlement calls are submitted to a relayer REST API compatible with OpenZeppelin
+/// Defender's (`POST /txs`, `GET /txs/{id}`), which signs, prices and resubmits them
+/// from its own `address`. The `signers` need no native balance.
+///
+/// ```json
+/// { "relay": { "url": "https://relay.example/api", "api_key": "$RELAY_API_KEY", "address": "0x..." } }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RelayConfig {
+    /// Base URL of the relayer API.
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub url: Url,
+    /// API key sent as a bearer token in the `Authorization` header (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<LiteralOrEnv<String>>,
+    /// Address the relayer sends transactions from, which their gas is estimated for.
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub address: Address,
+    /// Speed passed to the relayer, e.g. `safeLow`, `average`, `fast` or `fastest`.
+    #[serde(default = "eip155_chain_config::default_relay_speed")]
+    pub speed: String,
+}
+
+/// Replacement policy for settlement transactions that stay pending.
+///
+/// A transaction without a receipt after `interval_secs` is replaced by one with the
+/// same nonce and fees raised by `percent`, at most `max_bumps` times and never above
+/// `max_fee_per_gas`. The overall wait is still bounded by `receipt_timeout_secs`.
+///
+/// ```json
+/// { "fee_bump": { "interval_secs": 10, "percent": 20, "max_bumps": 3 } }
+/// ```
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FeeBumpConfig {
+    /// Seconds to wait for a receipt before replacing the transaction.
+    #[serde(default = "eip155_chain_config::default_fee_bump_interval_secs")]
+    pub interval_secs: u64,
+    /// Fee increase per replacement, in percent. Most nodes reject replacements
+    /// below 10%.
+    #[serde(default = "eip155_chain_config::default_fee_bump_percent")]
+    pub percent: u64,
+    /// Maximum number of replacements per settlement.
+    #[serde(default = "eip155_chain_config::default_fee_bump_max_bumps")]
+    pub max_bumps: u32,
+    /// Upper bound, in wei, for the max fee (or gas price) of a replacement.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<TokenAmount>,
 }
 
 mod eip155_chain_config {
@@ -95,12 +441,60 @@ mod eip155_chain_config {
     pub fn default_receipt_timeout_secs() -> u64 {
         30
     }
+    pub fn default_required_confirmations() -> u64 {
+        1
+    }
+    pub fn default_stuck_nonce_secs() -> u64 {
+        120
+    }
+    pub fn default_eip712_cache_ttl_secs() -> u64 {
+        3600
+    }
+    pub fn default_settlement_max_queued() -> usize {
+        64
+    }
+    pub fn default_settlement_queue_timeout_secs() -> u64 {
+        10
+    }
+    pub fn default_settlement_retry_after_secs() -> u64 {
+        5
+    }
+    /// EntryPoint v0.7.
+    pub fn default_relay_speed() -> String {
+        "fast".to_string()
+    }
+    pub fn default_entry_point() -> super::Address {
+        alloy_primitives::address!("0x0000000071727De22E5E9d8BAf0edAc6f37da032")
+    }
+    pub fn default_rpc_failure_threshold() -> u32 {
+        3
+    }
+    pub fn default_rpc_cooldown_secs() -> u64 {
+        30
+    }
+    pub fn default_rpc_request_timeout_secs() -> u64 {
+        30
+    }
+    pub fn default_fee_bump_interval_secs() -> u64 {
+        10
+    }
+    pub fn default_fee_bump_percent() -> u64 {
+        20
+    }
+    pub fn default_fee_bump_max_bumps() -> u32 {
+        3
+    }
+    pub fn default_remote_signer_method() -> String {
+        "signer_signDigest".to_string()
+    }
 }
 
 /// RPC provider configuration for a single provider.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RpcConfig {
     /// HTTP URL for the RPC endpoint.
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub http: Url,
     /// Rate limit for requests per second (optional).
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -109,11 +503,11 @@ pub struct RpcConfig {
 
 /// Configuration for EVM signers.
 ///
-/// Deserializes an array of private key strings (hex format, 0x-prefixed) and
-/// validates them as valid 32-byte private keys. The `EthereumWallet` is created
-/// lazily when needed via the `wallet()` method.
+/// Each entry is either a private key string or a signing backend object. Private
+/// keys are validated as 32-byte hex values at load time; backends are connected
+/// when the chain provider is built (see [`crate::chain::signer`]).
 ///
-/// Each string can be:
+/// A private key string can be:
 /// - A literal hex private key: `"0xcafe..."`
 /// - An environment variable reference: `"$PRIVATE_KEY"` or `"${PRIVATE_KEY}"`
 ///
@@ -122,11 +516,101 @@ pub struct RpcConfig {
 /// {
 ///   "signers": [
 ///     "$HOT_WALLET_KEY",
-///     "0xcafe000000000000000000000000000000000000000000000000000000000001"
+///     { "type": "aws_kms", "key_id": "alias/x402-settlement", "region": "eu-west-1" },
+///     {
+///       "type": "remote",
+///       "url": "https://signer.internal:9000",
+///       "address": "0x4444444444444444444444444444444444444444",
+///       "auth_token": "$SIGNER_TOKEN"
+///     }
 ///   ]
 /// }
 /// ```
-pub type Eip155SignersConfig = Vec<LiteralOrEnv<EvmPrivateKey>>;
+pub type Eip155SignersConfig = Vec<Eip155SignerConfig>;
+
+/// A single settlement signer.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum Eip155SignerConfig {
+    /// A private key, literal or from an environment variable.
+    PrivateKey(LiteralOrEnv<EvmPrivateKey>),
+    /// A signing backend.
+    Backend(SignerBackendConfig),
+}
+
+impl<'de> Deserialize<'de> for Eip155SignerConfig {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Not `untagged`, so that a malformed key or backend keeps its own error message.
+        let value = serde_json::Value::deserialize(deserializer)?;
+        if value.is_string() {
+            LiteralOrEnv::deserialize(value)
+                .map(Eip155SignerConfig::PrivateKey)
+                .map_err(serde::de::Error::custom)
+        } else {
+            SignerBackendConfig::deserialize(value)
+                .map(Eip155SignerConfig::Backend)
+                .map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+impl Eip155SignerConfig {
+    /// Returns the address of the signer, unless a signing backend has to be asked
+    /// for it.
+    pub fn address(&self) -> Option<Address> {
+        match self {
+            Eip155SignerConfig::PrivateKey(key)
+            | Eip155SignerConfig::Backend(SignerBackendConfig::Local { private_key: key }) => {
+                PrivateKeySigner::from_bytes(&B256::from(*key.as_bytes()))
+                    .ok()
+                    .map(|signer| signer.address())
+            }
+            Eip155SignerConfig::Backend(SignerBackendConfig::Remote { address, .. }) => {
+                Some(*address)
+            }
+            Eip155SignerConfig::Backend(SignerBackendConfig::AwsKms { .. }) => None,
+        }
+    }
+}
+
+/// A signing backend holding the settlement key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignerBackendConfig {
+    /// A private key held in process memory.
+    Local {
+        private_key: LiteralOrEnv<EvmPrivateKey>,
+    },
+    /// An `ECC_SECG_P256K1` key in AWS KMS. Credentials are read from the
+    /// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`
+    /// environment variables. Requires the `aws-kms` feature.
+    AwsKms {
+        /// Key id, ARN or alias.
+        key_id: String,
+        region: String,
+        /// Overrides the regional KMS endpoint, e.g. for a VPC endpoint.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+        endpoint: Option<Url>,
+    },
+    /// A remote signer answering a JSON-RPC call with `[address, digest]` params
+    /// with the 65-byte signature of the digest.
+    Remote {
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
+        url: Url,
+        /// Address of the key held by the signer.
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
+        address: Address,
+        /// JSON-RPC method name.
+        #[serde(default = "eip155_chain_config::default_remote_signer_method")]
+        method: String,
+        /// Bearer token sent in the `Authorization` header.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        auth_token: Option<LiteralOrEnv<String>>,
+    },
+}
 
 // ============================================================================
 // EVM Private Key
@@ -0,0 +1,205 @@
+//! Settlement dry runs.
+//!
+//! [`DryRunProvider`] stands in for the settlement provider when `POST /settle` is
+//! called with `dryRun`. It runs the same settlement code, but every transaction is
+//! gas-estimated and recorded instead of being signed and broadcast. The receipts
+//! it hands back are synthetic: successful, with the estimate as gas used and a
+//! zero transaction hash.
+
+use alloy_consensus::{Eip658Value, Receipt, ReceiptEnvelope, ReceiptWithBloom};
+use alloy_network::TransactionBuilder;
+use alloy_primitives::{Address, Bloom, Bytes, TxHash, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::{TransactionReceipt, TransactionRequest};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+use crate::chain::eip712_cache::Eip712DomainCache;
+use crate::chain::provider::{
+    Eip155Contracts, Eip155MetaTransactionProvider, MetaTransaction, MetaTransactionSendError,
+};
+use crate::chain::signer::SettlementSigner;
+use crate::chain::types::{Eip155ChainReference, PayerAddress, TokenAmount};
+
+/// A transaction the settlement would have sent.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunTransaction {
+    pub from: Address,
+    pub to: Address,
+    /// Calldata.
+    pub data: Bytes,
+    /// Estimated gas limit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas: Option<u64>,
+    /// Why the gas could not be estimated. Only set for transactions that depend on
+    /// an earlier transaction of the same settlement being mined.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of a settlement dry run.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementDryRun {
+    pub success: bool,
+    pub dry_run: bool,
+    pub payer: String,
+    pub network: String,
+    /// Transactions in the order they would be sent.
+    pub transactions: Vec<DryRunTransaction>,
+    /// Current gas price, in wei.
+    pub gas_price: TokenAmount,
+    /// Estimated gas cost of all transactions, in wei.
+    pub estimated_cost: TokenAmount,
+}
+
+/// A settlement provider that records transactions instead of sending them.
+#[derive(Debug)]
+pub struct DryRunProvider<'a, P> {
+    provider: &'a P,
+    from: Address,
+    transactions: Mutex<Vec<DryRunTransaction>>,
+}
+
+impl<'a, P: Eip155MetaTransactionProvider> DryRunProvider<'a, P> {
+    /// Wraps `provider`. Transactions without an explicit sender are estimated from `from`.
+    pub fn new(provider: &'a P, from: Address) -> Self {
+        Self {
+            provider,
+            from,
+            transactions: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Prices the recorded transactions at the current gas price.
+    pub async fn finish(
+        self,
+        payer: PayerAddress,
+        network: String,
+    ) -> Result<SettlementDryRun, MetaTransactionSendError> {
+        let gas_price = self.provider.inner().get_gas_price().await?;
+        let transactions = self
+            .transactions
+            .into_inner()
+            .expect("dry run lock poisoned");
+        let gas: u64 = transactions.iter().filter_map(|tx| tx.gas).sum();
+        Ok(SettlementDryRun {
+            success: true,
+            dry_run: true,
+            payer: payer.to_string(),
+            network,
+            transactions,
+            gas_price: TokenAmount(U256::from(gas_price)),
+            estimated_cost: TokenAmount(U256::from(gas_price) * U256::from(gas)),
+        })
+    }
+
+    async fn record(
+        &self,
+        tx: MetaTransaction,
+        from: Address,
+    ) -> Result<TransactionReceipt, MetaTransactionSendError> {
+        let request = TransactionRequest::default()
+            .with_from(from)
+            .with_to(tx.to)
+            .with_input(tx.calldata.clone());
+        let estimate = self.provider.inner().estimate_gas(request).await;
+        let mut transactions = self.transactions.lock().expect("dry run lock poisoned");
+        let (gas, error) = match estimate {
+            Ok(gas) => (Some(gas), None),
+            // E.g. a Permit2 `transferFrom` that needs the preceding `permit` on chain.
+            Err(e) if !transactions.is_empty() => (None, Some(e.to_string())),
+            Err(e) => return Err(e.into()),
+        };
+        transactions.push(DryRunTransaction {
+            from,
+            to: tx.to,
+            data: tx.calldata,
+            gas,
+            error,
+        });
+        Ok(synthetic_receipt(from, tx.to, gas.unwrap_or_default()))
+    }
+}
+
+/// A successful receipt with a zero transaction hash, for a transaction that was
+/// not sent.
+pub(crate) fn synthetic_receipt(from: Address, to: Address, gas_used: u64) -> TransactionReceipt {
+    TransactionReceipt {
+        inner: ReceiptEnvelope::Eip1559(ReceiptWithBloom {
+            receipt: Receipt {
+                status: Eip658Value::Eip658(true),
+                cumulative_gas_used: gas_used,
+                logs: Vec::new(),
+            },
+            logs_bloom: Bloom::ZERO,
+        }),
+        transaction_hash: TxHash::ZERO,
+        transaction_index: None,
+        block_hash: None,
+        block_number: None,
+        gas_used,
+        effective_gas_price: 0,
+        blob_gas_used: None,
+        blob_gas_price: None,
+        from,
+        to: Some(to),
+        contract_address: None,
+    }
+}
+
+impl<P> Eip155MetaTransactionProvider for DryRunProvider<'_, P>
+where
+    P: Eip155MetaTransactionProvider + Sync,
+{
+    type Error = MetaTransactionSendError;
+    type Inner = P::Inner;
+
+    fn inner(&self) -> &Self::Inner {
+        self.provider.inner()
+    }
+
+    fn chain(&self) -> &Eip155ChainReference {
+        self.provider.chain()
+    }
+
+    fn contracts(&self) -> &Eip155Contracts {
+        self.provider.contracts()
+    }
+
+    fn required_confirmations(&self) -> u64 {
+        self.provider.required_confirmations()
+    }
+
+    fn refund_treasury(&self) -> Option<Address> {
+        self.provider.refund_treasury()
+    }
+
+    fn signer(&self, address: Address) -> Option<Arc<dyn SettlementSigner>> {
+        self.provider.signer(address)
+    }
+
+    fn eip712_domains(&self) -> Option<&Eip712DomainCache> {
+        self.provider.eip712_domains()
+    }
+
+    fn min_remaining_validity_secs(&self) -> Option<u64> {
+        self.provider.min_remaining_validity_secs()
+    }
+
+    fn send_transaction(
+        &self,
+        tx: MetaTransaction,
+    ) -> impl Future<Output = Result<TransactionReceipt, Self::Error>> + Send {
+        self.record(tx, self.from)
+    }
+
+    fn send_transaction_from(
+        &self,
+        tx: MetaTransaction,
+        from: Address,
+    ) -> impl Future<Output = Result<TransactionReceipt, Self::Error>> + Send {
+        self.record(tx, from)
+    }
+}
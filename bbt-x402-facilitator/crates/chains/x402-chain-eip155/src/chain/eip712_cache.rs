@@ -0,0 +1,135 @@
+//! Cache of the EIP-712 domain name and version of token contracts.
+//!
+//! Payment requirements without `extra` leave the EIP-712 domain of an EIP-3009
+//! token to the facilitator, which reads `name()` and `version()` from the token on
+//! every verify and settle. Those values only change when a token is upgraded, so
+//! each chain provider keeps them in an [`Eip712DomainCache`] shared by all requests
+//! and schemes of the chain.
+//!
+//! Entries expire after `eip712_cache_ttl_secs` (zero disables the cache), and can be
+//! dropped per token or as a whole, e.g. after a token upgrade.
+
+use alloy_primitives::Address;
+use dashmap::DashMap;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// EIP-712 domain name and version per token contract of a chain.
+#[derive(Debug)]
+pub struct Eip712DomainCache {
+    ttl: Duration,
+    entries: DashMap<Address, CachedDomain>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedDomain {
+    name: String,
+    version: String,
+    fetched_at: Instant,
+}
+
+/// Entry of an [`Eip712DomainCache`], as listed by [`Eip712DomainCache::entries`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip712DomainEntry {
+    pub token: Address,
+    pub name: String,
+    pub version: String,
+    /// Seconds since the values were read from the token.
+    pub age_secs: u64,
+}
+
+impl Eip712DomainCache {
+    /// Cache keeping entries for `ttl`. A zero `ttl` caches nothing.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: DashMap::new(),
+        }
+    }
+
+    /// The cached name and version of `token`, unless missing or expired.
+    pub fn get(&self, token: Address) -> Option<(String, String)> {
+        let entry = self.entries.get(&token)?;
+        if entry.fetched_at.elapsed() < self.ttl {
+            return Some((entry.name.clone(), entry.version.clone()));
+        }
+        drop(entry);
+        self.entries
+            .remove_if(&token, |_, entry| entry.fetched_at.elapsed() >= self.ttl);
+        None
+    }
+
+    /// Records the name and version just read from `token`.
+    pub fn insert(&self, token: Address, name: String, version: String) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        self.entries.insert(
+            token,
+            CachedDomain {
+                name,
+                version,
+                fetched_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Drops the entry of `token`. Returns whether there was one.
+    pub fn invalidate(&self, token: Address) -> bool {
+        self.entries.remove(&token).is_some()
+    }
+
+    /// Drops every entry. Returns how many there were.
+    pub fn clear(&self) -> usize {
+        let count = self.entries.len();
+        self.entries.clear();
+        count
+    }
+
+    /// The cached entries, expired ones included until they are next looked up.
+    pub fn entries(&self) -> Vec<Eip712DomainEntry> {
+        let mut entries: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| Eip712DomainEntry {
+                token: *entry.key(),
+                name: entry.name.clone(),
+                version: entry.version.clone(),
+                age_secs: entry.fetched_at.elapsed().as_secs(),
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.token);
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_hits_until_invalidated() {
+        let cache = Eip712DomainCache::new(Duration::from_secs(60));
+        let token = Address::repeat_byte(1);
+        assert_eq!(cache.get(token), None);
+        cache.insert(token, "BBT".to_string(), "1".to_string());
+        assert_eq!(cache.get(token), Some(("BBT".to_string(), "1".to_string())));
+        assert_eq!(cache.entries().len(), 1);
+        assert!(cache.invalidate(token));
+        assert_eq!(cache.get(token), None);
+
+        cache.insert(token, "BBT".to_string(), "2".to_string());
+        assert_eq!(cache.clear(), 1);
+        assert_eq!(cache.get(token), None);
+    }
+
+    #[test]
+    fn test_zero_ttl_disables_cache() {
+        let cache = Eip712DomainCache::new(Duration::ZERO);
+        let token = Address::repeat_byte(2);
+        cache.insert(token, "BBT".to_string(), "1".to_string());
+        assert_eq!(cache.get(token), None);
+        assert!(cache.entries().is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+//! Replacement of stuck settlement transactions.
+//!
+//! Without fee bumping, a settlement transaction that is underpriced for the current
+//! base fee can stay pending until the receipt timeout, holding its nonce and every
+//! later transaction of the same signer. With a [`FeeBumpConfig`], the transaction is
+//! re-signed with the same nonce and higher fees every `interval_secs`, and all
+//! broadcast versions are watched until one of them is mined.
+
+use alloy_consensus::Transaction;
+use alloy_network::TransactionBuilder;
+use alloy_primitives::{Address, TxHash};
+use alloy_provider::{PendingTransactionBuilder, PendingTransactionError, Provider, SendableTx};
+use alloy_rpc_types_eth::{TransactionReceipt, TransactionRequest};
+use alloy_transport::TransportError;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::chain::config::FeeBumpConfig;
+use crate::chain::provider::{InnerProvider, MetaTransactionSendError};
+
+/// How often pending transactions are checked for a receipt.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Fees of a signed transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Fees {
+    Eip1559 {
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    },
+    Legacy {
+        gas_price: u128,
+    },
+}
+
+impl Fees {
+    fn of(tx: &impl Transaction) -> Self {
+        match tx.gas_price() {
+            Some(gas_price) => Fees::Legacy { gas_price },
+            None => Fees::Eip1559 {
+                max_fee_per_gas: tx.max_fee_per_gas(),
+                max_priority_fee_per_gas: tx.max_priority_fee_per_gas().unwrap_or_default(),
+            },
+        }
+    }
+
+    /// Fees raised by `config.percent`, or `None` if they would exceed the configured cap.
+    pub(crate) fn bumped(self, config: &FeeBumpConfig) -> Option<Self> {
+        let bump = |fee: u128| {
+            let raised = fee.saturating_mul(100 + u128::from(config.percent)) / 100;
+            raised.max(fee.saturating_add(1))
+        };
+        let bumped = match self {
+            Fees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => Fees::Eip1559 {
+                max_fee_per_gas: bump(max_fee_per_gas),
+                max_priority_fee_per_gas: bump(max_priority_fee_per_gas),
+            },
+            Fees::Legacy { gas_price } => Fees::Legacy {
+                gas_price: bump(gas_price),
+            },
+        };
+        let cap = config
+            .max_fee_per_gas
+            .map(|cap| u128::try_from(cap.0).unwrap_or(u128::MAX));
+        match (cap, bumped) {
+            (
+                Some(cap),
+                Fees::Eip1559 {
+                    max_fee_per_gas, ..
+                },
+            ) if max_fee_per_gas > cap => None,
+            (Some(cap), Fees::Legacy { gas_price }) if gas_price > cap => None,
+            _ => Some(bumped),
+        }
+    }
+
+    fn apply(self, txr: &mut TransactionRequest) {
+        match self {
+            Fees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                txr.set_max_fee_per_gas(max_fee_per_gas);
+                txr.set_max_priority_fee_per_gas(max_priority_fee_per_gas);
+            }
+            Fees::Legacy { gas_price } => txr.set_gas_price(gas_price),
+        }
+    }
+}
+
+/// Signs and broadcasts `txr`, returning the fees and hash of the sent transaction.
+async fn sign_and_send(
+    provider: &InnerProvider,
+    txr: TransactionRequest,
+) -> Result<(TransactionRequest, Fees, TxHash), MetaTransactionSendError> {
+    let SendableTx::Envelope(envelope) = provider.fill(txr.clone()).await? else {
+        return Err(MetaTransactionSendError::Custom(
+            "transaction was not signed by the wallet".to_string(),
+        ));
+    };
+    // Pin everything the fillers chose, so a replacement only differs in fees.
+    let mut sent = txr;
+    sent.set_nonce(envelope.nonce());
+    sent.set_gas_limit(envelope.gas_limit());
+    if let Some(chain_id) = envelope.chain_id() {
+        sent.set_chain_id(chain_id);
+    }
+    let fees = Fees::of(&envelope);
+    let pending = provider.send_tx_envelope(envelope).await?;
+    Ok((sent, fees, *pending.tx_hash()))
+}
+
+async fn find_receipt(
+    provider: &InnerProvider,
+    hashes: &[TxHash],
+) -> Result<Option<TransactionReceipt>, TransportError> {
+    for hash in hashes {
+        if let Some(receipt) = provider.get_transaction_receipt(*hash).await? {
+            return Ok(Some(receipt));
+        }
+    }
+    Ok(None)
+}
+
+/// Sends `txr` from `from` and waits for a receipt, replacing the transaction with
+/// higher fees while it stays pending.
+///
+/// Returns the receipt of whichever version was mined, after `confirmations` blocks.
+/// Fails if no version is mined within `receipt_timeout`.
+pub(crate) async fn send_with_fee_bumping(
+    provider: &InnerProvider,
+    txr: TransactionRequest,
+    _from: Address,
+    confirmations: u64,
+    receipt_timeout: Duration,
+    config: &FeeBumpConfig,
+) -> Result<TransactionReceipt, MetaTransactionSendError> {
+    let deadline = Instant::now() + receipt_timeout;
+    let interval = Duration::from_secs(config.interval_secs.max(1));
+    let (sent, mut fees, hash) = sign_and_send(provider, txr).await?;
+    let mut hashes = vec![hash];
+    let mut bumps = 0;
+    let mut next_bump = Instant::now() + interval;
+
+    loop {
+        if let Some(receipt) = find_receipt(provider, &hashes).await? {
+            if confirmations <= 1 {
+                return Ok(receipt);
+            }
+            let pending =
+                PendingTransactionBuilder::new(provider.root().clone(), receipt.transaction_hash)
+                    .with_required_confirmations(confirmations)
+                    .with_timeout(Some(deadline.saturating_duration_since(Instant::now())));
+            return Ok(pending.get_receipt().await?);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(
+                PendingTransactionError::TxWatcher(alloy_provider::WatchTxError::Timeout).into(),
+            );
+        }
+        if now >= next_bump && bumps < config.max_bumps {
+            bumps += 1;
+            next_bump = now + interval;
+            match fees.bumped(config) {
+                Some(bumped) => {
+                    let mut replacement = sent.clone();
+                    bumped.apply(&mut replacement);
+                    match sign_and_send(provider, replacement).await {
+                        Ok((_, _, hash)) => {
+                            #[cfg(feature = "telemetry")]
+                            tracing::info!(from = %_from, %hash, bump = bumps, fees = ?bumped, "Replaced pending transaction with higher fees");
+                            fees = bumped;
+                            hashes.push(hash);
+                        }
+                        // The previous version may have been mined meanwhile ("nonce too low"),
+                        // or the node wants a larger bump. Keep watching what was sent.
+                        Err(_e) => {
+                            #[cfg(feature = "telemetry")]
+                            tracing::warn!(from = %_from, error = %_e, "Transaction replacement rejected");
+                        }
+                    }
+                }
+                None => {
+                    #[cfg(feature = "telemetry")]
+                    tracing::warn!(from = %_from, fees = ?fees, "Fee bump cap reached, waiting for pending transaction");
+                    bumps = config.max_bumps;
+                }
+            }
+        }
+        let wait = RECEIPT_POLL_INTERVAL.min(deadline.saturating_duration_since(now));
+        tokio::time::sleep(wait).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::TokenAmount;
+    use alloy_primitives::U256;
+
+    #[test]
+    fn test_fee_bump() {
+        let config = FeeBumpConfig {
+            interval_secs: 10,
+            percent: 20,
+            max_bumps: 3,
+            max_fee_per_gas: Some(TokenAmount(U256::from(130))),
+        };
+        let fees = Fees::Eip1559 {
+            max_fee_per_gas: 100,
+            max_priority_fee_per_gas: 2,
+        };
+        let bumped = fees.bumped(&config).unwrap();
+        assert_eq!(
+            bumped,
+            Fees::Eip1559 {
+                max_fee_per_gas: 120,
+                max_priority_fee_per_gas: 3,
+            }
+        );
+        assert_eq!(bumped.bumped(&config), None);
+        assert_eq!(
+            Fees::Legacy { gas_price: 50 }.bumped(&config),
+            Some(Fees::Legacy { gas_price: 60 })
+        );
+    }
+}
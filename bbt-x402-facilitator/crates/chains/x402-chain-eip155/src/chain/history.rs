@@ -0,0 +1,157 @@
+//! Verification of past settlements.
+//!
+//! Settlement disputes can come up long after the payment, when the facilitator no
+//! longer has any record of it. [`verify_settlement`] answers from chain data
+//! alone: given a transaction hash and the payment it is claimed to settle, it
+//! checks that the transaction succeeded and emitted the matching ERC-20
+//! `Transfer`.
+//!
+//! Receipts and logs of old blocks are pruned by many RPC nodes, so the chain
+//! config can point these reads at archive nodes with `archive_rpc`. The archive
+//! provider is read-only and never used for settlement.
+
+use alloy_primitives::{Address, TxHash, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::BlockNumberOrTag;
+use alloy_sol_types::sol;
+use alloy_transport::TransportError;
+use serde::{Deserialize, Serialize};
+
+sol! {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+}
+
+/// The payment a transaction is claimed to settle.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpectedSettlement {
+    /// Token holder the funds were taken from.
+    pub payer: Address,
+    /// Recipient of the payment.
+    pub pay_to: Address,
+    /// Token contract.
+    pub asset: Address,
+    /// Amount in the token's smallest unit.
+    pub amount: U256,
+}
+
+/// A transaction and the payment it is claimed to settle.
+///
+/// ```json
+/// { "transaction": "0x...", "payer": "0x...", "payTo": "0x...", "asset": "0x...", "amount": "1000" }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SettlementQuery {
+    pub transaction: TxHash,
+    #[serde(flatten)]
+    pub expected: ExpectedSettlement,
+}
+
+/// Outcome of [`verify_settlement`].
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementVerification {
+    pub transaction: TxHash,
+    /// `true` if the transaction succeeded and transferred the expected amount.
+    pub settled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    /// Unix timestamp of the block that included the transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_timestamp: Option<u64>,
+    /// Why the transaction does not settle the expected payment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl SettlementVerification {
+    fn rejected(transaction: TxHash, reason: &str) -> Self {
+        Self {
+            transaction,
+            settled: false,
+            block_number: None,
+            block_timestamp: None,
+            reason: Some(reason.to_string()),
+        }
+    }
+}
+
+/// Checks on chain that `transaction` settled `expected`.
+///
+/// The transaction must have succeeded and contain a `Transfer` log of
+/// `expected.asset` moving exactly `expected.amount` from the payer to the
+/// recipient. Fails only if the node cannot be queried.
+pub async fn verify_settlement<P: Provider>(
+    provider: &P,
+    transaction: TxHash,
+    expected: &ExpectedSettlement,
+) -> Result<SettlementVerification, TransportError> {
+    let Some(receipt) = provider.get_transaction_receipt(transaction).await? else {
+        return Ok(SettlementVerification::rejected(
+            transaction,
+            "transaction not found",
+        ));
+    };
+    let mut verification = SettlementVerification::rejected(transaction, "");
+    verification.block_number = receipt.block_number;
+    if let Some(block_number) = receipt.block_number {
+        verification.block_timestamp = provider
+            .get_block_by_number(BlockNumberOrTag::Number(block_number))
+            .await?
+            .map(|block| block.header.timestamp);
+    }
+    if !receipt.status() {
+        verification.reason = Some("transaction reverted".to_string());
+        return Ok(verification);
+    }
+    let transfers: Vec<Transfer> = receipt
+        .inner
+        .logs()
+        .iter()
+        .filter(|log| log.address() == expected.asset)
+        .filter_map(|log| log.log_decode::<Transfer>().ok())
+        .map(|log| log.inner.data)
+        .filter(|transfer| transfer.from == expected.payer && transfer.to == expected.pay_to)
+        .collect();
+    verification.reason = match transfers.as_slice() {
+        [] => Some("no transfer from the payer to the recipient".to_string()),
+        transfers if transfers.iter().any(|t| t.value == expected.amount) => None,
+        [transfer, ..] => Some(format!(
+            "transferred {} instead of {}",
+            transfer.value, expected.amount
+        )),
+    };
+    verification.settled = verification.reason.is_none();
+    Ok(verification)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn test_expected_settlement_json() {
+        let query: SettlementQuery = serde_json::from_value(serde_json::json!({
+            "network": "eip155:42793",
+            "transaction": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "payer": "0x1111111111111111111111111111111111111111",
+            "payTo": "0x2222222222222222222222222222222222222222",
+            "asset": "0x3333333333333333333333333333333333333333",
+            "amount": "1000"
+        }))
+        .unwrap();
+        let expected = query.expected;
+        assert_eq!(
+            expected.pay_to,
+            address!("0x2222222222222222222222222222222222222222")
+        );
+        assert_eq!(expected.amount, U256::from(1000));
+
+        let verification = SettlementVerification::rejected(TxHash::ZERO, "transaction reverted");
+        let json = serde_json::to_value(&verification).unwrap();
+        assert_eq!(json["settled"], false);
+        assert_eq!(json["reason"], "transaction reverted");
+        assert!(json.get("blockNumber").is_none());
+    }
+}
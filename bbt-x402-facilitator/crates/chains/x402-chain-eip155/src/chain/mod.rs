@@ -14,7 +14,15 @@
 //! # Submodules
 //!
 //! - [`types`] - Wire format types like [`ChecksummedAddress`](types::ChecksummedAddress) and [`TokenAmount`](types::TokenAmount)
+//! - [`eip712_cache`] - Cache of the EIP-712 domains of token contracts
 //! - [`pending_nonce_manager`] - Nonce management for concurrent transaction submission
+//! - [`permit2_nonces`] - Permit2 nonces of a payer, for clients building Permit2 payloads
+//! - [`prepared`] - Settlement transactions for submission outside the facilitator
+//! - [`relay`] - Settlement through a managed relay service
+//! - [`rpc_failover`] - Ordered failover and circuit breaking across a chain's RPC endpoints
+//! - [`settlement_limiter`] - Bound on the settlement transactions in flight per chain
+//! - [`signer`] - Settlement signer backends (local key, remote signer, AWS KMS)
+//! - [`user_operation`] - Settlement through an ERC-4337 bundler and paymaster
 //!
 //! # ERC-3009 Support
 //!
@@ -35,6 +43,7 @@
 //!     eip712: Some(TokenDeploymentEip712 {
 //!         name: "BBT".into(),
 //!         version: "1".into(),
+//!         domain: Default::default(),
 //!     }),
 //! };
 //!
@@ -45,16 +54,54 @@
 
 pub mod types;
 
+#[cfg(feature = "aws-kms")]
+pub mod aws_kms;
 #[cfg(feature = "facilitator")]
 pub mod config;
 #[cfg(feature = "facilitator")]
+pub mod dry_run;
+#[cfg(feature = "facilitator")]
+pub mod eip712_cache;
+#[cfg(feature = "facilitator")]
+mod fee_bump;
+#[cfg(feature = "facilitator")]
+pub mod history;
+#[cfg(feature = "facilitator")]
 pub mod pending_nonce_manager;
 #[cfg(feature = "facilitator")]
+pub mod permit2_nonces;
+#[cfg(feature = "facilitator")]
+pub mod prepared;
+#[cfg(feature = "facilitator")]
 pub mod provider;
+#[cfg(feature = "facilitator")]
+pub mod relay;
+#[cfg(feature = "facilitator")]
+pub mod rpc_failover;
+#[cfg(feature = "facilitator")]
+pub mod settlement_limiter;
+#[cfg(feature = "facilitator")]
+pub mod signer;
+#[cfg(feature = "facilitator")]
+pub mod user_operation;
 
+#[cfg(feature = "facilitator")]
+pub use dry_run::{DryRunProvider, SettlementDryRun};
+#[cfg(feature = "facilitator")]
+pub use eip712_cache::{Eip712DomainCache, Eip712DomainEntry};
+#[cfg(feature = "facilitator")]
+pub use history::{ExpectedSettlement, SettlementQuery, SettlementVerification};
 #[cfg(feature = "facilitator")]
 pub use pending_nonce_manager::*;
 #[cfg(feature = "facilitator")]
+pub use permit2_nonces::{Permit2NonceQuery, Permit2Nonces};
+#[cfg(feature = "facilitator")]
+pub use prepared::{PreparedSettlement, PreparedTx, PreparingProvider};
+#[cfg(feature = "facilitator")]
 pub use provider::*;
+#[cfg(feature = "facilitator")]
+pub use settlement_limiter::{SettlementLimitExceeded, SettlementLimiterStatus};
+#[cfg(feature = "facilitator")]
+pub use signer::{SettlementSigner, SettlementSignerError};
 
 pub use types::*;
@@ -4,6 +4,22 @@
 //! upon Alloy's default implementation by querying pending transactions when fetching
 //! the initial nonce. This prevents "nonce too low" errors when the application restarts
 //! while transactions are still in the mempool.
+//!
+//! # Reconciliation
+//!
+//! The cache can drift from the chain: a transaction that never reached the mempool
+//! leaves a gap that holds up every later nonce, and an underpriced transaction can
+//! stay pending forever. [`PendingNonceManager::reconcile`] compares the cache with
+//! `eth_getTransactionCount` at the latest block and in the mempool, on startup and
+//! periodically:
+//!
+//! - a cache behind the mempool (another process sent from the signer) is resynced
+//!   right away
+//! - a cache ahead of the mempool for longer than the stuck threshold has skipped
+//!   nonces, and is resynced so that the next transactions fill them
+//! - a lowest pending nonce that is not mined within the stuck threshold is reported
+//!   as stuck; `Eip155ChainProvider::cancel_pending` replaces such transactions with
+//!   self-transfers
 
 use alloy_primitives::Address;
 use alloy_provider::Provider;
@@ -11,8 +27,45 @@ use alloy_provider::fillers::NonceManager;
 use alloy_transport::TransportResult;
 use async_trait::async_trait;
 use dashmap::DashMap;
+use serde::Serialize;
+use std::ops::Range;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Sentinel of a nonce not fetched yet.
+const NONE: u64 = u64::MAX;
+
+/// Nonce state of a signer, as found by [`PendingNonceManager::reconcile`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NonceReconciliation {
+    pub address: Address,
+    /// Nonce of the next transaction after the latest block.
+    pub confirmed_nonce: u64,
+    /// Nonce of the next transaction, counting the mempool.
+    pub pending_nonce: u64,
+    /// Nonce the cache would have handed out next, `None` if it was not cached.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_nonce: Option<u64>,
+    /// Nonces handed out by the cache that the node does not know of.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gap: Option<Range<u64>>,
+    /// Whether the transaction at `confirmed_nonce` has been pending for longer
+    /// than the stuck threshold.
+    pub stuck: bool,
+    /// Whether the cache was reset to `pending_nonce`.
+    pub resynced: bool,
+}
+
+/// When a signer's confirmed nonce last moved, and since when its cache is ahead.
+#[derive(Debug, Clone, Copy)]
+struct NonceProgress {
+    confirmed: u64,
+    since: Instant,
+    gap_since: Option<Instant>,
+}
 
 /// A nonce manager that caches nonces locally and queries pending transactions on initialization.
 ///
@@ -38,6 +91,8 @@ use tokio::sync::Mutex;
 pub struct PendingNonceManager {
     /// Cache of nonces per address. Each address has its own mutex-protected nonce value.
     nonces: Arc<DashMap<Address, Arc<Mutex<u64>>>>,
+    /// Reconciliation history per address, see [`PendingNonceManager::reconcile`].
+    progress: Arc<DashMap<Address, NonceProgress>>,
 }
 
 #[async_trait]
@@ -47,9 +102,6 @@ impl NonceManager for PendingNonceManager {
         P: Provider<N>,
         N: alloy_network::Network,
     {
-        // Use `u64::MAX` as a sentinel value to indicate that the nonce has not been fetched yet.
-        const NONE: u64 = u64::MAX;
-
         // Locks dashmap internally for a short duration to clone the `Arc`.
         // We also don't want to hold the dashmap lock through the await point below.
         let nonce = {
@@ -86,9 +138,203 @@ impl PendingNonceManager {
     pub async fn reset_nonce(&self, address: Address) {
         if let Some(nonce_lock) = self.nonces.get(&address) {
             let mut nonce = nonce_lock.lock().await;
-            *nonce = u64::MAX; // NONE sentinel - will trigger fresh query
+            *nonce = NONE; // will trigger fresh query
             #[cfg(feature = "telemetry")]
             tracing::debug!(%address, "reset nonce cache, will requery on next use");
         }
     }
+
+    /// Fetches the pending nonce of `address` ahead of its first transaction.
+    ///
+    /// Does nothing if the nonce is already cached. The cache holds the last used
+    /// nonce, so the fetched one is stored minus one; for an unused account this is
+    /// the sentinel, and the nonce is queried again on first use.
+    pub async fn sync_nonce<P: Provider>(
+        &self,
+        provider: &P,
+        address: Address,
+    ) -> TransportResult<()> {
+        let nonce = self.cached(address);
+        let mut nonce = nonce.lock().await;
+        if *nonce == NONE {
+            let pending = provider.get_transaction_count(address).pending().await?;
+            *nonce = pending.wrapping_sub(1);
+        }
+        Ok(())
+    }
+
+    /// Compares the cached nonce of `address` with the chain, and resyncs the cache
+    /// where it drifted, see the [module documentation](self).
+    ///
+    /// A pending transaction or a gap counts as stuck once it outlived `stuck_after`
+    /// across calls; gaps younger than that are left alone, as they may be nonces
+    /// handed out to transactions about to be broadcast.
+    pub async fn reconcile<P: Provider>(
+        &self,
+        provider: &P,
+        address: Address,
+        stuck_after: Duration,
+    ) -> TransportResult<NonceReconciliation> {
+        let confirmed = provider.get_transaction_count(address).latest().await?;
+        let pending = provider.get_transaction_count(address).pending().await?;
+        let now = Instant::now();
+        let nonce = self.cached(address);
+        let mut nonce = nonce.lock().await;
+        let mut progress = self.progress.entry(address).or_insert(NonceProgress {
+            confirmed,
+            since: now,
+            gap_since: None,
+        });
+        if progress.confirmed != confirmed || pending <= confirmed {
+            progress.confirmed = confirmed;
+            progress.since = now;
+        }
+        let stuck = pending > confirmed && now.duration_since(progress.since) >= stuck_after;
+        let local = (*nonce != NONE).then(|| nonce.wrapping_add(1));
+        let gap = local
+            .filter(|local| *local > pending)
+            .map(|local| pending..local);
+        let resync = match local {
+            None => true,
+            Some(local) if local < pending => true,
+            Some(local) if local > pending => {
+                let gap_since = *progress.gap_since.get_or_insert(now);
+                now.duration_since(gap_since) >= stuck_after
+            }
+            Some(_) => {
+                progress.gap_since = None;
+                false
+            }
+        };
+        if resync {
+            *nonce = pending.wrapping_sub(1);
+            progress.gap_since = None;
+        }
+        #[cfg(feature = "telemetry")]
+        if stuck || (resync && local.is_some()) {
+            tracing::warn!(%address, confirmed, pending, ?local, stuck, resync, "Signer nonce drifted");
+        }
+        Ok(NonceReconciliation {
+            address,
+            confirmed_nonce: confirmed,
+            pending_nonce: pending,
+            local_nonce: local,
+            gap,
+            stuck,
+            resynced: resync,
+        })
+    }
+
+    /// The cache slot of `address`, created empty if needed.
+    fn cached(&self, address: Address) -> Arc<Mutex<u64>> {
+        let rm = self
+            .nonces
+            .entry(address)
+            .or_insert_with(|| Arc::new(Mutex::new(NONE)));
+        Arc::clone(rm.value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U64;
+    use alloy_provider::RootProvider;
+    use alloy_rpc_client::RpcClient;
+    use alloy_transport::mock::{Asserter, MockTransport};
+
+    fn provider(asserter: &Asserter) -> RootProvider {
+        RootProvider::new(RpcClient::new(MockTransport::new(asserter.clone()), false))
+    }
+
+    /// Queues the answers of one reconciliation.
+    fn counts(asserter: &Asserter, confirmed: u64, pending: u64) {
+        asserter.push_success(&U64::from(confirmed));
+        asserter.push_success(&U64::from(pending));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_initializes_and_follows_other_senders() {
+        let asserter = Asserter::new();
+        let provider = provider(&asserter);
+        let manager = PendingNonceManager::default();
+        let address = Address::repeat_byte(1);
+
+        counts(&asserter, 5, 5);
+        let first = manager
+            .reconcile(&provider, address, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(first.local_nonce, None);
+        assert!(first.resynced);
+        assert!(!first.stuck);
+        assert_eq!(manager.get_next_nonce(&provider, address).await.unwrap(), 5);
+
+        // Another process sent two transactions from the signer.
+        counts(&asserter, 8, 8);
+        let behind = manager
+            .reconcile(&provider, address, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(behind.local_nonce, Some(6));
+        assert!(behind.resynced);
+        assert_eq!(manager.get_next_nonce(&provider, address).await.unwrap(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_detects_gaps_and_stuck_nonces() {
+        let asserter = Asserter::new();
+        let provider = provider(&asserter);
+        let manager = PendingNonceManager::default();
+        let address = Address::repeat_byte(2);
+
+        counts(&asserter, 3, 3);
+        manager
+            .reconcile(&provider, address, Duration::ZERO)
+            .await
+            .unwrap();
+        for expected in 3..6 {
+            assert_eq!(
+                manager.get_next_nonce(&provider, address).await.unwrap(),
+                expected
+            );
+        }
+
+        // Nonce 3 is stuck in the mempool and nonces 4 and 5 never reached it.
+        counts(&asserter, 3, 4);
+        let report = manager
+            .reconcile(&provider, address, Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(report.gap, Some(4..6));
+        assert!(report.stuck);
+        assert!(report.resynced);
+        assert_eq!(manager.get_next_nonce(&provider, address).await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_leaves_young_gaps_alone() {
+        let asserter = Asserter::new();
+        let provider = provider(&asserter);
+        let manager = PendingNonceManager::default();
+        let address = Address::repeat_byte(3);
+
+        counts(&asserter, 7, 7);
+        manager
+            .reconcile(&provider, address, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(manager.get_next_nonce(&provider, address).await.unwrap(), 7);
+
+        // Nonce 7 was handed out but not broadcast yet.
+        counts(&asserter, 7, 7);
+        let report = manager
+            .reconcile(&provider, address, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(report.gap, Some(7..8));
+        assert!(!report.resynced);
+        assert!(!report.stuck);
+        assert_eq!(manager.get_next_nonce(&provider, address).await.unwrap(), 8);
+    }
 }
@@ -0,0 +1,158 @@
+//! Permit2 nonces of a payer, read for clients without an RPC node of their own.
+//!
+//! A Permit2 payload is only valid with the right nonce, and the two Permit2 flows
+//! keep them differently:
+//!
+//! - `AllowanceTransfer` (the `permit2` payload) takes the sequential nonce stored
+//!   with the allowance of an owner, token and spender
+//! - `SignatureTransfer` (the `permit2Authorization` witness payload) takes any
+//!   unused unordered nonce: bit `nonce & 0xff` of word `nonce >> 8` of the owner's
+//!   nonce bitmap
+//!
+//! [`permit2_nonces`] reads both in one go.
+
+use alloy_primitives::{Address, U256};
+use alloy_provider::Provider;
+use alloy_sol_types::sol;
+use serde::{Deserialize, Serialize};
+
+sol! {
+    #[sol(rpc)]
+    interface IPermit2Nonces {
+        function allowance(address owner, address token, address spender) external view returns (uint160 amount, uint48 expiration, uint48 nonce);
+        function nonceBitmap(address owner, uint256 wordPos) external view returns (uint256);
+    }
+}
+
+/// Whose Permit2 nonces to read.
+///
+/// ```json
+/// { "owner": "0x...", "token": "0x...", "spender": "0x...", "word": "0" }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Permit2NonceQuery {
+    /// Payer signing the Permit2 payload.
+    pub owner: Address,
+    /// Token paid in.
+    pub token: Address,
+    /// Spender of the allowance; one of the facilitator signers when absent.
+    #[serde(default)]
+    pub spender: Option<Address>,
+    /// Word of the unordered nonce bitmap to read, `0` when absent.
+    #[serde(default)]
+    pub word: Option<U256>,
+}
+
+/// The `AllowanceTransfer` allowance of an owner, token and spender.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Permit2Allowance {
+    pub spender: Address,
+    /// Amount left on the allowance, in the token's smallest unit.
+    pub amount: U256,
+    /// Unix timestamp the allowance expires at.
+    pub expiration: u64,
+    /// Nonce the next `permit2` payload must sign.
+    pub nonce: u64,
+}
+
+/// A word of the `SignatureTransfer` unordered nonce bitmap of an owner.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Permit2UnorderedNonces {
+    pub word: U256,
+    /// Used nonces of the word, one bit each.
+    pub bitmap: U256,
+    /// Lowest unused nonce of the word, `None` if all of them are used.
+    pub next_nonce: Option<U256>,
+}
+
+/// Outcome of [`permit2_nonces`].
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Permit2Nonces {
+    pub owner: Address,
+    pub token: Address,
+    /// Permit2 contract the nonces were read from.
+    pub permit2: Address,
+    pub allowance: Permit2Allowance,
+    pub unordered: Permit2UnorderedNonces,
+}
+
+/// Lowest unused nonce of `word`, given its `bitmap`.
+pub fn next_unordered_nonce(word: U256, bitmap: U256) -> Option<U256> {
+    let bit = (!bitmap).trailing_zeros();
+    (bit < 256).then(|| (word << 8) | U256::from(bit))
+}
+
+/// Reads the Permit2 nonces of `query.owner` from the Permit2 contract at `permit2`.
+///
+/// `spender` is the allowance spender used when the query names none.
+pub async fn permit2_nonces<P: Provider>(
+    provider: &P,
+    permit2: Address,
+    spender: Address,
+    query: &Permit2NonceQuery,
+) -> Result<Permit2Nonces, alloy_contract::Error> {
+    let contract = IPermit2Nonces::new(permit2, provider);
+    let spender = query.spender.unwrap_or(spender);
+    let word = query.word.unwrap_or_default();
+    let allowance = contract
+        .allowance(query.owner, query.token, spender)
+        .call()
+        .await?;
+    let bitmap = contract.nonceBitmap(query.owner, word).call().await?;
+    Ok(Permit2Nonces {
+        owner: query.owner,
+        token: query.token,
+        permit2,
+        allowance: Permit2Allowance {
+            spender,
+            amount: U256::from(allowance.amount),
+            expiration: allowance.expiration.to(),
+            nonce: allowance.nonce.to(),
+        },
+        unordered: Permit2UnorderedNonces {
+            word,
+            bitmap,
+            next_nonce: next_unordered_nonce(word, bitmap),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_unordered_nonce() {
+        assert_eq!(
+            next_unordered_nonce(U256::ZERO, U256::ZERO),
+            Some(U256::ZERO)
+        );
+        assert_eq!(
+            next_unordered_nonce(U256::from(2), U256::from(0b1011)),
+            Some(U256::from(2 * 256 + 2))
+        );
+        assert_eq!(next_unordered_nonce(U256::from(1), U256::MAX), None);
+
+        let query: Permit2NonceQuery = serde_json::from_value(serde_json::json!({
+            "owner": "0x1111111111111111111111111111111111111111",
+            "token": "0x3333333333333333333333333333333333333333"
+        }))
+        .unwrap();
+        assert!(query.spender.is_none());
+        assert!(query.word.is_none());
+
+        // Query strings carry every parameter as a string.
+        let query: Permit2NonceQuery = serde_json::from_value(serde_json::json!({
+            "owner": "0x1111111111111111111111111111111111111111",
+            "token": "0x3333333333333333333333333333333333333333",
+            "spender": "0x2222222222222222222222222222222222222222",
+            "word": "3"
+        }))
+        .unwrap();
+        assert_eq!(query.word, Some(U256::from(3)));
+    }
+}
@@ -0,0 +1,180 @@
+//! Settlement transactions for external submission.
+//!
+//! Integrators with their own transaction pipeline can reuse this crate's payment
+//! verification and submit the settlement themselves. [`PreparingProvider`] stands in
+//! for the settlement provider: it runs the same settlement code, which may still read
+//! from the chain (e.g. to check whether a counterfactual wallet is deployed), but
+//! every transaction is recorded as a [`PreparedTx`] instead of being signed and
+//! broadcast. The receipts it hands back are synthetic: successful, with zero gas used
+//! and a zero transaction hash.
+//!
+//! Any of the schemes' public `settle_payment*` functions can be run against it; the
+//! exact schemes also offer `prepare_settlement`, which verifies a settle request first.
+
+use alloy_primitives::{Address, Bytes, U256};
+use alloy_rpc_types_eth::TransactionReceipt;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+use crate::chain::dry_run::synthetic_receipt;
+use crate::chain::eip712_cache::Eip712DomainCache;
+use crate::chain::provider::{
+    Eip155Contracts, Eip155MetaTransactionProvider, MetaTransaction, MetaTransactionSendError,
+};
+use crate::chain::signer::SettlementSigner;
+use crate::chain::types::{Eip155ChainReference, PayerAddress};
+
+/// A settlement transaction, ready to be signed and sent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreparedTx {
+    /// Account the transaction must be sent from, when the settlement depends on it,
+    /// e.g. the spender of a Permit2 allowance. Any account can send it otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<Address>,
+    pub to: Address,
+    /// Calldata.
+    pub data: Bytes,
+    /// Native value to attach, in wei.
+    pub value: U256,
+}
+
+/// Transactions settling a verified payment.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreparedSettlement {
+    pub payer: PayerAddress,
+    /// Transactions in the order they must be mined.
+    pub transactions: Vec<PreparedTx>,
+}
+
+/// A settlement provider that records transactions instead of sending them.
+#[derive(Debug)]
+pub struct PreparingProvider<'a, P> {
+    provider: &'a P,
+    transactions: Mutex<Vec<PreparedTx>>,
+}
+
+impl<'a, P: Eip155MetaTransactionProvider> PreparingProvider<'a, P> {
+    /// Wraps `provider`, which is only used for reads.
+    pub fn new(provider: &'a P) -> Self {
+        Self {
+            provider,
+            transactions: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The transactions recorded so far, in order.
+    pub fn finish(self, payer: PayerAddress) -> PreparedSettlement {
+        PreparedSettlement {
+            payer,
+            transactions: self
+                .transactions
+                .into_inner()
+                .expect("prepared transactions lock poisoned"),
+        }
+    }
+
+    fn record(&self, tx: MetaTransaction, from: Option<Address>) -> TransactionReceipt {
+        let receipt = synthetic_receipt(from.unwrap_or_default(), tx.to, 0);
+        self.transactions
+            .lock()
+            .expect("prepared transactions lock poisoned")
+            .push(PreparedTx {
+                from,
+                to: tx.to,
+                data: tx.calldata,
+                value: U256::ZERO,
+            });
+        receipt
+    }
+}
+
+impl<P> Eip155MetaTransactionProvider for PreparingProvider<'_, P>
+where
+    P: Eip155MetaTransactionProvider + Sync,
+{
+    type Error = MetaTransactionSendError;
+    type Inner = P::Inner;
+
+    fn inner(&self) -> &Self::Inner {
+        self.provider.inner()
+    }
+
+    fn chain(&self) -> &Eip155ChainReference {
+        self.provider.chain()
+    }
+
+    fn contracts(&self) -> &Eip155Contracts {
+        self.provider.contracts()
+    }
+
+    fn required_confirmations(&self) -> u64 {
+        self.provider.required_confirmations()
+    }
+
+    fn refund_treasury(&self) -> Option<Address> {
+        self.provider.refund_treasury()
+    }
+
+    fn signer(&self, address: Address) -> Option<Arc<dyn SettlementSigner>> {
+        self.provider.signer(address)
+    }
+
+    fn eip712_domains(&self) -> Option<&Eip712DomainCache> {
+        self.provider.eip712_domains()
+    }
+
+    fn min_remaining_validity_secs(&self) -> Option<u64> {
+        self.provider.min_remaining_validity_secs()
+    }
+
+    fn send_transaction(
+        &self,
+        tx: MetaTransaction,
+    ) -> impl Future<Output = Result<TransactionReceipt, Self::Error>> + Send {
+        std::future::ready(Ok(self.record(tx, None)))
+    }
+
+    fn send_transaction_from(
+        &self,
+        tx: MetaTransaction,
+        from: Address,
+    ) -> impl Future<Output = Result<TransactionReceipt, Self::Error>> + Send {
+        std::future::ready(Ok(self.record(tx, Some(from))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn test_prepared_tx_wire_format() {
+        let tx = PreparedTx {
+            from: None,
+            to: address!("0x1111111111111111111111111111111111111111"),
+            data: Bytes::from_static(&[0xab, 0xcd]),
+            value: U256::ZERO,
+        };
+        assert_eq!(
+            serde_json::to_value(&tx).unwrap(),
+            serde_json::json!({
+                "to": "0x1111111111111111111111111111111111111111",
+                "data": "0xabcd",
+                "value": "0x0",
+            })
+        );
+
+        let spender = address!("0x2222222222222222222222222222222222222222");
+        let tx = PreparedTx {
+            from: Some(spender),
+            ..tx
+        };
+        assert_eq!(
+            serde_json::to_value(&tx).unwrap()["from"],
+            serde_json::json!(spender)
+        );
+    }
+}
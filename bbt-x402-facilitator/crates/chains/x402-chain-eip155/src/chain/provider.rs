@@ -1,30 +1,44 @@
 use alloy_network::{Ethereum as AlloyEthereum, EthereumWallet, NetworkWallet, TransactionBuilder};
-use alloy_primitives::{Address, B256, Bytes};
+use alloy_primitives::{Address, Bytes, TxHash, U256};
 use alloy_provider::fillers::{
     BlobGasFiller, ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller, WalletFiller,
 };
 use alloy_provider::{
-    Identity, PendingTransactionError, Provider, ProviderBuilder, RootProvider, WalletProvider,
+    Identity, MULTICALL3_ADDRESS, PendingTransactionError, Provider, ProviderBuilder, RootProvider,
 };
 use alloy_rpc_client::RpcClient;
 use alloy_rpc_types_eth::{BlockId, TransactionReceipt, TransactionRequest};
-use alloy_signer::Signer;
-use alloy_signer_local::PrivateKeySigner;
-use alloy_transport::TransportError;
-use alloy_transport::layers::{FallbackLayer, ThrottleLayer};
+use alloy_transport::layers::ThrottleLayer;
+use alloy_transport::{IntoBoxTransport, TransportError};
 use alloy_transport_http::Http;
-use std::num::NonZeroUsize;
+use serde::Serialize;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 use tower::ServiceBuilder;
 use x402_types::chain::{ChainId, ChainProviderOps, FromConfig};
 
 #[cfg(feature = "telemetry")]
 use tracing::Instrument;
 
-use crate::chain::config::{Eip155ChainConfig, RpcConfig};
-use crate::chain::pending_nonce_manager::PendingNonceManager;
-use crate::chain::types::Eip155ChainReference;
+use crate::chain::config::{
+    Eip155ChainConfig, Eip155ContractsConfig, FeeBumpConfig, RpcConfig, RpcFailoverConfig,
+};
+use crate::chain::eip712_cache::Eip712DomainCache;
+use crate::chain::fee_bump;
+use crate::chain::history::{self, ExpectedSettlement, SettlementVerification};
+use crate::chain::pending_nonce_manager::{NonceReconciliation, PendingNonceManager};
+use crate::chain::permit2_nonces::{self, Permit2NonceQuery, Permit2Nonces};
+use crate::chain::relay::RelaySender;
+use crate::chain::rpc_failover::{RpcEndpointHealth, RpcFailover};
+use crate::chain::settlement_limiter::{
+    SettlementLimitExceeded, SettlementLimiter, SettlementLimiterStatus,
+};
+use crate::chain::signer::{SettlementSigner, SettlementTxSigner, settlement_signer};
+use crate::chain::types::{Eip155ChainReference, TokenAmount};
+use crate::chain::user_operation::UserOperationSender;
+use crate::v1_eip155_exact::signature_check::{self, SignatureQuery, SignatureValidation};
+use crate::v1_eip155_exact::{Eip155ExactError, PERMIT2_ADDRESS, VALIDATOR_ADDRESS};
 
 /// Combined filler type for gas, blob gas, nonce, and chain ID.
 pub type InnerFiller = JoinFill<
@@ -60,24 +74,94 @@ pub type InnerProvider = FillProvider<
 /// Uses [`PendingNonceManager`] to track nonces locally and query pending
 /// transactions on initialization. If a transaction fails, the nonce is
 /// automatically reset to force a fresh query on the next transaction.
+/// [`Eip155ChainProvider::reconcile_nonces`] detects drift and stuck nonces, and
+/// [`Eip155ChainProvider::cancel_pending`] clears stuck nonces.
+///
+/// # Concurrency
+///
+/// With `settlement_concurrency` configured, transactions take a slot of a
+/// [`SettlementLimiter`] until their receipt arrives, see
+/// [`crate::chain::settlement_limiter`].
 #[derive(Debug)]
 pub struct Eip155ChainProvider {
     chain: Eip155ChainReference,
     eip1559: bool,
     flashblocks: bool,
     receipt_timeout_secs: u64,
+    low_balance_threshold: Option<U256>,
+    fee_bump: Option<FeeBumpConfig>,
     inner: InnerProvider,
+    /// Read-only provider for historical queries, see [`history`].
+    archive: RootProvider,
+    /// Transport behind `inner`, kept for endpoint health.
+    rpc: RpcFailover,
+    /// Transport behind `archive`, when archive endpoints are configured.
+    archive_rpc: Option<RpcFailover>,
     /// Available signer addresses for round-robin selection.
     signer_addresses: Arc<Vec<Address>>,
     /// Current position in round-robin signer rotation.
     signer_cursor: Arc<AtomicUsize>,
+    /// Keys behind `signer_addresses`, for signing messages other than transactions.
+    signers: Vec<Arc<dyn SettlementSigner>>,
     /// Nonce manager for resetting nonces on transaction failures.
     nonce_manager: PendingNonceManager,
+    /// How long a pending nonce may stay unmined before it counts as stuck.
+    stuck_nonce_after: Duration,
+    /// Bound on the transactions in flight, if configured.
+    settlement_limiter: Option<SettlementLimiter>,
+    /// EIP-712 domains read from token contracts.
+    eip712_domains: Eip712DomainCache,
+    /// Chain override of how long a payment must remain valid to be settled.
+    min_remaining_validity_secs: Option<u64>,
+    /// Bundler settlements are sent through instead of the signers' EOAs, if configured.
+    user_operations: Option<UserOperationSender>,
+    /// Relay settlements are submitted through instead of the signers' EOAs, if configured.
+    relay: Option<RelaySender>,
+    contracts: Eip155Contracts,
+    required_confirmations: u64,
+    /// Signer refunds are sent from, one of `signer_addresses`.
+    refund_treasury: Option<Address>,
+}
+
+/// Addresses of the contracts payments are verified and settled through on a chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Eip155Contracts {
+    /// Permit2 contract.
+    pub permit2: Address,
+    /// EIP-6492 universal signature validator.
+    pub eip6492_validator: Address,
+}
+
+impl Default for Eip155Contracts {
+    /// The canonical deployments.
+    fn default() -> Self {
+        Self {
+            permit2: PERMIT2_ADDRESS,
+            eip6492_validator: VALIDATOR_ADDRESS,
+        }
+    }
+}
+
+impl From<Eip155ContractsConfig> for Eip155Contracts {
+    fn from(config: Eip155ContractsConfig) -> Self {
+        let defaults = Self::default();
+        Self {
+            permit2: config.permit2.unwrap_or(defaults.permit2),
+            eip6492_validator: config
+                .eip6492_validator
+                .unwrap_or(defaults.eip6492_validator),
+        }
+    }
 }
 
 impl Eip155ChainProvider {
+    /// Failover transport over the HTTP endpoints in `rpc`, tried in the listed order.
     #[allow(unused_variables)] // chain_id is needed for tracing only here
-    pub fn rpc_client(chain_id: ChainId, rpc: &[RpcConfig]) -> RpcClient {
+    pub fn rpc_failover(
+        chain_id: ChainId,
+        rpc: &[RpcConfig],
+        config: RpcFailoverConfig,
+    ) -> RpcFailover {
         let transports = rpc
             .iter()
             .filter_map(|provider_config| {
@@ -92,19 +176,282 @@ impl Eip155ChainProvider {
                 let rate_limit = provider_config.rate_limit.unwrap_or(u32::MAX);
                 let service = ServiceBuilder::new()
                     .layer(ThrottleLayer::new(rate_limit))
-                    .service(Http::new(rpc_url));
-                Some(service)
+                    .service(Http::new(rpc_url.clone()));
+                Some((rpc_url, service.into_box_transport()))
             })
             .collect::<Vec<_>>();
-        let fallback = ServiceBuilder::new()
-            .layer(
-                FallbackLayer::default().with_active_transport_count(
-                    NonZeroUsize::new(transports.len())
-                        .expect("Non-zero amount of stateless transports"),
+        RpcFailover::new(transports, config)
+    }
+
+    /// JSON-RPC client over [`Eip155ChainProvider::rpc_failover`].
+    pub fn rpc_client(
+        chain_id: ChainId,
+        rpc: &[RpcConfig],
+        config: RpcFailoverConfig,
+    ) -> RpcClient {
+        RpcClient::new(Self::rpc_failover(chain_id, rpc, config), false)
+    }
+
+    /// State of each RPC endpoint of the chain, see [`RpcFailover::health`].
+    pub fn rpc_health(&self) -> Vec<RpcEndpointHealth> {
+        self.rpc.health()
+    }
+
+    /// Probes every RPC endpoint of the chain, see [`RpcFailover::probe`].
+    pub async fn probe_rpc(&self) {
+        self.rpc.probe().await;
+        if let Some(archive) = &self.archive_rpc {
+            archive.probe().await;
+        }
+    }
+
+    /// Occupancy of the settlement slots, if `settlement_concurrency` is configured.
+    pub fn settlement_limiter_status(&self) -> Option<SettlementLimiterStatus> {
+        self.settlement_limiter
+            .as_ref()
+            .map(SettlementLimiter::status)
+    }
+
+    /// Native balance, in wei, below which a signer is reported as low.
+    pub fn low_balance_threshold(&self) -> Option<U256> {
+        self.low_balance_threshold
+    }
+
+    /// Checks on the archive RPC that `transaction` settled `expected`,
+    /// see [`history::verify_settlement`].
+    pub async fn verify_settlement(
+        &self,
+        transaction: TxHash,
+        expected: &ExpectedSettlement,
+    ) -> Result<SettlementVerification, TransportError> {
+        history::verify_settlement(&self.archive, transaction, expected).await
+    }
+
+    /// Reads the Permit2 nonces of `query.owner` from the chain's Permit2 contract,
+    /// see [`permit2_nonces::permit2_nonces`]. Allowances are read for the first
+    /// signer unless the query names a spender.
+    pub async fn permit2_nonces(
+        &self,
+        query: &Permit2NonceQuery,
+    ) -> Result<Permit2Nonces, alloy_contract::Error> {
+        let spender = self.signer_addresses.first().copied().unwrap_or_default();
+        permit2_nonces::permit2_nonces(&self.inner, self.contracts.permit2, spender, query).await
+    }
+
+    /// Checks a signature over typed data against the chain's contracts, see
+    /// [`signature_check::validate_signature`].
+    pub async fn validate_signature(
+        &self,
+        query: &SignatureQuery,
+    ) -> Result<SignatureValidation, Eip155ExactError> {
+        signature_check::validate_signature(&self.inner, &self.chain, &self.contracts, query).await
+    }
+
+    /// Fetches the native balance and nonce backlog of every configured signer.
+    pub async fn signer_status(&self) -> Result<Vec<Eip155SignerStatus>, TransportError> {
+        let mut statuses = Vec::with_capacity(self.signer_addresses.len());
+        for address in self.signer_addresses.iter().copied() {
+            let balance = self.inner.get_balance(address).await?;
+            let confirmed_nonce = self.inner.get_transaction_count(address).latest().await?;
+            let pending_nonce = self.inner.get_transaction_count(address).pending().await?;
+            statuses.push(Eip155SignerStatus {
+                address,
+                balance: balance.into(),
+                confirmed_nonce,
+                pending_nonce,
+                pending_transactions: pending_nonce.saturating_sub(confirmed_nonce),
+                low_balance: self
+                    .low_balance_threshold
+                    .is_some_and(|threshold| balance < threshold),
+            });
+        }
+        Ok(statuses)
+    }
+
+    /// Prepares the provider for its first settlements: fetches the chain head and
+    /// reconciles the nonce of every signer. Returns the head block number.
+    pub async fn warm_up(&self) -> Result<u64, TransportError> {
+        let head = self.inner.get_block_number().await?;
+        self.reconcile_nonces().await?;
+        Ok(head)
+    }
+
+    /// Compares the cached nonce of every signer with the chain, resyncing drifted
+    /// caches and reporting stuck nonces, see [`PendingNonceManager::reconcile`].
+    pub async fn reconcile_nonces(&self) -> Result<Vec<NonceReconciliation>, TransportError> {
+        let mut reconciliations = Vec::with_capacity(self.signer_addresses.len());
+        for address in self.signer_addresses.iter().copied() {
+            let reconciliation = self
+                .nonce_manager
+                .reconcile(&self.inner, address, self.stuck_nonce_after)
+                .await?;
+            reconciliations.push(reconciliation);
+        }
+        Ok(reconciliations)
+    }
+
+    /// Replaces every pending transaction of signer `address` with a zero-value
+    /// transfer to itself, priced at twice the current fees so that it outbids the
+    /// original. Returns the hashes of the replacements, without waiting for them to
+    /// be mined, and resets the cached nonce of the signer.
+    ///
+    /// Nonces handed out but never broadcast need no replacement: the reset makes
+    /// the next transactions fill them.
+    pub async fn cancel_pending(
+        &self,
+        address: Address,
+    ) -> Result<Vec<TxHash>, MetaTransactionSendError> {
+        if !self.signer_addresses.contains(&address) {
+            return Err(MetaTransactionSendError::Custom(format!(
+                "{address} is not a signer of {}",
+                self.chain
+            )));
+        }
+        let confirmed = self.inner.get_transaction_count(address).latest().await?;
+        let pending = self.inner.get_transaction_count(address).pending().await?;
+        let mut replacement = TransactionRequest::default()
+            .with_from(address)
+            .with_to(address)
+            .with_value(U256::ZERO)
+            .with_gas_limit(21_000);
+        if self.eip1559 {
+            let fees = self.inner.estimate_eip1559_fees().await?;
+            replacement.set_max_fee_per_gas(fees.max_fee_per_gas.saturating_mul(2));
+            replacement
+                .set_max_priority_fee_per_gas(fees.max_priority_fee_per_gas.saturating_mul(2));
+        } else {
+            let gas_price = self.inner.get_gas_price().await?;
+            replacement.set_gas_price(gas_price.saturating_mul(2));
+        }
+        let mut hashes = Vec::new();
+        for nonce in confirmed..pending {
+            let tx = replacement.clone().with_nonce(nonce);
+            match self.inner.send_transaction(tx).await {
+                Ok(pending) => hashes.push(*pending.tx_hash()),
+                Err(e) => {
+                    self.nonce_manager.reset_nonce(address).await;
+                    return Err(MetaTransactionSendError::Transport(e));
+                }
+            }
+        }
+        #[cfg(feature = "telemetry")]
+        tracing::warn!(chain = %self.chain, %address, confirmed, pending, "Cancelled pending signer transactions");
+        self.nonce_manager.reset_nonce(address).await;
+        Ok(hashes)
+    }
+
+    /// Checks that the chain can take settlements, for a startup summary.
+    ///
+    /// Checks, in order: that the RPC answers (`rpc`), that it serves the configured
+    /// chain (`chain_id`), that every signer holds native balance above the low-balance
+    /// threshold (`signer:<address>`), and that code is deployed at the Multicall3,
+    /// Permit2 and EIP-6492 validator addresses (`multicall3`, `permit2`,
+    /// `eip6492_validator`). Problems are reported as failed checks, never as errors.
+    /// Nothing else is checked when the RPC does not answer.
+    pub async fn self_test(&self) -> Vec<Eip155SelfTestCheck> {
+        let mut checks = Vec::new();
+        match self.inner.get_block_number().await {
+            Ok(head) => checks.push(Eip155SelfTestCheck::passed("rpc", format!("head {head}"))),
+            Err(e) => {
+                checks.push(Eip155SelfTestCheck::failed("rpc", e.to_string()));
+                return checks;
+            }
+        }
+        let chain_id = match self.inner.get_chain_id().await {
+            Ok(chain_id) if chain_id == self.chain.inner() => {
+                Eip155SelfTestCheck::passed("chain_id", chain_id.to_string())
+            }
+            Ok(chain_id) => Eip155SelfTestCheck::failed(
+                "chain_id",
+                format!(
+                    "RPC serves chain {chain_id}, configured for {}",
+                    self.chain.inner()
                 ),
-            )
-            .service(transports);
-        RpcClient::new(fallback, false)
+            ),
+            Err(e) => Eip155SelfTestCheck::failed("chain_id", e.to_string()),
+        };
+        checks.push(chain_id);
+        match self.signer_status().await {
+            Ok(statuses) => {
+                for status in statuses {
+                    let name = format!("signer:{}", status.address);
+                    let balance = status.balance.0;
+                    let check = if balance.is_zero() {
+                        Eip155SelfTestCheck::failed(name, "no native balance for gas".to_string())
+                    } else if status.low_balance {
+                        let threshold = self.low_balance_threshold.unwrap_or_default();
+                        Eip155SelfTestCheck::failed(
+                            name,
+                            format!("balance {balance} wei below threshold {threshold} wei"),
+                        )
+                    } else {
+                        Eip155SelfTestCheck::passed(name, format!("balance {balance} wei"))
+                    };
+                    checks.push(check);
+                }
+            }
+            Err(e) => checks.push(Eip155SelfTestCheck::failed("signers", e.to_string())),
+        }
+        let contracts = [
+            ("multicall3", MULTICALL3_ADDRESS),
+            ("permit2", self.contracts.permit2),
+            ("eip6492_validator", self.contracts.eip6492_validator),
+        ];
+        for (name, address) in contracts {
+            let check = match self.inner.get_code_at(address).await {
+                Ok(code) if code.is_empty() => {
+                    Eip155SelfTestCheck::failed(name, format!("no code at {address}"))
+                }
+                Ok(code) => Eip155SelfTestCheck::passed(
+                    name,
+                    format!("{} bytes of code at {address}", code.len()),
+                ),
+                Err(e) => Eip155SelfTestCheck::failed(name, e.to_string()),
+            };
+            checks.push(check);
+        }
+        checks
+    }
+
+    /// Checks that code is deployed at the Permit2 and EIP-6492 validator addresses.
+    ///
+    /// Missing code at an address overridden in `config` is an error. Missing code at
+    /// a canonical address, or a failing RPC, is only logged: Permit2 payments then
+    /// fail, and EIP-6492 signatures are verified without the validator.
+    async fn check_contracts(&self, config: Eip155ContractsConfig) -> Result<(), String> {
+        let contracts = [
+            ("permit2", self.contracts.permit2, config.permit2.is_some()),
+            (
+                "eip6492_validator",
+                self.contracts.eip6492_validator,
+                config.eip6492_validator.is_some(),
+            ),
+        ];
+        for (name, address, configured) in contracts {
+            match self.inner.get_code_at(address).await {
+                Ok(code) if code.is_empty() && configured => {
+                    return Err(format!(
+                        "no code at the configured {name} address {address} on {}",
+                        self.chain
+                    ));
+                }
+                Ok(code) if code.is_empty() => {
+                    #[cfg(feature = "telemetry")]
+                    tracing::warn!(chain = %self.chain, %address, "No {name} contract deployed");
+                }
+                Ok(_) => {}
+                Err(_error) => {
+                    #[cfg(feature = "telemetry")]
+                    tracing::warn!(
+                        chain = %self.chain,
+                        %address,
+                        error = %_error,
+                        "Could not check the {name} contract"
+                    );
+                }
+            }
+        }
+        Ok(())
     }
 
     /// Round-robin selection of next signer from wallet.
@@ -129,7 +476,39 @@ impl Eip155ChainProvider {
                 "Signer not configured for requested from address".to_string(),
             ));
         }
-        tracing::info!("[DEBUG] send_transaction START: from={}, to={}", from_address, tx.to);
+        let _slot = match &self.settlement_limiter {
+            Some(limiter) => Some(limiter.acquire().await?),
+            None => None,
+        };
+        if let Some(user_operations) = &self.user_operations {
+            let signer = self
+                .signer(from_address)
+                .ok_or_else(|| MetaTransactionSendError::Custom("Signer not found".to_string()))?;
+            return user_operations
+                .send(
+                    &self.inner,
+                    self.chain.inner(),
+                    self.eip1559,
+                    signer,
+                    tx,
+                    Duration::from_secs(self.receipt_timeout_secs),
+                )
+                .await;
+        }
+        if let Some(relay) = &self.relay {
+            return relay
+                .send(
+                    &self.inner,
+                    tx,
+                    Duration::from_secs(self.receipt_timeout_secs),
+                )
+                .await;
+        }
+        tracing::info!(
+            "[DEBUG] send_transaction START: from={}, to={}",
+            from_address,
+            tx.to
+        );
 
         let mut txr = TransactionRequest::default()
             .with_to(tx.to)
@@ -170,6 +549,22 @@ impl Eip155ChainProvider {
             txr.set_gas_limit(gas_limit)
         }
 
+        if let Some(fee_bump) = &self.fee_bump {
+            let result = fee_bump::send_with_fee_bumping(
+                &self.inner,
+                txr,
+                from_address,
+                tx.confirmations,
+                std::time::Duration::from_secs(self.receipt_timeout_secs),
+                fee_bump,
+            )
+            .await;
+            if result.is_err() {
+                self.nonce_manager.reset_nonce(from_address).await;
+            }
+            return result;
+        }
+
         tracing::info!("[DEBUG] sending transaction...");
         let pending_tx = match self.inner.send_transaction(txr).await {
             Ok(pending) => {
@@ -184,7 +579,10 @@ impl Eip155ChainProvider {
         };
 
         let timeout = std::time::Duration::from_secs(self.receipt_timeout_secs);
-        tracing::info!("[DEBUG] waiting for receipt (timeout={}s)...", self.receipt_timeout_secs);
+        tracing::info!(
+            "[DEBUG] waiting for receipt (timeout={}s)...",
+            self.receipt_timeout_secs
+        );
 
         let watcher = pending_tx
             .with_required_confirmations(tx.confirmations)
@@ -210,30 +608,33 @@ impl Eip155ChainProvider {
 
 /// Creates a new provider from configuration.
 ///
-/// Initializes signers, RPC transports, and the nonce manager.
+/// Initializes signers, RPC transports, and the nonce manager, and checks the
+/// contracts payments go through (see [`Eip155ContractsConfig`]).
 ///
 /// # Errors
 ///
 /// Returns an error if:
 /// - No signers are configured
-/// - Signer private keys are invalid
+/// - Signer private keys are invalid, or a signing backend cannot be reached
 /// - RPC transport initialization fails
+/// - A contract address overridden in the config has no code
+/// - Both `user_operations` and `relay` are configured
 #[async_trait::async_trait]
 impl FromConfig<Eip155ChainConfig> for Eip155ChainProvider {
     async fn from_config(config: &Eip155ChainConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        if config.user_operations().is_some() && config.relay().is_some() {
+            return Err("user_operations and relay are mutually exclusive".into());
+        }
+
         // 1. Signers
-        let signers = config
-            .signers()
-            .iter()
-            .map(|s| B256::from_slice(s.inner().as_bytes()))
-            .map(|b| {
-                PrivateKeySigner::from_bytes(&b)
-                    .map(|s| s.with_chain_id(Some(config.chain_reference().inner())))
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        let mut signers = Vec::with_capacity(config.signers().len());
+        for signer in config.signers() {
+            signers.push(SettlementTxSigner(settlement_signer(signer).await?));
+        }
         if signers.is_empty() {
             return Err("at least one signer should be provided".into());
         }
+        let settlement_signers = signers.iter().map(|signer| signer.0.clone()).collect();
         let wallet = {
             let mut iter = signers.into_iter();
             let first_signer = iter
@@ -247,11 +648,29 @@ impl FromConfig<Eip155ChainConfig> for Eip155ChainProvider {
         };
         let signer_addresses =
             NetworkWallet::<AlloyEthereum>::signer_addresses(&wallet).collect::<Vec<_>>();
+        if let Some(treasury) = config.refund_treasury()
+            && !signer_addresses.contains(&treasury)
+        {
+            return Err(format!("refund_treasury {treasury} is not one of the signers").into());
+        }
         let signer_addresses = Arc::new(signer_addresses);
         let signer_cursor = Arc::new(AtomicUsize::new(0));
 
         // 2. Transports
-        let client = Self::rpc_client(config.chain_id(), config.rpc());
+        let rpc = Self::rpc_failover(config.chain_id(), config.rpc(), config.rpc_failover());
+        let client = RpcClient::new(rpc.clone(), false);
+
+        let archive_rpc = config.has_archive_rpc().then(|| {
+            Self::rpc_failover(
+                config.chain_id(),
+                config.archive_rpc(),
+                config.rpc_failover(),
+            )
+        });
+        let archive = RootProvider::new(RpcClient::new(
+            archive_rpc.clone().unwrap_or_else(|| rpc.clone()),
+            false,
+        ));
 
         // 3. Provider
         // Create nonce manager explicitly so we can store a reference for error handling
@@ -276,16 +695,38 @@ impl FromConfig<Eip155ChainConfig> for Eip155ChainProvider {
         #[cfg(feature = "telemetry")]
         tracing::info!(chain=%config.chain_id(), signers=?signer_addresses, "Using EVM provider");
 
-        Ok(Self {
+        let provider = Self {
             chain: config.chain_reference(),
             eip1559: config.eip1559(),
             flashblocks: config.flashblocks(),
             receipt_timeout_secs: config.receipt_timeout_secs(),
+            low_balance_threshold: config.low_balance_threshold().map(|threshold| threshold.0),
+            fee_bump: config.fee_bump(),
             inner,
+            archive,
+            rpc,
+            archive_rpc,
             signer_addresses,
             signer_cursor,
+            signers: settlement_signers,
             nonce_manager,
-        })
+            stuck_nonce_after: Duration::from_secs(config.stuck_nonce_secs()),
+            settlement_limiter: config.settlement_concurrency().map(SettlementLimiter::new),
+            eip712_domains: Eip712DomainCache::new(Duration::from_secs(
+                config.eip712_cache_ttl_secs(),
+            )),
+            min_remaining_validity_secs: config.min_remaining_validity_secs(),
+            user_operations: config
+                .user_operations()
+                .cloned()
+                .map(UserOperationSender::new),
+            relay: config.relay().cloned().map(RelaySender::new),
+            contracts: config.contracts().into(),
+            required_confirmations: config.required_confirmations().max(1),
+            refund_treasury: config.refund_treasury(),
+        };
+        provider.check_contracts(config.contracts()).await?;
+        Ok(provider)
     }
 }
 
@@ -301,6 +742,33 @@ impl Eip155MetaTransactionProvider for Eip155ChainProvider {
         &self.chain
     }
 
+    fn contracts(&self) -> &Eip155Contracts {
+        &self.contracts
+    }
+
+    fn required_confirmations(&self) -> u64 {
+        self.required_confirmations
+    }
+
+    fn refund_treasury(&self) -> Option<Address> {
+        self.refund_treasury
+    }
+
+    fn signer(&self, address: Address) -> Option<Arc<dyn SettlementSigner>> {
+        self.signers
+            .iter()
+            .find(|signer| signer.address() == address)
+            .cloned()
+    }
+
+    fn eip712_domains(&self) -> Option<&Eip712DomainCache> {
+        Some(&self.eip712_domains)
+    }
+
+    fn min_remaining_validity_secs(&self) -> Option<u64> {
+        self.min_remaining_validity_secs
+    }
+
     /// Send a meta-transaction with provided `to`, `calldata`, and automatically selected signer.
     ///
     /// This method constructs a transaction from the provided [`MetaTransaction`], automatically
@@ -317,6 +785,11 @@ impl Eip155MetaTransactionProvider for Eip155ChainProvider {
     /// - **EIP-1559 networks**: Uses automatic gas pricing via the provider's fillers.
     /// - **Legacy networks**: Fetches the current gas price using `get_gas_price()` and sets it explicitly.
     ///
+    /// # Fee Bumping
+    ///
+    /// With `fee_bump` set in the chain config, a transaction still pending after
+    /// `interval_secs` is replaced with higher fees, see [`FeeBumpConfig`].
+    ///
     /// # Timeout Configuration
     ///
     /// Receipt fetching is subject to a configurable timeout:
@@ -346,12 +819,12 @@ impl Eip155MetaTransactionProvider for Eip155ChainProvider {
         self.send_transaction_with_from(tx, from_address).await
     }
 
-    fn send_transaction_from(
+    async fn send_transaction_from(
         &self,
         tx: MetaTransaction,
         from: Address,
-    ) -> impl Future<Output = Result<TransactionReceipt, Self::Error>> + Send {
-        async move { self.send_transaction_with_from(tx, from).await }
+    ) -> Result<TransactionReceipt, Self::Error> {
+        self.send_transaction_with_from(tx, from).await
     }
 }
 
@@ -364,12 +837,17 @@ pub enum MetaTransactionSendError {
     #[allow(dead_code)] // Public for consumption by downstream crates.
     #[error("{0}")]
     Custom(String),
+    /// No settlement slot freed up in time, see [`SettlementLimiter`].
+    #[error(transparent)]
+    Overloaded(#[from] SettlementLimitExceeded),
 }
 
 impl ChainProviderOps for Eip155ChainProvider {
+    /// The settlement signer set: the addresses advertised on `/supported` and
+    /// accepted as spenders are exactly those transactions are sent from.
     fn signer_addresses(&self) -> Vec<String> {
-        self.inner
-            .signer_addresses()
+        self.signer_addresses
+            .iter()
             .map(|a| a.to_string())
             .collect()
     }
@@ -379,6 +857,53 @@ impl ChainProviderOps for Eip155ChainProvider {
     }
 }
 
+/// On-chain operational status of a facilitator signer.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip155SignerStatus {
+    /// Signer address.
+    pub address: Address,
+    /// Native token balance, in wei.
+    pub balance: TokenAmount,
+    /// Nonce of the next transaction after the latest block.
+    pub confirmed_nonce: u64,
+    /// Nonce of the next transaction, counting the mempool.
+    pub pending_nonce: u64,
+    /// Transactions sent but not yet included in a block.
+    pub pending_transactions: u64,
+    /// Whether the balance is below the configured low-balance threshold.
+    pub low_balance: bool,
+}
+
+/// Outcome of one check of [`Eip155ChainProvider::self_test`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip155SelfTestCheck {
+    /// `rpc`, `chain_id`, `signer:<address>`, `signers`, `multicall3`, `permit2` or `eip6492_validator`.
+    pub name: String,
+    pub passed: bool,
+    /// What was found, or why the check failed.
+    pub detail: String,
+}
+
+impl Eip155SelfTestCheck {
+    fn passed(name: impl Into<String>, detail: String) -> Self {
+        Self {
+            name: name.into(),
+            passed: true,
+            detail,
+        }
+    }
+
+    fn failed(name: impl Into<String>, detail: String) -> Self {
+        Self {
+            name: name.into(),
+            passed: false,
+            detail,
+        }
+    }
+}
+
 /// Meta-transaction parameters: target address, calldata, and required confirmations.
 pub struct MetaTransaction {
     /// Target contract address.
@@ -400,6 +925,34 @@ pub trait Eip155MetaTransactionProvider {
     fn inner(&self) -> &Self::Inner;
     /// Returns reference to chain descriptor.
     fn chain(&self) -> &Eip155ChainReference;
+    /// Returns the Permit2 and EIP-6492 validator addresses of the chain.
+    fn contracts(&self) -> &Eip155Contracts;
+    /// Returns the block confirmations a settlement waits for by default.
+    fn required_confirmations(&self) -> u64;
+
+    /// Returns the signer refunds are sent from. `None` disables refunds.
+    fn refund_treasury(&self) -> Option<Address> {
+        None
+    }
+
+    /// Returns the key of signer `address`, for signing messages other than
+    /// transactions. `None` if it is not one of the signers.
+    fn signer(&self, address: Address) -> Option<Arc<dyn SettlementSigner>> {
+        let _ = address;
+        None
+    }
+
+    /// Returns the cache of token EIP-712 domains of the chain. `None` reads them
+    /// from the token on every payment.
+    fn eip712_domains(&self) -> Option<&Eip712DomainCache> {
+        None
+    }
+
+    /// Returns how long a payment must remain valid to be settled on the chain,
+    /// if the chain overrides the scheme's setting.
+    fn min_remaining_validity_secs(&self) -> Option<u64> {
+        None
+    }
 
     /// Sends a meta-transaction to the network.
     fn send_transaction(
@@ -427,6 +980,30 @@ impl<T: Eip155MetaTransactionProvider> Eip155MetaTransactionProvider for Arc<T>
         (**self).chain()
     }
 
+    fn contracts(&self) -> &Eip155Contracts {
+        (**self).contracts()
+    }
+
+    fn required_confirmations(&self) -> u64 {
+        (**self).required_confirmations()
+    }
+
+    fn refund_treasury(&self) -> Option<Address> {
+        (**self).refund_treasury()
+    }
+
+    fn signer(&self, address: Address) -> Option<Arc<dyn SettlementSigner>> {
+        (**self).signer(address)
+    }
+
+    fn eip712_domains(&self) -> Option<&Eip712DomainCache> {
+        (**self).eip712_domains()
+    }
+
+    fn min_remaining_validity_secs(&self) -> Option<u64> {
+        (**self).min_remaining_validity_secs()
+    }
+
     fn send_transaction(
         &self,
         tx: MetaTransaction,
@@ -442,3 +1019,62 @@ impl<T: Eip155MetaTransactionProvider> Eip155MetaTransactionProvider for Arc<T>
         (**self).send_transaction_from(tx, from)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::config::Eip155ChainConfigInner;
+    use std::collections::HashSet;
+
+    #[tokio::test]
+    async fn test_advertised_signers_match_settlement_signers() {
+        let inner: Eip155ChainConfigInner = serde_json::from_value(serde_json::json!({
+            "signers": [
+                "0x0000000000000000000000000000000000000000000000000000000000000001",
+                "0x0000000000000000000000000000000000000000000000000000000000000002",
+            ],
+            "rpc": [{ "http": "http://127.0.0.1:1" }],
+        }))
+        .unwrap();
+        let config = Eip155ChainConfig {
+            chain_reference: Eip155ChainReference::new(42793),
+            inner,
+        };
+        let provider = Eip155ChainProvider::from_config(&config).await.unwrap();
+
+        let advertised = ChainProviderOps::signer_addresses(&provider);
+        let settlement = provider
+            .signer_addresses
+            .iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(advertised, settlement);
+        let rotated = (0..advertised.len())
+            .map(|_| provider.next_signer_address().to_string())
+            .collect::<HashSet<_>>();
+        assert_eq!(rotated, advertised.iter().cloned().collect());
+
+        // A sender outside the advertised set is refused before any RPC call.
+        let tx = MetaTransaction {
+            to: Address::ZERO,
+            calldata: Bytes::new(),
+            confirmations: 1,
+        };
+        let result = provider.send_transaction_from(tx, Address::ZERO).await;
+        assert!(matches!(result, Err(MetaTransactionSendError::Custom(_))));
+    }
+
+    #[test]
+    fn test_contract_overrides_keep_canonical_defaults() {
+        let inner: Eip155ChainConfigInner = serde_json::from_value(serde_json::json!({
+            "signers": [],
+            "rpc": [],
+            "contracts": { "permit2": "0x5555555555555555555555555555555555555555" },
+        }))
+        .unwrap();
+        let contracts = Eip155Contracts::from(inner.contracts);
+        assert_eq!(contracts.permit2, Address::repeat_byte(0x55));
+        assert_eq!(contracts.eip6492_validator, VALIDATOR_ADDRESS);
+        assert_eq!(Eip155Contracts::default().permit2, PERMIT2_ADDRESS);
+    }
+}
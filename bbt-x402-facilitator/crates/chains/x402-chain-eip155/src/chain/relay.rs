@@ -0,0 +1,256 @@
+//! Settlement through a managed relay service.
+//!
+//! Operators that already run a relayer can have it submit settlements instead of
+//! the signers' EOAs, which leaves nonce management, gas pricing and resubmission of
+//! stuck transactions to the relay. With a [`RelayConfig`], every settlement call is
+//! sent to a relayer REST API compatible with OpenZeppelin Defender's:
+//!
+//! 1. its gas is estimated on the chain's RPC, as sent from the relayer's `address`;
+//! 2. `POST {url}/txs` with `to`, `data`, `value`, `gasLimit` and `speed` returns the
+//!    relayer's `transactionId`;
+//! 3. `GET {url}/txs/{transactionId}` is polled until the transaction is `mined` or
+//!    `confirmed`, then its receipt is read from the chain by the latest `hash`, which
+//!    changes when the relayer resubmits it.
+//!
+//! The signers stay the facilitator's identity towards payers: Permit2 allowance
+//! payloads naming a signer as spender can not be settled this way, as the relayer,
+//! not the signer, calls Permit2.
+
+use alloy_network::TransactionBuilder;
+use alloy_primitives::{Address, Bytes, TxHash};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::{TransactionReceipt, TransactionRequest};
+use alloy_transport_http::reqwest;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::chain::config::RelayConfig;
+use crate::chain::provider::{InnerProvider, MetaTransaction, MetaTransactionSendError};
+
+/// How often the relayer is asked for the state of a transaction.
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Transaction submitted to the relayer.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RelayTransactionRequest<'a> {
+    to: Address,
+    data: &'a Bytes,
+    value: &'static str,
+    gas_limit: u64,
+    speed: &'a str,
+}
+
+/// A transaction as reported by the relayer.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RelayTransaction {
+    transaction_id: String,
+    #[serde(default)]
+    hash: Option<TxHash>,
+    #[serde(default)]
+    status: Option<String>,
+}
+
+/// Where a relayed transaction stands.
+#[derive(Debug, PartialEq, Eq)]
+enum RelayStatus {
+    Pending,
+    Mined(TxHash),
+    Failed,
+}
+
+impl RelayTransaction {
+    fn status(&self) -> RelayStatus {
+        match (self.status.as_deref(), self.hash) {
+            (Some("mined" | "confirmed"), Some(hash)) => RelayStatus::Mined(hash),
+            (Some("failed"), _) => RelayStatus::Failed,
+            _ => RelayStatus::Pending,
+        }
+    }
+}
+
+/// Submits settlement calls through the configured relayer.
+#[derive(Debug)]
+pub(crate) struct RelaySender {
+    config: RelayConfig,
+    http: reqwest::Client,
+}
+
+impl RelaySender {
+    pub(crate) fn new(config: RelayConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Relays `tx` and returns its receipt once it has `tx.confirmations`.
+    pub(crate) async fn send(
+        &self,
+        provider: &InnerProvider,
+        tx: MetaTransaction,
+        timeout: Duration,
+    ) -> Result<TransactionReceipt, MetaTransactionSendError> {
+        let deadline = Instant::now() + timeout;
+        let estimate = TransactionRequest::default()
+            .with_from(self.config.address)
+            .with_to(tx.to)
+            .with_input(tx.calldata.clone());
+        let gas_limit = provider.estimate_gas(estimate).await?;
+        let request = RelayTransactionRequest {
+            to: tx.to,
+            data: &tx.calldata,
+            value: "0",
+            gas_limit,
+            speed: &self.config.speed,
+        };
+        let body = serde_json::to_vec(&request).expect("relay request serialization failed");
+        let submitted = self
+            .request(self.http.post(self.url("txs")?).body(body))
+            .await?;
+        let id = submitted.transaction_id;
+        #[cfg(feature = "telemetry")]
+        tracing::info!(relay_tx = %id, to = %tx.to, "Settlement submitted to relay");
+
+        let status_url = self.url(&format!("txs/{id}"))?;
+        let hash = loop {
+            let transaction = self.request(self.http.get(status_url.clone())).await?;
+            match transaction.status() {
+                RelayStatus::Mined(hash) => break hash,
+                RelayStatus::Failed => {
+                    return Err(MetaTransactionSendError::Custom(format!(
+                        "Relayed transaction {id} failed"
+                    )));
+                }
+                RelayStatus::Pending => {}
+            }
+            if Instant::now() >= deadline {
+                return Err(MetaTransactionSendError::Custom(format!(
+                    "Relayed transaction {id} not mined within {}s",
+                    timeout.as_secs()
+                )));
+            }
+            tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+        };
+
+        let receipt = loop {
+            if let Some(receipt) = provider.get_transaction_receipt(hash).await? {
+                break receipt;
+            }
+            if Instant::now() >= deadline {
+                return Err(MetaTransactionSendError::Custom(format!(
+                    "No receipt for relayed transaction {hash} within {}s",
+                    timeout.as_secs()
+                )));
+            }
+            tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+        };
+        if let Some(included) = receipt.block_number {
+            let target = included + tx.confirmations.max(1) - 1;
+            while provider.get_block_number().await? < target {
+                if Instant::now() >= deadline {
+                    return Err(MetaTransactionSendError::Custom(format!(
+                        "Relayed transaction {hash} not confirmed within {}s",
+                        timeout.as_secs()
+                    )));
+                }
+                tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+            }
+        }
+        Ok(receipt)
+    }
+
+    /// `path` under the relayer's base URL.
+    fn url(&self, path: &str) -> Result<reqwest::Url, MetaTransactionSendError> {
+        let mut base = self.config.url.clone();
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+        base.join(path)
+            .map_err(|e| MetaTransactionSendError::Custom(format!("Invalid relay URL: {e}")))
+    }
+
+    /// Sends `request` with the API key and parses the relayer's transaction.
+    async fn request(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<RelayTransaction, MetaTransactionSendError> {
+        let request = match &self.config.api_key {
+            Some(api_key) => request.bearer_auth(api_key.inner()),
+            None => request,
+        };
+        let response = request
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .send()
+            .await
+            .map_err(|e| MetaTransactionSendError::Custom(format!("Relay request failed: {e}")))?;
+        let status = response.status();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| MetaTransactionSendError::Custom(format!("Relay request failed: {e}")))?;
+        if !status.is_success() {
+            return Err(MetaTransactionSendError::Custom(format!(
+                "Relay responded {status}: {}",
+                String::from_utf8_lossy(&body)
+            )));
+        }
+        serde_json::from_slice(&body)
+            .map_err(|e| MetaTransactionSendError::Custom(format!("Invalid relay response: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+
+    #[test]
+    fn test_relay_wire_format() {
+        let data = Bytes::from_static(&[0xab, 0xcd]);
+        let request = RelayTransactionRequest {
+            to: address!("0x1111111111111111111111111111111111111111"),
+            data: &data,
+            value: "0",
+            gas_limit: 80_000,
+            speed: "fast",
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "to": "0x1111111111111111111111111111111111111111",
+                "data": "0xabcd",
+                "value": "0",
+                "gasLimit": 80_000,
+                "speed": "fast",
+            })
+        );
+
+        let hash = b256!("0x2222222222222222222222222222222222222222222222222222222222222222");
+        let status = |json: serde_json::Value| {
+            serde_json::from_value::<RelayTransaction>(json)
+                .unwrap()
+                .status()
+        };
+        assert_eq!(
+            status(serde_json::json!({ "transactionId": "a", "status": "pending" })),
+            RelayStatus::Pending
+        );
+        assert_eq!(
+            status(
+                serde_json::json!({ "transactionId": "a", "status": "submitted", "hash": hash })
+            ),
+            RelayStatus::Pending
+        );
+        assert_eq!(
+            status(serde_json::json!({ "transactionId": "a", "status": "mined", "hash": hash })),
+            RelayStatus::Mined(hash)
+        );
+        assert_eq!(
+            status(serde_json::json!({ "transactionId": "a", "status": "failed" })),
+            RelayStatus::Failed
+        );
+    }
+}
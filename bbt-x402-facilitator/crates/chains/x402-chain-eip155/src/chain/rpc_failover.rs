@@ -0,0 +1,335 @@
+//! Failover between the RPC endpoints of a chain.
+//!
+//! [`RpcFailover`] is the transport behind [`Eip155ChainProvider`](super::Eip155ChainProvider).
+//! Each request goes to the first listed endpoint whose circuit is closed and moves
+//! on to the next one when it fails, so a single flaky endpoint no longer takes
+//! down verification and settlement for the chain.
+//!
+//! Every endpoint has a circuit breaker, configured by
+//! [`RpcFailoverConfig`]:
+//!
+//! - **closed**: the endpoint takes requests;
+//! - **open**: after `failure_threshold` consecutive failures, the endpoint is
+//!   skipped for `cooldown_secs`;
+//! - **half-open**: once the cooldown is over, the next request is let through.
+//!   A success closes the circuit, a failure opens it again.
+//!
+//! Only transport failures count: connection and HTTP errors, and responses not
+//! received within `request_timeout_secs`. JSON-RPC errors, such as a reverted
+//! `eth_call`, are answers and are returned as they are.
+//!
+//! If every circuit is open, the endpoints are still tried in order rather than
+//! failing the request outright.
+//!
+//! [`RpcFailover::probe`] queries the block number of every endpoint, including
+//! the open ones, so a recovered endpoint is closed again without waiting for
+//! traffic. [`RpcFailover::health`] reports the state of each endpoint.
+
+use alloy_json_rpc::{RequestPacket, ResponsePacket};
+use alloy_provider::{Provider, RootProvider};
+use alloy_rpc_client::RpcClient;
+use alloy_transport::{BoxTransport, TransportError, TransportErrorKind, TransportFut};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::Service;
+use url::Url;
+use x402_types::timestamp::UnixTimestamp;
+
+use crate::chain::config::RpcFailoverConfig;
+
+/// State of an endpoint's circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// Health of one RPC endpoint, as reported by [`RpcFailover::health`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcEndpointHealth {
+    /// Scheme, host and port of the endpoint. Paths and credentials are left out,
+    /// as they often carry API keys.
+    pub endpoint: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// Block number returned by the last successful probe.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_block: Option<u64>,
+    /// Response time of the last successful probe, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_probe_at: Option<UnixTimestamp>,
+}
+
+#[derive(Debug, Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    last_error: Option<String>,
+    last_block: Option<u64>,
+    latency: Option<Duration>,
+    last_probe_at: Option<UnixTimestamp>,
+}
+
+impl Breaker {
+    fn state(&self, now: Instant) -> CircuitState {
+        match self.open_until {
+            None => CircuitState::Closed,
+            Some(until) if now < until => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.open_until = None;
+        self.last_error = None;
+    }
+
+    fn record_failure(&mut self, error: String, config: &RpcFailoverConfig) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        self.last_error = Some(error);
+        if self.consecutive_failures >= config.failure_threshold.max(1) {
+            self.open_until = Some(Instant::now() + Duration::from_secs(config.cooldown_secs));
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Endpoint {
+    label: String,
+    transport: BoxTransport,
+    breaker: Arc<Mutex<Breaker>>,
+}
+
+impl Endpoint {
+    fn breaker(&self) -> std::sync::MutexGuard<'_, Breaker> {
+        self.breaker.lock().expect("circuit breaker lock poisoned")
+    }
+}
+
+/// Transport sending each request to the first healthy RPC endpoint of a chain.
+#[derive(Debug, Clone)]
+pub struct RpcFailover {
+    endpoints: Arc<Vec<Endpoint>>,
+    config: RpcFailoverConfig,
+}
+
+impl RpcFailover {
+    /// Creates a failover transport over `endpoints`, tried in the given order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `endpoints` is empty.
+    pub fn new(endpoints: Vec<(Url, BoxTransport)>, config: RpcFailoverConfig) -> Self {
+        assert!(
+            !endpoints.is_empty(),
+            "at least one RPC endpoint is required"
+        );
+        let endpoints = endpoints
+            .into_iter()
+            .map(|(url, transport)| Endpoint {
+                label: endpoint_label(&url),
+                transport,
+                breaker: Arc::default(),
+            })
+            .collect();
+        Self {
+            endpoints: Arc::new(endpoints),
+            config,
+        }
+    }
+
+    /// Endpoints in the order a request tries them: those taking requests first,
+    /// then the open ones by how soon their cooldown ends.
+    fn ordered(&self) -> Vec<Endpoint> {
+        let now = Instant::now();
+        let (mut available, mut open): (Vec<_>, Vec<_>) = self
+            .endpoints
+            .iter()
+            .cloned()
+            .partition(|endpoint| endpoint.breaker().state(now) != CircuitState::Open);
+        open.sort_by_key(|endpoint| endpoint.breaker().open_until);
+        available.append(&mut open);
+        available
+    }
+
+    async fn request(self, request: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let timeout = Duration::from_secs(self.config.request_timeout_secs);
+        let mut last_error = None;
+        for mut endpoint in self.ordered() {
+            let result = tokio::time::timeout(timeout, endpoint.transport.call(request.clone()))
+                .await
+                .unwrap_or_else(|_| {
+                    Err(TransportErrorKind::custom_str(&format!(
+                        "no response within {}s",
+                        timeout.as_secs()
+                    )))
+                });
+            match result {
+                Ok(response) => {
+                    endpoint.breaker().record_success();
+                    return Ok(response);
+                }
+                Err(e) => {
+                    #[cfg(feature = "telemetry")]
+                    tracing::warn!(endpoint = %endpoint.label, error = %e, "RPC endpoint failed, failing over");
+                    endpoint
+                        .breaker()
+                        .record_failure(e.to_string(), &self.config);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.expect("at least one endpoint was tried"))
+    }
+
+    /// Queries the block number of every endpoint and updates its circuit.
+    ///
+    /// Open circuits are probed too, so that an endpoint that recovered takes
+    /// requests again. A JSON-RPC error counts as a failure here.
+    pub async fn probe(&self) {
+        let timeout = Duration::from_secs(self.config.request_timeout_secs);
+        for endpoint in self.endpoints.iter() {
+            let provider: RootProvider =
+                RootProvider::new(RpcClient::new(endpoint.transport.clone(), false));
+            let started = Instant::now();
+            let result = tokio::time::timeout(timeout, provider.get_block_number())
+                .await
+                .unwrap_or_else(|_| {
+                    Err(TransportErrorKind::custom_str(&format!(
+                        "no response within {}s",
+                        timeout.as_secs()
+                    )))
+                });
+            let mut breaker = endpoint.breaker();
+            breaker.last_probe_at = Some(UnixTimestamp::now());
+            match result {
+                Ok(block) => {
+                    breaker.record_success();
+                    breaker.last_block = Some(block);
+                    breaker.latency = Some(started.elapsed());
+                }
+                Err(e) => breaker.record_failure(e.to_string(), &self.config),
+            }
+        }
+    }
+
+    /// State of every endpoint, in the configured order.
+    pub fn health(&self) -> Vec<RpcEndpointHealth> {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let breaker = endpoint.breaker();
+                RpcEndpointHealth {
+                    endpoint: endpoint.label.clone(),
+                    state: breaker.state(now),
+                    consecutive_failures: breaker.consecutive_failures,
+                    last_error: breaker.last_error.clone(),
+                    last_block: breaker.last_block,
+                    latency_ms: breaker.latency.map(|latency| latency.as_millis() as u64),
+                    last_probe_at: breaker.last_probe_at,
+                }
+            })
+            .collect()
+    }
+
+    /// `true` unless every endpoint's circuit is open.
+    pub fn is_available(&self) -> bool {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .any(|endpoint| endpoint.breaker().state(now) != CircuitState::Open)
+    }
+}
+
+impl Service<RequestPacket> for RpcFailover {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().request(request))
+    }
+}
+
+fn endpoint_label(url: &Url) -> String {
+    match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{}://{host}:{port}", url.scheme()),
+        (Some(host), None) => format!("{}://{host}", url.scheme()),
+        _ => url.scheme().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_transport::IntoBoxTransport;
+    use alloy_transport::mock::{Asserter, MockTransport};
+
+    fn config() -> RpcFailoverConfig {
+        RpcFailoverConfig {
+            failure_threshold: 2,
+            cooldown_secs: 60,
+            request_timeout_secs: 5,
+        }
+    }
+
+    fn endpoint(url: &str, asserter: &Asserter) -> (Url, BoxTransport) {
+        (
+            url.parse().unwrap(),
+            MockTransport::new(asserter.clone()).into_box_transport(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_failover_opens_circuit() {
+        let primary = Asserter::new();
+        let secondary = Asserter::new();
+        let failover = RpcFailover::new(
+            vec![
+                endpoint("https://primary.example/key", &primary),
+                endpoint("https://secondary.example:8545", &secondary),
+            ],
+            config(),
+        );
+        let provider: RootProvider = RootProvider::new(RpcClient::new(failover.clone(), false));
+
+        // The mock fails requests once its queue is empty.
+        for block in [10u64, 11] {
+            secondary.push_success(&block);
+            assert_eq!(provider.get_block_number().await.unwrap(), block);
+        }
+        let health = failover.health();
+        assert_eq!(health[0].endpoint, "https://primary.example");
+        assert_eq!(health[0].state, CircuitState::Open);
+        assert_eq!(health[1].state, CircuitState::Closed);
+        assert!(failover.is_available());
+
+        // Open circuits are skipped.
+        secondary.push_success(&12u64);
+        primary.push_success(&99u64);
+        assert_eq!(provider.get_block_number().await.unwrap(), 12);
+
+        // A successful probe closes the primary's circuit again.
+        secondary.push_success(&13u64);
+        failover.probe().await;
+        let health = failover.health();
+        assert_eq!(health[0].state, CircuitState::Closed);
+        assert_eq!(health[0].last_block, Some(99));
+        assert_eq!(health[1].last_block, Some(13));
+    }
+}
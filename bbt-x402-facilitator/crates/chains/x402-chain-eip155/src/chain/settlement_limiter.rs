@@ -0,0 +1,171 @@
+//! Bounds the settlement transactions a chain provider has in flight.
+//!
+//! Every settlement transaction holds a slot of the chain's [`SettlementLimiter`] from
+//! submission until its receipt arrives. Without a bound, bursts of settlements pile up
+//! nonces on the same signers and hammer the RPC endpoints until they rate-limit the
+//! facilitator.
+//!
+//! When every slot is taken, a settlement waits in a bounded queue for up to
+//! `queue_timeout_secs`. A settlement that finds the queue full, or that waits too
+//! long, is rejected with [`SettlementLimitExceeded`], which carries the delay the
+//! caller is told to retry after.
+//!
+//! ```json
+//! { "settlement_concurrency": { "max_in_flight": 8, "max_queued": 64, "queue_timeout_secs": 10, "retry_after_secs": 5 } }
+//! ```
+
+use serde::Serialize;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::chain::config::SettlementConcurrencyConfig;
+
+/// Raised when a settlement finds no free slot in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("settlement capacity exhausted, retry after {}s", retry_after.as_secs())]
+pub struct SettlementLimitExceeded {
+    /// How long the caller should wait before trying again.
+    pub retry_after: Duration,
+}
+
+/// Current occupancy of a [`SettlementLimiter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementLimiterStatus {
+    pub max_in_flight: usize,
+    pub in_flight: usize,
+    pub max_queued: usize,
+    pub queued: usize,
+}
+
+/// Semaphore over the settlement transactions of a chain, with a bounded wait queue.
+#[derive(Debug)]
+pub struct SettlementLimiter {
+    permits: Arc<Semaphore>,
+    queued: AtomicUsize,
+    config: SettlementConcurrencyConfig,
+}
+
+/// A slot of a [`SettlementLimiter`], released when dropped.
+#[derive(Debug)]
+pub struct SettlementSlot(#[allow(dead_code)] OwnedSemaphorePermit);
+
+impl SettlementLimiter {
+    pub fn new(config: SettlementConcurrencyConfig) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(config.max_in_flight.max(1))),
+            queued: AtomicUsize::new(0),
+            config,
+        }
+    }
+
+    /// Takes a slot, waiting in the queue if none is free.
+    ///
+    /// Fails right away if the queue is full, and after `queue_timeout_secs` if no
+    /// slot frees up.
+    pub async fn acquire(&self) -> Result<SettlementSlot, SettlementLimitExceeded> {
+        if let Ok(permit) = self.permits.clone().try_acquire_owned() {
+            return Ok(SettlementSlot(permit));
+        }
+        let exceeded = SettlementLimitExceeded {
+            retry_after: Duration::from_secs(self.config.retry_after_secs),
+        };
+        let admitted = self
+            .queued
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |queued| {
+                (queued < self.config.max_queued).then_some(queued + 1)
+            })
+            .is_ok();
+        if !admitted {
+            #[cfg(feature = "telemetry")]
+            tracing::warn!(
+                max_queued = self.config.max_queued,
+                "Settlement queue full, rejecting"
+            );
+            return Err(exceeded);
+        }
+        let timeout = Duration::from_secs(self.config.queue_timeout_secs);
+        let permit = tokio::time::timeout(timeout, self.permits.clone().acquire_owned()).await;
+        self.queued.fetch_sub(1, Ordering::AcqRel);
+        match permit {
+            Ok(Ok(permit)) => Ok(SettlementSlot(permit)),
+            // The semaphore is never closed; a timeout is the only way to get here.
+            _ => {
+                #[cfg(feature = "telemetry")]
+                tracing::warn!(?timeout, "No settlement slot freed up in time, rejecting");
+                Err(exceeded)
+            }
+        }
+    }
+
+    /// Slots in use and settlements waiting for one.
+    pub fn status(&self) -> SettlementLimiterStatus {
+        let max_in_flight = self.config.max_in_flight.max(1);
+        SettlementLimiterStatus {
+            max_in_flight,
+            in_flight: max_in_flight - self.permits.available_permits(),
+            max_queued: self.config.max_queued,
+            queued: self.queued.load(Ordering::Acquire),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(max_in_flight: usize, max_queued: usize) -> SettlementLimiter {
+        SettlementLimiter::new(SettlementConcurrencyConfig {
+            max_in_flight,
+            max_queued,
+            queue_timeout_secs: 1,
+            retry_after_secs: 7,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_rejects_when_queue_is_full() {
+        let limiter = limiter(1, 0);
+        let slot = limiter.acquire().await.unwrap();
+        let error = limiter.acquire().await.unwrap_err();
+        assert_eq!(error.retry_after, Duration::from_secs(7));
+        drop(slot);
+        assert!(limiter.acquire().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_queued_settlement_takes_the_released_slot() {
+        let limiter = Arc::new(limiter(1, 1));
+        let slot = limiter.acquire().await.unwrap();
+        let waiter = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire().await.map(|_| ()) }
+        });
+        while limiter.status().queued == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(
+            limiter.status(),
+            SettlementLimiterStatus {
+                max_in_flight: 1,
+                in_flight: 1,
+                max_queued: 1,
+                queued: 1,
+            }
+        );
+        drop(slot);
+        assert!(waiter.await.unwrap().is_ok());
+        assert_eq!(limiter.status().queued, 0);
+        assert_eq!(limiter.status().in_flight, 0);
+    }
+
+    #[tokio::test]
+    async fn test_queued_settlement_times_out() {
+        let limiter = limiter(1, 1);
+        let _slot = limiter.acquire().await.unwrap();
+        assert!(limiter.acquire().await.is_err());
+        assert_eq!(limiter.status().queued, 0);
+    }
+}
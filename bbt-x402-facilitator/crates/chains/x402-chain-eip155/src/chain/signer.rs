@@ -0,0 +1,251 @@
+//! Settlement signers.
+//!
+//! The facilitator signs settlement transactions through the [`SettlementSigner`]
+//! trait, so the key does not have to live in the facilitator process. Signers are
+//! selected per chain in the `signers` list of the chain config (see
+//! [`Eip155SignersConfig`](crate::chain::config::Eip155SignersConfig)):
+//!
+//! - [`LocalSigner`] - a private key in process memory, from a key string or a
+//!   `{"type": "local"}` entry
+//! - [`RemoteSigner`] - a remote signing service reached over JSON-RPC
+//! - `AwsKmsSigner` - a secp256k1 key in AWS KMS (requires the `aws-kms` feature)
+//!
+//! Signers only sign transaction digests. Nonces, gas and chain id are filled in by
+//! the provider before the digest is computed.
+
+use alloy_network::TxSigner;
+use alloy_primitives::{Address, B256, Bytes, Signature};
+use alloy_rpc_client::RpcClient;
+use alloy_signer::SignerSync;
+use alloy_signer_local::PrivateKeySigner;
+use alloy_transport_http::Http;
+use alloy_transport_http::reqwest;
+use std::fmt::Debug;
+use std::sync::Arc;
+use url::Url;
+
+use crate::chain::config::{Eip155SignerConfig, EvmPrivateKey, SignerBackendConfig};
+
+/// Failure to produce a settlement signature.
+#[derive(Debug, thiserror::Error)]
+pub enum SettlementSignerError {
+    #[error("invalid signer configuration: {0}")]
+    Config(String),
+    #[error("signing backend request failed: {0}")]
+    Backend(String),
+    #[error("signing backend returned an invalid signature: {0}")]
+    InvalidSignature(String),
+    #[error("signer backend {0} is not enabled in this build")]
+    Unsupported(&'static str),
+}
+
+/// A key able to sign settlement transactions.
+#[async_trait::async_trait]
+pub trait SettlementSigner: Send + Sync + Debug {
+    /// Address of the key.
+    fn address(&self) -> Address;
+
+    /// Signs a 32-byte digest, returning a recoverable signature.
+    async fn sign_hash(&self, hash: &B256) -> Result<Signature, SettlementSignerError>;
+}
+
+/// Connects the signer described by `config`.
+pub async fn settlement_signer(
+    config: &Eip155SignerConfig,
+) -> Result<Arc<dyn SettlementSigner>, SettlementSignerError> {
+    let backend = match config {
+        Eip155SignerConfig::PrivateKey(key) => {
+            return Ok(Arc::new(LocalSigner::from_key(key.inner())?));
+        }
+        Eip155SignerConfig::Backend(backend) => backend,
+    };
+    match backend {
+        SignerBackendConfig::Local { private_key } => {
+            Ok(Arc::new(LocalSigner::from_key(private_key.inner())?))
+        }
+        SignerBackendConfig::Remote {
+            url,
+            address,
+            method,
+            auth_token,
+        } => Ok(Arc::new(RemoteSigner::new(
+            url.clone(),
+            *address,
+            method.clone(),
+            auth_token.as_ref().map(|token| token.inner().as_str()),
+        )?)),
+        #[cfg(feature = "aws-kms")]
+        SignerBackendConfig::AwsKms {
+            key_id,
+            region,
+            endpoint,
+        } => Ok(Arc::new(
+            crate::chain::aws_kms::AwsKmsSigner::connect(key_id, region, endpoint.clone()).await?,
+        )),
+        #[cfg(not(feature = "aws-kms"))]
+        SignerBackendConfig::AwsKms { .. } => Err(SettlementSignerError::Unsupported("aws_kms")),
+    }
+}
+
+/// Checks that `signature` over `hash` was made by `address`.
+pub(crate) fn ensure_signed_by(
+    signature: &Signature,
+    hash: &B256,
+    address: Address,
+) -> Result<(), SettlementSignerError> {
+    let recovered = signature
+        .recover_address_from_prehash(hash)
+        .map_err(|e| SettlementSignerError::InvalidSignature(e.to_string()))?;
+    if recovered != address {
+        return Err(SettlementSignerError::InvalidSignature(format!(
+            "signed by {recovered}, expected {address}"
+        )));
+    }
+    Ok(())
+}
+
+/// A private key held in process memory.
+#[derive(Debug, Clone)]
+pub struct LocalSigner(PrivateKeySigner);
+
+impl LocalSigner {
+    pub fn new(signer: PrivateKeySigner) -> Self {
+        Self(signer)
+    }
+
+    pub fn from_key(key: &EvmPrivateKey) -> Result<Self, SettlementSignerError> {
+        PrivateKeySigner::from_bytes(&B256::from(*key.as_bytes()))
+            .map(Self)
+            .map_err(|e| SettlementSignerError::Config(e.to_string()))
+    }
+}
+
+#[async_trait::async_trait]
+impl SettlementSigner for LocalSigner {
+    fn address(&self) -> Address {
+        self.0.address()
+    }
+
+    async fn sign_hash(&self, hash: &B256) -> Result<Signature, SettlementSignerError> {
+        self.0
+            .sign_hash_sync(hash)
+            .map_err(|e| SettlementSignerError::Backend(e.to_string()))
+    }
+}
+
+/// A remote signing service.
+///
+/// Each digest is sent as a JSON-RPC request with `[address, digest]` params, and
+/// the result must be the 65-byte `r || s || v` signature as hex. The signature is
+/// checked against `address` before use.
+#[derive(Debug)]
+pub struct RemoteSigner {
+    client: RpcClient,
+    address: Address,
+    method: String,
+}
+
+impl RemoteSigner {
+    pub fn new(
+        url: Url,
+        address: Address,
+        method: String,
+        auth_token: Option<&str>,
+    ) -> Result<Self, SettlementSignerError> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(token) = auth_token {
+            let value = reqwest::header::HeaderValue::from_str(&format!("Bearer {token}"))
+                .map_err(|e| SettlementSignerError::Config(e.to_string()))?;
+            headers.insert(reqwest::header::AUTHORIZATION, value);
+        }
+        let http = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .map_err(|e| SettlementSignerError::Config(e.to_string()))?;
+        Ok(Self {
+            client: RpcClient::new(Http::with_client(http, url), false),
+            address,
+            method,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl SettlementSigner for RemoteSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_hash(&self, hash: &B256) -> Result<Signature, SettlementSignerError> {
+        let bytes: Bytes = self
+            .client
+            .request(self.method.clone(), (self.address, *hash))
+            .await
+            .map_err(|e| SettlementSignerError::Backend(e.to_string()))?;
+        let signature = Signature::try_from(bytes.as_ref())
+            .map_err(|e| SettlementSignerError::InvalidSignature(e.to_string()))?;
+        ensure_signed_by(&signature, hash, self.address)?;
+        Ok(signature)
+    }
+}
+
+/// Adapts a [`SettlementSigner`] to alloy's wallet.
+#[derive(Debug, Clone)]
+pub(crate) struct SettlementTxSigner(pub(crate) Arc<dyn SettlementSigner>);
+
+#[async_trait::async_trait]
+impl TxSigner<Signature> for SettlementTxSigner {
+    fn address(&self) -> Address {
+        self.0.address()
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn alloy_consensus::SignableTransaction<Signature>,
+    ) -> alloy_signer::Result<Signature> {
+        self.0
+            .sign_hash(&tx.signature_hash())
+            .await
+            .map_err(alloy_signer::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    #[tokio::test]
+    async fn test_signer_config() {
+        let configs: Vec<Eip155SignerConfig> = serde_json::from_value(serde_json::json!([
+            "0xcafe000000000000000000000000000000000000000000000000000000000001",
+            {
+                "type": "local",
+                "private_key": "0xcafe000000000000000000000000000000000000000000000000000000000001"
+            },
+            {
+                "type": "remote",
+                "url": "http://localhost:9000",
+                "address": "0x4444444444444444444444444444444444444444"
+            }
+        ]))
+        .unwrap();
+        let key = settlement_signer(&configs[0]).await.unwrap();
+        let local = settlement_signer(&configs[1]).await.unwrap();
+        assert_eq!(key.address(), local.address());
+        let remote = settlement_signer(&configs[2]).await.unwrap();
+        assert_eq!(
+            remote.address(),
+            "0x4444444444444444444444444444444444444444"
+                .parse::<Address>()
+                .unwrap()
+        );
+
+        let hash = keccak256("settlement");
+        let signature = local.sign_hash(&hash).await.unwrap();
+        assert!(ensure_signed_by(&signature, &hash, local.address()).is_ok());
+        assert!(ensure_signed_by(&signature, &hash, remote.address()).is_err());
+
+        assert!(serde_json::from_value::<Eip155SignerConfig>(serde_json::json!("0x1234")).is_err());
+    }
+}
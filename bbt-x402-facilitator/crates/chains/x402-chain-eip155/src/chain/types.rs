@@ -3,12 +3,13 @@
 //! This module provides types that handle serialization and deserialization
 //! of EVM-specific values in the x402 protocol wire format.
 
-use alloy_primitives::{Address, U256, hex};
+use alloy_primitives::{Address, B256, U256, hex};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::{Display, Formatter};
 use std::ops::Mul;
 use std::str::FromStr;
 use x402_types::chain::{ChainId, DeployedTokenAmount};
+use x402_types::proto::amount;
 use x402_types::util::money_amount::{MoneyAmount, MoneyAmountParseError};
 
 /// An Ethereum address that serializes with EIP-55 checksum encoding.
@@ -17,6 +18,10 @@ use x402_types::util::money_amount::{MoneyAmount, MoneyAmountParseError};
 /// (e.g., `0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045`) for compatibility
 /// with the x402 protocol wire format.
 ///
+/// Price tags carry addresses only. An ENS name used as a `pay_to` is resolved
+/// before building them, e.g. with `x402_axum::ens::EnsResolver`, whose module
+/// documents why.
+///
 /// # Example
 ///
 /// ```
@@ -169,11 +174,61 @@ impl From<Address> for Eip155Asset {
     }
 }
 
+/// Declares an address newtype for one role in a payment.
+///
+/// The roles convert into a plain [`Address`] but not into each other, and are
+/// built only by naming the role, e.g. `PayTo(requirements.pay_to)`.
+macro_rules! address_role {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(pub Address);
+
+        impl $name {
+            /// Returns the underlying address.
+            pub fn address(&self) -> Address {
+                self.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                Display::fmt(&self.0, f)
+            }
+        }
+
+        impl From<$name> for Address {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+    };
+}
+
+address_role! {
+    /// The recipient of a payment, `payTo` in the payment requirements.
+    PayTo
+}
+
+address_role! {
+    /// The address allowed to move the payer's tokens under a Permit2 signature:
+    /// the x402 Permit2 proxy, or a facilitator signer for legacy allowance transfers.
+    Spender
+}
+
+address_role! {
+    /// The token holder paying, who signed the authorization.
+    PayerAddress
+}
+
 /// A token amount represented as a U256, serialized as a decimal string.
 ///
 /// This wrapper ensures token amounts are serialized as decimal strings
 /// (e.g., `"1000000"`) rather than hex to maintain compatibility with
 /// the x402 protocol wire format and avoid precision issues in JSON.
+/// Integral JSON numbers are accepted as well, and HTTP responses can be
+/// rewritten with numbers; see [`x402_types::proto::amount`].
 ///
 /// # Example
 ///
@@ -197,12 +252,18 @@ impl FromStr for TokenAmount {
     }
 }
 
+impl Display for TokenAmount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
 impl Serialize for TokenAmount {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.0.to_string())
+        amount::serialize(self, serializer)
     }
 }
 
@@ -211,8 +272,24 @@ impl<'de> Deserialize<'de> for TokenAmount {
     where
         D: Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        Self::from_str(&s).map_err(serde::de::Error::custom)
+        amount::deserialize(deserializer)
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for TokenAmount {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "TokenAmount".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "Amount in the smallest unit, as a decimal string, or as a number up to 2^53 - 1.",
+            "anyOf": [
+                { "type": "string", "pattern": "^[0-9]+$" },
+                { "type": "integer", "minimum": 0, "maximum": 9007199254740991_u64 },
+            ],
+        })
     }
 }
 
@@ -360,6 +437,7 @@ impl Display for Eip155ChainReference {
 ///     eip712: Some(TokenDeploymentEip712 {
 ///         name: "BBT".into(),
 ///         version: "1".into(),
+///         domain: Default::default(),
 ///     }),
 /// };
 ///
@@ -419,6 +497,7 @@ impl Eip155TokenDeployment {
     ///     eip712: Some(TokenDeploymentEip712 {
     ///         name: "BBT".into(),
     ///         version: "1".into(),
+    ///         domain: Default::default(),
     ///     }),
     /// };
     ///
@@ -464,6 +543,42 @@ pub struct TokenDeploymentEip712 {
     pub name: String,
     /// The token version as specified in the EIP-712 domain.
     pub version: String,
+    /// Deviations from the standard domain, flattened into the same object.
+    #[serde(flatten)]
+    pub domain: Eip712DomainShape,
+}
+
+/// Shape of a token's EIP-712 domain.
+///
+/// The default is the standard domain of `name`, `version`, `chainId` and
+/// `verifyingContract`. Tokens deviating from it leave fields out or add a `salt`,
+/// e.g. `{"name": "Token", "version": "", "omitVersion": true}`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip712DomainShape {
+    /// Leaves `version` out of the domain.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub omit_version: bool,
+    /// Leaves `chainId` out of the domain.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub omit_chain_id: bool,
+    /// Leaves `verifyingContract` out of the domain.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub omit_verifying_contract: bool,
+    /// The domain `salt`, for tokens that have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salt: Option<B256>,
+}
+
+impl Eip712DomainShape {
+    /// Whether this is the standard domain.
+    pub fn is_standard(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
 }
 
 #[cfg(test)]
@@ -564,4 +679,17 @@ mod tests {
         let expected = U256::from(999_999_999u64) * U256::from(10).pow(U256::from(18));
         assert_eq!(result.unwrap().amount, expected);
     }
+
+    #[test]
+    fn test_address_roles() {
+        let address = alloy_primitives::address!("0x1111111111111111111111111111111111111111");
+        let pay_to: PayTo = serde_json::from_value(serde_json::json!(address)).unwrap();
+        assert_eq!(pay_to, PayTo(address));
+        assert_eq!(
+            serde_json::to_value(pay_to).unwrap(),
+            serde_json::json!(address)
+        );
+        assert_eq!(pay_to.to_string(), address.to_string());
+        assert_eq!(Address::from(Spender(address)), address);
+    }
 }
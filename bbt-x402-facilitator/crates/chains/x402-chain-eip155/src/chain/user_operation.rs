@@ -0,0 +1,405 @@
+//! Settlement through an ERC-4337 bundler.
+//!
+//! Operators that do not want to fund hot EOAs can have settlements executed by a
+//! smart account instead. With a [`UserOperationConfig`], every settlement call is
+//! wrapped in `execute(to, 0, calldata)` of the account, sent to the bundler as an
+//! EntryPoint v0.7 UserOperation and, with a `paymaster_url`, sponsored by an
+//! ERC-7677 paymaster service:
+//!
+//! 1. `pm_getPaymasterStubData` for gas estimation, if a paymaster is configured;
+//! 2. `eth_estimateUserOperationGas` with a placeholder signature;
+//! 3. `pm_getPaymasterData` for the final sponsorship;
+//! 4. the UserOperation hash is signed, as an EIP-191 message, by the selected signer;
+//! 5. `eth_sendUserOperation`, then `eth_getUserOperationReceipt` until it is included.
+//!
+//! Each UserOperation uses a random 192-bit nonce key, so concurrent settlements of
+//! the same account never compete for a nonce. The account must already be deployed.
+//!
+//! The signers stay the facilitator's identity towards payers: Permit2 allowance
+//! payloads naming a signer as spender can not be settled this way, as the account,
+//! not the signer, calls Permit2.
+
+use alloy_primitives::{Address, B256, Bytes, U256, keccak256};
+use alloy_provider::Provider;
+use alloy_rpc_client::RpcClient;
+use alloy_rpc_types_eth::TransactionReceipt;
+use alloy_sol_types::{SolCall, SolValue, sol};
+use alloy_transport_http::Http;
+use rand::{Rng, rng};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::chain::config::UserOperationConfig;
+use crate::chain::provider::{InnerProvider, MetaTransaction, MetaTransactionSendError};
+use crate::chain::signer::SettlementSigner;
+
+/// How often the bundler is asked for the receipt of a UserOperation.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Well-formed ECDSA signature the gas of a UserOperation is estimated with.
+const PLACEHOLDER_SIGNATURE: [u8; 65] = alloy_primitives::hex!(
+    "fffffffffffffffffffffffffffffff0000000000000000000000000000000007aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa1c"
+);
+
+sol! {
+    /// Call entry point of the smart account.
+    function execute(address dest, uint256 value, bytes func);
+}
+
+/// A UserOperation in the EntryPoint v0.7 JSON-RPC format.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UserOperation {
+    sender: Address,
+    nonce: U256,
+    call_data: Bytes,
+    call_gas_limit: U256,
+    verification_gas_limit: U256,
+    pre_verification_gas: U256,
+    max_fee_per_gas: U256,
+    max_priority_fee_per_gas: U256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    paymaster: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    paymaster_verification_gas_limit: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    paymaster_post_op_gas_limit: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    paymaster_data: Option<Bytes>,
+    signature: Bytes,
+}
+
+impl UserOperation {
+    /// `paymasterAndData` of the packed UserOperation.
+    fn paymaster_and_data(&self) -> Bytes {
+        let Some(paymaster) = self.paymaster else {
+            return Bytes::new();
+        };
+        let mut packed = paymaster.to_vec();
+        packed.extend_from_slice(&uint128(self.paymaster_verification_gas_limit));
+        packed.extend_from_slice(&uint128(self.paymaster_post_op_gas_limit));
+        if let Some(data) = &self.paymaster_data {
+            packed.extend_from_slice(data);
+        }
+        packed.into()
+    }
+
+    /// The hash the account validates the signature against.
+    fn hash(&self, entry_point: Address, chain_id: u64) -> B256 {
+        let packed = (
+            self.sender,
+            self.nonce,
+            keccak256([]),
+            keccak256(&self.call_data),
+            pack_u128_pair(self.verification_gas_limit, self.call_gas_limit),
+            self.pre_verification_gas,
+            pack_u128_pair(self.max_priority_fee_per_gas, self.max_fee_per_gas),
+            keccak256(self.paymaster_and_data()),
+        )
+            .abi_encode_params();
+        keccak256((keccak256(packed), entry_point, U256::from(chain_id)).abi_encode_params())
+    }
+}
+
+/// Big-endian `uint128` of an optional value, zero when absent.
+fn uint128(value: Option<U256>) -> [u8; 16] {
+    let bytes = value.unwrap_or_default().to_be_bytes::<32>();
+    bytes[16..].try_into().expect("16 bytes")
+}
+
+/// Two `uint128` values packed into a `bytes32`, `high` first.
+fn pack_u128_pair(high: U256, low: U256) -> B256 {
+    let mut packed = [0u8; 32];
+    packed[..16].copy_from_slice(&uint128(Some(high)));
+    packed[16..].copy_from_slice(&uint128(Some(low)));
+    B256::from(packed)
+}
+
+/// Gas limits returned by `eth_estimateUserOperationGas`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GasEstimate {
+    pre_verification_gas: U256,
+    verification_gas_limit: U256,
+    call_gas_limit: U256,
+    #[serde(default)]
+    paymaster_verification_gas_limit: Option<U256>,
+    #[serde(default)]
+    paymaster_post_op_gas_limit: Option<U256>,
+}
+
+/// Sponsorship returned by `pm_getPaymasterStubData` and `pm_getPaymasterData`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PaymasterData {
+    paymaster: Address,
+    paymaster_data: Bytes,
+    #[serde(default)]
+    paymaster_verification_gas_limit: Option<U256>,
+    #[serde(default)]
+    paymaster_post_op_gas_limit: Option<U256>,
+}
+
+/// Result of `eth_getUserOperationReceipt`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UserOperationReceipt {
+    success: bool,
+    #[serde(default)]
+    reason: Option<String>,
+    receipt: TransactionReceipt,
+}
+
+/// Sends settlement calls as UserOperations of the configured smart account.
+#[derive(Debug)]
+pub(crate) struct UserOperationSender {
+    config: UserOperationConfig,
+    bundler: RpcClient,
+    paymaster: Option<RpcClient>,
+}
+
+impl UserOperationSender {
+    pub(crate) fn new(config: UserOperationConfig) -> Self {
+        let bundler = RpcClient::new(Http::new(config.bundler_url.clone()), false);
+        let paymaster = config
+            .paymaster_url
+            .clone()
+            .map(|url| RpcClient::new(Http::new(url), false));
+        Self {
+            config,
+            bundler,
+            paymaster,
+        }
+    }
+
+    /// Executes `tx` through the account, signing with `signer`, and returns the
+    /// receipt of the bundle transaction once it has `tx.confirmations`.
+    pub(crate) async fn send(
+        &self,
+        provider: &InnerProvider,
+        chain_id: u64,
+        eip1559: bool,
+        signer: Arc<dyn SettlementSigner>,
+        tx: MetaTransaction,
+        timeout: Duration,
+    ) -> Result<TransactionReceipt, MetaTransactionSendError> {
+        let deadline = Instant::now() + timeout;
+        let (max_fee_per_gas, max_priority_fee_per_gas) = if eip1559 {
+            let fees = provider.estimate_eip1559_fees().await?;
+            (fees.max_fee_per_gas, fees.max_priority_fee_per_gas)
+        } else {
+            let gas_price = provider.get_gas_price().await?;
+            (gas_price, gas_price)
+        };
+        let nonce_key = U256::from_be_slice(&rng().random::<[u8; 24]>());
+        let mut op = UserOperation {
+            sender: self.config.sender,
+            nonce: nonce_key << 64,
+            call_data: executeCall {
+                dest: tx.to,
+                value: U256::ZERO,
+                func: tx.calldata,
+            }
+            .abi_encode()
+            .into(),
+            call_gas_limit: U256::ZERO,
+            verification_gas_limit: U256::ZERO,
+            pre_verification_gas: U256::ZERO,
+            max_fee_per_gas: U256::from(max_fee_per_gas),
+            max_priority_fee_per_gas: U256::from(max_priority_fee_per_gas),
+            paymaster: None,
+            paymaster_verification_gas_limit: None,
+            paymaster_post_op_gas_limit: None,
+            paymaster_data: None,
+            signature: Bytes::from_static(&PLACEHOLDER_SIGNATURE),
+        };
+
+        if let Some(stub) = self
+            .paymaster_data("pm_getPaymasterStubData", &op, chain_id)
+            .await?
+        {
+            apply_paymaster(&mut op, stub);
+        }
+        let estimate: GasEstimate = self
+            .bundler
+            .request(
+                "eth_estimateUserOperationGas",
+                (&op, self.config.entry_point),
+            )
+            .await?;
+        op.pre_verification_gas = estimate.pre_verification_gas;
+        op.verification_gas_limit = estimate.verification_gas_limit;
+        op.call_gas_limit = estimate.call_gas_limit;
+        if op.paymaster.is_some() {
+            op.paymaster_verification_gas_limit = estimate
+                .paymaster_verification_gas_limit
+                .or(op.paymaster_verification_gas_limit);
+            op.paymaster_post_op_gas_limit = estimate
+                .paymaster_post_op_gas_limit
+                .or(op.paymaster_post_op_gas_limit);
+        }
+        if let Some(sponsorship) = self
+            .paymaster_data("pm_getPaymasterData", &op, chain_id)
+            .await?
+        {
+            apply_paymaster(&mut op, sponsorship);
+        }
+
+        let hash = op.hash(self.config.entry_point, chain_id);
+        let signature = signer
+            .sign_hash(&alloy_primitives::eip191_hash_message(hash))
+            .await
+            .map_err(|e| MetaTransactionSendError::Custom(e.to_string()))?;
+        op.signature = Bytes::from(signature.as_bytes().to_vec());
+
+        let op_hash: B256 = self
+            .bundler
+            .request("eth_sendUserOperation", (&op, self.config.entry_point))
+            .await?;
+        #[cfg(feature = "telemetry")]
+        tracing::info!(%op_hash, sender = %op.sender, "UserOperation submitted");
+
+        let receipt = loop {
+            let receipt: Option<UserOperationReceipt> = self
+                .bundler
+                .request("eth_getUserOperationReceipt", (op_hash,))
+                .await?;
+            if let Some(receipt) = receipt {
+                break receipt;
+            }
+            if Instant::now() >= deadline {
+                return Err(MetaTransactionSendError::Custom(format!(
+                    "UserOperation {op_hash} not included within {}s",
+                    timeout.as_secs()
+                )));
+            }
+            tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+        };
+        if !receipt.success {
+            return Err(MetaTransactionSendError::Custom(format!(
+                "UserOperation {op_hash} reverted: {}",
+                receipt.reason.as_deref().unwrap_or("no reason given")
+            )));
+        }
+        let receipt = receipt.receipt;
+
+        if let Some(included) = receipt.block_number {
+            let target = included + tx.confirmations.max(1) - 1;
+            while provider.get_block_number().await? < target {
+                if Instant::now() >= deadline {
+                    return Err(MetaTransactionSendError::Custom(format!(
+                        "UserOperation {op_hash} not confirmed within {}s",
+                        timeout.as_secs()
+                    )));
+                }
+                tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+            }
+        }
+        Ok(receipt)
+    }
+
+    /// Asks the paymaster service, if any, to sponsor `op`.
+    async fn paymaster_data(
+        &self,
+        method: &'static str,
+        op: &UserOperation,
+        chain_id: u64,
+    ) -> Result<Option<PaymasterData>, MetaTransactionSendError> {
+        let Some(paymaster) = &self.paymaster else {
+            return Ok(None);
+        };
+        let context = self
+            .config
+            .paymaster_context
+            .clone()
+            .unwrap_or_else(|| serde_json::json!({}));
+        let data = paymaster
+            .request(
+                method,
+                (
+                    op,
+                    self.config.entry_point,
+                    format!("{chain_id:#x}"),
+                    context,
+                ),
+            )
+            .await?;
+        Ok(Some(data))
+    }
+}
+
+fn apply_paymaster(op: &mut UserOperation, data: PaymasterData) {
+    op.paymaster = Some(data.paymaster);
+    op.paymaster_data = Some(data.paymaster_data);
+    if data.paymaster_verification_gas_limit.is_some() {
+        op.paymaster_verification_gas_limit = data.paymaster_verification_gas_limit;
+    }
+    if data.paymaster_post_op_gas_limit.is_some() {
+        op.paymaster_post_op_gas_limit = data.paymaster_post_op_gas_limit;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    fn op() -> UserOperation {
+        UserOperation {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            nonce: U256::from(7) << 64,
+            call_data: Bytes::from_static(&[1, 2, 3]),
+            call_gas_limit: U256::from(100_000),
+            verification_gas_limit: U256::from(200_000),
+            pre_verification_gas: U256::from(50_000),
+            max_fee_per_gas: U256::from(3),
+            max_priority_fee_per_gas: U256::from(1),
+            paymaster: Some(address!("0x2222222222222222222222222222222222222222")),
+            paymaster_verification_gas_limit: Some(U256::from(30_000)),
+            paymaster_post_op_gas_limit: Some(U256::from(10)),
+            paymaster_data: Some(Bytes::from_static(&[0xaa])),
+            signature: Bytes::new(),
+        }
+    }
+
+    #[test]
+    fn test_packs_gas_fields_as_entry_point_v07() {
+        let op = op();
+        let paymaster_and_data = op.paymaster_and_data();
+        assert_eq!(paymaster_and_data.len(), 20 + 16 + 16 + 1);
+        assert_eq!(&paymaster_and_data[..20], op.paymaster.unwrap().as_slice());
+        assert_eq!(
+            U256::from_be_slice(&paymaster_and_data[20..36]),
+            U256::from(30_000)
+        );
+        assert_eq!(
+            U256::from_be_slice(&paymaster_and_data[36..52]),
+            U256::from(10)
+        );
+
+        let gas_limits = pack_u128_pair(op.verification_gas_limit, op.call_gas_limit);
+        assert_eq!(
+            U256::from_be_bytes(gas_limits.0),
+            (U256::from(200_000) << 128) | U256::from(100_000)
+        );
+    }
+
+    #[test]
+    fn test_hash_binds_entry_point_chain_and_signature_free_fields() {
+        let entry_point = address!("0x0000000071727De22E5E9d8BAf0edAc6f37da032");
+        let mut op = op();
+        let hash = op.hash(entry_point, 42793);
+        assert_ne!(hash, op.hash(entry_point, 1));
+        assert_ne!(hash, op.hash(Address::ZERO, 42793));
+        op.signature = Bytes::from_static(&PLACEHOLDER_SIGNATURE);
+        assert_eq!(hash, op.hash(entry_point, 42793));
+        op.paymaster_data = None;
+        assert_ne!(hash, op.hash(entry_point, 42793));
+
+        let json = serde_json::to_value(&op).unwrap();
+        assert_eq!(json["callGasLimit"], "0x186a0");
+        assert!(json.get("paymasterData").is_none());
+    }
+}
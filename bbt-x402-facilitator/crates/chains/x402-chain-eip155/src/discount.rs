@@ -0,0 +1,121 @@
+//! Token-gated discounts: membership pricing for holders of an NFT or token.
+//!
+//! A server offers a lower price to payers holding a configured ERC-721 or ERC-20
+//! token, e.g. a membership NFT. The offers are advertised in the V2 exact
+//! requirements (`extra.discounts`, see `V2Eip155Exact::token_gated_price_tag`),
+//! next to the full `amount`:
+//!
+//! ```json
+//! "extra": {
+//!   "name": "BBT",
+//!   "version": "1",
+//!   "discounts": [
+//!     { "token": "0x5Af0...", "minBalance": "1", "amount": "8000000000000000" }
+//!   ]
+//! }
+//! ```
+//!
+//! A payer entitled to a discount signs for its `amount` instead of the full one.
+//! The facilitator accepts a payment below the full amount only if it equals the
+//! amount of a discount whose `token` the payer holds at least `minBalance` of,
+//! checked with `balanceOf(payer)` at verify and again at settle time. The same
+//! call serves ERC-20 balances and ERC-721 counts of owned tokens.
+//!
+//! Facilitators checking discounts list [`TOKEN_GATED_DISCOUNT_EXTENSION`] in the
+//! `extensions` of `/supported`. The price tag only advertises discounts to
+//! facilitators that do, since others would reject the discounted amount.
+
+use alloy_primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::chain::TokenAmount;
+
+/// Extension listed on `/supported` by facilitators accepting token-gated discounts.
+pub const TOKEN_GATED_DISCOUNT_EXTENSION: &str = "token-gated-discount";
+
+/// Errors that can occur while building token-gated price tags.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DiscountError {
+    #[error("Token-gated discounts need the EIP-712 domain of the token")]
+    MissingTokenDomain,
+    #[error("Discounted amount {discounted} is not below the price {price}")]
+    NotADiscount { discounted: U256, price: U256 },
+}
+
+/// A lower price for payers holding a token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenGatedDiscount {
+    /// ERC-721 or ERC-20 contract the payer must hold.
+    pub token: Address,
+    /// Minimum `balanceOf(payer)` of `token`: a number of NFTs, or an amount in
+    /// the token's smallest unit.
+    #[serde(default = "one")]
+    pub min_balance: TokenAmount,
+    /// Amount paid instead of the requirements `amount`, in the payment asset's smallest unit.
+    pub amount: TokenAmount,
+}
+
+fn one() -> TokenAmount {
+    TokenAmount(U256::from(1))
+}
+
+impl TokenGatedDiscount {
+    /// Discount of `amount` for holders of at least one token of `token`, e.g. one NFT
+    /// of a membership collection.
+    pub fn for_holders<A: Into<TokenAmount>>(token: Address, amount: A) -> Self {
+        Self {
+            token,
+            min_balance: one(),
+            amount: amount.into(),
+        }
+    }
+
+    /// Requires a balance of at least `min_balance` of the gate token.
+    pub fn with_min_balance<A: Into<TokenAmount>>(mut self, min_balance: A) -> Self {
+        self.min_balance = min_balance.into();
+        self
+    }
+
+    /// Whether a balance of the gate token entitles to the discount.
+    pub fn is_held(&self, balance: U256) -> bool {
+        balance >= self.min_balance.0
+    }
+}
+
+/// Discounts a payment of `paid` can claim: those of exactly that amount.
+pub fn matching_discounts(
+    discounts: &[TokenGatedDiscount],
+    paid: U256,
+) -> impl Iterator<Item = &TokenGatedDiscount> {
+    discounts
+        .iter()
+        .filter(move |discount| discount.amount.0 == paid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn test_discount_wire_format() {
+        let nft = address!("0x5Af0D9827E0c53E4799BB226655A1de152A425a5");
+        let discount: TokenGatedDiscount =
+            serde_json::from_str(&format!(r#"{{"token":"{nft}","amount":"800"}}"#)).unwrap();
+        assert_eq!(discount, TokenGatedDiscount::for_holders(nft, 800u64));
+        assert!(discount.is_held(U256::from(1)));
+        assert!(!discount.is_held(U256::ZERO));
+
+        let erc20 =
+            TokenGatedDiscount::for_holders(Address::ZERO, 500u64).with_min_balance(1000u64);
+        let json = serde_json::to_value(&erc20).unwrap();
+        assert_eq!(json["minBalance"], "1000");
+        assert!(!erc20.is_held(U256::from(999)));
+
+        let discounts = [discount, erc20];
+        let matching: Vec<_> = matching_discounts(&discounts, U256::from(500)).collect();
+        assert_eq!(matching, vec![&discounts[1]]);
+        assert_eq!(matching_discounts(&discounts, U256::from(1000)).count(), 0);
+    }
+}
@@ -13,6 +13,14 @@
 //! - **ERC-3009 Payments**: Gasless token transfers using `transferWithAuthorization`
 //! - **Smart Wallet Support**: EIP-1271 for deployed wallets, EIP-6492 for counterfactual wallets
 //! - **Multiple Signers**: Round-robin signer selection for load distribution
+//! - **Native Coin Payments**: ETH/XTZ payments from an escrow deposit with the "native" scheme
+//! - **Allowance Payments**: Signed intents pulled with `transferFrom` from a prior `approve` with the "allowance" scheme
+//! - **Subscriptions**: Periodic pulls from a Permit2 allowance with the "recurring" scheme
+//! - **Metered Payments**: Settling actual usage up to an authorized maximum with the "upto" scheme
+//! - **Deferred Settlement**: Aggregating many small payments into periodic transfers with the "deferred" scheme
+//! - **Stealth Addresses**: ERC-5564 one-time recipient addresses for payee privacy (V2)
+//! - **Token-Gated Discounts**: Lower prices for holders of an NFT or token balance (V2)
+//! - **Fiat Prices**: USD prices converted to token amounts with a Chainlink or HTTP price oracle (V2)
 //! - **Nonce Management**: Automatic nonce tracking with pending transaction awareness
 //!
 //! # Architecture
@@ -22,10 +30,19 @@
 //! - [`chain`] - Core EVM chain types, providers, and configuration
 //! - [`v1_eip155_exact`] - V1 protocol implementation with network names
 //! - [`v2_eip155_exact`] - V2 protocol implementation with CAIP-2 chain IDs
+//! - [`v2_eip155_allowance`] - V2 payments pulled from an ERC-20 allowance to the facilitator
+//! - [`v2_eip155_deferred`] - V2 payments accrued on a Permit2 allowance and settled in batches
+//! - [`v2_eip155_native`] - V2 payments in the chain's native coin, through an escrow
+//! - [`v2_eip155_recurring`] - V2 subscriptions pulled periodically from a Permit2 allowance
+//! - [`v2_eip155_upto`] - V2 metered payments settled up to a Permit2 allowance
+//! - [`stealth`] - ERC-5564 stealth addresses as payment recipients
+//! - [`discount`] - Token-gated discounts checked on chain
+//! - `oracle` - Exchange rates converting fiat prices to token amounts
 //!
 //! # Feature Flags
 //!
 //! - `server` - Server-side price tag generation
+//! - `oracle` - Fiat-denominated price tags, converted with a price oracle (implies `server`)
 //! - `client` - Client-side payment signing
 //! - `facilitator` - Facilitator-side payment verification and settlement
 //! - `telemetry` - OpenTelemetry tracing support
@@ -46,6 +63,7 @@
 //!     eip712: Some(TokenDeploymentEip712 {
 //!         name: "BBT".into(),
 //!         version: "1".into(),
+//!         domain: Default::default(),
 //!     }),
 //! };
 //!
@@ -86,16 +104,40 @@
 //! ```
 
 pub mod chain;
+pub mod discount;
+#[cfg(feature = "oracle")]
+pub mod oracle;
+pub mod stealth;
 pub mod v1_eip155_exact;
+pub mod v2_eip155_allowance;
+pub mod v2_eip155_deferred;
 pub mod v2_eip155_exact;
+pub mod v2_eip155_native;
+pub mod v2_eip155_recurring;
+pub mod v2_eip155_upto;
 
 mod networks;
 pub use networks::*;
 
 pub use v1_eip155_exact::V1Eip155Exact;
+pub use v2_eip155_allowance::V2Eip155Allowance;
+pub use v2_eip155_deferred::V2Eip155Deferred;
 pub use v2_eip155_exact::V2Eip155Exact;
+pub use v2_eip155_native::V2Eip155Native;
+pub use v2_eip155_recurring::V2Eip155Recurring;
+pub use v2_eip155_upto::V2Eip155Upto;
 
 #[cfg(feature = "client")]
 pub use v1_eip155_exact::client::V1Eip155ExactClient;
 #[cfg(feature = "client")]
+pub use v2_eip155_allowance::client::V2Eip155AllowanceClient;
+#[cfg(feature = "client")]
+pub use v2_eip155_deferred::client::V2Eip155DeferredClient;
+#[cfg(feature = "client")]
 pub use v2_eip155_exact::client::V2Eip155ExactClient;
+#[cfg(feature = "client")]
+pub use v2_eip155_native::client::V2Eip155NativeClient;
+#[cfg(feature = "client")]
+pub use v2_eip155_recurring::client::V2Eip155RecurringClient;
+#[cfg(feature = "client")]
+pub use v2_eip155_upto::client::V2Eip155UptoClient;
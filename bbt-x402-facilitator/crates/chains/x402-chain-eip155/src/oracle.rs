@@ -0,0 +1,413 @@
+//! Fiat-denominated prices converted to token amounts at request time.
+//!
+//! A [`PriceOracle`] holds the USD price of one token, read from a pluggable
+//! [`RateSource`]: a Chainlink price feed on chain ([`ChainlinkFeed`]) or an HTTP
+//! price API ([`HttpRateSource`]). `V2Eip155Exact::price_tag_fiat` turns a price such
+//! as `$0.05` into the token amount it is worth at that rate, rounded up, so prices
+//! of volatile tokens follow the market.
+//!
+//! Rates are reused for a TTL (30 seconds by default) and rejected once older than
+//! the staleness bound (one hour by default), measured from the time the source
+//! last updated them. A stale or unavailable rate fails the price tag instead of
+//! charging an outdated amount.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use x402_chain_eip155::V2Eip155Exact;
+//! use x402_chain_eip155::oracle::{HttpRateSource, PriceOracle};
+//!
+//! let source = HttpRateSource::new(
+//!     "https://api.coingecko.com/api/v3/simple/price?ids=tezos&vs_currencies=usd&include_last_updated_at=true".parse()?,
+//!     "/tezos/usd",
+//! )
+//! .with_updated_at_pointer("/tezos/last_updated_at");
+//! let oracle = PriceOracle::new(source);
+//!
+//! x402.with_dynamic_price(move |_headers, _uri, _base_url| {
+//!     let (oracle, wxtz) = (oracle.clone(), wxtz.clone());
+//!     async move {
+//!         // No price tag would serve the route for free: fall back to a token price.
+//!         let price_tag = V2Eip155Exact::price_tag_fiat(PAY_TO, &wxtz, "$0.05", &oracle)
+//!             .await
+//!             .unwrap_or_else(|_| V2Eip155Exact::price_tag(PAY_TO, wxtz.parse("0.1").unwrap()));
+//!         vec![price_tag]
+//!     }
+//! })
+//! ```
+
+use alloy_primitives::{Address, U256};
+use alloy_provider::Provider;
+use alloy_sol_types::sol;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use url::Url;
+use x402_types::proto::display::FiatEquivalent;
+use x402_types::timestamp::UnixTimestamp;
+use x402_types::util::money_amount::{MoneyAmount, MoneyAmountParseError};
+
+/// Default time a rate is reused before it is read from its source again.
+pub const DEFAULT_RATE_TTL: Duration = Duration::from_secs(30);
+
+/// Default age after which a rate is considered stale.
+pub const DEFAULT_MAX_RATE_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Errors that can occur while converting a fiat price to a token amount.
+#[derive(Debug, thiserror::Error)]
+pub enum OracleError {
+    #[error("Invalid fiat price: {0}")]
+    InvalidPrice(#[from] MoneyAmountParseError),
+    #[error("Rate source failed: {0}")]
+    Source(String),
+    #[error("Invalid exchange rate: {0}")]
+    InvalidRate(String),
+    #[error("Exchange rate is {age}s old, more than the {max_age}s allowed")]
+    Stale { age: u64, max_age: u64 },
+}
+
+/// USD price of one whole token, in fixed point with `decimals` decimals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExchangeRate {
+    pub price: U256,
+    pub decimals: u8,
+    /// When the source last updated the rate.
+    pub updated_at: UnixTimestamp,
+}
+
+impl ExchangeRate {
+    /// Amount of a token with `token_decimals` decimals worth `fiat`, rounded up.
+    pub fn token_amount(
+        &self,
+        fiat: &MoneyAmount,
+        token_decimals: u8,
+    ) -> Result<U256, OracleError> {
+        if self.price.is_zero() {
+            return Err(OracleError::InvalidRate("zero price".to_string()));
+        }
+        let ten = U256::from(10);
+        let numerator = U256::from(fiat.mantissa())
+            .checked_mul(ten.pow(U256::from(token_decimals)))
+            .and_then(|n| n.checked_mul(ten.pow(U256::from(self.decimals))))
+            .ok_or_else(|| OracleError::InvalidRate("amount overflow".to_string()))?;
+        let denominator = ten
+            .pow(U256::from(fiat.scale()))
+            .checked_mul(self.price)
+            .ok_or_else(|| OracleError::InvalidRate("amount overflow".to_string()))?;
+        Ok(numerator.div_ceil(denominator))
+    }
+
+    /// USD value of `amount` of a token with `token_decimals` decimals, rounded to
+    /// the nearest cent and quoted now.
+    pub fn fiat_equivalent(
+        &self,
+        amount: U256,
+        token_decimals: u8,
+    ) -> Result<FiatEquivalent, OracleError> {
+        let ten = U256::from(10);
+        let unit = ten.pow(U256::from(token_decimals as u32 + self.decimals as u32));
+        let cents = amount
+            .checked_mul(self.price)
+            .and_then(|n| n.checked_mul(U256::from(100)))
+            .ok_or_else(|| OracleError::InvalidRate("amount overflow".to_string()))?
+            .saturating_add(unit / U256::from(2))
+            / unit;
+        let hundred = U256::from(100);
+        Ok(FiatEquivalent {
+            currency: "USD".to_string(),
+            amount: format!("{}.{:0>2}", cents / hundred, (cents % hundred).to_string()),
+            quoted_at: UnixTimestamp::now(),
+        })
+    }
+}
+
+/// A source of the USD price of a token.
+#[async_trait::async_trait]
+pub trait RateSource: Send + Sync {
+    async fn rate(&self) -> Result<ExchangeRate, OracleError>;
+}
+
+sol! {
+    /// Chainlink price feed.
+    #[sol(rpc)]
+    interface AggregatorV3Interface {
+        function decimals() external view returns (uint8);
+        function latestRoundData() external view returns (
+            uint80 roundId,
+            int256 answer,
+            uint256 startedAt,
+            uint256 updatedAt,
+            uint80 answeredInRound
+        );
+    }
+}
+
+/// A Chainlink `<TOKEN> / USD` price feed, read through `provider`.
+pub struct ChainlinkFeed<P> {
+    feed: Address,
+    provider: P,
+}
+
+impl<P> ChainlinkFeed<P> {
+    /// Reads the feed (its proxy address) at `feed`.
+    pub fn new(feed: Address, provider: P) -> Self {
+        Self { feed, provider }
+    }
+}
+
+#[async_trait::async_trait]
+impl<P: Provider + Send + Sync> RateSource for ChainlinkFeed<P> {
+    async fn rate(&self) -> Result<ExchangeRate, OracleError> {
+        let feed = AggregatorV3Interface::new(self.feed, &self.provider);
+        let decimals = feed
+            .decimals()
+            .call()
+            .await
+            .map_err(|e| OracleError::Source(e.to_string()))?;
+        let round = feed
+            .latestRoundData()
+            .call()
+            .await
+            .map_err(|e| OracleError::Source(e.to_string()))?;
+        if !round.answer.is_positive() {
+            return Err(OracleError::InvalidRate(format!(
+                "feed {} answered {}",
+                self.feed, round.answer
+            )));
+        }
+        Ok(ExchangeRate {
+            price: round.answer.into_raw(),
+            decimals,
+            updated_at: UnixTimestamp::from_secs(round.updatedAt.saturating_to()),
+        })
+    }
+}
+
+/// A JSON HTTP API serving the USD price of a token.
+///
+/// The price is read at the JSON pointer `price_pointer`, as a number or a decimal
+/// string. Without an `updated_at_pointer` to a Unix timestamp, the rate counts as
+/// updated when fetched and is never stale.
+pub struct HttpRateSource {
+    client: reqwest::Client,
+    url: Url,
+    price_pointer: String,
+    updated_at_pointer: Option<String>,
+}
+
+impl HttpRateSource {
+    pub fn new<S: Into<String>>(url: Url, price_pointer: S) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            price_pointer: price_pointer.into(),
+            updated_at_pointer: None,
+        }
+    }
+
+    /// Reads the time of the last update, in Unix seconds, at the JSON pointer `pointer`.
+    pub fn with_updated_at_pointer<S: Into<String>>(mut self, pointer: S) -> Self {
+        self.updated_at_pointer = Some(pointer.into());
+        self
+    }
+
+    /// Uses `client` for requests, e.g. to send an API key header.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl RateSource for HttpRateSource {
+    async fn rate(&self) -> Result<ExchangeRate, OracleError> {
+        let body: serde_json::Value = self
+            .client
+            .get(self.url.clone())
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| OracleError::Source(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| OracleError::Source(e.to_string()))?;
+        let price = match body.pointer(&self.price_pointer) {
+            Some(serde_json::Value::Number(n)) => n.to_string(),
+            Some(serde_json::Value::String(s)) => s.clone(),
+            _ => {
+                return Err(OracleError::InvalidRate(format!(
+                    "no price at {}",
+                    self.price_pointer
+                )));
+            }
+        };
+        let price = MoneyAmount::parse(&price)
+            .map_err(|e| OracleError::InvalidRate(format!("{price}: {e}")))?;
+        let updated_at = match self.updated_at_pointer.as_ref() {
+            Some(pointer) => body
+                .pointer(pointer)
+                .and_then(serde_json::Value::as_u64)
+                .map(UnixTimestamp::from_secs)
+                .ok_or_else(|| OracleError::InvalidRate(format!("no timestamp at {pointer}")))?,
+            None => UnixTimestamp::now(),
+        };
+        let decimals = u8::try_from(price.scale())
+            .map_err(|_| OracleError::InvalidRate("too many decimals".to_string()))?;
+        Ok(ExchangeRate {
+            price: U256::from(price.mantissa()),
+            decimals,
+            updated_at,
+        })
+    }
+}
+
+/// Exchange rate of one token, cached and checked for staleness.
+///
+/// Cheap to clone: clones share the source and the cached rate.
+#[derive(Clone)]
+pub struct PriceOracle {
+    source: Arc<dyn RateSource>,
+    ttl: Duration,
+    max_age: Duration,
+    cached: Arc<RwLock<Option<(ExchangeRate, Instant)>>>,
+}
+
+impl Debug for PriceOracle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PriceOracle")
+            .field("ttl", &self.ttl)
+            .field("max_age", &self.max_age)
+            .finish()
+    }
+}
+
+impl PriceOracle {
+    pub fn new<S: RateSource + 'static>(source: S) -> Self {
+        Self {
+            source: Arc::new(source),
+            ttl: DEFAULT_RATE_TTL,
+            max_age: DEFAULT_MAX_RATE_AGE,
+            cached: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Sets how long a rate is reused before it is read again.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the age, since the source updated it, after which a rate is refused.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// The current rate, from the cache or the source.
+    pub async fn rate(&self) -> Result<ExchangeRate, OracleError> {
+        let cached = *self.cached.read().unwrap_or_else(|e| e.into_inner());
+        let rate = match cached {
+            Some((rate, fetched_at)) if fetched_at.elapsed() < self.ttl => rate,
+            _ => {
+                let rate = self.source.rate().await?;
+                *self.cached.write().unwrap_or_else(|e| e.into_inner()) =
+                    Some((rate, Instant::now()));
+                rate
+            }
+        };
+        let age = UnixTimestamp::now()
+            .as_secs()
+            .saturating_sub(rate.updated_at.as_secs());
+        if age > self.max_age.as_secs() {
+            return Err(OracleError::Stale {
+                age,
+                max_age: self.max_age.as_secs(),
+            });
+        }
+        Ok(rate)
+    }
+
+    /// Amount of a token with `token_decimals` decimals worth `fiat` (e.g. `$0.05`)
+    /// at the current rate, rounded up.
+    pub async fn token_amount(&self, fiat: &str, token_decimals: u8) -> Result<U256, OracleError> {
+        let fiat = MoneyAmount::parse(fiat)?;
+        self.rate().await?.token_amount(&fiat, token_decimals)
+    }
+
+    /// USD value of `amount` of a token with `token_decimals` decimals at the current
+    /// rate, for the display metadata of price tags.
+    pub async fn fiat_equivalent(
+        &self,
+        amount: U256,
+        token_decimals: u8,
+    ) -> Result<FiatEquivalent, OracleError> {
+        self.rate().await?.fiat_equivalent(amount, token_decimals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct FixedRate {
+        rate: ExchangeRate,
+        reads: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl RateSource for Arc<FixedRate> {
+        async fn rate(&self) -> Result<ExchangeRate, OracleError> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            Ok(self.rate)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fiat_price_conversion() {
+        // 1 token = $0.80000000, as a Chainlink feed with 8 decimals answers.
+        let source = Arc::new(FixedRate {
+            rate: ExchangeRate {
+                price: U256::from(80_000_000u64),
+                decimals: 8,
+                updated_at: UnixTimestamp::now(),
+            },
+            reads: AtomicUsize::new(0),
+        });
+        let oracle = PriceOracle::new(source.clone());
+        // $0.05 / $0.80 = 0.0625 tokens.
+        assert_eq!(
+            oracle.token_amount("$0.05", 18).await.unwrap(),
+            U256::from(62_500_000_000_000_000u64)
+        );
+        // $0.01 / $0.80 = 0.0125, rounded up at 2 decimals.
+        assert_eq!(
+            oracle.token_amount("$0.01", 2).await.unwrap(),
+            U256::from(2)
+        );
+        // 0.0625 tokens at $0.80 = $0.05.
+        let fiat = oracle
+            .fiat_equivalent(U256::from(62_500_000_000_000_000u64), 18)
+            .await
+            .unwrap();
+        assert_eq!(
+            (fiat.currency.as_str(), fiat.amount.as_str()),
+            ("USD", "0.05")
+        );
+        assert_eq!(source.reads.load(Ordering::SeqCst), 1);
+
+        let stale = Arc::new(FixedRate {
+            rate: ExchangeRate {
+                updated_at: UnixTimestamp::from_secs(UnixTimestamp::now().as_secs() - 7200),
+                ..source.rate
+            },
+            reads: AtomicUsize::new(0),
+        });
+        let oracle = PriceOracle::new(stale).with_max_age(Duration::from_secs(3600));
+        assert!(matches!(
+            oracle.token_amount("$0.05", 18).await,
+            Err(OracleError::Stale { .. })
+        ));
+    }
+}
@@ -0,0 +1,234 @@
+//! ERC-5564 stealth addresses as payment recipients.
+//!
+//! A payee publishes a [`StealthMetaAddress`] instead of a fixed `pay_to`, and every
+//! payment goes to a fresh one-time address derived from it, so payments to the same
+//! payee cannot be linked on chain. This implements scheme 1 of ERC-5564 (secp256k1
+//! with view tags):
+//!
+//! 1. The payee holds a spending key and a viewing key, and publishes the meta-address
+//!    `st:eth:0x<spending public key><viewing public key>` (compressed keys).
+//! 2. For every payment the client draws an ephemeral key `p`, computes the shared
+//!    secret `s = p·V` with the viewing public key `V`, hashes it into
+//!    `sₕ = keccak256(s)`, and pays to the address of `S + sₕ·G`, where `S` is the
+//!    spending public key.
+//! 3. The payee recomputes `s = v·P` from the ephemeral public key `P` with its
+//!    viewing key `v`, and spends with the key `k + sₕ`, where `k` is its spending
+//!    key, see [`stealth_private_key`].
+//!
+//! In x402 the server advertises the meta-address in the V2 exact requirements
+//! (`extra.stealthMetaAddress`, see `V2Eip155Exact::stealth_price_tag`) and `payTo`
+//! holds the address of the spending key, which clients unaware of stealth addresses
+//! pay to. A stealth-aware client sends its ephemeral private key in the payload
+//! (`payload.stealth.ephemeralPrivateKey`), so the facilitator can check that the
+//! signed recipient is derived from the meta-address. The key is only good for
+//! that one payment and does not allow spending from the stealth address. It never
+//! goes on chain, and no ERC-5564 announcement is made: the resource server
+//! receives the payload and is the one to record the ephemeral public key.
+
+use alloy_primitives::{Address, B256, Bytes, hex, keccak256};
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::{FieldBytes, ProjectivePoint, PublicKey, Scalar, SecretKey};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// ERC-5564 scheme id of secp256k1 stealth addresses with view tags.
+pub const STEALTH_SCHEME_ID: u8 = 1;
+
+const META_ADDRESS_PREFIX: &str = "st:eth:0x";
+
+/// Errors that can occur while deriving stealth addresses.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum StealthError {
+    #[error("Invalid stealth meta-address: {0}")]
+    InvalidMetaAddress(String),
+    #[error("Invalid {0}")]
+    InvalidKey(&'static str),
+    #[error("Stealth payments need the EIP-712 domain of the token")]
+    MissingTokenDomain,
+}
+
+/// Spending and viewing public keys of a payee, as published in
+/// `st:eth:0x<spending><viewing>` form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StealthMetaAddress {
+    spending: PublicKey,
+    viewing: PublicKey,
+}
+
+/// A one-time address derived from a [`StealthMetaAddress`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StealthAddress {
+    pub address: Address,
+    /// Compressed ephemeral public key the payee needs to find and spend the payment.
+    pub ephemeral_public_key: Bytes,
+    /// First byte of the hashed shared secret, letting the payee skip unrelated payments.
+    pub view_tag: u8,
+}
+
+impl StealthMetaAddress {
+    /// Builds the meta-address of a payee from its spending and viewing private keys.
+    pub fn from_private_keys(
+        spending_key: &B256,
+        viewing_key: &B256,
+    ) -> Result<Self, StealthError> {
+        Ok(Self {
+            spending: secret_key(spending_key, "spending key")?.public_key(),
+            viewing: secret_key(viewing_key, "viewing key")?.public_key(),
+        })
+    }
+
+    /// Address of the spending public key, used as `payTo` for clients paying without
+    /// stealth addresses.
+    pub fn spending_address(&self) -> Address {
+        point_address(self.spending.to_projective())
+    }
+
+    /// Derives the one-time address of a payment made with `ephemeral_private_key`.
+    pub fn stealth_address(
+        &self,
+        ephemeral_private_key: &B256,
+    ) -> Result<StealthAddress, StealthError> {
+        let ephemeral = secret_key(ephemeral_private_key, "ephemeral private key")?;
+        let shared = self.viewing.to_projective() * *ephemeral.to_nonzero_scalar();
+        let hashed = hashed_secret(shared);
+        let stealth =
+            self.spending.to_projective() + ProjectivePoint::GENERATOR * hash_scalar(&hashed);
+        Ok(StealthAddress {
+            address: point_address(stealth),
+            ephemeral_public_key: Bytes::copy_from_slice(
+                ephemeral.public_key().to_encoded_point(true).as_bytes(),
+            ),
+            view_tag: hashed[0],
+        })
+    }
+}
+
+/// Private key controlling the stealth address of a payment, computed by the payee.
+///
+/// Returns the key and the view tag of the payment.
+pub fn stealth_private_key(
+    spending_key: &B256,
+    viewing_key: &B256,
+    ephemeral_public_key: &[u8],
+) -> Result<(B256, u8), StealthError> {
+    let spending = secret_key(spending_key, "spending key")?;
+    let viewing = secret_key(viewing_key, "viewing key")?;
+    let ephemeral = PublicKey::from_sec1_bytes(ephemeral_public_key)
+        .map_err(|_| StealthError::InvalidKey("ephemeral public key"))?;
+    let shared = ephemeral.to_projective() * *viewing.to_nonzero_scalar();
+    let hashed = hashed_secret(shared);
+    let key = *spending.to_nonzero_scalar() + hash_scalar(&hashed);
+    Ok((B256::from_slice(&key.to_bytes()), hashed[0]))
+}
+
+fn secret_key(key: &B256, what: &'static str) -> Result<SecretKey, StealthError> {
+    SecretKey::from_slice(key.as_slice()).map_err(|_| StealthError::InvalidKey(what))
+}
+
+fn hashed_secret(shared: ProjectivePoint) -> B256 {
+    keccak256(shared.to_affine().to_encoded_point(true).as_bytes())
+}
+
+fn hash_scalar(hashed: &B256) -> Scalar {
+    <Scalar as Reduce<k256::U256>>::reduce_bytes(FieldBytes::from_slice(hashed.as_slice()))
+}
+
+fn point_address(point: ProjectivePoint) -> Address {
+    Address::from_raw_public_key(&point.to_affine().to_encoded_point(false).as_bytes()[1..])
+}
+
+impl FromStr for StealthMetaAddress {
+    type Err = StealthError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| StealthError::InvalidMetaAddress(reason.to_string());
+        let digits = s
+            .strip_prefix(META_ADDRESS_PREFIX)
+            .ok_or_else(|| invalid("expected st:eth:0x prefix"))?;
+        let bytes = hex::decode(digits).map_err(|_| invalid("invalid hex"))?;
+        if bytes.len() != 66 {
+            return Err(invalid("expected two 33-byte compressed public keys"));
+        }
+        let key = |bytes: &[u8]| {
+            PublicKey::from_sec1_bytes(bytes).map_err(|_| invalid("invalid public key"))
+        };
+        Ok(Self {
+            spending: key(&bytes[..33])?,
+            viewing: key(&bytes[33..])?,
+        })
+    }
+}
+
+impl Display for StealthMetaAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{META_ADDRESS_PREFIX}{}{}",
+            hex::encode(self.spending.to_encoded_point(true).as_bytes()),
+            hex::encode(self.viewing.to_encoded_point(true).as_bytes())
+        )
+    }
+}
+
+impl Serialize for StealthMetaAddress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for StealthMetaAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::b256;
+
+    #[test]
+    fn test_payee_controls_stealth_address() {
+        let spending_key =
+            b256!("0x1111111111111111111111111111111111111111111111111111111111111111");
+        let viewing_key =
+            b256!("0x2222222222222222222222222222222222222222222222222222222222222222");
+        let meta = StealthMetaAddress::from_private_keys(&spending_key, &viewing_key).unwrap();
+        let parsed: StealthMetaAddress = meta.to_string().parse().unwrap();
+        assert_eq!(parsed, meta);
+        assert!(meta.to_string().starts_with("st:eth:0x"));
+
+        let first = b256!("0x3333333333333333333333333333333333333333333333333333333333333333");
+        let second = b256!("0x4444444444444444444444444444444444444444444444444444444444444444");
+        let stealth = meta.stealth_address(&first).unwrap();
+        assert_ne!(
+            stealth.address,
+            meta.stealth_address(&second).unwrap().address
+        );
+        assert_ne!(stealth.address, meta.spending_address());
+
+        let (key, view_tag) =
+            stealth_private_key(&spending_key, &viewing_key, &stealth.ephemeral_public_key)
+                .unwrap();
+        let owner = point_address(
+            SecretKey::from_slice(key.as_slice())
+                .unwrap()
+                .public_key()
+                .to_projective(),
+        );
+        assert_eq!(owner, stealth.address);
+        assert_eq!(view_tag, stealth.view_tag);
+
+        assert!("st:eth:0x1234".parse::<StealthMetaAddress>().is_err());
+        assert!(meta.stealth_address(&B256::ZERO).is_err());
+    }
+}
@@ -12,10 +12,13 @@
 //! let signer = PrivateKeySigner::random();
 //! let client = V1Eip155ExactClient::new(signer);
 //! ```
+//!
+//! Any [`Eip712Signer`] can pay: EOAs through [`SignerLike`], and Safe smart accounts,
+//! with EIP-1271 or EIP-6492 signatures, through
+//! [`SafeSigner`](crate::v1_eip155_exact::safe::SafeSigner).
 
-use alloy_primitives::{Address, Bytes, FixedBytes, Signature, U256};
+use alloy_primitives::{Address, B256, Bytes, FixedBytes, Signature, U256};
 use alloy_signer_local::PrivateKeySigner;
-use alloy_sol_types::{SolStruct, eip712_domain};
 use async_trait::async_trait;
 use rand::{Rng, rng};
 use std::sync::Arc;
@@ -24,14 +27,15 @@ use x402_types::proto::PaymentRequired;
 use x402_types::proto::v1::X402Version1;
 use x402_types::scheme::X402SchemeId;
 use x402_types::scheme::client::{
-    PaymentCandidate, PaymentCandidateSigner, X402Error, X402SchemeClient,
+    PaymentCandidate, PaymentCandidateSigner, ValidityWindow, X402Error, X402SchemeClient,
 };
 use x402_types::timestamp::UnixTimestamp;
 use x402_types::util::Base64Bytes;
 
 use crate::v1_eip155_exact::{
     ExactEvmPayload, ExactEvmPayloadAuthorization, ExactScheme, PaymentRequirementsExtra,
-    TransferWithAuthorization, V1Eip155Exact, types,
+    V1Eip155Exact, receive_nonce, receive_with_authorization_hash, token_domain,
+    transfer_with_authorization_hash, types,
 };
 
 use crate::chain::Eip155ChainReference;
@@ -44,7 +48,7 @@ use crate::chain::Eip155ChainReference;
 ///
 /// # Type Parameters
 ///
-/// - `S`: The signer type, which must implement [`SignerLike`]
+/// - `S`: The signer type, which must implement [`Eip712Signer`]
 ///
 /// # Example
 ///
@@ -81,7 +85,7 @@ impl<S> X402SchemeId for V1Eip155ExactClient<S> {
 
 impl<S> X402SchemeClient for V1Eip155ExactClient<S>
 where
-    S: SignerLike + Clone + Send + Sync + 'static,
+    S: Eip712Signer + Clone + Send + Sync + 'static,
 {
     fn accept(&self, payment_required: &PaymentRequired) -> Vec<PaymentCandidate> {
         let payment_required = match payment_required {
@@ -104,6 +108,7 @@ where
                     scheme: self.scheme().to_string(),
                     x402_version: self.x402_version(),
                     pay_to: requirements.pay_to.to_string(),
+                    transfer_method: Some("eip3009".to_string()),
                     signer: Box::new(PayloadSigner {
                         signer: self.signer.clone(),
                         chain_reference,
@@ -133,6 +138,8 @@ pub struct Eip3009SigningParams {
     pub max_timeout_seconds: u64,
     /// Optional EIP-712 domain name and version override
     pub extra: Option<PaymentRequirementsExtra>,
+    /// Explicit validity window; derived from `max_timeout_seconds` when absent
+    pub validity: Option<ValidityWindow>,
 }
 
 /// Signs an ERC-3009 TransferWithAuthorization using EIP-712.
@@ -140,37 +147,46 @@ pub struct Eip3009SigningParams {
 /// This is the shared signing logic used by both v1 and v2 EIP-155 exact scheme clients.
 /// It constructs the EIP-712 domain, builds the authorization struct with appropriate
 /// timing parameters, and signs the resulting hash.
+///
+/// If the requirements name a `receiveForwarder`, a ReceiveWithAuthorization to the
+/// forwarder is signed instead, with `pay_to` bound into the nonce.
 #[allow(dead_code)] // Public for consumption by downstream crates.
-pub async fn sign_erc3009_authorization<S: SignerLike + Sync>(
+pub async fn sign_erc3009_authorization<S: Eip712Signer + Sync>(
     signer: &S,
     params: &Eip3009SigningParams,
 ) -> Result<ExactEvmPayload, X402Error> {
-    // Extract name/version from extra, defaulting to empty strings
-    let (name, version) = match &params.extra {
-        None => ("".to_string(), "".to_string()),
-        Some(extra) => (extra.name.clone(), extra.version.clone()),
-    };
-
-    // Build EIP-712 domain
-    let domain = eip712_domain! {
-        name: name,
-        version: version,
-        chain_id: params.chain_id,
-        verifying_contract: params.asset_address,
+    // Build EIP-712 domain from extra, defaulting to empty name and version
+    let chain = Eip155ChainReference::new(params.chain_id);
+    let domain = match &params.extra {
+        None => token_domain(&chain, params.asset_address, "", ""),
+        Some(extra) => extra.token_domain(&chain, params.asset_address),
     };
 
     // Build authorization with timing
-    let now = UnixTimestamp::now();
-    // valid_after should be in the past (10 minutes ago) to ensure the payment is immediately valid
-    let valid_after_secs = now.as_secs().saturating_sub(10 * 60);
-    let valid_after = UnixTimestamp::from_secs(valid_after_secs);
-    let valid_before = now + params.max_timeout_seconds;
-    let nonce: [u8; 32] = rng().random();
-    let nonce = FixedBytes(nonce);
+    let (valid_after, valid_before) = match params.validity {
+        Some(window) => (window.valid_after, window.valid_before),
+        None => {
+            let now = UnixTimestamp::now();
+            // valid_after should be in the past (10 minutes ago) to ensure the payment is immediately valid
+            let valid_after_secs = now.as_secs().saturating_sub(10 * 60);
+            (
+                UnixTimestamp::from_secs(valid_after_secs),
+                now + params.max_timeout_seconds,
+            )
+        }
+    };
+    let receive_forwarder = params
+        .extra
+        .as_ref()
+        .and_then(|extra| extra.receive_forwarder);
+    let (to, nonce) = match receive_forwarder {
+        Some(forwarder) => (forwarder, receive_nonce(params.pay_to, rng().random())),
+        None => (params.pay_to, FixedBytes(rng().random())),
+    };
 
     let authorization = ExactEvmPayloadAuthorization {
         from: signer.address(),
-        to: params.pay_to,
+        to,
         value: params.amount,
         valid_after,
         valid_before,
@@ -181,26 +197,21 @@ pub async fn sign_erc3009_authorization<S: SignerLike + Sync>(
     // IMPORTANT: The values here MUST match the authorization struct exactly,
     // as the facilitator will reconstruct this struct from the authorization
     // to verify the signature.
-    let transfer_with_authorization = TransferWithAuthorization {
-        from: authorization.from,
-        to: authorization.to,
-        value: authorization.value,
-        validAfter: U256::from(authorization.valid_after.as_secs()),
-        validBefore: U256::from(authorization.valid_before.as_secs()),
-        nonce: authorization.nonce,
+    let eip712_hash = if receive_forwarder.is_some() {
+        receive_with_authorization_hash(&authorization, &domain)
+    } else {
+        transfer_with_authorization_hash(&authorization, &domain)
     };
-
-    let eip712_hash = transfer_with_authorization.eip712_signing_hash(&domain);
     let signature = signer
-        .sign_hash(&eip712_hash)
-        .await
-        .map_err(|e| X402Error::SigningError(format!("{e:?}")))?;
+        .sign_eip712_hash(params.chain_id, &eip712_hash)
+        .await?;
 
     Ok(ExactEvmPayload {
-        signature: Some(Bytes::from(signature.as_bytes().to_vec())),
+        signature: Some(signature),
         authorization: Some(authorization),
         permit2: None,
         permit2_authorization: None,
+        stealth: None,
     })
 }
 
@@ -214,9 +225,25 @@ struct PayloadSigner<S> {
 #[async_trait]
 impl<S> PaymentCandidateSigner for PayloadSigner<S>
 where
-    S: SignerLike + Sync,
+    S: Eip712Signer + Sync,
 {
     async fn sign_payment(&self) -> Result<String, X402Error> {
+        self.sign_with_validity(None).await
+    }
+
+    async fn sign_payment_within(&self, window: ValidityWindow) -> Result<String, X402Error> {
+        self.sign_with_validity(Some(window)).await
+    }
+}
+
+impl<S> PayloadSigner<S>
+where
+    S: Eip712Signer + Sync,
+{
+    async fn sign_with_validity(
+        &self,
+        validity: Option<ValidityWindow>,
+    ) -> Result<String, X402Error> {
         let params = Eip3009SigningParams {
             chain_id: self.chain_reference.inner(),
             asset_address: self.requirements.asset,
@@ -224,6 +251,7 @@ where
             amount: self.requirements.max_amount_required,
             max_timeout_seconds: self.requirements.max_timeout_seconds,
             extra: self.requirements.extra.clone(),
+            validity,
         };
 
         let evm_payload = sign_erc3009_authorization(&self.signer, &params).await?;
@@ -288,3 +316,33 @@ impl<T: SignerLike + Send + Sync> SignerLike for Arc<T> {
         (**self).sign_hash(hash).await
     }
 }
+
+/// Signs payment digests on behalf of the address a payment is made from.
+///
+/// Implemented for every [`SignerLike`] EOA, producing 65-byte ECDSA signatures, and
+/// by [`SafeSigner`](crate::v1_eip155_exact::safe::SafeSigner) for Safe smart accounts,
+/// producing EIP-1271 signatures, wrapped as per EIP-6492 for undeployed accounts.
+#[async_trait]
+pub trait Eip712Signer {
+    /// Returns the address payments are made from.
+    fn address(&self) -> Address;
+
+    /// Signs an EIP-712 digest for a payment on the chain with the given ID, returning
+    /// the signature bytes of the payload.
+    async fn sign_eip712_hash(&self, chain_id: u64, hash: &B256) -> Result<Bytes, X402Error>;
+}
+
+#[async_trait]
+impl<T: SignerLike + Send + Sync> Eip712Signer for T {
+    fn address(&self) -> Address {
+        SignerLike::address(self)
+    }
+
+    async fn sign_eip712_hash(&self, _chain_id: u64, hash: &B256) -> Result<Bytes, X402Error> {
+        let signature = self
+            .sign_hash(hash)
+            .await
+            .map_err(|e| X402Error::SigningError(format!("{e:?}")))?;
+        Ok(Bytes::from(signature.as_bytes().to_vec()))
+    }
+}
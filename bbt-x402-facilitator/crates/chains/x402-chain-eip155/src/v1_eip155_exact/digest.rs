@@ -0,0 +1,301 @@
+//! EIP-712 digests signed by "exact" scheme payers.
+//!
+//! These are the exact hashes the facilitator recovers signers from, computed
+//! without a provider. Client SDKs in other languages can check their typed data
+//! encoding against them:
+//!
+//! - [`transfer_with_authorization_hash`] and [`receive_with_authorization_hash`]
+//!   for ERC-3009 payloads, under a [`token_domain`]
+//! - [`permit_witness_transfer_from_hash`] for Permit2 witness payloads, under the
+//!   [`permit2_witness_domain`]
+//! - [`permit_single_hash`] for Permit2 AllowanceTransfer payloads, under a
+//!   [`permit2_allowance_domain`]
+//! - [`payment_digest`] to pick the right one from payment requirements and a payload
+
+use alloy_primitives::{Address, B256, U256, address};
+use alloy_sol_types::{Eip712Domain, SolStruct, eip712_domain};
+use x402_types::chain::ChainId;
+
+use crate::chain::{Eip155ChainReference, Eip712DomainShape};
+use crate::v1_eip155_exact::types::{
+    self, ExactEvmPayload, ExactEvmPayloadAuthorization, PaymentRequirements, Permit2Authorization,
+    Permit2PermitSingle, ReceiveWithAuthorization, TransferWithAuthorization,
+};
+
+/// Permit2 contract address (canonical CREATE2 deployment).
+pub const PERMIT2_ADDRESS: Address = address!("0x000000000022D473030F116dDEE9F6B43aC78BA3");
+
+/// Errors selecting the digest for a payload.
+#[derive(Debug, thiserror::Error)]
+pub enum DigestError {
+    #[error("Unsupported network: {0}")]
+    UnsupportedNetwork(String),
+    #[error("Payment requirements carry no EIP-712 name and version for the token")]
+    MissingTokenDomain,
+    #[error("Payload carries neither an ERC-3009 nor a Permit2 witness authorization")]
+    MissingAuthorization,
+}
+
+/// EIP-712 domain of an ERC-3009 token.
+pub fn token_domain(
+    chain: &Eip155ChainReference,
+    asset: Address,
+    name: &str,
+    version: &str,
+) -> Eip712Domain {
+    eip712_domain! {
+        name: name.to_string(),
+        version: version.to_string(),
+        chain_id: chain.inner(),
+        verifying_contract: asset,
+    }
+}
+
+/// EIP-712 domain of an ERC-3009 token with the given domain shape.
+pub fn shaped_token_domain(
+    chain: &Eip155ChainReference,
+    asset: Address,
+    name: &str,
+    version: &str,
+    shape: &Eip712DomainShape,
+) -> Eip712Domain {
+    Eip712Domain::new(
+        Some(name.to_string().into()),
+        (!shape.omit_version).then(|| version.to_string().into()),
+        (!shape.omit_chain_id).then(|| U256::from(chain.inner())),
+        (!shape.omit_verifying_contract).then_some(asset),
+        shape.salt,
+    )
+}
+
+/// EIP-712 domain of Permit2 SignatureTransfer messages: name, chain id and
+/// verifying contract, without a version.
+pub fn permit2_witness_domain(chain: &Eip155ChainReference) -> Eip712Domain {
+    permit2_witness_domain_at(chain, PERMIT2_ADDRESS)
+}
+
+/// [`permit2_witness_domain`] for a chain where Permit2 is not at its canonical address.
+pub fn permit2_witness_domain_at(chain: &Eip155ChainReference, permit2: Address) -> Eip712Domain {
+    eip712_domain! {
+        name: "Permit2",
+        chain_id: chain.inner(),
+        verifying_contract: permit2,
+    }
+}
+
+/// EIP-712 domain of Permit2 AllowanceTransfer messages, which, unlike SignatureTransfer
+/// ones, carry a version.
+pub fn permit2_allowance_domain(chain: &Eip155ChainReference, permit2: Address) -> Eip712Domain {
+    eip712_domain! {
+        name: "Permit2",
+        version: "1",
+        chain_id: chain.inner(),
+        verifying_contract: permit2,
+    }
+}
+
+/// Signing hash of an ERC-3009 `TransferWithAuthorization`.
+pub fn transfer_with_authorization_hash(
+    authorization: &ExactEvmPayloadAuthorization,
+    domain: &Eip712Domain,
+) -> B256 {
+    TransferWithAuthorization {
+        from: authorization.from,
+        to: authorization.to,
+        value: authorization.value,
+        validAfter: U256::from(authorization.valid_after.as_secs()),
+        validBefore: U256::from(authorization.valid_before.as_secs()),
+        nonce: authorization.nonce,
+    }
+    .eip712_signing_hash(domain)
+}
+
+/// Signing hash of an ERC-3009 `ReceiveWithAuthorization`.
+pub fn receive_with_authorization_hash(
+    authorization: &ExactEvmPayloadAuthorization,
+    domain: &Eip712Domain,
+) -> B256 {
+    ReceiveWithAuthorization {
+        from: authorization.from,
+        to: authorization.to,
+        value: authorization.value,
+        validAfter: U256::from(authorization.valid_after.as_secs()),
+        validBefore: U256::from(authorization.valid_before.as_secs()),
+        nonce: authorization.nonce,
+    }
+    .eip712_signing_hash(domain)
+}
+
+/// Signing hash of a Permit2 `PermitWitnessTransferFrom` with the x402 witness.
+pub fn permit_witness_transfer_from_hash(
+    authorization: &Permit2Authorization,
+    domain: &Eip712Domain,
+) -> B256 {
+    types::PermitWitnessTransferFrom {
+        permitted: types::TokenPermissions {
+            token: authorization.permitted.token,
+            amount: authorization.permitted.amount,
+        },
+        spender: authorization.spender,
+        nonce: authorization.nonce,
+        deadline: U256::from(authorization.deadline.as_secs()),
+        witness: types::Witness {
+            to: authorization.witness.to,
+            validAfter: U256::from(authorization.witness.valid_after.as_secs()),
+            extra: authorization.witness.extra.clone(),
+        },
+    }
+    .eip712_signing_hash(domain)
+}
+
+/// Signing hash of a Permit2 `PermitSingle`, granting an allowance to its spender.
+///
+/// Amounts above `uint160` and times above `uint48` are clamped; Permit2 rejects
+/// such payloads anyway.
+pub fn permit_single_hash(permit: &Permit2PermitSingle, domain: &Eip712Domain) -> B256 {
+    let details = &permit.details;
+    types::PermitSingle {
+        details: types::PermitDetails {
+            token: details.token,
+            amount: alloy_primitives::U160::saturating_from(details.amount),
+            expiration: alloy_primitives::aliases::U48::saturating_from(details.expiration),
+            nonce: alloy_primitives::aliases::U48::saturating_from(details.nonce),
+        },
+        spender: permit.spender,
+        sigDeadline: U256::from(permit.sig_deadline),
+    }
+    .eip712_signing_hash(domain)
+}
+
+/// Digest the payer signs for `payload` under `requirements`.
+///
+/// A Permit2 witness authorization takes precedence, as in verification. ERC-3009
+/// payloads need the token's EIP-712 `name` and `version` in `requirements.extra`,
+/// and sign a `ReceiveWithAuthorization` when it names a receive forwarder.
+pub fn payment_digest(
+    requirements: &PaymentRequirements,
+    payload: &ExactEvmPayload,
+) -> Result<B256, DigestError> {
+    let chain = ChainId::from_network_name(&requirements.network)
+        .and_then(|chain_id| Eip155ChainReference::try_from(chain_id).ok())
+        .ok_or_else(|| DigestError::UnsupportedNetwork(requirements.network.clone()))?;
+    if let Some(authorization) = payload.permit2_authorization.as_ref() {
+        let domain = permit2_witness_domain(&chain);
+        return Ok(permit_witness_transfer_from_hash(authorization, &domain));
+    }
+    let authorization = payload
+        .authorization
+        .as_ref()
+        .ok_or(DigestError::MissingAuthorization)?;
+    let extra = requirements
+        .extra
+        .as_ref()
+        .ok_or(DigestError::MissingTokenDomain)?;
+    let domain = extra.token_domain(&chain, requirements.asset);
+    if extra.receive_forwarder.is_some() {
+        Ok(receive_with_authorization_hash(authorization, &domain))
+    } else {
+        Ok(transfer_with_authorization_hash(authorization, &domain))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::Bytes;
+    use x402_types::timestamp::UnixTimestamp;
+
+    #[test]
+    fn test_transfer_with_authorization_hash() {
+        let chain = Eip155ChainReference::new(42793);
+        let bbt = address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6");
+        let authorization = ExactEvmPayloadAuthorization {
+            from: address!("0x1111111111111111111111111111111111111111"),
+            to: address!("0x2222222222222222222222222222222222222222"),
+            value: U256::from(1_000_000u64),
+            valid_after: UnixTimestamp::from_secs(0),
+            valid_before: UnixTimestamp::from_secs(1_700_000_000),
+            nonce: B256::repeat_byte(0x33),
+        };
+        let domain = token_domain(&chain, bbt, "BBT", "1");
+        let hash = transfer_with_authorization_hash(&authorization, &domain);
+
+        // keccak256("\x19\x01" || domainSeparator || hashStruct(message)), spelled out.
+        let type_hash = alloy_primitives::keccak256(
+            "TransferWithAuthorization(address from,address to,uint256 value,uint256 validAfter,uint256 validBefore,bytes32 nonce)",
+        );
+        let mut encoded = type_hash.to_vec();
+        encoded.extend_from_slice(authorization.from.into_word().as_slice());
+        encoded.extend_from_slice(authorization.to.into_word().as_slice());
+        encoded.extend_from_slice(&authorization.value.to_be_bytes::<32>());
+        encoded.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
+        encoded.extend_from_slice(&U256::from(1_700_000_000u64).to_be_bytes::<32>());
+        encoded.extend_from_slice(authorization.nonce.as_slice());
+        let mut message = vec![0x19, 0x01];
+        message.extend_from_slice(domain.separator().as_slice());
+        message.extend_from_slice(alloy_primitives::keccak256(&encoded).as_slice());
+        assert_eq!(hash, alloy_primitives::keccak256(&message));
+        assert_ne!(
+            hash,
+            receive_with_authorization_hash(&authorization, &domain)
+        );
+
+        let payload = ExactEvmPayload {
+            signature: Some(Bytes::new()),
+            authorization: Some(authorization),
+            permit2: None,
+            permit2_authorization: None,
+            stealth: None,
+        };
+        let mut requirements: PaymentRequirements = serde_json::from_value(serde_json::json!({
+            "scheme": "exact",
+            "network": "etherlink",
+            "maxAmountRequired": "1000000",
+            "resource": "https://example.com/resource",
+            "description": "",
+            "mimeType": "application/json",
+            "payTo": "0x2222222222222222222222222222222222222222",
+            "maxTimeoutSeconds": 60,
+            "asset": bbt,
+            "extra": {"name": "BBT", "version": "1"},
+        }))
+        .unwrap();
+        assert_eq!(payment_digest(&requirements, &payload).unwrap(), hash);
+        requirements.extra = None;
+        assert!(matches!(
+            payment_digest(&requirements, &payload),
+            Err(DigestError::MissingTokenDomain)
+        ));
+    }
+
+    #[test]
+    fn test_shaped_token_domain() {
+        let chain = Eip155ChainReference::new(42793);
+        let token = address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6");
+        let salt = B256::repeat_byte(0x44);
+        let extra: types::PaymentRequirementsExtra = serde_json::from_value(serde_json::json!({
+            "name": "Token",
+            "omitVersion": true,
+            "omitChainId": true,
+            "salt": salt,
+        }))
+        .unwrap();
+        assert!(!extra.domain.is_standard());
+
+        let domain = extra.token_domain(&chain, token);
+        let expected = eip712_domain! {
+            name: "Token".to_string(),
+            verifying_contract: token,
+            salt: salt,
+        };
+        assert_eq!(domain.separator(), expected.separator());
+        assert_ne!(
+            domain.separator(),
+            token_domain(&chain, token, "Token", "").separator()
+        );
+
+        let json = serde_json::to_value(&extra).unwrap();
+        assert_eq!(json["omitVersion"], true);
+        assert!(json.get("omitVerifyingContract").is_none());
+    }
+}
@@ -0,0 +1,503 @@
+//! Static explanation of "exact" scheme payloads, served by `POST /debug/decode`.
+//!
+//! Integrators debugging a rejected payment usually face a signature that does not
+//! recover to the payer, because their typed data differs from what the facilitator
+//! hashes. [`explain_payment`] reports, without any RPC call:
+//!
+//! - the transfer method the payload selects (ERC-3009, Permit2 witness or allowance)
+//! - the signature kind: EOA, EIP-1271 or EIP-6492, and the address an EOA-sized
+//!   signature recovers to
+//! - the EIP-712 domain and digest the facilitator expects to be signed
+//! - which verification checks pass or fail without reading the chain
+//!
+//! Balances, allowances, nonces and contract wallet signatures are only checked by
+//! `/verify`, and are listed as such.
+
+use alloy_primitives::{Address, B256, Signature, U256};
+use alloy_sol_types::Eip712Domain;
+use serde::Serialize;
+use x402_types::proto::PaymentVerificationError;
+use x402_types::timestamp::UnixTimestamp;
+
+use crate::chain::{Eip155ChainReference, PayTo};
+use crate::v1_eip155_exact::digest;
+use crate::v1_eip155_exact::facilitator::{
+    EIP6492_MAGIC_SUFFIX, assert_permit2_time, assert_permit2_witness_time,
+    assert_receive_recipient, assert_time, join_spenders,
+};
+use crate::v1_eip155_exact::{
+    Eip155ExactConfig, ExactEvmPayload, PaymentRequirementsExtra, assert_amount_matching,
+    assert_asset_allowed,
+};
+
+/// Checks `/verify` runs against the chain, which an explanation cannot.
+const ON_CHAIN_CHECKS: [&str; 3] = ["balance", "allowance", "nonceUnused"];
+
+/// Payment requirements a payload is explained against, common to V1 and V2.
+#[derive(Debug, Clone)]
+pub struct ExplainedRequirements<'a> {
+    /// Chain the payment is made on.
+    pub chain: &'a Eip155ChainReference,
+    /// Token paid.
+    pub asset: Address,
+    /// Recipient of the payment.
+    pub pay_to: Address,
+    /// Required amount.
+    pub amount: U256,
+    /// Longest time the authorization may be valid for.
+    pub max_timeout_seconds: u64,
+    /// Scheme-specific `extra` of the requirements.
+    pub extra: Option<&'a PaymentRequirementsExtra>,
+}
+
+/// How a payload moves the tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransferMethod {
+    /// ERC-3009 `transferWithAuthorization` to `payTo`.
+    Eip3009,
+    /// ERC-3009 `receiveWithAuthorization` through a forwarder.
+    Eip3009Receive,
+    /// Permit2 AllowanceTransfer `permit`, then `transferFrom` by a facilitator signer.
+    Permit2,
+    /// Permit2 SignatureTransfer with the x402 witness, through the x402 proxy.
+    Permit2Witness,
+}
+
+/// Kind of a payer signature, as told from its bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SignatureKind {
+    /// An ECDSA signature recovering to the payer.
+    Eoa,
+    /// Any other signature, validated by the payer contract with EIP-1271.
+    Eip1271,
+    /// A signature of a wallet not deployed yet, wrapped as per EIP-6492.
+    Eip6492,
+}
+
+/// The payer signature of a payload.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureExplanation {
+    /// Kind of the signature.
+    pub kind: SignatureKind,
+    /// Length of the signature in bytes.
+    pub length: usize,
+    /// Address a 64 or 65 byte signature recovers to from the expected digest.
+    /// When it differs from the payer, the client signed different typed data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovered: Option<Address>,
+}
+
+/// The EIP-712 domain the facilitator expects the payload to be signed under.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainExplanation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verifying_contract: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub salt: Option<B256>,
+}
+
+impl From<&Eip712Domain> for DomainExplanation {
+    fn from(domain: &Eip712Domain) -> Self {
+        Self {
+            name: domain.name.as_ref().map(|name| name.to_string()),
+            version: domain.version.as_ref().map(|version| version.to_string()),
+            chain_id: domain.chain_id,
+            verifying_contract: domain.verifying_contract,
+            salt: domain.salt,
+        }
+    }
+}
+
+/// Outcome of a verification check run without the chain.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaticCheck {
+    /// Name of the check.
+    pub check: &'static str,
+    /// Whether the check passes, or `null` if it can only be decided on chain.
+    pub passed: Option<bool>,
+    /// Why the check fails or is left to `/verify`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl StaticCheck {
+    /// A check passing if `result` is `Ok`.
+    pub fn new(check: &'static str, result: Result<(), PaymentVerificationError>) -> Self {
+        Self {
+            check,
+            passed: Some(result.is_ok()),
+            reason: result.err().map(|e| e.to_string()),
+        }
+    }
+
+    /// A check `/verify` decides on chain.
+    pub fn on_chain(check: &'static str, reason: &str) -> Self {
+        Self {
+            check,
+            passed: None,
+            reason: Some(reason.to_string()),
+        }
+    }
+}
+
+/// Static explanation of a payload, see the [module documentation](self).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentExplanation {
+    /// How the payload moves the tokens.
+    pub transfer_method: TransferMethod,
+    /// Payer named by the payload.
+    pub payer: Address,
+    /// The payer signature, if any.
+    pub signature: Option<SignatureExplanation>,
+    /// EIP-712 domain the payload must be signed under, if known without the chain.
+    pub domain: Option<DomainExplanation>,
+    /// Digest the payload must be signed over, if the domain is known.
+    pub digest: Option<B256>,
+    /// Verification checks, in the order `/verify` runs them.
+    pub checks: Vec<StaticCheck>,
+    /// Checks only `/verify` runs.
+    pub not_checked: Vec<&'static str>,
+}
+
+/// Explains `payload` against `requirements`, as a facilitator with `config` and
+/// signers `spenders`, using the Permit2 contract at `permit2`.
+pub fn explain_payment(
+    requirements: &ExplainedRequirements,
+    payload: &ExactEvmPayload,
+    permit2: Address,
+    spenders: &[Address],
+    config: &Eip155ExactConfig,
+) -> Result<PaymentExplanation, PaymentVerificationError> {
+    let chain = requirements.chain;
+    let pay_to = PayTo(requirements.pay_to);
+    let amount_matching = requirements
+        .extra
+        .map(|extra| extra.amount_matching)
+        .unwrap_or_default();
+    let mut checks = Vec::new();
+    // The asset policy bounds the amount the payload transfers, not the required one.
+    let amount_checks = |sent: U256| {
+        let result = assert_amount_matching(&sent, &requirements.amount, amount_matching);
+        let amount = match requirements.extra {
+            Some(extra) if result.is_err() && !extra.discounts.is_empty() => {
+                StaticCheck::on_chain("amount", "may match a token-gated discount the payer holds")
+            }
+            _ => StaticCheck::new("amount", result),
+        };
+        let policy = StaticCheck::new(
+            "assetPolicy",
+            assert_asset_allowed(config.allowed_assets.as_ref(), &requirements.asset, sent),
+        );
+        [amount, policy]
+    };
+
+    let (transfer_method, payer, signature, domain, digest) =
+        if let Some(authorization) = payload.permit2_authorization.as_ref() {
+            checks.push(StaticCheck::new(
+                "asset",
+                assert_that(
+                    authorization.permitted.token == requirements.asset,
+                    PaymentVerificationError::asset_mismatch(
+                        requirements.asset,
+                        authorization.permitted.token,
+                    ),
+                ),
+            ));
+            checks.push(StaticCheck::new(
+                "spender",
+                assert_that(
+                    config.accepts_permit2_proxy(&authorization.spender),
+                    PaymentVerificationError::InvalidFormat(
+                        "permit2Authorization.spender must be the x402 Permit2 proxy".to_string(),
+                    ),
+                ),
+            ));
+            checks.push(StaticCheck::new(
+                "recipient",
+                assert_that(
+                    PayTo(authorization.witness.to) == pay_to,
+                    PaymentVerificationError::recipient_mismatch(pay_to, authorization.witness.to),
+                ),
+            ));
+            checks.extend(amount_checks(authorization.permitted.amount));
+            checks.push(StaticCheck::new(
+                "timeWindow",
+                assert_permit2_witness_time(
+                    authorization.deadline,
+                    authorization.witness.valid_after,
+                    requirements.max_timeout_seconds,
+                    config.grace_buffer_seconds,
+                    &config.clock,
+                ),
+            ));
+            let domain = digest::permit2_witness_domain_at(chain, permit2);
+            let digest = digest::permit_witness_transfer_from_hash(authorization, &domain);
+            (
+                TransferMethod::Permit2Witness,
+                authorization.from,
+                payload.signature.as_ref(),
+                Some(domain),
+                Some(digest),
+            )
+        } else if let Some(permit2_payload) = payload.permit2.as_ref() {
+            let permit_single = &permit2_payload.permit_single;
+            let details = &permit_single.details;
+            checks.push(StaticCheck::new(
+                "asset",
+                assert_that(
+                    details.token == requirements.asset,
+                    PaymentVerificationError::asset_mismatch(requirements.asset, details.token),
+                ),
+            ));
+            checks.push(StaticCheck::new(
+                "spender",
+                assert_that(
+                    spenders.contains(&permit_single.spender),
+                    PaymentVerificationError::recipient_mismatch(
+                        join_spenders(spenders),
+                        permit_single.spender,
+                    ),
+                ),
+            ));
+            checks.push(StaticCheck::new(
+                "timeWindow",
+                assert_permit2_time(
+                    UnixTimestamp::from_secs(permit_single.sig_deadline),
+                    UnixTimestamp::from_secs(details.expiration),
+                    config.grace_buffer_seconds,
+                    &config.clock,
+                ),
+            ));
+            checks.extend(amount_checks(details.amount));
+            let domain = digest::permit2_allowance_domain(chain, permit2);
+            let digest = digest::permit_single_hash(permit_single, &domain);
+            (
+                TransferMethod::Permit2,
+                permit2_payload.owner,
+                Some(&permit2_payload.signature),
+                Some(domain),
+                Some(digest),
+            )
+        } else {
+            let authorization = payload.authorization.as_ref().ok_or_else(|| {
+                PaymentVerificationError::InvalidFormat("Missing authorization".to_string())
+            })?;
+            let receive_forwarder = config.receive_forwarder(&requirements.asset);
+            let recipient = match receive_forwarder {
+                Some(forwarder) => assert_receive_recipient(authorization, forwarder, pay_to),
+                None => assert_that(
+                    PayTo(authorization.to) == pay_to,
+                    PaymentVerificationError::recipient_mismatch(pay_to, authorization.to),
+                ),
+            };
+            checks.push(StaticCheck::new("recipient", recipient));
+            checks.push(StaticCheck::new(
+                "timeWindow",
+                assert_time(
+                    authorization.valid_after,
+                    authorization.valid_before,
+                    config.grace_buffer_seconds,
+                    &config.clock,
+                ),
+            ));
+            checks.extend(amount_checks(authorization.value));
+            let domain = requirements
+                .extra
+                .map(|extra| extra.token_domain(chain, requirements.asset));
+            let digest = domain.as_ref().map(|domain| match receive_forwarder {
+                Some(_) => digest::receive_with_authorization_hash(authorization, domain),
+                None => digest::transfer_with_authorization_hash(authorization, domain),
+            });
+            let transfer_method = match receive_forwarder {
+                Some(_) => TransferMethod::Eip3009Receive,
+                None => TransferMethod::Eip3009,
+            };
+            (
+                transfer_method,
+                authorization.from,
+                payload.signature.as_ref(),
+                domain,
+                digest,
+            )
+        };
+
+    let signature = signature.map(|bytes| explain_signature(bytes, payer, digest));
+    checks.push(match (&signature, digest) {
+        (None, _) => StaticCheck::new(
+            "signature",
+            Err(PaymentVerificationError::InvalidFormat(
+                "Missing signature".to_string(),
+            )),
+        ),
+        (Some(_), None) => StaticCheck::on_chain(
+            "signature",
+            "the token EIP-712 name and version are read from the chain",
+        ),
+        (Some(signature), Some(_)) => match signature.kind {
+            SignatureKind::Eoa => StaticCheck::new("signature", Ok(())),
+            _ if signature.recovered.is_some() => StaticCheck::on_chain(
+                "signature",
+                "does not recover to the payer, valid only if the payer is a contract wallet accepting it",
+            ),
+            _ => StaticCheck::on_chain("signature", "validated by the payer contract wallet"),
+        },
+    });
+    if domain.is_none() {
+        checks.push(StaticCheck::on_chain(
+            "tokenDomain",
+            "requirements carry no EIP-712 name and version for the token",
+        ));
+    }
+
+    Ok(PaymentExplanation {
+        transfer_method,
+        payer,
+        signature,
+        domain: domain.as_ref().map(DomainExplanation::from),
+        digest,
+        checks,
+        not_checked: ON_CHAIN_CHECKS.to_vec(),
+    })
+}
+
+/// Tells the kind of `bytes`, and for ECDSA-sized signatures who they recover to from `digest`.
+pub fn explain_signature(
+    bytes: &[u8],
+    payer: Address,
+    digest: Option<B256>,
+) -> SignatureExplanation {
+    let length = bytes.len();
+    if length >= 32 && bytes[length - 32..] == EIP6492_MAGIC_SUFFIX {
+        return SignatureExplanation {
+            kind: SignatureKind::Eip6492,
+            length,
+            recovered: None,
+        };
+    }
+    let signature = match length {
+        65 => Signature::from_raw(bytes).ok(),
+        64 => Some(Signature::from_erc2098(bytes)),
+        _ => None,
+    };
+    let recovered = signature.zip(digest).and_then(|(signature, digest)| {
+        signature
+            .normalized_s()
+            .recover_address_from_prehash(&digest)
+            .ok()
+    });
+    let kind = if recovered == Some(payer) {
+        SignatureKind::Eoa
+    } else {
+        SignatureKind::Eip1271
+    };
+    SignatureExplanation {
+        kind,
+        length,
+        recovered,
+    }
+}
+
+fn assert_that(
+    condition: bool,
+    error: PaymentVerificationError,
+) -> Result<(), PaymentVerificationError> {
+    if condition { Ok(()) } else { Err(error) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1_eip155_exact::ExactEvmPayloadAuthorization;
+    use alloy_primitives::{Bytes, address};
+    use alloy_signer::SignerSync;
+    use alloy_signer_local::PrivateKeySigner;
+
+    #[test]
+    fn test_explain_eip3009_payment() {
+        let signer = PrivateKeySigner::random();
+        let chain = Eip155ChainReference::new(42793);
+        let asset = address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6");
+        let pay_to = Address::repeat_byte(2);
+        let extra = PaymentRequirementsExtra {
+            name: "BBT".to_string(),
+            version: "1".to_string(),
+            domain: Default::default(),
+            receive_forwarder: None,
+            stealth_meta_address: None,
+            discounts: Vec::new(),
+            amount_matching: Default::default(),
+            display: None,
+        };
+        let requirements = ExplainedRequirements {
+            chain: &chain,
+            asset,
+            pay_to,
+            amount: U256::from(100),
+            max_timeout_seconds: 300,
+            extra: Some(&extra),
+        };
+        let authorization = ExactEvmPayloadAuthorization {
+            from: signer.address(),
+            to: pay_to,
+            value: U256::from(90),
+            valid_after: UnixTimestamp::from_secs(0),
+            valid_before: UnixTimestamp::now() + 300,
+            nonce: B256::repeat_byte(7),
+        };
+        let domain = digest::token_domain(&chain, asset, "BBT", "1");
+        let hash = digest::transfer_with_authorization_hash(&authorization, &domain);
+        let signature = signer.sign_hash_sync(&hash).unwrap();
+        let payload = ExactEvmPayload {
+            signature: Some(Bytes::from(signature.as_bytes().to_vec())),
+            authorization: Some(authorization),
+            permit2: None,
+            permit2_authorization: None,
+            stealth: None,
+        };
+        let explanation = explain_payment(
+            &requirements,
+            &payload,
+            digest::PERMIT2_ADDRESS,
+            &[],
+            &Eip155ExactConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(explanation.transfer_method, TransferMethod::Eip3009);
+        assert_eq!(explanation.digest, Some(hash));
+        let signature = explanation.signature.as_ref().unwrap();
+        assert_eq!(signature.kind, SignatureKind::Eoa);
+        assert_eq!(signature.recovered, Some(signer.address()));
+        let failed: Vec<_> = explanation
+            .checks
+            .iter()
+            .filter(|check| check.passed == Some(false))
+            .map(|check| check.check)
+            .collect();
+        assert_eq!(failed, vec!["amount"]);
+
+        // A signature over other typed data recovers to someone else.
+        let other = explain_signature(
+            signature_bytes(&signer, B256::ZERO).as_ref(),
+            signer.address(),
+            Some(hash),
+        );
+        assert_eq!(other.kind, SignatureKind::Eip1271);
+        assert_ne!(other.recovered, Some(signer.address()));
+    }
+
+    fn signature_bytes(signer: &PrivateKeySigner, hash: B256) -> Bytes {
+        Bytes::from(signer.sign_hash_sync(&hash).unwrap().as_bytes().to_vec())
+    }
+}
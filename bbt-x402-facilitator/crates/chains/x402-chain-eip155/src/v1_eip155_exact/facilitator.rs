@@ -4,21 +4,24 @@
 //! payments on EVM chains. It handles:
 //!
 //! - Signature verification (EOA, EIP-1271, EIP-6492)
-//! - Balance and amount validation
+//! - Balance and amount validation, with token reads batched through Multicall3
 //! - EIP-712 domain construction
 //! - On-chain settlement with gas management
 //! - Smart wallet deployment for counterfactual signatures
+//! - `receiveWithAuthorization` settlement through a forwarder, see [`settlement`](super::settlement)
 
 use alloy_contract::SolCallBuilder;
-use alloy_primitives::{Address, B256, Bytes, Signature, TxHash, U160, U256, address, hex, keccak256};
+use alloy_network::TransactionBuilder;
 use alloy_primitives::aliases::U48;
+use alloy_primitives::{
+    Address, B256, Bytes, Signature, TxHash, U160, U256, address, hex, keccak256,
+};
 use alloy_provider::bindings::IMulticall3;
 use alloy_provider::{
     MULTICALL3_ADDRESS, MulticallError, MulticallItem, PendingTransactionError, Provider,
 };
-use alloy_rpc_types_eth::TransactionRequest;
-use alloy_network::TransactionBuilder;
-use alloy_sol_types::{Eip712Domain, SolCall, SolStruct, SolType, eip712_domain, sol};
+use alloy_rpc_types_eth::{TransactionReceipt, TransactionRequest};
+use alloy_sol_types::{Eip712Domain, SolCall, SolType, sol};
 use alloy_transport::TransportError;
 use std::collections::HashMap;
 use std::str::FromStr;
@@ -28,7 +31,7 @@ use x402_types::proto::{PaymentVerificationError, v1};
 use x402_types::scheme::{
     X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError,
 };
-use x402_types::timestamp::UnixTimestamp;
+use x402_types::timestamp::{Clock, UnixTimestamp};
 
 #[cfg(feature = "telemetry")]
 use tracing::{Instrument, instrument};
@@ -37,18 +40,23 @@ use tracing_core::Level;
 
 use crate::V1Eip155Exact;
 use crate::chain::{
-    Eip155ChainReference, Eip155MetaTransactionProvider, MetaTransaction, MetaTransactionSendError,
+    DryRunProvider, Eip155ChainReference, Eip155Contracts, Eip155MetaTransactionProvider,
+    Eip712DomainCache, MetaTransaction, MetaTransactionSendError, PayTo, PayerAddress,
+    PreparedSettlement, PreparingProvider, SettlementLimitExceeded, Spender,
 };
+use crate::v1_eip155_exact::settlement::permit2_proxy_address_from_env;
 use crate::v1_eip155_exact::{
-    ExactScheme, PaymentRequirementsExtra, TransferWithAuthorization, types,
+    AmountMatching, Eip155ExactConfig, ExactEvmPayloadAuthorization, ExactScheme,
+    PaymentRequirementsExtra, Permit2Authorization, Permit2TokenPermissions, Permit2Witness,
+    assert_asset_allowed, digest, explain, receive_nonce_pay_to, types,
 };
 
 /// Signature verifier for EIP-6492, EIP-1271, EOA, universally deployed on the supported EVM chains
-/// If absent on a target chain, verification will fail; you should deploy the validator there.
+/// Where it is absent, EIP-6492 signatures are checked against the wallet's factory and
+/// `isValidSignature` directly.
 pub const VALIDATOR_ADDRESS: Address = address!("0xdAcD51A54883eb67D95FAEb2BBfdC4a9a6BD2a3B");
 
-/// Permit2 contract address (canonical CREATE2 deployment).
-pub const PERMIT2_ADDRESS: Address = address!("0x000000000022D473030F116dDEE9F6B43aC78BA3");
+pub use super::digest::PERMIT2_ADDRESS;
 
 /// Default x402 Permit2 proxy address for the "exact" scheme.
 ///
@@ -60,51 +68,16 @@ pub const PERMIT2_ADDRESS: Address = address!("0x000000000022D473030F116dDEE9F6B
 pub const X402_EXACT_PERMIT2_PROXY_ADDRESS: Address =
     address!("0xB6FD384A0626BfeF85f3dBaf5223Dd964684B09E");
 
+/// Returns the Permit2 proxy address, overridden by `X402_EXACT_PERMIT2_PROXY_ADDRESS`.
+///
+/// # Panics
+///
+/// If the override is malformed. [`Eip155ExactConfig::from_value`] rejects it up front.
 pub fn x402_exact_permit2_proxy_address() -> Address {
-    if let Ok(raw) = std::env::var("X402_EXACT_PERMIT2_PROXY_ADDRESS") {
-        Address::from_str(&raw).unwrap_or_else(|_| {
-            panic!(
-                "Invalid X402_EXACT_PERMIT2_PROXY_ADDRESS: {}. Set a valid 0x-prefixed address.",
-                raw
-            )
-        })
-    } else {
-        X402_EXACT_PERMIT2_PROXY_ADDRESS
-    }
-}
-
-fn permit2_allowance_transfer_enabled() -> bool {
-    match std::env::var("X402_ENABLE_PERMIT2_ALLOWANCE_TRANSFER") {
-        Ok(v) => matches!(v.to_ascii_lowercase().as_str(), "1" | "true" | "yes"),
-        Err(_) => false,
-    }
-}
-
-fn parse_proxy_codehash_allowlist() -> Result<Option<Vec<B256>>, PaymentVerificationError> {
-    let Ok(raw) = std::env::var("X402_EXACT_PERMIT2_PROXY_CODEHASH_ALLOWLIST") else {
-        return Ok(None);
-    };
-    let raw = raw.trim();
-    if raw.is_empty() {
-        return Ok(None);
-    }
-    let mut hashes = Vec::new();
-    for token in raw.split(',') {
-        let t = token.trim();
-        if t.is_empty() {
-            continue;
-        }
-        let parsed = B256::from_str(t).map_err(|_| {
-            PaymentVerificationError::InvalidFormat(
-                "Invalid X402_EXACT_PERMIT2_PROXY_CODEHASH_ALLOWLIST entry".to_string(),
-            )
-        })?;
-        hashes.push(parsed);
+    match permit2_proxy_address_from_env() {
+        Ok(address) => address.unwrap_or(X402_EXACT_PERMIT2_PROXY_ADDRESS),
+        Err(error) => panic!("{error}"),
     }
-    if hashes.is_empty() {
-        return Ok(None);
-    }
-    Ok(Some(hashes))
 }
 
 impl<P> X402SchemeFacilitatorBuilder<P> for V1Eip155Exact
@@ -115,9 +88,12 @@ where
     fn build(
         &self,
         provider: P,
-        _config: Option<serde_json::Value>,
+        config: Option<serde_json::Value>,
     ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
-        Ok(Box::new(V1Eip155ExactFacilitator::new(provider)))
+        let config = Eip155ExactConfig::from_value(config)?;
+        Ok(Box::new(V1Eip155ExactFacilitator::with_config(
+            provider, config,
+        )))
     }
 }
 
@@ -132,16 +108,25 @@ where
 ///   and [`ChainProviderOps`]
 pub struct V1Eip155ExactFacilitator<P> {
     provider: P,
+    config: Eip155ExactConfig,
 }
 
 impl<P> V1Eip155ExactFacilitator<P> {
     /// Creates a new V1 EIP-155 exact scheme facilitator with the given provider.
     pub fn new(provider: P) -> Self {
-        Self { provider }
+        Self::with_config(provider, Eip155ExactConfig::default())
+    }
+
+    /// Creates a new V1 EIP-155 exact scheme facilitator with per-token settlement settings.
+    pub fn with_config(provider: P, config: Eip155ExactConfig) -> Self {
+        Self { provider, config }
     }
 }
 
-fn parse_signer_addresses(signers: Vec<String>) -> Result<Vec<Address>, Eip155ExactError> {
+/// Parses the facilitator signer addresses, the spenders payments may name.
+pub(crate) fn parse_signer_addresses(
+    signers: Vec<String>,
+) -> Result<Vec<Address>, Eip155ExactError> {
     let mut parsed = Vec::with_capacity(signers.len());
     for signer in signers {
         let addr = Address::from_str(&signer).map_err(|_| {
@@ -152,6 +137,54 @@ fn parse_signer_addresses(signers: Vec<String>) -> Result<Vec<Address>, Eip155Ex
     Ok(parsed)
 }
 
+impl<P> V1Eip155ExactFacilitator<P>
+where
+    P: Eip155MetaTransactionProvider + ChainProviderOps + Send + Sync,
+    P::Inner: Provider,
+    Eip155ExactError: From<P::Error>,
+{
+    /// Verifies `request` like `settle` does and returns the transactions that would
+    /// settle it, for callers that sign and submit settlements themselves.
+    ///
+    /// Nothing is sent. Transactions that must come from a particular signer, such as
+    /// the spender of a Permit2 allowance, carry its address in
+    /// [`PreparedTx::from`](crate::chain::PreparedTx::from).
+    pub async fn prepare_settlement(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<PreparedSettlement, X402SchemeFacilitatorError> {
+        self.verify(request).await?;
+        let request = types::SettleRequest::from_proto(request)?;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+        let allowed_spenders = parse_signer_addresses(self.provider.signer_addresses())?;
+        let allowed_spenders = allowed_spenders.into_iter().map(Spender).collect();
+        let context = assert_valid_payment(
+            self.provider.inner(),
+            self.provider.chain(),
+            self.provider.contracts(),
+            self.provider.eip712_domains(),
+            payload,
+            requirements,
+            Some(allowed_spenders),
+            &self.config,
+        )
+        .await?;
+        let min_remaining = self
+            .config
+            .settle_validity_secs(self.provider.min_remaining_validity_secs());
+        assert_remaining_validity(context.expires_at(), min_remaining, &self.config.clock)?;
+        let preparing = PreparingProvider::new(&self.provider);
+        let (payer, _) = settle_context::<_, MetaTransactionSendError>(
+            &preparing,
+            context,
+            self.provider.required_confirmations(),
+        )
+        .await?;
+        Ok(preparing.finish(payer))
+    }
+}
+
 #[async_trait::async_trait]
 impl<P> X402SchemeFacilitator for V1Eip155ExactFacilitator<P>
 where
@@ -163,16 +196,20 @@ where
         &self,
         request: &proto::VerifyRequest,
     ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
-        let request = types::VerifyRequest::from_proto(request.clone())?;
+        let request = types::VerifyRequest::from_proto(request)?;
         let payload = &request.payment_payload;
         let requirements = &request.payment_requirements;
         let allowed_spenders = parse_signer_addresses(self.provider.signer_addresses())?;
+        let allowed_spenders = allowed_spenders.into_iter().map(Spender).collect();
         let context = assert_valid_payment(
             self.provider.inner(),
             self.provider.chain(),
+            self.provider.contracts(),
+            self.provider.eip712_domains(),
             payload,
             requirements,
             Some(allowed_spenders),
+            &self.config,
         )
         .await?;
 
@@ -181,77 +218,188 @@ where
                 contract,
                 payment,
                 domain,
-            } => verify_payment(self.provider.inner(), &contract, &payment, &domain).await?,
+            } => {
+                verify_payment(
+                    self.provider.inner(),
+                    &contract,
+                    &payment,
+                    &domain,
+                    self.provider.contracts(),
+                )
+                .await?
+            }
+            PaymentContext::Eip3009Receive {
+                contract,
+                payment,
+                domain,
+            } => {
+                verify_payment_receive(
+                    self.provider.inner(),
+                    &contract,
+                    &payment,
+                    &domain,
+                    self.provider.contracts(),
+                )
+                .await?
+            }
             PaymentContext::Permit2 {
                 contract,
                 payment,
                 domain,
-            } => verify_payment_permit2(self.provider.inner(), &contract, &payment, &domain).await?,
+            } => {
+                verify_payment_permit2(self.provider.inner(), &contract, &payment, &domain).await?
+            }
             PaymentContext::Permit2Witness {
                 contract,
                 payment,
                 domain,
-            } => verify_payment_permit2_witness(self.provider.inner(), &contract, &payment, &domain).await?,
+            } => {
+                verify_payment_permit2_witness(
+                    self.provider.inner(),
+                    &contract,
+                    &payment,
+                    &domain,
+                    self.provider.contracts(),
+                )
+                .await?
+            }
         };
 
         Ok(v1::VerifyResponse::valid(payer.to_string()).into())
     }
 
+    async fn explain(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<Option<proto::ExplainResponse>, X402SchemeFacilitatorError> {
+        let request = types::VerifyRequest::from_proto(request)?;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+        let chain = self.provider.chain();
+        let chain_id: ChainId = chain.into();
+        let network = [&payload.network, &requirements.network]
+            .into_iter()
+            .all(|network| ChainId::from_network_name(network).as_ref() == Some(&chain_id));
+        let spenders = parse_signer_addresses(self.provider.signer_addresses())?;
+        let mut explanation = explain::explain_payment(
+            &explain::ExplainedRequirements {
+                chain,
+                asset: requirements.asset,
+                pay_to: requirements.pay_to,
+                amount: requirements.max_amount_required,
+                max_timeout_seconds: requirements.max_timeout_seconds,
+                extra: requirements.extra.as_ref(),
+            },
+            &payload.payload,
+            self.provider.contracts().permit2,
+            &spenders,
+            &self.config,
+        )?;
+        explanation.checks.insert(
+            0,
+            explain::StaticCheck::new(
+                "network",
+                if network {
+                    Ok(())
+                } else {
+                    Err(PaymentVerificationError::ChainIdMismatch)
+                },
+            ),
+        );
+        let explanation =
+            serde_json::to_value(&explanation).expect("PaymentExplanation serialization failed");
+        Ok(Some(proto::ExplainResponse(explanation)))
+    }
+
+    async fn refund(
+        &self,
+        request: &proto::RefundRequest,
+    ) -> Result<proto::RefundResponse, X402SchemeFacilitatorError> {
+        Ok(refund_payment(&self.provider, request).await?)
+    }
+
     async fn settle(
         &self,
         request: &proto::SettleRequest,
     ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
-        let request = types::SettleRequest::from_proto(request.clone())?;
+        let required = self.provider.required_confirmations();
+        let confirmations = match request.confirmation_policy() {
+            proto::ConfirmationPolicy::Confirmed => required,
+            proto::ConfirmationPolicy::Pending => 1,
+        };
+        let request = types::SettleRequest::from_proto(request)?;
         let payload = &request.payment_payload;
         let requirements = &request.payment_requirements;
         let allowed_spenders = parse_signer_addresses(self.provider.signer_addresses())?;
+        let allowed_spenders = allowed_spenders.into_iter().map(Spender).collect();
         let context = assert_valid_payment(
             self.provider.inner(),
             self.provider.chain(),
+            self.provider.contracts(),
+            self.provider.eip712_domains(),
             payload,
             requirements,
             Some(allowed_spenders),
+            &self.config,
         )
         .await?;
+        let min_remaining = self
+            .config
+            .settle_validity_secs(self.provider.min_remaining_validity_secs());
+        assert_remaining_validity(context.expires_at(), min_remaining, &self.config.clock)?;
 
-        let (payer, tx_hash) = match context {
-            PaymentContext::Eip3009 {
-                contract,
-                payment,
-                domain,
-            } => (
-                payment.from,
-                settle_payment(&self.provider, &contract, &payment, &domain).await?,
-            ),
-            PaymentContext::Permit2 {
-                contract,
-                payment,
-                domain,
-            } => {
-                let settlement =
-                    settle_payment_permit2(&self.provider, &contract, &payment, &domain).await?;
-                (
-                    payment.owner,
-                    settlement,
-                )
-            }
-            PaymentContext::Permit2Witness {
-                contract,
-                payment,
-                domain,
-            } => (
-                payment.from,
-                settle_payment_permit2_witness(&self.provider, &contract, &payment, &domain).await?,
-            ),
-        };
+        let settled_amount = context.transfer_amount();
+        let (payer, receipt) = settle_context(&self.provider, context, confirmations).await?;
         Ok(v1::SettleResponse::Success {
             payer: payer.to_string(),
-            transaction: tx_hash.to_string(),
+            transaction: receipt.transaction_hash.to_string(),
             network: payload.network.clone(),
+            receipt: v1::SettlementReceipt {
+                settled_amount: Some(settled_amount.to_string()),
+                ..settlement_receipt(&receipt, confirmations, required)
+            },
         }
         .into())
     }
 
+    async fn settle_dry_run(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleDryRunResponse, X402SchemeFacilitatorError> {
+        self.verify(request).await?;
+        let request = types::SettleRequest::from_proto(request)?;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+        let allowed_spenders = parse_signer_addresses(self.provider.signer_addresses())?;
+        let from = allowed_spenders.first().copied().unwrap_or_default();
+        let allowed_spenders = allowed_spenders.into_iter().map(Spender).collect();
+        let context = assert_valid_payment(
+            self.provider.inner(),
+            self.provider.chain(),
+            self.provider.contracts(),
+            self.provider.eip712_domains(),
+            payload,
+            requirements,
+            Some(allowed_spenders),
+            &self.config,
+        )
+        .await?;
+        let min_remaining = self
+            .config
+            .settle_validity_secs(self.provider.min_remaining_validity_secs());
+        assert_remaining_validity(context.expires_at(), min_remaining, &self.config.clock)?;
+        let dry_run = DryRunProvider::new(&self.provider, from);
+        let (payer, _) =
+            settle_context::<_, MetaTransactionSendError>(&dry_run, context, 1).await?;
+        let report = dry_run
+            .finish(payer, payload.network.clone())
+            .await
+            .map_err(<Eip155ExactError as From<MetaTransactionSendError>>::from)?;
+        Ok(proto::SettleDryRunResponse(
+            serde_json::to_value(report).expect("SettlementDryRun serialization failed"),
+        ))
+    }
+
     async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
         let chain_id = self.provider.chain_id();
         let kinds = {
@@ -262,7 +410,7 @@ where
                     x402_version: v1::X402Version1.into(),
                     scheme: ExactScheme.to_string(),
                     network: network.to_string(),
-                    extra: None,
+                    extra: Some(self.config.supported_extra()),
                 });
             }
             kinds
@@ -284,7 +432,7 @@ where
 #[derive(Debug)]
 pub struct ExactEvmPayment {
     /// Authorized sender (`from`) — EOA or smart wallet.
-    pub from: Address,
+    pub from: PayerAddress,
     /// Authorized recipient (`to`).
     pub to: Address,
     /// Transfer amount (token units).
@@ -299,14 +447,28 @@ pub struct ExactEvmPayment {
     pub signature: Bytes,
 }
 
+impl ExactEvmPayment {
+    /// The signed ERC-3009 authorization, as it appears in the payload.
+    pub fn authorization(&self) -> ExactEvmPayloadAuthorization {
+        ExactEvmPayloadAuthorization {
+            from: self.from.address(),
+            to: self.to,
+            value: self.value,
+            valid_after: self.valid_after,
+            valid_before: self.valid_before,
+            nonce: self.nonce,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Permit2Payment {
     /// Permit2 owner authorizing the allowance.
-    pub owner: Address,
+    pub owner: PayerAddress,
     /// Permit2 spender authorized to transfer.
-    pub spender: Address,
+    pub spender: Spender,
     /// Recipient address for the transfer.
-    pub pay_to: Address,
+    pub pay_to: PayTo,
     /// Token address being authorized.
     pub token: Address,
     /// Permitted allowance amount (uint160 bounded).
@@ -323,13 +485,24 @@ pub struct Permit2Payment {
     pub transfer_amount: U256,
 }
 
+/// An ERC-3009 `ReceiveWithAuthorization` payment settled through a receive forwarder.
+#[derive(Debug)]
+pub struct ExactEvmReceivePayment {
+    /// The signed authorization. Its `to` is the forwarder.
+    pub authorization: ExactEvmPayment,
+    /// Token contract being transferred.
+    pub token: Address,
+    /// Final recipient, bound into the authorization nonce.
+    pub pay_to: PayTo,
+}
+
 /// Coinbase-style Permit2 payment using SignatureTransfer (PermitWitnessTransferFrom).
 #[derive(Debug)]
 pub struct Permit2WitnessPayment {
     /// Signer/owner authorizing the transfer.
-    pub from: Address,
+    pub from: PayerAddress,
     /// The x402 Permit2 proxy address (spender in the signed message).
-    pub spender: Address,
+    pub spender: Spender,
     /// Token address being authorized.
     pub token: Address,
     /// Permitted amount (uint256).
@@ -339,7 +512,7 @@ pub struct Permit2WitnessPayment {
     /// Signature deadline timestamp.
     pub deadline: UnixTimestamp,
     /// Witness destination (must equal payment requirements pay_to).
-    pub pay_to: Address,
+    pub pay_to: PayTo,
     /// Lower time bound (payment invalid before this time).
     pub valid_after: UnixTimestamp,
     /// Extra witness bytes.
@@ -350,6 +523,98 @@ pub struct Permit2WitnessPayment {
     pub transfer_amount: U256,
 }
 
+impl Permit2WitnessPayment {
+    /// The signed Permit2 witness authorization, as it appears in the payload.
+    pub fn authorization(&self) -> Permit2Authorization {
+        Permit2Authorization {
+            from: self.from.address(),
+            permitted: Permit2TokenPermissions {
+                token: self.token,
+                amount: self.amount,
+            },
+            spender: self.spender.address(),
+            nonce: self.nonce,
+            deadline: self.deadline,
+            witness: Permit2Witness {
+                to: self.pay_to.address(),
+                valid_after: self.valid_after,
+                extra: self.extra.clone(),
+            },
+        }
+    }
+}
+
+/// Sends the settlement transactions of a validated payment through `provider`.
+///
+/// Returns the payer and the receipt of the final transaction, once it has
+/// `confirmations` blocks.
+async fn settle_context<P, E>(
+    provider: &P,
+    context: PaymentContext<'_, P::Inner>,
+    confirmations: u64,
+) -> Result<(PayerAddress, TransactionReceipt), Eip155ExactError>
+where
+    P: Eip155MetaTransactionProvider<Error = E>,
+    Eip155ExactError: From<E>,
+{
+    let settled = match context {
+        PaymentContext::Eip3009 {
+            contract,
+            payment,
+            domain,
+        } => (
+            payment.from,
+            settle_payment(provider, &contract, &payment, &domain, confirmations).await?,
+        ),
+        PaymentContext::Eip3009Receive {
+            contract,
+            payment,
+            domain,
+        } => (
+            payment.authorization.from,
+            settle_payment_receive(provider, &contract, &payment, &domain, confirmations).await?,
+        ),
+        PaymentContext::Permit2 {
+            contract,
+            payment,
+            domain,
+        } => (
+            payment.owner,
+            settle_payment_permit2(provider, &contract, &payment, &domain, confirmations).await?,
+        ),
+        PaymentContext::Permit2Witness {
+            contract,
+            payment,
+            domain,
+        } => (
+            payment.from,
+            settle_payment_permit2_witness(provider, &contract, &payment, &domain, confirmations)
+                .await?,
+        ),
+    };
+    Ok(settled)
+}
+
+/// Settlement details reported back to the merchant from `receipt`, which was
+/// awaited for `confirmations` out of the chain's `required` confirmations.
+pub fn settlement_receipt(
+    receipt: &TransactionReceipt,
+    confirmations: u64,
+    required: u64,
+) -> v1::SettlementReceipt {
+    v1::SettlementReceipt {
+        block_number: receipt.block_number,
+        gas_used: Some(receipt.gas_used),
+        signer: Some(receipt.from.to_string()),
+        effective_gas_price: Some(receipt.effective_gas_price),
+        facilitator_fee: None,
+        settled_amount: None,
+        confirmations: Some(confirmations),
+        required_confirmations: Some(required),
+        iou: None,
+    }
+}
+
 #[derive(Debug)]
 enum PaymentContext<'a, P: Provider> {
     Eip3009 {
@@ -357,6 +622,11 @@ enum PaymentContext<'a, P: Provider> {
         payment: ExactEvmPayment,
         domain: Eip712Domain,
     },
+    Eip3009Receive {
+        contract: X402ReceiveForwarder::X402ReceiveForwarderInstance<&'a P>,
+        payment: ExactEvmReceivePayment,
+        domain: Eip712Domain,
+    },
     Permit2 {
         contract: IPermit2::IPermit2Instance<&'a P>,
         payment: Permit2Payment,
@@ -369,6 +639,30 @@ enum PaymentContext<'a, P: Provider> {
     },
 }
 
+impl<P: Provider> PaymentContext<'_, P> {
+    /// Amount the settlement transfers to the payee.
+    fn transfer_amount(&self) -> U256 {
+        match self {
+            PaymentContext::Eip3009 { payment, .. } => payment.value,
+            PaymentContext::Eip3009Receive { payment, .. } => payment.authorization.value,
+            PaymentContext::Permit2 { payment, .. } => payment.transfer_amount,
+            PaymentContext::Permit2Witness { payment, .. } => payment.transfer_amount,
+        }
+    }
+
+    /// When the signed authorization stops being accepted on-chain.
+    fn expires_at(&self) -> UnixTimestamp {
+        match self {
+            PaymentContext::Eip3009 { payment, .. } => payment.valid_before,
+            PaymentContext::Eip3009Receive { payment, .. } => payment.authorization.valid_before,
+            PaymentContext::Permit2 { payment, .. } => {
+                UnixTimestamp::from_secs(payment.sig_deadline.min(payment.expiration))
+            }
+            PaymentContext::Permit2Witness { payment, .. } => payment.deadline,
+        }
+    }
+}
+
 sol!(
     #[allow(missing_docs)]
     #[allow(clippy::too_many_arguments)]
@@ -396,6 +690,15 @@ sol! {
     "abi/X402ExactPermit2Proxy.json"
 }
 
+sol! {
+    #[allow(missing_docs)]
+    #[allow(clippy::too_many_arguments)]
+    #[derive(Debug)]
+    #[sol(rpc)]
+    X402ReceiveForwarder,
+    "abi/X402ReceiveForwarder.json"
+}
+
 sol! {
     #[allow(missing_docs)]
     #[allow(clippy::too_many_arguments)]
@@ -405,19 +708,45 @@ sol! {
     "abi/Validator6492.json"
 }
 
+sol! {
+    /// ERC-1271 signature check of smart contract wallets.
+    interface IERC1271 {
+        function isValidSignature(bytes32 hash, bytes signature) external view returns (bytes4 magicValue);
+    }
+}
+
+sol! {
+    /// ERC-20 transfer, used to send refunds from the refund treasury.
+    interface IERC20Transfer {
+        function transfer(address to, uint256 amount) external returns (bool);
+    }
+}
+
+/// `isValidSignature` return value of a valid ERC-1271 signature.
+pub(crate) const ERC1271_MAGIC_VALUE: [u8; 4] = hex!("1626ba7e");
+
 /// Runs all preconditions needed for a successful payment:
 /// - Valid scheme, network, and receiver.
+/// - Token and transferred amount accepted by the asset policy.
 /// - Valid time window (validAfter/validBefore).
 /// - Correct EIP-712 domain construction.
 /// - Sufficient on-chain balance.
-/// - Sufficient value in payload.
+/// - Value in payload matching the required amount, see [`AmountMatching`].
+///
+/// The token reads (balance, Permit2 allowance, EIP-712 name and version) share one
+/// Multicall3 call, see [`fetch_token_state`]. The EIP-712 name and version come from
+/// `domains` while cached, see [`fetch_token_state_with_domain`].
 #[cfg_attr(feature = "telemetry", instrument(skip_all, err))]
+#[allow(clippy::too_many_arguments)]
 async fn assert_valid_payment<'a, P: Provider>(
     provider: &'a P,
     chain: &Eip155ChainReference,
+    contracts: &Eip155Contracts,
+    domains: Option<&Eip712DomainCache>,
     payload: &types::PaymentPayload,
     requirements: &types::PaymentRequirements,
-    allowed_spenders: Option<Vec<Address>>,
+    allowed_spenders: Option<Vec<Spender>>,
+    config: &Eip155ExactConfig,
 ) -> Result<PaymentContext<'a, P>, Eip155ExactError> {
     let chain_id: ChainId = chain.into();
     let payload_chain_id = ChainId::from_network_name(&payload.network)
@@ -430,69 +759,84 @@ async fn assert_valid_payment<'a, P: Provider>(
     if requirements_chain_id != chain_id {
         return Err(PaymentVerificationError::ChainIdMismatch.into());
     }
+    let pay_to = PayTo(requirements.pay_to);
+    let amount_matching = requirements
+        .extra
+        .as_ref()
+        .map(|extra| extra.amount_matching)
+        .unwrap_or_default();
     if let Some(permit2_auth) = payload.payload.permit2_authorization.as_ref() {
-        let proxy_address = x402_exact_permit2_proxy_address();
-        assert_proxy_codehash_allowed(provider, &proxy_address).await?;
-
         // Static checks to align with Coinbase's Permit2 witness proxy flow.
         if permit2_auth.permitted.token != requirements.asset {
-            return Err(PaymentVerificationError::AssetMismatch.into());
+            return Err(PaymentVerificationError::asset_mismatch(
+                requirements.asset,
+                permit2_auth.permitted.token,
+            )
+            .into());
         }
-        if permit2_auth.spender != proxy_address {
+        // Any configured proxy is accepted; the payment settles through the one it names.
+        let spender = Spender(permit2_auth.spender);
+        if !config.accepts_permit2_proxy(&spender.address()) {
             return Err(PaymentVerificationError::InvalidFormat(
                 "permit2Authorization.spender must be the x402 Permit2 proxy".to_string(),
             )
             .into());
         }
-        if permit2_auth.witness.to != requirements.pay_to {
-            return Err(PaymentVerificationError::RecipientMismatch.into());
+        let proxy_address = spender.address();
+        if PayTo(permit2_auth.witness.to) != pay_to {
+            return Err(PaymentVerificationError::recipient_mismatch(
+                pay_to,
+                permit2_auth.witness.to,
+            )
+            .into());
         }
 
         let amount_required = requirements.max_amount_required;
-        if permit2_auth.permitted.amount != amount_required {
-            return Err(PaymentVerificationError::InvalidPaymentAmount.into());
-        }
+        let amount = permit2_auth.permitted.amount;
+        assert_amount_matching(&amount, &amount_required, amount_matching)?;
+        assert_asset_allowed(config.allowed_assets.as_ref(), &requirements.asset, amount)?;
+        assert_proxy_codehash_allowed(provider, &proxy_address, &config.permit2_proxy_codehashes)
+            .await?;
 
         assert_permit2_witness_time(
             permit2_auth.deadline,
             permit2_auth.witness.valid_after,
             requirements.max_timeout_seconds,
+            config.grace_buffer_seconds,
+            &config.clock,
         )?;
 
-        let erc20_contract = IEIP3009::new(permit2_auth.permitted.token, provider);
-        assert_enough_balance(&erc20_contract, &permit2_auth.from, amount_required).await?;
-
+        let payer = PayerAddress(permit2_auth.from);
         // Permit2 SignatureTransfer still requires ERC20 approval for Permit2.
-        let allowance = erc20_contract
-            .allowance(permit2_auth.from, PERMIT2_ADDRESS)
-            .call()
-            .await
-            .map_err(|e| PaymentVerificationError::TransactionSimulation(e.to_string()))?;
-        if allowance < amount_required {
-            return Err(PaymentVerificationError::TransactionSimulation(
-                "Permit2 ERC20 allowance is insufficient".to_string(),
-            )
-            .into());
-        }
+        let token_state = fetch_token_state(
+            provider,
+            permit2_auth.permitted.token,
+            payer,
+            Some(contracts.permit2),
+            false,
+        )
+        .await?;
+        token_state.assert_enough_balance(amount)?;
+        token_state.assert_enough_allowance(amount)?;
 
         let signature = payload.payload.signature.clone().ok_or_else(|| {
             PaymentVerificationError::InvalidFormat("Missing signature".to_string())
         })?;
 
-        let domain = assert_permit2_witness_domain(chain);
+        let domain = assert_permit2_witness_domain(chain, contracts.permit2);
         let contract = X402ExactPermit2Proxy::new(proxy_address, provider);
         let payment = Permit2WitnessPayment {
-            from: permit2_auth.from,
-            spender: permit2_auth.spender,
+            from: payer,
+            spender,
             token: permit2_auth.permitted.token,
             amount: permit2_auth.permitted.amount,
             nonce: permit2_auth.nonce,
             deadline: permit2_auth.deadline,
-            pay_to: permit2_auth.witness.to,
+            pay_to,
             valid_after: permit2_auth.witness.valid_after,
             extra: permit2_auth.witness.extra.clone(),
             signature,
-            transfer_amount: amount_required,
+            transfer_amount: amount,
         };
         Ok(PaymentContext::Permit2Witness {
             contract,
@@ -500,7 +844,7 @@ async fn assert_valid_payment<'a, P: Provider>(
             domain,
         })
     } else if let Some(permit2) = payload.payload.permit2.as_ref() {
-        if !permit2_allowance_transfer_enabled() {
+        if !config.permit2_allowance_transfer_enabled() {
             return Err(PaymentVerificationError::InvalidFormat(
                 "Legacy permit2 payload is disabled; use payload.permit2Authorization witness flow"
                     .to_string(),
@@ -511,37 +855,58 @@ async fn assert_valid_payment<'a, P: Provider>(
         let details = &permit_single.details;
 
         if details.token != requirements.asset {
-            return Err(PaymentVerificationError::AssetMismatch.into());
+            return Err(PaymentVerificationError::asset_mismatch(
+                requirements.asset,
+                details.token,
+            )
+            .into());
         }
-        if let Some(spenders) = allowed_spenders.as_ref() {
-            if !spenders.iter().any(|s| *s == permit_single.spender) {
-                return Err(PaymentVerificationError::RecipientMismatch.into());
-            }
+        let spender = Spender(permit_single.spender);
+        if let Some(spenders) = allowed_spenders.as_ref()
+            && !spenders.contains(&spender)
+        {
+            return Err(PaymentVerificationError::recipient_mismatch(
+                join_spenders(spenders),
+                spender,
+            )
+            .into());
         }
 
         let sig_deadline = UnixTimestamp::from_secs(permit_single.sig_deadline);
         let expiration = UnixTimestamp::from_secs(details.expiration);
-        assert_permit2_time(sig_deadline, expiration)?;
+        assert_permit2_time(
+            sig_deadline,
+            expiration,
+            config.grace_buffer_seconds,
+            &config.clock,
+        )?;
 
         let amount_required = requirements.max_amount_required;
-        assert_enough_value(&details.amount, &amount_required)?;
+        assert_amount_matching(&details.amount, &amount_required, amount_matching)?;
+        assert_asset_allowed(
+            config.allowed_assets.as_ref(),
+            &details.token,
+            details.amount,
+        )?;
 
-        let erc20_contract = IEIP3009::new(details.token, provider);
-        assert_enough_balance(&erc20_contract, &permit2.owner, amount_required).await?;
+        let payer = PayerAddress(permit2.owner);
+        fetch_token_state(provider, details.token, payer, None, false)
+            .await?
+            .assert_enough_balance(details.amount)?;
 
-        let domain = assert_permit2_domain(chain);
-        let contract = IPermit2::new(PERMIT2_ADDRESS, provider);
+        let domain = assert_permit2_domain(chain, contracts.permit2);
+        let contract = IPermit2::new(contracts.permit2, provider);
         let payment = Permit2Payment {
-            owner: permit2.owner,
-            spender: permit_single.spender,
-            pay_to: requirements.pay_to,
+            owner: payer,
+            spender,
+            pay_to,
             token: details.token,
             amount: details.amount,
             expiration: details.expiration,
             nonce: details.nonce,
             sig_deadline: permit_single.sig_deadline,
             signature: permit2.signature.clone(),
-            transfer_amount: amount_required,
+            transfer_amount: details.amount,
         };
         Ok(PaymentContext::Permit2 {
             contract,
@@ -549,26 +914,51 @@ async fn assert_valid_payment<'a, P: Provider>(
             domain,
         })
     } else if let Some(authorization) = payload.payload.authorization.as_ref() {
-        if authorization.to != requirements.pay_to {
-            return Err(PaymentVerificationError::RecipientMismatch.into());
+        let receive_forwarder = config.receive_forwarder(&requirements.asset);
+        match receive_forwarder {
+            Some(forwarder) => assert_receive_recipient(authorization, forwarder, pay_to)?,
+            None if PayTo(authorization.to) != pay_to => {
+                return Err(
+                    PaymentVerificationError::recipient_mismatch(pay_to, authorization.to).into(),
+                );
+            }
+            None => {}
         }
         let valid_after = authorization.valid_after;
         let valid_before = authorization.valid_before;
-        assert_time(valid_after, valid_before)?;
+        assert_time(
+            valid_after,
+            valid_before,
+            config.grace_buffer_seconds,
+            &config.clock,
+        )?;
         let asset_address = requirements.asset;
         let contract = IEIP3009::new(asset_address, provider);
 
-        let domain = assert_domain(chain, &contract, &asset_address, &requirements.extra).await?;
-
         let amount_required = requirements.max_amount_required;
-        assert_enough_balance(&contract, &authorization.from, amount_required).await?;
-        assert_enough_value(&authorization.value, &amount_required)?;
+        assert_amount_matching(&authorization.value, &amount_required, amount_matching)?;
+        assert_asset_allowed(
+            config.allowed_assets.as_ref(),
+            &asset_address,
+            authorization.value,
+        )?;
+        let payer = PayerAddress(authorization.from);
+        let token_state = fetch_token_state_with_domain(
+            provider,
+            domains,
+            asset_address,
+            payer,
+            requirements.extra.is_some(),
+        )
+        .await?;
+        let domain = token_state.domain(chain, &asset_address, &requirements.extra)?;
+        token_state.assert_enough_balance(authorization.value)?;
 
         let signature = payload.payload.signature.clone().ok_or_else(|| {
             PaymentVerificationError::InvalidFormat("Missing signature".to_string())
         })?;
         let payment = ExactEvmPayment {
-            from: authorization.from,
+            from: payer,
             to: authorization.to,
             value: authorization.value,
             valid_after: authorization.valid_after,
@@ -577,11 +967,22 @@ async fn assert_valid_payment<'a, P: Provider>(
             signature,
         };
 
-        Ok(PaymentContext::Eip3009 {
-            contract,
-            payment,
-            domain,
-        })
+        match receive_forwarder {
+            Some(forwarder) => Ok(PaymentContext::Eip3009Receive {
+                contract: X402ReceiveForwarder::new(forwarder, provider),
+                payment: ExactEvmReceivePayment {
+                    authorization: payment,
+                    token: asset_address,
+                    pay_to,
+                },
+                domain,
+            }),
+            None => Ok(PaymentContext::Eip3009 {
+                contract,
+                payment,
+                domain,
+            }),
+        }
     } else {
         Err(PaymentVerificationError::InvalidFormat(
             "Missing authorization or permit2 payload".to_string(),
@@ -590,20 +991,73 @@ async fn assert_valid_payment<'a, P: Provider>(
     }
 }
 
+/// Checks that a `ReceiveWithAuthorization` is addressed to the configured forwarder
+/// and that its nonce binds the expected recipient.
+pub fn assert_receive_recipient(
+    authorization: &ExactEvmPayloadAuthorization,
+    forwarder: Address,
+    pay_to: PayTo,
+) -> Result<(), PaymentVerificationError> {
+    if authorization.to != forwarder {
+        return Err(PaymentVerificationError::recipient_mismatch(
+            forwarder,
+            authorization.to,
+        ));
+    }
+    let bound_pay_to = receive_nonce_pay_to(&authorization.nonce);
+    if PayTo(bound_pay_to) != pay_to {
+        return Err(PaymentVerificationError::recipient_mismatch(
+            pay_to,
+            bound_pay_to,
+        ));
+    }
+    Ok(())
+}
+
+/// The accepted Permit2 spenders as a comma-separated list, the `expected` recipient
+/// of a [`PaymentVerificationError::RecipientMismatch`] on the spender.
+pub fn join_spenders<T: std::fmt::Display>(spenders: impl IntoIterator<Item = T>) -> String {
+    spenders
+        .into_iter()
+        .map(|spender| spender.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Validates that the current time is within the `validAfter` and `validBefore` bounds.
 ///
-/// Adds a 6-second grace buffer when checking expiration to account for latency.
+/// Adds a grace buffer of `grace_seconds` when checking expiration to account for latency.
 #[cfg_attr(feature = "telemetry", instrument(skip_all, err))]
 pub fn assert_time(
     valid_after: UnixTimestamp,
     valid_before: UnixTimestamp,
+    grace_seconds: u64,
+    clock: &dyn Clock,
 ) -> Result<(), PaymentVerificationError> {
-    let now = UnixTimestamp::now();
-    if valid_before < now + 6 {
-        return Err(PaymentVerificationError::Expired);
+    let now = clock.now();
+    if valid_before < now + grace_seconds {
+        return Err(PaymentVerificationError::Expired { valid_before, now });
     }
     if valid_after > now {
-        return Err(PaymentVerificationError::Early);
+        return Err(PaymentVerificationError::Early { valid_after, now });
+    }
+    Ok(())
+}
+
+/// Checks that a payment stays valid for at least `min_remaining_secs`, long enough
+/// for its settlement transaction to be mined before the authorization expires.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err))]
+pub fn assert_remaining_validity(
+    expires_at: UnixTimestamp,
+    min_remaining_secs: u64,
+    clock: &dyn Clock,
+) -> Result<(), PaymentVerificationError> {
+    let now = clock.now();
+    if expires_at < now + min_remaining_secs {
+        return Err(PaymentVerificationError::Expired {
+            valid_before: expires_at,
+            now,
+        });
     }
     Ok(())
 }
@@ -612,13 +1066,21 @@ pub fn assert_time(
 pub fn assert_permit2_time(
     sig_deadline: UnixTimestamp,
     expiration: UnixTimestamp,
+    grace_seconds: u64,
+    clock: &dyn Clock,
 ) -> Result<(), PaymentVerificationError> {
-    let now = UnixTimestamp::now();
-    if sig_deadline < now + 6 {
-        return Err(PaymentVerificationError::Expired);
+    let now = clock.now();
+    if sig_deadline < now + grace_seconds {
+        return Err(PaymentVerificationError::Expired {
+            valid_before: sig_deadline,
+            now,
+        });
     }
-    if expiration < now + 6 {
-        return Err(PaymentVerificationError::Expired);
+    if expiration < now + grace_seconds {
+        return Err(PaymentVerificationError::Expired {
+            valid_before: expiration,
+            now,
+        });
     }
     Ok(())
 }
@@ -628,16 +1090,21 @@ pub fn assert_permit2_witness_time(
     deadline: UnixTimestamp,
     valid_after: UnixTimestamp,
     max_timeout_seconds: u64,
+    grace_seconds: u64,
+    clock: &dyn Clock,
 ) -> Result<(), PaymentVerificationError> {
-    let now = UnixTimestamp::now();
-    if deadline < now + 6 {
-        return Err(PaymentVerificationError::Expired);
+    let now = clock.now();
+    if deadline < now + grace_seconds {
+        return Err(PaymentVerificationError::Expired {
+            valid_before: deadline,
+            now,
+        });
     }
     if valid_after > now {
-        return Err(PaymentVerificationError::Early);
+        return Err(PaymentVerificationError::Early { valid_after, now });
     }
     if max_timeout_seconds > 0 {
-        let max_allowed_deadline = now + max_timeout_seconds + 6;
+        let max_allowed_deadline = now + max_timeout_seconds + grace_seconds;
         if deadline > max_allowed_deadline {
             return Err(PaymentVerificationError::InvalidFormat(
                 "Permit2 deadline exceeds maxTimeoutSeconds".to_string(),
@@ -647,25 +1114,20 @@ pub fn assert_permit2_witness_time(
     Ok(())
 }
 
-pub fn assert_permit2_witness_domain(chain: &Eip155ChainReference) -> Eip712Domain {
+pub fn assert_permit2_witness_domain(
+    chain: &Eip155ChainReference,
+    permit2: Address,
+) -> Eip712Domain {
     // Coinbase-style Permit2 typed data domain: name + chainId + verifyingContract (no version).
-    eip712_domain! {
-        name: "Permit2",
-        chain_id: chain.inner(),
-        verifying_contract: PERMIT2_ADDRESS,
-    }
+    digest::permit2_witness_domain_at(chain, permit2)
 }
 
-pub fn assert_permit2_domain(chain: &Eip155ChainReference) -> Eip712Domain {
-    eip712_domain! {
-        name: "Permit2",
-        version: "1",
-        chain_id: chain.inner(),
-        verifying_contract: PERMIT2_ADDRESS,
-    }
+pub fn assert_permit2_domain(chain: &Eip155ChainReference, permit2: Address) -> Eip712Domain {
+    digest::permit2_allowance_domain(chain, permit2)
 }
 
-fn permit2_amount(amount: U256) -> Result<U160, PaymentVerificationError> {
+/// Converts an amount to the `uint160` Permit2 allowances are kept in.
+pub fn permit2_amount(amount: U256) -> Result<U160, PaymentVerificationError> {
     if amount > U256::from(U160::MAX) {
         return Err(PaymentVerificationError::InvalidFormat(
             "Permit2 amount exceeds uint160".to_string(),
@@ -686,7 +1148,7 @@ fn permit2_u48(value: u64, field: &str) -> Result<U48, PaymentVerificationError>
     Ok(U48::from(value))
 }
 
-fn build_permit2_single_call(
+pub(crate) fn build_permit2_single_call(
     payment: &Permit2Payment,
 ) -> Result<IPermit2::PermitSingle, PaymentVerificationError> {
     let details = IPermit2::PermitDetails {
@@ -697,7 +1159,7 @@ fn build_permit2_single_call(
     };
     Ok(IPermit2::PermitSingle {
         details,
-        spender: payment.spender,
+        spender: payment.spender.address(),
         sigDeadline: U256::from(payment.sig_deadline),
     })
 }
@@ -717,13 +1179,16 @@ fn build_permit2_proxy_permit(
 
 fn build_permit2_proxy_witness(payment: &Permit2WitnessPayment) -> X402ExactPermit2Proxy::Witness {
     X402ExactPermit2Proxy::Witness {
-        to: payment.pay_to,
+        to: payment.pay_to.address(),
         validAfter: U256::from(payment.valid_after.as_secs()),
         extra: payment.extra.clone(),
     }
 }
 
 /// Constructs the correct EIP-712 domain for signature verification.
+///
+/// Uses the domain described by `extra`, including a nonstandard shape, and reads
+/// the standard domain's name and version from the token otherwise.
 #[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
     network = %chain.as_chain_id(),
     asset = %asset_address
@@ -734,47 +1199,32 @@ pub async fn assert_domain<P: Provider>(
     asset_address: &Address,
     extra: &Option<PaymentRequirementsExtra>,
 ) -> Result<Eip712Domain, Eip155ExactError> {
-    let name = extra.as_ref().map(|extra| extra.name.clone());
-    let name = if let Some(name) = name {
-        name
-    } else {
-        let name_b = token_contract.name();
-        let name_fut = name_b.call().into_future();
-        #[cfg(feature = "telemetry")]
-        let name = name_fut
-            .instrument(tracing::info_span!(
-                "fetch_eip712_name",
-                otel.kind = "client",
-            ))
-            .await?;
-        #[cfg(not(feature = "telemetry"))]
-        let name = name_fut.await?;
-        name
-    };
-    let version = extra.as_ref().map(|extra| extra.version.clone());
-    let version = if let Some(version) = version {
-        version
-    } else {
-        let version_b = token_contract.version();
-        let version_fut = version_b.call().into_future();
-        #[cfg(feature = "telemetry")]
-        let version = version_fut
-            .instrument(tracing::info_span!(
-                "fetch_eip712_version",
-                otel.kind = "client",
-            ))
-            .await?;
-        #[cfg(not(feature = "telemetry"))]
-        let version = version_fut.await?;
-        version
-    };
-    let domain = eip712_domain! {
-        name: name,
-        version: version,
-        chain_id: chain.inner(),
-        verifying_contract: *asset_address,
-    };
-    Ok(domain)
+    if let Some(extra) = extra {
+        return Ok(extra.token_domain(chain, *asset_address));
+    }
+    let name_b = token_contract.name();
+    let name_fut = name_b.call().into_future();
+    #[cfg(feature = "telemetry")]
+    let name = name_fut
+        .instrument(tracing::info_span!(
+            "fetch_eip712_name",
+            otel.kind = "client",
+        ))
+        .await?;
+    #[cfg(not(feature = "telemetry"))]
+    let name = name_fut.await?;
+    let version_b = token_contract.version();
+    let version_fut = version_b.call().into_future();
+    #[cfg(feature = "telemetry")]
+    let version = version_fut
+        .instrument(tracing::info_span!(
+            "fetch_eip712_version",
+            otel.kind = "client",
+        ))
+        .await?;
+    #[cfg(not(feature = "telemetry"))]
+    let version = version_fut.await?;
+    Ok(digest::token_domain(chain, *asset_address, &name, &version))
 }
 
 /// Checks if the payer has enough on-chain token balance to meet the `maxAmountRequired`.
@@ -787,10 +1237,10 @@ pub async fn assert_domain<P: Provider>(
 )))]
 pub async fn assert_enough_balance<P: Provider>(
     ieip3009_token_contract: &IEIP3009::IEIP3009Instance<P>,
-    sender: &Address,
+    sender: PayerAddress,
     max_amount_required: U256,
 ) -> Result<(), Eip155ExactError> {
-    let balance_of = ieip3009_token_contract.balanceOf(*sender);
+    let balance_of = ieip3009_token_contract.balanceOf(sender.address());
     let balance_fut = balance_of.call().into_future();
     #[cfg(feature = "telemetry")]
     let balance = balance_fut
@@ -804,11 +1254,207 @@ pub async fn assert_enough_balance<P: Provider>(
     #[cfg(not(feature = "telemetry"))]
     let balance = balance_fut.await?;
 
-    if balance < max_amount_required {
-        Err(PaymentVerificationError::InsufficientFunds.into())
+    if balance < max_amount_required {
+        Err(PaymentVerificationError::insufficient_funds(max_amount_required, balance).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Token state read before a payment is simulated.
+///
+/// Read by [`fetch_token_state`] in a single Multicall3 `aggregate3` call instead
+/// of one `eth_call` per value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenState {
+    pub balance: U256,
+    /// `allowance(payer, spender)`, if a spender was given.
+    pub allowance: Option<U256>,
+    /// EIP-712 domain name and version, unless the requirements already carry them.
+    pub eip712: Option<(String, String)>,
+}
+
+impl TokenState {
+    /// Checks if the payer has enough on-chain token balance to meet `max_amount_required`.
+    pub fn assert_enough_balance(&self, max_amount_required: U256) -> Result<(), Eip155ExactError> {
+        if self.balance < max_amount_required {
+            Err(
+                PaymentVerificationError::insufficient_funds(max_amount_required, self.balance)
+                    .into(),
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks that the payer approved Permit2 for at least `amount_required`.
+    pub fn assert_enough_allowance(&self, amount_required: U256) -> Result<(), Eip155ExactError> {
+        if self.allowance.unwrap_or_default() < amount_required {
+            Err(PaymentVerificationError::TransactionSimulation(
+                "Permit2 ERC20 allowance is insufficient".to_string(),
+            )
+            .into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Constructs the token's EIP-712 domain, preferring the one described by `extra`.
+    pub fn domain(
+        &self,
+        chain: &Eip155ChainReference,
+        asset_address: &Address,
+        extra: &Option<PaymentRequirementsExtra>,
+    ) -> Result<Eip712Domain, Eip155ExactError> {
+        let (name, version) = match (extra, &self.eip712) {
+            (Some(extra), _) => return Ok(extra.token_domain(chain, *asset_address)),
+            (None, Some((name, version))) => (name.as_str(), version.as_str()),
+            (None, None) => {
+                return Err(Eip155ExactError::ContractCall(
+                    "EIP-712 name and version were not fetched".to_string(),
+                ));
+            }
+        };
+        Ok(digest::token_domain(chain, *asset_address, name, version))
+    }
+}
+
+/// Reads the payer's balance, the optional allowance to `spender` and, if
+/// `fetch_eip712` is set, the token's EIP-712 name and version in one
+/// Multicall3 `aggregate3` call.
+///
+/// A failed `allowance` read is reported like an insufficient allowance, as a
+/// simulation failure; the other reads fail with [`Eip155ExactError::ContractCall`].
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
+    token_contract = %token,
+    payer = %payer
+)))]
+pub async fn fetch_token_state<P: Provider>(
+    provider: &P,
+    token: Address,
+    payer: PayerAddress,
+    spender: Option<Address>,
+    fetch_eip712: bool,
+) -> Result<TokenState, Eip155ExactError> {
+    let call = |call_data: Vec<u8>| IMulticall3::Call3 {
+        allowFailure: true,
+        target: token,
+        callData: call_data.into(),
+    };
+    let mut calls = vec![call(
+        IEIP3009::balanceOfCall {
+            account: payer.address(),
+        }
+        .abi_encode(),
+    )];
+    if let Some(spender) = spender {
+        calls.push(call(
+            IEIP3009::allowanceCall {
+                owner: payer.address(),
+                spender,
+            }
+            .abi_encode(),
+        ));
+    }
+    if fetch_eip712 {
+        calls.push(call(IEIP3009::nameCall {}.abi_encode()));
+        calls.push(call(IEIP3009::versionCall {}.abi_encode()));
+    }
+    let expected = calls.len();
+    let aggregate = IMulticall3::aggregate3Call { calls };
+    let request = TransactionRequest::default()
+        .with_to(MULTICALL3_ADDRESS)
+        .with_input(aggregate.abi_encode());
+    let output_fut = provider.call(request).into_future();
+    #[cfg(feature = "telemetry")]
+    let output = output_fut
+        .instrument(tracing::info_span!(
+            "fetch_token_state",
+            token_contract = %token,
+            sender = %payer,
+            otel.kind = "client"
+        ))
+        .await?;
+    #[cfg(not(feature = "telemetry"))]
+    let output = output_fut.await?;
+    let results = IMulticall3::aggregate3Call::abi_decode_returns(&output)
+        .map_err(|e| Eip155ExactError::ContractCall(e.to_string()))?;
+    if results.len() != expected {
+        return Err(Eip155ExactError::ContractCall(
+            "unexpected Multicall3 result count".to_string(),
+        ));
+    }
+    let mut results = results.into_iter();
+    let mut next = |function: &str| {
+        let result = results.next().expect("result count checked");
+        if result.success {
+            Ok(result.returnData)
+        } else {
+            Err(format!("{function} reverted: {}", result.returnData))
+        }
+    };
+
+    let balance = next("balanceOf")
+        .and_then(|data| {
+            IEIP3009::balanceOfCall::abi_decode_returns(&data).map_err(|e| e.to_string())
+        })
+        .map_err(Eip155ExactError::ContractCall)?;
+    let allowance = match spender {
+        Some(_) => Some(
+            next("allowance")
+                .and_then(|data| {
+                    IEIP3009::allowanceCall::abi_decode_returns(&data).map_err(|e| e.to_string())
+                })
+                .map_err(PaymentVerificationError::TransactionSimulation)?,
+        ),
+        None => None,
+    };
+    let eip712 = if fetch_eip712 {
+        let name = next("name")
+            .and_then(|data| {
+                IEIP3009::nameCall::abi_decode_returns(&data).map_err(|e| e.to_string())
+            })
+            .map_err(Eip155ExactError::ContractCall)?;
+        let version = next("version")
+            .and_then(|data| {
+                IEIP3009::versionCall::abi_decode_returns(&data).map_err(|e| e.to_string())
+            })
+            .map_err(Eip155ExactError::ContractCall)?;
+        Some((name, version))
     } else {
-        Ok(())
+        None
+    };
+    Ok(TokenState {
+        balance,
+        allowance,
+        eip712,
+    })
+}
+
+/// [`fetch_token_state`] for an EIP-3009 payment: reads the token's EIP-712 name and
+/// version only if the requirements lack them (`has_extra` unset) and `domains` has
+/// no fresh entry for the token, and caches what it read.
+pub async fn fetch_token_state_with_domain<P: Provider>(
+    provider: &P,
+    domains: Option<&Eip712DomainCache>,
+    token: Address,
+    payer: PayerAddress,
+    has_extra: bool,
+) -> Result<TokenState, Eip155ExactError> {
+    let cached = match domains {
+        Some(domains) if !has_extra => domains.get(token),
+        _ => None,
+    };
+    let fetch_eip712 = !has_extra && cached.is_none();
+    let mut state = fetch_token_state(provider, token, payer, None, fetch_eip712).await?;
+    match (cached, &state.eip712, domains) {
+        (Some(cached), _, _) => state.eip712 = Some(cached),
+        (None, Some((name, version)), Some(domains)) => {
+            domains.insert(token, name.clone(), version.clone());
+        }
+        _ => {}
     }
+    Ok(state)
 }
 
 /// Verifies that the declared `value` in the payload is sufficient for the required amount.
@@ -822,10 +1468,28 @@ pub fn assert_enough_value(
     sent: &U256,
     max_amount_required: &U256,
 ) -> Result<(), PaymentVerificationError> {
-    if sent != max_amount_required {
-        Err(PaymentVerificationError::InvalidPaymentAmount)
-    } else {
+    assert_amount_matching(sent, max_amount_required, AmountMatching::Exact)
+}
+
+/// Checks the signed amount against the required one, as the requirements'
+/// [`AmountMatching`] asks.
+pub fn assert_amount_matching(
+    sent: &U256,
+    amount_required: &U256,
+    amount_matching: AmountMatching,
+) -> Result<(), PaymentVerificationError> {
+    let matches = match amount_matching {
+        AmountMatching::Exact => sent == amount_required,
+        AmountMatching::AtLeast => sent >= amount_required,
+        AmountMatching::AtMost => !sent.is_zero() && sent <= amount_required,
+    };
+    if matches {
         Ok(())
+    } else {
+        Err(PaymentVerificationError::invalid_amount(
+            amount_required,
+            sent,
+        ))
     }
 }
 
@@ -863,28 +1527,36 @@ impl SignedMessage {
         payment: &ExactEvmPayment,
         domain: &Eip712Domain,
     ) -> Result<Self, StructuredSignatureFormatError> {
-        let transfer_with_authorization = TransferWithAuthorization {
-            from: payment.from,
-            to: payment.to,
-            value: payment.value,
-            validAfter: U256::from(payment.valid_after.as_secs()),
-            validBefore: U256::from(payment.valid_before.as_secs()),
-            nonce: payment.nonce,
-        };
-        let eip712_hash = transfer_with_authorization.eip712_signing_hash(domain);
+        let eip712_hash =
+            digest::transfer_with_authorization_hash(&payment.authorization(), domain);
+        Self::with_hash(payment, eip712_hash)
+    }
+
+    /// Like [`SignedMessage::extract`], for an ERC-3009 `ReceiveWithAuthorization`.
+    pub fn extract_receive(
+        payment: &ExactEvmPayment,
+        domain: &Eip712Domain,
+    ) -> Result<Self, StructuredSignatureFormatError> {
+        let eip712_hash = digest::receive_with_authorization_hash(&payment.authorization(), domain);
+        Self::with_hash(payment, eip712_hash)
+    }
+
+    fn with_hash(
+        payment: &ExactEvmPayment,
+        eip712_hash: B256,
+    ) -> Result<Self, StructuredSignatureFormatError> {
         let structured_signature: StructuredSignature = StructuredSignature::try_from_bytes(
             payment.signature.clone(),
-            payment.from,
+            payment.from.address(),
             &eip712_hash,
         )?;
         let signed_message = Self {
-            address: payment.from,
+            address: payment.from.address(),
             hash: eip712_hash,
             signature: structured_signature,
         };
         Ok(signed_message)
     }
-
 }
 
 /// A structured representation of an Ethereum signature.
@@ -895,8 +1567,11 @@ impl SignedMessage {
 ///   They include deployment metadata (factory + calldata) plus the inner
 ///   signature that the wallet contract will validate after deployment.
 /// - **EIP-1271 signatures**: plain contract (or EOA-style) signatures.
+///
+/// Payload signatures are attacker-controlled: classification never panics, whatever
+/// the bytes, and only fails on a malformed EIP-6492 wrapper.
 #[derive(Debug, Clone)]
-enum StructuredSignature {
+pub enum StructuredSignature {
     /// An EIP-6492 wrapped signature.
     EIP6492 {
         /// Factory contract that can deploy the wallet deterministically
@@ -919,7 +1594,7 @@ enum StructuredSignature {
 ///
 /// Any signature ending with this constant is treated as a 6492-wrapped
 /// signature; the preceding bytes are ABI-decoded as `(address factory, bytes factoryCalldata, bytes innerSig)`.
-const EIP6492_MAGIC_SUFFIX: [u8; 32] =
+pub const EIP6492_MAGIC_SUFFIX: [u8; 32] =
     hex!("6492649264926492649264926492649264926492649264926492649264926492");
 
 sol! {
@@ -941,6 +1616,11 @@ pub enum StructuredSignatureFormatError {
 }
 
 impl StructuredSignature {
+    /// Classifies `bytes` signed by `expected_signer` over `prehash`.
+    ///
+    /// EIP-6492 wrappers are decoded first. Otherwise 65-byte and 64-byte (ERC-2098)
+    /// signatures recovering to `expected_signer` are EOA signatures, with `s`
+    /// normalized, and anything else is left to EIP-1271 validation as is.
     pub fn try_from_bytes(
         bytes: Bytes,
         expected_signer: Address,
@@ -1032,7 +1712,7 @@ impl<'a, P: Provider> TransferWithAuthorization0Call<&'a P> {
         payment: &ExactEvmPayment,
         signature: Bytes,
     ) -> Self {
-        let from = payment.from;
+        let from = payment.from.address();
         let to = payment.to;
         let value = payment.value;
         let valid_after = U256::from(payment.valid_after.as_secs());
@@ -1079,7 +1759,7 @@ impl<'a, P: Provider> TransferWithAuthorization1Call<&'a P> {
         payment: &ExactEvmPayment,
         signature: Signature,
     ) -> Self {
-        let from = payment.from;
+        let from = payment.from.address();
         let to = payment.to;
         let value = payment.value;
         let valid_after = U256::from(payment.valid_after.as_secs());
@@ -1143,7 +1823,7 @@ pub struct TransferWithAuthorizationCall<P, TCall, TSignature> {
 /// Uses `eth_getCode` against this provider. This is useful after a counterfactual
 /// deployment to confirm visibility on the sending RPC before submitting a
 /// follow-up transaction.
-async fn is_contract_deployed<P: Provider>(
+pub(crate) async fn is_contract_deployed<P: Provider>(
     provider: &P,
     address: &Address,
 ) -> Result<bool, TransportError> {
@@ -1160,13 +1840,92 @@ async fn is_contract_deployed<P: Provider>(
     Ok(!bytes.is_empty())
 }
 
+/// Validates an EIP-6492 signature on chains where the EIP-6492 validator is not
+/// deployed, then simulates the payment call `then` that relies on it, if any.
+///
+/// Follows the ERC-6492 procedure for off-chain verifiers: the wallet is deployed
+/// through its factory (which fails harmlessly if it already exists), then asked
+/// whether `inner` is a valid signature of `hash`. The calls share one
+/// Multicall3 `eth_call`, so the payment sees the deployed wallet while nothing is
+/// deployed on chain.
+pub(crate) async fn verify_6492_without_validator<P: Provider>(
+    provider: &P,
+    payer: Address,
+    hash: B256,
+    factory: Address,
+    factory_calldata: Bytes,
+    inner: Bytes,
+    then: Option<(Address, Bytes)>,
+) -> Result<(), Eip155ExactError> {
+    let is_valid_signature = IERC1271::isValidSignatureCall {
+        hash,
+        signature: inner,
+    };
+    let mut calls = vec![
+        IMulticall3::Call3 {
+            allowFailure: true,
+            target: factory,
+            callData: factory_calldata,
+        },
+        IMulticall3::Call3 {
+            allowFailure: true,
+            target: payer,
+            callData: is_valid_signature.abi_encode().into(),
+        },
+    ];
+    let expected_results = if let Some((target, call_data)) = then {
+        calls.push(IMulticall3::Call3 {
+            allowFailure: true,
+            target,
+            callData: call_data,
+        });
+        3
+    } else {
+        2
+    };
+    let aggregate = IMulticall3::aggregate3Call { calls };
+    let call = TransactionRequest::default()
+        .with_to(MULTICALL3_ADDRESS)
+        .with_input(aggregate.abi_encode());
+    let output = provider.call(call).await?;
+    let results = IMulticall3::aggregate3Call::abi_decode_returns(&output)
+        .map_err(|e| Eip155ExactError::ContractCall(e.to_string()))?;
+    if results.len() != expected_results {
+        return Err(Eip155ExactError::ContractCall(
+            "unexpected Multicall3 result count".to_string(),
+        ));
+    }
+    let signature_result = &results[1];
+    let magic_value = signature_result
+        .success
+        .then(|| IERC1271::isValidSignatureCall::abi_decode_returns(&signature_result.returnData))
+        .and_then(Result::ok);
+    if magic_value.map(|value| value.0) != Some(ERC1271_MAGIC_VALUE) {
+        return Err(PaymentVerificationError::InvalidSignature(
+            "Wallet reported signature to be invalid".to_string(),
+        )
+        .into());
+    }
+    if let Some(then_result) = results.get(2)
+        && !then_result.success
+    {
+        return Err(PaymentVerificationError::TransactionSimulation(format!(
+            "execution reverted: {}",
+            then_result.returnData
+        ))
+        .into());
+    }
+    Ok(())
+}
+
 async fn assert_proxy_codehash_allowed<P: Provider>(
     provider: &P,
     address: &Address,
+    allowlist: &[B256],
 ) -> Result<(), Eip155ExactError> {
-    let Some(allowlist) = parse_proxy_codehash_allowlist()? else {
+    if allowlist.is_empty() {
         return Ok(());
-    };
+    }
     let code = provider
         .get_code_at(*address)
         .into_future()
@@ -1179,7 +1938,7 @@ async fn assert_proxy_codehash_allowed<P: Provider>(
         .into());
     }
     let codehash = keccak256(code);
-    if !allowlist.iter().any(|v| *v == codehash) {
+    if !allowlist.contains(&codehash) {
         return Err(PaymentVerificationError::InvalidFormat(
             "x402 proxy codehash is not in allowlist".to_string(),
         )
@@ -1193,12 +1952,35 @@ pub async fn verify_payment<P: Provider>(
     contract: &IEIP3009::IEIP3009Instance<&P>,
     payment: &ExactEvmPayment,
     eip712_domain: &Eip712Domain,
-) -> Result<Address, Eip155ExactError> {
+    contracts: &Eip155Contracts,
+) -> Result<PayerAddress, Eip155ExactError> {
     let signed_message = SignedMessage::extract(payment, eip712_domain)?;
 
     let payer = signed_message.address;
     let hash = signed_message.hash;
     match signed_message.signature {
+        StructuredSignature::EIP6492 {
+            factory,
+            factory_calldata,
+            inner,
+            original: _,
+        } if !is_contract_deployed(provider, &contracts.eip6492_validator).await? => {
+            let transfer_call =
+                TransferWithAuthorization0Call::new(contract, payment, inner.clone()).0;
+            verify_6492_without_validator(
+                provider,
+                payer,
+                hash,
+                factory,
+                factory_calldata,
+                inner,
+                Some((
+                    transfer_call.tx.target(),
+                    transfer_call.tx.calldata().clone(),
+                )),
+            )
+            .await?;
+        }
         StructuredSignature::EIP6492 {
             factory: _,
             factory_calldata: _,
@@ -1206,7 +1988,7 @@ pub async fn verify_payment<P: Provider>(
             original,
         } => {
             // Prepare the call to validate EIP-6492 signature
-            let validator6492 = Validator6492::new(VALIDATOR_ADDRESS, &provider);
+            let validator6492 = Validator6492::new(contracts.eip6492_validator, &provider);
             let is_valid_signature_call =
                 validator6492.isValidSigWithSideEffects(payer, hash, original);
             // Prepare the call to simulate transfer the funds
@@ -1291,7 +2073,100 @@ pub async fn verify_payment<P: Provider>(
         }
     }
 
-    Ok(payer)
+    Ok(PayerAddress(payer))
+}
+
+/// Builds the forwarder call settling a `ReceiveWithAuthorization` payment.
+fn forward_with_authorization<'a, P: Provider>(
+    forwarder: &'a X402ReceiveForwarder::X402ReceiveForwarderInstance<P>,
+    payment: &ExactEvmReceivePayment,
+    signature: Bytes,
+) -> SolCallBuilder<&'a P, X402ReceiveForwarder::forwardWithAuthorizationCall> {
+    let authorization = &payment.authorization;
+    forwarder.forwardWithAuthorization(
+        payment.token,
+        authorization.from.address(),
+        payment.pay_to.address(),
+        authorization.value,
+        U256::from(authorization.valid_after.as_secs()),
+        U256::from(authorization.valid_before.as_secs()),
+        authorization.nonce,
+        signature,
+    )
+}
+
+/// Verifies a `ReceiveWithAuthorization` payment by simulating the forwarder call.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
+    from = %payment.authorization.from,
+    pay_to = %payment.pay_to,
+    forwarder = %forwarder.address(),
+)))]
+pub async fn verify_payment_receive<P: Provider>(
+    provider: &P,
+    forwarder: &X402ReceiveForwarder::X402ReceiveForwarderInstance<&P>,
+    payment: &ExactEvmReceivePayment,
+    eip712_domain: &Eip712Domain,
+    contracts: &Eip155Contracts,
+) -> Result<PayerAddress, Eip155ExactError> {
+    let signed_message = SignedMessage::extract_receive(&payment.authorization, eip712_domain)?;
+    let payer = signed_message.address;
+    let signature = match signed_message.signature {
+        StructuredSignature::EIP6492 {
+            factory,
+            factory_calldata,
+            inner,
+            original: _,
+        } if !is_contract_deployed(provider, &contracts.eip6492_validator).await? => {
+            let forward_call = forward_with_authorization(forwarder, payment, inner.clone());
+            verify_6492_without_validator(
+                provider,
+                payer,
+                signed_message.hash,
+                factory,
+                factory_calldata,
+                inner,
+                Some((forward_call.target(), forward_call.calldata().clone())),
+            )
+            .await?;
+            return Ok(PayerAddress(payer));
+        }
+        StructuredSignature::EIP6492 {
+            factory: _,
+            factory_calldata: _,
+            inner,
+            original,
+        } => {
+            // Validate the signature (deploying the wallet in simulation) and forward in one go
+            let validator6492 = Validator6492::new(contracts.eip6492_validator, &provider);
+            let is_valid_signature_call =
+                validator6492.isValidSigWithSideEffects(payer, signed_message.hash, original);
+            let forward_call = forward_with_authorization(forwarder, payment, inner);
+            let (is_valid_signature_result, forward_result) = provider
+                .multicall()
+                .add(is_valid_signature_call)
+                .add(forward_call)
+                .aggregate3()
+                .await?;
+            let is_valid_signature_result = is_valid_signature_result
+                .map_err(|e| PaymentVerificationError::InvalidSignature(e.to_string()))?;
+            if !is_valid_signature_result {
+                return Err(PaymentVerificationError::InvalidSignature(
+                    "Chain reported signature to be invalid".to_string(),
+                )
+                .into());
+            }
+            forward_result
+                .map_err(|e| PaymentVerificationError::TransactionSimulation(e.to_string()))?;
+            return Ok(PayerAddress(payer));
+        }
+        StructuredSignature::EIP1271(signature) => signature,
+        StructuredSignature::EOA(signature) => Bytes::from(signature.as_bytes().to_vec()),
+    };
+    forward_with_authorization(forwarder, payment, signature)
+        .call()
+        .await
+        .map_err(|e| PaymentVerificationError::TransactionSimulation(e.to_string()))?;
+    Ok(PayerAddress(payer))
 }
 
 pub async fn verify_payment_permit2<P: Provider>(
@@ -1299,13 +2174,13 @@ pub async fn verify_payment_permit2<P: Provider>(
     contract: &IPermit2::IPermit2Instance<&P>,
     payment: &Permit2Payment,
     eip712_domain: &Eip712Domain,
-) -> Result<Address, Eip155ExactError> {
+) -> Result<PayerAddress, Eip155ExactError> {
     let _ = eip712_domain;
-    let payer = payment.owner;
+    let payer = payment.owner.address();
     let signature_bytes = payment.signature.clone();
     let permit_single = build_permit2_single_call(payment)?;
 
-    let permit_call = contract.permit(payment.owner, permit_single, signature_bytes);
+    let permit_call = contract.permit(payer, permit_single, signature_bytes);
 
     #[cfg(feature = "telemetry")]
     {
@@ -1331,7 +2206,7 @@ pub async fn verify_payment_permit2<P: Provider>(
 
     let erc20_contract = IEIP3009::new(payment.token, provider);
     let allowance = erc20_contract
-        .allowance(payment.owner, PERMIT2_ADDRESS)
+        .allowance(payer, *contract.address())
         .call()
         .await
         .map_err(|e| PaymentVerificationError::TransactionSimulation(e.to_string()))?;
@@ -1343,17 +2218,17 @@ pub async fn verify_payment_permit2<P: Provider>(
     }
 
     let token_transfer =
-        erc20_contract.transferFrom(payment.owner, payment.pay_to, payment.transfer_amount);
+        erc20_contract.transferFrom(payer, payment.pay_to.address(), payment.transfer_amount);
     let txr = TransactionRequest::default()
         .with_to(payment.token)
-        .with_from(PERMIT2_ADDRESS)
+        .with_from(*contract.address())
         .with_input(token_transfer.calldata().clone());
     provider
         .call(txr)
         .await
         .map_err(|e| PaymentVerificationError::TransactionSimulation(e.to_string()))?;
 
-    Ok(payer)
+    Ok(payment.owner)
 }
 
 pub async fn verify_payment_permit2_witness<P: Provider>(
@@ -1361,39 +2236,44 @@ pub async fn verify_payment_permit2_witness<P: Provider>(
     contract: &X402ExactPermit2Proxy::X402ExactPermit2ProxyInstance<&P>,
     payment: &Permit2WitnessPayment,
     eip712_domain: &Eip712Domain,
-) -> Result<Address, Eip155ExactError> {
-    let payer = payment.from;
+    contracts: &Eip155Contracts,
+) -> Result<PayerAddress, Eip155ExactError> {
+    let payer = payment.from.address();
 
     // Build EIP-712 prehash for EIP-6492 classification/validation.
-    let permit_witness_transfer_from = types::PermitWitnessTransferFrom {
-        permitted: types::TokenPermissions {
-            token: payment.token,
-            amount: payment.amount,
-        },
-        spender: payment.spender,
-        nonce: payment.nonce,
-        deadline: U256::from(payment.deadline.as_secs()),
-        witness: types::Witness {
-            to: payment.pay_to,
-            validAfter: U256::from(payment.valid_after.as_secs()),
-            extra: payment.extra.clone(),
-        },
-    };
-    let eip712_hash = permit_witness_transfer_from.eip712_signing_hash(eip712_domain);
+    let eip712_hash =
+        digest::permit_witness_transfer_from_hash(&payment.authorization(), eip712_domain);
 
-    let structured_signature: StructuredSignature = StructuredSignature::try_from_bytes(
-        payment.signature.clone(),
-        payer,
-        &eip712_hash,
-    )?;
+    let structured_signature: StructuredSignature =
+        StructuredSignature::try_from_bytes(payment.signature.clone(), payer, &eip712_hash)?;
 
     let permit = build_permit2_proxy_permit(payment);
     let witness = build_permit2_proxy_witness(payment);
 
     match structured_signature {
-        StructuredSignature::EIP6492 { inner, original, .. } => {
+        StructuredSignature::EIP6492 {
+            factory,
+            factory_calldata,
+            inner,
+            original: _,
+        } if !is_contract_deployed(provider, &contracts.eip6492_validator).await? => {
+            let settle_call = contract.settle(permit, payer, witness, inner.clone());
+            verify_6492_without_validator(
+                provider,
+                payer,
+                eip712_hash,
+                factory,
+                factory_calldata,
+                inner,
+                Some((settle_call.target(), settle_call.calldata().clone())),
+            )
+            .await?;
+        }
+        StructuredSignature::EIP6492 {
+            inner, original, ..
+        } => {
             // Validate wrapper (may deploy wallet), then simulate proxy settle with inner signature.
-            let validator6492 = Validator6492::new(VALIDATOR_ADDRESS, &provider);
+            let validator6492 = Validator6492::new(contracts.eip6492_validator, &provider);
             let is_valid_signature_call =
                 validator6492.isValidSigWithSideEffects(payer, eip712_hash, original);
             let settle_call = contract.settle(permit, payer, witness, inner);
@@ -1452,7 +2332,7 @@ pub async fn verify_payment_permit2_witness<P: Provider>(
         }
     }
 
-    Ok(payer)
+    Ok(payment.from)
 }
 
 pub async fn settle_payment<P, E>(
@@ -1460,13 +2340,14 @@ pub async fn settle_payment<P, E>(
     contract: &IEIP3009::IEIP3009Instance<&P::Inner>,
     payment: &ExactEvmPayment,
     eip712_domain: &Eip712Domain,
-) -> Result<TxHash, Eip155ExactError>
+    confirmations: u64,
+) -> Result<TransactionReceipt, Eip155ExactError>
 where
     P: Eip155MetaTransactionProvider<Error = E>,
     Eip155ExactError: From<E>,
 {
     let signed_message = SignedMessage::extract(payment, eip712_domain)?;
-    let payer = payment.from;
+    let payer = payment.from.address();
     let receipt = match signed_message.signature {
         StructuredSignature::EIP6492 {
             factory,
@@ -1484,7 +2365,7 @@ where
                     MetaTransaction {
                         to: transfer_call.tx.target(),
                         calldata: transfer_call.tx.calldata().clone(),
-                        confirmations: 1,
+                        confirmations,
                     },
                 );
                 #[cfg(feature = "telemetry")]
@@ -1525,7 +2406,7 @@ where
                     MetaTransaction {
                         to: MULTICALL3_ADDRESS,
                         calldata: aggregate_call.abi_encode().into(),
-                        confirmations: 1,
+                        confirmations,
                     },
                 );
                 #[cfg(feature = "telemetry")]
@@ -1558,7 +2439,7 @@ where
                 MetaTransaction {
                     to: transfer_call.tx.target(),
                     calldata: transfer_call.tx.calldata().clone(),
-                    confirmations: 1,
+                    confirmations,
                 },
             );
             #[cfg(feature = "telemetry")]
@@ -1589,7 +2470,7 @@ where
                 MetaTransaction {
                     to: transfer_call.tx.target(),
                     calldata: transfer_call.tx.calldata().clone(),
-                    confirmations: 1,
+                    confirmations,
                 },
             );
             #[cfg(feature = "telemetry")]
@@ -1620,7 +2501,7 @@ where
             tx = %receipt.transaction_hash,
             "transferWithAuthorization_0 succeeded"
         );
-        Ok(receipt.transaction_hash)
+        Ok(receipt)
     } else {
         #[cfg(feature = "telemetry")]
         tracing::event!(
@@ -1635,12 +2516,165 @@ where
     }
 }
 
+/// Sends `request.amount` of the `request.asset` token from the refund treasury back to
+/// `request.recipient`, see [`Eip155MetaTransactionProvider::refund_treasury`].
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
+    settlement = %request.settlement,
+    recipient = %request.recipient,
+    amount = %request.amount,
+)))]
+pub async fn refund_payment<P, E>(
+    provider: &P,
+    request: &proto::RefundRequest,
+) -> Result<proto::RefundResponse, Eip155ExactError>
+where
+    P: Eip155MetaTransactionProvider<Error = E>,
+    Eip155ExactError: From<E>,
+{
+    let chain_id: ChainId = (*provider.chain()).into();
+    if request.network != chain_id {
+        return Err(PaymentVerificationError::ChainIdMismatch.into());
+    }
+    let Some(treasury) = provider.refund_treasury() else {
+        return Err(PaymentVerificationError::InvalidFormat(format!(
+            "refunds are not enabled on {chain_id}: no refund_treasury configured"
+        ))
+        .into());
+    };
+    let invalid =
+        |what: &str| PaymentVerificationError::InvalidFormat(format!("Invalid refund {what}"));
+    let asset = Address::from_str(&request.asset).map_err(|_| invalid("asset"))?;
+    let recipient = Address::from_str(&request.recipient).map_err(|_| invalid("recipient"))?;
+    let amount = U256::from_str_radix(&request.amount, 10).map_err(|_| invalid("amount"))?;
+    let calldata = IERC20Transfer::transferCall {
+        to: recipient,
+        amount,
+    }
+    .abi_encode();
+    let receipt = provider
+        .send_transaction_from(
+            MetaTransaction {
+                to: asset,
+                calldata: calldata.into(),
+                confirmations: provider.required_confirmations(),
+            },
+            treasury,
+        )
+        .await?;
+    if !receipt.status() {
+        return Err(Eip155ExactError::TransactionReverted(
+            receipt.transaction_hash,
+        ));
+    }
+    #[cfg(feature = "telemetry")]
+    tracing::info!(tx = %receipt.transaction_hash, %treasury, "Refund sent");
+    Ok(proto::RefundResponse(serde_json::json!({
+        "success": true,
+        "transaction": receipt.transaction_hash.to_string(),
+        "network": chain_id,
+        "treasury": treasury.to_string(),
+        "recipient": recipient.to_string(),
+        "amount": amount.to_string(),
+        "settlement": request.settlement,
+    })))
+}
+
+/// Settles a `ReceiveWithAuthorization` payment through the receive forwarder.
+///
+/// Counterfactual (EIP-6492) wallets are deployed in the same transaction via Multicall3.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
+    from = %payment.authorization.from,
+    pay_to = %payment.pay_to,
+    forwarder = %forwarder.address(),
+)))]
+pub async fn settle_payment_receive<P, E>(
+    provider: &P,
+    forwarder: &X402ReceiveForwarder::X402ReceiveForwarderInstance<&P::Inner>,
+    payment: &ExactEvmReceivePayment,
+    eip712_domain: &Eip712Domain,
+    confirmations: u64,
+) -> Result<TransactionReceipt, Eip155ExactError>
+where
+    P: Eip155MetaTransactionProvider<Error = E>,
+    Eip155ExactError: From<E>,
+{
+    let signed_message = SignedMessage::extract_receive(&payment.authorization, eip712_domain)?;
+    let payer = payment.authorization.from.address();
+    let (signature, deployment) = match signed_message.signature {
+        StructuredSignature::EIP6492 {
+            factory,
+            factory_calldata,
+            inner,
+            original: _,
+        } => {
+            let deployment = if is_contract_deployed(provider.inner(), &payer).await? {
+                None
+            } else {
+                Some(IMulticall3::Call3 {
+                    allowFailure: true,
+                    target: factory,
+                    callData: factory_calldata,
+                })
+            };
+            (inner, deployment)
+        }
+        StructuredSignature::EIP1271(signature) => (signature, None),
+        StructuredSignature::EOA(signature) => (Bytes::from(signature.as_bytes().to_vec()), None),
+    };
+    let forward_call = forward_with_authorization(forwarder, payment, signature);
+    let meta_transaction = match deployment {
+        None => MetaTransaction {
+            to: forward_call.target(),
+            calldata: forward_call.calldata().clone(),
+            confirmations,
+        },
+        Some(deployment_call) => {
+            let forward = IMulticall3::Call3 {
+                allowFailure: false,
+                target: forward_call.target(),
+                callData: forward_call.calldata().clone(),
+            };
+            let aggregate_call = IMulticall3::aggregate3Call {
+                calls: vec![deployment_call, forward],
+            };
+            MetaTransaction {
+                to: MULTICALL3_ADDRESS,
+                calldata: aggregate_call.abi_encode().into(),
+                confirmations,
+            }
+        }
+    };
+    let receipt =
+        Eip155MetaTransactionProvider::send_transaction(provider, meta_transaction).await?;
+    if receipt.status() {
+        #[cfg(feature = "telemetry")]
+        tracing::event!(Level::INFO,
+            status = "ok",
+            tx = %receipt.transaction_hash,
+            "forwardWithAuthorization succeeded"
+        );
+        Ok(receipt)
+    } else {
+        #[cfg(feature = "telemetry")]
+        tracing::event!(
+            Level::WARN,
+            status = "failed",
+            tx = %receipt.transaction_hash,
+            "forwardWithAuthorization failed"
+        );
+        Err(Eip155ExactError::TransactionReverted(
+            receipt.transaction_hash,
+        ))
+    }
+}
+
 pub async fn settle_payment_permit2<P, E>(
     provider: &P,
     contract: &IPermit2::IPermit2Instance<&P::Inner>,
     payment: &Permit2Payment,
     eip712_domain: &Eip712Domain,
-) -> Result<TxHash, Eip155ExactError>
+    confirmations: u64,
+) -> Result<TransactionReceipt, Eip155ExactError>
 where
     P: Eip155MetaTransactionProvider<Error = E>,
     Eip155ExactError: From<E>,
@@ -1654,13 +2688,13 @@ where
         payment.token,
         payment.amount
     );
-    
+
     let signature_bytes = payment.signature.clone();
     let permit_single = build_permit2_single_call(payment)?;
     let transfer_amount = permit2_amount(payment.transfer_amount)?;
 
     tracing::info!("[DEBUG] calling permit() on Permit2 contract...");
-    let permit_tx = contract.permit(payment.owner, permit_single, signature_bytes);
+    let permit_tx = contract.permit(payment.owner.address(), permit_single, signature_bytes);
     let permit_tx_fut = Eip155MetaTransactionProvider::send_transaction_from(
         provider,
         MetaTransaction {
@@ -1668,7 +2702,7 @@ where
             calldata: permit_tx.calldata().clone(),
             confirmations: 1,
         },
-        payment.spender,
+        payment.spender.address(),
     );
     #[cfg(feature = "telemetry")]
     let permit_receipt = permit_tx_fut
@@ -1684,7 +2718,10 @@ where
     #[cfg(not(feature = "telemetry"))]
     let permit_receipt = permit_tx_fut.await?;
 
-    tracing::info!("[DEBUG] permit() completed, status={}", permit_receipt.status());
+    tracing::info!(
+        "[DEBUG] permit() completed, status={}",
+        permit_receipt.status()
+    );
     if !permit_receipt.status() {
         tracing::error!("[DEBUG] permit() REVERTED!");
         return Err(Eip155ExactError::TransactionReverted(
@@ -1693,16 +2730,20 @@ where
     }
 
     tracing::info!("[DEBUG] calling transferFrom() on Permit2 contract...");
-    let transfer_tx =
-        contract.transferFrom(payment.owner, payment.pay_to, transfer_amount, payment.token);
+    let transfer_tx = contract.transferFrom(
+        payment.owner.address(),
+        payment.pay_to.address(),
+        transfer_amount,
+        payment.token,
+    );
     let transfer_tx_fut = Eip155MetaTransactionProvider::send_transaction_from(
         provider,
         MetaTransaction {
             to: transfer_tx.target(),
             calldata: transfer_tx.calldata().clone(),
-            confirmations: 1,
+            confirmations,
         },
-        payment.spender,
+        payment.spender.address(),
     );
     #[cfg(feature = "telemetry")]
     let transfer_receipt = transfer_tx_fut
@@ -1718,10 +2759,16 @@ where
     #[cfg(not(feature = "telemetry"))]
     let transfer_receipt = transfer_tx_fut.await?;
 
-    tracing::info!("[DEBUG] transferFrom() completed, status={}", transfer_receipt.status());
+    tracing::info!(
+        "[DEBUG] transferFrom() completed, status={}",
+        transfer_receipt.status()
+    );
     if transfer_receipt.status() {
-        tracing::info!("[DEBUG] settle_payment_permit2 SUCCESS, tx={}", transfer_receipt.transaction_hash);
-        Ok(transfer_receipt.transaction_hash)
+        tracing::info!(
+            "[DEBUG] settle_payment_permit2 SUCCESS, tx={}",
+            transfer_receipt.transaction_hash
+        );
+        Ok(transfer_receipt)
     } else {
         tracing::error!("[DEBUG] transferFrom() REVERTED!");
         Err(Eip155ExactError::TransactionReverted(
@@ -1735,7 +2782,8 @@ pub async fn settle_payment_permit2_witness<P, E>(
     contract: &X402ExactPermit2Proxy::X402ExactPermit2ProxyInstance<&P::Inner>,
     payment: &Permit2WitnessPayment,
     eip712_domain: &Eip712Domain,
-) -> Result<TxHash, Eip155ExactError>
+    confirmations: u64,
+) -> Result<TransactionReceipt, Eip155ExactError>
 where
     P: Eip155MetaTransactionProvider<Error = E>,
     Eip155ExactError: From<E>,
@@ -1744,14 +2792,19 @@ where
 
     let permit = build_permit2_proxy_permit(payment);
     let witness = build_permit2_proxy_witness(payment);
-    let settle_tx = contract.settle(permit, payment.from, witness, payment.signature.clone());
+    let settle_tx = contract.settle(
+        permit,
+        payment.from.address(),
+        witness,
+        payment.signature.clone(),
+    );
 
     let tx_fut = Eip155MetaTransactionProvider::send_transaction(
         provider,
         MetaTransaction {
             to: settle_tx.target(),
             calldata: settle_tx.calldata().clone(),
-            confirmations: 1,
+            confirmations,
         },
     );
 
@@ -1770,9 +2823,11 @@ where
     let receipt = tx_fut.await?;
 
     if receipt.status() {
-        Ok(receipt.transaction_hash)
+        Ok(receipt)
     } else {
-        Err(Eip155ExactError::TransactionReverted(receipt.transaction_hash))
+        Err(Eip155ExactError::TransactionReverted(
+            receipt.transaction_hash,
+        ))
     }
 }
 
@@ -1788,6 +2843,8 @@ pub enum Eip155ExactError {
     ContractCall(String),
     #[error(transparent)]
     PaymentVerification(#[from] PaymentVerificationError),
+    #[error(transparent)]
+    Overloaded(#[from] SettlementLimitExceeded),
 }
 
 impl From<Eip155ExactError> for X402SchemeFacilitatorError {
@@ -1798,6 +2855,9 @@ impl From<Eip155ExactError> for X402SchemeFacilitatorError {
             Eip155ExactError::TransactionReverted(_) => Self::OnchainFailure(value.to_string()),
             Eip155ExactError::ContractCall(_) => Self::OnchainFailure(value.to_string()),
             Eip155ExactError::PaymentVerification(e) => Self::PaymentVerification(e),
+            Eip155ExactError::Overloaded(e) => Self::Overloaded {
+                retry_after: e.retry_after,
+            },
         }
     }
 }
@@ -1814,6 +2874,7 @@ impl From<MetaTransactionSendError> for Eip155ExactError {
             MetaTransactionSendError::Transport(e) => Self::Transport(e),
             MetaTransactionSendError::PendingTransaction(e) => Self::PendingTransaction(e),
             MetaTransactionSendError::Custom(e) => Self::ContractCall(e),
+            MetaTransactionSendError::Overloaded(e) => Self::Overloaded(e),
         }
     }
 }
@@ -1852,3 +2913,175 @@ impl From<alloy_contract::Error> for Eip155ExactError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_network::Ethereum;
+    use alloy_primitives::FixedBytes;
+    use alloy_provider::RootProvider;
+    use alloy_rpc_client::RpcClient;
+    use alloy_sol_types::SolValue;
+    use alloy_transport::mock::Asserter;
+    use x402_types::timestamp::ManualClock;
+
+    #[test]
+    fn test_time_windows_at_their_edges() {
+        let at = UnixTimestamp::from_secs;
+        let clock = ManualClock::new(at(1_000));
+
+        // Valid from `validAfter` on, until `grace` seconds before `validBefore`.
+        assert!(assert_time(at(1_000), at(1_006), 6, &clock).is_ok());
+        assert!(matches!(
+            assert_time(at(1_001), at(2_000), 6, &clock),
+            Err(PaymentVerificationError::Early { .. })
+        ));
+        clock.advance(1);
+        assert!(matches!(
+            assert_time(at(1_000), at(1_006), 6, &clock),
+            Err(PaymentVerificationError::Expired { now, .. }) if now == at(1_001)
+        ));
+        assert!(assert_time(at(1_000), at(1_006), 5, &clock).is_ok());
+
+        clock.set(at(1_000));
+        assert!(assert_permit2_time(at(1_030), at(1_030), 30, &clock).is_ok());
+        assert!(matches!(
+            assert_permit2_time(at(1_030), at(1_029), 30, &clock),
+            Err(PaymentVerificationError::Expired { valid_before, .. }) if valid_before == at(1_029)
+        ));
+        assert!(assert_remaining_validity(at(1_060), 60, &clock).is_ok());
+        assert!(assert_remaining_validity(at(1_059), 60, &clock).is_err());
+
+        assert!(assert_permit2_witness_time(at(1_306), at(1_000), 300, 6, &clock).is_ok());
+        assert!(matches!(
+            assert_permit2_witness_time(at(1_307), at(1_000), 300, 6, &clock),
+            Err(PaymentVerificationError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_amount_matching() {
+        let required = U256::from(1000);
+        let matches = |sent: u64, amount_matching| {
+            assert_amount_matching(&U256::from(sent), &required, amount_matching).is_ok()
+        };
+        assert!(matches(1000, AmountMatching::Exact));
+        assert!(!matches(999, AmountMatching::Exact));
+        assert!(matches(1001, AmountMatching::AtLeast));
+        assert!(!matches(999, AmountMatching::AtLeast));
+        assert!(matches(1000, AmountMatching::AtMost));
+        assert!(matches(1, AmountMatching::AtMost));
+        assert!(!matches(1001, AmountMatching::AtMost));
+        assert!(!matches(0, AmountMatching::AtMost));
+    }
+
+    /// Verifies a wrapped signature against a wallet whose Multicall3 `aggregate3`
+    /// answers with `results`.
+    async fn verify_6492(
+        results: Bytes,
+        then: Option<(Address, Bytes)>,
+    ) -> Result<(), Eip155ExactError> {
+        let asserter = Asserter::new();
+        asserter.push_success(&results);
+        let provider = RootProvider::<Ethereum>::new(RpcClient::mocked(asserter));
+        verify_6492_without_validator(
+            &provider,
+            Address::repeat_byte(0x55),
+            B256::repeat_byte(0x11),
+            Address::repeat_byte(0x66),
+            Bytes::from_static(&[0xab; 68]),
+            Bytes::from_static(&[0xcd; 65]),
+            then,
+        )
+        .await
+    }
+
+    fn aggregate3(results: Vec<(bool, Bytes)>) -> Bytes {
+        results.abi_encode().into()
+    }
+
+    fn magic_value(value: [u8; 4]) -> Bytes {
+        FixedBytes::from(value).abi_encode().into()
+    }
+
+    #[tokio::test]
+    async fn test_verify_6492_without_validator() {
+        let deployed = (true, Bytes::new());
+        let then = Some((Address::repeat_byte(0x77), Bytes::from_static(&[0x01])));
+
+        // The wallet returns the ERC-1271 magic value, even when its factory call
+        // fails because it is already deployed.
+        let valid = aggregate3(vec![
+            deployed.clone(),
+            (true, magic_value(ERC1271_MAGIC_VALUE)),
+        ]);
+        assert!(verify_6492(valid, None).await.is_ok());
+        let redeployed = aggregate3(vec![
+            (false, Bytes::new()),
+            (true, magic_value(ERC1271_MAGIC_VALUE)),
+            (true, Bytes::new()),
+        ]);
+        assert!(verify_6492(redeployed, then.clone()).await.is_ok());
+
+        // Any other value, or a reverted check, rejects the signature.
+        for signature_result in [
+            (true, magic_value(hex!("ffffffff"))),
+            (false, magic_value(ERC1271_MAGIC_VALUE)),
+        ] {
+            let results = aggregate3(vec![deployed.clone(), signature_result]);
+            assert!(matches!(
+                verify_6492(results, None).await,
+                Err(Eip155ExactError::PaymentVerification(
+                    PaymentVerificationError::InvalidSignature(_)
+                ))
+            ));
+        }
+
+        // A reverted call after the check fails the simulation.
+        let reverted = aggregate3(vec![
+            deployed.clone(),
+            (true, magic_value(ERC1271_MAGIC_VALUE)),
+            (false, Bytes::new()),
+        ]);
+        assert!(matches!(
+            verify_6492(reverted, then.clone()).await,
+            Err(Eip155ExactError::PaymentVerification(
+                PaymentVerificationError::TransactionSimulation(_)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_6492_without_validator_malformed_return_data() {
+        let deployed = (true, Bytes::new());
+
+        // A signature check returning fewer than 32 bytes is no magic value.
+        let short = aggregate3(vec![
+            deployed.clone(),
+            (true, Bytes::from(ERC1271_MAGIC_VALUE.to_vec())),
+        ]);
+        assert!(matches!(
+            verify_6492(short, None).await,
+            Err(Eip155ExactError::PaymentVerification(
+                PaymentVerificationError::InvalidSignature(_)
+            ))
+        ));
+
+        // Output that is no `Result[]`, or has a result per call missing.
+        for results in [
+            Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]),
+            aggregate3(vec![(true, magic_value(ERC1271_MAGIC_VALUE))]),
+        ] {
+            assert!(matches!(
+                verify_6492(results, None).await,
+                Err(Eip155ExactError::ContractCall(_))
+            ));
+        }
+        let two_results = aggregate3(vec![deployed, (true, magic_value(ERC1271_MAGIC_VALUE))]);
+        let then = Some((Address::repeat_byte(0x77), Bytes::from_static(&[0x01])));
+        assert!(matches!(
+            verify_6492(two_results, then).await,
+            Err(Eip155ExactError::ContractCall(_))
+        ));
+    }
+}
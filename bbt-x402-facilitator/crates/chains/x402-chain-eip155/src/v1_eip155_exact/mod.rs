@@ -11,6 +11,11 @@
 //! - EIP-1271 support for deployed smart wallet signatures
 //! - EOA signature support with split (v, r, s) components
 //! - On-chain balance verification before settlement
+//! - `receiveWithAuthorization` settlement through a forwarder, selected per token
+//!   (see [`settlement`])
+//! - Optional allow-list of accepted tokens with amount bounds (see [`policy`])
+//! - Provider-free EIP-712 digest computation for client SDK tests (see [`digest`])
+//! - Static explanation of payloads for debugging integrations (see [`explain`])
 //!
 //! # Signature Handling
 //!
@@ -42,6 +47,7 @@
 //!     eip712: Some(TokenDeploymentEip712 {
 //!         name: "BBT".into(),
 //!         version: "1".into(),
+//!         domain: Default::default(),
 //!     }),
 //! };
 //! let price = V1Eip155Exact::price_tag(
@@ -62,11 +68,30 @@ pub use server::*;
 pub mod facilitator;
 #[cfg(feature = "facilitator")]
 pub use facilitator::*;
+#[cfg(feature = "facilitator")]
+pub mod explain;
+#[cfg(feature = "facilitator")]
+pub mod policy;
+#[cfg(feature = "facilitator")]
+pub use policy::*;
+#[cfg(feature = "facilitator")]
+pub mod settlement;
+#[cfg(feature = "facilitator")]
+pub mod signature_check;
+#[cfg(feature = "facilitator")]
+pub use settlement::*;
+
+#[cfg(any(feature = "facilitator", feature = "client"))]
+pub mod digest;
+#[cfg(any(feature = "facilitator", feature = "client"))]
+pub use digest::*;
 
 #[cfg(feature = "client")]
 pub mod client;
 #[cfg(feature = "client")]
 pub use client::*;
+#[cfg(feature = "client")]
+pub mod safe;
 
 pub mod types;
 pub use types::*;
@@ -0,0 +1,307 @@
+//! Facilitator-level asset policy for the EIP-155 "exact" scheme.
+//!
+//! Without a policy, the facilitator verifies and settles payments in any token the
+//! resource server asks for. Listing tokens under `allowedAssets` in the scheme's
+//! `config` section restricts it to those tokens, optionally within amount bounds:
+//!
+//! ```json
+//! {
+//!   "id": "v2-eip155-exact",
+//!   "chains": "eip155:42793",
+//!   "config": {
+//!     "allowedAssets": {
+//!       "0x7EfE4bdd11237610bcFca478937658bE39F8dfd6": {
+//!         "symbol": "BBT",
+//!         "decimals": 18,
+//!         "minAmount": "0.001",
+//!         "maxAmount": "500"
+//!       },
+//!       "0x796Ea11Fa2dD751eD01b53C372fFDB4AAa8f00F9": {}
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! With `decimals` set, `minAmount` and `maxAmount` are in whole token units and may
+//! not be more precise than the token allows. Without it, they are in the token's
+//! smallest unit. The policy bounds the amount the payload transfers, and is checked
+//! before any RPC call is made, including the token-gated discount lookup of V2.
+//!
+//! The accepted tokens are advertised on `/supported`, under `assets` in the scheme's
+//! `extra`, with their `symbol` and `decimals` when set and their bounds in the
+//! token's smallest unit (see [`AssetMetadata`]).
+
+use alloy_primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Mul;
+use x402_types::proto::PaymentVerificationError;
+
+use crate::chain::ChecksummedAddress;
+use x402_types::util::money_amount::{MoneyAmount, MoneyAmountParseError};
+
+/// Accepted tokens, keyed by token address.
+pub type AllowedAssets = HashMap<Address, AssetPolicy>;
+
+/// Amount bounds for an accepted token, in the token's smallest unit.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "AssetPolicyConfig", into = "AssetPolicyConfig")]
+pub struct AssetPolicy {
+    /// Ticker shown to clients, e.g. `BBT`.
+    pub symbol: Option<String>,
+    /// Decimals of the token, as configured.
+    pub decimals: Option<u8>,
+    /// Smallest accepted payment, inclusive.
+    pub min_amount: Option<U256>,
+    /// Largest accepted payment, inclusive.
+    pub max_amount: Option<U256>,
+}
+
+impl AssetPolicy {
+    /// Checks `amount` against the bounds.
+    pub fn check_amount(&self, amount: U256) -> Result<(), PaymentVerificationError> {
+        if let Some(min) = self.min_amount.filter(|min| amount < *min) {
+            return Err(PaymentVerificationError::invalid_amount(min, amount));
+        }
+        if let Some(max) = self.max_amount.filter(|max| amount > *max) {
+            return Err(PaymentVerificationError::invalid_amount(max, amount));
+        }
+        Ok(())
+    }
+}
+
+/// An accepted token as advertised on `/supported`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetMetadata {
+    /// Token contract address.
+    pub address: ChecksummedAddress,
+    /// Ticker, when configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// Decimals of the token, when configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+    /// Smallest accepted payment, in the token's smallest unit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_amount: Option<String>,
+    /// Largest accepted payment, in the token's smallest unit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_amount: Option<String>,
+}
+
+/// Metadata of the accepted tokens, ordered by address.
+pub fn asset_metadata(allowed_assets: &AllowedAssets) -> Vec<AssetMetadata> {
+    let mut assets: Vec<AssetMetadata> = allowed_assets
+        .iter()
+        .map(|(address, policy)| AssetMetadata {
+            address: (*address).into(),
+            symbol: policy.symbol.clone(),
+            decimals: policy.decimals,
+            min_amount: policy.min_amount.map(|amount| amount.to_string()),
+            max_amount: policy.max_amount.map(|amount| amount.to_string()),
+        })
+        .collect();
+    assets.sort_by_key(|asset| asset.address.0);
+    assets
+}
+
+/// Ensures `asset` is allowed by `allowed_assets` and `amount` is within its bounds.
+///
+/// `None` means no policy is configured and every asset is accepted.
+pub fn assert_asset_allowed(
+    allowed_assets: Option<&AllowedAssets>,
+    asset: &Address,
+    amount: U256,
+) -> Result<(), PaymentVerificationError> {
+    let Some(allowed_assets) = allowed_assets else {
+        return Ok(());
+    };
+    let policy = allowed_assets
+        .get(asset)
+        .ok_or(PaymentVerificationError::UnsupportedAsset)?;
+    policy.check_amount(amount)
+}
+
+/// Wire format of [`AssetPolicy`], before amounts are scaled to the smallest unit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AssetPolicyConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    decimals: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_amount: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_amount: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum AssetPolicyError {
+    #[error("invalid token amount {0:?}")]
+    InvalidAmount(String),
+    #[error(transparent)]
+    MoneyAmount(#[from] MoneyAmountParseError),
+    #[error("minAmount is greater than maxAmount")]
+    EmptyRange,
+}
+
+fn parse_bound(value: &str, decimals: Option<u8>) -> Result<U256, AssetPolicyError> {
+    let Some(decimals) = decimals else {
+        return U256::from_str_radix(value, 10)
+            .map_err(|_| AssetPolicyError::InvalidAmount(value.to_string()));
+    };
+    let money_amount = MoneyAmount::parse(value)?;
+    let scale = money_amount.scale();
+    let token_scale = decimals as u32;
+    if scale > token_scale {
+        return Err(MoneyAmountParseError::WrongPrecision {
+            money: scale,
+            token: token_scale,
+        }
+        .into());
+    }
+    let multiplier = U256::from(10).pow(U256::from(token_scale - scale));
+    Ok(U256::from(money_amount.mantissa()).mul(multiplier))
+}
+
+impl TryFrom<AssetPolicyConfig> for AssetPolicy {
+    type Error = AssetPolicyError;
+
+    fn try_from(config: AssetPolicyConfig) -> Result<Self, Self::Error> {
+        let min_amount = config
+            .min_amount
+            .as_deref()
+            .map(|value| parse_bound(value, config.decimals))
+            .transpose()?;
+        let max_amount = config
+            .max_amount
+            .as_deref()
+            .map(|value| parse_bound(value, config.decimals))
+            .transpose()?;
+        if let (Some(min), Some(max)) = (min_amount, max_amount)
+            && min > max
+        {
+            return Err(AssetPolicyError::EmptyRange);
+        }
+        Ok(Self {
+            symbol: config.symbol,
+            decimals: config.decimals,
+            min_amount,
+            max_amount,
+        })
+    }
+}
+
+impl From<AssetPolicy> for AssetPolicyConfig {
+    fn from(policy: AssetPolicy) -> Self {
+        let decimals = policy.decimals;
+        let format = |amount: U256| match decimals {
+            Some(decimals) => format_units(amount, decimals),
+            None => amount.to_string(),
+        };
+        Self {
+            symbol: policy.symbol,
+            decimals,
+            min_amount: policy.min_amount.map(format),
+            max_amount: policy.max_amount.map(format),
+        }
+    }
+}
+
+/// Formats `amount` in whole token units, the inverse of [`parse_bound`].
+fn format_units(amount: U256, decimals: u8) -> String {
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+    let digits = format!("{digits:0>width$}", width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{whole}.{fraction}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn test_asset_policy_bounds() {
+        let bbt = address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6");
+        let allowed: AllowedAssets = serde_json::from_value(serde_json::json!({
+            "0x7EfE4bdd11237610bcFca478937658bE39F8dfd6": {
+                "decimals": 18,
+                "minAmount": "0.001",
+                "maxAmount": "500"
+            }
+        }))
+        .unwrap();
+        let one_bbt = U256::from(10).pow(U256::from(18));
+
+        assert!(assert_asset_allowed(Some(&allowed), &bbt, one_bbt).is_ok());
+        assert!(matches!(
+            assert_asset_allowed(Some(&allowed), &bbt, U256::from(1000)),
+            Err(PaymentVerificationError::InvalidPaymentAmount { .. })
+        ));
+        assert!(matches!(
+            assert_asset_allowed(Some(&allowed), &bbt, one_bbt * U256::from(501)),
+            Err(PaymentVerificationError::InvalidPaymentAmount { .. })
+        ));
+        assert!(matches!(
+            assert_asset_allowed(Some(&allowed), &Address::ZERO, one_bbt),
+            Err(PaymentVerificationError::UnsupportedAsset)
+        ));
+        assert!(assert_asset_allowed(None, &Address::ZERO, one_bbt).is_ok());
+    }
+
+    #[test]
+    fn test_asset_policy_rejects_bad_bounds() {
+        let parse = |value| serde_json::from_value::<AssetPolicy>(value);
+        assert!(parse(serde_json::json!({ "decimals": 6, "minAmount": "0.0000001" })).is_err());
+        assert!(parse(serde_json::json!({ "minAmount": "10", "maxAmount": "1" })).is_err());
+        assert!(parse(serde_json::json!({ "maxAmount": "1.5" })).is_err());
+        assert_eq!(
+            parse(serde_json::json!({ "maxAmount": "1500" }))
+                .unwrap()
+                .max_amount,
+            Some(U256::from(1500))
+        );
+    }
+
+    #[test]
+    fn test_asset_metadata_and_round_trip() {
+        let allowed: AllowedAssets = serde_json::from_value(serde_json::json!({
+            "0x7EfE4bdd11237610bcFca478937658bE39F8dfd6": {
+                "symbol": "BBT",
+                "decimals": 18,
+                "minAmount": "0.001"
+            },
+            "0x0000000000000000000000000000000000000001": {}
+        }))
+        .unwrap();
+        let assets = asset_metadata(&allowed);
+        assert_eq!(assets[0].address.0, Address::with_last_byte(1));
+        assert_eq!(
+            serde_json::to_value(&assets[1]).unwrap(),
+            serde_json::json!({
+                "address": "0x7EfE4bdd11237610bcFca478937658bE39F8dfd6",
+                "symbol": "BBT",
+                "decimals": 18,
+                "minAmount": "1000000000000000"
+            })
+        );
+
+        let round_trip: AllowedAssets =
+            serde_json::from_value(serde_json::to_value(&allowed).unwrap()).unwrap();
+        assert_eq!(round_trip, allowed);
+        assert_eq!(format_units(U256::from(1_500_000), 6), "1.5");
+        assert_eq!(format_units(U256::from(5), 2), "0.05");
+    }
+}
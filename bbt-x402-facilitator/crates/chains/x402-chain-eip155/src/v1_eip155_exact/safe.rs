@@ -0,0 +1,195 @@
+//! Payment signing for Safe smart accounts.
+//!
+//! A [`SafeSigner`] pays from a Safe rather than from one of its owners. The owners
+//! sign the Safe's `SafeMessage` wrapping of the payment digest, and the signature
+//! is validated on-chain by the Safe through EIP-1271 `isValidSignature`, as done
+//! by Safe's `CompatibilityFallbackHandler`:
+//!
+//! ```text
+//! safeMessageHash = EIP-712(SafeMessage(bytes message = paymentDigest), domain = { chainId, verifyingContract: safe })
+//! signature       = concat(owner signatures over safeMessageHash, sorted by owner address)
+//! ```
+//!
+//! A Safe that is not deployed yet can pay once its deployment is described with
+//! [`SafeSigner::with_deployment`]: the signature is then wrapped as per EIP-6492,
+//! and the facilitator simulates the deployment to validate it.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use x402_chain_eip155::V2Eip155ExactClient;
+//! use x402_chain_eip155::v1_eip155_exact::safe::SafeSigner;
+//!
+//! let safe = SafeSigner::new(safe_address, vec![Arc::new(owner_a), Arc::new(owner_b)]);
+//! let client = V2Eip155ExactClient::new(safe);
+//! ```
+
+use alloy_primitives::{Address, B256, Bytes, hex};
+use alloy_sol_types::{SolStruct, SolValue, eip712_domain, sol};
+use async_trait::async_trait;
+use x402_types::scheme::client::X402Error;
+
+use crate::v1_eip155_exact::client::{Eip712Signer, SignerLike};
+
+/// The fixed 32-byte suffix marking an [EIP-6492](https://eips.ethereum.org/EIPS/eip-6492) signature.
+const EIP6492_MAGIC_SUFFIX: [u8; 32] =
+    hex!("6492649264926492649264926492649264926492649264926492649264926492");
+
+sol! {
+    /// Message type Safe wraps the digests it validates with EIP-1271 in.
+    struct SafeMessage {
+        bytes message;
+    }
+}
+
+/// Deployment of a counterfactual smart account, for EIP-6492 signatures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eip6492Deployment {
+    /// Factory deploying the account.
+    pub factory: Address,
+    /// Call to the factory that deploys the account.
+    pub factory_calldata: Bytes,
+}
+
+/// Signs payments from a Safe with the keys of its owners.
+///
+/// The owners given must reach the Safe's threshold.
+#[derive(Debug, Clone)]
+pub struct SafeSigner<S> {
+    safe: Address,
+    owners: Vec<S>,
+    deployment: Option<Eip6492Deployment>,
+}
+
+impl<S> SafeSigner<S> {
+    /// Signer paying from `safe`, signing with `owners`.
+    pub fn new(safe: Address, owners: Vec<S>) -> Self {
+        Self {
+            safe,
+            owners,
+            deployment: None,
+        }
+    }
+
+    /// Wraps signatures as per EIP-6492, for a Safe not deployed yet.
+    pub fn with_deployment(mut self, deployment: Eip6492Deployment) -> Self {
+        self.deployment = Some(deployment);
+        self
+    }
+}
+
+/// The digest Safe owners sign for the Safe to accept `hash` on `chain_id`.
+pub fn safe_message_hash(safe: Address, chain_id: u64, hash: &B256) -> B256 {
+    let domain = eip712_domain! {
+        chain_id: chain_id,
+        verifying_contract: safe,
+    };
+    SafeMessage {
+        message: Bytes::copy_from_slice(hash.as_slice()),
+    }
+    .eip712_signing_hash(&domain)
+}
+
+/// Wraps a signature of a counterfactual account as per EIP-6492.
+pub fn wrap_eip6492(deployment: &Eip6492Deployment, signature: Bytes) -> Bytes {
+    let mut wrapped = (
+        deployment.factory,
+        deployment.factory_calldata.clone(),
+        signature,
+    )
+        .abi_encode_params();
+    wrapped.extend_from_slice(&EIP6492_MAGIC_SUFFIX);
+    wrapped.into()
+}
+
+#[async_trait]
+impl<S> Eip712Signer for SafeSigner<S>
+where
+    S: SignerLike + Send + Sync,
+{
+    fn address(&self) -> Address {
+        self.safe
+    }
+
+    async fn sign_eip712_hash(&self, chain_id: u64, hash: &B256) -> Result<Bytes, X402Error> {
+        if self.owners.is_empty() {
+            return Err(X402Error::SigningError(
+                "Safe signer has no owners".to_string(),
+            ));
+        }
+        let safe_hash = safe_message_hash(self.safe, chain_id, hash);
+        // Safe requires owner signatures in ascending owner order.
+        let mut owners: Vec<&S> = self.owners.iter().collect();
+        owners.sort_by_key(|owner| owner.address());
+        let mut signature = Vec::with_capacity(owners.len() * 65);
+        for owner in owners {
+            let owner_signature = owner
+                .sign_hash(&safe_hash)
+                .await
+                .map_err(|e| X402Error::SigningError(format!("{e:?}")))?;
+            signature.extend_from_slice(&owner_signature.as_bytes());
+        }
+        let signature = Bytes::from(signature);
+        Ok(match &self.deployment {
+            Some(deployment) => wrap_eip6492(deployment, signature),
+            None => signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Signature, address};
+    use alloy_signer_local::PrivateKeySigner;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_owner_signatures_are_sorted_and_recoverable() {
+        let safe = address!("0x5afe000000000000000000000000000000000001");
+        let owners: Vec<_> = (0..2)
+            .map(|_| Arc::new(PrivateKeySigner::random()))
+            .collect();
+        let mut expected: Vec<_> = owners
+            .iter()
+            .map(|owner| owner.as_ref().address())
+            .collect();
+        expected.sort();
+        let signer = SafeSigner::new(safe, owners);
+        let hash = B256::repeat_byte(7);
+
+        let signature = signer.sign_eip712_hash(42793, &hash).await.unwrap();
+        assert_eq!(Eip712Signer::address(&signer), safe);
+        assert_eq!(signature.len(), 130);
+        let safe_hash = safe_message_hash(safe, 42793, &hash);
+        let recovered: Vec<_> = signature
+            .chunks(65)
+            .map(|chunk| {
+                Signature::from_raw(chunk)
+                    .unwrap()
+                    .recover_address_from_prehash(&safe_hash)
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(recovered, expected);
+    }
+
+    #[tokio::test]
+    async fn test_counterfactual_safe_wraps_eip6492() {
+        let deployment = Eip6492Deployment {
+            factory: address!("0x4e1DCf7AD4e460CfD30791CCC4F9c8a4f820ec67"),
+            factory_calldata: Bytes::from_static(&[0xde, 0xad]),
+        };
+        let signer = SafeSigner::new(Address::repeat_byte(5), vec![PrivateKeySigner::random()])
+            .with_deployment(deployment.clone());
+
+        let signature = signer.sign_eip712_hash(1, &B256::ZERO).await.unwrap();
+        assert!(signature.ends_with(&EIP6492_MAGIC_SUFFIX));
+        let (factory, calldata, inner) =
+            <(Address, Bytes, Bytes)>::abi_decode_params(&signature[..signature.len() - 32])
+                .unwrap();
+        assert_eq!(factory, deployment.factory);
+        assert_eq!(calldata, deployment.factory_calldata);
+        assert_eq!(inner.len(), 65);
+    }
+}
@@ -20,7 +20,7 @@ impl V1Eip155Exact {
     ///
     /// # Parameters
     ///
-    /// - `pay_to`: The recipient address (can be any type convertible to [`ChecksummedAddress`])
+    /// - `pay_to`: The recipient address (can be any type convertible to [`ChecksummedAddress`]).
     /// - `asset`: The token deployment and amount required
     ///
     /// # Returns
@@ -41,6 +41,7 @@ impl V1Eip155Exact {
     ///     eip712: Some(TokenDeploymentEip712 {
     ///         name: "BBT".into(),
     ///         version: "1".into(),
+    ///         domain: Default::default(),
     ///     }),
     /// };
     /// let price_tag = V1Eip155Exact::price_tag(
@@ -77,4 +78,24 @@ impl V1Eip155Exact {
             enricher: None,
         }
     }
+
+    /// Creates one V1 price tag per accepted token, all paying to `pay_to`.
+    ///
+    /// The tags are advertised together in the `accepts` list, in the given order,
+    /// so the payer can pay in whichever token it holds.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`V1Eip155Exact::price_tag`].
+    #[allow(dead_code)] // Public for consumption by downstream crates.
+    pub fn price_tags<A: Into<ChecksummedAddress>>(
+        pay_to: A,
+        assets: impl IntoIterator<Item = DeployedTokenAmount<U256, Eip155TokenDeployment>>,
+    ) -> Vec<v1::PriceTag> {
+        let pay_to = pay_to.into();
+        assets
+            .into_iter()
+            .map(|asset| Self::price_tag(pay_to, asset))
+            .collect()
+    }
 }
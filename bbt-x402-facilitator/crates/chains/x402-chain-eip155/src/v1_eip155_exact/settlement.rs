@@ -0,0 +1,373 @@
+//! Per-token settlement configuration for the EIP-155 "exact" scheme.
+//!
+//! By default, ERC-3009 payments are settled with `transferWithAuthorization`. Some
+//! token deployments only let the recipient itself redeem an authorization, through
+//! `receiveWithAuthorization`. For those tokens, the facilitator settles through a
+//! forwarder contract (see `contracts/x402ReceiveForwarder.sol`): the payer signs a
+//! `ReceiveWithAuthorization` to the forwarder, and the forwarder passes the funds on
+//! to the recipient bound into the nonce.
+//!
+//! The mode is selected per token in the scheme's `config` section:
+//!
+//! ```json
+//! {
+//!   "id": "v2-eip155-exact",
+//!   "chains": "eip155:42793",
+//!   "config": {
+//!     "tokens": {
+//!       "0x7EfE4bdd11237610bcFca478937658bE39F8dfd6": {
+//!         "settlement": "receiveWithAuthorization",
+//!         "forwarder": "0x..."
+//!       }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Resource servers advertise the forwarder to clients with the `receiveForwarder`
+//! field of the payment requirements `extra`.
+//!
+//! The same section lists the x402 Permit2 proxies accepted as `spender` on the
+//! chains it covers. During a proxy migration both deployments are listed, the new
+//! one first: `/supported` advertises it as `permit2Proxy`, while payments signed
+//! for the old one still verify and settle through the proxy they name.
+//!
+//! ```json
+//! { "config": { "permit2Proxies": ["0xNEW...", "0xOLD..."] } }
+//! ```
+//!
+//! The remaining settings, with the environment variables used when they are absent:
+//!
+//! | Key | Environment variable | Description |
+//! |-----|----------------------|-------------|
+//! | `graceBufferSeconds` | | Seconds an authorization must stay valid past now (default: `6`) |
+//! | `minRemainingValiditySecs` | | Seconds an authorization must stay valid past now to be settled, at least `graceBufferSeconds` (default: `30`). The chain's `min_remaining_validity_secs` takes precedence |
+//! | `permit2AllowanceTransfer` | `X402_ENABLE_PERMIT2_ALLOWANCE_TRANSFER` | Accept legacy Permit2 `PermitSingle` payloads in V1 (default: `false`) |
+//! | `permit2ProxyCodehashes` | `X402_EXACT_PERMIT2_PROXY_CODEHASH_ALLOWLIST` | Code hashes a Permit2 proxy must have (default: any) |
+//! | `permit2Proxies` | `X402_EXACT_PERMIT2_PROXY_ADDRESS` | Accepted Permit2 proxies |
+//!
+//! Unknown keys and malformed values, in the config or the environment, fail the
+//! scheme build with an [`Eip155ExactConfigError`].
+
+use alloy_primitives::{Address, B256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use x402_types::timestamp::SharedClock;
+
+use crate::v1_eip155_exact::policy::{AllowedAssets, asset_metadata};
+use crate::v1_eip155_exact::x402_exact_permit2_proxy_address;
+
+/// Default of [`Eip155ExactConfig::grace_buffer_seconds`].
+pub const DEFAULT_GRACE_BUFFER_SECONDS: u64 = 6;
+
+/// Default of [`Eip155ExactConfig::min_remaining_validity_secs`].
+pub const DEFAULT_MIN_REMAINING_VALIDITY_SECS: u64 = 30;
+
+/// Errors in the scheme configuration of the EIP-155 "exact" facilitators.
+#[derive(Debug, thiserror::Error)]
+pub enum Eip155ExactConfigError {
+    #[error("Invalid exact scheme config: {0}")]
+    Invalid(#[from] serde_json::Error),
+    #[error("Invalid {name}: {reason}")]
+    Env { name: &'static str, reason: String },
+}
+
+/// Scheme configuration for the EIP-155 "exact" facilitators.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Eip155ExactConfig {
+    /// Settlement overrides, keyed by token address.
+    #[serde(default)]
+    pub tokens: HashMap<Address, TokenSettlement>,
+    /// Tokens the facilitator accepts, see [`policy`](crate::v1_eip155_exact::policy).
+    /// When absent, any token is accepted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_assets: Option<AllowedAssets>,
+    /// Accepted x402 Permit2 proxies, preferred first. When empty, only
+    /// [`x402_exact_permit2_proxy_address`] is accepted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permit2_proxies: Vec<Address>,
+    /// Seconds an authorization or Permit2 deadline must remain valid past now,
+    /// covering the time to settle it.
+    #[serde(default = "default_grace_buffer_seconds")]
+    pub grace_buffer_seconds: u64,
+    /// Clock the time windows are checked against; the system clock outside tests.
+    #[serde(skip)]
+    pub clock: SharedClock,
+    /// Seconds an authorization or Permit2 deadline must remain valid past now for
+    /// the payment to be settled, covering the time until the settlement transaction
+    /// is mined. Overridden by the chain's `min_remaining_validity_secs`.
+    #[serde(default = "default_min_remaining_validity_secs")]
+    pub min_remaining_validity_secs: u64,
+    /// Whether legacy Permit2 AllowanceTransfer payloads are accepted by V1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permit2_allowance_transfer: Option<bool>,
+    /// Code hashes a Permit2 proxy must have. When empty, any deployed proxy is accepted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permit2_proxy_codehashes: Vec<B256>,
+}
+
+impl Default for Eip155ExactConfig {
+    fn default() -> Self {
+        Self {
+            tokens: HashMap::new(),
+            allowed_assets: None,
+            permit2_proxies: Vec::new(),
+            grace_buffer_seconds: DEFAULT_GRACE_BUFFER_SECONDS,
+            clock: SharedClock::default(),
+            min_remaining_validity_secs: DEFAULT_MIN_REMAINING_VALIDITY_SECS,
+            permit2_allowance_transfer: None,
+            permit2_proxy_codehashes: Vec::new(),
+        }
+    }
+}
+
+fn default_grace_buffer_seconds() -> u64 {
+    DEFAULT_GRACE_BUFFER_SECONDS
+}
+
+fn default_min_remaining_validity_secs() -> u64 {
+    DEFAULT_MIN_REMAINING_VALIDITY_SECS
+}
+
+impl Eip155ExactConfig {
+    /// Parses the scheme-specific `config` value. A missing value means defaults.
+    ///
+    /// Settings absent from the config are taken from their environment variables,
+    /// which must be well-formed when set.
+    pub fn from_value(value: Option<serde_json::Value>) -> Result<Self, Eip155ExactConfigError> {
+        let mut config: Self = value
+            .map(serde_json::from_value)
+            .transpose()?
+            .unwrap_or_default();
+        // Validated here so that `x402_exact_permit2_proxy_address` cannot fail later.
+        permit2_proxy_address_from_env()?;
+        if config.permit2_allowance_transfer.is_none() {
+            config.permit2_allowance_transfer = env_var("X402_ENABLE_PERMIT2_ALLOWANCE_TRANSFER")
+                .map(|raw| parse_env_bool("X402_ENABLE_PERMIT2_ALLOWANCE_TRANSFER", &raw))
+                .transpose()?;
+        }
+        if config.permit2_proxy_codehashes.is_empty()
+            && let Some(raw) = env_var("X402_EXACT_PERMIT2_PROXY_CODEHASH_ALLOWLIST")
+        {
+            config.permit2_proxy_codehashes = raw
+                .split(',')
+                .map(str::trim)
+                .filter(|hash| !hash.is_empty())
+                .map(|hash| {
+                    B256::from_str(hash).map_err(|_| Eip155ExactConfigError::Env {
+                        name: "X402_EXACT_PERMIT2_PROXY_CODEHASH_ALLOWLIST",
+                        reason: format!("{hash} is not a 32-byte hex hash"),
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        Ok(config)
+    }
+
+    /// Seconds a payment must remain valid to be settled, given the chain's
+    /// override. Never less than `grace_buffer_seconds`.
+    pub fn settle_validity_secs(&self, chain_override: Option<u64>) -> u64 {
+        chain_override
+            .unwrap_or(self.min_remaining_validity_secs)
+            .max(self.grace_buffer_seconds)
+    }
+
+    /// Whether legacy Permit2 AllowanceTransfer payloads are accepted.
+    pub fn permit2_allowance_transfer_enabled(&self) -> bool {
+        self.permit2_allowance_transfer.unwrap_or(false)
+    }
+
+    /// Returns the receive forwarder configured for `token`, if it settles with
+    /// `receiveWithAuthorization`.
+    pub fn receive_forwarder(&self, token: &Address) -> Option<Address> {
+        match self.tokens.get(token)? {
+            TokenSettlement::TransferWithAuthorization => None,
+            TokenSettlement::ReceiveWithAuthorization { forwarder } => Some(*forwarder),
+        }
+    }
+
+    /// The proxy new payments should name as `spender`.
+    pub fn preferred_permit2_proxy(&self) -> Address {
+        self.permit2_proxies
+            .first()
+            .copied()
+            .unwrap_or_else(x402_exact_permit2_proxy_address)
+    }
+
+    /// Returns whether `spender` is an accepted x402 Permit2 proxy.
+    pub fn accepts_permit2_proxy(&self, spender: &Address) -> bool {
+        if self.permit2_proxies.is_empty() {
+            *spender == x402_exact_permit2_proxy_address()
+        } else {
+            self.permit2_proxies.contains(spender)
+        }
+    }
+
+    /// The `extra` advertised with the scheme on `/supported`.
+    ///
+    /// Lists the accepted tokens under `assets` when an asset policy is configured.
+    pub fn supported_extra(&self) -> serde_json::Value {
+        let mut extra = serde_json::json!({ "permit2Proxy": self.preferred_permit2_proxy() });
+        if let Some(allowed_assets) = &self.allowed_assets {
+            extra["assets"] = serde_json::json!(asset_metadata(allowed_assets));
+        }
+        extra
+    }
+}
+
+/// Reads the Permit2 proxy address override from `X402_EXACT_PERMIT2_PROXY_ADDRESS`.
+pub fn permit2_proxy_address_from_env() -> Result<Option<Address>, Eip155ExactConfigError> {
+    env_var("X402_EXACT_PERMIT2_PROXY_ADDRESS")
+        .map(|raw| {
+            Address::from_str(&raw).map_err(|_| Eip155ExactConfigError::Env {
+                name: "X402_EXACT_PERMIT2_PROXY_ADDRESS",
+                reason: format!("{raw} is not a 0x-prefixed address"),
+            })
+        })
+        .transpose()
+}
+
+/// Returns a trimmed environment variable, treating an empty value as unset.
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn parse_env_bool(name: &'static str, raw: &str) -> Result<bool, Eip155ExactConfigError> {
+    match raw.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" => Ok(true),
+        "0" | "false" | "no" => Ok(false),
+        _ => Err(Eip155ExactConfigError::Env {
+            name,
+            reason: format!("{raw} is not a boolean"),
+        }),
+    }
+}
+
+/// How ERC-3009 payments in a given token are settled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "settlement", rename_all = "camelCase")]
+pub enum TokenSettlement {
+    /// `transferWithAuthorization`, sent directly to the token (default).
+    TransferWithAuthorization,
+    /// `receiveWithAuthorization`, sent through the given forwarder contract.
+    ReceiveWithAuthorization {
+        /// Address of the deployed receive forwarder.
+        forwarder: Address,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn test_parse_token_settlement() {
+        let config = Eip155ExactConfig::from_value(Some(serde_json::json!({
+            "tokens": {
+                "0x7EfE4bdd11237610bcFca478937658bE39F8dfd6": {
+                    "settlement": "receiveWithAuthorization",
+                    "forwarder": "0x1111111111111111111111111111111111111111"
+                },
+                "0x2222222222222222222222222222222222222222": {
+                    "settlement": "transferWithAuthorization"
+                }
+            }
+        })))
+        .unwrap();
+        assert_eq!(
+            config.receive_forwarder(&address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6")),
+            Some(address!("0x1111111111111111111111111111111111111111"))
+        );
+        assert_eq!(
+            config.receive_forwarder(&address!("0x2222222222222222222222222222222222222222")),
+            None
+        );
+        assert!(
+            Eip155ExactConfig::from_value(None)
+                .unwrap()
+                .tokens
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_permit2_proxy_migration() {
+        let new = address!("0x4444444444444444444444444444444444444444");
+        let old = address!("0x5555555555555555555555555555555555555555");
+        let config = Eip155ExactConfig::from_value(Some(serde_json::json!({
+            "permit2Proxies": [new, old]
+        })))
+        .unwrap();
+        assert_eq!(config.preferred_permit2_proxy(), new);
+        assert!(config.accepts_permit2_proxy(&new));
+        assert!(config.accepts_permit2_proxy(&old));
+        assert!(!config.accepts_permit2_proxy(&x402_exact_permit2_proxy_address()));
+        assert_eq!(
+            config.supported_extra(),
+            serde_json::json!({ "permit2Proxy": new })
+        );
+    }
+
+    #[test]
+    fn test_malformed_config_is_rejected() {
+        let config = Eip155ExactConfig::from_value(Some(serde_json::json!({
+            "graceBufferSeconds": 30
+        })))
+        .unwrap();
+        assert_eq!(config.grace_buffer_seconds, 30);
+        assert_eq!(
+            Eip155ExactConfig::from_value(None)
+                .unwrap()
+                .grace_buffer_seconds,
+            DEFAULT_GRACE_BUFFER_SECONDS
+        );
+
+        let error = Eip155ExactConfig::from_value(Some(serde_json::json!({
+            "permit2Proxy": "0x4444444444444444444444444444444444444444"
+        })))
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown field `permit2Proxy`"));
+        assert!(
+            Eip155ExactConfig::from_value(Some(serde_json::json!({
+                "permit2ProxyCodehashes": ["0x1234"]
+            })))
+            .is_err()
+        );
+        assert!(matches!(
+            parse_env_bool("X402_ENABLE_PERMIT2_ALLOWANCE_TRANSFER", "enabled"),
+            Err(Eip155ExactConfigError::Env { .. })
+        ));
+    }
+
+    #[test]
+    fn test_settle_validity_honours_chain_override_and_grace() {
+        let config = Eip155ExactConfig::from_value(Some(serde_json::json!({
+            "graceBufferSeconds": 10,
+            "minRemainingValiditySecs": 45
+        })))
+        .unwrap();
+        assert_eq!(config.settle_validity_secs(None), 45);
+        assert_eq!(config.settle_validity_secs(Some(120)), 120);
+        assert_eq!(config.settle_validity_secs(Some(2)), 10);
+        assert_eq!(
+            Eip155ExactConfig::default().settle_validity_secs(None),
+            DEFAULT_MIN_REMAINING_VALIDITY_SECS
+        );
+    }
+
+    #[test]
+    fn test_receive_nonce_binds_pay_to() {
+        use crate::v1_eip155_exact::{receive_nonce, receive_nonce_pay_to};
+
+        let pay_to = address!("0x3333333333333333333333333333333333333333");
+        let nonce = receive_nonce(pay_to, [7u8; 12]);
+        assert_eq!(receive_nonce_pay_to(&nonce), pay_to);
+        assert_eq!(&nonce[20..], &[7u8; 12]);
+    }
+}
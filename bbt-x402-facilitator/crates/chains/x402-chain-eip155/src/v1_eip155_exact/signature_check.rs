@@ -0,0 +1,276 @@
+//! Signature checks without payment requirements, for wallet developers.
+//!
+//! Smart accounts sign in many formats, and a payment rejected with
+//! `invalid_signature` does not tell which part was wrong. [`validate_signature`]
+//! runs only the signature path of verification over any EIP-712 typed data: the
+//! same [`StructuredSignature`] classification, then EOA recovery, an ERC-1271
+//! `isValidSignature` call, or EIP-6492 validation, without reading balances or
+//! simulating a transfer.
+
+use alloy_dyn_abi::TypedData;
+use alloy_network::TransactionBuilder;
+use alloy_primitives::{Address, B256, Bytes, Signature};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::TransactionRequest;
+use alloy_sol_types::SolCall;
+use serde::{Deserialize, Serialize};
+use x402_types::proto::PaymentVerificationError;
+
+use crate::chain::{Eip155ChainReference, Eip155Contracts};
+use crate::v1_eip155_exact::facilitator::{
+    ERC1271_MAGIC_VALUE, Eip155ExactError, IERC1271, StructuredSignature, Validator6492,
+    is_contract_deployed, verify_6492_without_validator,
+};
+
+/// A signature over EIP-712 typed data to check.
+///
+/// ```json
+/// { "typedData": { "domain": {}, "types": {}, "primaryType": "...", "message": {} }, "signature": "0x...", "signer": "0x...", "token": "0x..." }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureQuery {
+    /// The signed typed data, as passed to `eth_signTypedData_v4`.
+    pub typed_data: TypedData,
+    pub signature: Bytes,
+    /// Account the signature is claimed to be from. Required for contract wallets;
+    /// recovered from EOA signatures when absent.
+    #[serde(default)]
+    pub signer: Option<Address>,
+    /// Token the payment is in. When given, the typed data must be signed for the
+    /// token (ERC-3009) or for the chain's Permit2 contract.
+    #[serde(default)]
+    pub token: Option<Address>,
+}
+
+/// How a signature was validated, see [`StructuredSignature`].
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureKind {
+    /// Recovered to the signer.
+    Eoa,
+    /// Checked by the signer's `isValidSignature`.
+    Eip1271,
+    /// Checked through the EIP-6492 wrapper of a counterfactual wallet.
+    Eip6492,
+}
+
+/// Outcome of [`validate_signature`].
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureValidation {
+    /// `true` if the signature is a valid signature of `signer` over `hash`.
+    pub valid: bool,
+    /// Claimed or recovered signer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<SignatureKind>,
+    /// EIP-712 signing hash of the typed data.
+    pub hash: B256,
+    /// Why the signature is not valid.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl SignatureValidation {
+    fn valid(hash: B256, signer: Address, kind: SignatureKind) -> Self {
+        Self {
+            valid: true,
+            signer: Some(signer),
+            kind: Some(kind),
+            hash,
+            reason: None,
+        }
+    }
+
+    fn rejected(hash: B256, reason: impl Into<String>) -> Self {
+        Self {
+            valid: false,
+            signer: None,
+            kind: None,
+            hash,
+            reason: Some(reason.into()),
+        }
+    }
+
+    fn of(mut self, signer: Address, kind: SignatureKind) -> Self {
+        self.signer = Some(signer);
+        self.kind = Some(kind);
+        self
+    }
+}
+
+/// Checks `query.signature` over `query.typed_data` the way payment verification does.
+///
+/// Invalid signatures, typed data for another chain or contract, and malformed
+/// EIP-6492 wrappers are reported in the [`SignatureValidation`]; errors are left
+/// for typed data that cannot be hashed and nodes that cannot be queried.
+pub async fn validate_signature<P: Provider>(
+    provider: &P,
+    chain: &Eip155ChainReference,
+    contracts: &Eip155Contracts,
+    query: &SignatureQuery,
+) -> Result<SignatureValidation, Eip155ExactError> {
+    let hash = query
+        .typed_data
+        .eip712_signing_hash()
+        .map_err(|e| PaymentVerificationError::InvalidFormat(format!("Invalid typed data: {e}")))?;
+    let domain = &query.typed_data.domain;
+    if let Some(chain_id) = domain.chain_id
+        && chain_id != alloy_primitives::U256::from(chain.inner())
+    {
+        return Ok(SignatureValidation::rejected(
+            hash,
+            format!(
+                "typed data is signed for chain {chain_id}, not {}",
+                chain.inner()
+            ),
+        ));
+    }
+    if let Some(token) = query.token {
+        let verifying_contract = domain.verifying_contract;
+        if verifying_contract != Some(token) && verifying_contract != Some(contracts.permit2) {
+            return Ok(SignatureValidation::rejected(
+                hash,
+                "typed data is signed for neither the token nor Permit2",
+            ));
+        }
+    }
+
+    let signer = match query.signer {
+        Some(signer) => signer,
+        None => match recover_signer(&query.signature, &hash) {
+            Some(signer) => signer,
+            None => {
+                return Ok(SignatureValidation::rejected(
+                    hash,
+                    "signer is required for contract wallet signatures",
+                ));
+            }
+        },
+    };
+
+    let structured =
+        match StructuredSignature::try_from_bytes(query.signature.clone(), signer, &hash) {
+            Ok(structured) => structured,
+            Err(e) => {
+                return Ok(SignatureValidation::rejected(hash, e.to_string())
+                    .of(signer, SignatureKind::Eip6492));
+            }
+        };
+    match structured {
+        StructuredSignature::EOA(_) => {
+            Ok(SignatureValidation::valid(hash, signer, SignatureKind::Eoa))
+        }
+        StructuredSignature::EIP1271(signature) => {
+            if !is_contract_deployed(provider, &signer).await? {
+                return Ok(SignatureValidation::rejected(
+                    hash,
+                    "not a signature of the signer, which has no code to check it",
+                )
+                .of(signer, SignatureKind::Eip1271));
+            }
+            let call = IERC1271::isValidSignatureCall { hash, signature };
+            let request = TransactionRequest::default()
+                .with_to(signer)
+                .with_input(call.abi_encode());
+            let magic_value = provider.call(request).await.ok().and_then(|output| {
+                IERC1271::isValidSignatureCall::abi_decode_returns(&output).ok()
+            });
+            Ok(
+                if magic_value.map(|value| value.0) == Some(ERC1271_MAGIC_VALUE) {
+                    SignatureValidation::valid(hash, signer, SignatureKind::Eip1271)
+                } else {
+                    SignatureValidation::rejected(hash, "Wallet reported signature to be invalid")
+                        .of(signer, SignatureKind::Eip1271)
+                },
+            )
+        }
+        StructuredSignature::EIP6492 {
+            factory,
+            factory_calldata,
+            inner,
+            original,
+        } => {
+            let result = if is_contract_deployed(provider, &contracts.eip6492_validator).await? {
+                let validator = Validator6492::new(contracts.eip6492_validator, provider);
+                match validator
+                    .isValidSigWithSideEffects(signer, hash, original)
+                    .call()
+                    .await
+                {
+                    Ok(true) => Ok(()),
+                    Ok(false) => Err("Chain reported signature to be invalid".to_string()),
+                    Err(e) => Err(e.to_string()),
+                }
+            } else {
+                match verify_6492_without_validator(
+                    provider,
+                    signer,
+                    hash,
+                    factory,
+                    factory_calldata,
+                    inner,
+                    None,
+                )
+                .await
+                {
+                    Ok(()) => Ok(()),
+                    Err(Eip155ExactError::PaymentVerification(e)) => Err(e.to_string()),
+                    Err(e) => return Err(e),
+                }
+            };
+            Ok(match result {
+                Ok(()) => SignatureValidation::valid(hash, signer, SignatureKind::Eip6492),
+                Err(reason) => {
+                    SignatureValidation::rejected(hash, reason).of(signer, SignatureKind::Eip6492)
+                }
+            })
+        }
+    }
+}
+
+/// The signer of an EOA signature, in its 65-byte or ERC-2098 compact form.
+fn recover_signer(signature: &Bytes, hash: &B256) -> Option<Address> {
+    let signature = match signature.len() {
+        65 => Signature::from_raw(signature).ok()?,
+        64 => Signature::from_erc2098(signature),
+        _ => return None,
+    };
+    signature.recover_address_from_prehash(hash).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_query_json() {
+        let query: SignatureQuery = serde_json::from_value(serde_json::json!({
+            "typedData": {
+                "types": {
+                    "EIP712Domain": [
+                        { "name": "name", "type": "string" },
+                        { "name": "chainId", "type": "uint256" }
+                    ],
+                    "Ping": [{ "name": "value", "type": "uint256" }]
+                },
+                "primaryType": "Ping",
+                "domain": { "name": "Test", "chainId": 42793 },
+                "message": { "value": "1" }
+            },
+            "signature": "0x1234"
+        }))
+        .unwrap();
+        assert!(query.signer.is_none());
+        assert!(query.typed_data.eip712_signing_hash().is_ok());
+
+        let rejected = SignatureValidation::rejected(B256::ZERO, "bad")
+            .of(Address::ZERO, SignatureKind::Eip1271);
+        let json = serde_json::to_value(&rejected).unwrap();
+        assert_eq!(json["valid"], false);
+        assert_eq!(json["kind"], "eip1271");
+        assert_eq!(json["reason"], "bad");
+    }
+}
@@ -6,9 +6,14 @@
 use alloy_primitives::{Address, B256, Bytes, U256};
 use serde::{Deserialize, Serialize};
 use x402_types::lit_str;
+use x402_types::proto::display::DisplayMetadata;
 use x402_types::proto::v1;
 use x402_types::timestamp::UnixTimestamp;
 
+use crate::chain::Eip712DomainShape;
+use crate::discount::TokenGatedDiscount;
+use crate::stealth::StealthMetaAddress;
+
 #[cfg(any(feature = "facilitator", feature = "client"))]
 use alloy_sol_types::sol;
 
@@ -56,6 +61,20 @@ pub struct ExactEvmPayload {
     /// - The proxy enforces `witness.to == payTo` on-chain
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub permit2_authorization: Option<Permit2Authorization>,
+
+    /// Ephemeral key of a payment to a stealth address (V2 only), see [`crate::stealth`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stealth: Option<StealthPayload>,
+}
+
+/// Ephemeral key a client derived the stealth address of a payment from.
+///
+/// Lets the facilitator check that the signed recipient belongs to the
+/// `stealthMetaAddress` of the requirements.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StealthPayload {
+    pub ephemeral_private_key: B256,
 }
 
 /// EIP-712 structured data for ERC-3009 transfer authorization.
@@ -165,15 +184,107 @@ pub type PaymentRequirements =
 /// Some token contracts require specific `name` and `version` values in their
 /// EIP-712 domain for signature verification. This struct allows servers to
 /// specify these values in the payment requirements, avoiding the need for
-/// the facilitator to query them from the contract.
+/// the facilitator to query them from the contract. Tokens with a nonstandard
+/// domain also describe its shape, see [`Eip712DomainShape`].
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PaymentRequirementsExtra {
     /// The token name as used in the EIP-712 domain.
     pub name: String,
 
-    /// The token version as used in the EIP-712 domain.
+    /// The token version as used in the EIP-712 domain. May be left out with
+    /// `omitVersion`.
+    #[serde(default)]
     pub version: String,
+
+    /// Deviations from the standard EIP-712 domain, flattened into `extra`.
+    #[serde(flatten)]
+    pub domain: Eip712DomainShape,
+
+    /// Forwarder settling this token with `receiveWithAuthorization`.
+    ///
+    /// When set, the client signs a `ReceiveWithAuthorization` to the forwarder
+    /// instead of a `TransferWithAuthorization` to `payTo`, and binds `payTo`
+    /// into the nonce, see [`receive_nonce`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receive_forwarder: Option<Address>,
+
+    /// ERC-5564 meta-address the payment may go to instead of `payTo` (V2 only).
+    ///
+    /// Clients supporting stealth addresses pay to a one-time address derived from
+    /// it, see [`crate::stealth`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stealth_meta_address: Option<StealthMetaAddress>,
+
+    /// Lower amounts for holders of a token (V2 only), see [`crate::discount`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub discounts: Vec<TokenGatedDiscount>,
+
+    /// How the signed amount must compare to the required one. Exact by default.
+    #[serde(default, skip_serializing_if = "AmountMatching::is_exact")]
+    pub amount_matching: AmountMatching,
+
+    /// Symbol, logo and formatted amounts for payment prompts. Informational only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<DisplayMetadata>,
+}
+
+#[cfg(any(feature = "facilitator", feature = "client"))]
+impl PaymentRequirementsExtra {
+    /// The token's EIP-712 domain, as described by the requirements.
+    pub fn token_domain(
+        &self,
+        chain: &crate::chain::Eip155ChainReference,
+        asset: Address,
+    ) -> alloy_sol_types::Eip712Domain {
+        crate::v1_eip155_exact::digest::shaped_token_domain(
+            chain,
+            asset,
+            &self.name,
+            &self.version,
+            &self.domain,
+        )
+    }
+}
+
+/// How the amount a payer signed for is matched against the required amount.
+///
+/// Settlements transfer the signed amount and report it as `settledAmount`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AmountMatching {
+    /// The signed amount must equal the required amount.
+    #[default]
+    Exact,
+    /// Any signed amount at or above the required amount is accepted, for merchants
+    /// tolerating overpayment such as client rounding.
+    AtLeast,
+    /// The required amount is a maximum: any non-zero signed amount up to it is
+    /// accepted, for pay-what-you-use resources charging for what was used.
+    AtMost,
+}
+
+impl AmountMatching {
+    /// Whether the signed amount must equal the required amount, the default.
+    pub fn is_exact(&self) -> bool {
+        *self == AmountMatching::Exact
+    }
+}
+
+/// Builds an ERC-3009 nonce bound to the final recipient of a forwarded payment.
+///
+/// The first 20 bytes are `pay_to`, the remaining 12 bytes are random `salt`.
+/// The receive forwarder refuses to forward to any other address.
+pub fn receive_nonce(pay_to: Address, salt: [u8; 12]) -> B256 {
+    let mut nonce = [0u8; 32];
+    nonce[..20].copy_from_slice(pay_to.as_slice());
+    nonce[20..].copy_from_slice(&salt);
+    B256::from(nonce)
+}
+
+/// Returns the recipient bound into a nonce built by [`receive_nonce`].
+pub fn receive_nonce_pay_to(nonce: &B256) -> Address {
+    Address::from_slice(&nonce[..20])
 }
 
 #[cfg(any(feature = "facilitator", feature = "client"))]
@@ -198,6 +309,23 @@ sol!(
     }
 );
 
+#[cfg(any(feature = "facilitator", feature = "client"))]
+sol!(
+    /// Solidity-compatible struct definition for ERC-3009 `receiveWithAuthorization`.
+    ///
+    /// Same fields as [`TransferWithAuthorization`], but a different EIP-712 type,
+    /// and the token only accepts it when `msg.sender == to`.
+    #[derive(Serialize, Deserialize)]
+    struct ReceiveWithAuthorization {
+        address from;
+        address to;
+        uint256 value;
+        uint256 validAfter;
+        uint256 validBefore;
+        bytes32 nonce;
+    }
+);
+
 #[cfg(any(feature = "facilitator", feature = "client"))]
 sol!(
     /// Solidity-compatible struct for Permit2 `PermitDetails`.
@@ -255,3 +383,35 @@ sol!(
         Witness witness;
     }
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount_matching_wire_format() {
+        let extra: PaymentRequirementsExtra =
+            serde_json::from_str(r#"{"name":"BBT","version":"1"}"#).unwrap();
+        assert_eq!(extra.amount_matching, AmountMatching::Exact);
+        assert!(
+            serde_json::to_value(&extra)
+                .unwrap()
+                .get("amountMatching")
+                .is_none()
+        );
+
+        let extra: PaymentRequirementsExtra =
+            serde_json::from_str(r#"{"name":"BBT","version":"1","amountMatching":"atLeast"}"#)
+                .unwrap();
+        assert_eq!(extra.amount_matching, AmountMatching::AtLeast);
+        assert_eq!(
+            serde_json::to_value(&extra).unwrap()["amountMatching"],
+            "atLeast"
+        );
+
+        let extra: PaymentRequirementsExtra =
+            serde_json::from_str(r#"{"name":"BBT","version":"1","amountMatching":"atMost"}"#)
+                .unwrap();
+        assert_eq!(extra.amount_matching, AmountMatching::AtMost);
+    }
+}
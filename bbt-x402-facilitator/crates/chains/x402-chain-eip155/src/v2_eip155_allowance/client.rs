@@ -0,0 +1,203 @@
+//! Client-side payment signing for the V2 EIP-155 "allowance" scheme.
+//!
+//! This module provides [`V2Eip155AllowanceClient`] for signing payment intents
+//! pulled from an ERC-20 allowance. The payer must have approved the `spender`
+//! named by the requirements for the token beforehand, with `approve`.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! use x402_chain_eip155::v2_eip155_allowance::client::V2Eip155AllowanceClient;
+//! use alloy_signer_local::PrivateKeySigner;
+//!
+//! let signer = PrivateKeySigner::random();
+//! let client = V2Eip155AllowanceClient::new(signer);
+//! ```
+
+use alloy_primitives::{Bytes, FixedBytes};
+use async_trait::async_trait;
+use rand::{Rng, rng};
+use x402_types::proto::v2::ResourceInfo;
+use x402_types::proto::{PaymentRequired, v2};
+use x402_types::scheme::X402SchemeId;
+use x402_types::scheme::client::{
+    PaymentCandidate, PaymentCandidateSigner, ValidityWindow, X402Error, X402SchemeClient,
+};
+use x402_types::timestamp::UnixTimestamp;
+use x402_types::util::Base64Bytes;
+
+use crate::chain::Eip155ChainReference;
+use crate::v1_eip155_exact::ExactEvmPayloadAuthorization;
+use crate::v1_eip155_exact::client::SignerLike;
+use crate::v2_eip155_allowance::{
+    AllowanceEvmPayload, V2Eip155Allowance, allowance_domain, allowance_payment_hash, types,
+};
+
+/// Client for signing V2 EIP-155 allowance scheme payments.
+///
+/// # Type Parameters
+///
+/// - `S`: The signer type, which must implement [`SignerLike`]
+#[derive(Debug)]
+#[allow(dead_code)] // Public for consumption by downstream crates.
+pub struct V2Eip155AllowanceClient<S> {
+    signer: S,
+}
+
+#[allow(dead_code)] // Public for consumption by downstream crates.
+impl<S> V2Eip155AllowanceClient<S> {
+    /// Creates a new V2 EIP-155 allowance scheme client with the given signer.
+    pub fn new(signer: S) -> Self {
+        Self { signer }
+    }
+}
+
+impl<S> X402SchemeId for V2Eip155AllowanceClient<S> {
+    fn namespace(&self) -> &str {
+        V2Eip155Allowance.namespace()
+    }
+
+    fn scheme(&self) -> &str {
+        V2Eip155Allowance.scheme()
+    }
+}
+
+impl<S> X402SchemeClient for V2Eip155AllowanceClient<S>
+where
+    S: SignerLike + Clone + Send + Sync + 'static,
+{
+    fn accept(&self, payment_required: &PaymentRequired) -> Vec<PaymentCandidate> {
+        let payment_required = match payment_required {
+            PaymentRequired::V2(payment_required) => payment_required,
+            PaymentRequired::V1(_) => {
+                return vec![];
+            }
+        };
+        payment_required
+            .accepts
+            .iter()
+            .filter_map(|v| {
+                let requirements: types::PaymentRequirements = v.as_concrete()?;
+                // Without a spender there is no allowance to pull from.
+                requirements.extra.as_ref()?.spender?;
+                let chain_reference = Eip155ChainReference::try_from(&requirements.network).ok()?;
+                let candidate = PaymentCandidate {
+                    chain_id: requirements.network.clone(),
+                    asset: requirements.asset.to_string(),
+                    amount: requirements.amount.into(),
+                    scheme: self.scheme().to_string(),
+                    x402_version: self.x402_version(),
+                    pay_to: requirements.pay_to.to_string(),
+                    transfer_method: Some("allowance".to_string()),
+                    signer: Box::new(PayloadSigner {
+                        resource_info: Some(payment_required.resource.clone()),
+                        signer: self.signer.clone(),
+                        chain_reference,
+                        requirements,
+                    }),
+                };
+                Some(candidate)
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+/// Signs an [`AllowancePayment`](crate::v2_eip155_allowance::AllowancePayment) for
+/// `requirements`.
+///
+/// Without an explicit `validity`, the payment is valid from ten minutes ago until
+/// `maxTimeoutSeconds` from now.
+#[allow(dead_code)] // Public for consumption by downstream crates.
+pub async fn sign_allowance_payment<S: SignerLike + Sync>(
+    signer: &S,
+    chain_reference: &Eip155ChainReference,
+    requirements: &types::PaymentRequirements,
+    validity: Option<ValidityWindow>,
+) -> Result<AllowanceEvmPayload, X402Error> {
+    let spender = requirements
+        .extra
+        .as_ref()
+        .and_then(|extra| extra.spender)
+        .ok_or_else(|| X402Error::SigningError("Missing extra.spender".to_string()))?;
+    let (valid_after, valid_before) = match validity {
+        Some(window) => (window.valid_after, window.valid_before),
+        None => {
+            let now = UnixTimestamp::now();
+            (
+                UnixTimestamp::from_secs(now.as_secs().saturating_sub(10 * 60)),
+                now + requirements.max_timeout_seconds,
+            )
+        }
+    };
+    let authorization = ExactEvmPayloadAuthorization {
+        from: signer.address(),
+        to: requirements.pay_to.address(),
+        value: requirements.amount.into(),
+        valid_after,
+        valid_before,
+        nonce: FixedBytes(rng().random()),
+    };
+    let hash = allowance_payment_hash(
+        &authorization,
+        requirements.asset.address(),
+        &allowance_domain(chain_reference, spender),
+    );
+    let signature = signer
+        .sign_hash(&hash)
+        .await
+        .map_err(|e| X402Error::SigningError(format!("{e:?}")))?;
+    Ok(AllowanceEvmPayload {
+        signature: Bytes::from(signature.as_bytes().to_vec()),
+        authorization,
+    })
+}
+
+#[allow(dead_code)] // Public for consumption by downstream crates.
+struct PayloadSigner<S> {
+    signer: S,
+    resource_info: Option<ResourceInfo>,
+    chain_reference: Eip155ChainReference,
+    requirements: types::PaymentRequirements,
+}
+
+#[async_trait]
+impl<S> PaymentCandidateSigner for PayloadSigner<S>
+where
+    S: Sync + SignerLike,
+{
+    async fn sign_payment(&self) -> Result<String, X402Error> {
+        self.sign_with_validity(None).await
+    }
+
+    async fn sign_payment_within(&self, window: ValidityWindow) -> Result<String, X402Error> {
+        self.sign_with_validity(Some(window)).await
+    }
+}
+
+impl<S> PayloadSigner<S>
+where
+    S: Sync + SignerLike,
+{
+    async fn sign_with_validity(
+        &self,
+        validity: Option<ValidityWindow>,
+    ) -> Result<String, X402Error> {
+        let allowance_payload = sign_allowance_payment(
+            &self.signer,
+            &self.chain_reference,
+            &self.requirements,
+            validity,
+        )
+        .await?;
+        let payload = types::PaymentPayload {
+            x402_version: v2::X402Version2,
+            accepted: self.requirements.clone(),
+            resource: self.resource_info.clone(),
+            payload: allowance_payload,
+        };
+        let json = serde_json::to_vec(&payload)?;
+        let b64 = Base64Bytes::encode(&json);
+
+        Ok(b64.to_string())
+    }
+}
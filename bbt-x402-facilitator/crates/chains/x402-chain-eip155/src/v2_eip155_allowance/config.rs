@@ -0,0 +1,104 @@
+//! Scheme configuration for the EIP-155 "allowance" facilitator.
+//!
+//! Payers approve the facilitator once per token, so the facilitator can restrict
+//! which tokens it pulls. Without `tokens`, any ERC-20 token is accepted.
+//!
+//! ```json
+//! {
+//!   "id": "v2-eip155-allowance",
+//!   "chains": "eip155:42793",
+//!   "config": {
+//!     "tokens": ["0x..."],
+//!     "graceBufferSeconds": 6
+//!   }
+//! }
+//! ```
+//!
+//! The config section is optional. Unknown keys and malformed values fail the scheme
+//! build with an [`Eip155AllowanceConfigError`].
+
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+use x402_types::timestamp::SharedClock;
+
+use crate::v1_eip155_exact::settlement::DEFAULT_GRACE_BUFFER_SECONDS;
+
+/// Errors in the scheme configuration of the EIP-155 "allowance" facilitator.
+#[derive(Debug, thiserror::Error)]
+pub enum Eip155AllowanceConfigError {
+    #[error("Invalid allowance scheme config: {0}")]
+    Invalid(#[from] serde_json::Error),
+}
+
+/// Scheme configuration for the EIP-155 "allowance" facilitator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Eip155AllowanceConfig {
+    /// Tokens the facilitator pulls. Empty means any token.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<Address>,
+    /// Seconds an authorization must remain valid past now, covering the time to
+    /// settle it.
+    #[serde(default = "default_grace_buffer_seconds")]
+    pub grace_buffer_seconds: u64,
+    /// Clock the time windows are checked against; the system clock outside tests.
+    #[serde(skip)]
+    pub clock: SharedClock,
+}
+
+impl Default for Eip155AllowanceConfig {
+    fn default() -> Self {
+        Self {
+            tokens: Vec::new(),
+            grace_buffer_seconds: DEFAULT_GRACE_BUFFER_SECONDS,
+            clock: SharedClock::default(),
+        }
+    }
+}
+
+fn default_grace_buffer_seconds() -> u64 {
+    DEFAULT_GRACE_BUFFER_SECONDS
+}
+
+impl Eip155AllowanceConfig {
+    /// Parses the scheme-specific `config` value. A missing value means defaults.
+    pub fn from_value(
+        value: Option<serde_json::Value>,
+    ) -> Result<Self, Eip155AllowanceConfigError> {
+        Ok(value
+            .map(serde_json::from_value)
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    /// Returns whether the facilitator pulls payments in `token`.
+    pub fn accepts_token(&self, token: &Address) -> bool {
+        self.tokens.is_empty() || self.tokens.contains(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn test_token_allowlist() {
+        let token = address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6");
+        let config = Eip155AllowanceConfig::from_value(None).unwrap();
+        assert!(config.accepts_token(&token));
+        assert_eq!(config.grace_buffer_seconds, DEFAULT_GRACE_BUFFER_SECONDS);
+
+        let config = Eip155AllowanceConfig::from_value(Some(serde_json::json!({
+            "tokens": [token]
+        })))
+        .unwrap();
+        assert!(config.accepts_token(&token));
+        assert!(!config.accepts_token(&Address::ZERO));
+
+        assert!(matches!(
+            Eip155AllowanceConfig::from_value(Some(serde_json::json!({ "token": token }))),
+            Err(Eip155AllowanceConfigError::Invalid(_))
+        ));
+    }
+}
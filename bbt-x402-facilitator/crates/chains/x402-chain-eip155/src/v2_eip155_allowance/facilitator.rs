@@ -0,0 +1,493 @@
+//! Facilitator-side payment verification and settlement for the V2 EIP-155 allowance scheme.
+//!
+//! Payments are checked against the requirements, the payer's signed intent, and
+//! the payer's balance and allowance to the spender, then `transferFrom` is
+//! simulated from the spender. Settlement sends the same call from the spender.
+//!
+//! Tokens cannot tell one signed intent from another, so the facilitator remembers
+//! the nonces it has settled until their `validBefore` has passed, see
+//! [`AllowanceNonces`]. They are kept in memory: facilitator instances sharing a
+//! spender key must not settle the same payments.
+
+use alloy_contract::SolCallBuilder;
+use alloy_network::TransactionBuilder;
+use alloy_primitives::{Address, B256, Bytes, Signature, U256};
+use alloy_provider::{MulticallItem, Provider};
+use alloy_rpc_types_eth::{TransactionReceipt, TransactionRequest};
+use alloy_sol_types::SolCall;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+use x402_types::chain::{ChainId, ChainProviderOps};
+use x402_types::proto;
+use x402_types::proto::{PaymentVerificationError, v2};
+use x402_types::scheme::{
+    X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError,
+};
+use x402_types::timestamp::{Clock, UnixTimestamp};
+
+#[cfg(feature = "telemetry")]
+use tracing::instrument;
+#[cfg(feature = "telemetry")]
+use tracing_core::Level;
+
+use crate::chain::{
+    DryRunProvider, Eip155ChainReference, Eip155MetaTransactionProvider, MetaTransaction,
+    MetaTransactionSendError, PayTo, PayerAddress,
+};
+use crate::v1_eip155_exact::facilitator::{
+    ERC1271_MAGIC_VALUE, Eip155ExactError, IEIP3009, IERC1271, assert_enough_value, assert_time,
+    fetch_token_state, join_spenders, settlement_receipt,
+};
+use crate::v2_eip155_allowance::{
+    AllowanceScheme, Eip155AllowanceConfig, V2Eip155Allowance, allowance_domain,
+    allowance_payment_hash, types,
+};
+
+impl<P> X402SchemeFacilitatorBuilder<P> for V2Eip155Allowance
+where
+    P: Eip155MetaTransactionProvider + ChainProviderOps + Send + Sync + 'static,
+    Eip155ExactError: From<P::Error>,
+{
+    fn build(
+        &self,
+        provider: P,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
+        let config = Eip155AllowanceConfig::from_value(config)?;
+        Ok(Box::new(V2Eip155AllowanceFacilitator::new(
+            provider, config,
+        )))
+    }
+}
+
+/// Facilitator for V2 EIP-155 allowance scheme payments.
+///
+/// # Type Parameters
+///
+/// - `P`: The provider type, which must implement [`Eip155MetaTransactionProvider`]
+///   and [`ChainProviderOps`]
+pub struct V2Eip155AllowanceFacilitator<P> {
+    provider: P,
+    config: Eip155AllowanceConfig,
+    nonces: AllowanceNonces,
+}
+
+impl<P> V2Eip155AllowanceFacilitator<P> {
+    /// Creates a new V2 EIP-155 allowance scheme facilitator.
+    pub fn new(provider: P, config: Eip155AllowanceConfig) -> Self {
+        Self {
+            provider,
+            config,
+            nonces: AllowanceNonces::default(),
+        }
+    }
+}
+
+impl<P: ChainProviderOps> V2Eip155AllowanceFacilitator<P> {
+    /// The facilitator signers, which are the spenders payers may approve.
+    fn spenders(&self) -> Result<Vec<Address>, Eip155ExactError> {
+        self.provider
+            .signer_addresses()
+            .iter()
+            .map(|signer| {
+                Address::from_str(signer).map_err(|_| {
+                    PaymentVerificationError::InvalidFormat("Invalid signer address".to_string())
+                        .into()
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl<P> X402SchemeFacilitator for V2Eip155AllowanceFacilitator<P>
+where
+    P: Eip155MetaTransactionProvider + ChainProviderOps + Send + Sync,
+    P::Inner: Provider,
+    Eip155ExactError: From<P::Error>,
+{
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
+        let request = types::VerifyRequest::from_proto(request)?;
+        let payment = assert_valid_payment(
+            self.provider.inner(),
+            self.provider.chain(),
+            &request.payment_payload,
+            &request.payment_requirements,
+            &self.config,
+            &self.spenders()?,
+        )
+        .await?;
+        self.nonces.assert_unused(&payment)?;
+        let payer = verify_payment(self.provider.inner(), &payment).await?;
+        Ok(v2::VerifyResponse::valid(payer.to_string()).into())
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let required = self.provider.required_confirmations();
+        let confirmations = match request.confirmation_policy() {
+            proto::ConfirmationPolicy::Confirmed => required,
+            proto::ConfirmationPolicy::Pending => 1,
+        };
+        let request = types::SettleRequest::from_proto(request)?;
+        let payload = &request.payment_payload;
+        let payment = assert_valid_payment(
+            self.provider.inner(),
+            self.provider.chain(),
+            payload,
+            &request.payment_requirements,
+            &self.config,
+            &self.spenders()?,
+        )
+        .await?;
+        self.nonces.consume(&payment, self.config.clock.now())?;
+        let receipt = match settle_payment(&self.provider, &payment, confirmations).await {
+            Ok(receipt) => receipt,
+            Err(e) => {
+                self.nonces.release(&payment);
+                return Err(e.into());
+            }
+        };
+        Ok(v2::SettleResponse::Success {
+            payer: payment.from.to_string(),
+            transaction: receipt.transaction_hash.to_string(),
+            network: payload.accepted.network.to_string(),
+            receipt: settlement_receipt(&receipt, confirmations, required),
+        }
+        .into())
+    }
+
+    async fn settle_dry_run(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleDryRunResponse, X402SchemeFacilitatorError> {
+        self.verify(request).await?;
+        let request = types::SettleRequest::from_proto(request)?;
+        let payload = &request.payment_payload;
+        let payment = assert_valid_payment(
+            self.provider.inner(),
+            self.provider.chain(),
+            payload,
+            &request.payment_requirements,
+            &self.config,
+            &self.spenders()?,
+        )
+        .await?;
+        let dry_run = DryRunProvider::new(&self.provider, payment.spender);
+        settle_payment::<_, MetaTransactionSendError>(&dry_run, &payment, 1).await?;
+        let report = dry_run
+            .finish(payment.from, payload.accepted.network.to_string())
+            .await
+            .map_err(<Eip155ExactError as From<MetaTransactionSendError>>::from)?;
+        Ok(proto::SettleDryRunResponse(
+            serde_json::to_value(report).expect("SettlementDryRun serialization failed"),
+        ))
+    }
+
+    async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
+        let chain_id = self.provider.chain_id();
+        let spender = self.spenders()?.first().copied();
+        let kinds = vec![proto::SupportedPaymentKind {
+            x402_version: v2::X402Version2.into(),
+            scheme: AllowanceScheme.to_string(),
+            network: chain_id.clone().into(),
+            extra: spender.map(|spender| serde_json::json!({ "spender": spender })),
+        }];
+        let signers = {
+            let mut signers = HashMap::with_capacity(1);
+            signers.insert(chain_id, self.provider.signer_addresses());
+            signers
+        };
+        Ok(proto::SupportedResponse {
+            kinds,
+            extensions: Vec::new(),
+            signers,
+        })
+    }
+}
+
+/// Nonces of settled allowance payments, by payer, kept until the payment expires.
+#[derive(Debug, Default)]
+pub struct AllowanceNonces {
+    used: Mutex<HashMap<(Address, B256), UnixTimestamp>>,
+}
+
+impl AllowanceNonces {
+    /// Fails if the nonce of `payment` was settled or is being settled.
+    pub fn assert_unused(&self, payment: &AllowanceEvmPayment) -> Result<(), Eip155ExactError> {
+        let used = self.used.lock().expect("allowance nonces poisoned");
+        if used.contains_key(&(payment.from.address(), payment.nonce)) {
+            return Err(nonce_used());
+        }
+        Ok(())
+    }
+
+    /// Marks the nonce of `payment` as used, failing if it already is.
+    ///
+    /// Nonces of payments expired at `now` are forgotten, since those payments fail
+    /// the time window check anyway.
+    pub fn consume(
+        &self,
+        payment: &AllowanceEvmPayment,
+        now: UnixTimestamp,
+    ) -> Result<(), Eip155ExactError> {
+        let mut used = self.used.lock().expect("allowance nonces poisoned");
+        used.retain(|_, valid_before| *valid_before > now);
+        if used
+            .insert(
+                (payment.from.address(), payment.nonce),
+                payment.valid_before,
+            )
+            .is_some()
+        {
+            return Err(nonce_used());
+        }
+        Ok(())
+    }
+
+    /// Forgets the nonce of `payment` after its settlement failed.
+    pub fn release(&self, payment: &AllowanceEvmPayment) {
+        self.used
+            .lock()
+            .expect("allowance nonces poisoned")
+            .remove(&(payment.from.address(), payment.nonce));
+    }
+}
+
+fn nonce_used() -> Eip155ExactError {
+    PaymentVerificationError::InvalidSignature("Nonce already used".to_string()).into()
+}
+
+/// A validated allowance payment, ready to be simulated or settled.
+#[derive(Debug)]
+pub struct AllowanceEvmPayment {
+    /// Payer the token is pulled from.
+    pub from: PayerAddress,
+    /// Recipient of the token.
+    pub pay_to: PayTo,
+    /// Token pulled.
+    pub token: Address,
+    /// Amount, in the token's smallest unit.
+    pub value: U256,
+    /// Not valid at/after this timestamp.
+    pub valid_before: UnixTimestamp,
+    /// Unique 32-byte nonce (prevents replay).
+    pub nonce: B256,
+    /// Facilitator signer the payer approved, which sends `transferFrom`.
+    pub spender: Address,
+}
+
+/// Runs all preconditions needed for a successful allowance payment:
+/// - Valid scheme, network and receiver.
+/// - Token accepted by the facilitator, and spender one of its signers.
+/// - Exact amount and valid time window.
+/// - Signature of the payer over the signed intent (EOA or EIP-1271).
+/// - Sufficient balance, and allowance to the spender.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err))]
+pub async fn assert_valid_payment<P: Provider>(
+    provider: &P,
+    chain: &Eip155ChainReference,
+    payload: &types::PaymentPayload,
+    requirements: &types::PaymentRequirements,
+    config: &Eip155AllowanceConfig,
+    spenders: &[Address],
+) -> Result<AllowanceEvmPayment, Eip155ExactError> {
+    let accepted = &payload.accepted;
+    if accepted != requirements {
+        return Err(PaymentVerificationError::AcceptedRequirementsMismatch.into());
+    }
+    let chain_id: ChainId = chain.into();
+    if accepted.network != chain_id {
+        return Err(PaymentVerificationError::ChainIdMismatch.into());
+    }
+    let token = accepted.asset.address();
+    if !config.accepts_token(&token) {
+        return Err(PaymentVerificationError::UnsupportedAsset.into());
+    }
+    let spender = accepted
+        .extra
+        .as_ref()
+        .and_then(|extra| extra.spender)
+        .ok_or_else(|| {
+            PaymentVerificationError::InvalidFormat("Missing extra.spender".to_string())
+        })?;
+    if !spenders.contains(&spender) {
+        return Err(
+            PaymentVerificationError::recipient_mismatch(join_spenders(spenders), spender).into(),
+        );
+    }
+
+    let authorization = &payload.payload.authorization;
+    let pay_to = PayTo(accepted.pay_to.address());
+    if PayTo(authorization.to) != pay_to {
+        return Err(PaymentVerificationError::recipient_mismatch(pay_to, authorization.to).into());
+    }
+    let amount_required: U256 = accepted.amount.into();
+    assert_enough_value(&authorization.value, &amount_required)?;
+    assert_time(
+        authorization.valid_after,
+        authorization.valid_before,
+        config.grace_buffer_seconds,
+        &config.clock,
+    )?;
+
+    let payer = authorization.from;
+    let signature = &payload.payload.signature;
+    let hash = allowance_payment_hash(authorization, token, &allowance_domain(chain, spender));
+    assert_valid_signature(provider, payer, hash, signature).await?;
+
+    let token_state =
+        fetch_token_state(provider, token, PayerAddress(payer), Some(spender), false).await?;
+    token_state.assert_enough_balance(amount_required)?;
+    let allowance = token_state.allowance.unwrap_or_default();
+    if allowance < amount_required {
+        return Err(PaymentVerificationError::TransactionSimulation(format!(
+            "ERC20 allowance to {spender} is insufficient: {allowance} < {amount_required}"
+        ))
+        .into());
+    }
+
+    Ok(AllowanceEvmPayment {
+        from: PayerAddress(payer),
+        pay_to,
+        token,
+        value: authorization.value,
+        valid_before: authorization.valid_before,
+        nonce: authorization.nonce,
+        spender,
+    })
+}
+
+/// Checks that `signature` over `hash` is from `payer`, recovering it for an EOA and
+/// calling `isValidSignature` for a deployed contract wallet.
+async fn assert_valid_signature<P: Provider>(
+    provider: &P,
+    payer: Address,
+    hash: B256,
+    signature: &Bytes,
+) -> Result<(), Eip155ExactError> {
+    let is_eoa_signature = signature.len() == 65
+        && Signature::from_raw(signature)
+            .ok()
+            .and_then(|signature| signature.recover_address_from_prehash(&hash).ok())
+            == Some(payer);
+    if is_eoa_signature {
+        return Ok(());
+    }
+    let invalid = || {
+        PaymentVerificationError::InvalidSignature("Signature does not match the payer".to_string())
+    };
+    if provider.get_code_at(payer).await?.is_empty() {
+        return Err(invalid().into());
+    }
+    let call = IERC1271::isValidSignatureCall {
+        hash,
+        signature: signature.clone(),
+    };
+    let request = TransactionRequest::default()
+        .with_to(payer)
+        .with_input(call.abi_encode());
+    let magic_value = provider
+        .call(request)
+        .await
+        .ok()
+        .and_then(|output| IERC1271::isValidSignatureCall::abi_decode_returns(&output).ok());
+    if magic_value.map(|value| value.0) != Some(ERC1271_MAGIC_VALUE) {
+        return Err(invalid().into());
+    }
+    Ok(())
+}
+
+/// Builds the token call settling `payment`.
+fn transfer_from<'a, P: Provider>(
+    token: &'a IEIP3009::IEIP3009Instance<P>,
+    payment: &AllowanceEvmPayment,
+) -> SolCallBuilder<&'a P, IEIP3009::transferFromCall> {
+    token.transferFrom(
+        payment.from.address(),
+        payment.pay_to.address(),
+        payment.value,
+    )
+}
+
+/// Verifies an allowance payment by simulating `transferFrom` from the spender.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
+    from = %payment.from,
+    pay_to = %payment.pay_to,
+    token = %payment.token,
+)))]
+pub async fn verify_payment<P: Provider>(
+    provider: &P,
+    payment: &AllowanceEvmPayment,
+) -> Result<PayerAddress, Eip155ExactError> {
+    let token = IEIP3009::new(payment.token, provider);
+    let transferred = transfer_from(&token, payment)
+        .from(payment.spender)
+        .call()
+        .await
+        .map_err(|e| PaymentVerificationError::TransactionSimulation(e.to_string()))?;
+    if !transferred {
+        return Err(PaymentVerificationError::TransactionSimulation(
+            "transferFrom returned false".to_string(),
+        )
+        .into());
+    }
+    Ok(payment.from)
+}
+
+/// Pulls an allowance payment with `transferFrom`, sent by its spender and waiting
+/// for `confirmations` blocks.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
+    from = %payment.from,
+    pay_to = %payment.pay_to,
+    token = %payment.token,
+)))]
+pub async fn settle_payment<P, E>(
+    provider: &P,
+    payment: &AllowanceEvmPayment,
+    confirmations: u64,
+) -> Result<TransactionReceipt, Eip155ExactError>
+where
+    P: Eip155MetaTransactionProvider<Error = E>,
+    Eip155ExactError: From<E>,
+{
+    let token = IEIP3009::new(payment.token, provider.inner());
+    let call = transfer_from(&token, payment);
+    let meta_transaction = MetaTransaction {
+        to: call.target(),
+        calldata: call.calldata().clone(),
+        confirmations,
+    };
+    let receipt = Eip155MetaTransactionProvider::send_transaction_from(
+        provider,
+        meta_transaction,
+        payment.spender,
+    )
+    .await?;
+    if receipt.status() {
+        #[cfg(feature = "telemetry")]
+        tracing::event!(Level::INFO,
+            status = "ok",
+            tx = %receipt.transaction_hash,
+            "transferFrom succeeded"
+        );
+        Ok(receipt)
+    } else {
+        #[cfg(feature = "telemetry")]
+        tracing::event!(
+            Level::WARN,
+            status = "failed",
+            tx = %receipt.transaction_hash,
+            "transferFrom failed"
+        );
+        Err(Eip155ExactError::TransactionReverted(
+            receipt.transaction_hash,
+        ))
+    }
+}
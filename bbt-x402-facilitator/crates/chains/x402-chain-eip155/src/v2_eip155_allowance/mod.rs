@@ -0,0 +1,72 @@
+//! V2 EIP-155 "allowance" payment scheme implementation.
+//!
+//! This module implements payments in ERC-20 tokens that support neither ERC-3009,
+//! EIP-2612 nor a Permit2 approval, for the V2 x402 protocol. The payer approves a
+//! facilitator signer once, and the facilitator pulls each payment from that
+//! allowance:
+//!
+//! 1. The payer calls `approve(spender, amount)` on the token, ahead of any payment,
+//!    for the `spender` advertised by the facilitator on `/supported`.
+//! 2. For each payment, the payer signs an EIP-712 [`AllowancePayment`] under a
+//!    domain bound to the spender (see [`allowance_domain`]).
+//! 3. The facilitator checks the signature and sends `transferFrom(from, to, value)`
+//!    from the spender.
+//!
+//! The token does not see the signed intent, so replay protection is up to the
+//! facilitator, which remembers settled nonces until they expire, see
+//! [`AllowanceNonces`](facilitator::AllowanceNonces).
+//!
+//! # Features
+//!
+//! - EOA signatures, and EIP-1271 signatures of deployed smart wallets
+//! - Balance and allowance checks before settlement
+//! - Settlement simulated during verification
+//! - Optional token allowlist, see [`config`]
+//!
+//! # Usage
+//!
+//! ```ignore
+//! use x402_chain_eip155::v2_eip155_allowance::V2Eip155Allowance;
+//!
+//! let price = V2Eip155Allowance::price_tag(
+//!     "0x1234...",  // pay_to address
+//!     token.amount(1_000_000u64),
+//! );
+//! ```
+
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "server")]
+#[allow(unused_imports)]
+pub use server::*;
+
+#[cfg(feature = "facilitator")]
+pub mod config;
+#[cfg(feature = "facilitator")]
+pub use config::*;
+#[cfg(feature = "facilitator")]
+pub mod facilitator;
+#[cfg(feature = "facilitator")]
+pub use facilitator::*;
+
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "client")]
+pub use client::*;
+
+pub mod types;
+pub use types::*;
+
+use x402_types::scheme::X402SchemeId;
+
+pub struct V2Eip155Allowance;
+
+impl X402SchemeId for V2Eip155Allowance {
+    fn namespace(&self) -> &str {
+        "eip155"
+    }
+
+    fn scheme(&self) -> &str {
+        AllowanceScheme.as_ref()
+    }
+}
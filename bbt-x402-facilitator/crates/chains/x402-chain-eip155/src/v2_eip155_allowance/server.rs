@@ -0,0 +1,112 @@
+//! Server-side price tag generation for the V2 EIP-155 allowance scheme.
+
+use alloy_primitives::U256;
+use std::sync::Arc;
+use x402_types::chain::{ChainId, DeployedTokenAmount};
+use x402_types::proto;
+use x402_types::proto::v2;
+
+use crate::chain::{ChecksummedAddress, Eip155TokenDeployment};
+use crate::v2_eip155_allowance::{
+    AllowancePaymentRequirementsExtra, AllowanceScheme, V2Eip155Allowance,
+};
+
+impl V2Eip155Allowance {
+    /// Creates a V2 price tag for a payment pulled from the payer's ERC-20 allowance.
+    ///
+    /// The payer must have approved a facilitator signer for `asset`. The price tag
+    /// enricher names that signer as the `spender` in the requirements `extra`, from
+    /// the facilitator's `/supported`.
+    ///
+    /// # Parameters
+    ///
+    /// - `pay_to`: The recipient address (can be any type convertible to [`ChecksummedAddress`]).
+    /// - `asset`: The token deployment and amount required
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use x402_chain_eip155::V2Eip155Allowance;
+    ///
+    /// let price_tag = V2Eip155Allowance::price_tag(
+    ///     "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb",
+    ///     token.amount(1_000_000u64),
+    /// );
+    /// ```
+    #[allow(dead_code)] // Public for consumption by downstream crates.
+    pub fn price_tag<A: Into<ChecksummedAddress>>(
+        pay_to: A,
+        asset: DeployedTokenAmount<U256, Eip155TokenDeployment>,
+    ) -> v2::PriceTag {
+        let chain_id: ChainId = asset.token.chain_reference.into();
+        let extra = AllowancePaymentRequirementsExtra { spender: None };
+        let requirements = v2::PaymentRequirements {
+            scheme: AllowanceScheme.to_string(),
+            pay_to: pay_to.into().to_string(),
+            asset: asset.token.address.to_string(),
+            network: chain_id,
+            amount: asset.amount.to_string(),
+            max_timeout_seconds: 300,
+            extra: serde_json::to_value(&extra).ok(),
+        };
+        v2::PriceTag {
+            requirements,
+            enricher: Some(Arc::new(enrich_spender)),
+        }
+    }
+}
+
+/// Sets the `spender` the facilitator advertises for the price tag's chain.
+fn enrich_spender(price_tag: &mut v2::PriceTag, capabilities: &proto::SupportedResponse) {
+    let requirements = &mut price_tag.requirements;
+    let spender = capabilities
+        .kinds
+        .iter()
+        .find(|kind| {
+            kind.scheme == AllowanceScheme.as_ref()
+                && kind.network == requirements.network.to_string()
+        })
+        .and_then(|kind| kind.extra.as_ref()?.get("spender")?.as_str()?.parse().ok());
+    if let Some(spender) = spender {
+        let extra = AllowancePaymentRequirementsExtra {
+            spender: Some(spender),
+        };
+        requirements.extra = serde_json::to_value(&extra).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, address};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_price_tag_spender_from_supported() {
+        let token = Eip155TokenDeployment {
+            chain_reference: crate::chain::Eip155ChainReference::new(42793),
+            address: address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6"),
+            decimals: 18,
+            eip712: None,
+        };
+        let mut price_tag =
+            V2Eip155Allowance::price_tag(Address::repeat_byte(1), token.amount(5u64));
+        assert_eq!(price_tag.requirements.extra, Some(serde_json::json!({})));
+
+        let spender = Address::repeat_byte(2);
+        let supported = proto::SupportedResponse {
+            kinds: vec![proto::SupportedPaymentKind {
+                x402_version: 2,
+                scheme: "allowance".to_string(),
+                network: "eip155:42793".to_string(),
+                extra: Some(serde_json::json!({ "spender": spender })),
+            }],
+            extensions: Vec::new(),
+            signers: HashMap::new(),
+        };
+        enrich_spender(&mut price_tag, &supported);
+        let extra: AllowancePaymentRequirementsExtra =
+            serde_json::from_value(price_tag.requirements.extra.unwrap()).unwrap();
+        assert_eq!(extra.spender, Some(spender));
+    }
+}
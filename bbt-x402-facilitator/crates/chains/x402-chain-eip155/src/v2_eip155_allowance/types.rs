@@ -0,0 +1,141 @@
+//! Type definitions for the V2 EIP-155 "allowance" payment scheme.
+//!
+//! The payload carries the same authorization fields as an ERC-3009 payment, signed
+//! as an [`AllowancePayment`] that also names the token, under an EIP-712 domain
+//! bound to the facilitator signer the payer approved.
+
+use alloy_primitives::{Address, Bytes};
+use serde::{Deserialize, Serialize};
+use x402_types::lit_str;
+use x402_types::proto::v2;
+
+#[cfg(any(feature = "facilitator", feature = "client"))]
+use alloy_primitives::{B256, U256};
+#[cfg(any(feature = "facilitator", feature = "client"))]
+use alloy_sol_types::{Eip712Domain, SolStruct, eip712_domain, sol};
+
+#[cfg(any(feature = "facilitator", feature = "client"))]
+use crate::chain::Eip155ChainReference;
+use crate::chain::{Eip155Asset, TokenAmount};
+use crate::v1_eip155_exact::types::ExactEvmPayloadAuthorization;
+
+lit_str!(AllowanceScheme, "allowance");
+
+/// EIP-712 domain name of allowance payments.
+pub const ALLOWANCE_DOMAIN_NAME: &str = "x402 Allowance";
+
+/// EIP-712 domain version of allowance payments.
+pub const ALLOWANCE_DOMAIN_VERSION: &str = "1";
+
+/// Type alias for V2 verify requests using the allowance scheme.
+pub type VerifyRequest = v2::VerifyRequest<PaymentPayload, PaymentRequirements>;
+
+/// Type alias for V2 settle requests (same structure as verify requests).
+pub type SettleRequest = VerifyRequest;
+
+/// Type alias for V2 payment payloads of the allowance scheme.
+pub type PaymentPayload = v2::PaymentPayload<PaymentRequirements, AllowanceEvmPayload>;
+
+/// Type alias for V2 payment requirements of the allowance scheme.
+///
+/// `extra` names the facilitator signer the payer has approved to spend the asset.
+pub type PaymentRequirements = v2::PaymentRequirements<
+    AllowanceScheme,
+    TokenAmount,
+    Eip155Asset,
+    AllowancePaymentRequirementsExtra,
+>;
+
+/// Signed intent to pay from the payer's ERC-20 allowance to the facilitator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowanceEvmPayload {
+    /// EOA or EIP-1271 signature of the [`AllowancePayment`].
+    pub signature: Bytes,
+
+    /// The signed payment. `to` is the final recipient, `value` is in the token's
+    /// smallest unit.
+    pub authorization: ExactEvmPayloadAuthorization,
+}
+
+/// Scheme-specific `extra` of allowance payment requirements.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowancePaymentRequirementsExtra {
+    /// Facilitator signer the payer approved with `approve`, which pulls the payment
+    /// with `transferFrom`. Filled in from the facilitator's `/supported` when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spender: Option<Address>,
+}
+
+#[cfg(any(feature = "facilitator", feature = "client"))]
+sol!(
+    /// Solidity-compatible struct signed by allowance scheme payers.
+    #[derive(Serialize, Deserialize)]
+    struct AllowancePayment {
+        address from;
+        address to;
+        address token;
+        uint256 value;
+        uint256 validAfter;
+        uint256 validBefore;
+        bytes32 nonce;
+    }
+);
+
+/// EIP-712 domain of payments pulled by `spender`.
+///
+/// The spender is an account rather than a contract, so binding it as the verifying
+/// contract keeps a payment from being pulled by another facilitator the payer
+/// also approved.
+#[cfg(any(feature = "facilitator", feature = "client"))]
+pub fn allowance_domain(chain: &Eip155ChainReference, spender: Address) -> Eip712Domain {
+    eip712_domain! {
+        name: ALLOWANCE_DOMAIN_NAME,
+        version: ALLOWANCE_DOMAIN_VERSION,
+        chain_id: chain.inner(),
+        verifying_contract: spender,
+    }
+}
+
+/// Signing hash of an [`AllowancePayment`] in `token`.
+#[cfg(any(feature = "facilitator", feature = "client"))]
+pub fn allowance_payment_hash(
+    authorization: &ExactEvmPayloadAuthorization,
+    token: Address,
+    domain: &Eip712Domain,
+) -> B256 {
+    AllowancePayment {
+        from: authorization.from,
+        to: authorization.to,
+        token,
+        value: authorization.value,
+        validAfter: U256::from(authorization.valid_after.as_secs()),
+        validBefore: U256::from(authorization.valid_before.as_secs()),
+        nonce: authorization.nonce,
+    }
+    .eip712_signing_hash(domain)
+}
+
+#[cfg(all(test, any(feature = "facilitator", feature = "client")))]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    #[test]
+    fn test_allowance_payment_type() {
+        let typehash = keccak256(
+            "AllowancePayment(address from,address to,address token,uint256 value,uint256 validAfter,uint256 validBefore,bytes32 nonce)",
+        );
+        let payment = AllowancePayment {
+            from: Address::ZERO,
+            to: Address::ZERO,
+            token: Address::ZERO,
+            value: U256::ZERO,
+            validAfter: U256::ZERO,
+            validBefore: U256::ZERO,
+            nonce: B256::ZERO,
+        };
+        assert_eq!(payment.eip712_type_hash(), typehash);
+    }
+}
@@ -0,0 +1,299 @@
+//! Client-side payment signing for the V2 EIP-155 "deferred" scheme.
+//!
+//! This module provides [`V2Eip155DeferredClient`] for paying resources with deferred
+//! settlement. The client signs one Permit2 `PermitSingle` per token and spender,
+//! opening a tab, and reuses it for every payment until its allowance would be
+//! exceeded or it is about to expire. It then signs the next nonce, and the
+//! facilitator settles the old tab before opening the new one. The payer must have
+//! approved the Permit2 contract for the token beforehand.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! use x402_chain_eip155::v2_eip155_deferred::client::V2Eip155DeferredClient;
+//! use alloy_signer_local::PrivateKeySigner;
+//!
+//! let signer = PrivateKeySigner::random();
+//! let client = V2Eip155DeferredClient::new(signer).with_tab_payments(1000);
+//! ```
+
+use alloy_primitives::{Address, Bytes, U256};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use x402_types::chain::ChainId;
+use x402_types::proto::v2::ResourceInfo;
+use x402_types::proto::{PaymentRequired, v2};
+use x402_types::scheme::X402SchemeId;
+use x402_types::scheme::client::{
+    PaymentCandidate, PaymentCandidateSigner, X402Error, X402SchemeClient,
+};
+use x402_types::timestamp::UnixTimestamp;
+use x402_types::util::Base64Bytes;
+
+use crate::chain::Eip155ChainReference;
+use crate::v1_eip155_exact::client::SignerLike;
+use crate::v1_eip155_exact::digest::{
+    PERMIT2_ADDRESS, permit_single_hash, permit2_allowance_domain,
+};
+use crate::v1_eip155_exact::types::{Permit2Details, Permit2Payload, Permit2PermitSingle};
+use crate::v2_eip155_deferred::{V2Eip155Deferred, types};
+
+/// Default number of payments at the requirements price a tab allows.
+pub const DEFAULT_TAB_PAYMENTS: u64 = 100;
+
+/// Default time a tab stays open: one day.
+pub const DEFAULT_TAB_DURATION_SECONDS: u64 = 86_400;
+
+/// Tabs opened by a client, by chain, token and spender.
+type Tabs = Arc<Mutex<HashMap<(ChainId, Address, Address), OpenTab>>>;
+
+/// A signed allowance the client pays with, and how much of it is spent.
+#[derive(Debug, Clone)]
+struct OpenTab {
+    payload: Permit2Payload,
+    spent: U256,
+}
+
+/// Client for signing V2 EIP-155 deferred scheme payments.
+///
+/// Clones share their open tabs.
+///
+/// # Type Parameters
+///
+/// - `S`: The signer type, which must implement [`SignerLike`]
+#[derive(Debug, Clone)]
+#[allow(dead_code)] // Public for consumption by downstream crates.
+pub struct V2Eip155DeferredClient<S> {
+    signer: S,
+    nonce: u64,
+    permit2: Address,
+    tab_payments: u64,
+    tab_duration_seconds: u64,
+    tabs: Tabs,
+}
+
+#[allow(dead_code)] // Public for consumption by downstream crates.
+impl<S> V2Eip155DeferredClient<S> {
+    /// Creates a new V2 EIP-155 deferred scheme client with the given signer.
+    pub fn new(signer: S) -> Self {
+        Self {
+            signer,
+            nonce: 0,
+            permit2: PERMIT2_ADDRESS,
+            tab_payments: DEFAULT_TAB_PAYMENTS,
+            tab_duration_seconds: DEFAULT_TAB_DURATION_SECONDS,
+            tabs: Arc::default(),
+        }
+    }
+
+    /// Sets the Permit2 nonce of the first tab, as returned by
+    /// `Permit2.allowance(owner, token, spender)`. Defaults to `0`, which is right
+    /// for the first payment to a spender.
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// Sets the Permit2 contract, for chains where it is not at its canonical address.
+    pub fn with_permit2(mut self, permit2: Address) -> Self {
+        self.permit2 = permit2;
+        self
+    }
+
+    /// Sets how many payments at the price of the first one a tab allows, which
+    /// makes its Permit2 allowance. Defaults to [`DEFAULT_TAB_PAYMENTS`].
+    pub fn with_tab_payments(mut self, tab_payments: u64) -> Self {
+        self.tab_payments = tab_payments.max(1);
+        self
+    }
+
+    /// Sets how long a tab stays open. Defaults to [`DEFAULT_TAB_DURATION_SECONDS`].
+    pub fn with_tab_duration_seconds(mut self, tab_duration_seconds: u64) -> Self {
+        self.tab_duration_seconds = tab_duration_seconds;
+        self
+    }
+}
+
+impl<S> X402SchemeId for V2Eip155DeferredClient<S> {
+    fn namespace(&self) -> &str {
+        V2Eip155Deferred.namespace()
+    }
+
+    fn scheme(&self) -> &str {
+        V2Eip155Deferred.scheme()
+    }
+}
+
+impl<S> X402SchemeClient for V2Eip155DeferredClient<S>
+where
+    S: SignerLike + Clone + Send + Sync + 'static,
+{
+    fn accept(&self, payment_required: &PaymentRequired) -> Vec<PaymentCandidate> {
+        let payment_required = match payment_required {
+            PaymentRequired::V2(payment_required) => payment_required,
+            PaymentRequired::V1(_) => {
+                return vec![];
+            }
+        };
+        payment_required
+            .accepts
+            .iter()
+            .filter_map(|v| {
+                let requirements: types::PaymentRequirements = v.as_concrete()?;
+                // Without a spender the facilitator cannot pull.
+                requirements.extra.as_ref()?.spender?;
+                let chain_reference = Eip155ChainReference::try_from(&requirements.network).ok()?;
+                let candidate = PaymentCandidate {
+                    chain_id: requirements.network.clone(),
+                    asset: requirements.asset.to_string(),
+                    amount: requirements.amount.into(),
+                    scheme: self.scheme().to_string(),
+                    x402_version: self.x402_version(),
+                    pay_to: requirements.pay_to.to_string(),
+                    transfer_method: Some("permit2".to_string()),
+                    signer: Box::new(PayloadSigner {
+                        resource_info: Some(payment_required.resource.clone()),
+                        client: self.clone(),
+                        chain_reference,
+                        requirements,
+                    }),
+                };
+                Some(candidate)
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+impl<S: SignerLike + Sync> V2Eip155DeferredClient<S> {
+    /// The allowance to pay `requirements` with: the open tab for its token and
+    /// spender if it has room and time left, else a newly signed one.
+    async fn tab_payload(
+        &self,
+        chain_reference: &Eip155ChainReference,
+        requirements: &types::PaymentRequirements,
+    ) -> Result<Permit2Payload, X402Error> {
+        let spender = requirements
+            .extra
+            .as_ref()
+            .and_then(|extra| extra.spender)
+            .ok_or_else(|| X402Error::SigningError("Missing extra.spender".to_string()))?;
+        let key = (
+            requirements.network.clone(),
+            requirements.asset.address(),
+            spender,
+        );
+        let amount: U256 = requirements.amount.into();
+        let valid_until = (UnixTimestamp::now() + requirements.max_timeout_seconds).as_secs();
+        let nonce = {
+            let mut tabs = self.tabs.lock().unwrap_or_else(|e| e.into_inner());
+            match tabs.get_mut(&key) {
+                Some(tab) => {
+                    let details = &tab.payload.permit_single.details;
+                    let spent = tab.spent.saturating_add(amount);
+                    if spent <= details.amount && details.expiration > valid_until {
+                        tab.spent = spent;
+                        return Ok(tab.payload.clone());
+                    }
+                    details.nonce + 1
+                }
+                None => self.nonce,
+            }
+        };
+        let payload = sign_deferred_tab(
+            &self.signer,
+            chain_reference,
+            requirements,
+            amount.saturating_mul(U256::from(self.tab_payments)),
+            UnixTimestamp::now() + self.tab_duration_seconds,
+            nonce,
+            self.permit2,
+        )
+        .await?;
+        let tab = OpenTab {
+            payload: payload.clone(),
+            spent: amount,
+        };
+        self.tabs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, tab);
+        Ok(payload)
+    }
+}
+
+/// Signs a Permit2 allowance of `ceiling`, expiring at `expiration`, which opens a
+/// deferred payment tab with the spender of `requirements`.
+///
+/// The signature must be used before the first payment is settled, which happens
+/// within `maxTimeoutSeconds`.
+#[allow(dead_code)] // Public for consumption by downstream crates.
+pub async fn sign_deferred_tab<S: SignerLike + Sync>(
+    signer: &S,
+    chain_reference: &Eip155ChainReference,
+    requirements: &types::PaymentRequirements,
+    ceiling: U256,
+    expiration: UnixTimestamp,
+    nonce: u64,
+    permit2: Address,
+) -> Result<Permit2Payload, X402Error> {
+    let spender = requirements
+        .extra
+        .as_ref()
+        .and_then(|extra| extra.spender)
+        .ok_or_else(|| X402Error::SigningError("Missing extra.spender".to_string()))?;
+    let permit_single = Permit2PermitSingle {
+        details: Permit2Details {
+            token: requirements.asset.address(),
+            amount: ceiling,
+            expiration: expiration.as_secs(),
+            nonce,
+        },
+        spender,
+        sig_deadline: expiration.as_secs(),
+    };
+    let hash = permit_single_hash(
+        &permit_single,
+        &permit2_allowance_domain(chain_reference, permit2),
+    );
+    let signature = signer
+        .sign_hash(&hash)
+        .await
+        .map_err(|e| X402Error::SigningError(format!("{e:?}")))?;
+    Ok(Permit2Payload {
+        owner: signer.address(),
+        permit_single,
+        signature: Bytes::from(signature.as_bytes().to_vec()),
+    })
+}
+
+#[allow(dead_code)] // Public for consumption by downstream crates.
+struct PayloadSigner<S> {
+    client: V2Eip155DeferredClient<S>,
+    resource_info: Option<ResourceInfo>,
+    chain_reference: Eip155ChainReference,
+    requirements: types::PaymentRequirements,
+}
+
+#[async_trait]
+impl<S> PaymentCandidateSigner for PayloadSigner<S>
+where
+    S: Send + Sync + SignerLike,
+{
+    async fn sign_payment(&self) -> Result<String, X402Error> {
+        let permit2_payload = self
+            .client
+            .tab_payload(&self.chain_reference, &self.requirements)
+            .await?;
+        let payload = types::PaymentPayload {
+            x402_version: v2::X402Version2,
+            accepted: self.requirements.clone(),
+            resource: self.resource_info.clone(),
+            payload: permit2_payload,
+        };
+        let json = serde_json::to_vec(&payload)?;
+        let b64 = Base64Bytes::encode(&json);
+
+        Ok(b64.to_string())
+    }
+}
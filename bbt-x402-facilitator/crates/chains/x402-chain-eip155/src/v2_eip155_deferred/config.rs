@@ -0,0 +1,149 @@
+//! Scheme configuration for the EIP-155 "deferred" facilitator.
+//!
+//! Payments are settled once the amount owed to a payee reaches the `flushThresholds`
+//! of its token, or `maxDelaySeconds` after the first payment not settled yet,
+//! whichever comes first. Tokens without a threshold are only settled on the timer.
+//!
+//! Tabs are tracked in memory. Accepted payments are only paid out while the
+//! facilitator remembers their tab, so production deployments set `tabsDir` to keep
+//! them across restarts and config reloads, in one JSON file per chain (e.g.
+//! `eip155-42793.json`).
+//!
+//! ```json
+//! {
+//!   "id": "v2-eip155-deferred",
+//!   "chains": "eip155:42793",
+//!   "config": {
+//!     "tabsDir": "/var/lib/x402/tabs",
+//!     "maxDelaySeconds": 3600,
+//!     "flushThresholds": {
+//!       "0x7EfE4bdd11237610bcFca478937658bE39F8dfd6": "5000000000000000000"
+//!     },
+//!     "graceBufferSeconds": 6
+//!   }
+//! }
+//! ```
+//!
+//! The config section is optional. Unknown keys, malformed values and an unreadable
+//! tabs file fail the scheme build with an [`Eip155DeferredConfigError`].
+
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use x402_types::chain::ChainId;
+use x402_types::timestamp::SharedClock;
+
+use crate::chain::TokenAmount;
+use crate::v1_eip155_exact::settlement::DEFAULT_GRACE_BUFFER_SECONDS;
+
+/// Default longest time a payment waits to be settled: one hour.
+pub const DEFAULT_MAX_DELAY_SECONDS: u64 = 3600;
+
+/// Errors in the scheme configuration of the EIP-155 "deferred" facilitator.
+#[derive(Debug, thiserror::Error)]
+pub enum Eip155DeferredConfigError {
+    #[error("Invalid deferred scheme config: {0}")]
+    Invalid(#[from] serde_json::Error),
+    #[error("Failed to load tabs from {path}: {reason}")]
+    Tabs { path: PathBuf, reason: String },
+}
+
+/// Scheme configuration for the EIP-155 "deferred" facilitator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Eip155DeferredConfig {
+    /// Directory the tabs are kept in. When absent, they are lost on restart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tabs_dir: Option<PathBuf>,
+    /// Longest a payment waits to be settled.
+    #[serde(default = "default_max_delay_seconds")]
+    pub max_delay_seconds: u64,
+    /// Amount owed to a payee, per token, that is settled right away.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub flush_thresholds: HashMap<Address, TokenAmount>,
+    /// Seconds a Permit2 signature or allowance must remain valid past the time a
+    /// tab is settled by.
+    #[serde(default = "default_grace_buffer_seconds")]
+    pub grace_buffer_seconds: u64,
+    /// Clock the time windows are checked against; the system clock outside tests.
+    #[serde(skip)]
+    pub clock: SharedClock,
+}
+
+impl Default for Eip155DeferredConfig {
+    fn default() -> Self {
+        Self {
+            tabs_dir: None,
+            max_delay_seconds: DEFAULT_MAX_DELAY_SECONDS,
+            flush_thresholds: HashMap::new(),
+            grace_buffer_seconds: DEFAULT_GRACE_BUFFER_SECONDS,
+            clock: SharedClock::default(),
+        }
+    }
+}
+
+fn default_max_delay_seconds() -> u64 {
+    DEFAULT_MAX_DELAY_SECONDS
+}
+
+fn default_grace_buffer_seconds() -> u64 {
+    DEFAULT_GRACE_BUFFER_SECONDS
+}
+
+impl Eip155DeferredConfig {
+    /// Parses the scheme-specific `config` value. A missing value means defaults.
+    pub fn from_value(value: Option<serde_json::Value>) -> Result<Self, Eip155DeferredConfigError> {
+        Ok(value
+            .map(serde_json::from_value)
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    /// The file the tabs of `chain_id` are kept in, if persisted.
+    pub fn tabs_path(&self, chain_id: &ChainId) -> Option<PathBuf> {
+        let file = format!("{}.json", chain_id.to_string().replace(':', "-"));
+        self.tabs_dir.as_ref().map(|dir| dir.join(file))
+    }
+
+    /// The amount owed to a payee in `token` that is settled right away, if any.
+    pub fn flush_threshold(&self, token: &Address) -> Option<TokenAmount> {
+        self.flush_thresholds.get(token).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{U256, address};
+
+    #[test]
+    fn test_deferred_config() {
+        let config = Eip155DeferredConfig::from_value(None).unwrap();
+        assert!(config.tabs_dir.is_none());
+        assert_eq!(config.max_delay_seconds, DEFAULT_MAX_DELAY_SECONDS);
+
+        let token = address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6");
+        let config = Eip155DeferredConfig::from_value(Some(serde_json::json!({
+            "tabsDir": "/tmp/tabs",
+            "maxDelaySeconds": 600,
+            "flushThresholds": { token.to_string(): "5000" }
+        })))
+        .unwrap();
+        assert_eq!(
+            config.tabs_path(&"eip155:42793".parse().unwrap()),
+            Some(PathBuf::from("/tmp/tabs/eip155-42793.json"))
+        );
+        assert_eq!(config.max_delay_seconds, 600);
+        assert_eq!(
+            config.flush_threshold(&token),
+            Some(TokenAmount(U256::from(5000)))
+        );
+        assert_eq!(config.flush_threshold(&Address::ZERO), None);
+
+        assert!(matches!(
+            Eip155DeferredConfig::from_value(Some(serde_json::json!({ "maxDelay": 600 }))),
+            Err(Eip155DeferredConfigError::Invalid(_))
+        ));
+    }
+}
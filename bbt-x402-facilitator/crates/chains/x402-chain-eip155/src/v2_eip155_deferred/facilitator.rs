@@ -0,0 +1,655 @@
+//! Facilitator-side payment verification and settlement for the V2 EIP-155 deferred scheme.
+//!
+//! The first payment on a Permit2 allowance is verified like a legacy AllowanceTransfer
+//! payment of the "exact" scheme and opens a tab, see
+//! [`tabs`](crate::v2_eip155_deferred::tabs). Settling a payment only accrues it to its
+//! payee and answers with a signed [`DeferredIou`]. A payee's accrual is paid out in one
+//! Permit2 `transferFrom` when it reaches the flush threshold, inline, or when it is
+//! due, by [`X402SchemeFacilitator::flush`].
+
+use alloy_primitives::{Address, B256, Bytes, U256};
+use alloy_provider::{MulticallItem, Provider};
+use alloy_rpc_types_eth::TransactionReceipt;
+use std::collections::HashMap;
+use std::str::FromStr;
+use x402_types::chain::{ChainId, ChainProviderOps};
+use x402_types::proto;
+use x402_types::proto::{PaymentVerificationError, v1, v2};
+use x402_types::scheme::{
+    X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError,
+};
+use x402_types::timestamp::{Clock, UnixTimestamp};
+
+#[cfg(feature = "telemetry")]
+use tracing::instrument;
+
+use crate::chain::{
+    Eip155ChainReference, Eip155MetaTransactionProvider, MetaTransaction, PayTo, PayerAddress,
+    Spender, TokenAmount,
+};
+use crate::v1_eip155_exact::facilitator::{
+    Eip155ExactError, IPermit2, Permit2Payment, assert_permit2_domain, assert_permit2_time,
+    build_permit2_single_call, fetch_token_state, join_spenders, permit2_amount,
+    settlement_receipt, verify_payment_permit2,
+};
+use crate::v2_eip155_deferred::{
+    Accrual, DeferredIou, DeferredScheme, Eip155DeferredConfig, Eip155DeferredConfigError, Tab,
+    TabBook, V2Eip155Deferred, tab_id, types,
+};
+
+impl<P> X402SchemeFacilitatorBuilder<P> for V2Eip155Deferred
+where
+    P: Eip155MetaTransactionProvider + ChainProviderOps + Send + Sync + 'static,
+    Eip155ExactError: From<P::Error>,
+{
+    fn build(
+        &self,
+        provider: P,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
+        let config = Eip155DeferredConfig::from_value(config)?;
+        let tabs = match config.tabs_path(&provider.chain_id()) {
+            Some(path) => TabBook::open(&path)
+                .map_err(|reason| Eip155DeferredConfigError::Tabs { path, reason })?,
+            None => TabBook::new(),
+        };
+        Ok(Box::new(V2Eip155DeferredFacilitator::new(
+            provider, config, tabs,
+        )))
+    }
+}
+
+/// Facilitator for V2 EIP-155 deferred scheme payments.
+///
+/// # Type Parameters
+///
+/// - `P`: The provider type, which must implement [`Eip155MetaTransactionProvider`]
+///   and [`ChainProviderOps`]
+pub struct V2Eip155DeferredFacilitator<P> {
+    provider: P,
+    config: Eip155DeferredConfig,
+    tabs: TabBook,
+    /// Serializes settlements and flushes, so a payment is never paid out twice.
+    settling: tokio::sync::Mutex<()>,
+}
+
+impl<P> V2Eip155DeferredFacilitator<P> {
+    /// Creates a new V2 EIP-155 deferred scheme facilitator keeping its tabs in `tabs`.
+    pub fn new(provider: P, config: Eip155DeferredConfig, tabs: TabBook) -> Self {
+        Self {
+            provider,
+            config,
+            tabs,
+            settling: tokio::sync::Mutex::new(()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<P> X402SchemeFacilitator for V2Eip155DeferredFacilitator<P>
+where
+    P: Eip155MetaTransactionProvider + ChainProviderOps + Send + Sync,
+    P::Inner: Provider,
+    Eip155ExactError: From<P::Error>,
+{
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
+        let request = types::VerifyRequest::from_proto(request)?;
+        let payment = assert_valid_payment(
+            self.provider.chain(),
+            &request.payment_payload,
+            &request.payment_requirements,
+            &self.spenders()?,
+        )?;
+        match self.tabs.get(&payment.tab_id) {
+            Some(tab) => {
+                assert_accruable(&tab, &payment, &self.config)?;
+                self.verify_funds(&tab, payment.permit.transfer_amount)
+                    .await?;
+            }
+            // The permit can only be simulated once the tabs it replaces are settled.
+            None if self
+                .replaced_tabs(&payment)
+                .iter()
+                .any(|tab| !tab.permitted) =>
+            {
+                assert_permit2_time(
+                    UnixTimestamp::from_secs(payment.permit.sig_deadline),
+                    UnixTimestamp::from_secs(payment.permit.expiration),
+                    self.config.grace_buffer_seconds,
+                    &self.config.clock,
+                )?;
+                let tab = payment.open_tab(self.provider.chain_id(), self.config.clock.now());
+                self.verify_funds(&tab, payment.permit.transfer_amount)
+                    .await?;
+            }
+            None => self.verify_permit(&payment).await?,
+        }
+        Ok(v2::VerifyResponse::valid(payment.permit.owner.to_string()).into())
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let required = self.provider.required_confirmations();
+        let confirmations = match request.confirmation_policy() {
+            proto::ConfirmationPolicy::Confirmed => required,
+            proto::ConfirmationPolicy::Pending => 1,
+        };
+        let request = types::SettleRequest::from_proto(request)?;
+        let network = request.payment_payload.accepted.network.to_string();
+        let payment = assert_valid_payment(
+            self.provider.chain(),
+            &request.payment_payload,
+            &request.payment_requirements,
+            &self.spenders()?,
+        )?;
+        let pay_to = payment.permit.pay_to.address();
+        let amount = payment.permit.transfer_amount;
+
+        let _settling = self.settling.lock().await;
+        let now = self.config.clock.now();
+        let mut tab = match self.tabs.get(&payment.tab_id) {
+            Some(tab) => {
+                assert_accruable(&tab, &payment, &self.config)?;
+                self.verify_funds(&tab, amount).await?;
+                tab
+            }
+            None => {
+                // The new permit overwrites the allowance of the tabs it replaces.
+                for mut replaced in self.replaced_tabs(&payment) {
+                    for pay_to in owed_payees(&replaced) {
+                        self.settle_accrual(&mut replaced, pay_to, required).await?;
+                    }
+                    self.tabs.remove(&replaced.id);
+                }
+                self.verify_permit(&payment).await?;
+                payment.open_tab(request.payment_payload.accepted.network.clone(), now)
+            }
+        };
+        let accrual = tab.accrue(pay_to, amount, now).clone();
+        if tab.due(now, &self.config).contains(&pay_to) {
+            match self.settle_accrual(&mut tab, pay_to, confirmations).await {
+                Ok(receipt) => {
+                    return Ok(v2::SettleResponse::Success {
+                        payer: tab.owner.to_string(),
+                        transaction: receipt.transaction_hash.to_string(),
+                        network,
+                        receipt: v1::SettlementReceipt {
+                            settled_amount: Some(accrual.owed.to_string()),
+                            ..settlement_receipt(&receipt, confirmations, required)
+                        },
+                    }
+                    .into());
+                }
+                Err(_e) => {
+                    // The accrual stays due: the next flush retries it.
+                    #[cfg(feature = "telemetry")]
+                    tracing::warn!(tab = %tab.id, pay_to = %pay_to, error = %_e, "Deferred settlement failed, answering with an IOU");
+                }
+            }
+        }
+        let iou = self.sign_iou(&tab, &accrual).await?;
+        self.tabs.insert(tab);
+        Ok(v2::SettleResponse::Success {
+            payer: iou.payer.to_string(),
+            transaction: B256::ZERO.to_string(),
+            network,
+            receipt: v1::SettlementReceipt {
+                iou: Some(serde_json::to_value(&iou).expect("DeferredIou serialization failed")),
+                ..v1::SettlementReceipt::default()
+            },
+        }
+        .into())
+    }
+
+    async fn flush(&self) -> Result<(), X402SchemeFacilitatorError> {
+        let confirmations = self.provider.required_confirmations();
+        let _settling = self.settling.lock().await;
+        let now = self.config.clock.now();
+        let mut errors = Vec::new();
+        for mut tab in self.tabs.all() {
+            for pay_to in tab.due(now, &self.config) {
+                if let Err(e) = self.settle_accrual(&mut tab, pay_to, confirmations).await {
+                    errors.push(format!("tab {} to {pay_to}: {e}", tab.id));
+                }
+            }
+            if tab.is_closed(now) {
+                self.tabs.remove(&tab.id);
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(X402SchemeFacilitatorError::OnchainFailure(format!(
+                "Deferred settlement failed: {}",
+                errors.join("; ")
+            )))
+        }
+    }
+
+    async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
+        let chain_id = self.provider.chain_id();
+        let spender = self.spenders()?.first().copied();
+        let kinds = vec![proto::SupportedPaymentKind {
+            x402_version: v2::X402Version2.into(),
+            scheme: DeferredScheme.to_string(),
+            network: chain_id.clone().into(),
+            extra: spender.map(|spender| {
+                serde_json::json!({
+                    "spender": spender,
+                    "maxDelaySeconds": self.config.max_delay_seconds,
+                })
+            }),
+        }];
+        let signers = {
+            let mut signers = HashMap::with_capacity(1);
+            signers.insert(chain_id, self.provider.signer_addresses());
+            signers
+        };
+        Ok(proto::SupportedResponse {
+            kinds,
+            extensions: Vec::new(),
+            signers,
+        })
+    }
+}
+
+impl<P> V2Eip155DeferredFacilitator<P>
+where
+    P: Eip155MetaTransactionProvider + ChainProviderOps,
+    P::Inner: Provider,
+    Eip155ExactError: From<P::Error>,
+{
+    /// The facilitator signers, which are the accepted Permit2 spenders.
+    fn spenders(&self) -> Result<Vec<Address>, Eip155ExactError> {
+        self.provider
+            .signer_addresses()
+            .iter()
+            .map(|signer| {
+                Address::from_str(signer).map_err(|_| {
+                    PaymentVerificationError::InvalidFormat("Invalid signer address".to_string())
+                        .into()
+                })
+            })
+            .collect()
+    }
+
+    /// Open tabs on the allowance slot of `payment`, which its permit replaces.
+    ///
+    /// Permit2 keeps one allowance per owner, token and spender, so a new permit
+    /// overwrites the allowance of an earlier one, and only takes the next nonce once
+    /// the earlier permit is used.
+    fn replaced_tabs(&self, payment: &DeferredEvmPayment) -> Vec<Tab> {
+        let permit = &payment.permit;
+        self.tabs
+            .all()
+            .into_iter()
+            .filter(|tab| {
+                tab.id != payment.tab_id
+                    && tab.owner == permit.owner.address()
+                    && tab.token == permit.token
+                    && tab.spender == permit.spender.address()
+            })
+            .collect()
+    }
+
+    /// Checks the Permit2 signature and allowance are valid, and the payer could pay
+    /// the first payment, before a tab is opened.
+    async fn verify_permit(&self, payment: &DeferredEvmPayment) -> Result<(), Eip155ExactError> {
+        let permit = &payment.permit;
+        assert_permit2_time(
+            UnixTimestamp::from_secs(permit.sig_deadline),
+            UnixTimestamp::from_secs(permit.expiration),
+            self.config.grace_buffer_seconds,
+            &self.config.clock,
+        )?;
+        let provider = self.provider.inner();
+        let permit2 = self.provider.contracts().permit2;
+        let contract = IPermit2::new(permit2, provider);
+        let domain = assert_permit2_domain(self.provider.chain(), permit2);
+        verify_payment_permit2(provider, &contract, permit, &domain).await?;
+        Ok(())
+    }
+
+    /// Checks the payer holds, and approves to Permit2, what `tab` owes plus `amount`.
+    async fn verify_funds(&self, tab: &Tab, amount: U256) -> Result<(), Eip155ExactError> {
+        let owed = tab.owed().saturating_add(amount);
+        let permit2 = self.provider.contracts().permit2;
+        let token_state = fetch_token_state(
+            self.provider.inner(),
+            tab.token,
+            PayerAddress(tab.owner),
+            Some(permit2),
+            false,
+        )
+        .await?;
+        token_state.assert_enough_balance(owed)?;
+        token_state.assert_enough_allowance(owed)
+    }
+
+    /// Pays `pay_to` everything it is owed on `tab` in one Permit2 `transferFrom`,
+    /// submitting the `permit` first if it has not been yet. The tab is saved after
+    /// each step, so a failure never submits the permit twice.
+    #[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
+        tab = %tab.id,
+        owner = %tab.owner,
+        pay_to = %pay_to,
+    )))]
+    async fn settle_accrual(
+        &self,
+        tab: &mut Tab,
+        pay_to: Address,
+        confirmations: u64,
+    ) -> Result<TransactionReceipt, Eip155ExactError> {
+        let owed = tab
+            .accrual(pay_to)
+            .map(|accrual| accrual.owed.0)
+            .unwrap_or_default();
+        let permit2 = self.provider.contracts().permit2;
+        let contract = IPermit2::new(permit2, self.provider.inner());
+        if !tab.permitted {
+            let allowance = contract
+                .allowance(tab.owner, tab.token, tab.spender)
+                .call()
+                .await
+                .map_err(|e| Eip155ExactError::ContractCall(e.to_string()))?;
+            // A nonce past the tab's means its permit went through in an earlier attempt.
+            if allowance.nonce.to::<u64>() <= tab.nonce {
+                let permit = build_permit2_single_call(&tab.permit())?;
+                let call = contract.permit(tab.owner, permit, tab.signature.clone());
+                self.send(&call, tab.spender, 1).await?;
+            }
+            tab.permitted = true;
+            self.tabs.insert(tab.clone());
+        }
+        let call = contract.transferFrom(tab.owner, pay_to, permit2_amount(owed)?, tab.token);
+        let receipt = self.send(&call, tab.spender, confirmations).await?;
+        tab.record_settlement(pay_to, receipt.transaction_hash);
+        self.tabs.insert(tab.clone());
+        Ok(receipt)
+    }
+
+    /// Sends `call` from `spender`, failing if it reverts.
+    async fn send<C: MulticallItem>(
+        &self,
+        call: &C,
+        spender: Address,
+        confirmations: u64,
+    ) -> Result<TransactionReceipt, Eip155ExactError> {
+        let receipt = Eip155MetaTransactionProvider::send_transaction_from(
+            &self.provider,
+            MetaTransaction {
+                to: call.target(),
+                calldata: call.input(),
+                confirmations,
+            },
+            spender,
+        )
+        .await?;
+        if receipt.status() {
+            Ok(receipt)
+        } else {
+            Err(Eip155ExactError::TransactionReverted(
+                receipt.transaction_hash,
+            ))
+        }
+    }
+
+    /// Signs, with the tab's spender, the IOU for what `accrual` is owed.
+    async fn sign_iou(
+        &self,
+        tab: &Tab,
+        accrual: &Accrual,
+    ) -> Result<DeferredIou, X402SchemeFacilitatorError> {
+        let mut iou = DeferredIou {
+            tab_id: tab.id,
+            network: tab.network.clone(),
+            payer: tab.owner,
+            pay_to: accrual.pay_to,
+            token: tab.token,
+            owed: accrual.owed,
+            payments: accrual.payments,
+            settle_by: tab.settle_by(accrual, &self.config),
+            signer: tab.spender,
+            signature: Bytes::new(),
+        };
+        let signer = self.provider.signer(tab.spender).ok_or_else(|| {
+            X402SchemeFacilitatorError::OnchainFailure(format!(
+                "No key to sign IOUs for signer {}",
+                tab.spender
+            ))
+        })?;
+        let signature = signer.sign_hash(&iou.signing_hash()).await.map_err(|e| {
+            X402SchemeFacilitatorError::OnchainFailure(format!("Failed to sign IOU: {e}"))
+        })?;
+        iou.signature = Bytes::from(signature.as_bytes().to_vec());
+        Ok(iou)
+    }
+}
+
+/// A validated deferred payment.
+#[derive(Debug)]
+pub struct DeferredEvmPayment {
+    /// Identifier of the tab the payment opens or is accrued on.
+    pub tab_id: B256,
+    /// The Permit2 allowance, with the price of the payment as `transfer_amount`.
+    pub permit: Permit2Payment,
+}
+
+impl DeferredEvmPayment {
+    /// The tab opened by this payment at `now`, with nothing accrued yet.
+    fn open_tab(&self, network: ChainId, now: UnixTimestamp) -> Tab {
+        let permit = &self.permit;
+        Tab {
+            id: self.tab_id,
+            network,
+            owner: permit.owner.address(),
+            spender: permit.spender.address(),
+            token: permit.token,
+            ceiling: TokenAmount(permit.amount),
+            nonce: permit.nonce,
+            expiration: UnixTimestamp::from_secs(permit.expiration),
+            sig_deadline: UnixTimestamp::from_secs(permit.sig_deadline),
+            signature: permit.signature.clone(),
+            permitted: false,
+            opened_at: now,
+            accruals: Vec::new(),
+        }
+    }
+}
+
+impl Tab {
+    /// The Permit2 allowance the tab was opened with.
+    fn permit(&self) -> Permit2Payment {
+        Permit2Payment {
+            owner: PayerAddress(self.owner),
+            spender: Spender(self.spender),
+            pay_to: PayTo(Address::ZERO),
+            token: self.token,
+            amount: self.ceiling.0,
+            expiration: self.expiration.as_secs(),
+            nonce: self.nonce,
+            sig_deadline: self.sig_deadline.as_secs(),
+            signature: self.signature.clone(),
+            transfer_amount: U256::ZERO,
+        }
+    }
+}
+
+/// Runs the checks every deferred payment must pass:
+/// - Valid scheme, network and asset.
+/// - Permit2 spender named by the requirements and controlled by the facilitator.
+/// - Allowance covering at least the payment.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err))]
+pub fn assert_valid_payment(
+    chain: &Eip155ChainReference,
+    payload: &types::PaymentPayload,
+    requirements: &types::PaymentRequirements,
+    spenders: &[Address],
+) -> Result<DeferredEvmPayment, Eip155ExactError> {
+    let accepted = &payload.accepted;
+    if accepted != requirements {
+        return Err(PaymentVerificationError::AcceptedRequirementsMismatch.into());
+    }
+    let chain_id: ChainId = chain.into();
+    if accepted.network != chain_id {
+        return Err(PaymentVerificationError::ChainIdMismatch.into());
+    }
+
+    let permit2 = &payload.payload;
+    let permit_single = &permit2.permit_single;
+    let details = &permit_single.details;
+    if details.token != accepted.asset.address() {
+        return Err(PaymentVerificationError::asset_mismatch(
+            accepted.asset.address(),
+            details.token,
+        )
+        .into());
+    }
+    let spender = permit_single.spender;
+    let expected = accepted.extra.as_ref().and_then(|extra| extra.spender);
+    if expected.is_some_and(|expected| expected != spender) || !spenders.contains(&spender) {
+        let expected = match expected {
+            Some(expected) => expected.to_string(),
+            None => join_spenders(spenders),
+        };
+        return Err(PaymentVerificationError::recipient_mismatch(expected, spender).into());
+    }
+    let amount: U256 = accepted.amount.into();
+    if amount.is_zero() || details.amount < amount {
+        return Err(PaymentVerificationError::invalid_amount(amount, details.amount).into());
+    }
+
+    Ok(DeferredEvmPayment {
+        tab_id: tab_id(chain, permit2.owner, details.token, spender, details.nonce),
+        permit: Permit2Payment {
+            owner: PayerAddress(permit2.owner),
+            spender: Spender(spender),
+            pay_to: PayTo(accepted.pay_to.address()),
+            token: details.token,
+            amount: details.amount,
+            expiration: details.expiration,
+            nonce: details.nonce,
+            sig_deadline: permit_single.sig_deadline,
+            signature: permit2.signature.clone(),
+            transfer_amount: amount,
+        },
+    })
+}
+
+/// Checks `payment` can be accrued on `tab`: it carries the allowance the tab was
+/// opened with, the tab can still be settled in time, and has room for it.
+fn assert_accruable(
+    tab: &Tab,
+    payment: &DeferredEvmPayment,
+    config: &Eip155DeferredConfig,
+) -> Result<(), PaymentVerificationError> {
+    let permit = &payment.permit;
+    if tab.signature != permit.signature
+        || tab.ceiling.0 != permit.amount
+        || tab.expiration.as_secs() != permit.expiration
+    {
+        return Err(PaymentVerificationError::InvalidFormat(
+            "Permit2 allowance differs from the one the tab was opened with".to_string(),
+        ));
+    }
+    let now = config.clock.now();
+    if now + config.grace_buffer_seconds >= tab.deadline() {
+        return Err(PaymentVerificationError::Expired {
+            valid_before: tab.deadline(),
+            now,
+        });
+    }
+    if tab.remaining() < permit.transfer_amount {
+        return Err(PaymentVerificationError::invalid_amount(
+            tab.remaining(),
+            permit.transfer_amount,
+        ));
+    }
+    Ok(())
+}
+
+/// Payees `tab` owes something to.
+fn owed_payees(tab: &Tab) -> Vec<Address> {
+    tab.accruals
+        .iter()
+        .filter(|accrual| !accrual.owed.0.is_zero())
+        .map(|accrual| accrual.pay_to)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn test_assert_accruable() {
+        let chain = Eip155ChainReference::new(42793);
+        let spender = Address::repeat_byte(3);
+        let payment = |amount: u64, nonce: u64| {
+            let payload: types::PaymentPayload = serde_json::from_value(serde_json::json!({
+                "x402Version": 2,
+                "accepted": {
+                    "scheme": "deferred",
+                    "network": "eip155:42793",
+                    "amount": amount.to_string(),
+                    "payTo": Address::repeat_byte(5),
+                    "maxTimeoutSeconds": 60,
+                    "asset": address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6"),
+                    "extra": { "spender": spender },
+                },
+                "payload": {
+                    "owner": Address::repeat_byte(2),
+                    "permitSingle": {
+                        "details": {
+                            "token": address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6"),
+                            "amount": "250",
+                            "expiration": UnixTimestamp::now().as_secs() + 3600,
+                            "nonce": nonce,
+                        },
+                        "spender": spender,
+                        "sigDeadline": UnixTimestamp::now().as_secs() + 3600,
+                    },
+                    "signature": Bytes::from(vec![7; 65]),
+                },
+            }))
+            .unwrap();
+            assert_valid_payment(&chain, &payload, &payload.accepted, &[spender]).unwrap()
+        };
+        let config = Eip155DeferredConfig::default();
+        let first = payment(100, 0);
+        let mut tab = first.open_tab(chain.into(), UnixTimestamp::now());
+        assert_eq!(
+            first.tab_id,
+            tab_id(&chain, Address::repeat_byte(2), tab.token, spender, 0)
+        );
+        tab.accrue(
+            Address::repeat_byte(5),
+            U256::from(100),
+            UnixTimestamp::now(),
+        );
+        assert!(assert_accruable(&tab, &payment(100, 0), &config).is_ok());
+        tab.accrue(
+            Address::repeat_byte(5),
+            U256::from(100),
+            UnixTimestamp::now(),
+        );
+        // 200 of the 250 allowed are accrued.
+        assert!(matches!(
+            assert_accruable(&tab, &payment(100, 0), &config),
+            Err(PaymentVerificationError::InvalidPaymentAmount { .. })
+        ));
+        assert!(assert_accruable(&tab, &payment(50, 0), &config).is_ok());
+        tab.signature = Bytes::from(vec![8; 65]);
+        assert!(matches!(
+            assert_accruable(&tab, &payment(50, 0), &config),
+            Err(PaymentVerificationError::InvalidFormat(_))
+        ));
+        assert_ne!(payment(100, 1).tab_id, first.tab_id);
+    }
+}
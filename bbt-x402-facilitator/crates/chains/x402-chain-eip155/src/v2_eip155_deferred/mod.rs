@@ -0,0 +1,69 @@
+//! V2 EIP-155 "deferred" payment scheme implementation.
+//!
+//! This module implements aggregated payments for the V2 x402 protocol, for
+//! high-frequency, low-value requests where one transaction per payment costs more
+//! than the payment. It builds on the Permit2 AllowanceTransfer path:
+//!
+//! 1. The payer signs a Permit2 `PermitSingle` granting a facilitator signer (the
+//!    `spender` of the requirements `extra`) an allowance, which opens a tab. The
+//!    same payload pays for every request until the allowance is used up or expires.
+//! 2. Verification checks the tab has room for the requirements `amount`, and the
+//!    payer holds what the tab owes plus that amount.
+//! 3. Settlement accrues the payment to its payee and returns a signed
+//!    [`DeferredIou`] in the settlement receipt, with a zero transaction hash. What
+//!    a payee is owed is paid out in one Permit2 `transferFrom` once it reaches the
+//!    configured threshold, or after at most `maxDelaySeconds`.
+//!
+//! The facilitator `config` module documents the thresholds and how tabs are kept.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! use x402_chain_eip155::v2_eip155_deferred::V2Eip155Deferred;
+//!
+//! let price = V2Eip155Deferred::price_tag(
+//!     "0x1234...",  // pay_to address
+//!     bbt.amount(1_000_000_000_000_000u64),  // 0.001 BBT per request
+//! );
+//! ```
+
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "server")]
+#[allow(unused_imports)]
+pub use server::*;
+
+#[cfg(feature = "facilitator")]
+pub mod config;
+#[cfg(feature = "facilitator")]
+pub use config::*;
+#[cfg(feature = "facilitator")]
+pub mod facilitator;
+#[cfg(feature = "facilitator")]
+pub use facilitator::*;
+#[cfg(feature = "facilitator")]
+pub mod tabs;
+#[cfg(feature = "facilitator")]
+pub use tabs::*;
+
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "client")]
+pub use client::*;
+
+pub mod types;
+pub use types::*;
+
+use x402_types::scheme::X402SchemeId;
+
+pub struct V2Eip155Deferred;
+
+impl X402SchemeId for V2Eip155Deferred {
+    fn namespace(&self) -> &str {
+        "eip155"
+    }
+
+    fn scheme(&self) -> &str {
+        DeferredScheme.as_ref()
+    }
+}
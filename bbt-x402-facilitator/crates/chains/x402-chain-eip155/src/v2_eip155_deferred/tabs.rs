@@ -0,0 +1,387 @@
+//! Facilitator-side accounting of deferred payments.
+//!
+//! The first payment on a Permit2 allowance opens a [`Tab`]. Every payment accrues the
+//! requirements `amount` to its payee without sending a transaction. A payee is paid
+//! everything it is owed in one Permit2 `transferFrom` once its accrual is due; the
+//! first settlement of a tab also submits the `permit`.
+
+use alloy_primitives::{Address, B256, Bytes, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use x402_types::chain::ChainId;
+use x402_types::timestamp::UnixTimestamp;
+
+use crate::chain::TokenAmount;
+use crate::v2_eip155_deferred::Eip155DeferredConfig;
+
+/// Payments to one payee accrued on a tab.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Accrual {
+    /// Recipient of the payments.
+    pub pay_to: Address,
+    /// Total of the payments not settled yet.
+    pub owed: TokenAmount,
+    /// Number of payments accepted so far.
+    pub payments: u64,
+    /// When the oldest payment not settled yet was accepted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<UnixTimestamp>,
+    /// Total settled so far.
+    pub settled: TokenAmount,
+    /// Transaction of the last settlement.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_transaction: Option<B256>,
+}
+
+/// A Permit2 allowance payments are accrued against.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tab {
+    /// See [`tab_id`](crate::v2_eip155_deferred::tab_id).
+    pub id: B256,
+    /// Chain the allowance is granted on.
+    pub network: ChainId,
+    /// Payer granting the allowance.
+    pub owner: Address,
+    /// Facilitator signer the allowance is granted to.
+    pub spender: Address,
+    /// Token paid.
+    pub token: Address,
+    /// Total the allowance lets the facilitator pull.
+    pub ceiling: TokenAmount,
+    /// Permit2 nonce of the allowance.
+    pub nonce: u64,
+    /// When the allowance expires.
+    pub expiration: UnixTimestamp,
+    /// When the Permit2 signature expires.
+    pub sig_deadline: UnixTimestamp,
+    /// Permit2 signature of the allowance.
+    pub signature: Bytes,
+    /// Whether the `permit` has been submitted.
+    pub permitted: bool,
+    /// When the first payment was accepted.
+    pub opened_at: UnixTimestamp,
+    /// Payments accrued per payee.
+    pub accruals: Vec<Accrual>,
+}
+
+impl Tab {
+    /// Total of the payments not settled yet.
+    pub fn owed(&self) -> U256 {
+        self.accruals.iter().fold(U256::ZERO, |owed, accrual| {
+            owed.saturating_add(accrual.owed.0)
+        })
+    }
+
+    /// Total settled so far.
+    pub fn settled(&self) -> U256 {
+        self.accruals.iter().fold(U256::ZERO, |settled, accrual| {
+            settled.saturating_add(accrual.settled.0)
+        })
+    }
+
+    /// Allowance left for new payments.
+    pub fn remaining(&self) -> U256 {
+        self.ceiling
+            .0
+            .saturating_sub(self.owed())
+            .saturating_sub(self.settled())
+    }
+
+    /// Last time the tab can be settled: the signature deadline until the `permit`
+    /// is submitted, the allowance expiration after.
+    pub fn deadline(&self) -> UnixTimestamp {
+        if self.permitted {
+            self.expiration
+        } else {
+            self.sig_deadline.min(self.expiration)
+        }
+    }
+
+    /// The payments to `pay_to`, if any.
+    pub fn accrual(&self, pay_to: Address) -> Option<&Accrual> {
+        self.accruals
+            .iter()
+            .find(|accrual| accrual.pay_to == pay_to)
+    }
+
+    /// Records a payment of `amount` to `pay_to` at `now`.
+    pub fn accrue(&mut self, pay_to: Address, amount: U256, now: UnixTimestamp) -> &Accrual {
+        let index = match self
+            .accruals
+            .iter()
+            .position(|accrual| accrual.pay_to == pay_to)
+        {
+            Some(index) => index,
+            None => {
+                self.accruals.push(Accrual {
+                    pay_to,
+                    owed: TokenAmount(U256::ZERO),
+                    payments: 0,
+                    since: None,
+                    settled: TokenAmount(U256::ZERO),
+                    last_transaction: None,
+                });
+                self.accruals.len() - 1
+            }
+        };
+        let accrual = &mut self.accruals[index];
+        accrual.owed = TokenAmount(accrual.owed.0.saturating_add(amount));
+        accrual.payments += 1;
+        accrual.since.get_or_insert(now);
+        accrual
+    }
+
+    /// When what is owed to `accrual` is settled at the latest: `maxDelaySeconds`
+    /// after its oldest payment, and a grace buffer before the tab's deadline.
+    pub fn settle_by(&self, accrual: &Accrual, config: &Eip155DeferredConfig) -> UnixTimestamp {
+        let deadline = UnixTimestamp::from_secs(
+            self.deadline()
+                .as_secs()
+                .saturating_sub(config.grace_buffer_seconds),
+        );
+        match accrual.since {
+            Some(since) => deadline.min(since + config.max_delay_seconds),
+            None => deadline,
+        }
+    }
+
+    /// The payees whose accruals are due at `now`: past their settle-by time, or
+    /// owed at least the flush threshold of the token.
+    pub fn due(&self, now: UnixTimestamp, config: &Eip155DeferredConfig) -> Vec<Address> {
+        let threshold = config.flush_threshold(&self.token);
+        self.accruals
+            .iter()
+            .filter(|accrual| !accrual.owed.0.is_zero())
+            .filter(|accrual| {
+                now >= self.settle_by(accrual, config)
+                    || threshold.is_some_and(|threshold| accrual.owed.0 >= threshold.0)
+            })
+            .map(|accrual| accrual.pay_to)
+            .collect()
+    }
+
+    /// Records the settlement of everything owed to `pay_to` in `transaction`.
+    pub fn record_settlement(&mut self, pay_to: Address, transaction: B256) {
+        self.permitted = true;
+        if let Some(accrual) = self
+            .accruals
+            .iter_mut()
+            .find(|accrual| accrual.pay_to == pay_to)
+        {
+            accrual.settled = TokenAmount(accrual.settled.0.saturating_add(accrual.owed.0));
+            accrual.owed = TokenAmount(U256::ZERO);
+            accrual.since = None;
+            accrual.last_transaction = Some(transaction);
+        }
+    }
+
+    /// Whether the tab can be forgotten at `now`: nothing is owed and no payment can
+    /// be added any more.
+    pub fn is_closed(&self, now: UnixTimestamp) -> bool {
+        self.owed().is_zero() && (now >= self.deadline() || self.remaining().is_zero())
+    }
+}
+
+/// Tabs known to the facilitator.
+///
+/// Entries are kept in memory and, if a path is configured, written to a JSON file
+/// after every change and restored from it on startup.
+#[derive(Debug, Default)]
+pub struct TabBook {
+    path: Option<PathBuf>,
+    tabs: Mutex<HashMap<B256, Tab>>,
+}
+
+impl TabBook {
+    /// Creates an empty, in-memory book.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a book persisted to `path`, restoring the tabs already stored there.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let tabs: Vec<Tab> = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| e.to_string())?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.to_string()),
+        };
+        Ok(Self {
+            path: Some(path),
+            tabs: Mutex::new(tabs.into_iter().map(|tab| (tab.id, tab)).collect()),
+        })
+    }
+
+    /// Returns the tab `id`.
+    pub fn get(&self, id: &B256) -> Option<Tab> {
+        self.lock().get(id).cloned()
+    }
+
+    /// Returns every tab.
+    pub fn all(&self) -> Vec<Tab> {
+        self.lock().values().cloned().collect()
+    }
+
+    /// Inserts or replaces a tab, and persists the book if a path is configured.
+    pub fn insert(&self, tab: Tab) {
+        let mut tabs = self.lock();
+        tabs.insert(tab.id, tab);
+        self.persist(&tabs);
+    }
+
+    /// Removes the tab `id`, and persists the book if a path is configured.
+    pub fn remove(&self, id: &B256) {
+        let mut tabs = self.lock();
+        if tabs.remove(id).is_some() {
+            self.persist(&tabs);
+        }
+    }
+
+    fn persist(&self, tabs: &HashMap<B256, Tab>) {
+        if let Some(path) = &self.path
+            && let Err(_e) = persist(path, tabs)
+        {
+            #[cfg(feature = "telemetry")]
+            tracing::error!(error = %_e, path = %path.display(), "Failed to persist tabs");
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<B256, Tab>> {
+        self.tabs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Writes the tabs to a temporary file and renames it over `path`.
+fn persist(path: &Path, tabs: &HashMap<B256, Tab>) -> std::io::Result<()> {
+    let tabs: Vec<_> = tabs.values().collect();
+    let content = serde_json::to_vec_pretty(&tabs).map_err(std::io::Error::other)?;
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tab() -> Tab {
+        Tab {
+            id: B256::repeat_byte(1),
+            network: "eip155:42793".parse().unwrap(),
+            owner: Address::repeat_byte(2),
+            spender: Address::repeat_byte(3),
+            token: Address::repeat_byte(4),
+            ceiling: TokenAmount(U256::from(1_000)),
+            nonce: 0,
+            expiration: UnixTimestamp::from_secs(100_000),
+            sig_deadline: UnixTimestamp::from_secs(50_000),
+            signature: Bytes::from(vec![7; 65]),
+            permitted: false,
+            opened_at: UnixTimestamp::from_secs(10_000),
+            accruals: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_tab_accrual() {
+        let config = Eip155DeferredConfig {
+            max_delay_seconds: 1_000,
+            flush_thresholds: HashMap::from([(
+                Address::repeat_byte(4),
+                TokenAmount(U256::from(300)),
+            )]),
+            grace_buffer_seconds: 10,
+            ..Eip155DeferredConfig::default()
+        };
+        let at = UnixTimestamp::from_secs;
+        let (alice, bob) = (Address::repeat_byte(5), Address::repeat_byte(6));
+        let mut tab = tab();
+        tab.accrue(alice, U256::from(100), at(10_000));
+        tab.accrue(alice, U256::from(100), at(10_500));
+        tab.accrue(bob, U256::from(50), at(10_600));
+        assert_eq!(tab.owed(), U256::from(250));
+        assert_eq!(tab.remaining(), U256::from(750));
+
+        // The timer runs from the oldest payment not settled yet.
+        let accrual = tab.accrual(alice).unwrap().clone();
+        assert_eq!(accrual.payments, 2);
+        assert_eq!(tab.settle_by(&accrual, &config), at(11_000));
+        assert!(tab.due(at(10_999), &config).is_empty());
+        assert_eq!(tab.due(at(11_000), &config), vec![alice]);
+
+        // Reaching the threshold makes an accrual due right away.
+        tab.accrue(bob, U256::from(250), at(10_700));
+        assert_eq!(tab.due(at(10_700), &config), vec![bob]);
+
+        tab.record_settlement(bob, B256::repeat_byte(9));
+        assert!(tab.permitted);
+        let accrual = tab.accrual(bob).unwrap();
+        assert_eq!(accrual.owed, TokenAmount(U256::ZERO));
+        assert_eq!(accrual.settled, TokenAmount(U256::from(300)));
+        assert_eq!(accrual.since, None);
+        assert_eq!(tab.remaining(), U256::from(500));
+
+        // Once permitted, the allowance expiration bounds the settle-by time.
+        tab.accrue(bob, U256::from(10), at(99_500));
+        let accrual = tab.accrual(bob).unwrap().clone();
+        assert_eq!(tab.settle_by(&accrual, &config), at(99_990));
+        assert!(!tab.is_closed(at(100_000)));
+        tab.record_settlement(alice, B256::repeat_byte(9));
+        tab.record_settlement(bob, B256::repeat_byte(9));
+        assert!(tab.is_closed(at(100_000)));
+    }
+
+    #[test]
+    fn test_tab_book_persists() {
+        let path = std::env::temp_dir().join(format!("x402-tabs-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let book = TabBook::open(&path).unwrap();
+        let mut tab = tab();
+        tab.accrue(
+            Address::repeat_byte(5),
+            U256::from(100),
+            UnixTimestamp::from_secs(10_000),
+        );
+        book.insert(tab.clone());
+        let restored = TabBook::open(&path).unwrap();
+        assert_eq!(restored.get(&B256::repeat_byte(1)), Some(tab));
+        restored.remove(&B256::repeat_byte(1));
+        assert!(TabBook::open(&path).unwrap().all().is_empty());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_tab_book_persists_large_amounts() {
+        let path =
+            std::env::temp_dir().join(format!("x402-tabs-large-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        // 1 token of 18 decimals, above the 2^53 a JSON number holds exactly.
+        let one = U256::from(10u64.pow(18));
+        let mut tab = Tab {
+            ceiling: TokenAmount(one * U256::from(10)),
+            ..tab()
+        };
+        tab.accrue(
+            Address::repeat_byte(5),
+            one,
+            UnixTimestamp::from_secs(10_000),
+        );
+        tab.record_settlement(Address::repeat_byte(5), B256::repeat_byte(9));
+        tab.accrue(
+            Address::repeat_byte(5),
+            one,
+            UnixTimestamp::from_secs(10_500),
+        );
+        TabBook::open(&path).unwrap().insert(tab.clone());
+        let restored = TabBook::open(&path).unwrap();
+        assert_eq!(restored.get(&B256::repeat_byte(1)), Some(tab));
+        let _ = fs::remove_file(&path);
+    }
+}
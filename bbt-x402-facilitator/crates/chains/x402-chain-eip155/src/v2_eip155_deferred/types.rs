@@ -0,0 +1,134 @@
+//! Type definitions for the V2 EIP-155 "deferred" payment scheme.
+//!
+//! The payload is a Permit2 [`Permit2Payload`], as used by legacy AllowanceTransfer
+//! payments of the "exact" scheme. Its allowance opens a tab with the facilitator:
+//! the allowance `amount` is the most the payments on the tab may add up to, and its
+//! `expiration` is when the tab closes. The requirements `amount` is the price of one
+//! payment.
+//!
+//! Until a payment is settled on chain, the facilitator answers with a [`DeferredIou`]
+//! signed by the tab's spender.
+
+use alloy_primitives::{Address, B256, Bytes, eip191_hash_message, keccak256};
+use alloy_sol_types::SolValue;
+use serde::{Deserialize, Serialize};
+use x402_types::chain::ChainId;
+use x402_types::lit_str;
+use x402_types::proto::v2;
+use x402_types::timestamp::UnixTimestamp;
+
+use crate::chain::{Eip155Asset, Eip155ChainReference, TokenAmount};
+use crate::v1_eip155_exact::types::Permit2Payload;
+
+lit_str!(DeferredScheme, "deferred");
+
+/// Type alias for V2 verify requests using the deferred scheme.
+pub type VerifyRequest = v2::VerifyRequest<PaymentPayload, PaymentRequirements>;
+
+/// Type alias for V2 settle requests (same structure as verify requests).
+pub type SettleRequest = VerifyRequest;
+
+/// Type alias for V2 payment payloads of the deferred scheme.
+pub type PaymentPayload = v2::PaymentPayload<PaymentRequirements, Permit2Payload>;
+
+/// Type alias for V2 payment requirements of the deferred scheme.
+///
+/// `amount` is the price of one payment, `extra` sets the spender the Permit2
+/// allowance is granted to.
+pub type PaymentRequirements = v2::PaymentRequirements<
+    DeferredScheme,
+    TokenAmount,
+    Eip155Asset,
+    DeferredPaymentRequirementsExtra,
+>;
+
+/// Scheme-specific `extra` of deferred payment requirements.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeferredPaymentRequirementsExtra {
+    /// Facilitator signer the Permit2 allowance is granted to, as advertised on
+    /// `/supported`. Filled in by the resource server middleware when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spender: Option<Address>,
+}
+
+/// Identifier of the tab a Permit2 allowance opens.
+///
+/// A `PermitSingle` can only be used once, as its nonce is consumed by `permit`, so
+/// the chain, owner, token, spender and nonce identify the tab.
+pub fn tab_id(
+    chain: &Eip155ChainReference,
+    owner: Address,
+    token: Address,
+    spender: Address,
+    nonce: u64,
+) -> B256 {
+    keccak256((chain.inner(), owner, token, spender, nonce).abi_encode())
+}
+
+/// The facilitator's promise to settle the payments to `payTo` accrued on a tab.
+///
+/// It is returned in the settlement receipt of a deferred payment, in place of the
+/// transaction details. Each IOU supersedes the previous ones of the same tab and
+/// payee, as `owed` covers all the payments not settled yet.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeferredIou {
+    /// Tab the payments are accrued on, see [`tab_id`].
+    pub tab_id: B256,
+    /// Chain the payments are settled on.
+    pub network: ChainId,
+    /// Payer who opened the tab.
+    pub payer: Address,
+    /// Recipient of the payments.
+    pub pay_to: Address,
+    /// Token paid.
+    pub token: Address,
+    /// Total of the payments to `pay_to` not settled yet, this one included.
+    pub owed: TokenAmount,
+    /// Number of payments to `pay_to` on the tab so far, this one included.
+    pub payments: u64,
+    /// When the facilitator settles `owed` at the latest.
+    pub settle_by: UnixTimestamp,
+    /// Facilitator signer settling the tab, its Permit2 spender.
+    pub signer: Address,
+    /// EIP-191 signature of [`DeferredIou::digest`] by `signer`.
+    pub signature: Bytes,
+}
+
+impl DeferredIou {
+    /// Hash of the IOU terms, signed with the EIP-191 `personal_sign` prefix.
+    pub fn digest(&self) -> B256 {
+        keccak256(
+            (
+                "x402 deferred IOU",
+                self.tab_id,
+                self.payer,
+                self.pay_to,
+                self.token,
+                self.owed.0,
+                self.payments,
+                self.settle_by.as_secs(),
+            )
+                .abi_encode(),
+        )
+    }
+
+    /// The hash `signer` signs, [`DeferredIou::digest`] with the EIP-191 prefix.
+    pub fn signing_hash(&self) -> B256 {
+        eip191_hash_message(self.digest())
+    }
+
+    /// Checks the IOU is signed by its `signer`.
+    #[cfg(any(feature = "client", feature = "facilitator"))]
+    pub fn is_signed(&self) -> bool {
+        alloy_primitives::Signature::from_raw(&self.signature)
+            .ok()
+            .and_then(|signature| {
+                signature
+                    .recover_address_from_prehash(&self.signing_hash())
+                    .ok()
+            })
+            .is_some_and(|recovered| recovered == self.signer)
+    }
+}
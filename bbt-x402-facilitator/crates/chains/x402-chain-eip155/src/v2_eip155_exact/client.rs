@@ -3,6 +3,13 @@
 //! This module provides [`V2Eip155ExactClient`] for signing ERC-3009
 //! `transferWithAuthorization` payments on EVM chains using the V2 protocol.
 //!
+//! Requirements carrying a `stealthMetaAddress` are paid to a one-time ERC-5564
+//! stealth address rather than `payTo`, see [`crate::stealth`].
+//!
+//! Clients told which tokens the payer holds with
+//! [`V2Eip155ExactClient::with_held_tokens`] pay the lowest token-gated discount
+//! those tokens entitle to, see [`crate::discount`].
+//!
 //! # Usage
 //!
 //! ```ignore
@@ -13,19 +20,22 @@
 //! let client = V2Eip155ExactClient::new(signer);
 //! ```
 
+use alloy_primitives::{Address, B256};
 use async_trait::async_trait;
+use rand::{Rng, rng};
 use x402_types::proto::v2::ResourceInfo;
 use x402_types::proto::{PaymentRequired, v2};
 use x402_types::scheme::X402SchemeId;
 use x402_types::scheme::client::{
-    PaymentCandidate, PaymentCandidateSigner, X402Error, X402SchemeClient,
+    PaymentCandidate, PaymentCandidateSigner, ValidityWindow, X402Error, X402SchemeClient,
 };
 use x402_types::util::Base64Bytes;
 
-use crate::chain::Eip155ChainReference;
+use crate::chain::{Eip155ChainReference, TokenAmount};
 use crate::v1_eip155_exact::client::{
-    Eip3009SigningParams, SignerLike, sign_erc3009_authorization,
+    Eip712Signer, Eip3009SigningParams, sign_erc3009_authorization,
 };
+use crate::v1_eip155_exact::types::StealthPayload;
 use crate::v2_eip155_exact::V2Eip155Exact;
 use crate::v2_eip155_exact::types;
 
@@ -37,7 +47,8 @@ use crate::v2_eip155_exact::types;
 ///
 /// # Type Parameters
 ///
-/// - `S`: The signer type, which must implement [`SignerLike`](crate::v1_eip155_exact::client::SignerLike)
+/// - `S`: The signer type, which must implement [`Eip712Signer`](crate::v1_eip155_exact::client::Eip712Signer),
+///   e.g. an EOA or a [`SafeSigner`](crate::v1_eip155_exact::safe::SafeSigner)
 ///
 /// # Example
 ///
@@ -52,13 +63,38 @@ use crate::v2_eip155_exact::types;
 #[allow(dead_code)] // Public for consumption by downstream crates.
 pub struct V2Eip155ExactClient<S> {
     signer: S,
+    held_tokens: Vec<Address>,
 }
 
 #[allow(dead_code)] // Public for consumption by downstream crates.
 impl<S> V2Eip155ExactClient<S> {
     /// Creates a new V2 EIP-155 exact scheme client with the given signer.
     pub fn new(signer: S) -> Self {
-        Self { signer }
+        Self {
+            signer,
+            held_tokens: Vec::new(),
+        }
+    }
+
+    /// Claims token-gated discounts gated by `tokens`, which the payer holds.
+    ///
+    /// The holding is not checked by the client: the facilitator rejects a
+    /// discounted payment if the payer lacks the required balance.
+    pub fn with_held_tokens<I: IntoIterator<Item = Address>>(mut self, tokens: I) -> Self {
+        self.held_tokens = tokens.into_iter().collect();
+        self
+    }
+
+    /// Amount to pay: the lowest discount claimable with the held tokens, or the price.
+    fn amount(&self, requirements: &types::PaymentRequirements) -> TokenAmount {
+        requirements
+            .extra
+            .iter()
+            .flat_map(|extra| extra.discounts.iter())
+            .filter(|discount| self.held_tokens.contains(&discount.token))
+            .map(|discount| discount.amount)
+            .min_by_key(|amount| amount.0)
+            .unwrap_or(requirements.amount)
     }
 }
 
@@ -74,7 +110,7 @@ impl<S> X402SchemeId for V2Eip155ExactClient<S> {
 
 impl<S> X402SchemeClient for V2Eip155ExactClient<S>
 where
-    S: SignerLike + Clone + Send + Sync + 'static,
+    S: Eip712Signer + Clone + Send + Sync + 'static,
 {
     fn accept(&self, payment_required: &PaymentRequired) -> Vec<PaymentCandidate> {
         let payment_required = match payment_required {
@@ -89,18 +125,21 @@ where
             .filter_map(|v| {
                 let requirements: types::PaymentRequirements = v.as_concrete()?;
                 let chain_reference = Eip155ChainReference::try_from(&requirements.network).ok()?;
+                let amount = self.amount(&requirements);
                 let candidate = PaymentCandidate {
                     chain_id: requirements.network.clone(),
                     asset: requirements.asset.to_string(),
-                    amount: requirements.amount.into(),
+                    amount: amount.into(),
                     scheme: self.scheme().to_string(),
                     x402_version: self.x402_version(),
                     pay_to: requirements.pay_to.to_string(),
+                    transfer_method: Some("eip3009".to_string()),
                     signer: Box::new(PayloadSigner {
                         resource_info: Some(payment_required.resource.clone()),
                         signer: self.signer.clone(),
                         chain_reference,
                         requirements,
+                        amount,
                     }),
                 };
                 Some(candidate)
@@ -115,24 +154,65 @@ struct PayloadSigner<S> {
     resource_info: Option<ResourceInfo>,
     chain_reference: Eip155ChainReference,
     requirements: types::PaymentRequirements,
+    /// Amount signed for, below the requirements `amount` when claiming a discount.
+    amount: TokenAmount,
 }
 
 #[async_trait]
 impl<S> PaymentCandidateSigner for PayloadSigner<S>
 where
-    S: Sync + SignerLike,
+    S: Sync + Eip712Signer,
 {
     async fn sign_payment(&self) -> Result<String, X402Error> {
+        self.sign_with_validity(None).await
+    }
+
+    async fn sign_payment_within(&self, window: ValidityWindow) -> Result<String, X402Error> {
+        self.sign_with_validity(Some(window)).await
+    }
+}
+
+impl<S> PayloadSigner<S>
+where
+    S: Sync + Eip712Signer,
+{
+    async fn sign_with_validity(
+        &self,
+        validity: Option<ValidityWindow>,
+    ) -> Result<String, X402Error> {
+        // Requirements with a stealth meta-address are paid to a fresh one-time address.
+        let stealth_meta_address = self
+            .requirements
+            .extra
+            .as_ref()
+            .and_then(|extra| extra.stealth_meta_address.as_ref());
+        let (pay_to, stealth) = match stealth_meta_address {
+            Some(meta_address) => {
+                let ephemeral_private_key = B256::from(rng().random::<[u8; 32]>());
+                let stealth_address = meta_address
+                    .stealth_address(&ephemeral_private_key)
+                    .map_err(|e| X402Error::SigningError(e.to_string()))?;
+                (
+                    stealth_address.address,
+                    Some(StealthPayload {
+                        ephemeral_private_key,
+                    }),
+                )
+            }
+            None => (self.requirements.pay_to.address(), None),
+        };
         let params = Eip3009SigningParams {
             chain_id: self.chain_reference.inner(),
             asset_address: self.requirements.asset.address(),
-            pay_to: self.requirements.pay_to.address(),
-            amount: self.requirements.amount.into(),
+            pay_to,
+            amount: self.amount.into(),
             max_timeout_seconds: self.requirements.max_timeout_seconds,
             extra: self.requirements.extra.clone(),
+            validity,
         };
 
-        let evm_payload = sign_erc3009_authorization(&self.signer, &params).await?;
+        let mut evm_payload = sign_erc3009_authorization(&self.signer, &params).await?;
+        evm_payload.stealth = stealth;
 
         // Build the payment payload
         let payload = types::PaymentPayload {
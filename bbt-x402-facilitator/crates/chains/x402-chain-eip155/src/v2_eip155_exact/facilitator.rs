@@ -4,33 +4,44 @@
 //! It reuses most of the V1 verification and settlement logic but handles V2-specific
 //! payload structures with embedded requirements and CAIP-2 chain IDs.
 
+use alloy_primitives::U256;
 use alloy_provider::Provider;
-use std::str::FromStr;
+use alloy_rpc_types_eth::TransactionReceipt;
 use alloy_sol_types::Eip712Domain;
+use alloy_sol_types::sol;
 use std::collections::HashMap;
 use x402_types::chain::{ChainId, ChainProviderOps};
 use x402_types::proto;
 use x402_types::proto::{PaymentVerificationError, v2};
-use x402_types::timestamp::UnixTimestamp;
 use x402_types::scheme::{
     X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError,
 };
+use x402_types::timestamp::UnixTimestamp;
 
 #[cfg(feature = "telemetry")]
 use tracing::instrument;
 
 use crate::V2Eip155Exact;
-use crate::chain::{Eip155ChainReference, Eip155MetaTransactionProvider};
-use crate::v1_eip155_exact::ExactScheme;
+use crate::chain::{
+    DryRunProvider, Eip155ChainReference, Eip155Contracts, Eip155MetaTransactionProvider,
+    Eip712DomainCache, MetaTransactionSendError, PayTo, PayerAddress, PreparedSettlement,
+    PreparingProvider, Spender,
+};
+use crate::discount::{TOKEN_GATED_DISCOUNT_EXTENSION, matching_discounts};
 use crate::v1_eip155_exact::facilitator::{
-    Eip155ExactError, ExactEvmPayment, IEIP3009, IPermit2, Permit2Payment, Permit2WitnessPayment,
-    X402ExactPermit2Proxy,
-    assert_domain, assert_enough_balance, assert_enough_value, assert_permit2_domain,
-    assert_permit2_time, assert_permit2_witness_domain, assert_permit2_witness_time, assert_time,
-    settle_payment, settle_payment_permit2, settle_payment_permit2_witness,
-    verify_payment, verify_payment_permit2, verify_payment_permit2_witness,
-    x402_exact_permit2_proxy_address,
+    Eip155ExactError, ExactEvmPayment, ExactEvmReceivePayment, IEIP3009, IPermit2, Permit2Payment,
+    Permit2WitnessPayment, X402ExactPermit2Proxy, X402ReceiveForwarder, assert_amount_matching,
+    assert_permit2_domain, assert_permit2_time, assert_permit2_witness_domain,
+    assert_permit2_witness_time, assert_receive_recipient, assert_remaining_validity, assert_time,
+    fetch_token_state, fetch_token_state_with_domain, join_spenders, parse_signer_addresses,
+    refund_payment, settle_payment, settle_payment_permit2, settle_payment_permit2_witness,
+    settle_payment_receive, settlement_receipt, verify_payment, verify_payment_permit2,
+    verify_payment_permit2_witness, verify_payment_receive,
 };
+use crate::v1_eip155_exact::policy::assert_asset_allowed;
+use crate::v1_eip155_exact::settlement::Eip155ExactConfig;
+use crate::v1_eip155_exact::types::{AmountMatching, ExactEvmPayload};
+use crate::v1_eip155_exact::{ExactScheme, explain};
 use crate::v2_eip155_exact::types;
 
 impl<P> X402SchemeFacilitatorBuilder<P> for V2Eip155Exact
@@ -41,9 +52,12 @@ where
     fn build(
         &self,
         provider: P,
-        _config: Option<serde_json::Value>,
+        config: Option<serde_json::Value>,
     ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
-        Ok(Box::new(V2Eip155ExactFacilitator::new(provider)))
+        let config = Eip155ExactConfig::from_value(config)?;
+        Ok(Box::new(V2Eip155ExactFacilitator::with_config(
+            provider, config,
+        )))
     }
 }
 
@@ -59,24 +73,67 @@ where
 ///   and [`ChainProviderOps`]
 pub struct V2Eip155ExactFacilitator<P> {
     provider: P,
+    config: Eip155ExactConfig,
 }
 
 impl<P> V2Eip155ExactFacilitator<P> {
     /// Creates a new V2 EIP-155 exact scheme facilitator with the given provider.
     pub fn new(provider: P) -> Self {
-        Self { provider }
+        Self::with_config(provider, Eip155ExactConfig::default())
+    }
+
+    /// Creates a new V2 EIP-155 exact scheme facilitator with per-token settlement settings.
+    pub fn with_config(provider: P, config: Eip155ExactConfig) -> Self {
+        Self { provider, config }
     }
 }
 
-fn parse_signer_addresses(signers: Vec<String>) -> Result<Vec<alloy_primitives::Address>, Eip155ExactError> {
-    let mut parsed = Vec::with_capacity(signers.len());
-    for signer in signers {
-        let addr = alloy_primitives::Address::from_str(&signer).map_err(|_| {
-            PaymentVerificationError::InvalidFormat("Invalid signer address".to_string())
-        })?;
-        parsed.push(addr);
+impl<P> V2Eip155ExactFacilitator<P>
+where
+    P: Eip155MetaTransactionProvider + ChainProviderOps + Send + Sync,
+    P::Inner: Provider,
+    Eip155ExactError: From<P::Error>,
+{
+    /// Verifies `request` like `settle` does and returns the transactions that would
+    /// settle it, for callers that sign and submit settlements themselves.
+    ///
+    /// Nothing is sent. Transactions that must come from a particular signer, such as
+    /// the spender of a Permit2 allowance, carry its address in
+    /// [`PreparedTx::from`](crate::chain::PreparedTx::from).
+    pub async fn prepare_settlement(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<PreparedSettlement, X402SchemeFacilitatorError> {
+        self.verify(request).await?;
+        let request = types::SettleRequest::from_proto(request)?;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+        let allowed_spenders = parse_signer_addresses(self.provider.signer_addresses())?;
+        let allowed_spenders = allowed_spenders.into_iter().map(Spender).collect();
+        let context = assert_valid_payment(
+            self.provider.inner(),
+            self.provider.chain(),
+            self.provider.contracts(),
+            self.provider.eip712_domains(),
+            payload,
+            requirements,
+            Some(allowed_spenders),
+            &self.config,
+        )
+        .await?;
+        let min_remaining = self
+            .config
+            .settle_validity_secs(self.provider.min_remaining_validity_secs());
+        assert_remaining_validity(context.expires_at(), min_remaining, &self.config.clock)?;
+        let preparing = PreparingProvider::new(&self.provider);
+        let (payer, _) = settle_context::<_, MetaTransactionSendError>(
+            &preparing,
+            context,
+            self.provider.required_confirmations(),
+        )
+        .await?;
+        Ok(preparing.finish(payer))
     }
-    Ok(parsed)
 }
 
 #[async_trait::async_trait]
@@ -90,16 +147,20 @@ where
         &self,
         request: &proto::VerifyRequest,
     ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
-        let request = types::VerifyRequest::from_proto(request.clone())?;
+        let request = types::VerifyRequest::from_proto(request)?;
         let payload = &request.payment_payload;
         let requirements = &request.payment_requirements;
         let allowed_spenders = parse_signer_addresses(self.provider.signer_addresses())?;
+        let allowed_spenders = allowed_spenders.into_iter().map(Spender).collect();
         let context = assert_valid_payment(
             self.provider.inner(),
             self.provider.chain(),
+            self.provider.contracts(),
+            self.provider.eip712_domains(),
             payload,
             requirements,
             Some(allowed_spenders),
+            &self.config,
         )
         .await?;
 
@@ -108,87 +169,202 @@ where
                 contract,
                 payment,
                 domain,
-            } => verify_payment(self.provider.inner(), &contract, &payment, &domain).await?,
+            } => {
+                verify_payment(
+                    self.provider.inner(),
+                    &contract,
+                    &payment,
+                    &domain,
+                    self.provider.contracts(),
+                )
+                .await?
+            }
+            PaymentContext::Eip3009Receive {
+                contract,
+                payment,
+                domain,
+            } => {
+                verify_payment_receive(
+                    self.provider.inner(),
+                    &contract,
+                    &payment,
+                    &domain,
+                    self.provider.contracts(),
+                )
+                .await?
+            }
             PaymentContext::Permit2 {
                 contract,
                 payment,
                 domain,
-            } => verify_payment_permit2(self.provider.inner(), &contract, &payment, &domain).await?,
+            } => {
+                verify_payment_permit2(self.provider.inner(), &contract, &payment, &domain).await?
+            }
             PaymentContext::Permit2Witness {
                 contract,
                 payment,
                 domain,
-            } => verify_payment_permit2_witness(self.provider.inner(), &contract, &payment, &domain).await?,
+            } => {
+                verify_payment_permit2_witness(
+                    self.provider.inner(),
+                    &contract,
+                    &payment,
+                    &domain,
+                    self.provider.contracts(),
+                )
+                .await?
+            }
         };
         Ok(v2::VerifyResponse::valid(payer.to_string()).into())
     }
 
+    async fn explain(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<Option<proto::ExplainResponse>, X402SchemeFacilitatorError> {
+        let request = types::VerifyRequest::from_proto(request)?;
+        let accepted = &request.payment_payload.accepted;
+        let payload = &request.payment_payload.payload;
+        let chain = self.provider.chain();
+        let chain_id: ChainId = chain.into();
+        let pay_to = assert_stealth_pay_to(accepted, payload);
+        let spenders = parse_signer_addresses(self.provider.signer_addresses())?;
+        let mut explanation = explain::explain_payment(
+            &explain::ExplainedRequirements {
+                chain,
+                asset: accepted.asset.address(),
+                pay_to: pay_to
+                    .as_ref()
+                    .map_or(accepted.pay_to.address(), |pay_to| pay_to.address()),
+                amount: accepted.amount.into(),
+                max_timeout_seconds: accepted.max_timeout_seconds,
+                extra: accepted.extra.as_ref(),
+            },
+            payload,
+            self.provider.contracts().permit2,
+            &spenders,
+            &self.config,
+        )?;
+        let accepted_check = if accepted != &request.payment_requirements {
+            Err(PaymentVerificationError::AcceptedRequirementsMismatch)
+        } else if accepted.network != chain_id {
+            Err(PaymentVerificationError::ChainIdMismatch)
+        } else {
+            Ok(())
+        };
+        let mut checks = vec![explain::StaticCheck::new(
+            "acceptedRequirements",
+            accepted_check,
+        )];
+        if payload.stealth.is_some() {
+            let stealth_check = pay_to.map(|_| ()).map_err(|e| match e {
+                Eip155ExactError::PaymentVerification(e) => e,
+                e => PaymentVerificationError::InvalidFormat(e.to_string()),
+            });
+            checks.push(explain::StaticCheck::new("stealthRecipient", stealth_check));
+        }
+        explanation.checks.splice(0..0, checks);
+        let explanation =
+            serde_json::to_value(&explanation).expect("PaymentExplanation serialization failed");
+        Ok(Some(proto::ExplainResponse(explanation)))
+    }
+
+    async fn refund(
+        &self,
+        request: &proto::RefundRequest,
+    ) -> Result<proto::RefundResponse, X402SchemeFacilitatorError> {
+        Ok(refund_payment(&self.provider, request).await?)
+    }
+
     async fn settle(
         &self,
         request: &proto::SettleRequest,
     ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
-        let request = types::SettleRequest::from_proto(request.clone())?;
+        let required = self.provider.required_confirmations();
+        let confirmations = match request.confirmation_policy() {
+            proto::ConfirmationPolicy::Confirmed => required,
+            proto::ConfirmationPolicy::Pending => 1,
+        };
+        let request = types::SettleRequest::from_proto(request)?;
         let payload = &request.payment_payload;
         let requirements = &request.payment_requirements;
         let allowed_spenders = parse_signer_addresses(self.provider.signer_addresses())?;
+        let allowed_spenders = allowed_spenders.into_iter().map(Spender).collect();
         let context = assert_valid_payment(
             self.provider.inner(),
             self.provider.chain(),
+            self.provider.contracts(),
+            self.provider.eip712_domains(),
             payload,
             requirements,
             Some(allowed_spenders),
+            &self.config,
         )
         .await?;
+        let min_remaining = self
+            .config
+            .settle_validity_secs(self.provider.min_remaining_validity_secs());
+        assert_remaining_validity(context.expires_at(), min_remaining, &self.config.clock)?;
 
-        let (payer, tx_hash): (
-            alloy_primitives::Address,
-            alloy_primitives::TxHash,
-        ) = match context {
-            PaymentContext::Eip3009 {
-                contract,
-                payment,
-                domain,
-            } => (
-                payment.from,
-                settle_payment(&self.provider, &contract, &payment, &domain).await?,
-            ),
-            PaymentContext::Permit2 {
-                contract,
-                payment,
-                domain,
-            } => {
-                let settlement =
-                    settle_payment_permit2(&self.provider, &contract, &payment, &domain).await?;
-                (
-                    payment.owner,
-                    settlement,
-                )
-            }
-            PaymentContext::Permit2Witness {
-                contract,
-                payment,
-                domain,
-            } => (
-                payment.from,
-                settle_payment_permit2_witness(&self.provider, &contract, &payment, &domain).await?,
-            ),
-        };
-
+        let settled_amount = context.transfer_amount();
+        let (payer, receipt) = settle_context(&self.provider, context, confirmations).await?;
         Ok(v2::SettleResponse::Success {
             payer: payer.to_string(),
-            transaction: tx_hash.to_string(),
+            transaction: receipt.transaction_hash.to_string(),
             network: payload.accepted.network.to_string(),
+            receipt: v2::SettlementReceipt {
+                settled_amount: Some(settled_amount.to_string()),
+                ..settlement_receipt(&receipt, confirmations, required)
+            },
         }
         .into())
     }
 
+    async fn settle_dry_run(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleDryRunResponse, X402SchemeFacilitatorError> {
+        self.verify(request).await?;
+        let request = types::SettleRequest::from_proto(request)?;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+        let allowed_spenders = parse_signer_addresses(self.provider.signer_addresses())?;
+        let from = allowed_spenders.first().copied().unwrap_or_default();
+        let allowed_spenders = allowed_spenders.into_iter().map(Spender).collect();
+        let context = assert_valid_payment(
+            self.provider.inner(),
+            self.provider.chain(),
+            self.provider.contracts(),
+            self.provider.eip712_domains(),
+            payload,
+            requirements,
+            Some(allowed_spenders),
+            &self.config,
+        )
+        .await?;
+        let min_remaining = self
+            .config
+            .settle_validity_secs(self.provider.min_remaining_validity_secs());
+        assert_remaining_validity(context.expires_at(), min_remaining, &self.config.clock)?;
+        let dry_run = DryRunProvider::new(&self.provider, from);
+        let (payer, _) =
+            settle_context::<_, MetaTransactionSendError>(&dry_run, context, 1).await?;
+        let report = dry_run
+            .finish(payer, payload.accepted.network.to_string())
+            .await
+            .map_err(<Eip155ExactError as From<MetaTransactionSendError>>::from)?;
+        Ok(proto::SettleDryRunResponse(
+            serde_json::to_value(report).expect("SettlementDryRun serialization failed"),
+        ))
+    }
+
     async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
         let chain_id = self.provider.chain_id();
         let kinds = vec![proto::SupportedPaymentKind {
             x402_version: v2::X402Version2.into(),
             scheme: ExactScheme.to_string(),
             network: chain_id.clone().into(),
-            extra: None,
+            extra: Some(self.config.supported_extra()),
         }];
         let signers = {
             let mut signers = HashMap::with_capacity(1);
@@ -197,18 +373,74 @@ where
         };
         Ok(proto::SupportedResponse {
             kinds,
-            extensions: Vec::new(),
+            extensions: vec![TOKEN_GATED_DISCOUNT_EXTENSION.to_string()],
             signers,
         })
     }
 }
 
+/// Sends the settlement transactions of a validated payment through `provider`.
+///
+/// Returns the payer and the receipt of the final transaction, once it has
+/// `confirmations` blocks.
+async fn settle_context<P, E>(
+    provider: &P,
+    context: PaymentContext<'_, P::Inner>,
+    confirmations: u64,
+) -> Result<(PayerAddress, TransactionReceipt), Eip155ExactError>
+where
+    P: Eip155MetaTransactionProvider<Error = E>,
+    Eip155ExactError: From<E>,
+{
+    let settled = match context {
+        PaymentContext::Eip3009 {
+            contract,
+            payment,
+            domain,
+        } => (
+            payment.from,
+            settle_payment(provider, &contract, &payment, &domain, confirmations).await?,
+        ),
+        PaymentContext::Eip3009Receive {
+            contract,
+            payment,
+            domain,
+        } => (
+            payment.authorization.from,
+            settle_payment_receive(provider, &contract, &payment, &domain, confirmations).await?,
+        ),
+        PaymentContext::Permit2 {
+            contract,
+            payment,
+            domain,
+        } => (
+            payment.owner,
+            settle_payment_permit2(provider, &contract, &payment, &domain, confirmations).await?,
+        ),
+        PaymentContext::Permit2Witness {
+            contract,
+            payment,
+            domain,
+        } => (
+            payment.from,
+            settle_payment_permit2_witness(provider, &contract, &payment, &domain, confirmations)
+                .await?,
+        ),
+    };
+    Ok(settled)
+}
+
 enum PaymentContext<'a, P: Provider> {
     Eip3009 {
         contract: IEIP3009::IEIP3009Instance<&'a P>,
         payment: ExactEvmPayment,
         domain: Eip712Domain,
     },
+    Eip3009Receive {
+        contract: X402ReceiveForwarder::X402ReceiveForwarderInstance<&'a P>,
+        payment: ExactEvmReceivePayment,
+        domain: Eip712Domain,
+    },
     Permit2 {
         contract: IPermit2::IPermit2Instance<&'a P>,
         payment: Permit2Payment,
@@ -221,19 +453,122 @@ enum PaymentContext<'a, P: Provider> {
     },
 }
 
+impl<P: Provider> PaymentContext<'_, P> {
+    /// Amount the settlement transfers to the payee.
+    fn transfer_amount(&self) -> U256 {
+        match self {
+            PaymentContext::Eip3009 { payment, .. } => payment.value,
+            PaymentContext::Eip3009Receive { payment, .. } => payment.authorization.value,
+            PaymentContext::Permit2 { payment, .. } => payment.transfer_amount,
+            PaymentContext::Permit2Witness { payment, .. } => payment.transfer_amount,
+        }
+    }
+
+    /// When the signed authorization stops being accepted on-chain.
+    fn expires_at(&self) -> UnixTimestamp {
+        match self {
+            PaymentContext::Eip3009 { payment, .. } => payment.valid_before,
+            PaymentContext::Eip3009Receive { payment, .. } => payment.authorization.valid_before,
+            PaymentContext::Permit2 { payment, .. } => {
+                UnixTimestamp::from_secs(payment.sig_deadline.min(payment.expiration))
+            }
+            PaymentContext::Permit2Witness { payment, .. } => payment.deadline,
+        }
+    }
+}
+
+/// The recipient a payment must go to: `payTo`, or with a `stealth` payload, the
+/// one-time address derived from the requirements' `stealthMetaAddress`.
+fn assert_stealth_pay_to(
+    accepted: &types::PaymentRequirements,
+    payload: &ExactEvmPayload,
+) -> Result<PayTo, Eip155ExactError> {
+    let Some(stealth) = payload.stealth.as_ref() else {
+        return Ok(PayTo(accepted.pay_to.address()));
+    };
+    let meta_address = accepted
+        .extra
+        .as_ref()
+        .and_then(|extra| extra.stealth_meta_address.as_ref())
+        .ok_or_else(|| {
+            PaymentVerificationError::InvalidFormat(
+                "stealth payment to requirements without stealthMetaAddress".to_string(),
+            )
+        })?;
+    let stealth_address = meta_address
+        .stealth_address(&stealth.ephemeral_private_key)
+        .map_err(|e| PaymentVerificationError::InvalidFormat(e.to_string()))?;
+    Ok(PayTo(stealth_address.address))
+}
+
+sol! {
+    /// Balance of an ERC-20 or ERC-721 token gating a discount.
+    #[sol(rpc)]
+    interface ITokenGate {
+        function balanceOf(address owner) external view returns (uint256);
+    }
+}
+
+/// Returns the amount the payer must transfer for a payment signed over `paid`.
+///
+/// That is the requirements `amount`, or a lower amount of a token-gated discount
+/// if the payer holds its gate token on chain, see [`crate::discount`]. Discounts
+/// are only claimed by paying their exact amount, whatever the amount matching.
+///
+/// With [`AmountMatching::AtMost`] the requirements `amount` is a maximum and the
+/// payer owes what they signed, as long as [`assert_amount_matching`] accepts it.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(payer = %payer, paid = %paid)))]
+async fn assert_payable_amount<P: Provider>(
+    provider: &P,
+    accepted: &types::PaymentRequirements,
+    payer: PayerAddress,
+    paid: U256,
+) -> Result<U256, Eip155ExactError> {
+    let price: U256 = accepted.amount.into();
+    let extra = accepted.extra.as_ref();
+    if extra.is_some_and(|extra| extra.amount_matching == AmountMatching::AtMost) {
+        assert_amount_matching(&paid, &price, AmountMatching::AtMost)?;
+        return Ok(paid);
+    }
+    if paid >= price {
+        return Ok(price);
+    }
+    let discounts = extra
+        .map(|extra| extra.discounts.as_slice())
+        .unwrap_or_default();
+    for discount in matching_discounts(discounts, paid) {
+        let balance = ITokenGate::new(discount.token, provider)
+            .balanceOf(payer.address())
+            .call()
+            .await?;
+        if discount.is_held(balance) {
+            return Ok(paid);
+        }
+    }
+    Err(PaymentVerificationError::invalid_amount(price, paid).into())
+}
+
 /// Runs all preconditions needed for a successful payment:
-/// - Valid scheme, network, and receiver.
+/// - Valid scheme, network, and receiver (`payTo` or a stealth address derived from
+///   `stealthMetaAddress`).
+/// - Token and transferred amount accepted by the asset policy, before any RPC call.
+/// - Amount of the requirements, or of a token-gated discount the payer holds.
 /// - Valid time window (validAfter/validBefore).
 /// - Correct EIP-712 domain construction.
 /// - Sufficient on-chain balance.
-/// - Sufficient value in payload.
+/// - Value in payload matching the required amount, see
+///   [`AmountMatching`](crate::v1_eip155_exact::types::AmountMatching).
 #[cfg_attr(feature = "telemetry", instrument(skip_all, err))]
+#[allow(clippy::too_many_arguments)]
 async fn assert_valid_payment<'a, P: Provider>(
     provider: &'a P,
     chain: &'a Eip155ChainReference,
+    contracts: &Eip155Contracts,
+    domains: Option<&Eip712DomainCache>,
     payload: &'a types::PaymentPayload,
     requirements: &'a types::PaymentRequirements,
-    allowed_spenders: Option<Vec<alloy_primitives::Address>>,
+    allowed_spenders: Option<Vec<Spender>>,
+    config: &Eip155ExactConfig,
 ) -> Result<PaymentContext<'a, P>, Eip155ExactError> {
     let accepted = &payload.accepted;
     if accepted != requirements {
@@ -246,77 +581,91 @@ async fn assert_valid_payment<'a, P: Provider>(
     if payload_chain_id != &chain_id {
         return Err(PaymentVerificationError::ChainIdMismatch.into());
     }
-    if let Some(asset_chain_id) = accepted.asset.chain_id() {
-        if asset_chain_id != &chain_id {
-            return Err(PaymentVerificationError::ChainIdMismatch.into());
-        }
+    if let Some(asset_chain_id) = accepted.asset.chain_id()
+        && asset_chain_id != &chain_id
+    {
+        return Err(PaymentVerificationError::ChainIdMismatch.into());
     }
-    if let Some(asset_chain_id) = requirements.asset.chain_id() {
-        if asset_chain_id != &chain_id {
-            return Err(PaymentVerificationError::ChainIdMismatch.into());
-        }
+    if let Some(asset_chain_id) = requirements.asset.chain_id()
+        && asset_chain_id != &chain_id
+    {
+        return Err(PaymentVerificationError::ChainIdMismatch.into());
     }
+    let pay_to = assert_stealth_pay_to(accepted, payload)?;
+    let amount_matching = accepted
+        .extra
+        .as_ref()
+        .map(|extra| extra.amount_matching)
+        .unwrap_or_default();
     if let Some(permit2_auth) = payload.permit2_authorization.as_ref() {
-        let proxy_address = x402_exact_permit2_proxy_address();
         let asset_address: alloy_primitives::Address = accepted.asset.address();
-        let amount_required = accepted.amount;
-        let amount_required_u256: alloy_primitives::U256 = amount_required.into();
+        let payer = PayerAddress(permit2_auth.from);
+        let amount = permit2_auth.permitted.amount;
 
         if permit2_auth.permitted.token != asset_address {
-            return Err(PaymentVerificationError::AssetMismatch.into());
+            return Err(PaymentVerificationError::asset_mismatch(
+                asset_address,
+                permit2_auth.permitted.token,
+            )
+            .into());
         }
-        if permit2_auth.spender != proxy_address {
+        // Any configured proxy is accepted; the payment settles through the one it names.
+        let spender = Spender(permit2_auth.spender);
+        if !config.accepts_permit2_proxy(&spender.address()) {
             return Err(PaymentVerificationError::InvalidFormat(
                 "permit2Authorization.spender must be the x402 Permit2 proxy".to_string(),
             )
             .into());
         }
-        if permit2_auth.witness.to != accepted.pay_to.address() {
-            return Err(PaymentVerificationError::RecipientMismatch.into());
-        }
-        if permit2_auth.permitted.amount != amount_required_u256 {
-            return Err(PaymentVerificationError::InvalidPaymentAmount.into());
+        let proxy_address = spender.address();
+        if PayTo(permit2_auth.witness.to) != pay_to {
+            return Err(PaymentVerificationError::recipient_mismatch(
+                pay_to,
+                permit2_auth.witness.to,
+            )
+            .into());
         }
+        assert_asset_allowed(config.allowed_assets.as_ref(), &asset_address, amount)?;
+        let amount_required = assert_payable_amount(provider, accepted, payer, amount).await?;
+        assert_amount_matching(&amount, &amount_required, amount_matching)?;
 
         assert_permit2_witness_time(
             permit2_auth.deadline,
             permit2_auth.witness.valid_after,
             accepted.max_timeout_seconds,
+            config.grace_buffer_seconds,
+            &config.clock,
         )?;
 
-        let erc20_contract = IEIP3009::new(asset_address, provider);
-        assert_enough_balance(&erc20_contract, &permit2_auth.from, amount_required_u256).await?;
-
-        let allowance = erc20_contract
-            .allowance(permit2_auth.from, crate::v1_eip155_exact::facilitator::PERMIT2_ADDRESS)
-            .call()
-            .await
-            .map_err(|e| PaymentVerificationError::TransactionSimulation(e.to_string()))?;
-        if allowance < amount_required_u256 {
-            return Err(PaymentVerificationError::TransactionSimulation(
-                "Permit2 ERC20 allowance is insufficient".to_string(),
-            )
-            .into());
-        }
+        let token_state = fetch_token_state(
+            provider,
+            asset_address,
+            payer,
+            Some(contracts.permit2),
+            false,
+        )
+        .await?;
+        token_state.assert_enough_balance(amount)?;
+        token_state.assert_enough_allowance(amount)?;
 
         let signature = payload.signature.clone().ok_or_else(|| {
             PaymentVerificationError::InvalidFormat("Missing signature".to_string())
         })?;
 
-        let domain = assert_permit2_witness_domain(chain);
+        let domain = assert_permit2_witness_domain(chain, contracts.permit2);
         let contract = X402ExactPermit2Proxy::new(proxy_address, provider);
         let payment = Permit2WitnessPayment {
-            from: permit2_auth.from,
-            spender: permit2_auth.spender,
+            from: payer,
+            spender,
             token: asset_address,
             amount: permit2_auth.permitted.amount,
             nonce: permit2_auth.nonce,
             deadline: permit2_auth.deadline,
-            pay_to: accepted.pay_to.address(),
+            pay_to,
             valid_after: permit2_auth.witness.valid_after,
             extra: permit2_auth.witness.extra.clone(),
             signature,
-            transfer_amount: amount_required_u256,
+            transfer_amount: amount,
         };
 
         Ok(PaymentContext::Permit2Witness {
@@ -330,40 +679,57 @@ async fn assert_valid_payment<'a, P: Provider>(
         let asset_address: alloy_primitives::Address = accepted.asset.address();
 
         if details.token != asset_address {
-            return Err(PaymentVerificationError::AssetMismatch.into());
+            return Err(
+                PaymentVerificationError::asset_mismatch(asset_address, details.token).into(),
+            );
         }
-        if let Some(spenders) = allowed_spenders.as_ref() {
-            if !spenders.iter().any(|s| *s == permit_single.spender) {
-                return Err(PaymentVerificationError::RecipientMismatch.into());
-            }
+        let spender = Spender(permit_single.spender);
+        if let Some(spenders) = allowed_spenders.as_ref()
+            && !spenders.contains(&spender)
+        {
+            return Err(PaymentVerificationError::recipient_mismatch(
+                join_spenders(spenders),
+                spender,
+            )
+            .into());
         }
 
         let sig_deadline = UnixTimestamp::from_secs(permit_single.sig_deadline);
         let expiration = UnixTimestamp::from_secs(details.expiration);
-        assert_permit2_time(sig_deadline, expiration)?;
+        assert_permit2_time(
+            sig_deadline,
+            expiration,
+            config.grace_buffer_seconds,
+            &config.clock,
+        )?;
 
-        let amount_required = accepted.amount;
-        assert_enough_value(&details.amount, &amount_required.into())?;
+        let payer = PayerAddress(permit2.owner);
+        assert_asset_allowed(
+            config.allowed_assets.as_ref(),
+            &asset_address,
+            details.amount,
+        )?;
+        let amount_required =
+            assert_payable_amount(provider, accepted, payer, details.amount).await?;
+        assert_amount_matching(&details.amount, &amount_required, amount_matching)?;
 
-        let erc20_contract = IEIP3009::new(asset_address, provider);
-        assert_enough_balance(&erc20_contract, &permit2.owner, amount_required.into()).await?;
+        fetch_token_state(provider, asset_address, payer, None, false)
+            .await?
+            .assert_enough_balance(details.amount)?;
 
-        let domain = assert_permit2_domain(chain);
-        let contract = IPermit2::new(
-            crate::v1_eip155_exact::facilitator::PERMIT2_ADDRESS,
-            provider,
-        );
+        let domain = assert_permit2_domain(chain, contracts.permit2);
+        let contract = IPermit2::new(contracts.permit2, provider);
         let payment = Permit2Payment {
-            owner: permit2.owner,
-            spender: permit_single.spender,
-            pay_to: accepted.pay_to.address(),
+            owner: payer,
+            spender,
+            pay_to,
             token: details.token,
             amount: details.amount,
             expiration: details.expiration,
             nonce: details.nonce,
             sig_deadline: permit_single.sig_deadline,
             signature: permit2.signature.clone(),
-            transfer_amount: amount_required.into(),
+            transfer_amount: details.amount,
         };
 
         Ok(PaymentContext::Permit2 {
@@ -375,23 +741,49 @@ async fn assert_valid_payment<'a, P: Provider>(
         let authorization = payload.authorization.as_ref().ok_or_else(|| {
             PaymentVerificationError::InvalidFormat("Missing authorization".to_string())
         })?;
-        if authorization.to != accepted.pay_to.address() {
-            return Err(PaymentVerificationError::RecipientMismatch.into());
+        let receive_forwarder = config.receive_forwarder(&accepted.asset.address());
+        match receive_forwarder {
+            Some(forwarder) => assert_receive_recipient(authorization, forwarder, pay_to)?,
+            None if PayTo(authorization.to) != pay_to => {
+                return Err(
+                    PaymentVerificationError::recipient_mismatch(pay_to, authorization.to).into(),
+                );
+            }
+            None => {}
         }
         let valid_after = authorization.valid_after;
         let valid_before = authorization.valid_before;
-        assert_time(valid_after, valid_before)?;
+        assert_time(
+            valid_after,
+            valid_before,
+            config.grace_buffer_seconds,
+            &config.clock,
+        )?;
         let asset_address = accepted.asset.address();
         let contract = IEIP3009::new(asset_address, provider);
 
-        let domain = assert_domain(chain, &contract, &asset_address, &accepted.extra).await?;
-
-        let amount_required = accepted.amount;
-        assert_enough_balance(&contract, &authorization.from, amount_required.into()).await?;
-        assert_enough_value(&authorization.value, &amount_required.into())?;
+        let payer = PayerAddress(authorization.from);
+        assert_asset_allowed(
+            config.allowed_assets.as_ref(),
+            &asset_address,
+            authorization.value,
+        )?;
+        let amount_required =
+            assert_payable_amount(provider, accepted, payer, authorization.value).await?;
+        assert_amount_matching(&authorization.value, &amount_required, amount_matching)?;
+        let token_state = fetch_token_state_with_domain(
+            provider,
+            domains,
+            asset_address,
+            payer,
+            accepted.extra.is_some(),
+        )
+        .await?;
+        let domain = token_state.domain(chain, &asset_address, &accepted.extra)?;
+        token_state.assert_enough_balance(authorization.value)?;
 
         let payment = ExactEvmPayment {
-            from: authorization.from,
+            from: payer,
             to: authorization.to,
             value: authorization.value,
             valid_after: authorization.valid_after,
@@ -402,10 +794,21 @@ async fn assert_valid_payment<'a, P: Provider>(
             })?,
         };
 
-        Ok(PaymentContext::Eip3009 {
-            contract,
-            payment,
-            domain,
-        })
+        match receive_forwarder {
+            Some(forwarder) => Ok(PaymentContext::Eip3009Receive {
+                contract: X402ReceiveForwarder::new(forwarder, provider),
+                payment: ExactEvmReceivePayment {
+                    authorization: payment,
+                    token: asset_address,
+                    pay_to,
+                },
+                domain,
+            }),
+            None => Ok(PaymentContext::Eip3009 {
+                contract,
+                payment,
+                domain,
+            }),
+        }
     }
 }
@@ -32,6 +32,7 @@
 //!     eip712: Some(TokenDeploymentEip712 {
 //!         name: "BBT".into(),
 //!         version: "1".into(),
+//!         domain: Default::default(),
 //!     }),
 //! };
 //! let price = V2Eip155Exact::price_tag(
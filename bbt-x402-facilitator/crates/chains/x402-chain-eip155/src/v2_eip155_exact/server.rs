@@ -5,12 +5,35 @@
 //! chain IDs instead of network names.
 
 use alloy_primitives::U256;
+use std::sync::Arc;
 use x402_types::chain::{ChainId, DeployedTokenAmount};
+use x402_types::proto::display::DisplayMetadata;
 use x402_types::proto::v2;
 
 use crate::V2Eip155Exact;
 use crate::chain::{ChecksummedAddress, Eip155TokenDeployment};
+use crate::discount::{DiscountError, TOKEN_GATED_DISCOUNT_EXTENSION, TokenGatedDiscount};
+#[cfg(feature = "oracle")]
+use crate::oracle::{OracleError, PriceOracle};
+use crate::stealth::{StealthError, StealthMetaAddress};
 use crate::v1_eip155_exact::ExactScheme;
+use crate::v1_eip155_exact::types::{AmountMatching, PaymentRequirementsExtra};
+
+/// Errors that can occur while setting the amount matching of a price tag.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AmountMatchingError {
+    #[error("Amount matching needs the EIP-712 domain of the token in the requirements extra")]
+    MissingTokenDomain,
+}
+
+/// Errors that can occur while adding display metadata to a price tag.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DisplayError {
+    #[error("Display metadata needs the EIP-712 domain of the token in the requirements extra")]
+    MissingTokenDomain,
+    #[error("Invalid requirements amount {0}")]
+    InvalidAmount(String),
+}
 
 impl V2Eip155Exact {
     /// Creates a V2 price tag for an ERC-3009 payment on an EVM chain.
@@ -21,7 +44,7 @@ impl V2Eip155Exact {
     ///
     /// # Parameters
     ///
-    /// - `pay_to`: The recipient address (can be any type convertible to [`ChecksummedAddress`])
+    /// - `pay_to`: The recipient address (can be any type convertible to [`ChecksummedAddress`]).
     /// - `asset`: The token deployment and amount required
     ///
     /// # Returns
@@ -42,6 +65,7 @@ impl V2Eip155Exact {
     ///     eip712: Some(TokenDeploymentEip712 {
     ///         name: "BBT".into(),
     ///         version: "1".into(),
+    ///         domain: Default::default(),
     ///     }),
     /// };
     /// let price_tag = V2Eip155Exact::price_tag(
@@ -73,4 +97,254 @@ impl V2Eip155Exact {
             enricher: None,
         }
     }
+
+    /// Creates one V2 price tag per accepted token, all paying to `pay_to`.
+    ///
+    /// Lets a resource be paid in whichever token the payer holds, each at its own
+    /// amount. The tags are advertised together in the `accepts` list, in the given
+    /// order, which is the order clients without preferences try them in.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use x402_chain_eip155::V2Eip155Exact;
+    ///
+    /// let price_tags = V2Eip155Exact::price_tags(
+    ///     "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb",
+    ///     [usdc.parse("0.05")?, bbt.parse("2")?],
+    /// );
+    /// let layer = x402.with_price_tags(price_tags);
+    /// ```
+    #[allow(dead_code)] // Public for consumption by downstream crates.
+    pub fn price_tags<A: Into<ChecksummedAddress>>(
+        pay_to: A,
+        assets: impl IntoIterator<Item = DeployedTokenAmount<U256, Eip155TokenDeployment>>,
+    ) -> Vec<v2::PriceTag> {
+        let pay_to = pay_to.into();
+        assets
+            .into_iter()
+            .map(|asset| Self::price_tag(pay_to, asset))
+            .collect()
+    }
+
+    /// Creates a V2 price tag paying to one-time ERC-5564 stealth addresses of `meta_address`.
+    ///
+    /// `payTo` is set to the address of the spending key, which clients without stealth
+    /// support pay to, and `extra.stealthMetaAddress` to the meta-address. See
+    /// [`crate::stealth`] for how payments are derived and recovered.
+    ///
+    /// Fails with [`StealthError::MissingTokenDomain`] if the token deployment has no
+    /// EIP-712 domain, as the requirements `extra` must carry it.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use x402_chain_eip155::V2Eip155Exact;
+    /// use x402_chain_eip155::stealth::StealthMetaAddress;
+    ///
+    /// let meta_address: StealthMetaAddress = "st:eth:0x02...03...".parse()?;
+    /// let price_tag = V2Eip155Exact::stealth_price_tag(&meta_address, bbt.amount(10_000_000_000_000_000u64))?;
+    /// ```
+    #[allow(dead_code)] // Public for consumption by downstream crates.
+    pub fn stealth_price_tag(
+        meta_address: &StealthMetaAddress,
+        asset: DeployedTokenAmount<U256, Eip155TokenDeployment>,
+    ) -> Result<v2::PriceTag, StealthError> {
+        let eip712 = asset
+            .token
+            .eip712
+            .clone()
+            .ok_or(StealthError::MissingTokenDomain)?;
+        let extra = PaymentRequirementsExtra {
+            name: eip712.name,
+            version: eip712.version,
+            domain: eip712.domain,
+            receive_forwarder: None,
+            stealth_meta_address: Some(meta_address.clone()),
+            discounts: Vec::new(),
+            amount_matching: AmountMatching::Exact,
+            display: None,
+        };
+        let mut price_tag = Self::price_tag(meta_address.spending_address(), asset);
+        price_tag.requirements.extra = serde_json::to_value(&extra).ok();
+        Ok(price_tag)
+    }
+
+    /// Creates a V2 price tag with lower amounts for holders of a token, e.g. a membership NFT.
+    ///
+    /// The price tag enricher adds the `discounts` to the requirements `extra` only when
+    /// the facilitator lists the [`TOKEN_GATED_DISCOUNT_EXTENSION`] on `/supported`, as
+    /// other facilitators would reject a discounted amount. The facilitator checks the
+    /// holding on chain, see [`crate::discount`].
+    ///
+    /// Fails with [`DiscountError::MissingTokenDomain`] if the token deployment has no
+    /// EIP-712 domain, and with [`DiscountError::NotADiscount`] if a discounted amount
+    /// is not below the price.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use alloy_primitives::address;
+    /// use x402_chain_eip155::V2Eip155Exact;
+    /// use x402_chain_eip155::discount::TokenGatedDiscount;
+    ///
+    /// let members = address!("0x5Af0D9827E0c53E4799BB226655A1de152A425a5");
+    /// let price_tag = V2Eip155Exact::token_gated_price_tag(
+    ///     "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb",
+    ///     bbt.amount(10_000_000_000_000_000u64),
+    ///     vec![TokenGatedDiscount::for_holders(members, 8_000_000_000_000_000u64)],
+    /// )?;
+    /// ```
+    #[allow(dead_code)] // Public for consumption by downstream crates.
+    pub fn token_gated_price_tag<A: Into<ChecksummedAddress>>(
+        pay_to: A,
+        asset: DeployedTokenAmount<U256, Eip155TokenDeployment>,
+        discounts: Vec<TokenGatedDiscount>,
+    ) -> Result<v2::PriceTag, DiscountError> {
+        if asset.token.eip712.is_none() {
+            return Err(DiscountError::MissingTokenDomain);
+        }
+        if let Some(discount) = discounts.iter().find(|d| d.amount.0 >= asset.amount) {
+            return Err(DiscountError::NotADiscount {
+                discounted: discount.amount.0,
+                price: asset.amount,
+            });
+        }
+        let mut price_tag = Self::price_tag(pay_to, asset);
+        price_tag.enricher = Some(Arc::new(move |price_tag, capabilities| {
+            if !capabilities
+                .extensions
+                .iter()
+                .any(|extension| extension == TOKEN_GATED_DISCOUNT_EXTENSION)
+            {
+                return;
+            }
+            // Keeps what was set on the extra since, such as the amount matching.
+            let extra = price_tag.requirements.extra.as_ref().and_then(|extra| {
+                serde_json::from_value::<PaymentRequirementsExtra>(extra.clone()).ok()
+            });
+            if let Some(mut extra) = extra {
+                extra.discounts = discounts.clone();
+                price_tag.requirements.extra = serde_json::to_value(&extra).ok();
+            }
+        }));
+        Ok(price_tag)
+    }
+
+    /// Creates a V2 price tag for a fiat `price`, such as `$0.05`, converted to an amount
+    /// of `token` at the current rate of `oracle`.
+    ///
+    /// Meant to be called per request, e.g. in `X402Middleware::with_dynamic_price`, so
+    /// the amount follows the token's market price. Fails if the rate is unavailable or
+    /// stale, see [`crate::oracle`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use x402_chain_eip155::V2Eip155Exact;
+    ///
+    /// let price_tag = V2Eip155Exact::price_tag_fiat(
+    ///     "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb",
+    ///     &wxtz,
+    ///     "$0.05",
+    ///     &oracle,
+    /// )
+    /// .await?;
+    /// ```
+    #[cfg(feature = "oracle")]
+    #[allow(dead_code)] // Public for consumption by downstream crates.
+    pub async fn price_tag_fiat<A: Into<ChecksummedAddress>>(
+        pay_to: A,
+        token: &Eip155TokenDeployment,
+        price: &str,
+        oracle: &PriceOracle,
+    ) -> Result<v2::PriceTag, OracleError> {
+        let amount = oracle.token_amount(price, token.decimals).await?;
+        Ok(Self::price_tag(pay_to, token.amount(amount)))
+    }
+
+    /// Sets how the amount signed by payers of `price_tag` must compare to its amount.
+    ///
+    /// With [`AmountMatching::AtLeast`], the facilitator accepts payments signed for
+    /// more than the amount, e.g. because of client rounding, settles the signed amount
+    /// and reports it as `settledAmount` in the settle response. With
+    /// [`AmountMatching::AtMost`], the amount is a maximum and payments signed for
+    /// less are accepted alike.
+    ///
+    /// Fails with [`AmountMatchingError::MissingTokenDomain`] if the requirements `extra`
+    /// does not carry the token's EIP-712 domain.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use x402_chain_eip155::V2Eip155Exact;
+    /// use x402_chain_eip155::v1_eip155_exact::AmountMatching;
+    ///
+    /// let price_tag = V2Eip155Exact::with_amount_matching(
+    ///     V2Eip155Exact::price_tag(pay_to, bbt.parse("0.01")?),
+    ///     AmountMatching::AtLeast,
+    /// )?;
+    /// ```
+    #[allow(dead_code)] // Public for consumption by downstream crates.
+    pub fn with_amount_matching(
+        mut price_tag: v2::PriceTag,
+        amount_matching: AmountMatching,
+    ) -> Result<v2::PriceTag, AmountMatchingError> {
+        let mut extra: PaymentRequirementsExtra = price_tag
+            .requirements
+            .extra
+            .take()
+            .and_then(|extra| serde_json::from_value(extra).ok())
+            .ok_or(AmountMatchingError::MissingTokenDomain)?;
+        extra.amount_matching = amount_matching;
+        price_tag.requirements.extra = serde_json::to_value(&extra).ok();
+        Ok(price_tag)
+    }
+
+    /// Adds display metadata for wallets and UIs to `price_tag`.
+    ///
+    /// Unless `display` already has one, the formatted amount is computed from the
+    /// requirements amount with the token's `decimals`, in the locale of `display`.
+    /// The metadata is informational only, see [`x402_types::proto::display`].
+    ///
+    /// Fails with [`DisplayError::MissingTokenDomain`] if the requirements `extra`
+    /// does not carry the token's EIP-712 domain.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use x402_chain_eip155::V2Eip155Exact;
+    /// use x402_types::proto::display::DisplayMetadata;
+    ///
+    /// let price_tag = V2Eip155Exact::with_display(
+    ///     V2Eip155Exact::price_tag(pay_to, usdc.parse("1234.5")?),
+    ///     usdc.decimals,
+    ///     DisplayMetadata::new("USDC")
+    ///         .with_logo_uri("https://example.com/usdc.svg")
+    ///         .with_locale("de-DE"),
+    /// )?;
+    /// ```
+    #[allow(dead_code)] // Public for consumption by downstream crates.
+    pub fn with_display(
+        mut price_tag: v2::PriceTag,
+        decimals: u8,
+        mut display: DisplayMetadata,
+    ) -> Result<v2::PriceTag, DisplayError> {
+        let mut extra: PaymentRequirementsExtra = price_tag
+            .requirements
+            .extra
+            .take()
+            .and_then(|extra| serde_json::from_value(extra).ok())
+            .ok_or(DisplayError::MissingTokenDomain)?;
+        if display.formatted_amount.is_none() {
+            let amount: U256 =
+                price_tag.requirements.amount.parse().map_err(|_| {
+                    DisplayError::InvalidAmount(price_tag.requirements.amount.clone())
+                })?;
+            display = display.with_amount(amount, decimals);
+        }
+        extra.display = Some(display);
+        price_tag.requirements.extra = serde_json::to_value(&extra).ok();
+        Ok(price_tag)
+    }
 }
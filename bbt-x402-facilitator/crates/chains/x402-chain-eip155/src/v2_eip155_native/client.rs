@@ -0,0 +1,203 @@
+//! Client-side payment signing for the V2 EIP-155 "native" scheme.
+//!
+//! This module provides [`V2Eip155NativeClient`] for signing native coin payments
+//! against an escrow deposit. The payer must have deposited enough into the escrow
+//! named by the requirements beforehand.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! use x402_chain_eip155::v2_eip155_native::client::V2Eip155NativeClient;
+//! use alloy_signer_local::PrivateKeySigner;
+//!
+//! let signer = PrivateKeySigner::random();
+//! let client = V2Eip155NativeClient::new(signer);
+//! ```
+
+use alloy_primitives::{Bytes, FixedBytes};
+use async_trait::async_trait;
+use rand::{Rng, rng};
+use x402_types::proto::v2::ResourceInfo;
+use x402_types::proto::{PaymentRequired, v2};
+use x402_types::scheme::X402SchemeId;
+use x402_types::scheme::client::{
+    PaymentCandidate, PaymentCandidateSigner, ValidityWindow, X402Error, X402SchemeClient,
+};
+use x402_types::timestamp::UnixTimestamp;
+use x402_types::util::Base64Bytes;
+
+use crate::chain::Eip155ChainReference;
+use crate::v1_eip155_exact::ExactEvmPayloadAuthorization;
+use crate::v1_eip155_exact::client::SignerLike;
+use crate::v2_eip155_native::{
+    NATIVE_ASSET, NativeEvmPayload, V2Eip155Native, native_escrow_domain, native_payment_hash,
+    types,
+};
+
+/// Client for signing V2 EIP-155 native scheme payments.
+///
+/// # Type Parameters
+///
+/// - `S`: The signer type, which must implement [`SignerLike`]
+#[derive(Debug)]
+#[allow(dead_code)] // Public for consumption by downstream crates.
+pub struct V2Eip155NativeClient<S> {
+    signer: S,
+}
+
+#[allow(dead_code)] // Public for consumption by downstream crates.
+impl<S> V2Eip155NativeClient<S> {
+    /// Creates a new V2 EIP-155 native scheme client with the given signer.
+    pub fn new(signer: S) -> Self {
+        Self { signer }
+    }
+}
+
+impl<S> X402SchemeId for V2Eip155NativeClient<S> {
+    fn namespace(&self) -> &str {
+        V2Eip155Native.namespace()
+    }
+
+    fn scheme(&self) -> &str {
+        V2Eip155Native.scheme()
+    }
+}
+
+impl<S> X402SchemeClient for V2Eip155NativeClient<S>
+where
+    S: SignerLike + Clone + Send + Sync + 'static,
+{
+    fn accept(&self, payment_required: &PaymentRequired) -> Vec<PaymentCandidate> {
+        let payment_required = match payment_required {
+            PaymentRequired::V2(payment_required) => payment_required,
+            PaymentRequired::V1(_) => {
+                return vec![];
+            }
+        };
+        payment_required
+            .accepts
+            .iter()
+            .filter_map(|v| {
+                let requirements: types::PaymentRequirements = v.as_concrete()?;
+                if requirements.asset.address() != NATIVE_ASSET || requirements.extra.is_none() {
+                    return None;
+                }
+                let chain_reference = Eip155ChainReference::try_from(&requirements.network).ok()?;
+                let candidate = PaymentCandidate {
+                    chain_id: requirements.network.clone(),
+                    asset: requirements.asset.to_string(),
+                    amount: requirements.amount.into(),
+                    scheme: self.scheme().to_string(),
+                    x402_version: self.x402_version(),
+                    pay_to: requirements.pay_to.to_string(),
+                    transfer_method: Some("native".to_string()),
+                    signer: Box::new(PayloadSigner {
+                        resource_info: Some(payment_required.resource.clone()),
+                        signer: self.signer.clone(),
+                        chain_reference,
+                        requirements,
+                    }),
+                };
+                Some(candidate)
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+/// Signs a [`NativePayment`](crate::v2_eip155_native::NativePayment) for `requirements`.
+///
+/// Without an explicit `validity`, the payment is valid from ten minutes ago until
+/// `maxTimeoutSeconds` from now.
+#[allow(dead_code)] // Public for consumption by downstream crates.
+pub async fn sign_native_payment<S: SignerLike + Sync>(
+    signer: &S,
+    chain_reference: &Eip155ChainReference,
+    requirements: &types::PaymentRequirements,
+    validity: Option<ValidityWindow>,
+) -> Result<NativeEvmPayload, X402Error> {
+    let escrow = requirements
+        .extra
+        .as_ref()
+        .map(|extra| extra.escrow)
+        .ok_or_else(|| X402Error::SigningError("Missing extra.escrow".to_string()))?;
+    let (valid_after, valid_before) = match validity {
+        Some(window) => (window.valid_after, window.valid_before),
+        None => {
+            let now = UnixTimestamp::now();
+            (
+                UnixTimestamp::from_secs(now.as_secs().saturating_sub(10 * 60)),
+                now + requirements.max_timeout_seconds,
+            )
+        }
+    };
+    let authorization = ExactEvmPayloadAuthorization {
+        from: signer.address(),
+        to: requirements.pay_to.address(),
+        value: requirements.amount.into(),
+        valid_after,
+        valid_before,
+        nonce: FixedBytes(rng().random()),
+    };
+    let hash = native_payment_hash(
+        &authorization,
+        &native_escrow_domain(chain_reference, escrow),
+    );
+    let signature = signer
+        .sign_hash(&hash)
+        .await
+        .map_err(|e| X402Error::SigningError(format!("{e:?}")))?;
+    Ok(NativeEvmPayload {
+        signature: Bytes::from(signature.as_bytes().to_vec()),
+        authorization,
+    })
+}
+
+#[allow(dead_code)] // Public for consumption by downstream crates.
+struct PayloadSigner<S> {
+    signer: S,
+    resource_info: Option<ResourceInfo>,
+    chain_reference: Eip155ChainReference,
+    requirements: types::PaymentRequirements,
+}
+
+#[async_trait]
+impl<S> PaymentCandidateSigner for PayloadSigner<S>
+where
+    S: Sync + SignerLike,
+{
+    async fn sign_payment(&self) -> Result<String, X402Error> {
+        self.sign_with_validity(None).await
+    }
+
+    async fn sign_payment_within(&self, window: ValidityWindow) -> Result<String, X402Error> {
+        self.sign_with_validity(Some(window)).await
+    }
+}
+
+impl<S> PayloadSigner<S>
+where
+    S: Sync + SignerLike,
+{
+    async fn sign_with_validity(
+        &self,
+        validity: Option<ValidityWindow>,
+    ) -> Result<String, X402Error> {
+        let native_payload = sign_native_payment(
+            &self.signer,
+            &self.chain_reference,
+            &self.requirements,
+            validity,
+        )
+        .await?;
+        let payload = types::PaymentPayload {
+            x402_version: v2::X402Version2,
+            accepted: self.requirements.clone(),
+            resource: self.resource_info.clone(),
+            payload: native_payload,
+        };
+        let json = serde_json::to_vec(&payload)?;
+        let b64 = Base64Bytes::encode(&json);
+
+        Ok(b64.to_string())
+    }
+}
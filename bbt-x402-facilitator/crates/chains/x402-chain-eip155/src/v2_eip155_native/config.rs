@@ -0,0 +1,113 @@
+//! Scheme configuration for the EIP-155 "native" facilitator.
+//!
+//! The facilitator only settles through escrows it knows, listed in the scheme's
+//! `config` section. During an escrow migration both deployments are listed, the new
+//! one first: `/supported` advertises it as `escrow`, while payments naming the old
+//! one still verify and settle through it.
+//!
+//! ```json
+//! {
+//!   "id": "v2-eip155-native",
+//!   "chains": "eip155:42793",
+//!   "config": {
+//!     "escrows": ["0x..."],
+//!     "graceBufferSeconds": 6
+//!   }
+//! }
+//! ```
+//!
+//! Unknown keys, malformed values and an empty escrow list fail the scheme build
+//! with an [`Eip155NativeConfigError`].
+
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+use x402_types::timestamp::SharedClock;
+
+use crate::v1_eip155_exact::settlement::DEFAULT_GRACE_BUFFER_SECONDS;
+
+/// Errors in the scheme configuration of the EIP-155 "native" facilitator.
+#[derive(Debug, thiserror::Error)]
+pub enum Eip155NativeConfigError {
+    #[error("Invalid native scheme config: {0}")]
+    Invalid(#[from] serde_json::Error),
+    #[error("Native scheme config lists no escrow")]
+    NoEscrow,
+}
+
+/// Scheme configuration for the EIP-155 "native" facilitator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Eip155NativeConfig {
+    /// Accepted native escrows, preferred first.
+    pub escrows: Vec<Address>,
+    /// Seconds an authorization must remain valid past now, covering the time to
+    /// settle it.
+    #[serde(default = "default_grace_buffer_seconds")]
+    pub grace_buffer_seconds: u64,
+    /// Clock the time windows are checked against; the system clock outside tests.
+    #[serde(skip)]
+    pub clock: SharedClock,
+}
+
+fn default_grace_buffer_seconds() -> u64 {
+    DEFAULT_GRACE_BUFFER_SECONDS
+}
+
+impl Eip155NativeConfig {
+    /// Parses the scheme-specific `config` value, which is required.
+    pub fn from_value(value: Option<serde_json::Value>) -> Result<Self, Eip155NativeConfigError> {
+        let config: Self = serde_json::from_value(value.unwrap_or_default())?;
+        if config.escrows.is_empty() {
+            return Err(Eip155NativeConfigError::NoEscrow);
+        }
+        Ok(config)
+    }
+
+    /// The escrow new payments should go through.
+    pub fn preferred_escrow(&self) -> Address {
+        self.escrows[0]
+    }
+
+    /// Returns whether `escrow` is an accepted native escrow.
+    pub fn accepts_escrow(&self, escrow: &Address) -> bool {
+        self.escrows.contains(escrow)
+    }
+
+    /// The `extra` advertised with the scheme on `/supported`.
+    pub fn supported_extra(&self) -> serde_json::Value {
+        serde_json::json!({ "escrow": self.preferred_escrow() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn test_escrow_migration() {
+        let new = address!("0x4444444444444444444444444444444444444444");
+        let old = address!("0x5555555555555555555555555555555555555555");
+        let config = Eip155NativeConfig::from_value(Some(serde_json::json!({
+            "escrows": [new, old]
+        })))
+        .unwrap();
+        assert_eq!(config.preferred_escrow(), new);
+        assert!(config.accepts_escrow(&old));
+        assert!(!config.accepts_escrow(&Address::ZERO));
+        assert_eq!(config.grace_buffer_seconds, DEFAULT_GRACE_BUFFER_SECONDS);
+        assert_eq!(
+            config.supported_extra(),
+            serde_json::json!({ "escrow": new })
+        );
+
+        assert!(matches!(
+            Eip155NativeConfig::from_value(Some(serde_json::json!({ "escrows": [] }))),
+            Err(Eip155NativeConfigError::NoEscrow)
+        ));
+        assert!(matches!(
+            Eip155NativeConfig::from_value(None),
+            Err(Eip155NativeConfigError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,374 @@
+//! Facilitator-side payment verification and settlement for the V2 EIP-155 native scheme.
+//!
+//! Payments are checked against the requirements and the payer's escrow deposit,
+//! then `payWithAuthorization` is simulated, which also checks the signature and
+//! nonce on chain. Settlement sends the same call.
+
+use alloy_contract::SolCallBuilder;
+use alloy_primitives::{Address, B256, Bytes, Signature, U256};
+use alloy_provider::{MulticallItem, Provider};
+use alloy_rpc_types_eth::TransactionReceipt;
+use alloy_sol_types::sol;
+use std::collections::HashMap;
+use x402_types::chain::{ChainId, ChainProviderOps};
+use x402_types::proto;
+use x402_types::proto::{PaymentVerificationError, v2};
+use x402_types::scheme::{
+    X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError,
+};
+
+#[cfg(feature = "telemetry")]
+use tracing::instrument;
+#[cfg(feature = "telemetry")]
+use tracing_core::Level;
+
+use crate::chain::{
+    DryRunProvider, Eip155ChainReference, Eip155MetaTransactionProvider, MetaTransaction,
+    MetaTransactionSendError, PayTo, PayerAddress,
+};
+use crate::v1_eip155_exact::facilitator::{
+    Eip155ExactError, assert_enough_value, assert_time, settlement_receipt,
+};
+use crate::v2_eip155_native::{
+    Eip155NativeConfig, NATIVE_ASSET, NativeScheme, V2Eip155Native, native_escrow_domain,
+    native_payment_hash, types,
+};
+
+sol! {
+    #[allow(missing_docs)]
+    #[allow(clippy::too_many_arguments)]
+    #[derive(Debug)]
+    #[sol(rpc)]
+    X402NativeEscrow,
+    "abi/X402NativeEscrow.json"
+}
+
+impl<P> X402SchemeFacilitatorBuilder<P> for V2Eip155Native
+where
+    P: Eip155MetaTransactionProvider + ChainProviderOps + Send + Sync + 'static,
+    Eip155ExactError: From<P::Error>,
+{
+    fn build(
+        &self,
+        provider: P,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
+        let config = Eip155NativeConfig::from_value(config)?;
+        Ok(Box::new(V2Eip155NativeFacilitator::new(provider, config)))
+    }
+}
+
+/// Facilitator for V2 EIP-155 native scheme payments.
+///
+/// # Type Parameters
+///
+/// - `P`: The provider type, which must implement [`Eip155MetaTransactionProvider`]
+///   and [`ChainProviderOps`]
+pub struct V2Eip155NativeFacilitator<P> {
+    provider: P,
+    config: Eip155NativeConfig,
+}
+
+impl<P> V2Eip155NativeFacilitator<P> {
+    /// Creates a new V2 EIP-155 native scheme facilitator settling through the
+    /// escrows in `config`.
+    pub fn new(provider: P, config: Eip155NativeConfig) -> Self {
+        Self { provider, config }
+    }
+}
+
+#[async_trait::async_trait]
+impl<P> X402SchemeFacilitator for V2Eip155NativeFacilitator<P>
+where
+    P: Eip155MetaTransactionProvider + ChainProviderOps + Send + Sync,
+    P::Inner: Provider,
+    Eip155ExactError: From<P::Error>,
+{
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
+        let request = types::VerifyRequest::from_proto(request)?;
+        let payment = assert_valid_payment(
+            self.provider.inner(),
+            self.provider.chain(),
+            &request.payment_payload,
+            &request.payment_requirements,
+            &self.config,
+        )
+        .await?;
+        let payer = verify_payment(self.provider.inner(), &payment).await?;
+        Ok(v2::VerifyResponse::valid(payer.to_string()).into())
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let required = self.provider.required_confirmations();
+        let confirmations = match request.confirmation_policy() {
+            proto::ConfirmationPolicy::Confirmed => required,
+            proto::ConfirmationPolicy::Pending => 1,
+        };
+        let request = types::SettleRequest::from_proto(request)?;
+        let payload = &request.payment_payload;
+        let payment = assert_valid_payment(
+            self.provider.inner(),
+            self.provider.chain(),
+            payload,
+            &request.payment_requirements,
+            &self.config,
+        )
+        .await?;
+        let receipt = settle_payment(&self.provider, &payment, confirmations).await?;
+        Ok(v2::SettleResponse::Success {
+            payer: payment.from.to_string(),
+            transaction: receipt.transaction_hash.to_string(),
+            network: payload.accepted.network.to_string(),
+            receipt: settlement_receipt(&receipt, confirmations, required),
+        }
+        .into())
+    }
+
+    async fn settle_dry_run(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleDryRunResponse, X402SchemeFacilitatorError> {
+        self.verify(request).await?;
+        let request = types::SettleRequest::from_proto(request)?;
+        let payload = &request.payment_payload;
+        let payment = assert_valid_payment(
+            self.provider.inner(),
+            self.provider.chain(),
+            payload,
+            &request.payment_requirements,
+            &self.config,
+        )
+        .await?;
+        let from = self
+            .provider
+            .signer_addresses()
+            .first()
+            .and_then(|signer| signer.parse::<Address>().ok())
+            .unwrap_or_default();
+        let dry_run = DryRunProvider::new(&self.provider, from);
+        settle_payment::<_, MetaTransactionSendError>(&dry_run, &payment, 1).await?;
+        let report = dry_run
+            .finish(payment.from, payload.accepted.network.to_string())
+            .await
+            .map_err(<Eip155ExactError as From<MetaTransactionSendError>>::from)?;
+        Ok(proto::SettleDryRunResponse(
+            serde_json::to_value(report).expect("SettlementDryRun serialization failed"),
+        ))
+    }
+
+    async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
+        let chain_id = self.provider.chain_id();
+        let kinds = vec![proto::SupportedPaymentKind {
+            x402_version: v2::X402Version2.into(),
+            scheme: NativeScheme.to_string(),
+            network: chain_id.clone().into(),
+            extra: Some(self.config.supported_extra()),
+        }];
+        let signers = {
+            let mut signers = HashMap::with_capacity(1);
+            signers.insert(chain_id, self.provider.signer_addresses());
+            signers
+        };
+        Ok(proto::SupportedResponse {
+            kinds,
+            extensions: Vec::new(),
+            signers,
+        })
+    }
+}
+
+/// A validated native payment, ready to be simulated or settled.
+#[derive(Debug)]
+pub struct NativeEvmPayment {
+    /// Payer whose escrow deposit is debited.
+    pub from: PayerAddress,
+    /// Recipient of the native coin.
+    pub pay_to: PayTo,
+    /// Amount, in wei.
+    pub value: U256,
+    /// Not valid before this timestamp (exclusive).
+    pub valid_after: u64,
+    /// Not valid at/after this timestamp.
+    pub valid_before: u64,
+    /// Unique 32-byte nonce (prevents replay).
+    pub nonce: B256,
+    /// Escrow contract the payment goes through.
+    pub escrow: Address,
+    /// EOA or EIP-1271 signature of the `NativePayment`.
+    pub signature: Bytes,
+}
+
+/// Runs all preconditions needed for a successful native payment:
+/// - Valid scheme, network, native asset and receiver.
+/// - Escrow accepted by the facilitator.
+/// - Exact amount and valid time window.
+/// - Signature of an EOA payer matching the signed message.
+/// - Sufficient escrow deposit.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err))]
+pub async fn assert_valid_payment<P: Provider>(
+    provider: &P,
+    chain: &Eip155ChainReference,
+    payload: &types::PaymentPayload,
+    requirements: &types::PaymentRequirements,
+    config: &Eip155NativeConfig,
+) -> Result<NativeEvmPayment, Eip155ExactError> {
+    let accepted = &payload.accepted;
+    if accepted != requirements {
+        return Err(PaymentVerificationError::AcceptedRequirementsMismatch.into());
+    }
+    let chain_id: ChainId = chain.into();
+    if accepted.network != chain_id {
+        return Err(PaymentVerificationError::ChainIdMismatch.into());
+    }
+    if accepted.asset.address() != NATIVE_ASSET {
+        return Err(PaymentVerificationError::UnsupportedAsset.into());
+    }
+    let escrow = accepted
+        .extra
+        .as_ref()
+        .map(|extra| extra.escrow)
+        .ok_or_else(|| {
+            PaymentVerificationError::InvalidFormat("Missing extra.escrow".to_string())
+        })?;
+    if !config.accepts_escrow(&escrow) {
+        return Err(PaymentVerificationError::InvalidFormat(
+            "extra.escrow is not an accepted native escrow".to_string(),
+        )
+        .into());
+    }
+
+    let authorization = &payload.payload.authorization;
+    let pay_to = PayTo(accepted.pay_to.address());
+    if PayTo(authorization.to) != pay_to {
+        return Err(PaymentVerificationError::recipient_mismatch(pay_to, authorization.to).into());
+    }
+    let amount_required: U256 = accepted.amount.into();
+    assert_enough_value(&authorization.value, &amount_required)?;
+    assert_time(
+        authorization.valid_after,
+        authorization.valid_before,
+        config.grace_buffer_seconds,
+        &config.clock,
+    )?;
+
+    let payer = authorization.from;
+    let signature = payload.payload.signature.clone();
+    let hash = native_payment_hash(authorization, &native_escrow_domain(chain, escrow));
+    let is_eoa_signature = signature.len() == 65
+        && Signature::from_raw(&signature)
+            .ok()
+            .and_then(|signature| signature.recover_address_from_prehash(&hash).ok())
+            == Some(payer);
+    // Contract wallets are checked by the escrow, through EIP-1271, when simulating.
+    if !is_eoa_signature && provider.get_code_at(payer).await?.is_empty() {
+        return Err(PaymentVerificationError::InvalidSignature(
+            "Signature does not match the payer".to_string(),
+        )
+        .into());
+    }
+
+    let deposit = X402NativeEscrow::new(escrow, provider)
+        .balanceOf(payer)
+        .call()
+        .await?;
+    if deposit < amount_required {
+        return Err(PaymentVerificationError::insufficient_funds(amount_required, deposit).into());
+    }
+
+    Ok(NativeEvmPayment {
+        from: PayerAddress(payer),
+        pay_to,
+        value: authorization.value,
+        valid_after: authorization.valid_after.as_secs(),
+        valid_before: authorization.valid_before.as_secs(),
+        nonce: authorization.nonce,
+        escrow,
+        signature,
+    })
+}
+
+/// Builds the escrow call settling `payment`.
+fn pay_with_authorization<'a, P: Provider>(
+    escrow: &'a X402NativeEscrow::X402NativeEscrowInstance<P>,
+    payment: &NativeEvmPayment,
+) -> SolCallBuilder<&'a P, X402NativeEscrow::payWithAuthorizationCall> {
+    escrow.payWithAuthorization(
+        payment.from.address(),
+        payment.pay_to.address(),
+        payment.value,
+        U256::from(payment.valid_after),
+        U256::from(payment.valid_before),
+        payment.nonce,
+        payment.signature.clone(),
+    )
+}
+
+/// Verifies a native payment by simulating `payWithAuthorization`.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
+    from = %payment.from,
+    pay_to = %payment.pay_to,
+    escrow = %payment.escrow,
+)))]
+pub async fn verify_payment<P: Provider>(
+    provider: &P,
+    payment: &NativeEvmPayment,
+) -> Result<PayerAddress, Eip155ExactError> {
+    let escrow = X402NativeEscrow::new(payment.escrow, provider);
+    pay_with_authorization(&escrow, payment)
+        .call()
+        .await
+        .map_err(|e| PaymentVerificationError::TransactionSimulation(e.to_string()))?;
+    Ok(payment.from)
+}
+
+/// Settles a native payment through its escrow, waiting for `confirmations` blocks.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
+    from = %payment.from,
+    pay_to = %payment.pay_to,
+    escrow = %payment.escrow,
+)))]
+pub async fn settle_payment<P, E>(
+    provider: &P,
+    payment: &NativeEvmPayment,
+    confirmations: u64,
+) -> Result<TransactionReceipt, Eip155ExactError>
+where
+    P: Eip155MetaTransactionProvider<Error = E>,
+    Eip155ExactError: From<E>,
+{
+    let escrow = X402NativeEscrow::new(payment.escrow, provider.inner());
+    let call = pay_with_authorization(&escrow, payment);
+    let meta_transaction = MetaTransaction {
+        to: call.target(),
+        calldata: call.calldata().clone(),
+        confirmations,
+    };
+    let receipt =
+        Eip155MetaTransactionProvider::send_transaction(provider, meta_transaction).await?;
+    if receipt.status() {
+        #[cfg(feature = "telemetry")]
+        tracing::event!(Level::INFO,
+            status = "ok",
+            tx = %receipt.transaction_hash,
+            "payWithAuthorization succeeded"
+        );
+        Ok(receipt)
+    } else {
+        #[cfg(feature = "telemetry")]
+        tracing::event!(
+            Level::WARN,
+            status = "failed",
+            tx = %receipt.transaction_hash,
+            "payWithAuthorization failed"
+        );
+        Err(Eip155ExactError::TransactionReverted(
+            receipt.transaction_hash,
+        ))
+    }
+}
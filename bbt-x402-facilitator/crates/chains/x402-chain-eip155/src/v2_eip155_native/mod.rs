@@ -0,0 +1,74 @@
+//! V2 EIP-155 "native" payment scheme implementation.
+//!
+//! This module implements payments in a chain's native coin (ETH, XTZ, ...) for the
+//! V2 x402 protocol. Native transfers cannot be authorized by a signature the way
+//! ERC-3009 tokens can, so they go through an escrow contract (see
+//! `contracts/x402NativeEscrow.sol`, ABI in `abi/X402NativeEscrow.json`):
+//!
+//! 1. The payer deposits native coin into the escrow once, ahead of any payment.
+//! 2. For each payment, the payer signs an EIP-712 [`NativePayment`] under the
+//!    escrow's domain (see [`native_escrow_domain`]).
+//! 3. The facilitator submits it with `payWithAuthorization`, and the escrow sends
+//!    `value` from the payer's deposit to `payTo`.
+//!
+//! Payment requirements name the escrow in `extra.escrow` and the native asset as
+//! [`NATIVE_ASSET`]. The facilitator only settles through the escrows listed in its
+//! scheme config, see [`config`].
+//!
+//! # Features
+//!
+//! - EOA signatures, and EIP-1271 signatures of deployed smart wallets
+//! - Deposit balance check before settlement
+//! - Settlement simulated during verification
+//!
+//! # Usage
+//!
+//! ```ignore
+//! use alloy_primitives::{U256, address};
+//! use x402_chain_eip155::chain::Eip155ChainReference;
+//! use x402_chain_eip155::v2_eip155_native::V2Eip155Native;
+//!
+//! let price = V2Eip155Native::price_tag(
+//!     "0x1234...",  // pay_to address
+//!     Eip155ChainReference::new(42793),
+//!     U256::from(10_000_000_000_000_000u64),  // 0.01 XTZ
+//!     address!("0x..."),  // escrow
+//! );
+//! ```
+
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "server")]
+#[allow(unused_imports)]
+pub use server::*;
+
+#[cfg(feature = "facilitator")]
+pub mod config;
+#[cfg(feature = "facilitator")]
+pub use config::*;
+#[cfg(feature = "facilitator")]
+pub mod facilitator;
+#[cfg(feature = "facilitator")]
+pub use facilitator::*;
+
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "client")]
+pub use client::*;
+
+pub mod types;
+pub use types::*;
+
+use x402_types::scheme::X402SchemeId;
+
+pub struct V2Eip155Native;
+
+impl X402SchemeId for V2Eip155Native {
+    fn namespace(&self) -> &str {
+        "eip155"
+    }
+
+    fn scheme(&self) -> &str {
+        NativeScheme.as_ref()
+    }
+}
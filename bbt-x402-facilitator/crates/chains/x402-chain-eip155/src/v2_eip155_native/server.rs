@@ -0,0 +1,60 @@
+//! Server-side price tag generation for the V2 EIP-155 native scheme.
+
+use alloy_primitives::{Address, U256};
+use x402_types::chain::ChainId;
+use x402_types::proto::v2;
+
+use crate::chain::{ChecksummedAddress, Eip155ChainReference};
+use crate::v2_eip155_native::{
+    NATIVE_ASSET, NativePaymentRequirementsExtra, NativeScheme, V2Eip155Native,
+};
+
+impl V2Eip155Native {
+    /// Creates a V2 price tag for a payment in the chain's native coin.
+    ///
+    /// # Parameters
+    ///
+    /// - `pay_to`: The recipient address (can be any type convertible to [`ChecksummedAddress`]).
+    /// - `chain_reference`: The chain the payment is made on
+    /// - `amount`: The amount, in wei
+    /// - `escrow`: The native escrow the payer's deposit is held in, as advertised
+    ///   by the facilitator on `/supported`
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use alloy_primitives::{U256, address};
+    /// use x402_chain_eip155::chain::Eip155ChainReference;
+    /// use x402_chain_eip155::V2Eip155Native;
+    ///
+    /// let price_tag = V2Eip155Native::price_tag(
+    ///     "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb",
+    ///     Eip155ChainReference::new(42793),
+    ///     U256::from(10_000_000_000_000_000u64), // 0.01 XTZ
+    ///     address!("0x..."),
+    /// );
+    /// ```
+    #[allow(dead_code)] // Public for consumption by downstream crates.
+    pub fn price_tag<A: Into<ChecksummedAddress>>(
+        pay_to: A,
+        chain_reference: Eip155ChainReference,
+        amount: U256,
+        escrow: Address,
+    ) -> v2::PriceTag {
+        let chain_id: ChainId = chain_reference.into();
+        let extra = serde_json::to_value(NativePaymentRequirementsExtra { escrow }).ok();
+        let requirements = v2::PaymentRequirements {
+            scheme: NativeScheme.to_string(),
+            pay_to: pay_to.into().to_string(),
+            asset: NATIVE_ASSET.to_string(),
+            network: chain_id,
+            amount: amount.to_string(),
+            max_timeout_seconds: 300,
+            extra,
+        };
+        v2::PriceTag {
+            requirements,
+            enricher: None,
+        }
+    }
+}
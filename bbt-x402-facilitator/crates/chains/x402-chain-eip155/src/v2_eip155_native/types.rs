@@ -0,0 +1,130 @@
+//! Type definitions for the V2 EIP-155 "native" payment scheme.
+//!
+//! The payload carries the same authorization fields as an ERC-3009 payment, signed
+//! as a [`NativePayment`] under the escrow's EIP-712 domain instead of a token's.
+
+use alloy_primitives::{Address, Bytes, address};
+use serde::{Deserialize, Serialize};
+use x402_types::lit_str;
+use x402_types::proto::v2;
+
+#[cfg(any(feature = "facilitator", feature = "client"))]
+use alloy_primitives::{B256, U256};
+#[cfg(any(feature = "facilitator", feature = "client"))]
+use alloy_sol_types::{Eip712Domain, SolStruct, eip712_domain, sol};
+
+#[cfg(any(feature = "facilitator", feature = "client"))]
+use crate::chain::Eip155ChainReference;
+use crate::chain::{Eip155Asset, TokenAmount};
+use crate::v1_eip155_exact::types::ExactEvmPayloadAuthorization;
+
+lit_str!(NativeScheme, "native");
+
+/// Asset address standing for the chain's native coin in payment requirements.
+pub const NATIVE_ASSET: Address = address!("0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+
+/// EIP-712 domain name of the native escrow.
+pub const NATIVE_ESCROW_NAME: &str = "x402NativeEscrow";
+
+/// EIP-712 domain version of the native escrow.
+pub const NATIVE_ESCROW_VERSION: &str = "1";
+
+/// Type alias for V2 verify requests using the native scheme.
+pub type VerifyRequest = v2::VerifyRequest<PaymentPayload, PaymentRequirements>;
+
+/// Type alias for V2 settle requests (same structure as verify requests).
+pub type SettleRequest = VerifyRequest;
+
+/// Type alias for V2 payment payloads of the native scheme.
+pub type PaymentPayload = v2::PaymentPayload<PaymentRequirements, NativeEvmPayload>;
+
+/// Type alias for V2 payment requirements of the native scheme.
+///
+/// The asset is [`NATIVE_ASSET`], and `extra` names the escrow the payment goes through.
+pub type PaymentRequirements =
+    v2::PaymentRequirements<NativeScheme, TokenAmount, Eip155Asset, NativePaymentRequirementsExtra>;
+
+/// Signed authorization to pay from the payer's escrow deposit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NativeEvmPayload {
+    /// EOA or EIP-1271 signature of the [`NativePayment`].
+    pub signature: Bytes,
+
+    /// The signed payment. `to` is the final recipient, `value` is in wei.
+    pub authorization: ExactEvmPayloadAuthorization,
+}
+
+/// Scheme-specific `extra` of native payment requirements.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NativePaymentRequirementsExtra {
+    /// Escrow contract holding the payer's deposit, and the EIP-712 verifying contract.
+    pub escrow: Address,
+}
+
+#[cfg(any(feature = "facilitator", feature = "client"))]
+sol!(
+    /// Solidity-compatible struct signed by native scheme payers.
+    ///
+    /// Matches `NATIVE_PAYMENT_TYPEHASH` of the escrow contract.
+    #[derive(Serialize, Deserialize)]
+    struct NativePayment {
+        address from;
+        address to;
+        uint256 value;
+        uint256 validAfter;
+        uint256 validBefore;
+        bytes32 nonce;
+    }
+);
+
+/// EIP-712 domain of the native escrow deployed at `escrow`.
+#[cfg(any(feature = "facilitator", feature = "client"))]
+pub fn native_escrow_domain(chain: &Eip155ChainReference, escrow: Address) -> Eip712Domain {
+    eip712_domain! {
+        name: NATIVE_ESCROW_NAME,
+        version: NATIVE_ESCROW_VERSION,
+        chain_id: chain.inner(),
+        verifying_contract: escrow,
+    }
+}
+
+/// Signing hash of a [`NativePayment`].
+#[cfg(any(feature = "facilitator", feature = "client"))]
+pub fn native_payment_hash(
+    authorization: &ExactEvmPayloadAuthorization,
+    domain: &Eip712Domain,
+) -> B256 {
+    NativePayment {
+        from: authorization.from,
+        to: authorization.to,
+        value: authorization.value,
+        validAfter: U256::from(authorization.valid_after.as_secs()),
+        validBefore: U256::from(authorization.valid_before.as_secs()),
+        nonce: authorization.nonce,
+    }
+    .eip712_signing_hash(domain)
+}
+
+#[cfg(all(test, any(feature = "facilitator", feature = "client")))]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    #[test]
+    fn test_native_payment_type_matches_escrow() {
+        let typehash = keccak256(
+            "NativePayment(address from,address to,uint256 value,uint256 validAfter,uint256 validBefore,bytes32 nonce)",
+        );
+        let payment = NativePayment {
+            from: Address::ZERO,
+            to: Address::ZERO,
+            value: U256::ZERO,
+            validAfter: U256::ZERO,
+            validBefore: U256::ZERO,
+            nonce: B256::ZERO,
+        };
+        assert_eq!(payment.eip712_type_hash(), typehash);
+    }
+}
@@ -0,0 +1,110 @@
+//! Scheme configuration for the EIP-155 "recurring" facilitator.
+//!
+//! Subscriptions are tracked in memory. A later pull only works while the facilitator
+//! remembers the subscription, since the Permit2 signature that opened it cannot be
+//! used twice, so production deployments set `subscriptionsDir` to keep them across
+//! restarts and config reloads, in one JSON file per chain (e.g. `eip155-42793.json`).
+//!
+//! ```json
+//! {
+//!   "id": "v2-eip155-recurring",
+//!   "chains": "eip155:42793",
+//!   "config": {
+//!     "subscriptionsDir": "/var/lib/x402/subscriptions",
+//!     "graceBufferSeconds": 6
+//!   }
+//! }
+//! ```
+//!
+//! The config section is optional. Unknown keys, malformed values and an unreadable
+//! subscriptions file fail the scheme build with an [`Eip155RecurringConfigError`].
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use x402_types::chain::ChainId;
+use x402_types::timestamp::SharedClock;
+
+use crate::v1_eip155_exact::settlement::DEFAULT_GRACE_BUFFER_SECONDS;
+
+/// Errors in the scheme configuration of the EIP-155 "recurring" facilitator.
+#[derive(Debug, thiserror::Error)]
+pub enum Eip155RecurringConfigError {
+    #[error("Invalid recurring scheme config: {0}")]
+    Invalid(#[from] serde_json::Error),
+    #[error("Failed to load subscriptions from {path}: {reason}")]
+    Subscriptions { path: PathBuf, reason: String },
+}
+
+/// Scheme configuration for the EIP-155 "recurring" facilitator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Eip155RecurringConfig {
+    /// Directory the subscriptions are kept in. When absent, they are lost on restart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscriptions_dir: Option<PathBuf>,
+    /// Seconds a Permit2 signature or allowance must remain valid past now, covering
+    /// the time to settle it.
+    #[serde(default = "default_grace_buffer_seconds")]
+    pub grace_buffer_seconds: u64,
+    /// Clock the time windows are checked against; the system clock outside tests.
+    #[serde(skip)]
+    pub clock: SharedClock,
+}
+
+impl Default for Eip155RecurringConfig {
+    fn default() -> Self {
+        Self {
+            subscriptions_dir: None,
+            grace_buffer_seconds: DEFAULT_GRACE_BUFFER_SECONDS,
+            clock: SharedClock::default(),
+        }
+    }
+}
+
+fn default_grace_buffer_seconds() -> u64 {
+    DEFAULT_GRACE_BUFFER_SECONDS
+}
+
+impl Eip155RecurringConfig {
+    /// Parses the scheme-specific `config` value. A missing value means defaults.
+    pub fn from_value(
+        value: Option<serde_json::Value>,
+    ) -> Result<Self, Eip155RecurringConfigError> {
+        Ok(value
+            .map(serde_json::from_value)
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    /// The file the subscriptions of `chain_id` are kept in, if persisted.
+    pub fn subscriptions_path(&self, chain_id: &ChainId) -> Option<PathBuf> {
+        let file = format!("{}.json", chain_id.to_string().replace(':', "-"));
+        self.subscriptions_dir.as_ref().map(|dir| dir.join(file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recurring_config() {
+        let config = Eip155RecurringConfig::from_value(None).unwrap();
+        assert!(config.subscriptions_dir.is_none());
+        assert_eq!(config.grace_buffer_seconds, DEFAULT_GRACE_BUFFER_SECONDS);
+
+        let config = Eip155RecurringConfig::from_value(Some(serde_json::json!({
+            "subscriptionsDir": "/tmp/subscriptions"
+        })))
+        .unwrap();
+        assert_eq!(
+            config.subscriptions_path(&"eip155:42793".parse().unwrap()),
+            Some(PathBuf::from("/tmp/subscriptions/eip155-42793.json"))
+        );
+
+        assert!(matches!(
+            Eip155RecurringConfig::from_value(Some(serde_json::json!({ "escrows": [] }))),
+            Err(Eip155RecurringConfigError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,486 @@
+//! Facilitator-side payment verification and settlement for the V2 EIP-155 recurring scheme.
+//!
+//! The first settlement of a subscription submits the Permit2 `permit` granting the
+//! allowance to a facilitator signer, then pulls the first period with `transferFrom`,
+//! exactly like a legacy AllowanceTransfer payment of the "exact" scheme. Later
+//! settlements of the same payload pull one period each once it is due, see
+//! [`subscriptions`](crate::v2_eip155_recurring::subscriptions).
+
+use alloy_primitives::{Address, B256, U256};
+use alloy_provider::{MulticallItem, Provider};
+use alloy_rpc_types_eth::TransactionReceipt;
+use std::collections::HashMap;
+use std::str::FromStr;
+use x402_types::chain::{ChainId, ChainProviderOps};
+use x402_types::proto;
+use x402_types::proto::{PaymentVerificationError, v1, v2};
+use x402_types::scheme::{
+    X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError,
+};
+use x402_types::timestamp::{Clock, UnixTimestamp};
+
+#[cfg(feature = "telemetry")]
+use tracing::instrument;
+
+use crate::chain::{
+    Eip155ChainReference, Eip155MetaTransactionProvider, MetaTransaction, PayTo, PayerAddress,
+    Spender, TokenAmount,
+};
+use crate::v1_eip155_exact::facilitator::{
+    Eip155ExactError, IPermit2, Permit2Payment, assert_permit2_domain, assert_permit2_time,
+    fetch_token_state, join_spenders, permit2_amount, settle_payment_permit2, settlement_receipt,
+    verify_payment_permit2,
+};
+use crate::v2_eip155_recurring::{
+    Eip155RecurringConfig, Eip155RecurringConfigError, RecurringScheme, Subscription,
+    SubscriptionBook, SubscriptionStatus, V2Eip155Recurring, subscription_id, types,
+};
+
+impl<P> X402SchemeFacilitatorBuilder<P> for V2Eip155Recurring
+where
+    P: Eip155MetaTransactionProvider + ChainProviderOps + Send + Sync + 'static,
+    Eip155ExactError: From<P::Error>,
+{
+    fn build(
+        &self,
+        provider: P,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
+        let config = Eip155RecurringConfig::from_value(config)?;
+        let subscriptions = match config.subscriptions_path(&provider.chain_id()) {
+            Some(path) => SubscriptionBook::open(&path)
+                .map_err(|reason| Eip155RecurringConfigError::Subscriptions { path, reason })?,
+            None => SubscriptionBook::new(),
+        };
+        Ok(Box::new(V2Eip155RecurringFacilitator::new(
+            provider,
+            config,
+            subscriptions,
+        )))
+    }
+}
+
+/// Facilitator for V2 EIP-155 recurring scheme payments.
+///
+/// # Type Parameters
+///
+/// - `P`: The provider type, which must implement [`Eip155MetaTransactionProvider`]
+///   and [`ChainProviderOps`]
+pub struct V2Eip155RecurringFacilitator<P> {
+    provider: P,
+    config: Eip155RecurringConfig,
+    subscriptions: SubscriptionBook,
+    /// Serializes settlements, so a period is never pulled twice.
+    settling: tokio::sync::Mutex<()>,
+}
+
+impl<P> V2Eip155RecurringFacilitator<P> {
+    /// Creates a new V2 EIP-155 recurring scheme facilitator tracking its
+    /// subscriptions in `subscriptions`.
+    pub fn new(
+        provider: P,
+        config: Eip155RecurringConfig,
+        subscriptions: SubscriptionBook,
+    ) -> Self {
+        Self {
+            provider,
+            config,
+            subscriptions,
+            settling: tokio::sync::Mutex::new(()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<P> X402SchemeFacilitator for V2Eip155RecurringFacilitator<P>
+where
+    P: Eip155MetaTransactionProvider + ChainProviderOps + Send + Sync,
+    P::Inner: Provider,
+    Eip155ExactError: From<P::Error>,
+{
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
+        let request = types::VerifyRequest::from_proto(request)?;
+        let payment = assert_valid_payment(
+            self.provider.chain(),
+            &request.payment_payload,
+            &request.payment_requirements,
+            &self.spenders()?,
+        )?;
+        let provider = self.provider.inner();
+        let permit2 = self.provider.contracts().permit2;
+        match self.subscriptions.get(&payment.id) {
+            None => {
+                assert_valid_permit(provider, &payment, &self.config).await?;
+                let contract = IPermit2::new(permit2, provider);
+                let domain = assert_permit2_domain(self.provider.chain(), permit2);
+                verify_payment_permit2(provider, &contract, &payment.permit, &domain).await?;
+            }
+            Some(subscription) => {
+                assert_same_terms(&subscription, &payment)?;
+                if assert_pullable(&subscription, self.config.clock.now())? {
+                    verify_pull(provider, permit2, &subscription).await?;
+                }
+            }
+        }
+        Ok(v2::VerifyResponse::valid(payment.permit.owner.to_string()).into())
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let required = self.provider.required_confirmations();
+        let confirmations = match request.confirmation_policy() {
+            proto::ConfirmationPolicy::Confirmed => required,
+            proto::ConfirmationPolicy::Pending => 1,
+        };
+        let request = types::SettleRequest::from_proto(request)?;
+        let network = request.payment_payload.accepted.network.to_string();
+        let payment = assert_valid_payment(
+            self.provider.chain(),
+            &request.payment_payload,
+            &request.payment_requirements,
+            &self.spenders()?,
+        )?;
+        let provider = self.provider.inner();
+        let permit2 = self.provider.contracts().permit2;
+
+        let _settling = self.settling.lock().await;
+        let now = self.config.clock.now();
+        let (subscription, receipt) = match self.subscriptions.get(&payment.id) {
+            None => {
+                assert_valid_permit(provider, &payment, &self.config).await?;
+                let contract = IPermit2::new(permit2, provider);
+                let domain = assert_permit2_domain(self.provider.chain(), permit2);
+                let receipt = settle_payment_permit2(
+                    &self.provider,
+                    &contract,
+                    &payment.permit,
+                    &domain,
+                    confirmations,
+                )
+                .await?;
+                let network = request.payment_payload.accepted.network.clone();
+                let subscription = payment.subscription(network, now, &receipt);
+                (subscription, receipt)
+            }
+            Some(mut subscription) => {
+                assert_same_terms(&subscription, &payment)?;
+                if !assert_pullable(&subscription, self.config.clock.now())? {
+                    // The current period is already paid for.
+                    return Ok(v2::SettleResponse::Success {
+                        payer: subscription.owner.to_string(),
+                        transaction: subscription.last_transaction.to_string(),
+                        network,
+                        receipt: v1::SettlementReceipt::default(),
+                    }
+                    .into());
+                }
+                let receipt =
+                    pull_period(&self.provider, permit2, &subscription, confirmations).await?;
+                subscription.record_pull(now, receipt.transaction_hash);
+                (subscription, receipt)
+            }
+        };
+        let payer = subscription.owner.to_string();
+        self.subscriptions.insert(subscription);
+        Ok(v2::SettleResponse::Success {
+            payer,
+            transaction: receipt.transaction_hash.to_string(),
+            network,
+            receipt: settlement_receipt(&receipt, confirmations, required),
+        }
+        .into())
+    }
+
+    async fn subscription(
+        &self,
+        id: &str,
+    ) -> Result<Option<proto::SubscriptionResponse>, X402SchemeFacilitatorError> {
+        let Ok(id) = B256::from_str(id) else {
+            return Ok(None);
+        };
+        Ok(self.subscriptions.get(&id).map(|subscription| {
+            proto::SubscriptionResponse(subscription.to_status_json(self.config.clock.now()))
+        }))
+    }
+
+    async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
+        let chain_id = self.provider.chain_id();
+        let spender = self.spenders()?.first().copied();
+        let kinds = vec![proto::SupportedPaymentKind {
+            x402_version: v2::X402Version2.into(),
+            scheme: RecurringScheme.to_string(),
+            network: chain_id.clone().into(),
+            extra: spender.map(|spender| serde_json::json!({ "spender": spender })),
+        }];
+        let signers = {
+            let mut signers = HashMap::with_capacity(1);
+            signers.insert(chain_id, self.provider.signer_addresses());
+            signers
+        };
+        Ok(proto::SupportedResponse {
+            kinds,
+            extensions: Vec::new(),
+            signers,
+        })
+    }
+}
+
+impl<P: ChainProviderOps> V2Eip155RecurringFacilitator<P> {
+    /// The facilitator signers, which are the accepted Permit2 spenders.
+    fn spenders(&self) -> Result<Vec<Address>, Eip155ExactError> {
+        self.provider
+            .signer_addresses()
+            .iter()
+            .map(|signer| {
+                Address::from_str(signer).map_err(|_| {
+                    PaymentVerificationError::InvalidFormat("Invalid signer address".to_string())
+                        .into()
+                })
+            })
+            .collect()
+    }
+}
+
+/// A validated recurring payment.
+#[derive(Debug)]
+pub struct RecurringEvmPayment {
+    /// Identifier of the subscription the payment opens or continues.
+    pub id: B256,
+    /// The Permit2 allowance, with the amount of one period as `transfer_amount`.
+    pub permit: Permit2Payment,
+    /// Seconds between two pulls.
+    pub period_seconds: u64,
+}
+
+impl RecurringEvmPayment {
+    /// The subscription opened by pulling the first period at `now` in `receipt`.
+    fn subscription(
+        &self,
+        network: ChainId,
+        now: UnixTimestamp,
+        receipt: &TransactionReceipt,
+    ) -> Subscription {
+        let permit = &self.permit;
+        Subscription {
+            id: self.id,
+            network,
+            owner: permit.owner.address(),
+            spender: permit.spender.address(),
+            token: permit.token,
+            pay_to: permit.pay_to.address(),
+            amount: TokenAmount(permit.transfer_amount),
+            period_seconds: self.period_seconds,
+            ceiling: TokenAmount(permit.amount),
+            expiration: UnixTimestamp::from_secs(permit.expiration),
+            started_at: now,
+            next_due_at: now + self.period_seconds,
+            pulled: TokenAmount(permit.transfer_amount),
+            payments: 1,
+            last_transaction: receipt.transaction_hash,
+        }
+    }
+}
+
+/// Runs the checks every recurring payment must pass:
+/// - Valid scheme, network, asset and period.
+/// - Permit2 spender named by the requirements and controlled by the facilitator.
+/// - Allowance covering at least one period.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err))]
+pub fn assert_valid_payment(
+    chain: &Eip155ChainReference,
+    payload: &types::PaymentPayload,
+    requirements: &types::PaymentRequirements,
+    spenders: &[Address],
+) -> Result<RecurringEvmPayment, Eip155ExactError> {
+    let accepted = &payload.accepted;
+    if accepted != requirements {
+        return Err(PaymentVerificationError::AcceptedRequirementsMismatch.into());
+    }
+    let chain_id: ChainId = chain.into();
+    if accepted.network != chain_id {
+        return Err(PaymentVerificationError::ChainIdMismatch.into());
+    }
+    let extra = accepted.extra.as_ref().ok_or_else(|| {
+        PaymentVerificationError::InvalidFormat("Missing extra.periodSeconds".to_string())
+    })?;
+    if extra.period_seconds == 0 {
+        return Err(PaymentVerificationError::InvalidFormat(
+            "extra.periodSeconds must be positive".to_string(),
+        )
+        .into());
+    }
+
+    let permit2 = &payload.payload;
+    let permit_single = &permit2.permit_single;
+    let details = &permit_single.details;
+    if details.token != accepted.asset.address() {
+        return Err(PaymentVerificationError::asset_mismatch(
+            accepted.asset.address(),
+            details.token,
+        )
+        .into());
+    }
+    let spender = permit_single.spender;
+    if extra.spender.is_some_and(|expected| expected != spender) || !spenders.contains(&spender) {
+        let expected = match extra.spender {
+            Some(expected) => expected.to_string(),
+            None => join_spenders(spenders),
+        };
+        return Err(PaymentVerificationError::recipient_mismatch(expected, spender).into());
+    }
+    let amount: U256 = accepted.amount.into();
+    if amount.is_zero() || details.amount < amount {
+        return Err(PaymentVerificationError::invalid_amount(amount, details.amount).into());
+    }
+
+    Ok(RecurringEvmPayment {
+        id: subscription_id(chain, permit2.owner, details.token, spender, details.nonce),
+        permit: Permit2Payment {
+            owner: PayerAddress(permit2.owner),
+            spender: Spender(spender),
+            pay_to: PayTo(accepted.pay_to.address()),
+            token: details.token,
+            amount: details.amount,
+            expiration: details.expiration,
+            nonce: details.nonce,
+            sig_deadline: permit_single.sig_deadline,
+            signature: permit2.signature.clone(),
+            transfer_amount: amount,
+        },
+        period_seconds: extra.period_seconds,
+    })
+}
+
+/// Checks the Permit2 signature and allowance are still valid, and the payer holds
+/// the first period, before a subscription is opened.
+async fn assert_valid_permit<P: Provider>(
+    provider: &P,
+    payment: &RecurringEvmPayment,
+    config: &Eip155RecurringConfig,
+) -> Result<(), Eip155ExactError> {
+    let permit = &payment.permit;
+    assert_permit2_time(
+        UnixTimestamp::from_secs(permit.sig_deadline),
+        UnixTimestamp::from_secs(permit.expiration),
+        config.grace_buffer_seconds,
+        &config.clock,
+    )?;
+    fetch_token_state(provider, permit.token, permit.owner, None, false)
+        .await?
+        .assert_enough_balance(permit.transfer_amount)
+}
+
+/// Checks a payment continues `subscription` on the terms it was opened with.
+fn assert_same_terms(
+    subscription: &Subscription,
+    payment: &RecurringEvmPayment,
+) -> Result<(), PaymentVerificationError> {
+    if subscription.pay_to != payment.permit.pay_to.address() {
+        return Err(PaymentVerificationError::recipient_mismatch(
+            subscription.pay_to,
+            payment.permit.pay_to,
+        ));
+    }
+    if subscription.amount.0 != payment.permit.transfer_amount
+        || subscription.period_seconds != payment.period_seconds
+    {
+        return Err(PaymentVerificationError::invalid_amount(
+            subscription.amount.0,
+            payment.permit.transfer_amount,
+        ));
+    }
+    Ok(())
+}
+
+/// Returns whether a period of `subscription` can be pulled at `now`, `false` if
+/// the current one is paid for, and fails if the allowance is used up or expired.
+fn assert_pullable(
+    subscription: &Subscription,
+    now: UnixTimestamp,
+) -> Result<bool, PaymentVerificationError> {
+    match subscription.status(now) {
+        SubscriptionStatus::Active => Ok(false),
+        SubscriptionStatus::Due => Ok(true),
+        SubscriptionStatus::Exhausted => Err(PaymentVerificationError::invalid_amount(
+            subscription.amount.0,
+            subscription.remaining(),
+        )),
+        SubscriptionStatus::Expired => Err(PaymentVerificationError::Expired {
+            valid_before: subscription.expiration,
+            now,
+        }),
+    }
+}
+
+/// Checks the payer still holds and approves to Permit2 the amount of a period, and
+/// simulates pulling it.
+async fn verify_pull<P: Provider>(
+    provider: &P,
+    permit2: Address,
+    subscription: &Subscription,
+) -> Result<(), Eip155ExactError> {
+    let owner = PayerAddress(subscription.owner);
+    let token_state =
+        fetch_token_state(provider, subscription.token, owner, Some(permit2), false).await?;
+    token_state.assert_enough_balance(subscription.amount.0)?;
+    token_state.assert_enough_allowance(subscription.amount.0)?;
+    IPermit2::new(permit2, provider)
+        .transferFrom(
+            subscription.owner,
+            subscription.pay_to,
+            permit2_amount(subscription.amount.0)?,
+            subscription.token,
+        )
+        .from(subscription.spender)
+        .call()
+        .await
+        .map_err(|e| PaymentVerificationError::TransactionSimulation(e.to_string()))?;
+    Ok(())
+}
+
+/// Pulls one period of `subscription` with Permit2 `transferFrom`, sent by its spender.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
+    subscription = %subscription.id,
+    owner = %subscription.owner,
+    pay_to = %subscription.pay_to,
+)))]
+async fn pull_period<P, E>(
+    provider: &P,
+    permit2: Address,
+    subscription: &Subscription,
+    confirmations: u64,
+) -> Result<TransactionReceipt, Eip155ExactError>
+where
+    P: Eip155MetaTransactionProvider<Error = E>,
+    Eip155ExactError: From<E>,
+{
+    let contract = IPermit2::new(permit2, provider.inner());
+    let transfer = contract.transferFrom(
+        subscription.owner,
+        subscription.pay_to,
+        permit2_amount(subscription.amount.0)?,
+        subscription.token,
+    );
+    let receipt = Eip155MetaTransactionProvider::send_transaction_from(
+        provider,
+        MetaTransaction {
+            to: transfer.target(),
+            calldata: transfer.calldata().clone(),
+            confirmations,
+        },
+        subscription.spender,
+    )
+    .await?;
+    if receipt.status() {
+        Ok(receipt)
+    } else {
+        Err(Eip155ExactError::TransactionReverted(
+            receipt.transaction_hash,
+        ))
+    }
+}
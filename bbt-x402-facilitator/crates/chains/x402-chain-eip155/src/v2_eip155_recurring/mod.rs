@@ -0,0 +1,71 @@
+//! V2 EIP-155 "recurring" payment scheme implementation.
+//!
+//! This module implements subscriptions for the V2 x402 protocol on top of the
+//! Permit2 AllowanceTransfer path, which the "exact" scheme only uses for one-shot
+//! payments:
+//!
+//! 1. The payer signs a Permit2 `PermitSingle` granting a facilitator signer (the
+//!    `spender` of the requirements `extra`) an allowance of several periods, which
+//!    expires when the subscription ends.
+//! 2. The first settlement submits the `permit` and pulls the requirements `amount`
+//!    with Permit2 `transferFrom`.
+//! 3. Settling the payload again pulls the next period once `extra.periodSeconds`
+//!    have passed, until the allowance is used up or expires. Before that, it pulls
+//!    nothing and reports the last transaction.
+//!
+//! The facilitator tracks the schedule of every subscription, see [`subscriptions`],
+//! and serves it on `GET /subscriptions/{id}`, with the id from [`subscription_id`].
+//!
+//! # Usage
+//!
+//! ```ignore
+//! use std::time::Duration;
+//! use x402_chain_eip155::v2_eip155_recurring::V2Eip155Recurring;
+//!
+//! let price = V2Eip155Recurring::price_tag(
+//!     "0x1234...",  // pay_to address
+//!     bbt.amount(5_000_000_000_000_000_000u64),  // 5 BBT per period
+//!     Duration::from_secs(30 * 24 * 60 * 60),  // every 30 days
+//! );
+//! ```
+
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "server")]
+#[allow(unused_imports)]
+pub use server::*;
+
+#[cfg(feature = "facilitator")]
+pub mod config;
+#[cfg(feature = "facilitator")]
+pub use config::*;
+#[cfg(feature = "facilitator")]
+pub mod facilitator;
+#[cfg(feature = "facilitator")]
+pub use facilitator::*;
+#[cfg(feature = "facilitator")]
+pub mod subscriptions;
+#[cfg(feature = "facilitator")]
+pub use subscriptions::*;
+
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "client")]
+pub use client::*;
+
+pub mod types;
+pub use types::*;
+
+use x402_types::scheme::X402SchemeId;
+
+pub struct V2Eip155Recurring;
+
+impl X402SchemeId for V2Eip155Recurring {
+    fn namespace(&self) -> &str {
+        "eip155"
+    }
+
+    fn scheme(&self) -> &str {
+        RecurringScheme.as_ref()
+    }
+}
@@ -0,0 +1,123 @@
+//! Server-side price tag generation for the V2 EIP-155 recurring scheme.
+
+use alloy_primitives::U256;
+use std::sync::Arc;
+use std::time::Duration;
+use x402_types::chain::{ChainId, DeployedTokenAmount};
+use x402_types::proto;
+use x402_types::proto::v2;
+
+use crate::chain::{ChecksummedAddress, Eip155TokenDeployment};
+use crate::v2_eip155_recurring::{
+    RecurringPaymentRequirementsExtra, RecurringScheme, V2Eip155Recurring,
+};
+
+impl V2Eip155Recurring {
+    /// Creates a V2 price tag for a subscription paying `asset` every `period`.
+    ///
+    /// The payer grants a Permit2 allowance to a facilitator signer, which pulls the
+    /// amount once per period. The price tag enricher names that signer as the
+    /// `spender` in the requirements `extra`, from the facilitator's `/supported`.
+    ///
+    /// # Parameters
+    ///
+    /// - `pay_to`: The recipient address (can be any type convertible to [`ChecksummedAddress`]).
+    /// - `asset`: The token deployment and the amount pulled every period
+    /// - `period`: Time between two pulls, in whole seconds
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use std::time::Duration;
+    /// use x402_chain_eip155::v2_eip155_recurring::V2Eip155Recurring;
+    ///
+    /// let price_tag = V2Eip155Recurring::price_tag(
+    ///     "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb",
+    ///     bbt.amount(5_000_000_000_000_000_000u64), // 5 BBT
+    ///     Duration::from_secs(30 * 24 * 60 * 60),   // every 30 days
+    /// );
+    /// ```
+    #[allow(dead_code)] // Public for consumption by downstream crates.
+    pub fn price_tag<A: Into<ChecksummedAddress>>(
+        pay_to: A,
+        asset: DeployedTokenAmount<U256, Eip155TokenDeployment>,
+        period: Duration,
+    ) -> v2::PriceTag {
+        let chain_id: ChainId = asset.token.chain_reference.into();
+        let extra = RecurringPaymentRequirementsExtra {
+            period_seconds: period.as_secs(),
+            spender: None,
+        };
+        let requirements = v2::PaymentRequirements {
+            scheme: RecurringScheme.to_string(),
+            pay_to: pay_to.into().to_string(),
+            asset: asset.token.address.to_string(),
+            network: chain_id,
+            amount: asset.amount.to_string(),
+            max_timeout_seconds: 300,
+            extra: serde_json::to_value(&extra).ok(),
+        };
+        v2::PriceTag {
+            requirements,
+            enricher: Some(Arc::new(enrich_spender)),
+        }
+    }
+}
+
+/// Sets the `spender` the facilitator advertises for the price tag's chain.
+fn enrich_spender(price_tag: &mut v2::PriceTag, capabilities: &proto::SupportedResponse) {
+    let requirements = &mut price_tag.requirements;
+    let spender = capabilities
+        .kinds
+        .iter()
+        .find(|kind| {
+            kind.scheme == RecurringScheme.as_ref()
+                && kind.network == requirements.network.to_string()
+        })
+        .and_then(|kind| kind.extra.as_ref()?.get("spender")?.as_str()?.parse().ok());
+    let extra = requirements.extra.as_ref().and_then(|extra| {
+        serde_json::from_value::<RecurringPaymentRequirementsExtra>(extra.clone()).ok()
+    });
+    if let (Some(spender), Some(mut extra)) = (spender, extra) {
+        extra.spender = Some(spender);
+        requirements.extra = serde_json::to_value(&extra).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, address};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_price_tag_spender_from_supported() {
+        let token = Eip155TokenDeployment {
+            chain_reference: crate::chain::Eip155ChainReference::new(42793),
+            address: address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6"),
+            decimals: 18,
+            eip712: None,
+        };
+        let mut price_tag = V2Eip155Recurring::price_tag(
+            Address::repeat_byte(1),
+            token.amount(5u64),
+            Duration::from_secs(3_600),
+        );
+        let spender = Address::repeat_byte(2);
+        let supported = proto::SupportedResponse {
+            kinds: vec![proto::SupportedPaymentKind {
+                x402_version: 2,
+                scheme: "recurring".to_string(),
+                network: "eip155:42793".to_string(),
+                extra: Some(serde_json::json!({ "spender": spender })),
+            }],
+            extensions: Vec::new(),
+            signers: HashMap::new(),
+        };
+        enrich_spender(&mut price_tag, &supported);
+        let extra: RecurringPaymentRequirementsExtra =
+            serde_json::from_value(price_tag.requirements.extra.unwrap()).unwrap();
+        assert_eq!(extra.period_seconds, 3_600);
+        assert_eq!(extra.spender, Some(spender));
+    }
+}
@@ -0,0 +1,271 @@
+//! Facilitator-side schedule tracking of recurring payments.
+//!
+//! The first settlement of a subscription submits the Permit2 `permit` and pulls the
+//! first period. The facilitator then records a [`Subscription`] with the time the
+//! next pull is due. Settling the same payload again before then pulls nothing and
+//! returns the last transaction, as the current period is paid for; once it is due,
+//! only `transferFrom` is sent against the remaining allowance.
+
+use alloy_primitives::{Address, B256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use x402_types::chain::ChainId;
+use x402_types::timestamp::UnixTimestamp;
+
+use crate::chain::TokenAmount;
+
+/// A Permit2 allowance pulled from periodically.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscription {
+    /// See [`subscription_id`](crate::v2_eip155_recurring::subscription_id).
+    pub id: B256,
+    /// Chain the allowance is granted on.
+    pub network: ChainId,
+    /// Payer granting the allowance.
+    pub owner: Address,
+    /// Facilitator signer the allowance is granted to.
+    pub spender: Address,
+    /// Token pulled.
+    pub token: Address,
+    /// Recipient of every pull.
+    pub pay_to: Address,
+    /// Amount pulled every period.
+    pub amount: TokenAmount,
+    /// Seconds between two pulls.
+    pub period_seconds: u64,
+    /// Total the allowance lets the facilitator pull.
+    pub ceiling: TokenAmount,
+    /// When the allowance expires.
+    pub expiration: UnixTimestamp,
+    /// When the first period was pulled.
+    pub started_at: UnixTimestamp,
+    /// When the next period can be pulled.
+    pub next_due_at: UnixTimestamp,
+    /// Total pulled so far.
+    pub pulled: TokenAmount,
+    /// Number of periods pulled so far.
+    pub payments: u64,
+    /// Transaction of the last pull.
+    pub last_transaction: B256,
+}
+
+/// Where a subscription stands in its schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubscriptionStatus {
+    /// The current period is paid for.
+    Active,
+    /// The next period can be pulled.
+    Due,
+    /// The allowance does not cover another period.
+    Exhausted,
+    /// The allowance has expired.
+    Expired,
+}
+
+impl Subscription {
+    /// Status of the subscription at `now`.
+    pub fn status(&self, now: UnixTimestamp) -> SubscriptionStatus {
+        if now >= self.expiration {
+            SubscriptionStatus::Expired
+        } else if self.remaining() < self.amount.0 {
+            SubscriptionStatus::Exhausted
+        } else if now >= self.next_due_at {
+            SubscriptionStatus::Due
+        } else {
+            SubscriptionStatus::Active
+        }
+    }
+
+    /// Allowance left to pull.
+    pub fn remaining(&self) -> U256 {
+        self.ceiling.0.saturating_sub(self.pulled.0)
+    }
+
+    /// Records the pull of one period at `now`, in `transaction`.
+    ///
+    /// The next period is due one period after this one was, or one period from now
+    /// if the subscription lapsed for longer than that.
+    pub fn record_pull(&mut self, now: UnixTimestamp, transaction: B256) {
+        let next_due_at = self.next_due_at + self.period_seconds;
+        self.next_due_at = if next_due_at > now {
+            next_due_at
+        } else {
+            now + self.period_seconds
+        };
+        self.pulled = TokenAmount(self.pulled.0.saturating_add(self.amount.0));
+        self.payments += 1;
+        self.last_transaction = transaction;
+    }
+
+    /// The subscription with its status at `now`, as served by `GET /subscriptions/{id}`.
+    pub fn to_status_json(&self, now: UnixTimestamp) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).expect("Subscription serialization failed");
+        json["status"] = serde_json::json!(self.status(now));
+        json["remaining"] = serde_json::json!(TokenAmount(self.remaining()));
+        json
+    }
+}
+
+/// Subscriptions known to the facilitator.
+///
+/// Entries are kept in memory and, if a path is configured, written to a JSON file
+/// after every change and restored from it on startup.
+#[derive(Debug, Default)]
+pub struct SubscriptionBook {
+    path: Option<PathBuf>,
+    subscriptions: Mutex<HashMap<B256, Subscription>>,
+}
+
+impl SubscriptionBook {
+    /// Creates an empty, in-memory book.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a book persisted to `path`, restoring the subscriptions already stored there.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let subscriptions: Vec<Subscription> = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| e.to_string())?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.to_string()),
+        };
+        Ok(Self {
+            path: Some(path),
+            subscriptions: Mutex::new(
+                subscriptions
+                    .into_iter()
+                    .map(|subscription| (subscription.id, subscription))
+                    .collect(),
+            ),
+        })
+    }
+
+    /// Returns the subscription `id`.
+    pub fn get(&self, id: &B256) -> Option<Subscription> {
+        self.lock().get(id).cloned()
+    }
+
+    /// Inserts or replaces a subscription, and persists the book if a path is configured.
+    pub fn insert(&self, subscription: Subscription) {
+        let mut subscriptions = self.lock();
+        subscriptions.insert(subscription.id, subscription);
+        if let Some(path) = &self.path
+            && let Err(_e) = persist(path, &subscriptions)
+        {
+            #[cfg(feature = "telemetry")]
+            tracing::error!(error = %_e, path = %path.display(), "Failed to persist subscriptions");
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<B256, Subscription>> {
+        self.subscriptions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Writes the subscriptions to a temporary file and renames it over `path`.
+fn persist(path: &Path, subscriptions: &HashMap<B256, Subscription>) -> std::io::Result<()> {
+    let subscriptions: Vec<_> = subscriptions.values().collect();
+    let content = serde_json::to_vec_pretty(&subscriptions).map_err(std::io::Error::other)?;
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription() -> Subscription {
+        Subscription {
+            id: B256::repeat_byte(1),
+            network: "eip155:42793".parse().unwrap(),
+            owner: Address::repeat_byte(2),
+            spender: Address::repeat_byte(3),
+            token: Address::repeat_byte(4),
+            pay_to: Address::repeat_byte(5),
+            amount: TokenAmount(U256::from(100)),
+            period_seconds: 1_000,
+            ceiling: TokenAmount(U256::from(300)),
+            expiration: UnixTimestamp::from_secs(100_000),
+            started_at: UnixTimestamp::from_secs(10_000),
+            next_due_at: UnixTimestamp::from_secs(11_000),
+            pulled: TokenAmount(U256::from(100)),
+            payments: 1,
+            last_transaction: B256::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_subscription_schedule() {
+        let mut subscription = subscription();
+        let at = UnixTimestamp::from_secs;
+        assert_eq!(subscription.status(at(10_500)), SubscriptionStatus::Active);
+        assert_eq!(subscription.status(at(11_000)), SubscriptionStatus::Due);
+
+        // Pulled on time: the schedule keeps its rhythm.
+        subscription.record_pull(at(11_200), B256::repeat_byte(9));
+        assert_eq!(subscription.next_due_at, at(12_000));
+        assert_eq!(subscription.payments, 2);
+
+        // Pulled after lapsing: the next period starts now.
+        subscription.record_pull(at(15_000), B256::repeat_byte(9));
+        assert_eq!(subscription.next_due_at, at(16_000));
+        assert_eq!(subscription.remaining(), U256::ZERO);
+        assert_eq!(
+            subscription.status(at(16_000)),
+            SubscriptionStatus::Exhausted
+        );
+        assert_eq!(
+            subscription.status(at(100_000)),
+            SubscriptionStatus::Expired
+        );
+
+        let json = subscription.to_status_json(at(15_500));
+        assert_eq!(json["status"], "exhausted");
+        assert_eq!(json["remaining"], "0");
+    }
+
+    #[test]
+    fn test_subscription_book_persists() {
+        let path =
+            std::env::temp_dir().join(format!("x402-subscriptions-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let book = SubscriptionBook::open(&path).unwrap();
+        book.insert(subscription());
+        let restored = SubscriptionBook::open(&path).unwrap();
+        assert_eq!(restored.get(&B256::repeat_byte(1)), Some(subscription()));
+        assert_eq!(restored.get(&B256::ZERO), None);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_subscription_book_persists_large_amounts() {
+        let path = std::env::temp_dir().join(format!(
+            "x402-subscriptions-large-{}.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        // 1 token of 18 decimals, above the 2^53 a JSON number holds exactly.
+        let one = U256::from(10u64.pow(18));
+        let subscription = Subscription {
+            amount: TokenAmount(one),
+            ceiling: TokenAmount(one * U256::from(12)),
+            pulled: TokenAmount(one),
+            ..subscription()
+        };
+        SubscriptionBook::open(&path)
+            .unwrap()
+            .insert(subscription.clone());
+        let restored = SubscriptionBook::open(&path).unwrap();
+        assert_eq!(restored.get(&B256::repeat_byte(1)), Some(subscription));
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,64 @@
+//! Type definitions for the V2 EIP-155 "recurring" payment scheme.
+//!
+//! The payload is a Permit2 [`Permit2Payload`], as used by legacy AllowanceTransfer
+//! payments of the "exact" scheme. Its allowance `amount` is the ceiling of the
+//! subscription and its `expiration` the end of it; the requirements `amount` is
+//! what is pulled every period.
+
+use alloy_primitives::{Address, B256, keccak256};
+use alloy_sol_types::SolValue;
+use serde::{Deserialize, Serialize};
+use x402_types::lit_str;
+use x402_types::proto::v2;
+
+use crate::chain::{Eip155Asset, Eip155ChainReference, TokenAmount};
+use crate::v1_eip155_exact::types::Permit2Payload;
+
+lit_str!(RecurringScheme, "recurring");
+
+/// Type alias for V2 verify requests using the recurring scheme.
+pub type VerifyRequest = v2::VerifyRequest<PaymentPayload, PaymentRequirements>;
+
+/// Type alias for V2 settle requests (same structure as verify requests).
+pub type SettleRequest = VerifyRequest;
+
+/// Type alias for V2 payment payloads of the recurring scheme.
+pub type PaymentPayload = v2::PaymentPayload<PaymentRequirements, Permit2Payload>;
+
+/// Type alias for V2 payment requirements of the recurring scheme.
+///
+/// `amount` is pulled once per period, `extra` sets the period and the spender
+/// the Permit2 allowance is granted to.
+pub type PaymentRequirements = v2::PaymentRequirements<
+    RecurringScheme,
+    TokenAmount,
+    Eip155Asset,
+    RecurringPaymentRequirementsExtra,
+>;
+
+/// Scheme-specific `extra` of recurring payment requirements.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringPaymentRequirementsExtra {
+    /// Seconds between two pulls.
+    pub period_seconds: u64,
+    /// Facilitator signer the Permit2 allowance is granted to, as advertised on
+    /// `/supported`. Filled in by the resource server middleware when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spender: Option<Address>,
+}
+
+/// Identifier of the subscription a Permit2 allowance opens.
+///
+/// A `PermitSingle` can only be used once, as its nonce is consumed by `permit`, so
+/// the chain, owner, token, spender and nonce identify the subscription. Clients
+/// compute it to query `GET /subscriptions/{id}`.
+pub fn subscription_id(
+    chain: &Eip155ChainReference,
+    owner: Address,
+    token: Address,
+    spender: Address,
+    nonce: u64,
+) -> B256 {
+    keccak256((chain.inner(), owner, token, spender, nonce).abi_encode())
+}
@@ -0,0 +1,208 @@
+//! Client-side payment signing for the V2 EIP-155 "upto" scheme.
+//!
+//! This module provides [`V2Eip155UptoClient`] for paying metered resources. The
+//! client signs a Permit2 `PermitSingle` granting the facilitator's spender an
+//! allowance of the maximum price, of which the resource server settles what the
+//! request actually cost. The allowance expires with the signature, so what is
+//! left of it cannot be pulled later. The payer must have approved the Permit2
+//! contract for the token beforehand.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! use x402_chain_eip155::v2_eip155_upto::client::V2Eip155UptoClient;
+//! use alloy_signer_local::PrivateKeySigner;
+//!
+//! let signer = PrivateKeySigner::random();
+//! let client = V2Eip155UptoClient::new(signer);
+//! ```
+
+use alloy_primitives::{Address, Bytes, U256};
+use async_trait::async_trait;
+use x402_types::proto::v2::ResourceInfo;
+use x402_types::proto::{PaymentRequired, v2};
+use x402_types::scheme::X402SchemeId;
+use x402_types::scheme::client::{
+    PaymentCandidate, PaymentCandidateSigner, X402Error, X402SchemeClient,
+};
+use x402_types::timestamp::UnixTimestamp;
+use x402_types::util::Base64Bytes;
+
+use crate::chain::Eip155ChainReference;
+use crate::v1_eip155_exact::client::SignerLike;
+use crate::v1_eip155_exact::digest::{
+    PERMIT2_ADDRESS, permit_single_hash, permit2_allowance_domain,
+};
+use crate::v1_eip155_exact::types::{Permit2Details, Permit2Payload, Permit2PermitSingle};
+use crate::v2_eip155_upto::{V2Eip155Upto, types};
+
+/// Client for signing V2 EIP-155 upto scheme payments.
+///
+/// # Type Parameters
+///
+/// - `S`: The signer type, which must implement [`SignerLike`]
+#[derive(Debug)]
+#[allow(dead_code)] // Public for consumption by downstream crates.
+pub struct V2Eip155UptoClient<S> {
+    signer: S,
+    nonce: u64,
+    permit2: Address,
+}
+
+#[allow(dead_code)] // Public for consumption by downstream crates.
+impl<S> V2Eip155UptoClient<S> {
+    /// Creates a new V2 EIP-155 upto scheme client with the given signer.
+    pub fn new(signer: S) -> Self {
+        Self {
+            signer,
+            nonce: 0,
+            permit2: PERMIT2_ADDRESS,
+        }
+    }
+
+    /// Sets the Permit2 nonce of the allowance, as returned by
+    /// `Permit2.allowance(owner, token, spender)`. Defaults to `0`, which is right
+    /// for the first payment to a spender.
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// Sets the Permit2 contract, for chains where it is not at its canonical address.
+    pub fn with_permit2(mut self, permit2: Address) -> Self {
+        self.permit2 = permit2;
+        self
+    }
+}
+
+impl<S> X402SchemeId for V2Eip155UptoClient<S> {
+    fn namespace(&self) -> &str {
+        V2Eip155Upto.namespace()
+    }
+
+    fn scheme(&self) -> &str {
+        V2Eip155Upto.scheme()
+    }
+}
+
+impl<S> X402SchemeClient for V2Eip155UptoClient<S>
+where
+    S: SignerLike + Clone + Send + Sync + 'static,
+{
+    fn accept(&self, payment_required: &PaymentRequired) -> Vec<PaymentCandidate> {
+        let payment_required = match payment_required {
+            PaymentRequired::V2(payment_required) => payment_required,
+            PaymentRequired::V1(_) => {
+                return vec![];
+            }
+        };
+        payment_required
+            .accepts
+            .iter()
+            .filter_map(|v| {
+                let requirements: types::PaymentRequirements = v.as_concrete()?;
+                // Without a spender the facilitator cannot pull.
+                requirements.extra.as_ref()?.spender?;
+                let chain_reference = Eip155ChainReference::try_from(&requirements.network).ok()?;
+                let candidate = PaymentCandidate {
+                    chain_id: requirements.network.clone(),
+                    asset: requirements.asset.to_string(),
+                    amount: requirements.amount.into(),
+                    scheme: self.scheme().to_string(),
+                    x402_version: self.x402_version(),
+                    pay_to: requirements.pay_to.to_string(),
+                    transfer_method: Some("permit2".to_string()),
+                    signer: Box::new(PayloadSigner {
+                        resource_info: Some(payment_required.resource.clone()),
+                        signer: self.signer.clone(),
+                        chain_reference,
+                        requirements,
+                        nonce: self.nonce,
+                        permit2: self.permit2,
+                    }),
+                };
+                Some(candidate)
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+/// Signs a Permit2 allowance of the maximum `amount` of `requirements`.
+///
+/// Both the allowance and the signature expire `maxTimeoutSeconds` from now.
+#[allow(dead_code)] // Public for consumption by downstream crates.
+pub async fn sign_upto_payment<S: SignerLike + Sync>(
+    signer: &S,
+    chain_reference: &Eip155ChainReference,
+    requirements: &types::PaymentRequirements,
+    nonce: u64,
+    permit2: Address,
+) -> Result<Permit2Payload, X402Error> {
+    let spender = requirements
+        .extra
+        .as_ref()
+        .and_then(|extra| extra.spender)
+        .ok_or_else(|| X402Error::SigningError("Missing extra.spender".to_string()))?;
+    let amount: U256 = requirements.amount.into();
+    let deadline = (UnixTimestamp::now() + requirements.max_timeout_seconds).as_secs();
+    let permit_single = Permit2PermitSingle {
+        details: Permit2Details {
+            token: requirements.asset.address(),
+            amount,
+            expiration: deadline,
+            nonce,
+        },
+        spender,
+        sig_deadline: deadline,
+    };
+    let hash = permit_single_hash(
+        &permit_single,
+        &permit2_allowance_domain(chain_reference, permit2),
+    );
+    let signature = signer
+        .sign_hash(&hash)
+        .await
+        .map_err(|e| X402Error::SigningError(format!("{e:?}")))?;
+    Ok(Permit2Payload {
+        owner: signer.address(),
+        permit_single,
+        signature: Bytes::from(signature.as_bytes().to_vec()),
+    })
+}
+
+#[allow(dead_code)] // Public for consumption by downstream crates.
+struct PayloadSigner<S> {
+    signer: S,
+    resource_info: Option<ResourceInfo>,
+    chain_reference: Eip155ChainReference,
+    requirements: types::PaymentRequirements,
+    nonce: u64,
+    permit2: Address,
+}
+
+#[async_trait]
+impl<S> PaymentCandidateSigner for PayloadSigner<S>
+where
+    S: Sync + SignerLike,
+{
+    async fn sign_payment(&self) -> Result<String, X402Error> {
+        let permit2_payload = sign_upto_payment(
+            &self.signer,
+            &self.chain_reference,
+            &self.requirements,
+            self.nonce,
+            self.permit2,
+        )
+        .await?;
+        let payload = types::PaymentPayload {
+            x402_version: v2::X402Version2,
+            accepted: self.requirements.clone(),
+            resource: self.resource_info.clone(),
+            payload: permit2_payload,
+        };
+        let json = serde_json::to_vec(&payload)?;
+        let b64 = Base64Bytes::encode(&json);
+
+        Ok(b64.to_string())
+    }
+}
@@ -0,0 +1,62 @@
+//! Scheme configuration for the EIP-155 "upto" facilitator.
+//!
+//! ```json
+//! {
+//!   "id": "v2-eip155-upto",
+//!   "chains": "eip155:42793",
+//!   "config": {
+//!     "graceBufferSeconds": 6
+//!   }
+//! }
+//! ```
+//!
+//! The config section is optional. Unknown keys and malformed values fail the scheme
+//! build with an [`Eip155UptoConfigError`].
+
+use serde::{Deserialize, Serialize};
+use x402_types::timestamp::SharedClock;
+
+use crate::v1_eip155_exact::settlement::DEFAULT_GRACE_BUFFER_SECONDS;
+
+/// Errors in the scheme configuration of the EIP-155 "upto" facilitator.
+#[derive(Debug, thiserror::Error)]
+pub enum Eip155UptoConfigError {
+    #[error("Invalid upto scheme config: {0}")]
+    Invalid(#[from] serde_json::Error),
+}
+
+/// Scheme configuration for the EIP-155 "upto" facilitator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Eip155UptoConfig {
+    /// Seconds a Permit2 signature or allowance must remain valid past now, covering
+    /// the time to serve the request and settle it.
+    #[serde(default = "default_grace_buffer_seconds")]
+    pub grace_buffer_seconds: u64,
+    /// Clock the time windows are checked against; the system clock outside tests.
+    #[serde(skip)]
+    pub clock: SharedClock,
+}
+
+impl Default for Eip155UptoConfig {
+    fn default() -> Self {
+        Self {
+            grace_buffer_seconds: DEFAULT_GRACE_BUFFER_SECONDS,
+            clock: SharedClock::default(),
+        }
+    }
+}
+
+fn default_grace_buffer_seconds() -> u64 {
+    DEFAULT_GRACE_BUFFER_SECONDS
+}
+
+impl Eip155UptoConfig {
+    /// Parses the scheme-specific `config` value. A missing value means defaults.
+    pub fn from_value(value: Option<serde_json::Value>) -> Result<Self, Eip155UptoConfigError> {
+        Ok(value
+            .map(serde_json::from_value)
+            .transpose()?
+            .unwrap_or_default())
+    }
+}
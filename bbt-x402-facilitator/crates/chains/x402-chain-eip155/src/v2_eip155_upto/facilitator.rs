@@ -0,0 +1,281 @@
+//! Facilitator-side payment verification and settlement for the V2 EIP-155 upto scheme.
+//!
+//! Verification checks the Permit2 allowance could be pulled in full. Settlement
+//! submits the `permit` and pulls the `settleAmount` the resource server reports,
+//! which must not exceed the authorized maximum, with Permit2 `transferFrom`.
+
+use alloy_primitives::{Address, U256};
+use alloy_provider::Provider;
+use std::collections::HashMap;
+use std::str::FromStr;
+use x402_types::chain::{ChainId, ChainProviderOps};
+use x402_types::proto;
+use x402_types::proto::{PaymentVerificationError, v1, v2};
+use x402_types::scheme::{
+    X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError,
+};
+use x402_types::timestamp::UnixTimestamp;
+
+#[cfg(feature = "telemetry")]
+use tracing::instrument;
+
+use crate::chain::{
+    Eip155ChainReference, Eip155MetaTransactionProvider, PayTo, PayerAddress, Spender,
+};
+use crate::v1_eip155_exact::facilitator::{
+    Eip155ExactError, IPermit2, Permit2Payment, assert_permit2_domain, assert_permit2_time,
+    join_spenders, settle_payment_permit2, settlement_receipt, verify_payment_permit2,
+};
+use crate::v2_eip155_upto::{Eip155UptoConfig, UptoScheme, V2Eip155Upto, types};
+
+impl<P> X402SchemeFacilitatorBuilder<P> for V2Eip155Upto
+where
+    P: Eip155MetaTransactionProvider + ChainProviderOps + Send + Sync + 'static,
+    Eip155ExactError: From<P::Error>,
+{
+    fn build(
+        &self,
+        provider: P,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
+        let config = Eip155UptoConfig::from_value(config)?;
+        Ok(Box::new(V2Eip155UptoFacilitator::new(provider, config)))
+    }
+}
+
+/// Facilitator for V2 EIP-155 upto scheme payments.
+///
+/// # Type Parameters
+///
+/// - `P`: The provider type, which must implement [`Eip155MetaTransactionProvider`]
+///   and [`ChainProviderOps`]
+pub struct V2Eip155UptoFacilitator<P> {
+    provider: P,
+    config: Eip155UptoConfig,
+}
+
+impl<P> V2Eip155UptoFacilitator<P> {
+    /// Creates a new V2 EIP-155 upto scheme facilitator.
+    pub fn new(provider: P, config: Eip155UptoConfig) -> Self {
+        Self { provider, config }
+    }
+}
+
+#[async_trait::async_trait]
+impl<P> X402SchemeFacilitator for V2Eip155UptoFacilitator<P>
+where
+    P: Eip155MetaTransactionProvider + ChainProviderOps + Send + Sync,
+    P::Inner: Provider,
+    Eip155ExactError: From<P::Error>,
+{
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
+        let request = types::VerifyRequest::from_proto(request)?;
+        let payment = assert_valid_payment(
+            self.provider.chain(),
+            &request.payment_payload,
+            &request.payment_requirements,
+            &self.spenders()?,
+            &self.config,
+        )?;
+        let provider = self.provider.inner();
+        let permit2 = self.provider.contracts().permit2;
+        let contract = IPermit2::new(permit2, provider);
+        let domain = assert_permit2_domain(self.provider.chain(), permit2);
+        let payer = verify_payment_permit2(provider, &contract, &payment, &domain).await?;
+        Ok(v2::VerifyResponse::valid(payer.to_string()).into())
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let required = self.provider.required_confirmations();
+        let confirmations = match request.confirmation_policy() {
+            proto::ConfirmationPolicy::Confirmed => required,
+            proto::ConfirmationPolicy::Pending => 1,
+        };
+        let settle_amount = request.settle_amount();
+        let request = types::SettleRequest::from_proto(request)?;
+        let mut payment = assert_valid_payment(
+            self.provider.chain(),
+            &request.payment_payload,
+            &request.payment_requirements,
+            &self.spenders()?,
+            &self.config,
+        )?;
+        payment.transfer_amount =
+            assert_settle_amount(settle_amount.as_deref(), payment.transfer_amount)?;
+        let permit2 = self.provider.contracts().permit2;
+        let contract = IPermit2::new(permit2, self.provider.inner());
+        let domain = assert_permit2_domain(self.provider.chain(), permit2);
+        let receipt =
+            settle_payment_permit2(&self.provider, &contract, &payment, &domain, confirmations)
+                .await?;
+        Ok(v2::SettleResponse::Success {
+            payer: payment.owner.to_string(),
+            transaction: receipt.transaction_hash.to_string(),
+            network: request.payment_payload.accepted.network.to_string(),
+            receipt: v1::SettlementReceipt {
+                settled_amount: Some(payment.transfer_amount.to_string()),
+                ..settlement_receipt(&receipt, confirmations, required)
+            },
+        }
+        .into())
+    }
+
+    async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
+        let chain_id = self.provider.chain_id();
+        let spender = self.spenders()?.first().copied();
+        let kinds = vec![proto::SupportedPaymentKind {
+            x402_version: v2::X402Version2.into(),
+            scheme: UptoScheme.to_string(),
+            network: chain_id.clone().into(),
+            extra: spender.map(|spender| serde_json::json!({ "spender": spender })),
+        }];
+        let signers = {
+            let mut signers = HashMap::with_capacity(1);
+            signers.insert(chain_id, self.provider.signer_addresses());
+            signers
+        };
+        Ok(proto::SupportedResponse {
+            kinds,
+            extensions: Vec::new(),
+            signers,
+        })
+    }
+}
+
+impl<P: ChainProviderOps> V2Eip155UptoFacilitator<P> {
+    /// The facilitator signers, which are the accepted Permit2 spenders.
+    fn spenders(&self) -> Result<Vec<Address>, Eip155ExactError> {
+        self.provider
+            .signer_addresses()
+            .iter()
+            .map(|signer| {
+                Address::from_str(signer).map_err(|_| {
+                    PaymentVerificationError::InvalidFormat("Invalid signer address".to_string())
+                        .into()
+                })
+            })
+            .collect()
+    }
+}
+
+/// Runs the checks every upto payment must pass:
+/// - Valid scheme, network and asset.
+/// - Permit2 spender named by the requirements and controlled by the facilitator.
+/// - Allowance covering the maximum, with signature and allowance still valid.
+///
+/// The returned payment transfers the maximum, see [`assert_settle_amount`] for the
+/// amount actually settled.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err))]
+pub fn assert_valid_payment(
+    chain: &Eip155ChainReference,
+    payload: &types::PaymentPayload,
+    requirements: &types::PaymentRequirements,
+    spenders: &[Address],
+    config: &Eip155UptoConfig,
+) -> Result<Permit2Payment, Eip155ExactError> {
+    let accepted = &payload.accepted;
+    if accepted != requirements {
+        return Err(PaymentVerificationError::AcceptedRequirementsMismatch.into());
+    }
+    let chain_id: ChainId = chain.into();
+    if accepted.network != chain_id {
+        return Err(PaymentVerificationError::ChainIdMismatch.into());
+    }
+
+    let permit2 = &payload.payload;
+    let permit_single = &permit2.permit_single;
+    let details = &permit_single.details;
+    if details.token != accepted.asset.address() {
+        return Err(PaymentVerificationError::asset_mismatch(
+            accepted.asset.address(),
+            details.token,
+        )
+        .into());
+    }
+    let spender = permit_single.spender;
+    let expected = accepted.extra.as_ref().and_then(|extra| extra.spender);
+    if expected.is_some_and(|expected| expected != spender) || !spenders.contains(&spender) {
+        let expected = match expected {
+            Some(expected) => expected.to_string(),
+            None => join_spenders(spenders),
+        };
+        return Err(PaymentVerificationError::recipient_mismatch(expected, spender).into());
+    }
+    let max_amount: U256 = accepted.amount.into();
+    if max_amount.is_zero() || details.amount < max_amount {
+        return Err(PaymentVerificationError::invalid_amount(max_amount, details.amount).into());
+    }
+    assert_permit2_time(
+        UnixTimestamp::from_secs(permit_single.sig_deadline),
+        UnixTimestamp::from_secs(details.expiration),
+        config.grace_buffer_seconds,
+        &config.clock,
+    )?;
+
+    Ok(Permit2Payment {
+        owner: PayerAddress(permit2.owner),
+        spender: Spender(spender),
+        pay_to: PayTo(accepted.pay_to.address()),
+        token: details.token,
+        amount: details.amount,
+        expiration: details.expiration,
+        nonce: details.nonce,
+        sig_deadline: permit_single.sig_deadline,
+        signature: permit2.signature.clone(),
+        transfer_amount: max_amount,
+    })
+}
+
+/// Returns the amount to settle: the `settleAmount` of the settle request, which must
+/// be positive and at most `max_amount`, or `max_amount` when none is given.
+pub fn assert_settle_amount(
+    settle_amount: Option<&str>,
+    max_amount: U256,
+) -> Result<U256, PaymentVerificationError> {
+    let Some(settle_amount) = settle_amount else {
+        return Ok(max_amount);
+    };
+    let settle_amount = U256::from_str_radix(settle_amount, 10)
+        .map_err(|_| PaymentVerificationError::InvalidFormat("Invalid settleAmount".to_string()))?;
+    if settle_amount.is_zero() || settle_amount > max_amount {
+        return Err(PaymentVerificationError::invalid_amount(
+            max_amount,
+            settle_amount,
+        ));
+    }
+    Ok(settle_amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assert_settle_amount() {
+        let max = U256::from(1_000);
+        assert_eq!(assert_settle_amount(None, max).unwrap(), max);
+        assert_eq!(
+            assert_settle_amount(Some("250"), max).unwrap(),
+            U256::from(250)
+        );
+        assert_eq!(assert_settle_amount(Some("1000"), max).unwrap(), max);
+        assert!(matches!(
+            assert_settle_amount(Some("1001"), max),
+            Err(PaymentVerificationError::InvalidPaymentAmount { .. })
+        ));
+        assert!(matches!(
+            assert_settle_amount(Some("0"), max),
+            Err(PaymentVerificationError::InvalidPaymentAmount { .. })
+        ));
+        assert!(matches!(
+            assert_settle_amount(Some("0x10"), max),
+            Err(PaymentVerificationError::InvalidFormat(_))
+        ));
+    }
+}
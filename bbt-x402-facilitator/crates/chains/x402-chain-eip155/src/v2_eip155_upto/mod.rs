@@ -0,0 +1,67 @@
+//! V2 EIP-155 "upto" payment scheme implementation.
+//!
+//! This module implements metered payments for the V2 x402 protocol, where the price
+//! is only known once the request has been served (streamed responses, per-token LLM
+//! billing, ...). It builds on the Permit2 AllowanceTransfer path, which can transfer
+//! less than the allowance:
+//!
+//! 1. The payer signs a Permit2 `PermitSingle` granting a facilitator signer (the
+//!    `spender` of the requirements `extra`) an allowance of the requirements
+//!    `amount`, which is the most the request may cost.
+//! 2. Verification checks the allowance could be pulled in full.
+//! 3. The resource server serves the request, meters it, and settles with a
+//!    `settleAmount` member in the settle request. The facilitator submits the
+//!    `permit` and pulls only that amount with Permit2 `transferFrom`, after
+//!    checking `settleAmount <= amount`. Without `settleAmount`, the full amount
+//!    is settled.
+//!
+//! The settled amount is reported in the settlement receipt. With `x402-axum`, the
+//! protected handler reports it as a `SettleAmount` response extension.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! use x402_chain_eip155::v2_eip155_upto::V2Eip155Upto;
+//!
+//! let price = V2Eip155Upto::price_tag(
+//!     "0x1234...",  // pay_to address
+//!     bbt.amount(2_000_000_000_000_000_000u64),  // at most 2 BBT
+//! );
+//! ```
+
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "server")]
+#[allow(unused_imports)]
+pub use server::*;
+
+#[cfg(feature = "facilitator")]
+pub mod config;
+#[cfg(feature = "facilitator")]
+pub use config::*;
+#[cfg(feature = "facilitator")]
+pub mod facilitator;
+#[cfg(feature = "facilitator")]
+pub use facilitator::*;
+
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "client")]
+pub use client::*;
+
+pub mod types;
+pub use types::*;
+
+use x402_types::scheme::X402SchemeId;
+
+pub struct V2Eip155Upto;
+
+impl X402SchemeId for V2Eip155Upto {
+    fn namespace(&self) -> &str {
+        "eip155"
+    }
+
+    fn scheme(&self) -> &str {
+        UptoScheme.as_ref()
+    }
+}
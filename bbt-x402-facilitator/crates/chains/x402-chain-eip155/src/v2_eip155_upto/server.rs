@@ -0,0 +1,108 @@
+//! Server-side price tag generation for the V2 EIP-155 upto scheme.
+
+use alloy_primitives::U256;
+use std::sync::Arc;
+use x402_types::chain::{ChainId, DeployedTokenAmount};
+use x402_types::proto;
+use x402_types::proto::v2;
+
+use crate::chain::{ChecksummedAddress, Eip155TokenDeployment};
+use crate::v2_eip155_upto::{UptoPaymentRequirementsExtra, UptoScheme, V2Eip155Upto};
+
+impl V2Eip155Upto {
+    /// Creates a V2 price tag for a metered request costing at most `asset`.
+    ///
+    /// The payer grants a Permit2 allowance of the maximum to a facilitator signer.
+    /// After serving the request, the resource server settles what it actually cost,
+    /// e.g. with an `x402_axum::SettleAmount` response extension. The price tag
+    /// enricher names the signer as the `spender` in the requirements `extra`, from
+    /// the facilitator's `/supported`.
+    ///
+    /// # Parameters
+    ///
+    /// - `pay_to`: The recipient address (can be any type convertible to [`ChecksummedAddress`]).
+    /// - `asset`: The token deployment and the most the request may cost
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use x402_chain_eip155::v2_eip155_upto::V2Eip155Upto;
+    ///
+    /// let price_tag = V2Eip155Upto::price_tag(
+    ///     "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb",
+    ///     bbt.amount(2_000_000_000_000_000_000u64), // at most 2 BBT
+    /// );
+    /// ```
+    #[allow(dead_code)] // Public for consumption by downstream crates.
+    pub fn price_tag<A: Into<ChecksummedAddress>>(
+        pay_to: A,
+        asset: DeployedTokenAmount<U256, Eip155TokenDeployment>,
+    ) -> v2::PriceTag {
+        let chain_id: ChainId = asset.token.chain_reference.into();
+        let requirements = v2::PaymentRequirements {
+            scheme: UptoScheme.to_string(),
+            pay_to: pay_to.into().to_string(),
+            asset: asset.token.address.to_string(),
+            network: chain_id,
+            amount: asset.amount.to_string(),
+            max_timeout_seconds: 300,
+            extra: serde_json::to_value(UptoPaymentRequirementsExtra::default()).ok(),
+        };
+        v2::PriceTag {
+            requirements,
+            enricher: Some(Arc::new(enrich_spender)),
+        }
+    }
+}
+
+/// Sets the `spender` the facilitator advertises for the price tag's chain.
+fn enrich_spender(price_tag: &mut v2::PriceTag, capabilities: &proto::SupportedResponse) {
+    let requirements = &mut price_tag.requirements;
+    let spender = capabilities
+        .kinds
+        .iter()
+        .find(|kind| {
+            kind.scheme == UptoScheme.as_ref() && kind.network == requirements.network.to_string()
+        })
+        .and_then(|kind| kind.extra.as_ref()?.get("spender")?.as_str()?.parse().ok());
+    if let Some(spender) = spender {
+        let extra = UptoPaymentRequirementsExtra {
+            spender: Some(spender),
+        };
+        requirements.extra = serde_json::to_value(&extra).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, address};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_price_tag_spender_from_supported() {
+        let token = Eip155TokenDeployment {
+            chain_reference: crate::chain::Eip155ChainReference::new(42793),
+            address: address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6"),
+            decimals: 18,
+            eip712: None,
+        };
+        let mut price_tag = V2Eip155Upto::price_tag(Address::repeat_byte(1), token.amount(5u64));
+        assert_eq!(price_tag.requirements.amount, "5");
+        let spender = Address::repeat_byte(2);
+        let supported = proto::SupportedResponse {
+            kinds: vec![proto::SupportedPaymentKind {
+                x402_version: 2,
+                scheme: "upto".to_string(),
+                network: "eip155:42793".to_string(),
+                extra: Some(serde_json::json!({ "spender": spender })),
+            }],
+            extensions: Vec::new(),
+            signers: HashMap::new(),
+        };
+        enrich_spender(&mut price_tag, &supported);
+        let extra: UptoPaymentRequirementsExtra =
+            serde_json::from_value(price_tag.requirements.extra.unwrap()).unwrap();
+        assert_eq!(extra.spender, Some(spender));
+    }
+}
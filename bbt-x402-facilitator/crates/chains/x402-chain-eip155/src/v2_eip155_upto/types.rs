@@ -0,0 +1,44 @@
+//! Type definitions for the V2 EIP-155 "upto" payment scheme.
+//!
+//! The payload is a Permit2 [`Permit2Payload`], as used by legacy AllowanceTransfer
+//! payments of the "exact" scheme. Its allowance `amount` must cover the requirements
+//! `amount`, the maximum the resource server may settle.
+
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+use x402_types::lit_str;
+use x402_types::proto::v2;
+
+use crate::chain::{Eip155Asset, TokenAmount};
+use crate::v1_eip155_exact::types::Permit2Payload;
+
+lit_str!(UptoScheme, "upto");
+
+/// Type alias for V2 verify requests using the upto scheme.
+pub type VerifyRequest = v2::VerifyRequest<PaymentPayload, PaymentRequirements>;
+
+/// Type alias for V2 settle requests (same structure as verify requests).
+///
+/// The amount to settle is read from the `settleAmount` request member, see
+/// [`x402_types::proto::VerifyRequest::settle_amount`].
+pub type SettleRequest = VerifyRequest;
+
+/// Type alias for V2 payment payloads of the upto scheme.
+pub type PaymentPayload = v2::PaymentPayload<PaymentRequirements, Permit2Payload>;
+
+/// Type alias for V2 payment requirements of the upto scheme.
+///
+/// `amount` is the most the request may cost, `extra` sets the spender the
+/// Permit2 allowance is granted to.
+pub type PaymentRequirements =
+    v2::PaymentRequirements<UptoScheme, TokenAmount, Eip155Asset, UptoPaymentRequirementsExtra>;
+
+/// Scheme-specific `extra` of upto payment requirements.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UptoPaymentRequirementsExtra {
+    /// Facilitator signer the Permit2 allowance is granted to, as advertised on
+    /// `/supported`. Filled in by the resource server middleware when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spender: Option<Address>,
+}
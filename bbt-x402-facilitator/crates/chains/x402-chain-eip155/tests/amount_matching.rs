@@ -0,0 +1,235 @@
+//! `amountMatching` through the full V2 verify path.
+//!
+//! Each test runs `verify` on a signed ERC-3009 payment against a mocked JSON-RPC
+//! transport, so the requirements amount goes through the same checks as on a live
+//! chain: the asset policy bounds on the amount actually transferred first, then the
+//! discount lookup, then the amount matching mode.
+
+use alloy_network::Ethereum;
+use alloy_primitives::{Address, B256, Bytes, U256, address};
+use alloy_provider::RootProvider;
+use alloy_rpc_client::RpcClient;
+use alloy_rpc_types_eth::TransactionReceipt;
+use alloy_signer::SignerSync;
+use alloy_signer_local::PrivateKeySigner;
+use alloy_sol_types::{SolStruct, SolValue, eip712_domain};
+use alloy_transport::mock::Asserter;
+use serde_json::json;
+use x402_types::chain::{ChainId, ChainProviderOps};
+use x402_types::proto;
+use x402_types::scheme::{X402SchemeFacilitator, X402SchemeFacilitatorError};
+use x402_types::timestamp::UnixTimestamp;
+
+use x402_chain_eip155::chain::{
+    Eip155ChainReference, Eip155Contracts, Eip155MetaTransactionProvider, MetaTransaction,
+};
+use x402_chain_eip155::v1_eip155_exact::{
+    Eip155ExactConfig, Eip155ExactError, TransferWithAuthorization,
+};
+use x402_chain_eip155::v2_eip155_exact::V2Eip155ExactFacilitator;
+
+const CHAIN: u64 = 42793;
+const TOKEN: Address = address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6");
+const PAY_TO: Address = address!("0x3333333333333333333333333333333333333333");
+const FACILITATOR: Address = address!("0x4444444444444444444444444444444444444444");
+const AMOUNT: u64 = 10_000;
+
+/// Chain provider backed by a mocked RPC transport. Verification never sends
+/// transactions, so the send methods are left unimplemented.
+struct MockChainProvider {
+    chain: Eip155ChainReference,
+    contracts: Eip155Contracts,
+    inner: RootProvider<Ethereum>,
+}
+
+impl Eip155MetaTransactionProvider for MockChainProvider {
+    type Error = Eip155ExactError;
+    type Inner = RootProvider<Ethereum>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn chain(&self) -> &Eip155ChainReference {
+        &self.chain
+    }
+
+    fn contracts(&self) -> &Eip155Contracts {
+        &self.contracts
+    }
+
+    fn required_confirmations(&self) -> u64 {
+        1
+    }
+
+    async fn send_transaction(
+        &self,
+        _tx: MetaTransaction,
+    ) -> Result<TransactionReceipt, Self::Error> {
+        unimplemented!("tests only verify")
+    }
+
+    async fn send_transaction_from(
+        &self,
+        _tx: MetaTransaction,
+        _from: Address,
+    ) -> Result<TransactionReceipt, Self::Error> {
+        unimplemented!("tests only verify")
+    }
+}
+
+impl ChainProviderOps for MockChainProvider {
+    fn signer_addresses(&self) -> Vec<String> {
+        vec![FACILITATOR.to_string()]
+    }
+
+    fn chain_id(&self) -> ChainId {
+        self.chain.into()
+    }
+}
+
+fn abi<T: SolValue>(value: T) -> Bytes {
+    value.abi_encode().into()
+}
+
+/// A request for `AMOUNT` under `amount_matching`, paid with a signed authorization of `value`.
+fn verify_request(amount_matching: &str, value: u64) -> proto::VerifyRequest {
+    signed_request(json!({ "amountMatching": amount_matching }), value)
+}
+
+/// A request for `AMOUNT` with `extra` added to the requirements `extra`, paid with a
+/// signed authorization of `value`.
+fn signed_request(extra: serde_json::Value, value: u64) -> proto::VerifyRequest {
+    let signer = PrivateKeySigner::random();
+    let now = UnixTimestamp::now().as_secs();
+    let authorization = TransferWithAuthorization {
+        from: signer.address(),
+        to: PAY_TO,
+        value: U256::from(value),
+        validAfter: U256::from(now - 60),
+        validBefore: U256::from(now + 3600),
+        nonce: B256::repeat_byte(0x11),
+    };
+    let domain = eip712_domain! {
+        name: "BBT",
+        version: "1",
+        chain_id: CHAIN,
+        verifying_contract: TOKEN,
+    };
+    let signature = signer
+        .sign_hash_sync(&authorization.eip712_signing_hash(&domain))
+        .unwrap();
+    let mut requirements_extra = json!({ "name": "BBT", "version": "1" });
+    requirements_extra
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    let requirements = json!({
+        "scheme": "exact",
+        "network": format!("eip155:{CHAIN}"),
+        "amount": AMOUNT.to_string(),
+        "payTo": PAY_TO,
+        "maxTimeoutSeconds": 300,
+        "asset": TOKEN,
+        "extra": requirements_extra
+    });
+    json!({
+        "x402Version": 2,
+        "paymentPayload": {
+            "x402Version": 2,
+            "accepted": requirements,
+            "payload": {
+                "signature": Bytes::from(signature.as_bytes().to_vec()),
+                "authorization": {
+                    "from": signer.address(),
+                    "to": PAY_TO,
+                    "value": value.to_string(),
+                    "validAfter": (now - 60).to_string(),
+                    "validBefore": (now + 3600).to_string(),
+                    "nonce": authorization.nonce,
+                }
+            },
+        },
+        "paymentRequirements": requirements,
+    })
+    .into()
+}
+
+/// Whether `request` verifies, with the balance and simulation calls answered successfully.
+async fn is_valid(request: proto::VerifyRequest) -> bool {
+    is_valid_with(request, Eip155ExactConfig::default()).await
+}
+
+/// Like [`is_valid`], for a facilitator with `config`.
+async fn is_valid_with(request: proto::VerifyRequest, config: Eip155ExactConfig) -> bool {
+    let asserter = Asserter::new();
+    // Balance comes back from one Multicall3 `aggregate3`, then the transfer is simulated.
+    asserter.push_success(&abi(vec![(true, abi(U256::MAX))]));
+    asserter.push_success(&Bytes::new());
+    let provider = MockChainProvider {
+        chain: Eip155ChainReference::new(CHAIN),
+        contracts: Eip155Contracts::default(),
+        inner: RootProvider::new(RpcClient::mocked(asserter)),
+    };
+    let facilitator = V2Eip155ExactFacilitator::with_config(provider, config);
+    match facilitator.verify(&request).await {
+        Ok(response) => response.0["isValid"] == true,
+        Err(_) => false,
+    }
+}
+
+#[tokio::test]
+async fn test_at_most_accepts_up_to_the_amount() {
+    assert!(is_valid(verify_request("atMost", AMOUNT / 2)).await);
+    assert!(is_valid(verify_request("atMost", AMOUNT)).await);
+    assert!(!is_valid(verify_request("atMost", AMOUNT + 1)).await);
+    assert!(!is_valid(verify_request("atMost", 0)).await);
+}
+
+#[tokio::test]
+async fn test_exact_and_at_least_reject_underpayments() {
+    assert!(is_valid(verify_request("exact", AMOUNT)).await);
+    assert!(!is_valid(verify_request("exact", AMOUNT / 2)).await);
+    assert!(is_valid(verify_request("atLeast", AMOUNT + 1)).await);
+    assert!(!is_valid(verify_request("atLeast", AMOUNT / 2)).await);
+}
+
+/// An asset policy accepting `TOKEN` payments of `AMOUNT / 2` to `AMOUNT`.
+fn bounded_config() -> Eip155ExactConfig {
+    let policy = json!({ "minAmount": (AMOUNT / 2).to_string(), "maxAmount": AMOUNT.to_string() });
+    let allowed_assets = serde_json::Map::from_iter([(TOKEN.to_string(), policy)]);
+    serde_json::from_value(json!({ "allowedAssets": allowed_assets })).unwrap()
+}
+
+#[tokio::test]
+async fn test_asset_policy_bounds_the_overpayment() {
+    assert!(is_valid_with(verify_request("atLeast", AMOUNT), bounded_config()).await);
+    assert!(!is_valid_with(verify_request("atLeast", AMOUNT + 1), bounded_config()).await);
+}
+
+#[tokio::test]
+async fn test_asset_policy_bounds_the_underpayment() {
+    assert!(is_valid_with(verify_request("atMost", AMOUNT / 2), bounded_config()).await);
+    assert!(!is_valid_with(verify_request("atMost", AMOUNT / 2 - 1), bounded_config()).await);
+}
+
+#[tokio::test]
+async fn test_asset_policy_is_checked_before_the_discount_lookup() {
+    // No RPC response is queued: the gate token balance lookup would fail on transport.
+    let provider = MockChainProvider {
+        chain: Eip155ChainReference::new(CHAIN),
+        contracts: Eip155Contracts::default(),
+        inner: RootProvider::new(RpcClient::mocked(Asserter::new())),
+    };
+    let facilitator = V2Eip155ExactFacilitator::with_config(provider, bounded_config());
+    let discount = json!({
+        "token": address!("0x5555555555555555555555555555555555555555"),
+        "amount": (AMOUNT / 4).to_string(),
+    });
+    let request = signed_request(json!({ "discounts": [discount] }), AMOUNT / 4);
+    let error = facilitator.verify(&request).await.unwrap_err();
+    assert!(
+        matches!(error, X402SchemeFacilitatorError::PaymentVerification(_)),
+        "{error:?}"
+    );
+}
@@ -0,0 +1,277 @@
+//! Property tests of the "exact" scheme wire types.
+//!
+//! Well-formed payloads must survive a JSON round trip unchanged, and no JSON a
+//! client can send, however malformed, may panic the request summary or the typed
+//! V1 and V2 parsers.
+
+use alloy_primitives::{Address, B256, Bytes, U256};
+use proptest::prelude::*;
+use serde_json::{Value, json};
+use x402_types::proto;
+use x402_types::timestamp::UnixTimestamp;
+
+use x402_chain_eip155::v1_eip155_exact::types as v1_types;
+use x402_chain_eip155::v1_eip155_exact::{
+    ExactEvmPayload, ExactEvmPayloadAuthorization, Permit2Authorization, Permit2Details,
+    Permit2Payload, Permit2PermitSingle, Permit2TokenPermissions, Permit2Witness,
+};
+use x402_chain_eip155::v2_eip155_exact::types as v2_types;
+
+fn address() -> impl Strategy<Value = Address> {
+    any::<[u8; 20]>().prop_map(Address::from)
+}
+
+fn u256() -> impl Strategy<Value = U256> {
+    any::<[u64; 4]>().prop_map(U256::from_limbs)
+}
+
+/// Timestamps in seconds up to year 5138; larger integers are read as milliseconds.
+fn timestamp() -> impl Strategy<Value = UnixTimestamp> {
+    (0u64..100_000_000_000).prop_map(UnixTimestamp::from_secs)
+}
+
+fn bytes() -> impl Strategy<Value = Bytes> {
+    prop::collection::vec(any::<u8>(), 0..160).prop_map(Bytes::from)
+}
+
+fn eip3009_payload() -> impl Strategy<Value = ExactEvmPayload> {
+    (
+        bytes(),
+        address(),
+        address(),
+        u256(),
+        timestamp(),
+        timestamp(),
+        any::<[u8; 32]>(),
+    )
+        .prop_map(
+            |(signature, from, to, value, valid_after, valid_before, nonce)| ExactEvmPayload {
+                signature: Some(signature),
+                authorization: Some(ExactEvmPayloadAuthorization {
+                    from,
+                    to,
+                    value,
+                    valid_after,
+                    valid_before,
+                    nonce: B256::from(nonce),
+                }),
+                permit2: None,
+                permit2_authorization: None,
+                stealth: None,
+            },
+        )
+}
+
+fn permit2_payload() -> impl Strategy<Value = ExactEvmPayload> {
+    (
+        bytes(),
+        address(),
+        address(),
+        u256(),
+        any::<u64>(),
+        any::<u64>(),
+        address(),
+        any::<u64>(),
+    )
+        .prop_map(
+            |(signature, owner, token, amount, expiration, nonce, spender, sig_deadline)| {
+                ExactEvmPayload {
+                    signature: None,
+                    authorization: None,
+                    permit2: Some(Permit2Payload {
+                        owner,
+                        permit_single: Permit2PermitSingle {
+                            details: Permit2Details {
+                                token,
+                                amount,
+                                expiration,
+                                nonce,
+                            },
+                            spender,
+                            sig_deadline,
+                        },
+                        signature,
+                    }),
+                    permit2_authorization: None,
+                    stealth: None,
+                }
+            },
+        )
+}
+
+fn permit2_witness_payload() -> impl Strategy<Value = ExactEvmPayload> {
+    (
+        bytes(),
+        (address(), address(), u256()),
+        (address(), u256(), timestamp()),
+        (address(), timestamp(), bytes()),
+    )
+        .prop_map(
+            |(
+                signature,
+                (from, token, amount),
+                (spender, nonce, deadline),
+                (to, valid_after, extra),
+            )| {
+                ExactEvmPayload {
+                    signature: Some(signature),
+                    authorization: None,
+                    permit2: None,
+                    permit2_authorization: Some(Permit2Authorization {
+                        from,
+                        permitted: Permit2TokenPermissions { token, amount },
+                        spender,
+                        nonce,
+                        deadline,
+                        witness: Permit2Witness {
+                            to,
+                            valid_after,
+                            extra,
+                        },
+                    }),
+                    stealth: None,
+                }
+            },
+        )
+}
+
+fn payload() -> impl Strategy<Value = ExactEvmPayload> {
+    prop_oneof![
+        eip3009_payload(),
+        permit2_payload(),
+        permit2_witness_payload()
+    ]
+}
+
+/// Any JSON value, biased towards the keys of x402 requests so that parsers get past
+/// their first checks.
+fn json() -> impl Strategy<Value = Value> {
+    let key = prop_oneof![
+        Just("x402Version".to_string()),
+        Just("paymentPayload".to_string()),
+        Just("paymentRequirements".to_string()),
+        Just("payload".to_string()),
+        Just("accepted".to_string()),
+        Just("scheme".to_string()),
+        Just("network".to_string()),
+        Just("authorization".to_string()),
+        Just("permit2Authorization".to_string()),
+        Just("signature".to_string()),
+        Just("from".to_string()),
+        Just("amount".to_string()),
+        "[a-zA-Z]{0,8}",
+    ];
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<f64>()
+            .prop_map(|f| serde_json::Number::from_f64(f).map_or(Value::Null, Value::Number)),
+        Just(json!(1)),
+        Just(json!(2)),
+        Just(json!("exact")),
+        Just(json!("eip155:42793")),
+        Just(json!("etherlink")),
+        "0x[0-9a-f]{0,80}".prop_map(Value::from),
+        ".{0,24}".prop_map(Value::from),
+    ];
+    leaf.prop_recursive(4, 64, 8, move |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(Value::Array),
+            prop::collection::btree_map(key.clone(), inner, 0..8)
+                .prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+fn round_trip(payload: &ExactEvmPayload) -> Result<(), TestCaseError> {
+    let encoded = serde_json::to_value(payload).unwrap();
+    let decoded: ExactEvmPayload = serde_json::from_value(encoded.clone())
+        .map_err(|e| TestCaseError::fail(format!("{e}: {encoded}")))?;
+    prop_assert_eq!(serde_json::to_value(&decoded).unwrap(), encoded);
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn payloads_round_trip(payload in payload()) {
+        round_trip(&payload)?;
+    }
+
+    #[test]
+    fn v2_requests_round_trip(payload in payload(), amount in u256(), pay_to in address(), asset in address()) {
+        let requirements = json!({
+            "scheme": "exact",
+            "network": "eip155:42793",
+            "amount": amount.to_string(),
+            "payTo": pay_to,
+            "maxTimeoutSeconds": 60,
+            "asset": asset,
+            "extra": { "name": "USD Coin", "version": "2" }
+        });
+        let request: proto::VerifyRequest = json!({
+            "x402Version": 2,
+            "paymentPayload": { "x402Version": 2, "accepted": requirements, "payload": payload },
+            "paymentRequirements": requirements
+        })
+        .into();
+        let typed = v2_types::VerifyRequest::from_proto(&request)
+            .map_err(|e| TestCaseError::fail(format!("{e:?}")))?;
+        round_trip(&typed.payment_payload.payload)?;
+        prop_assert_eq!(request.amount(), Some(amount.to_string()));
+        prop_assert!(request.payer().is_some());
+    }
+
+    #[test]
+    fn v1_requests_round_trip(payload in payload(), amount in u256(), pay_to in address(), asset in address()) {
+        let request: proto::VerifyRequest = json!({
+            "x402Version": 1,
+            "paymentPayload": { "x402Version": 1, "scheme": "exact", "network": "etherlink", "payload": payload },
+            "paymentRequirements": {
+                "scheme": "exact",
+                "network": "etherlink",
+                "maxAmountRequired": amount.to_string(),
+                "resource": "https://example.com/resource",
+                "description": "",
+                "mimeType": "application/json",
+                "payTo": pay_to,
+                "maxTimeoutSeconds": 60,
+                "asset": asset
+            }
+        })
+        .into();
+        let typed = v1_types::VerifyRequest::from_proto(&request)
+            .map_err(|e| TestCaseError::fail(format!("{e:?}")))?;
+        round_trip(&typed.payment_payload.payload)?;
+        prop_assert_eq!(request.amount(), Some(amount.to_string()));
+    }
+
+    #[test]
+    fn arbitrary_json_never_panics(value in json()) {
+        let request: proto::VerifyRequest = value.into();
+        let _ = request.scheme_handler_slug();
+        let _ = (request.payer(), request.payee(), request.amount(), request.asset());
+        let _ = v1_types::VerifyRequest::from_proto(&request);
+        let _ = v2_types::VerifyRequest::from_proto(&request);
+        let _ = serde_json::from_str::<ExactEvmPayload>(request.as_raw().get());
+    }
+
+    #[test]
+    fn arbitrary_payload_json_never_panics(payload in json()) {
+        let request: proto::VerifyRequest = json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "x402Version": 2,
+                "accepted": { "scheme": "exact", "network": "eip155:42793" },
+                "payload": payload
+            },
+            "paymentRequirements": { "scheme": "exact", "network": "eip155:42793" }
+        })
+        .into();
+        let _ = (request.payer(), request.payee(), request.amount(), request.asset());
+        let _ = v2_types::VerifyRequest::from_proto(&request);
+        let _ = serde_json::from_value::<ExactEvmPayload>(payload);
+    }
+}
@@ -0,0 +1,175 @@
+//! Property tests of [`StructuredSignature`] classification and EIP-6492 decoding.
+//!
+//! Payload signatures come straight from payers, so the classifier must hold up on
+//! adversarial bytes: never panic, never take a signature of another key for an EOA
+//! signature of the payer, and keep EIP-1271 and EIP-6492 bytes intact for the wallet
+//! contract to check.
+
+use alloy_primitives::{Address, B256, Bytes, U256};
+use alloy_signer::SignerSync;
+use alloy_signer_local::PrivateKeySigner;
+use alloy_sol_types::SolValue;
+use proptest::prelude::*;
+
+use x402_chain_eip155::v1_eip155_exact::{
+    EIP6492_MAGIC_SUFFIX, Sig6492, StructuredSignature, StructuredSignatureFormatError,
+};
+
+/// Order of the secp256k1 group, the bound of valid `s` values.
+const SECP256K1_N: U256 = U256::from_be_slice(&alloy_primitives::hex!(
+    "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"
+));
+
+fn signer() -> impl Strategy<Value = PrivateKeySigner> {
+    any::<[u8; 32]>().prop_filter_map("not a valid secp256k1 scalar", |key| {
+        PrivateKeySigner::from_bytes(&B256::from(key)).ok()
+    })
+}
+
+fn address() -> impl Strategy<Value = Address> {
+    any::<[u8; 20]>().prop_map(Address::from)
+}
+
+fn bytes(max: usize) -> impl Strategy<Value = Bytes> {
+    prop::collection::vec(any::<u8>(), 0..max).prop_map(Bytes::from)
+}
+
+fn wrap_6492(factory: Address, calldata: Bytes, inner: Bytes) -> Bytes {
+    let body = Sig6492 {
+        factory,
+        factoryCalldata: calldata,
+        innerSig: inner,
+    }
+    .abi_encode_params();
+    [body, EIP6492_MAGIC_SUFFIX.to_vec()].concat().into()
+}
+
+fn classify(bytes: &Bytes, signer: Address, prehash: &B256) -> StructuredSignature {
+    StructuredSignature::try_from_bytes(bytes.clone(), signer, prehash)
+        .expect("only EIP-6492 wrappers fail to classify")
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn arbitrary_bytes_never_panic(
+        bytes in bytes(512),
+        signer in address(),
+        prehash in any::<[u8; 32]>(),
+    ) {
+        let prehash = B256::from(prehash);
+        let ends_with_magic =
+            bytes.len() >= 32 && bytes[bytes.len() - 32..] == EIP6492_MAGIC_SUFFIX;
+        match StructuredSignature::try_from_bytes(bytes.clone(), signer, &prehash) {
+            Ok(StructuredSignature::EIP6492 { original, .. }) => {
+                prop_assert!(ends_with_magic);
+                prop_assert_eq!(original, bytes);
+            }
+            Ok(StructuredSignature::EIP1271(kept)) => {
+                prop_assert!(!ends_with_magic);
+                prop_assert_eq!(kept, bytes);
+            }
+            Ok(StructuredSignature::EOA(signature)) => {
+                prop_assert!(!ends_with_magic);
+                prop_assert!(bytes.len() == 64 || bytes.len() == 65);
+                prop_assert_eq!(signature.recover_address_from_prehash(&prehash).ok(), Some(signer));
+            }
+            Err(StructuredSignatureFormatError::InvalidEIP6492Format(_)) => {
+                prop_assert!(ends_with_magic);
+            }
+        }
+    }
+
+    #[test]
+    fn payer_signatures_are_eoa(signer in signer(), prehash in any::<[u8; 32]>()) {
+        let prehash = B256::from(prehash);
+        let signature = signer.sign_hash_sync(&prehash).unwrap();
+        let full = Bytes::from(signature.as_bytes().to_vec());
+        let compact = Bytes::from(signature.as_erc2098().to_vec());
+        for bytes in [full, compact] {
+            match classify(&bytes, signer.address(), &prehash) {
+                StructuredSignature::EOA(classified) => {
+                    prop_assert!(classified.s() <= SECP256K1_N >> 1);
+                    prop_assert_eq!(
+                        classified.recover_address_from_prehash(&prehash).unwrap(),
+                        signer.address()
+                    );
+                }
+                other => prop_assert!(false, "classified as {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn high_s_signatures_are_normalized(signer in signer(), prehash in any::<[u8; 32]>()) {
+        let prehash = B256::from(prehash);
+        let signature = signer.sign_hash_sync(&prehash).unwrap();
+        // The malleable twin of a signature: s' = n - s, with the parity flipped.
+        let twin = alloy_primitives::Signature::new(
+            signature.r(),
+            SECP256K1_N - signature.s(),
+            !signature.v(),
+        );
+        let bytes = Bytes::from(twin.as_bytes().to_vec());
+        match classify(&bytes, signer.address(), &prehash) {
+            StructuredSignature::EOA(classified) => prop_assert_eq!(classified, signature),
+            other => prop_assert!(false, "classified as {:?}", other),
+        }
+    }
+
+    #[test]
+    fn signatures_of_other_keys_are_not_eoa(
+        signer in signer(),
+        claimed in address(),
+        prehash in any::<[u8; 32]>(),
+    ) {
+        prop_assume!(claimed != signer.address());
+        let prehash = B256::from(prehash);
+        let bytes = Bytes::from(signer.sign_hash_sync(&prehash).unwrap().as_bytes().to_vec());
+        match classify(&bytes, claimed, &prehash) {
+            StructuredSignature::EIP1271(kept) => prop_assert_eq!(kept, bytes),
+            other => prop_assert!(false, "classified as {:?}", other),
+        }
+    }
+
+    #[test]
+    fn eip6492_wrappers_round_trip(
+        factory in address(),
+        calldata in bytes(256),
+        inner in bytes(256),
+        signer in address(),
+    ) {
+        let wrapped = wrap_6492(factory, calldata.clone(), inner.clone());
+        let decoded = [
+            classify(&wrapped, signer, &B256::ZERO),
+            StructuredSignature::try_from(wrapped.clone()).unwrap(),
+        ];
+        for signature in decoded {
+            match signature {
+                StructuredSignature::EIP6492 { factory: f, factory_calldata, inner: i, original } => {
+                    prop_assert_eq!(f, factory);
+                    prop_assert_eq!(&factory_calldata, &calldata);
+                    prop_assert_eq!(&i, &inner);
+                    prop_assert_eq!(&original, &wrapped);
+                }
+                other => prop_assert!(false, "classified as {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn truncated_eip6492_wrappers_are_rejected(
+        factory in address(),
+        calldata in bytes(128),
+        inner in prop::collection::vec(any::<u8>(), 1..128),
+        cut in 1usize..32,
+    ) {
+        let padding = (32 - inner.len() % 32) % 32;
+        let wrapped = wrap_6492(factory, calldata, inner.into());
+        // Cutting into the padding of the inner signature goes unnoticed; cut into its bytes.
+        let body = &wrapped[..wrapped.len() - 32 - padding - cut];
+        let truncated: Bytes = [body, &EIP6492_MAGIC_SUFFIX[..]].concat().into();
+        prop_assert!(StructuredSignature::try_from(truncated).is_err());
+    }
+}
@@ -0,0 +1,423 @@
+//! HTTP client for the facilitator admin API.
+
+use http::{Method, StatusCode, header};
+use reqwest::Client;
+use std::time::Duration;
+use url::Url;
+use x402_types::proto::SettleResponse;
+
+use crate::types::{
+    ClusterStatus, DeadLetter, DeadLetters, RefundOrder, SettlementQuery, Settlements,
+    SignerDriftReport,
+};
+
+/// Errors that can occur while calling the admin API.
+#[derive(Debug, thiserror::Error)]
+pub enum AdminClientError {
+    #[error("URL parse error: {context}: {source}")]
+    UrlParse {
+        context: &'static str,
+        #[source]
+        source: url::ParseError,
+    },
+    #[error("HTTP error: {context}: {source}")]
+    Http {
+        context: &'static str,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("Failed to deserialize JSON: {context}: {source}")]
+    JsonDeserialization {
+        context: &'static str,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("Unexpected HTTP status {status}: {context}: {body}")]
+    HttpStatus {
+        context: &'static str,
+        status: StatusCode,
+        body: String,
+    },
+    #[error("Failed to read response body as text: {context}: {source}")]
+    ResponseBodyRead {
+        context: &'static str,
+        #[source]
+        source: reqwest::Error,
+    },
+}
+
+/// A client for the admin endpoints of a facilitator.
+///
+/// Every request carries the admin API key as a bearer token. The facilitator
+/// only serves `/admin/` routes when `API_KEYS` holds a key with the `admin` scope.
+#[derive(Clone, Debug)]
+pub struct AdminClient {
+    /// Base URL of the facilitator (e.g. `https://facilitator.example/`)
+    base_url: Url,
+    /// API key with the `admin` scope
+    api_key: String,
+    /// Shared Reqwest HTTP client
+    client: Client,
+    /// Optional request timeout
+    timeout: Option<Duration>,
+}
+
+impl AdminClient {
+    /// Constructs a new [`AdminClient`] from the facilitator base URL and an admin API key.
+    pub fn new(base_url: Url, api_key: impl Into<String>) -> Self {
+        Self {
+            base_url,
+            api_key: api_key.into(),
+            client: Client::new(),
+            timeout: None,
+        }
+    }
+
+    /// Like [`AdminClient::new`], parsing the base URL.
+    pub fn try_new(base_url: &str, api_key: impl Into<String>) -> Result<Self, AdminClientError> {
+        let base_url = Url::parse(base_url).map_err(|e| AdminClientError::UrlParse {
+            context: "Invalid facilitator base URL",
+            source: e,
+        })?;
+        Ok(Self::new(base_url, api_key))
+    }
+
+    /// Returns the base URL used by this client.
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Uses `client` for all future requests, e.g. to share a connection pool.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Sets a timeout for all future requests.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// `GET /admin/dlq`: lists the dead-lettered settlements.
+    ///
+    /// Fails with a `404` [`AdminClientError::HttpStatus`] when the facilitator runs
+    /// without `SETTLEMENT_DLQ_ENABLED`.
+    pub async fn dead_letters(&self) -> Result<DeadLetters, AdminClientError> {
+        self.call(Method::GET, "admin/dlq", "GET /admin/dlq", None, &[])
+            .await
+    }
+
+    /// `GET /admin/dlq/{id}`: returns a dead-lettered settlement, or `None` if there is no such entry.
+    pub async fn dead_letter(&self, id: u64) -> Result<Option<DeadLetter>, AdminClientError> {
+        self.call_optional(
+            Method::GET,
+            &format!("admin/dlq/{id}"),
+            "GET /admin/dlq/{id}",
+        )
+        .await
+    }
+
+    /// `POST /admin/dlq/{id}/requeue`: settles a dead-lettered entry again.
+    ///
+    /// Returns the `/settle` response. The facilitator drops the entry once it settles.
+    pub async fn requeue_dead_letter(&self, id: u64) -> Result<SettleResponse, AdminClientError> {
+        let path = format!("admin/dlq/{id}/requeue");
+        self.call(
+            Method::POST,
+            &path,
+            "POST /admin/dlq/{id}/requeue",
+            None,
+            &[],
+        )
+        .await
+    }
+
+    /// `POST /admin/dlq/{id}/void`: drops a dead-lettered entry without settling it.
+    ///
+    /// Returns the dropped entry, or `None` if there is no such entry.
+    pub async fn void_dead_letter(&self, id: u64) -> Result<Option<DeadLetter>, AdminClientError> {
+        let path = format!("admin/dlq/{id}/void");
+        self.call_optional(Method::POST, &path, "POST /admin/dlq/{id}/void")
+            .await
+    }
+
+    /// `GET /admin/signers`: compares the advertised signers with the settlement signers.
+    ///
+    /// The report is returned whether or not the signers are consistent.
+    pub async fn signer_drift(&self) -> Result<SignerDriftReport, AdminClientError> {
+        self.call(
+            Method::GET,
+            "admin/signers",
+            "GET /admin/signers",
+            None,
+            &[StatusCode::SERVICE_UNAVAILABLE],
+        )
+        .await
+    }
+
+    /// `GET /health/cluster`: reports the leadership of the replica that answers.
+    pub async fn cluster_status(&self) -> Result<ClusterStatus, AdminClientError> {
+        self.call(
+            Method::GET,
+            "health/cluster",
+            "GET /health/cluster",
+            None,
+            &[StatusCode::SERVICE_UNAVAILABLE],
+        )
+        .await
+    }
+
+    /// `POST /admin/cluster/resign`: makes the replica that answers give up cluster leadership.
+    pub async fn resign_cluster(&self) -> Result<ClusterStatus, AdminClientError> {
+        self.call(
+            Method::POST,
+            "admin/cluster/resign",
+            "POST /admin/cluster/resign",
+            None,
+            &[],
+        )
+        .await
+    }
+
+    /// `POST /settlements/verify`: checks on chain that a past transaction settled a payment.
+    ///
+    /// Both the query and the report are chain-specific, see the facilitator's
+    /// `history` module for their fields.
+    pub async fn verify_settlement(
+        &self,
+        query: &serde_json::Value,
+    ) -> Result<serde_json::Value, AdminClientError> {
+        self.call(
+            Method::POST,
+            "settlements/verify",
+            "POST /settlements/verify",
+            Some(query),
+            &[],
+        )
+        .await
+    }
+
+    /// `GET /settlements`: lists the recorded settlement attempts matching `query`, most recent first.
+    ///
+    /// Fails with a `404` [`AdminClientError::HttpStatus`] when the facilitator runs
+    /// without `SETTLEMENT_LEDGER_ENABLED`.
+    pub async fn settlements(
+        &self,
+        query: &SettlementQuery,
+    ) -> Result<Settlements, AdminClientError> {
+        let mut params = url::form_urlencoded::Serializer::new(String::new());
+        if let Some(payer) = &query.payer {
+            params.append_pair("payer", payer);
+        }
+        if let Some(payee) = &query.payee {
+            params.append_pair("payee", payee);
+        }
+        if let Some(from) = query.from {
+            params.append_pair("from", &from.as_secs().to_string());
+        }
+        if let Some(to) = query.to {
+            params.append_pair("to", &to.as_secs().to_string());
+        }
+        if let Some(transaction) = &query.transaction {
+            params.append_pair("transaction", transaction);
+        }
+        if let Some(refund_of) = &query.refund_of {
+            params.append_pair("refundOf", refund_of);
+        }
+        if let Some(limit) = query.limit {
+            params.append_pair("limit", &limit.to_string());
+        }
+        let path = format!("settlements?{}", params.finish());
+        self.call(Method::GET, &path, "GET /settlements", None, &[])
+            .await
+    }
+
+    /// `POST /refund`: sends a settled payment, or part of it, back to its payer.
+    ///
+    /// Returns the scheme-specific refund report, with the refund `transaction`.
+    pub async fn refund(&self, order: &RefundOrder) -> Result<serde_json::Value, AdminClientError> {
+        let body = serde_json::to_value(order).expect("RefundOrder serialization failed");
+        self.call(Method::POST, "refund", "POST /refund", Some(&body), &[])
+            .await
+    }
+
+    /// Like [`AdminClient::call`], mapping `404 Not Found` to `None`.
+    async fn call_optional<R>(
+        &self,
+        method: Method,
+        path: &str,
+        context: &'static str,
+    ) -> Result<Option<R>, AdminClientError>
+    where
+        R: serde::de::DeserializeOwned,
+    {
+        match self.call(method, path, context, None, &[]).await {
+            Ok(response) => Ok(Some(response)),
+            Err(AdminClientError::HttpStatus {
+                status: StatusCode::NOT_FOUND,
+                ..
+            }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Sends an authenticated request to `path`, relative to the base URL, and
+    /// deserializes the JSON response.
+    ///
+    /// Responses with a status other than `200 OK` or one of `accepted` are errors.
+    /// `context` is a human-readable identifier used in error messages (e.g. `"GET /admin/dlq"`).
+    async fn call<R>(
+        &self,
+        method: Method,
+        path: &str,
+        context: &'static str,
+        body: Option<&serde_json::Value>,
+        accepted: &[StatusCode],
+    ) -> Result<R, AdminClientError>
+    where
+        R: serde::de::DeserializeOwned,
+    {
+        let url = self
+            .base_url
+            .join(path)
+            .map_err(|e| AdminClientError::UrlParse { context, source: e })?;
+        let mut req = self
+            .client
+            .request(method, url)
+            .header(header::AUTHORIZATION, format!("Bearer {}", self.api_key));
+        if let Some(body) = body {
+            req = req.json(body);
+        }
+        if let Some(timeout) = self.timeout {
+            req = req.timeout(timeout);
+        }
+        let http_response = req
+            .send()
+            .await
+            .map_err(|e| AdminClientError::Http { context, source: e })?;
+
+        let status = http_response.status();
+        if status == StatusCode::OK || accepted.contains(&status) {
+            http_response
+                .json::<R>()
+                .await
+                .map_err(|e| AdminClientError::JsonDeserialization { context, source: e })
+        } else {
+            let body = http_response
+                .text()
+                .await
+                .map_err(|e| AdminClientError::ResponseBodyRead { context, source: e })?;
+            Err(AdminClientError::HttpStatus {
+                context,
+                status,
+                body,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_admin_client() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/admin/dlq"))
+            .and(header("authorization", "Bearer secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "depth": 1,
+                "oldestAgeSecs": 42,
+                "entries": [{
+                    "id": 7,
+                    "request": { "x402Version": 2, "paymentPayload": {}, "paymentRequirements": {} },
+                    "lastError": "nonce too low",
+                    "attempts": 3,
+                    "firstFailedAt": "1700000000",
+                    "lastFailedAt": "1700000042"
+                }]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/admin/dlq/8/void"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+                "error": "not_found"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/admin/signers"))
+            .respond_with(ResponseTemplate::new(503).set_body_json(serde_json::json!({
+                "consistent": false,
+                "chains": [{
+                    "chainId": "eip155:42793",
+                    "consistent": false,
+                    "advertised": ["0xA"],
+                    "settlement": ["0xA"],
+                    "notSettling": [],
+                    "notAdvertised": [],
+                    "unfunded": ["0xA"]
+                }]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/settlements"))
+            .and(query_param("payer", "0xB"))
+            .and(query_param("from", "1700000000"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "entries": [{
+                    "recordedAt": "1700000042",
+                    "outcome": "settlement_succeeded",
+                    "payloadHash": "0x01",
+                    "network": "eip155:42793",
+                    "payer": "0xB",
+                    "payee": "0xC",
+                    "asset": null,
+                    "amount": "1000",
+                    "transaction": "0xabc",
+                    "attempts": 1
+                }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = AdminClient::try_new(&mock_server.uri(), "secret").unwrap();
+        let dead_letters = client.dead_letters().await.unwrap();
+        assert_eq!(dead_letters.depth, 1);
+        assert_eq!(dead_letters.entries[0].id, 7);
+        assert_eq!(dead_letters.entries[0].last_failed_at.as_secs(), 1700000042);
+
+        assert!(client.void_dead_letter(8).await.unwrap().is_none());
+
+        let drift = client.signer_drift().await.unwrap();
+        assert!(!drift.consistent);
+        assert_eq!(drift.chains[0].unfunded, vec!["0xA".to_string()]);
+
+        let query = crate::types::SettlementQuery {
+            payer: Some("0xB".to_string()),
+            from: Some(x402_types::timestamp::UnixTimestamp::from_secs(1700000000)),
+            ..Default::default()
+        };
+        let settlements = client.settlements(&query).await.unwrap();
+        assert_eq!(settlements.entries[0].transaction.as_deref(), Some("0xabc"));
+
+        let err = AdminClient::try_new(&mock_server.uri(), "wrong")
+            .unwrap()
+            .dead_letters()
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AdminClientError::HttpStatus {
+                status: StatusCode::NOT_FOUND,
+                ..
+            }
+        ));
+    }
+}
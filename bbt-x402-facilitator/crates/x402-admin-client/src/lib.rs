@@ -0,0 +1,49 @@
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+
+//! Typed client for the admin API of the x402 facilitator.
+//!
+//! Operators can script runbooks against stable types instead of raw HTTP:
+//! inspect and work through the settlement dead-letter queue, check settlements
+//! on chain, query the settlement ledger and refund payments, audit signers and
+//! hand over cluster leadership.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use x402_admin_client::AdminClient;
+//!
+//! # async fn run() -> Result<(), x402_admin_client::AdminClientError> {
+//! let admin = AdminClient::try_new("https://facilitator.example/", "admin-key")?;
+//! for entry in admin.dead_letters().await?.entries {
+//!     if entry.last_error.contains("nonce too low") {
+//!         admin.void_dead_letter(entry.id).await?;
+//!     } else {
+//!         admin.requeue_dead_letter(entry.id).await?;
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Endpoints
+//!
+//! | Method | Endpoint |
+//! |--------|----------|
+//! | [`AdminClient::dead_letters`] | `GET /admin/dlq` |
+//! | [`AdminClient::dead_letter`] | `GET /admin/dlq/{id}` |
+//! | [`AdminClient::requeue_dead_letter`] | `POST /admin/dlq/{id}/requeue` |
+//! | [`AdminClient::void_dead_letter`] | `POST /admin/dlq/{id}/void` |
+//! | [`AdminClient::verify_settlement`] | `POST /settlements/verify` |
+//! | [`AdminClient::settlements`] | `GET /settlements` |
+//! | [`AdminClient::refund`] | `POST /refund` |
+//! | [`AdminClient::signer_drift`] | `GET /admin/signers` |
+//! | [`AdminClient::cluster_status`] | `GET /health/cluster` |
+//! | [`AdminClient::resign_cluster`] | `POST /admin/cluster/resign` |
+//!
+//! The API key must have the `admin` scope, see `x402_facilitator_local::auth`.
+
+pub mod client;
+pub mod types;
+
+pub use client::{AdminClient, AdminClientError};
+pub use types::*;
@@ -0,0 +1,145 @@
+//! Response types of the facilitator admin API.
+//!
+//! These mirror the JSON served by the facilitator and are kept independent of its
+//! server-side types, so scripts only depend on the wire format.
+
+use serde::{Deserialize, Serialize};
+use x402_types::chain::ChainId;
+use x402_types::proto::SettleRequest;
+use x402_types::timestamp::UnixTimestamp;
+
+/// A settlement that exhausted its retries, as served by `GET /admin/dlq/{id}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    pub id: u64,
+    /// The `/settle` request that failed.
+    pub request: SettleRequest,
+    pub last_error: String,
+    pub attempts: u32,
+    pub first_failed_at: UnixTimestamp,
+    pub last_failed_at: UnixTimestamp,
+}
+
+/// Content of the dead-letter queue, as served by `GET /admin/dlq`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetters {
+    pub depth: usize,
+    /// Age of the oldest entry, in seconds. `None` when the queue is empty.
+    pub oldest_age_secs: Option<u64>,
+    pub entries: Vec<DeadLetter>,
+}
+
+/// Leadership of a replica, as served by `GET /health/cluster`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterStatus {
+    pub node_id: String,
+    /// `false` when the replica runs standalone, without a shared lease store.
+    pub clustered: bool,
+    /// Whether the replica runs the singleton jobs.
+    pub leader: bool,
+    /// Holder of the leader lease, as last seen by the replica.
+    pub leader_id: Option<String>,
+    pub lease_expires_at: Option<UnixTimestamp>,
+    pub last_renewal_at: Option<UnixTimestamp>,
+    /// Error of the last failed lease renewal.
+    pub last_error: Option<String>,
+}
+
+/// Signer set of one chain as advertised, as used for settlement, and where they differ.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainSignerDrift {
+    pub chain_id: ChainId,
+    /// `true` if both sets are equal and every signer is funded.
+    pub consistent: bool,
+    /// Signers listed on `/supported`.
+    pub advertised: Vec<String>,
+    /// Signers settlements are sent from.
+    pub settlement: Vec<String>,
+    /// Advertised signers that never send settlements.
+    pub not_settling: Vec<String>,
+    /// Settlement signers payers cannot name as spender.
+    pub not_advertised: Vec<String>,
+    /// Settlement signers without native balance or below the low-balance threshold.
+    pub unfunded: Vec<String>,
+    /// Why the balances could not be queried.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Signer drift across all chains, as served by `GET /admin/signers`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignerDriftReport {
+    /// `true` if every chain is consistent.
+    pub consistent: bool,
+    /// Per-chain drift, sorted by chain id.
+    pub chains: Vec<ChainSignerDrift>,
+}
+
+/// A recorded settlement attempt, as served by `GET /settlements`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementRecord {
+    pub recorded_at: UnixTimestamp,
+    /// `settlement_succeeded`, `settlement_rejected`, `settlement_failed`,
+    /// `settlement_dead_lettered` or `settlement_refunded`.
+    pub outcome: String,
+    /// Keccak-256 hash of the `paymentPayload` JSON.
+    pub payload_hash: Option<String>,
+    pub network: Option<String>,
+    #[serde(default)]
+    pub x402_version: Option<u8>,
+    #[serde(default)]
+    pub scheme: Option<String>,
+    pub payer: Option<String>,
+    pub payee: Option<String>,
+    pub asset: Option<String>,
+    pub amount: Option<String>,
+    #[serde(default)]
+    pub transaction: Option<String>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    pub attempts: u32,
+    /// Transaction hash of the settlement a `settlement_refunded` entry refunds.
+    #[serde(default)]
+    pub refund_of: Option<String>,
+}
+
+/// Recorded settlement attempts, most recent first, as served by `GET /settlements`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settlements {
+    pub entries: Vec<SettlementRecord>,
+}
+
+/// Filter of [`AdminClient::settlements`](crate::AdminClient::settlements).
+/// Every field given must match.
+#[derive(Debug, Clone, Default)]
+pub struct SettlementQuery {
+    pub payer: Option<String>,
+    pub payee: Option<String>,
+    /// Earliest recording time, inclusive.
+    pub from: Option<UnixTimestamp>,
+    /// Latest recording time, inclusive.
+    pub to: Option<UnixTimestamp>,
+    pub transaction: Option<String>,
+    /// Transaction hash of the settlement refunded.
+    pub refund_of: Option<String>,
+    /// Maximum number of entries. The facilitator returns 100 by default and at most 1000.
+    pub limit: Option<usize>,
+}
+
+/// A refund of a settled payment, as sent to `POST /refund`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefundOrder {
+    /// Transaction hash of the settlement to refund.
+    pub settlement: String,
+    /// Amount to refund, in the asset's smallest unit. Defaults to what is left to refund.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
@@ -0,0 +1,247 @@
+//! ENS names as payment recipients.
+//!
+//! An [`EnsResolver`] turns names such as `merchant.eth`, or DNS names imported into
+//! ENS such as `pay.example.com`, into the address they point to. Price tags always
+//! carry the resolved checksummed address: resolve the name before building them,
+//! so a name that does not resolve fails at startup rather than on a payment.
+//!
+//! Resolution reads the ENS registry and the name's resolver with `eth_call`s on
+//! the configured RPC endpoint, usually an Ethereum mainnet node even when payments
+//! happen on another chain. Addresses are cached for a TTL (one hour by default).
+//!
+//! Names are matched after lowercasing only; full ENSIP-15 normalization is not applied.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use x402_axum::ens::EnsResolver;
+//! use x402_chain_eip155::V2Eip155Exact;
+//!
+//! let ens = EnsResolver::new("https://ethereum-rpc.publicnode.com".parse()?);
+//! let pay_to = ens.resolve("merchant.eth").await?;
+//! let price_tag = V2Eip155Exact::price_tag(pay_to, bbt.parse("0.01")?);
+//! ```
+//!
+//! [`PricingTable`](crate::PricingTable) accepts names as `pay_to` when its file has
+//! an `ens` section, see [`crate::pricing`].
+
+use alloy_primitives::{Address, B256, address, keccak256};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use url::Url;
+
+use crate::rpc::eth_call;
+
+/// The ENS registry, at the same address on every network ENS is deployed to.
+pub const ENS_REGISTRY: Address = address!("0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+
+/// Default time a resolved address is reused before the name is resolved again.
+pub const DEFAULT_ENS_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Errors that can occur while resolving an ENS name.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum EnsError {
+    #[error("Failed to resolve {name}: {reason}")]
+    Rpc { name: String, reason: String },
+    #[error("{0} has no resolver")]
+    NoResolver(String),
+    #[error("{0} does not resolve to an address")]
+    NoAddress(String),
+}
+
+/// Whether `value` is an ENS name rather than an address: dot-separated labels,
+/// without whitespace, not starting with `0x`.
+pub fn is_ens_name(value: &str) -> bool {
+    !value.starts_with("0x")
+        && !value.chars().any(char::is_whitespace)
+        && value.contains('.')
+        && value.split('.').all(|label| !label.is_empty())
+}
+
+/// The ENS `namehash` of a name, as defined by EIP-137.
+pub fn namehash(name: &str) -> B256 {
+    let name = name.to_lowercase();
+    let mut node = B256::ZERO;
+    for label in name.rsplit('.').filter(|label| !label.is_empty()) {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(node.as_slice());
+        bytes[32..].copy_from_slice(keccak256(label.as_bytes()).as_slice());
+        node = keccak256(bytes);
+    }
+    node
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CachedName {
+    address: Address,
+    resolved_at: Instant,
+}
+
+/// Resolves ENS names to addresses and caches them.
+///
+/// Cloning is cheap; clones share the cache.
+#[derive(Debug, Clone)]
+pub struct EnsResolver {
+    client: reqwest::Client,
+    rpc: Url,
+    registry: Address,
+    ttl: Duration,
+    cache: Arc<RwLock<HashMap<String, CachedName>>>,
+}
+
+impl EnsResolver {
+    /// Creates a resolver reading the ENS registry through the JSON-RPC endpoint `rpc`.
+    pub fn new(rpc: Url) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            rpc,
+            registry: ENS_REGISTRY,
+            ttl: DEFAULT_ENS_TTL,
+            cache: Arc::default(),
+        }
+    }
+
+    /// Uses the registry at `registry` instead of [`ENS_REGISTRY`].
+    pub fn with_registry(mut self, registry: Address) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Sets how long a resolved address is reused.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Returns the address `name` points to, from the cache while it has not expired.
+    pub async fn resolve(&self, name: &str) -> Result<Address, EnsError> {
+        let name = name.to_lowercase();
+        if let Some(address) = self.cached(&name).filter(|_| self.is_fresh(&name)) {
+            return Ok(address);
+        }
+        let address = self.lookup(&name).await?;
+        self.cache.write().expect("ENS cache lock poisoned").insert(
+            name,
+            CachedName {
+                address,
+                resolved_at: Instant::now(),
+            },
+        );
+        Ok(address)
+    }
+
+    /// The last address `name` resolved to, even if it has expired.
+    pub fn cached(&self, name: &str) -> Option<Address> {
+        self.cache
+            .read()
+            .expect("ENS cache lock poisoned")
+            .get(&name.to_lowercase())
+            .map(|cached| cached.address)
+    }
+
+    /// Whether `name` has been resolved within the TTL.
+    pub fn is_fresh(&self, name: &str) -> bool {
+        self.cache
+            .read()
+            .expect("ENS cache lock poisoned")
+            .get(&name.to_lowercase())
+            .is_some_and(|cached| cached.resolved_at.elapsed() < self.ttl)
+    }
+
+    async fn lookup(&self, name: &str) -> Result<Address, EnsError> {
+        const RESOLVER_SELECTOR: &str = "0178b8bf";
+        const ADDR_SELECTOR: &str = "3b3b57de";
+        let node = namehash(name);
+        let rpc_error = |reason| EnsError::Rpc {
+            name: name.to_string(),
+            reason,
+        };
+        let resolver = eth_call(
+            &self.client,
+            &self.rpc,
+            &self.registry.to_string(),
+            &format!("0x{RESOLVER_SELECTOR}{node:x}"),
+        )
+        .await
+        .map_err(rpc_error)?;
+        let resolver = word_address(&resolver)
+            .filter(|resolver| !resolver.is_zero())
+            .ok_or_else(|| EnsError::NoResolver(name.to_string()))?;
+        let address = eth_call(
+            &self.client,
+            &self.rpc,
+            &resolver.to_string(),
+            &format!("0x{ADDR_SELECTOR}{node:x}"),
+        )
+        .await
+        .map_err(rpc_error)?;
+        word_address(&address)
+            .filter(|address| !address.is_zero())
+            .ok_or_else(|| EnsError::NoAddress(name.to_string()))
+    }
+}
+
+/// The address in the first 32-byte word of an ABI-encoded `eth_call` result.
+fn word_address(result: &str) -> Option<Address> {
+    let digits = result.trim_start_matches("0x");
+    let word = digits.get(..64)?;
+    let bytes = alloy_primitives::hex::decode(word).ok()?;
+    Some(Address::from_slice(&bytes[12..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn word(address: Address) -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": format!("0x{:0>64}", alloy_primitives::hex::encode(address)),
+        })
+    }
+
+    #[test]
+    fn test_namehash() {
+        assert_eq!(namehash(""), B256::ZERO);
+        assert_eq!(
+            namehash("foo.eth").to_string(),
+            "0xde9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f"
+        );
+        assert!(is_ens_name("merchant.eth"));
+        assert!(is_ens_name("pay.example.com"));
+        assert!(!is_ens_name("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"));
+        assert!(!is_ens_name("merchant"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_and_cache() {
+        let resolver = address!("0x4976fb03C32e5B8cfe2b6cCB31c09Ba78EBaBa41");
+        let owner = address!("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+        let rpc = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("0x0178b8bf"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(word(resolver)))
+            .expect(1)
+            .mount(&rpc)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("0x3b3b57de"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(word(owner)))
+            .expect(1)
+            .mount(&rpc)
+            .await;
+
+        let ens = EnsResolver::new(rpc.uri().parse().unwrap());
+        assert_eq!(ens.resolve("Merchant.eth").await.unwrap(), owner);
+        assert_eq!(ens.resolve("merchant.eth").await.unwrap(), owner);
+        assert!(ens.is_fresh("merchant.eth"));
+
+        let expired = ens.clone().with_ttl(Duration::ZERO);
+        assert!(!expired.is_fresh("merchant.eth"));
+        assert_eq!(expired.cached("merchant.eth"), Some(owner));
+    }
+}
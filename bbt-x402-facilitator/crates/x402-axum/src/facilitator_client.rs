@@ -35,7 +35,8 @@ use tokio::sync::RwLock;
 use url::Url;
 use x402_types::facilitator::Facilitator;
 use x402_types::proto::{
-    SettleRequest, SettleResponse, SupportedResponse, VerifyRequest, VerifyResponse,
+    SettleDryRunResponse, SettleRequest, SettleResponse, SupportedResponse, VerifyRequest,
+    VerifyResponse,
 };
 
 #[cfg(feature = "telemetry")]
@@ -173,6 +174,14 @@ impl Facilitator for FacilitatorClient {
         FacilitatorClient::settle(self, request).await
     }
 
+    /// Dry-runs a settlement with the facilitator.
+    async fn settle_dry_run(
+        &self,
+        request: &SettleRequest,
+    ) -> Result<SettleDryRunResponse, FacilitatorClientError> {
+        FacilitatorClient::settle_dry_run(self, request).await
+    }
+
     /// Retrieves the supported payment kinds from the facilitator.
     ///
     /// Results are cached with a configurable TTL to avoid repeated HTTP requests.
@@ -339,6 +348,17 @@ impl FacilitatorClient {
             .await
     }
 
+    /// Sends a `POST /settle?dryRun=true` request to the facilitator.
+    pub async fn settle_dry_run(
+        &self,
+        request: &SettleRequest,
+    ) -> Result<SettleDryRunResponse, FacilitatorClientError> {
+        let mut url = self.settle_url.clone();
+        url.set_query(Some("dryRun=true"));
+        self.post_json(&url, "POST /settle?dryRun=true", request)
+            .await
+    }
+
     /// Sends a `GET /supported` request to the facilitator.
     /// This is the inner method that always makes an HTTP request.
     #[cfg_attr(
@@ -25,6 +25,7 @@
 //!     eip712: Some(TokenDeploymentEip712 {
 //!         name: "BBT".into(),
 //!         version: "1".into(),
+//!         domain: Default::default(),
 //!     }),
 //! };
 //!
@@ -55,6 +56,8 @@
 //!
 //! - **[`X402Middleware::with_price_tag`]** sets the assets and amounts accepted for payment (static pricing).
 //! - **[`X402Middleware::with_dynamic_price`]** sets a callback for dynamic pricing based on request context.
+//! - **[`X402Middleware::with_pricing_table`]** loads prices per route from a JSON/TOML file.
+//! - **[`X402Middleware::with_payment_transports`]** additionally accepts payments in a query parameter or body envelope.
 //! - **[`X402Middleware::with_base_url`]** sets the base URL for computing full resource URLs.
 //!   If not set, defaults to `http://localhost/` (avoid in production).
 //! - **[`X402LayerBuilder::with_description`]** is optional but helps the payer understand what is being paid for.
@@ -75,12 +78,15 @@ use tower::util::BoxCloneSyncService;
 use tower::{Layer, Service};
 use url::Url;
 use x402_types::facilitator::Facilitator;
+use x402_types::proto::transport::PaymentTransport;
 
 use crate::facilitator_client::FacilitatorClient;
 use crate::paygate::{
     DynamicPriceTags, Paygate, PaygateProtocol, PriceTagSource, ResourceInfoBuilder,
     StaticPriceTags,
 };
+use crate::pricing::PricingTable;
+use crate::transport::lift_payment;
 
 /// The main X402 middleware instance for enforcing x402 payments on routes.
 ///
@@ -91,6 +97,7 @@ pub struct X402Middleware<F> {
     facilitator: F,
     base_url: Option<Url>,
     settle_before_execution: bool,
+    transports: Arc<Vec<PaymentTransport>>,
 }
 
 impl<F> X402Middleware<F> {
@@ -112,6 +119,7 @@ impl X402Middleware<Arc<FacilitatorClient>> {
             facilitator: Arc::new(facilitator),
             base_url: None,
             settle_before_execution: false,
+            transports: Arc::new(Vec::new()),
         }
     }
 
@@ -122,6 +130,7 @@ impl X402Middleware<Arc<FacilitatorClient>> {
             facilitator: Arc::new(facilitator),
             base_url: None,
             settle_before_execution: false,
+            transports: Arc::new(Vec::new()),
         })
     }
 
@@ -140,6 +149,7 @@ impl X402Middleware<Arc<FacilitatorClient>> {
             facilitator,
             base_url: self.base_url.clone(),
             settle_before_execution: self.settle_before_execution,
+            transports: self.transports.clone(),
         }
     }
 }
@@ -184,6 +194,31 @@ where
         this
     }
 
+    /// Accepts payments in the given transports in addition to the payment header.
+    ///
+    /// The accepted transports are listed in the `402` challenge, so clients can
+    /// choose a transport that survives proxies stripping large headers. Payments
+    /// sent via query parameter or body envelope are moved into the payment header
+    /// before verification; the protected handler sees neither.
+    pub fn with_payment_transports<I>(&self, transports: I) -> X402Middleware<F>
+    where
+        I: IntoIterator<Item = PaymentTransport>,
+    {
+        let mut accepted = vec![PaymentTransport::Header];
+        for transport in transports {
+            if !accepted.contains(&transport) {
+                accepted.push(transport);
+            }
+        }
+        let mut this = self.clone();
+        this.transports = if accepted.len() > 1 {
+            Arc::new(accepted)
+        } else {
+            Arc::new(Vec::new())
+        };
+        this
+    }
+
     /// Disables settlement prior to request execution (default behavior).
     ///
     /// When disabled, settlement occurs after successful request execution.
@@ -214,6 +249,26 @@ where
             base_url: self.base_url.clone().map(Arc::new),
             resource: Arc::new(ResourceInfoBuilder::default()),
             settle_before_execution: self.settle_before_execution,
+            transports: self.transports.clone(),
+        }
+    }
+
+    /// Sets several alternative price tags for the protected route, e.g. one per
+    /// accepted token.
+    ///
+    /// All of them are advertised in the `accepts` list of the 402 response and the
+    /// payer picks one. Equivalent to chaining [`X402LayerBuilder::with_price_tag`].
+    pub fn with_price_tags<TPriceTag>(
+        &self,
+        price_tags: impl IntoIterator<Item = TPriceTag>,
+    ) -> X402LayerBuilder<StaticPriceTags<TPriceTag>, TFacilitator> {
+        X402LayerBuilder {
+            facilitator: self.facilitator.clone(),
+            price_source: StaticPriceTags::new(price_tags.into_iter().collect()),
+            base_url: self.base_url.clone().map(Arc::new),
+            resource: Arc::new(ResourceInfoBuilder::default()),
+            settle_before_execution: self.settle_before_execution,
+            transports: self.transports.clone(),
         }
     }
 
@@ -236,6 +291,7 @@ where
     ///     eip712: Some(TokenDeploymentEip712 {
     ///         name: "BBT".into(),
     ///         version: "1".into(),
+    ///         domain: Default::default(),
     ///     }),
     /// };
     ///
@@ -267,6 +323,26 @@ where
             base_url: self.base_url.clone().map(Arc::new),
             resource: Arc::new(ResourceInfoBuilder::default()),
             settle_before_execution: self.settle_before_execution,
+            transports: self.transports.clone(),
+        }
+    }
+
+    /// Sets a file-based pricing table as the price source.
+    ///
+    /// The table decides per request path and method which price tags apply.
+    /// Requests matching no route in the table are passed through without payment,
+    /// so the layer can be applied to a whole router.
+    pub fn with_pricing_table(
+        &self,
+        pricing_table: PricingTable,
+    ) -> X402LayerBuilder<PricingTable, TFacilitator> {
+        X402LayerBuilder {
+            facilitator: self.facilitator.clone(),
+            price_source: pricing_table,
+            base_url: self.base_url.clone().map(Arc::new),
+            resource: Arc::new(ResourceInfoBuilder::default()),
+            settle_before_execution: self.settle_before_execution,
+            transports: self.transports.clone(),
         }
     }
 }
@@ -279,6 +355,7 @@ where
 pub struct X402LayerBuilder<TSource, TFacilitator> {
     facilitator: TFacilitator,
     settle_before_execution: bool,
+    transports: Arc<Vec<PaymentTransport>>,
     base_url: Option<Arc<Url>>,
     price_source: TSource,
     resource: Arc<ResourceInfoBuilder>,
@@ -345,6 +422,7 @@ where
         X402MiddlewareService {
             facilitator: self.facilitator.clone(),
             settle_before_execution: self.settle_before_execution,
+            transports: self.transports.clone(),
             base_url: self.base_url.clone(),
             price_source: self.price_source.clone(),
             resource: self.resource.clone(),
@@ -365,6 +443,8 @@ pub struct X402MiddlewareService<TSource, TFacilitator> {
     base_url: Option<Arc<Url>>,
     /// Whether to settle payment before executing the request (true) or after (false)
     settle_before_execution: bool,
+    /// Accepted payment transports; empty means header only
+    transports: Arc<Vec<PaymentTransport>>,
     /// Price tag source - can be static or dynamic
     price_source: TSource,
     /// Resource information
@@ -395,12 +475,13 @@ where
         let base_url = self.base_url.clone();
         let resource_builder = self.resource.clone();
         let settle_before_execution = self.settle_before_execution;
+        let transports = self.transports.clone();
         let mut inner = self.inner.clone();
 
         Box::pin(async move {
             // Resolve price tags from the source
             let accepts = price_source
-                .resolve(req.headers(), req.uri(), base_url.as_deref())
+                .resolve_for_method(req.method(), req.headers(), req.uri(), base_url.as_deref())
                 .await;
 
             // If no price tags are configured, bypass payment enforcement
@@ -408,6 +489,14 @@ where
                 return inner.call(req).await;
             }
 
+            // Move payments sent via query parameter or body envelope into the header
+            let req = match lift_payment(req, &transports, TSource::PriceTag::PAYMENT_HEADER_NAME)
+                .await
+            {
+                Ok(req) => req,
+                Err(response) => return Ok(response),
+            };
+
             let resource = resource_builder.as_resource_info(base_url.as_deref(), &req);
 
             let gate = Paygate {
@@ -415,6 +504,7 @@ where
                 settle_before_execution,
                 accepts: Arc::new(accepts),
                 resource,
+                transports,
             };
             gate.handle_request(inner, req).await
         })
@@ -27,6 +27,7 @@
 //!     eip712: Some(TokenDeploymentEip712 {
 //!         name: "BBT".into(),
 //!         version: "1".into(),
+//!         domain: Default::default(),
 //!     }),
 //! };
 //!
@@ -76,6 +77,7 @@
 //!     eip712: Some(TokenDeploymentEip712 {
 //!         name: "BBT".into(),
 //!         version: "1".into(),
+//!         domain: Default::default(),
 //!     }),
 //! };
 //!
@@ -113,20 +115,43 @@
 //! - **[`X402Middleware::settle_after_execution`]** - Settle payment **after** request execution (default).
 //!   This allows processing the request before committing the payment on-chain.
 //!
+//! When settling after execution, a handler can insert a [`SettleAmount`] into its response
+//! extensions to settle less than the authorized maximum, as the "upto" scheme allows.
+//!
 //! ## Configuration Notes
 //!
 //! - **[`X402Middleware::with_price_tag`]** sets the assets and amounts accepted for payment (static pricing).
 //! - **[`X402Middleware::with_dynamic_price`]** sets a callback for dynamic pricing based on request context.
+//! - **[`X402Middleware::with_payment_transports`]** additionally accepts payments in a query parameter or body envelope.
 //! - **[`X402Middleware::with_base_url`]** sets the base URL for computing full resource URLs.
 //!   If not set, derives from forwarded/host headers and falls back to `https://invalid.local` (set `with_base_url` in production).
 //! - **[`X402Middleware::with_supported_cache_ttl`]** configures the TTL for caching facilitator capabilities.
 //! - **[`X402LayerBuilder::with_description`]** is optional but helps the payer understand what is being paid for.
 //! - **[`X402LayerBuilder::with_mime_type`]** sets the MIME type of the protected resource (default: `application/json`).
 //! - **[`X402LayerBuilder::with_resource`]** explicitly sets the full URI of the protected resource.
+//!
+//! ## Pricing Tables
+//!
+//! Prices can also be kept in a JSON or TOML file with per-route patterns, methods and
+//! token amounts. Use [`X402Middleware::with_pricing_table`] with a [`PricingTable`];
+//! edits to the file are picked up without a restart. See [`pricing`] for the file format.
+//!
+//! ## ENS Recipients
+//!
+//! `pay_to` can be given as an ENS name: resolve it with an [`ens::EnsResolver`]
+//! before building price tags, or add an `ens` section to a pricing table.
 
+pub mod ens;
 pub mod facilitator_client;
 pub mod layer;
 pub mod paygate;
+pub mod pricing;
+mod rpc;
+mod transport;
 
+pub use ens::{EnsError, EnsResolver};
 pub use layer::{X402LayerBuilder, X402Middleware};
-pub use paygate::{DynamicPriceTags, PaygateProtocol, PriceTagSource, StaticPriceTags};
+pub use paygate::{
+    DynamicPriceTags, PaygateProtocol, PriceTagSource, SettleAmount, StaticPriceTags,
+};
+pub use pricing::{PricingTable, PricingTableError};
@@ -23,6 +23,7 @@
 //!     settle_before_execution: false,
 //!     accepts: Arc::new(price_tags),
 //!     resource: ResourceInfoBuilder::default().as_resource_info(&base_url, &uri),
+//!     transports: Arc::new(Vec::new()),
 //! };
 //!
 //! // Handle a request
@@ -32,7 +33,7 @@
 use axum_core::body::Body;
 use axum_core::extract::Request;
 use axum_core::response::{IntoResponse, Response};
-use http::{HeaderMap, HeaderValue, StatusCode, Uri};
+use http::{HeaderMap, HeaderValue, Method, StatusCode, Uri};
 use serde_json::json;
 use std::convert::Infallible;
 use std::future::Future;
@@ -42,6 +43,7 @@ use tower::Service;
 use url::Url;
 use x402_types::facilitator::Facilitator;
 use x402_types::proto;
+use x402_types::proto::transport::PaymentTransport;
 use x402_types::proto::{SupportedResponse, v1, v2};
 
 #[cfg(feature = "telemetry")]
@@ -172,10 +174,13 @@ pub trait PaygateProtocol: Clone + Send + Sync + 'static {
     ) -> Result<proto::VerifyRequest, VerificationError>;
 
     /// Converts an error into an HTTP response with appropriate format.
+    ///
+    /// `transports` lists the accepted payment transports advertised in the challenge.
     fn error_into_response(
         err: PaygateError,
         accepts: &[Self],
         resource: &v2::ResourceInfo,
+        transports: &[PaymentTransport],
     ) -> Response;
 
     /// Converts the verify response to the protocol-specific format and validates it.
@@ -227,6 +232,7 @@ impl PaygateProtocol for v1::PriceTag {
         err: PaygateError,
         accepts: &[Self],
         resource: &v2::ResourceInfo,
+        transports: &[PaymentTransport],
     ) -> Response {
         match err {
             PaygateError::Verification(err) => {
@@ -237,6 +243,7 @@ impl PaygateProtocol for v1::PriceTag {
                         .map(|pt| price_tag_to_v1_requirements_with_resource(pt, resource))
                         .collect(),
                     x402_version: v1::X402Version1,
+                    transports: transports.to_vec(),
                 };
                 let payment_required_response_bytes =
                     serde_json::to_vec(&payment_required_response).expect("serialization failed");
@@ -337,16 +344,17 @@ impl PaygateProtocol for v2::PriceTag {
             payment_requirements: selected.requirements.clone(),
         };
 
-        let json = serde_json::to_value(&verify_request)
+        let raw = serde_json::value::to_raw_value(&verify_request)
             .map_err(|e| VerificationError::VerificationFailed(format!("{e}")))?;
 
-        Ok(proto::VerifyRequest::from(json))
+        Ok(proto::VerifyRequest::from(raw))
     }
 
     fn error_into_response(
         err: PaygateError,
         accepts: &[Self],
         resource: &v2::ResourceInfo,
+        transports: &[PaymentTransport],
     ) -> Response {
         match err {
             PaygateError::Verification(err) => {
@@ -355,6 +363,7 @@ impl PaygateProtocol for v2::PriceTag {
                     accepts: accepts.iter().map(|pt| pt.requirements.clone()).collect(),
                     x402_version: v2::X402Version2,
                     resource: resource.clone(),
+                    transports: transports.to_vec(),
                 };
                 // V2 sends payment required in the "Payment-Required" header (base64 encoded)
                 let payment_required_bytes =
@@ -410,6 +419,25 @@ impl PaygateProtocol for v2::PriceTag {
 // Unified Paygate Implementation
 // ============================================================================
 
+/// Amount to settle, reported by the protected handler as a response extension.
+///
+/// With schemes authorizing a maximum, such as "upto", the handler meters what the
+/// request actually used and the paygate settles that instead of the full
+/// authorization. It only applies when settling after execution.
+///
+/// ```ignore
+/// async fn completion() -> Response {
+///     let (body, tokens) = run_completion().await;
+///     let mut response = body.into_response();
+///     response
+///         .extensions_mut()
+///         .insert(SettleAmount((tokens * PRICE_PER_TOKEN).to_string()));
+///     response
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettleAmount(pub String);
+
 /// Unified payment gate that works with both V1 and V2 protocols.
 ///
 /// The protocol version is determined by the price tag type parameter `P`, which must
@@ -424,6 +452,8 @@ pub struct Paygate<TPriceTag, TFacilitator> {
     pub accepts: Arc<Vec<TPriceTag>>,
     /// Resource information for the protected endpoint
     pub resource: v2::ResourceInfo,
+    /// Accepted payment transports; empty means header only
+    pub transports: Arc<Vec<PaymentTransport>>,
 }
 
 impl<TPriceTag, TFacilitator> Paygate<TPriceTag, TFacilitator> {
@@ -489,6 +519,7 @@ where
                     err,
                     &enriched_accepts,
                     &self.resource,
+                    &self.transports,
                 ))
             }
         }
@@ -574,7 +605,11 @@ where
                 return Ok(response.into_response());
             }
 
-            let settlement = self.settle_payment(&verify_request).await?;
+            let settle_request = match response.extensions().get::<SettleAmount>() {
+                Some(SettleAmount(amount)) => verify_request.with_settle_amount(amount.clone()),
+                None => verify_request,
+            };
+            let settlement = self.settle_payment(&settle_request).await?;
 
             let header_value = settlement_to_header(settlement)?;
 
@@ -676,6 +711,21 @@ pub trait PriceTagSource {
         uri: &Uri,
         base_url: Option<&Url>,
     ) -> impl Future<Output = Vec<Self::PriceTag>> + Send;
+
+    /// Resolves price tags for the given request context, including the HTTP method.
+    ///
+    /// The middleware calls this method. The default implementation ignores the
+    /// method and delegates to [`PriceTagSource::resolve`].
+    fn resolve_for_method(
+        &self,
+        method: &Method,
+        headers: &HeaderMap,
+        uri: &Uri,
+        base_url: Option<&Url>,
+    ) -> impl Future<Output = Vec<Self::PriceTag>> + Send {
+        let _ = method;
+        self.resolve(headers, uri, base_url)
+    }
 }
 
 // ============================================================================
@@ -780,6 +830,7 @@ type BoxedDynamicPriceCallback<TPriceTag> = dyn for<'a> Fn(
 ///     eip712: Some(TokenDeploymentEip712 {
 ///         name: "BBT".into(),
 ///         version: "1".into(),
+///         domain: Default::default(),
 ///     }),
 /// };
 ///
@@ -837,6 +888,7 @@ impl<TPriceTag> DynamicPriceTags<TPriceTag> {
     ///     eip712: Some(TokenDeploymentEip712 {
     ///         name: "BBT".into(),
     ///         version: "1".into(),
+    ///         domain: Default::default(),
     ///     }),
     /// };
     ///
@@ -0,0 +1,959 @@
+//! File-based pricing tables.
+//!
+//! A [`PricingTable`] is a [`PriceTagSource`] whose prices come from a JSON (or,
+//! with the `toml` feature, TOML) file instead of code. Operators can change
+//! prices by editing the file; the table picks up changes on its own.
+//!
+//! ## File Format
+//!
+//! ```json
+//! {
+//!   "defaults": {
+//!     "pay_to": "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045",
+//!     "max_timeout_seconds": 300
+//!   },
+//!   "rpc": {
+//!     "eip155:42793": "https://node.mainnet.etherlink.com"
+//!   },
+//!   "tokens": {
+//!     "bbt": {
+//!       "network": "eip155:42793",
+//!       "asset": "0x7EfE4bdd11237610bcFca478937658bE39F8dfd6",
+//!       "decimals": 18,
+//!       "extra": { "name": "BBT", "version": "1" }
+//!     }
+//!   },
+//!   "routes": [
+//!     {
+//!       "path": "/api/reports/{id}",
+//!       "methods": ["GET"],
+//!       "prices": [{ "token": "bbt", "amount": "0.01" }]
+//!     },
+//!     {
+//!       "path": "/api/premium/**",
+//!       "prices": [
+//!         { "token": "bbt", "amount": "0.05", "pay_to": "0x1111111111111111111111111111111111111111" }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! - `path` matches the request path segment by segment. `*`, `:name` and `{name}`
+//!   match a single segment; a trailing `**` matches any remainder.
+//! - `methods` restricts the route to the given HTTP methods. An empty list matches all.
+//! - `amount` is in whole tokens and converted with the token's `decimals`;
+//!   `amount_atomic` gives the amount in the token's smallest unit instead.
+//! - `pay_to`, `max_timeout_seconds`, `scheme` and `extra` can be set per price,
+//!   falling back to `defaults` and the token entry.
+//! - `pay_to` may be an ENS name, such as `merchant.eth`, if the file has an `ens`
+//!   section (see below).
+//!
+//! Routes are matched in file order and the first match wins. Requests that match
+//! no route are passed through without payment.
+//!
+//! ## Decimals Check
+//!
+//! For tokens on EVM networks listed under `rpc`, the configured `decimals` are
+//! compared with the token's on-chain `decimals()`. [`PricingTable::check_decimals`]
+//! runs the check up front, e.g. at startup; otherwise it runs on first use. Price
+//! tags of a token whose decimals do not match are not served, so a route priced
+//! only in that token is passed through without payment. Tokens whose decimals
+//! could not be read are served and checked again on the next request.
+//!
+//! ## ENS Names
+//!
+//! ```json
+//! { "ens": { "rpc": "https://ethereum-rpc.publicnode.com", "ttl_secs": 3600 } }
+//! ```
+//!
+//! With an `ens` section, `pay_to` values that are names are resolved through the
+//! ENS registry read from `rpc` (see [`crate::ens`]), and price tags carry the
+//! resolved checksummed address. [`PricingTable::load_resolved`] and
+//! [`PricingTable::resolve_names`] resolve them up front and fail on names that do
+//! not resolve; otherwise they are resolved on first use. Names are resolved again
+//! once `ttl_secs` (default one hour) have passed; if that fails, the last address
+//! stays in use. Price tags whose name never resolved are not served.
+//!
+//! ## Hot Reload
+//!
+//! A table loaded with [`PricingTable::load`] checks the file's modification time
+//! at most once per reload interval (5 seconds by default) while serving requests,
+//! and re-reads the file when it changed. A file that fails to parse is reported
+//! and ignored; the previous prices stay in effect.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use x402_axum::{PricingTable, X402Middleware};
+//!
+//! let x402 = X402Middleware::new("https://facilitator.x402.rs");
+//! let pricing = PricingTable::load("pricing.json")?;
+//!
+//! let app: Router = Router::new()
+//!     .route("/api/reports/{id}", get(report))
+//!     .layer(x402.with_pricing_table(pricing));
+//! ```
+
+use http::{HeaderMap, Method, Uri};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+use url::Url;
+use x402_types::chain::ChainId;
+use x402_types::proto::v2;
+
+use crate::ens::{EnsError, EnsResolver, is_ens_name};
+use crate::paygate::PriceTagSource;
+use crate::rpc::eth_call;
+
+/// Default interval between checks of the pricing file for changes.
+const DEFAULT_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Errors that can occur while loading a pricing table.
+#[derive(Debug, thiserror::Error)]
+pub enum PricingTableError {
+    #[error("Failed to read pricing table {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Failed to parse pricing table: {0}")]
+    Parse(String),
+    #[error("Invalid pricing table: {0}")]
+    Invalid(String),
+    #[error("Token {token} is configured with {configured} decimals but has {on_chain} on chain")]
+    DecimalsMismatch {
+        token: String,
+        configured: u8,
+        on_chain: u8,
+    },
+    #[error("Failed to read decimals of token {token}: {reason}")]
+    DecimalsUnavailable { token: String, reason: String },
+    #[error(transparent)]
+    Ens(#[from] EnsError),
+}
+
+/// Contents of a pricing table file.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PricingTableFile {
+    /// Values applied to every price unless overridden.
+    #[serde(default)]
+    pub defaults: PricingDefaults,
+    /// Tokens that prices refer to, by name.
+    #[serde(default)]
+    pub tokens: HashMap<String, PricingToken>,
+    /// JSON-RPC endpoints by network, used to check token decimals on chain.
+    #[serde(default)]
+    pub rpc: HashMap<ChainId, Url>,
+    /// Priced routes, matched in order.
+    #[serde(default)]
+    pub routes: Vec<PricedRoute>,
+    /// Where ENS names used as `pay_to` are resolved.
+    #[serde(default)]
+    pub ens: Option<PricingEns>,
+}
+
+/// ENS resolution of `pay_to` names.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PricingEns {
+    /// JSON-RPC endpoint of a network with the ENS registry, usually Ethereum mainnet.
+    pub rpc: Url,
+    /// Seconds a resolved address is reused (default: 3600).
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+/// Defaults applied to every price in a pricing table.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PricingDefaults {
+    /// Recipient address or ENS name.
+    pub pay_to: Option<String>,
+    /// Maximum time in seconds for payment validity (default: 300).
+    pub max_timeout_seconds: Option<u64>,
+    /// Payment scheme (default: `exact`).
+    pub scheme: Option<String>,
+}
+
+/// A token that prices can be denominated in.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PricingToken {
+    /// CAIP-2 chain ID of the network the token lives on.
+    pub network: ChainId,
+    /// Token contract address.
+    pub asset: String,
+    /// Number of decimals used to convert whole-token amounts.
+    pub decimals: u8,
+    /// Scheme-specific extra data, such as the EIP-712 domain name and version.
+    #[serde(default)]
+    pub extra: Option<serde_json::Value>,
+}
+
+/// A route and the prices accepted for it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PricedRoute {
+    /// Path pattern.
+    pub path: String,
+    /// HTTP methods the route applies to. Empty means all methods.
+    #[serde(default)]
+    pub methods: Vec<String>,
+    /// Accepted payment options.
+    pub prices: Vec<RoutePrice>,
+}
+
+/// A single accepted payment option of a route.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoutePrice {
+    /// Name of the token in the `tokens` section.
+    pub token: String,
+    /// Amount in whole tokens, e.g. `"0.01"`.
+    #[serde(default)]
+    pub amount: Option<String>,
+    /// Amount in the token's smallest unit.
+    #[serde(default)]
+    pub amount_atomic: Option<String>,
+    /// Recipient address or ENS name, overriding the default.
+    #[serde(default)]
+    pub pay_to: Option<String>,
+    /// Maximum time in seconds for payment validity, overriding the default.
+    #[serde(default)]
+    pub max_timeout_seconds: Option<u64>,
+    /// Payment scheme, overriding the default.
+    #[serde(default)]
+    pub scheme: Option<String>,
+    /// Scheme-specific extra data, overriding the token's.
+    #[serde(default)]
+    pub extra: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Any,
+    Rest,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRoute {
+    segments: Vec<Segment>,
+    methods: Vec<Method>,
+    /// Price tags with the name of their token.
+    price_tags: Vec<(String, v2::PriceTag)>,
+}
+
+/// A token whose configured decimals are checked against the chain.
+#[derive(Debug, Clone)]
+struct DecimalsCheck {
+    token: String,
+    rpc: Url,
+    asset: String,
+    decimals: u8,
+}
+
+#[derive(Debug, Default)]
+struct CompiledTable {
+    routes: Vec<CompiledRoute>,
+    decimals_checks: Vec<DecimalsCheck>,
+    /// Resolver of the ENS names in `names`.
+    ens: Option<EnsResolver>,
+    /// ENS names used as `pay_to`, sorted.
+    names: Vec<String>,
+}
+
+impl CompiledRoute {
+    fn matches(&self, method: Option<&Method>, path: &str) -> bool {
+        if let Some(method) = method
+            && !self.methods.is_empty()
+            && !self.methods.contains(method)
+        {
+            return false;
+        }
+        let mut path_segments = path.split('/').filter(|s| !s.is_empty());
+        for segment in &self.segments {
+            match segment {
+                Segment::Rest => return true,
+                Segment::Any => {
+                    if path_segments.next().is_none() {
+                        return false;
+                    }
+                }
+                Segment::Literal(literal) => {
+                    if path_segments.next() != Some(literal.as_str()) {
+                        return false;
+                    }
+                }
+            }
+        }
+        path_segments.next().is_none()
+    }
+}
+
+#[derive(Debug)]
+struct LoadedTable {
+    routes: Arc<Vec<CompiledRoute>>,
+    modified: Option<SystemTime>,
+    checked_at: Instant,
+    /// Decimals checks not passed yet.
+    pending_checks: Arc<Vec<DecimalsCheck>>,
+    /// Tokens whose configured decimals do not match the chain.
+    mismatched: HashSet<String>,
+    ens: Option<EnsResolver>,
+    names: Arc<Vec<String>>,
+    /// Incremented on every reload, so checks of a replaced table are discarded.
+    generation: u64,
+}
+
+impl LoadedTable {
+    /// `price_tag` with its `pay_to` name replaced by the resolved address, or
+    /// `None` if the name has not resolved yet.
+    fn with_resolved_pay_to(&self, price_tag: &v2::PriceTag) -> Option<v2::PriceTag> {
+        let pay_to = &price_tag.requirements.pay_to;
+        if !is_ens_name(pay_to) {
+            return Some(price_tag.clone());
+        }
+        let address = self.ens.as_ref()?.cached(pay_to)?;
+        let mut price_tag = price_tag.clone();
+        price_tag.requirements.pay_to = address.to_checksum(None);
+        Some(price_tag)
+    }
+
+    fn new(table: CompiledTable, modified: Option<SystemTime>) -> Self {
+        Self {
+            routes: Arc::new(table.routes),
+            modified,
+            checked_at: Instant::now(),
+            pending_checks: Arc::new(table.decimals_checks),
+            mismatched: HashSet::new(),
+            ens: table.ens,
+            names: Arc::new(table.names),
+            generation: 0,
+        }
+    }
+
+    fn replace(&mut self, table: CompiledTable, modified: Option<SystemTime>) {
+        let generation = self.generation + 1;
+        *self = Self::new(table, modified);
+        self.generation = generation;
+    }
+}
+
+/// A [`PriceTagSource`] backed by a pricing table file.
+///
+/// Cloning is cheap; clones share the loaded table.
+#[derive(Debug, Clone)]
+pub struct PricingTable {
+    table: Arc<RwLock<LoadedTable>>,
+    path: Option<Arc<PathBuf>>,
+    reload_interval: Option<Duration>,
+}
+
+impl PricingTable {
+    /// Loads a pricing table from a file and watches it for changes.
+    ///
+    /// Files with a `.toml` extension are parsed as TOML, all others as JSON.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, PricingTableError> {
+        let path = path.as_ref().to_path_buf();
+        let (table, modified) = read_table(&path)?;
+        Ok(Self {
+            table: Arc::new(RwLock::new(LoadedTable::new(table, modified))),
+            path: Some(Arc::new(path)),
+            reload_interval: Some(DEFAULT_RELOAD_INTERVAL),
+        })
+    }
+
+    /// Like [`PricingTable::load`], then resolves the ENS names used as `pay_to`.
+    ///
+    /// Fails if a name does not resolve, so misconfigured recipients are caught at startup.
+    pub async fn load_resolved<P: AsRef<Path>>(path: P) -> Result<Self, PricingTableError> {
+        let table = Self::load(path)?;
+        table.resolve_names().await?;
+        Ok(table)
+    }
+
+    /// Builds a pricing table from already parsed file contents. The table is not reloaded.
+    pub fn from_file_contents(file: PricingTableFile) -> Result<Self, PricingTableError> {
+        let table = compile(file)?;
+        Ok(Self {
+            table: Arc::new(RwLock::new(LoadedTable::new(table, None))),
+            path: None,
+            reload_interval: None,
+        })
+    }
+
+    /// Builds a pricing table from a JSON string. The table is not reloaded.
+    pub fn from_json_str(json: &str) -> Result<Self, PricingTableError> {
+        let file =
+            serde_json::from_str(json).map_err(|e| PricingTableError::Parse(e.to_string()))?;
+        Self::from_file_contents(file)
+    }
+
+    /// Builds a pricing table from a TOML string. The table is not reloaded.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(toml: &str) -> Result<Self, PricingTableError> {
+        let file = toml::from_str(toml).map_err(|e| PricingTableError::Parse(e.to_string()))?;
+        Self::from_file_contents(file)
+    }
+
+    /// Sets how often the pricing file is checked for changes.
+    pub fn with_reload_interval(mut self, interval: Duration) -> Self {
+        self.reload_interval = Some(interval);
+        self
+    }
+
+    /// Disables checking the pricing file for changes.
+    pub fn without_hot_reload(mut self) -> Self {
+        self.reload_interval = None;
+        self
+    }
+
+    /// Re-reads the pricing file now.
+    ///
+    /// On error the previously loaded prices stay in effect. Tables not loaded
+    /// from a file are left unchanged.
+    pub fn reload(&self) -> Result<(), PricingTableError> {
+        let Some(path) = self.path.as_deref() else {
+            return Ok(());
+        };
+        let (compiled, modified) = read_table(path)?;
+        let mut table = self.table.write().expect("pricing table lock poisoned");
+        table.replace(compiled, modified);
+        Ok(())
+    }
+
+    /// Compares the configured decimals of tokens with their on-chain `decimals()`.
+    ///
+    /// Tokens that match are not checked again until the table is reloaded.
+    /// Returns the first mismatch or read failure; all tokens are checked either way.
+    pub async fn check_decimals(&self) -> Result<(), PricingTableError> {
+        let (pending, generation) = {
+            let table = self.table.read().expect("pricing table lock poisoned");
+            (table.pending_checks.clone(), table.generation)
+        };
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let client = reqwest::Client::new();
+        let mut still_pending = Vec::new();
+        let mut mismatched = Vec::new();
+        let mut first_error = None;
+        for check in pending.iter() {
+            let error = match onchain_decimals(&client, &check.rpc, &check.asset).await {
+                Ok(on_chain) if on_chain == check.decimals => continue,
+                Ok(on_chain) => {
+                    mismatched.push(check.token.clone());
+                    PricingTableError::DecimalsMismatch {
+                        token: check.token.clone(),
+                        configured: check.decimals,
+                        on_chain,
+                    }
+                }
+                Err(reason) => {
+                    still_pending.push(check.clone());
+                    PricingTableError::DecimalsUnavailable {
+                        token: check.token.clone(),
+                        reason,
+                    }
+                }
+            };
+            #[cfg(feature = "telemetry")]
+            tracing::warn!(error = %error, "Pricing table decimals check failed");
+            first_error.get_or_insert(error);
+        }
+        let mut table = self.table.write().expect("pricing table lock poisoned");
+        if table.generation == generation {
+            table.pending_checks = Arc::new(still_pending);
+            table.mismatched.extend(mismatched);
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Resolves the ENS names used as `pay_to`, skipping those resolved within the TTL.
+    ///
+    /// Returns the first name that does not resolve; all names are tried either way.
+    pub async fn resolve_names(&self) -> Result<(), PricingTableError> {
+        let (ens, names) = {
+            let table = self.table.read().expect("pricing table lock poisoned");
+            (table.ens.clone(), table.names.clone())
+        };
+        let Some(ens) = ens else {
+            return Ok(());
+        };
+        let mut first_error = None;
+        for name in names.iter() {
+            if let Err(error) = ens.resolve(name).await {
+                #[cfg(feature = "telemetry")]
+                tracing::warn!(error = %error, "Pricing table pay_to name resolution failed");
+                first_error.get_or_insert(error);
+            }
+        }
+        first_error.map_or(Ok(()), |error| Err(error.into()))
+    }
+
+    /// Returns the price tags of the first route matching `method` and `path`.
+    ///
+    /// With `method` set to `None`, method restrictions of routes are ignored.
+    ///
+    /// Price tags of tokens that failed the decimals check, and those paying to an
+    /// ENS name that has not resolved yet, are left out.
+    pub fn lookup(&self, method: Option<&Method>, path: &str) -> Vec<v2::PriceTag> {
+        self.reload_if_changed();
+        let table = self.table.read().expect("pricing table lock poisoned");
+        table
+            .routes
+            .iter()
+            .find(|route| route.matches(method, path))
+            .map(|route| {
+                route
+                    .price_tags
+                    .iter()
+                    .filter(|(token, _)| !table.mismatched.contains(token))
+                    .filter_map(|(_, price_tag)| table.with_resolved_pay_to(price_tag))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Like [`PricingTable::lookup`], running pending decimals checks and
+    /// resolving expired ENS names first.
+    async fn checked_lookup(&self, method: Option<&Method>, path: &str) -> Vec<v2::PriceTag> {
+        self.reload_if_changed();
+        let (has_pending, has_expired_names) = {
+            let table = self.table.read().expect("pricing table lock poisoned");
+            let has_expired_names = table
+                .ens
+                .as_ref()
+                .is_some_and(|ens| table.names.iter().any(|name| !ens.is_fresh(name)));
+            (!table.pending_checks.is_empty(), has_expired_names)
+        };
+        if has_pending {
+            // Failures are logged; mismatched tokens are left out by `lookup`.
+            let _ = self.check_decimals().await;
+        }
+        if has_expired_names {
+            // Failures are logged; the last resolved addresses stay in use.
+            let _ = self.resolve_names().await;
+        }
+        self.lookup(method, path)
+    }
+
+    fn reload_if_changed(&self) {
+        let (Some(path), Some(interval)) = (self.path.as_deref(), self.reload_interval) else {
+            return;
+        };
+        {
+            let table = self.table.read().expect("pricing table lock poisoned");
+            if table.checked_at.elapsed() < interval {
+                return;
+            }
+        }
+        let mut table = self.table.write().expect("pricing table lock poisoned");
+        if table.checked_at.elapsed() < interval {
+            return;
+        }
+        table.checked_at = Instant::now();
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if modified.is_none() || modified == table.modified {
+            return;
+        }
+        // Remember the new timestamp even on failure, so a broken file is reported once.
+        table.modified = modified;
+        match read_table(path) {
+            Ok((compiled, modified)) => {
+                table.replace(compiled, modified);
+                #[cfg(feature = "telemetry")]
+                tracing::info!(path = %path.display(), "Reloaded pricing table");
+            }
+            Err(_error) => {
+                #[cfg(feature = "telemetry")]
+                tracing::warn!(path = %path.display(), error = %_error, "Ignoring invalid pricing table");
+            }
+        }
+    }
+}
+
+impl PriceTagSource for PricingTable {
+    type PriceTag = v2::PriceTag;
+
+    async fn resolve(
+        &self,
+        _headers: &HeaderMap,
+        uri: &Uri,
+        _base_url: Option<&Url>,
+    ) -> Vec<Self::PriceTag> {
+        self.checked_lookup(None, uri.path()).await
+    }
+
+    async fn resolve_for_method(
+        &self,
+        method: &Method,
+        _headers: &HeaderMap,
+        uri: &Uri,
+        _base_url: Option<&Url>,
+    ) -> Vec<Self::PriceTag> {
+        self.checked_lookup(Some(method), uri.path()).await
+    }
+}
+
+fn read_table(path: &Path) -> Result<(CompiledTable, Option<SystemTime>), PricingTableError> {
+    let io_error = |source| PricingTableError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    let content = std::fs::read_to_string(path).map_err(io_error)?;
+    let is_toml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    let file: PricingTableFile = if is_toml {
+        parse_toml(&content)?
+    } else {
+        serde_json::from_str(&content).map_err(|e| PricingTableError::Parse(e.to_string()))?
+    };
+    Ok((compile(file)?, modified))
+}
+
+#[cfg(feature = "toml")]
+fn parse_toml(content: &str) -> Result<PricingTableFile, PricingTableError> {
+    toml::from_str(content).map_err(|e| PricingTableError::Parse(e.to_string()))
+}
+
+#[cfg(not(feature = "toml"))]
+fn parse_toml(_content: &str) -> Result<PricingTableFile, PricingTableError> {
+    Err(PricingTableError::Parse(
+        "TOML pricing tables require the `toml` feature".to_string(),
+    ))
+}
+
+fn compile(file: PricingTableFile) -> Result<CompiledTable, PricingTableError> {
+    let invalid = PricingTableError::Invalid;
+    let routes = file
+        .routes
+        .iter()
+        .map(|route| {
+            let segments = parse_pattern(&route.path).map_err(invalid)?;
+            let methods = route
+                .methods
+                .iter()
+                .map(|method| {
+                    Method::from_bytes(method.trim().to_uppercase().as_bytes())
+                        .map_err(|_| invalid(format!("{}: invalid method {method}", route.path)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if route.prices.is_empty() {
+                return Err(invalid(format!("{}: no prices", route.path)));
+            }
+            let price_tags = route
+                .prices
+                .iter()
+                .map(|price| {
+                    let price_tag = price_tag(&file, price)
+                        .map_err(|e| invalid(format!("{}: {e}", route.path)))?;
+                    Ok((price.token.clone(), price_tag))
+                })
+                .collect::<Result<Vec<_>, PricingTableError>>()?;
+            Ok(CompiledRoute {
+                segments,
+                methods,
+                price_tags,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut decimals_checks = file
+        .tokens
+        .iter()
+        .filter(|(_, token)| token.network.namespace == "eip155")
+        .filter_map(|(name, token)| {
+            Some(DecimalsCheck {
+                token: name.clone(),
+                rpc: file.rpc.get(&token.network)?.clone(),
+                asset: token.asset.clone(),
+                decimals: token.decimals,
+            })
+        })
+        .collect::<Vec<_>>();
+    decimals_checks.sort_by(|a, b| a.token.cmp(&b.token));
+    let mut names = routes
+        .iter()
+        .flat_map(|route| &route.price_tags)
+        .map(|(_, price_tag)| &price_tag.requirements.pay_to)
+        .filter(|pay_to| is_ens_name(pay_to))
+        .cloned()
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    let ens = match (&file.ens, names.first()) {
+        (Some(ens), _) => {
+            let resolver = EnsResolver::new(ens.rpc.clone());
+            Some(match ens.ttl_secs {
+                Some(ttl) => resolver.with_ttl(Duration::from_secs(ttl)),
+                None => resolver,
+            })
+        }
+        (None, Some(name)) => {
+            return Err(invalid(format!(
+                "pay_to {name} is an ENS name but the table has no ens section"
+            )));
+        }
+        (None, None) => None,
+    };
+    Ok(CompiledTable {
+        routes,
+        decimals_checks,
+        ens,
+        names,
+    })
+}
+
+/// Reads the `decimals()` of an ERC-20 token with an `eth_call`.
+async fn onchain_decimals(client: &reqwest::Client, rpc: &Url, asset: &str) -> Result<u8, String> {
+    const DECIMALS_SELECTOR: &str = "0x313ce567";
+    let result = eth_call(client, rpc, asset, DECIMALS_SELECTOR).await?;
+    let digits = result.trim_start_matches("0x");
+    if digits.is_empty() {
+        return Err(format!("{asset} has no decimals()"));
+    }
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u8::from_str_radix(digits, 16).map_err(|_| format!("invalid decimals() result {result}"))
+}
+
+fn price_tag(file: &PricingTableFile, price: &RoutePrice) -> Result<v2::PriceTag, String> {
+    let token = file
+        .tokens
+        .get(&price.token)
+        .ok_or_else(|| format!("unknown token {}", price.token))?;
+    let amount = match (&price.amount, &price.amount_atomic) {
+        (Some(amount), None) => parse_token_amount(amount, token.decimals)?,
+        (None, Some(atomic)) => {
+            let atomic = atomic.trim();
+            if atomic.is_empty() || !atomic.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(format!("invalid amount_atomic {atomic}"));
+            }
+            atomic.to_string()
+        }
+        _ => return Err("exactly one of amount and amount_atomic is required".to_string()),
+    };
+    let pay_to = price
+        .pay_to
+        .clone()
+        .or_else(|| file.defaults.pay_to.clone())
+        .ok_or("pay_to is required")?;
+    let requirements = v2::PaymentRequirements {
+        scheme: price
+            .scheme
+            .clone()
+            .or_else(|| file.defaults.scheme.clone())
+            .unwrap_or_else(|| "exact".to_string()),
+        network: token.network.clone(),
+        amount,
+        pay_to,
+        max_timeout_seconds: price
+            .max_timeout_seconds
+            .or(file.defaults.max_timeout_seconds)
+            .unwrap_or(300),
+        asset: token.asset.clone(),
+        extra: price.extra.clone().or_else(|| token.extra.clone()),
+    };
+    Ok(v2::PriceTag {
+        requirements,
+        enricher: None,
+    })
+}
+
+fn parse_pattern(pattern: &str) -> Result<Vec<Segment>, String> {
+    if !pattern.starts_with('/') {
+        return Err(format!("path {pattern} must start with /"));
+    }
+    let segments = pattern
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|segment| match segment {
+            "**" => Segment::Rest,
+            "*" => Segment::Any,
+            s if s.starts_with(':') || (s.starts_with('{') && s.ends_with('}')) => Segment::Any,
+            s => Segment::Literal(s.to_string()),
+        })
+        .collect::<Vec<_>>();
+    if segments
+        .iter()
+        .rev()
+        .skip(1)
+        .any(|segment| *segment == Segment::Rest)
+    {
+        return Err(format!("path {pattern}: ** is only allowed at the end"));
+    }
+    Ok(segments)
+}
+
+/// Converts a whole-token decimal amount into the token's smallest unit.
+fn parse_token_amount(amount: &str, decimals: u8) -> Result<String, String> {
+    let amount = amount.trim();
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(format!("invalid amount {amount}"));
+    }
+    if fraction.len() > decimals as usize {
+        return Err(format!(
+            "amount {amount} has more than {decimals} decimal places"
+        ));
+    }
+    let atomic = format!("{whole}{fraction:0<width$}", width = decimals as usize);
+    let atomic = atomic.trim_start_matches('0');
+    Ok(if atomic.is_empty() { "0" } else { atomic }.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = r#"{
+        "defaults": { "pay_to": "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045" },
+        "tokens": {
+            "bbt": {
+                "network": "eip155:42793",
+                "asset": "0x7EfE4bdd11237610bcFca478937658bE39F8dfd6",
+                "decimals": 18,
+                "extra": { "name": "BBT", "version": "1" }
+            }
+        },
+        "routes": [
+            {
+                "path": "/reports/{id}",
+                "methods": ["get"],
+                "prices": [{ "token": "bbt", "amount": "0.01" }]
+            },
+            {
+                "path": "/premium/**",
+                "prices": [{ "token": "bbt", "amount_atomic": "5", "max_timeout_seconds": 60 }]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_token_amount() {
+        assert_eq!(parse_token_amount("0.01", 18).unwrap(), "10000000000000000");
+        assert_eq!(parse_token_amount("12", 6).unwrap(), "12000000");
+        assert_eq!(parse_token_amount("1.5", 1).unwrap(), "15");
+        assert_eq!(parse_token_amount("0", 6).unwrap(), "0");
+        assert!(parse_token_amount("0.001", 2).is_err());
+        assert!(parse_token_amount("1e3", 6).is_err());
+        assert!(parse_token_amount(".", 6).is_err());
+    }
+
+    #[test]
+    fn test_lookup_matches_path_and_method() {
+        let table = PricingTable::from_json_str(TABLE).unwrap();
+
+        let tags = table.lookup(Some(&Method::GET), "/reports/42");
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].requirements.amount, "10000000000000000");
+        assert_eq!(tags[0].requirements.max_timeout_seconds, 300);
+        assert_eq!(tags[0].requirements.network.to_string(), "eip155:42793");
+
+        assert!(table.lookup(Some(&Method::POST), "/reports/42").is_empty());
+        assert!(
+            table
+                .lookup(Some(&Method::GET), "/reports/42/raw")
+                .is_empty()
+        );
+
+        let tags = table.lookup(Some(&Method::POST), "/premium/a/b");
+        assert_eq!(tags[0].requirements.amount, "5");
+        assert_eq!(tags[0].requirements.max_timeout_seconds, 60);
+        assert!(table.lookup(Some(&Method::GET), "/free").is_empty());
+    }
+
+    #[test]
+    fn test_reload_keeps_previous_table_on_error() {
+        let path = std::env::temp_dir().join(format!("x402-pricing-{}.json", std::process::id()));
+        std::fs::write(&path, TABLE).unwrap();
+        let table = PricingTable::load(&path).unwrap();
+        assert_eq!(table.lookup(None, "/premium/x").len(), 1);
+
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(table.reload().is_err());
+        assert_eq!(table.lookup(None, "/premium/x").len(), 1);
+
+        std::fs::write(&path, r#"{ "routes": [] }"#).unwrap();
+        table.reload().unwrap();
+        assert!(table.lookup(None, "/premium/x").is_empty());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_decimals_are_not_served() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let rpc = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": format!("0x{:064x}", 6),
+            })))
+            .mount(&rpc)
+            .await;
+        let mut file: serde_json::Value = serde_json::from_str(TABLE).unwrap();
+        file["rpc"] = serde_json::json!({ "eip155:42793": rpc.uri() });
+        let table = PricingTable::from_json_str(&file.to_string()).unwrap();
+        assert_eq!(table.lookup(None, "/premium/x").len(), 1);
+
+        let error = table.check_decimals().await.unwrap_err();
+        assert!(matches!(
+            error,
+            PricingTableError::DecimalsMismatch {
+                configured: 18,
+                on_chain: 6,
+                ..
+            }
+        ));
+        assert!(table.lookup(None, "/premium/x").is_empty());
+
+        file["tokens"]["bbt"]["decimals"] = 6.into();
+        let table = PricingTable::from_json_str(&file.to_string()).unwrap();
+        let uri: Uri = "/premium/x".parse().unwrap();
+        let tags = table.resolve(&HeaderMap::new(), &uri, None).await;
+        assert_eq!(tags.len(), 1);
+        table.check_decimals().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ens_pay_to_is_resolved() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mut file: serde_json::Value = serde_json::from_str(TABLE).unwrap();
+        file["defaults"]["pay_to"] = "merchant.eth".into();
+        let error = PricingTable::from_json_str(&file.to_string()).unwrap_err();
+        assert!(matches!(error, PricingTableError::Invalid(_)));
+
+        // Answers both the registry and the resolver call with the same address.
+        let rpc = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": "0x000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa96045",
+            })))
+            .mount(&rpc)
+            .await;
+        file["ens"] = serde_json::json!({ "rpc": rpc.uri() });
+        let table = PricingTable::from_json_str(&file.to_string()).unwrap();
+        assert!(table.lookup(None, "/premium/x").is_empty());
+
+        let uri: Uri = "/premium/x".parse().unwrap();
+        let tags = table.resolve(&HeaderMap::new(), &uri, None).await;
+        assert_eq!(
+            tags[0].requirements.pay_to,
+            "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
+        );
+        table.resolve_names().await.unwrap();
+    }
+}
@@ -0,0 +1,36 @@
+//! Minimal JSON-RPC reads against EVM nodes, for checks made while loading configuration.
+
+use std::time::Duration;
+use url::Url;
+
+/// Runs an `eth_call` of `data` on `to` at the latest block and returns the hex result.
+pub(crate) async fn eth_call(
+    client: &reqwest::Client,
+    rpc: &Url,
+    to: &str,
+    data: &str,
+) -> Result<String, String> {
+    let response: serde_json::Value = client
+        .post(rpc.clone())
+        .json(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_call",
+            "params": [{ "to": to, "data": data }, "latest"],
+        }))
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(error) = response.get("error") {
+        return Err(format!("RPC error: {error}"));
+    }
+    response
+        .get("result")
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| "RPC response has no result".to_string())
+}
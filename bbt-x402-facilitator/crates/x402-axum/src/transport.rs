@@ -0,0 +1,151 @@
+//! Alternative transports for payment payloads.
+//!
+//! When enabled with [`X402Middleware::with_payment_transports`](crate::X402Middleware::with_payment_transports),
+//! a payment sent in the query parameter or in a JSON body envelope is moved into
+//! the payment header before the paygate runs. The paygate and the protected
+//! handler only ever see the header transport.
+
+use axum_core::body::Body;
+use axum_core::extract::Request;
+use axum_core::response::{IntoResponse, Response};
+use http::{HeaderMap, HeaderValue, StatusCode, Uri, header};
+use http_body_util::{BodyExt, Limited};
+use url::form_urlencoded;
+use x402_types::proto::transport::{PAYMENT_QUERY_PARAM, PaymentEnvelope, PaymentTransport};
+
+/// Maximum request body size buffered when looking for a payment envelope.
+const MAX_ENVELOPE_BYTES: usize = 1024 * 1024;
+
+/// Moves a payment carried in the query or body into the `header_name` header.
+///
+/// Requests that already carry the header, or use no accepted transport, are
+/// returned unchanged. Returns an error response if the body cannot be read.
+pub(crate) async fn lift_payment(
+    mut req: Request,
+    transports: &[PaymentTransport],
+    header_name: &'static str,
+) -> Result<Request, Response> {
+    if transports.is_empty() || req.headers().contains_key(header_name) {
+        return Ok(req);
+    }
+
+    if transports.contains(&PaymentTransport::Query)
+        && let Some((payment, uri)) = take_query_payment(req.uri())
+    {
+        let value = HeaderValue::from_str(&payment).map_err(|_| {
+            (StatusCode::BAD_REQUEST, "Invalid payment query parameter").into_response()
+        })?;
+        *req.uri_mut() = uri;
+        req.headers_mut().insert(header_name, value);
+        return Ok(req);
+    }
+
+    if transports.contains(&PaymentTransport::Body) && is_json(req.headers()) {
+        return lift_body_payment(req, header_name).await;
+    }
+
+    Ok(req)
+}
+
+/// Removes the payment query parameter from `uri`, returning its value and the new URI.
+///
+/// Other query parameters are kept exactly as sent.
+fn take_query_payment(uri: &Uri) -> Option<(String, Uri)> {
+    let query = uri.query()?;
+    let mut payment = None;
+    let mut remaining = Vec::new();
+    for pair in query.split('&') {
+        match form_urlencoded::parse(pair.as_bytes()).next() {
+            Some((key, value)) if payment.is_none() && key == PAYMENT_QUERY_PARAM => {
+                payment = Some(value.into_owned());
+            }
+            _ => remaining.push(pair),
+        }
+    }
+    let payment = payment?;
+
+    let path_and_query = if remaining.is_empty() {
+        uri.path().to_string()
+    } else {
+        format!("{}?{}", uri.path(), remaining.join("&"))
+    };
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(path_and_query.parse().ok()?);
+    Some((payment, Uri::from_parts(parts).ok()?))
+}
+
+async fn lift_body_payment(req: Request, header_name: &'static str) -> Result<Request, Response> {
+    let (mut parts, body) = req.into_parts();
+    let bytes = Limited::new(body, MAX_ENVELOPE_BYTES)
+        .collect()
+        .await
+        .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE.into_response())?
+        .to_bytes();
+
+    let Ok(envelope) = serde_json::from_slice::<PaymentEnvelope>(&bytes) else {
+        return Ok(Request::from_parts(parts, Body::from(bytes)));
+    };
+    let value = HeaderValue::from_str(&envelope.x402_payment)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid payment envelope").into_response())?;
+    let body = if envelope.body.is_null() {
+        Vec::new()
+    } else {
+        serde_json::to_vec(&envelope.body).expect("serialization failed")
+    };
+    parts.headers.insert(header_name, value);
+    parts
+        .headers
+        .insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+    Ok(Request::from_parts(parts, Body::from(body)))
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.trim_start().starts_with("application/json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_query_payment_keeps_other_params() {
+        let uri: Uri = "/weather?city=San%20Jose&x402-payment=eyJh%2Bb%3D&units=c"
+            .parse()
+            .unwrap();
+        let (payment, uri) = take_query_payment(&uri).unwrap();
+        assert_eq!(payment, "eyJh+b=");
+        assert_eq!(uri.to_string(), "/weather?city=San%20Jose&units=c");
+
+        let uri: Uri = "/weather?x402-payment=abc".parse().unwrap();
+        let (_, uri) = take_query_payment(&uri).unwrap();
+        assert_eq!(uri.to_string(), "/weather");
+
+        let uri: Uri = "/weather?city=Lyon".parse().unwrap();
+        assert!(take_query_payment(&uri).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lift_body_payment_unwraps_envelope() {
+        let envelope = r#"{"x402Payment":"cGF5bWVudA==","body":{"q":1}}"#;
+        let req = http::Request::builder()
+            .method("POST")
+            .uri("/search")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(envelope))
+            .unwrap();
+        let req = lift_payment(
+            req,
+            &[PaymentTransport::Header, PaymentTransport::Body],
+            "Payment-Signature",
+        )
+        .await
+        .unwrap();
+        assert_eq!(req.headers()["Payment-Signature"], "cGF5bWVudA==");
+        assert_eq!(req.headers()[header::CONTENT_LENGTH], "7");
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], br#"{"q":1}"#);
+    }
+}
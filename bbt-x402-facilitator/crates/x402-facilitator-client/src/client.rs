@@ -0,0 +1,505 @@
+//! HTTP client for the payment API of a facilitator.
+
+use http::{HeaderMap, Method, StatusCode, header};
+use reqwest::Client;
+use serde::Serialize;
+use std::time::Duration;
+use url::Url;
+use x402_types::facilitator::Facilitator;
+use x402_types::proto;
+use x402_types::proto::{SettleDryRunResponse, SupportedResponse, v1};
+
+#[cfg(feature = "telemetry")]
+use tracing::instrument;
+
+use crate::retry::{RetryPolicy, retry_after};
+
+/// Errors that can occur while calling a facilitator.
+#[derive(Debug, thiserror::Error)]
+pub enum FacilitatorClientError {
+    #[error("URL parse error: {context}: {source}")]
+    UrlParse {
+        context: &'static str,
+        #[source]
+        source: url::ParseError,
+    },
+    #[error("HTTP error: {context}: {source}")]
+    Http {
+        context: &'static str,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("Failed to deserialize JSON: {context}: {source}")]
+    JsonDeserialization {
+        context: &'static str,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("Unexpected HTTP status {status}: {context}: {body}")]
+    HttpStatus {
+        context: &'static str,
+        status: StatusCode,
+        body: String,
+        /// Delay the facilitator asked for in a `Retry-After` header.
+        retry_after: Option<Duration>,
+    },
+    #[error("Failed to read response body as text: {context}: {source}")]
+    ResponseBodyRead {
+        context: &'static str,
+        #[source]
+        source: reqwest::Error,
+    },
+}
+
+/// A client for the payment endpoints of a facilitator.
+///
+/// Failed calls are retried according to a [`RetryPolicy`]. With the `telemetry`
+/// feature, every call runs in its own span, and the current trace context is
+/// sent along with the globally registered OpenTelemetry propagator.
+#[derive(Clone, Debug)]
+pub struct FacilitatorClient {
+    /// Base URL of the facilitator (e.g. `https://facilitator.example/`)
+    base_url: Url,
+    /// Optional API key, sent as a bearer token
+    api_key: Option<String>,
+    /// Custom headers sent with each request
+    headers: HeaderMap,
+    /// Shared Reqwest HTTP client
+    client: Client,
+    /// Optional request timeout, per attempt
+    timeout: Option<Duration>,
+    /// How failed calls are retried
+    retry_policy: RetryPolicy,
+}
+
+impl FacilitatorClient {
+    /// Constructs a new [`FacilitatorClient`] from the facilitator base URL.
+    pub fn new(base_url: Url) -> Self {
+        Self {
+            base_url,
+            api_key: None,
+            headers: HeaderMap::new(),
+            client: Client::new(),
+            timeout: None,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Like [`FacilitatorClient::new`], parsing the base URL.
+    pub fn try_new(base_url: &str) -> Result<Self, FacilitatorClientError> {
+        let base_url = Url::parse(base_url).map_err(|e| FacilitatorClientError::UrlParse {
+            context: "Invalid facilitator base URL",
+            source: e,
+        })?;
+        Ok(Self::new(base_url))
+    }
+
+    /// Returns the base URL used by this client.
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Returns the retry policy used by this client.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Sends `api_key` as a bearer token, for facilitators guarding their
+    /// endpoints with `API_KEYS`.
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Attaches custom headers to all future requests.
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Uses `client` for all future requests, e.g. to share a connection pool.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Sets a timeout for each attempt of all future requests.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how failed calls are retried. Defaults to [`RetryPolicy::default`].
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// `POST /verify`: checks a payment against its requirements.
+    ///
+    /// `request` is a [`proto::VerifyRequest`] or a scheme-specific request such as
+    /// `x402_types::proto::v2::VerifyRequest`. A payment the facilitator rejects is
+    /// returned as [`v1::VerifyResponse::Invalid`], which V2 shares.
+    #[cfg_attr(
+        feature = "telemetry",
+        instrument(name = "x402.facilitator_client.verify", skip_all, err)
+    )]
+    pub async fn verify<T>(&self, request: &T) -> Result<v1::VerifyResponse, FacilitatorClientError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let body = serde_json::to_value(request).expect("VerifyRequest serialization failed");
+        self.call(
+            Method::POST,
+            "verify",
+            "POST /verify",
+            Some(&body),
+            &[StatusCode::BAD_REQUEST],
+            true,
+        )
+        .await
+    }
+
+    /// `POST /settle`: settles a verified payment.
+    ///
+    /// A settlement the facilitator rejects is returned as [`v1::SettleResponse::Error`].
+    /// Only failures the facilitator cannot have acted on are retried, see
+    /// [`RetryPolicy`].
+    #[cfg_attr(
+        feature = "telemetry",
+        instrument(name = "x402.facilitator_client.settle", skip_all, err)
+    )]
+    pub async fn settle<T>(&self, request: &T) -> Result<v1::SettleResponse, FacilitatorClientError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let body = serde_json::to_value(request).expect("SettleRequest serialization failed");
+        self.call(
+            Method::POST,
+            "settle",
+            "POST /settle",
+            Some(&body),
+            &[StatusCode::BAD_REQUEST],
+            false,
+        )
+        .await
+    }
+
+    /// `POST /settle?dryRun=true`: returns the transactions a settlement would send,
+    /// without broadcasting them.
+    #[cfg_attr(
+        feature = "telemetry",
+        instrument(name = "x402.facilitator_client.settle_dry_run", skip_all, err)
+    )]
+    pub async fn settle_dry_run<T>(
+        &self,
+        request: &T,
+    ) -> Result<SettleDryRunResponse, FacilitatorClientError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let body = serde_json::to_value(request).expect("SettleRequest serialization failed");
+        self.call(
+            Method::POST,
+            "settle?dryRun=true",
+            "POST /settle?dryRun=true",
+            Some(&body),
+            &[],
+            true,
+        )
+        .await
+    }
+
+    /// `GET /supported`: lists the payment kinds, extensions and signers of the facilitator.
+    #[cfg_attr(
+        feature = "telemetry",
+        instrument(name = "x402.facilitator_client.supported", skip_all, err)
+    )]
+    pub async fn supported(&self) -> Result<SupportedResponse, FacilitatorClientError> {
+        self.call(Method::GET, "supported", "GET /supported", None, &[], true)
+            .await
+    }
+
+    /// `GET /health`: checks the facilitator is up, answering like `/supported`.
+    #[cfg_attr(
+        feature = "telemetry",
+        instrument(name = "x402.facilitator_client.health", skip_all, err)
+    )]
+    pub async fn health(&self) -> Result<SupportedResponse, FacilitatorClientError> {
+        self.call(Method::GET, "health", "GET /health", None, &[], true)
+            .await
+    }
+
+    /// Sends a request to `path`, relative to the base URL, until it succeeds or the
+    /// retry policy gives up, and deserializes the JSON response.
+    ///
+    /// Responses with a status other than `200 OK` or one of `accepted` are errors.
+    /// `context` is a human-readable identifier used in error messages (e.g. `"POST /verify"`).
+    async fn call<R>(
+        &self,
+        method: Method,
+        path: &str,
+        context: &'static str,
+        body: Option<&serde_json::Value>,
+        accepted: &[StatusCode],
+        idempotent: bool,
+    ) -> Result<R, FacilitatorClientError>
+    where
+        R: serde::de::DeserializeOwned,
+    {
+        let url = self
+            .base_url
+            .join(path)
+            .map_err(|e| FacilitatorClientError::UrlParse { context, source: e })?;
+        let mut attempt = 1;
+        loop {
+            let result = self
+                .call_once(method.clone(), url.clone(), context, body, accepted)
+                .await;
+            match result {
+                Err(e) if self.retry_policy.should_retry(&e, attempt, idempotent) => {
+                    let retry_after = match &e {
+                        FacilitatorClientError::HttpStatus { retry_after, .. } => *retry_after,
+                        _ => None,
+                    };
+                    let backoff = self.retry_policy.backoff(attempt, retry_after);
+                    #[cfg(feature = "telemetry")]
+                    tracing::warn!(error = %e, attempt, ?backoff, "Request to facilitator failed, retrying");
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Makes a single attempt of [`FacilitatorClient::call`].
+    async fn call_once<R>(
+        &self,
+        method: Method,
+        url: Url,
+        context: &'static str,
+        body: Option<&serde_json::Value>,
+        accepted: &[StatusCode],
+    ) -> Result<R, FacilitatorClientError>
+    where
+        R: serde::de::DeserializeOwned,
+    {
+        #[allow(unused_mut)] // For when telemetry is disabled
+        let mut headers = self.headers.clone();
+        #[cfg(feature = "telemetry")]
+        inject_trace_context(&mut headers);
+        let mut req = self.client.request(method, url).headers(headers);
+        if let Some(api_key) = &self.api_key {
+            req = req.header(header::AUTHORIZATION, format!("Bearer {api_key}"));
+        }
+        if let Some(body) = body {
+            req = req.json(body);
+        }
+        if let Some(timeout) = self.timeout {
+            req = req.timeout(timeout);
+        }
+        let http_response = req
+            .send()
+            .await
+            .map_err(|e| FacilitatorClientError::Http { context, source: e })?;
+
+        let status = http_response.status();
+        if status == StatusCode::OK || accepted.contains(&status) {
+            http_response
+                .json::<R>()
+                .await
+                .map_err(|e| FacilitatorClientError::JsonDeserialization { context, source: e })
+        } else {
+            let retry_after = retry_after(http_response.headers());
+            let body = http_response
+                .text()
+                .await
+                .map_err(|e| FacilitatorClientError::ResponseBodyRead { context, source: e })?;
+            Err(FacilitatorClientError::HttpStatus {
+                context,
+                status,
+                body,
+                retry_after,
+            })
+        }
+    }
+}
+
+impl Facilitator for FacilitatorClient {
+    type Error = FacilitatorClientError;
+
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, FacilitatorClientError> {
+        FacilitatorClient::verify(self, request)
+            .await
+            .map(Into::into)
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, FacilitatorClientError> {
+        FacilitatorClient::settle(self, request)
+            .await
+            .map(Into::into)
+    }
+
+    async fn settle_dry_run(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<SettleDryRunResponse, FacilitatorClientError> {
+        FacilitatorClient::settle_dry_run(self, request).await
+    }
+
+    async fn supported(&self) -> Result<SupportedResponse, FacilitatorClientError> {
+        FacilitatorClient::supported(self).await
+    }
+}
+
+/// Adds the context of the current span to `headers`, with the globally registered
+/// OpenTelemetry propagator (e.g. W3C `traceparent`). Nothing is added while no
+/// propagator is registered.
+#[cfg(feature = "telemetry")]
+fn inject_trace_context(headers: &mut HeaderMap) {
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+    impl opentelemetry::propagation::Injector for HeaderInjector<'_> {
+        fn set(&mut self, key: &str, value: String) {
+            if let (Ok(name), Ok(value)) = (
+                header::HeaderName::from_bytes(key.as_bytes()),
+                header::HeaderValue::from_str(&value),
+            ) {
+                self.0.insert(name, value);
+            }
+        }
+    }
+
+    let context = tracing::Span::current().context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(headers))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn settle_request() -> serde_json::Value {
+        serde_json::json!({
+            "x402Version": 2,
+            "paymentPayload": {},
+            "paymentRequirements": {}
+        })
+    }
+
+    #[tokio::test]
+    async fn test_facilitator_client() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/verify"))
+            .and(header("authorization", "Bearer secret"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "isValid": false,
+                "invalidReason": "insufficient_funds",
+                "invalidReasonDetails": "",
+                "payer": ""
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/settle"))
+            .and(query_param("dryRun", "true"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "transactions": []
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/settle"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "success": true,
+                "payer": "0xB",
+                "transaction": "0xabc",
+                "network": "eip155:42793",
+                "blockNumber": 7
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "kinds": [{ "x402Version": 2, "scheme": "exact", "network": "eip155:42793" }],
+                "signers": {}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = FacilitatorClient::try_new(&mock_server.uri())
+            .unwrap()
+            .with_api_key("secret");
+        let verified = client.verify(&settle_request()).await.unwrap();
+        assert!(matches!(
+            verified,
+            v1::VerifyResponse::Invalid { ref reason, .. } if reason == "insufficient_funds"
+        ));
+        let settled = client.settle(&settle_request()).await.unwrap();
+        assert!(matches!(
+            settled,
+            v1::SettleResponse::Success { ref transaction, ref receipt, .. }
+                if transaction == "0xabc" && receipt.block_number == Some(7)
+        ));
+        let dry_run = client.settle_dry_run(&settle_request()).await.unwrap();
+        assert_eq!(dry_run.0["transactions"], serde_json::json!([]));
+        let health = client.health().await.unwrap();
+        assert_eq!(health.kinds[0].scheme, "exact");
+    }
+
+    #[tokio::test]
+    async fn test_facilitator_client_retries() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/supported"))
+            .respond_with(ResponseTemplate::new(502))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/supported"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "kinds": [],
+                "signers": {}
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/settle"))
+            .respond_with(ResponseTemplate::new(502))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = FacilitatorClient::try_new(&mock_server.uri())
+            .unwrap()
+            .with_retry_policy(RetryPolicy {
+                initial_backoff: Duration::from_millis(1),
+                ..RetryPolicy::default()
+            });
+        assert!(client.supported().await.unwrap().kinds.is_empty());
+        // A settlement may have been broadcast before the gateway failed.
+        let err = client.settle(&settle_request()).await.err().unwrap();
+        assert!(matches!(
+            err,
+            FacilitatorClientError::HttpStatus {
+                status: StatusCode::BAD_GATEWAY,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,54 @@
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+
+//! Typed client for the payment API of an x402 facilitator.
+//!
+//! Resource servers that verify and settle payments themselves, outside of the
+//! `x402-axum` middleware, call the facilitator through this crate instead of
+//! hand-rolling HTTP requests: requests and responses are the `x402-types`
+//! protocol types, transient failures are retried, and with the `telemetry`
+//! feature the caller's trace continues into the facilitator.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use x402_facilitator_client::{FacilitatorClient, RetryPolicy};
+//! use x402_types::proto::{VerifyRequest, v1};
+//!
+//! # async fn run(request: VerifyRequest) -> Result<(), x402_facilitator_client::FacilitatorClientError> {
+//! let facilitator = FacilitatorClient::try_new("https://facilitator.example/")?
+//!     .with_api_key("verify-key")
+//!     .with_retry_policy(RetryPolicy::default());
+//! match facilitator.verify(&request).await? {
+//!     v1::VerifyResponse::Valid { payer } => {
+//!         if let v1::SettleResponse::Success { transaction, .. } = facilitator.settle(&request).await? {
+//!             println!("{payer} paid in {transaction}");
+//!         }
+//!     }
+//!     v1::VerifyResponse::Invalid { reason, .. } => println!("rejected: {reason}"),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Endpoints
+//!
+//! | Method | Endpoint |
+//! |--------|----------|
+//! | [`FacilitatorClient::verify`] | `POST /verify` |
+//! | [`FacilitatorClient::settle`] | `POST /settle` |
+//! | [`FacilitatorClient::settle_dry_run`] | `POST /settle?dryRun=true` |
+//! | [`FacilitatorClient::supported`] | `GET /supported` |
+//! | [`FacilitatorClient::health`] | `GET /health` |
+//!
+//! [`FacilitatorClient`] also implements [`x402_types::facilitator::Facilitator`].
+//!
+//! ## Feature Flags
+//!
+//! - `telemetry` - Tracing spans per call, and trace context propagation through the
+//!   globally registered OpenTelemetry propagator
+
+pub mod client;
+pub mod retry;
+
+pub use client::{FacilitatorClient, FacilitatorClientError};
+pub use retry::RetryPolicy;
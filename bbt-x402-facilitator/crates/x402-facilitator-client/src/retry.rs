@@ -0,0 +1,133 @@
+//! Retry policy for calls to the facilitator.
+//!
+//! `/verify`, `/supported` and `/health` have no side effects and are retried on
+//! any transient failure. `/settle` may broadcast a transaction, so it is only
+//! retried when the facilitator cannot have acted on it: the connection failed, or
+//! the facilitator answered `429 Too Many Requests` or `503 Service Unavailable`.
+
+use http::{HeaderMap, StatusCode, header};
+use std::time::Duration;
+
+use crate::client::FacilitatorClientError;
+
+/// How a [`FacilitatorClient`](crate::FacilitatorClient) retries failed calls.
+///
+/// Delays double from `initial_backoff` up to `max_backoff`. A `Retry-After`
+/// header, in seconds, sets the delay instead, up to `max_backoff`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per call, the first one included. `1` disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+    /// Longest delay between two attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, 200 milliseconds apart, then 400.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy making a single attempt per call.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before attempt `attempt + 1`, after `attempt` failed ones.
+    pub fn backoff(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)));
+        retry_after.unwrap_or(exponential).min(self.max_backoff)
+    }
+
+    /// Whether a call that failed with `error` on attempt `attempt` (counted from 1)
+    /// is tried again. `idempotent` calls are retried on every transient failure.
+    pub fn should_retry(
+        &self,
+        error: &FacilitatorClientError,
+        attempt: u32,
+        idempotent: bool,
+    ) -> bool {
+        attempt < self.max_attempts && is_transient(error, idempotent)
+    }
+}
+
+/// Whether `error` may go away on a later attempt, without the facilitator having
+/// acted on the failed one unless the call is `idempotent`.
+fn is_transient(error: &FacilitatorClientError, idempotent: bool) -> bool {
+    match error {
+        FacilitatorClientError::Http { source, .. } => {
+            source.is_connect() || (idempotent && (source.is_timeout() || source.is_request()))
+        }
+        FacilitatorClientError::HttpStatus { status, .. } => match *status {
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => true,
+            StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::GATEWAY_TIMEOUT => idempotent,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// The delay a `Retry-After` header asks for, when given in seconds.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers.get(header::RETRY_AFTER)?.to_str().ok()?;
+    seconds.trim().parse().ok().map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_error(status: StatusCode) -> FacilitatorClientError {
+        FacilitatorClientError::HttpStatus {
+            context: "POST /settle",
+            status,
+            body: String::new(),
+            retry_after: None,
+        }
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(1, None), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2, None), Duration::from_millis(400));
+        assert_eq!(policy.backoff(10, None), Duration::from_secs(5));
+        assert_eq!(
+            policy.backoff(1, Some(Duration::from_secs(2))),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            policy.backoff(1, Some(Duration::from_secs(60))),
+            Duration::from_secs(5)
+        );
+
+        let unavailable = status_error(StatusCode::SERVICE_UNAVAILABLE);
+        assert!(policy.should_retry(&unavailable, 1, false));
+        assert!(policy.should_retry(&unavailable, 2, false));
+        assert!(!policy.should_retry(&unavailable, 3, false));
+        let bad_gateway = status_error(StatusCode::BAD_GATEWAY);
+        assert!(policy.should_retry(&bad_gateway, 1, true));
+        assert!(!policy.should_retry(&bad_gateway, 1, false));
+        assert!(!policy.should_retry(&status_error(StatusCode::UNAUTHORIZED), 1, true));
+        assert!(!RetryPolicy::none().should_retry(&unavailable, 1, true));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RETRY_AFTER, "3".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(3)));
+    }
+}
@@ -0,0 +1,53 @@
+//! Generates the gRPC service of the `grpc` feature.
+//!
+//! The messages of `proto/facilitator.proto` are written out in `src/grpc.rs`, so
+//! building does not need `protoc`. Only the service glue is generated here.
+
+fn main() {
+    #[cfg(feature = "grpc")]
+    grpc::compile();
+}
+
+#[cfg(feature = "grpc")]
+mod grpc {
+    use tonic_build::manual::{Builder, Method, MethodBuilder, Service};
+
+    fn method(name: &str, route_name: &str, input: &str, output: &str) -> MethodBuilder {
+        Method::builder()
+            .name(name)
+            .route_name(route_name)
+            .input_type(format!("crate::grpc::pb::{input}"))
+            .output_type(format!("crate::grpc::pb::{output}"))
+            .codec_path("tonic_prost::ProstCodec")
+    }
+
+    pub fn compile() {
+        println!("cargo:rerun-if-changed=build.rs");
+        let service = Service::builder()
+            .name("Facilitator")
+            .package("x402.facilitator.v1")
+            .method(method("verify", "Verify", "VerifyRequest", "VerifyResponse").build())
+            .method(method("settle", "Settle", "SettleRequest", "SettleResponse").build())
+            .method(
+                method(
+                    "supported",
+                    "Supported",
+                    "SupportedRequest",
+                    "SupportedResponse",
+                )
+                .build(),
+            )
+            .method(
+                method(
+                    "settlement_events",
+                    "SettlementEvents",
+                    "SettlementEventsRequest",
+                    "SettlementEvent",
+                )
+                .server_streaming()
+                .build(),
+            )
+            .build();
+        Builder::new().build_client(false).compile(&[service]);
+    }
+}
@@ -0,0 +1,348 @@
+//! API key authentication for facilitator endpoints.
+//!
+//! When API keys are configured, `POST /verify`, `POST /settle` and
+//! `POST /settlements/verify` require a key in the `Authorization: Bearer` header
+//! (or `X-API-Key`). Each key carries a scope: `verify` keys may call everything
+//! but `/settle`, `settle` keys may call all three. The
+//! `/admin` endpoints require an `admin` key, whatever the method, and the
+//! `GET /events` feed, `GET /subscriptions/{id}` and `POST /debug/decode` a `verify`
+//! key. The settlement ledger, `GET /settlements`, and `POST /refund` require an
+//! `admin` key. Registering with the resource catalog, `POST /discovery/resources`,
+//! requires a `settle` key. Discovery endpoints (`/supported`, `/health`,
+//! `GET /discovery/resources`, ...) stay public.
+//!
+//! The identity of the calling key is recorded in the compliance audit log, so
+//! every check can be traced back to the resource server that requested it.
+//! Only the key id is logged, never the key itself.
+//!
+//! # Configuration
+//!
+//! Keys are read from the `api_keys` section of the configuration file (see
+//! [`x402_types::config::ApiKeyConfig`]) and from the `API_KEYS` environment
+//! variable. The latter holds a comma-separated list of `id:key:scope` entries:
+//!
+//! ```text
+//! API_KEYS=shop-backend:s3cr3t:settle,pricing-probe:0th3r:verify
+//! ```
+//!
+//! If the scope is omitted, the key gets the `verify` scope.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use std::sync::Arc;
+//! use x402_facilitator_local::{ApiKeyAuth, handlers};
+//!
+//! let routes = match ApiKeyAuth::from_env()? {
+//!     Some(auth) => handlers::authenticated_routes(handlers::routes(), Arc::new(auth)),
+//!     None => handlers::routes(),
+//! };
+//! ```
+
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+
+use alloy_primitives::{B256, keccak256};
+use axum::Json;
+use axum::extract::{Request, State};
+use axum::http::{Method, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde_json::json;
+use x402_types::config::{ApiKeyConfig, ApiKeyScope};
+
+use crate::rate_limit::api_key_from_headers;
+
+tokio::task_local! {
+    static CURRENT_API_KEY: ApiKeyIdentity;
+}
+
+/// The identity of an authenticated API key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiKeyIdentity {
+    /// Identifier of the key holder.
+    pub id: String,
+    /// Permission granted to the key.
+    pub scope: ApiKeyScope,
+}
+
+impl ApiKeyIdentity {
+    /// Returns `true` if the key may call an endpoint requiring `required`.
+    pub fn allows(&self, required: ApiKeyScope) -> bool {
+        self.scope >= required
+    }
+}
+
+/// Set of API keys accepted by the facilitator.
+///
+/// Keys are stored as keccak256 hashes, so lookups do not compare secrets directly.
+#[derive(Clone, Debug, Default)]
+pub struct ApiKeyAuth {
+    keys: HashMap<B256, ApiKeyIdentity>,
+}
+
+impl ApiKeyAuth {
+    /// Creates an empty key set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a key. A later key with the same secret replaces the earlier one.
+    pub fn insert(&mut self, id: impl Into<String>, key: &str, scope: ApiKeyScope) {
+        let identity = ApiKeyIdentity {
+            id: id.into(),
+            scope,
+        };
+        self.keys.insert(keccak256(key.as_bytes()), identity);
+    }
+
+    /// Returns the number of configured keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if no keys are configured.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Builds the key set from configuration entries and the `API_KEYS` environment variable.
+    ///
+    /// Returns `None` if no keys are configured, meaning authentication is disabled.
+    pub fn from_config_and_env(configured: &[ApiKeyConfig]) -> Result<Option<Self>, String> {
+        let mut auth = Self::new();
+        for entry in configured {
+            if entry.id.trim().is_empty() || entry.key.trim().is_empty() {
+                return Err("api_keys entries require a non-empty id and key".to_string());
+            }
+            auth.insert(entry.id.trim(), entry.key.trim(), entry.scope);
+        }
+        for (id, key, scope) in parse_api_keys(&env::var("API_KEYS").unwrap_or_default())? {
+            auth.insert(id, &key, scope);
+        }
+        Ok((!auth.is_empty()).then_some(auth))
+    }
+
+    /// Builds the key set from the `API_KEYS` environment variable.
+    ///
+    /// Returns `None` if the variable is unset or empty.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        Self::from_config_and_env(&[])
+    }
+
+    /// Looks up the identity of a presented key.
+    pub fn identify(&self, key: &str) -> Option<&ApiKeyIdentity> {
+        self.keys.get(&keccak256(key.as_bytes()))
+    }
+}
+
+/// Returns the identity of the API key that authenticated the current request.
+///
+/// Available inside handlers and facilitator calls guarded by [`require_api_key`].
+pub fn current_api_key() -> Option<ApiKeyIdentity> {
+    CURRENT_API_KEY.try_with(Clone::clone).ok()
+}
+
+/// Runs `future` with `identity` as the [`current_api_key`], for callers outside of Axum.
+#[cfg(feature = "grpc")]
+pub(crate) async fn with_api_key<F: Future>(
+    identity: Option<ApiKeyIdentity>,
+    future: F,
+) -> F::Output {
+    match identity {
+        Some(identity) => CURRENT_API_KEY.scope(identity, future).await,
+        None => future.await,
+    }
+}
+
+/// Axum middleware requiring a valid API key on `POST /verify`, `POST /settle` and `/admin`.
+///
+/// Missing or unknown keys are rejected with `401 Unauthorized`, keys lacking the
+/// required scope with `403 Forbidden`. The key identity is inserted into the
+/// request extensions and made available through [`current_api_key`].
+///
+/// Use with [`axum::middleware::from_fn_with_state`].
+pub async fn require_api_key(
+    State(auth): State<Arc<ApiKeyAuth>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(required) = required_scope(request.method(), request.uri().path()) else {
+        return next.run(request).await;
+    };
+    let Some(identity) = api_key_from_headers(request.headers())
+        .and_then(|key| auth.identify(key))
+        .cloned()
+    else {
+        return auth_error(
+            StatusCode::UNAUTHORIZED,
+            "unauthorized",
+            "missing or invalid API key",
+        );
+    };
+    if !identity.allows(required) {
+        #[cfg(feature = "telemetry")]
+        tracing::warn!(api_key_id = %identity.id, scope = %identity.scope, required = %required, "API key scope insufficient");
+        return auth_error(
+            StatusCode::FORBIDDEN,
+            "forbidden",
+            "API key scope does not permit this endpoint",
+        );
+    }
+
+    #[cfg(feature = "telemetry")]
+    tracing::debug!(api_key_id = %identity.id, "API key authenticated");
+    request.extensions_mut().insert(identity.clone());
+    CURRENT_API_KEY.scope(identity, next.run(request)).await
+}
+
+fn required_scope(method: &Method, path: &str) -> Option<ApiKeyScope> {
+    if path.starts_with("/admin/") {
+        return Some(ApiKeyScope::Admin);
+    }
+    if path == "/events"
+        || path.starts_with("/subscriptions/")
+        || path.starts_with("/debug/")
+        || path.starts_with("/permit2/")
+        || path == "/validate-signature"
+    {
+        return Some(ApiKeyScope::Verify);
+    }
+    if method == Method::GET && path == "/settlements" {
+        return Some(ApiKeyScope::Admin);
+    }
+    if method != Method::POST {
+        return None;
+    }
+    match path {
+        "/verify" | "/settlements/verify" => Some(ApiKeyScope::Verify),
+        "/settle" | "/discovery/resources" => Some(ApiKeyScope::Settle),
+        "/refund" => Some(ApiKeyScope::Admin),
+        _ => None,
+    }
+}
+
+fn auth_error(status: StatusCode, error: &'static str, details: &'static str) -> Response {
+    let mut response = (
+        status,
+        Json(json!({
+            "error": error,
+            "details": details,
+        })),
+    )
+        .into_response();
+    if status == StatusCode::UNAUTHORIZED {
+        response.headers_mut().insert(
+            header::WWW_AUTHENTICATE,
+            header::HeaderValue::from_static("Bearer"),
+        );
+    }
+    response
+}
+
+fn parse_api_keys(raw: &str) -> Result<Vec<(String, String, ApiKeyScope)>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let mut parts = entry.splitn(3, ':').map(str::trim);
+            let id = parts.next().unwrap_or_default();
+            let key = parts.next().unwrap_or_default();
+            if id.is_empty() || key.is_empty() {
+                return Err("API_KEYS entries must have the form id:key[:scope]".to_string());
+            }
+            let scope = match parts.next() {
+                Some(scope) => scope
+                    .parse::<ApiKeyScope>()
+                    .map_err(|e| format!("API_KEYS entry {id}: {e}"))?,
+                None => ApiKeyScope::Verify,
+            };
+            Ok((id.to_string(), key.to_string(), scope))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_api_keys() {
+        let keys = parse_api_keys("shop:abc:settle, probe:def ,").unwrap();
+        assert_eq!(
+            keys,
+            vec![
+                ("shop".to_string(), "abc".to_string(), ApiKeyScope::Settle),
+                ("probe".to_string(), "def".to_string(), ApiKeyScope::Verify),
+            ]
+        );
+        assert!(parse_api_keys("shop").is_err());
+        assert!(parse_api_keys("shop:abc:root").is_err());
+    }
+
+    #[test]
+    fn test_scopes() {
+        let mut auth = ApiKeyAuth::new();
+        auth.insert("shop", "abc", ApiKeyScope::Settle);
+        auth.insert("probe", "def", ApiKeyScope::Verify);
+
+        let shop = auth.identify("abc").unwrap();
+        assert_eq!(shop.id, "shop");
+        assert!(shop.allows(ApiKeyScope::Verify));
+        assert!(shop.allows(ApiKeyScope::Settle));
+
+        let probe = auth.identify("def").unwrap();
+        assert!(probe.allows(ApiKeyScope::Verify));
+        assert!(!probe.allows(ApiKeyScope::Settle));
+
+        assert!(auth.identify("xyz").is_none());
+        assert_eq!(
+            required_scope(&Method::POST, "/settle"),
+            Some(ApiKeyScope::Settle)
+        );
+        assert_eq!(required_scope(&Method::GET, "/settle"), None);
+        assert_eq!(
+            required_scope(&Method::POST, "/discovery/resources"),
+            Some(ApiKeyScope::Settle)
+        );
+        assert_eq!(required_scope(&Method::GET, "/discovery/resources"), None);
+        assert_eq!(
+            required_scope(&Method::POST, "/settlements/verify"),
+            Some(ApiKeyScope::Verify)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/admin/dlq"),
+            Some(ApiKeyScope::Admin)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/events"),
+            Some(ApiKeyScope::Verify)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/subscriptions/0x01"),
+            Some(ApiKeyScope::Verify)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/debug/decode"),
+            Some(ApiKeyScope::Verify)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/permit2/nonce"),
+            Some(ApiKeyScope::Verify)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/validate-signature"),
+            Some(ApiKeyScope::Verify)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/settlements"),
+            Some(ApiKeyScope::Admin)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/refund"),
+            Some(ApiKeyScope::Admin)
+        );
+        assert!(!shop.allows(ApiKeyScope::Admin));
+    }
+}
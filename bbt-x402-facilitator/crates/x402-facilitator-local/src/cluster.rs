@@ -0,0 +1,480 @@
+//! Leader election between facilitator replicas.
+//!
+//! Several replicas can serve `/verify` and `/settle` behind a load balancer, but
+//! some background jobs (sweeps, reconciliation, list refreshes) must only run
+//! once cluster-wide. A [`Cluster`] elects one leader through a lease held in a
+//! [`LeaseStore`] shared by all replicas: the leader renews the lease before it
+//! expires, and any other replica takes it over once it did expire, for instance
+//! after the leader crashed.
+//!
+//! Jobs registered with [`Scheduler::every_on_leader`](crate::util::Scheduler::every_on_leader)
+//! only run on the replica that currently holds the lease. A leader that cannot
+//! reach the store stops considering itself leader once its lease expires, so two
+//! replicas never run a singleton job at the same time as long as their clocks
+//! agree within the lease TTL.
+//!
+//! # Configuration
+//!
+//! | Variable | Description |
+//! |----------|-------------|
+//! | `CLUSTER_LEASE_PATH` | Directory shared by all replicas (e.g. a network volume) holding the lease; enables leader election |
+//! | `CLUSTER_NODE_ID` | Name of this replica in the lease (default: `$HOSTNAME` and the process id) |
+//! | `CLUSTER_LEASE_TTL_SECS` | How long a lease is valid without renewal (default: `30`) |
+//!
+//! Without `CLUSTER_LEASE_PATH`, the facilitator runs standalone and is always
+//! the leader. Other stores, such as a database row, plug in by implementing
+//! [`LeaseStore`].
+//!
+//! # Status
+//!
+//! [`handlers::cluster_routes`](crate::handlers::cluster_routes) serves the
+//! leadership of this replica under `GET /health/cluster`, and
+//! [`handlers::cluster_admin_routes`](crate::handlers::cluster_admin_routes) lets an
+//! operator make the leader step down, e.g. before draining it.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use x402_types::timestamp::UnixTimestamp;
+
+/// Name of the lease deciding which replica runs singleton jobs.
+pub const LEADER_LEASE: &str = "leader";
+
+const DEFAULT_LEASE_TTL: Duration = Duration::from_secs(30);
+
+/// A lock file older than this is left over from a crashed replica.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(10);
+
+/// A lease on a name, held by one replica until it expires.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lease {
+    pub holder: String,
+    pub expires_at: UnixTimestamp,
+}
+
+impl Lease {
+    fn is_live(&self, now: UnixTimestamp) -> bool {
+        self.expires_at > now
+    }
+}
+
+/// Where replicas keep their leases.
+///
+/// Both operations must be atomic with respect to the other replicas sharing the
+/// store: two replicas must never both succeed in acquiring a live lease.
+pub trait LeaseStore: Send + Sync + std::fmt::Debug {
+    /// Acquires or renews the lease `name` for `holder` until `ttl` from now.
+    ///
+    /// Succeeds only if the lease is free, expired or already held by `holder`.
+    /// Returns the lease as it is after the attempt, whoever holds it.
+    fn try_acquire(&self, name: &str, holder: &str, ttl: Duration) -> std::io::Result<Lease>;
+
+    /// Gives up the lease `name` if it is held by `holder`.
+    fn release(&self, name: &str, holder: &str) -> std::io::Result<()>;
+}
+
+/// Keeps leases in memory, for a single replica or for tests.
+#[derive(Debug, Default)]
+pub struct MemoryLeaseStore {
+    leases: Mutex<BTreeMap<String, Lease>>,
+}
+
+impl LeaseStore for MemoryLeaseStore {
+    fn try_acquire(&self, name: &str, holder: &str, ttl: Duration) -> std::io::Result<Lease> {
+        let mut leases = self.leases.lock().expect("lease store lock poisoned");
+        let now = UnixTimestamp::now();
+        let lease = leases
+            .entry(name.to_string())
+            .and_modify(|lease| {
+                if lease.holder == holder || !lease.is_live(now) {
+                    *lease = new_lease(holder, ttl);
+                }
+            })
+            .or_insert_with(|| new_lease(holder, ttl));
+        Ok(lease.clone())
+    }
+
+    fn release(&self, name: &str, holder: &str) -> std::io::Result<()> {
+        let mut leases = self.leases.lock().expect("lease store lock poisoned");
+        if leases.get(name).is_some_and(|lease| lease.holder == holder) {
+            leases.remove(name);
+        }
+        Ok(())
+    }
+}
+
+/// Keeps each lease in a JSON file of a directory shared by all replicas.
+///
+/// Updates are serialized by a lock file created exclusively next to the lease,
+/// so the directory must be on a file system where exclusive creation is atomic
+/// across hosts (local disks, NFSv3+, most network volumes).
+#[derive(Debug)]
+pub struct FileLeaseStore {
+    dir: PathBuf,
+}
+
+impl FileLeaseStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn lease_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.lease.json"))
+    }
+
+    /// Runs `update` on the lease `name` while holding its lock file.
+    fn with_lock<T>(
+        &self,
+        name: &str,
+        update: impl FnOnce(&Path) -> std::io::Result<T>,
+    ) -> std::io::Result<T> {
+        let lock_path = self.dir.join(format!("{name}.lock"));
+        let lock = match create_lock(&lock_path) {
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && is_stale(&lock_path) => {
+                let _ = fs::remove_file(&lock_path);
+                create_lock(&lock_path)
+            }
+            result => result,
+        }?;
+        let result = update(&self.lease_path(name));
+        drop(lock);
+        fs::remove_file(&lock_path)?;
+        result
+    }
+}
+
+impl LeaseStore for FileLeaseStore {
+    fn try_acquire(&self, name: &str, holder: &str, ttl: Duration) -> std::io::Result<Lease> {
+        self.with_lock(name, |path| {
+            let now = UnixTimestamp::now();
+            match read_lease(path)? {
+                Some(lease) if lease.holder != holder && lease.is_live(now) => Ok(lease),
+                _ => {
+                    let lease = new_lease(holder, ttl);
+                    persist(path, &lease)?;
+                    Ok(lease)
+                }
+            }
+        })
+    }
+
+    fn release(&self, name: &str, holder: &str) -> std::io::Result<()> {
+        self.with_lock(name, |path| match read_lease(path)? {
+            Some(lease) if lease.holder == holder => fs::remove_file(path),
+            _ => Ok(()),
+        })
+    }
+}
+
+fn new_lease(holder: &str, ttl: Duration) -> Lease {
+    Lease {
+        holder: holder.to_string(),
+        expires_at: UnixTimestamp::now() + ttl.as_secs(),
+    }
+}
+
+fn create_lock(path: &Path) -> std::io::Result<fs::File> {
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+}
+
+fn is_stale(lock_path: &Path) -> bool {
+    fs::metadata(lock_path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age > STALE_LOCK_AGE)
+}
+
+fn read_lease(path: &Path) -> std::io::Result<Option<Lease>> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes `lease` to a temporary file, syncs it and renames it over `path`.
+fn persist(path: &Path, lease: &Lease) -> std::io::Result<()> {
+    let content = serde_json::to_vec_pretty(lease).map_err(std::io::Error::other)?;
+    let tmp = path.with_extension("tmp");
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(&content)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+/// Leadership of this replica, as served by `GET /health/cluster`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterStatus {
+    pub node_id: String,
+    /// `false` when running standalone, without a shared lease store.
+    pub clustered: bool,
+    /// Whether this replica runs the singleton jobs.
+    pub leader: bool,
+    /// Holder of the leader lease, as last seen by this replica.
+    pub leader_id: Option<String>,
+    pub lease_expires_at: Option<UnixTimestamp>,
+    pub last_renewal_at: Option<UnixTimestamp>,
+    /// Error of the last failed renewal, cleared by the next successful one.
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct ClusterState {
+    lease: Option<Lease>,
+    last_renewal_at: Option<UnixTimestamp>,
+    last_error: Option<String>,
+    /// Set by [`Cluster::resign`]: the lease is not acquired again before then.
+    resigned_until: Option<UnixTimestamp>,
+}
+
+/// Elects the replica running singleton background jobs.
+#[derive(Debug)]
+pub struct Cluster {
+    node_id: String,
+    store: Option<Box<dyn LeaseStore>>,
+    ttl: Duration,
+    state: Mutex<ClusterState>,
+}
+
+impl Cluster {
+    /// A single replica, always the leader.
+    pub fn standalone(node_id: impl Into<String>) -> Self {
+        Self {
+            node_id: node_id.into(),
+            store: None,
+            ttl: DEFAULT_LEASE_TTL,
+            state: Mutex::new(ClusterState::default()),
+        }
+    }
+
+    /// A replica electing the leader through `store`, with leases valid for `ttl`.
+    ///
+    /// The replica is not the leader until the first [`Cluster::renew`].
+    pub fn new(
+        node_id: impl Into<String>,
+        store: impl LeaseStore + 'static,
+        ttl: Duration,
+    ) -> Self {
+        Self {
+            node_id: node_id.into(),
+            store: Some(Box::new(store)),
+            ttl,
+            state: Mutex::new(ClusterState::default()),
+        }
+    }
+
+    /// Builds the cluster membership from the `CLUSTER_*` environment variables.
+    ///
+    /// Returns a standalone replica unless `CLUSTER_LEASE_PATH` is set.
+    pub fn from_env() -> Result<Self, String> {
+        let node_id = match env::var("CLUSTER_NODE_ID") {
+            Ok(node_id) if !node_id.trim().is_empty() => node_id.trim().to_string(),
+            _ => default_node_id(),
+        };
+        let path = match env::var("CLUSTER_LEASE_PATH") {
+            Ok(path) if !path.trim().is_empty() => PathBuf::from(path.trim()),
+            _ => return Ok(Self::standalone(node_id)),
+        };
+        let ttl = match env::var("CLUSTER_LEASE_TTL_SECS") {
+            Ok(value) => {
+                let secs = value
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|e| format!("invalid CLUSTER_LEASE_TTL_SECS: {e}"))?;
+                if secs < 3 {
+                    return Err("CLUSTER_LEASE_TTL_SECS must be at least 3".to_string());
+                }
+                Duration::from_secs(secs)
+            }
+            Err(_) => DEFAULT_LEASE_TTL,
+        };
+        fs::create_dir_all(&path)
+            .map_err(|e| format!("failed to create {}: {e}", path.display()))?;
+        Ok(Self::new(node_id, FileLeaseStore::new(path), ttl))
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// How often [`Cluster::renew`] should run so the lease never lapses.
+    pub fn renew_interval(&self) -> Duration {
+        self.ttl / 3
+    }
+
+    /// Whether this replica should run singleton jobs right now.
+    pub fn is_leader(&self) -> bool {
+        if self.store.is_none() {
+            return true;
+        }
+        let now = UnixTimestamp::now();
+        self.lock()
+            .lease
+            .as_ref()
+            .is_some_and(|lease| lease.holder == self.node_id && lease.is_live(now))
+    }
+
+    /// Acquires or renews the leader lease.
+    ///
+    /// On error the last known lease is kept, so a leader stays leader until its
+    /// lease expires.
+    pub fn renew(&self) -> Result<(), String> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        let now = UnixTimestamp::now();
+        if self.lock().resigned_until.is_some_and(|until| until > now) {
+            return Ok(());
+        }
+        let _was_leader = self.is_leader();
+        let result = store.try_acquire(LEADER_LEASE, &self.node_id, self.ttl);
+        let mut state = self.lock();
+        state.last_renewal_at = Some(now);
+        match result {
+            Ok(lease) => {
+                #[cfg(feature = "telemetry")]
+                if (lease.holder == self.node_id) != _was_leader {
+                    tracing::info!(node_id = %self.node_id, holder = %lease.holder, "Cluster leadership changed");
+                }
+                state.lease = Some(lease);
+                state.last_error = None;
+                Ok(())
+            }
+            Err(e) => {
+                let error = format!("failed to renew the leader lease: {e}");
+                state.last_error = Some(error.clone());
+                Err(error)
+            }
+        }
+    }
+
+    /// Gives up leadership, letting another replica take over at its next renewal.
+    ///
+    /// This replica does not try to acquire the lease again for one lease TTL.
+    pub fn resign(&self) -> Result<(), String> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        store
+            .release(LEADER_LEASE, &self.node_id)
+            .map_err(|e| format!("failed to release the leader lease: {e}"))?;
+        let mut state = self.lock();
+        state.resigned_until = Some(UnixTimestamp::now() + self.ttl.as_secs());
+        if state
+            .lease
+            .as_ref()
+            .is_some_and(|lease| lease.holder == self.node_id)
+        {
+            state.lease = None;
+        }
+        Ok(())
+    }
+
+    pub fn status(&self) -> ClusterStatus {
+        let leader = self.is_leader();
+        let state = self.lock();
+        ClusterStatus {
+            node_id: self.node_id.clone(),
+            clustered: self.store.is_some(),
+            leader,
+            leader_id: match &self.store {
+                Some(_) => state.lease.as_ref().map(|lease| lease.holder.clone()),
+                None => Some(self.node_id.clone()),
+            },
+            lease_expires_at: state.lease.as_ref().map(|lease| lease.expires_at),
+            last_renewal_at: state.last_renewal_at,
+            last_error: state.last_error.clone(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ClusterState> {
+        self.state.lock().expect("cluster lock poisoned")
+    }
+}
+
+fn default_node_id() -> String {
+    let host = env::var("HOSTNAME").unwrap_or_else(|_| "facilitator".to_string());
+    format!("{host}-{}", std::process::id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Debug, Clone, Default)]
+    struct SharedStore(Arc<MemoryLeaseStore>);
+
+    impl LeaseStore for SharedStore {
+        fn try_acquire(&self, name: &str, holder: &str, ttl: Duration) -> std::io::Result<Lease> {
+            self.0.try_acquire(name, holder, ttl)
+        }
+
+        fn release(&self, name: &str, holder: &str) -> std::io::Result<()> {
+            self.0.release(name, holder)
+        }
+    }
+
+    #[test]
+    fn test_leader_election() {
+        let store = SharedStore::default();
+        let a = Cluster::new("a", store.clone(), Duration::from_secs(30));
+        let b = Cluster::new("b", store, Duration::from_secs(30));
+        assert!(!a.is_leader());
+
+        a.renew().unwrap();
+        b.renew().unwrap();
+        assert!(a.is_leader());
+        assert!(!b.is_leader());
+        assert_eq!(b.status().leader_id.as_deref(), Some("a"));
+
+        a.resign().unwrap();
+        a.renew().unwrap();
+        b.renew().unwrap();
+        assert!(b.is_leader());
+        assert!(!a.is_leader());
+
+        assert!(Cluster::standalone("solo").is_leader());
+    }
+
+    #[test]
+    fn test_file_lease_store() {
+        let dir = std::env::temp_dir().join(format!("x402-lease-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let store = FileLeaseStore::new(&dir);
+        let ttl = Duration::from_secs(30);
+
+        assert_eq!(store.try_acquire("job", "a", ttl).unwrap().holder, "a");
+        assert_eq!(store.try_acquire("job", "b", ttl).unwrap().holder, "a");
+        store.release("job", "b").unwrap();
+        assert_eq!(store.try_acquire("job", "b", ttl).unwrap().holder, "a");
+        store.release("job", "a").unwrap();
+        assert_eq!(store.try_acquire("job", "b", ttl).unwrap().holder, "b");
+
+        // An expired lease is taken over.
+        persist(
+            &store.lease_path("job"),
+            &Lease {
+                holder: "b".to_string(),
+                expires_at: UnixTimestamp::from_secs(0),
+            },
+        )
+        .unwrap();
+        assert_eq!(store.try_acquire("job", "a", ttl).unwrap().holder, "a");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
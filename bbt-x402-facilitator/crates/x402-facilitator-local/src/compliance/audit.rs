@@ -0,0 +1,525 @@
+//! Destinations of compliance audit records.
+//!
+//! Every audit event is serialized once, as a JSON line, and handed to each
+//! configured [`AuditSink`]. `COMPLIANCE_AUDIT_SINKS` lists the sinks, comma-separated:
+//!
+//! - `file` - appends to `COMPLIANCE_AUDIT_LOG`
+//! - `stdout` - prints one JSON line per event, for container log collectors
+//! - `syslog` - sends an RFC 5424 message to `COMPLIANCE_AUDIT_SYSLOG`, either a
+//!   `host:port` UDP address or a Unix socket path (default: `/dev/log`)
+//! - `http` - POSTs each event to `COMPLIANCE_AUDIT_HTTP_URL`, with
+//!   `COMPLIANCE_AUDIT_HTTP_TOKEN` as bearer token if set
+//! - `s3` - uploads batches of events as JSONL objects to an S3-compatible bucket,
+//!   see [`S3AuditSink`]
+//!
+//! Without `COMPLIANCE_AUDIT_SINKS`, events go to `COMPLIANCE_AUDIT_LOG` if it is
+//! set, and nowhere otherwise. Sinks never block or fail a request: write errors
+//! are reported on stderr and the record is dropped.
+
+use std::env;
+use std::fmt::Debug;
+use std::fs::{OpenOptions, create_dir_all};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy_primitives::hex;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc;
+
+/// A destination for audit records.
+pub trait AuditSink: Debug + Send + Sync {
+    /// Records one serialized audit event. Must not block on network I/O.
+    fn write(&self, record: &str);
+}
+
+/// Builds the sinks selected by `COMPLIANCE_AUDIT_SINKS`.
+pub fn sinks_from_env() -> Result<Vec<Box<dyn AuditSink>>, String> {
+    let audit_log_path = env::var("COMPLIANCE_AUDIT_LOG")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let Some(raw) = env::var("COMPLIANCE_AUDIT_SINKS")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    else {
+        return Ok(audit_log_path
+            .map(|path| Box::new(FileAuditSink::new(path)) as Box<dyn AuditSink>)
+            .into_iter()
+            .collect());
+    };
+    let mut sinks: Vec<Box<dyn AuditSink>> = Vec::new();
+    for name in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        let sink: Box<dyn AuditSink> = match name.to_lowercase().as_str() {
+            "file" => {
+                let path = audit_log_path.clone().ok_or_else(|| {
+                    "COMPLIANCE_AUDIT_LOG is required for the file audit sink".to_string()
+                })?;
+                Box::new(FileAuditSink::new(path))
+            }
+            "stdout" => Box::new(StdoutAuditSink),
+            "syslog" => Box::new(SyslogAuditSink::from_env()),
+            "http" => Box::new(HttpAuditSink::from_env()?),
+            "s3" => Box::new(S3AuditSink::from_env()?),
+            other => return Err(format!("unknown compliance audit sink: {other}")),
+        };
+        sinks.push(sink);
+    }
+    Ok(sinks)
+}
+
+/// Appends records to a local file.
+#[derive(Debug)]
+pub struct FileAuditSink {
+    path: PathBuf,
+}
+
+impl FileAuditSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn write(&self, record: &str) {
+        let path = &self.path;
+        if let Some(parent) = path.parent()
+            && let Err(error) = create_dir_all(parent)
+        {
+            eprintln!("failed to create compliance log directory {parent:?}: {error}");
+            return;
+        }
+        match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(mut file) => {
+                if let Err(error) = writeln!(file, "{record}") {
+                    eprintln!(
+                        "failed to write compliance audit record to {}: {error}",
+                        path.display()
+                    );
+                }
+            }
+            Err(error) => {
+                eprintln!(
+                    "failed to open compliance audit log {}: {error}",
+                    path.display()
+                );
+            }
+        }
+    }
+}
+
+/// Prints records to stdout.
+#[derive(Debug)]
+pub struct StdoutAuditSink;
+
+impl AuditSink for StdoutAuditSink {
+    fn write(&self, record: &str) {
+        println!("{record}");
+    }
+}
+
+/// Sends records to a syslog daemon, with facility `auth` and severity `notice`.
+#[derive(Debug)]
+pub struct SyslogAuditSink {
+    address: String,
+    hostname: String,
+}
+
+impl SyslogAuditSink {
+    pub fn from_env() -> Self {
+        let address =
+            env::var("COMPLIANCE_AUDIT_SYSLOG").unwrap_or_else(|_| "/dev/log".to_string());
+        let hostname = env::var("HOSTNAME").unwrap_or_else(|_| "-".to_string());
+        Self { address, hostname }
+    }
+
+    fn message(&self, record: &str) -> String {
+        // PRI = facility auth (4) * 8 + severity notice (5).
+        format!(
+            "<37>1 {} {} x402-facilitator - compliance - {record}",
+            rfc3339(SystemTime::now()),
+            self.hostname
+        )
+    }
+
+    fn send(&self, message: &[u8]) -> std::io::Result<()> {
+        if self.address.starts_with('/') {
+            let socket = std::os::unix::net::UnixDatagram::unbound()?;
+            socket.send_to(message, &self.address)?;
+        } else {
+            let socket = std::net::UdpSocket::bind("0.0.0.0:0")?;
+            socket.send_to(message, &self.address)?;
+        }
+        Ok(())
+    }
+}
+
+impl AuditSink for SyslogAuditSink {
+    fn write(&self, record: &str) {
+        if let Err(error) = self.send(self.message(record).as_bytes()) {
+            eprintln!(
+                "failed to send compliance audit record to syslog {}: {error}",
+                self.address
+            );
+        }
+    }
+}
+
+/// POSTs each record to an HTTP collector.
+#[derive(Debug)]
+pub struct HttpAuditSink {
+    url: String,
+    token: Option<String>,
+    client: reqwest::Client,
+}
+
+impl HttpAuditSink {
+    pub fn from_env() -> Result<Self, String> {
+        let url = env::var("COMPLIANCE_AUDIT_HTTP_URL").map_err(|_| {
+            "COMPLIANCE_AUDIT_HTTP_URL is required for the http audit sink".to_string()
+        })?;
+        Ok(Self {
+            url,
+            token: env::var("COMPLIANCE_AUDIT_HTTP_TOKEN").ok(),
+            client: reqwest::Client::new(),
+        })
+    }
+}
+
+impl AuditSink for HttpAuditSink {
+    fn write(&self, record: &str) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            eprintln!("dropping compliance audit record: no async runtime for the http sink");
+            return;
+        };
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("content-type", "application/json")
+            .body(record.to_string());
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let url = self.url.clone();
+        runtime.spawn(async move {
+            match request.send().await {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => eprintln!(
+                    "compliance audit collector {url} returned status {}",
+                    response.status()
+                ),
+                Err(error) => {
+                    eprintln!("failed to send compliance audit record to {url}: {error}")
+                }
+            }
+        });
+    }
+}
+
+/// Uploads records to an S3-compatible bucket.
+///
+/// Records are buffered and written as one JSONL object per rotation, named
+/// `<prefix><timestamp>-<sequence>.jsonl`. An object is written once
+/// `COMPLIANCE_AUDIT_S3_MAX_RECORDS` records (default `1000`) are buffered, or
+/// every `COMPLIANCE_AUDIT_S3_ROTATE_SECS` seconds (default `60`). Records still
+/// buffered when the process is killed are lost.
+///
+/// - `COMPLIANCE_AUDIT_S3_BUCKET` - bucket name (required)
+/// - `COMPLIANCE_AUDIT_S3_REGION` - region (default: `us-east-1`)
+/// - `COMPLIANCE_AUDIT_S3_ENDPOINT` - endpoint, for MinIO and other S3-compatible
+///   stores (default: `https://s3.<region>.amazonaws.com`)
+/// - `COMPLIANCE_AUDIT_S3_PREFIX` - object key prefix (default: `compliance-audit/`)
+/// - `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, `AWS_SESSION_TOKEN` - credentials
+///
+/// Objects are addressed path-style: `<endpoint>/<bucket>/<key>`.
+#[derive(Debug)]
+pub struct S3AuditSink {
+    uploader: S3Uploader,
+    max_records: usize,
+    rotate_every: Duration,
+    sender: OnceLock<mpsc::UnboundedSender<String>>,
+}
+
+#[derive(Clone, Debug)]
+struct S3Uploader {
+    endpoint: String,
+    bucket: String,
+    region: String,
+    prefix: String,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    client: reqwest::Client,
+    sequence: Arc<Mutex<u64>>,
+}
+
+impl S3AuditSink {
+    pub fn from_env() -> Result<Self, String> {
+        let bucket = env::var("COMPLIANCE_AUDIT_S3_BUCKET").map_err(|_| {
+            "COMPLIANCE_AUDIT_S3_BUCKET is required for the s3 audit sink".to_string()
+        })?;
+        let region =
+            env::var("COMPLIANCE_AUDIT_S3_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+        let endpoint = env::var("COMPLIANCE_AUDIT_S3_ENDPOINT")
+            .unwrap_or_else(|_| format!("https://s3.{region}.amazonaws.com"));
+        let access_key_id = env::var("AWS_ACCESS_KEY_ID")
+            .map_err(|_| "AWS_ACCESS_KEY_ID is required for the s3 audit sink".to_string())?;
+        let secret_access_key = env::var("AWS_SECRET_ACCESS_KEY")
+            .map_err(|_| "AWS_SECRET_ACCESS_KEY is required for the s3 audit sink".to_string())?;
+        let max_records = parse_env_number("COMPLIANCE_AUDIT_S3_MAX_RECORDS", 1000)?;
+        let rotate_secs = parse_env_number("COMPLIANCE_AUDIT_S3_ROTATE_SECS", 60)?;
+        Ok(Self {
+            uploader: S3Uploader {
+                endpoint: endpoint.trim_end_matches('/').to_string(),
+                bucket,
+                region,
+                prefix: env::var("COMPLIANCE_AUDIT_S3_PREFIX")
+                    .unwrap_or_else(|_| "compliance-audit/".to_string()),
+                access_key_id,
+                secret_access_key,
+                session_token: env::var("AWS_SESSION_TOKEN").ok(),
+                client: reqwest::Client::new(),
+                sequence: Default::default(),
+            },
+            max_records: max_records.max(1) as usize,
+            rotate_every: Duration::from_secs(rotate_secs.max(1)),
+            sender: OnceLock::new(),
+        })
+    }
+
+    /// Starts the upload task on first use, as it needs a running runtime.
+    fn sender(&self) -> Option<&mpsc::UnboundedSender<String>> {
+        if let Some(sender) = self.sender.get() {
+            return Some(sender);
+        }
+        let runtime = tokio::runtime::Handle::try_current().ok()?;
+        Some(self.sender.get_or_init(|| {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            runtime.spawn(
+                self.uploader
+                    .clone()
+                    .run(receiver, self.max_records, self.rotate_every),
+            );
+            sender
+        }))
+    }
+}
+
+impl AuditSink for S3AuditSink {
+    fn write(&self, record: &str) {
+        match self.sender() {
+            Some(sender) => {
+                if sender.send(record.to_string()).is_err() {
+                    eprintln!("dropping compliance audit record: s3 upload task stopped");
+                }
+            }
+            None => eprintln!("dropping compliance audit record: no async runtime for the s3 sink"),
+        }
+    }
+}
+
+impl S3Uploader {
+    async fn run(
+        self,
+        mut receiver: mpsc::UnboundedReceiver<String>,
+        max_records: usize,
+        rotate_every: Duration,
+    ) {
+        let mut batch = Vec::new();
+        let mut rotation = tokio::time::interval(rotate_every);
+        loop {
+            tokio::select! {
+                record = receiver.recv() => match record {
+                    Some(record) => {
+                        batch.push(record);
+                        if batch.len() >= max_records {
+                            self.upload(std::mem::take(&mut batch)).await;
+                        }
+                    }
+                    None => {
+                        self.upload(batch).await;
+                        return;
+                    }
+                },
+                _ = rotation.tick() => self.upload(std::mem::take(&mut batch)).await,
+            }
+        }
+    }
+
+    async fn upload(&self, batch: Vec<String>) {
+        if batch.is_empty() {
+            return;
+        }
+        let now = SystemTime::now();
+        let sequence = {
+            let mut sequence = self
+                .sequence
+                .lock()
+                .expect("s3 audit sequence lock poisoned");
+            *sequence += 1;
+            *sequence
+        };
+        let key = object_key(&self.prefix, now, sequence);
+        let mut body = batch.join("\n");
+        body.push('\n');
+        if let Err(error) = self.put_object(&key, body.into_bytes(), now).await {
+            eprintln!(
+                "failed to upload {} compliance audit records to s3://{}/{key}: {error}",
+                batch.len(),
+                self.bucket
+            );
+        }
+    }
+
+    async fn put_object(&self, key: &str, body: Vec<u8>, now: SystemTime) -> Result<(), String> {
+        let url = reqwest::Url::parse(&format!("{}/{}/{key}", self.endpoint, self.bucket))
+            .map_err(|e| e.to_string())?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err("S3 endpoint has no host".to_string()),
+        };
+        let amz_date = amz_date(now);
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{name}:{value}\n"))
+            .collect();
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_request = format!(
+            "PUT\n{}\n\n{canonical_headers}\n{signed_headers}\n{payload_hash}",
+            url.path()
+        );
+        let date = &amz_date[..8];
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let k_date = hmac_sha256(
+            format!("AWS4{}", self.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        );
+        let k_region = hmac_sha256(&k_date, self.region.as_bytes());
+        let k_service = hmac_sha256(&k_region, b"s3");
+        let k_signing = hmac_sha256(&k_service, b"aws4_request");
+        let signature = hex::encode(hmac_sha256(&k_signing, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            self.access_key_id
+        );
+
+        let mut request = self
+            .client
+            .put(url)
+            .header("authorization", authorization)
+            .header("content-type", "application/x-ndjson")
+            .body(body);
+        for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name, value);
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("status {status}: {text}"));
+        }
+        Ok(())
+    }
+}
+
+/// Key of the object holding one batch, e.g. `compliance-audit/20250102T030405Z-7.jsonl`.
+fn object_key(prefix: &str, now: SystemTime, sequence: u64) -> String {
+    format!("{prefix}{}-{sequence}.jsonl", amz_date(now))
+}
+
+fn parse_env_number(key: &str, default: u64) -> Result<u64, String> {
+    match env::var(key) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| format!("{key} is not a number: {value}")),
+        Err(_) => Ok(default),
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// UTC calendar date and time of day of `time`.
+fn civil_time(time: SystemTime) -> (i64, i64, i64, u64, u64, u64) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day, rem / 3_600, rem % 3_600 / 60, rem % 60)
+}
+
+/// Formats a time as the SigV4 `YYYYMMDD'T'HHMMSS'Z'` timestamp.
+fn amz_date(time: SystemTime) -> String {
+    let (year, month, day, hour, minute, second) = civil_time(time);
+    format!("{year:04}{month:02}{day:02}T{hour:02}{minute:02}{second:02}Z")
+}
+
+/// Formats a time as an RFC 3339 UTC timestamp.
+fn rfc3339(time: SystemTime) -> String {
+    let (year, month, day, hour, minute, second) = civil_time(time);
+    format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_record_framing() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_735_787_045);
+        assert_eq!(
+            object_key("compliance-audit/", time, 7),
+            "compliance-audit/20250102T030405Z-7.jsonl"
+        );
+        let sink = SyslogAuditSink {
+            address: "/dev/log".to_string(),
+            hostname: "facilitator-0".to_string(),
+        };
+        let message = sink.message("{\"outcome\":\"allowed\"}");
+        assert!(message.starts_with("<37>1 "));
+        assert!(
+            message.ends_with(
+                " facilitator-0 x402-facilitator - compliance - {\"outcome\":\"allowed\"}"
+            )
+        );
+    }
+}
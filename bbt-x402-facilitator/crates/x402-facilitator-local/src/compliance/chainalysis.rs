@@ -0,0 +1,110 @@
+//! Chainalysis sanctions screening.
+//!
+//! - `CHAINALYSIS_API_KEY` - API key (required)
+//! - `CHAINALYSIS_REST_URL` - address endpoint (default: the public sanctions API)
+//! - `COMPLIANCE_BLOCKED_STATUS` - status that denies an address (default: `BLOCKED`)
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::env;
+use std::time::Duration;
+
+use super::{ComplianceProvider, ScreeningResult, fetch_json, timeout_from_env};
+
+/// Screens addresses against the Chainalysis sanctions API.
+#[derive(Clone, Debug)]
+pub struct ChainalysisProvider {
+    rest_url: String,
+    api_key: String,
+    blocked_status: String,
+    timeout: Duration,
+    client: reqwest::Client,
+}
+
+impl ChainalysisProvider {
+    pub fn from_env() -> Result<Self, String> {
+        let api_key = env::var("CHAINALYSIS_API_KEY").map_err(|_| {
+            "CHAINALYSIS_API_KEY is required when COMPLIANCE_PROVIDER=chainalysis".to_string()
+        })?;
+        let rest_url = env::var("CHAINALYSIS_REST_URL")
+            .unwrap_or_else(|_| "https://public.chainalysis.com/api/v1/address".to_string());
+        let blocked_status =
+            env::var("COMPLIANCE_BLOCKED_STATUS").unwrap_or_else(|_| "BLOCKED".to_string());
+        Ok(Self {
+            rest_url,
+            api_key,
+            blocked_status,
+            timeout: timeout_from_env(),
+            client: reqwest::Client::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl ComplianceProvider for ChainalysisProvider {
+    fn name(&self) -> &str {
+        "chainalysis"
+    }
+
+    async fn screen(&self, address: &str) -> ScreeningResult {
+        let url = format!("{}/{}", self.rest_url.trim_end_matches('/'), address);
+        let request = self
+            .client
+            .get(&url)
+            .header("X-API-KEY", self.api_key.as_str())
+            .timeout(self.timeout);
+        let payload = match fetch_json(self.name(), request).await {
+            Ok(payload) => payload,
+            Err(reason) => return ScreeningResult::Unknown(reason),
+        };
+        match extract_sanctions_status(&payload, &self.blocked_status) {
+            Some(true) => ScreeningResult::Denied("status matches blocked policy".to_string()),
+            Some(false) => ScreeningResult::Clear,
+            None => {
+                ScreeningResult::Unknown("unrecognized chainalysis response format".to_string())
+            }
+        }
+    }
+}
+
+fn extract_sanctions_status(value: &Value, blocked_status: &str) -> Option<bool> {
+    let blocked = blocked_status.to_ascii_lowercase();
+
+    if let Some(status) = value.get("sanctions").and_then(Value::as_str) {
+        let status = status.trim().to_ascii_lowercase();
+        if status == blocked {
+            return Some(true);
+        }
+        if status == "clear" || status == "not_blocked" || status == "allowed" {
+            return Some(false);
+        }
+    }
+
+    if let Some(is_sanctioned) = value.get("is_sanctioned").and_then(Value::as_bool) {
+        return Some(is_sanctioned);
+    }
+
+    if let Some(status) = value.get("status").and_then(Value::as_str) {
+        let status = status.trim().to_ascii_lowercase();
+        if status == blocked {
+            return Some(true);
+        }
+        if status == "clear" || status == "not_blocked" || status == "allowed" {
+            return Some(false);
+        }
+    }
+
+    if let Some(risk_level) = value.get("riskLevel").and_then(Value::as_str) {
+        match risk_level.to_ascii_lowercase().as_str() {
+            "high" | "critical" => return Some(true),
+            "low" => return Some(false),
+            _ => {}
+        }
+    }
+
+    if let Some(identifications) = value.get("identifications").and_then(Value::as_array) {
+        return Some(!identifications.is_empty());
+    }
+
+    None
+}
@@ -0,0 +1,157 @@
+//! Elliptic wallet screening.
+//!
+//! - `ELLIPTIC_API_KEY` - API key (required)
+//! - `ELLIPTIC_API_SECRET` - base64-encoded API secret used to sign requests (required)
+//! - `ELLIPTIC_REST_URL` - API base URL (default: `https://aml-api.elliptic.co`)
+//! - `ELLIPTIC_RISK_THRESHOLD` - risk score, from 0 to 10, at or above which an
+//!   address is denied (default: `5`)
+//!
+//! Addresses are screened with a synchronous holistic wallet exposure analysis.
+//! An address without exposure, reported with a `null` risk score, is clear.
+
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use hmac::{Hmac, Mac};
+use serde_json::{Value, json};
+use sha2::Sha256;
+use std::env;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{ComplianceProvider, ScreeningResult, fetch_json, timeout_from_env};
+
+const WALLET_PATH: &str = "/v2/wallet/synchronous";
+
+/// Screens addresses with the Elliptic AML API.
+#[derive(Clone, Debug)]
+pub struct EllipticProvider {
+    rest_url: String,
+    api_key: String,
+    api_secret: Vec<u8>,
+    risk_threshold: f64,
+    timeout: Duration,
+    client: reqwest::Client,
+}
+
+impl EllipticProvider {
+    pub fn from_env() -> Result<Self, String> {
+        let api_key = env::var("ELLIPTIC_API_KEY").map_err(|_| {
+            "ELLIPTIC_API_KEY is required when COMPLIANCE_PROVIDER=elliptic".to_string()
+        })?;
+        let api_secret = env::var("ELLIPTIC_API_SECRET").map_err(|_| {
+            "ELLIPTIC_API_SECRET is required when COMPLIANCE_PROVIDER=elliptic".to_string()
+        })?;
+        let api_secret = STANDARD
+            .decode(api_secret.trim())
+            .map_err(|e| format!("ELLIPTIC_API_SECRET is not valid base64: {e}"))?;
+        let rest_url = env::var("ELLIPTIC_REST_URL")
+            .unwrap_or_else(|_| "https://aml-api.elliptic.co".to_string());
+        let risk_threshold = match env::var("ELLIPTIC_RISK_THRESHOLD") {
+            Ok(value) => value
+                .trim()
+                .parse()
+                .map_err(|_| format!("ELLIPTIC_RISK_THRESHOLD is not a number: {value}"))?,
+            Err(_) => 5.0,
+        };
+        Ok(Self {
+            rest_url,
+            api_key,
+            api_secret,
+            risk_threshold,
+            timeout: timeout_from_env(),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Signs a request as Elliptic expects: a base64 HMAC-SHA256 over the
+    /// timestamp, method, lowercased path and body.
+    fn sign(&self, timestamp: &str, body: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.api_secret)
+            .expect("HMAC accepts keys of any length");
+        mac.update(timestamp.as_bytes());
+        mac.update(b"POST");
+        mac.update(WALLET_PATH.to_lowercase().as_bytes());
+        mac.update(body.as_bytes());
+        STANDARD.encode(mac.finalize().into_bytes())
+    }
+}
+
+#[async_trait]
+impl ComplianceProvider for EllipticProvider {
+    fn name(&self) -> &str {
+        "elliptic"
+    }
+
+    async fn screen(&self, address: &str) -> ScreeningResult {
+        let body = json!({
+            "subject": {
+                "asset": "holistic",
+                "blockchain": "holistic",
+                "type": "address",
+                "hash": address,
+            },
+            "type": "wallet_exposure",
+        })
+        .to_string();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_millis())
+            .unwrap_or(0)
+            .to_string();
+        let request = self
+            .client
+            .post(format!(
+                "{}{WALLET_PATH}",
+                self.rest_url.trim_end_matches('/')
+            ))
+            .header("content-type", "application/json")
+            .header("x-access-key", self.api_key.as_str())
+            .header("x-access-sign", self.sign(&timestamp, &body))
+            .header("x-access-timestamp", timestamp)
+            .body(body)
+            .timeout(self.timeout);
+        match fetch_json(self.name(), request).await {
+            Ok(payload) => verdict(&payload, self.risk_threshold),
+            Err(reason) => ScreeningResult::Unknown(reason),
+        }
+    }
+}
+
+fn verdict(payload: &Value, risk_threshold: f64) -> ScreeningResult {
+    match payload.get("risk_score") {
+        Some(Value::Null) => ScreeningResult::Clear,
+        Some(score) => match score.as_f64() {
+            Some(score) if score >= risk_threshold => {
+                ScreeningResult::Denied(format!("Elliptic risk score {score}"))
+            }
+            Some(_) => ScreeningResult::Clear,
+            None => ScreeningResult::Unknown(format!("invalid Elliptic risk score {score}")),
+        },
+        None => ScreeningResult::Unknown("unrecognized Elliptic response format".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elliptic_verdict() {
+        assert_eq!(
+            verdict(&json!({"risk_score": null}), 5.0),
+            ScreeningResult::Clear
+        );
+        assert_eq!(
+            verdict(&json!({"risk_score": 1.2}), 5.0),
+            ScreeningResult::Clear
+        );
+        assert_eq!(
+            verdict(&json!({"risk_score": 7.5}), 5.0),
+            ScreeningResult::Denied("Elliptic risk score 7.5".to_string())
+        );
+        assert!(matches!(
+            verdict(&json!({"id": "analysis"}), 5.0),
+            ScreeningResult::Unknown(_)
+        ));
+    }
+}
@@ -0,0 +1,277 @@
+//! Geographic blocking of `POST /verify` and `POST /settle`.
+//!
+//! [`GeoBlocker`] resolves the country of the calling client and rejects
+//! requests from blocked countries with `451 Unavailable For Legal Reasons`,
+//! before the payment is looked at. Every check is recorded as a `geo_check`
+//! audit event through the [`ComplianceGate`].
+//!
+//! - `GEO_BLOCKING_ENABLED` - enable geo blocking (default: `false`)
+//! - `GEO_BLOCKED_COUNTRIES` - comma-separated ISO 3166-1 alpha-2 codes
+//!   (default: `CU,IR,KP,SY`)
+//! - `GEO_IP_DATABASE` - path to a MaxMind GeoIP2 / GeoLite2 Country or City database
+//! - `GEO_COUNTRY_HEADER` - header carrying the client country, as set by a CDN or
+//!   load balancer (e.g. `CF-IPCountry`); takes precedence over the database
+//! - `GEO_TRUST_FORWARDED_FOR` - take the client IP from `X-Forwarded-For` (default: `false`)
+//! - `GEO_FAIL_CLOSED` - reject clients whose country cannot be resolved (default: `false`)
+//!
+//! At least one of `GEO_IP_DATABASE` and `GEO_COUNTRY_HEADER` is required.
+
+use std::env;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde_json::{Value, json};
+
+use super::mmdb::MaxMindDb;
+use super::{ComplianceGate, parse_bool};
+
+/// Countries under comprehensive OFAC sanctions programs.
+const DEFAULT_BLOCKED_COUNTRIES: &str = "CU,IR,KP,SY";
+
+/// Outcome of a geo check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GeoDecision {
+    /// The client is in a country that is not blocked.
+    Allowed { country: String },
+    /// The client is in a blocked country.
+    Blocked { country: String },
+    /// The client country could not be resolved.
+    Unknown,
+}
+
+/// Rejects requests from blocked countries.
+#[derive(Debug)]
+pub struct GeoBlocker {
+    blocked_countries: Vec<String>,
+    database: Option<MaxMindDb>,
+    country_header: Option<String>,
+    trust_forwarded_for: bool,
+    fail_closed: bool,
+    compliance_gate: ComplianceGate,
+}
+
+impl GeoBlocker {
+    /// Builds the blocker from `GEO_*` variables, or `None` if geo blocking is disabled.
+    ///
+    /// Checks are audited through `compliance_gate`.
+    pub fn from_env(compliance_gate: ComplianceGate) -> Result<Option<Self>, String> {
+        let enabled = env::var("GEO_BLOCKING_ENABLED")
+            .map(|value| parse_bool(&value))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+        let blocked_countries = parse_countries(
+            &env::var("GEO_BLOCKED_COUNTRIES")
+                .unwrap_or_else(|_| DEFAULT_BLOCKED_COUNTRIES.to_string()),
+        )?;
+        let database = match env::var("GEO_IP_DATABASE") {
+            Ok(path) if !path.trim().is_empty() => Some(MaxMindDb::open(Path::new(path.trim()))?),
+            _ => None,
+        };
+        let country_header = env::var("GEO_COUNTRY_HEADER")
+            .ok()
+            .map(|header| header.trim().to_ascii_lowercase())
+            .filter(|header| !header.is_empty());
+        if database.is_none() && country_header.is_none() {
+            return Err(
+                "GEO_BLOCKING_ENABLED requires GEO_IP_DATABASE or GEO_COUNTRY_HEADER".to_string(),
+            );
+        }
+        Ok(Some(Self {
+            blocked_countries,
+            database,
+            country_header,
+            trust_forwarded_for: env::var("GEO_TRUST_FORWARDED_FOR")
+                .map(|value| parse_bool(&value))
+                .unwrap_or(false),
+            fail_closed: env::var("GEO_FAIL_CLOSED")
+                .map(|value| parse_bool(&value))
+                .unwrap_or(false),
+            compliance_gate,
+        }))
+    }
+
+    /// Resolves the country of a client from its IP address or the country header.
+    pub fn country(&self, ip: Option<IpAddr>, header_country: Option<&str>) -> Option<String> {
+        if let Some(country) = header_country
+            .map(str::trim)
+            .filter(|country| country.len() == 2)
+        {
+            return Some(country.to_ascii_uppercase());
+        }
+        let record = self.database.as_ref()?.lookup(ip?).ok()??;
+        ["country", "registered_country"]
+            .iter()
+            .find_map(|field| record.get(field)?.get("iso_code")?.as_str())
+            .map(str::to_ascii_uppercase)
+    }
+
+    /// Checks a client country against the block list.
+    pub fn decide(&self, country: Option<String>) -> GeoDecision {
+        match country {
+            Some(country) if self.blocked_countries.contains(&country) => {
+                GeoDecision::Blocked { country }
+            }
+            Some(country) => GeoDecision::Allowed { country },
+            None => GeoDecision::Unknown,
+        }
+    }
+
+    fn client_ip(&self, request: &Request) -> Option<IpAddr> {
+        if self.trust_forwarded_for
+            && let Some(forwarded) = request
+                .headers()
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(',').next())
+                .and_then(|value| value.trim().parse().ok())
+        {
+            return Some(forwarded);
+        }
+        request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+    }
+}
+
+/// Middleware applying a [`GeoBlocker`] to `POST /verify` and `POST /settle`.
+pub async fn enforce_geo_blocking(
+    State(blocker): State<Arc<GeoBlocker>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let request_type = match (request.method(), request.uri().path()) {
+        (&Method::POST, "/verify") => "verify",
+        (&Method::POST, "/settle") => "settle",
+        _ => return next.run(request).await,
+    };
+    let ip = blocker.client_ip(&request);
+    let header_country = blocker.country_header.as_ref().and_then(|header| {
+        request
+            .headers()
+            .get(header.as_str())
+            .and_then(|value| value.to_str().ok())
+    });
+    let decision = blocker.decide(blocker.country(ip, header_country));
+    let (outcome, country, reason) = match &decision {
+        GeoDecision::Allowed { country } => ("allowed", Some(country.as_str()), None),
+        GeoDecision::Blocked { country } => (
+            "denied",
+            Some(country.as_str()),
+            Some(format!("requests from {country} are blocked")),
+        ),
+        GeoDecision::Unknown if blocker.fail_closed => (
+            "denied",
+            None,
+            Some("client country could not be resolved".to_string()),
+        ),
+        GeoDecision::Unknown => ("unknown", None, None),
+    };
+    blocker.compliance_gate.log_geo_check(
+        request_type,
+        outcome,
+        reason.as_deref(),
+        geo_metadata(ip, country),
+    );
+    match reason {
+        Some(reason) if outcome == "denied" => (
+            StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS,
+            Json(json!({
+                "error": "geo_blocked",
+                "details": reason,
+            })),
+        )
+            .into_response(),
+        _ => next.run(request).await,
+    }
+}
+
+fn geo_metadata(ip: Option<IpAddr>, country: Option<&str>) -> Value {
+    json!({
+        "clientIp": ip.map(|ip| ip.to_string()),
+        "country": country,
+    })
+}
+
+fn parse_countries(raw: &str) -> Result<Vec<String>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|country| !country.is_empty())
+        .map(|country| {
+            if country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()) {
+                Ok(country.to_ascii_uppercase())
+            } else {
+                Err(format!(
+                    "GEO_BLOCKED_COUNTRIES contains an invalid country code: {country}"
+                ))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An IPv4 database with one node: `0.0.0.0/1` is in Iran, `128.0.0.0/1` is unknown.
+    fn test_database() -> MaxMindDb {
+        let string = |value: &str| {
+            let mut bytes = vec![0x40 | value.len() as u8];
+            bytes.extend_from_slice(value.as_bytes());
+            bytes
+        };
+        let mut db = vec![0, 0, 17, 0, 0, 1];
+        db.extend_from_slice(&[0; 16]);
+        db.push(0xe1);
+        db.extend(string("country"));
+        db.push(0xe1);
+        db.extend(string("iso_code"));
+        db.extend(string("IR"));
+        db.extend_from_slice(b"\xab\xcd\xefMaxMind.com");
+        db.push(0xe3);
+        db.extend(string("node_count"));
+        db.extend_from_slice(&[0xc1, 1]);
+        db.extend(string("record_size"));
+        db.extend_from_slice(&[0xa1, 24]);
+        db.extend(string("ip_version"));
+        db.extend_from_slice(&[0xa1, 4]);
+        MaxMindDb::from_bytes(db).unwrap()
+    }
+
+    #[test]
+    fn test_geo_decision() {
+        let blocker = GeoBlocker {
+            blocked_countries: parse_countries("cu, IR").unwrap(),
+            database: Some(test_database()),
+            country_header: None,
+            trust_forwarded_for: false,
+            fail_closed: false,
+            compliance_gate: ComplianceGate::disabled(),
+        };
+        let blocked = blocker.country(Some("10.1.2.3".parse().unwrap()), None);
+        assert_eq!(
+            blocker.decide(blocked),
+            GeoDecision::Blocked {
+                country: "IR".to_string()
+            }
+        );
+        let unknown = blocker.country(Some("203.0.113.7".parse().unwrap()), None);
+        assert_eq!(blocker.decide(unknown), GeoDecision::Unknown);
+        let header = blocker.country(Some("10.1.2.3".parse().unwrap()), Some("de"));
+        assert_eq!(
+            blocker.decide(header),
+            GeoDecision::Allowed {
+                country: "DE".to_string()
+            }
+        );
+        assert!(parse_countries("Iran").is_err());
+    }
+}
@@ -0,0 +1,260 @@
+//! Minimal reader for MaxMind DB (`.mmdb`) files.
+//!
+//! Only what country lookups need is implemented: the binary search tree with
+//! 24, 28 and 32 bit records, and decoding of data section values into JSON.
+//! See <https://maxmind.github.io/MaxMind-DB/> for the format.
+
+use std::net::IpAddr;
+use std::path::Path;
+
+use serde_json::{Map, Number, Value};
+
+const METADATA_MARKER: &[u8] = b"\xab\xcd\xefMaxMind.com";
+
+/// Size of the zero-filled separator between the search tree and the data section.
+const DATA_SECTION_SEPARATOR: usize = 16;
+
+/// Maximum nesting of decoded maps and arrays.
+const MAX_DEPTH: usize = 32;
+
+/// A MaxMind DB loaded in memory.
+#[derive(Debug)]
+pub(crate) struct MaxMindDb {
+    buffer: Vec<u8>,
+    node_count: usize,
+    record_size: usize,
+    ip_version: u64,
+    data_section: usize,
+    ipv4_start: usize,
+}
+
+impl MaxMindDb {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let buffer =
+            std::fs::read(path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        Self::from_bytes(buffer).map_err(|e| format!("invalid MaxMind DB {}: {e}", path.display()))
+    }
+
+    pub fn from_bytes(buffer: Vec<u8>) -> Result<Self, String> {
+        let metadata_start = buffer
+            .windows(METADATA_MARKER.len())
+            .rposition(|window| window == METADATA_MARKER)
+            .map(|position| position + METADATA_MARKER.len())
+            .ok_or("metadata marker not found")?;
+        let metadata = Decoder {
+            buffer: &buffer[metadata_start..],
+        }
+        .decode(0, 0)?
+        .0;
+        let field = |name: &str| {
+            metadata
+                .get(name)
+                .and_then(Value::as_u64)
+                .ok_or(format!("metadata has no {name}"))
+        };
+        let node_count = field("node_count")? as usize;
+        let record_size = field("record_size")? as usize;
+        let ip_version = field("ip_version")?;
+        if !matches!(record_size, 24 | 28 | 32) {
+            return Err(format!("unsupported record size {record_size}"));
+        }
+        let search_tree_size = node_count * record_size / 4;
+        let data_section = search_tree_size + DATA_SECTION_SEPARATOR;
+        if data_section > metadata_start {
+            return Err("search tree overlaps metadata".to_string());
+        }
+        let mut db = Self {
+            buffer,
+            node_count,
+            record_size,
+            ip_version,
+            data_section,
+            ipv4_start: 0,
+        };
+        if ip_version == 6 {
+            // IPv4 addresses live under `::/96`.
+            let mut node = 0;
+            for _ in 0..96 {
+                if node >= node_count {
+                    break;
+                }
+                node = db.record(node, 0)?;
+            }
+            db.ipv4_start = node;
+        }
+        Ok(db)
+    }
+
+    /// Returns the record stored for `ip`, if any.
+    pub fn lookup(&self, ip: IpAddr) -> Result<Option<Value>, String> {
+        let (mut node, bits): (usize, Vec<u8>) = match ip {
+            IpAddr::V4(ip) if self.ip_version == 6 => (self.ipv4_start, ip.octets().to_vec()),
+            IpAddr::V4(ip) => (0, ip.octets().to_vec()),
+            IpAddr::V6(ip) if self.ip_version == 6 => (0, ip.octets().to_vec()),
+            IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+                Some(ip) => (0, ip.octets().to_vec()),
+                None => return Ok(None),
+            },
+        };
+        for bit in 0..bits.len() * 8 {
+            if node >= self.node_count {
+                break;
+            }
+            let value = (bits[bit / 8] >> (7 - bit % 8)) & 1;
+            node = self.record(node, value)?;
+        }
+        if node <= self.node_count {
+            return Ok(None);
+        }
+        let offset = node - self.node_count - DATA_SECTION_SEPARATOR;
+        let decoder = Decoder {
+            buffer: self
+                .buffer
+                .get(self.data_section..)
+                .ok_or("data section out of bounds")?,
+        };
+        Ok(Some(decoder.decode(offset, 0)?.0))
+    }
+
+    /// Reads the left (`0`) or right (`1`) record of a search tree node.
+    fn record(&self, node: usize, side: u8) -> Result<usize, String> {
+        let node_bytes = self.record_size / 4;
+        let start = node * node_bytes;
+        let bytes = self
+            .buffer
+            .get(start..start + node_bytes)
+            .ok_or("search tree node out of bounds")?;
+        let be = |bytes: &[u8]| bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        Ok(match (self.record_size, side) {
+            (24, 0) => be(&bytes[0..3]),
+            (24, _) => be(&bytes[3..6]),
+            (28, 0) => ((bytes[3] as usize & 0xf0) << 20) | be(&bytes[0..3]),
+            (28, _) => ((bytes[3] as usize & 0x0f) << 24) | be(&bytes[4..7]),
+            (_, 0) => be(&bytes[0..4]),
+            (_, _) => be(&bytes[4..8]),
+        })
+    }
+}
+
+/// Decodes values of a data section, or of the metadata.
+struct Decoder<'a> {
+    buffer: &'a [u8],
+}
+
+impl Decoder<'_> {
+    /// Decodes the value at `offset`, returning it with the offset following it.
+    fn decode(&self, offset: usize, depth: usize) -> Result<(Value, usize), String> {
+        if depth > MAX_DEPTH {
+            return Err("data nested too deeply".to_string());
+        }
+        let control = *self.byte(offset)?;
+        let mut offset = offset + 1;
+        let mut kind = control >> 5;
+        if kind == 1 {
+            let pointer_size = ((control >> 3) & 0x3) as usize;
+            let bytes = self.bytes(offset, pointer_size + 1)?;
+            let low = (control & 0x7) as usize;
+            let be = |bytes: &[u8]| bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+            let target = match pointer_size {
+                0 => (low << 8) | be(bytes),
+                1 => ((low << 16) | be(bytes)) + 2048,
+                2 => ((low << 24) | be(bytes)) + 526_336,
+                _ => be(bytes),
+            };
+            let (value, _) = self.decode(target, depth + 1)?;
+            return Ok((value, offset + pointer_size + 1));
+        }
+        if kind == 0 {
+            kind = self.byte(offset)?.saturating_add(7);
+            offset += 1;
+        }
+        let mut size = (control & 0x1f) as usize;
+        if size >= 29 {
+            let extra = size - 28;
+            let be = self
+                .bytes(offset, extra)?
+                .iter()
+                .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+            size = match extra {
+                1 => 29 + be,
+                2 => 285 + be,
+                _ => 65_821 + be,
+            };
+            offset += extra;
+        }
+        let uint = |bytes: &[u8]| bytes.iter().fold(0u128, |acc, b| (acc << 8) | *b as u128);
+        let value = match kind {
+            2 => {
+                let bytes = self.bytes(offset, size)?;
+                offset += size;
+                Value::String(String::from_utf8_lossy(bytes).into_owned())
+            }
+            3 => {
+                let bytes = self.bytes(offset, 8)?;
+                offset += 8;
+                let value = f64::from_be_bytes(bytes.try_into().expect("8 bytes"));
+                Number::from_f64(value).map_or(Value::Null, Value::Number)
+            }
+            15 => {
+                let bytes = self.bytes(offset, 4)?;
+                offset += 4;
+                let value = f32::from_be_bytes(bytes.try_into().expect("4 bytes"));
+                Number::from_f64(value.into()).map_or(Value::Null, Value::Number)
+            }
+            4 => {
+                offset += size;
+                Value::Null
+            }
+            5 | 6 | 9 | 10 => {
+                let value = uint(self.bytes(offset, size)?);
+                offset += size;
+                match u64::try_from(value) {
+                    Ok(value) => Value::from(value),
+                    Err(_) => Value::String(value.to_string()),
+                }
+            }
+            8 => {
+                let value = uint(self.bytes(offset, size)?) as u32 as i32;
+                offset += size;
+                Value::from(value)
+            }
+            7 => {
+                let mut map = Map::new();
+                for _ in 0..size {
+                    let (key, next) = self.decode(offset, depth + 1)?;
+                    let (value, next) = self.decode(next, depth + 1)?;
+                    let Value::String(key) = key else {
+                        return Err("map key is not a string".to_string());
+                    };
+                    map.insert(key, value);
+                    offset = next;
+                }
+                Value::Object(map)
+            }
+            11 => {
+                let mut array = Vec::with_capacity(size.min(1024));
+                for _ in 0..size {
+                    let (value, next) = self.decode(offset, depth + 1)?;
+                    array.push(value);
+                    offset = next;
+                }
+                Value::Array(array)
+            }
+            14 => Value::Bool(size != 0),
+            kind => return Err(format!("unsupported data type {kind}")),
+        };
+        Ok((value, offset))
+    }
+
+    fn byte(&self, offset: usize) -> Result<&u8, String> {
+        self.buffer
+            .get(offset)
+            .ok_or("data out of bounds".to_string())
+    }
+
+    fn bytes(&self, offset: usize, len: usize) -> Result<&[u8], String> {
+        self.buffer
+            .get(offset..offset + len)
+            .ok_or("data out of bounds".to_string())
+    }
+}
@@ -0,0 +1,620 @@
+//! Compliance controls for facilitator-side request filtering.
+//!
+//! [`ComplianceGate`] checks the payer and payee of every request against the
+//! configured deny and allow lists, then screens them with a [`ComplianceProvider`].
+//! `COMPLIANCE_PROVIDER` selects the provider:
+//!
+//! - `chainalysis` (default) - [`ChainalysisProvider`]
+//! - `trm` - [`TrmProvider`]
+//! - `elliptic` - [`EllipticProvider`]
+//! - anything else - lists only
+//!
+//! Other screening services plug in through [`ComplianceGate::with_provider`].
+//! When a provider cannot give a verdict, the address is denied if
+//! `COMPLIANCE_FAIL_CLOSED` is set (the default) and allowed with a warning otherwise.
+//!
+//! Every check is recorded as an audit event in the sinks selected by
+//! `COMPLIANCE_AUDIT_SINKS`, see [`audit`].
+//!
+//! Requests can also be blocked by client country before any address is
+//! screened, see [`geo`].
+
+use std::env;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+use x402_types::proto::PaymentVerificationError;
+
+use crate::auth::current_api_key;
+
+pub mod audit;
+mod chainalysis;
+mod elliptic;
+pub mod geo;
+mod mmdb;
+mod trm;
+
+pub use audit::AuditSink;
+pub use chainalysis::ChainalysisProvider;
+pub use elliptic::EllipticProvider;
+pub use geo::GeoBlocker;
+pub use trm::TrmProvider;
+
+/// Verdict of a [`ComplianceProvider`] on one address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScreeningResult {
+    /// Nothing is known against the address.
+    Clear,
+    /// The address must not take part in payments, for the given reason.
+    Denied(String),
+    /// No verdict could be obtained, e.g. because the service was unreachable.
+    Unknown(String),
+}
+
+/// An address screening service.
+#[async_trait]
+pub trait ComplianceProvider: Debug + Send + Sync {
+    /// Name recorded in audit events.
+    fn name(&self) -> &str;
+
+    /// Screens a lowercase `0x`-prefixed address.
+    async fn screen(&self, address: &str) -> ScreeningResult;
+}
+
+#[derive(Clone, Debug)]
+pub struct ComplianceGate {
+    enabled: bool,
+    deny_list: Vec<String>,
+    allow_list: Vec<String>,
+    provider: Option<Arc<dyn ComplianceProvider>>,
+    fail_closed: bool,
+    audit_sinks: Vec<Arc<dyn AuditSink>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CompliancePartyRecord {
+    role: String,
+    address: String,
+    status: String,
+    provider: String,
+    reason: Option<String>,
+}
+
+#[derive(Debug)]
+struct CompliancePartyCheckFailure {
+    party: CompliancePartyRecord,
+    error: PaymentVerificationError,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ComplianceAuditEvent {
+    event_type: String,
+    request_type: String,
+    timestamp_ms: u128,
+    outcome: String,
+    provider: String,
+    payer: Option<String>,
+    payee: Option<String>,
+    wallet: Option<String>,
+    user_agent: Option<String>,
+    reason: Option<String>,
+    parties: Vec<CompliancePartyRecord>,
+    metadata: Option<Value>,
+}
+
+/// An audit event as written to the log, tagged with the calling API key.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ComplianceAuditRecord<'a> {
+    #[serde(flatten)]
+    event: &'a ComplianceAuditEvent,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key_id: Option<String>,
+}
+
+impl ComplianceGate {
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            deny_list: Vec::new(),
+            allow_list: Vec::new(),
+            provider: None,
+            fail_closed: true,
+            audit_sinks: Vec::new(),
+        }
+    }
+
+    /// Screens addresses with `provider` instead of the configured one.
+    pub fn with_provider(mut self, provider: Arc<dyn ComplianceProvider>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Also records audit events in `sink`.
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit_sinks.push(sink);
+        self
+    }
+
+    pub fn from_env() -> Result<Self, String> {
+        let raw_enabled =
+            env::var("COMPLIANCE_SCREENING_ENABLED").unwrap_or_else(|_| "true".to_string());
+        let enabled = parse_bool(raw_enabled.as_str());
+
+        let deny_list = parse_address_list("COMPLIANCE_DENY_LIST")?;
+        let allow_list = parse_address_list("COMPLIANCE_ALLOW_LIST")?;
+
+        if enabled && deny_list.iter().any(|addr| !is_valid_address(addr)) {
+            return Err("COMPLIANCE_DENY_LIST contains an invalid address format".to_string());
+        }
+        if enabled && allow_list.iter().any(|addr| !is_valid_address(addr)) {
+            return Err("COMPLIANCE_ALLOW_LIST contains an invalid address format".to_string());
+        }
+
+        let provider: Option<Arc<dyn ComplianceProvider>> = match env::var("COMPLIANCE_PROVIDER")
+            .unwrap_or_else(|_| "chainalysis".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "chainalysis" => Some(Arc::new(ChainalysisProvider::from_env()?)),
+            "trm" => Some(Arc::new(TrmProvider::from_env()?)),
+            "elliptic" => Some(Arc::new(EllipticProvider::from_env()?)),
+            _ => None,
+        };
+        let fail_closed = parse_bool(
+            env::var("COMPLIANCE_FAIL_CLOSED")
+                .as_deref()
+                .unwrap_or("true"),
+        );
+
+        let audit_sinks = audit::sinks_from_env()?
+            .into_iter()
+            .map(Arc::from)
+            .collect();
+
+        Ok(Self {
+            enabled,
+            deny_list,
+            allow_list,
+            provider,
+            fail_closed,
+            audit_sinks,
+        })
+    }
+
+    pub async fn validate_for_request(
+        &self,
+        request_type: &str,
+        payer: Option<&str>,
+        payee: Option<&str>,
+    ) -> Result<(), PaymentVerificationError> {
+        if !self.enabled {
+            self.record_audit(ComplianceAuditEvent {
+                event_type: "compliance_check".to_string(),
+                request_type: request_type.to_string(),
+                timestamp_ms: current_timestamp_ms(),
+                outcome: "disabled".to_string(),
+                provider: self.provider_name().to_string(),
+                payer: payer.map(str::to_lowercase),
+                payee: payee.map(str::to_lowercase),
+                wallet: None,
+                user_agent: None,
+                reason: Some("compliance disabled".to_string()),
+                parties: Vec::new(),
+                metadata: None,
+            });
+            return Ok(());
+        }
+
+        // Screening cannot be skipped by a request whose parties cannot be read.
+        let (Some(payer_raw), Some(payee_raw)) = (payer, payee) else {
+            let error = PaymentVerificationError::ComplianceFailed(
+                "payer or payee could not be read from the request".to_string(),
+            );
+            self.record_audit(ComplianceAuditEvent {
+                event_type: "compliance_check".to_string(),
+                request_type: request_type.to_string(),
+                timestamp_ms: current_timestamp_ms(),
+                outcome: "denied".to_string(),
+                provider: self.provider_name().to_string(),
+                payer: payer.map(str::to_lowercase),
+                payee: payee.map(str::to_lowercase),
+                wallet: None,
+                user_agent: None,
+                reason: Some(format!("{error}")),
+                parties: Vec::new(),
+                metadata: None,
+            });
+            return Err(error);
+        };
+
+        let mut party_records = Vec::new();
+
+        let payer_normalized = normalize_address(payer_raw).ok_or_else(|| {
+            PaymentVerificationError::ComplianceFailed(
+                "payer has an invalid address format".to_string(),
+            )
+        })?;
+
+        match self.validate_party("payer", &payer_normalized).await {
+            Ok(record) => party_records.push(record),
+            Err(failure) => {
+                self.record_audit(ComplianceAuditEvent {
+                    event_type: "compliance_check".to_string(),
+                    request_type: request_type.to_string(),
+                    timestamp_ms: current_timestamp_ms(),
+                    outcome: "denied".to_string(),
+                    provider: self.provider_name().to_string(),
+                    payer: Some(payer_normalized),
+                    payee: payee.map(str::to_lowercase),
+                    wallet: None,
+                    user_agent: None,
+                    reason: Some(format!("{}", failure.error)),
+                    parties: vec![failure.party],
+                    metadata: None,
+                });
+                return Err(failure.error);
+            }
+        }
+
+        let payee_normalized = normalize_address(payee_raw).ok_or_else(|| {
+            PaymentVerificationError::ComplianceFailed(
+                "payee has an invalid address format".to_string(),
+            )
+        })?;
+
+        match self.validate_party("payee", &payee_normalized).await {
+            Ok(record) => party_records.push(record),
+            Err(failure) => {
+                self.record_audit(ComplianceAuditEvent {
+                    event_type: "compliance_check".to_string(),
+                    request_type: request_type.to_string(),
+                    timestamp_ms: current_timestamp_ms(),
+                    outcome: "denied".to_string(),
+                    provider: self.provider_name().to_string(),
+                    payer: payer.map(str::to_lowercase),
+                    payee: Some(payee_normalized),
+                    wallet: None,
+                    user_agent: None,
+                    reason: Some(format!("{}", failure.error)),
+                    parties: party_records
+                        .into_iter()
+                        .chain(std::iter::once(failure.party))
+                        .collect(),
+                    metadata: None,
+                });
+                return Err(failure.error);
+            }
+        }
+
+        self.record_audit(ComplianceAuditEvent {
+            event_type: "compliance_check".to_string(),
+            request_type: request_type.to_string(),
+            timestamp_ms: current_timestamp_ms(),
+            outcome: "allowed".to_string(),
+            provider: self.provider_name().to_string(),
+            payer: payer.map(str::to_lowercase),
+            payee: payee.map(str::to_lowercase),
+            wallet: None,
+            user_agent: None,
+            reason: None,
+            parties: party_records,
+            metadata: None,
+        });
+
+        Ok(())
+    }
+
+    /// Checks that the screening provider answers, by screening the zero address.
+    ///
+    /// Succeeds without a request when screening is disabled or no provider is
+    /// configured. The probe is not recorded as an audit event.
+    pub async fn probe(&self) -> Result<(), String> {
+        let Some(provider) = self.provider.as_ref().filter(|_| self.enabled) else {
+            return Ok(());
+        };
+        match provider
+            .screen("0x0000000000000000000000000000000000000000")
+            .await
+        {
+            ScreeningResult::Unknown(reason) => Err(format!("{}: {reason}", provider.name())),
+            ScreeningResult::Clear | ScreeningResult::Denied(_) => Ok(()),
+        }
+    }
+
+    pub async fn validate(
+        &self,
+        payer: Option<&str>,
+        payee: Option<&str>,
+    ) -> Result<(), PaymentVerificationError> {
+        self.validate_for_request("request", payer, payee).await
+    }
+
+    pub fn log_connection(
+        &self,
+        wallet: &str,
+        reason: Option<&str>,
+        source: Option<&str>,
+        user_agent: Option<&str>,
+        metadata: Option<Value>,
+    ) {
+        let address = normalize_address(wallet);
+        let outcome = if address.is_some() {
+            "accepted"
+        } else {
+            "invalid_address"
+        };
+        let mut event_metadata = metadata.unwrap_or_else(|| json!({}));
+        if !event_metadata.is_object() {
+            event_metadata = json!({
+                "metadataType": event_metadata.to_string(),
+            });
+        }
+
+        if let Some(obj) = event_metadata.as_object_mut() {
+            obj.insert(
+                "source".to_string(),
+                json!(source.unwrap_or("wallet_client")),
+            );
+            obj.insert("provider".to_string(), json!(self.provider_name()));
+            if let Some(address) = address.as_ref() {
+                obj.insert("normalizedAddress".to_string(), json!(address));
+            }
+        }
+
+        self.record_audit(ComplianceAuditEvent {
+            event_type: "connection".to_string(),
+            request_type: "connect".to_string(),
+            timestamp_ms: current_timestamp_ms(),
+            outcome: outcome.to_string(),
+            provider: self.provider_name().to_string(),
+            payer: address,
+            payee: None,
+            wallet: Some(wallet.to_string()),
+            user_agent: user_agent.map(ToString::to_string),
+            reason: reason.map(ToString::to_string),
+            parties: Vec::new(),
+            metadata: Some(event_metadata),
+        });
+    }
+
+    /// Records the outcome of a [`GeoBlocker`] check.
+    pub fn log_geo_check(
+        &self,
+        request_type: &str,
+        outcome: &str,
+        reason: Option<&str>,
+        metadata: Value,
+    ) {
+        self.record_audit(ComplianceAuditEvent {
+            event_type: "geo_check".to_string(),
+            request_type: request_type.to_string(),
+            timestamp_ms: current_timestamp_ms(),
+            outcome: outcome.to_string(),
+            provider: "geo".to_string(),
+            payer: None,
+            payee: None,
+            wallet: None,
+            user_agent: None,
+            reason: reason.map(ToString::to_string),
+            parties: Vec::new(),
+            metadata: Some(metadata),
+        });
+    }
+
+    async fn validate_party(
+        &self,
+        role: &str,
+        address: &str,
+    ) -> Result<CompliancePartyRecord, CompliancePartyCheckFailure> {
+        if self
+            .deny_list
+            .iter()
+            .any(|denied| denied.as_str() == address)
+        {
+            let party = CompliancePartyRecord {
+                role: role.to_string(),
+                address: address.to_string(),
+                status: "denied".to_string(),
+                provider: self.provider_name().to_string(),
+                reason: Some("address is explicitly denied".to_string()),
+            };
+            return Err(CompliancePartyCheckFailure {
+                party,
+                error: PaymentVerificationError::ComplianceFailed(format!(
+                    "{role} is denied by compliance policy: {address}"
+                )),
+            });
+        }
+
+        if !self.allow_list.is_empty() && !self.allow_list.iter().any(|allowed| allowed == address)
+        {
+            let party = CompliancePartyRecord {
+                role: role.to_string(),
+                address: address.to_string(),
+                status: "denied".to_string(),
+                provider: self.provider_name().to_string(),
+                reason: Some("address is not in compliance allow-list".to_string()),
+            };
+            return Err(CompliancePartyCheckFailure {
+                party,
+                error: PaymentVerificationError::ComplianceFailed(format!(
+                    "{role} is not in compliance allow-list: {address}"
+                )),
+            });
+        }
+
+        let Some(provider) = &self.provider else {
+            return Ok(self.party_record(role, address, "passed", None));
+        };
+        match provider.screen(address).await {
+            ScreeningResult::Clear => Ok(self.party_record(
+                role,
+                address,
+                "passed",
+                Some(format!("{} clear", provider.name())),
+            )),
+            ScreeningResult::Denied(reason) => Err(CompliancePartyCheckFailure {
+                error: PaymentVerificationError::ComplianceFailed(format!(
+                    "{role} failed provider screening: {reason}"
+                )),
+                party: self.party_record(role, address, "denied", Some(reason)),
+            }),
+            ScreeningResult::Unknown(reason) if self.fail_closed => {
+                Err(CompliancePartyCheckFailure {
+                    error: PaymentVerificationError::ComplianceFailed(format!(
+                        "{role} screening result unresolved: {reason}"
+                    )),
+                    party: self.party_record(role, address, "denied", Some(reason)),
+                })
+            }
+            ScreeningResult::Unknown(reason) => {
+                Ok(self.party_record(role, address, "warn", Some(reason)))
+            }
+        }
+    }
+
+    fn party_record(
+        &self,
+        role: &str,
+        address: &str,
+        status: &str,
+        reason: Option<String>,
+    ) -> CompliancePartyRecord {
+        CompliancePartyRecord {
+            role: role.to_string(),
+            address: address.to_string(),
+            status: status.to_string(),
+            provider: self.provider_name().to_string(),
+            reason,
+        }
+    }
+
+    fn provider_name(&self) -> &str {
+        self.provider
+            .as_ref()
+            .map_or("lists", |provider| provider.name())
+    }
+
+    fn record_audit(&self, event: ComplianceAuditEvent) {
+        if self.audit_sinks.is_empty() {
+            return;
+        }
+
+        let record = ComplianceAuditRecord {
+            event: &event,
+            api_key_id: current_api_key().map(|identity| identity.id),
+        };
+        let serialized = match serde_json::to_string(&record) {
+            Ok(serialized) => serialized,
+            Err(error) => {
+                eprintln!("failed to serialize compliance audit event: {error}");
+                return;
+            }
+        };
+
+        for sink in &self.audit_sinks {
+            sink.write(&serialized);
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> bool {
+    matches!(
+        value.to_lowercase().as_str(),
+        "1" | "true" | "yes" | "y" | "on" | "enabled"
+    )
+}
+
+fn parse_address_list(key: &str) -> Result<Vec<String>, String> {
+    let raw = env::var(key).unwrap_or_default();
+    Ok(raw
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .filter_map(|address| {
+            let normalized = normalize_address(address)?;
+            Some(normalized)
+        })
+        .collect())
+}
+
+fn normalize_address(address: &str) -> Option<String> {
+    let normalized = address.trim().to_lowercase();
+    if normalized.starts_with("0x") && normalized.len() == 42 {
+        return Some(normalized);
+    }
+
+    if normalized.len() == 40
+        && normalized
+            .chars()
+            .all(|character| character.is_ascii_hexdigit())
+    {
+        return Some(format!("0x{normalized}"));
+    }
+
+    None
+}
+
+fn is_valid_address(address: &str) -> bool {
+    let normalized = address.trim().to_lowercase();
+    normalized.len() == 42
+        && normalized.starts_with("0x")
+        && normalized.as_bytes()[2..]
+            .iter()
+            .all(|byte| (*byte as char).is_ascii_hexdigit())
+}
+
+fn current_timestamp_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_millis())
+        .unwrap_or(0)
+}
+
+/// Request timeout of screening providers, from `COMPLIANCE_TIMEOUT_MS`.
+fn timeout_from_env() -> Duration {
+    let timeout_ms = env::var("COMPLIANCE_TIMEOUT_MS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(1500);
+    Duration::from_millis(timeout_ms)
+}
+
+/// Sends a screening request and returns its JSON response body.
+async fn fetch_json(provider: &str, request: reqwest::RequestBuilder) -> Result<Value, String> {
+    let response = request
+        .send()
+        .await
+        .map_err(|error| format!("{provider} request failed: {error}"))?;
+
+    if response.status() != StatusCode::OK {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("{provider} returned status {status}: {body}"));
+    }
+
+    let body = response
+        .text()
+        .await
+        .map_err(|error| format!("failed to read {provider} response: {error}"))?;
+
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(format!("empty response from {provider}"));
+    }
+
+    serde_json::from_str(body).map_err(|error| format!("invalid JSON from {provider}: {error}"))
+}
@@ -0,0 +1,140 @@
+//! TRM Labs address screening.
+//!
+//! - `TRM_API_KEY` - API key (required)
+//! - `TRM_REST_URL` - screening endpoint (default: `https://api.trmlabs.com/public/v2/screening/addresses`)
+//! - `TRM_CHAIN` - TRM chain name of the screened addresses (default: `ethereum`)
+//! - `TRM_BLOCKED_RISK_LEVELS` - comma-separated risk level labels that deny an
+//!   address (default: `severe,high`)
+//!
+//! An address is denied when any of its risk indicators or attributed entities
+//! carries a blocked risk level.
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use std::env;
+use std::time::Duration;
+
+use super::{ComplianceProvider, ScreeningResult, fetch_json, timeout_from_env};
+
+/// Screens addresses with the TRM Labs screening API.
+#[derive(Clone, Debug)]
+pub struct TrmProvider {
+    rest_url: String,
+    api_key: String,
+    chain: String,
+    blocked_risk_levels: Vec<String>,
+    timeout: Duration,
+    client: reqwest::Client,
+}
+
+impl TrmProvider {
+    pub fn from_env() -> Result<Self, String> {
+        let api_key = env::var("TRM_API_KEY")
+            .map_err(|_| "TRM_API_KEY is required when COMPLIANCE_PROVIDER=trm".to_string())?;
+        let rest_url = env::var("TRM_REST_URL").unwrap_or_else(|_| {
+            "https://api.trmlabs.com/public/v2/screening/addresses".to_string()
+        });
+        let chain = env::var("TRM_CHAIN").unwrap_or_else(|_| "ethereum".to_string());
+        let blocked_risk_levels = env::var("TRM_BLOCKED_RISK_LEVELS")
+            .unwrap_or_else(|_| "severe,high".to_string())
+            .split(',')
+            .map(|level| level.trim().to_ascii_lowercase())
+            .filter(|level| !level.is_empty())
+            .collect();
+        Ok(Self {
+            rest_url,
+            api_key,
+            chain,
+            blocked_risk_levels,
+            timeout: timeout_from_env(),
+            client: reqwest::Client::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl ComplianceProvider for TrmProvider {
+    fn name(&self) -> &str {
+        "trm"
+    }
+
+    async fn screen(&self, address: &str) -> ScreeningResult {
+        let request = self
+            .client
+            .post(&self.rest_url)
+            .basic_auth(&self.api_key, Some(&self.api_key))
+            .json(&json!([{ "address": address, "chain": self.chain }]))
+            .timeout(self.timeout);
+        match fetch_json(self.name(), request).await {
+            Ok(payload) => verdict(&payload, &self.blocked_risk_levels),
+            Err(reason) => ScreeningResult::Unknown(reason),
+        }
+    }
+}
+
+fn verdict(payload: &Value, blocked_risk_levels: &[String]) -> ScreeningResult {
+    let Some(screening) = payload.as_array().and_then(|results| results.first()) else {
+        return ScreeningResult::Unknown("unrecognized TRM response format".to_string());
+    };
+    let indicators = screening
+        .get("addressRiskIndicators")
+        .and_then(Value::as_array);
+    let entities = screening.get("entities").and_then(Value::as_array);
+    if indicators.is_none() && entities.is_none() {
+        return ScreeningResult::Unknown("unrecognized TRM response format".to_string());
+    }
+    let risks = indicators
+        .into_iter()
+        .flatten()
+        .map(|indicator| (indicator, "categoryRiskScoreLevelLabel"))
+        .chain(
+            entities
+                .into_iter()
+                .flatten()
+                .map(|entity| (entity, "riskScoreLevelLabel")),
+        );
+    for (risk, level_field) in risks {
+        let Some(level) = risk.get(level_field).and_then(Value::as_str) else {
+            continue;
+        };
+        if blocked_risk_levels.contains(&level.to_ascii_lowercase()) {
+            let category = risk
+                .get("category")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown category");
+            return ScreeningResult::Denied(format!("TRM risk level {level}: {category}"));
+        }
+    }
+    ScreeningResult::Clear
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trm_verdict() {
+        let blocked = vec!["severe".to_string(), "high".to_string()];
+        let clear = json!([{
+            "address": "0x1111111111111111111111111111111111111111",
+            "addressRiskIndicators": [
+                {"category": "Exchange", "categoryRiskScoreLevelLabel": "Low"}
+            ],
+            "entities": []
+        }]);
+        assert_eq!(verdict(&clear, &blocked), ScreeningResult::Clear);
+
+        let sanctioned = json!([{
+            "addressRiskIndicators": [],
+            "entities": [{"category": "Sanctions", "riskScoreLevelLabel": "Severe"}]
+        }]);
+        assert_eq!(
+            verdict(&sanctioned, &blocked),
+            ScreeningResult::Denied("TRM risk level Severe: Sanctions".to_string())
+        );
+        assert!(matches!(
+            verdict(&json!({"error": "bad request"}), &blocked),
+            ScreeningResult::Unknown(_)
+        ));
+    }
+}
@@ -0,0 +1,361 @@
+//! Settlement retries and the dead-letter queue.
+//!
+//! When enabled, a settlement that fails on-chain (RPC errors, dropped or reverted
+//! transactions, receipt timeouts) is retried with exponential backoff. Payment
+//! verification failures are never retried. A settlement that still fails after the
+//! last attempt is parked in the [`DeadLetterQueue`], together with the original
+//! request and the last error, so an operator can inspect it and either requeue it
+//! (settle it again) or void it.
+//!
+//! Requeueing is safe against double payment: the payment authorization carries a
+//! nonce that the token or Permit2 contract consumes, so a settlement that did land
+//! on-chain fails on the second try and can be voided.
+//!
+//! # Configuration
+//!
+//! | Variable | Description |
+//! |----------|-------------|
+//! | `SETTLEMENT_DLQ_ENABLED` | Enable retries and the dead-letter queue (default: `false`) |
+//! | `SETTLEMENT_MAX_ATTEMPTS` | Settlement attempts before an entry is dead-lettered (default: `3`) |
+//! | `SETTLEMENT_RETRY_BACKOFF_MS` | Delay before the first retry, doubled on every further retry (default: `1000`) |
+//! | `SETTLEMENT_DLQ_PATH` | JSON file the queue is persisted to and restored from (default: in memory only) |
+//!
+//! # Admin API
+//!
+//! [`handlers::dead_letter_routes`](crate::handlers::dead_letter_routes) serves the
+//! queue under `/admin/dlq`. The routes require an API key with the `admin` scope,
+//! so they should only be mounted when API keys are configured.
+//!
+//! | Method | Path | Description |
+//! |--------|------|-------------|
+//! | `GET` | `/admin/dlq` | Queue depth, age of the oldest entry, and all entries |
+//! | `GET` | `/admin/dlq/{id}` | A single entry |
+//! | `POST` | `/admin/dlq/{id}/requeue` | Settle the entry again, removing it on success |
+//! | `POST` | `/admin/dlq/{id}/void` | Remove the entry without settling it |
+//!
+//! # Metrics
+//!
+//! With the `telemetry` feature, [`DeadLetterQueue::register_metrics`] exports the
+//! `x402.settlement.dlq.depth` and `x402.settlement.dlq.oldest_age` (seconds) gauges.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use x402_types::proto;
+use x402_types::timestamp::UnixTimestamp;
+
+/// Retry policy for on-chain settlement failures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SettlementRetry {
+    /// Total number of settlement attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry. Every further retry waits twice as long.
+    pub backoff: Duration,
+}
+
+impl Default for SettlementRetry {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Duration::from_millis(1000),
+        }
+    }
+}
+
+impl SettlementRetry {
+    /// Returns the delay before attempt number `attempt + 1`.
+    pub fn delay_after(&self, attempt: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+    }
+}
+
+/// A settlement that exhausted its retries.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    /// Identifier used by the admin API.
+    pub id: u64,
+    /// The original `/settle` request.
+    pub request: proto::SettleRequest,
+    /// Error returned by the last attempt.
+    pub last_error: String,
+    /// Number of settlement attempts made so far, across requeues.
+    pub attempts: u32,
+    /// When the entry was dead-lettered.
+    pub first_failed_at: UnixTimestamp,
+    /// When the last attempt failed.
+    pub last_failed_at: UnixTimestamp,
+}
+
+/// Queue depth and age of the oldest entry.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterStats {
+    /// Number of entries in the queue.
+    pub depth: usize,
+    /// Seconds since the oldest entry was dead-lettered, if the queue is not empty.
+    pub oldest_age_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DeadLetterState {
+    next_id: u64,
+    entries: BTreeMap<u64, DeadLetter>,
+}
+
+/// Settlements that failed on-chain after all retries.
+///
+/// Entries are kept in memory and, if a path is configured, written to a JSON file
+/// after every change and restored from it on startup.
+#[derive(Debug)]
+pub struct DeadLetterQueue {
+    retry: SettlementRetry,
+    path: Option<PathBuf>,
+    state: Mutex<DeadLetterState>,
+}
+
+impl DeadLetterQueue {
+    /// Creates an empty, in-memory queue.
+    pub fn new(retry: SettlementRetry) -> Self {
+        Self {
+            retry,
+            path: None,
+            state: Mutex::new(DeadLetterState::default()),
+        }
+    }
+
+    /// Creates a queue persisted to `path`, restoring the entries already stored there.
+    pub fn open(retry: SettlementRetry, path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let state = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("invalid dead-letter queue file {}: {e}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => DeadLetterState::default(),
+            Err(e) => {
+                return Err(format!(
+                    "failed to read dead-letter queue file {}: {e}",
+                    path.display()
+                ));
+            }
+        };
+        Ok(Self {
+            retry,
+            path: Some(path),
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Builds the queue from the `SETTLEMENT_*` environment variables.
+    ///
+    /// Returns `None` unless `SETTLEMENT_DLQ_ENABLED` is set.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let enabled = env::var("SETTLEMENT_DLQ_ENABLED")
+            .map(|value| parse_bool(&value))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+        let defaults = SettlementRetry::default();
+        let max_attempts = match env::var("SETTLEMENT_MAX_ATTEMPTS") {
+            Ok(value) => value
+                .trim()
+                .parse::<u32>()
+                .map_err(|e| format!("invalid SETTLEMENT_MAX_ATTEMPTS: {e}"))?
+                .max(1),
+            Err(_) => defaults.max_attempts,
+        };
+        let backoff = match env::var("SETTLEMENT_RETRY_BACKOFF_MS") {
+            Ok(value) => Duration::from_millis(
+                value
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|e| format!("invalid SETTLEMENT_RETRY_BACKOFF_MS: {e}"))?,
+            ),
+            Err(_) => defaults.backoff,
+        };
+        let retry = SettlementRetry {
+            max_attempts,
+            backoff,
+        };
+        match env::var("SETTLEMENT_DLQ_PATH") {
+            Ok(path) if !path.trim().is_empty() => Self::open(retry, path.trim()).map(Some),
+            _ => Ok(Some(Self::new(retry))),
+        }
+    }
+
+    /// Returns the retry policy applied before entries are dead-lettered.
+    pub fn retry(&self) -> &SettlementRetry {
+        &self.retry
+    }
+
+    /// Adds a failed settlement, returning its id.
+    pub fn push(&self, request: &proto::SettleRequest, error: &str, attempts: u32) -> u64 {
+        let now = UnixTimestamp::now();
+        self.update(|state| {
+            let id = state.next_id;
+            state.next_id += 1;
+            state.entries.insert(
+                id,
+                DeadLetter {
+                    id,
+                    request: request.clone(),
+                    last_error: error.to_string(),
+                    attempts,
+                    first_failed_at: now,
+                    last_failed_at: now,
+                },
+            );
+            id
+        })
+    }
+
+    /// Records another failed attempt to settle entry `id`.
+    pub fn record_failure(&self, id: u64, error: &str, attempts: u32) {
+        let now = UnixTimestamp::now();
+        self.update(|state| {
+            if let Some(entry) = state.entries.get_mut(&id) {
+                entry.last_error = error.to_string();
+                entry.attempts += attempts;
+                entry.last_failed_at = now;
+            }
+        })
+    }
+
+    /// Removes entry `id`, returning it if it existed.
+    pub fn remove(&self, id: u64) -> Option<DeadLetter> {
+        self.update(|state| state.entries.remove(&id))
+    }
+
+    /// Returns entry `id`.
+    pub fn get(&self, id: u64) -> Option<DeadLetter> {
+        self.lock().entries.get(&id).cloned()
+    }
+
+    /// Returns all entries, oldest first.
+    pub fn entries(&self) -> Vec<DeadLetter> {
+        self.lock().entries.values().cloned().collect()
+    }
+
+    /// Returns the queue depth and the age of the oldest entry.
+    pub fn stats(&self) -> DeadLetterStats {
+        let state = self.lock();
+        let now = UnixTimestamp::now().as_secs();
+        DeadLetterStats {
+            depth: state.entries.len(),
+            oldest_age_secs: state
+                .entries
+                .values()
+                .map(|entry| entry.first_failed_at.as_secs())
+                .min()
+                .map(|oldest| now.saturating_sub(oldest)),
+        }
+    }
+
+    /// Exports the queue depth and oldest entry age as OpenTelemetry gauges.
+    ///
+    /// Must be called after the global meter provider is installed.
+    #[cfg(feature = "telemetry")]
+    pub fn register_metrics(self: &std::sync::Arc<Self>) {
+        let meter = opentelemetry::global::meter("x402-facilitator-local");
+        let queue = self.clone();
+        meter
+            .u64_observable_gauge("x402.settlement.dlq.depth")
+            .with_description("Settlements waiting in the dead-letter queue")
+            .with_callback(move |observer| observer.observe(queue.stats().depth as u64, &[]))
+            .build();
+        let queue = self.clone();
+        meter
+            .u64_observable_gauge("x402.settlement.dlq.oldest_age")
+            .with_description("Age of the oldest dead-lettered settlement")
+            .with_unit("s")
+            .with_callback(move |observer| {
+                observer.observe(queue.stats().oldest_age_secs.unwrap_or(0), &[])
+            })
+            .build();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, DeadLetterState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Applies `change` and persists the queue if a path is configured.
+    fn update<T>(&self, change: impl FnOnce(&mut DeadLetterState) -> T) -> T {
+        let mut state = self.lock();
+        let result = change(&mut state);
+        if let Some(path) = &self.path
+            && let Err(_e) = persist(path, &state)
+        {
+            #[cfg(feature = "telemetry")]
+            tracing::error!(error = %_e, path = %path.display(), "Failed to persist dead-letter queue");
+        }
+        result
+    }
+}
+
+/// Writes the queue to a temporary file and renames it over `path`.
+fn persist(path: &Path, state: &DeadLetterState) -> std::io::Result<()> {
+    let content = serde_json::to_vec_pretty(state).map_err(std::io::Error::other)?;
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path)
+}
+
+fn parse_bool(value: &str) -> bool {
+    matches!(
+        value.to_lowercase().as_str(),
+        "1" | "true" | "yes" | "y" | "on" | "enabled"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> proto::SettleRequest {
+        serde_json::json!({ "x402Version": 2, "paymentPayload": {}, "paymentRequirements": {} })
+            .into()
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let retry = SettlementRetry {
+            max_attempts: 4,
+            backoff: Duration::from_millis(100),
+        };
+        assert_eq!(retry.delay_after(1), Duration::from_millis(100));
+        assert_eq!(retry.delay_after(2), Duration::from_millis(200));
+        assert_eq!(retry.delay_after(3), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn test_queue_persists_entries() {
+        let path = env::temp_dir().join(format!("x402-dlq-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let queue = DeadLetterQueue::open(SettlementRetry::default(), &path).unwrap();
+        assert_eq!(queue.stats(), DeadLetterStats::default());
+        let first = queue.push(&request(), "receipt timeout", 3);
+        let second = queue.push(&request(), "nonce too low", 3);
+        queue.record_failure(first, "still failing", 2);
+        assert!(queue.remove(second).is_some());
+
+        let restored = DeadLetterQueue::open(SettlementRetry::default(), &path).unwrap();
+        let entries = restored.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, first);
+        assert_eq!(entries[0].attempts, 5);
+        assert_eq!(entries[0].last_error, "still failing");
+        assert_eq!(restored.stats().depth, 1);
+        assert_ne!(restored.push(&request(), "again", 1), second);
+
+        let _ = fs::remove_file(&path);
+    }
+}
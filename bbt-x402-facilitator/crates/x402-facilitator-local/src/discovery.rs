@@ -0,0 +1,421 @@
+//! Discovery of priced resources.
+//!
+//! [`DiscoveryCatalog`] lists the paid endpoints of the resource servers using the
+//! facilitator, so clients and agents can find what they can pay for, like the
+//! x402 "bazaar". Resource servers register an endpoint with its URL, the payment
+//! options it answers `402 Payment Required` with and free-form metadata; clients
+//! query the catalog with `GET /discovery/resources`.
+//!
+//! Payment options are validated like a 402 response (see
+//! [`ParsedPaymentRequired`]): an endpoint is only listed if every option is usable.
+//! Registering a URL again replaces its entry. Entries expire unless they are
+//! registered again within the TTL, so endpoints that went away drop out of the
+//! catalog on their own.
+//!
+//! When API keys are configured, registering requires a `settle` key; querying
+//! stays public.
+//!
+//! # Configuration
+//!
+//! | Variable | Description |
+//! |----------|-------------|
+//! | `DISCOVERY_ENABLED` | Serve `/discovery/resources` (default: `false`) |
+//! | `DISCOVERY_TTL_SECS` | How long an entry is listed after its last registration (default: `86400`) |
+//! | `DISCOVERY_MAX_RESOURCES` | Endpoints listed at most (default: `10000`) |
+//!
+//! The catalog is kept in memory, per facilitator instance.
+
+use std::collections::HashMap;
+use std::env;
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use x402_types::proto::PaymentRequired;
+use x402_types::proto::payment_required::{ParsedPaymentRequired, PaymentOption, resolve_network};
+use x402_types::timestamp::UnixTimestamp;
+
+/// Entries returned by a query without a `limit`.
+const DEFAULT_LIMIT: usize = 100;
+/// Entries returned by a query at most.
+const MAX_LIMIT: usize = 1000;
+
+/// Why a resource could not be registered.
+#[derive(Debug, thiserror::Error)]
+pub enum DiscoveryError {
+    #[error("Invalid resource URL {0}")]
+    InvalidUrl(String),
+    #[error("Invalid payment option {index}: {error}")]
+    InvalidOption { index: usize, error: String },
+    #[error("Invalid payment options: {0}")]
+    InvalidOptions(String),
+    #[error("The catalog is full ({0} resources)")]
+    CatalogFull(usize),
+}
+
+/// A priced endpoint, as registered by its resource server.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceRegistration {
+    /// URL of the endpoint.
+    pub resource: String,
+    /// Kind of resource, `http` unless given.
+    #[serde(rename = "type", default = "default_type")]
+    pub kind: String,
+    /// Protocol version of `accepts`.
+    pub x402_version: u8,
+    /// The payment options the endpoint answers `402 Payment Required` with.
+    pub accepts: Vec<Value>,
+    /// Free-form description of the endpoint, e.g. its input and output.
+    #[serde(default)]
+    pub metadata: Option<Value>,
+}
+
+fn default_type() -> String {
+    "http".to_string()
+}
+
+/// A listed endpoint.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredResource {
+    pub resource: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub x402_version: u8,
+    /// The validated payment options; V2 networks are CAIP-2 IDs.
+    pub accepts: Vec<Value>,
+    /// When the endpoint was last registered.
+    pub last_updated: UnixTimestamp,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+}
+
+/// Filters and paging of `GET /discovery/resources`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryQuery {
+    /// Only endpoints of this kind.
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    /// Only endpoints payable on this network, by name or CAIP-2 ID.
+    pub network: Option<String>,
+    /// Only endpoints payable with this scheme.
+    pub scheme: Option<String>,
+    /// Only endpoints payable in this asset.
+    pub asset: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// One page of the catalog.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryPage {
+    pub items: Vec<DiscoveredResource>,
+    pub pagination: Pagination,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pagination {
+    pub limit: usize,
+    pub offset: usize,
+    /// Entries matching the query, on all pages.
+    pub total: usize,
+}
+
+#[derive(Debug)]
+struct Entry {
+    resource: DiscoveredResource,
+    options: Vec<PaymentOption>,
+}
+
+/// Catalog of priced endpoints, see the [module documentation](self).
+#[derive(Debug)]
+pub struct DiscoveryCatalog {
+    ttl: Duration,
+    max_resources: usize,
+    entries: RwLock<HashMap<String, Entry>>,
+}
+
+impl DiscoveryCatalog {
+    /// Creates a catalog listing up to `max_resources` endpoints for `ttl` after
+    /// their last registration.
+    pub fn new(ttl: Duration, max_resources: usize) -> Self {
+        Self {
+            ttl,
+            max_resources,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Builds the catalog from the `DISCOVERY_*` environment variables.
+    ///
+    /// Returns `None` unless `DISCOVERY_ENABLED` is set.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let enabled = env::var("DISCOVERY_ENABLED")
+            .map(|value| parse_bool(&value))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+        let ttl = match env::var("DISCOVERY_TTL_SECS") {
+            Ok(value) => Duration::from_secs(
+                value
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|e| format!("invalid DISCOVERY_TTL_SECS: {e}"))?,
+            ),
+            Err(_) => Duration::from_secs(86_400),
+        };
+        let max_resources = match env::var("DISCOVERY_MAX_RESOURCES") {
+            Ok(value) => value
+                .trim()
+                .parse::<usize>()
+                .map_err(|e| format!("invalid DISCOVERY_MAX_RESOURCES: {e}"))?,
+            Err(_) => 10_000,
+        };
+        Ok(Some(Self::new(ttl, max_resources)))
+    }
+
+    /// Lists `registration`, replacing the entry of the same URL.
+    pub fn register(
+        &self,
+        registration: ResourceRegistration,
+    ) -> Result<DiscoveredResource, DiscoveryError> {
+        self.register_at(registration, UnixTimestamp::now())
+    }
+
+    fn register_at(
+        &self,
+        registration: ResourceRegistration,
+        now: UnixTimestamp,
+    ) -> Result<DiscoveredResource, DiscoveryError> {
+        match reqwest::Url::parse(&registration.resource) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            _ => return Err(DiscoveryError::InvalidUrl(registration.resource)),
+        }
+        let parsed = ParsedPaymentRequired::from_value(json!({
+            "x402Version": registration.x402_version,
+            "accepts": registration.accepts,
+            "resource": { "url": registration.resource, "description": "", "mimeType": "" },
+        }))
+        .map_err(|e| DiscoveryError::InvalidOptions(e.to_string()))?;
+        if let Some(rejected) = parsed.rejected.first() {
+            return Err(DiscoveryError::InvalidOption {
+                index: rejected.index,
+                error: rejected.error.to_string(),
+            });
+        }
+        let accepts = match &parsed.payment_required {
+            PaymentRequired::V1(payment_required) => to_values(&payment_required.accepts),
+            PaymentRequired::V2(payment_required) => to_values(&payment_required.accepts),
+        };
+        let resource = DiscoveredResource {
+            resource: registration.resource,
+            kind: registration.kind,
+            x402_version: registration.x402_version,
+            accepts,
+            last_updated: now,
+            metadata: registration.metadata,
+        };
+
+        let mut entries = self.entries.write().expect("discovery lock poisoned");
+        if !entries.contains_key(&resource.resource) && entries.len() >= self.max_resources {
+            entries.retain(|_, entry| !self.expired(entry, now));
+            if entries.len() >= self.max_resources {
+                return Err(DiscoveryError::CatalogFull(self.max_resources));
+            }
+        }
+        entries.insert(
+            resource.resource.clone(),
+            Entry {
+                resource: resource.clone(),
+                options: parsed.options,
+            },
+        );
+        Ok(resource)
+    }
+
+    /// Returns the page of listed endpoints matching `query`, by URL.
+    pub fn query(&self, query: &DiscoveryQuery) -> DiscoveryPage {
+        self.query_at(query, UnixTimestamp::now())
+    }
+
+    fn query_at(&self, query: &DiscoveryQuery, now: UnixTimestamp) -> DiscoveryPage {
+        let network = query
+            .network
+            .as_deref()
+            .map(|network| resolve_network(network).ok());
+        let asset = query
+            .asset
+            .as_deref()
+            .map(|asset| Address::from_str(asset).ok());
+        let entries = self.entries.read().expect("discovery lock poisoned");
+        let mut matching: Vec<&DiscoveredResource> = entries
+            .values()
+            .filter(|entry| !self.expired(entry, now))
+            .filter(|entry| {
+                query
+                    .kind
+                    .as_ref()
+                    .is_none_or(|kind| &entry.resource.kind == kind)
+            })
+            .filter(|entry| {
+                entry.options.iter().any(|option| {
+                    network
+                        .as_ref()
+                        .is_none_or(|network| network.as_ref() == Some(&option.chain_id))
+                        && query
+                            .scheme
+                            .as_ref()
+                            .is_none_or(|scheme| &option.scheme == scheme)
+                        && asset
+                            .as_ref()
+                            .is_none_or(|asset| asset.as_ref() == Some(&option.asset))
+                })
+            })
+            .map(|entry| &entry.resource)
+            .collect();
+        matching.sort_by(|a, b| a.resource.cmp(&b.resource));
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+        let offset = query.offset.unwrap_or(0);
+        DiscoveryPage {
+            items: matching
+                .iter()
+                .skip(offset)
+                .take(limit)
+                .map(|resource| (*resource).clone())
+                .collect(),
+            pagination: Pagination {
+                limit,
+                offset,
+                total: matching.len(),
+            },
+        }
+    }
+
+    fn expired(&self, entry: &Entry, now: UnixTimestamp) -> bool {
+        entry.resource.last_updated.as_secs() + self.ttl.as_secs() <= now.as_secs()
+    }
+}
+
+fn to_values<T: Serialize>(accepts: &[T]) -> Vec<Value> {
+    accepts
+        .iter()
+        .map(|requirements| {
+            serde_json::to_value(requirements).expect("payment requirements serialization failed")
+        })
+        .collect()
+}
+
+fn parse_bool(value: &str) -> bool {
+    matches!(
+        value.to_lowercase().as_str(),
+        "1" | "true" | "yes" | "y" | "on" | "enabled"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registration(resource: &str, network: &str) -> ResourceRegistration {
+        serde_json::from_value(json!({
+            "resource": resource,
+            "x402Version": 2,
+            "accepts": [{
+                "scheme": "exact",
+                "network": network,
+                "amount": "10000",
+                "payTo": "0x1111111111111111111111111111111111111111",
+                "maxTimeoutSeconds": 60,
+                "asset": "0x2222222222222222222222222222222222222222"
+            }],
+            "metadata": { "description": "Weather report" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_register_and_query() {
+        let catalog = DiscoveryCatalog::new(Duration::from_secs(60), 2);
+        let now = UnixTimestamp::from_secs(1_700_000_000);
+        let listed = catalog
+            .register_at(registration("https://a.example/weather", "etherlink"), now)
+            .unwrap();
+        assert_eq!(listed.kind, "http");
+        assert_eq!(listed.accepts[0]["network"], "eip155:42793");
+        catalog
+            .register_at(registration("https://b.example/news", "eip155:1"), now)
+            .unwrap();
+
+        let page = catalog.query_at(&DiscoveryQuery::default(), now);
+        assert_eq!(page.pagination.total, 2);
+        assert_eq!(page.items[0].resource, "https://a.example/weather");
+
+        let query = DiscoveryQuery {
+            network: Some("etherlink".to_string()),
+            ..Default::default()
+        };
+        let page = catalog.query_at(&query, now);
+        assert_eq!(page.pagination.total, 1);
+        assert_eq!(page.items[0].resource, "https://a.example/weather");
+
+        let query = DiscoveryQuery {
+            scheme: Some("upto".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(catalog.query_at(&query, now).pagination.total, 0);
+
+        let query = DiscoveryQuery {
+            limit: Some(1),
+            offset: Some(1),
+            ..Default::default()
+        };
+        let page = catalog.query_at(&query, now);
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].resource, "https://b.example/news");
+
+        assert!(matches!(
+            catalog.register_at(registration("https://c.example/", "etherlink"), now),
+            Err(DiscoveryError::CatalogFull(2))
+        ));
+        let later = UnixTimestamp::from_secs(1_700_000_060);
+        assert_eq!(
+            catalog
+                .query_at(&DiscoveryQuery::default(), later)
+                .pagination
+                .total,
+            0
+        );
+        catalog
+            .register_at(registration("https://c.example/", "etherlink"), later)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_register_rejects_invalid_resources() {
+        let catalog = DiscoveryCatalog::new(Duration::from_secs(60), 10);
+        assert!(matches!(
+            catalog.register(registration("ftp://a.example/", "etherlink")),
+            Err(DiscoveryError::InvalidUrl(_))
+        ));
+        assert!(matches!(
+            catalog.register(registration("https://a.example/", "nowhere")),
+            Err(DiscoveryError::InvalidOptions(_))
+        ));
+        let mut partly_valid = registration("https://a.example/", "etherlink");
+        let mut invalid = partly_valid.accepts[0].clone();
+        invalid["amount"] = "ten".into();
+        partly_valid.accepts.push(invalid);
+        assert!(matches!(
+            catalog.register(partly_valid),
+            Err(DiscoveryError::InvalidOption { index: 1, .. })
+        ));
+    }
+}
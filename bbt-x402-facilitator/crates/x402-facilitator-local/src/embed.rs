@@ -0,0 +1,209 @@
+//! Embedding the facilitator into an existing Axum or tower application.
+//!
+//! The `x402-facilitator` binary is one way to serve [`FacilitatorLocal`]. An API gateway
+//! can serve `/verify`, `/settle` and `/supported` in its own process instead, with a
+//! facilitator built from its own registries and compliance gate:
+//!
+//! ```ignore
+//! use x402_facilitator_local::{EmbeddedFacilitator, FacilitatorLocal};
+//!
+//! let facilitator = FacilitatorLocal::new_with_compliance(scheme_registry, compliance_gate);
+//! let x402 = EmbeddedFacilitator::new(facilitator)
+//!     .with_api_key_auth(api_key_auth)
+//!     .with_rate_limiter(rate_limiter);
+//!
+//! let app = Router::new()
+//!     .route("/orders", post(create_order))
+//!     .nest("/x402", x402.router())
+//!     .with_state(app_state);
+//! ```
+//!
+//! [`EmbeddedFacilitator::router`] carries its own state, so it merges or nests into a
+//! router of any application state. [`EmbeddedFacilitator::into_service`] returns a tower
+//! `Service` for stacks other than Axum.
+//!
+//! The payment routes are guarded as in the binary: blocked countries are turned away
+//! first, then API keys are checked, then rate limits. With API keys, the admin routes
+//! of the facilitator can be served too, see [`EmbeddedFacilitator::with_admin_routes`].
+//! Scheme handlers can be swapped while serving with [`FacilitatorLocal::replace_handlers`]
+//! on [`EmbeddedFacilitator::facilitator`].
+
+use std::sync::Arc;
+
+use axum::Router;
+use axum::body::Body;
+use axum::routing::RouterIntoService;
+use x402_types::scheme::SchemeRegistry;
+
+use crate::auth::ApiKeyAuth;
+use crate::compliance::GeoBlocker;
+use crate::facilitator_local::FacilitatorLocal;
+use crate::handlers;
+use crate::rate_limit::RateLimiter;
+
+/// The facilitator HTTP API, ready to be mounted in another application.
+///
+/// See the [module documentation](self).
+#[derive(Clone)]
+pub struct EmbeddedFacilitator {
+    facilitator: Arc<FacilitatorLocal<SchemeRegistry>>,
+    api_key_auth: Option<Arc<ApiKeyAuth>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    geo_blocker: Option<Arc<GeoBlocker>>,
+    admin_routes: bool,
+}
+
+impl EmbeddedFacilitator {
+    /// Serves `facilitator`, a [`FacilitatorLocal`] or one shared with the application.
+    pub fn new(facilitator: impl Into<Arc<FacilitatorLocal<SchemeRegistry>>>) -> Self {
+        Self {
+            facilitator: facilitator.into(),
+            api_key_auth: None,
+            rate_limiter: None,
+            geo_blocker: None,
+            admin_routes: false,
+        }
+    }
+
+    /// Guards `POST /verify` and `POST /settle` with API keys, see [`handlers::authenticated_routes`].
+    pub fn with_api_key_auth(mut self, api_key_auth: Arc<ApiKeyAuth>) -> Self {
+        self.api_key_auth = Some(api_key_auth);
+        self
+    }
+
+    /// Rate limits the payment routes, see [`handlers::rate_limited_routes`].
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Rejects payments from blocked countries, see [`handlers::geo_blocked_routes`].
+    pub fn with_geo_blocker(mut self, geo_blocker: Arc<GeoBlocker>) -> Self {
+        self.geo_blocker = Some(geo_blocker);
+        self
+    }
+
+    /// Also serves the admin routes of the facilitator behind `admin` API keys: the
+    /// registry inspection and switches, and the dead-letter queue, guardrail and
+    /// settlement ledger when attached to the facilitator.
+    ///
+    /// Without [`with_api_key_auth`](Self::with_api_key_auth), no admin route is served.
+    pub fn with_admin_routes(mut self) -> Self {
+        self.admin_routes = true;
+        self
+    }
+
+    /// The facilitator being served.
+    pub fn facilitator(&self) -> &Arc<FacilitatorLocal<SchemeRegistry>> {
+        &self.facilitator
+    }
+
+    /// The facilitator routes, to merge or nest into a router of any state `S`.
+    pub fn router<S>(&self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let payment_routes = match &self.rate_limiter {
+            Some(rate_limiter) => handlers::rate_limited_routes(rate_limiter.clone()),
+            None => handlers::routes(),
+        };
+        let payment_routes = match &self.api_key_auth {
+            Some(api_key_auth) => {
+                handlers::authenticated_routes(payment_routes, api_key_auth.clone())
+            }
+            None => payment_routes,
+        };
+        // Blocked countries are turned away before API keys are checked.
+        let payment_routes = match &self.geo_blocker {
+            Some(geo_blocker) => handlers::geo_blocked_routes(payment_routes, geo_blocker.clone()),
+            None => payment_routes,
+        };
+        let mut router = Router::new()
+            .merge(payment_routes.with_state(self.facilitator.clone()))
+            .merge(handlers::compliance_routes().with_state(self.facilitator.clone()));
+        if let (true, Some(api_key_auth)) = (self.admin_routes, &self.api_key_auth) {
+            router = router.merge(self.admin_router(api_key_auth));
+        }
+        router
+    }
+
+    /// The facilitator routes as a tower `Service`.
+    pub fn into_service(self) -> RouterIntoService<Body> {
+        self.router::<()>().into_service()
+    }
+
+    fn admin_router<S>(&self, api_key_auth: &Arc<ApiKeyAuth>) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let mut admin_routes = handlers::registry_routes();
+        if self.facilitator.dead_letters().is_some() {
+            admin_routes = admin_routes.merge(handlers::dead_letter_routes());
+        }
+        if self.facilitator.guardrail().is_some() {
+            admin_routes = admin_routes.merge(handlers::guardrail_routes());
+        }
+        #[cfg(feature = "storage")]
+        if self.facilitator.settlement_ledger().is_some() {
+            admin_routes = admin_routes.merge(handlers::refund_routes());
+        }
+        let admin_routes = handlers::authenticated_routes(admin_routes, api_key_auth.clone());
+        let router = Router::new().merge(admin_routes.with_state(self.facilitator.clone()));
+        #[cfg(feature = "storage")]
+        if let Some(ledger) = self.facilitator.settlement_ledger() {
+            let ledger_routes = handlers::authenticated_routes(
+                handlers::settlement_ledger_routes(),
+                api_key_auth.clone(),
+            );
+            return router.merge(ledger_routes.with_state(ledger.clone()));
+        }
+        router
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    use super::*;
+    use x402_types::config::ApiKeyScope;
+
+    async fn status(router: Router, method: &str, uri: &str) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from("{}"))
+            .unwrap();
+        router.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_embedded_routes() {
+        let facilitator = FacilitatorLocal::new(SchemeRegistry::default());
+        let mut auth = ApiKeyAuth::new();
+        auth.insert("ops", "secret", ApiKeyScope::Admin);
+        let embedded = EmbeddedFacilitator::new(facilitator).with_api_key_auth(Arc::new(auth));
+
+        let app = Router::new().nest("/x402", embedded.router());
+        assert_eq!(
+            status(app.clone(), "GET", "/x402/supported").await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(app.clone(), "POST", "/x402/verify").await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(app, "GET", "/x402/admin/schemes").await,
+            StatusCode::NOT_FOUND
+        );
+
+        let app = Router::new().nest("/x402", embedded.with_admin_routes().router());
+        assert_eq!(
+            status(app, "GET", "/x402/admin/schemes").await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
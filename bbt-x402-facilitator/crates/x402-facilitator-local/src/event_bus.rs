@@ -0,0 +1,538 @@
+//! Streaming of settlement and compliance events to Kafka or NATS.
+//!
+//! [`EventBus`] publishes facilitator events to topics of an existing streaming
+//! platform. Settlement events are published when `/settle` completes, one per
+//! outcome, with the event types of [`crate::notify`]. Compliance events are the
+//! audit records of the [`ComplianceGate`](crate::ComplianceGate), which the bus
+//! receives as an [`AuditSink`].
+//!
+//! Every event is a [`BusEvent`], encoded as JSON or with the [`EVENT_AVRO_SCHEMA`]
+//! Avro schema. Compliance events keep the full audit record in `detail`.
+//!
+//! # Configuration
+//!
+//! | Variable | Description |
+//! |----------|-------------|
+//! | `EVENT_BUS` | `kafka` or `nats` (default: disabled) |
+//! | `EVENT_BUS_URL` | Kafka REST Proxy base URL, or NATS server `host:port` (a `nats://` prefix is allowed) |
+//! | `EVENT_BUS_FORMAT` | `json` (default) or `avro` |
+//! | `EVENT_BUS_TOKEN` | Bearer token of the REST Proxy, or NATS auth token |
+//! | `EVENT_BUS_SETTLEMENT_TOPIC` | Topic or subject of settlement events (default: `x402.settlements`) |
+//! | `EVENT_BUS_COMPLIANCE_TOPIC` | Topic or subject of compliance events (default: `x402.compliance`) |
+//!
+//! Kafka is reached through the Confluent REST Proxy v2 API. In `avro` format the
+//! records are posted together with the schema, and the proxy registers it with
+//! the schema registry and encodes them. NATS messages carry the JSON document, or
+//! the Avro binary encoding of the record without container header.
+//!
+//! Publishing never delays a request: events are queued and sent in order by a
+//! background task. Events that cannot be sent are logged and dropped; use the
+//! [outbox](crate::outbox) where every event has to arrive.
+
+use std::env;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::sync::mpsc;
+use x402_types::config::NotificationEventKind;
+use x402_types::proto;
+
+use crate::compliance::AuditSink;
+
+/// Avro schema of a [`BusEvent`], fields in encoding order.
+pub const EVENT_AVRO_SCHEMA: &str = r#"{"type":"record","name":"FacilitatorEvent","namespace":"x402","fields":[{"name":"category","type":"string"},{"name":"eventType","type":"string"},{"name":"timestampMs","type":"long"},{"name":"outcome","type":"string"},{"name":"network","type":["null","string"],"default":null},{"name":"payer","type":["null","string"],"default":null},{"name":"payee","type":["null","string"],"default":null},{"name":"transaction","type":["null","string"],"default":null},{"name":"reason","type":["null","string"],"default":null},{"name":"detail","type":["null","string"],"default":null}]}"#;
+
+const DEFAULT_SETTLEMENT_TOPIC: &str = "x402.settlements";
+const DEFAULT_COMPLIANCE_TOPIC: &str = "x402.compliance";
+
+/// Time allowed for connecting to NATS or posting to the REST Proxy.
+const PUBLISH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A settlement or compliance event as published on the bus.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusEvent {
+    /// `settlement` or `compliance`.
+    pub category: String,
+    /// Notification event type for settlements, audit event type for compliance.
+    pub event_type: String,
+    pub timestamp_ms: u64,
+    /// `success` or `failure` for settlements, the audit outcome for compliance.
+    pub outcome: String,
+    /// CAIP-2 chain id of the payment.
+    pub network: Option<String>,
+    pub payer: Option<String>,
+    pub payee: Option<String>,
+    /// Settlement transaction hash.
+    pub transaction: Option<String>,
+    pub reason: Option<String>,
+    /// Source record as JSON, for compliance events.
+    pub detail: Option<String>,
+}
+
+impl BusEvent {
+    /// Creates the event of a completed settlement.
+    pub fn settlement(
+        kind: NotificationEventKind,
+        request: &proto::SettleRequest,
+        transaction: Option<&str>,
+        reason: Option<String>,
+    ) -> Self {
+        let outcome = match kind {
+            NotificationEventKind::SettlementSucceeded => "success",
+            _ => "failure",
+        };
+        Self {
+            category: "settlement".to_string(),
+            event_type: kind.as_str().to_string(),
+            timestamp_ms: now_ms(),
+            outcome: outcome.to_string(),
+            network: request
+                .scheme_handler_slug()
+                .map(|slug| slug.chain_id.to_string()),
+            payer: request.payer(),
+            payee: request.payee(),
+            transaction: transaction.map(str::to_string),
+            reason,
+            detail: None,
+        }
+    }
+
+    /// Creates a compliance event from a serialized audit record.
+    pub fn from_audit_record(record: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(record).ok()?;
+        let text = |field: &str| value.get(field).and_then(Value::as_str).map(str::to_string);
+        Some(Self {
+            category: "compliance".to_string(),
+            event_type: text("eventType")?,
+            timestamp_ms: value
+                .get("timestampMs")
+                .and_then(Value::as_u64)
+                .unwrap_or_else(now_ms),
+            outcome: text("outcome").unwrap_or_default(),
+            network: None,
+            payer: text("payer"),
+            payee: text("payee"),
+            transaction: None,
+            reason: text("reason"),
+            detail: Some(record.to_string()),
+        })
+    }
+
+    /// Avro binary encoding of the event under [`EVENT_AVRO_SCHEMA`].
+    pub fn to_avro(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        write_string(&mut buffer, &self.category);
+        write_string(&mut buffer, &self.event_type);
+        write_long(&mut buffer, self.timestamp_ms as i64);
+        write_string(&mut buffer, &self.outcome);
+        for field in self.optional_fields() {
+            match field {
+                Some(value) => {
+                    write_long(&mut buffer, 1);
+                    write_string(&mut buffer, value);
+                }
+                None => write_long(&mut buffer, 0),
+            }
+        }
+        buffer
+    }
+
+    /// Avro JSON encoding of the event, as the REST Proxy expects it.
+    fn to_avro_json(&self) -> Value {
+        let union = |field: Option<&String>| match field {
+            Some(value) => json!({ "string": value }),
+            None => Value::Null,
+        };
+        let [network, payer, payee, transaction, reason, detail] = self.optional_fields();
+        json!({
+            "category": self.category,
+            "eventType": self.event_type,
+            "timestampMs": self.timestamp_ms,
+            "outcome": self.outcome,
+            "network": union(network),
+            "payer": union(payer),
+            "payee": union(payee),
+            "transaction": union(transaction),
+            "reason": union(reason),
+            "detail": union(detail),
+        })
+    }
+
+    fn optional_fields(&self) -> [Option<&String>; 6] {
+        [
+            self.network.as_ref(),
+            self.payer.as_ref(),
+            self.payee.as_ref(),
+            self.transaction.as_ref(),
+            self.reason.as_ref(),
+            self.detail.as_ref(),
+        ]
+    }
+}
+
+/// Writes a zigzag-encoded variable-length Avro `long`.
+fn write_long(buffer: &mut Vec<u8>, value: i64) {
+    let mut n = ((value << 1) ^ (value >> 63)) as u64;
+    while n >= 0x80 {
+        buffer.push((n as u8 & 0x7f) | 0x80);
+        n >>= 7;
+    }
+    buffer.push(n as u8);
+}
+
+fn write_string(buffer: &mut Vec<u8>, value: &str) {
+    write_long(buffer, value.len() as i64);
+    buffer.extend_from_slice(value.as_bytes());
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Encoding of published events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventFormat {
+    Json,
+    Avro,
+}
+
+#[derive(Clone, Debug)]
+enum Transport {
+    Kafka {
+        url: String,
+        client: reqwest::Client,
+    },
+    Nats {
+        address: String,
+    },
+}
+
+#[derive(Clone, Debug)]
+struct Publisher {
+    transport: Transport,
+    format: EventFormat,
+    token: Option<String>,
+}
+
+/// Publishes facilitator events to Kafka or NATS.
+#[derive(Debug)]
+pub struct EventBus {
+    publisher: Publisher,
+    settlement_topic: String,
+    compliance_topic: String,
+    sender: OnceLock<mpsc::UnboundedSender<(String, BusEvent)>>,
+}
+
+impl EventBus {
+    /// Builds the bus from the `EVENT_BUS*` environment variables.
+    ///
+    /// Returns `None` unless `EVENT_BUS` is set.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let Some(kind) = env::var("EVENT_BUS")
+            .ok()
+            .map(|value| value.trim().to_lowercase())
+            .filter(|value| !value.is_empty())
+        else {
+            return Ok(None);
+        };
+        let url = env::var("EVENT_BUS_URL")
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| "EVENT_BUS_URL is required with EVENT_BUS".to_string())?;
+        let transport = match kind.as_str() {
+            "kafka" => Transport::Kafka {
+                url: url.trim_end_matches('/').to_string(),
+                client: reqwest::Client::builder()
+                    .timeout(PUBLISH_TIMEOUT)
+                    .build()
+                    .map_err(|e| format!("failed to build the Kafka REST client: {e}"))?,
+            },
+            "nats" => Transport::Nats {
+                address: url.trim_start_matches("nats://").to_string(),
+            },
+            other => return Err(format!("unknown EVENT_BUS: {other}")),
+        };
+        let format = match env::var("EVENT_BUS_FORMAT")
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "" | "json" => EventFormat::Json,
+            "avro" => EventFormat::Avro,
+            other => return Err(format!("unknown EVENT_BUS_FORMAT: {other}")),
+        };
+        let topic = |name: &str, default: &str| {
+            env::var(name)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .unwrap_or_else(|| default.to_string())
+        };
+        Ok(Some(Self {
+            publisher: Publisher {
+                transport,
+                format,
+                token: env::var("EVENT_BUS_TOKEN")
+                    .ok()
+                    .filter(|token| !token.is_empty()),
+            },
+            settlement_topic: topic("EVENT_BUS_SETTLEMENT_TOPIC", DEFAULT_SETTLEMENT_TOPIC),
+            compliance_topic: topic("EVENT_BUS_COMPLIANCE_TOPIC", DEFAULT_COMPLIANCE_TOPIC),
+            sender: OnceLock::new(),
+        }))
+    }
+
+    /// Queues a settlement event for publishing.
+    pub fn publish_settlement(&self, event: BusEvent) {
+        self.enqueue(&self.settlement_topic, event);
+    }
+
+    fn enqueue(&self, topic: &str, event: BusEvent) {
+        let sent = self
+            .sender()
+            .is_some_and(|sender| sender.send((topic.to_string(), event)).is_ok());
+        if !sent {
+            #[cfg(feature = "telemetry")]
+            tracing::warn!(topic, "Dropping event, the event bus task is not running");
+        }
+    }
+
+    /// Starts the publishing task on first use, as it needs a running runtime.
+    fn sender(&self) -> Option<&mpsc::UnboundedSender<(String, BusEvent)>> {
+        if let Some(sender) = self.sender.get() {
+            return Some(sender);
+        }
+        let runtime = tokio::runtime::Handle::try_current().ok()?;
+        Some(self.sender.get_or_init(|| {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            runtime.spawn(self.publisher.clone().run(receiver));
+            sender
+        }))
+    }
+}
+
+impl AuditSink for EventBus {
+    fn write(&self, record: &str) {
+        if let Some(event) = BusEvent::from_audit_record(record) {
+            self.enqueue(&self.compliance_topic, event);
+        }
+    }
+}
+
+impl Publisher {
+    async fn run(self, mut receiver: mpsc::UnboundedReceiver<(String, BusEvent)>) {
+        let mut nats: Option<NatsConnection> = None;
+        loop {
+            let (topic, event) = tokio::select! {
+                next = receiver.recv() => match next {
+                    Some(next) => next,
+                    None => return,
+                },
+                // Keeps the idle connection alive by answering server PINGs.
+                alive = NatsConnection::serve(nats.as_mut()) => {
+                    if !alive {
+                        nats = None;
+                    }
+                    continue;
+                }
+            };
+            if let Err(_e) = self.publish(&mut nats, &topic, &event).await {
+                #[cfg(feature = "telemetry")]
+                tracing::warn!(topic = %topic, event_type = %event.event_type, error = %_e, "Failed to publish event");
+            }
+        }
+    }
+
+    async fn publish(
+        &self,
+        nats: &mut Option<NatsConnection>,
+        topic: &str,
+        event: &BusEvent,
+    ) -> Result<(), String> {
+        match &self.transport {
+            Transport::Kafka { url, client } => {
+                let (content_type, body) = match self.format {
+                    EventFormat::Json => (
+                        "application/vnd.kafka.json.v2+json",
+                        json!({ "records": [{ "value": event }] }),
+                    ),
+                    EventFormat::Avro => (
+                        "application/vnd.kafka.avro.v2+json",
+                        json!({
+                            "value_schema": EVENT_AVRO_SCHEMA,
+                            "records": [{ "value": event.to_avro_json() }],
+                        }),
+                    ),
+                };
+                let mut request = client
+                    .post(format!("{url}/topics/{topic}"))
+                    .header("content-type", content_type)
+                    .body(body.to_string());
+                if let Some(token) = &self.token {
+                    request = request.bearer_auth(token);
+                }
+                request
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }
+            Transport::Nats { address } => {
+                let payload = match self.format {
+                    EventFormat::Json => serde_json::to_vec(event).map_err(|e| e.to_string())?,
+                    EventFormat::Avro => event.to_avro(),
+                };
+                // A connection closed since the last event is only noticed on write,
+                // so the first failure reconnects and tries again.
+                for _ in 0..2 {
+                    let connection = match nats {
+                        Some(connection) => connection,
+                        None => nats.insert(
+                            NatsConnection::connect(address, self.token.as_deref())
+                                .await
+                                .map_err(|e| format!("failed to connect to NATS {address}: {e}"))?,
+                        ),
+                    };
+                    match connection.publish(topic, &payload).await {
+                        Ok(()) => return Ok(()),
+                        Err(_) => *nats = None,
+                    }
+                }
+                Err(format!("failed to publish to NATS {address}"))
+            }
+        }
+    }
+}
+
+/// A client connection to a NATS server, speaking the text protocol.
+struct NatsConnection {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+}
+
+impl NatsConnection {
+    async fn connect(address: &str, token: Option<&str>) -> std::io::Result<Self> {
+        let stream = tokio::time::timeout(PUBLISH_TIMEOUT, TcpStream::connect(address))
+            .await
+            .map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::TimedOut, "connect timed out")
+            })??;
+        let (reader, writer) = stream.into_split();
+        let mut connection = Self {
+            reader: BufReader::new(reader),
+            writer,
+        };
+        let info = connection.read_line().await?;
+        if !info.starts_with("INFO") {
+            return Err(std::io::Error::other(format!(
+                "unexpected greeting: {info}"
+            )));
+        }
+        let mut options = json!({
+            "verbose": false,
+            "pedantic": false,
+            "name": "x402-facilitator",
+            "lang": "rust",
+            "version": env!("CARGO_PKG_VERSION"),
+        });
+        if let Some(token) = token {
+            options["auth_token"] = json!(token);
+        }
+        connection
+            .writer
+            .write_all(format!("CONNECT {options}\r\nPING\r\n").as_bytes())
+            .await?;
+        // The server answers PING with PONG once the CONNECT was accepted.
+        let reply = connection.read_line().await?;
+        if !reply.starts_with("PONG") {
+            return Err(std::io::Error::other(format!(
+                "connection refused: {reply}"
+            )));
+        }
+        Ok(connection)
+    }
+
+    async fn publish(&mut self, subject: &str, payload: &[u8]) -> std::io::Result<()> {
+        let mut message = format!("PUB {subject} {}\r\n", payload.len()).into_bytes();
+        message.extend_from_slice(payload);
+        message.extend_from_slice(b"\r\n");
+        self.writer.write_all(&message).await
+    }
+
+    /// Handles one message from the server, or never returns without a connection.
+    ///
+    /// Returns `false` once the connection is closed.
+    async fn serve(connection: Option<&mut Self>) -> bool {
+        let Some(connection) = connection else {
+            return std::future::pending().await;
+        };
+        match connection.read_line().await {
+            Ok(line) if line.starts_with("PING") => {
+                connection.writer.write_all(b"PONG\r\n").await.is_ok()
+            }
+            Ok(_line) => {
+                #[cfg(feature = "telemetry")]
+                if _line.starts_with("-ERR") {
+                    tracing::warn!(error = %_line, "NATS server reported an error");
+                }
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    async fn read_line(&mut self) -> std::io::Result<String> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).await? == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(line.trim_end().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_encoding() {
+        let record = r#"{"eventType":"screening","requestType":"settle","timestampMs":1700000000000,"outcome":"denied","payer":"0xabc","payee":null,"reason":"sanctioned"}"#;
+        let event = BusEvent::from_audit_record(record).unwrap();
+        assert_eq!(event.category, "compliance");
+        assert_eq!(event.outcome, "denied");
+        assert_eq!(event.payer.as_deref(), Some("0xabc"));
+        assert_eq!(event.payee, None);
+
+        let event = BusEvent {
+            category: "settlement".to_string(),
+            event_type: "x".to_string(),
+            timestamp_ms: 1,
+            outcome: "success".to_string(),
+            payer: Some("p".to_string()),
+            ..Default::default()
+        };
+        let mut expected = vec![20];
+        expected.extend_from_slice(b"settlement");
+        expected.extend_from_slice(&[2, b'x', 2, 14]);
+        expected.extend_from_slice(b"success");
+        expected.extend_from_slice(&[0, 2, 2, b'p', 0, 0, 0, 0]);
+        assert_eq!(event.to_avro(), expected);
+        assert_eq!(event.to_avro_json()["payer"], json!({ "string": "p" }));
+
+        let mut buffer = Vec::new();
+        write_long(&mut buffer, -65);
+        assert_eq!(buffer, [0x81, 0x01]);
+    }
+}
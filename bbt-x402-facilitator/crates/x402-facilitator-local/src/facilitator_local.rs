@@ -30,8 +30,88 @@
 //!
 //! If no matching handler is found, the request returns an error with
 //! [`PaymentVerificationError::UnsupportedScheme`](x402_types::proto::PaymentVerificationError::UnsupportedScheme).
+//!
+//! # V1 Payments
+//!
+//! With [`FacilitatorLocal::with_v1_upconversion`], a V1 payment whose chain and
+//! scheme are served only by a V2 handler is upgraded to V2 (see
+//! [`x402_types::proto::compat`]) and handled as such, so resource servers do not
+//! have to keep V1 schemes registered for older clients.
+//!
+//! # Reloading
+//!
+//! The registry can be swapped at runtime with [`FacilitatorLocal::replace_handlers`],
+//! e.g. after the configuration file changed. Each request takes a snapshot of the
+//! registry when it starts, so requests in flight finish on the registry they began with.
+//!
+//! # Retries
+//!
+//! With a [`DeadLetterQueue`] attached through [`FacilitatorLocal::with_dead_letter_queue`],
+//! on-chain settlement failures are retried, and settlements that still fail are
+//! dead-lettered for an operator to requeue or void (see [`crate::dead_letter`]).
+//!
+//! # Shutdown
+//!
+//! With [`InFlightSettlements`] attached through
+//! [`FacilitatorLocal::with_in_flight_settlements`], settlements are journaled while
+//! in flight, so that shutdown can wait for them and turn new ones away. Those cut
+//! off anyway are dead-lettered at the next startup by
+//! [`FacilitatorLocal::recover_interrupted_settlements`] (see [`crate::in_flight`]).
+//!
+//! # Notifications
+//!
+//! With a [`NotificationDispatcher`] attached through [`FacilitatorLocal::with_notifications`],
+//! every settlement is reported to the channels its routing rules select
+//! (see [`crate::notify`]). With an [`Outbox`] attached as well through
+//! [`FacilitatorLocal::with_outbox`], events are stored before `/settle` responds and
+//! retried until delivered (see [`crate::outbox`]).
+//!
+//! # Event Streaming
+//!
+//! With an [`EventBus`] attached through [`FacilitatorLocal::with_event_bus`], the
+//! outcome of every settlement is also published to Kafka or NATS (see
+//! [`crate::event_bus`]).
+//!
+//! With [`PaymentEvents`] attached through [`FacilitatorLocal::with_payment_events`],
+//! accepted verifications and every step of a settlement are streamed live to
+//! `GET /events` subscribers (see [`crate::payment_events`]).
+//!
+//! # Payload Log
+//!
+//! With a [`PayloadLog`] attached through [`FacilitatorLocal::with_payload_log`],
+//! every verify and settle request is logged with its outcome, with signatures
+//! and, if configured, addresses redacted (see [`crate::payload_log`]).
+//!
+//! # Velocity Limits
+//!
+//! With a [`VelocityLimiter`] attached through [`FacilitatorLocal::with_velocity_limits`],
+//! payments are rejected at verify and settle time once their payer has settled
+//! too many payments, or too much value, within a window (see [`crate::velocity`]).
+//!
+//! # Guardrail
+//!
+//! With a [`SettlementGuardrail`] attached through [`FacilitatorLocal::with_guardrail`],
+//! settlement is paused, and `/settle` rejected with
+//! [`FacilitatorLocalError::Paused`], once a signer burns too much gas or settles
+//! too much value within an hour, until an operator resumes it (see
+//! [`crate::guardrail`]).
+//!
+//! # Runtime Switches
+//!
+//! Chains and scheme handlers can be disabled while the facilitator runs through
+//! [`FacilitatorLocal::toggles`]. Their payments are then rejected as unsupported and
+//! they are left out of `/supported` (see [`crate::registry`]).
+//!
+//! # Settlement Ledger
+//!
+//! With the `storage` feature and a `SettlementLedger` attached through
+//! `FacilitatorLocal::with_settlement_ledger`, every settlement attempt, including
+//! dead-letter requeues, is recorded for `GET /settlements` (see `crate::ledger`).
+//! Settled payments can then be refunded with `FacilitatorLocal::refund`
+//! (see `crate::refund`).
 
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
 use serde_json::Value;
 use x402_types::facilitator::Facilitator;
@@ -40,6 +120,30 @@ use x402_types::proto::PaymentVerificationError;
 use x402_types::scheme::{SchemeRegistry, X402SchemeFacilitatorError};
 
 use crate::compliance::ComplianceGate;
+use crate::dead_letter::DeadLetterQueue;
+use crate::event_bus::{BusEvent, EventBus};
+use crate::guardrail::SettlementGuardrail;
+use crate::in_flight::InFlightSettlements;
+#[cfg(feature = "storage")]
+use crate::ledger::{LedgerEntry, LedgerQuery, MAX_QUERY_LIMIT, RefundStatus, SettlementLedger};
+use crate::notify::{NotificationDispatcher, NotificationEvent};
+use crate::outbox::Outbox;
+use crate::payload_log::PayloadLog;
+use crate::payment_events::{PaymentEvent, PaymentEventType, PaymentEvents};
+#[cfg(feature = "storage")]
+use crate::refund::{RefundError, RefundOrder};
+use crate::registry::{RegisteredChain, RegisteredScheme, RuntimeToggles};
+#[cfg(feature = "telemetry")]
+use crate::util::metrics::{FacilitatorMetrics, Operation};
+use crate::velocity::VelocityLimiter;
+#[cfg(feature = "storage")]
+use alloy_primitives::U256;
+#[cfg(feature = "storage")]
+use std::collections::HashSet;
+use x402_types::config::NotificationEventKind;
+#[cfg(feature = "storage")]
+use x402_types::config::NotificationSeverity;
+use x402_types::scheme::SchemeHandlerSlug;
 
 /// A local [`Facilitator`](x402_types::facilitator::Facilitator) implementation that delegates to scheme handlers.
 ///
@@ -64,8 +168,21 @@ use crate::compliance::ComplianceGate;
 /// let response = facilitator.verify(&verify_request).await?;
 /// ```
 pub struct FacilitatorLocal<A> {
-    handlers: A,
+    handlers: RwLock<Arc<A>>,
     compliance_gate: ComplianceGate,
+    dead_letters: Option<Arc<DeadLetterQueue>>,
+    in_flight: Option<Arc<InFlightSettlements>>,
+    notifications: Option<Arc<NotificationDispatcher>>,
+    outbox: Option<Arc<Outbox>>,
+    event_bus: Option<Arc<EventBus>>,
+    payment_events: Option<Arc<PaymentEvents>>,
+    payload_log: Option<Arc<PayloadLog>>,
+    velocity: Option<Arc<VelocityLimiter>>,
+    guardrail: Option<Arc<SettlementGuardrail>>,
+    toggles: RuntimeToggles,
+    upconvert_v1: bool,
+    #[cfg(feature = "storage")]
+    ledger: Option<Arc<SettlementLedger>>,
 }
 
 impl<A> FacilitatorLocal<A> {
@@ -79,8 +196,261 @@ impl<A> FacilitatorLocal<A> {
     /// Creates a new [`FacilitatorLocal`] with an explicit compliance policy.
     pub fn new_with_compliance(handlers: A, compliance_gate: ComplianceGate) -> Self {
         Self {
-            handlers,
+            handlers: RwLock::new(Arc::new(handlers)),
             compliance_gate,
+            dead_letters: None,
+            in_flight: None,
+            notifications: None,
+            outbox: None,
+            event_bus: None,
+            payment_events: None,
+            payload_log: None,
+            velocity: None,
+            guardrail: None,
+            toggles: RuntimeToggles::default(),
+            upconvert_v1: false,
+            #[cfg(feature = "storage")]
+            ledger: None,
+        }
+    }
+
+    /// Retries failed settlements and dead-letters them once retries are exhausted.
+    pub fn with_dead_letter_queue(mut self, dead_letters: Arc<DeadLetterQueue>) -> Self {
+        self.dead_letters = Some(dead_letters);
+        self
+    }
+
+    /// Journals settlements in `in_flight` while they run, and turns new ones away once it drains.
+    pub fn with_in_flight_settlements(mut self, in_flight: Arc<InFlightSettlements>) -> Self {
+        self.in_flight = Some(in_flight);
+        self
+    }
+
+    /// Reports settlements through `notifications`.
+    pub fn with_notifications(mut self, notifications: Arc<NotificationDispatcher>) -> Self {
+        self.notifications = Some(notifications);
+        self
+    }
+
+    /// Stores notifications in `outbox` until they are delivered.
+    ///
+    /// Only takes effect together with [`FacilitatorLocal::with_notifications`].
+    pub fn with_outbox(mut self, outbox: Arc<Outbox>) -> Self {
+        self.outbox = Some(outbox);
+        self
+    }
+
+    /// Publishes the outcome of every settlement to `event_bus`.
+    pub fn with_event_bus(mut self, event_bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// Streams accepted verifications and settlement steps to `payment_events` subscribers.
+    pub fn with_payment_events(mut self, payment_events: Arc<PaymentEvents>) -> Self {
+        self.payment_events = Some(payment_events);
+        self
+    }
+
+    /// Logs every verify and settle request, redacted, to `payload_log`.
+    pub fn with_payload_log(mut self, payload_log: Arc<PayloadLog>) -> Self {
+        self.payload_log = Some(payload_log);
+        self
+    }
+
+    /// Rejects payments whose payer exceeds the limits of `velocity`.
+    pub fn with_velocity_limits(mut self, velocity: Arc<VelocityLimiter>) -> Self {
+        self.velocity = Some(velocity);
+        self
+    }
+
+    /// Pauses settlement once a signer exceeds the caps of `guardrail`.
+    pub fn with_guardrail(mut self, guardrail: Arc<SettlementGuardrail>) -> Self {
+        self.guardrail = Some(guardrail);
+        self
+    }
+
+    /// Handles V1 payments with the V2 handler of their chain and scheme when there is
+    /// no V1 handler for them.
+    pub fn with_v1_upconversion(mut self) -> Self {
+        self.upconvert_v1 = true;
+        self
+    }
+
+    /// Records every settlement attempt in `ledger`.
+    #[cfg(feature = "storage")]
+    pub fn with_settlement_ledger(mut self, ledger: Arc<SettlementLedger>) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// Returns the settlement ledger, if one is attached.
+    #[cfg(feature = "storage")]
+    pub fn settlement_ledger(&self) -> Option<&Arc<SettlementLedger>> {
+        self.ledger.as_ref()
+    }
+
+    /// Returns the notification outbox, if one is attached.
+    pub fn outbox(&self) -> Option<&Arc<Outbox>> {
+        self.outbox.as_ref()
+    }
+
+    /// Returns the dead-letter queue, if one is attached.
+    pub fn dead_letters(&self) -> Option<&Arc<DeadLetterQueue>> {
+        self.dead_letters.as_ref()
+    }
+
+    /// Returns the settlement guardrail, if one is attached.
+    pub fn guardrail(&self) -> Option<&Arc<SettlementGuardrail>> {
+        self.guardrail.as_ref()
+    }
+
+    /// Returns the switches disabling chains and scheme handlers at runtime.
+    pub fn toggles(&self) -> &RuntimeToggles {
+        &self.toggles
+    }
+
+    /// Returns the in-flight settlement tracker, if one is attached.
+    pub fn in_flight_settlements(&self) -> Option<&Arc<InFlightSettlements>> {
+        self.in_flight.as_ref()
+    }
+
+    /// Moves the settlements a previous run left unfinished to the dead-letter queue.
+    ///
+    /// Without a dead-letter queue they are left in the journal. Returns the number
+    /// of settlements dead-lettered.
+    pub fn recover_interrupted_settlements(&self) -> usize {
+        let Some(in_flight) = &self.in_flight else {
+            return 0;
+        };
+        let Some(dead_letters) = &self.dead_letters else {
+            let _interrupted = in_flight.interrupted();
+            #[cfg(feature = "telemetry")]
+            if !_interrupted.is_empty() {
+                tracing::error!(
+                    count = _interrupted.len(),
+                    "Settlements were interrupted by a previous shutdown, enable SETTLEMENT_DLQ_ENABLED to requeue or void them"
+                );
+            }
+            return 0;
+        };
+        let interrupted = in_flight.take_interrupted();
+        for entry in &interrupted {
+            let error = format!(
+                "interrupted by shutdown, settlement started at {} may or may not have landed",
+                entry.started_at.as_secs()
+            );
+            let _id = dead_letters.push(&entry.request, &error, 1);
+            #[cfg(feature = "telemetry")]
+            tracing::warn!(dead_letter_id = _id, payer = ?entry.request.payer(), "Interrupted settlement dead-lettered");
+            let kind = NotificationEventKind::SettlementDeadLettered;
+            self.record(kind, &entry.request, None, Some(error), 1);
+        }
+        interrupted.len()
+    }
+
+    /// Returns a snapshot of the current scheme handler registry.
+    pub fn handlers(&self) -> Arc<A> {
+        self.handlers
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Replaces the scheme handler registry, returning the previous one.
+    ///
+    /// Requests already being processed keep using the previous registry.
+    pub fn replace_handlers(&self, handlers: A) -> Arc<A> {
+        let mut current = self
+            .handlers
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::mem::replace(&mut *current, Arc::new(handlers))
+    }
+
+    /// Sends `event` through the outbox if there is one, or directly otherwise.
+    fn notify(&self, event: NotificationEvent) {
+        let Some(notifications) = &self.notifications else {
+            return;
+        };
+        let Some(outbox) = &self.outbox else {
+            notifications.notify(event);
+            return;
+        };
+        let channels: Vec<String> = notifications
+            .route(&event)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        if channels.is_empty() {
+            return;
+        }
+        match outbox.enqueue(event.clone(), channels) {
+            Ok(_) => {
+                let outbox = outbox.clone();
+                let notifications = notifications.clone();
+                tokio::spawn(async move {
+                    // Failures are retried by the periodic outbox run.
+                    let _ = outbox.deliver_due(&notifications).await;
+                });
+            }
+            Err(_e) => {
+                #[cfg(feature = "telemetry")]
+                tracing::error!(error = %_e, "Failed to store notification, delivering once");
+                notifications.notify(event);
+            }
+        }
+    }
+
+    fn publish(
+        &self,
+        kind: NotificationEventKind,
+        request: &proto::SettleRequest,
+        transaction: Option<&str>,
+        reason: Option<String>,
+    ) {
+        if let Some(event_bus) = &self.event_bus {
+            event_bus.publish_settlement(BusEvent::settlement(kind, request, transaction, reason));
+        }
+    }
+
+    #[cfg_attr(not(feature = "storage"), allow(unused_variables))]
+    fn record(
+        &self,
+        kind: NotificationEventKind,
+        request: &proto::SettleRequest,
+        transaction: Option<&str>,
+        reason: Option<String>,
+        attempts: u32,
+    ) {
+        #[cfg(feature = "storage")]
+        if let Some(ledger) = &self.ledger {
+            ledger.record(LedgerEntry::new(
+                kind,
+                request,
+                transaction,
+                reason,
+                attempts,
+            ));
+        }
+    }
+
+    #[cfg_attr(not(feature = "storage"), allow(unused_variables))]
+    fn record_settled(
+        &self,
+        request: &proto::SettleRequest,
+        response: &proto::SettleResponse,
+        attempts: u32,
+    ) {
+        #[cfg(feature = "storage")]
+        if let Some(ledger) = &self.ledger {
+            ledger.record(LedgerEntry::settled(request, response, attempts));
+        }
+    }
+
+    fn stream(&self, event: impl FnOnce() -> PaymentEvent) {
+        if let Some(payment_events) = &self.payment_events {
+            payment_events.publish(event());
         }
     }
 
@@ -90,9 +460,19 @@ impl<A> FacilitatorLocal<A> {
     ) -> Result<(), PaymentVerificationError> {
         let payer_address = request.payer();
         let payee_address = request.payee();
-        self.compliance_gate
+        #[cfg(feature = "telemetry")]
+        let started = std::time::Instant::now();
+        let result = self
+            .compliance_gate
             .validate_for_request("verify", payer_address.as_deref(), payee_address.as_deref())
-            .await
+            .await;
+        #[cfg(feature = "telemetry")]
+        FacilitatorMetrics::global().record_compliance(
+            Operation::Verify,
+            result.is_ok(),
+            started.elapsed(),
+        );
+        result
     }
 
     pub async fn validate_settle_parties(
@@ -101,9 +481,19 @@ impl<A> FacilitatorLocal<A> {
     ) -> Result<(), PaymentVerificationError> {
         let payer_address = request.payer();
         let payee_address = request.payee();
-        self.compliance_gate
+        #[cfg(feature = "telemetry")]
+        let started = std::time::Instant::now();
+        let result = self
+            .compliance_gate
             .validate_for_request("settle", payer_address.as_deref(), payee_address.as_deref())
-            .await
+            .await;
+        #[cfg(feature = "telemetry")]
+        FacilitatorMetrics::global().record_compliance(
+            Operation::Settle,
+            result.is_ok(),
+            started.elapsed(),
+        );
+        result
     }
 
     pub fn log_wallet_connection(
@@ -119,15 +509,397 @@ impl<A> FacilitatorLocal<A> {
     }
 }
 
+/// Counts a settlement that ended with `kind` and records its duration and gas used.
+#[cfg(feature = "telemetry")]
+fn record_settlement_metrics(
+    request: &proto::SettleRequest,
+    kind: NotificationEventKind,
+    duration: std::time::Duration,
+    gas_used: Option<u64>,
+) {
+    let metrics = FacilitatorMetrics::global();
+    let slug = request.scheme_handler_slug();
+    metrics.record_request(Operation::Settle, slug.as_ref(), kind.as_str());
+    metrics.record_settlement(slug.as_ref(), kind.as_str(), duration, gas_used);
+}
+
+fn route_handler<'a>(
+    handlers: &'a SchemeRegistry,
+    toggles: &RuntimeToggles,
+    request: &proto::VerifyRequest,
+) -> Result<&'a dyn x402_types::scheme::X402SchemeFacilitator, FacilitatorLocalError> {
+    let (slug, handler) = request
+        .scheme_handler_slug()
+        .and_then(|slug| handlers.by_slug(&slug).map(|handler| (slug, handler)))
+        .ok_or_else(|| {
+            FacilitatorLocalError::Verification(PaymentVerificationError::UnsupportedScheme.into())
+        })?;
+    toggles
+        .check(&slug)
+        .map_err(FacilitatorLocalError::verification)?;
+    Ok(handler)
+}
+
 impl FacilitatorLocal<SchemeRegistry> {
-    async fn route_handler(
+    /// Returns `request` upgraded to V2 if V1 upconversion is enabled and only a V2
+    /// handler serves it.
+    fn upconverted(&self, request: &proto::VerifyRequest) -> Option<proto::VerifyRequest> {
+        if !self.upconvert_v1 {
+            return None;
+        }
+        let slug = request.scheme_handler_slug()?;
+        let handlers = self.handlers();
+        if slug.x402_version != 1 || handlers.by_slug(&slug).is_some() {
+            return None;
+        }
+        let upgraded = request.to_v2().ok()?;
+        upgraded
+            .scheme_handler_slug()
+            .and_then(|slug| handlers.by_slug(&slug))
+            .map(|_| upgraded)
+    }
+
+    /// Returns the registered scheme handlers, sorted by slug, with what they
+    /// advertise and whether they are enabled.
+    pub async fn registered_schemes(&self) -> Vec<RegisteredScheme> {
+        let handlers = self.handlers();
+        let mut schemes = Vec::new();
+        for (slug, handler) in handlers.iter() {
+            let supported = handler.supported().await.ok();
+            let (kinds, signers) = supported
+                .map(|supported| {
+                    let signers = supported.signers.into_values().flatten().collect();
+                    (supported.kinds, signers)
+                })
+                .unwrap_or_default();
+            schemes.push(RegisteredScheme {
+                slug: slug.to_string(),
+                chain_id: slug.chain_id.clone(),
+                x402_version: slug.x402_version,
+                scheme: slug.name.clone(),
+                enabled: self.toggles.scheme_enabled(slug),
+                kinds,
+                signers,
+            });
+        }
+        schemes.sort_by(|a, b| a.slug.cmp(&b.slug));
+        schemes
+    }
+
+    /// Returns the chains of the registered scheme handlers, sorted by chain ID.
+    pub async fn registered_chains(&self) -> Vec<RegisteredChain> {
+        let mut chains: Vec<RegisteredChain> = Vec::new();
+        for scheme in self.registered_schemes().await {
+            let index = match chains.iter().position(|c| c.chain_id == scheme.chain_id) {
+                Some(index) => index,
+                None => {
+                    chains.push(RegisteredChain {
+                        enabled: self.toggles.chain_enabled(&scheme.chain_id),
+                        chain_id: scheme.chain_id.clone(),
+                        schemes: Vec::new(),
+                        signers: Vec::new(),
+                    });
+                    chains.len() - 1
+                }
+            };
+            let chain = &mut chains[index];
+            chain.schemes.push(scheme.slug);
+            for signer in scheme.signers {
+                if !chain.signers.contains(&signer) {
+                    chain.signers.push(signer);
+                }
+            }
+        }
+        chains.sort_by_key(|chain| chain.chain_id.to_string());
+        chains
+    }
+
+    /// Returns the slug of the registered scheme handler named `slug`, as formatted by
+    /// [`SchemeHandlerSlug`]'s `Display`.
+    pub fn scheme_slug(&self, slug: &str) -> Option<SchemeHandlerSlug> {
+        self.handlers()
+            .iter()
+            .map(|(registered, _)| registered)
+            .find(|registered| registered.to_string() == slug)
+            .cloned()
+    }
+
+    /// Settles `request`, retrying on-chain failures as allowed by the dead-letter queue's policy.
+    ///
+    /// Returns the outcome of the last attempt and the number of attempts made.
+    async fn settle_with_retries(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> (Result<proto::SettleResponse, FacilitatorLocalError>, u32) {
+        if let Some(Err(reason)) = self.guardrail.as_ref().map(|guardrail| guardrail.check()) {
+            return (Err(FacilitatorLocalError::Paused(reason)), 0);
+        }
+        if let Err(e) = self.validate_settle_parties(request).await {
+            return (Err(FacilitatorLocalError::settlement(e)), 0);
+        }
+        let handlers = self.handlers();
+        let handler = match route_handler(&handlers, &self.toggles, request) {
+            Ok(handler) => handler,
+            Err(e) => return (Err(e), 0),
+        };
+        let reservation = match self
+            .velocity
+            .as_ref()
+            .map(|velocity| velocity.reserve(request))
+        {
+            Some(Err(e)) => return (Err(FacilitatorLocalError::settlement(e)), 0),
+            Some(Ok(reservation)) => Some(reservation),
+            None => None,
+        };
+        let retry = self.dead_letters.as_ref().map(|queue| *queue.retry());
+        let max_attempts = retry.map_or(1, |retry| retry.max_attempts);
+        let mut attempt = 1;
+        let result = loop {
+            match handler.settle(request).await {
+                Ok(response) => break Ok(response),
+                Err(X402SchemeFacilitatorError::OnchainFailure(_e)) if attempt < max_attempts => {
+                    let delay = retry
+                        .map(|retry| retry.delay_after(attempt))
+                        .unwrap_or_default();
+                    #[cfg(feature = "telemetry")]
+                    tracing::warn!(error = %_e, attempt, ?delay, "Settlement failed, retrying");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => break Err(FacilitatorLocalError::Settlement(e)),
+            }
+        };
+        if let (Err(_), Some(velocity), Some(reservation)) = (&result, &self.velocity, reservation)
+        {
+            velocity.release(reservation);
+        }
+        if let (Ok(response), Some(guardrail)) = (&result, &self.guardrail) {
+            guardrail.record(request, response);
+        }
+        (result, attempt)
+    }
+
+    /// Settles dead-lettered entry `id` again.
+    ///
+    /// The entry is removed on success. On failure it stays in the queue with the new
+    /// error recorded. Returns `None` if there is no such entry.
+    pub async fn requeue_dead_letter(
+        &self,
+        id: u64,
+    ) -> Option<Result<proto::SettleResponse, FacilitatorLocalError>> {
+        let dead_letters = self.dead_letters.as_ref()?;
+        let entry = dead_letters.get(id)?;
+        let (result, attempts) = self.settle_with_retries(&entry.request).await;
+        match &result {
+            Ok(response) => {
+                dead_letters.remove(id);
+                self.record_settled(&entry.request, response, attempts);
+            }
+            Err(e) => {
+                dead_letters.record_failure(id, &e.to_string(), attempts);
+                let kind = NotificationEventKind::SettlementDeadLettered;
+                self.record(kind, &entry.request, None, Some(e.to_string()), attempts);
+            }
+        }
+        Some(result)
+    }
+
+    /// Sends the settlement `order` references back to its payer, see [`crate::refund`].
+    ///
+    /// The refund is recorded in the settlement ledger and reported to the merchant's
+    /// notification channels.
+    #[cfg(feature = "storage")]
+    pub async fn refund(&self, order: &RefundOrder) -> Result<proto::RefundResponse, RefundError> {
+        let ledger = self.ledger.as_ref().ok_or(RefundError::LedgerDisabled)?;
+        let _refunds = ledger.lock_refunds().await;
+        let settlement = ledger
+            .query(&LedgerQuery {
+                transaction: Some(order.settlement.clone()),
+                limit: Some(MAX_QUERY_LIMIT),
+                ..Default::default()
+            })
+            .map_err(RefundError::Ledger)?
+            .into_iter()
+            .find(|entry| {
+                entry.outcome == NotificationEventKind::SettlementSucceeded
+                    && entry.refund_of.is_none()
+            })
+            .ok_or_else(|| RefundError::SettlementNotFound(order.settlement.clone()))?;
+        let not_refundable = |why| RefundError::NotRefundable(order.settlement.clone(), why);
+        let (Some(network), Some(x402_version), Some(scheme), Some(payer), Some(asset)) = (
+            &settlement.network,
+            settlement.x402_version,
+            &settlement.scheme,
+            &settlement.payer,
+            &settlement.asset,
+        ) else {
+            return Err(not_refundable("the ledger entry lacks the payment details"));
+        };
+        let parse_amount = |amount: &str| {
+            U256::from_str_radix(amount, 10)
+                .map_err(|_| RefundError::InvalidAmount(amount.to_string()))
+        };
+        let settled = parse_amount(
+            settlement
+                .settled_amount
+                .as_deref()
+                .or(settlement.amount.as_deref())
+                .unwrap_or_default(),
+        )?;
+        let refunds = ledger
+            .query(&LedgerQuery {
+                refund_of: Some(order.settlement.clone()),
+                limit: Some(MAX_QUERY_LIMIT),
+                ..Default::default()
+            })
+            .map_err(RefundError::Ledger)?;
+        // The latest entry of a refund tells its progress: pending and sent refunds
+        // count, failed ones do not.
+        let mut refunded = U256::ZERO;
+        let mut counted = HashSet::new();
+        let mut last_number = 0;
+        for refund in refunds {
+            if let Some(number) = refund.refund_number {
+                last_number = last_number.max(number);
+                if !counted.insert(number) {
+                    continue;
+                }
+            }
+            if refund.refund_status != Some(RefundStatus::Failed) {
+                refunded += parse_amount(refund.amount.as_deref().unwrap_or_default())?;
+            }
+        }
+        let remaining = settled.saturating_sub(refunded);
+        let amount = match &order.amount {
+            Some(amount) => parse_amount(amount)?,
+            None => remaining,
+        };
+        if amount.is_zero() {
+            return Err(match order.amount {
+                Some(_) => RefundError::InvalidAmount(amount.to_string()),
+                None => not_refundable("it is fully refunded"),
+            });
+        }
+        if amount > remaining {
+            return Err(RefundError::ExceedsRemaining {
+                requested: amount.to_string(),
+                remaining: remaining.to_string(),
+            });
+        }
+        let chain_id = network
+            .parse()
+            .map_err(|_| not_refundable("the ledger entry has an invalid network"))?;
+        let slug = SchemeHandlerSlug::new(chain_id, x402_version, scheme.clone());
+        let request = proto::RefundRequest {
+            network: slug.chain_id.clone(),
+            asset: asset.clone(),
+            recipient: payer.clone(),
+            amount: amount.to_string(),
+            settlement: settlement
+                .transaction
+                .clone()
+                .unwrap_or_else(|| order.settlement.clone()),
+        };
+        let handlers = self.handlers();
+        let handler = handlers.by_slug(&slug).ok_or_else(|| {
+            RefundError::Scheme(PaymentVerificationError::UnsupportedScheme.into())
+        })?;
+        // Recorded before sending, so a refund that is interrupted after sending is
+        // not sent twice.
+        let pending = LedgerEntry::refund(
+            &settlement,
+            request.amount.clone(),
+            last_number + 1,
+            order.reason.clone(),
+        );
+        ledger.try_record(&pending).map_err(RefundError::Ledger)?;
+        let response = match handler.refund(&request).await {
+            Ok(response) => response,
+            // The refund transaction may have landed, so the refund stays pending.
+            Err(e @ X402SchemeFacilitatorError::OnchainFailure(_)) => {
+                return Err(RefundError::Scheme(e));
+            }
+            Err(e) => {
+                ledger.record(pending.refund_failed(e.to_string()));
+                return Err(RefundError::Scheme(e));
+            }
+        };
+        let transaction = response
+            .0
+            .get("transaction")
+            .and_then(|transaction| transaction.as_str());
+        #[cfg(feature = "telemetry")]
+        tracing::info!(settlement = %request.settlement, amount = %request.amount, transaction = ?transaction, "Payment refunded");
+        let entry = pending.refund_sent(transaction);
+        self.notify(NotificationEvent {
+            id: None,
+            kind: NotificationEventKind::SettlementRefunded,
+            severity: NotificationSeverity::Info,
+            merchant: entry.payee.clone(),
+            payer: entry.payer.clone(),
+            network: entry.network.clone(),
+            message: format!(
+                "refunded {} of settlement {} in transaction {}",
+                request.amount,
+                request.settlement,
+                transaction.unwrap_or("unknown")
+            ),
+            timestamp: entry.recorded_at,
+        });
+        ledger.record(entry);
+        Ok(response)
+    }
+
+    /// Returns the status of subscription `id`, from the first scheme handler that
+    /// tracks it, see `x402_chain_eip155::v2_eip155_recurring`.
+    pub async fn subscription(
+        &self,
+        id: &str,
+    ) -> Result<Option<proto::SubscriptionResponse>, X402SchemeFacilitatorError> {
+        let handlers = self.handlers();
+        for handler in handlers.values() {
+            if let Some(subscription) = handler.subscription(id).await? {
+                return Ok(Some(subscription));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Explains `request` without reading the chain, with the scheme handler it is
+    /// routed to. Returns `None` if no handler serves it, or the handler has no
+    /// explanation for its payloads.
+    pub async fn explain(
         &self,
         request: &proto::VerifyRequest,
-    ) -> Result<&dyn x402_types::scheme::X402SchemeFacilitator, FacilitatorLocalError> {
-        request
+    ) -> Result<Option<proto::ExplainResponse>, X402SchemeFacilitatorError> {
+        let upconverted = self.upconverted(request);
+        let request = upconverted.as_ref().unwrap_or(request);
+        let handlers = self.handlers();
+        match request
             .scheme_handler_slug()
-            .and_then(|slug| self.handlers.by_slug(&slug))
-            .ok_or_else(|| FacilitatorLocalError::Verification(PaymentVerificationError::UnsupportedScheme.into()))
+            .and_then(|slug| handlers.by_slug(&slug))
+        {
+            Some(handler) => handler.explain(request).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Settles the deferred payments that are due with every scheme handler, see
+    /// `x402_chain_eip155::v2_eip155_deferred`. A failing handler does not keep the
+    /// others from flushing.
+    pub async fn flush_deferred(&self) -> Result<(), String> {
+        let handlers = self.handlers();
+        let mut errors = Vec::new();
+        for handler in handlers.values() {
+            if let Err(e) = handler.flush().await {
+                errors.push(e.to_string());
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
     }
 }
 
@@ -138,45 +910,169 @@ impl Facilitator for FacilitatorLocal<SchemeRegistry> {
         &self,
         request: &proto::VerifyRequest,
     ) -> Result<proto::VerifyResponse, Self::Error> {
-        self.validate_verify_parties(request)
-            .await
-            .map_err(|error| FacilitatorLocalError::verification(error))?;
+        let upconverted = self.upconverted(request);
+        let request = upconverted.as_ref().unwrap_or(request);
+        let result = async {
+            self.validate_verify_parties(request)
+                .await
+                .map_err(FacilitatorLocalError::verification)?;
+            if let Some(velocity) = &self.velocity {
+                velocity
+                    .check(request)
+                    .map_err(FacilitatorLocalError::verification)?;
+            }
 
-        let handler = self
-            .route_handler(request)
-            .await?;
-        let response = handler
-            .verify(request)
-            .await
-            .map_err(FacilitatorLocalError::Verification)?;
-        Ok(response)
+            let handlers = self.handlers();
+            let handler = route_handler(&handlers, &self.toggles, request)?;
+            handler
+                .verify(request)
+                .await
+                .map_err(FacilitatorLocalError::Verification)
+        }
+        .await;
+        if let Some(payload_log) = &self.payload_log {
+            payload_log.verify(request, &result);
+        }
+        let valid = matches!(
+            &result,
+            Ok(response) if response.0.get("isValid").and_then(Value::as_bool) == Some(true)
+        );
+        #[cfg(feature = "telemetry")]
+        FacilitatorMetrics::global().record_request(
+            Operation::Verify,
+            request.scheme_handler_slug().as_ref(),
+            match &result {
+                Ok(_) if valid => "valid",
+                Ok(_) => "invalid",
+                Err(_) => "rejected",
+            },
+        );
+        if valid {
+            self.stream(|| PaymentEvent::new(PaymentEventType::VerifyAccepted, request));
+        }
+        result
     }
 
     async fn settle(
         &self,
         request: &proto::SettleRequest,
     ) -> Result<proto::SettleResponse, Self::Error> {
+        let upconverted = self.upconverted(request);
+        let request = upconverted.as_ref().unwrap_or(request);
+        let in_flight = match &self.in_flight {
+            Some(in_flight) => Some(
+                in_flight
+                    .begin(request)
+                    .ok_or(FacilitatorLocalError::ShuttingDown)?,
+            ),
+            None => None,
+        };
+        self.stream(|| PaymentEvent::new(PaymentEventType::SettlementSubmitted, request));
+        #[cfg(feature = "telemetry")]
+        let started = std::time::Instant::now();
+        let (result, attempts) = self.settle_with_retries(request).await;
+        if let Some(in_flight) = in_flight {
+            in_flight.finish();
+        }
+        if let Some(payload_log) = &self.payload_log {
+            payload_log.settle(request, &result);
+        }
+        let error = match &result {
+            Ok(response) => {
+                let transaction = response
+                    .0
+                    .get("transaction")
+                    .and_then(|transaction| transaction.as_str());
+                let kind = NotificationEventKind::SettlementSucceeded;
+                #[cfg(feature = "telemetry")]
+                record_settlement_metrics(
+                    request,
+                    kind,
+                    started.elapsed(),
+                    response.0.get("gasUsed").and_then(Value::as_u64),
+                );
+                self.publish(kind, request, transaction, None);
+                self.record_settled(request, response, attempts);
+                self.stream(|| {
+                    PaymentEvent::new(PaymentEventType::SettlementConfirmed, request)
+                        .with_transaction(transaction)
+                });
+                self.notify(NotificationEvent::for_settlement(
+                    kind,
+                    request,
+                    format!(
+                        "settled in transaction {}",
+                        transaction.unwrap_or("unknown")
+                    ),
+                ));
+                return result;
+            }
+            Err(error) => error,
+        };
+        let onchain_failure = matches!(
+            error,
+            FacilitatorLocalError::Settlement(X402SchemeFacilitatorError::OnchainFailure(_))
+        );
+        let kind = match &self.dead_letters {
+            Some(dead_letters) if onchain_failure => {
+                let _id = dead_letters.push(request, &error.to_string(), attempts);
+                #[cfg(feature = "telemetry")]
+                tracing::error!(dead_letter_id = _id, attempts, error = %error, "Settlement dead-lettered");
+                NotificationEventKind::SettlementDeadLettered
+            }
+            _ if onchain_failure => NotificationEventKind::SettlementFailed,
+            _ => NotificationEventKind::SettlementRejected,
+        };
+        #[cfg(feature = "telemetry")]
+        record_settlement_metrics(request, kind, started.elapsed(), None);
+        self.publish(kind, request, None, Some(error.to_string()));
+        self.record(kind, request, None, Some(error.to_string()), attempts);
+        self.stream(|| {
+            PaymentEvent::new(PaymentEventType::SettlementFailed, request)
+                .with_reason(error.to_string())
+        });
+        self.notify(NotificationEvent::for_settlement(
+            kind,
+            request,
+            error.to_string(),
+        ));
+        result
+    }
+
+    /// Dry runs skip retries, the dead-letter queue, notifications and the event bus.
+    async fn settle_dry_run(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleDryRunResponse, Self::Error> {
+        let upconverted = self.upconverted(request);
+        let request = upconverted.as_ref().unwrap_or(request);
         self.validate_settle_parties(request)
             .await
-            .map_err(|error| FacilitatorLocalError::settlement(error))?;
-
-        let handler = self
-            .route_handler(request)
-            .await?;
-        let response = handler
-            .settle(request)
+            .map_err(FacilitatorLocalError::settlement)?;
+        let handlers = self.handlers();
+        let handler = route_handler(&handlers, &self.toggles, request)?;
+        handler
+            .settle_dry_run(request)
             .await
-            .map_err(FacilitatorLocalError::Settlement)?;
-        Ok(response)
+            .map_err(FacilitatorLocalError::Settlement)
     }
 
     async fn supported(&self) -> Result<proto::SupportedResponse, Self::Error> {
         let mut kinds = vec![];
+        let mut extensions: Vec<String> = vec![];
         let mut signers = HashMap::new();
-        for provider in self.handlers.values() {
+        for (slug, provider) in self.handlers().iter() {
+            if !self.toggles.scheme_enabled(slug) {
+                continue;
+            }
             let supported = provider.supported().await.ok();
             if let Some(mut supported) = supported {
                 kinds.append(&mut supported.kinds);
+                for extension in supported.extensions {
+                    if !extensions.contains(&extension) {
+                        extensions.push(extension);
+                    }
+                }
                 for (chain_id, signer_addresses) in supported.signers {
                     signers.entry(chain_id).or_insert(signer_addresses);
                 }
@@ -184,7 +1080,7 @@ impl Facilitator for FacilitatorLocal<SchemeRegistry> {
         }
         Ok(proto::SupportedResponse {
             kinds,
-            extensions: Vec::new(),
+            extensions,
             signers,
         })
     }
@@ -219,4 +1115,70 @@ pub enum FacilitatorLocalError {
     /// typically due to transaction failures or network issues.
     #[error(transparent)]
     Settlement(X402SchemeFacilitatorError),
+    /// The facilitator is draining before shutdown and takes no new settlements.
+    #[error("facilitator is shutting down")]
+    ShuttingDown,
+    /// Settlement is paused by the guardrail, for the given reason, until resumed.
+    #[error("settlement is paused: {0}")]
+    Paused(String),
+}
+
+#[cfg(all(test, feature = "storage"))]
+mod tests {
+    use super::*;
+    use crate::ledger::MemoryLedgerStore;
+
+    fn order(amount: &str) -> RefundOrder {
+        RefundOrder {
+            settlement: "0xabc".to_string(),
+            amount: Some(amount.to_string()),
+            reason: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refunds_count_pending_refunds_against_the_settled_amount() {
+        let ledger = Arc::new(SettlementLedger::new(MemoryLedgerStore::default()));
+        let request = proto::SettleRequest::from(serde_json::json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "x402Version": 2,
+                "accepted": {"scheme": "exact", "network": "eip155:42793"},
+                "payload": {"authorization": {"from": "0xaaa0000000000000000000000000000000000001"}},
+            },
+            "paymentRequirements": {"amount": "1000", "asset": "0xbbb0000000000000000000000000000000000002"},
+        }));
+        let response = proto::SettleResponse(serde_json::json!({
+            "success": true,
+            "transaction": "0xabc",
+            "settledAmount": "400",
+        }));
+        let settlement = LedgerEntry::settled(&request, &response, 1);
+        ledger.record(settlement.clone());
+        let facilitator =
+            FacilitatorLocal::new(SchemeRegistry::default()).with_settlement_ledger(ledger.clone());
+        let remaining = |result| match result {
+            Err(RefundError::ExceedsRemaining { remaining, .. }) => Some(remaining),
+            // The amount is refundable, but no scheme handler serves the payment here.
+            Err(RefundError::Scheme(_)) => None,
+            other => panic!("unexpected refund outcome: {other:?}"),
+        };
+
+        // Only what was settled can be refunded.
+        assert_eq!(
+            remaining(facilitator.refund(&order("401")).await).as_deref(),
+            Some("400")
+        );
+        assert_eq!(remaining(facilitator.refund(&order("400")).await), None);
+
+        let pending = LedgerEntry::refund(&settlement, "300".to_string(), 1, None);
+        ledger.record(pending.clone());
+        assert_eq!(
+            remaining(facilitator.refund(&order("200")).await).as_deref(),
+            Some("100")
+        );
+
+        ledger.record(pending.refund_failed("treasury is empty".to_string()));
+        assert_eq!(remaining(facilitator.refund(&order("200")).await), None);
+    }
 }
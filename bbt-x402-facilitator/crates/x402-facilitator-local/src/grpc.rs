@@ -0,0 +1,986 @@
+//! gRPC interface of the facilitator, served next to the HTTP API.
+//!
+//! [`GrpcFacilitator`] exposes the `x402.facilitator.v1.Facilitator` service of
+//! `proto/facilitator.proto` on top of the same facilitator as the HTTP handlers:
+//!
+//! | RPC | HTTP counterpart |
+//! |-----|------------------|
+//! | `Verify` | `POST /verify` |
+//! | `Settle` | `POST /settle` |
+//! | `Supported` | `GET /supported` |
+//! | `SettlementEvents` (server streaming) | the `settlement.*` events of `GET /events` |
+//!
+//! Payment payloads and requirements are scheme-specific and travel as the JSON
+//! objects of the HTTP API, while responses are typed messages. Rejected payments
+//! are answered with `is_valid: false` or `success: false` and the error reason
+//! code of the HTTP API; on-chain failures with the `INTERNAL` status, and settlements
+//! during shutdown or while the chain has no settlement slot free with `UNAVAILABLE`.
+//!
+//! With an [`ApiKeyAuth`] attached, `Verify` and `SettlementEvents` require a
+//! `verify` key and `Settle` a `settle` key, given in the `authorization: Bearer`
+//! or `x-api-key` metadata. With a [`RateLimiter`] attached, requests over the
+//! limits are answered with `RESOURCE_EXHAUSTED`, and `Settle` counts against the
+//! daily settlement cap of the key, or of the client address without a valid key.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use x402_facilitator_local::grpc::GrpcFacilitator;
+//!
+//! let service = GrpcFacilitator::new(facilitator)
+//!     .with_payment_events(payment_events)
+//!     .with_api_key_auth(api_key_auth)
+//!     .into_service();
+//! tonic::transport::Server::builder()
+//!     .add_service(service)
+//!     .serve(addr)
+//!     .await?;
+//! ```
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use alloy_primitives::U256;
+use futures_util::{Stream, stream};
+use serde_json::{Map, Value};
+use tokio::sync::broadcast::error::RecvError;
+use tonic::{Request, Response, Status};
+use x402_types::config::ApiKeyScope;
+use x402_types::facilitator::Facilitator;
+use x402_types::proto;
+use x402_types::proto::AsPaymentProblem;
+use x402_types::scheme::X402SchemeFacilitatorError;
+
+use crate::auth::{ApiKeyAuth, ApiKeyIdentity, with_api_key};
+use crate::facilitator_local::FacilitatorLocalError;
+use crate::payment_events::{PaymentEvent, PaymentEventFilter, PaymentEventType, PaymentEvents};
+use crate::rate_limit::{RateLimitDecision, RateLimiter, api_key_from_headers};
+
+/// Messages and service of `proto/facilitator.proto`.
+pub mod pb {
+    /// How long `Settle` waits for the settlement transaction.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum ConfirmationPolicy {
+        /// Same as [`ConfirmationPolicy::Confirmed`].
+        Unspecified = 0,
+        /// Wait for the confirmations the chain is configured to require.
+        Confirmed = 1,
+        /// Respond as soon as the transaction is broadcast.
+        Pending = 2,
+    }
+
+    /// Request of `Verify`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct VerifyRequest {
+        /// Protocol version of the payment, 1 or 2.
+        #[prost(uint32, tag = "1")]
+        pub x402_version: u32,
+        /// The signed payment as a JSON object, or the base64 string of the payment header.
+        #[prost(bytes = "vec", tag = "2")]
+        pub payment_payload: Vec<u8>,
+        /// The payment requirements as a JSON object.
+        #[prost(bytes = "vec", tag = "3")]
+        pub payment_requirements: Vec<u8>,
+    }
+
+    /// Request of `Settle`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SettleRequest {
+        /// Protocol version of the payment, 1 or 2.
+        #[prost(uint32, tag = "1")]
+        pub x402_version: u32,
+        /// The signed payment as a JSON object, or the base64 string of the payment header.
+        #[prost(bytes = "vec", tag = "2")]
+        pub payment_payload: Vec<u8>,
+        /// The payment requirements as a JSON object.
+        #[prost(bytes = "vec", tag = "3")]
+        pub payment_requirements: Vec<u8>,
+        /// How long to wait for the settlement transaction.
+        #[prost(enumeration = "ConfirmationPolicy", tag = "4")]
+        pub confirmation_policy: i32,
+        /// Amount to settle, for schemes settling less than an authorized maximum (`upto`).
+        #[prost(string, optional, tag = "5")]
+        pub settle_amount: Option<String>,
+    }
+
+    /// Response of `Verify`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct VerifyResponse {
+        #[prost(bool, tag = "1")]
+        pub is_valid: bool,
+        /// Address of the payer. Empty when the payment could not be decoded.
+        #[prost(string, tag = "2")]
+        pub payer: String,
+        /// Error reason code of the HTTP API, e.g. `invalid_signature`. Empty when valid.
+        #[prost(string, tag = "3")]
+        pub invalid_reason: String,
+        #[prost(string, tag = "4")]
+        pub invalid_reason_details: String,
+        /// Machine-readable details of the rejection, as a JSON object. Empty when there are none.
+        #[prost(bytes = "vec", tag = "5")]
+        pub invalid_reason_context: Vec<u8>,
+    }
+
+    /// Response of `Settle`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SettleResponse {
+        #[prost(bool, tag = "1")]
+        pub success: bool,
+        /// Error reason code of the HTTP API, e.g. `insufficient_funds`. Empty on success.
+        #[prost(string, tag = "2")]
+        pub error_reason: String,
+        #[prost(string, tag = "3")]
+        pub error_reason_details: String,
+        #[prost(string, tag = "4")]
+        pub payer: String,
+        /// Hash of the settlement transaction.
+        #[prost(string, tag = "5")]
+        pub transaction: String,
+        #[prost(string, tag = "6")]
+        pub network: String,
+        #[prost(uint64, optional, tag = "7")]
+        pub block_number: Option<u64>,
+        #[prost(uint64, optional, tag = "8")]
+        pub gas_used: Option<u64>,
+        /// Price paid per unit of gas, in wei.
+        #[prost(string, optional, tag = "9")]
+        pub effective_gas_price: Option<String>,
+        /// Fee the facilitator charged, in the smallest unit of the asset.
+        #[prost(string, optional, tag = "10")]
+        pub facilitator_fee: Option<String>,
+        /// Amount transferred to the payee, in the smallest unit of the asset.
+        #[prost(string, optional, tag = "11")]
+        pub settled_amount: Option<String>,
+        #[prost(uint64, optional, tag = "12")]
+        pub confirmations: Option<u64>,
+        #[prost(uint64, optional, tag = "13")]
+        pub required_confirmations: Option<u64>,
+        /// `pending` or `confirmed`.
+        #[prost(string, optional, tag = "14")]
+        pub confirmation_status: Option<String>,
+        /// Signed settlement promise of a deferred payment, as a JSON object. Empty otherwise.
+        #[prost(bytes = "vec", tag = "15")]
+        pub iou: Vec<u8>,
+        /// Machine-readable details of the failure, as a JSON object. Empty when there are none.
+        #[prost(bytes = "vec", tag = "16")]
+        pub error_reason_context: Vec<u8>,
+    }
+
+    /// Request of `Supported`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SupportedRequest {}
+
+    /// A supported payment kind.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SupportedKind {
+        #[prost(uint32, tag = "1")]
+        pub x402_version: u32,
+        #[prost(string, tag = "2")]
+        pub scheme: String,
+        #[prost(string, tag = "3")]
+        pub network: String,
+        /// Scheme-specific data as a JSON object. Empty when there is none.
+        #[prost(bytes = "vec", tag = "4")]
+        pub extra: Vec<u8>,
+    }
+
+    /// Signer addresses of a chain.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Signers {
+        #[prost(string, repeated, tag = "1")]
+        pub addresses: Vec<String>,
+    }
+
+    /// Response of `Supported`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SupportedResponse {
+        #[prost(message, repeated, tag = "1")]
+        pub kinds: Vec<SupportedKind>,
+        #[prost(string, repeated, tag = "2")]
+        pub extensions: Vec<String>,
+        /// Signer addresses per chain ID.
+        #[prost(map = "string, message", tag = "3")]
+        pub signers: std::collections::HashMap<String, Signers>,
+    }
+
+    /// Request of `SettlementEvents`. Every field given must match.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SettlementEventsRequest {
+        #[prost(string, optional, tag = "1")]
+        pub payer: Option<String>,
+        #[prost(string, optional, tag = "2")]
+        pub payee: Option<String>,
+        /// CAIP-2 chain ID.
+        #[prost(string, optional, tag = "3")]
+        pub chain: Option<String>,
+    }
+
+    /// A settlement step, streamed by `SettlementEvents`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SettlementEvent {
+        /// `settlement.submitted`, `settlement.confirmed`, `settlement.failed`, or
+        /// `lagged` when the subscriber read too slowly and missed events.
+        #[prost(string, tag = "1")]
+        pub event_type: String,
+        #[prost(uint64, tag = "2")]
+        pub timestamp_ms: u64,
+        #[prost(string, optional, tag = "3")]
+        pub network: Option<String>,
+        #[prost(string, optional, tag = "4")]
+        pub payer: Option<String>,
+        #[prost(string, optional, tag = "5")]
+        pub payee: Option<String>,
+        #[prost(string, optional, tag = "6")]
+        pub amount: Option<String>,
+        #[prost(string, optional, tag = "7")]
+        pub asset: Option<String>,
+        #[prost(string, optional, tag = "8")]
+        pub transaction: Option<String>,
+        /// Why the settlement failed.
+        #[prost(string, optional, tag = "9")]
+        pub reason: Option<String>,
+        /// Number of events missed, on `lagged` events.
+        #[prost(uint64, tag = "10")]
+        pub skipped: u64,
+    }
+
+    include!(concat!(
+        env!("OUT_DIR"),
+        "/x402.facilitator.v1.Facilitator.rs"
+    ));
+}
+
+pub use pb::facilitator_server::FacilitatorServer;
+
+/// Serves a facilitator over gRPC, see the [module documentation](self).
+pub struct GrpcFacilitator<A> {
+    facilitator: A,
+    payment_events: Option<Arc<PaymentEvents>>,
+    api_key_auth: Option<Arc<ApiKeyAuth>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl<A> GrpcFacilitator<A> {
+    /// Serves `facilitator`, without authentication or rate limits.
+    pub fn new(facilitator: A) -> Self {
+        Self {
+            facilitator,
+            payment_events: None,
+            api_key_auth: None,
+            rate_limiter: None,
+        }
+    }
+
+    /// Streams the settlement events of `payment_events` on `SettlementEvents`.
+    ///
+    /// Without it, `SettlementEvents` answers `UNIMPLEMENTED`.
+    pub fn with_payment_events(mut self, payment_events: Arc<PaymentEvents>) -> Self {
+        self.payment_events = Some(payment_events);
+        self
+    }
+
+    /// Requires API keys on `Verify`, `Settle` and `SettlementEvents`.
+    pub fn with_api_key_auth(mut self, api_key_auth: Arc<ApiKeyAuth>) -> Self {
+        self.api_key_auth = Some(api_key_auth);
+        self
+    }
+
+    /// Enforces the per-IP and per-API-key limits and the daily settlement cap of `rate_limiter`.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Wraps the facilitator into the tonic service to add to a server.
+    pub fn into_service(self) -> FacilitatorServer<Self>
+    where
+        Self: pb::facilitator_server::Facilitator,
+    {
+        FacilitatorServer::new(self)
+    }
+
+    /// Checks the API key of `request` and applies the rate limits.
+    ///
+    /// Returns the client the daily settlement cap is counted for (see
+    /// [`crate::rate_limit`]), and the key identity when `required` is enforced.
+    fn authorize<T>(
+        &self,
+        request: &Request<T>,
+        required: Option<ApiKeyScope>,
+    ) -> Result<(Option<String>, Option<ApiKeyIdentity>), Status> {
+        let headers = request.metadata().clone().into_headers();
+        let identity = self
+            .api_key_auth
+            .as_ref()
+            .zip(api_key_from_headers(&headers))
+            .and_then(|(api_key_auth, key)| api_key_auth.identify(key))
+            .cloned();
+        if let (Some(_), Some(required)) = (&self.api_key_auth, required) {
+            let identity = identity
+                .as_ref()
+                .ok_or_else(|| Status::unauthenticated("missing or invalid API key"))?;
+            if !identity.allows(required) {
+                #[cfg(feature = "telemetry")]
+                tracing::warn!(api_key_id = %identity.id, scope = %identity.scope, required = %required, "API key scope insufficient");
+                return Err(Status::permission_denied(
+                    "API key scope does not permit this method",
+                ));
+            }
+        }
+        let key_id = identity.as_ref().map(|identity| identity.id.as_str());
+        let ip = request.remote_addr().map(|addr| addr.ip().to_string());
+        if let Some(rate_limiter) = &self.rate_limiter
+            && let RateLimitDecision::Limited { reason, .. } =
+                rate_limiter.check_request(ip.as_deref(), key_id)
+        {
+            return Err(Status::resource_exhausted(reason));
+        }
+        let client = RateLimiter::client_id(key_id, ip.as_deref());
+        Ok((client, identity.filter(|_| required.is_some())))
+    }
+}
+
+type SettlementEventStream =
+    Pin<Box<dyn Stream<Item = Result<pb::SettlementEvent, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl<A> pb::facilitator_server::Facilitator for GrpcFacilitator<A>
+where
+    A: Facilitator<Error = FacilitatorLocalError> + Send + Sync + 'static,
+{
+    async fn verify(
+        &self,
+        request: Request<pb::VerifyRequest>,
+    ) -> Result<Response<pb::VerifyResponse>, Status> {
+        let (_, identity) = self.authorize(&request, Some(ApiKeyScope::Verify))?;
+        let request = request.into_inner();
+        let body = payment_request(
+            request.x402_version,
+            &request.payment_payload,
+            &request.payment_requirements,
+        )?;
+        let body = proto::VerifyRequest::from(Value::Object(body)).with_decoded_payment_payload();
+        let response = match with_api_key(identity, self.facilitator.verify(&body)).await {
+            Ok(response) => verify_response(response.0),
+            Err(FacilitatorLocalError::Verification(error)) => invalid_verify_response(error)?,
+            Err(FacilitatorLocalError::Settlement(error)) => invalid_verify_response(error)?,
+            Err(
+                error @ (FacilitatorLocalError::ShuttingDown | FacilitatorLocalError::Paused(_)),
+            ) => {
+                return Err(Status::unavailable(error.to_string()));
+            }
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn settle(
+        &self,
+        request: Request<pb::SettleRequest>,
+    ) -> Result<Response<pb::SettleResponse>, Status> {
+        let (client, identity) = self.authorize(&request, Some(ApiKeyScope::Settle))?;
+        let request = request.into_inner();
+        let mut body = payment_request(
+            request.x402_version,
+            &request.payment_payload,
+            &request.payment_requirements,
+        )?;
+        if request.confirmation_policy() == pb::ConfirmationPolicy::Pending {
+            body.insert("confirmationPolicy".to_string(), Value::from("pending"));
+        }
+        if let Some(settle_amount) = request.settle_amount {
+            body.insert("settleAmount".to_string(), Value::from(settle_amount));
+        }
+        let body = proto::SettleRequest::from(Value::Object(body)).with_decoded_payment_payload();
+
+        let reservation = match (&self.rate_limiter, client) {
+            (Some(rate_limiter), Some(client)) => {
+                let amount = body
+                    .transfer_amount()
+                    .and_then(|amount| U256::from_str(&amount).ok());
+                match (body.asset(), amount) {
+                    (Some(asset), Some(amount)) => {
+                        if let RateLimitDecision::Limited { reason, .. } =
+                            rate_limiter.reserve_settlement(&client, &asset, amount)
+                        {
+                            return Err(Status::resource_exhausted(reason));
+                        }
+                        Some((rate_limiter, client, asset, amount))
+                    }
+                    _ if rate_limiter.config().daily_settle_cap.is_some() => {
+                        return Err(Status::invalid_argument(
+                            "asset or amount could not be read from the request",
+                        ));
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        let response = match with_api_key(identity, self.facilitator.settle(&body)).await {
+            Ok(response) => Ok(settle_response(response.0)),
+            Err(FacilitatorLocalError::Settlement(error)) => invalid_settle_response(error),
+            Err(FacilitatorLocalError::Verification(error)) => invalid_settle_response(error),
+            Err(
+                error @ (FacilitatorLocalError::ShuttingDown | FacilitatorLocalError::Paused(_)),
+            ) => Err(Status::unavailable(error.to_string())),
+        };
+        if let Some((rate_limiter, client, asset, amount)) = reservation {
+            match &response {
+                Ok(response) if response.success => {
+                    let settled = response
+                        .settled_amount
+                        .as_deref()
+                        .and_then(|settled| U256::from_str(settled).ok());
+                    if let Some(settled) = settled {
+                        rate_limiter.reconcile_settlement(&client, &asset, amount, settled);
+                    }
+                }
+                _ => rate_limiter.release_settlement(&client, &asset, amount),
+            }
+        }
+        response.map(Response::new)
+    }
+
+    async fn supported(
+        &self,
+        request: Request<pb::SupportedRequest>,
+    ) -> Result<Response<pb::SupportedResponse>, Status> {
+        self.authorize(&request, None)?;
+        let supported = self
+            .facilitator
+            .supported()
+            .await
+            .map_err(|error| Status::internal(error.to_string()))?;
+        Ok(Response::new(supported_response(supported)))
+    }
+
+    type SettlementEventsStream = SettlementEventStream;
+
+    async fn settlement_events(
+        &self,
+        request: Request<pb::SettlementEventsRequest>,
+    ) -> Result<Response<Self::SettlementEventsStream>, Status> {
+        self.authorize(&request, Some(ApiKeyScope::Verify))?;
+        let Some(payment_events) = &self.payment_events else {
+            return Err(Status::unimplemented("settlement events are not enabled"));
+        };
+        let request = request.into_inner();
+        let filter = PaymentEventFilter {
+            payer: request.payer,
+            payee: request.payee,
+            chain: request.chain,
+        };
+        let receiver = payment_events.subscribe();
+        let events = stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(event)
+                        if event.event_type != PaymentEventType::VerifyAccepted
+                            && filter.matches(&event) =>
+                    {
+                        settlement_event(event)
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => pb::SettlementEvent {
+                        event_type: "lagged".to_string(),
+                        skipped,
+                        ..Default::default()
+                    },
+                    Err(RecvError::Closed) => return None,
+                };
+                return Some((Ok(event), (receiver, filter)));
+            }
+        });
+        Ok(Response::new(Box::pin(events)))
+    }
+}
+
+/// Builds the JSON body of the HTTP API from the members of a gRPC request.
+fn payment_request(
+    x402_version: u32,
+    payment_payload: &[u8],
+    payment_requirements: &[u8],
+) -> Result<Map<String, Value>, Status> {
+    // A payload that is not JSON is taken as the base64 string of the payment header.
+    let payment_payload = serde_json::from_slice::<Value>(payment_payload)
+        .or_else(|_| {
+            std::str::from_utf8(payment_payload).map(|encoded| Value::from(encoded.trim()))
+        })
+        .map_err(|_| Status::invalid_argument("payment_payload is neither JSON nor base64"))?;
+    let payment_requirements = serde_json::from_slice::<Value>(payment_requirements)
+        .ok()
+        .filter(Value::is_object)
+        .ok_or_else(|| Status::invalid_argument("payment_requirements is not a JSON object"))?;
+    let mut body = Map::new();
+    body.insert("x402Version".to_string(), Value::from(x402_version));
+    body.insert("paymentPayload".to_string(), payment_payload);
+    body.insert("paymentRequirements".to_string(), payment_requirements);
+    Ok(body)
+}
+
+fn string_member(json: &Value, name: &str) -> Option<String> {
+    json.get(name).and_then(Value::as_str).map(str::to_string)
+}
+
+/// A JSON member as bytes, empty when absent or null.
+fn json_member_bytes(json: &Value, name: &str) -> Vec<u8> {
+    json.get(name)
+        .filter(|value| !value.is_null())
+        .map(|value| value.to_string().into_bytes())
+        .unwrap_or_default()
+}
+
+/// Reason code, details and JSON context bytes of a rejected payment.
+fn error_reason(error: &X402SchemeFacilitatorError) -> Result<(String, String, Vec<u8>), Status> {
+    let problem = error.as_payment_problem();
+    match error {
+        X402SchemeFacilitatorError::PaymentVerification(_) => {
+            let reason = serde_json::to_value(problem.reason())
+                .ok()
+                .and_then(|reason| reason.as_str().map(str::to_string))
+                .unwrap_or_default();
+            let context = problem
+                .context()
+                .map(|context| context.to_string().into_bytes())
+                .unwrap_or_default();
+            Ok((reason, problem.details().to_string(), context))
+        }
+        X402SchemeFacilitatorError::OnchainFailure(_) => Err(Status::internal(problem.details())),
+        X402SchemeFacilitatorError::Overloaded { .. } => {
+            Err(Status::unavailable(problem.details()))
+        }
+    }
+}
+
+fn verify_response(json: Value) -> pb::VerifyResponse {
+    pb::VerifyResponse {
+        is_valid: json
+            .get("isValid")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        payer: string_member(&json, "payer").unwrap_or_default(),
+        invalid_reason: string_member(&json, "invalidReason").unwrap_or_default(),
+        invalid_reason_details: string_member(&json, "invalidReasonDetails").unwrap_or_default(),
+        invalid_reason_context: json_member_bytes(&json, "invalidReasonContext"),
+    }
+}
+
+fn invalid_verify_response(
+    error: X402SchemeFacilitatorError,
+) -> Result<pb::VerifyResponse, Status> {
+    let (invalid_reason, invalid_reason_details, invalid_reason_context) = error_reason(&error)?;
+    Ok(pb::VerifyResponse {
+        is_valid: false,
+        invalid_reason,
+        invalid_reason_details,
+        invalid_reason_context,
+        ..Default::default()
+    })
+}
+
+fn settle_response(json: Value) -> pb::SettleResponse {
+    let u64_member = |name: &str| json.get(name).and_then(Value::as_u64);
+    pb::SettleResponse {
+        success: json
+            .get("success")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        error_reason: string_member(&json, "errorReason").unwrap_or_default(),
+        error_reason_details: string_member(&json, "errorReasonDetails").unwrap_or_default(),
+        payer: string_member(&json, "payer").unwrap_or_default(),
+        transaction: string_member(&json, "transaction").unwrap_or_default(),
+        network: string_member(&json, "network").unwrap_or_default(),
+        block_number: u64_member("blockNumber"),
+        gas_used: u64_member("gasUsed"),
+        effective_gas_price: string_member(&json, "effectiveGasPrice"),
+        facilitator_fee: string_member(&json, "facilitatorFee"),
+        settled_amount: string_member(&json, "settledAmount"),
+        confirmations: u64_member("confirmations"),
+        required_confirmations: u64_member("requiredConfirmations"),
+        confirmation_status: string_member(&json, "confirmationStatus"),
+        iou: json_member_bytes(&json, "iou"),
+        error_reason_context: json_member_bytes(&json, "errorReasonContext"),
+    }
+}
+
+fn invalid_settle_response(
+    error: X402SchemeFacilitatorError,
+) -> Result<pb::SettleResponse, Status> {
+    let (error_reason, error_reason_details, error_reason_context) = error_reason(&error)?;
+    Ok(pb::SettleResponse {
+        success: false,
+        error_reason,
+        error_reason_details,
+        error_reason_context,
+        ..Default::default()
+    })
+}
+
+fn supported_response(supported: proto::SupportedResponse) -> pb::SupportedResponse {
+    pb::SupportedResponse {
+        kinds: supported
+            .kinds
+            .into_iter()
+            .map(|kind| pb::SupportedKind {
+                x402_version: kind.x402_version.into(),
+                scheme: kind.scheme,
+                network: kind.network,
+                extra: kind
+                    .extra
+                    .map(|extra| extra.to_string().into_bytes())
+                    .unwrap_or_default(),
+            })
+            .collect(),
+        extensions: supported.extensions,
+        signers: supported
+            .signers
+            .into_iter()
+            .map(|(chain_id, addresses)| (chain_id.to_string(), pb::Signers { addresses }))
+            .collect::<HashMap<_, _>>(),
+    }
+}
+
+fn settlement_event(event: PaymentEvent) -> pb::SettlementEvent {
+    pb::SettlementEvent {
+        event_type: event.event_type.as_str().to_string(),
+        timestamp_ms: event.timestamp_ms,
+        network: event.network,
+        payer: event.payer,
+        payee: event.payee,
+        amount: event.amount,
+        asset: event.asset,
+        transaction: event.transaction,
+        reason: event.reason,
+        skipped: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures_util::StreamExt;
+    use serde_json::json;
+    use x402_types::proto::PaymentVerificationError;
+
+    use super::pb::facilitator_server::Facilitator as _;
+    use super::*;
+
+    const PAYER: &str = "0x1111111111111111111111111111111111111111";
+
+    #[derive(Default)]
+    struct MockFacilitator {
+        requests: Mutex<Vec<Value>>,
+        error: Option<fn() -> X402SchemeFacilitatorError>,
+    }
+
+    impl MockFacilitator {
+        fn failing(error: fn() -> X402SchemeFacilitatorError) -> Self {
+            Self {
+                error: Some(error),
+                ..Default::default()
+            }
+        }
+
+        fn record(&self, request: &proto::VerifyRequest) {
+            self.requests
+                .lock()
+                .unwrap()
+                .push(request.clone().into_json());
+        }
+    }
+
+    impl Facilitator for MockFacilitator {
+        type Error = FacilitatorLocalError;
+
+        async fn verify(
+            &self,
+            request: &proto::VerifyRequest,
+        ) -> Result<proto::VerifyResponse, Self::Error> {
+            self.record(request);
+            match self.error {
+                Some(error) => Err(FacilitatorLocalError::Verification(error())),
+                None => Ok(proto::VerifyResponse(
+                    json!({ "isValid": true, "payer": PAYER }),
+                )),
+            }
+        }
+
+        async fn settle(
+            &self,
+            request: &proto::SettleRequest,
+        ) -> Result<proto::SettleResponse, Self::Error> {
+            self.record(request);
+            match self.error {
+                Some(error) => Err(FacilitatorLocalError::Settlement(error())),
+                None => Ok(proto::SettleResponse(json!({
+                    "success": true,
+                    "payer": PAYER,
+                    "transaction": "0xabc",
+                    "network": "eip155:42793",
+                    "blockNumber": 7,
+                    "confirmationStatus": "confirmed",
+                }))),
+            }
+        }
+
+        async fn settle_dry_run(
+            &self,
+            _request: &proto::SettleRequest,
+        ) -> Result<proto::SettleDryRunResponse, Self::Error> {
+            Ok(proto::SettleDryRunResponse(json!({})))
+        }
+
+        async fn supported(&self) -> Result<proto::SupportedResponse, Self::Error> {
+            Ok(proto::SupportedResponse {
+                kinds: vec![proto::SupportedPaymentKind {
+                    x402_version: 2,
+                    scheme: "exact".to_string(),
+                    network: "eip155:42793".to_string(),
+                    extra: None,
+                }],
+                ..Default::default()
+            })
+        }
+    }
+
+    fn verify_request() -> pb::VerifyRequest {
+        pb::VerifyRequest {
+            x402_version: 2,
+            payment_payload: br#"{"x402Version":2,"payload":{}}"#.to_vec(),
+            payment_requirements: br#"{"scheme":"exact","amount":"1000"}"#.to_vec(),
+        }
+    }
+
+    fn with_key<T>(message: T, key: &str) -> Request<T> {
+        let mut request = Request::new(message);
+        request
+            .metadata_mut()
+            .insert("x-api-key", key.parse().unwrap());
+        request
+    }
+
+    #[tokio::test]
+    async fn test_verify_builds_http_request_body() {
+        let grpc = GrpcFacilitator::new(MockFacilitator::default());
+
+        let response = grpc
+            .verify(Request::new(verify_request()))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(response.is_valid);
+        assert_eq!(response.payer, PAYER);
+        let requests = grpc.facilitator.requests.lock().unwrap();
+        assert_eq!(requests[0]["x402Version"], 2);
+        assert_eq!(requests[0]["paymentRequirements"]["amount"], "1000");
+        assert_eq!(requests[0]["paymentPayload"]["x402Version"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_invalid_requirements() {
+        let grpc = GrpcFacilitator::new(MockFacilitator::default());
+        let mut request = verify_request();
+        request.payment_requirements = b"[]".to_vec();
+
+        let status = grpc.verify(Request::new(request)).await.unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_payment_is_an_invalid_response() {
+        let grpc = GrpcFacilitator::new(MockFacilitator::failing(|| {
+            PaymentVerificationError::InvalidSignature("bad signature".to_string()).into()
+        }));
+
+        let response = grpc
+            .verify(Request::new(verify_request()))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(!response.is_valid);
+        assert_eq!(response.invalid_reason, "invalid_signature");
+        assert_eq!(response.invalid_reason_details, "bad signature");
+        assert!(response.invalid_reason_context.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rejected_payment_carries_its_context() {
+        let grpc = GrpcFacilitator::new(MockFacilitator::failing(|| {
+            PaymentVerificationError::invalid_amount(1000, 999).into()
+        }));
+
+        let response = grpc
+            .verify(Request::new(verify_request()))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(response.invalid_reason, "invalid_payment_amount");
+        let context: Value = serde_json::from_slice(&response.invalid_reason_context).unwrap();
+        assert_eq!(
+            context,
+            serde_json::json!({"required": "1000", "provided": "999"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_settle_forwards_policy_and_amount() {
+        let grpc = GrpcFacilitator::new(MockFacilitator::default());
+        let request = pb::SettleRequest {
+            x402_version: 2,
+            payment_payload: br#"{"x402Version":2,"payload":{}}"#.to_vec(),
+            payment_requirements: br#"{"scheme":"upto"}"#.to_vec(),
+            confirmation_policy: pb::ConfirmationPolicy::Pending.into(),
+            settle_amount: Some("500".to_string()),
+        };
+
+        let response = grpc
+            .settle(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(response.success);
+        assert_eq!(response.transaction, "0xabc");
+        assert_eq!(response.block_number, Some(7));
+        assert_eq!(response.confirmation_status.as_deref(), Some("confirmed"));
+        assert!(response.iou.is_empty());
+        let requests = grpc.facilitator.requests.lock().unwrap();
+        assert_eq!(requests[0]["confirmationPolicy"], "pending");
+        assert_eq!(requests[0]["settleAmount"], "500");
+    }
+
+    #[tokio::test]
+    async fn test_onchain_failure_is_internal_status() {
+        let grpc = GrpcFacilitator::new(MockFacilitator::failing(|| {
+            X402SchemeFacilitatorError::OnchainFailure("reverted".to_string())
+        }));
+        let request = pb::SettleRequest {
+            x402_version: 2,
+            payment_payload: br#"{"x402Version":2,"payload":{}}"#.to_vec(),
+            payment_requirements: b"{}".to_vec(),
+            ..Default::default()
+        };
+
+        let status = grpc.settle(Request::new(request)).await.unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::Internal);
+    }
+
+    #[tokio::test]
+    async fn test_api_key_scopes() {
+        let mut api_key_auth = ApiKeyAuth::new();
+        api_key_auth.insert("pricing-probe", "verify-key", ApiKeyScope::Verify);
+        let grpc = GrpcFacilitator::new(MockFacilitator::default())
+            .with_api_key_auth(Arc::new(api_key_auth));
+        let settle_request = pb::SettleRequest {
+            x402_version: 2,
+            payment_payload: b"{}".to_vec(),
+            payment_requirements: b"{}".to_vec(),
+            ..Default::default()
+        };
+
+        let status = grpc
+            .verify(Request::new(verify_request()))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        let status = grpc
+            .verify(with_key(verify_request(), "other-key"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        assert!(
+            grpc.verify(with_key(verify_request(), "verify-key"))
+                .await
+                .is_ok()
+        );
+        let status = grpc
+            .settle(with_key(settle_request, "verify-key"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert!(
+            grpc.supported(Request::new(pb::SupportedRequest {}))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_supported_response() {
+        let grpc = GrpcFacilitator::new(MockFacilitator::default());
+
+        let response = grpc
+            .supported(Request::new(pb::SupportedRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(response.kinds.len(), 1);
+        assert_eq!(response.kinds[0].x402_version, 2);
+        assert_eq!(response.kinds[0].network, "eip155:42793");
+        assert!(response.kinds[0].extra.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_settlement_events_skip_verifications() {
+        let payment_events = Arc::new(PaymentEvents::new(8));
+        let grpc = GrpcFacilitator::new(MockFacilitator::default())
+            .with_payment_events(payment_events.clone());
+        let request = pb::SettlementEventsRequest {
+            payer: Some(PAYER.to_string()),
+            ..Default::default()
+        };
+        let mut events = grpc
+            .settlement_events(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let event = |event_type, payer: &str| PaymentEvent {
+            event_type,
+            timestamp_ms: 0,
+            network: Some("eip155:42793".to_string()),
+            payer: Some(payer.to_string()),
+            payee: None,
+            amount: Some("1000".to_string()),
+            asset: None,
+            transaction: None,
+            reason: None,
+        };
+        payment_events.publish(event(PaymentEventType::VerifyAccepted, PAYER));
+        payment_events.publish(event(
+            PaymentEventType::SettlementSubmitted,
+            "0x2222222222222222222222222222222222222222",
+        ));
+        payment_events.publish(
+            event(PaymentEventType::SettlementConfirmed, PAYER).with_transaction(Some("0xabc")),
+        );
+
+        let received = events.next().await.unwrap().unwrap();
+        assert_eq!(received.event_type, "settlement.confirmed");
+        assert_eq!(received.transaction.as_deref(), Some("0xabc"));
+        assert_eq!(received.amount.as_deref(), Some("1000"));
+    }
+
+    #[tokio::test]
+    async fn test_settlement_events_need_payment_events() {
+        let grpc = GrpcFacilitator::new(MockFacilitator::default());
+
+        let status = grpc
+            .settlement_events(Request::new(pb::SettlementEventsRequest::default()))
+            .await
+            .err()
+            .unwrap();
+
+        assert_eq!(status.code(), tonic::Code::Unimplemented);
+    }
+}
@@ -0,0 +1,339 @@
+//! Signer spending caps with an automatic kill-switch.
+//!
+//! [`SettlementGuardrail`] tracks the gas each facilitator signer burns, and the
+//! value it settles, within a sliding hour. When a signer goes above a cap, as a
+//! leaked API key or a runaway client would make it, all settlement is paused:
+//! `/settle` answers `503 Service Unavailable` with the reason until an operator
+//! clears the pause with `POST /admin/resume`. Verification keeps working.
+//!
+//! Usage is taken from the settle responses: the `signer` that submitted the
+//! transaction, its `gasUsed`, and the `settledAmount`, or else the request's
+//! [`transfer_amount`](x402_types::proto::VerifyRequest::transfer_amount), of the
+//! payment asset. Value is tracked separately for every asset, in the asset's
+//! smallest unit. Settlements that report no signer, such as deferred payments
+//! accepted without a transaction, are not counted. With a value cap, a settlement
+//! whose asset or amount cannot be read pauses settlement, as it cannot be counted.
+//!
+//! # Configuration
+//!
+//! | Variable | Description |
+//! |----------|-------------|
+//! | `GUARDRAIL_MAX_GAS_PER_HOUR` | Gas per signer and network within an hour |
+//! | `GUARDRAIL_MAX_VALUE_PER_HOUR` | Settled value per signer, network and asset within an hour |
+//!
+//! Without either variable, settlement is never paused.
+//!
+//! Usage and the pause are kept in memory, per facilitator instance.
+
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use alloy_primitives::U256;
+use serde::Serialize;
+use serde_json::Value;
+use x402_types::proto;
+
+/// Window the caps apply to.
+const WINDOW: Duration = Duration::from_secs(3600);
+
+/// A settlement counted against the caps of its signer.
+#[derive(Debug)]
+struct Usage {
+    at: Instant,
+    gas: u64,
+    asset: String,
+    amount: U256,
+}
+
+/// Why and since when settlement is paused.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuardrailPause {
+    pub reason: String,
+    /// Unix timestamp, in seconds, of the pause.
+    pub paused_at: u64,
+}
+
+/// Usage of a signer within the last hour.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignerUsage {
+    pub network: String,
+    pub signer: String,
+    pub gas_used: u64,
+    /// Settled value per asset, as decimal strings.
+    pub value: HashMap<String, String>,
+}
+
+/// State of the guardrail, as reported by `GET /admin/guardrail`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuardrailStatus {
+    pub max_gas_per_hour: Option<u64>,
+    pub max_value_per_hour: Option<String>,
+    pub paused: Option<GuardrailPause>,
+    pub signers: Vec<SignerUsage>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    usage: HashMap<(String, String), VecDeque<Usage>>,
+    paused: Option<GuardrailPause>,
+}
+
+/// Pauses settlement when a signer exceeds its caps, see the [module documentation](self).
+#[derive(Debug)]
+pub struct SettlementGuardrail {
+    max_gas: Option<u64>,
+    max_value: Option<U256>,
+    state: Mutex<State>,
+}
+
+impl SettlementGuardrail {
+    /// Creates a guardrail capping the gas and the value per asset each signer
+    /// settles within an hour. `None` leaves a measure uncapped.
+    pub fn new(max_gas: Option<u64>, max_value: Option<U256>) -> Self {
+        Self {
+            max_gas,
+            max_value,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Creates a guardrail from `GUARDRAIL_MAX_GAS_PER_HOUR` and
+    /// `GUARDRAIL_MAX_VALUE_PER_HOUR`.
+    ///
+    /// Returns `Ok(None)` when neither is set.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let max_gas = non_empty_var("GUARDRAIL_MAX_GAS_PER_HOUR")
+            .map(|value| u64::from_str(value.trim()))
+            .transpose()
+            .map_err(|e| format!("invalid GUARDRAIL_MAX_GAS_PER_HOUR: {e}"))?;
+        let max_value = non_empty_var("GUARDRAIL_MAX_VALUE_PER_HOUR")
+            .map(|value| U256::from_str(value.trim()))
+            .transpose()
+            .map_err(|e| format!("invalid GUARDRAIL_MAX_VALUE_PER_HOUR: {e}"))?;
+        if max_gas.is_none() && max_value.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self::new(max_gas, max_value)))
+    }
+
+    /// Returns the reason settlement is paused, if it is.
+    pub fn check(&self) -> Result<(), String> {
+        let state = self.state.lock().expect("guardrail lock poisoned");
+        match &state.paused {
+            Some(pause) => Err(pause.reason.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// Counts a successful settlement of `request`, pausing settlement if its signer
+    /// is now above a cap.
+    pub fn record(&self, request: &proto::SettleRequest, response: &proto::SettleResponse) {
+        self.record_at(request, response, Instant::now());
+    }
+
+    /// Clears the pause and the usage counted so far.
+    ///
+    /// Returns the pause that was cleared, if settlement was paused.
+    pub fn resume(&self) -> Option<GuardrailPause> {
+        let mut state = self.state.lock().expect("guardrail lock poisoned");
+        state.usage.clear();
+        state.paused.take()
+    }
+
+    /// Returns the caps, the pause and the usage of every signer within the last hour.
+    pub fn status(&self) -> GuardrailStatus {
+        let now = Instant::now();
+        let state = self.state.lock().expect("guardrail lock poisoned");
+        let mut signers = state
+            .usage
+            .iter()
+            .map(|((network, signer), history)| {
+                let recent = history
+                    .iter()
+                    .filter(|usage| now.duration_since(usage.at) < WINDOW);
+                let mut value: HashMap<String, U256> = HashMap::new();
+                let mut gas_used = 0u64;
+                for usage in recent {
+                    gas_used = gas_used.saturating_add(usage.gas);
+                    let total = value.entry(usage.asset.clone()).or_default();
+                    *total = total.saturating_add(usage.amount);
+                }
+                SignerUsage {
+                    network: network.clone(),
+                    signer: signer.clone(),
+                    gas_used,
+                    value: value
+                        .into_iter()
+                        .map(|(asset, amount)| (asset, amount.to_string()))
+                        .collect(),
+                }
+            })
+            .collect::<Vec<_>>();
+        signers.sort_by(|a, b| (&a.network, &a.signer).cmp(&(&b.network, &b.signer)));
+        GuardrailStatus {
+            max_gas_per_hour: self.max_gas,
+            max_value_per_hour: self.max_value.map(|value| value.to_string()),
+            paused: state.paused.clone(),
+            signers,
+        }
+    }
+
+    fn record_at(
+        &self,
+        request: &proto::SettleRequest,
+        response: &proto::SettleResponse,
+        now: Instant,
+    ) {
+        let Some(signer) = response.0.get("signer").and_then(Value::as_str) else {
+            return;
+        };
+        let network = response
+            .0
+            .get("network")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let signer = signer.to_lowercase();
+        let gas = response
+            .0
+            .get("gasUsed")
+            .and_then(Value::as_u64)
+            .unwrap_or_default();
+        let asset = request.asset().map(|asset| asset.to_lowercase());
+        let amount = response
+            .0
+            .get("settledAmount")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| request.transfer_amount())
+            .and_then(|amount| U256::from_str(&amount).ok());
+        let unreadable = asset.is_none() || amount.is_none();
+        let asset = asset.unwrap_or_default();
+        let amount = amount.unwrap_or_default();
+
+        let mut state = self.state.lock().expect("guardrail lock poisoned");
+        state.usage.retain(|_, history| {
+            history
+                .back()
+                .is_some_and(|usage| now.duration_since(usage.at) < WINDOW)
+        });
+        let history = state
+            .usage
+            .entry((network.clone(), signer.clone()))
+            .or_default();
+        while history
+            .front()
+            .is_some_and(|usage| now.duration_since(usage.at) >= WINDOW)
+        {
+            history.pop_front();
+        }
+        history.push_back(Usage {
+            at: now,
+            gas,
+            asset: asset.clone(),
+            amount,
+        });
+        let gas_used = history
+            .iter()
+            .fold(0u64, |total, usage| total.saturating_add(usage.gas));
+        let value = history
+            .iter()
+            .filter(|usage| usage.asset == asset)
+            .fold(U256::ZERO, |total, usage| {
+                total.saturating_add(usage.amount)
+            });
+
+        if state.paused.is_some() {
+            return;
+        }
+        let reason = match (self.max_gas, self.max_value) {
+            (Some(cap), _) if gas_used > cap => format!(
+                "signer {signer} on {network} used {gas_used} gas within an hour, above the cap of {cap}"
+            ),
+            (_, Some(cap)) if value > cap => format!(
+                "signer {signer} on {network} settled {value} of {asset} within an hour, above the cap of {cap}"
+            ),
+            (_, Some(_)) if unreadable => format!(
+                "signer {signer} on {network} settled a payment whose asset or amount could not be read"
+            ),
+            _ => return,
+        };
+        #[cfg(feature = "telemetry")]
+        tracing::error!(%reason, "Settlement paused by guardrail");
+        let paused_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        state.paused = Some(GuardrailPause { reason, paused_at });
+    }
+}
+
+fn non_empty_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGNER: &str = "0xAAA0000000000000000000000000000000000001";
+    const USDC: &str = "0xbbb0000000000000000000000000000000000002";
+
+    fn settlement(gas: u64, amount: u64) -> (proto::SettleRequest, proto::SettleResponse) {
+        let request = proto::SettleRequest::from(serde_json::json!({
+            "x402Version": 2,
+            "paymentPayload": {"x402Version": 2},
+            "paymentRequirements": {"asset": USDC, "amount": "1"},
+        }));
+        let response = proto::SettleResponse(serde_json::json!({
+            "success": true,
+            "network": "eip155:42793",
+            "signer": SIGNER,
+            "gasUsed": gas,
+            "settledAmount": amount.to_string(),
+        }));
+        (request, response)
+    }
+
+    #[test]
+    fn test_caps_pause_until_resumed() {
+        let guardrail = SettlementGuardrail::new(Some(100_000), Some(U256::from(1_000)));
+        let start = Instant::now();
+
+        let (request, response) = settlement(60_000, 400);
+        guardrail.record_at(&request, &response, start);
+        assert_eq!(guardrail.check(), Ok(()));
+
+        // Usage older than an hour no longer counts.
+        let later = start + WINDOW;
+        guardrail.record_at(&request, &response, later);
+        assert_eq!(guardrail.check(), Ok(()));
+
+        let (request, response) = settlement(10_000, 700);
+        guardrail.record_at(&request, &response, later);
+        let reason = guardrail.check().unwrap_err();
+        assert!(reason.contains("settled 1100"), "{reason}");
+        assert_eq!(guardrail.status().signers[0].gas_used, 70_000);
+
+        assert!(guardrail.resume().is_some());
+        assert_eq!(guardrail.check(), Ok(()));
+        assert!(guardrail.status().signers.is_empty());
+        assert!(guardrail.resume().is_none());
+
+        // Value that cannot be read cannot be capped.
+        let request = proto::SettleRequest::from(serde_json::json!({
+            "x402Version": 2,
+            "paymentPayload": {"x402Version": 2},
+            "paymentRequirements": {"amount": "1"},
+        }));
+        guardrail.record_at(&request, &response, later);
+        let reason = guardrail.check().unwrap_err();
+        assert!(reason.contains("could not be read"), "{reason}");
+    }
+}
@@ -9,15 +9,20 @@
 //! Each endpoint consumes or produces structured JSON payloads defined in `x402-rs`,
 //! and is compatible with official x402 client SDKs.
 
+use std::convert::Infallible;
 use std::sync::Arc;
 
-use axum::extract::State;
-use axum::http::{HeaderMap, StatusCode};
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
 use axum::response::Response;
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::routing::{get, post};
 use axum::{Json, Router, response::IntoResponse};
+use futures_util::{Stream, stream};
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde_json::{Value, json};
+use tokio::sync::broadcast::error::RecvError;
+use x402_types::chain::ChainId;
 use x402_types::facilitator::Facilitator;
 use x402_types::proto;
 use x402_types::proto::{AsPaymentProblem, ErrorReason};
@@ -26,7 +31,20 @@ use x402_types::scheme::{SchemeRegistry, X402SchemeFacilitatorError};
 #[cfg(feature = "telemetry")]
 use tracing::instrument;
 
+use crate::auth::{ApiKeyAuth, require_api_key};
+use crate::cluster::Cluster;
+use crate::compliance::GeoBlocker;
+use crate::compliance::geo::enforce_geo_blocking;
+use crate::discovery::{DiscoveryCatalog, DiscoveryError, DiscoveryQuery, ResourceRegistration};
 use crate::facilitator_local::{FacilitatorLocal, FacilitatorLocalError};
+#[cfg(feature = "storage")]
+use crate::ledger::{LedgerQuery, SettlementLedger};
+use crate::payment_events::{PaymentEventFilter, PaymentEvents};
+use crate::rate_limit::{RateLimiter, enforce_rate_limit};
+#[cfg(feature = "storage")]
+use crate::refund::{RefundError, RefundOrder};
+use crate::response_format::{ResponseAmountFormat, format_response_amounts};
+use crate::util::Scheduler;
 
 /// `POST /compliance/connect`: Records wallet-connection attempts for audit and observability.
 #[cfg_attr(feature = "telemetry", instrument(skip_all))]
@@ -52,10 +70,14 @@ pub(crate) async fn post_wallet_connect_event(
         .unwrap_or_else(|| "unknown".to_string());
 
     if let Some(meta) = metadata.as_object_mut() {
-            meta.insert("remoteAddress".to_string(), json!(remote_addr));
+        meta.insert("remoteAddress".to_string(), json!(remote_addr));
         meta.insert(
             "source".to_string(),
-            json!(body.source.clone().unwrap_or_else(|| "wallet_client".to_string())),
+            json!(
+                body.source
+                    .clone()
+                    .unwrap_or_else(|| "wallet_client".to_string())
+            ),
         );
     }
 
@@ -158,11 +180,644 @@ where
         .route("/supported", get(get_supported::<A>))
 }
 
+/// Creates the facilitator router of [`routes`] guarded by a [`RateLimiter`].
+///
+/// Requests exceeding the per-IP or per-API-key limits, or the per-client daily
+/// settlement cap, are rejected with `429 Too Many Requests` and a `Retry-After` header.
+///
+/// API keys are only known once authenticated, so wrap the router in
+/// [`authenticated_routes`] when keys are configured. Client IPs are taken from the
+/// connection, so the router must be served with
+/// `into_make_service_with_connect_info::<SocketAddr>()` unless `X-Forwarded-For`
+/// is trusted.
+pub fn rate_limited_routes<A>(limiter: Arc<RateLimiter>) -> Router<A>
+where
+    A: Facilitator + Clone + Send + Sync + 'static,
+    A::Error: IntoResponse,
+{
+    routes().layer(axum::middleware::from_fn_with_state(
+        limiter,
+        enforce_rate_limit,
+    ))
+}
+
+/// Guards `POST /verify` and `POST /settle` of the given router with API keys.
+///
+/// Requests without a valid key get `401 Unauthorized`; `verify`-scoped keys get
+/// `403 Forbidden` on `/settle`. The remaining routes are left public.
+pub fn authenticated_routes<A>(routes: Router<A>, auth: Arc<ApiKeyAuth>) -> Router<A>
+where
+    A: Clone + Send + Sync + 'static,
+{
+    routes.layer(axum::middleware::from_fn_with_state(auth, require_api_key))
+}
+
+/// Rejects `POST /verify` and `POST /settle` of the given router from blocked countries.
+///
+/// Blocked requests get `451 Unavailable For Legal Reasons`. Like the rate limiter,
+/// client IPs are taken from the connection unless `X-Forwarded-For` is trusted.
+pub fn geo_blocked_routes<A>(routes: Router<A>, blocker: Arc<GeoBlocker>) -> Router<A>
+where
+    A: Clone + Send + Sync + 'static,
+{
+    routes.layer(axum::middleware::from_fn_with_state(
+        blocker,
+        enforce_geo_blocking,
+    ))
+}
+
+/// Writes the amounts of the given router's JSON responses in the current
+/// [`ResponseAmountFormat`], see [`crate::response_format`].
+pub fn amount_formatted_routes<A>(routes: Router<A>, format: Arc<ResponseAmountFormat>) -> Router<A>
+where
+    A: Clone + Send + Sync + 'static,
+{
+    routes.layer(axum::middleware::from_fn_with_state(
+        format,
+        format_response_amounts,
+    ))
+}
+
 /// Routes for x402 compliance/audit helpers.
 pub fn compliance_routes() -> Router<Arc<FacilitatorLocal<SchemeRegistry>>> {
     Router::new().route("/compliance/connect", post(post_wallet_connect_event))
 }
 
+/// Admin routes inspecting the settlement dead-letter queue, see [`crate::dead_letter`].
+///
+/// The routes answer `404 Not Found` when no queue is attached to the facilitator.
+/// Guard them with [`authenticated_routes`]: they require an `admin` API key.
+pub fn dead_letter_routes() -> Router<Arc<FacilitatorLocal<SchemeRegistry>>> {
+    Router::new()
+        .route("/admin/dlq", get(get_dead_letters))
+        .route("/admin/dlq/{id}", get(get_dead_letter))
+        .route("/admin/dlq/{id}/requeue", post(post_requeue_dead_letter))
+        .route("/admin/dlq/{id}/void", post(post_void_dead_letter))
+}
+
+fn dead_letter_not_found() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({ "error": "not_found", "details": "no such dead-letter entry" })),
+    )
+        .into_response()
+}
+
+/// `GET /admin/dlq`: Returns the queue depth, oldest entry age and all entries.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn get_dead_letters(
+    State(facilitator): State<Arc<FacilitatorLocal<SchemeRegistry>>>,
+) -> Response {
+    let Some(dead_letters) = facilitator.dead_letters() else {
+        return dead_letter_not_found();
+    };
+    let stats = dead_letters.stats();
+    Json(json!({
+        "depth": stats.depth,
+        "oldestAgeSecs": stats.oldest_age_secs,
+        "entries": dead_letters.entries(),
+    }))
+    .into_response()
+}
+
+/// `GET /admin/dlq/{id}`: Returns a single dead-lettered settlement.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn get_dead_letter(
+    State(facilitator): State<Arc<FacilitatorLocal<SchemeRegistry>>>,
+    Path(id): Path<u64>,
+) -> Response {
+    match facilitator.dead_letters().and_then(|queue| queue.get(id)) {
+        Some(entry) => Json(entry).into_response(),
+        None => dead_letter_not_found(),
+    }
+}
+
+/// `POST /admin/dlq/{id}/requeue`: Settles the entry again.
+///
+/// Responds like `POST /settle`. The entry is removed if the settlement succeeds.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn post_requeue_dead_letter(
+    State(facilitator): State<Arc<FacilitatorLocal<SchemeRegistry>>>,
+    Path(id): Path<u64>,
+) -> Response {
+    match facilitator.requeue_dead_letter(id).await {
+        Some(Ok(response)) => (StatusCode::OK, Json(response)).into_response(),
+        Some(Err(error)) => error.into_response(),
+        None => dead_letter_not_found(),
+    }
+}
+
+/// `POST /admin/dlq/{id}/void`: Drops the entry without settling it.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn post_void_dead_letter(
+    State(facilitator): State<Arc<FacilitatorLocal<SchemeRegistry>>>,
+    Path(id): Path<u64>,
+) -> Response {
+    match facilitator
+        .dead_letters()
+        .and_then(|queue| queue.remove(id))
+    {
+        Some(entry) => {
+            #[cfg(feature = "telemetry")]
+            tracing::info!(dead_letter_id = id, "Dead-lettered settlement voided");
+            Json(entry).into_response()
+        }
+        None => dead_letter_not_found(),
+    }
+}
+
+/// Admin routes listing the registered chains and scheme handlers, and disabling
+/// them at runtime, see [`crate::registry`].
+///
+/// Guard them with [`authenticated_routes`]: they require an `admin` API key.
+pub fn registry_routes() -> Router<Arc<FacilitatorLocal<SchemeRegistry>>> {
+    Router::new()
+        .route("/admin/schemes", get(get_registered_schemes))
+        .route("/admin/schemes/{slug}/{action}", post(post_scheme_toggle))
+        .route("/admin/chains", get(get_registered_chains))
+        .route("/admin/chains/{chain_id}/{action}", post(post_chain_toggle))
+}
+
+fn registry_not_found(details: String) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({ "error": "not_found", "details": details })),
+    )
+        .into_response()
+}
+
+/// Reads the `{action}` of a toggle route: `true` for `enable`, `false` for `disable`.
+fn toggle_action(action: &str) -> Option<bool> {
+    match action {
+        "enable" => Some(true),
+        "disable" => Some(false),
+        _ => None,
+    }
+}
+
+/// `GET /admin/schemes`: Lists the scheme handlers, what they advertise and whether they are enabled.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn get_registered_schemes(
+    State(facilitator): State<Arc<FacilitatorLocal<SchemeRegistry>>>,
+) -> Response {
+    Json(facilitator.registered_schemes().await).into_response()
+}
+
+/// `GET /admin/chains`: Lists the chains with their scheme handlers and signers.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn get_registered_chains(
+    State(facilitator): State<Arc<FacilitatorLocal<SchemeRegistry>>>,
+) -> Response {
+    Json(facilitator.registered_chains().await).into_response()
+}
+
+/// `POST /admin/schemes/{slug}/enable` and `/disable`: Switches a scheme handler on or off.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn post_scheme_toggle(
+    State(facilitator): State<Arc<FacilitatorLocal<SchemeRegistry>>>,
+    Path((slug, action)): Path<(String, String)>,
+) -> Response {
+    let Some(enabled) = toggle_action(&action) else {
+        return registry_not_found(format!("unknown action: {action}"));
+    };
+    let Some(slug) = facilitator.scheme_slug(&slug) else {
+        return registry_not_found(format!("unknown scheme handler: {slug}"));
+    };
+    let changed = facilitator.toggles().set_scheme_enabled(&slug, enabled);
+    #[cfg(feature = "telemetry")]
+    tracing::warn!(slug = %slug, enabled, "Scheme handler switched at runtime");
+    Json(json!({ "slug": slug.to_string(), "enabled": enabled, "changed": changed }))
+        .into_response()
+}
+
+/// `POST /admin/chains/{chain_id}/enable` and `/disable`: Switches all scheme handlers of a chain on or off.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn post_chain_toggle(
+    State(facilitator): State<Arc<FacilitatorLocal<SchemeRegistry>>>,
+    Path((chain_id, action)): Path<(String, String)>,
+) -> Response {
+    let Some(enabled) = toggle_action(&action) else {
+        return registry_not_found(format!("unknown action: {action}"));
+    };
+    let registered = chain_id.parse::<ChainId>().ok().filter(|chain_id| {
+        facilitator
+            .handlers()
+            .iter()
+            .any(|(slug, _)| &slug.chain_id == chain_id)
+    });
+    let Some(chain_id) = registered else {
+        return registry_not_found(format!("unknown chain: {chain_id}"));
+    };
+    let changed = facilitator.toggles().set_chain_enabled(&chain_id, enabled);
+    #[cfg(feature = "telemetry")]
+    tracing::warn!(chain_id = %chain_id, enabled, "Chain switched at runtime");
+    Json(json!({ "chainId": chain_id, "enabled": enabled, "changed": changed })).into_response()
+}
+
+/// Admin routes inspecting and resuming the settlement guardrail, see [`crate::guardrail`].
+///
+/// The routes answer `404 Not Found` when no guardrail is attached to the facilitator.
+/// Guard them with [`authenticated_routes`]: they require an `admin` API key.
+pub fn guardrail_routes() -> Router<Arc<FacilitatorLocal<SchemeRegistry>>> {
+    Router::new()
+        .route("/admin/guardrail", get(get_guardrail))
+        .route("/admin/resume", post(post_resume))
+}
+
+fn guardrail_not_found() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({ "error": "not_found", "details": "no settlement guardrail configured" })),
+    )
+        .into_response()
+}
+
+/// `GET /admin/guardrail`: Returns the caps, the pause and the usage of every signer.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn get_guardrail(
+    State(facilitator): State<Arc<FacilitatorLocal<SchemeRegistry>>>,
+) -> Response {
+    match facilitator.guardrail() {
+        Some(guardrail) => Json(guardrail.status()).into_response(),
+        None => guardrail_not_found(),
+    }
+}
+
+/// `POST /admin/resume`: Clears the pause and the usage counted so far.
+///
+/// Responds with the pause that was cleared, `null` if settlement was not paused.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn post_resume(State(facilitator): State<Arc<FacilitatorLocal<SchemeRegistry>>>) -> Response {
+    let Some(guardrail) = facilitator.guardrail() else {
+        return guardrail_not_found();
+    };
+    let cleared = guardrail.resume();
+    #[cfg(feature = "telemetry")]
+    if let Some(pause) = &cleared {
+        tracing::info!(reason = %pause.reason, "Settlement resumed");
+    }
+    Json(json!({ "resumed": cleared })).into_response()
+}
+
+/// Routes streaming payment activity, see [`crate::payment_events`].
+///
+/// Guard them with [`authenticated_routes`] when API keys are configured: they
+/// require a `verify` API key.
+pub fn payment_event_routes() -> Router<Arc<PaymentEvents>> {
+    Router::new().route("/events", get(get_payment_events))
+}
+
+/// `GET /events`: Streams payment events matching the query as Server-Sent Events.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn get_payment_events(
+    State(payment_events): State<Arc<PaymentEvents>>,
+    Query(filter): Query<PaymentEventFilter>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = payment_events.subscribe();
+    let events = stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) if filter.matches(&event) => Event::default()
+                    .event(event.event_type.as_str())
+                    .data(serde_json::to_string(&event).unwrap_or_default()),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => Event::default()
+                    .event("lagged")
+                    .data(json!({ "skipped": skipped }).to_string()),
+                Err(RecvError::Closed) => return None,
+            };
+            return Some((Ok(event), (receiver, filter)));
+        }
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Routes of the resource catalog, see [`crate::discovery`].
+///
+/// Guard them with [`authenticated_routes`] when API keys are configured:
+/// registering requires a `settle` API key, querying stays public.
+pub fn discovery_routes() -> Router<Arc<DiscoveryCatalog>> {
+    Router::new().route(
+        "/discovery/resources",
+        get(get_discovery_resources).post(post_discovery_resource),
+    )
+}
+
+/// `GET /discovery/resources`: Returns the listed endpoints matching the query.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn get_discovery_resources(
+    State(catalog): State<Arc<DiscoveryCatalog>>,
+    Query(query): Query<DiscoveryQuery>,
+) -> Response {
+    Json(catalog.query(&query)).into_response()
+}
+
+/// `POST /discovery/resources`: Lists an endpoint, replacing its previous entry.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn post_discovery_resource(
+    State(catalog): State<Arc<DiscoveryCatalog>>,
+    Json(registration): Json<ResourceRegistration>,
+) -> Response {
+    match catalog.register(registration) {
+        Ok(resource) => (StatusCode::CREATED, Json(resource)).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+impl IntoResponse for DiscoveryError {
+    fn into_response(self) -> Response {
+        let (status, error) = match self {
+            DiscoveryError::InvalidUrl(_)
+            | DiscoveryError::InvalidOption { .. }
+            | DiscoveryError::InvalidOptions(_) => (StatusCode::BAD_REQUEST, "invalid_resource"),
+            DiscoveryError::CatalogFull(_) => (StatusCode::INSUFFICIENT_STORAGE, "catalog_full"),
+        };
+        (
+            status,
+            Json(json!({ "error": error, "details": self.to_string() })),
+        )
+            .into_response()
+    }
+}
+
+/// Routes querying the settlement ledger, see [`crate::ledger`].
+///
+/// Guard them with [`authenticated_routes`]: they require an `admin` API key.
+#[cfg(feature = "storage")]
+pub fn settlement_ledger_routes() -> Router<Arc<SettlementLedger>> {
+    Router::new().route("/settlements", get(get_settlements))
+}
+
+/// `GET /settlements`: Returns the recorded settlement attempts matching the query, most recent first.
+#[cfg(feature = "storage")]
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn get_settlements(
+    State(ledger): State<Arc<SettlementLedger>>,
+    Query(query): Query<LedgerQuery>,
+) -> Response {
+    match ledger.query(&query) {
+        Ok(entries) => Json(json!({ "entries": entries })).into_response(),
+        Err(_e) => {
+            #[cfg(feature = "telemetry")]
+            tracing::error!(error = %_e, "Settlement ledger query failed");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": "internal_error", "details": "settlement ledger query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Admin routes refunding settled payments, see [`crate::refund`].
+///
+/// The routes answer `404 Not Found` when no settlement ledger is attached to the
+/// facilitator. Guard them with [`authenticated_routes`]: they require an `admin` API key.
+#[cfg(feature = "storage")]
+pub fn refund_routes() -> Router<Arc<FacilitatorLocal<SchemeRegistry>>> {
+    Router::new().route("/refund", post(post_refund))
+}
+
+/// `POST /refund`: Sends a settled payment, or part of it, back to its payer.
+#[cfg(feature = "storage")]
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn post_refund(
+    State(facilitator): State<Arc<FacilitatorLocal<SchemeRegistry>>>,
+    Json(order): Json<RefundOrder>,
+) -> Response {
+    match facilitator.refund(&order).await {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+#[cfg(feature = "storage")]
+impl IntoResponse for RefundError {
+    fn into_response(self) -> Response {
+        let (status, error) = match self {
+            RefundError::LedgerDisabled | RefundError::SettlementNotFound(_) => {
+                (StatusCode::NOT_FOUND, "not_found")
+            }
+            RefundError::NotRefundable(..) | RefundError::ExceedsRemaining { .. } => {
+                (StatusCode::CONFLICT, "not_refundable")
+            }
+            RefundError::InvalidAmount(_) => (StatusCode::BAD_REQUEST, "invalid_amount"),
+            RefundError::Ledger(_) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
+            RefundError::Scheme(error) => {
+                return FacilitatorLocalError::Settlement(error).into_response();
+            }
+        };
+        (
+            status,
+            Json(json!({ "error": error, "details": self.to_string() })),
+        )
+            .into_response()
+    }
+}
+
+/// Routes reporting the schedule of recurring payments.
+///
+/// Guard them with [`authenticated_routes`] when API keys are configured: they
+/// require a `verify` API key.
+pub fn subscription_routes() -> Router<Arc<FacilitatorLocal<SchemeRegistry>>> {
+    Router::new().route("/subscriptions/{id}", get(get_subscription))
+}
+
+/// `GET /subscriptions/{id}`: Returns the schedule, pulls and status of a subscription.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn get_subscription(
+    State(facilitator): State<Arc<FacilitatorLocal<SchemeRegistry>>>,
+    Path(id): Path<String>,
+) -> Response {
+    match facilitator.subscription(&id).await {
+        Ok(Some(subscription)) => Json(subscription.0).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "not_found", "details": "no such subscription" })),
+        )
+            .into_response(),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": "internal_error", "details": error.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Headers a client sends its payment in: `X-PAYMENT` (V1) and `Payment-Signature` (V2).
+const PAYMENT_HEADERS: [&str; 2] = ["X-PAYMENT", "Payment-Signature"];
+
+/// Routes explaining payment payloads, for integrators debugging rejected payments.
+///
+/// Only serve them in development: they are meant for people, not resource servers.
+/// Guard them with [`authenticated_routes`] when API keys are configured: they
+/// require a `verify` API key.
+pub fn debug_routes() -> Router<Arc<FacilitatorLocal<SchemeRegistry>>> {
+    Router::new().route("/debug/decode", post(post_debug_decode))
+}
+
+/// `POST /debug/decode`: Explains a payment payload without reading the chain.
+///
+/// The payment is taken from, in order:
+/// - a `/verify` body, with `paymentPayload` as an object or its base64 transport form
+/// - an `X-PAYMENT` or `Payment-Signature` header, with `paymentRequirements` in the
+///   body if any
+/// - a bare payment payload as the body
+///
+/// V2 payloads without `paymentRequirements` are explained against their `accepted`
+/// requirements. The response reports the detected version, scheme and parties, and
+/// the scheme's explanation: signature kind, expected EIP-712 domain and digest, and
+/// the verification checks that pass or fail statically.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn post_debug_decode(
+    State(facilitator): State<Arc<FacilitatorLocal<SchemeRegistry>>>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> Response {
+    let request = match debug_decode_request(&headers, &body) {
+        Ok(request) => request,
+        Err(details) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "invalid_request", "details": details })),
+            )
+                .into_response();
+        }
+    };
+    let slug = request.scheme_handler_slug();
+    let (explanation, error) = match facilitator.explain(&request).await {
+        Ok(Some(explanation)) => (Some(explanation.0), None),
+        Ok(None) if slug.is_none() => (
+            None,
+            Some("unknown protocol version, network or scheme".to_string()),
+        ),
+        Ok(None) => (
+            None,
+            Some("no explanation for payloads of this scheme".to_string()),
+        ),
+        Err(error) => (None, Some(error.to_string())),
+    };
+    let json = request.clone().into_json();
+    Json(json!({
+        "x402Version": json.get("x402Version"),
+        "scheme": slug.as_ref().map(|slug| slug.name.clone()),
+        "network": slug.as_ref().map(|slug| slug.chain_id.to_string()),
+        "supported": slug.is_some_and(|slug| facilitator.handlers().by_slug(&slug).is_some()),
+        "payer": request.payer(),
+        "payee": request.payee(),
+        "amount": request.amount(),
+        "asset": request.asset(),
+        "paymentPayload": json.get("paymentPayload"),
+        "paymentRequirements": json.get("paymentRequirements"),
+        "explanation": explanation,
+        "error": error,
+    }))
+    .into_response()
+}
+
+/// Builds the `/verify` request a `POST /debug/decode` call describes.
+fn debug_decode_request(headers: &HeaderMap, body: &[u8]) -> Result<proto::VerifyRequest, String> {
+    let mut json: Value = if body.iter().all(u8::is_ascii_whitespace) {
+        json!({})
+    } else {
+        serde_json::from_slice(body).map_err(|e| format!("invalid JSON body: {e}"))?
+    };
+    let object = json
+        .as_object_mut()
+        .ok_or_else(|| "body must be a JSON object".to_string())?;
+    if !object.contains_key("paymentPayload") {
+        let header = PAYMENT_HEADERS
+            .iter()
+            .find_map(|name| headers.get(*name)?.to_str().ok());
+        let payload = match header {
+            Some(header) => Value::String(header.trim().to_string()),
+            None if object.contains_key("payload") => Value::Object(std::mem::take(object)),
+            None => return Err("no paymentPayload, payment header or payment payload".to_string()),
+        };
+        object.insert("paymentPayload".to_string(), payload);
+    }
+    let request = proto::VerifyRequest::from(json).with_decoded_payment_payload();
+    let mut json = request.into_json();
+    let payment_payload = json.get("paymentPayload").cloned().unwrap_or_default();
+    if !payment_payload.is_object() {
+        return Err("paymentPayload is neither a JSON object nor its base64 encoding".to_string());
+    }
+    let object = json.as_object_mut().expect("request is a JSON object");
+    if !object.contains_key("x402Version")
+        && let Some(version) = payment_payload.get("x402Version")
+    {
+        object.insert("x402Version".to_string(), version.clone());
+    }
+    if !object.contains_key("paymentRequirements")
+        && let Some(accepted) = payment_payload.get("accepted")
+    {
+        object.insert("paymentRequirements".to_string(), accepted.clone());
+    }
+    Ok(json.into())
+}
+
+/// Routes reporting the background tasks of a [`Scheduler`].
+pub fn scheduler_routes() -> Router<Arc<Scheduler>> {
+    Router::new().route("/health/tasks", get(get_task_health))
+}
+
+/// `GET /health/tasks`: Reports the runs and last error of every background task.
+///
+/// Responds with `503 Service Unavailable` if a task exited or its last run failed.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn get_task_health(State(scheduler): State<Arc<Scheduler>>) -> Response {
+    let tasks = scheduler.health();
+    let healthy = tasks.iter().all(|task| task.healthy());
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(json!({ "healthy": healthy, "tasks": tasks }))).into_response()
+}
+
+/// Routes reporting the leadership of this replica, see [`crate::cluster`].
+pub fn cluster_routes() -> Router<Arc<Cluster>> {
+    Router::new().route("/health/cluster", get(get_cluster_health))
+}
+
+/// Admin routes changing the leadership of this replica.
+///
+/// Guard them with [`authenticated_routes`]: they require an `admin` API key.
+pub fn cluster_admin_routes() -> Router<Arc<Cluster>> {
+    Router::new().route("/admin/cluster/resign", post(post_cluster_resign))
+}
+
+/// `GET /health/cluster`: Reports whether this replica leads the cluster, and which one does.
+///
+/// Responds with `503 Service Unavailable` if the last lease renewal failed.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn get_cluster_health(State(cluster): State<Arc<Cluster>>) -> Response {
+    let status = cluster.status();
+    let code = if status.last_error.is_none() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (code, Json(status)).into_response()
+}
+
+/// `POST /admin/cluster/resign`: Releases the leader lease if this replica holds it.
+///
+/// Another replica takes over at its next renewal. This replica stays a follower
+/// for at least one lease TTL.
+#[cfg_attr(feature = "telemetry", instrument(skip_all))]
+async fn post_cluster_resign(State(cluster): State<Arc<Cluster>>) -> Response {
+    match cluster.resign() {
+        Ok(()) => {
+            #[cfg(feature = "telemetry")]
+            tracing::info!(node_id = %cluster.node_id(), "Resigned cluster leadership");
+            Json(cluster.status()).into_response()
+        }
+        Err(e) => (StatusCode::SERVICE_UNAVAILABLE, Json(json!({ "error": e }))).into_response(),
+    }
+}
+
 /// `GET /`: Returns a simple greeting message from the facilitator.
 #[cfg_attr(feature = "telemetry", instrument(skip_all))]
 pub async fn get_root() -> impl IntoResponse {
@@ -174,6 +829,14 @@ pub async fn get_root() -> impl IntoResponse {
 ///
 /// Facilitators may expose this to help clients dynamically configure their payment requests
 /// based on available network and scheme support.
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/supported",
+    tag = "payments",
+    responses(
+        (status = 200, description = "Supported payment kinds and signer addresses", body = proto::SupportedResponse),
+    ),
+))]
 #[cfg_attr(feature = "telemetry", instrument(skip_all))]
 pub async fn get_supported<A>(State(facilitator): State<A>) -> impl IntoResponse
 where
@@ -190,6 +853,14 @@ where
 ///
 /// Returns the same response as `/supported`, making it useful for load balancers
 /// and monitoring systems to check if the facilitator is operational.
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/health",
+    tag = "payments",
+    responses(
+        (status = 200, description = "The facilitator is operational; same body as `/supported`", body = proto::SupportedResponse),
+    ),
+))]
 #[cfg_attr(feature = "telemetry", instrument(skip_all))]
 pub async fn get_health<A>(State(facilitator): State<A>) -> impl IntoResponse
 where
@@ -207,11 +878,29 @@ where
 ///
 /// Responds with a [`VerifyResponse`](x402_types::proto::VerifyResponse) indicating whether the payment can be accepted.
 ///
+/// The `paymentPayload` may also be given as the base64 string received from the
+/// client, as carried by any of the payment transports.
+///
 /// # Errors
 ///
 /// Returns `400 Bad Request` if the payment verification fails (e.g., invalid signature,
 /// unsupported scheme, insufficient funds). Returns `500 Internal Server Error` if an
 /// unexpected error occurs during verification.
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/verify",
+    tag = "payments",
+    security((), ("bearer" = []), ("api_key" = [])),
+    request_body = proto::openapi::VerifyRequestSchema,
+    responses(
+        (status = 200, description = "The payment is valid", body = proto::openapi::VerifyResponseSchema),
+        (status = 400, description = "The payment is invalid", body = proto::openapi::VerifyResponseSchema),
+        (status = 401, description = "Missing or unknown API key, when API keys are configured"),
+        (status = 429, description = "Rate limit exceeded, see `Retry-After`"),
+        (status = 451, description = "Payer or client region blocked by compliance screening"),
+        (status = 500, description = "Verification could not be completed", body = proto::openapi::VerifyResponseSchema),
+    ),
+))]
 #[cfg_attr(feature = "telemetry", instrument(skip_all))]
 pub async fn post_verify<A>(
     State(facilitator): State<A>,
@@ -221,13 +910,14 @@ where
     A: Facilitator,
     A::Error: IntoResponse,
 {
+    let body = body.with_decoded_payment_payload();
     match facilitator.verify(&body).await {
         Ok(valid_response) => (StatusCode::OK, Json(valid_response)).into_response(),
         Err(error) => {
             #[cfg(feature = "telemetry")]
             tracing::warn!(
                 error = ?error,
-                body = %serde_json::to_string(&body).unwrap_or_else(|_| "<can-not-serialize>".to_string()),
+                body = %body.as_raw(),
                 "Verification failed"
             );
             error.into_response()
@@ -235,6 +925,15 @@ where
     }
 }
 
+/// Query parameters of `POST /settle`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettleParams {
+    /// Validate and estimate the settlement without broadcasting it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
 /// `POST /settle`: Facilitator-side execution of a valid x402 payment on-chain.
 ///
 /// Given a valid [`SettleRequest`](x402_types::proto::SettleRequest), this endpoint attempts to execute the payment
@@ -243,26 +942,58 @@ where
 ///
 /// This endpoint is typically called after a successful `/verify` step.
 ///
+/// With `?dryRun=true` (or `"dryRun": true` in the body), the payment is validated,
+/// simulated and gas-estimated, and the response lists the would-be transactions and
+/// their estimated cost instead of broadcasting them.
+///
 /// # Errors
 ///
 /// Returns `400 Bad Request` if the payment verification fails (e.g., invalid signature,
 /// insufficient funds). Returns `500 Internal Server Error` if the on-chain settlement fails.
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/settle",
+    tag = "payments",
+    security((), ("bearer" = []), ("api_key" = [])),
+    params(
+        ("dryRun" = Option<bool>, Query, description = "Estimate the settlement without broadcasting it"),
+    ),
+    request_body = proto::openapi::VerifyRequestSchema,
+    responses(
+        (status = 200, description = "The payment was settled. Dry runs answer with a `SettleDryRunResponse`", body = proto::openapi::SettleResponseSchema),
+        (status = 400, description = "The payment is invalid", body = proto::openapi::SettleResponseSchema),
+        (status = 401, description = "Missing or unknown API key, when API keys are configured"),
+        (status = 403, description = "The API key is scoped to `verify`"),
+        (status = 429, description = "Rate limit or daily settlement quota exceeded, see `Retry-After`"),
+        (status = 451, description = "Payer or client region blocked by compliance screening"),
+        (status = 500, description = "The settlement transaction failed", body = proto::openapi::SettleResponseSchema),
+        (status = 503, description = "The facilitator is shutting down and takes no new settlements, or the chain has no settlement slot free (see `Retry-After`)", body = proto::openapi::SettleResponseSchema),
+    ),
+))]
 #[cfg_attr(feature = "telemetry", instrument(skip_all))]
 pub async fn post_settle<A>(
     State(facilitator): State<A>,
+    Query(params): Query<SettleParams>,
     Json(body): Json<proto::SettleRequest>,
 ) -> impl IntoResponse
 where
     A: Facilitator,
     A::Error: IntoResponse,
 {
+    let body = body.with_decoded_payment_payload();
+    if params.dry_run || body.dry_run() {
+        return match facilitator.settle_dry_run(&body).await {
+            Ok(dry_run) => (StatusCode::OK, Json(dry_run)).into_response(),
+            Err(error) => error.into_response(),
+        };
+    }
     match facilitator.settle(&body).await {
         Ok(valid_response) => (StatusCode::OK, Json(valid_response)).into_response(),
         Err(error) => {
             #[cfg(feature = "telemetry")]
             tracing::warn!(
                 error = ?error,
-                body = %serde_json::to_string(&body).unwrap_or_else(|_| "<can-not-serialize>".to_string()),
+                body = %body.as_raw(),
                 "Settlement failed"
             );
             error.into_response()
@@ -270,6 +1001,27 @@ where
     }
 }
 
+/// Invalid payments are the client's fault, failed transactions the facilitator's;
+/// a chain out of settlement slots is temporarily unavailable.
+fn scheme_error_status(error: &X402SchemeFacilitatorError) -> StatusCode {
+    match error {
+        X402SchemeFacilitatorError::PaymentVerification(_) => StatusCode::BAD_REQUEST,
+        X402SchemeFacilitatorError::OnchainFailure(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        X402SchemeFacilitatorError::Overloaded { .. } => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+/// Adds a `Retry-After` header to the response of an overloaded chain.
+fn with_retry_after(mut response: Response, error: &X402SchemeFacilitatorError) -> Response {
+    if let X402SchemeFacilitatorError::Overloaded { retry_after } = error {
+        let retry_after_secs = retry_after.as_secs().max(1);
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+    }
+    response
+}
+
 impl IntoResponse for FacilitatorLocalError {
     fn into_response(self) -> Response {
         #[derive(Serialize, Deserialize)]
@@ -278,6 +1030,8 @@ impl IntoResponse for FacilitatorLocalError {
             is_valid: bool,
             invalid_reason: ErrorReason,
             invalid_reason_details: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            invalid_reason_context: Option<serde_json::Value>,
             payer: &'a str,
         }
 
@@ -289,6 +1043,8 @@ impl IntoResponse for FacilitatorLocalError {
             transaction: &'a str,
             error_reason: ErrorReason,
             error_reason_details: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            error_reason_context: Option<serde_json::Value>,
             payer: &'a str,
         }
 
@@ -299,15 +1055,14 @@ impl IntoResponse for FacilitatorLocalError {
                     is_valid: false,
                     invalid_reason: problem.reason(),
                     invalid_reason_details: problem.details(),
+                    invalid_reason_context: problem.context().cloned(),
                     payer: "",
                 };
-                let status_code = match scheme_handler_error {
-                    X402SchemeFacilitatorError::PaymentVerification(_) => StatusCode::BAD_REQUEST,
-                    X402SchemeFacilitatorError::OnchainFailure(_) => {
-                        StatusCode::INTERNAL_SERVER_ERROR
-                    }
-                };
-                (status_code, Json(verification_error_response)).into_response()
+                let response = (
+                    scheme_error_status(&scheme_handler_error),
+                    Json(verification_error_response),
+                );
+                with_retry_after(response.into_response(), &scheme_handler_error)
             }
             FacilitatorLocalError::Settlement(scheme_handler_error) => {
                 let problem = scheme_handler_error.as_payment_problem();
@@ -317,15 +1072,31 @@ impl IntoResponse for FacilitatorLocalError {
                     transaction: "",
                     error_reason: problem.reason(),
                     error_reason_details: problem.details(),
+                    error_reason_context: problem.context().cloned(),
                     payer: "",
                 };
-                let status_code = match scheme_handler_error {
-                    X402SchemeFacilitatorError::PaymentVerification(_) => StatusCode::BAD_REQUEST,
-                    X402SchemeFacilitatorError::OnchainFailure(_) => {
-                        StatusCode::INTERNAL_SERVER_ERROR
-                    }
+                let response = (
+                    scheme_error_status(&scheme_handler_error),
+                    Json(settlement_error_response),
+                );
+                with_retry_after(response.into_response(), &scheme_handler_error)
+            }
+            FacilitatorLocalError::ShuttingDown | FacilitatorLocalError::Paused(_) => {
+                let details = self.to_string();
+                let settlement_error_response = SettlementErrorResponse {
+                    success: false,
+                    network: "",
+                    transaction: "",
+                    error_reason: ErrorReason::UnexpectedError,
+                    error_reason_details: &details,
+                    error_reason_context: None,
+                    payer: "",
                 };
-                (status_code, Json(settlement_error_response)).into_response()
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(settlement_error_response),
+                )
+                    .into_response()
             }
         }
     }
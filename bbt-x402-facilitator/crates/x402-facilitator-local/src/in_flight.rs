@@ -0,0 +1,370 @@
+//! Draining of in-flight settlements on shutdown.
+//!
+//! A settlement is in flight from the moment `/settle` hands it to the scheme
+//! handler until the handler returns, which for most schemes means until the
+//! transaction is confirmed. With [`InFlightSettlements`] attached through
+//! [`FacilitatorLocal::with_in_flight_settlements`](crate::FacilitatorLocal::with_in_flight_settlements),
+//! every settlement is written to a journal before it starts and removed once it
+//! finished.
+//!
+//! On shutdown, [`InFlightSettlements::drain`] turns new settlements away with
+//! `503 Service Unavailable`, and waits up to the drain deadline for the ones in
+//! flight to finish. Settlements still unfinished at the deadline, abandoned by a
+//! disconnected client, or cut off by a crash stay in the journal. On the next
+//! startup, [`FacilitatorLocal::recover_interrupted_settlements`](crate::FacilitatorLocal::recover_interrupted_settlements)
+//! moves them to the dead-letter queue, where an operator requeues or voids them
+//! (see [`crate::dead_letter`]). Without a dead-letter queue they stay in the
+//! journal and are reported on every startup.
+//!
+//! Whether an interrupted transaction landed is not known, but requeueing is safe:
+//! a settlement that did land consumed its nonce, so settling it again fails.
+//!
+//! # Configuration
+//!
+//! | Variable | Description |
+//! |----------|-------------|
+//! | `SETTLEMENT_DRAIN_TIMEOUT_SECS` | How long shutdown waits for in-flight settlements (default: `30`) |
+//! | `SETTLEMENT_JOURNAL_PATH` | JSON file in-flight settlements are journaled to (default: in memory only) |
+//!
+//! Without `SETTLEMENT_JOURNAL_PATH`, shutdown still drains, but settlements
+//! unfinished at the deadline are only logged. With it, every settlement costs two
+//! synced writes of the journal.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use x402_types::proto;
+use x402_types::timestamp::UnixTimestamp;
+
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A settlement handed to a scheme handler and not finished yet.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InFlightSettlement {
+    pub id: u64,
+    /// The original `/settle` request.
+    pub request: proto::SettleRequest,
+    /// When the settlement was handed to the scheme handler.
+    pub started_at: UnixTimestamp,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JournalState {
+    next_id: u64,
+    entries: BTreeMap<u64, InFlightSettlement>,
+}
+
+#[derive(Debug, Default)]
+struct Journal {
+    state: JournalState,
+    /// Entries no settlement of this run is working on: restored from the
+    /// journal, or abandoned before they finished.
+    interrupted: BTreeSet<u64>,
+}
+
+impl Journal {
+    fn in_flight(&self) -> impl Iterator<Item = &InFlightSettlement> {
+        self.state
+            .entries
+            .values()
+            .filter(|entry| !self.interrupted.contains(&entry.id))
+    }
+}
+
+/// Settlements in flight, drained on shutdown.
+#[derive(Debug)]
+pub struct InFlightSettlements {
+    drain_timeout: Duration,
+    path: Option<PathBuf>,
+    journal: Mutex<Journal>,
+    draining: AtomicBool,
+    finished: Notify,
+}
+
+impl InFlightSettlements {
+    /// Tracks settlements in memory only.
+    pub fn new(drain_timeout: Duration) -> Self {
+        Self {
+            drain_timeout,
+            path: None,
+            journal: Mutex::new(Journal::default()),
+            draining: AtomicBool::new(false),
+            finished: Notify::new(),
+        }
+    }
+
+    /// Journals settlements to `path`, restoring the ones a previous run left
+    /// there as interrupted.
+    pub fn open(drain_timeout: Duration, path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let state: JournalState = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("invalid settlement journal {}: {e}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => JournalState::default(),
+            Err(e) => {
+                return Err(format!(
+                    "failed to read settlement journal {}: {e}",
+                    path.display()
+                ));
+            }
+        };
+        let interrupted = state.entries.keys().copied().collect();
+        Ok(Self {
+            path: Some(path),
+            journal: Mutex::new(Journal { state, interrupted }),
+            ..Self::new(drain_timeout)
+        })
+    }
+
+    /// Builds the tracker from the `SETTLEMENT_DRAIN_TIMEOUT_SECS` and
+    /// `SETTLEMENT_JOURNAL_PATH` environment variables.
+    pub fn from_env() -> Result<Self, String> {
+        let drain_timeout = match env::var("SETTLEMENT_DRAIN_TIMEOUT_SECS") {
+            Ok(value) => Duration::from_secs(
+                value
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|e| format!("invalid SETTLEMENT_DRAIN_TIMEOUT_SECS: {e}"))?,
+            ),
+            Err(_) => DEFAULT_DRAIN_TIMEOUT,
+        };
+        match env::var("SETTLEMENT_JOURNAL_PATH") {
+            Ok(path) if !path.trim().is_empty() => Self::open(drain_timeout, path.trim()),
+            _ => Ok(Self::new(drain_timeout)),
+        }
+    }
+
+    /// Returns how long [`InFlightSettlements::drain`] waits.
+    pub fn drain_timeout(&self) -> Duration {
+        self.drain_timeout
+    }
+
+    /// Returns whether shutdown started and new settlements are turned away.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    /// Journals `request` as in flight.
+    ///
+    /// Returns `None` once draining started. The settlement stays in flight until
+    /// [`InFlightGuard::finish`] is called; a guard dropped before that marks it as
+    /// interrupted.
+    pub fn begin(&self, request: &proto::SettleRequest) -> Option<InFlightGuard<'_>> {
+        if self.is_draining() {
+            return None;
+        }
+        let started_at = UnixTimestamp::now();
+        let id = self.update(|journal| {
+            let id = journal.state.next_id;
+            journal.state.next_id += 1;
+            journal.state.entries.insert(
+                id,
+                InFlightSettlement {
+                    id,
+                    request: request.clone(),
+                    started_at,
+                },
+            );
+            id
+        });
+        Some(InFlightGuard {
+            settlements: self,
+            id,
+            finished: false,
+        })
+    }
+
+    /// Returns the settlements currently in flight, oldest first.
+    pub fn in_flight(&self) -> Vec<InFlightSettlement> {
+        self.lock().in_flight().cloned().collect()
+    }
+
+    /// Returns the interrupted settlements still in the journal, oldest first.
+    pub fn interrupted(&self) -> Vec<InFlightSettlement> {
+        let journal = self.lock();
+        journal
+            .interrupted
+            .iter()
+            .filter_map(|id| journal.state.entries.get(id).cloned())
+            .collect()
+    }
+
+    /// Removes the interrupted settlements from the journal and returns them.
+    pub fn take_interrupted(&self) -> Vec<InFlightSettlement> {
+        self.update(|journal| {
+            let interrupted = std::mem::take(&mut journal.interrupted);
+            interrupted
+                .into_iter()
+                .filter_map(|id| journal.state.entries.remove(&id))
+                .collect()
+        })
+    }
+
+    /// Turns new settlements away and waits up to the drain timeout for the ones
+    /// in flight to finish.
+    ///
+    /// Returns the settlements still in flight at the deadline. They stay in the
+    /// journal for [`InFlightSettlements::take_interrupted`] at the next startup.
+    pub async fn drain(&self) -> Vec<InFlightSettlement> {
+        self.draining.store(true, Ordering::SeqCst);
+        let deadline = tokio::time::Instant::now() + self.drain_timeout;
+        loop {
+            // Registered before checking, so that a settlement finishing in between is not missed.
+            let finished = self.finished.notified();
+            let in_flight = self.in_flight();
+            if in_flight.is_empty() {
+                return in_flight;
+            }
+            tokio::select! {
+                _ = finished => {}
+                _ = tokio::time::sleep_until(deadline) => return self.in_flight(),
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Journal> {
+        self.journal
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Applies `change` and persists the journal if a path is configured.
+    fn update<T>(&self, change: impl FnOnce(&mut Journal) -> T) -> T {
+        let mut journal = self.lock();
+        let result = change(&mut journal);
+        if let Some(path) = &self.path
+            && let Err(_e) = persist(path, &journal.state)
+        {
+            #[cfg(feature = "telemetry")]
+            tracing::error!(error = %_e, path = %path.display(), "Failed to persist settlement journal");
+        }
+        result
+    }
+}
+
+/// Keeps a settlement in flight, see [`InFlightSettlements::begin`].
+#[derive(Debug)]
+pub struct InFlightGuard<'a> {
+    settlements: &'a InFlightSettlements,
+    id: u64,
+    finished: bool,
+}
+
+impl InFlightGuard<'_> {
+    /// Removes the settlement from the journal, whatever its outcome.
+    pub fn finish(mut self) {
+        self.finished = true;
+        self.settlements.update(|journal| {
+            journal.state.entries.remove(&self.id);
+        });
+        self.settlements.finished.notify_waiters();
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    /// A settlement whose future was dropped may still land on-chain, so it is
+    /// kept in the journal, but no longer holds up draining.
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        let mut journal = self.settlements.lock();
+        journal.interrupted.insert(self.id);
+        drop(journal);
+        self.settlements.finished.notify_waiters();
+    }
+}
+
+/// Writes `state` to a temporary file, syncs it and renames it over `path`.
+fn persist(path: &Path, state: &JournalState) -> std::io::Result<()> {
+    let content = serde_json::to_vec_pretty(state).map_err(std::io::Error::other)?;
+    let tmp = path.with_extension("tmp");
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(&content)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> proto::SettleRequest {
+        serde_json::json!({ "x402Version": 2, "paymentPayload": {}, "paymentRequirements": {} })
+            .into()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_waits_for_in_flight_settlements() {
+        let settlements = InFlightSettlements::new(Duration::from_secs(30));
+        let guard = settlements.begin(&request()).unwrap();
+
+        let drained = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            guard.finish();
+        };
+        let (unfinished, ()) = tokio::join!(settlements.drain(), drained);
+
+        assert!(unfinished.is_empty());
+        assert!(settlements.is_draining());
+        assert!(settlements.begin(&request()).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_gives_up_at_deadline() {
+        let settlements = InFlightSettlements::new(Duration::from_secs(30));
+        let _guard = settlements.begin(&request()).unwrap();
+
+        let unfinished = settlements.drain().await;
+
+        assert_eq!(unfinished.len(), 1);
+        assert_eq!(unfinished[0].id, 0);
+    }
+
+    #[test]
+    fn test_journal_restores_interrupted_settlements() {
+        let path = env::temp_dir().join(format!("x402-journal-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let settlements = InFlightSettlements::open(DEFAULT_DRAIN_TIMEOUT, &path).unwrap();
+        let finished = settlements.begin(&request()).unwrap();
+        let unfinished = settlements.begin(&request()).unwrap();
+        finished.finish();
+        // The process stops without finishing the second settlement.
+        std::mem::forget(unfinished);
+
+        let restored = InFlightSettlements::open(DEFAULT_DRAIN_TIMEOUT, &path).unwrap();
+        assert!(restored.in_flight().is_empty());
+        assert_eq!(restored.interrupted().len(), 1);
+        let next = restored.begin(&request()).unwrap();
+        assert_eq!(restored.in_flight().len(), 1);
+        let taken = restored.take_interrupted();
+        assert_eq!(taken.len(), 1);
+        assert_eq!(taken[0].id, 1);
+        next.finish();
+
+        let restored = InFlightSettlements::open(DEFAULT_DRAIN_TIMEOUT, &path).unwrap();
+        assert!(restored.interrupted().is_empty());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_dropped_settlement_is_interrupted() {
+        let settlements = InFlightSettlements::new(DEFAULT_DRAIN_TIMEOUT);
+        drop(settlements.begin(&request()).unwrap());
+
+        assert!(settlements.in_flight().is_empty());
+        assert_eq!(settlements.interrupted().len(), 1);
+    }
+}
@@ -0,0 +1,550 @@
+//! Persisted ledger of settlement attempts.
+//!
+//! With a [`SettlementLedger`] attached through
+//! [`FacilitatorLocal::with_settlement_ledger`](crate::FacilitatorLocal::with_settlement_ledger), every
+//! `/settle` call, every dead-letter requeue and every refund (see [`crate::refund`])
+//! is recorded as a [`LedgerEntry`]: the hash of the payment payload, payer, payee,
+//! asset and amount, the outcome and, for settled or refunded payments, the
+//! transaction hash. Operators query it for reconciliation, support and disputes.
+//!
+//! Recording never fails a settlement: the payment has already been settled or
+//! rejected when the entry is written, so a write error is only logged.
+//!
+//! # Configuration
+//!
+//! | Variable | Description |
+//! |----------|-------------|
+//! | `SETTLEMENT_LEDGER_ENABLED` | Record settlement attempts (default: `false`) |
+//! | `SETTLEMENT_LEDGER_PATH` | JSON Lines file the ledger is appended to and restored from (default: in memory only) |
+//!
+//! Without `SETTLEMENT_LEDGER_PATH`, the ledger is lost on restart. Databases,
+//! such as a SQLite or Postgres table, plug in by implementing [`LedgerStore`].
+//!
+//! # Query API
+//!
+//! [`handlers::settlement_ledger_routes`](crate::handlers::settlement_ledger_routes)
+//! serves `GET /settlements`, filtered by [`LedgerQuery`]:
+//!
+//! ```text
+//! GET /settlements?payer=0x...&from=2026-01-01T00:00:00Z&to=1767312000&limit=50
+//! GET /settlements?refundOf=0x5f1c...
+//! ```
+//!
+//! The route requires an API key with the `admin` scope, so it should only be
+//! mounted when API keys are configured.
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use alloy_primitives::{B256, keccak256};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_json::value::RawValue;
+use x402_types::config::NotificationEventKind;
+use x402_types::proto;
+use x402_types::timestamp::UnixTimestamp;
+
+/// Entries returned by a query without `limit`.
+pub const DEFAULT_QUERY_LIMIT: usize = 100;
+/// Most entries a single query returns.
+pub const MAX_QUERY_LIMIT: usize = 1000;
+
+/// A settlement attempt as recorded in the ledger.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    pub recorded_at: UnixTimestamp,
+    /// `settlement_succeeded`, `settlement_rejected`, `settlement_failed`,
+    /// `settlement_dead_lettered` or `settlement_refunded`.
+    pub outcome: NotificationEventKind,
+    /// Keccak-256 hash of the `paymentPayload` JSON, as received.
+    pub payload_hash: Option<B256>,
+    /// CAIP-2 chain id of the payment.
+    pub network: Option<String>,
+    /// x402 protocol version of the payment.
+    #[serde(default)]
+    pub x402_version: Option<u8>,
+    /// Payment scheme, e.g. `exact`.
+    #[serde(default)]
+    pub scheme: Option<String>,
+    pub payer: Option<String>,
+    pub payee: Option<String>,
+    pub asset: Option<String>,
+    /// Amount in the asset's smallest unit.
+    pub amount: Option<String>,
+    /// Amount the settlement transferred, when it reports one (`settledAmount`), such
+    /// as less than `amount` for `upto` payments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settled_amount: Option<String>,
+    /// Settlement transaction hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<String>,
+    /// Why the settlement did not succeed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Settlement attempts made, including retries.
+    pub attempts: u32,
+    /// Transaction hash of the settlement a `settlement_refunded` entry refunds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refund_of: Option<String>,
+    /// Number of the refund among the refunds of its settlement, shared by the
+    /// entries recording its progress.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refund_number: Option<u32>,
+    /// Progress of the refund of a `settlement_refunded` entry. Entries without one
+    /// were recorded once their refund was sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refund_status: Option<RefundStatus>,
+}
+
+/// Progress of a refund, see [`LedgerEntry::refund`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RefundStatus {
+    /// Recorded before the refund is sent. A refund left pending may or may not
+    /// have been sent, and its amount is not refundable again.
+    Pending,
+    /// The refund was sent.
+    Sent,
+    /// The refund was not sent, and its amount is refundable again.
+    Failed,
+}
+
+impl LedgerEntry {
+    /// Creates the entry of a completed settlement attempt.
+    pub fn new(
+        outcome: NotificationEventKind,
+        request: &proto::SettleRequest,
+        transaction: Option<&str>,
+        reason: Option<String>,
+        attempts: u32,
+    ) -> Self {
+        let slug = request.scheme_handler_slug();
+        Self {
+            recorded_at: UnixTimestamp::now(),
+            outcome,
+            payload_hash: payload_hash(request),
+            network: slug.as_ref().map(|slug| slug.chain_id.to_string()),
+            x402_version: slug.as_ref().map(|slug| slug.x402_version),
+            scheme: slug.map(|slug| slug.name),
+            payer: request.payer(),
+            payee: request.payee(),
+            asset: request.asset(),
+            amount: request.amount(),
+            settled_amount: None,
+            transaction: transaction.map(str::to_string),
+            reason,
+            attempts,
+            refund_of: None,
+            refund_number: None,
+            refund_status: None,
+        }
+    }
+
+    /// Creates the entry of a successful settlement, with the transaction and the
+    /// amount its response reports.
+    pub fn settled(
+        request: &proto::SettleRequest,
+        response: &proto::SettleResponse,
+        attempts: u32,
+    ) -> Self {
+        let reported = |field| response.0.get(field).and_then(Value::as_str);
+        Self {
+            settled_amount: reported("settledAmount")
+                .map(str::to_string)
+                .or_else(|| request.settle_amount()),
+            ..Self::new(
+                NotificationEventKind::SettlementSucceeded,
+                request,
+                reported("transaction"),
+                None,
+                attempts,
+            )
+        }
+    }
+
+    /// Creates the pending entry of refund `number` of the `settlement` payment, for
+    /// `amount`, recorded before the refund is sent.
+    ///
+    /// The refund is then recorded as sent with [`LedgerEntry::refund_sent`], or as
+    /// failed with [`LedgerEntry::refund_failed`].
+    pub fn refund(
+        settlement: &LedgerEntry,
+        amount: String,
+        number: u32,
+        reason: Option<String>,
+    ) -> Self {
+        Self {
+            recorded_at: UnixTimestamp::now(),
+            outcome: NotificationEventKind::SettlementRefunded,
+            amount: Some(amount),
+            settled_amount: None,
+            transaction: None,
+            reason,
+            attempts: 1,
+            refund_of: settlement.transaction.clone(),
+            refund_number: Some(number),
+            refund_status: Some(RefundStatus::Pending),
+            ..settlement.clone()
+        }
+    }
+
+    /// Records this pending refund as sent in `transaction`.
+    pub fn refund_sent(&self, transaction: Option<&str>) -> Self {
+        Self {
+            recorded_at: UnixTimestamp::now(),
+            transaction: transaction.map(str::to_string),
+            refund_status: Some(RefundStatus::Sent),
+            ..self.clone()
+        }
+    }
+
+    /// Records this pending refund as not sent, because of `error`.
+    pub fn refund_failed(&self, error: String) -> Self {
+        Self {
+            recorded_at: UnixTimestamp::now(),
+            reason: Some(error),
+            refund_status: Some(RefundStatus::Failed),
+            ..self.clone()
+        }
+    }
+}
+
+fn payload_hash(request: &proto::SettleRequest) -> Option<B256> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Payload<'a> {
+        #[serde(borrow)]
+        payment_payload: &'a RawValue,
+    }
+    serde_json::from_str::<Payload>(request.as_raw().get())
+        .ok()
+        .map(|payload| keccak256(payload.payment_payload.get()))
+}
+
+/// Query parameters of `GET /settlements`. Every parameter given must match.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerQuery {
+    /// Payer address, compared case-insensitively.
+    pub payer: Option<String>,
+    /// Payee address, compared case-insensitively.
+    pub payee: Option<String>,
+    /// Earliest recording time, inclusive. Milliseconds are accepted.
+    #[serde(
+        default,
+        deserialize_with = "x402_types::timestamp::lenient::deserialize"
+    )]
+    pub from: Option<UnixTimestamp>,
+    /// Latest recording time, inclusive. Milliseconds are accepted.
+    #[serde(
+        default,
+        deserialize_with = "x402_types::timestamp::lenient::deserialize"
+    )]
+    pub to: Option<UnixTimestamp>,
+    /// Transaction hash, compared case-insensitively.
+    pub transaction: Option<String>,
+    /// Transaction hash of the settlement refunded, compared case-insensitively.
+    pub refund_of: Option<String>,
+    /// Maximum number of entries, [`DEFAULT_QUERY_LIMIT`] by default and at most
+    /// [`MAX_QUERY_LIMIT`].
+    pub limit: Option<usize>,
+}
+
+impl LedgerQuery {
+    /// Whether `entry` passes the filter.
+    pub fn matches(&self, entry: &LedgerEntry) -> bool {
+        let hex_matches = |wanted: &Option<String>, actual: &Option<String>| match wanted {
+            Some(wanted) => actual
+                .as_deref()
+                .is_some_and(|actual| actual.eq_ignore_ascii_case(wanted)),
+            None => true,
+        };
+        hex_matches(&self.payer, &entry.payer)
+            && hex_matches(&self.payee, &entry.payee)
+            && hex_matches(&self.transaction, &entry.transaction)
+            && hex_matches(&self.refund_of, &entry.refund_of)
+            && self.from.is_none_or(|from| entry.recorded_at >= from)
+            && self.to.is_none_or(|to| entry.recorded_at <= to)
+    }
+
+    /// The number of entries to return.
+    pub fn limit(&self) -> usize {
+        self.limit
+            .unwrap_or(DEFAULT_QUERY_LIMIT)
+            .min(MAX_QUERY_LIMIT)
+    }
+}
+
+/// Where the ledger keeps its entries.
+///
+/// [`LedgerStore::append`] must not return before the entry is durable.
+pub trait LedgerStore: Send + Sync + std::fmt::Debug {
+    fn append(&self, entry: &LedgerEntry) -> std::io::Result<()>;
+    /// Entries matching `query`, most recent first, at most [`LedgerQuery::limit`].
+    fn query(&self, query: &LedgerQuery) -> Result<Vec<LedgerEntry>, String>;
+}
+
+/// Keeps the ledger in memory only.
+#[derive(Debug, Default)]
+pub struct MemoryLedgerStore {
+    entries: Mutex<Vec<LedgerEntry>>,
+}
+
+impl MemoryLedgerStore {
+    fn with_entries(entries: Vec<LedgerEntry>) -> Self {
+        Self {
+            entries: Mutex::new(entries),
+        }
+    }
+}
+
+impl LedgerStore for MemoryLedgerStore {
+    fn append(&self, entry: &LedgerEntry) -> std::io::Result<()> {
+        self.entries
+            .lock()
+            .expect("ledger lock poisoned")
+            .push(entry.clone());
+        Ok(())
+    }
+
+    fn query(&self, query: &LedgerQuery) -> Result<Vec<LedgerEntry>, String> {
+        Ok(self
+            .entries
+            .lock()
+            .expect("ledger lock poisoned")
+            .iter()
+            .rev()
+            .filter(|entry| query.matches(entry))
+            .take(query.limit())
+            .cloned()
+            .collect())
+    }
+}
+
+/// Appends the ledger to a JSON Lines file, one entry per line.
+///
+/// Entries are read back into memory when the store is opened, and queries are
+/// answered from there.
+#[derive(Debug)]
+pub struct FileLedgerStore {
+    path: PathBuf,
+    file: Mutex<fs::File>,
+    entries: MemoryLedgerStore,
+}
+
+impl FileLedgerStore {
+    /// Opens the ledger file at `path`, creating it if needed.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let entries = match fs::read_to_string(&path) {
+            Ok(content) => content
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .enumerate()
+                .map(|(index, line)| {
+                    serde_json::from_str(line).map_err(|e| {
+                        format!(
+                            "invalid ledger file {} at line {}: {e}",
+                            path.display(),
+                            index + 1
+                        )
+                    })
+                })
+                .collect::<Result<Vec<LedgerEntry>, String>>()?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(format!(
+                    "failed to read ledger file {}: {e}",
+                    path.display()
+                ));
+            }
+        };
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("failed to open ledger file {}: {e}", path.display()))?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            entries: MemoryLedgerStore::with_entries(entries),
+        })
+    }
+
+    /// The file the ledger is appended to.
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+impl LedgerStore for FileLedgerStore {
+    fn append(&self, entry: &LedgerEntry) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(entry).map_err(std::io::Error::other)?;
+        line.push(b'\n');
+        let mut file = self.file.lock().expect("ledger file lock poisoned");
+        file.write_all(&line)?;
+        file.sync_data()?;
+        self.entries.append(entry)
+    }
+
+    fn query(&self, query: &LedgerQuery) -> Result<Vec<LedgerEntry>, String> {
+        self.entries.query(query)
+    }
+}
+
+/// Records settlement attempts and answers queries over them.
+#[derive(Debug)]
+pub struct SettlementLedger {
+    store: Box<dyn LedgerStore>,
+    /// Held while a refund is checked and sent, so refunds of a settlement cannot
+    /// add up to more than it settled.
+    refunds: tokio::sync::Mutex<()>,
+}
+
+impl SettlementLedger {
+    /// Creates a ledger on `store`.
+    pub fn new(store: impl LedgerStore + 'static) -> Self {
+        Self {
+            store: Box::new(store),
+            refunds: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Builds the ledger from the `SETTLEMENT_LEDGER_*` environment variables.
+    ///
+    /// Returns `None` unless `SETTLEMENT_LEDGER_ENABLED` is set.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let enabled = env::var("SETTLEMENT_LEDGER_ENABLED")
+            .map(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+        match env::var("SETTLEMENT_LEDGER_PATH") {
+            Ok(path) if !path.trim().is_empty() => {
+                FileLedgerStore::open(path.trim()).map(|store| Some(Self::new(store)))
+            }
+            _ => Ok(Some(Self::new(MemoryLedgerStore::default()))),
+        }
+    }
+
+    /// Records `entry`, logging write errors.
+    pub fn record(&self, entry: LedgerEntry) {
+        if let Err(_e) = self.store.append(&entry) {
+            #[cfg(feature = "telemetry")]
+            tracing::error!(error = %_e, payer = ?entry.payer, transaction = ?entry.transaction, "Failed to record settlement in the ledger");
+        }
+    }
+
+    /// Records `entry`, failing if it could not be written.
+    pub fn try_record(&self, entry: &LedgerEntry) -> Result<(), String> {
+        self.store.append(entry).map_err(|e| e.to_string())
+    }
+
+    /// Entries matching `query`, most recent first.
+    pub fn query(&self, query: &LedgerQuery) -> Result<Vec<LedgerEntry>, String> {
+        self.store.query(query)
+    }
+
+    pub(crate) async fn lock_refunds(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.refunds.lock().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(payer: &str, recorded_at: u64) -> LedgerEntry {
+        LedgerEntry {
+            recorded_at: UnixTimestamp::from_secs(recorded_at),
+            outcome: NotificationEventKind::SettlementSucceeded,
+            payload_hash: Some(keccak256(payer)),
+            network: Some("eip155:42793".to_string()),
+            x402_version: Some(2),
+            scheme: Some("exact".to_string()),
+            payer: Some(payer.to_string()),
+            payee: Some("0x2222222222222222222222222222222222222222".to_string()),
+            asset: None,
+            amount: Some("1000".to_string()),
+            settled_amount: None,
+            transaction: Some("0xabc".to_string()),
+            reason: None,
+            attempts: 1,
+            refund_of: None,
+            refund_number: None,
+            refund_status: None,
+        }
+    }
+
+    #[test]
+    fn test_file_ledger_query() {
+        let path = env::temp_dir().join(format!("x402-ledger-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let payer = "0xAAAAaaaaAAAAaaaaAAAAaaaaAAAAaaaaAAAAaaaa";
+        {
+            let ledger = SettlementLedger::new(FileLedgerStore::open(&path).unwrap());
+            ledger.record(entry(payer, 100));
+            ledger.record(entry("0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb", 150));
+            ledger.record(entry(payer, 200));
+        }
+
+        let ledger = SettlementLedger::new(FileLedgerStore::open(&path).unwrap());
+        let query = LedgerQuery {
+            payer: Some(payer.to_lowercase()),
+            ..Default::default()
+        };
+        let entries = ledger.query(&query).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].recorded_at.as_secs(), 200);
+
+        let query = LedgerQuery {
+            from: Some(UnixTimestamp::from_secs(120)),
+            to: Some(UnixTimestamp::from_secs(180)),
+            ..Default::default()
+        };
+        let entries = ledger.query(&query).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].payer.as_deref(),
+            Some("0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb")
+        );
+
+        let query = LedgerQuery {
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(ledger.query(&query).unwrap().len(), 1);
+
+        let settlement = entry(payer, 300);
+        let refund = LedgerEntry::refund(
+            &settlement,
+            "400".to_string(),
+            1,
+            Some("out of stock".to_string()),
+        );
+        ledger.record(refund.clone());
+        ledger.record(refund.refund_sent(Some("0xdef")));
+        let query: LedgerQuery = serde_json::from_value(serde_json::json!({
+            "refundOf": "0xABC"
+        }))
+        .unwrap();
+        let refunds = ledger.query(&query).unwrap();
+        assert_eq!(refunds.len(), 2);
+        assert_eq!(
+            refunds[0].outcome,
+            NotificationEventKind::SettlementRefunded
+        );
+        assert_eq!(refunds[0].amount.as_deref(), Some("400"));
+        assert_eq!(refunds[0].transaction.as_deref(), Some("0xdef"));
+        assert_eq!(refunds[0].refund_status, Some(RefundStatus::Sent));
+        assert_eq!(refunds[1].refund_status, Some(RefundStatus::Pending));
+        assert_eq!(refunds[1].refund_number, refunds[0].refund_number);
+        assert_eq!(refunds[0].payer, settlement.payer);
+        fs::remove_file(&path).unwrap();
+    }
+}
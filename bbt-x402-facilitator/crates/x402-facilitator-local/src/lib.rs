@@ -7,15 +7,75 @@
 //! using registered scheme handlers.
 //!
 //! This crate provides:
-//! - route-level error handling via Axum handlers
+//! - route-level error handling via Axum handlers, mountable in an existing Axum or
+//!   tower application
 //! - request-level compliance screening
-//! - chain and scheme orchestration with an internal registry
+//! - optional API key authentication with per-key scopes
+//! - per-client rate limiting and settlement quotas
+//! - settlement retries with a dead-letter queue and admin API
+//! - per-signer gas and value caps that pause settlement until resumed
+//! - draining of in-flight settlements on shutdown, with recovery of interrupted ones
+//! - settlement notifications routed per merchant, with an outbox for guaranteed delivery
+//! - settlement and compliance event streaming to Kafka or NATS
+//! - a live Server-Sent Events feed of payment activity
+//! - a catalog of priced resources for clients to discover
+//! - a redacted log of verify and settle payloads for dispute forensics
+//! - a persisted, queryable ledger of settlement attempts, and refunds of settled payments (`storage` feature)
+//! - leader election between replicas for singleton background jobs
+//! - chain and scheme orchestration with an internal registry, inspected and
+//!   switched off at runtime through the admin API
+//! - an OpenAPI description of the payment API (`openapi` feature)
+//! - a gRPC interface to verification, settlement and settlement events (`grpc` feature)
 
+pub mod auth;
+pub mod cluster;
 pub mod compliance;
+pub mod dead_letter;
+pub mod discovery;
+pub mod embed;
+pub mod event_bus;
 pub mod facilitator_local;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod guardrail;
 pub mod handlers;
+pub mod in_flight;
+#[cfg(feature = "storage")]
+pub mod ledger;
+pub mod notify;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod outbox;
+pub mod payload_log;
+pub mod payment_events;
+pub mod rate_limit;
+#[cfg(feature = "storage")]
+pub mod refund;
+pub mod registry;
+pub mod response_format;
 pub mod util;
+pub mod velocity;
 
+pub use auth::{ApiKeyAuth, ApiKeyIdentity};
+pub use cluster::{Cluster, ClusterStatus, LeaseStore};
 pub use compliance::*;
+pub use dead_letter::{DeadLetter, DeadLetterQueue, DeadLetterStats, SettlementRetry};
+pub use discovery::{DiscoveryCatalog, DiscoveryQuery, ResourceRegistration};
+pub use embed::EmbeddedFacilitator;
+pub use event_bus::{BusEvent, EventBus};
 pub use facilitator_local::*;
+pub use guardrail::{GuardrailPause, GuardrailStatus, SettlementGuardrail, SignerUsage};
 pub use handlers::*;
+pub use in_flight::{InFlightGuard, InFlightSettlement, InFlightSettlements};
+#[cfg(feature = "storage")]
+pub use ledger::{LedgerEntry, LedgerQuery, LedgerStore, RefundStatus, SettlementLedger};
+pub use notify::{NotificationDispatcher, NotificationEvent};
+pub use outbox::{Outbox, OutboxStore};
+pub use payload_log::{PayloadLog, PayloadRedaction};
+pub use payment_events::{PaymentEvent, PaymentEventFilter, PaymentEventType, PaymentEvents};
+pub use rate_limit::{RateLimitConfig, RateLimiter};
+#[cfg(feature = "storage")]
+pub use refund::{RefundError, RefundOrder};
+pub use registry::{RegisteredChain, RegisteredScheme, RuntimeToggles};
+pub use response_format::ResponseAmountFormat;
+pub use velocity::{VelocityLimit, VelocityLimiter};
@@ -0,0 +1,516 @@
+//! Notifications about settlements.
+//!
+//! [`NotificationDispatcher`] sends settlement events to people: a JSON webhook,
+//! a Slack incoming webhook, or an email through an SMTP relay. Which channel an
+//! event goes to is decided by routing rules, so every merchant can get the
+//! failures of its own payments while operators get everything above a severity.
+//!
+//! | Event | Severity |
+//! |-------|----------|
+//! | `settlement_succeeded` | `info` |
+//! | `settlement_rejected` | `warning` |
+//! | `settlement_failed` | `error` |
+//! | `settlement_dead_lettered` | `critical` |
+//! | `settlement_refunded` | `info` |
+//!
+//! # Configuration
+//!
+//! Channels and rules are read from the `notifications` section of the
+//! configuration file (see [`x402_types::config::NotificationsConfig`]):
+//!
+//! ```json
+//! {
+//!   "notifications": {
+//!     "channels": {
+//!       "ops-slack": { "type": "slack", "webhook_url": "$SLACK_WEBHOOK_URL" },
+//!       "ops-mail": {
+//!         "type": "smtp",
+//!         "server": "localhost:25",
+//!         "from": "x402@example.com",
+//!         "to": ["oncall@example.com"]
+//!       },
+//!       "shop": { "type": "webhook", "url": "https://shop.example.com/x402/events" }
+//!     },
+//!     "rules": [
+//!       { "channel": "ops-slack", "min_severity": "error" },
+//!       { "channel": "ops-mail", "events": ["settlement_dead_lettered"] },
+//!       { "channel": "shop", "merchant": "0x3333333333333333333333333333333333333333" }
+//!     ]
+//!   }
+//! }
+//! ```
+//!
+//! The SMTP channel speaks plain SMTP without TLS or authentication. Point it at a
+//! relay on the same host or private network (a local MTA or mail sidecar).
+//!
+//! Notifications are delivered in the background and never delay the `/settle`
+//! response. Delivery failures are logged and dropped, unless an
+//! [`Outbox`](crate::outbox::Outbox) is attached to the facilitator: it stores
+//! events before `/settle` responds and retries them until every channel took them.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use x402_types::config::{
+    NotificationChannelConfig, NotificationEventKind, NotificationSeverity, NotificationsConfig,
+};
+use x402_types::proto;
+use x402_types::timestamp::UnixTimestamp;
+
+/// Time allowed for delivering a notification through a single channel.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A facilitator event worth telling someone about.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationEvent {
+    /// Unique event id, set when the event goes through the outbox. Receivers can
+    /// use it to drop events delivered twice.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub kind: NotificationEventKind,
+    pub severity: NotificationSeverity,
+    /// Merchant (`payTo`) address of the payment.
+    pub merchant: Option<String>,
+    /// Payer address of the payment.
+    pub payer: Option<String>,
+    /// CAIP-2 chain id of the payment.
+    pub network: Option<String>,
+    /// Human-readable description of what happened.
+    pub message: String,
+    pub timestamp: UnixTimestamp,
+}
+
+impl NotificationEvent {
+    /// Creates an event about `request`, with the severity of its kind.
+    pub fn for_settlement(
+        kind: NotificationEventKind,
+        request: &proto::SettleRequest,
+        message: impl Into<String>,
+    ) -> Self {
+        let severity = match kind {
+            NotificationEventKind::SettlementSucceeded => NotificationSeverity::Info,
+            NotificationEventKind::SettlementRejected => NotificationSeverity::Warning,
+            NotificationEventKind::SettlementFailed => NotificationSeverity::Error,
+            NotificationEventKind::SettlementDeadLettered => NotificationSeverity::Critical,
+            NotificationEventKind::SettlementRefunded => NotificationSeverity::Info,
+        };
+        Self {
+            id: None,
+            kind,
+            severity,
+            merchant: request.payee(),
+            payer: request.payer(),
+            network: request
+                .scheme_handler_slug()
+                .map(|slug| slug.chain_id.to_string()),
+            message: message.into(),
+            timestamp: UnixTimestamp::now(),
+        }
+    }
+
+    /// One-line summary used by the chat and email channels.
+    pub fn summary(&self) -> String {
+        format!(
+            "[{}] {}: {} (merchant {}, payer {}, network {})",
+            self.severity,
+            self.kind,
+            self.message,
+            self.merchant.as_deref().unwrap_or("unknown"),
+            self.payer.as_deref().unwrap_or("unknown"),
+            self.network.as_deref().unwrap_or("unknown"),
+        )
+    }
+}
+
+/// Failure to deliver a notification.
+#[derive(Debug, thiserror::Error)]
+pub enum NotifyError {
+    #[error("HTTP delivery failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("SMTP delivery failed: {0}")]
+    Smtp(String),
+    #[error("delivery timed out")]
+    Timeout,
+    #[error("unknown notification channel {0}")]
+    UnknownChannel(String),
+}
+
+impl From<std::io::Error> for NotifyError {
+    fn from(error: std::io::Error) -> Self {
+        NotifyError::Smtp(error.to_string())
+    }
+}
+
+/// A way of delivering notifications.
+#[async_trait::async_trait]
+pub trait NotificationChannel: Send + Sync {
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifyError>;
+}
+
+/// Posts the event as JSON to an HTTP endpoint.
+pub struct WebhookChannel {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookChannel {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationChannel for WebhookChannel {
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifyError> {
+        self.client
+            .post(&self.url)
+            .json(event)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Posts the event summary to a Slack incoming webhook.
+pub struct SlackChannel {
+    client: reqwest::Client,
+    webhook_url: String,
+}
+
+impl SlackChannel {
+    pub fn new(webhook_url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            webhook_url: webhook_url.into(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationChannel for SlackChannel {
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifyError> {
+        self.client
+            .post(&self.webhook_url)
+            .json(&json!({ "text": event.summary() }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Emails the event through an SMTP relay.
+pub struct SmtpChannel {
+    server: String,
+    from: String,
+    to: Vec<String>,
+}
+
+impl SmtpChannel {
+    pub fn new(server: impl Into<String>, from: impl Into<String>, to: Vec<String>) -> Self {
+        Self {
+            server: server.into(),
+            from: from.into(),
+            to,
+        }
+    }
+
+    fn message(&self, event: &NotificationEvent) -> String {
+        let body = serde_json::to_string_pretty(event).unwrap_or_default();
+        // Dot-stuffing: a line starting with '.' gets another one.
+        let body = body
+            .lines()
+            .map(|line| {
+                if line.starts_with('.') {
+                    format!(".{line}")
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        format!(
+            "From: <{}>\r\nTo: {}\r\nSubject: x402 facilitator: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n\r\n{}\r\n.\r\n",
+            self.from,
+            self.to
+                .iter()
+                .map(|to| format!("<{to}>"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            event.summary().replace(['\r', '\n'], " "),
+            event.summary(),
+            body,
+        )
+    }
+}
+
+/// Reads an SMTP reply, including continuation lines, and checks its code.
+async fn expect_reply<R>(reader: &mut R, expected: &str) -> Result<(), NotifyError>
+where
+    R: AsyncBufReadExt + Unpin,
+{
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Err(NotifyError::Smtp("connection closed".to_string()));
+        }
+        if !line.starts_with(expected) {
+            return Err(NotifyError::Smtp(format!(
+                "unexpected reply: {}",
+                line.trim_end()
+            )));
+        }
+        // "250-..." continues, "250 ..." is the last line.
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(());
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationChannel for SmtpChannel {
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifyError> {
+        let stream = TcpStream::connect(&self.server).await?;
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        expect_reply(&mut reader, "220").await?;
+        writer.write_all(b"EHLO x402-facilitator\r\n").await?;
+        expect_reply(&mut reader, "250").await?;
+        writer
+            .write_all(format!("MAIL FROM:<{}>\r\n", self.from).as_bytes())
+            .await?;
+        expect_reply(&mut reader, "250").await?;
+        for to in &self.to {
+            writer
+                .write_all(format!("RCPT TO:<{to}>\r\n").as_bytes())
+                .await?;
+            expect_reply(&mut reader, "25").await?;
+        }
+        writer.write_all(b"DATA\r\n").await?;
+        expect_reply(&mut reader, "354").await?;
+        writer.write_all(self.message(event).as_bytes()).await?;
+        expect_reply(&mut reader, "250").await?;
+        writer.write_all(b"QUIT\r\n").await?;
+        Ok(())
+    }
+}
+
+/// Routes an event to a channel.
+#[derive(Clone, Debug)]
+pub struct NotificationRule {
+    pub channel: String,
+    /// Lowercased merchant address, or `None` for every merchant.
+    pub merchant: Option<String>,
+    /// Event types, or empty for every type.
+    pub events: Vec<NotificationEventKind>,
+    pub min_severity: NotificationSeverity,
+}
+
+impl NotificationRule {
+    /// Returns `true` if `event` should be sent to the rule's channel.
+    pub fn matches(&self, event: &NotificationEvent) -> bool {
+        let merchant_matches = match (&self.merchant, &event.merchant) {
+            (None, _) => true,
+            (Some(merchant), Some(payee)) => merchant.eq_ignore_ascii_case(payee),
+            (Some(_), None) => false,
+        };
+        merchant_matches
+            && (self.events.is_empty() || self.events.contains(&event.kind))
+            && event.severity >= self.min_severity
+    }
+}
+
+/// Sends events to the channels of every matching rule.
+#[derive(Clone, Default)]
+pub struct NotificationDispatcher {
+    channels: HashMap<String, Arc<dyn NotificationChannel>>,
+    rules: Vec<NotificationRule>,
+}
+
+impl NotificationDispatcher {
+    /// Creates a dispatcher without channels or rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a named channel.
+    pub fn with_channel(
+        mut self,
+        name: impl Into<String>,
+        channel: impl NotificationChannel + 'static,
+    ) -> Self {
+        self.channels.insert(name.into(), Arc::new(channel));
+        self
+    }
+
+    /// Adds a routing rule.
+    pub fn with_rule(mut self, rule: NotificationRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Builds the dispatcher from the `notifications` configuration section.
+    ///
+    /// Returns `None` if no rules are configured. Rules naming an unknown channel are rejected.
+    pub fn from_config(config: &NotificationsConfig) -> Result<Option<Self>, String> {
+        if config.rules.is_empty() {
+            return Ok(None);
+        }
+        let mut dispatcher = Self::new();
+        for (name, channel) in &config.channels {
+            dispatcher = match channel {
+                NotificationChannelConfig::Webhook { url } => {
+                    dispatcher.with_channel(name, WebhookChannel::new(url.inner()))
+                }
+                NotificationChannelConfig::Slack { webhook_url } => {
+                    dispatcher.with_channel(name, SlackChannel::new(webhook_url.inner()))
+                }
+                NotificationChannelConfig::Smtp { server, from, to } => {
+                    if to.is_empty() {
+                        return Err(format!("notification channel {name}: no recipients"));
+                    }
+                    dispatcher.with_channel(name, SmtpChannel::new(server, from, to.clone()))
+                }
+            };
+        }
+        for rule in &config.rules {
+            if !dispatcher.channels.contains_key(&rule.channel) {
+                return Err(format!(
+                    "notification rule references unknown channel {}",
+                    rule.channel
+                ));
+            }
+            dispatcher = dispatcher.with_rule(NotificationRule {
+                channel: rule.channel.clone(),
+                merchant: rule.merchant.as_deref().map(str::to_lowercase),
+                events: rule.events.clone(),
+                min_severity: rule.min_severity,
+            });
+        }
+        Ok(Some(dispatcher))
+    }
+
+    /// Names of the channels `event` is routed to, each listed once.
+    pub fn route(&self, event: &NotificationEvent) -> Vec<&str> {
+        let mut channels: Vec<&str> = Vec::new();
+        for rule in self.rules.iter().filter(|rule| rule.matches(event)) {
+            if !channels.contains(&rule.channel.as_str()) {
+                channels.push(&rule.channel);
+            }
+        }
+        channels
+    }
+
+    /// Delivers `event` to channel `name` and waits for the outcome.
+    pub async fn deliver(&self, name: &str, event: &NotificationEvent) -> Result<(), NotifyError> {
+        let channel = self
+            .channels
+            .get(name)
+            .ok_or_else(|| NotifyError::UnknownChannel(name.to_string()))?;
+        send_with_timeout(channel.as_ref(), event).await
+    }
+
+    /// Delivers `event` to its channels in the background.
+    pub fn notify(&self, event: NotificationEvent) {
+        let event = Arc::new(event);
+        for name in self.route(&event) {
+            let Some(channel) = self.channels.get(name).cloned() else {
+                continue;
+            };
+            let event = event.clone();
+            let _name = name.to_string();
+            tokio::spawn(async move {
+                let result = send_with_timeout(channel.as_ref(), &event).await;
+                #[cfg(feature = "telemetry")]
+                if let Err(e) = result {
+                    tracing::warn!(channel = %_name, error = %e, "Notification delivery failed");
+                }
+                #[cfg(not(feature = "telemetry"))]
+                let _ = result;
+            });
+        }
+    }
+}
+
+async fn send_with_timeout(
+    channel: &dyn NotificationChannel,
+    event: &NotificationEvent,
+) -> Result<(), NotifyError> {
+    tokio::time::timeout(DELIVERY_TIMEOUT, channel.send(event))
+        .await
+        .unwrap_or(Err(NotifyError::Timeout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHOP: &str = "0x3333333333333333333333333333333333333333";
+
+    fn event(kind: NotificationEventKind) -> NotificationEvent {
+        let request: proto::SettleRequest = json!({
+            "x402Version": 2,
+            "paymentPayload": { "x402Version": 2, "accepted": {}, "payload": {} },
+            "paymentRequirements": { "payTo": SHOP },
+        })
+        .into();
+        NotificationEvent::for_settlement(kind, &request, "receipt timeout")
+    }
+
+    #[test]
+    fn test_routing_rules() {
+        let config: NotificationsConfig = serde_json::from_value(json!({
+            "channels": {
+                "ops": { "type": "slack", "webhook_url": "https://hooks.slack.test/x" },
+                "shop": { "type": "webhook", "url": "https://shop.test/events" },
+                "mail": { "type": "smtp", "server": "localhost:25", "from": "a@b.c", "to": ["d@e.f"] }
+            },
+            "rules": [
+                { "channel": "ops", "min_severity": "error" },
+                { "channel": "mail", "events": ["settlement_dead_lettered"] },
+                { "channel": "shop", "merchant": SHOP.to_uppercase().replace("0X", "0x") }
+            ]
+        }))
+        .unwrap();
+        let dispatcher = NotificationDispatcher::from_config(&config)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            dispatcher.route(&event(NotificationEventKind::SettlementRejected)),
+            vec!["shop"]
+        );
+        assert_eq!(
+            dispatcher.route(&event(NotificationEventKind::SettlementFailed)),
+            vec!["ops", "shop"]
+        );
+        assert_eq!(
+            dispatcher.route(&event(NotificationEventKind::SettlementDeadLettered)),
+            vec!["ops", "mail", "shop"]
+        );
+    }
+
+    #[test]
+    fn test_unknown_channel_rejected() {
+        let config: NotificationsConfig = serde_json::from_value(json!({
+            "rules": [{ "channel": "missing" }]
+        }))
+        .unwrap();
+        assert!(NotificationDispatcher::from_config(&config).is_err());
+        assert!(
+            NotificationDispatcher::from_config(&NotificationsConfig::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! OpenAPI description of the facilitator payment API.
+//!
+//! [`FacilitatorApi`] describes `POST /verify`, `POST /settle`, `GET /supported`
+//! and `GET /health` as served by [`routes`](crate::handlers::routes), so that
+//! integrators in other languages can generate clients from the spec instead of
+//! reading the Rust types. The admin and operational endpoints are not part of it.
+//!
+//! `/verify` and `/settle` declare the optional API key authentication of
+//! [`auth`](crate::auth): a key in `Authorization: Bearer` or in `X-API-Key`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use utoipa::OpenApi;
+//! use x402_facilitator_local::openapi::FacilitatorApi;
+//!
+//! let spec = FacilitatorApi::openapi().to_pretty_json()?;
+//! ```
+
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use x402_types::proto;
+
+use crate::handlers;
+
+/// The OpenAPI spec of the facilitator payment API.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "x402 facilitator",
+        description = "Verifies and settles x402 payments on behalf of resource servers."
+    ),
+    paths(
+        handlers::post_verify,
+        handlers::post_settle,
+        handlers::get_supported,
+        handlers::get_health
+    ),
+    components(schemas(
+        proto::openapi::VerifyRequestSchema,
+        proto::openapi::PaymentPayloadSchema,
+        proto::openapi::PaymentRequirementsSchema,
+        proto::openapi::VerifyResponseSchema,
+        proto::openapi::SettleResponseSchema,
+        proto::openapi::SettleDryRunResponseSchema,
+        proto::openapi::DryRunTransactionSchema,
+        proto::v2::ResourceInfo,
+        proto::ConfirmationPolicy,
+        proto::ErrorReason,
+        proto::SupportedResponse,
+        proto::SupportedPaymentKind
+    )),
+    modifiers(&ApiKeySecurity),
+    tags((name = "payments", description = "Payment verification and settlement"))
+)]
+pub struct FacilitatorApi;
+
+/// Registers the `bearer` and `api_key` security schemes of [`crate::auth`].
+struct ApiKeySecurity;
+
+impl Modify for ApiKeySecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_describes_payment_endpoints() {
+        let spec = serde_json::to_value(FacilitatorApi::openapi()).unwrap();
+        for path in ["/verify", "/settle", "/supported", "/health"] {
+            assert!(spec["paths"][path].is_object(), "missing {path}");
+        }
+        let settle = &spec["paths"]["/settle"]["post"];
+        assert_eq!(settle["parameters"][0]["name"], "dryRun");
+        assert_eq!(
+            settle["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/VerifyRequest"
+        );
+        let schemas = &spec["components"]["schemas"];
+        assert_eq!(
+            schemas["VerifyRequest"]["properties"]["paymentPayload"]["$ref"],
+            "#/components/schemas/PaymentPayload"
+        );
+        assert!(schemas["ErrorReason"]["enum"].as_array().unwrap().len() > 1);
+        assert!(spec["components"]["securitySchemes"]["bearer"].is_object());
+        assert!(spec["components"]["securitySchemes"]["api_key"].is_object());
+    }
+}
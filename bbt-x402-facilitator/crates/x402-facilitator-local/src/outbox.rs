@@ -0,0 +1,414 @@
+//! Guaranteed delivery of settlement notifications.
+//!
+//! Without an outbox, [`NotificationDispatcher::notify`] tries each channel once,
+//! and an event is lost when the channel is down or the facilitator stops before
+//! it was sent. With an [`Outbox`] attached through
+//! [`FacilitatorLocal::with_outbox`](crate::FacilitatorLocal::with_outbox), every
+//! settlement event is written to an [`OutboxStore`], together with the channels
+//! it is routed to, before `/settle` responds. An entry is removed only once every
+//! one of its channels accepted the event.
+//!
+//! Delivery is attempted right away, and [`Outbox::deliver_due`] retries failed
+//! channels with exponential backoff, up to [`MAX_RETRY_BACKOFF`]. The facilitator
+//! runs it periodically, so entries restored from the store after a crash are
+//! delivered once it is back up.
+//!
+//! Delivery is at least once: a channel sees an event twice if the facilitator
+//! stops between sending it and recording the delivery. Every event carries an
+//! `id` that receivers can deduplicate on.
+//!
+//! # Configuration
+//!
+//! | Variable | Description |
+//! |----------|-------------|
+//! | `NOTIFICATION_OUTBOX_ENABLED` | Deliver notifications through the outbox (default: `false`) |
+//! | `NOTIFICATION_OUTBOX_PATH` | JSON file the outbox is persisted to and restored from (default: in memory only) |
+//! | `NOTIFICATION_OUTBOX_RETRY_BACKOFF_MS` | Delay before the first retry, doubled on every further retry (default: `1000`) |
+//!
+//! Without `NOTIFICATION_OUTBOX_PATH`, undelivered events survive channel outages
+//! but not restarts. Other stores, such as a database table, plug in by
+//! implementing [`OutboxStore`].
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use x402_types::timestamp::UnixTimestamp;
+
+use crate::notify::{NotificationDispatcher, NotificationEvent, NotifyError};
+
+/// Longest delay between two delivery attempts of an entry.
+pub const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(15 * 60);
+
+const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(1000);
+
+/// An event waiting to be delivered.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxEntry {
+    pub id: u64,
+    pub event: NotificationEvent,
+    /// Channels that have not accepted the event yet.
+    pub pending_channels: Vec<String>,
+    /// Number of failed delivery attempts.
+    pub attempts: u32,
+    /// Error of the last failed attempt, prefixed with the channel name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// Earliest time of the next attempt.
+    pub next_attempt_at: UnixTimestamp,
+}
+
+/// Content of an [`OutboxStore`].
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxState {
+    pub next_id: u64,
+    pub entries: BTreeMap<u64, OutboxEntry>,
+}
+
+/// Where the outbox keeps undelivered events.
+///
+/// [`OutboxStore::save`] must not return before the state is durable: an event is
+/// only considered accepted once it was saved.
+pub trait OutboxStore: Send + Sync + std::fmt::Debug {
+    fn load(&self) -> Result<OutboxState, String>;
+    fn save(&self, state: &OutboxState) -> std::io::Result<()>;
+}
+
+/// Keeps the outbox in memory only.
+#[derive(Debug, Default)]
+pub struct MemoryOutboxStore;
+
+impl OutboxStore for MemoryOutboxStore {
+    fn load(&self) -> Result<OutboxState, String> {
+        Ok(OutboxState::default())
+    }
+
+    fn save(&self, _state: &OutboxState) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Keeps the outbox in a JSON file, replaced atomically on every change.
+#[derive(Debug)]
+pub struct FileOutboxStore {
+    path: PathBuf,
+}
+
+impl FileOutboxStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl OutboxStore for FileOutboxStore {
+    fn load(&self) -> Result<OutboxState, String> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("invalid outbox file {}: {e}", self.path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(OutboxState::default()),
+            Err(e) => Err(format!(
+                "failed to read outbox file {}: {e}",
+                self.path.display()
+            )),
+        }
+    }
+
+    fn save(&self, state: &OutboxState) -> std::io::Result<()> {
+        persist(&self.path, state)
+    }
+}
+
+/// Writes `state` to a temporary file, syncs it and renames it over `path`.
+fn persist(path: &Path, state: &OutboxState) -> std::io::Result<()> {
+    let content = serde_json::to_vec_pretty(state).map_err(std::io::Error::other)?;
+    let tmp = path.with_extension("tmp");
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(&content)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+/// Number of undelivered events and age of the oldest one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxStats {
+    pub depth: usize,
+    /// Seconds since the oldest undelivered event happened, if any.
+    pub oldest_age_secs: Option<u64>,
+}
+
+/// Settlement events stored until every channel accepted them.
+#[derive(Debug)]
+pub struct Outbox {
+    store: Box<dyn OutboxStore>,
+    backoff: Duration,
+    state: Mutex<OutboxState>,
+    /// Held while delivering, so that an entry is never sent by two runs at once.
+    delivering: tokio::sync::Mutex<()>,
+}
+
+impl Outbox {
+    /// Creates an outbox on `store`, restoring the entries already stored there.
+    pub fn new(store: impl OutboxStore + 'static, backoff: Duration) -> Result<Self, String> {
+        let state = store.load()?;
+        Ok(Self {
+            store: Box::new(store),
+            backoff,
+            state: Mutex::new(state),
+            delivering: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Builds the outbox from the `NOTIFICATION_OUTBOX_*` environment variables.
+    ///
+    /// Returns `None` unless `NOTIFICATION_OUTBOX_ENABLED` is set.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let enabled = env::var("NOTIFICATION_OUTBOX_ENABLED")
+            .map(|value| parse_bool(&value))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+        let backoff = match env::var("NOTIFICATION_OUTBOX_RETRY_BACKOFF_MS") {
+            Ok(value) => Duration::from_millis(
+                value
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|e| format!("invalid NOTIFICATION_OUTBOX_RETRY_BACKOFF_MS: {e}"))?,
+            ),
+            Err(_) => DEFAULT_RETRY_BACKOFF,
+        };
+        match env::var("NOTIFICATION_OUTBOX_PATH") {
+            Ok(path) if !path.trim().is_empty() => {
+                Self::new(FileOutboxStore::new(path.trim()), backoff).map(Some)
+            }
+            _ => Self::new(MemoryOutboxStore, backoff).map(Some),
+        }
+    }
+
+    /// Stores `event` for delivery to `channels`, returning its entry id.
+    ///
+    /// The event is durable once this returns `Ok`. On error nothing is stored.
+    pub fn enqueue(
+        &self,
+        mut event: NotificationEvent,
+        channels: Vec<String>,
+    ) -> std::io::Result<u64> {
+        let mut state = self.lock();
+        let id = state.next_id;
+        event.id = Some(format!("{}-{id}", event.timestamp.as_secs()));
+        let mut next = state.clone();
+        next.next_id += 1;
+        next.entries.insert(
+            id,
+            OutboxEntry {
+                id,
+                next_attempt_at: event.timestamp,
+                event,
+                pending_channels: channels,
+                attempts: 0,
+                last_error: None,
+            },
+        );
+        self.store.save(&next)?;
+        *state = next;
+        Ok(id)
+    }
+
+    /// Returns all undelivered entries, oldest first.
+    pub fn entries(&self) -> Vec<OutboxEntry> {
+        self.lock().entries.values().cloned().collect()
+    }
+
+    /// Returns the number of undelivered events and the age of the oldest one.
+    pub fn stats(&self) -> OutboxStats {
+        let state = self.lock();
+        let now = UnixTimestamp::now().as_secs();
+        OutboxStats {
+            depth: state.entries.len(),
+            oldest_age_secs: state
+                .entries
+                .values()
+                .map(|entry| entry.event.timestamp.as_secs())
+                .min()
+                .map(|oldest| now.saturating_sub(oldest)),
+        }
+    }
+
+    /// Delivers every entry whose next attempt is due.
+    ///
+    /// Channels that accept an event are removed from its entry, the others are
+    /// retried later. Returns an error naming the entries still pending after a
+    /// failure. Does nothing if another run is in progress.
+    pub async fn deliver_due(&self, dispatcher: &NotificationDispatcher) -> Result<(), String> {
+        let Ok(_delivering) = self.delivering.try_lock() else {
+            return Ok(());
+        };
+        let now = UnixTimestamp::now();
+        let due: Vec<OutboxEntry> = self
+            .lock()
+            .entries
+            .values()
+            .filter(|entry| entry.next_attempt_at <= now)
+            .cloned()
+            .collect();
+        let mut failed = 0;
+        for entry in due {
+            let mut pending = Vec::new();
+            let mut last_error = None;
+            for channel in &entry.pending_channels {
+                match dispatcher.deliver(channel, &entry.event).await {
+                    Ok(()) => {}
+                    // The channel was removed from the configuration, retrying cannot help.
+                    Err(NotifyError::UnknownChannel(_)) => {
+                        #[cfg(feature = "telemetry")]
+                        tracing::warn!(outbox_id = entry.id, channel = %channel, "Dropping notification for unknown channel");
+                    }
+                    Err(e) => {
+                        #[cfg(feature = "telemetry")]
+                        tracing::warn!(outbox_id = entry.id, channel = %channel, error = %e, "Notification delivery failed");
+                        last_error = Some(format!("{channel}: {e}"));
+                        pending.push(channel.clone());
+                    }
+                }
+            }
+            if !pending.is_empty() {
+                failed += 1;
+            }
+            self.record_attempt(entry.id, pending, last_error);
+        }
+        if failed > 0 {
+            return Err(format!("{failed} notifications left in the outbox"));
+        }
+        Ok(())
+    }
+
+    /// Removes delivered channels from entry `id`, and the entry once none is left.
+    fn record_attempt(&self, id: u64, pending: Vec<String>, last_error: Option<String>) {
+        let mut state = self.lock();
+        if pending.is_empty() {
+            state.entries.remove(&id);
+        } else if let Some(entry) = state.entries.get_mut(&id) {
+            entry.attempts += 1;
+            let delay = self
+                .backoff
+                .saturating_mul(2u32.saturating_pow(entry.attempts - 1))
+                .min(MAX_RETRY_BACKOFF);
+            entry.next_attempt_at =
+                UnixTimestamp::from_secs(UnixTimestamp::now().as_secs() + delay.as_secs());
+            entry.pending_channels = pending;
+            entry.last_error = last_error;
+        }
+        // A lost update only means a channel gets the event again after a restart.
+        if let Err(_e) = self.store.save(&state) {
+            #[cfg(feature = "telemetry")]
+            tracing::error!(error = %_e, "Failed to persist notification outbox");
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, OutboxState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn parse_bool(value: &str) -> bool {
+    matches!(
+        value.to_lowercase().as_str(),
+        "1" | "true" | "yes" | "y" | "on" | "enabled"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::{NotificationChannel, NotificationRule};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use x402_types::config::{NotificationEventKind, NotificationSeverity};
+    use x402_types::proto;
+
+    #[derive(Default)]
+    struct TestChannel {
+        down: AtomicBool,
+        sent: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl NotificationChannel for Arc<TestChannel> {
+        async fn send(&self, _event: &NotificationEvent) -> Result<(), NotifyError> {
+            if self.down.load(Ordering::SeqCst) {
+                return Err(NotifyError::Timeout);
+            }
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn rule(channel: &str) -> NotificationRule {
+        NotificationRule {
+            channel: channel.to_string(),
+            merchant: None,
+            events: Vec::new(),
+            min_severity: NotificationSeverity::Info,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_undelivered_events_survive_restart() {
+        let path = env::temp_dir().join(format!("x402-outbox-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let ops = Arc::new(TestChannel::default());
+        let shop = Arc::new(TestChannel::default());
+        shop.down.store(true, Ordering::SeqCst);
+        let dispatcher = NotificationDispatcher::new()
+            .with_channel("ops", ops.clone())
+            .with_channel("shop", shop.clone())
+            .with_rule(rule("ops"))
+            .with_rule(rule("shop"));
+        let request: proto::SettleRequest = serde_json::json!({
+            "x402Version": 2,
+            "paymentPayload": {},
+            "paymentRequirements": {}
+        })
+        .into();
+        let event = NotificationEvent::for_settlement(
+            NotificationEventKind::SettlementSucceeded,
+            &request,
+            "settled",
+        );
+        let channels = dispatcher
+            .route(&event)
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+
+        let outbox = Outbox::new(FileOutboxStore::new(&path), Duration::ZERO).unwrap();
+        outbox.enqueue(event, channels).unwrap();
+        assert!(outbox.deliver_due(&dispatcher).await.is_err());
+        assert_eq!(ops.sent.load(Ordering::SeqCst), 1);
+
+        let restored = Outbox::new(FileOutboxStore::new(&path), Duration::ZERO).unwrap();
+        let entries = restored.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].pending_channels, vec!["shop"]);
+        assert_eq!(entries[0].attempts, 1);
+        assert!(entries[0].event.id.is_some());
+
+        shop.down.store(false, Ordering::SeqCst);
+        restored.deliver_due(&dispatcher).await.unwrap();
+        assert_eq!(ops.sent.load(Ordering::SeqCst), 1);
+        assert_eq!(shop.sent.load(Ordering::SeqCst), 1);
+        assert_eq!(restored.stats().depth, 0);
+
+        let _ = fs::remove_file(&path);
+    }
+}
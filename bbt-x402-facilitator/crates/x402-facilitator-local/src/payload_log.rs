@@ -0,0 +1,469 @@
+//! Redacted logging of verify and settle payloads, for forensics after disputes.
+//!
+//! With a [`PayloadLog`] attached through
+//! [`FacilitatorLocal::with_payload_log`](crate::FacilitatorLocal::with_payload_log),
+//! every `/verify` and `/settle` request is written, with its response or error, as
+//! one JSON line to each configured [`AuditSink`]:
+//!
+//! ```json
+//! {"timestamp":"1700000000","operation":"settle","network":"eip155:42793","scheme":"exact","request":{...},"response":{...}}
+//! ```
+//!
+//! Before a record is written, [`PayloadRedaction`] rewrites its sensitive fields:
+//!
+//! - signatures (`signature` and `*Signature` fields) are hashed or left out, so
+//!   a disputed signature can be matched against the log without the log holding
+//!   a signature that could be replayed
+//! - payer and payee addresses (`from`, `to`, `payTo`, `owner`, `payer`, `payee`,
+//!   `recipient`), and addresses in error messages, are kept, shortened or hashed
+//!
+//! Hashes are HMAC-SHA256 under `PAYLOAD_LOG_HASH_KEY` when it is set, so that
+//! addresses cannot be recovered by hashing known ones, and SHA-256 otherwise.
+//!
+//! # Configuration
+//!
+//! | Variable | Description |
+//! |----------|-------------|
+//! | `PAYLOAD_LOG_SINKS` | Comma-separated sinks: `file` and `stdout` (default: payloads are not logged) |
+//! | `PAYLOAD_LOG_PATH` | File the `file` sink appends to |
+//! | `PAYLOAD_LOG_SIGNATURES` | `hash` (default) or `omit` |
+//! | `PAYLOAD_LOG_ADDRESSES` | `full` (default), `truncate` or `hash` |
+//! | `PAYLOAD_LOG_HASH_KEY` | Key of the HMAC-SHA256 hashes (default: unkeyed SHA-256) |
+//!
+//! Like compliance audit records, payload records never block or fail a request.
+
+use std::env;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use alloy_primitives::hex;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use x402_types::proto;
+use x402_types::timestamp::UnixTimestamp;
+
+use crate::compliance::audit::{AuditSink, FileAuditSink, StdoutAuditSink};
+
+/// Fields holding the address of a party to a payment.
+const ADDRESS_FIELDS: &[&str] = &[
+    "from",
+    "to",
+    "payTo",
+    "owner",
+    "payer",
+    "payee",
+    "recipient",
+];
+
+/// How signatures are written to the payload log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SignatureRedaction {
+    /// Replaced by their hash.
+    #[default]
+    Hash,
+    /// Left out.
+    Omit,
+}
+
+impl FromStr for SignatureRedaction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "hash" => Ok(Self::Hash),
+            "omit" => Ok(Self::Omit),
+            other => Err(format!("unknown signature redaction: {other}")),
+        }
+    }
+}
+
+/// How payer and payee addresses are written to the payload log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AddressRedaction {
+    /// Written as they are.
+    #[default]
+    Full,
+    /// Shortened to their first and last four hex digits, as in `0x1234...abcd`.
+    Truncate,
+    /// Replaced by their hash.
+    Hash,
+}
+
+impl FromStr for AddressRedaction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "full" => Ok(Self::Full),
+            "truncate" => Ok(Self::Truncate),
+            "hash" => Ok(Self::Hash),
+            other => Err(format!("unknown address redaction: {other}")),
+        }
+    }
+}
+
+/// What the payload log redacts, see the [module documentation](self).
+#[derive(Debug, Clone, Default)]
+pub struct PayloadRedaction {
+    pub signatures: SignatureRedaction,
+    pub addresses: AddressRedaction,
+    /// Key of the HMAC-SHA256 hashes. Hashes are unkeyed SHA-256 without one.
+    pub hash_key: Option<Vec<u8>>,
+}
+
+impl PayloadRedaction {
+    /// Reads the policy from `PAYLOAD_LOG_SIGNATURES`, `PAYLOAD_LOG_ADDRESSES` and
+    /// `PAYLOAD_LOG_HASH_KEY`.
+    pub fn from_env() -> Result<Self, String> {
+        let signatures = match env::var("PAYLOAD_LOG_SIGNATURES") {
+            Ok(value) => value
+                .parse()
+                .map_err(|e| format!("invalid PAYLOAD_LOG_SIGNATURES: {e}"))?,
+            Err(_) => SignatureRedaction::default(),
+        };
+        let addresses = match env::var("PAYLOAD_LOG_ADDRESSES") {
+            Ok(value) => value
+                .parse()
+                .map_err(|e| format!("invalid PAYLOAD_LOG_ADDRESSES: {e}"))?,
+            Err(_) => AddressRedaction::default(),
+        };
+        let hash_key = env::var("PAYLOAD_LOG_HASH_KEY")
+            .ok()
+            .filter(|key| !key.is_empty())
+            .map(String::into_bytes);
+        Ok(Self {
+            signatures,
+            addresses,
+            hash_key,
+        })
+    }
+
+    /// Returns `value` with its sensitive fields redacted.
+    pub fn redact(&self, value: &Value) -> Value {
+        let mut value = value.clone();
+        self.redact_in_place(&mut value);
+        value
+    }
+
+    fn redact_in_place(&self, value: &mut Value) {
+        match value {
+            Value::Object(object) => {
+                let signatures: Vec<String> = object
+                    .keys()
+                    .filter(|key| is_signature_field(key))
+                    .cloned()
+                    .collect();
+                for key in signatures {
+                    match self.signatures {
+                        SignatureRedaction::Omit => {
+                            object.remove(&key);
+                        }
+                        SignatureRedaction::Hash => {
+                            let hash = match &object[&key] {
+                                Value::String(signature) => self.hash(signature),
+                                other => self.hash(&other.to_string()),
+                            };
+                            object.insert(key, Value::String(hash));
+                        }
+                    }
+                }
+                for (key, field) in object.iter_mut() {
+                    match field {
+                        Value::String(address)
+                            if ADDRESS_FIELDS.contains(&key.as_str()) && is_address(address) =>
+                        {
+                            *address = self.redact_address(address);
+                        }
+                        _ => self.redact_in_place(field),
+                    }
+                }
+            }
+            Value::Array(values) => values
+                .iter_mut()
+                .for_each(|value| self.redact_in_place(value)),
+            _ => {}
+        }
+    }
+
+    /// Returns `text` with every address in it redacted.
+    pub fn redact_text(&self, text: &str) -> String {
+        if self.addresses == AddressRedaction::Full {
+            return text.to_string();
+        }
+        let mut redacted = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("0x") {
+            let candidate = rest[start..].get(..42);
+            let followed_by_hex = rest
+                .as_bytes()
+                .get(start + 42)
+                .is_some_and(u8::is_ascii_hexdigit);
+            redacted.push_str(&rest[..start]);
+            match candidate {
+                Some(address) if is_address(address) && !followed_by_hex => {
+                    redacted.push_str(&self.redact_address(address));
+                    rest = &rest[start + 42..];
+                }
+                _ => {
+                    redacted.push_str("0x");
+                    rest = &rest[start + 2..];
+                }
+            }
+        }
+        redacted.push_str(rest);
+        redacted
+    }
+
+    fn redact_address(&self, address: &str) -> String {
+        match self.addresses {
+            AddressRedaction::Full => address.to_string(),
+            AddressRedaction::Truncate => format!("{}...{}", &address[..6], &address[38..]),
+            AddressRedaction::Hash => self.hash(&address.to_lowercase()),
+        }
+    }
+
+    fn hash(&self, value: &str) -> String {
+        match &self.hash_key {
+            Some(key) => {
+                let mut mac =
+                    Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+                mac.update(value.as_bytes());
+                format!("hmac-sha256:{}", hex::encode(mac.finalize().into_bytes()))
+            }
+            None => format!("sha256:{}", hex::encode(Sha256::digest(value.as_bytes()))),
+        }
+    }
+}
+
+fn is_signature_field(key: &str) -> bool {
+    key == "signature" || key.ends_with("Signature")
+}
+
+fn is_address(value: &str) -> bool {
+    value.len() == 42
+        && value.starts_with("0x")
+        && value[2..].bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// One line of the payload log.
+#[derive(Debug, Serialize)]
+struct PayloadLogRecord<'a> {
+    timestamp: UnixTimestamp,
+    operation: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scheme: Option<String>,
+    request: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Writes redacted verify and settle payloads to audit sinks.
+#[derive(Debug)]
+pub struct PayloadLog {
+    sinks: Vec<Box<dyn AuditSink>>,
+    redaction: PayloadRedaction,
+}
+
+impl PayloadLog {
+    pub fn new(sinks: Vec<Box<dyn AuditSink>>, redaction: PayloadRedaction) -> Self {
+        Self { sinks, redaction }
+    }
+
+    /// Builds the payload log `PAYLOAD_LOG_SINKS` asks for, `None` if it names no sink.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let Some(raw) = env::var("PAYLOAD_LOG_SINKS")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+        else {
+            return Ok(None);
+        };
+        let mut sinks: Vec<Box<dyn AuditSink>> = Vec::new();
+        for name in raw
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            let sink: Box<dyn AuditSink> = match name.to_lowercase().as_str() {
+                "file" => {
+                    let path = env::var("PAYLOAD_LOG_PATH")
+                        .ok()
+                        .filter(|path| !path.trim().is_empty())
+                        .ok_or_else(|| {
+                            "PAYLOAD_LOG_PATH is required for the file payload log sink".to_string()
+                        })?;
+                    Box::new(FileAuditSink::new(path.trim()))
+                }
+                "stdout" => Box::new(StdoutAuditSink),
+                other => return Err(format!("unknown payload log sink: {other}")),
+            };
+            sinks.push(sink);
+        }
+        Ok(Some(Self::new(sinks, PayloadRedaction::from_env()?)))
+    }
+
+    /// Returns the redaction policy.
+    pub fn redaction(&self) -> &PayloadRedaction {
+        &self.redaction
+    }
+
+    /// Logs a `/verify` request with its outcome.
+    pub fn verify<E: Display>(
+        &self,
+        request: &proto::VerifyRequest,
+        result: &Result<proto::VerifyResponse, E>,
+    ) {
+        let result = result.as_ref().map(|response| &response.0);
+        self.write("verify", request, result);
+    }
+
+    /// Logs a `/settle` request with its outcome.
+    pub fn settle<E: Display>(
+        &self,
+        request: &proto::SettleRequest,
+        result: &Result<proto::SettleResponse, E>,
+    ) {
+        let result = result.as_ref().map(|response| &response.0);
+        self.write("settle", request, result);
+    }
+
+    fn write<E: Display>(
+        &self,
+        operation: &str,
+        request: &proto::VerifyRequest,
+        result: Result<&Value, &E>,
+    ) {
+        if self.sinks.is_empty() {
+            return;
+        }
+        let slug = request.scheme_handler_slug();
+        let raw: Value = serde_json::from_str(request.as_raw().get()).unwrap_or(Value::Null);
+        let record = PayloadLogRecord {
+            timestamp: UnixTimestamp::now(),
+            operation,
+            network: slug.as_ref().map(|slug| slug.chain_id.to_string()),
+            scheme: slug.map(|slug| slug.name),
+            request: self.redaction.redact(&raw),
+            response: result.ok().map(|response| self.redaction.redact(response)),
+            error: result
+                .err()
+                .map(|error| self.redaction.redact_text(&error.to_string())),
+        };
+        let record = match serde_json::to_string(&record) {
+            Ok(record) => record,
+            Err(error) => {
+                eprintln!("failed to serialize payload log record: {error}");
+                return;
+            }
+        };
+        for sink in &self.sinks {
+            sink.write(&record);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    const PAYER: &str = "0x1111111111111111111111111111111111111111";
+    const ASSET: &str = "0x3333333333333333333333333333333333333333";
+
+    #[derive(Debug, Default)]
+    struct MemorySink(Arc<Mutex<Vec<String>>>);
+
+    impl AuditSink for MemorySink {
+        fn write(&self, record: &str) {
+            self.0.lock().unwrap().push(record.to_string());
+        }
+    }
+
+    #[test]
+    fn test_redaction_policies() {
+        let payload = json!({
+            "paymentPayload": {
+                "payload": {
+                    "signature": "0xdeadbeef",
+                    "authorization": { "from": PAYER, "to": PAYER, "value": "1000" }
+                }
+            },
+            "paymentRequirements": { "asset": ASSET, "payTo": PAYER }
+        });
+
+        let hashed = PayloadRedaction::default().redact(&payload);
+        let signature = hashed["paymentPayload"]["payload"]["signature"]
+            .as_str()
+            .unwrap();
+        assert!(signature.starts_with("sha256:"));
+        assert_eq!(
+            hashed["paymentPayload"]["payload"]["authorization"]["from"],
+            PAYER
+        );
+
+        let redaction = PayloadRedaction {
+            signatures: SignatureRedaction::Omit,
+            addresses: AddressRedaction::Truncate,
+            hash_key: None,
+        };
+        let redacted = redaction.redact(&payload);
+        assert!(
+            redacted["paymentPayload"]["payload"]
+                .get("signature")
+                .is_none()
+        );
+        assert_eq!(
+            redacted["paymentPayload"]["payload"]["authorization"]["from"],
+            "0x1111...1111"
+        );
+        // Assets are not parties to the payment.
+        assert_eq!(redacted["paymentRequirements"]["asset"], ASSET);
+        assert_eq!(
+            redaction.redact_text(&format!("Recipient mismatch: expected {PAYER}, got 0x12")),
+            "Recipient mismatch: expected 0x1111...1111, got 0x12"
+        );
+
+        let keyed = PayloadRedaction {
+            addresses: AddressRedaction::Hash,
+            hash_key: Some(b"secret".to_vec()),
+            ..PayloadRedaction::default()
+        };
+        let hashed = keyed.redact(&payload);
+        let from = hashed["paymentPayload"]["payload"]["authorization"]["from"]
+            .as_str()
+            .unwrap();
+        assert!(from.starts_with("hmac-sha256:"));
+        assert_eq!(hashed["paymentRequirements"]["payTo"], from);
+    }
+
+    #[test]
+    fn test_payload_log_records_requests_and_errors() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let log = PayloadLog::new(
+            vec![Box::new(MemorySink(records.clone()))],
+            PayloadRedaction::default(),
+        );
+        let request: proto::SettleRequest = serde_json::from_value(json!({
+            "x402Version": 1,
+            "paymentPayload": { "payload": { "signature": "0xdeadbeef" } }
+        }))
+        .unwrap();
+
+        log.settle(
+            &request,
+            &Err::<proto::SettleResponse, _>("nonce already used"),
+        );
+        let records = records.lock().unwrap();
+        let record: Value = serde_json::from_str(&records[0]).unwrap();
+        assert_eq!(record["operation"], "settle");
+        assert_eq!(record["error"], "nonce already used");
+        assert!(record.get("response").is_none());
+        assert!(!records[0].contains("deadbeef"));
+    }
+}
@@ -0,0 +1,234 @@
+//! Live feed of payment activity for dashboards.
+//!
+//! [`PaymentEvents`] fans out a [`PaymentEvent`] for every step of a payment to
+//! the clients subscribed to `GET /events` (see [`crate::handlers::payment_event_routes`]),
+//! as Server-Sent Events:
+//!
+//! | Event type | Emitted when |
+//! |------------|--------------|
+//! | `verify.accepted` | `/verify` accepted a payment |
+//! | `settlement.submitted` | `/settle` started settling a payment |
+//! | `settlement.confirmed` | the settlement transaction is in a block, with the confirmations the request asked for |
+//! | `settlement.failed` | the settlement was rejected or failed on chain |
+//!
+//! Each SSE message carries the event type as `event:` and the [`PaymentEvent`] as
+//! JSON `data:`. Subscribers narrow the feed with the `payer`, `payee` and `chain`
+//! query parameters, see [`PaymentEventFilter`]:
+//!
+//! ```text
+//! GET /events?payee=0x1111111111111111111111111111111111111111&chain=eip155:42793
+//! ```
+//!
+//! The feed is live only: there is no replay, and a subscriber that reads too
+//! slowly misses events. It is then sent a `lagged` message holding the number
+//! of events it missed. Use the [event bus](crate::event_bus) or the
+//! [notifications](crate::notify) where every event has to arrive.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use x402_types::proto;
+
+/// Events buffered per subscriber before it is considered lagging.
+pub const DEFAULT_PAYMENT_EVENTS_CAPACITY: usize = 1024;
+
+/// Step of a payment reported by a [`PaymentEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaymentEventType {
+    #[serde(rename = "verify.accepted")]
+    VerifyAccepted,
+    #[serde(rename = "settlement.submitted")]
+    SettlementSubmitted,
+    #[serde(rename = "settlement.confirmed")]
+    SettlementConfirmed,
+    #[serde(rename = "settlement.failed")]
+    SettlementFailed,
+}
+
+impl PaymentEventType {
+    /// The event type as sent in the SSE `event:` field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentEventType::VerifyAccepted => "verify.accepted",
+            PaymentEventType::SettlementSubmitted => "settlement.submitted",
+            PaymentEventType::SettlementConfirmed => "settlement.confirmed",
+            PaymentEventType::SettlementFailed => "settlement.failed",
+        }
+    }
+}
+
+/// A step of a payment, as streamed on `GET /events`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentEvent {
+    pub event_type: PaymentEventType,
+    pub timestamp_ms: u64,
+    /// CAIP-2 chain id of the payment.
+    pub network: Option<String>,
+    pub payer: Option<String>,
+    pub payee: Option<String>,
+    /// Amount in the asset's smallest unit.
+    pub amount: Option<String>,
+    pub asset: Option<String>,
+    /// Settlement transaction hash, for `settlement.confirmed`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<String>,
+    /// Why the settlement failed, for `settlement.failed`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl PaymentEvent {
+    /// Creates the event of a `/verify` or `/settle` request.
+    pub fn new(event_type: PaymentEventType, request: &proto::VerifyRequest) -> Self {
+        Self {
+            event_type,
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as u64)
+                .unwrap_or_default(),
+            network: request
+                .scheme_handler_slug()
+                .map(|slug| slug.chain_id.to_string()),
+            payer: request.payer(),
+            payee: request.payee(),
+            amount: request.amount(),
+            asset: request.asset(),
+            transaction: None,
+            reason: None,
+        }
+    }
+
+    /// Sets the settlement transaction hash.
+    pub fn with_transaction(mut self, transaction: Option<&str>) -> Self {
+        self.transaction = transaction.map(str::to_string);
+        self
+    }
+
+    /// Sets the failure reason.
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+/// Query parameters of `GET /events`. Every parameter given must match.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PaymentEventFilter {
+    /// Payer address, compared case-insensitively.
+    pub payer: Option<String>,
+    /// Payee address, compared case-insensitively.
+    pub payee: Option<String>,
+    /// CAIP-2 chain id, e.g. `eip155:42793`.
+    pub chain: Option<String>,
+}
+
+impl PaymentEventFilter {
+    /// Whether `event` passes the filter.
+    pub fn matches(&self, event: &PaymentEvent) -> bool {
+        let address_matches = |wanted: &Option<String>, actual: &Option<String>| match wanted {
+            Some(wanted) => actual
+                .as_deref()
+                .is_some_and(|actual| actual.eq_ignore_ascii_case(wanted)),
+            None => true,
+        };
+        address_matches(&self.payer, &event.payer)
+            && address_matches(&self.payee, &event.payee)
+            && self
+                .chain
+                .as_ref()
+                .is_none_or(|chain| event.network.as_ref() == Some(chain))
+    }
+}
+
+/// Broadcasts payment events to the subscribers of `GET /events`.
+#[derive(Debug)]
+pub struct PaymentEvents {
+    sender: broadcast::Sender<PaymentEvent>,
+}
+
+impl Default for PaymentEvents {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAYMENT_EVENTS_CAPACITY)
+    }
+}
+
+impl PaymentEvents {
+    /// Creates a feed buffering up to `capacity` events per subscriber.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Sends `event` to every current subscriber. Events without subscribers are dropped.
+    pub fn publish(&self, event: PaymentEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Receives the events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<PaymentEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Number of connected subscribers.
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: PaymentEventType, payer: &str, network: &str) -> PaymentEvent {
+        PaymentEvent {
+            event_type,
+            timestamp_ms: 0,
+            network: Some(network.to_string()),
+            payer: Some(payer.to_string()),
+            payee: Some("0x2222222222222222222222222222222222222222".to_string()),
+            amount: Some("10000".to_string()),
+            asset: None,
+            transaction: None,
+            reason: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filtered_subscription() {
+        let events = PaymentEvents::new(8);
+        let mut receiver = events.subscribe();
+        let filter = PaymentEventFilter {
+            payer: Some("0xAAAAaaaaAAAAaaaaAAAAaaaaAAAAaaaaAAAAaaaa".to_string()),
+            payee: None,
+            chain: Some("eip155:42793".to_string()),
+        };
+
+        let payer = "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+        events.publish(event(PaymentEventType::VerifyAccepted, payer, "eip155:1"));
+        events.publish(event(
+            PaymentEventType::SettlementSubmitted,
+            "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+            "eip155:42793",
+        ));
+        events.publish(
+            event(PaymentEventType::SettlementConfirmed, payer, "eip155:42793")
+                .with_transaction(Some("0xabc")),
+        );
+
+        let mut matched = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            if filter.matches(&event) {
+                matched.push(event);
+            }
+        }
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].event_type, PaymentEventType::SettlementConfirmed);
+        assert_eq!(matched[0].transaction.as_deref(), Some("0xabc"));
+
+        let json = serde_json::to_value(&matched[0]).unwrap();
+        assert_eq!(json["eventType"], "settlement.confirmed");
+        assert!(json.get("reason").is_none());
+    }
+}
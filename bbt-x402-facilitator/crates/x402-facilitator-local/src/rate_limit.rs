@@ -0,0 +1,694 @@
+//! Rate limiting and per-client quotas for facilitator endpoints.
+//!
+//! [`RateLimiter`] enforces two independent token buckets on every request:
+//! one per client IP address and one per API key. A request that finds either
+//! bucket empty is rejected with `429 Too Many Requests` and a `Retry-After` header.
+//!
+//! In addition, `POST /settle` requests count towards a per-client daily cap on the
+//! settled amount. The cap is tracked separately for every asset, in the asset's
+//! smallest unit, and resets at midnight UTC. A settlement reserves the amount it
+//! would transfer (see [`proto::VerifyRequest::transfer_amount`]) and is then
+//! counted at the `settledAmount` it reports. Settlements that fail do not count
+//! towards the cap.
+//!
+//! API keys are those authenticated by [`require_api_key`](crate::auth::require_api_key),
+//! counted under the key id, so the limiter must run inside
+//! [`authenticated_routes`](crate::handlers::authenticated_routes). Requests
+//! without a valid key get no key bucket, and their daily cap is counted per client IP.
+//!
+//! # Configuration
+//!
+//! | Variable | Description |
+//! |----------|-------------|
+//! | `RATE_LIMIT_ENABLED` | Enable rate limiting (default: `false`) |
+//! | `RATE_LIMIT_IP_BURST` | Bucket capacity per client IP (default: `60`) |
+//! | `RATE_LIMIT_IP_PER_SECOND` | Refill rate per client IP (default: `10`) |
+//! | `RATE_LIMIT_KEY_BURST` | Bucket capacity per API key (default: `120`) |
+//! | `RATE_LIMIT_KEY_PER_SECOND` | Refill rate per API key (default: `20`) |
+//! | `RATE_LIMIT_KEY_DAILY_SETTLE_CAP` | Daily settled amount per client and asset (default: unlimited) |
+//! | `RATE_LIMIT_TRUST_FORWARDED_FOR` | Take the client IP from `X-Forwarded-For` (default: `false`) |
+//!
+//! Setting a burst to `0` disables the corresponding bucket.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use std::sync::Arc;
+//! use x402_facilitator_local::{handlers, RateLimiter};
+//!
+//! let app = match RateLimiter::from_env()? {
+//!     Some(limiter) => handlers::rate_limited_routes(Arc::new(limiter)),
+//!     None => handlers::routes(),
+//! };
+//! ```
+
+use std::collections::HashMap;
+use std::env;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use alloy_primitives::U256;
+use axum::Json;
+use axum::body::{Body, to_bytes};
+use axum::extract::{ConnectInfo, Query, Request, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde_json::{Value, json};
+use x402_types::proto;
+use x402_types::proto::amount;
+
+use crate::auth::ApiKeyIdentity;
+use crate::handlers::SettleParams;
+
+/// Maximum request body size buffered when inspecting settlement amounts.
+const MAX_SETTLE_BODY_BYTES: usize = 1024 * 1024;
+
+/// Number of tracked buckets after which idle buckets are pruned.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Token bucket parameters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BucketConfig {
+    /// Maximum number of requests that can be made in a burst.
+    pub burst: u32,
+    /// Number of requests replenished per second.
+    pub per_second: f64,
+}
+
+/// Rate limiting configuration.
+#[derive(Clone, Debug, Default)]
+pub struct RateLimitConfig {
+    /// Bucket applied per client IP address.
+    pub per_ip: Option<BucketConfig>,
+    /// Bucket applied per API key.
+    pub per_key: Option<BucketConfig>,
+    /// Daily cap on the settled amount per client and asset.
+    pub daily_settle_cap: Option<U256>,
+    /// Whether to take the client IP from the `X-Forwarded-For` header.
+    pub trust_forwarded_for: bool,
+}
+
+impl RateLimitConfig {
+    /// Loads the configuration from environment variables.
+    ///
+    /// Returns `Ok(None)` when rate limiting is disabled.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let enabled = env::var("RATE_LIMIT_ENABLED")
+            .map(|value| parse_bool(&value))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+        let per_ip = bucket_from_env("RATE_LIMIT_IP_BURST", 60, "RATE_LIMIT_IP_PER_SECOND", 10.0)?;
+        let per_key = bucket_from_env(
+            "RATE_LIMIT_KEY_BURST",
+            120,
+            "RATE_LIMIT_KEY_PER_SECOND",
+            20.0,
+        )?;
+        let daily_settle_cap = match env::var("RATE_LIMIT_KEY_DAILY_SETTLE_CAP") {
+            Ok(value) if !value.trim().is_empty() => Some(
+                U256::from_str(value.trim())
+                    .map_err(|e| format!("invalid RATE_LIMIT_KEY_DAILY_SETTLE_CAP: {e}"))?,
+            ),
+            _ => None,
+        };
+        let trust_forwarded_for = env::var("RATE_LIMIT_TRUST_FORWARDED_FOR")
+            .map(|value| parse_bool(&value))
+            .unwrap_or(false);
+        Ok(Some(Self {
+            per_ip,
+            per_key,
+            daily_settle_cap,
+            trust_forwarded_for,
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    fn full(config: &BucketConfig, now: Instant) -> Self {
+        Self {
+            tokens: config.burst as f64,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, config: &BucketConfig, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * config.per_second).min(config.burst as f64);
+        self.updated_at = now;
+    }
+
+    /// Takes a token, or returns how long to wait until one is available.
+    fn try_take(&mut self, config: &BucketConfig, now: Instant) -> Result<(), Duration> {
+        self.refill(config, now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else if config.per_second > 0.0 {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / config.per_second,
+            ))
+        } else {
+            Err(Duration::from_secs(u32::MAX as u64))
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buckets(HashMap<String, Bucket>);
+
+impl Buckets {
+    fn try_take(&mut self, id: &str, config: &BucketConfig, now: Instant) -> Result<(), Duration> {
+        if self.0.len() > PRUNE_THRESHOLD {
+            // Buckets that have refilled completely carry no state worth keeping.
+            self.0.retain(|_, bucket| {
+                bucket.refill(config, now);
+                bucket.tokens < config.burst as f64
+            });
+        }
+        self.0
+            .entry(id.to_string())
+            .or_insert_with(|| Bucket::full(config, now))
+            .try_take(config, now)
+    }
+}
+
+#[derive(Debug)]
+struct DailySpend {
+    day: u64,
+    spent: U256,
+}
+
+/// Outcome of a rate limit check.
+#[derive(Debug, PartialEq)]
+pub enum RateLimitDecision {
+    /// The request may proceed.
+    Allowed,
+    /// The request exceeded a limit and may be retried after the given delay.
+    Limited {
+        /// Human-readable description of the exceeded limit.
+        reason: &'static str,
+        /// Delay before the request is expected to succeed.
+        retry_after: Duration,
+    },
+}
+
+/// Enforces per-IP and per-API-key rate limits and per-client daily settlement caps.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    ip_buckets: Mutex<Buckets>,
+    key_buckets: Mutex<Buckets>,
+    daily_spend: Mutex<HashMap<(String, String), DailySpend>>,
+}
+
+impl RateLimiter {
+    /// Creates a new rate limiter.
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            ip_buckets: Mutex::new(Buckets::default()),
+            key_buckets: Mutex::new(Buckets::default()),
+            daily_spend: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates a rate limiter from environment variables.
+    ///
+    /// Returns `Ok(None)` when rate limiting is disabled.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        Ok(RateLimitConfig::from_env()?.map(Self::new))
+    }
+
+    /// Returns the active configuration.
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Takes a token from the buckets of the given client IP and API key id.
+    pub fn check_request(&self, ip: Option<&str>, key_id: Option<&str>) -> RateLimitDecision {
+        let now = Instant::now();
+        if let (Some(config), Some(ip)) = (self.config.per_ip.as_ref(), ip)
+            && config.burst > 0
+        {
+            let mut buckets = self.ip_buckets.lock().expect("rate limit lock poisoned");
+            if let Err(retry_after) = buckets.try_take(ip, config, now) {
+                return RateLimitDecision::Limited {
+                    reason: "client IP rate limit exceeded",
+                    retry_after,
+                };
+            }
+        }
+        if let (Some(config), Some(key_id)) = (self.config.per_key.as_ref(), key_id)
+            && config.burst > 0
+        {
+            let mut buckets = self.key_buckets.lock().expect("rate limit lock poisoned");
+            if let Err(retry_after) = buckets.try_take(key_id, config, now) {
+                return RateLimitDecision::Limited {
+                    reason: "API key rate limit exceeded",
+                    retry_after,
+                };
+            }
+        }
+        RateLimitDecision::Allowed
+    }
+
+    /// Reserves `amount` of `asset` against the daily settlement cap of `client`.
+    ///
+    /// Call [`RateLimiter::release_settlement`] if the settlement does not go through,
+    /// or [`RateLimiter::reconcile_settlement`] once it reports what it transferred.
+    pub fn reserve_settlement(&self, client: &str, asset: &str, amount: U256) -> RateLimitDecision {
+        let Some(cap) = self.config.daily_settle_cap else {
+            return RateLimitDecision::Allowed;
+        };
+        let (day, until_midnight) = current_day();
+        let mut spend = self.daily_spend.lock().expect("rate limit lock poisoned");
+        spend.retain(|_, entry| entry.day == day);
+        let entry = spend
+            .entry((client.to_string(), asset.to_string()))
+            .or_insert(DailySpend {
+                day,
+                spent: U256::ZERO,
+            });
+        match entry.spent.checked_add(amount) {
+            Some(total) if total <= cap => {
+                entry.spent = total;
+                RateLimitDecision::Allowed
+            }
+            _ => RateLimitDecision::Limited {
+                reason: "API key daily settlement cap exceeded",
+                retry_after: until_midnight,
+            },
+        }
+    }
+
+    /// Returns a previously reserved amount to the daily settlement cap of `client`.
+    pub fn release_settlement(&self, client: &str, asset: &str, amount: U256) {
+        self.reconcile_settlement(client, asset, amount, U256::ZERO);
+    }
+
+    /// Counts a settlement that reserved `reserved` at the `settled` amount it reports.
+    ///
+    /// The settlement has happened, so it is counted even if the cap is now exceeded.
+    pub fn reconcile_settlement(&self, client: &str, asset: &str, reserved: U256, settled: U256) {
+        let mut spend = self.daily_spend.lock().expect("rate limit lock poisoned");
+        if let Some(entry) = spend.get_mut(&(client.to_string(), asset.to_string())) {
+            entry.spent = entry.spent.saturating_sub(reserved).saturating_add(settled);
+        }
+    }
+
+    /// The client a daily settlement cap is counted for: the authenticated API key
+    /// id, or else the client IP.
+    pub(crate) fn client_id(key_id: Option<&str>, ip: Option<&str>) -> Option<String> {
+        match key_id {
+            Some(key_id) => Some(format!("key:{key_id}")),
+            None => ip.map(|ip| format!("ip:{ip}")),
+        }
+    }
+
+    fn client_ip(&self, request: &Request) -> Option<String> {
+        if self.config.trust_forwarded_for
+            && let Some(forwarded) = request
+                .headers()
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(',').next())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        {
+            return Some(forwarded.to_string());
+        }
+        request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string())
+    }
+}
+
+/// Extracts the API key from the `X-API-Key` or `Authorization: Bearer` header.
+pub fn api_key_from_headers(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+        })
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// Axum middleware enforcing the limits of a [`RateLimiter`].
+///
+/// Use with [`axum::middleware::from_fn_with_state`].
+pub async fn enforce_rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let ip = limiter.client_ip(&request);
+    let key_id = request
+        .extensions()
+        .get::<ApiKeyIdentity>()
+        .map(|identity| identity.id.clone());
+
+    if let RateLimitDecision::Limited {
+        reason,
+        retry_after,
+    } = limiter.check_request(ip.as_deref(), key_id.as_deref())
+    {
+        return too_many_requests(reason, retry_after);
+    }
+
+    let is_settle =
+        request.method() == axum::http::Method::POST && request.uri().path() == "/settle";
+    // Dry runs broadcast nothing, so they do not count towards the daily cap.
+    let is_dry_run = Query::<SettleParams>::try_from_uri(request.uri())
+        .is_ok_and(|Query(params)| params.dry_run);
+    let (Some(client), true, false, true) = (
+        RateLimiter::client_id(key_id.as_deref(), ip.as_deref()),
+        is_settle,
+        is_dry_run,
+        limiter.config.daily_settle_cap.is_some(),
+    ) else {
+        return next.run(request).await;
+    };
+
+    // Buffer the body to read the settlement amount, then hand it on unchanged.
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_SETTLE_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    let settle_request = serde_json::from_slice::<proto::SettleRequest>(&bytes).ok();
+    let is_dry_run = settle_request
+        .as_ref()
+        .is_some_and(proto::SettleRequest::dry_run);
+    let settlement = settle_request.and_then(|settle_request| {
+        let amount = U256::from_str(&settle_request.transfer_amount()?).ok()?;
+        Some((settle_request.asset()?, amount))
+    });
+    let request = Request::from_parts(parts, Body::from(bytes));
+
+    if is_dry_run {
+        return next.run(request).await;
+    }
+    let Some((asset, amount)) = settlement else {
+        // A settlement the cap cannot count is not let through to the handler.
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "invalid_request",
+                "details": "asset or amount could not be read from the request",
+            })),
+        )
+            .into_response();
+    };
+    if let RateLimitDecision::Limited {
+        reason,
+        retry_after,
+    } = limiter.reserve_settlement(&client, &asset, amount)
+    {
+        return too_many_requests(reason, retry_after);
+    }
+
+    let response = next.run(request).await;
+    if !response.status().is_success() {
+        limiter.release_settlement(&client, &asset, amount);
+        return response;
+    }
+    // Count the amount the settlement reports, which may differ from the reservation.
+    let (parts, body) = response.into_parts();
+    let Ok(bytes) = to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let settled = serde_json::from_slice::<Value>(&bytes)
+        .ok()
+        .and_then(|json| json.get("settledAmount").cloned())
+        .and_then(|settled| amount::deserialize::<U256, _>(settled).ok());
+    if let Some(settled) = settled {
+        limiter.reconcile_settlement(&client, &asset, amount, settled);
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+fn too_many_requests(reason: &'static str, retry_after: Duration) -> Response {
+    let retry_after_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(json!({
+            "error": "rate_limited",
+            "details": reason,
+            "retryAfter": retry_after_secs,
+        })),
+    )
+        .into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+    response
+}
+
+/// Returns the current UTC day number and the time left until it ends.
+fn current_day() -> (u64, Duration) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0);
+    let day = now / 86_400;
+    (day, Duration::from_secs((day + 1) * 86_400 - now))
+}
+
+fn bucket_from_env(
+    burst_key: &str,
+    default_burst: u32,
+    rate_key: &str,
+    default_rate: f64,
+) -> Result<Option<BucketConfig>, String> {
+    let burst = match env::var(burst_key) {
+        Ok(value) => value
+            .trim()
+            .parse::<u32>()
+            .map_err(|e| format!("invalid {burst_key}: {e}"))?,
+        Err(_) => default_burst,
+    };
+    let per_second = match env::var(rate_key) {
+        Ok(value) => value
+            .trim()
+            .parse::<f64>()
+            .map_err(|e| format!("invalid {rate_key}: {e}"))?,
+        Err(_) => default_rate,
+    };
+    if burst == 0 {
+        return Ok(None);
+    }
+    Ok(Some(BucketConfig { burst, per_second }))
+}
+
+fn parse_bool(value: &str) -> bool {
+    matches!(
+        value.to_lowercase().as_str(),
+        "1" | "true" | "yes" | "y" | "on" | "enabled"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(per_ip: Option<BucketConfig>, daily_settle_cap: Option<U256>) -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            per_ip,
+            per_key: None,
+            daily_settle_cap,
+            trust_forwarded_for: false,
+        })
+    }
+
+    #[test]
+    fn test_ip_bucket_exhausts_after_burst() {
+        let limiter = limiter(
+            Some(BucketConfig {
+                burst: 2,
+                per_second: 0.5,
+            }),
+            None,
+        );
+        assert_eq!(
+            limiter.check_request(Some("10.0.0.1"), None),
+            RateLimitDecision::Allowed
+        );
+        assert_eq!(
+            limiter.check_request(Some("10.0.0.1"), None),
+            RateLimitDecision::Allowed
+        );
+        match limiter.check_request(Some("10.0.0.1"), None) {
+            RateLimitDecision::Limited { retry_after, .. } => {
+                assert!(retry_after <= Duration::from_secs(2));
+            }
+            RateLimitDecision::Allowed => panic!("third request should be limited"),
+        }
+        // Other clients have their own bucket.
+        assert_eq!(
+            limiter.check_request(Some("10.0.0.2"), None),
+            RateLimitDecision::Allowed
+        );
+    }
+
+    #[test]
+    fn test_daily_settle_cap_per_key_and_asset() {
+        let limiter = limiter(None, Some(U256::from(100)));
+        assert_eq!(
+            limiter.reserve_settlement("key", "0xasset", U256::from(60)),
+            RateLimitDecision::Allowed
+        );
+        assert!(matches!(
+            limiter.reserve_settlement("key", "0xasset", U256::from(60)),
+            RateLimitDecision::Limited { .. }
+        ));
+        assert_eq!(
+            limiter.reserve_settlement("key", "0xother", U256::from(60)),
+            RateLimitDecision::Allowed
+        );
+        limiter.release_settlement("key", "0xasset", U256::from(60));
+        assert_eq!(
+            limiter.reserve_settlement("key", "0xasset", U256::from(100)),
+            RateLimitDecision::Allowed
+        );
+    }
+
+    /// A `POST` of `body` to `uri`, authenticated as the API key `key_id` when given.
+    fn settle_request(uri: &str, key_id: Option<&str>, body: Value) -> Request {
+        let mut request = Request::post(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        if let Some(key_id) = key_id {
+            request.extensions_mut().insert(ApiKeyIdentity {
+                id: key_id.to_string(),
+                scope: x402_types::config::ApiKeyScope::Settle,
+            });
+        }
+        request
+    }
+
+    #[tokio::test]
+    async fn test_dry_runs_do_not_count_towards_the_daily_settle_cap() {
+        use axum::Router;
+        use axum::routing::post;
+        use tower::ServiceExt;
+
+        let limiter = Arc::new(limiter(None, Some(U256::from(100))));
+        let app = Router::new()
+            .route("/settle", post(|| async { StatusCode::OK }))
+            .layer(axum::middleware::from_fn_with_state(
+                limiter,
+                enforce_rate_limit,
+            ));
+        let settle = |uri: &str, dry_run: bool| {
+            let body = json!({
+                "x402Version": 2,
+                "paymentRequirements": { "amount": "60", "asset": "0xasset" },
+                "dryRun": dry_run,
+            });
+            let request = settle_request(uri, Some("shop"), body);
+            let app = app.clone();
+            async move { app.oneshot(request).await.unwrap().status() }
+        };
+
+        for _ in 0..3 {
+            assert_eq!(settle("/settle?dryRun=true", false).await, StatusCode::OK);
+            assert_eq!(settle("/settle", true).await, StatusCode::OK);
+        }
+        assert_eq!(settle("/settle", false).await, StatusCode::OK);
+        assert_eq!(
+            settle("/settle", false).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_settlements_are_rejected_under_a_daily_settle_cap() {
+        use axum::Router;
+        use axum::routing::post;
+        use tower::ServiceExt;
+
+        let limiter = Arc::new(limiter(None, Some(U256::from(100))));
+        let app = Router::new()
+            .route("/settle", post(|| async { StatusCode::OK }))
+            .layer(axum::middleware::from_fn_with_state(
+                limiter,
+                enforce_rate_limit,
+            ));
+        let request = settle_request(
+            "/settle",
+            Some("shop"),
+            json!({"x402Version": 2, "paymentRequirements": {"amount": "60"}}),
+        );
+        assert_eq!(
+            app.oneshot(request).await.unwrap().status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    async fn test_daily_settle_cap_counts_the_settled_amount_per_client() {
+        use axum::Router;
+        use axum::routing::post;
+        use tower::ServiceExt;
+
+        let limiter = Arc::new(limiter(None, Some(U256::from(100))));
+        let app = Router::new()
+            .route(
+                "/settle",
+                post(|| async { Json(json!({ "success": true, "settledAmount": "20" })) }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                limiter,
+                enforce_rate_limit,
+            ));
+        let settle = |key_id: Option<&str>| {
+            // 60 is required, 30 signed, and the settlement reports 20.
+            let body = json!({
+                "x402Version": 2,
+                "paymentPayload": {
+                    "accepted": { "scheme": "exact", "network": "eip155:42793" },
+                    "payload": { "authorization": { "value": "30" } }
+                },
+                "paymentRequirements": { "amount": "60", "asset": "0xasset" },
+            });
+            let mut request = settle_request("/settle", key_id, body);
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 443))));
+            let app = app.clone();
+            async move { app.oneshot(request).await.unwrap().status() }
+        };
+
+        // Each settlement reserves 30 and then counts 20: 80 after four, and the
+        // fifth reservation would exceed the cap of 100.
+        for _ in 0..4 {
+            assert_eq!(settle(Some("shop")).await, StatusCode::OK);
+        }
+        assert_eq!(settle(Some("shop")).await, StatusCode::TOO_MANY_REQUESTS);
+        // Another key has its own cap, whatever the IP.
+        assert_eq!(settle(Some("other")).await, StatusCode::OK);
+        // Without a valid key, the cap is counted for the client IP.
+        for _ in 0..4 {
+            assert_eq!(settle(None).await, StatusCode::OK);
+        }
+        assert_eq!(settle(None).await, StatusCode::TOO_MANY_REQUESTS);
+    }
+}
@@ -0,0 +1,73 @@
+//! Refunds of settled payments.
+//!
+//! [`FacilitatorLocal::refund`](crate::FacilitatorLocal::refund) sends a settled
+//! payment, or part of it, back to its payer, for merchants who cannot deliver
+//! what was paid for. The settlement is referenced by its transaction hash and
+//! looked up in the [settlement ledger](crate::ledger), which supplies the chain,
+//! token, payer and amount. The refund is paid by the scheme handler of the
+//! payment from a facilitator-controlled treasury, and recorded in the ledger as
+//! `settlement_refunded` entries: a `pending` one before it is sent, then a `sent`
+//! or `failed` one with the same `refundNumber`.
+//!
+//! # API
+//!
+//! [`handlers::refund_routes`](crate::handlers::refund_routes) serves `POST /refund`:
+//!
+//! ```json
+//! { "settlement": "0x5f1c...", "amount": "500000", "reason": "out of stock" }
+//! ```
+//!
+//! `amount` is in the asset's smallest unit and defaults to what is left to
+//! refund. Refunds of a settlement never add up to more than it settled, the
+//! `settledAmount` it reported or else the required amount. A refund left pending,
+//! because the facilitator stopped or the chain failed while it was sent, counts as
+//! refunded until an operator checks the treasury. The response is the one of the
+//! scheme handler, with the refund `transaction`.
+//!
+//! The route requires an API key with the `admin` scope, so it should only be
+//! mounted when API keys are configured.
+//!
+//! # Treasury
+//!
+//! On EIP-155 chains, refunds of `exact` payments are ERC-20 transfers from the
+//! signer named `refund_treasury` in the chain config, which must hold the
+//! tokens refunded. Chains without one, and other schemes, reject refunds.
+
+use serde::Deserialize;
+use x402_types::scheme::X402SchemeFacilitatorError;
+
+/// Body of `POST /refund`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefundOrder {
+    /// Transaction hash of the settlement to refund.
+    pub settlement: String,
+    /// Amount to refund, in the asset's smallest unit. Defaults to what is left to refund.
+    #[serde(default)]
+    pub amount: Option<String>,
+    /// Why the payment is refunded, kept in the ledger.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Why a refund was not sent.
+#[derive(Debug, thiserror::Error)]
+pub enum RefundError {
+    #[error("No settlement ledger is attached")]
+    LedgerDisabled,
+    #[error("No settled payment with transaction {0} in the ledger")]
+    SettlementNotFound(String),
+    #[error("Settlement {0} cannot be refunded: {1}")]
+    NotRefundable(String, &'static str),
+    #[error("Invalid refund amount: {0}")]
+    InvalidAmount(String),
+    #[error("Refund of {requested} exceeds the {remaining} left to refund")]
+    ExceedsRemaining {
+        requested: String,
+        remaining: String,
+    },
+    #[error("Settlement ledger error: {0}")]
+    Ledger(String),
+    #[error(transparent)]
+    Scheme(X402SchemeFacilitatorError),
+}
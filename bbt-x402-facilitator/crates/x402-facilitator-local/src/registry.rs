@@ -0,0 +1,153 @@
+//! Runtime inspection and switches of the registered chains and scheme handlers.
+//!
+//! What a running facilitator supports depends on its config file, on the scheme
+//! handlers that could be built from it, and on config reloads. The admin API lists
+//! it as the facilitator sees it:
+//!
+//! - `GET /admin/schemes` lists every scheme handler with its slug
+//!   (`<chain_id>:v<x402_version>:<scheme>`), the payment kinds it advertises,
+//!   including the asset policy in their `extra`, and its signers
+//! - `GET /admin/chains` lists every chain with its scheme handlers and signers
+//!
+//! Chains and scheme handlers can be taken out of service without editing the config,
+//! e.g. during an RPC outage or a token incident:
+//!
+//! - `POST /admin/chains/{chain_id}/disable` and `/enable`
+//! - `POST /admin/schemes/{slug}/disable` and `/enable`
+//!
+//! Payments on a disabled chain are rejected as `unsupported_chain`, payments for a
+//! disabled scheme handler as `unsupported_scheme`, and neither is advertised on
+//! `/supported`. The switches are kept in memory: they outlive config reloads, but
+//! not restarts.
+
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+use serde::Serialize;
+use x402_types::chain::ChainId;
+use x402_types::proto::{PaymentVerificationError, SupportedPaymentKind};
+use x402_types::scheme::SchemeHandlerSlug;
+
+/// Chains and scheme handlers disabled at runtime, see the [module documentation](self).
+#[derive(Debug, Default)]
+pub struct RuntimeToggles {
+    chains: RwLock<HashSet<ChainId>>,
+    schemes: RwLock<HashSet<SchemeHandlerSlug>>,
+}
+
+impl RuntimeToggles {
+    /// Disables or enables all scheme handlers of a chain.
+    ///
+    /// Returns `false` if the chain already was in that state.
+    pub fn set_chain_enabled(&self, chain_id: &ChainId, enabled: bool) -> bool {
+        let mut chains = self.chains.write().expect("toggles lock poisoned");
+        if enabled {
+            chains.remove(chain_id)
+        } else {
+            chains.insert(chain_id.clone())
+        }
+    }
+
+    /// Disables or enables a scheme handler.
+    ///
+    /// Returns `false` if the handler already was in that state.
+    pub fn set_scheme_enabled(&self, slug: &SchemeHandlerSlug, enabled: bool) -> bool {
+        let mut schemes = self.schemes.write().expect("toggles lock poisoned");
+        if enabled {
+            schemes.remove(slug)
+        } else {
+            schemes.insert(slug.clone())
+        }
+    }
+
+    /// Returns `true` unless the chain is disabled.
+    pub fn chain_enabled(&self, chain_id: &ChainId) -> bool {
+        !self
+            .chains
+            .read()
+            .expect("toggles lock poisoned")
+            .contains(chain_id)
+    }
+
+    /// Returns `true` unless the scheme handler or its chain is disabled.
+    pub fn scheme_enabled(&self, slug: &SchemeHandlerSlug) -> bool {
+        self.chain_enabled(&slug.chain_id)
+            && !self
+                .schemes
+                .read()
+                .expect("toggles lock poisoned")
+                .contains(slug)
+    }
+
+    /// Rejects payments for a disabled chain or scheme handler.
+    pub fn check(&self, slug: &SchemeHandlerSlug) -> Result<(), PaymentVerificationError> {
+        if !self.chain_enabled(&slug.chain_id) {
+            return Err(PaymentVerificationError::UnsupportedChain);
+        }
+        if !self.scheme_enabled(slug) {
+            return Err(PaymentVerificationError::UnsupportedScheme);
+        }
+        Ok(())
+    }
+}
+
+/// A scheme handler, as listed by `GET /admin/schemes`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredScheme {
+    /// `<chain_id>:v<x402_version>:<scheme>`, as used by the admin switches.
+    pub slug: String,
+    pub chain_id: ChainId,
+    pub x402_version: u8,
+    pub scheme: String,
+    /// `false` if the handler or its chain is disabled.
+    pub enabled: bool,
+    /// Payment kinds the handler advertises on `/supported` when enabled.
+    pub kinds: Vec<SupportedPaymentKind>,
+    /// Signers the handler settles with.
+    pub signers: Vec<String>,
+}
+
+/// A chain, as listed by `GET /admin/chains`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredChain {
+    pub chain_id: ChainId,
+    /// `false` if the chain is disabled.
+    pub enabled: bool,
+    /// Slugs of the scheme handlers on the chain.
+    pub schemes: Vec<String>,
+    /// Signers the scheme handlers of the chain settle with.
+    pub signers: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggles() {
+        let toggles = RuntimeToggles::default();
+        let etherlink = ChainId::new("eip155", "42793");
+        let exact = SchemeHandlerSlug::new(etherlink.clone(), 2, "exact".to_string());
+        let upto = SchemeHandlerSlug::new(etherlink.clone(), 2, "upto".to_string());
+
+        assert!(toggles.set_scheme_enabled(&exact, false));
+        assert!(!toggles.set_scheme_enabled(&exact, false));
+        assert!(matches!(
+            toggles.check(&exact),
+            Err(PaymentVerificationError::UnsupportedScheme)
+        ));
+        assert!(toggles.check(&upto).is_ok());
+
+        assert!(toggles.set_chain_enabled(&etherlink, false));
+        assert!(matches!(
+            toggles.check(&upto),
+            Err(PaymentVerificationError::UnsupportedChain)
+        ));
+
+        toggles.set_chain_enabled(&etherlink, true);
+        toggles.set_scheme_enabled(&exact, true);
+        assert!(toggles.check(&exact).is_ok());
+    }
+}
@@ -0,0 +1,122 @@
+//! Amount format of HTTP responses.
+//!
+//! Amounts are serialized as decimal strings everywhere, including persisted
+//! state. Clients that only understand JSON numbers get them through this
+//! middleware, which rewrites the amount fields of JSON responses when the
+//! configured [`AmountFormat`] is [`AmountFormat::Number`]. See
+//! [`x402_types::proto::amount`] for the fields and the limits.
+//!
+//! The format is held by a [`ResponseAmountFormat`], so a config reload can
+//! switch it while the router is serving.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use std::sync::Arc;
+//! use x402_facilitator_local::{ResponseAmountFormat, handlers};
+//!
+//! let format = Arc::new(ResponseAmountFormat::new(config.amount_format()));
+//! let app = handlers::amount_formatted_routes(app, format.clone());
+//! ```
+
+use std::sync::{Arc, RwLock};
+
+use axum::body::{Body, to_bytes};
+use axum::extract::{Request, State};
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use x402_types::proto::amount::AmountFormat;
+
+/// The [`AmountFormat`] applied to responses, replaceable at runtime.
+#[derive(Debug, Default)]
+pub struct ResponseAmountFormat {
+    format: RwLock<AmountFormat>,
+}
+
+impl ResponseAmountFormat {
+    pub fn new(format: AmountFormat) -> Self {
+        Self {
+            format: RwLock::new(format),
+        }
+    }
+
+    /// The current format.
+    pub fn get(&self) -> AmountFormat {
+        *self.format.read().expect("amount format lock poisoned")
+    }
+
+    /// Switches the format of subsequent responses.
+    pub fn set(&self, format: AmountFormat) {
+        *self.format.write().expect("amount format lock poisoned") = format;
+    }
+}
+
+/// Axum middleware rewriting the amounts of JSON responses in the current format.
+///
+/// Responses that are not JSON, such as event streams, pass through untouched.
+///
+/// Use with [`axum::middleware::from_fn_with_state`].
+pub async fn format_response_amounts(
+    State(format): State<Arc<ResponseAmountFormat>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+    let format = format.get();
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value == HeaderValue::from_static("application/json"));
+    if format == AmountFormat::String || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    let Ok(mut value) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    format.apply(&mut value);
+    let body = serde_json::to_vec(&value).expect("JSON value serializes");
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::routing::get;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    async fn settled_amount(format: &Arc<ResponseAmountFormat>) -> serde_json::Value {
+        let router = Router::new()
+            .route(
+                "/settle",
+                get(|| async { axum::Json(json!({ "settledAmount": "1000" })) }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                format.clone(),
+                format_response_amounts,
+            ));
+        let response = router
+            .oneshot(Request::get("/settle").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()["settledAmount"].clone()
+    }
+
+    #[tokio::test]
+    async fn test_format_follows_reload() {
+        let format = Arc::new(ResponseAmountFormat::default());
+        assert_eq!(settled_amount(&format).await, json!("1000"));
+        format.set(AmountFormat::Number);
+        assert_eq!(settled_amount(&format).await, json!(1000));
+    }
+}
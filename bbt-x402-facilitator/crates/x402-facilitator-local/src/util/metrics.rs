@@ -0,0 +1,250 @@
+//! OpenTelemetry metrics of payment verification and settlement.
+//!
+//! [`FacilitatorMetrics`] records the instruments SLO dashboards are built from:
+//!
+//! | Instrument | Kind | Unit | Attributes |
+//! |------------|------|------|------------|
+//! | `x402.facilitator.requests` | counter | `{request}` | `x402.operation`, `x402.network`, `x402.scheme`, `x402.outcome` |
+//! | `x402.facilitator.settle.duration` | histogram | `s` | `x402.network`, `x402.scheme`, `x402.outcome` |
+//! | `x402.facilitator.settle.gas_used` | histogram | `{gas}` | `x402.network`, `x402.scheme` |
+//! | `x402.facilitator.compliance.duration` | histogram | `s` | `x402.operation`, `x402.outcome` |
+//!
+//! Measurements are exported with the meter provider installed by
+//! [`Telemetry::register`](crate::util::Telemetry::register), and so carry its
+//! resource attributes. They are recorded with the OpenTelemetry context of the
+//! current request span attached, which is what exemplar reservoirs sample the
+//! trace and span IDs from: exporters linking metrics to traces pick them up
+//! without further setup. The OpenTelemetry SDK in use does not sample exemplars
+//! yet, so the data points carry none until it does.
+
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::{Counter, Histogram, Meter};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use x402_types::scheme::SchemeHandlerSlug;
+
+/// Name of the meter the instruments are created with.
+const METER_NAME: &str = "x402-facilitator-local";
+
+/// Buckets of the duration histograms, in seconds: settlements wait for blocks,
+/// compliance checks for screening providers.
+const DURATION_BUCKETS: &[f64] = &[
+    0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+/// Buckets of the gas used histogram.
+const GAS_BUCKETS: &[f64] = &[
+    25_000.0,
+    50_000.0,
+    75_000.0,
+    100_000.0,
+    150_000.0,
+    200_000.0,
+    300_000.0,
+    500_000.0,
+    1_000_000.0,
+];
+
+/// Facilitator operation a measurement is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Verify,
+    Settle,
+}
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Verify => "verify",
+            Operation::Settle => "settle",
+        }
+    }
+}
+
+/// Instruments of the facilitator metrics, see the [module documentation](self).
+pub struct FacilitatorMetrics {
+    requests: Counter<u64>,
+    settle_duration: Histogram<f64>,
+    settle_gas_used: Histogram<u64>,
+    compliance_duration: Histogram<f64>,
+}
+
+impl FacilitatorMetrics {
+    /// Returns the process-wide instruments, creating them on first use.
+    ///
+    /// Must first be called after the global meter provider is installed:
+    /// instruments created before are never exported.
+    pub fn global() -> &'static Self {
+        static METRICS: OnceLock<FacilitatorMetrics> = OnceLock::new();
+        METRICS.get_or_init(Self::new)
+    }
+
+    fn new() -> Self {
+        Self::with_meter(&opentelemetry::global::meter(METER_NAME))
+    }
+
+    fn with_meter(meter: &Meter) -> Self {
+        Self {
+            requests: meter
+                .u64_counter("x402.facilitator.requests")
+                .with_description("Verify and settle requests, by outcome")
+                .with_unit("{request}")
+                .build(),
+            settle_duration: meter
+                .f64_histogram("x402.facilitator.settle.duration")
+                .with_description("Time to settle a payment, retries included")
+                .with_unit("s")
+                .with_boundaries(DURATION_BUCKETS.to_vec())
+                .build(),
+            settle_gas_used: meter
+                .u64_histogram("x402.facilitator.settle.gas_used")
+                .with_description("Gas used by settlement transactions")
+                .with_unit("{gas}")
+                .with_boundaries(GAS_BUCKETS.to_vec())
+                .build(),
+            compliance_duration: meter
+                .f64_histogram("x402.facilitator.compliance.duration")
+                .with_description("Time to screen the parties of a payment")
+                .with_unit("s")
+                .with_boundaries(DURATION_BUCKETS.to_vec())
+                .build(),
+        }
+    }
+
+    /// Counts a verify or settle request for `slug` that ended with `outcome`.
+    pub fn record_request(
+        &self,
+        operation: Operation,
+        slug: Option<&SchemeHandlerSlug>,
+        outcome: &'static str,
+    ) {
+        let mut attributes = payment_attributes(slug);
+        attributes.push(KeyValue::new("x402.operation", operation.as_str()));
+        attributes.push(KeyValue::new("x402.outcome", outcome));
+        in_span_context(|| self.requests.add(1, &attributes));
+    }
+
+    /// Records a settlement for `slug` that took `duration` and ended with `outcome`,
+    /// and the gas its transaction used when known.
+    pub fn record_settlement(
+        &self,
+        slug: Option<&SchemeHandlerSlug>,
+        outcome: &'static str,
+        duration: Duration,
+        gas_used: Option<u64>,
+    ) {
+        let attributes = payment_attributes(slug);
+        in_span_context(|| {
+            if let Some(gas_used) = gas_used {
+                self.settle_gas_used.record(gas_used, &attributes);
+            }
+            let mut attributes = attributes.clone();
+            attributes.push(KeyValue::new("x402.outcome", outcome));
+            self.settle_duration
+                .record(duration.as_secs_f64(), &attributes);
+        });
+    }
+
+    /// Records a compliance check of a verify or settle request that took `duration`.
+    pub fn record_compliance(&self, operation: Operation, allowed: bool, duration: Duration) {
+        let attributes = [
+            KeyValue::new("x402.operation", operation.as_str()),
+            KeyValue::new("x402.outcome", if allowed { "allowed" } else { "denied" }),
+        ];
+        in_span_context(|| {
+            self.compliance_duration
+                .record(duration.as_secs_f64(), &attributes)
+        });
+    }
+}
+
+/// The network and scheme attributes of a payment, `unknown` for requests that
+/// name no known scheme.
+fn payment_attributes(slug: Option<&SchemeHandlerSlug>) -> Vec<KeyValue> {
+    match slug {
+        Some(slug) => vec![
+            KeyValue::new("x402.network", slug.chain_id.to_string()),
+            KeyValue::new("x402.scheme", slug.name.clone()),
+        ],
+        None => vec![
+            KeyValue::new("x402.network", "unknown"),
+            KeyValue::new("x402.scheme", "unknown"),
+        ],
+    }
+}
+
+/// Runs `record` with the OpenTelemetry context of the current span attached.
+fn in_span_context(record: impl FnOnce()) {
+    let _guard = Span::current().context().attach();
+    record();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::data::{AggregatedMetrics, MetricData};
+    use opentelemetry_sdk::metrics::{InMemoryMetricExporter, PeriodicReader, SdkMeterProvider};
+    use x402_types::chain::ChainId;
+
+    #[test]
+    fn test_metrics_carry_network_and_scheme() {
+        let exporter = InMemoryMetricExporter::default();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(exporter.clone()).build())
+            .build();
+        let metrics = FacilitatorMetrics::with_meter(&provider.meter(METER_NAME));
+        let slug = SchemeHandlerSlug::new(ChainId::new("eip155", "42793"), 2, "exact".to_string());
+
+        metrics.record_request(Operation::Settle, Some(&slug), "settlement_succeeded");
+        metrics.record_settlement(
+            Some(&slug),
+            "settlement_succeeded",
+            Duration::from_millis(1500),
+            Some(62_000),
+        );
+        metrics.record_compliance(Operation::Verify, false, Duration::from_millis(20));
+        provider.force_flush().unwrap();
+
+        let exported = exporter.get_finished_metrics().unwrap();
+        let metric = |name: &str| {
+            exported
+                .iter()
+                .flat_map(|resource| resource.scope_metrics())
+                .flat_map(|scope| scope.metrics())
+                .find(|metric| metric.name() == name)
+                .unwrap_or_else(|| panic!("{name} not exported"))
+                .data()
+        };
+        let AggregatedMetrics::U64(MetricData::Sum(requests)) = metric("x402.facilitator.requests")
+        else {
+            panic!("requests is not a u64 sum");
+        };
+        let point = requests.data_points().next().unwrap();
+        assert_eq!(point.value(), 1);
+        let attributes: Vec<_> = point.attributes().cloned().collect();
+        assert!(attributes.contains(&KeyValue::new("x402.network", "eip155:42793")));
+        assert!(attributes.contains(&KeyValue::new("x402.scheme", "exact")));
+        assert!(attributes.contains(&KeyValue::new("x402.operation", "settle")));
+
+        let AggregatedMetrics::U64(MetricData::Histogram(gas)) =
+            metric("x402.facilitator.settle.gas_used")
+        else {
+            panic!("gas used is not a u64 histogram");
+        };
+        assert_eq!(gas.data_points().next().unwrap().sum(), 62_000);
+        let AggregatedMetrics::F64(MetricData::Histogram(compliance)) =
+            metric("x402.facilitator.compliance.duration")
+        else {
+            panic!("compliance duration is not an f64 histogram");
+        };
+        let point = compliance.data_points().next().unwrap();
+        assert!(
+            point
+                .attributes()
+                .any(|attribute| *attribute == KeyValue::new("x402.outcome", "denied"))
+        );
+    }
+}
@@ -4,6 +4,8 @@
 //!
 //! | Module | Description | Feature |
 //! |--------|-------------|---------|
+//! | [`metrics`] | OpenTelemetry metrics of verification and settlement | `telemetry` |
+//! | [`scheduler`] | Background tasks with jitter, health reporting and shutdown | - |
 //! | [`sig_down`] | Graceful shutdown signal handling | - |
 //! | [`telemetry`] | OpenTelemetry tracing and metrics setup | `telemetry` |
 //!
@@ -16,10 +18,14 @@
 //! let token = sig_down.cancellation_token();
 //! ```
 
+#[cfg(feature = "telemetry")]
+pub mod metrics;
+pub mod scheduler;
 pub mod sig_down;
 #[cfg(feature = "telemetry")]
 pub mod telemetry;
 
+pub use scheduler::*;
 pub use sig_down::*;
 #[cfg(feature = "telemetry")]
 pub use telemetry::*;
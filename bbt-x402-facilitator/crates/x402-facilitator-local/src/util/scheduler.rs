@@ -0,0 +1,290 @@
+//! In-process scheduler for background tasks.
+//!
+//! Periodic jobs (config watching, list refreshes, reconciliation, sweeps) are
+//! registered on a single [`Scheduler`] instead of being spawned ad hoc. The
+//! scheduler:
+//!
+//! - runs each job on its own tokio task, every `period` plus a random jitter so
+//!   replicas do not hit shared backends in lockstep,
+//! - records the outcome of every run, reported by [`Scheduler::health`],
+//! - runs singleton jobs only on the cluster leader, see [`crate::cluster`],
+//! - stops all jobs when its [`CancellationToken`] is cancelled and lets
+//!   [`Scheduler::shutdown`] wait for in-flight runs to finish.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use std::time::Duration;
+//! use x402_facilitator_local::util::{Scheduler, SigDown};
+//!
+//! let sig_down = SigDown::try_new()?;
+//! let scheduler = Scheduler::new(sig_down.cancellation_token());
+//! scheduler.every("refresh", Duration::from_secs(60), Duration::from_secs(5), || async {
+//!     Ok(())
+//! });
+//! sig_down.recv().await;
+//! scheduler.shutdown().await;
+//! ```
+
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::BuildHasher;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+use x402_types::timestamp::UnixTimestamp;
+
+use crate::cluster::Cluster;
+
+/// Outcome of the runs of one scheduled task.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskHealth {
+    pub name: String,
+    /// `false` once the task has exited.
+    pub running: bool,
+    pub runs: u64,
+    /// Runs skipped because this replica was not the cluster leader.
+    pub skipped: u64,
+    pub failures: u64,
+    /// Failures since the last successful run.
+    pub consecutive_failures: u64,
+    pub last_run_at: Option<UnixTimestamp>,
+    pub last_success_at: Option<UnixTimestamp>,
+    pub last_error: Option<String>,
+}
+
+impl TaskHealth {
+    /// A task is healthy while it runs and its last run did not fail.
+    pub fn healthy(&self) -> bool {
+        self.running && self.consecutive_failures == 0
+    }
+
+    fn record(&mut self, result: Result<(), String>) {
+        let now = UnixTimestamp::now();
+        self.runs += 1;
+        self.last_run_at = Some(now);
+        match result {
+            Ok(()) => {
+                self.consecutive_failures = 0;
+                self.last_success_at = Some(now);
+                self.last_error = None;
+            }
+            Err(e) => {
+                self.failures += 1;
+                self.consecutive_failures += 1;
+                self.last_error = Some(e);
+            }
+        }
+    }
+}
+
+/// Runs named background tasks until shutdown.
+#[derive(Debug)]
+pub struct Scheduler {
+    tracker: TaskTracker,
+    cancellation_token: CancellationToken,
+    tasks: Mutex<Vec<Arc<Mutex<TaskHealth>>>>,
+}
+
+impl Scheduler {
+    /// Creates a scheduler whose tasks stop when `cancellation_token` is cancelled.
+    pub fn new(cancellation_token: CancellationToken) -> Self {
+        Self {
+            tracker: TaskTracker::new(),
+            cancellation_token,
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn register(&self, name: &str) -> Arc<Mutex<TaskHealth>> {
+        let health = Arc::new(Mutex::new(TaskHealth {
+            name: name.to_string(),
+            running: true,
+            ..TaskHealth::default()
+        }));
+        self.tasks
+            .lock()
+            .expect("scheduler lock poisoned")
+            .push(health.clone());
+        health
+    }
+
+    /// Runs `job` every `period`, each wait extended by a random delay of up to `jitter`.
+    ///
+    /// The first run happens after the first wait. A failed run is recorded and the
+    /// task keeps its schedule.
+    pub fn every<F, Fut>(&self, name: &str, period: Duration, jitter: Duration, job: F)
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.schedule(name, period, jitter, None, job);
+    }
+
+    /// Like [`Scheduler::every`], but skips the runs while this replica is not the
+    /// leader of `cluster`, so that the job runs once cluster-wide.
+    ///
+    /// Skipped runs are counted in [`TaskHealth::skipped`] and do not affect the
+    /// task's health.
+    pub fn every_on_leader<F, Fut>(
+        &self,
+        name: &str,
+        period: Duration,
+        jitter: Duration,
+        cluster: Arc<Cluster>,
+        job: F,
+    ) where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.schedule(name, period, jitter, Some(cluster), job);
+    }
+
+    fn schedule<F, Fut>(
+        &self,
+        name: &str,
+        period: Duration,
+        jitter: Duration,
+        cluster: Option<Arc<Cluster>>,
+        mut job: F,
+    ) where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let health = self.register(name);
+        let token = self.cancellation_token.clone();
+        let _name = name.to_string();
+        self.tracker.spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = tokio::time::sleep(period + random_jitter(jitter)) => {}
+                }
+                if cluster.as_ref().is_some_and(|cluster| !cluster.is_leader()) {
+                    health.lock().expect("task health lock poisoned").skipped += 1;
+                    continue;
+                }
+                let result = job().await;
+                #[cfg(feature = "telemetry")]
+                if let Err(e) = &result {
+                    tracing::warn!(task = %_name, error = %e, "Scheduled task failed");
+                }
+                health
+                    .lock()
+                    .expect("task health lock poisoned")
+                    .record(result);
+            }
+            health.lock().expect("task health lock poisoned").running = false;
+        });
+    }
+
+    /// Runs a long-lived task, such as a signal listener, until it returns.
+    ///
+    /// The task receives the shutdown token and is expected to return once it is
+    /// cancelled. Its result is recorded like a single run.
+    pub fn spawn<F, Fut>(&self, name: &str, task: F)
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let health = self.register(name);
+        let future = task(self.cancellation_token.clone());
+        let _name = name.to_string();
+        self.tracker.spawn(async move {
+            let result = future.await;
+            #[cfg(feature = "telemetry")]
+            if let Err(e) = &result {
+                tracing::error!(task = %_name, error = %e, "Background task exited");
+            }
+            let mut health = health.lock().expect("task health lock poisoned");
+            health.record(result);
+            health.running = false;
+        });
+    }
+
+    /// Status of every registered task, in registration order.
+    pub fn health(&self) -> Vec<TaskHealth> {
+        self.tasks
+            .lock()
+            .expect("scheduler lock poisoned")
+            .iter()
+            .map(|task| task.lock().expect("task health lock poisoned").clone())
+            .collect()
+    }
+
+    /// Cancels all tasks and waits for running jobs to finish.
+    pub async fn shutdown(&self) {
+        self.cancellation_token.cancel();
+        self.tracker.close();
+        self.tracker.wait().await;
+    }
+}
+
+/// A random duration in `[0, max]`.
+fn random_jitter(max: Duration) -> Duration {
+    let max_ms = max.as_millis() as u64;
+    if max_ms == 0 {
+        return Duration::ZERO;
+    }
+    let random = RandomState::new().hash_one(std::time::SystemTime::now());
+    Duration::from_millis(random % (max_ms + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_scheduler() {
+        let scheduler = Scheduler::new(CancellationToken::new());
+        let calls = Arc::new(AtomicU64::new(0));
+        let counter = calls.clone();
+        scheduler.every("flaky", Duration::from_secs(1), Duration::ZERO, move || {
+            let call = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if call == 1 {
+                    Err("boom".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+        });
+        let follower = Arc::new(Cluster::new(
+            "b",
+            crate::cluster::MemoryLeaseStore::default(),
+            Duration::from_secs(30),
+        ));
+        scheduler.every_on_leader(
+            "singleton",
+            Duration::from_secs(1),
+            Duration::ZERO,
+            follower,
+            || async { Ok(()) },
+        );
+        scheduler.spawn("listener", |token| async move {
+            token.cancelled().await;
+            Ok(())
+        });
+
+        tokio::time::sleep(Duration::from_millis(2500)).await;
+        let health = scheduler.health();
+        assert_eq!(health[0].runs, 2);
+        assert_eq!(health[0].last_error.as_deref(), Some("boom"));
+        assert!(!health[0].healthy());
+        assert_eq!((health[1].runs, health[1].skipped), (0, 2));
+        assert!(health[1].healthy());
+        assert!(health[2].running);
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(scheduler.health()[0].healthy());
+
+        scheduler.shutdown().await;
+        assert!(scheduler.health().iter().all(|task| !task.running));
+        assert!(random_jitter(Duration::from_millis(10)) <= Duration::from_millis(10));
+    }
+}
@@ -31,6 +31,9 @@
 //!
 //! The [`TaskTracker`] is used to ensure the signal handler task completes before
 //! the application exits.
+//!
+//! Shutdown may take a while when in-flight work is drained first. A second signal
+//! triggers the [`SigDown::force_token`], for operators who do not want to wait.
 
 use tokio::signal::unix::SignalKind;
 use tokio::signal::unix::signal;
@@ -64,6 +67,7 @@ use tokio_util::task::TaskTracker;
 pub struct SigDown {
     task_tracker: TaskTracker,
     cancellation_token: CancellationToken,
+    force_token: CancellationToken,
 }
 
 impl SigDown {
@@ -89,6 +93,8 @@ impl SigDown {
         let mut sigint = signal(SignalKind::interrupt())?;
         let inner = CancellationToken::new();
         let outer = inner.clone();
+        let force_token = CancellationToken::new();
+        let force = force_token.clone();
         let task_tracker = TaskTracker::new();
         task_tracker.spawn(async move {
             tokio::select! {
@@ -99,11 +105,20 @@ impl SigDown {
                     inner.cancel();
                 }
             }
+            // Not tracked, so that `recv` returns after the first signal.
+            tokio::spawn(async move {
+                tokio::select! {
+                    _ = sigterm.recv() => {},
+                    _ = sigint.recv() => {}
+                }
+                force.cancel();
+            });
         });
         task_tracker.close();
         Ok(Self {
             task_tracker,
             cancellation_token: outer,
+            force_token,
         })
     }
 
@@ -131,6 +146,28 @@ impl SigDown {
         self.cancellation_token.clone()
     }
 
+    /// Returns a token cancelled by a second shutdown signal.
+    ///
+    /// Subsystems draining in-flight work on shutdown stop waiting when it fires.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use x402_facilitator_local::util::SigDown;
+    ///
+    /// let sig_down = SigDown::try_new()?;
+    /// let force_token = sig_down.force_token();
+    ///
+    /// sig_down.recv().await;
+    /// tokio::select! {
+    ///     _ = drain_in_flight_work() => {},
+    ///     _ = force_token.cancelled() => println!("Forced shutdown"),
+    /// }
+    /// ```
+    pub fn force_token(&self) -> CancellationToken {
+        self.force_token.clone()
+    }
+
     /// Waits for a shutdown signal and ensures the signal handler task completes.
     ///
     /// This method blocks until either SIGTERM or SIGINT is received, then waits
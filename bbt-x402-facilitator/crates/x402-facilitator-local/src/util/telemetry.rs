@@ -40,16 +40,20 @@
 //!
 //! The telemetry system provides:
 //! - Distributed tracing via OpenTelemetry
-//! - Metrics collection via OTLP
-//! - HTTP request tracing for axum applications
+//! - Metrics collection via OTLP, including the verification and settlement
+//!   metrics of [`FacilitatorMetrics`](crate::util::metrics::FacilitatorMetrics)
+//! - HTTP request tracing for axum applications, continuing the caller's trace from a
+//!   W3C `traceparent` header
 //! - Automatic graceful shutdown of exporters via [`TelemetryProviders`]
 
-use axum::http::{Request, Response};
+use axum::http::{HeaderMap, Request, Response};
+use opentelemetry::propagation::Extractor;
 use opentelemetry::trace::{Status, TracerProvider};
 use opentelemetry::{KeyValue, Value, global};
 use opentelemetry_sdk::{
     Resource,
     metrics::{MeterProviderBuilder, PeriodicReader, SdkMeterProvider},
+    propagation::TraceContextPropagator,
     trace::{RandomIdGenerator, Sampler, SdkTracerProvider},
 };
 use opentelemetry_semantic_conventions::{
@@ -128,6 +132,9 @@ pub struct Telemetry {
     ///
     /// May be overridden by the `OTEL_SERVICE_DEPLOYMENT` environment variable.
     pub deployment: Option<Value>,
+    /// Further resource attributes, such as the networks and schemes the service
+    /// handles, attached to every span and metric.
+    pub attributes: Vec<KeyValue>,
 }
 
 impl Telemetry {
@@ -162,6 +169,13 @@ impl Telemetry {
         this
     }
 
+    /// Adds a resource attribute.
+    pub fn with_attribute(&self, key: &'static str, value: impl Into<Value>) -> Self {
+        let mut this = self.clone();
+        this.attributes.push(KeyValue::new(key, value));
+        this
+    }
+
     /// Resolves the service name for telemetry.
     ///
     /// Order of precedence:
@@ -207,6 +221,7 @@ impl Telemetry {
     /// - Service name (if set or inferred from `OTEL_SERVICE_NAME`)
     /// - Service version (from `OTEL_SERVICE_VERSION` or `self.version`)
     /// - Deployment environment (from `OTEL_SERVICE_DEPLOYMENT` or `self.deployment`)
+    /// - The attributes added with [`Telemetry::with_attribute`]
     ///
     /// The semantic attributes are attached with the OpenTelemetry semantic conventions (see [`SCHEMA_URL`]).
     pub fn resource(&self) -> Resource {
@@ -224,7 +239,7 @@ impl Telemetry {
         if !attributes.is_empty() {
             builder = builder.with_schema_url(attributes, SCHEMA_URL);
         }
-        builder.build()
+        builder.with_attributes(self.attributes.clone()).build()
     }
 
     /// Initializes the OpenTelemetry tracer provider.
@@ -302,6 +317,8 @@ impl Telemetry {
         let telemetry_protocol = TelemetryProtocol::from_env();
         match telemetry_protocol {
             Some(telemetry_protocol) => {
+                // Requests carrying a W3C `traceparent` join the caller's trace.
+                global::set_text_map_propagator(TraceContextPropagator::new());
                 let tracer_provider = self.init_tracer_provider(&telemetry_protocol);
                 let meter_provider = self.init_meter_provider(&telemetry_protocol);
                 let tracer = tracer_provider.tracer("tracing-otel-subscriber");
@@ -422,14 +439,33 @@ pub struct FacilitatorHttpMakeSpan;
 
 impl<A> MakeSpan<A> for FacilitatorHttpMakeSpan {
     fn make_span(&mut self, request: &Request<A>) -> Span {
-        tracing::info_span!(
+        let span = tracing::info_span!(
             "http_request",
             otel.kind = "server",
             otel.name = %format!("{} {}", request.method(), request.uri()),
             method = %request.method(),
             uri = %request.uri(),
             version = ?request.version(),
-        )
+        );
+        let parent = global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(request.headers()))
+        });
+        // Without a propagated context, the span starts a new trace.
+        let _ = span.set_parent(parent);
+        span
+    }
+}
+
+/// Reads propagated trace context from request headers.
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
     }
 }
 
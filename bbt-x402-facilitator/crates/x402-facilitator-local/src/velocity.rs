@@ -0,0 +1,398 @@
+//! Per-payer velocity limits.
+//!
+//! [`VelocityLimiter`] caps how many payments each payer address settles, and how
+//! much value, within sliding windows, as compliance teams ask of transaction
+//! monitoring that deny and allow lists cannot express. A payment that would
+//! exceed a limit is rejected at `/verify` and at `/settle` with
+//! [`PaymentVerificationError::VelocityLimitExceeded`], reported to clients as
+//! `velocity_limit_exceeded`.
+//!
+//! Only successful settlements count: verifications do not, and a settlement that
+//! fails gives its share of the limits back. Value is tracked separately for every
+//! asset, in the asset's smallest unit.
+//!
+//! # Configuration
+//!
+//! | Variable | Description |
+//! |----------|-------------|
+//! | `VELOCITY_MAX_SETTLEMENTS` | Settlements per payer, as comma-separated `<window>=<count>` |
+//! | `VELOCITY_MAX_VALUE` | Settled value per payer and asset, as comma-separated `<window>=<amount>` |
+//!
+//! Windows are a number followed by `s`, `m`, `h` or `d`, e.g.
+//! `VELOCITY_MAX_SETTLEMENTS=1m=5,1h=50` and `VELOCITY_MAX_VALUE=1d=1000000000`.
+//! Without either variable, payers are not limited.
+//!
+//! Settlements are counted in memory, per facilitator instance.
+
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use alloy_primitives::U256;
+use x402_types::proto;
+use x402_types::proto::PaymentVerificationError;
+
+/// Number of tracked payers after which payers without recent settlements are pruned.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// What a [`VelocityLimit`] caps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VelocityMeasure {
+    /// The number of settlements.
+    Settlements(u64),
+    /// The settled value per asset, in its smallest unit.
+    Value(U256),
+}
+
+/// A cap on the settlements of a payer within a sliding window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VelocityLimit {
+    pub window: Duration,
+    pub measure: VelocityMeasure,
+}
+
+impl VelocityLimit {
+    /// Caps the number of settlements within `window`.
+    pub fn settlements(window: Duration, count: u64) -> Self {
+        Self {
+            window,
+            measure: VelocityMeasure::Settlements(count),
+        }
+    }
+
+    /// Caps the value settled in each asset within `window`.
+    pub fn value(window: Duration, amount: U256) -> Self {
+        Self {
+            window,
+            measure: VelocityMeasure::Value(amount),
+        }
+    }
+
+    fn exceeded(&self) -> PaymentVerificationError {
+        let (measure, limit) = match self.measure {
+            VelocityMeasure::Settlements(count) => ("settlements", count.to_string()),
+            VelocityMeasure::Value(amount) => ("value", amount.to_string()),
+        };
+        PaymentVerificationError::VelocityLimitExceeded {
+            measure: measure.to_string(),
+            limit,
+            window_seconds: self.window.as_secs(),
+        }
+    }
+}
+
+/// A settlement counted against the limits of its payer.
+#[derive(Debug)]
+struct Settlement {
+    id: u64,
+    at: Instant,
+    asset: String,
+    amount: U256,
+}
+
+/// A settlement counted by [`VelocityLimiter::reserve`], to be given back with
+/// [`VelocityLimiter::release`] if it does not go through.
+#[derive(Debug)]
+pub struct VelocityReservation {
+    payer: String,
+    id: u64,
+}
+
+/// Enforces per-payer velocity limits, see the [module documentation](self).
+#[derive(Debug)]
+pub struct VelocityLimiter {
+    limits: Vec<VelocityLimit>,
+    settlements: Mutex<HashMap<String, VecDeque<Settlement>>>,
+    next_id: AtomicU64,
+}
+
+impl VelocityLimiter {
+    /// Creates a limiter enforcing `limits`.
+    pub fn new(limits: Vec<VelocityLimit>) -> Self {
+        Self {
+            limits,
+            settlements: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Creates a limiter from `VELOCITY_MAX_SETTLEMENTS` and `VELOCITY_MAX_VALUE`.
+    ///
+    /// Returns `Ok(None)` when neither is set.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let mut limits = Vec::new();
+        if let Some(value) = non_empty_var("VELOCITY_MAX_SETTLEMENTS") {
+            for (window, count) in parse_limits("VELOCITY_MAX_SETTLEMENTS", &value)? {
+                let count = u64::from_str(count)
+                    .map_err(|e| format!("invalid VELOCITY_MAX_SETTLEMENTS count: {e}"))?;
+                limits.push(VelocityLimit::settlements(window, count));
+            }
+        }
+        if let Some(value) = non_empty_var("VELOCITY_MAX_VALUE") {
+            for (window, amount) in parse_limits("VELOCITY_MAX_VALUE", &value)? {
+                let amount = U256::from_str(amount)
+                    .map_err(|e| format!("invalid VELOCITY_MAX_VALUE amount: {e}"))?;
+                limits.push(VelocityLimit::value(window, amount));
+            }
+        }
+        Ok((!limits.is_empty()).then(|| Self::new(limits)))
+    }
+
+    /// Returns the enforced limits.
+    pub fn limits(&self) -> &[VelocityLimit] {
+        &self.limits
+    }
+
+    /// Checks whether settling `request` would exceed a limit of its payer.
+    ///
+    /// Requests whose payer, asset or amount cannot be read are rejected.
+    pub fn check(&self, request: &proto::VerifyRequest) -> Result<(), PaymentVerificationError> {
+        let (payer, asset, amount) = payment_of(request)?;
+        let mut settlements = self.settlements.lock().expect("velocity lock poisoned");
+        self.check_at(&mut settlements, &payer, &asset, amount, Instant::now())
+    }
+
+    /// Counts the settlement of `request` against the limits of its payer, unless it
+    /// would exceed one.
+    ///
+    /// Requests whose payer, asset or amount cannot be read are rejected.
+    pub fn reserve(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<VelocityReservation, PaymentVerificationError> {
+        let (payer, asset, amount) = payment_of(request)?;
+        self.reserve_at(payer, asset, amount, Instant::now())
+    }
+
+    /// Gives back a settlement counted by [`VelocityLimiter::reserve`].
+    pub fn release(&self, reservation: VelocityReservation) {
+        let mut settlements = self.settlements.lock().expect("velocity lock poisoned");
+        if let Some(history) = settlements.get_mut(&reservation.payer) {
+            history.retain(|settlement| settlement.id != reservation.id);
+        }
+    }
+
+    fn reserve_at(
+        &self,
+        payer: String,
+        asset: String,
+        amount: U256,
+        now: Instant,
+    ) -> Result<VelocityReservation, PaymentVerificationError> {
+        let mut settlements = self.settlements.lock().expect("velocity lock poisoned");
+        self.check_at(&mut settlements, &payer, &asset, amount, now)?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        settlements
+            .entry(payer.clone())
+            .or_default()
+            .push_back(Settlement {
+                id,
+                at: now,
+                asset,
+                amount,
+            });
+        Ok(VelocityReservation { payer, id })
+    }
+
+    fn check_at(
+        &self,
+        settlements: &mut HashMap<String, VecDeque<Settlement>>,
+        payer: &str,
+        asset: &str,
+        amount: U256,
+        now: Instant,
+    ) -> Result<(), PaymentVerificationError> {
+        let longest = self
+            .limits
+            .iter()
+            .map(|limit| limit.window)
+            .max()
+            .unwrap_or_default();
+        if settlements.len() > PRUNE_THRESHOLD {
+            settlements.retain(|_, history| {
+                history
+                    .back()
+                    .is_some_and(|settlement| now.duration_since(settlement.at) < longest)
+            });
+        }
+        let Some(history) = settlements.get_mut(payer) else {
+            return self.check_history(&VecDeque::new(), asset, amount, now);
+        };
+        while history
+            .front()
+            .is_some_and(|settlement| now.duration_since(settlement.at) >= longest)
+        {
+            history.pop_front();
+        }
+        self.check_history(history, asset, amount, now)
+    }
+
+    fn check_history(
+        &self,
+        history: &VecDeque<Settlement>,
+        asset: &str,
+        amount: U256,
+        now: Instant,
+    ) -> Result<(), PaymentVerificationError> {
+        for limit in &self.limits {
+            let recent = history
+                .iter()
+                .filter(|settlement| now.duration_since(settlement.at) < limit.window);
+            let within = match limit.measure {
+                VelocityMeasure::Settlements(count) => (recent.count() as u64) < count,
+                VelocityMeasure::Value(cap) => recent
+                    .filter(|settlement| settlement.asset == asset)
+                    .try_fold(amount, |total, settlement| {
+                        total.checked_add(settlement.amount)
+                    })
+                    .is_some_and(|total| total <= cap),
+            };
+            if !within {
+                return Err(limit.exceeded());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The lowercase payer, lowercase asset and transferred amount of a payment.
+fn payment_of(
+    request: &proto::VerifyRequest,
+) -> Result<(String, String, U256), PaymentVerificationError> {
+    let payer = request.payer();
+    let asset = request.asset();
+    let amount = request
+        .transfer_amount()
+        .and_then(|amount| U256::from_str(&amount).ok());
+    match (payer, asset, amount) {
+        (Some(payer), Some(asset), Some(amount)) => {
+            Ok((payer.to_lowercase(), asset.to_lowercase(), amount))
+        }
+        _ => Err(PaymentVerificationError::InvalidFormat(
+            "payer, asset or amount could not be read from the request".to_string(),
+        )),
+    }
+}
+
+fn non_empty_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+/// Parses comma-separated `<window>=<limit>` pairs.
+fn parse_limits<'a>(name: &str, value: &'a str) -> Result<Vec<(Duration, &'a str)>, String> {
+    value
+        .split(',')
+        .map(|pair| {
+            let (window, limit) = pair.split_once('=').ok_or_else(|| {
+                format!("invalid {name} entry, expected <window>=<limit>: {pair}")
+            })?;
+            let window = parse_window(window.trim())
+                .ok_or_else(|| format!("invalid {name} window: {window}"))?;
+            Ok((window, limit.trim()))
+        })
+        .collect()
+}
+
+/// Parses a window such as `30s`, `15m`, `1h` or `7d`.
+fn parse_window(window: &str) -> Option<Duration> {
+    let unit = match window.chars().last()? {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86_400,
+        _ => return None,
+    };
+    let count = u64::from_str(&window[..window.len() - 1]).ok()?;
+    (count > 0).then(|| Duration::from_secs(count * unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYER: &str = "0xaaa0000000000000000000000000000000000001";
+    const USDC: &str = "0xbbb0000000000000000000000000000000000002";
+    const EURC: &str = "0xccc0000000000000000000000000000000000003";
+
+    fn window(result: Result<VelocityReservation, PaymentVerificationError>) -> u64 {
+        match result {
+            Err(PaymentVerificationError::VelocityLimitExceeded { window_seconds, .. }) => {
+                window_seconds
+            }
+            other => panic!("expected VelocityLimitExceeded, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_limits_slide_with_time() {
+        let limiter = VelocityLimiter::new(vec![
+            VelocityLimit::settlements(Duration::from_secs(60), 2),
+            VelocityLimit::value(Duration::from_secs(3600), U256::from(100)),
+        ]);
+        let reserve = |asset: &str, amount: u64, at: Instant| {
+            limiter.reserve_at(PAYER.to_string(), asset.to_string(), U256::from(amount), at)
+        };
+        let start = Instant::now();
+
+        reserve(USDC, 40, start).unwrap();
+        let second = reserve(USDC, 40, start).unwrap();
+        assert_eq!(window(reserve(EURC, 1, start)), 60);
+
+        let later = start + Duration::from_secs(60);
+        assert_eq!(window(reserve(USDC, 30, later)), 3600);
+        // Value is capped per asset.
+        reserve(EURC, 100, later).unwrap();
+
+        // A settlement that did not go through counts no more.
+        limiter.release(second);
+        reserve(USDC, 60, later).unwrap();
+
+        let next_hour = later + Duration::from_secs(3600);
+        reserve(USDC, 100, next_hour).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_payments_are_rejected() {
+        let limiter =
+            VelocityLimiter::new(vec![VelocityLimit::settlements(Duration::from_secs(60), 1)]);
+        let request = |payload: serde_json::Value| {
+            proto::SettleRequest::from(serde_json::json!({
+                "x402Version": 2,
+                "paymentPayload": {"x402Version": 2, "payload": payload},
+                "paymentRequirements": {"asset": USDC, "amount": "1"},
+            }))
+        };
+
+        let paid = request(serde_json::json!({"authorization": {"from": PAYER}}));
+        limiter.check(&paid).unwrap();
+        limiter.reserve(&paid).unwrap();
+        assert_eq!(window(limiter.reserve(&paid)), 60);
+
+        let unreadable = request(serde_json::json!({"authorization": {}}));
+        assert!(matches!(
+            limiter.check(&unreadable),
+            Err(PaymentVerificationError::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            limiter.reserve(&unreadable),
+            Err(PaymentVerificationError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        assert_eq!(
+            parse_limits("VELOCITY_MAX_SETTLEMENTS", "1m=5, 24h = 50").unwrap(),
+            vec![
+                (Duration::from_secs(60), "5"),
+                (Duration::from_secs(86_400), "50")
+            ]
+        );
+        assert!(parse_limits("VELOCITY_MAX_SETTLEMENTS", "5").is_err());
+        assert!(parse_limits("VELOCITY_MAX_SETTLEMENTS", "1w=5").is_err());
+        assert!(parse_limits("VELOCITY_MAX_SETTLEMENTS", "0s=5").is_err());
+    }
+}
@@ -0,0 +1,253 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+use x402_reqwest::X402Client;
+use x402_types::proto;
+use x402_types::proto::payment_required::{ParsedPaymentRequired, PaymentRequiredError};
+use x402_types::scheme::client::{PaymentSelector, X402Error};
+use x402_types::util::Base64Bytes;
+
+use crate::types::{CallToolParams, CallToolResult, JsonRpcError, PAYMENT_REQUIRED_CODE};
+
+/// Why a `tools/call` request got no result.
+#[derive(Debug, thiserror::Error)]
+pub enum McpError {
+    /// The server answered with a JSON-RPC error.
+    #[error("JSON-RPC error {}: {}", .0.code, .0.message)]
+    Rpc(JsonRpcError),
+    /// The request could not be sent or the response not read.
+    #[error("MCP transport failed: {0}")]
+    Transport(String),
+}
+
+/// Sends `tools/call` requests over an MCP session.
+///
+/// Implement it on top of the MCP SDK or transport in use (stdio, streamable HTTP,
+/// ...); [`X402McpClient`] adds the payment negotiation.
+#[async_trait]
+pub trait McpTransport: Send + Sync {
+    /// Calls a tool and returns its result.
+    async fn call_tool(&self, params: CallToolParams) -> Result<CallToolResult, McpError>;
+}
+
+/// Why a paid tool call failed.
+#[derive(Debug, thiserror::Error)]
+pub enum X402McpError {
+    #[error(transparent)]
+    Mcp(#[from] McpError),
+    /// The server's payment challenge could not be parsed.
+    #[error("Invalid payment challenge: {0}")]
+    InvalidChallenge(#[from] PaymentRequiredError),
+    /// No payment could be made, e.g. no registered scheme matches or a spend limit
+    /// would be exceeded.
+    #[error(transparent)]
+    Payment(#[from] X402Error),
+    /// The server answered the paid call with another challenge.
+    #[error("Payment rejected: {}", .0.as_deref().unwrap_or("no reason given"))]
+    PaymentRejected(Option<String>),
+}
+
+/// An MCP client that pays for tool calls.
+///
+/// Tool calls are sent as-is. If the server answers with a payment challenge, the
+/// [`X402Client`] selects one of its options and signs it, with the same scheme
+/// clients, selector, balances and spend limits as for HTTP requests, and the call
+/// is sent again with the payment.
+pub struct X402McpClient<T, TSelector> {
+    transport: T,
+    x402: Arc<X402Client<TSelector>>,
+}
+
+impl<T, TSelector> X402McpClient<T, TSelector>
+where
+    T: McpTransport,
+    TSelector: PaymentSelector,
+{
+    /// Wraps an MCP transport, paying with `x402`.
+    pub fn new(transport: T, x402: Arc<X402Client<TSelector>>) -> Self {
+        Self { transport, x402 }
+    }
+
+    /// Returns the wrapped transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Calls a tool, paying for it if the server asks for payment.
+    ///
+    /// The receipt of a payment is available from
+    /// [`CallToolResult::payment_receipt`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`X402McpError::PaymentRejected`] if the server does not accept the
+    /// payment, and the other variants if the call, the challenge or the payment
+    /// fails.
+    pub async fn call_tool(&self, params: CallToolParams) -> Result<CallToolResult, X402McpError> {
+        let challenge = match self.transport.call_tool(params.clone()).await {
+            Ok(result) => match result.payment_required() {
+                Some(challenge) => challenge,
+                None => return Ok(result),
+            },
+            Err(McpError::Rpc(error)) if error.code == PAYMENT_REQUIRED_CODE => {
+                error.data.ok_or(PaymentRequiredError::MissingVersion)?
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let challenge = ParsedPaymentRequired::from_value(challenge)?.payment_required;
+        let payment = self.sign(&challenge).await?;
+
+        match self.transport.call_tool(params.with_payment(payment)).await {
+            Ok(result) => match result.payment_required() {
+                Some(challenge) => Err(X402McpError::PaymentRejected(rejection(&challenge))),
+                None => Ok(result),
+            },
+            Err(McpError::Rpc(error)) if error.code == PAYMENT_REQUIRED_CODE => {
+                let reason = error.data.as_ref().and_then(rejection);
+                Err(X402McpError::PaymentRejected(
+                    reason.or(Some(error.message)),
+                ))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Signs a payment for the challenge and returns the payment payload as JSON.
+    async fn sign(&self, challenge: &proto::PaymentRequired) -> Result<Value, X402Error> {
+        let signed_payload = self.x402.sign_payment(challenge).await?;
+        let json = Base64Bytes::from(signed_payload.as_bytes())
+            .decode()
+            .map_err(|e| X402Error::SigningError(format!("{e}")))?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+}
+
+/// Returns the reason a server gives in a challenge for rejecting a payment.
+fn rejection(challenge: &Value) -> Option<String> {
+    challenge
+        .get("error")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+    use serde_json::json;
+    use std::sync::Mutex;
+    use x402_types::scheme::X402SchemeId;
+    use x402_types::scheme::client::{PaymentCandidate, PaymentCandidateSigner, X402SchemeClient};
+
+    use crate::types::PAYMENT_META_KEY;
+
+    struct FakeScheme;
+
+    impl X402SchemeId for FakeScheme {
+        fn namespace(&self) -> &str {
+            "eip155"
+        }
+
+        fn scheme(&self) -> &str {
+            "exact"
+        }
+    }
+
+    struct FakeSigner;
+
+    #[async_trait]
+    impl PaymentCandidateSigner for FakeSigner {
+        async fn sign_payment(&self) -> Result<String, X402Error> {
+            let payload = json!({"x402Version": 2, "payload": {"signature": "0xabcd"}});
+            Ok(Base64Bytes::encode(payload.to_string()).to_string())
+        }
+    }
+
+    impl X402SchemeClient for FakeScheme {
+        fn accept(&self, payment_required: &proto::PaymentRequired) -> Vec<PaymentCandidate> {
+            let proto::PaymentRequired::V2(payment_required) = payment_required else {
+                return vec![];
+            };
+            payment_required
+                .accepts
+                .iter()
+                .map(|requirements| PaymentCandidate {
+                    chain_id: requirements.network.clone(),
+                    asset: requirements.asset.to_string(),
+                    amount: requirements.amount.parse().unwrap_or(U256::ZERO),
+                    scheme: requirements.scheme.clone(),
+                    x402_version: 2,
+                    pay_to: requirements.pay_to.to_string(),
+                    transfer_method: None,
+                    signer: Box::new(FakeSigner),
+                })
+                .collect()
+        }
+    }
+
+    /// A tool server charging for every call, accepting any payment.
+    #[derive(Default)]
+    struct PaidTool {
+        calls: Mutex<Vec<CallToolParams>>,
+    }
+
+    #[async_trait]
+    impl McpTransport for PaidTool {
+        async fn call_tool(&self, params: CallToolParams) -> Result<CallToolResult, McpError> {
+            self.calls.lock().unwrap().push(params.clone());
+            let paid = params
+                .meta
+                .is_some_and(|meta| meta.contains_key(PAYMENT_META_KEY));
+            if !paid {
+                return Ok(CallToolResult {
+                    structured_content: Some(json!({
+                        "x402Version": 2,
+                        "resource": {"url": "mcp://tool/weather", "description": "", "mimeType": ""},
+                        "accepts": [{
+                            "scheme": "exact",
+                            "network": "eip155:42793",
+                            "amount": "10000",
+                            "payTo": "0x1111111111111111111111111111111111111111",
+                            "maxTimeoutSeconds": 60,
+                            "asset": "0x2222222222222222222222222222222222222222"
+                        }]
+                    })),
+                    is_error: Some(true),
+                    ..Default::default()
+                });
+            }
+            let meta = json!({"x402/payment-response": {
+                "success": true,
+                "payer": "0x3333333333333333333333333333333333333333",
+                "transaction": "0xabcd",
+                "network": "eip155:42793"
+            }});
+            Ok(CallToolResult {
+                content: vec![json!({"type": "text", "text": "Sunny"})],
+                meta: meta.as_object().cloned(),
+                ..Default::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_pays_challenge() {
+        let x402 = Arc::new(X402Client::new().register(FakeScheme));
+        let client = X402McpClient::new(PaidTool::default(), x402);
+
+        let result = client
+            .call_tool(CallToolParams::new("weather", Default::default()))
+            .await
+            .unwrap();
+        assert_eq!(result.content[0]["text"], "Sunny");
+        let receipt = result.payment_receipt().unwrap().unwrap();
+        assert_eq!(receipt.transaction, "0xabcd");
+
+        let calls = client.transport().calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(
+            calls[1].meta.as_ref().unwrap()[PAYMENT_META_KEY]["payload"]["signature"],
+            "0xabcd"
+        );
+    }
+}
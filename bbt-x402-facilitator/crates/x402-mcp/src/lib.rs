@@ -0,0 +1,40 @@
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+
+//! [x402](https://www.x402.org) payments for [Model Context Protocol](https://modelcontextprotocol.io)
+//! tool calls.
+//!
+//! MCP has no HTTP status codes, so paid tools negotiate payment in the `tools/call`
+//! exchange itself:
+//!
+//! 1. The server answers an unpaid call with an error result (`isError: true`)
+//!    carrying the payment challenge in `structuredContent`, or with a JSON-RPC error
+//!    with code `402` carrying it in `data`.
+//! 2. The client signs one of the offered options and calls the tool again with the
+//!    payment payload in `_meta["x402/payment"]`.
+//! 3. The server settles the payment and returns the tool result, with the settle
+//!    response in `_meta["x402/payment-response"]`.
+//!
+//! [`X402McpClient`] runs the client side on top of any MCP transport, paying with
+//! the same [`X402Client`](x402_reqwest::X402Client) used for HTTP requests: the same
+//! scheme clients, payment selection, balances and spend limits.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use x402_mcp::{CallToolParams, McpTransport, X402McpClient};
+//! use x402_reqwest::X402Client;
+//!
+//! // `session` implements `McpTransport` on top of the MCP SDK in use.
+//! let x402 = Arc::new(X402Client::new().register(V2Eip155ExactClient::new(signer)));
+//! let client = X402McpClient::new(session, x402);
+//! let result = client.call_tool(CallToolParams::new("weather", arguments)).await?;
+//! if let Some(receipt) = result.payment_receipt()? {
+//!     println!("Paid in {} on {}", receipt.transaction, receipt.network);
+//! }
+//! ```
+
+mod client;
+pub mod types;
+
+pub use client::*;
+pub use types::{CallToolParams, CallToolResult, JsonRpcError};
@@ -0,0 +1,107 @@
+//! The parts of an MCP `tools/call` exchange that carry x402 payments.
+//!
+//! Only the members the payment flow reads or writes are typed; tool arguments and
+//! content blocks stay JSON, so the types fit any MCP SDK.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use x402_reqwest::{PaymentReceipt, PaymentReceiptError};
+use x402_types::proto::v1;
+
+/// `_meta` key of a tool call that carries the payment payload.
+pub const PAYMENT_META_KEY: &str = "x402/payment";
+/// `_meta` key of a tool result that carries the settle response.
+pub const PAYMENT_RESPONSE_META_KEY: &str = "x402/payment-response";
+/// JSON-RPC error code of a payment challenge sent as an error.
+pub const PAYMENT_REQUIRED_CODE: i64 = 402;
+
+/// Parameters of a `tools/call` request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CallToolParams {
+    /// Name of the tool to call.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Map<String, Value>>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+}
+
+impl CallToolParams {
+    /// Parameters calling `name` with the given arguments.
+    pub fn new(name: impl Into<String>, arguments: Map<String, Value>) -> Self {
+        Self {
+            name: name.into(),
+            arguments: Some(arguments),
+            meta: None,
+        }
+    }
+
+    /// Attaches a payment payload, as JSON.
+    pub fn with_payment(mut self, payment: Value) -> Self {
+        self.meta
+            .get_or_insert_with(Map::new)
+            .insert(PAYMENT_META_KEY.to_string(), payment);
+        self
+    }
+}
+
+/// Result of a `tools/call` request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallToolResult {
+    /// Content blocks, e.g. `{"type": "text", "text": "..."}`.
+    #[serde(default)]
+    pub content: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_error: Option<bool>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Map<String, Value>>,
+}
+
+impl CallToolResult {
+    /// Returns the payment challenge, if this is an error result asking for payment.
+    ///
+    /// Servers put the challenge in `structuredContent`, or, for clients without
+    /// structured content support, as JSON text in the first content block.
+    pub fn payment_required(&self) -> Option<Value> {
+        if self.is_error != Some(true) {
+            return None;
+        }
+        let text = self
+            .content
+            .first()
+            .and_then(|block| block.get("text"))
+            .and_then(Value::as_str)
+            .and_then(|text| serde_json::from_str(text).ok());
+        self.structured_content
+            .clone()
+            .into_iter()
+            .chain(text)
+            .find(|value| value.get("x402Version").is_some())
+    }
+
+    /// Returns the receipt of the payment made for this call, if any.
+    pub fn payment_receipt(&self) -> Result<Option<PaymentReceipt>, PaymentReceiptError> {
+        let Some(settlement) = self
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.get(PAYMENT_RESPONSE_META_KEY))
+        else {
+            return Ok(None);
+        };
+        let settlement = serde_json::from_value::<v1::SettleResponse>(settlement.clone())
+            .map_err(|e| PaymentReceiptError::Malformed(e.to_string()))?;
+        PaymentReceipt::try_from(settlement).map(Some)
+    }
+}
+
+/// A JSON-RPC error response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
@@ -0,0 +1,197 @@
+//! Picking a payment option the payer can afford.
+//!
+//! Servers may accept several tokens for one resource, e.g. USDC or BBT, each at
+//! its own amount. Selectors only see the options, not the wallet, so the first
+//! or preferred option can be a token the payer does not hold, and the payment
+//! fails at verification. A [`BalanceSource`] attached with
+//! [`X402Client::with_balances`] tells the middleware what the payer holds: options
+//! whose token balance is known to be below their amount are dropped before the
+//! selector runs. Options with an unknown balance are kept.
+//!
+//! [`RpcBalances`] reads ERC-20 balances of the payer from EVM JSON-RPC endpoints,
+//! [`StaticBalances`] serves balances the caller tracks itself.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use x402_reqwest::{RpcBalances, X402Client};
+//!
+//! let balances = RpcBalances::new(signer.address())
+//!     .with_rpc("eip155:42793".parse()?, "https://node.mainnet.etherlink.com".parse()?);
+//! let client = X402Client::new()
+//!     .register(V2Eip155ExactClient::new(signer))
+//!     .with_balances(Arc::new(balances));
+//! ```
+
+use alloy_primitives::{Address, U256};
+use async_trait::async_trait;
+use reqwest::Url;
+use std::collections::HashMap;
+use std::time::Duration;
+use x402_types::chain::ChainId;
+use x402_types::scheme::client::PaymentCandidate;
+
+#[cfg(doc)]
+use crate::X402Client;
+
+/// `balanceOf(address)` selector.
+const BALANCE_OF: &str = "0x70a08231";
+
+/// Balances of the payer, by chain and token.
+#[async_trait]
+pub trait BalanceSource: Send + Sync {
+    /// Balance of `asset` on `chain_id`, in the token's smallest unit. `None` if unknown.
+    async fn balance(&self, chain_id: &ChainId, asset: &str) -> Option<U256>;
+}
+
+/// Balances set by the caller. Assets compare case-insensitively.
+#[derive(Debug, Clone, Default)]
+pub struct StaticBalances(HashMap<(ChainId, String), U256>);
+
+impl StaticBalances {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the balance of `asset` on `chain_id`.
+    pub fn with_balance(mut self, chain_id: ChainId, asset: &str, amount: U256) -> Self {
+        self.0.insert((chain_id, asset.to_lowercase()), amount);
+        self
+    }
+}
+
+#[async_trait]
+impl BalanceSource for StaticBalances {
+    async fn balance(&self, chain_id: &ChainId, asset: &str) -> Option<U256> {
+        self.0
+            .get(&(chain_id.clone(), asset.to_lowercase()))
+            .copied()
+    }
+}
+
+/// ERC-20 balances of `holder`, read with `eth_call` from one RPC endpoint per chain.
+///
+/// Balances of chains without an endpoint, and failed reads, are unknown.
+#[derive(Debug, Clone)]
+pub struct RpcBalances {
+    client: reqwest::Client,
+    holder: Address,
+    rpcs: HashMap<ChainId, Url>,
+}
+
+impl RpcBalances {
+    pub fn new(holder: Address) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            holder,
+            rpcs: HashMap::new(),
+        }
+    }
+
+    /// Reads balances on `chain_id` from `rpc`.
+    pub fn with_rpc(mut self, chain_id: ChainId, rpc: Url) -> Self {
+        self.rpcs.insert(chain_id, rpc);
+        self
+    }
+}
+
+#[async_trait]
+impl BalanceSource for RpcBalances {
+    async fn balance(&self, chain_id: &ChainId, asset: &str) -> Option<U256> {
+        let rpc = self.rpcs.get(chain_id)?;
+        let data = format!(
+            "{BALANCE_OF}{:0>64}",
+            alloy_primitives::hex::encode(self.holder)
+        );
+        let response: serde_json::Value = self
+            .client
+            .post(rpc.clone())
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_call",
+                "params": [{ "to": asset, "data": data }, "latest"],
+            }))
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await
+            .ok()?
+            .json()
+            .await
+            .ok()?;
+        let result = response.get("result")?.as_str()?;
+        U256::from_str_radix(result.trim_start_matches("0x"), 16).ok()
+    }
+}
+
+/// Drops the candidates whose token balance is known to be below their amount.
+pub(crate) async fn affordable(
+    candidates: Vec<PaymentCandidate>,
+    balances: &dyn BalanceSource,
+) -> Vec<PaymentCandidate> {
+    let mut affordable = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let balance = balances
+            .balance(&candidate.chain_id, &candidate.asset)
+            .await;
+        if balance.is_none_or(|balance| balance >= candidate.amount) {
+            affordable.push(candidate);
+        }
+    }
+    affordable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use x402_types::scheme::client::{PaymentCandidateSigner, X402Error};
+
+    struct NoopSigner;
+
+    #[async_trait]
+    impl PaymentCandidateSigner for NoopSigner {
+        async fn sign_payment(&self) -> Result<String, X402Error> {
+            Ok(String::new())
+        }
+    }
+
+    fn candidate(asset: &str, amount: u64) -> PaymentCandidate {
+        PaymentCandidate {
+            chain_id: "eip155:42793".parse().unwrap(),
+            asset: asset.to_string(),
+            amount: U256::from(amount),
+            scheme: "exact".to_string(),
+            x402_version: 2,
+            pay_to: "0xBBB0000000000000000000000000000000000002".to_string(),
+            transfer_method: None,
+            signer: Box::new(NoopSigner),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_affordable_keeps_held_and_unknown_tokens() {
+        let usdc = "0xAAA0000000000000000000000000000000000001";
+        let bbt = "0xAAA0000000000000000000000000000000000002";
+        let other = "0xAAA0000000000000000000000000000000000003";
+        let balances = StaticBalances::new()
+            .with_balance("eip155:42793".parse().unwrap(), usdc, U256::from(5))
+            .with_balance(
+                "eip155:42793".parse().unwrap(),
+                &bbt.to_lowercase(),
+                U256::from(1_000),
+            );
+
+        let kept = affordable(
+            vec![
+                candidate(usdc, 10),
+                candidate(bbt, 1_000),
+                candidate(other, 1),
+            ],
+            &balances,
+        )
+        .await;
+        let assets: Vec<_> = kept.iter().map(|c| c.asset.as_str()).collect();
+        assert_eq!(assets, vec![bbt, other]);
+    }
+}
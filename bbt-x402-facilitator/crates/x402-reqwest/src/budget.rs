@@ -0,0 +1,265 @@
+//! Spend limits for automated clients.
+//!
+//! An agent that pays for whatever its requests run into can drain a wallet when a
+//! loop goes wrong or a server raises its price. A [`Budget`] attached with
+//! [`X402Client::with_budget`] caps spending per token, per request, per rolling
+//! hour and in total. The middleware charges the budget after choosing a payment
+//! option and before signing it; a payment that would exceed a cap fails the
+//! request with [`X402Error::BudgetExceeded`] and nothing is signed.
+//!
+//! A payment counts as spent once it is signed, whether or not the server accepts
+//! it, as the signed authorization can be settled either way. Tokens without a
+//! limit are not restricted.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use alloy_primitives::U256;
+//! use std::sync::Arc;
+//! use x402_reqwest::{Budget, SpendLimit, X402Client};
+//!
+//! let budget = Arc::new(Budget::new().with_limit(
+//!     "eip155:42793".parse().unwrap(),
+//!     "0x7EfE4bdd11237610bcFca478937658bE39F8dfd6",
+//!     SpendLimit::new()
+//!         .with_per_request(U256::from(1_000_000))
+//!         .with_per_hour(U256::from(50_000_000))
+//!         .with_total(U256::from(500_000_000)),
+//! ));
+//! let client = X402Client::new().with_budget(budget.clone());
+//! ```
+
+use alloy_primitives::U256;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use x402_types::chain::ChainId;
+use x402_types::scheme::client::{BudgetExceeded, PaymentCandidate, SpendWindow, X402Error};
+
+#[cfg(doc)]
+use crate::X402Client;
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+/// Spend caps for one token, in its smallest unit. Unset caps do not apply.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "keystore",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct SpendLimit {
+    /// Largest single payment.
+    #[cfg_attr(
+        feature = "keystore",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub per_request: Option<U256>,
+    /// Largest sum of payments within any rolling hour.
+    #[cfg_attr(
+        feature = "keystore",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub per_hour: Option<U256>,
+    /// Largest sum of all payments.
+    #[cfg_attr(
+        feature = "keystore",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub total: Option<U256>,
+}
+
+impl SpendLimit {
+    /// Creates a limit without caps.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caps single payments.
+    pub fn with_per_request(mut self, amount: U256) -> Self {
+        self.per_request = Some(amount);
+        self
+    }
+
+    /// Caps the sum of payments within any rolling hour.
+    pub fn with_per_hour(mut self, amount: U256) -> Self {
+        self.per_hour = Some(amount);
+        self
+    }
+
+    /// Caps the sum of all payments.
+    pub fn with_total(mut self, amount: U256) -> Self {
+        self.total = Some(amount);
+        self
+    }
+}
+
+/// Payments made with one token.
+#[derive(Debug, Default)]
+struct TokenSpend {
+    total: U256,
+    last_hour: VecDeque<(Instant, U256)>,
+}
+
+impl TokenSpend {
+    fn hourly(&mut self, now: Instant) -> U256 {
+        while let Some((at, _)) = self.last_hour.front() {
+            if now.duration_since(*at) < HOUR {
+                break;
+            }
+            self.last_hour.pop_front();
+        }
+        self.last_hour.iter().map(|(_, amount)| *amount).sum()
+    }
+}
+
+/// Spend limits per token, shared by every request of the clients it is attached to.
+#[derive(Debug, Default)]
+pub struct Budget {
+    limits: HashMap<(ChainId, String), SpendLimit>,
+    spent: Mutex<HashMap<(ChainId, String), TokenSpend>>,
+}
+
+impl Budget {
+    /// Creates a budget without limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits spending of the token `asset` on `chain_id`. Addresses compare
+    /// case-insensitively.
+    pub fn with_limit(mut self, chain_id: ChainId, asset: &str, limit: SpendLimit) -> Self {
+        self.limits.insert(token_key(&chain_id, asset), limit);
+        self
+    }
+
+    /// Limit of `asset` on `chain_id`, if any.
+    pub fn limit(&self, chain_id: &ChainId, asset: &str) -> Option<&SpendLimit> {
+        self.limits.get(&token_key(chain_id, asset))
+    }
+
+    /// Total amount of `asset` on `chain_id` spent through this budget.
+    pub fn spent(&self, chain_id: &ChainId, asset: &str) -> U256 {
+        let spent = self.spent.lock().expect("budget lock poisoned");
+        spent
+            .get(&token_key(chain_id, asset))
+            .map(|spend| spend.total)
+            .unwrap_or_default()
+    }
+
+    /// Records the payment of `candidate`, or fails if it would exceed a limit.
+    pub fn charge(&self, candidate: &PaymentCandidate) -> Result<(), X402Error> {
+        self.charge_at(candidate, Instant::now())
+    }
+
+    fn charge_at(&self, candidate: &PaymentCandidate, now: Instant) -> Result<(), X402Error> {
+        let key = token_key(&candidate.chain_id, &candidate.asset);
+        let Some(limit) = self.limits.get(&key) else {
+            return Ok(());
+        };
+        let amount = candidate.amount;
+        let mut spent = self.spent.lock().expect("budget lock poisoned");
+        let spend = spent.entry(key).or_default();
+        let hourly = spend.hourly(now);
+        let caps = [
+            (SpendWindow::Request, limit.per_request, U256::ZERO),
+            (SpendWindow::Hour, limit.per_hour, hourly),
+            (SpendWindow::Total, limit.total, spend.total),
+        ];
+        for (window, cap, used) in caps {
+            let Some(cap) = cap else { continue };
+            let remaining = cap.saturating_sub(used);
+            if amount > remaining {
+                return Err(X402Error::BudgetExceeded(Box::new(BudgetExceeded {
+                    window,
+                    chain_id: candidate.chain_id.clone(),
+                    asset: candidate.asset.clone(),
+                    amount,
+                    remaining,
+                })));
+            }
+        }
+        spend.total += amount;
+        spend.last_hour.push_back((now, amount));
+        Ok(())
+    }
+}
+
+fn token_key(chain_id: &ChainId, asset: &str) -> (ChainId, String) {
+    (chain_id.clone(), asset.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use x402_types::scheme::client::PaymentCandidateSigner;
+
+    struct NoopSigner;
+
+    #[async_trait]
+    impl PaymentCandidateSigner for NoopSigner {
+        async fn sign_payment(&self) -> Result<String, X402Error> {
+            Ok(String::new())
+        }
+    }
+
+    fn candidate(asset: &str, amount: u64) -> PaymentCandidate {
+        PaymentCandidate {
+            chain_id: "eip155:42793".parse().unwrap(),
+            asset: asset.to_string(),
+            amount: U256::from(amount),
+            scheme: "exact".to_string(),
+            x402_version: 2,
+            pay_to: "0xBBB0000000000000000000000000000000000002".to_string(),
+            transfer_method: None,
+            signer: Box::new(NoopSigner),
+        }
+    }
+
+    fn exceeded(result: Result<(), X402Error>) -> (SpendWindow, u64) {
+        match result {
+            Err(X402Error::BudgetExceeded(e)) => (e.window, e.remaining.to::<u64>()),
+            other => panic!("expected BudgetExceeded, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_budget_limits() {
+        let token = "0xAAA0000000000000000000000000000000000001";
+        let budget = Budget::new().with_limit(
+            "eip155:42793".parse().unwrap(),
+            &token.to_lowercase(),
+            SpendLimit::new()
+                .with_per_request(U256::from(50))
+                .with_per_hour(U256::from(100))
+                .with_total(U256::from(150)),
+        );
+        let start = Instant::now();
+
+        assert_eq!(
+            exceeded(budget.charge_at(&candidate(token, 60), start)),
+            (SpendWindow::Request, 50)
+        );
+        budget.charge_at(&candidate(token, 50), start).unwrap();
+        budget.charge_at(&candidate(token, 40), start).unwrap();
+        assert_eq!(
+            exceeded(budget.charge_at(&candidate(token, 20), start)),
+            (SpendWindow::Hour, 10)
+        );
+
+        let later = start + HOUR;
+        budget.charge_at(&candidate(token, 50), later).unwrap();
+        assert_eq!(
+            exceeded(budget.charge_at(&candidate(token, 20), later)),
+            (SpendWindow::Total, 10)
+        );
+        let chain_id = "eip155:42793".parse().unwrap();
+        assert_eq!(budget.spent(&chain_id, token), U256::from(140));
+
+        // Tokens without a limit are not tracked.
+        let other = "0xCCC0000000000000000000000000000000000003";
+        budget.charge_at(&candidate(other, 1000), later).unwrap();
+        assert_eq!(budget.spent(&chain_id, other), U256::ZERO);
+    }
+}
@@ -3,17 +3,25 @@
 //! This module provides the [`X402Client`] which orchestrates scheme clients
 //! and payment selection for automatic payment handling.
 
-use http::{Extensions, HeaderMap, StatusCode};
-use reqwest::{Request, Response};
+use http::{Extensions, HeaderMap, HeaderValue, StatusCode};
+use reqwest::{Request, Response, ResponseBuilderExt};
 use reqwest_middleware as rqm;
 use std::sync::Arc;
+use tokio::sync::OwnedMutexGuard;
 use x402_types::proto;
+use x402_types::proto::transport::{PAYMENT_QUERY_PARAM, PaymentEnvelope, PaymentTransport};
 use x402_types::proto::{v1, v2};
 use x402_types::scheme::client::{
-    FirstMatch, PaymentCandidate, PaymentSelector, X402Error, X402SchemeClient,
+    FirstMatch, PaymentCandidate, PaymentPreferences, PaymentSelector, X402Error, X402SchemeClient,
 };
 use x402_types::util::Base64Bytes;
 
+use crate::balance::{self, BalanceSource};
+use crate::budget::Budget;
+use crate::coalesce::{InFlightPayments, SigningLocks};
+use crate::presign::PresignedPayments;
+use crate::retry::RetryPolicy;
+
 #[cfg(feature = "telemetry")]
 use tracing::{debug, info, instrument, trace};
 
@@ -53,6 +61,13 @@ use tracing::{debug, info, instrument, trace};
 pub struct X402Client<TSelector> {
     schemes: ClientSchemes,
     selector: TSelector,
+    presigned: Option<Arc<PresignedPayments>>,
+    budget: Option<Arc<Budget>>,
+    balances: Option<Arc<dyn BalanceSource>>,
+    transport: PaymentTransport,
+    retry_policy: Option<RetryPolicy>,
+    signing_locks: SigningLocks,
+    in_flight: Option<InFlightPayments>,
 }
 
 impl X402Client<FirstMatch> {
@@ -70,6 +85,13 @@ impl Default for X402Client<FirstMatch> {
         Self {
             schemes: ClientSchemes::default(),
             selector: FirstMatch,
+            presigned: None,
+            budget: None,
+            balances: None,
+            transport: PaymentTransport::Header,
+            retry_policy: None,
+            signing_locks: SigningLocks::default(),
+            in_flight: None,
         }
     }
 }
@@ -127,6 +149,138 @@ impl<TSelector> X402Client<TSelector> {
         X402Client {
             selector,
             schemes: self.schemes,
+            presigned: self.presigned,
+            budget: self.budget,
+            balances: self.balances,
+            transport: self.transport,
+            retry_policy: self.retry_policy,
+            signing_locks: self.signing_locks,
+            in_flight: self.in_flight,
+        }
+    }
+
+    /// Selects payment options by ordered preferences instead of taking the first match.
+    ///
+    /// Useful with servers that accept several schemes, chains or tokens: the
+    /// choice then follows the caller's preferences rather than the order of the
+    /// server's `accepts` list.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use x402_reqwest::X402Client;
+    /// use x402_types::scheme::client::PaymentPreferences;
+    ///
+    /// let client = X402Client::new().with_preferences(
+    ///     PaymentPreferences::new()
+    ///         .prefer_transfer_methods(["permit2", "eip3009"])
+    ///         .prefer_chains(vec!["eip155:42793".parse().unwrap()]),
+    /// );
+    /// ```
+    pub fn with_preferences(
+        self,
+        preferences: PaymentPreferences,
+    ) -> X402Client<PaymentPreferences> {
+        self.with_selector(preferences)
+    }
+
+    /// Attaches a store of pre-signed payments.
+    ///
+    /// Before sending a request, the middleware looks up a usable payment for the
+    /// request URL in the store and attaches it, skipping both the 402 round-trip
+    /// and the signing step. If the server still answers with 402, the regular
+    /// payment flow runs.
+    ///
+    /// See [`X402Client::presign`] for filling the store.
+    pub fn with_presigned(mut self, store: Arc<PresignedPayments>) -> Self {
+        self.presigned = Some(store);
+        self
+    }
+
+    /// Attaches spend limits.
+    ///
+    /// Every payment is charged to the budget before it is signed. A payment that
+    /// would exceed a limit fails with [`X402Error::BudgetExceeded`]. See the
+    /// [`budget`](crate::budget) module.
+    pub fn with_budget(mut self, budget: Arc<Budget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Attaches the payer's token balances.
+    ///
+    /// Payment options whose token balance is known to be below their amount are
+    /// dropped before selection, so with servers accepting several tokens the
+    /// payment goes out in a token the payer holds. See the
+    /// [`balance`](crate::balance) module.
+    pub fn with_balances(mut self, balances: Arc<dyn BalanceSource>) -> Self {
+        self.balances = Some(balances);
+        self
+    }
+
+    /// Sets the preferred transport for payment payloads.
+    ///
+    /// The payment is sent in the query parameter or in a JSON body envelope when the
+    /// server lists that transport in its challenge, and in the payment header
+    /// otherwise. The body transport also falls back to the header if the request
+    /// body is not JSON. Defaults to [`PaymentTransport::Header`].
+    pub fn with_transport(mut self, transport: PaymentTransport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Retries payments the server rejects as not yet valid or expired.
+    ///
+    /// Such a rejection is answered with a new payment, signed against the new
+    /// challenge, up to the policy's number of retries. Without a policy the
+    /// rejection is returned to the caller. See the [`retry`](crate::retry) module.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Shares one payment between concurrent requests answered with the same challenge.
+    ///
+    /// The first of the requests signs the payment and the others send it too, if
+    /// the server accepts it for them; a request whose shared payment is rejected
+    /// signs its own. See the [`coalesce`](crate::coalesce) module.
+    pub fn with_coalescing(mut self) -> Self {
+        self.in_flight = Some(InFlightPayments::default());
+        self
+    }
+
+    /// Returns the registered scheme clients.
+    pub(crate) fn schemes(&self) -> &ClientSchemes {
+        &self.schemes
+    }
+
+    /// Returns the configured payment selector.
+    pub(crate) fn selector(&self) -> &TSelector {
+        &self.selector
+    }
+
+    /// Returns the payment options of the challenge the payer can handle and afford.
+    pub(crate) async fn candidates(
+        &self,
+        payment_required: &proto::PaymentRequired,
+    ) -> Vec<PaymentCandidate> {
+        let candidates = self.schemes.candidates(payment_required);
+        match &self.balances {
+            Some(balances) => balance::affordable(candidates, balances.as_ref()).await,
+            None => candidates,
+        }
+    }
+
+    /// Waits until no other payment in the token of `candidate` is being signed.
+    pub(crate) async fn lock_signing(&self, candidate: &PaymentCandidate) -> OwnedMutexGuard<()> {
+        self.signing_locks.acquire(candidate).await
+    }
+
+    /// Charges a payment to the budget, if one is attached.
+    pub(crate) fn charge(&self, candidate: &PaymentCandidate) -> Result<(), X402Error> {
+        match &self.budget {
+            Some(budget) => budget.charge(candidate),
+            None => Ok(()),
         }
     }
 }
@@ -154,6 +308,7 @@ where
     /// Returns [`X402Error::ParseError`] if the response cannot be parsed.
     /// Returns [`X402Error::NoMatchingPaymentOption`] if no registered scheme
     /// can handle the payment requirements.
+    /// Returns [`X402Error::BudgetExceeded`] if the payment would exceed a spend limit.
     #[cfg_attr(
         feature = "telemetry",
         instrument(name = "x402.reqwest.make_payment_headers", skip_all, err)
@@ -162,7 +317,30 @@ where
         let payment_required = parse_payment_required(res)
             .await
             .ok_or(X402Error::ParseError("Invalid 402 response".to_string()))?;
-        let candidates = self.schemes.candidates(&payment_required);
+        let signed_payload = self.sign_payment(&payment_required).await?;
+        let header_name = payment_header_name(&payment_required);
+        let headers = {
+            let mut headers = HeaderMap::new();
+            headers.insert(header_name, signed_payload.parse().unwrap());
+            headers
+        };
+
+        Ok(headers)
+    }
+
+    /// Selects a payment option of the challenge and signs it.
+    ///
+    /// Returns the encoded payment payload. Balances, spend limits and signing locks
+    /// apply as for HTTP requests, so the client can pay over other transports too.
+    #[cfg_attr(
+        feature = "telemetry",
+        instrument(name = "x402.reqwest.sign_payment", skip_all, err)
+    )]
+    pub async fn sign_payment(
+        &self,
+        payment_required: &proto::PaymentRequired,
+    ) -> Result<String, X402Error> {
+        let candidates = self.candidates(payment_required).await;
 
         // Select the best candidate
         let selected = self
@@ -177,18 +355,80 @@ where
             "Selected payment scheme"
         );
 
-        let signed_payload = selected.sign().await?;
-        let header_name = match &payment_required {
-            proto::PaymentRequired::V1(_) => "X-Payment",
-            proto::PaymentRequired::V2(_) => "Payment-Signature",
-        };
-        let headers = {
-            let mut headers = HeaderMap::new();
-            headers.insert(header_name, signed_payload.parse().unwrap());
-            headers
-        };
+        let _signing = self.lock_signing(selected).await;
+        self.charge(selected)?;
+        selected.sign().await
+    }
+}
 
-        Ok(headers)
+/// Returns the transports accepted by the server for the given challenge.
+fn challenge_transports(payment_required: &proto::PaymentRequired) -> &[PaymentTransport] {
+    match payment_required {
+        proto::PaymentRequired::V1(payment_required) => &payment_required.transports,
+        proto::PaymentRequired::V2(payment_required) => &payment_required.transports,
+    }
+}
+
+/// Attaches a signed payment to the request using the preferred transport, if the
+/// server accepts it, or the payment header otherwise.
+fn attach_payment(
+    req: &mut Request,
+    payment_required: &proto::PaymentRequired,
+    signed_payload: String,
+    preferred: PaymentTransport,
+) -> Result<(), X402Error> {
+    let transport = if preferred.is_accepted_by(challenge_transports(payment_required)) {
+        preferred
+    } else {
+        PaymentTransport::Header
+    };
+
+    match transport {
+        PaymentTransport::Query => {
+            req.url_mut()
+                .query_pairs_mut()
+                .append_pair(PAYMENT_QUERY_PARAM, &signed_payload);
+            return Ok(());
+        }
+        PaymentTransport::Body => {
+            let original = match req.body().map(|body| body.as_bytes()) {
+                None => Some(serde_json::Value::Null),
+                Some(Some([])) => Some(serde_json::Value::Null),
+                Some(Some(bytes)) => serde_json::from_slice(bytes).ok(),
+                // Streaming bodies can not be wrapped
+                Some(None) => None,
+            };
+            if let Some(original) = original {
+                let envelope = PaymentEnvelope {
+                    x402_payment: signed_payload,
+                    body: original,
+                };
+                let bytes = serde_json::to_vec(&envelope)
+                    .map_err(|e| X402Error::SigningError(format!("{e}")))?;
+                req.headers_mut().remove(http::header::CONTENT_LENGTH);
+                req.headers_mut().insert(
+                    http::header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/json"),
+                );
+                *req.body_mut() = Some(bytes.into());
+                return Ok(());
+            }
+        }
+        PaymentTransport::Header => {}
+    }
+
+    let header_value = HeaderValue::from_str(&signed_payload)
+        .map_err(|e| X402Error::SigningError(format!("{e}")))?;
+    req.headers_mut()
+        .insert(payment_header_name(payment_required), header_value);
+    Ok(())
+}
+
+/// Returns the request header name used to send a payment for the given protocol version.
+pub(crate) fn payment_header_name(payment_required: &proto::PaymentRequired) -> &'static str {
+    match payment_required {
+        proto::PaymentRequired::V1(_) => "X-Payment",
+        proto::PaymentRequired::V2(_) => "Payment-Signature",
     }
 }
 
@@ -236,18 +476,34 @@ where
     /// When a 402 response is received, this middleware:
     /// 1. Extracts payment requirements from the response
     /// 2. Signs a payment using registered scheme clients
-    /// 3. Retries the request with the payment header
+    /// 3. Retries the request with the payment, in the header or the preferred transport
+    /// 4. With a [`RetryPolicy`], pays again if the payment is rejected for its timing
+    ///
+    /// With [`X402Client::with_coalescing`], step 2 reuses the payment of a concurrent
+    /// request answered with the same challenge, and signs one only if the server
+    /// rejects it.
     #[cfg_attr(
         feature = "telemetry",
         instrument(name = "x402.reqwest.handle", skip_all, err)
     )]
     async fn handle(
         &self,
-        req: Request,
+        mut req: Request,
         extensions: &mut Extensions,
         next: rqm::Next<'_>,
     ) -> rqm::Result<Response> {
         let retry_req = req.try_clone();
+        let url = req.url().to_string();
+        if let Some(presigned) = self
+            .presigned
+            .as_ref()
+            .and_then(|store| store.take(req.url().as_str()))
+        {
+            #[cfg(feature = "telemetry")]
+            debug!(url = ?req.url(), "Attaching pre-signed payment");
+            req.headers_mut()
+                .insert(presigned.header_name, presigned.header_value);
+        }
         let res = run_next(next.clone(), req, extensions).await?;
 
         if res.status() != StatusCode::PAYMENT_REQUIRED {
@@ -259,24 +515,134 @@ where
         #[cfg(feature = "telemetry")]
         info!(url = ?res.url(), "Received 402 Payment Required, processing payment");
 
-        let headers = self
-            .make_payment_headers(res)
-            .await
+        let mut payment_required = parse_payment_required(res).await.ok_or_else(|| {
+            rqm::Error::Middleware(X402Error::ParseError("Invalid 402 response".to_string()).into())
+        })?;
+        let mut slot = self
+            .in_flight
+            .as_ref()
+            .map(|in_flight| in_flight.join(&url, &payment_required));
+        let (mut signed_payload, mut shared) = match &mut slot {
+            Some(slot) => slot.payment(|| self.sign_payment(&payment_required)).await,
+            None => self
+                .sign_payment(&payment_required)
+                .await
+                .map(|payload| (payload, false)),
+        }
+        .map_err(|e| rqm::Error::Middleware(e.into()))?;
+        let mut retries = 0;
+        loop {
+            // Retry with payment
+            let mut retry =
+                retry_req
+                    .as_ref()
+                    .and_then(Request::try_clone)
+                    .ok_or(rqm::Error::Middleware(
+                        X402Error::RequestNotCloneable.into(),
+                    ))?;
+            attach_payment(
+                &mut retry,
+                &payment_required,
+                signed_payload,
+                self.transport,
+            )
             .map_err(|e| rqm::Error::Middleware(e.into()))?;
 
-        // Retry with payment
-        let mut retry = retry_req.ok_or(rqm::Error::Middleware(
-            X402Error::RequestNotCloneable.into(),
-        ))?;
-        retry.headers_mut().extend(headers);
+            #[cfg(feature = "telemetry")]
+            trace!(url = ?retry.url(), "Retrying request with payment");
 
-        #[cfg(feature = "telemetry")]
-        trace!(url = ?retry.url(), "Retrying request with payment headers");
+            let res = run_next(next.clone(), retry, extensions).await?;
+            if shared && res.status() == StatusCode::PAYMENT_REQUIRED {
+                let (res, rejection) = read_payment_required(res).await?;
+                let Some(rejection) = rejection else {
+                    return Ok(res);
+                };
+                #[cfg(feature = "telemetry")]
+                debug!("Shared payment rejected, paying on its own");
+                payment_required = rejection;
+                signed_payload = self
+                    .sign_payment(&payment_required)
+                    .await
+                    .map_err(|e| rqm::Error::Middleware(e.into()))?;
+                shared = false;
+                continue;
+            }
+            let policy = match self.retry_policy {
+                Some(policy) if retries < policy.max_retries => policy,
+                _ => return Ok(res),
+            };
+            if res.status() != StatusCode::PAYMENT_REQUIRED {
+                return Ok(res);
+            }
+            let (res, rejection) = read_payment_required(res).await?;
+            match rejection {
+                Some(rejection) if policy.is_retryable(&rejection) => {
+                    #[cfg(feature = "telemetry")]
+                    info!(
+                        error = ?rejection_error(&rejection),
+                        "Payment rejected for its timing, paying again"
+                    );
+                    payment_required = rejection;
+                    signed_payload = self
+                        .sign_payment(&payment_required)
+                        .await
+                        .map_err(|e| rqm::Error::Middleware(e.into()))?;
+                    retries += 1;
+                }
+                _ => return Ok(res),
+            }
+        }
+    }
+}
 
-        run_next(next, retry, extensions).await
+/// Returns the server's error message of a challenge.
+#[cfg(feature = "telemetry")]
+fn rejection_error(payment_required: &proto::PaymentRequired) -> Option<&str> {
+    match payment_required {
+        proto::PaymentRequired::V1(payment_required) => payment_required.error.as_deref(),
+        proto::PaymentRequired::V2(payment_required) => payment_required.error.as_deref(),
     }
 }
 
+/// Parses a 402 response like [`parse_payment_required`], but also returns the
+/// response, with its body buffered, to hand to the caller if it is not retried.
+async fn read_payment_required(
+    response: Response,
+) -> rqm::Result<(Response, Option<proto::PaymentRequired>)> {
+    let status = response.status();
+    let version = response.version();
+    let url = response.url().clone();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?;
+    let payment_required = decode_v2_header(&headers)
+        .map(proto::PaymentRequired::V2)
+        .or_else(|| decode_v1_body(&body).map(proto::PaymentRequired::V1));
+    let mut builder = http::Response::builder()
+        .status(status)
+        .version(version)
+        .url(url);
+    if let Some(builder_headers) = builder.headers_mut() {
+        *builder_headers = headers;
+    }
+    let response = builder
+        .body(body)
+        .map_err(|e| rqm::Error::Middleware(e.into()))?;
+    Ok((Response::from(response), payment_required))
+}
+
+/// Decodes a V2 challenge from the `Payment-Required` header.
+fn decode_v2_header(headers: &HeaderMap) -> Option<v2::PaymentRequired> {
+    headers
+        .get("Payment-Required")
+        .and_then(|h| Base64Bytes::from(h.as_bytes()).decode().ok())
+        .and_then(|b| serde_json::from_slice::<v2::PaymentRequired>(&b).ok())
+}
+
+/// Decodes a V1 challenge from a response body.
+fn decode_v1_body(body: &[u8]) -> Option<v1::PaymentRequired> {
+    serde_json::from_slice::<v1::PaymentRequired>(body).ok()
+}
+
 /// Parses a 402 Payment Required response into a [`proto::PaymentRequired`].
 ///
 /// Supports both V1 (JSON body) and V2 (base64-encoded header) formats.
@@ -286,11 +652,7 @@ where
 )]
 pub async fn parse_payment_required(response: Response) -> Option<proto::PaymentRequired> {
     // Try V2 format first (header-based)
-    let headers = response.headers();
-    let v2_payment_required = headers
-        .get("Payment-Required")
-        .and_then(|h| Base64Bytes::from(h.as_bytes()).decode().ok())
-        .and_then(|b| serde_json::from_slice::<v2::PaymentRequired>(&b).ok());
+    let v2_payment_required = decode_v2_header(response.headers());
     if let Some(v2_payment_required) = v2_payment_required {
         #[cfg(feature = "telemetry")]
         debug!("Parsed V2 payment required from header");
@@ -298,11 +660,7 @@ pub async fn parse_payment_required(response: Response) -> Option<proto::Payment
     }
 
     // Fall back to V1 format (body-based)
-    let v1_payment_required = response
-        .bytes()
-        .await
-        .ok()
-        .and_then(|b| serde_json::from_slice::<v1::PaymentRequired>(&b).ok());
+    let v1_payment_required = response.bytes().await.ok().and_then(|b| decode_v1_body(&b));
     if let Some(v1_payment_required) = v1_payment_required {
         #[cfg(feature = "telemetry")]
         debug!("Parsed V1 payment required from body");
@@ -0,0 +1,193 @@
+//! Payments for concurrent requests.
+//!
+//! Agents often send many requests at once, and several of them can run into a
+//! `402 Payment Required` at the same time. Two safeguards keep such bursts from
+//! signing conflicting or duplicate payments:
+//!
+//! - Signing is serialized per chain and token. From charging the budget until the
+//!   payment is signed, the middleware holds a lock for the payment's token, so
+//!   scheme clients deriving nonces from their own state, such as the tabs of the
+//!   deferred scheme, never hand one nonce to two payments. Payments in different
+//!   tokens are still signed in parallel.
+//! - With [`X402Client::with_coalescing`], concurrent requests to the same URL that
+//!   are answered with the same challenge share one payment: the first request
+//!   signs it, the others wait for it and send it too. Whether one payment pays for
+//!   several requests is up to the server. A request whose shared payment is
+//!   rejected with another 402 signs a payment of its own and is sent again. A
+//!   shared payment is charged to the budget once.
+//!
+//! A payment is shared until the request that signed it has its response, so
+//! requests running into the challenge later sign their own.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use x402_reqwest::X402Client;
+//!
+//! let client = X402Client::new().with_coalescing();
+//! ```
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Mutex as AsyncMutex, OnceCell, OwnedMutexGuard};
+use x402_types::chain::ChainId;
+use x402_types::proto;
+use x402_types::scheme::client::{PaymentCandidate, X402Error};
+
+#[cfg(doc)]
+use crate::X402Client;
+
+/// A chain and token, the asset lowercased.
+type TokenKey = (ChainId, String);
+
+/// Locks serializing the signing of payments, by chain and token.
+#[derive(Debug, Default)]
+pub(crate) struct SigningLocks {
+    locks: Mutex<HashMap<TokenKey, Arc<AsyncMutex<()>>>>,
+}
+
+impl SigningLocks {
+    /// Waits until no other payment in the token of `candidate` is being signed.
+    pub(crate) async fn acquire(&self, candidate: &PaymentCandidate) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().expect("signing locks poisoned");
+            let key = (candidate.chain_id.clone(), candidate.asset.to_lowercase());
+            locks.entry(key).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+}
+
+/// Payments shared between concurrent requests, by URL and challenge.
+#[derive(Debug, Default)]
+pub(crate) struct InFlightPayments {
+    payments: Mutex<HashMap<String, Arc<OnceCell<String>>>>,
+}
+
+impl InFlightPayments {
+    /// Joins the requests to `url` answered with `payment_required`.
+    pub(crate) fn join(&self, url: &str, payment_required: &proto::PaymentRequired) -> Slot<'_> {
+        let challenge = match payment_required {
+            proto::PaymentRequired::V1(payment_required) => serde_json::to_string(payment_required),
+            proto::PaymentRequired::V2(payment_required) => serde_json::to_string(payment_required),
+        }
+        .unwrap_or_default();
+        let key = format!("{url}\n{challenge}");
+        let payment = self
+            .payments
+            .lock()
+            .expect("in-flight payments poisoned")
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        Slot {
+            payments: self,
+            key,
+            payment,
+            signed: false,
+        }
+    }
+}
+
+/// A request's share of the payment for a challenge.
+///
+/// The payment stops being shared when the slot of the request that signed it is
+/// dropped.
+pub(crate) struct Slot<'a> {
+    payments: &'a InFlightPayments,
+    key: String,
+    payment: Arc<OnceCell<String>>,
+    signed: bool,
+}
+
+impl Slot<'_> {
+    /// Returns the payment for the challenge, and whether another request signed it.
+    ///
+    /// The payment is signed with `sign` unless another request has signed it or is
+    /// signing it, in which case this waits for it.
+    pub(crate) async fn payment<F, Fut>(&mut self, sign: F) -> Result<(String, bool), X402Error>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<String, X402Error>>,
+    {
+        let mut signed = false;
+        let payment = self
+            .payment
+            .get_or_try_init(|| {
+                signed = true;
+                sign()
+            })
+            .await?
+            .clone();
+        self.signed = signed;
+        Ok((payment, !signed))
+    }
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        if !self.signed {
+            return;
+        }
+        let mut payments = self
+            .payments
+            .payments
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if payments
+            .get(&self.key)
+            .is_some_and(|payment| Arc::ptr_eq(payment, &self.payment))
+        {
+            payments.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use x402_types::proto::v1;
+
+    fn challenge() -> proto::PaymentRequired {
+        proto::PaymentRequired::V1(v1::PaymentRequired {
+            x402_version: v1::X402Version1,
+            accepts: vec![],
+            error: None,
+            transports: vec![],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_share_one_payment() {
+        let in_flight = InFlightPayments::default();
+        let signatures = AtomicUsize::new(0);
+        let sign = || async {
+            let n = signatures.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            Ok(format!("payment-{n}"))
+        };
+
+        let mut first = in_flight.join("https://api.example.com/data", &challenge());
+        let mut second = in_flight.join("https://api.example.com/data", &challenge());
+        let mut other = in_flight.join("https://api.example.com/other", &challenge());
+        let (first_payment, second_payment, other_payment) = tokio::join!(
+            first.payment(sign),
+            second.payment(sign),
+            other.payment(sign)
+        );
+        let (first_payment, first_shared) = first_payment.unwrap();
+        assert_eq!(second_payment.unwrap(), (first_payment.clone(), true));
+        assert!(!first_shared);
+        assert_ne!(other_payment.unwrap().0, first_payment);
+        assert_eq!(signatures.load(Ordering::SeqCst), 2);
+
+        // Once the signing request is done, the next one signs a payment of its own.
+        drop((first, second, other));
+        let mut later = in_flight.join("https://api.example.com/data", &challenge());
+        let (later_payment, later_shared) = later.payment(sign).await.unwrap();
+        assert!(!later_shared);
+        assert_ne!(later_payment, first_payment);
+    }
+}
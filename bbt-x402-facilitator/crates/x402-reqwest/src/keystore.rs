@@ -0,0 +1,438 @@
+//! Encrypted storage for agent signing keys.
+//!
+//! Long-running agents otherwise keep raw hex keys in environment variables, where
+//! they end up in process listings, crash dumps and CI logs. A [`Keystore`] is a
+//! JSON file holding several keys under aliases, each encrypted with a password:
+//! the key is derived with PBKDF2-HMAC-SHA256 from the password and a random salt,
+//! and the private key sealed with AES-256-GCM, bound to its address. Only the
+//! addresses and spend limits are stored in the clear.
+//!
+//! Every alias can carry its own spend limits, returned as a [`Budget`] when the
+//! key is unlocked, so an agent's caps travel with its key.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "keys": {
+//!     "research-agent": {
+//!       "address": "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf",
+//!       "kdf": { "function": "pbkdf2-hmac-sha256", "iterations": 600000, "salt": "0x…" },
+//!       "cipher": { "function": "aes-256-gcm", "nonce": "0x…" },
+//!       "ciphertext": "0x…",
+//!       "limits": [
+//!         { "chainId": "eip155:42793", "asset": "0x7EfE…", "perHour": "0x2faf080" }
+//!       ]
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Passwords are passed to [`Keystore::unlock`], or read from the environment by
+//! [`Keystore::unlock_from_env`]: `X402_KEYSTORE_PASSWORD_<ALIAS>` (the alias in
+//! upper case, `-` replaced by `_`), then `X402_KEYSTORE_PASSWORD`.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use x402_reqwest::keystore::Keystore;
+//! use x402_reqwest::X402Client;
+//!
+//! let keystore = Keystore::open("agent-keys.json")?;
+//! let key = keystore.unlock_from_env("research-agent")?;
+//! let client = X402Client::new()
+//!     .register(V2Eip155ExactClient::new(Arc::new(key.signer)))
+//!     .with_budget(Arc::new(key.budget));
+//! ```
+
+use alloy_primitives::{Address, B256, Bytes};
+use alloy_signer_local::PrivateKeySigner;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use x402_types::chain::ChainId;
+
+use crate::budget::{Budget, SpendLimit};
+
+/// Environment variable holding the password of every alias without its own variable.
+pub const KEYSTORE_PASSWORD_ENV: &str = "X402_KEYSTORE_PASSWORD";
+
+/// Default PBKDF2 iteration count, per the OWASP recommendation for HMAC-SHA256.
+pub const DEFAULT_KDF_ITERATIONS: u32 = 600_000;
+
+const KEYSTORE_VERSION: u32 = 1;
+const KDF_FUNCTION: &str = "pbkdf2-hmac-sha256";
+const CIPHER_FUNCTION: &str = "aes-256-gcm";
+const SALT_LEN: usize = 16;
+
+/// Errors that can occur while reading, changing or unlocking a keystore.
+#[derive(Debug, thiserror::Error)]
+pub enum KeystoreError {
+    #[error("Keystore I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid keystore file: {0}")]
+    Format(#[from] serde_json::Error),
+    #[error("Unsupported keystore: {0}")]
+    Unsupported(String),
+    #[error("Invalid key alias {0:?}: use letters, digits, '-' and '_'")]
+    InvalidAlias(String),
+    #[error("Key alias {0:?} already exists")]
+    DuplicateAlias(String),
+    #[error("Unknown key alias {0:?}")]
+    UnknownAlias(String),
+    #[error("Wrong password for key alias {0:?}")]
+    WrongPassword(String),
+    #[error("No password for key alias {alias:?}: set {env} or {KEYSTORE_PASSWORD_ENV}")]
+    MissingPassword { alias: String, env: String },
+    #[error("Failed to generate randomness")]
+    Random,
+}
+
+/// A key decrypted from a [`Keystore`], with the spend limits of its alias.
+#[derive(Debug)]
+pub struct UnlockedKey {
+    pub signer: PrivateKeySigner,
+    pub budget: Budget,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeystoreFile {
+    version: u32,
+    #[serde(default)]
+    keys: BTreeMap<String, EncryptedKey>,
+}
+
+impl Default for KeystoreFile {
+    fn default() -> Self {
+        Self {
+            version: KEYSTORE_VERSION,
+            keys: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptedKey {
+    address: Address,
+    kdf: KdfParams,
+    cipher: CipherParams,
+    ciphertext: Bytes,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    limits: Vec<AliasLimit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KdfParams {
+    function: String,
+    iterations: u32,
+    salt: Bytes,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CipherParams {
+    function: String,
+    nonce: Bytes,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AliasLimit {
+    chain_id: ChainId,
+    asset: String,
+    #[serde(flatten)]
+    limit: SpendLimit,
+}
+
+/// Password-encrypted signing keys stored in a JSON file, by alias.
+///
+/// Changes are written to the file immediately, through a temporary file renamed
+/// over it. On Unix the file is only readable by its owner.
+#[derive(Debug)]
+pub struct Keystore {
+    path: PathBuf,
+    file: KeystoreFile,
+    iterations: u32,
+}
+
+impl Keystore {
+    /// Opens the keystore at `path`. A missing file is an empty keystore, created on
+    /// the first change.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, KeystoreError> {
+        let path = path.as_ref().to_path_buf();
+        let file = match fs::read(&path) {
+            Ok(content) => serde_json::from_slice::<KeystoreFile>(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => KeystoreFile::default(),
+            Err(e) => return Err(e.into()),
+        };
+        if file.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::Unsupported(format!(
+                "version {}",
+                file.version
+            )));
+        }
+        Ok(Self {
+            path,
+            file,
+            iterations: DEFAULT_KDF_ITERATIONS,
+        })
+    }
+
+    /// Sets the PBKDF2 iteration count for keys added from now on. Defaults to
+    /// [`DEFAULT_KDF_ITERATIONS`]; existing keys keep theirs.
+    pub fn with_kdf_iterations(mut self, iterations: NonZeroU32) -> Self {
+        self.iterations = iterations.get();
+        self
+    }
+
+    /// Aliases of the stored keys, in alphabetical order.
+    pub fn aliases(&self) -> impl Iterator<Item = &str> {
+        self.file.keys.keys().map(String::as_str)
+    }
+
+    /// Address of the key stored under `alias`, readable without the password.
+    pub fn address(&self, alias: &str) -> Option<Address> {
+        self.file.keys.get(alias).map(|key| key.address)
+    }
+
+    /// Encrypts `signer` with `password` and stores it under `alias`.
+    pub fn insert(
+        &mut self,
+        alias: &str,
+        signer: &PrivateKeySigner,
+        password: &str,
+    ) -> Result<(), KeystoreError> {
+        if alias.is_empty()
+            || !alias
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(KeystoreError::InvalidAlias(alias.to_string()));
+        }
+        if self.file.keys.contains_key(alias) {
+            return Err(KeystoreError::DuplicateAlias(alias.to_string()));
+        }
+
+        let rng = SystemRandom::new();
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        rng.fill(&mut salt).map_err(|_| KeystoreError::Random)?;
+        rng.fill(&mut nonce).map_err(|_| KeystoreError::Random)?;
+
+        let address = signer.address();
+        let cipher = cipher_key(password, &salt, self.iterations);
+        let mut ciphertext = signer.to_bytes().to_vec();
+        cipher
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(address.as_slice()),
+                &mut ciphertext,
+            )
+            .map_err(|_| KeystoreError::Random)?;
+
+        self.file.keys.insert(
+            alias.to_string(),
+            EncryptedKey {
+                address,
+                kdf: KdfParams {
+                    function: KDF_FUNCTION.to_string(),
+                    iterations: self.iterations,
+                    salt: Bytes::copy_from_slice(&salt),
+                },
+                cipher: CipherParams {
+                    function: CIPHER_FUNCTION.to_string(),
+                    nonce: Bytes::copy_from_slice(&nonce),
+                },
+                ciphertext: ciphertext.into(),
+                limits: Vec::new(),
+            },
+        );
+        self.persist()
+    }
+
+    /// Removes the key stored under `alias`. Returns whether it existed.
+    pub fn remove(&mut self, alias: &str) -> Result<bool, KeystoreError> {
+        if self.file.keys.remove(alias).is_none() {
+            return Ok(false);
+        }
+        self.persist()?;
+        Ok(true)
+    }
+
+    /// Limits spending of the token `asset` on `chain_id` with the key stored under
+    /// `alias`, replacing a previous limit of that token.
+    pub fn set_limit(
+        &mut self,
+        alias: &str,
+        chain_id: ChainId,
+        asset: &str,
+        limit: SpendLimit,
+    ) -> Result<(), KeystoreError> {
+        let key = self
+            .file
+            .keys
+            .get_mut(alias)
+            .ok_or_else(|| KeystoreError::UnknownAlias(alias.to_string()))?;
+        key.limits.retain(|existing| {
+            existing.chain_id != chain_id || !existing.asset.eq_ignore_ascii_case(asset)
+        });
+        key.limits.push(AliasLimit {
+            chain_id,
+            asset: asset.to_string(),
+            limit,
+        });
+        self.persist()
+    }
+
+    /// Decrypts the key stored under `alias` with `password`.
+    pub fn unlock(&self, alias: &str, password: &str) -> Result<UnlockedKey, KeystoreError> {
+        let key = self
+            .file
+            .keys
+            .get(alias)
+            .ok_or_else(|| KeystoreError::UnknownAlias(alias.to_string()))?;
+        if key.kdf.function != KDF_FUNCTION {
+            return Err(KeystoreError::Unsupported(key.kdf.function.clone()));
+        }
+        if key.cipher.function != CIPHER_FUNCTION {
+            return Err(KeystoreError::Unsupported(key.cipher.function.clone()));
+        }
+        let nonce = Nonce::try_assume_unique_for_key(&key.cipher.nonce)
+            .map_err(|_| KeystoreError::Unsupported("nonce length".to_string()))?;
+
+        let cipher = cipher_key(password, &key.kdf.salt, key.kdf.iterations);
+        let mut plaintext = key.ciphertext.to_vec();
+        let secret = cipher
+            .open_in_place(nonce, Aad::from(key.address.as_slice()), &mut plaintext)
+            .map_err(|_| KeystoreError::WrongPassword(alias.to_string()))?;
+        let signer = PrivateKeySigner::from_bytes(&B256::from_slice(secret))
+            .map_err(|_| KeystoreError::WrongPassword(alias.to_string()));
+        plaintext.fill(0);
+        let signer = signer?;
+
+        let budget = key.limits.iter().fold(Budget::new(), |budget, limit| {
+            budget.with_limit(limit.chain_id.clone(), &limit.asset, limit.limit.clone())
+        });
+        Ok(UnlockedKey { signer, budget })
+    }
+
+    /// Decrypts the key stored under `alias` with the password from the environment:
+    /// `X402_KEYSTORE_PASSWORD_<ALIAS>`, or `X402_KEYSTORE_PASSWORD` if unset.
+    pub fn unlock_from_env(&self, alias: &str) -> Result<UnlockedKey, KeystoreError> {
+        let alias_env = password_env(alias);
+        let password = std::env::var(&alias_env)
+            .or_else(|_| std::env::var(KEYSTORE_PASSWORD_ENV))
+            .map_err(|_| KeystoreError::MissingPassword {
+                alias: alias.to_string(),
+                env: alias_env,
+            })?;
+        self.unlock(alias, &password)
+    }
+
+    /// Writes the keystore to a temporary file and renames it over the keystore file.
+    fn persist(&self) -> Result<(), KeystoreError> {
+        let content = serde_json::to_vec_pretty(&self.file)?;
+        let tmp = self.path.with_extension("tmp");
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(&tmp)?.write_all(&content)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Name of the environment variable holding the password of `alias`.
+fn password_env(alias: &str) -> String {
+    format!(
+        "{KEYSTORE_PASSWORD_ENV}_{}",
+        alias.to_ascii_uppercase().replace('-', "_")
+    )
+}
+
+/// Derives the AES-256-GCM key from a password.
+fn cipher_key(password: &str, salt: &[u8], iterations: u32) -> LessSafeKey {
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(iterations).unwrap_or(NonZeroU32::MIN),
+        salt,
+        password.as_bytes(),
+        &mut key,
+    );
+    let cipher =
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &key).expect("AES-256-GCM key is 32 bytes"));
+    key.fill(0);
+    cipher
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+
+    #[test]
+    fn test_keystore_roundtrip() {
+        let path = std::env::temp_dir().join(format!("x402-keystore-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let chain_id: ChainId = "eip155:42793".parse().unwrap();
+        let asset = "0x7EfE4bdd11237610bcFca478937658bE39F8dfd6";
+        let signer = PrivateKeySigner::random();
+
+        let mut keystore = Keystore::open(&path)
+            .unwrap()
+            .with_kdf_iterations(NonZeroU32::new(1_000).unwrap());
+        keystore
+            .insert("research-agent", &signer, "hunter2")
+            .unwrap();
+        keystore
+            .set_limit(
+                "research-agent",
+                chain_id.clone(),
+                asset,
+                SpendLimit::new().with_total(U256::from(500)),
+            )
+            .unwrap();
+        assert!(matches!(
+            keystore.insert("research-agent", &signer, "hunter2"),
+            Err(KeystoreError::DuplicateAlias(_))
+        ));
+        assert!(matches!(
+            keystore.insert("bad alias", &signer, "hunter2"),
+            Err(KeystoreError::InvalidAlias(_))
+        ));
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains(&alloy_primitives::hex::encode(signer.to_bytes())));
+
+        let keystore = Keystore::open(&path).unwrap();
+        assert_eq!(
+            keystore.aliases().collect::<Vec<_>>(),
+            vec!["research-agent"]
+        );
+        assert_eq!(keystore.address("research-agent"), Some(signer.address()));
+        assert!(matches!(
+            keystore.unlock("research-agent", "wrong"),
+            Err(KeystoreError::WrongPassword(_))
+        ));
+        let key = keystore.unlock("research-agent", "hunter2").unwrap();
+        assert_eq!(key.signer.address(), signer.address());
+        assert_eq!(
+            key.budget.limit(&chain_id, &asset.to_lowercase()),
+            Some(&SpendLimit::new().with_total(U256::from(500)))
+        );
+        assert_eq!(
+            password_env("research-agent"),
+            "X402_KEYSTORE_PASSWORD_RESEARCH_AGENT"
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -49,10 +49,81 @@
 //! to choose the best option. By default, it uses [`FirstMatch`] which selects the first
 //! matching scheme. You can implement custom selection logic by providing your own selector.
 //!
-//! See [`X402Client::with_selector`] for custom payment selection.
+//! With servers that offer several schemes, chains or tokens, [`X402Client::with_preferences`]
+//! ranks the options by ordered preferences instead, e.g. Permit2 over ERC-3009 or
+//! one chain over another. See [`X402Client::with_selector`] for custom payment selection.
+//!
+//! Servers may accept several tokens for one resource. [`X402Client::with_balances`]
+//! drops the options in tokens the payer does not hold enough of before selection,
+//! see the [`balance`](crate::balance) module.
+//!
+//! ## Spend Limits
+//!
+//! [`X402Client::with_budget`] caps what the client pays per token: per request, per
+//! rolling hour and in total. See the [`budget`](crate::budget) module.
+//!
+//! ## Keystore
+//!
+//! With the `keystore` feature, agent keys live password-encrypted in a file under
+//! aliases, each with its own spend limits, instead of as raw hex in the environment.
+//! See the [`keystore`](crate::keystore) module.
+//!
+//! ## Pre-signed Payments
+//!
+//! Latency-sensitive clients can sign a batch of payments for a known resource ahead
+//! of time with [`X402Client::presign`] and attach them from a [`PresignedPayments`]
+//! store. See the [`presign`](crate::presign) module for details.
+//!
+//! ## Price Checks
+//!
+//! [`X402Client::probe`] fetches a resource's payment challenge without paying, so
+//! prices can be compared across providers before committing spend.
+//!
+//! ## Retries
+//!
+//! A payment the server rejects as not yet valid or expired, e.g. because of clock
+//! skew, is surfaced to the caller by default. [`X402Client::with_retry_policy`] signs
+//! a fresh payment and sends the request again instead. See the [`retry`](crate::retry)
+//! module.
+//!
+//! ## Concurrent Requests
+//!
+//! Payments in one token are signed one at a time, so concurrent requests never
+//! sign payments with the same nonce. [`X402Client::with_coalescing`] goes further
+//! and shares one payment between concurrent requests answered with the same
+//! challenge, where the server accepts it. See the [`coalesce`](crate::coalesce)
+//! module.
+//!
+//! ## Payment Transports
+//!
+//! Payments are sent in a request header by default. If proxies on the way strip
+//! large headers, [`X402Client::with_transport`] selects the query parameter or a
+//! JSON body envelope instead, used whenever the server's challenge allows it.
+//!
+//! ## Payment Receipts
+//!
+//! Resource servers report the settlement of a payment in a response header.
+//! [`PaymentReceiptExt::payment_receipt`] decodes it into a [`PaymentReceipt`] with
+//! the network, transaction hash and payer. See the [`receipt`](crate::receipt) module.
 
+pub mod balance;
+pub mod budget;
 mod builder;
 mod client;
+pub mod coalesce;
+#[cfg(feature = "keystore")]
+pub mod keystore;
+pub mod presign;
+pub mod probe;
+pub mod receipt;
+pub mod retry;
 
+pub use balance::{BalanceSource, RpcBalances, StaticBalances};
+pub use budget::{Budget, SpendLimit};
 pub use builder::*;
 pub use client::*;
+pub use presign::{PresignPlan, PresignedPayment, PresignedPayments};
+pub use probe::{PriceOption, PriceQuote};
+pub use receipt::{PaymentReceipt, PaymentReceiptError, PaymentReceiptExt};
+pub use retry::RetryPolicy;
+pub use x402_types::proto::transport::PaymentTransport;
@@ -0,0 +1,272 @@
+//! Ahead-of-time payment signing for latency-sensitive clients.
+//!
+//! Signing a payment authorization costs a signer round-trip (or a remote KMS call)
+//! on the request path. For resources whose price is known in advance, a batch of
+//! authorizations can be signed up front with [`X402Client::presign`] and kept in a
+//! [`PresignedPayments`] store. Once the store is attached with
+//! [`X402Client::with_presigned`], the middleware attaches a stored payment to
+//! outgoing requests for that resource without signing anything.
+//!
+//! Each authorization carries its own nonce and a staggered validity window, so the
+//! batch covers a span of time rather than expiring all at once.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use x402_reqwest::{PresignPlan, PresignedPayments, X402Client};
+//!
+//! let store = Arc::new(PresignedPayments::new());
+//! let x402_client = X402Client::new()
+//!     .register(V2Eip155ExactClient::new(signer))
+//!     .with_presigned(store.clone());
+//!
+//! // `payment_required` was obtained earlier, e.g. via `parse_payment_required`.
+//! let plan = PresignPlan::new(10)
+//!     .with_validity(Duration::from_secs(300))
+//!     .with_stagger(Duration::from_secs(60));
+//! let batch = x402_client.presign(&payment_required, &plan).await?;
+//! store.extend("https://api.example.com/protected", batch);
+//! ```
+
+use http::HeaderValue;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+use x402_types::proto;
+use x402_types::scheme::client::{PaymentSelector, ValidityWindow, X402Error};
+use x402_types::timestamp::UnixTimestamp;
+
+use crate::client::{X402Client, payment_header_name};
+
+/// Describes how a batch of authorizations is pre-signed.
+///
+/// Authorization `i` becomes valid at `start + i * stagger` and expires
+/// `validity` later.
+#[derive(Debug, Clone)]
+pub struct PresignPlan {
+    /// Number of authorizations to sign.
+    pub count: usize,
+    /// Length of each authorization's validity window.
+    pub validity: Duration,
+    /// Offset between the start of consecutive validity windows.
+    pub stagger: Duration,
+    /// Start of the first validity window. Defaults to the time of signing.
+    pub start: Option<UnixTimestamp>,
+}
+
+impl PresignPlan {
+    /// Creates a plan for `count` authorizations with 5-minute windows, each
+    /// starting one minute after the previous one.
+    pub fn new(count: usize) -> Self {
+        Self {
+            count,
+            validity: Duration::from_secs(300),
+            stagger: Duration::from_secs(60),
+            start: None,
+        }
+    }
+
+    /// Sets the length of each validity window.
+    pub fn with_validity(mut self, validity: Duration) -> Self {
+        self.validity = validity;
+        self
+    }
+
+    /// Sets the offset between consecutive validity windows.
+    pub fn with_stagger(mut self, stagger: Duration) -> Self {
+        self.stagger = stagger;
+        self
+    }
+
+    /// Sets the start of the first validity window.
+    pub fn with_start(mut self, start: UnixTimestamp) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Computes the validity windows described by this plan.
+    pub fn windows(&self) -> Vec<ValidityWindow> {
+        let start = self.start.unwrap_or_else(UnixTimestamp::now);
+        (0..self.count as u64)
+            .map(|i| {
+                let valid_after = start + i * self.stagger.as_secs();
+                ValidityWindow {
+                    valid_after,
+                    valid_before: valid_after + self.validity.as_secs(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// A payment authorization signed ahead of time.
+#[derive(Debug, Clone)]
+pub struct PresignedPayment {
+    /// Name of the header the payment is sent in.
+    pub header_name: &'static str,
+    /// Encoded payment payload.
+    pub header_value: HeaderValue,
+    /// Validity window of the signed authorization.
+    pub window: ValidityWindow,
+}
+
+/// Store of pre-signed payments, keyed by resource URL.
+///
+/// Every stored payment is single-use: [`PresignedPayments::take`] removes it.
+/// Expired payments are discarded on access.
+#[derive(Debug, Default)]
+pub struct PresignedPayments {
+    entries: Mutex<HashMap<String, VecDeque<PresignedPayment>>>,
+}
+
+impl PresignedPayments {
+    /// Seconds of validity a payment must have left to be handed out, leaving
+    /// time for the request to reach the facilitator.
+    pub const MIN_REMAINING_VALIDITY_SECS: u64 = 6;
+
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds payments for `resource`.
+    pub fn extend<I>(&self, resource: impl Into<String>, payments: I)
+    where
+        I: IntoIterator<Item = PresignedPayment>,
+    {
+        let mut entries = self.entries.lock().expect("presigned store lock poisoned");
+        entries.entry(resource.into()).or_default().extend(payments);
+    }
+
+    /// Removes and returns a payment for `resource` that is usable right now.
+    ///
+    /// Payments whose window has already closed are dropped. Payments whose window
+    /// has not opened yet stay in the store.
+    pub fn take(&self, resource: &str) -> Option<PresignedPayment> {
+        let mut entries = self.entries.lock().expect("presigned store lock poisoned");
+        let queue = entries.get_mut(resource)?;
+        let now = UnixTimestamp::now();
+        let deadline = now + Self::MIN_REMAINING_VALIDITY_SECS;
+        queue.retain(|p| p.window.valid_before > deadline);
+        let position = queue.iter().position(|p| p.window.contains(now))?;
+        let payment = queue.remove(position);
+        if queue.is_empty() {
+            entries.remove(resource);
+        }
+        payment
+    }
+
+    /// Returns the number of stored payments for `resource`, including ones not yet valid.
+    pub fn len(&self, resource: &str) -> usize {
+        let entries = self.entries.lock().expect("presigned store lock poisoned");
+        entries.get(resource).map(VecDeque::len).unwrap_or(0)
+    }
+
+    /// Returns `true` if no payments are stored for `resource`.
+    pub fn is_empty(&self, resource: &str) -> bool {
+        self.len(resource) == 0
+    }
+
+    /// Removes all payments stored for `resource`.
+    pub fn clear(&self, resource: &str) {
+        let mut entries = self.entries.lock().expect("presigned store lock poisoned");
+        entries.remove(resource);
+    }
+}
+
+impl<TSelector> X402Client<TSelector>
+where
+    TSelector: PaymentSelector,
+{
+    /// Signs a batch of payments for the given requirements ahead of time.
+    ///
+    /// The payment option is chosen with the configured [`PaymentSelector`], then one
+    /// authorization is signed per window of `plan`, each with a fresh nonce.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`X402Error::NoMatchingPaymentOption`] if no registered scheme can
+    /// handle the requirements, or [`X402Error::SigningError`] if the selected
+    /// scheme does not support explicit validity windows. Each authorization is
+    /// charged to the budget, so [`X402Error::BudgetExceeded`] ends the batch early.
+    pub async fn presign(
+        &self,
+        payment_required: &proto::PaymentRequired,
+        plan: &PresignPlan,
+    ) -> Result<Vec<PresignedPayment>, X402Error> {
+        let candidates = self.candidates(payment_required).await;
+        let selected = self
+            .selector()
+            .select(&candidates)
+            .ok_or(X402Error::NoMatchingPaymentOption)?;
+        let header_name = payment_header_name(payment_required);
+
+        let _signing = self.lock_signing(selected).await;
+        let mut payments = Vec::with_capacity(plan.count);
+        for window in plan.windows() {
+            self.charge(selected)?;
+            let signed_payload = selected.sign_within(window).await?;
+            let header_value = HeaderValue::from_str(&signed_payload)
+                .map_err(|e| X402Error::SigningError(format!("{e}")))?;
+            payments.push(PresignedPayment {
+                header_name,
+                header_value,
+                window,
+            });
+        }
+        Ok(payments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payment(valid_after: u64, valid_before: u64) -> PresignedPayment {
+        PresignedPayment {
+            header_name: "Payment-Signature",
+            header_value: HeaderValue::from_static("payload"),
+            window: ValidityWindow {
+                valid_after: UnixTimestamp::from_secs(valid_after),
+                valid_before: UnixTimestamp::from_secs(valid_before),
+            },
+        }
+    }
+
+    #[test]
+    fn test_plan_staggers_windows() {
+        let plan = PresignPlan::new(3)
+            .with_validity(Duration::from_secs(100))
+            .with_stagger(Duration::from_secs(30))
+            .with_start(UnixTimestamp::from_secs(1_000));
+        let windows = plan.windows();
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[0].valid_after.as_secs(), 1_000);
+        assert_eq!(windows[0].valid_before.as_secs(), 1_100);
+        assert_eq!(windows[2].valid_after.as_secs(), 1_060);
+        assert_eq!(windows[2].valid_before.as_secs(), 1_160);
+    }
+
+    #[test]
+    fn test_take_skips_expired_and_future_payments() {
+        let now = UnixTimestamp::now().as_secs();
+        let store = PresignedPayments::new();
+        store.extend(
+            "https://example.com/a",
+            [
+                payment(now - 100, now - 10),
+                payment(now + 100, now + 200),
+                payment(now - 10, now + 100),
+            ],
+        );
+
+        let taken = store.take("https://example.com/a").unwrap();
+        assert_eq!(taken.window.valid_after.as_secs(), now - 10);
+        // The expired payment is gone, the future one stays.
+        assert_eq!(store.len("https://example.com/a"), 1);
+        assert!(store.take("https://example.com/a").is_none());
+        assert!(store.take("https://example.com/b").is_none());
+    }
+}
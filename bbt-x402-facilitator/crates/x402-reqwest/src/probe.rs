@@ -0,0 +1,184 @@
+//! Price checks against x402-protected resources.
+//!
+//! [`X402Client::probe`] requests a resource without a payment, expects a
+//! `402 Payment Required` challenge and returns it as a [`PriceQuote`]. Nothing is
+//! signed or paid, so agents can compare prices across providers before committing
+//! spend.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use x402_reqwest::X402Client;
+//!
+//! let x402_client = X402Client::new().register(V2Eip155ExactClient::new(signer));
+//! let quote = x402_client.probe("https://api.example.com/protected").await?;
+//! for option in quote.options.iter().filter(|o| o.payable) {
+//!     println!("{} {} on {}", option.amount, option.asset, option.network);
+//! }
+//! ```
+
+use http::StatusCode;
+use reqwest::{IntoUrl, RequestBuilder};
+use x402_types::chain::ChainId;
+use x402_types::proto;
+use x402_types::scheme::client::{PaymentCandidate, X402Error};
+use x402_types::timestamp::UnixTimestamp;
+
+#[cfg(feature = "telemetry")]
+use tracing::instrument;
+
+use crate::client::{X402Client, parse_payment_required};
+
+/// A payment challenge returned by a resource, flattened for comparison.
+#[derive(Debug, Clone)]
+pub struct PriceQuote {
+    /// The raw challenge as returned by the server.
+    pub payment_required: proto::PaymentRequired,
+    /// Description of the resource, if provided.
+    pub description: Option<String>,
+    /// The payment options offered by the server.
+    pub options: Vec<PriceOption>,
+}
+
+impl PriceQuote {
+    /// Returns the options that a registered scheme client is able to pay.
+    pub fn payable(&self) -> impl Iterator<Item = &PriceOption> {
+        self.options.iter().filter(|option| option.payable)
+    }
+}
+
+/// A single payment option from a [`PriceQuote`].
+#[derive(Debug, Clone)]
+pub struct PriceOption {
+    /// The x402 protocol version of the option.
+    pub x402_version: u8,
+    /// The payment scheme name (e.g. `exact`).
+    pub scheme: String,
+    /// The network as stated by the server: a network name in V1, a CAIP-2 chain ID in V2.
+    pub network: String,
+    /// The CAIP-2 chain ID, if the network is known.
+    pub chain_id: Option<ChainId>,
+    /// The token asset.
+    pub asset: String,
+    /// The amount in the token's smallest unit.
+    pub amount: String,
+    /// The recipient address.
+    pub pay_to: String,
+    /// Maximum time in seconds the server accepts for completing the payment.
+    pub max_timeout_seconds: u64,
+    /// Expiry of a payment signed at the time of the probe.
+    pub expires_at: UnixTimestamp,
+    /// Whether a registered scheme client can pay this option.
+    pub payable: bool,
+}
+
+impl PriceOption {
+    fn matches(&self, candidate: &PaymentCandidate) -> bool {
+        self.chain_id.as_ref() == Some(&candidate.chain_id)
+            && self.scheme == candidate.scheme
+            && self.x402_version == candidate.x402_version
+            && self.asset.eq_ignore_ascii_case(&candidate.asset)
+            && self.pay_to.eq_ignore_ascii_case(&candidate.pay_to)
+            && self.amount == candidate.amount.to_string()
+    }
+}
+
+impl<TSelector> X402Client<TSelector> {
+    /// Requests `url` without paying and returns the payment challenge.
+    ///
+    /// Sends a `GET` request with a default [`reqwest::Client`]. Use
+    /// [`X402Client::probe_request`] for other methods, headers or clients.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`X402Error::Http`] if the request fails and [`X402Error::ParseError`]
+    /// if the response is not a valid `402 Payment Required` challenge.
+    pub async fn probe<U: IntoUrl>(&self, url: U) -> Result<PriceQuote, X402Error> {
+        self.probe_request(reqwest::Client::new().get(url)).await
+    }
+
+    /// Sends the given request without paying and returns the payment challenge.
+    ///
+    /// The request is sent as-is, bypassing any payment middleware.
+    #[cfg_attr(
+        feature = "telemetry",
+        instrument(name = "x402.reqwest.probe", skip_all, err)
+    )]
+    pub async fn probe_request(&self, request: RequestBuilder) -> Result<PriceQuote, X402Error> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| X402Error::Http(e.to_string()))?;
+        if response.status() != StatusCode::PAYMENT_REQUIRED {
+            return Err(X402Error::ParseError(format!(
+                "Expected 402 Payment Required, got {}",
+                response.status()
+            )));
+        }
+        let payment_required = parse_payment_required(response)
+            .await
+            .ok_or(X402Error::ParseError("Invalid 402 response".to_string()))?;
+        Ok(self.quote(payment_required))
+    }
+
+    /// Flattens a payment challenge into a [`PriceQuote`].
+    pub fn quote(&self, payment_required: proto::PaymentRequired) -> PriceQuote {
+        let now = UnixTimestamp::now();
+        let (description, mut options) = match &payment_required {
+            proto::PaymentRequired::V1(payment_required) => {
+                let options = payment_required
+                    .accepts
+                    .iter()
+                    .map(|requirements| PriceOption {
+                        x402_version: 1,
+                        scheme: requirements.scheme.clone(),
+                        network: requirements.network.clone(),
+                        chain_id: ChainId::from_network_name(&requirements.network),
+                        asset: requirements.asset.clone(),
+                        amount: requirements.max_amount_required.clone(),
+                        pay_to: requirements.pay_to.clone(),
+                        max_timeout_seconds: requirements.max_timeout_seconds,
+                        expires_at: now + requirements.max_timeout_seconds,
+                        payable: false,
+                    })
+                    .collect::<Vec<_>>();
+                let description = payment_required
+                    .accepts
+                    .first()
+                    .map(|requirements| requirements.description.clone());
+                (description, options)
+            }
+            proto::PaymentRequired::V2(payment_required) => {
+                let options = payment_required
+                    .accepts
+                    .iter()
+                    .map(|requirements| PriceOption {
+                        x402_version: 2,
+                        scheme: requirements.scheme.clone(),
+                        network: requirements.network.to_string(),
+                        chain_id: Some(requirements.network.clone()),
+                        asset: requirements.asset.clone(),
+                        amount: requirements.amount.clone(),
+                        pay_to: requirements.pay_to.clone(),
+                        max_timeout_seconds: requirements.max_timeout_seconds,
+                        expires_at: now + requirements.max_timeout_seconds,
+                        payable: false,
+                    })
+                    .collect::<Vec<_>>();
+                let description = Some(payment_required.resource.description.clone());
+                (description, options)
+            }
+        };
+
+        let candidates = self.schemes().candidates(&payment_required);
+        for option in options.iter_mut() {
+            option.payable = candidates.iter().any(|candidate| option.matches(candidate));
+        }
+
+        PriceQuote {
+            payment_required,
+            description: description.filter(|d| !d.is_empty()),
+            options,
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Payment receipts returned by resource servers.
+//!
+//! After settling a payment, a resource server reports the settlement in a response
+//! header: `X-Payment-Response` in V1, `Payment-Response` in V2, both carrying the
+//! facilitator's settle response as base64-encoded JSON. [`PaymentReceiptExt`] decodes
+//! it into a [`PaymentReceipt`], e.g. to show the payer a confirmation.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use x402_reqwest::PaymentReceiptExt;
+//!
+//! let response = http_client.get("https://api.example.com/protected").send().await?;
+//! if let Some(receipt) = response.payment_receipt()? {
+//!     println!("Paid by {} in {} on {}", receipt.payer, receipt.transaction, receipt.network);
+//! }
+//! ```
+
+use http::HeaderMap;
+use reqwest::Response;
+use std::fmt;
+use x402_types::proto::v1::{self, SettlementReceipt};
+use x402_types::util::Base64Bytes;
+
+/// Header carrying the settlement of a V1 payment.
+pub const V1_PAYMENT_RESPONSE_HEADER: &str = "X-Payment-Response";
+/// Header carrying the settlement of a V2 payment.
+pub const V2_PAYMENT_RESPONSE_HEADER: &str = "Payment-Response";
+
+/// A settled payment, as reported by the resource server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentReceipt {
+    /// The network the payment was settled on: a network name in V1, a CAIP-2 chain
+    /// ID in V2.
+    pub network: String,
+    /// Hash of the settlement transaction.
+    pub transaction: String,
+    /// Address of the payer.
+    pub payer: String,
+    /// Details from the transaction receipt, as far as the facilitator reported them.
+    pub details: SettlementReceipt,
+}
+
+/// Why a payment receipt could not be read from a response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaymentReceiptError {
+    /// The receipt is not a valid settle response.
+    Malformed(String),
+    /// The server reports that the payment was not settled.
+    SettlementFailed { reason: String, network: String },
+}
+
+impl fmt::Display for PaymentReceiptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaymentReceiptError::Malformed(details) => {
+                write!(f, "Malformed payment receipt: {details}")
+            }
+            PaymentReceiptError::SettlementFailed { reason, network } => {
+                write!(f, "Payment settlement on {network} failed: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PaymentReceiptError {}
+
+/// Reads the payment receipt from the headers of a response.
+///
+/// Returns `Ok(None)` if the response carries no payment response header, as for
+/// resources that were not paid for.
+pub fn decode_payment_receipt(
+    headers: &HeaderMap,
+) -> Result<Option<PaymentReceipt>, PaymentReceiptError> {
+    let Some(header) = headers
+        .get(V2_PAYMENT_RESPONSE_HEADER)
+        .or_else(|| headers.get(V1_PAYMENT_RESPONSE_HEADER))
+    else {
+        return Ok(None);
+    };
+    let json = Base64Bytes::from(header.as_bytes())
+        .decode()
+        .map_err(|e| PaymentReceiptError::Malformed(e.to_string()))?;
+    let settlement = serde_json::from_slice::<v1::SettleResponse>(&json)
+        .map_err(|e| PaymentReceiptError::Malformed(e.to_string()))?;
+    PaymentReceipt::try_from(settlement).map(Some)
+}
+
+impl TryFrom<v1::SettleResponse> for PaymentReceipt {
+    type Error = PaymentReceiptError;
+
+    fn try_from(settlement: v1::SettleResponse) -> Result<Self, Self::Error> {
+        match settlement {
+            v1::SettleResponse::Success {
+                payer,
+                transaction,
+                network,
+                receipt,
+            } => Ok(PaymentReceipt {
+                network,
+                transaction,
+                payer,
+                details: receipt,
+            }),
+            v1::SettleResponse::Error { reason, network } => {
+                Err(PaymentReceiptError::SettlementFailed { reason, network })
+            }
+        }
+    }
+}
+
+/// Access to the payment receipt of a [`reqwest::Response`].
+pub trait PaymentReceiptExt {
+    /// Returns the receipt of the payment made for this response, if any.
+    ///
+    /// See [`decode_payment_receipt`].
+    fn payment_receipt(&self) -> Result<Option<PaymentReceipt>, PaymentReceiptError>;
+}
+
+impl PaymentReceiptExt for Response {
+    fn payment_receipt(&self) -> Result<Option<PaymentReceipt>, PaymentReceiptError> {
+        decode_payment_receipt(self.headers())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    fn response_with(header: &str, settlement: serde_json::Value) -> Response {
+        let value = Base64Bytes::encode(serde_json::to_vec(&settlement).unwrap());
+        let response = http::Response::builder()
+            .header(header, HeaderValue::from_bytes(value.as_ref()).unwrap())
+            .body("")
+            .unwrap();
+        Response::from(response)
+    }
+
+    #[test]
+    fn test_payment_receipt() {
+        let settlement = serde_json::json!({
+            "success": true,
+            "payer": "0x2222222222222222222222222222222222222222",
+            "transaction": "0xabcd",
+            "network": "eip155:42793",
+            "blockNumber": 7,
+        });
+        for header in [V1_PAYMENT_RESPONSE_HEADER, V2_PAYMENT_RESPONSE_HEADER] {
+            let receipt = response_with(header, settlement.clone())
+                .payment_receipt()
+                .unwrap()
+                .unwrap();
+            assert_eq!(receipt.payer, "0x2222222222222222222222222222222222222222");
+            assert_eq!(receipt.transaction, "0xabcd");
+            assert_eq!(receipt.network, "eip155:42793");
+            assert_eq!(receipt.details.block_number, Some(7));
+        }
+
+        let failed = response_with(
+            V2_PAYMENT_RESPONSE_HEADER,
+            serde_json::json!({"success": false, "error_reason": "insufficient_funds", "network": "etherlink"}),
+        );
+        assert!(matches!(
+            failed.payment_receipt(),
+            Err(PaymentReceiptError::SettlementFailed { .. })
+        ));
+
+        let unpaid = Response::from(http::Response::new(""));
+        assert_eq!(unpaid.payment_receipt(), Ok(None));
+    }
+}
@@ -0,0 +1,107 @@
+//! Retries of payments rejected for their timing.
+//!
+//! A payment authorization is only valid within a time window. Clock skew between
+//! client and facilitator, or a slow round-trip, can make the server reject a freshly
+//! signed payment as not yet valid or as expired, answering with another
+//! `402 Payment Required`. With a [`RetryPolicy`] attached through
+//! [`X402Client::with_retry_policy`], the middleware then signs a new payment, with
+//! fresh timestamps and nonce, against the new challenge and sends the request again.
+//!
+//! Only rejections whose reason is [`ErrorReason::InvalidPaymentEarly`] or
+//! [`ErrorReason::InvalidPaymentExpired`] are retried; any other response is returned
+//! as-is. Every retry signs a new payment, which is charged to the attached budget.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use x402_reqwest::{RetryPolicy, X402Client};
+//!
+//! let client = X402Client::new().with_retry_policy(RetryPolicy::new().with_max_retries(2));
+//! ```
+
+use x402_types::proto::{self, ErrorReason, PaymentVerificationError};
+
+#[cfg(doc)]
+use crate::X402Client;
+
+/// How often a payment rejected for its timing is signed and sent again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries per request after the first payment. Defaults to 1.
+    pub max_retries: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 1 }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy retrying once.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the retries per request.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Whether a challenge answering a paid request rejects the payment for its timing.
+    pub fn is_retryable(&self, payment_required: &proto::PaymentRequired) -> bool {
+        let error = match payment_required {
+            proto::PaymentRequired::V1(payment_required) => payment_required.error.as_deref(),
+            proto::PaymentRequired::V2(payment_required) => payment_required.error.as_deref(),
+        };
+        error.is_some_and(is_timing_error)
+    }
+}
+
+/// Whether a server error message names an early or expired authorization, either by
+/// its [`ErrorReason`] code or by the facilitator's message.
+fn is_timing_error(error: &str) -> bool {
+    let reasons = [
+        ErrorReason::InvalidPaymentEarly,
+        ErrorReason::InvalidPaymentExpired,
+    ];
+    let messages = [
+        PaymentVerificationError::EARLY_MESSAGE,
+        PaymentVerificationError::EXPIRED_MESSAGE,
+    ];
+    reasons.iter().any(|reason| {
+        serde_json::to_value(reason)
+            .ok()
+            .and_then(|code| code.as_str().map(|code| error.contains(code)))
+            .unwrap_or(false)
+    }) || messages.iter().any(|message| error.contains(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use x402_types::proto::v1;
+
+    fn rejected(error: Option<&str>) -> proto::PaymentRequired {
+        proto::PaymentRequired::V1(v1::PaymentRequired {
+            x402_version: v1::X402Version1,
+            accepts: vec![],
+            error: error.map(str::to_string),
+            transports: vec![],
+        })
+    }
+
+    #[test]
+    fn test_retries_only_timing_rejections() {
+        let policy = RetryPolicy::new();
+        assert!(policy.is_retryable(&rejected(Some(
+            "Verification failed: invalid_payment_expired"
+        ))));
+        assert!(policy.is_retryable(&rejected(Some(
+            "Verification failed: Payment authorization is not yet valid"
+        ))));
+        assert!(!policy.is_retryable(&rejected(Some("Verification failed: insufficient_funds"))));
+        assert!(!policy.is_retryable(&rejected(None)));
+    }
+}
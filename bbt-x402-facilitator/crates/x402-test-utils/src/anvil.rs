@@ -0,0 +1,228 @@
+//! A local Anvil node with well-known accounts.
+
+use alloy_primitives::{Address, B256, b256};
+use alloy_signer_local::PrivateKeySigner;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+use url::Url;
+
+use crate::TestUtilsError;
+
+/// Private keys of the first accounts of Anvil's default mnemonic
+/// (`test test test test test test test test test test test junk`).
+pub const ANVIL_PRIVATE_KEYS: [B256; 4] = [
+    b256!("0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"),
+    b256!("0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d"),
+    b256!("0x5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a"),
+    b256!("0x7c852118294e51e653712a81e05800f419141751be58f605c371e15141b007a6"),
+];
+
+/// Chain ID Anvil runs with by default.
+pub const ANVIL_CHAIN_ID: u64 = 31337;
+
+/// Well-known accounts, one per role in a payment.
+#[derive(Debug, Clone)]
+pub struct Accounts {
+    /// Deploys the contracts and mints tokens.
+    pub deployer: PrivateKeySigner,
+    /// Submits settlements.
+    pub facilitator: PrivateKeySigner,
+    /// Signs payments.
+    pub payer: PrivateKeySigner,
+    /// Receives payments; holds no tokens to begin with.
+    pub pay_to: PrivateKeySigner,
+}
+
+impl Default for Accounts {
+    fn default() -> Self {
+        let [deployer, facilitator, payer, pay_to] = ANVIL_PRIVATE_KEYS.map(|key| {
+            PrivateKeySigner::from_bytes(&key).expect("well-known Anvil keys are valid")
+        });
+        Self {
+            deployer,
+            facilitator,
+            payer,
+            pay_to,
+        }
+    }
+}
+
+impl Accounts {
+    /// All signers, deployer first.
+    pub fn signers(&self) -> [&PrivateKeySigner; 4] {
+        [&self.deployer, &self.facilitator, &self.payer, &self.pay_to]
+    }
+
+    /// All addresses, deployer first.
+    pub fn addresses(&self) -> [Address; 4] {
+        self.signers().map(PrivateKeySigner::address)
+    }
+}
+
+/// Starts an Anvil node.
+///
+/// The binary is `ANVIL_PATH` if set, `anvil` from `PATH` otherwise. The node listens on
+/// a free local port and mines a block per transaction. Blocks follow the wall clock,
+/// as the facilitator checks validity windows against it.
+#[derive(Debug, Clone)]
+pub struct AnvilBuilder {
+    program: PathBuf,
+    chain_id: u64,
+    timestamp: Option<u64>,
+    args: Vec<String>,
+    startup_timeout: Duration,
+}
+
+impl Default for AnvilBuilder {
+    fn default() -> Self {
+        Self {
+            program: std::env::var_os("ANVIL_PATH")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("anvil")),
+            chain_id: ANVIL_CHAIN_ID,
+            timestamp: None,
+            args: Vec::new(),
+            startup_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl AnvilBuilder {
+    /// Sets the chain ID.
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Sets the genesis timestamp, in unix seconds.
+    ///
+    /// Payloads signed with [`PaymentParams`](crate::PaymentParams) defaults only settle
+    /// while the chain is no more than ten minutes behind the wall clock.
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Appends a raw command-line argument, e.g. `--block-time`.
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Sets how long to wait for the node to listen.
+    pub fn startup_timeout(mut self, timeout: Duration) -> Self {
+        self.startup_timeout = timeout;
+        self
+    }
+
+    /// Spawns the node and waits until it listens.
+    pub fn spawn(self) -> Result<AnvilInstance, TestUtilsError> {
+        let mut command = Command::new(&self.program);
+        command
+            .arg("--port")
+            .arg("0")
+            .arg("--chain-id")
+            .arg(self.chain_id.to_string());
+        if let Some(timestamp) = self.timestamp {
+            command.arg("--timestamp").arg(timestamp.to_string());
+        }
+        let mut child = command
+            .args(&self.args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| TestUtilsError::AnvilSpawn(format!("{}: {e}", self.program.display())))?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        // The pipe is drained for the whole life of the node, lest Anvil block on logging.
+        let (listening_tx, listening_rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut listening_tx = Some(listening_tx);
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if let Some(address) = line.strip_prefix("Listening on ")
+                    && let Some(tx) = listening_tx.take()
+                {
+                    let _ = tx.send(address.trim().to_string());
+                }
+            }
+        });
+        let address = match listening_rx.recv_timeout(self.startup_timeout) {
+            Ok(address) => address,
+            Err(_) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(TestUtilsError::AnvilSpawn(
+                    "node did not report a listening address".to_string(),
+                ));
+            }
+        };
+        let endpoint = Url::parse(&format!("http://{address}"))
+            .map_err(|e| TestUtilsError::AnvilSpawn(format!("{address}: {e}")))?;
+        Ok(AnvilInstance {
+            child,
+            endpoint,
+            chain_id: self.chain_id,
+            accounts: Accounts::default(),
+        })
+    }
+}
+
+/// A running Anvil node, killed on drop.
+#[derive(Debug)]
+pub struct AnvilInstance {
+    child: Child,
+    endpoint: Url,
+    chain_id: u64,
+    accounts: Accounts,
+}
+
+impl AnvilInstance {
+    /// Spawns a node with the defaults of [`AnvilBuilder`].
+    pub fn spawn() -> Result<Self, TestUtilsError> {
+        AnvilBuilder::default().spawn()
+    }
+
+    /// HTTP endpoint of the node.
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// Chain ID of the node.
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Accounts funded by the node.
+    pub fn accounts(&self) -> &Accounts {
+        &self.accounts
+    }
+}
+
+impl Drop for AnvilInstance {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn test_well_known_accounts() {
+        assert_eq!(
+            Accounts::default().addresses(),
+            [
+                address!("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"),
+                address!("0x70997970C51812dc3A010C7d01b50e0d17dc79C8"),
+                address!("0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC"),
+                address!("0x90F79bf6EB2c4f870365E785982E1f101E93b906"),
+            ]
+        );
+    }
+}
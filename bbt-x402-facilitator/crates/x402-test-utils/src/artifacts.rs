@@ -0,0 +1,123 @@
+//! Bytecode of the contracts a fixture deploys, read from Foundry build artifacts.
+
+use alloy_primitives::{Bytes, hex};
+use std::path::{Path, PathBuf};
+
+use crate::TestUtilsError;
+
+/// Environment variable naming the artifacts directory of [`Artifacts::from_env`].
+pub const ARTIFACTS_DIR_ENV: &str = "X402_TEST_ARTIFACTS";
+
+/// Creation and runtime bytecode of a contract.
+#[derive(Debug, Clone)]
+pub struct ContractArtifact {
+    /// Creation bytecode, deployed with a transaction.
+    pub bytecode: Bytes,
+    /// Runtime bytecode, placed at a fixed address with `anvil_setCode`.
+    pub deployed_bytecode: Bytes,
+}
+
+impl ContractArtifact {
+    /// Parses a Foundry (`out/<File>.sol/<Contract>.json`) or Hardhat artifact.
+    pub fn from_json(json: &serde_json::Value) -> Result<Self, String> {
+        let code = |field: &str| -> Result<Bytes, String> {
+            let value = &json[field];
+            let code = value["object"]
+                .as_str()
+                .or_else(|| value.as_str())
+                .ok_or_else(|| format!("missing {field}"))?;
+            hex::decode(code)
+                .map(Bytes::from)
+                .map_err(|e| format!("{field}: {e}"))
+        };
+        Ok(Self {
+            bytecode: code("bytecode")?,
+            deployed_bytecode: code("deployedBytecode")?,
+        })
+    }
+
+    /// Reads the artifact at `path`.
+    pub fn read(path: &Path) -> Result<Self, TestUtilsError> {
+        let artifact_error =
+            |reason: String| TestUtilsError::Artifact(format!("{}: {reason}", path.display()));
+        let contents = std::fs::read(path).map_err(|e| artifact_error(e.to_string()))?;
+        let json = serde_json::from_slice(&contents).map_err(|e| artifact_error(e.to_string()))?;
+        Self::from_json(&json).map_err(artifact_error)
+    }
+}
+
+/// The contracts of a [`Fixture`](crate::Fixture).
+///
+/// - `TestErc3009Token`: the ERC-20 with EIP-3009 authorizations shipped in this crate's
+///   `contracts` directory
+/// - `Permit2`: Uniswap's Permit2
+/// - `x402ExactPermit2Proxy`: the x402 Permit2 witness proxy
+#[derive(Debug, Clone)]
+pub struct Artifacts {
+    /// The ERC-3009 test token.
+    pub token: ContractArtifact,
+    /// Permit2.
+    pub permit2: ContractArtifact,
+    /// The x402 Permit2 proxy.
+    pub permit2_proxy: ContractArtifact,
+}
+
+impl Artifacts {
+    /// Loads the artifacts from `dir`, searched recursively for `TestErc3009Token.json`,
+    /// `Permit2.json` and `x402ExactPermit2Proxy.json`, e.g. a Foundry `out` directory.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, TestUtilsError> {
+        let dir = dir.as_ref();
+        let read = |contract: &str| {
+            let path = find_file(dir, &format!("{contract}.json")).ok_or_else(|| {
+                TestUtilsError::Artifact(format!("no {contract}.json in {}", dir.display()))
+            })?;
+            ContractArtifact::read(&path)
+        };
+        Ok(Self {
+            token: read("TestErc3009Token")?,
+            permit2: read("Permit2")?,
+            permit2_proxy: read("x402ExactPermit2Proxy")?,
+        })
+    }
+
+    /// Loads the artifacts from the directory named by [`ARTIFACTS_DIR_ENV`].
+    pub fn from_env() -> Result<Self, TestUtilsError> {
+        let dir = std::env::var_os(ARTIFACTS_DIR_ENV)
+            .ok_or_else(|| TestUtilsError::Artifact(format!("{ARTIFACTS_DIR_ENV} is not set")))?;
+        Self::load(dir)
+    }
+}
+
+fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    let mut subdirs = Vec::new();
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            subdirs.push(path);
+        } else if path.file_name().is_some_and(|file| file == name) {
+            return Some(path);
+        }
+    }
+    subdirs.sort();
+    subdirs.iter().find_map(|subdir| find_file(subdir, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_foundry_and_hardhat_artifacts() {
+        let foundry = serde_json::json!({
+            "bytecode": { "object": "0x6001" },
+            "deployedBytecode": { "object": "0x6002" }
+        });
+        let artifact = ContractArtifact::from_json(&foundry).unwrap();
+        assert_eq!(artifact.bytecode, Bytes::from(vec![0x60, 0x01]));
+        assert_eq!(artifact.deployed_bytecode, Bytes::from(vec![0x60, 0x02]));
+
+        let hardhat = serde_json::json!({ "bytecode": "0x6001", "deployedBytecode": "0x6002" });
+        assert!(ContractArtifact::from_json(&hardhat).is_ok());
+        assert!(ContractArtifact::from_json(&serde_json::json!({ "bytecode": "0x" })).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+//! An Anvil node with the x402 contracts deployed and the payer funded.
+
+use alloy_network::{EthereumWallet, TransactionBuilder};
+use alloy_primitives::{Address, Bytes, U256};
+use alloy_provider::{DynProvider, Provider, ProviderBuilder};
+use alloy_rpc_types_eth::TransactionRequest;
+use alloy_signer_local::PrivateKeySigner;
+use alloy_sol_types::sol;
+use std::sync::atomic::{AtomicU64, Ordering};
+use x402_chain_eip155::v1_eip155_exact::{PERMIT2_ADDRESS, X402_EXACT_PERMIT2_PROXY_ADDRESS};
+
+use crate::{AnvilInstance, Artifacts, PaymentParams, TestUtilsError};
+
+/// EIP-712 domain name of the test token.
+pub const TEST_TOKEN_NAME: &str = "Test USD";
+
+/// EIP-712 domain version of the test token.
+pub const TEST_TOKEN_VERSION: &str = "2";
+
+/// Decimals of the test token.
+pub const TEST_TOKEN_DECIMALS: u8 = 6;
+
+/// Test tokens the payer holds after [`Fixture::deploy`]: 1,000 TUSD.
+pub const PAYER_TOKEN_BALANCE: u64 = 1_000_000_000;
+
+sol! {
+    #[sol(rpc)]
+    interface ITestErc3009Token {
+        function mint(address to, uint256 value) external;
+        function approve(address spender, uint256 value) external returns (bool);
+        function balanceOf(address owner) external view returns (uint256);
+    }
+
+    #[sol(rpc)]
+    interface IPermit2Allowance {
+        function allowance(address owner, address token, address spender) external view returns (uint160 amount, uint48 expiration, uint48 nonce);
+    }
+
+    #[sol(rpc)]
+    interface IX402Permit2ProxyInit {
+        function initialize(address permit2) external;
+    }
+}
+
+/// An Anvil node with the contracts of every "exact" payment context:
+///
+/// - the ERC-3009 test token, deployed by the deployer account
+/// - Permit2, at its canonical address
+/// - the x402 Permit2 proxy, initialized at the address the facilitator uses by default
+///
+/// The payer holds [`PAYER_TOKEN_BALANCE`] test tokens and has approved Permit2 for all
+/// of them, so that a facilitator configured with [`Fixture::facilitator_chain_config`]
+/// verifies and settles the payloads of [`Fixture::payment`] in any context.
+#[derive(Debug)]
+pub struct Fixture {
+    anvil: AnvilInstance,
+    provider: DynProvider,
+    token: Address,
+    nonces: AtomicU64,
+}
+
+impl Fixture {
+    /// Spawns Anvil and deploys the contracts from [`Artifacts::from_env`].
+    pub async fn spawn() -> Result<Self, TestUtilsError> {
+        Self::deploy(AnvilInstance::spawn()?, &Artifacts::from_env()?).await
+    }
+
+    /// Deploys the contracts of `artifacts` on `anvil` and funds the payer.
+    pub async fn deploy(
+        anvil: AnvilInstance,
+        artifacts: &Artifacts,
+    ) -> Result<Self, TestUtilsError> {
+        let accounts = anvil.accounts().clone();
+        let mut wallet = EthereumWallet::from(accounts.deployer.clone());
+        for signer in accounts.signers() {
+            wallet.register_signer(signer.clone());
+        }
+        let provider = ProviderBuilder::new()
+            .wallet(wallet)
+            .connect_http(anvil.endpoint().clone())
+            .erased();
+
+        let deployment = TransactionRequest::default()
+            .with_from(accounts.deployer.address())
+            .with_deploy_code(artifacts.token.bytecode.clone());
+        let token = provider
+            .send_transaction(deployment)
+            .await
+            .map_err(rpc_error)?
+            .get_receipt()
+            .await
+            .map_err(rpc_error)?
+            .contract_address
+            .ok_or_else(|| {
+                TestUtilsError::Rpc("test token deployment created no contract".into())
+            })?;
+
+        let fixture = Self {
+            anvil,
+            provider,
+            token,
+            nonces: AtomicU64::new(0),
+        };
+        fixture
+            .set_code(PERMIT2_ADDRESS, &artifacts.permit2.deployed_bytecode)
+            .await?;
+        fixture
+            .set_code(
+                X402_EXACT_PERMIT2_PROXY_ADDRESS,
+                &artifacts.permit2_proxy.deployed_bytecode,
+            )
+            .await?;
+        IX402Permit2ProxyInit::new(X402_EXACT_PERMIT2_PROXY_ADDRESS, &fixture.provider)
+            .initialize(PERMIT2_ADDRESS)
+            .from(accounts.deployer.address())
+            .send()
+            .await
+            .map_err(rpc_error)?
+            .get_receipt()
+            .await
+            .map_err(rpc_error)?;
+
+        fixture
+            .mint(accounts.payer.address(), U256::from(PAYER_TOKEN_BALANCE))
+            .await?;
+        fixture.approve_permit2(&accounts.payer).await?;
+        Ok(fixture)
+    }
+
+    /// The Anvil node.
+    pub fn anvil(&self) -> &AnvilInstance {
+        &self.anvil
+    }
+
+    /// A provider signing for every account of [`AnvilInstance::accounts`].
+    pub fn provider(&self) -> &DynProvider {
+        &self.provider
+    }
+
+    /// The ERC-3009 test token.
+    pub fn token(&self) -> Address {
+        self.token
+    }
+
+    /// Replaces the code at `address`.
+    pub async fn set_code(&self, address: Address, code: &Bytes) -> Result<(), TestUtilsError> {
+        self.provider
+            .raw_request::<_, serde_json::Value>("anvil_setCode".into(), (address, code))
+            .await
+            .map_err(rpc_error)?;
+        Ok(())
+    }
+
+    /// Sets the native balance of `address`, in wei.
+    pub async fn set_balance(&self, address: Address, balance: U256) -> Result<(), TestUtilsError> {
+        self.provider
+            .raw_request::<_, serde_json::Value>("anvil_setBalance".into(), (address, balance))
+            .await
+            .map_err(rpc_error)?;
+        Ok(())
+    }
+
+    /// Mints `amount` test tokens to `to`.
+    pub async fn mint(&self, to: Address, amount: U256) -> Result<(), TestUtilsError> {
+        ITestErc3009Token::new(self.token, &self.provider)
+            .mint(to, amount)
+            .from(self.anvil.accounts().deployer.address())
+            .send()
+            .await
+            .map_err(rpc_error)?
+            .get_receipt()
+            .await
+            .map_err(rpc_error)?;
+        Ok(())
+    }
+
+    /// Approves Permit2 for all test tokens of `owner`, one of [`AnvilInstance::accounts`].
+    pub async fn approve_permit2(&self, owner: &PrivateKeySigner) -> Result<(), TestUtilsError> {
+        ITestErc3009Token::new(self.token, &self.provider)
+            .approve(PERMIT2_ADDRESS, U256::MAX)
+            .from(owner.address())
+            .send()
+            .await
+            .map_err(rpc_error)?
+            .get_receipt()
+            .await
+            .map_err(rpc_error)?;
+        Ok(())
+    }
+
+    /// Test tokens held by `owner`.
+    pub async fn balance_of(&self, owner: Address) -> Result<U256, TestUtilsError> {
+        ITestErc3009Token::new(self.token, &self.provider)
+            .balanceOf(owner)
+            .call()
+            .await
+            .map_err(rpc_error)
+    }
+
+    /// The next Permit2 allowance nonce of `owner` for the test token and `spender`.
+    pub async fn allowance_nonce(
+        &self,
+        owner: Address,
+        spender: Address,
+    ) -> Result<u64, TestUtilsError> {
+        let allowance = IPermit2Allowance::new(PERMIT2_ADDRESS, &self.provider)
+            .allowance(owner, self.token, spender)
+            .call()
+            .await
+            .map_err(rpc_error)?;
+        Ok(allowance.nonce.to())
+    }
+
+    /// A payment of `amount` test tokens from the payer to the pay-to account, with an
+    /// unused nonce and the payer's next allowance nonce for the facilitator.
+    pub async fn payment(&self, amount: U256) -> Result<PaymentParams, TestUtilsError> {
+        let accounts = self.anvil.accounts();
+        let mut params = PaymentParams::new(
+            self.anvil.chain_id(),
+            self.token,
+            TEST_TOKEN_NAME,
+            TEST_TOKEN_VERSION,
+            accounts.pay_to.address(),
+            amount,
+            accounts.facilitator.address(),
+        );
+        params.nonce = U256::from(self.nonces.fetch_add(1, Ordering::Relaxed) + 1);
+        params.allowance_nonce = self
+            .allowance_nonce(accounts.payer.address(), accounts.facilitator.address())
+            .await?;
+        Ok(params)
+    }
+
+    /// The `chains` entry of a facilitator settling on the node with the facilitator account.
+    pub fn facilitator_chain_config(&self) -> serde_json::Value {
+        let signer = self.anvil.accounts().facilitator.credential().to_bytes();
+        serde_json::json!({
+            format!("eip155:{}", self.anvil.chain_id()): {
+                "eip1559": true,
+                "signers": [format!("0x{}", alloy_primitives::hex::encode(signer))],
+                "rpc": [{ "http": self.anvil.endpoint() }]
+            }
+        })
+    }
+}
+
+fn rpc_error(error: impl std::fmt::Display) -> TestUtilsError {
+    TestUtilsError::Rpc(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[ignore = "needs anvil and X402_TEST_ARTIFACTS"]
+    async fn test_deploys_and_funds() {
+        let fixture = Fixture::spawn().await.unwrap();
+        let accounts = fixture.anvil().accounts();
+        assert_eq!(
+            fixture.balance_of(accounts.payer.address()).await.unwrap(),
+            U256::from(PAYER_TOKEN_BALANCE)
+        );
+        assert!(
+            !fixture
+                .provider()
+                .get_code_at(PERMIT2_ADDRESS)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let first = fixture.payment(U256::from(1)).await.unwrap();
+        let second = fixture.payment(U256::from(1)).await.unwrap();
+        assert_eq!(first.allowance_nonce, 0);
+        assert_ne!(first.nonce, second.nonce);
+    }
+}
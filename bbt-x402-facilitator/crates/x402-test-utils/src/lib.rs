@@ -0,0 +1,57 @@
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+
+//! Deterministic test harness for x402 integrations on EVM chains.
+//!
+//! Spins up an [Anvil](https://book.getfoundry.sh/anvil/) node, deploys an ERC-3009 test
+//! token, Permit2 and the x402 Permit2 proxy, funds a payer, and signs "exact" scheme
+//! payloads for every payment context: ERC-3009, Permit2 allowances and Permit2 witness
+//! transfers. Accounts are Anvil's well-known ones, and nonces count up from one, so
+//! that runs sign the same payloads but for their validity windows.
+//!
+//! ## Requirements
+//!
+//! - `anvil` on `PATH`, or its path in `ANVIL_PATH`
+//! - Foundry artifacts of `TestErc3009Token` (in this crate's `contracts` directory),
+//!   Uniswap's `Permit2` and `x402ExactPermit2Proxy`, in the directory named by
+//!   `X402_TEST_ARTIFACTS`, see [`Artifacts`]
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use alloy_primitives::U256;
+//! use x402_test_utils::{Fixture, PaymentContextKind};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let fixture = Fixture::spawn().await?;
+//! // Start a facilitator with `fixture.facilitator_chain_config()` as its `chains`.
+//! let payer = &fixture.anvil().accounts().payer;
+//! for kind in PaymentContextKind::ALL {
+//!     let payment = fixture.payment(U256::from(10_000)).await?;
+//!     let payload = payment.sign(payer, kind).await?;
+//!     let request = payment.v2_request(&payload);
+//!     // POST `request` to the facilitator's /verify and /settle.
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod anvil;
+pub mod artifacts;
+pub mod fixture;
+pub mod payload;
+
+pub use anvil::*;
+pub use artifacts::*;
+pub use fixture::*;
+pub use payload::*;
+
+/// Errors setting up a test chain.
+#[derive(Debug, thiserror::Error)]
+pub enum TestUtilsError {
+    #[error("Failed to start Anvil: {0}")]
+    AnvilSpawn(String),
+    #[error("Invalid contract artifact: {0}")]
+    Artifact(String),
+    #[error("RPC request failed: {0}")]
+    Rpc(String),
+}
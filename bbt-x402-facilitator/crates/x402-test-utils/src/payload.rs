@@ -0,0 +1,293 @@
+//! Signed "exact" scheme payloads for each payment context, and the requests carrying them.
+
+use alloy_primitives::{Address, B256, Bytes, U256};
+use x402_chain_eip155::chain::Eip155ChainReference;
+use x402_chain_eip155::v1_eip155_exact::{
+    Eip712Signer, ExactEvmPayload, ExactEvmPayloadAuthorization, PERMIT2_ADDRESS,
+    Permit2Authorization, Permit2Details, Permit2Payload, Permit2PermitSingle,
+    Permit2TokenPermissions, Permit2Witness, X402_EXACT_PERMIT2_PROXY_ADDRESS, permit_single_hash,
+    permit_witness_transfer_from_hash, permit2_allowance_domain, permit2_witness_domain_at,
+    token_domain, transfer_with_authorization_hash,
+};
+use x402_types::proto;
+use x402_types::scheme::client::X402Error;
+use x402_types::timestamp::UnixTimestamp;
+
+/// How a payer authorizes an "exact" scheme payment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentContextKind {
+    /// An ERC-3009 `TransferWithAuthorization` on the token.
+    Eip3009,
+    /// A Permit2 `PermitSingle` allowance to the facilitator.
+    Permit2,
+    /// A Permit2 `PermitWitnessTransferFrom` to the x402 Permit2 proxy.
+    Permit2Witness,
+}
+
+impl PaymentContextKind {
+    /// All payment contexts.
+    pub const ALL: [PaymentContextKind; 3] = [Self::Eip3009, Self::Permit2, Self::Permit2Witness];
+}
+
+/// What a payment pays and how long it is valid for.
+///
+/// [`Fixture::payment`](crate::Fixture::payment) fills these in for the fixture's contracts
+/// and accounts. Every field can be overridden to build invalid payloads.
+#[derive(Debug, Clone)]
+pub struct PaymentParams {
+    /// Chain the payment is for.
+    pub chain_id: u64,
+    /// Token paid in.
+    pub asset: Address,
+    /// EIP-712 domain name of the token.
+    pub token_name: String,
+    /// EIP-712 domain version of the token.
+    pub token_version: String,
+    /// Recipient of the payment.
+    pub pay_to: Address,
+    /// Amount, in the token's smallest unit.
+    pub amount: U256,
+    /// `maxTimeoutSeconds` of the requirements.
+    pub max_timeout_seconds: u64,
+    /// Start of the validity window.
+    pub valid_after: UnixTimestamp,
+    /// End of the validity window, also the Permit2 deadlines.
+    pub valid_before: UnixTimestamp,
+    /// ERC-3009 and Permit2 witness nonce; any unused value.
+    pub nonce: U256,
+    /// Permit2 allowance nonce; must be the payer's next one for the token and spender.
+    pub allowance_nonce: u64,
+    /// Spender of Permit2 allowances, one of the facilitator's signers.
+    pub spender: Address,
+    /// Permit2 contract.
+    pub permit2: Address,
+    /// x402 Permit2 proxy, spender of Permit2 witness transfers.
+    pub permit2_proxy: Address,
+}
+
+impl PaymentParams {
+    /// Parameters of a payment of `amount` of `asset` to `pay_to`, valid from ten
+    /// minutes ago for `max_timeout_seconds`, through the canonical Permit2 and proxy.
+    pub fn new(
+        chain_id: u64,
+        asset: Address,
+        token_name: impl Into<String>,
+        token_version: impl Into<String>,
+        pay_to: Address,
+        amount: U256,
+        spender: Address,
+    ) -> Self {
+        let max_timeout_seconds = 300;
+        let now = UnixTimestamp::now();
+        Self {
+            chain_id,
+            asset,
+            token_name: token_name.into(),
+            token_version: token_version.into(),
+            pay_to,
+            amount,
+            max_timeout_seconds,
+            valid_after: UnixTimestamp::from_secs(now.as_secs().saturating_sub(10 * 60)),
+            valid_before: now + max_timeout_seconds,
+            nonce: U256::ZERO,
+            allowance_nonce: 0,
+            spender,
+            permit2: PERMIT2_ADDRESS,
+            permit2_proxy: X402_EXACT_PERMIT2_PROXY_ADDRESS,
+        }
+    }
+
+    /// Signs the payment with `signer` in the given context.
+    pub async fn sign<S: Eip712Signer + Sync>(
+        &self,
+        signer: &S,
+        kind: PaymentContextKind,
+    ) -> Result<ExactEvmPayload, X402Error> {
+        let chain = Eip155ChainReference::new(self.chain_id);
+        let mut payload = ExactEvmPayload {
+            signature: None,
+            authorization: None,
+            permit2: None,
+            permit2_authorization: None,
+            stealth: None,
+        };
+        match kind {
+            PaymentContextKind::Eip3009 => {
+                let authorization = ExactEvmPayloadAuthorization {
+                    from: signer.address(),
+                    to: self.pay_to,
+                    value: self.amount,
+                    valid_after: self.valid_after,
+                    valid_before: self.valid_before,
+                    nonce: B256::from(self.nonce),
+                };
+                let domain =
+                    token_domain(&chain, self.asset, &self.token_name, &self.token_version);
+                let hash = transfer_with_authorization_hash(&authorization, &domain);
+                payload.signature = Some(signer.sign_eip712_hash(self.chain_id, &hash).await?);
+                payload.authorization = Some(authorization);
+            }
+            PaymentContextKind::Permit2 => {
+                let permit_single = Permit2PermitSingle {
+                    details: Permit2Details {
+                        token: self.asset,
+                        amount: self.amount,
+                        expiration: self.valid_before.as_secs(),
+                        nonce: self.allowance_nonce,
+                    },
+                    spender: self.spender,
+                    sig_deadline: self.valid_before.as_secs(),
+                };
+                let domain = permit2_allowance_domain(&chain, self.permit2);
+                let hash = permit_single_hash(&permit_single, &domain);
+                payload.permit2 = Some(Permit2Payload {
+                    owner: signer.address(),
+                    permit_single,
+                    signature: signer.sign_eip712_hash(self.chain_id, &hash).await?,
+                });
+            }
+            PaymentContextKind::Permit2Witness => {
+                let authorization = Permit2Authorization {
+                    from: signer.address(),
+                    permitted: Permit2TokenPermissions {
+                        token: self.asset,
+                        amount: self.amount,
+                    },
+                    spender: self.permit2_proxy,
+                    nonce: self.nonce,
+                    deadline: self.valid_before,
+                    witness: Permit2Witness {
+                        to: self.pay_to,
+                        valid_after: self.valid_after,
+                        extra: Bytes::new(),
+                    },
+                };
+                let domain = permit2_witness_domain_at(&chain, self.permit2);
+                let hash = permit_witness_transfer_from_hash(&authorization, &domain);
+                payload.signature = Some(signer.sign_eip712_hash(self.chain_id, &hash).await?);
+                payload.permit2_authorization = Some(authorization);
+            }
+        }
+        Ok(payload)
+    }
+
+    /// Payment requirements of the payment in the V2 wire format.
+    pub fn v2_requirements(&self) -> serde_json::Value {
+        serde_json::json!({
+            "scheme": "exact",
+            "network": format!("eip155:{}", self.chain_id),
+            "amount": self.amount.to_string(),
+            "payTo": self.pay_to,
+            "maxTimeoutSeconds": self.max_timeout_seconds,
+            "asset": self.asset,
+            "extra": { "name": self.token_name, "version": self.token_version }
+        })
+    }
+
+    /// Payment requirements of the payment in the V1 wire format, on the chain named `network`.
+    pub fn v1_requirements(&self, network: &str) -> serde_json::Value {
+        serde_json::json!({
+            "scheme": "exact",
+            "network": network,
+            "maxAmountRequired": self.amount.to_string(),
+            "resource": "http://localhost/resource",
+            "description": "",
+            "mimeType": "application/json",
+            "payTo": self.pay_to,
+            "maxTimeoutSeconds": self.max_timeout_seconds,
+            "asset": self.asset,
+            "extra": { "name": self.token_name, "version": self.token_version }
+        })
+    }
+
+    /// A V2 `/verify` or `/settle` request for `payload`.
+    pub fn v2_request(&self, payload: &ExactEvmPayload) -> proto::VerifyRequest {
+        let requirements = self.v2_requirements();
+        serde_json::json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "x402Version": 2,
+                "accepted": requirements,
+                "payload": payload
+            },
+            "paymentRequirements": requirements
+        })
+        .into()
+    }
+
+    /// A V1 `/verify` or `/settle` request for `payload`, on the chain named `network`.
+    ///
+    /// Local chains have no V1 name unless one is registered with
+    /// [`register_network`](x402_types::networks::register_network).
+    pub fn v1_request(&self, network: &str, payload: &ExactEvmPayload) -> proto::VerifyRequest {
+        serde_json::json!({
+            "x402Version": 1,
+            "paymentPayload": {
+                "x402Version": 1,
+                "scheme": "exact",
+                "network": network,
+                "payload": payload
+            },
+            "paymentRequirements": self.v1_requirements(network)
+        })
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Accounts;
+    use x402_chain_eip155::v1_eip155_exact::{PaymentRequirements, payment_digest};
+
+    fn params() -> PaymentParams {
+        let accounts = Accounts::default();
+        let mut params = PaymentParams::new(
+            42793,
+            Address::repeat_byte(0x33),
+            "Test USD",
+            "2",
+            accounts.pay_to.address(),
+            U256::from(1_000_000u64),
+            accounts.facilitator.address(),
+        );
+        params.nonce = U256::from(7u64);
+        params
+    }
+
+    #[tokio::test]
+    async fn test_signs_every_payment_context() {
+        let params = params();
+        let payer = Accounts::default().payer;
+        for kind in PaymentContextKind::ALL {
+            let payload = params.sign(&payer, kind).await.unwrap();
+            let request = params.v2_request(&payload);
+            let signed_by: Address = request.payer().unwrap().parse().unwrap();
+            assert_eq!(signed_by, payer.address(), "{kind:?}");
+            assert_eq!(
+                request.amount(),
+                Some(params.amount.to_string()),
+                "{kind:?}"
+            );
+        }
+
+        // The signed digests are the ones the facilitator recovers signers from.
+        let requirements: PaymentRequirements =
+            serde_json::from_value(params.v1_requirements("etherlink")).unwrap();
+        for kind in [
+            PaymentContextKind::Eip3009,
+            PaymentContextKind::Permit2Witness,
+        ] {
+            let payload = params.sign(&payer, kind).await.unwrap();
+            let digest = payment_digest(&requirements, &payload).unwrap();
+            let signature =
+                alloy_primitives::Signature::try_from(payload.signature.as_ref().unwrap().as_ref())
+                    .unwrap();
+            assert_eq!(
+                signature.recover_address_from_prehash(&digest).unwrap(),
+                payer.address(),
+                "{kind:?}"
+            );
+        }
+    }
+}
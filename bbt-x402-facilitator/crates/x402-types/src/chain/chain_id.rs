@@ -180,6 +180,22 @@ impl<'de> Deserialize<'de> for ChainId {
     }
 }
 
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for ChainId {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "ChainId".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "pattern": "^[^:]+:.+$",
+            "description": "CAIP-2 chain identifier, e.g. `eip155:42793`.",
+            "examples": ["eip155:42793"],
+        })
+    }
+}
+
 /// A pattern for matching chain IDs.
 ///
 /// Chain ID patterns allow flexible matching of blockchain networks:
@@ -400,6 +416,21 @@ impl<'de> Deserialize<'de> for ChainIdPattern {
     }
 }
 
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for ChainIdPattern {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "ChainIdPattern".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "description": "A chain (`eip155:42793`), all chains of a namespace (`eip155:*`) or a set of chains (`eip155:{1,42793}`).",
+            "examples": ["eip155:42793", "eip155:*", "eip155:{1,42793}"],
+        })
+    }
+}
+
 impl From<ChainId> for ChainIdPattern {
     fn from(chain_id: ChainId) -> Self {
         ChainIdPattern::exact(chain_id.namespace, chain_id.reference)
@@ -430,7 +461,6 @@ mod tests {
         assert_eq!(serialized, "\"eip155:1\"");
     }
 
-
     #[test]
     fn test_chain_id_deserialize_eip155() {
         let chain_id: ChainId = serde_json::from_str("\"eip155:1\"").unwrap();
@@ -438,7 +468,6 @@ mod tests {
         assert_eq!(chain_id.reference, "1");
     }
 
-
     #[test]
     fn test_chain_id_roundtrip_eip155() {
         let original = ChainId::new("eip155", "42793");
@@ -448,7 +477,6 @@ mod tests {
         assert_eq!(original, deserialized);
     }
 
-
     #[test]
     fn test_chain_id_deserialize_invalid_format() {
         let result: Result<ChainId, _> = serde_json::from_str("\"invalid\"");
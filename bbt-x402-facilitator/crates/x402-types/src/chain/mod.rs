@@ -87,7 +87,7 @@ impl<T: ChainProviderOps> ChainProviderOps for Arc<T> {
 /// // Find provider matching a pattern
 /// let any_evm = registry.by_chain_id_pattern(&ChainIdPattern::wildcard("eip155"));
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ChainRegistry<P>(HashMap<ChainId, P>);
 
 impl<P> ChainRegistry<P> {
@@ -136,6 +136,11 @@ impl<P> ChainRegistry<P> {
             .filter_map(|(chain_id, provider)| pattern.matches(chain_id).then_some(provider))
             .collect()
     }
+
+    /// Iterates over all configured chains and their providers.
+    pub fn iter(&self) -> impl Iterator<Item = (&ChainId, &P)> {
+        self.0.iter()
+    }
 }
 
 /// A token amount paired with its deployment information.
@@ -23,10 +23,31 @@
 //!   "chains": { /* chain-specific configuration */ },
 //!   "schemes": [
 //!     { "scheme": "v2-eip155-exact", "chains": ["eip155:42793"] }
-//!   ]
+//!   ],
+//!   "api_keys": [
+//!     { "id": "shop-backend", "key": "$SHOP_API_KEY", "scope": "settle" }
+//!   ],
+//!   "notifications": {
+//!     "channels": {
+//!       "ops": { "type": "slack", "webhook_url": "$SLACK_WEBHOOK_URL" }
+//!     },
+//!     "rules": [
+//!       { "channel": "ops", "min_severity": "error" }
+//!     ]
+//!   },
+//!   "amount_format": "string",
+//!   "networks": { "my-devnet": "eip155:31337" }
 //! }
 //! ```
 //!
+//! `amount_format` selects how amounts are written in responses: `"string"`
+//! (default, as the x402 specification requires) or `"number"`. See
+//! [`AmountFormat`].
+//!
+//! `networks` names chains that are not built in, so V1 payloads, which address
+//! chains by network name, can be used on them. See
+//! [`register_network`](crate::networks::register_network).
+//!
 //! # Environment Variables
 //!
 //! - `CONFIG` - Path to configuration file (default: `config.json`)
@@ -53,8 +74,17 @@
 //!
 //! - `cli` - Enables CLI argument parsing via [`clap`]. When enabled, [`Config::load()`]
 //!   parses command-line arguments to determine the config file path.
+//! - `schema` - Derives [`schemars::JsonSchema`] for the configuration types, so the
+//!   JSON Schema of a configuration file can be generated with
+//!   `schemars::schema_for!(Config<ChainsConfig>)`.
+//!
+//! # Validation Errors
+//!
+//! [`Config::load_from_path`] reports a value that does not match the format with the
+//! path to it, e.g. ``Invalid config at `schemes[0].chains`: Invalid chain id format``.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::net::IpAddr;
 use std::ops::{Deref, DerefMut};
@@ -66,6 +96,9 @@ use clap::Parser;
 #[cfg(feature = "cli")]
 use std::path::Path;
 
+use crate::chain::ChainId;
+use crate::networks::{NetworkRegistryError, register_network};
+use crate::proto::amount::AmountFormat;
 use crate::scheme::SchemeConfig;
 
 // ============================================================================
@@ -166,6 +199,20 @@ where
     }
 }
 
+#[cfg(feature = "schema")]
+impl<T> schemars::JsonSchema for LiteralOrEnv<T> {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "LiteralOrEnv".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "description": "A literal value, or a `$VAR` or `${VAR}` environment variable reference.",
+        })
+    }
+}
+
 impl<T> serde::Serialize for LiteralOrEnv<T>
 where
     T: Serialize,
@@ -188,9 +235,43 @@ pub struct CliArgs {
     /// Path to the JSON configuration file
     #[cfg_attr(
         feature = "cli",
-        arg(long, short, env = "CONFIG", default_value = "config.json")
+        arg(
+            long,
+            short,
+            env = "CONFIG",
+            default_value = "config.json",
+            global = true
+        )
     )]
     pub config: PathBuf,
+    /// Check the configuration file and exit, printing where it is invalid
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub validate_config: bool,
+    /// Print the JSON Schema of the configuration file and exit
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub config_schema: bool,
+}
+
+#[cfg(feature = "cli")]
+impl CliArgs {
+    /// Parses the command line.
+    pub fn from_args() -> Self {
+        CliArgs::parse()
+    }
+
+    /// Returns the canonical path of the configuration file given on the command line.
+    ///
+    /// Used by [`Config::load`], and by servers that re-read the file later on.
+    pub fn config_path() -> Result<PathBuf, ConfigError> {
+        CliArgs::from_args().canonical_config_path()
+    }
+
+    /// Returns the canonical path of [`CliArgs::config`].
+    pub fn canonical_config_path(&self) -> Result<PathBuf, ConfigError> {
+        Path::new(&self.config)
+            .canonicalize()
+            .map_err(|e| ConfigError::FileRead(self.config.clone(), e))
+    }
 }
 
 /// Server configuration.
@@ -198,15 +279,36 @@ pub struct CliArgs {
 /// Fields use serde defaults that fall back to environment variables,
 /// then to hardcoded defaults.
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "schema",
+    schemars(bound = "TChainsConfig: schemars::JsonSchema + Default")
+)]
 pub struct Config<TChainsConfig> {
+    /// Server port (default: `$PORT`, then 9090).
     #[serde(default = "config_defaults::default_port")]
     port: u16,
+    /// Server bind address (default: `$HOST`, then `0.0.0.0`).
     #[serde(default = "config_defaults::default_host")]
     host: IpAddr,
+    /// Chains to serve, by CAIP-2 chain identifier.
     #[serde(default)]
     chains: TChainsConfig,
+    /// Payment schemes to serve, and on which chains.
     #[serde(default)]
     schemes: Vec<SchemeConfig>,
+    /// API keys accepted by the facilitator.
+    #[serde(default)]
+    api_keys: Vec<ApiKeyConfig>,
+    /// Notification channels and routing rules.
+    #[serde(default)]
+    notifications: NotificationsConfig,
+    /// How amounts are written in responses.
+    #[serde(default)]
+    amount_format: AmountFormat,
+    /// Network names of chains that are not built in.
+    #[serde(default)]
+    networks: HashMap<String, ChainId>,
 }
 
 impl<TChainsConfig> Default for Config<TChainsConfig>
@@ -219,6 +321,10 @@ where
             host: config_defaults::default_host(),
             chains: TChainsConfig::default(),
             schemes: Vec::new(),
+            api_keys: Vec::new(),
+            notifications: NotificationsConfig::default(),
+            amount_format: AmountFormat::default(),
+            networks: HashMap::new(),
         }
     }
 }
@@ -227,10 +333,10 @@ pub mod config_defaults {
     use std::env;
     use std::net::IpAddr;
 
-pub const DEFAULT_PORT: u16 = 9090;
+    pub const DEFAULT_PORT: u16 = 9090;
     pub const DEFAULT_HOST: &str = "0.0.0.0";
 
-/// Returns the default port value with fallback: $PORT env var -> 9090
+    /// Returns the default port value with fallback: $PORT env var -> 9090
     pub fn default_port() -> u16 {
         env::var("PORT")
             .ok()
@@ -273,6 +379,212 @@ impl<TChainsConfig> Config<TChainsConfig> {
     pub fn chains(&self) -> &TChainsConfig {
         &self.chains
     }
+
+    /// Get the API keys accepted by the facilitator endpoints.
+    ///
+    /// An empty list leaves the endpoints unauthenticated.
+    pub fn api_keys(&self) -> &Vec<ApiKeyConfig> {
+        &self.api_keys
+    }
+
+    /// Get the notification channels and routing rules.
+    pub fn notifications(&self) -> &NotificationsConfig {
+        &self.notifications
+    }
+
+    /// Get the format of amounts in responses.
+    pub fn amount_format(&self) -> AmountFormat {
+        self.amount_format
+    }
+
+    /// Get the custom network names, mapped to their CAIP-2 chain identifiers.
+    pub fn networks(&self) -> &HashMap<String, ChainId> {
+        &self.networks
+    }
+
+    /// Registers the custom network names, see [`crate::networks::register_network`].
+    pub fn register_networks(&self) -> Result<(), NetworkRegistryError> {
+        for (name, chain_id) in &self.networks {
+            register_network(name, chain_id.clone())?;
+        }
+        Ok(())
+    }
+}
+
+/// Permission granted to an API key.
+///
+/// Scopes are ordered: a `settle` key may also call `/verify`, and an `admin`
+/// key may call everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyScope {
+    /// May call `/verify` only.
+    Verify,
+    /// May call `/verify` and `/settle`.
+    Settle,
+    /// May also call the `/admin` endpoints.
+    Admin,
+}
+
+impl ApiKeyScope {
+    /// Returns the scope name as used in configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::Verify => "verify",
+            ApiKeyScope::Settle => "settle",
+            ApiKeyScope::Admin => "admin",
+        }
+    }
+}
+
+impl FromStr for ApiKeyScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "verify" => Ok(ApiKeyScope::Verify),
+            "settle" => Ok(ApiKeyScope::Settle),
+            "admin" => Ok(ApiKeyScope::Admin),
+            other => Err(format!("unknown API key scope: {other}")),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiKeyScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An API key accepted by the facilitator.
+///
+/// The `key` value supports environment variable references, so secrets can stay
+/// out of the configuration file.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ApiKeyConfig {
+    /// Identity of the key holder, recorded in audit logs. Never the secret itself.
+    pub id: String,
+    /// The secret sent by clients.
+    pub key: LiteralOrEnv<String>,
+    /// Permission granted to the key.
+    pub scope: ApiKeyScope,
+}
+
+/// Where the facilitator sends notifications, and which events go where.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NotificationsConfig {
+    /// Delivery channels, by name.
+    #[serde(default)]
+    pub channels: HashMap<String, NotificationChannelConfig>,
+    /// Routing rules. An event is sent to the channel of every rule it matches.
+    #[serde(default)]
+    pub rules: Vec<NotificationRuleConfig>,
+}
+
+/// A notification delivery channel.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum NotificationChannelConfig {
+    /// JSON `POST` of the event to an HTTP endpoint.
+    Webhook { url: LiteralOrEnv<String> },
+    /// Message posted to a Slack incoming webhook.
+    Slack { webhook_url: LiteralOrEnv<String> },
+    /// Plain-text email relayed through an SMTP server, without TLS or authentication.
+    Smtp {
+        /// `host:port` of the relay.
+        server: String,
+        from: String,
+        to: Vec<String>,
+    },
+}
+
+/// Routes matching events to a channel.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NotificationRuleConfig {
+    /// Name of the channel in [`NotificationsConfig::channels`].
+    pub channel: String,
+    /// Merchant (`payTo`) address the rule applies to. Applies to all merchants if unset.
+    #[serde(default)]
+    pub merchant: Option<String>,
+    /// Event types the rule applies to. Applies to all events if empty.
+    #[serde(default)]
+    pub events: Vec<NotificationEventKind>,
+    /// Least severe event the rule applies to.
+    #[serde(default)]
+    pub min_severity: NotificationSeverity,
+}
+
+/// Type of a facilitator notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEventKind {
+    /// A settlement landed on-chain.
+    SettlementSucceeded,
+    /// A settlement was rejected because the payment did not verify.
+    SettlementRejected,
+    /// A settlement failed on-chain.
+    SettlementFailed,
+    /// A settlement exhausted its retries and was moved to the dead-letter queue.
+    SettlementDeadLettered,
+    /// A settled payment was sent back to its payer.
+    SettlementRefunded,
+}
+
+/// Severity of a facilitator notification, from least to most severe.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationSeverity {
+    #[default]
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl NotificationEventKind {
+    /// Returns the event type name as used in configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationEventKind::SettlementSucceeded => "settlement_succeeded",
+            NotificationEventKind::SettlementRejected => "settlement_rejected",
+            NotificationEventKind::SettlementFailed => "settlement_failed",
+            NotificationEventKind::SettlementDeadLettered => "settlement_dead_lettered",
+            NotificationEventKind::SettlementRefunded => "settlement_refunded",
+        }
+    }
+}
+
+impl std::fmt::Display for NotificationEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl NotificationSeverity {
+    /// Returns the severity name as used in configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationSeverity::Info => "info",
+            NotificationSeverity::Warning => "warning",
+            NotificationSeverity::Error => "error",
+            NotificationSeverity::Critical => "critical",
+        }
+    }
+}
+
+impl std::fmt::Display for NotificationSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 impl<TChainsConfig> Config<TChainsConfig>
@@ -289,17 +601,23 @@ where
     /// environment variables or defaults during deserialization.
     #[cfg(feature = "cli")]
     pub fn load() -> Result<Self, ConfigError> {
-        let cli_args = CliArgs::parse();
-        let config_path = Path::new(&cli_args.config)
-            .canonicalize()
-            .map_err(|e| ConfigError::FileRead(cli_args.config, e))?;
-        Self::load_from_path(config_path)
+        Self::load_from_path(CliArgs::config_path()?)
     }
 
     /// Load configuration from a specific path (or use defaults if None).
     pub fn load_from_path(path: PathBuf) -> Result<Self, ConfigError> {
         let content = fs::read_to_string(&path).map_err(|e| ConfigError::FileRead(path, e))?;
-        let config: Config<TChainsConfig> = serde_json::from_str(&content)?;
+        Self::from_json(&content)
+    }
+
+    /// Parses a configuration file's content.
+    ///
+    /// A value that does not match the format is reported as
+    /// [`ConfigError::Invalid`], with the path to it, e.g. `schemes[0].chains`.
+    pub fn from_json(content: &str) -> Result<Self, ConfigError> {
+        let mut deserializer = serde_json::Deserializer::from_str(content);
+        let config = serde_path_to_error::deserialize(&mut deserializer)?;
+        deserializer.end()?;
         Ok(config)
     }
 }
@@ -311,4 +629,23 @@ pub enum ConfigError {
     FileRead(PathBuf, std::io::Error),
     #[error("Failed to parse config file: {0}")]
     JsonParse(#[from] serde_json::Error),
+    /// A value at `path` does not match the configuration format.
+    #[error("Invalid config at `{path}`: {source}")]
+    Invalid {
+        path: String,
+        source: serde_json::Error,
+    },
+}
+
+impl From<serde_path_to_error::Error<serde_json::Error>> for ConfigError {
+    fn from(error: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        // Syntax errors have no path worth reporting.
+        if error.path().iter().next().is_none() || error.inner().is_syntax() {
+            return ConfigError::JsonParse(error.into_inner());
+        }
+        ConfigError::Invalid {
+            path: error.path().to_string(),
+            source: error.into_inner(),
+        }
+    }
 }
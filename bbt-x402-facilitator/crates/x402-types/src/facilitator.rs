@@ -52,6 +52,19 @@ pub trait Facilitator {
         request: &proto::SettleRequest,
     ) -> impl Future<Output = Result<proto::SettleResponse, Self::Error>> + Send;
 
+    /// Runs a settlement without broadcasting it.
+    ///
+    /// Performs the same validation and transaction simulation as [`Facilitator::settle`],
+    /// and returns the would-be transactions with their estimated cost.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if validation or simulation fails.
+    fn settle_dry_run(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> impl Future<Output = Result<proto::SettleDryRunResponse, Self::Error>> + Send;
+
     #[allow(dead_code)] // For some reason clippy believes it is not used.
     fn supported(
         &self,
@@ -75,6 +88,13 @@ impl<T: Facilitator> Facilitator for Arc<T> {
         self.as_ref().settle(request)
     }
 
+    fn settle_dry_run(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> impl Future<Output = Result<proto::SettleDryRunResponse, Self::Error>> + Send {
+        self.as_ref().settle_dry_run(request)
+    }
+
     fn supported(
         &self,
     ) -> impl Future<Output = Result<proto::SupportedResponse, Self::Error>> + Send {
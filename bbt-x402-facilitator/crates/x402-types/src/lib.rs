@@ -21,7 +21,7 @@
 //! - [`networks`] - Registry of well-known blockchain networks
 //! - [`proto`] - Wire format types for protocol messages (V1 and V2)
 //! - [`scheme`] - Payment scheme system for extensible payment methods
-//! - [`timestamp`] - Unix timestamp utilities for payment authorization windows
+//! - [`timestamp`] - Unix timestamp utilities and clocks for payment authorization windows
 //! - [`util`] - Helper types (base64, string literals, money amounts)
 //!
 //! # Protocol Versions
@@ -35,6 +35,7 @@
 //!
 //! - `cli` - Enables CLI argument parsing via clap for configuration loading
 //! - `telemetry` - Enables tracing instrumentation for debugging and monitoring
+//! - `openapi` - Derives `utoipa` schemas for the facilitator wire format, see `proto::openapi`
 
 pub mod chain;
 pub mod config;
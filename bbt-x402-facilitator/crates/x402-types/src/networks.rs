@@ -52,6 +52,7 @@
 //! - [`KNOWN_NETWORKS`]: A static array of all well-known networks
 //! - [`chain_id_by_network_name`]: Lookup function to get ChainId by network name
 //! - [`network_name_by_chain_id`]: Reverse lookup function to get network name by ChainId
+//! - [`register_network`]: Adds a custom network to the registry at runtime
 //! - [`USDC`]: Marker struct used for token deployment implementations
 //!
 //! # Namespace-Specific Traits
@@ -65,6 +66,13 @@
 //!
 //! The module supports EVM networks in the eip155 namespace.
 //!
+//! # Custom Networks
+//!
+//! Private or new chains can be given a network name at runtime with [`register_network`],
+//! which the facilitator does for the `networks` section of its configuration. Registered
+//! names are consulted by every lookup, after the built-in ones, and can be neither
+//! unregistered nor remapped.
+//!
 //! # Examples
 //!
 //! ```
@@ -87,7 +95,7 @@
 //! ```
 
 use std::collections::HashMap;
-use std::sync::LazyLock;
+use std::sync::{LazyLock, RwLock};
 
 use crate::chain::ChainId;
 
@@ -131,13 +139,11 @@ impl NetworkInfo {
 /// - Enabling convenient network name lookups via [`ChainId::from_network_name()`](crate::chain::ChainId::from_network_name)
 /// - Providing human-readable network names via [`ChainId::as_network_name()`](crate::chain::ChainId::as_network_name)
 /// - Serving as a reference for commonly used blockchain networks
-pub static KNOWN_NETWORKS: &[NetworkInfo] = &[
-    NetworkInfo {
-        name: "etherlink",
-        namespace: "eip155",
-        reference: "42793",
-    },
-];
+pub static KNOWN_NETWORKS: &[NetworkInfo] = &[NetworkInfo {
+    name: "etherlink",
+    namespace: "eip155",
+    reference: "42793",
+}];
 
 /// Lazy-initialized hashmap for network name to ChainId lookups.
 ///
@@ -243,8 +249,13 @@ pub static CHAIN_ID_TO_NAME: LazyLock<HashMap<ChainId, &'static str>> = LazyLock
 ///
 /// assert!(chain_id_by_network_name("unknown-network").is_none());
 /// ```
-pub fn chain_id_by_network_name(name: &str) -> Option<&ChainId> {
-    NAME_TO_CHAIN_ID.get(name)
+pub fn chain_id_by_network_name(name: &str) -> Option<&'static ChainId> {
+    NAME_TO_CHAIN_ID.get(name).or_else(|| {
+        let custom = CUSTOM_NETWORKS
+            .read()
+            .expect("network registry lock poisoned");
+        custom.by_name.get(name).copied()
+    })
 }
 
 /// Retrieves a network name by its ChainId.
@@ -287,7 +298,96 @@ pub fn chain_id_by_network_name(name: &str) -> Option<&ChainId> {
 /// assert!(network_name_by_chain_id(&unknown).is_none());
 /// ```
 pub fn network_name_by_chain_id(chain_id: &ChainId) -> Option<&'static str> {
-    CHAIN_ID_TO_NAME.get(chain_id).copied()
+    CHAIN_ID_TO_NAME.get(chain_id).copied().or_else(|| {
+        let custom = CUSTOM_NETWORKS
+            .read()
+            .expect("network registry lock poisoned");
+        custom.by_chain_id.get(chain_id).copied()
+    })
+}
+
+/// Networks added with [`register_network`].
+///
+/// Entries live for the rest of the process, so lookups can hand out `'static`
+/// references like they do for [`KNOWN_NETWORKS`].
+#[derive(Default)]
+struct CustomNetworks {
+    by_name: HashMap<&'static str, &'static ChainId>,
+    by_chain_id: HashMap<ChainId, &'static str>,
+}
+
+static CUSTOM_NETWORKS: LazyLock<RwLock<CustomNetworks>> = LazyLock::new(Default::default);
+
+/// Error registering a custom network.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum NetworkRegistryError {
+    /// The name already refers to another chain.
+    #[error("network name {name:?} is already registered for {chain_id}")]
+    NameTaken { name: String, chain_id: ChainId },
+    /// The chain already has another name.
+    #[error("chain {chain_id} is already registered as {name:?}")]
+    ChainNamed { chain_id: ChainId, name: String },
+}
+
+/// Registers `name` as the network name of `chain_id`.
+///
+/// Afterwards [`ChainId::from_network_name()`](crate::chain::ChainId::from_network_name)
+/// resolves `name` and [`ChainId::as_network_name()`](crate::chain::ChainId::as_network_name)
+/// returns it, so V1 payloads can be used on the chain. Registering the same mapping
+/// again is a no-op; a name or chain that already has another mapping, built-in or
+/// registered, is rejected.
+///
+/// # Examples
+///
+/// ```
+/// use x402_types::chain::ChainId;
+/// use x402_types::networks::register_network;
+///
+/// register_network("my-devnet", ChainId::new("eip155", "31337")).unwrap();
+/// assert_eq!(
+///     ChainId::from_network_name("my-devnet"),
+///     Some(ChainId::new("eip155", "31337"))
+/// );
+/// assert!(register_network("etherlink", ChainId::new("eip155", "1")).is_err());
+/// ```
+pub fn register_network(name: &str, chain_id: ChainId) -> Result<(), NetworkRegistryError> {
+    match chain_id_by_network_name(name) {
+        Some(existing) if *existing == chain_id => return Ok(()),
+        Some(existing) => {
+            return Err(NetworkRegistryError::NameTaken {
+                name: name.to_string(),
+                chain_id: existing.clone(),
+            });
+        }
+        None => {}
+    }
+    let mut custom = CUSTOM_NETWORKS
+        .write()
+        .expect("network registry lock poisoned");
+    // Checked under the write lock, as another registration may have raced this one.
+    if let Some(existing) = custom.by_name.get(name) {
+        if **existing == chain_id {
+            return Ok(());
+        }
+        return Err(NetworkRegistryError::NameTaken {
+            name: name.to_string(),
+            chain_id: (*existing).clone(),
+        });
+    }
+    let existing_name = CHAIN_ID_TO_NAME
+        .get(&chain_id)
+        .or_else(|| custom.by_chain_id.get(&chain_id));
+    if let Some(existing_name) = existing_name {
+        return Err(NetworkRegistryError::ChainNamed {
+            chain_id,
+            name: existing_name.to_string(),
+        });
+    }
+    let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+    let leaked: &'static ChainId = Box::leak(Box::new(chain_id.clone()));
+    custom.by_name.insert(name, leaked);
+    custom.by_chain_id.insert(chain_id, name);
+    Ok(())
 }
 
 /// Marker struct for USDC token deployment implementations.
@@ -328,4 +428,26 @@ mod tests {
         let unknown_chain_id = ChainId::new("eip155", "999999");
         assert!(unknown_chain_id.as_network_name().is_none());
     }
+
+    #[test]
+    fn test_register_custom_network() {
+        let devnet = ChainId::new("eip155", "424242");
+        register_network("test-devnet", devnet.clone()).unwrap();
+        register_network("test-devnet", devnet.clone()).unwrap();
+        assert_eq!(chain_id_by_network_name("test-devnet"), Some(&devnet));
+        assert_eq!(network_name_by_chain_id(&devnet), Some("test-devnet"));
+
+        assert!(matches!(
+            register_network("test-devnet", ChainId::new("eip155", "1")),
+            Err(NetworkRegistryError::NameTaken { .. })
+        ));
+        assert!(matches!(
+            register_network("test-devnet-alias", devnet),
+            Err(NetworkRegistryError::ChainNamed { .. })
+        ));
+        assert!(matches!(
+            register_network("etherlink-mainnet", ChainId::new("eip155", "42793")),
+            Err(NetworkRegistryError::ChainNamed { .. })
+        ));
+    }
 }
@@ -0,0 +1,320 @@
+//! Wire format of token amounts.
+//!
+//! The x402 specification encodes amounts as decimal strings, since most token
+//! amounts do not fit in the 53-bit integers that JavaScript numbers represent
+//! exactly. Some SDKs send JSON numbers anyway. This module makes amount fields
+//! tolerant on input and configurable on output:
+//!
+//! - [`deserialize`] accepts a decimal string or a non-negative integral JSON
+//!   number. Numbers above 2^53 are rejected instead of silently rounded, as their
+//!   exact value may already have been lost by the sender's JSON encoder.
+//! - [`serialize`] always writes a string, so whatever it writes, including
+//!   persisted state, reads back through [`deserialize`].
+//! - [`AmountFormat::apply`] rewrites the amount fields of a serialized response
+//!   as numbers for clients that need them. It is meant for the HTTP boundary only;
+//!   amounts above [`MAX_SAFE_INTEGER`] stay strings.
+//!
+//! Use [`serialize`] and [`deserialize`] on a field with
+//! `#[serde(with = "x402_types::proto::amount")]`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Price {
+//!     #[serde(with = "x402_types::proto::amount")]
+//!     amount: u128,
+//! }
+//!
+//! let price: Price = serde_json::from_str(r#"{"amount": 1000}"#).unwrap();
+//! assert_eq!(serde_json::to_string(&price).unwrap(), r#"{"amount":"1000"}"#);
+//! ```
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// Largest integer a JSON number carries exactly through an IEEE 754 double.
+pub const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// How amounts are written in responses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum AmountFormat {
+    /// Decimal strings, as the specification requires.
+    #[default]
+    String,
+    /// JSON numbers, for clients that cannot parse string amounts.
+    Number,
+}
+
+/// Object keys holding amounts in responses, rewritten by [`AmountFormat::apply`].
+const AMOUNT_KEYS: &[&str] = &[
+    "amount",
+    "maxAmountRequired",
+    "settledAmount",
+    "owed",
+    "settled",
+    "ceiling",
+    "pulled",
+    "balance",
+    "minBalance",
+    "gasPrice",
+    "estimatedCost",
+];
+
+impl AmountFormat {
+    /// Rewrites the amount fields of a serialized response in this format.
+    ///
+    /// With [`AmountFormat::Number`], decimal strings under the known amount keys
+    /// become JSON numbers, unless they exceed [`MAX_SAFE_INTEGER`] and would not
+    /// read back exactly. [`AmountFormat::String`] leaves the value as it is.
+    pub fn apply(self, value: &mut serde_json::Value) {
+        if self == AmountFormat::String {
+            return;
+        }
+        match value {
+            serde_json::Value::Object(object) => {
+                for (key, field) in object.iter_mut() {
+                    if let Some(number) = field
+                        .as_str()
+                        .filter(|_| AMOUNT_KEYS.contains(&key.as_str()))
+                        .and_then(safe_integer)
+                    {
+                        *field = number.into();
+                    } else {
+                        self.apply(field);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|item| self.apply(item)),
+            _ => {}
+        }
+    }
+}
+
+/// The canonical decimal `value` as an integer a JSON number holds exactly.
+fn safe_integer(value: &str) -> Option<u64> {
+    value
+        .parse::<u64>()
+        .ok()
+        .filter(|number| *number <= MAX_SAFE_INTEGER && number.to_string() == value)
+}
+
+/// Serializes an amount as the decimal string of its [`Display`] form.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    serializer.serialize_str(&value.to_string())
+}
+
+/// Deserializes an amount from a string or an integral JSON number.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    let decimal = deserializer.deserialize_any(AmountVisitor(PhantomData::<T>))?;
+    decimal.parse::<T>().map_err(de::Error::custom)
+}
+
+/// Collects the amount as a string, for parsing by the target type.
+struct AmountVisitor<T>(PhantomData<T>);
+
+impl<T> AmountVisitor<T> {
+    fn checked<E: de::Error>(value: u128) -> Result<String, E> {
+        if value > u128::from(MAX_SAFE_INTEGER) {
+            return Err(E::custom(format_args!(
+                "amount {value} is too large for a JSON number, send it as a string"
+            )));
+        }
+        Ok(value.to_string())
+    }
+}
+
+impl<'de, T> Visitor<'de> for AmountVisitor<T> {
+    type Value = String;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an amount as a decimal string or an integer")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<String, E> {
+        Ok(value.to_string())
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<String, E> {
+        Ok(value)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<String, E> {
+        Self::checked(u128::from(value))
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<String, E> {
+        Self::checked(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<String, E> {
+        let value = u64::try_from(value)
+            .map_err(|_| E::custom(format_args!("amount {value} is negative")))?;
+        self.visit_u64(value)
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<String, E> {
+        if value.fract() != 0.0 || value < 0.0 || value > MAX_SAFE_INTEGER as f64 {
+            return Err(E::custom(format_args!(
+                "amount {value} is not an integer a JSON number can hold exactly, send it as a string"
+            )));
+        }
+        Ok(format!("{value:.0}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Price {
+        #[serde(with = "super")]
+        amount: u128,
+    }
+
+    fn parse(value: serde_json::Value) -> Result<Price, serde_json::Error> {
+        serde_json::from_value(value)
+    }
+
+    #[test]
+    fn test_amount_deserialize() {
+        let accepted = [
+            (json!({"amount": "1000"}), 1000),
+            (json!({"amount": 1000}), 1000),
+            (json!({"amount": 0}), 0),
+            (json!({"amount": 1000.0}), 1000),
+            (
+                json!({"amount": MAX_SAFE_INTEGER}),
+                u128::from(MAX_SAFE_INTEGER),
+            ),
+            (
+                json!({"amount": "340282366920938463463374607431768211455"}),
+                u128::MAX,
+            ),
+        ];
+        for (value, amount) in accepted {
+            assert_eq!(parse(value.clone()).unwrap(), Price { amount }, "{value}");
+        }
+
+        let rejected = [
+            json!({"amount": -1}),
+            json!({"amount": 1.5}),
+            json!({"amount": MAX_SAFE_INTEGER + 1}),
+            json!({"amount": 1e30}),
+            json!({"amount": "1.5"}),
+            json!({"amount": "-1"}),
+            json!({"amount": ""}),
+            json!({"amount": true}),
+            json!({"amount": null}),
+        ];
+        for value in rejected {
+            assert!(parse(value.clone()).is_err(), "{value}");
+        }
+        // Parsed from text rather than a `Value`, large numbers take the `u128`/`f64` paths.
+        assert!(serde_json::from_str::<Price>(r#"{"amount": 100000000000000000000}"#).is_err());
+        assert_eq!(
+            serde_json::from_str::<Price>(r#"{"amount": 1000}"#)
+                .unwrap()
+                .amount,
+            1000
+        );
+    }
+
+    #[test]
+    fn test_amount_serialize() {
+        let price = Price { amount: u128::MAX };
+        assert_eq!(
+            serde_json::to_value(&price).unwrap(),
+            json!({"amount": u128::MAX.to_string()})
+        );
+
+        let format: AmountFormat = serde_json::from_value(json!("number")).unwrap();
+        assert_eq!(format, AmountFormat::Number);
+    }
+
+    #[test]
+    fn test_amount_format_apply() {
+        let response = json!({
+            "settledAmount": "5",
+            "amount": MAX_SAFE_INTEGER.to_string(),
+            "tabs": [
+                { "owed": (MAX_SAFE_INTEGER + 1).to_string(), "settled": "1000000000000000000" },
+                { "ceiling": "007", "balance": "0x10", "pulled": "12" }
+            ],
+            "network": "12",
+        });
+
+        let mut unchanged = response.clone();
+        AmountFormat::String.apply(&mut unchanged);
+        assert_eq!(unchanged, response);
+
+        let mut numbers = response;
+        AmountFormat::Number.apply(&mut numbers);
+        assert_eq!(
+            numbers,
+            json!({
+                "settledAmount": 5,
+                "amount": MAX_SAFE_INTEGER,
+                "tabs": [
+                    { "owed": (MAX_SAFE_INTEGER + 1).to_string(), "settled": "1000000000000000000" },
+                    { "ceiling": "007", "balance": "0x10", "pulled": 12 }
+                ],
+                "network": "12",
+            })
+        );
+        // Every rewritten amount reads back exactly.
+        let amount: Price = serde_json::from_value(json!({"amount": numbers["amount"]})).unwrap();
+        assert_eq!(amount.amount, u128::from(MAX_SAFE_INTEGER));
+    }
+
+    #[test]
+    fn test_payment_requirements_amount() {
+        let requirements: crate::proto::v2::PaymentRequirements = serde_json::from_value(json!({
+            "scheme": "exact",
+            "network": "eip155:42793",
+            "amount": 10000,
+            "payTo": "0x1111111111111111111111111111111111111111",
+            "maxTimeoutSeconds": 60,
+            "asset": "0x2222222222222222222222222222222222222222"
+        }))
+        .unwrap();
+        assert_eq!(requirements.amount, "10000");
+        assert_eq!(
+            serde_json::to_value(&requirements).unwrap()["amount"],
+            "10000"
+        );
+
+        let requirements: crate::proto::v1::PaymentRequirements<String, u128> =
+            serde_json::from_value(json!({
+                "scheme": "exact",
+                "network": "etherlink",
+                "maxAmountRequired": "10000",
+                "resource": "https://example.com",
+                "description": "",
+                "mimeType": "application/json",
+                "payTo": "0x1111111111111111111111111111111111111111",
+                "maxTimeoutSeconds": 60,
+                "asset": "0x2222222222222222222222222222222222222222"
+            }))
+            .unwrap();
+        assert_eq!(requirements.max_amount_required, 10000);
+    }
+}
@@ -0,0 +1,289 @@
+//! Conversions between V1 and V2 payments.
+//!
+//! V1 and V2 carry the same payment under different shapes: V1 names the network
+//! (`"etherlink"`) and puts the resource metadata in the requirements, while V2 uses
+//! CAIP-2 chain IDs (`"eip155:42793"`), moves the resource metadata to
+//! [`v2::ResourceInfo`] and embeds the accepted requirements in the payload. The
+//! signed scheme payload itself is the same in both versions and is passed through
+//! untouched.
+//!
+//! - Upgrading to V2 is lossless, apart from the V1 `outputSchema`, which V2 has no
+//!   place for and which plays no part in verification.
+//! - Downgrading to V1 fails when the chain has no V1 network name, or when the
+//!   payload's accepted requirements differ from the requirements, which V1 can not
+//!   express.
+//!
+//! [`VerifyRequest::to_v2`] and [`VerifyRequest::to_v1`] convert whole facilitator
+//! requests, keeping their other members (`dryRun`, `settleAmount`, ...). A
+//! facilitator can use them to serve V1 payments with V2 scheme handlers.
+
+use serde_json::Value;
+use serde_json::value::RawValue;
+use std::str::FromStr;
+
+use crate::chain::ChainId;
+use crate::proto::{VerifyRequest, v1, v2};
+
+/// Why a payment could not be converted to the other protocol version.
+#[derive(Debug, thiserror::Error)]
+pub enum VersionConversionError {
+    #[error("Invalid request: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unsupported x402Version")]
+    UnsupportedVersion,
+    #[error("Unknown network {0}")]
+    UnknownNetwork(String),
+    #[error("Chain {0} has no V1 network name")]
+    NoNetworkName(ChainId),
+    #[error("Accepted requirements differ from the payment requirements")]
+    AcceptedMismatch,
+}
+
+/// Resolves a V1 network, by name or, as some servers send, as a CAIP-2 ID.
+fn chain_id(network: &str) -> Result<ChainId, VersionConversionError> {
+    ChainId::from_network_name(network)
+        .or_else(|| ChainId::from_str(network).ok())
+        .ok_or_else(|| VersionConversionError::UnknownNetwork(network.to_string()))
+}
+
+fn network_name(chain_id: &ChainId) -> Result<String, VersionConversionError> {
+    chain_id
+        .as_network_name()
+        .map(str::to_string)
+        .ok_or_else(|| VersionConversionError::NoNetworkName(chain_id.clone()))
+}
+
+/// Splits V1 requirements into V2 requirements and the resource they are for.
+pub fn requirements_to_v2(
+    requirements: &v1::PaymentRequirements,
+) -> Result<(v2::PaymentRequirements, v2::ResourceInfo), VersionConversionError> {
+    let resource = v2::ResourceInfo {
+        description: requirements.description.clone(),
+        mime_type: requirements.mime_type.clone(),
+        url: requirements.resource.clone(),
+    };
+    let requirements = v2::PaymentRequirements {
+        scheme: requirements.scheme.clone(),
+        network: chain_id(&requirements.network)?,
+        amount: requirements.max_amount_required.clone(),
+        pay_to: requirements.pay_to.clone(),
+        max_timeout_seconds: requirements.max_timeout_seconds,
+        asset: requirements.asset.clone(),
+        extra: requirements.extra.clone(),
+    };
+    Ok((requirements, resource))
+}
+
+/// Joins V2 requirements and the resource they are for into V1 requirements.
+///
+/// Without a resource, the V1 resource fields are left empty.
+pub fn requirements_to_v1(
+    requirements: &v2::PaymentRequirements,
+    resource: Option<&v2::ResourceInfo>,
+) -> Result<v1::PaymentRequirements, VersionConversionError> {
+    Ok(v1::PaymentRequirements {
+        scheme: requirements.scheme.clone(),
+        network: network_name(&requirements.network)?,
+        max_amount_required: requirements.amount.clone(),
+        resource: resource.map(|r| r.url.clone()).unwrap_or_default(),
+        description: resource.map(|r| r.description.clone()).unwrap_or_default(),
+        mime_type: resource.map(|r| r.mime_type.clone()).unwrap_or_default(),
+        output_schema: None,
+        pay_to: requirements.pay_to.clone(),
+        max_timeout_seconds: requirements.max_timeout_seconds,
+        asset: requirements.asset.clone(),
+        extra: requirements.extra.clone(),
+    })
+}
+
+/// Upgrades a V1 payment and the requirements it pays for to V2.
+///
+/// The accepted requirements embedded in the payload take their scheme and network
+/// from the V1 payload, so a payload signed for other terms still fails verification.
+pub fn payment_to_v2<TPayload>(
+    payload: v1::PaymentPayload<String, TPayload>,
+    requirements: &v1::PaymentRequirements,
+) -> Result<
+    (
+        v2::PaymentPayload<v2::PaymentRequirements, TPayload>,
+        v2::PaymentRequirements,
+    ),
+    VersionConversionError,
+> {
+    let (requirements, resource) = requirements_to_v2(requirements)?;
+    let accepted = v2::PaymentRequirements {
+        scheme: payload.scheme,
+        network: chain_id(&payload.network)?,
+        ..requirements.clone()
+    };
+    let payload = v2::PaymentPayload {
+        accepted,
+        payload: payload.payload,
+        resource: Some(resource),
+        x402_version: v2::X402Version2,
+    };
+    Ok((payload, requirements))
+}
+
+/// Downgrades a V2 payment and the requirements it pays for to V1.
+pub fn payment_to_v1<TPayload>(
+    payload: v2::PaymentPayload<v2::PaymentRequirements, TPayload>,
+    requirements: &v2::PaymentRequirements,
+) -> Result<
+    (
+        v1::PaymentPayload<String, TPayload>,
+        v1::PaymentRequirements,
+    ),
+    VersionConversionError,
+> {
+    if &payload.accepted != requirements {
+        return Err(VersionConversionError::AcceptedMismatch);
+    }
+    let requirements = requirements_to_v1(requirements, payload.resource.as_ref())?;
+    let payload = v1::PaymentPayload {
+        x402_version: v1::X402Version1,
+        scheme: payload.accepted.scheme,
+        network: requirements.network.clone(),
+        payload: payload.payload,
+    };
+    Ok((payload, requirements))
+}
+
+type V1Request =
+    v1::VerifyRequest<v1::PaymentPayload<String, Box<RawValue>>, v1::PaymentRequirements>;
+type V2Request = v2::VerifyRequest<
+    v2::PaymentPayload<v2::PaymentRequirements, Box<RawValue>>,
+    v2::PaymentRequirements,
+>;
+
+impl VerifyRequest {
+    /// Returns the request in V2 form, converting it if it is a V1 request.
+    pub fn to_v2(&self) -> Result<Self, VersionConversionError> {
+        let request = self.clone().with_decoded_payment_payload();
+        match self.x402_version() {
+            Some(v2::X402Version2::VALUE) => Ok(request),
+            Some(v1::X402Version1::VALUE) => {
+                let v1: V1Request = serde_json::from_str(request.as_raw().get())?;
+                let (payment_payload, payment_requirements) =
+                    payment_to_v2(v1.payment_payload, &v1.payment_requirements)?;
+                let v2 = V2Request {
+                    x402_version: v2::X402Version2,
+                    payment_payload,
+                    payment_requirements,
+                };
+                Ok(request.with_members(serde_json::to_value(v2)?))
+            }
+            _ => Err(VersionConversionError::UnsupportedVersion),
+        }
+    }
+
+    /// Returns the request in V1 form, converting it if it is a V2 request.
+    pub fn to_v1(&self) -> Result<Self, VersionConversionError> {
+        let request = self.clone().with_decoded_payment_payload();
+        match self.x402_version() {
+            Some(v1::X402Version1::VALUE) => Ok(request),
+            Some(v2::X402Version2::VALUE) => {
+                let v2: V2Request = serde_json::from_str(request.as_raw().get())?;
+                let (payment_payload, payment_requirements) =
+                    payment_to_v1(v2.payment_payload, &v2.payment_requirements)?;
+                let v1 = V1Request {
+                    x402_version: v1::X402Version1,
+                    payment_payload,
+                    payment_requirements,
+                };
+                Ok(request.with_members(serde_json::to_value(v1)?))
+            }
+            _ => Err(VersionConversionError::UnsupportedVersion),
+        }
+    }
+
+    /// Replaces the members of the request that `converted` has, keeping the others.
+    fn with_members(self, converted: Value) -> Self {
+        let mut json = self.into_json();
+        if let (Some(object), Value::Object(converted)) = (json.as_object_mut(), converted) {
+            object.extend(converted);
+        }
+        json.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v1_request() -> VerifyRequest {
+        VerifyRequest::from(serde_json::json!({
+            "x402Version": 1,
+            "paymentPayload": {
+                "x402Version": 1,
+                "scheme": "exact",
+                "network": "etherlink",
+                "payload": {"signature": "0xabcd", "authorization": {"from": "0x2222222222222222222222222222222222222222"}}
+            },
+            "paymentRequirements": {
+                "scheme": "exact",
+                "network": "etherlink",
+                "maxAmountRequired": "10000",
+                "resource": "https://example.com/weather",
+                "description": "Weather report",
+                "mimeType": "application/json",
+                "payTo": "0x1111111111111111111111111111111111111111",
+                "maxTimeoutSeconds": 60,
+                "asset": "0x3333333333333333333333333333333333333333",
+                "extra": {"name": "BBT", "version": "1"}
+            },
+            "settleAmount": "5000"
+        }))
+    }
+
+    #[test]
+    fn test_request_round_trip() {
+        let v2 = v1_request().to_v2().unwrap();
+        assert_eq!(
+            v2.scheme_handler_slug().unwrap().to_string(),
+            "eip155:42793:v2:exact"
+        );
+        assert_eq!(v2.payer(), v1_request().payer());
+        assert_eq!(v2.amount().as_deref(), Some("10000"));
+        assert_eq!(v2.settle_amount().as_deref(), Some("5000"));
+        let json = v2.clone().into_json();
+        assert_eq!(
+            json["paymentPayload"]["accepted"],
+            json["paymentRequirements"]
+        );
+        assert_eq!(
+            json["paymentPayload"]["resource"]["url"],
+            "https://example.com/weather"
+        );
+        assert_eq!(json["paymentPayload"]["payload"]["signature"], "0xabcd");
+
+        let v1 = v2.to_v1().unwrap();
+        assert_eq!(v1.into_json(), v1_request().into_json());
+    }
+
+    #[test]
+    fn test_downgrade_limits() {
+        let v2 = v1_request().to_v2().unwrap();
+        let mut json = v2.into_json();
+        json["paymentPayload"]["accepted"]["amount"] = "1".into();
+        assert!(matches!(
+            VerifyRequest::from(json.clone()).to_v1(),
+            Err(VersionConversionError::AcceptedMismatch)
+        ));
+
+        json["paymentPayload"]["accepted"]["amount"] = "10000".into();
+        json["paymentPayload"]["accepted"]["network"] = "eip155:1".into();
+        json["paymentRequirements"]["network"] = "eip155:1".into();
+        assert!(matches!(
+            VerifyRequest::from(json).to_v1(),
+            Err(VersionConversionError::NoNetworkName(_))
+        ));
+
+        let mut json = v1_request().into_json();
+        json["paymentRequirements"]["network"] = "nowhere".into();
+        assert!(matches!(
+            VerifyRequest::from(json).to_v2(),
+            Err(VersionConversionError::UnknownNetwork(_))
+        ));
+    }
+}
@@ -0,0 +1,164 @@
+//! Display metadata for rendering payment prompts.
+//!
+//! Payment requirements carry amounts in the token's smallest unit and assets as
+//! contract addresses, which wallets and UIs would have to resolve with separate
+//! token lookups before showing a prompt. Servers can add [`DisplayMetadata`] to
+//! the requirements `extra` (under `display`) instead: the token symbol and logo,
+//! the amount formatted for a locale, and its fiat equivalent at quote time.
+//!
+//! ```json
+//! "display": {
+//!   "symbol": "USDC",
+//!   "logoUri": "https://example.com/usdc.svg",
+//!   "locale": "de-DE",
+//!   "formattedAmount": "1.234,5 USDC",
+//!   "fiat": { "currency": "USD", "amount": "1234.50", "quotedAt": 1760000000 }
+//! }
+//! ```
+//!
+//! The metadata is informational only: facilitators ignore it, and clients must
+//! sign for the requirements `amount` and `asset`, never for what is displayed.
+
+use alloy_primitives::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::timestamp::UnixTimestamp;
+
+/// How to present a price to a human.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayMetadata {
+    /// Token symbol, e.g. `USDC`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// URI of the token logo.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo_uri: Option<String>,
+    /// BCP 47 locale the amount is formatted for, e.g. `de-DE`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// The amount in whole tokens, formatted for `locale`, with the symbol.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted_amount: Option<String>,
+    /// Fiat value of the amount when the price was quoted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fiat: Option<FiatEquivalent>,
+}
+
+impl DisplayMetadata {
+    /// Metadata for a token with the given symbol.
+    pub fn new<S: Into<String>>(symbol: S) -> Self {
+        Self {
+            symbol: Some(symbol.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the token logo URI.
+    pub fn with_logo_uri<S: Into<String>>(mut self, logo_uri: S) -> Self {
+        self.logo_uri = Some(logo_uri.into());
+        self
+    }
+
+    /// Sets the locale amounts are formatted for.
+    pub fn with_locale<S: Into<String>>(mut self, locale: S) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// Sets the fiat equivalent of the amount.
+    pub fn with_fiat(mut self, fiat: FiatEquivalent) -> Self {
+        self.fiat = Some(fiat);
+        self
+    }
+
+    /// Sets the formatted amount from an amount in the token's smallest unit, using
+    /// [`format_token_amount`] with the locale and symbol of the metadata.
+    pub fn with_amount(mut self, amount: U256, decimals: u8) -> Self {
+        let number = format_token_amount(amount, decimals, self.locale.as_deref());
+        self.formatted_amount = Some(match &self.symbol {
+            Some(symbol) => format!("{number} {symbol}"),
+            None => number,
+        });
+        self
+    }
+}
+
+/// Fiat value of a price when it was quoted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FiatEquivalent {
+    /// ISO 4217 currency code, e.g. `USD`.
+    pub currency: String,
+    /// Decimal amount with a `.` separator, e.g. `0.05`.
+    pub amount: String,
+    /// When the exchange rate was applied.
+    pub quoted_at: UnixTimestamp,
+}
+
+/// Formats an amount in a token's smallest unit as whole tokens, e.g. `1234500000`
+/// with 6 decimals as `1,234.5`, or `1.234,5` for a German locale.
+///
+/// Trailing fractional zeros are dropped. Separators follow the language of the
+/// BCP 47 `locale`; unknown or missing locales use English separators.
+pub fn format_token_amount(amount: U256, decimals: u8, locale: Option<&str>) -> String {
+    let (group_separator, decimal_separator) = separators(locale);
+    let unit = U256::from(10).pow(U256::from(decimals));
+    let integer = (amount / unit).to_string();
+    let fraction = format!(
+        "{:0>width$}",
+        (amount % unit).to_string(),
+        width = decimals as usize
+    );
+    let fraction = fraction.trim_end_matches('0');
+
+    let mut formatted = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i).is_multiple_of(3) {
+            formatted.push_str(group_separator);
+        }
+        formatted.push(digit);
+    }
+    if !fraction.is_empty() {
+        formatted.push(decimal_separator);
+        formatted.push_str(fraction);
+    }
+    formatted
+}
+
+/// Group and decimal separators for the language of a locale.
+fn separators(locale: Option<&str>) -> (&'static str, char) {
+    let language = locale
+        .and_then(|locale| locale.split(['-', '_']).next())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match language.as_str() {
+        "da" | "de" | "el" | "es" | "id" | "it" | "nl" | "pt" | "tr" => (".", ','),
+        "cs" | "fi" | "fr" | "hu" | "nb" | "no" | "pl" | "ru" | "sk" | "sv" | "uk" => {
+            ("\u{a0}", ',')
+        }
+        _ => (",", '.'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_token_amount() {
+        let amount = U256::from(1_234_500_000u64);
+        assert_eq!(format_token_amount(amount, 6, None), "1,234.5");
+        assert_eq!(format_token_amount(amount, 6, Some("de-DE")), "1.234,5");
+        assert_eq!(format_token_amount(amount, 6, Some("fr")), "1\u{a0}234,5");
+        assert_eq!(format_token_amount(U256::from(50_000u64), 6, None), "0.05");
+        assert_eq!(format_token_amount(U256::from(7u64), 0, None), "7");
+
+        let display = DisplayMetadata::new("USDC")
+            .with_locale("de-DE")
+            .with_amount(amount, 6);
+        let json = serde_json::to_value(&display).unwrap();
+        assert_eq!(json["formattedAmount"], "1.234,5 USDC");
+        assert!(json.get("logoUri").is_none());
+    }
+}
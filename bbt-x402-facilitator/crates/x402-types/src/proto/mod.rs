@@ -17,20 +17,34 @@
 //! - [`SettleRequest`] / [`SettleResponse`] - Payment settlement messages
 //! - [`PaymentVerificationError`] - Errors that can occur during verification
 //! - [`PaymentProblem`] - Structured error response for payment failures
+//! - [`payment_required::ParsedPaymentRequired`] - Validated, typed view of a 402 response
+//! - [`display::DisplayMetadata`] - Symbol, logo and formatted amounts for payment prompts
+//! - [`compat`] - Conversion of payments between V1 and V2
 //!
 //! # Wire Format
 //!
 //! All types serialize to JSON using camelCase field names. The protocol version
-//! is indicated by the `x402Version` field in payment payloads.
+//! is indicated by the `x402Version` field in payment payloads. Amounts are
+//! decimal strings; [`amount`] also accepts JSON numbers from clients that send them.
 
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 use serde_with::{VecSkipError, serde_as};
 use std::collections::HashMap;
 use std::str::FromStr;
 
 use crate::chain::ChainId;
 use crate::scheme::SchemeHandlerSlug;
+use crate::timestamp::UnixTimestamp;
+use crate::util::Base64Bytes;
 
+pub mod amount;
+pub mod compat;
+pub mod display;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod payment_required;
+pub mod transport;
 pub mod util;
 pub mod v1;
 pub mod v2;
@@ -62,6 +76,34 @@ where
     V2(T::V2),
 }
 
+impl<T> Clone for ProtocolVersioned<T>
+where
+    T: ProtocolV,
+    T::V1: Clone,
+    T::V2: Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            ProtocolVersioned::V1(v1) => ProtocolVersioned::V1(v1.clone()),
+            ProtocolVersioned::V2(v2) => ProtocolVersioned::V2(v2.clone()),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for ProtocolVersioned<T>
+where
+    T: ProtocolV,
+    T::V1: std::fmt::Debug,
+    T::V2: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProtocolVersioned::V1(v1) => f.debug_tuple("V1").field(v1).finish(),
+            ProtocolVersioned::V2(v2) => f.debug_tuple("V2").field(v2).finish(),
+        }
+    }
+}
+
 /// Describes a payment method supported by a facilitator.
 ///
 /// This type is returned in the [`SupportedResponse`] to indicate what
@@ -77,6 +119,7 @@ where
 /// }
 /// ```
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct SupportedPaymentKind {
     /// The x402 protocol version (1 or 2).
@@ -87,6 +130,7 @@ pub struct SupportedPaymentKind {
     pub network: String,
     /// Optional scheme-specific extra data.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Object>))]
     pub extra: Option<serde_json::Value>,
 }
 
@@ -110,6 +154,7 @@ pub struct SupportedPaymentKind {
 /// ```
 #[serde_as]
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "camelCase")]
 #[allow(dead_code)] // Public for consumption by downstream crates.
 pub struct SupportedResponse {
@@ -121,6 +166,7 @@ pub struct SupportedResponse {
     pub extensions: Vec<String>,
     /// Map of chain IDs to signer addresses for that chain.
     #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(value_type = HashMap<String, Vec<String>>))]
     pub signers: HashMap<ChainId, Vec<String>>,
 }
 
@@ -130,9 +176,16 @@ pub struct SupportedResponse {
 /// to a facilitator for verification. The facilitator checks that the payment
 /// authorization is valid, properly signed, and matches the requirements.
 ///
-/// The inner JSON structure varies by protocol version and scheme.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VerifyRequest(serde_json::Value);
+/// The inner JSON structure varies by protocol version and scheme. It is kept as
+/// the raw JSON text it was received as: scheme handlers deserialize their typed
+/// request straight from it (see [`v1::VerifyRequest::from_proto`]), without an
+/// intermediate [`serde_json::Value`] tree. The few fields needed for routing and
+/// screening are extracted once, when the request is constructed.
+#[derive(Debug, Clone)]
+pub struct VerifyRequest {
+    raw: Box<RawValue>,
+    summary: RequestSummary,
+}
 
 /// Request to settle a verified payment on-chain.
 ///
@@ -140,16 +193,200 @@ pub struct VerifyRequest(serde_json::Value);
 /// payload that was previously verified.
 pub type SettleRequest = VerifyRequest;
 
+/// How long `/settle` waits for the settlement transaction, from the
+/// `"confirmationPolicy"` request member.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum ConfirmationPolicy {
+    /// Wait for the number of confirmations the chain is configured to require.
+    #[default]
+    Confirmed,
+    /// Respond as soon as the transaction is in a block. The response reports a
+    /// pending status while fewer confirmations than required have been seen.
+    Pending,
+}
+
+/// Routing and screening fields of a [`VerifyRequest`].
+#[derive(Debug, Clone, Default)]
+struct RequestSummary {
+    x402_version: Option<u8>,
+    slug: Option<SchemeHandlerSlug>,
+    payer: Option<String>,
+    payee: Option<String>,
+    amount: Option<String>,
+    signed_amount: Option<String>,
+    asset: Option<String>,
+    payload_is_encoded: bool,
+    dry_run: bool,
+    confirmation_policy: ConfirmationPolicy,
+    settle_amount: Option<String>,
+}
+
+/// A JSON object with its members left unparsed.
+///
+/// Keys are owned so that escaped keys such as `"\u0070ayer"` are unescaped and
+/// found under the member name serde reads them as.
+type RawObject<'a> = HashMap<String, &'a RawValue>;
+
+fn raw_object(raw: &RawValue) -> Option<RawObject<'_>> {
+    serde_json::from_str(raw.get()).ok()
+}
+
+fn raw_member<'a>(object: Option<&RawObject<'a>>, key: &str) -> Option<&'a RawValue> {
+    object?.get(key).copied()
+}
+
+fn raw_string(raw: Option<&RawValue>) -> Option<String> {
+    serde_json::from_str(raw?.get()).ok()
+}
+
+fn raw_amount(raw: Option<&RawValue>) -> Option<String> {
+    let mut deserializer = serde_json::Deserializer::from_str(raw?.get());
+    amount::deserialize(&mut deserializer).ok()
+}
+
+impl RequestSummary {
+    fn extract(raw: &RawValue) -> Self {
+        let Some(root) = raw_object(raw) else {
+            return Self::default();
+        };
+        let payment_payload_raw = raw_member(Some(&root), "paymentPayload");
+        let payment_payload = payment_payload_raw.and_then(raw_object);
+        let requirements = raw_member(Some(&root), "paymentRequirements").and_then(raw_object);
+        let accepted = raw_member(payment_payload.as_ref(), "accepted").and_then(raw_object);
+        let payload = raw_member(payment_payload.as_ref(), "payload").and_then(raw_object);
+
+        let x402_version = raw_member(Some(&root), "x402Version")
+            .and_then(|raw| serde_json::from_str::<u64>(raw.get()).ok())
+            .and_then(|version| u8::try_from(version).ok());
+        let slug = match x402_version {
+            Some(v1::X402Version1::VALUE) => {
+                let network = raw_string(raw_member(payment_payload.as_ref(), "network"));
+                let scheme = raw_string(raw_member(payment_payload.as_ref(), "scheme"));
+                network
+                    .and_then(|network| ChainId::from_network_name(&network))
+                    .zip(scheme)
+                    .map(|(chain_id, scheme)| SchemeHandlerSlug::new(chain_id, 1, scheme))
+            }
+            Some(v2::X402Version2::VALUE) => {
+                let network = raw_string(raw_member(accepted.as_ref(), "network"));
+                let scheme = raw_string(raw_member(accepted.as_ref(), "scheme"));
+                network
+                    .and_then(|network| ChainId::from_str(&network).ok())
+                    .zip(scheme)
+                    .map(|(chain_id, scheme)| SchemeHandlerSlug::new(chain_id, 2, scheme))
+            }
+            _ => None,
+        };
+
+        let payer = raw_string(raw_member(
+            raw_member(payload.as_ref(), "authorization")
+                .and_then(raw_object)
+                .as_ref(),
+            "from",
+        ))
+        .or_else(|| {
+            let permit2 = raw_member(payload.as_ref(), "permit2").and_then(raw_object);
+            raw_string(raw_member(permit2.as_ref(), "owner"))
+        })
+        .or_else(|| {
+            let authorization =
+                raw_member(payload.as_ref(), "permit2Authorization").and_then(raw_object);
+            raw_string(raw_member(authorization.as_ref(), "from"))
+        })
+        .map(|address| address.to_lowercase());
+        let payee = raw_string(raw_member(requirements.as_ref(), "payTo"))
+            .or_else(|| raw_string(raw_member(accepted.as_ref(), "payTo")))
+            .map(|address| address.to_lowercase());
+        let amount = raw_amount(raw_member(requirements.as_ref(), "maxAmountRequired"))
+            .or_else(|| raw_amount(raw_member(requirements.as_ref(), "amount")));
+        let signed_amount = raw_amount(raw_member(
+            raw_member(payload.as_ref(), "authorization")
+                .and_then(raw_object)
+                .as_ref(),
+            "value",
+        ))
+        .or_else(|| {
+            let permit2 = raw_member(payload.as_ref(), "permit2").and_then(raw_object);
+            let permit = raw_member(permit2.as_ref(), "permitSingle").and_then(raw_object);
+            let details = raw_member(permit.as_ref(), "details").and_then(raw_object);
+            raw_amount(raw_member(details.as_ref(), "amount"))
+        })
+        .or_else(|| {
+            let authorization =
+                raw_member(payload.as_ref(), "permit2Authorization").and_then(raw_object);
+            let permitted = raw_member(authorization.as_ref(), "permitted").and_then(raw_object);
+            raw_amount(raw_member(permitted.as_ref(), "amount"))
+        });
+        let asset = raw_string(raw_member(requirements.as_ref(), "asset"))
+            .map(|asset| asset.to_lowercase());
+        let payload_is_encoded = payment_payload_raw.is_some_and(|raw| raw.get().starts_with('"'));
+        let dry_run = raw_member(Some(&root), "dryRun")
+            .and_then(|raw| serde_json::from_str::<bool>(raw.get()).ok())
+            .unwrap_or(false);
+        let confirmation_policy = raw_member(Some(&root), "confirmationPolicy")
+            .and_then(|raw| serde_json::from_str::<ConfirmationPolicy>(raw.get()).ok())
+            .unwrap_or_default();
+        let settle_amount = raw_amount(raw_member(Some(&root), "settleAmount"));
+
+        Self {
+            x402_version,
+            slug,
+            payer,
+            payee,
+            amount,
+            signed_amount,
+            asset,
+            payload_is_encoded,
+            dry_run,
+            confirmation_policy,
+            settle_amount,
+        }
+    }
+}
+
+impl From<Box<RawValue>> for VerifyRequest {
+    fn from(raw: Box<RawValue>) -> Self {
+        let summary = RequestSummary::extract(&raw);
+        Self { raw, summary }
+    }
+}
+
 impl From<serde_json::Value> for VerifyRequest {
     fn from(value: serde_json::Value) -> Self {
-        Self(value)
+        serde_json::value::to_raw_value(&value)
+            .expect("serializing a JSON value cannot fail")
+            .into()
+    }
+}
+
+impl Serialize for VerifyRequest {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.raw.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for VerifyRequest {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Box::<RawValue>::deserialize(deserializer).map(Self::from)
     }
 }
 
 impl VerifyRequest {
     /// Consumes the request and returns the inner JSON value.
     pub fn into_json(self) -> serde_json::Value {
-        self.0
+        serde_json::from_str(self.raw.get()).expect("raw request is valid JSON")
+    }
+
+    /// Returns the request as raw JSON text.
+    pub fn as_raw(&self) -> &RawValue {
+        &self.raw
+    }
+
+    /// Returns the protocol version of the request (`"x402Version"`), when present.
+    pub fn x402_version(&self) -> Option<u8> {
+        self.summary.x402_version
     }
 
     /// Extracts the scheme handler slug from the request.
@@ -159,34 +396,7 @@ impl VerifyRequest {
     ///
     /// Returns `None` if the request format is invalid or the scheme is unknown.
     pub fn scheme_handler_slug(&self) -> Option<SchemeHandlerSlug> {
-        let x402_version: u8 = self.0.get("x402Version")?.as_u64()?.try_into().ok()?;
-        match x402_version {
-            v1::X402Version1::VALUE => {
-                let network_name = self.0.get("paymentPayload")?.get("network")?.as_str()?;
-                let chain_id = ChainId::from_network_name(network_name)?;
-                let scheme = self.0.get("paymentPayload")?.get("scheme")?.as_str()?;
-                let slug = SchemeHandlerSlug::new(chain_id, 1, scheme.into());
-                Some(slug)
-            }
-            v2::X402Version2::VALUE => {
-                let chain_id_string = self
-                    .0
-                    .get("paymentPayload")?
-                    .get("accepted")?
-                    .get("network")?
-                    .as_str()?;
-                let chain_id = ChainId::from_str(chain_id_string).ok()?;
-                let scheme = self
-                    .0
-                    .get("paymentPayload")?
-                    .get("accepted")?
-                    .get("scheme")?
-                    .as_str()?;
-                let slug = SchemeHandlerSlug::new(chain_id, 2, scheme.into());
-                Some(slug)
-            }
-            _ => None,
-        }
+        self.summary.slug.clone()
     }
 
     /// Returns the payer address from the signed payload, when present.
@@ -196,18 +406,7 @@ impl VerifyRequest {
     /// - V1 and V2 Permit2 payer from `permit2.owner`
     /// - V1 and V2 Permit2 witness payer from `permit2Authorization.from`
     pub fn payer(&self) -> Option<String> {
-        let payload = self.0.get("paymentPayload")?.get("payload")?;
-        payload
-            .get("authorization")
-            .and_then(|authorization| authorization.get("from"))
-            .or_else(|| payload.get("permit2").and_then(|permit2| permit2.get("owner")))
-            .or_else(|| {
-                payload
-                    .get("permit2Authorization")
-                    .and_then(|authorization| authorization.get("from"))
-            })
-            .and_then(|address| address.as_str())
-            .map(str::to_lowercase)
+        self.summary.payer.clone()
     }
 
     /// Returns the recipient address from the payment requirements, when present.
@@ -216,19 +415,100 @@ impl VerifyRequest {
     /// - V1 requirements payee from `paymentRequirements.payTo`
     /// - V2 requirements payee from `paymentRequirements.payTo` or `paymentPayload.accepted.payTo`
     pub fn payee(&self) -> Option<String> {
-        self.0
-            .get("paymentRequirements")
-            .and_then(|requirements| requirements.get("payTo"))
-            .and_then(|address| address.as_str())
-            .or_else(|| {
-                self
-                    .0
-                    .get("paymentPayload")?
-                    .get("accepted")?
-                    .get("payTo")
-                    .and_then(|address| address.as_str())
-            })
-            .map(str::to_lowercase)
+        self.summary.payee.clone()
+    }
+
+    /// Returns the required amount from the payment requirements, when present.
+    ///
+    /// This extracts:
+    /// - V1 amount from `paymentRequirements.maxAmountRequired`
+    /// - V2 amount from `paymentRequirements.amount`
+    pub fn amount(&self) -> Option<String> {
+        self.summary.amount.clone()
+    }
+
+    /// Returns the amount the payment transfers, as far as the request tells.
+    ///
+    /// That is the `"settleAmount"` when present. Otherwise the "exact" scheme
+    /// transfers the amount the payer signed, which differs from the required one
+    /// when paying less or more is accepted or a discount applies:
+    /// - `authorization.value` for ERC-3009
+    /// - `permit2.permitSingle.details.amount` for Permit2
+    /// - `permit2Authorization.permitted.amount` for the Permit2 witness
+    ///
+    /// Other schemes sign an allowance rather than a transfer, and fall back to the
+    /// required [`amount`](Self::amount).
+    pub fn transfer_amount(&self) -> Option<String> {
+        let is_exact = self
+            .summary
+            .slug
+            .as_ref()
+            .is_some_and(|slug| slug.name == "exact");
+        self.settle_amount()
+            .or_else(|| self.summary.signed_amount.clone().filter(|_| is_exact))
+            .or_else(|| self.amount())
+    }
+
+    /// Returns the asset from the payment requirements, when present.
+    pub fn asset(&self) -> Option<String> {
+        self.summary.asset.clone()
+    }
+
+    /// Returns `true` if the request asks for a settlement dry run (`"dryRun": true`).
+    pub fn dry_run(&self) -> bool {
+        self.summary.dry_run
+    }
+
+    /// Returns the requested [`ConfirmationPolicy`], [`ConfirmationPolicy::Confirmed`] by default.
+    pub fn confirmation_policy(&self) -> ConfirmationPolicy {
+        self.summary.confirmation_policy
+    }
+
+    /// Returns the amount the resource server asks to settle (`"settleAmount"`), when present.
+    ///
+    /// Schemes authorizing a maximum, such as "upto", settle this amount instead of the
+    /// full authorization. Other schemes ignore it.
+    pub fn settle_amount(&self) -> Option<String> {
+        self.summary.settle_amount.clone()
+    }
+
+    /// Sets the `"settleAmount"` member of the request.
+    pub fn with_settle_amount(self, amount: impl Into<String>) -> Self {
+        let mut json = self.into_json();
+        if let Some(object) = json.as_object_mut() {
+            object.insert(
+                "settleAmount".to_string(),
+                serde_json::Value::String(amount.into()),
+            );
+        }
+        json.into()
+    }
+
+    /// Decodes a `paymentPayload` given in its transported form.
+    ///
+    /// Resource servers may forward the payment exactly as they received it: the
+    /// base64-encoded string from the header, query parameter or body envelope
+    /// (see [`transport`]). This replaces such a string with the decoded JSON object.
+    /// Requests with an object payload, or a string that does not decode, are
+    /// returned unchanged.
+    pub fn with_decoded_payment_payload(self) -> Self {
+        if !self.summary.payload_is_encoded {
+            return self;
+        }
+        let mut json = self.clone().into_json();
+        let decoded = json
+            .get("paymentPayload")
+            .and_then(|payload| payload.as_str())
+            .and_then(|encoded| Base64Bytes::from(encoded.as_bytes()).decode().ok())
+            .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+            .filter(serde_json::Value::is_object);
+        match (decoded, json.as_object_mut()) {
+            (Some(decoded), Some(object)) => {
+                object.insert("paymentPayload".to_string(), decoded);
+                json.into()
+            }
+            _ => self,
+        }
     }
 }
 
@@ -246,39 +526,136 @@ pub struct VerifyResponse(pub serde_json::Value);
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SettleResponse(pub serde_json::Value);
 
+/// Response from a settlement dry run (`POST /settle?dryRun=true`).
+///
+/// Contains the transactions the settlement would send and their estimated
+/// cost, as JSON. Nothing is broadcast.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettleDryRunResponse(pub serde_json::Value);
+
+/// Request to send a settled payment back to its payer (`POST /refund`).
+///
+/// Built by the facilitator from the ledger entry of the original settlement,
+/// so scheme handlers receive the chain, token and amount to refund directly.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefundRequest {
+    /// Chain the payment was settled on.
+    pub network: ChainId,
+    /// Token the payment was made in.
+    pub asset: String,
+    /// Address the refund is sent to, the payer of the original payment.
+    pub recipient: String,
+    /// Amount to refund, in the asset's smallest unit.
+    pub amount: String,
+    /// Transaction hash of the original settlement.
+    pub settlement: String,
+}
+
+/// Response from a refund request.
+///
+/// Contains the refund result as JSON, including the refund transaction hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefundResponse(pub serde_json::Value);
+
+/// Status of a recurring payment authorization (`GET /subscriptions/{id}`).
+///
+/// Contains the schedule and payments of the subscription as JSON, in the
+/// format of the scheme that tracks it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionResponse(pub serde_json::Value);
+
+/// Static explanation of a payment payload (`POST /debug/decode`).
+///
+/// Contains what a scheme can tell about a payload without reading the chain,
+/// such as its signature kind, the expected EIP-712 digest and the checks that
+/// pass or fail, in the format of the scheme.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainResponse(pub serde_json::Value);
+
 /// Errors that can occur during payment verification.
 ///
 /// These errors are returned when a payment fails validation checks
-/// performed by the facilitator before settlement.
+/// performed by the facilitator before settlement. Variants about amounts,
+/// validity windows, recipients and assets carry what was expected and what
+/// the payload had, returned to clients as the [`PaymentProblem::context`].
+/// Amounts are decimal strings in the token's smallest unit.
 #[derive(Debug, thiserror::Error)]
 pub enum PaymentVerificationError {
     /// The payment payload format is invalid or malformed.
     #[error("Invalid format: {0}")]
     InvalidFormat(String),
     /// The payment amount doesn't match the requirements.
-    #[error("Payment amount is invalid with respect to the payment requirements")]
-    InvalidPaymentAmount,
+    #[error("Payment amount {provided} is invalid with respect to the required amount {required}")]
+    InvalidPaymentAmount {
+        /// Amount the requirements or the facilitator's policy call for.
+        required: String,
+        /// Amount the payload authorizes.
+        provided: String,
+    },
     /// The payment authorization's `validAfter` timestamp is in the future.
-    #[error("Payment authorization is not yet valid")]
-    Early,
-    /// The payment authorization's `validBefore` timestamp has passed.
-    #[error("Payment authorization is expired")]
-    Expired,
+    #[error("Payment authorization is not yet valid: valid after {valid_after}, now {now}")]
+    Early {
+        /// Start of the authorization's validity window.
+        valid_after: UnixTimestamp,
+        /// Time of the check.
+        now: UnixTimestamp,
+    },
+    /// The payment authorization's `validBefore` timestamp has passed, or is
+    /// too close to leave time for settlement.
+    #[error("Payment authorization is expired: valid before {valid_before}, now {now}")]
+    Expired {
+        /// End of the authorization's validity window.
+        valid_before: UnixTimestamp,
+        /// Time of the check.
+        now: UnixTimestamp,
+    },
     /// The payment's chain ID doesn't match the requirements.
     #[error("Payment chain id is invalid with respect to the payment requirements")]
     ChainIdMismatch,
     /// The payment recipient doesn't match the requirements.
-    #[error("Payment recipient is invalid with respect to the payment requirements")]
-    RecipientMismatch,
+    #[error(
+        "Payment recipient {actual} is invalid with respect to the required recipient {expected}"
+    )]
+    RecipientMismatch {
+        /// Recipient the requirements call for.
+        expected: String,
+        /// Recipient the payload pays.
+        actual: String,
+    },
     /// The payment asset (token) doesn't match the requirements.
-    #[error("Payment asset is invalid with respect to the payment requirements")]
-    AssetMismatch,
+    #[error("Payment asset {actual} is invalid with respect to the required asset {expected}")]
+    AssetMismatch {
+        /// Asset the requirements call for.
+        expected: String,
+        /// Asset the payload pays in.
+        actual: String,
+    },
+    /// The payment asset (token) is not accepted by this facilitator.
+    #[error("Payment asset is not supported by the facilitator")]
+    UnsupportedAsset,
     /// The payer or payee failed off-chain compliance screening.
     #[error("Compliance check failed: {0}")]
     ComplianceFailed(String),
+    /// The payer has settled more payments, or more value, within a window than
+    /// the facilitator's velocity limits allow.
+    #[error("Velocity limit exceeded: more than {limit} {measure} within {window_seconds} seconds")]
+    VelocityLimitExceeded {
+        /// What the limit caps: `"settlements"` or `"value"`.
+        measure: String,
+        /// The limit, a count of settlements or an amount.
+        limit: String,
+        /// Length of the sliding window the limit applies to.
+        window_seconds: u64,
+    },
     /// The payer's on-chain balance is insufficient.
-    #[error("Onchain balance is not enough to cover the payment amount")]
-    InsufficientFunds,
+    #[error("Onchain balance {balance} is not enough to cover the payment amount {required}")]
+    InsufficientFunds {
+        /// Amount the payment needs.
+        required: String,
+        /// What the payer holds.
+        balance: String,
+    },
     /// The payment signature is invalid.
     #[error("{0}")]
     InvalidSignature(String),
@@ -296,18 +673,105 @@ pub enum PaymentVerificationError {
     AcceptedRequirementsMismatch,
 }
 
+impl PaymentVerificationError {
+    /// Message prefix of [`PaymentVerificationError::Early`].
+    pub const EARLY_MESSAGE: &'static str = "Payment authorization is not yet valid";
+
+    /// Message prefix of [`PaymentVerificationError::Expired`].
+    pub const EXPIRED_MESSAGE: &'static str = "Payment authorization is expired";
+
+    /// An [`PaymentVerificationError::InvalidPaymentAmount`] from any displayable amounts.
+    pub fn invalid_amount(required: impl ToString, provided: impl ToString) -> Self {
+        Self::InvalidPaymentAmount {
+            required: required.to_string(),
+            provided: provided.to_string(),
+        }
+    }
+
+    /// A [`PaymentVerificationError::RecipientMismatch`] from any displayable addresses.
+    pub fn recipient_mismatch(expected: impl ToString, actual: impl ToString) -> Self {
+        Self::RecipientMismatch {
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        }
+    }
+
+    /// A [`PaymentVerificationError::AssetMismatch`] from any displayable addresses.
+    pub fn asset_mismatch(expected: impl ToString, actual: impl ToString) -> Self {
+        Self::AssetMismatch {
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        }
+    }
+
+    /// An [`PaymentVerificationError::InsufficientFunds`] from any displayable amounts.
+    pub fn insufficient_funds(required: impl ToString, balance: impl ToString) -> Self {
+        Self::InsufficientFunds {
+            required: required.to_string(),
+            balance: balance.to_string(),
+        }
+    }
+
+    /// The structured fields of the error as a JSON object with camelCase keys, for
+    /// the variants that have any.
+    ///
+    /// Timing errors also carry `secondsUntilValid` or `secondsSinceExpiry`, so that
+    /// clients can tell the payer how long to wait or that they have to sign again.
+    pub fn context(&self) -> Option<serde_json::Value> {
+        let context = match self {
+            Self::InvalidPaymentAmount { required, provided } => {
+                serde_json::json!({ "required": required, "provided": provided })
+            }
+            Self::Early { valid_after, now } => serde_json::json!({
+                "validAfter": valid_after.as_secs(),
+                "now": now.as_secs(),
+                "secondsUntilValid": valid_after.as_secs().saturating_sub(now.as_secs()),
+            }),
+            Self::Expired { valid_before, now } => serde_json::json!({
+                "validBefore": valid_before.as_secs(),
+                "now": now.as_secs(),
+                "secondsSinceExpiry": now.as_secs().saturating_sub(valid_before.as_secs()),
+            }),
+            Self::RecipientMismatch { expected, actual }
+            | Self::AssetMismatch { expected, actual } => {
+                serde_json::json!({ "expected": expected, "actual": actual })
+            }
+            Self::InsufficientFunds { required, balance } => {
+                serde_json::json!({ "required": required, "balance": balance })
+            }
+            Self::VelocityLimitExceeded {
+                measure,
+                limit,
+                window_seconds,
+            } => serde_json::json!({
+                "measure": measure,
+                "limit": limit,
+                "windowSeconds": window_seconds,
+            }),
+            _ => return None,
+        };
+        Some(context)
+    }
+}
+
 impl AsPaymentProblem for PaymentVerificationError {
     fn as_payment_problem(&self) -> PaymentProblem {
         let error_reason = match self {
             PaymentVerificationError::InvalidFormat(_) => ErrorReason::InvalidFormat,
-            PaymentVerificationError::InvalidPaymentAmount => ErrorReason::InvalidPaymentAmount,
-            PaymentVerificationError::InsufficientFunds => ErrorReason::InsufficientFunds,
-            PaymentVerificationError::Early => ErrorReason::InvalidPaymentEarly,
-            PaymentVerificationError::Expired => ErrorReason::InvalidPaymentExpired,
+            PaymentVerificationError::InvalidPaymentAmount { .. } => {
+                ErrorReason::InvalidPaymentAmount
+            }
+            PaymentVerificationError::InsufficientFunds { .. } => ErrorReason::InsufficientFunds,
+            PaymentVerificationError::Early { .. } => ErrorReason::InvalidPaymentEarly,
+            PaymentVerificationError::Expired { .. } => ErrorReason::InvalidPaymentExpired,
             PaymentVerificationError::ChainIdMismatch => ErrorReason::ChainIdMismatch,
-            PaymentVerificationError::RecipientMismatch => ErrorReason::RecipientMismatch,
-            PaymentVerificationError::AssetMismatch => ErrorReason::AssetMismatch,
+            PaymentVerificationError::RecipientMismatch { .. } => ErrorReason::RecipientMismatch,
+            PaymentVerificationError::AssetMismatch { .. } => ErrorReason::AssetMismatch,
+            PaymentVerificationError::UnsupportedAsset => ErrorReason::UnsupportedAsset,
             PaymentVerificationError::ComplianceFailed(_) => ErrorReason::ComplianceFailed,
+            PaymentVerificationError::VelocityLimitExceeded { .. } => {
+                ErrorReason::VelocityLimitExceeded
+            }
             PaymentVerificationError::InvalidSignature(_) => ErrorReason::InvalidSignature,
             PaymentVerificationError::TransactionSimulation(_) => {
                 ErrorReason::TransactionSimulation
@@ -318,7 +782,11 @@ impl AsPaymentProblem for PaymentVerificationError {
                 ErrorReason::AcceptedRequirementsMismatch
             }
         };
-        PaymentProblem::new(error_reason, self.to_string())
+        let problem = PaymentProblem::new(error_reason, self.to_string());
+        match self.context() {
+            Some(context) => problem.with_context(context),
+            None => problem,
+        }
     }
 }
 
@@ -333,6 +801,7 @@ impl From<serde_json::Error> for PaymentVerificationError {
 /// These codes are used in error responses to allow clients to
 /// programmatically handle different failure scenarios.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum ErrorReason {
     /// The payment payload format is invalid.
@@ -349,8 +818,12 @@ pub enum ErrorReason {
     RecipientMismatch,
     /// The token asset doesn't match.
     AssetMismatch,
+    /// The token asset is not accepted by the facilitator.
+    UnsupportedAsset,
     /// Compliance screening failed.
     ComplianceFailed,
+    /// The payer exceeded the facilitator's velocity limits.
+    VelocityLimitExceeded,
     /// The accepted details don't match requirements.
     AcceptedRequirementsMismatch,
     /// The signature is invalid.
@@ -382,12 +855,29 @@ pub struct PaymentProblem {
     reason: ErrorReason,
     /// Human-readable error details.
     details: String,
+    /// Machine-readable error details, see [`PaymentVerificationError::context`].
+    context: Option<serde_json::Value>,
 }
 
 impl PaymentProblem {
     /// Creates a new payment problem with the given reason and details.
     pub fn new(reason: ErrorReason, details: String) -> Self {
-        Self { reason, details }
+        Self {
+            reason,
+            details,
+            context: None,
+        }
+    }
+
+    /// Attaches machine-readable details to the problem.
+    pub fn with_context(mut self, context: serde_json::Value) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Returns the machine-readable error details, if any.
+    pub fn context(&self) -> Option<&serde_json::Value> {
+        self.context.as_ref()
     }
 
     /// Returns the error reason code.
@@ -413,3 +903,161 @@ impl ProtocolV for PaymentRequiredV {
 ///
 /// This is returned with HTTP 402 status to indicate that payment is required.
 pub type PaymentRequired = ProtocolVersioned<PaymentRequiredV>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_request_summary() {
+        let request: VerifyRequest = serde_json::from_str(
+            r#"{
+                "x402Version": 2,
+                "paymentPayload": {
+                    "x402Version": 2,
+                    "accepted": {"scheme": "exact", "network": "eip155:42793", "payTo": "0xAbC0000000000000000000000000000000000001"},
+                    "payload": {"permit2Authorization": {"from": "0xDEF0000000000000000000000000000000000002"}}
+                },
+                "paymentRequirements": {"amount": "100", "asset": "0xAAA0000000000000000000000000000000000003"},
+                "dryRun": true
+            }"#,
+        )
+        .unwrap();
+        let slug = request.scheme_handler_slug().unwrap();
+        assert_eq!(slug.to_string(), "eip155:42793:v2:exact");
+        assert_eq!(
+            request.payer().as_deref(),
+            Some("0xdef0000000000000000000000000000000000002")
+        );
+        assert_eq!(
+            request.payee().as_deref(),
+            Some("0xabc0000000000000000000000000000000000001")
+        );
+        assert_eq!(request.amount().as_deref(), Some("100"));
+        assert_eq!(
+            request.asset().as_deref(),
+            Some("0xaaa0000000000000000000000000000000000003")
+        );
+        assert!(request.dry_run());
+        assert_eq!(request.confirmation_policy(), ConfirmationPolicy::Confirmed);
+        assert!(request.settle_amount().is_none());
+        assert_eq!(request.transfer_amount().as_deref(), Some("100"));
+        let request = request.with_settle_amount("40");
+        assert_eq!(request.settle_amount().as_deref(), Some("40"));
+        assert_eq!(request.amount().as_deref(), Some("100"));
+        assert_eq!(request.transfer_amount().as_deref(), Some("40"));
+
+        // An "exact" payment transfers the signed amount, an allowance the required one.
+        let payment = |scheme: &str| {
+            VerifyRequest::from(serde_json::json!({
+                "x402Version": 2,
+                "paymentPayload": {
+                    "accepted": {"scheme": scheme, "network": "eip155:42793"},
+                    "payload": {"permit2": {"permitSingle": {"details": {"amount": "80"}}}}
+                },
+                "paymentRequirements": {"amount": "100"}
+            }))
+        };
+        assert_eq!(payment("exact").transfer_amount().as_deref(), Some("80"));
+        assert_eq!(
+            payment("deferred").transfer_amount().as_deref(),
+            Some("100")
+        );
+
+        let request = VerifyRequest::from(serde_json::json!({
+            "x402Version": 1,
+            "paymentRequirements": {"maxAmountRequired": 250}
+        }));
+        assert_eq!(request.amount().as_deref(), Some("250"));
+
+        let request = VerifyRequest::from(serde_json::json!({"x402Version": 3}));
+        assert!(request.scheme_handler_slug().is_none());
+        assert!(request.payer().is_none());
+        assert!(!request.dry_run());
+    }
+
+    #[test]
+    fn test_verify_request_summary_with_escaped_keys() {
+        // Scheme handlers read escaped keys as the members they name, and so must
+        // screening and limits.
+        let request: VerifyRequest = serde_json::from_str(
+            r#"{
+                "x402Version": 2,
+                "p\u0061ymentPayload": {
+                    "x402Version": 2,
+                    "accepted": {"scheme": "exact", "network": "eip155:42793", "p\u0061yTo": "0xAbC0000000000000000000000000000000000001"},
+                    "payload": {"sign\u0061ture": "0x", "\u0061uthorization": {"fr\u006fm": "0xDEF0000000000000000000000000000000000002"}}
+                },
+                "paymentRequirements": {"\u0061mount": "100", "\u0061sset": "0xAAA0000000000000000000000000000000000003"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            request.scheme_handler_slug().unwrap().to_string(),
+            "eip155:42793:v2:exact"
+        );
+        assert_eq!(
+            request.payer().as_deref(),
+            Some("0xdef0000000000000000000000000000000000002")
+        );
+        assert_eq!(
+            request.payee().as_deref(),
+            Some("0xabc0000000000000000000000000000000000001")
+        );
+        assert_eq!(request.amount().as_deref(), Some("100"));
+        assert_eq!(
+            request.asset().as_deref(),
+            Some("0xaaa0000000000000000000000000000000000003")
+        );
+    }
+
+    #[test]
+    fn test_verification_error_context() {
+        let expired = PaymentVerificationError::Expired {
+            valid_before: UnixTimestamp::from_secs(1_700_000_000),
+            now: UnixTimestamp::from_secs(1_700_000_003),
+        };
+        assert!(
+            expired
+                .to_string()
+                .starts_with(PaymentVerificationError::EXPIRED_MESSAGE)
+        );
+        let problem = expired.as_payment_problem();
+        assert_eq!(problem.reason(), ErrorReason::InvalidPaymentExpired);
+        assert_eq!(
+            problem.context(),
+            Some(&serde_json::json!({
+                "validBefore": 1_700_000_000u64,
+                "now": 1_700_000_003u64,
+                "secondsSinceExpiry": 3
+            }))
+        );
+
+        let early = PaymentVerificationError::Early {
+            valid_after: UnixTimestamp::from_secs(1_700_000_010),
+            now: UnixTimestamp::from_secs(1_700_000_000),
+        };
+        assert!(
+            early
+                .to_string()
+                .starts_with(PaymentVerificationError::EARLY_MESSAGE)
+        );
+        assert_eq!(early.context().unwrap()["secondsUntilValid"], 10);
+
+        let amount = PaymentVerificationError::invalid_amount(1000u64, 999u64);
+        assert_eq!(
+            amount.context(),
+            Some(serde_json::json!({"required": "1000", "provided": "999"}))
+        );
+        assert_eq!(
+            PaymentVerificationError::insufficient_funds(5u64, 4u64)
+                .context()
+                .unwrap()["balance"],
+            "4"
+        );
+
+        let signature = PaymentVerificationError::InvalidSignature("bad".to_string());
+        assert!(signature.context().is_none());
+        assert!(signature.as_payment_problem().context().is_none());
+    }
+}
@@ -0,0 +1,191 @@
+//! OpenAPI schemas of the facilitator wire format.
+//!
+//! [`VerifyRequest`](super::VerifyRequest) and the facilitator responses are kept
+//! as raw JSON, and the V1 responses have hand-written serializers, so their
+//! schemas are described by the mirror types of this module instead of being
+//! derived. Each one is registered under the name of the type it describes.
+//!
+//! The request schemas describe V2 messages. V1 messages carry the same
+//! `x402Version`, `paymentPayload` and `paymentRequirements` members with the V1
+//! fields (`network` names, `maxAmountRequired`, ...).
+//!
+//! Only available with the `openapi` feature.
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::proto::v2::ResourceInfo;
+use crate::proto::{ConfirmationPolicy, ErrorReason};
+
+/// Request body of `POST /verify` and `POST /settle`.
+#[derive(Serialize, ToSchema)]
+#[schema(as = VerifyRequest)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyRequestSchema {
+    /// Protocol version of the payment, `1` or `2`.
+    #[schema(example = 2)]
+    pub x402_version: u8,
+    /// The signed payment. Also accepted as the base64 string the client sent in
+    /// its payment header.
+    pub payment_payload: PaymentPayloadSchema,
+    /// The requirements the payment must meet, as offered by the resource server.
+    pub payment_requirements: PaymentRequirementsSchema,
+    /// How long `/settle` waits for the settlement transaction. Defaults to `confirmed`.
+    pub confirmation_policy: Option<ConfirmationPolicy>,
+    /// Amount to settle, for schemes settling less than the required maximum
+    /// (`upto`). Smallest unit of the asset.
+    #[schema(example = "1000000000000000000")]
+    pub settle_amount: Option<String>,
+    /// On `/settle`, returns the transactions the settlement would send without
+    /// broadcasting them. Same as the `dryRun` query parameter.
+    pub dry_run: Option<bool>,
+}
+
+/// A signed V2 payment.
+#[derive(Serialize, ToSchema)]
+#[schema(as = PaymentPayload)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentPayloadSchema {
+    /// Protocol version, `2`.
+    #[schema(example = 2)]
+    pub x402_version: u8,
+    /// The payment requirements the payer accepted.
+    pub accepted: PaymentRequirementsSchema,
+    /// The scheme-specific signed payload, e.g. an EIP-3009 authorization and its
+    /// signature for the `exact` scheme.
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Value,
+    /// The resource paid for.
+    pub resource: Option<ResourceInfo>,
+}
+
+/// Terms under which a V2 payment is accepted.
+#[derive(Serialize, ToSchema)]
+#[schema(as = PaymentRequirements)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequirementsSchema {
+    /// Payment scheme, e.g. `exact`.
+    #[schema(example = "exact")]
+    pub scheme: String,
+    /// CAIP-2 chain ID.
+    #[schema(example = "eip155:42793")]
+    pub network: String,
+    /// Price in the smallest unit of the asset, as a decimal string.
+    #[schema(example = "10000000000000000")]
+    pub amount: String,
+    /// Recipient of the payment.
+    pub pay_to: String,
+    /// Seconds the payment authorization stays valid.
+    #[schema(example = 300)]
+    pub max_timeout_seconds: u64,
+    /// Token contract address.
+    pub asset: String,
+    /// Scheme-specific data, e.g. the EIP-712 domain of the token.
+    #[schema(value_type = Option<Object>)]
+    pub extra: Option<serde_json::Value>,
+}
+
+/// Response of `POST /verify`.
+///
+/// Rejected payments are answered with `400 Bad Request` and `isValid: false`.
+#[derive(Serialize, ToSchema)]
+#[schema(as = VerifyResponse)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyResponseSchema {
+    /// Whether the payment can be settled.
+    pub is_valid: bool,
+    /// Address of the payer. Empty when the payment could not be decoded.
+    pub payer: Option<String>,
+    /// Why the payment was rejected.
+    pub invalid_reason: Option<ErrorReason>,
+    /// Human-readable details of the rejection.
+    pub invalid_reason_details: Option<String>,
+    /// Machine-readable details of the rejection, e.g. the required and provided
+    /// amounts, or how long ago the authorization expired.
+    #[schema(value_type = Option<Object>)]
+    pub invalid_reason_context: Option<serde_json::Value>,
+}
+
+/// Response of `POST /settle`.
+///
+/// Failed settlements are answered with `400 Bad Request` (rejected payment) or
+/// `500 Internal Server Error` (on-chain failure) and `success: false`.
+#[derive(Serialize, ToSchema)]
+#[schema(as = SettleResponse)]
+#[serde(rename_all = "camelCase")]
+pub struct SettleResponseSchema {
+    /// Whether the payment was settled.
+    pub success: bool,
+    /// Why the settlement failed.
+    pub error_reason: Option<ErrorReason>,
+    /// Human-readable details of the failure.
+    pub error_reason_details: Option<String>,
+    /// Machine-readable details of the failure, as `invalidReasonContext` of `/verify`.
+    #[schema(value_type = Option<Object>)]
+    pub error_reason_context: Option<serde_json::Value>,
+    /// Address of the payer.
+    pub payer: Option<String>,
+    /// Hash of the settlement transaction. All zeros for deferred payments, see `iou`.
+    pub transaction: Option<String>,
+    /// Chain the payment was settled on.
+    pub network: String,
+    /// Block the settlement transaction was included in.
+    pub block_number: Option<u64>,
+    /// Gas used by the settlement transaction.
+    pub gas_used: Option<u64>,
+    /// Address of the facilitator signer that submitted the transaction.
+    pub signer: Option<String>,
+    /// Price paid per unit of gas, in wei, as a decimal string.
+    pub effective_gas_price: Option<String>,
+    /// Fee the facilitator charged, in the smallest unit of the asset.
+    pub facilitator_fee: Option<String>,
+    /// Amount transferred to the payee, in the smallest unit of the asset.
+    pub settled_amount: Option<String>,
+    /// Confirmations the transaction had when the facilitator responded.
+    pub confirmations: Option<u64>,
+    /// Confirmations the chain requires before a settlement counts as final.
+    pub required_confirmations: Option<u64>,
+    /// `pending` or `confirmed`.
+    pub confirmation_status: Option<String>,
+    /// Signed promise to settle a payment accepted for deferred settlement.
+    #[schema(value_type = Option<Object>)]
+    pub iou: Option<serde_json::Value>,
+}
+
+/// Response of `POST /settle?dryRun=true` on EVM chains.
+#[derive(Serialize, ToSchema)]
+#[schema(as = SettleDryRunResponse)]
+#[serde(rename_all = "camelCase")]
+pub struct SettleDryRunResponseSchema {
+    /// Whether the settlement would go through.
+    pub success: bool,
+    /// Always `true`.
+    pub dry_run: bool,
+    /// Address of the payer.
+    pub payer: String,
+    /// Chain the payment would be settled on.
+    pub network: String,
+    /// Transactions in the order they would be sent.
+    pub transactions: Vec<DryRunTransactionSchema>,
+    /// Current gas price, in wei.
+    pub gas_price: String,
+    /// Estimated gas cost of all transactions, in wei.
+    pub estimated_cost: String,
+}
+
+/// A transaction a settlement would send.
+#[derive(Serialize, ToSchema)]
+#[schema(as = DryRunTransaction)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunTransactionSchema {
+    /// Facilitator signer sending the transaction.
+    pub from: String,
+    /// Contract called.
+    pub to: String,
+    /// Calldata, hex-encoded.
+    pub data: String,
+    /// Estimated gas limit.
+    pub gas: Option<u64>,
+    /// Why the gas could not be estimated.
+    pub error: Option<String>,
+}
@@ -0,0 +1,334 @@
+//! Validated parsing of 402 Payment Required responses.
+//!
+//! Sellers send their price tags as loosely typed JSON: amounts and addresses are
+//! strings, V1 names networks (`"etherlink"`) while V2 uses CAIP-2 IDs
+//! (`"eip155:42793"`), and some servers mix the two or repeat an option.
+//! [`ParsedPaymentRequired`] does that parsing once:
+//!
+//! - `x402Version` must be 1 or 2,
+//! - network names and CAIP-2 IDs are both accepted in either version,
+//! - each option gets a typed [`PaymentOption`] with the amount as [`U256`] and
+//!   the recipient and asset as [`Address`],
+//! - identical options are kept once,
+//! - options that fail validation are dropped and listed in
+//!   [`ParsedPaymentRequired::rejected`] instead of failing the whole response.
+//!
+//! The response fails to parse only when its envelope is invalid or none of its
+//! options are usable.
+//!
+//! # Example
+//!
+//! ```rust
+//! use x402_types::proto::payment_required::ParsedPaymentRequired;
+//!
+//! let body = br#"{
+//!     "x402Version": 1,
+//!     "accepts": [{
+//!         "scheme": "exact",
+//!         "network": "etherlink",
+//!         "maxAmountRequired": "10000",
+//!         "resource": "https://example.com/weather",
+//!         "description": "Weather report",
+//!         "mimeType": "application/json",
+//!         "payTo": "0x1111111111111111111111111111111111111111",
+//!         "maxTimeoutSeconds": 60,
+//!         "asset": "0x2222222222222222222222222222222222222222"
+//!     }]
+//! }"#;
+//! let parsed = ParsedPaymentRequired::parse(body).unwrap();
+//! let option = &parsed.options[0];
+//! assert_eq!(option.chain_id.to_string(), "eip155:42793");
+//! assert_eq!(option.amount, alloy_primitives::U256::from(10000));
+//! ```
+
+use alloy_primitives::{Address, U256};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::chain::ChainId;
+use crate::proto::{PaymentRequired, v1, v2};
+use crate::util::Base64Bytes;
+
+/// Why a 402 response, or one of its options, could not be used.
+#[derive(Debug, thiserror::Error)]
+pub enum PaymentRequiredError {
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid base64: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("Missing x402Version")]
+    MissingVersion,
+    #[error("Unsupported x402Version {0}")]
+    UnsupportedVersion(u64),
+    #[error("Unknown network {0}")]
+    UnknownNetwork(String),
+    #[error("Invalid amount {0}")]
+    InvalidAmount(String),
+    #[error("Invalid {field} address {value}")]
+    InvalidAddress { field: &'static str, value: String },
+    #[error("No valid payment options")]
+    NoOptions,
+}
+
+/// One way to pay for the resource, with its fields validated and typed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentOption {
+    pub x402_version: u8,
+    pub scheme: String,
+    /// The network as a CAIP-2 ID, whichever form the seller used.
+    pub chain_id: ChainId,
+    /// Amount in the token's smallest unit. For V1 this is `maxAmountRequired`.
+    pub amount: U256,
+    pub pay_to: Address,
+    pub asset: Address,
+    pub max_timeout_seconds: u64,
+    pub extra: Option<Value>,
+}
+
+/// An option that was dropped, by its position in the original `accepts` list.
+#[derive(Debug)]
+pub struct RejectedOption {
+    pub index: usize,
+    pub error: PaymentRequiredError,
+}
+
+/// A validated 402 Payment Required response.
+#[derive(Debug)]
+pub struct ParsedPaymentRequired {
+    /// The response with only the usable, deduplicated options in `accepts`.
+    /// `payment_required.accepts[i]` is the source of `options[i]`.
+    pub payment_required: PaymentRequired,
+    pub options: Vec<PaymentOption>,
+    pub rejected: Vec<RejectedOption>,
+}
+
+impl ParsedPaymentRequired {
+    /// Parses a JSON response body, V1 or V2.
+    pub fn parse(body: &[u8]) -> Result<Self, PaymentRequiredError> {
+        Self::from_value(serde_json::from_slice(body)?)
+    }
+
+    /// Parses the base64-encoded `Payment-Required` header of a V2 response.
+    pub fn from_header(value: &[u8]) -> Result<Self, PaymentRequiredError> {
+        Self::parse(&Base64Bytes::from(value).decode()?)
+    }
+
+    /// Parses a response already decoded as JSON.
+    pub fn from_value(mut value: Value) -> Result<Self, PaymentRequiredError> {
+        let version = value
+            .get("x402Version")
+            .and_then(Value::as_u64)
+            .ok_or(PaymentRequiredError::MissingVersion)?;
+        let accepts = match value.as_object_mut().and_then(|o| o.remove("accepts")) {
+            Some(Value::Array(accepts)) => accepts,
+            _ => Vec::new(),
+        };
+        let (payment_required, options, mut rejected) = match version {
+            1 => {
+                let mut envelope: v1::PaymentRequired = serde_json::from_value(value)?;
+                let (accepts, options, rejected) =
+                    collect(accepts, |_| Ok(()), PaymentOption::from_v1);
+                envelope.accepts = accepts;
+                (PaymentRequired::V1(envelope), options, rejected)
+            }
+            2 => {
+                let mut envelope: v2::PaymentRequired = serde_json::from_value(value)?;
+                let (accepts, options, rejected) =
+                    collect(accepts, normalize_network, PaymentOption::from_v2);
+                envelope.accepts = accepts;
+                (PaymentRequired::V2(envelope), options, rejected)
+            }
+            version => return Err(PaymentRequiredError::UnsupportedVersion(version)),
+        };
+        if options.is_empty() {
+            return Err(rejected
+                .pop()
+                .map(|rejected| rejected.error)
+                .unwrap_or(PaymentRequiredError::NoOptions));
+        }
+        Ok(Self {
+            payment_required,
+            options,
+            rejected,
+        })
+    }
+
+    /// Options payable on `chain_id`, in the seller's order.
+    pub fn options_on<'a>(
+        &'a self,
+        chain_id: &'a ChainId,
+    ) -> impl Iterator<Item = &'a PaymentOption> + 'a {
+        self.options
+            .iter()
+            .filter(move |option| &option.chain_id == chain_id)
+    }
+}
+
+/// Validates every entry of `accepts`, keeping the first of identical options.
+fn collect<R: DeserializeOwned>(
+    accepts: Vec<Value>,
+    prepare: fn(&mut Value) -> Result<(), PaymentRequiredError>,
+    option: fn(&R) -> Result<PaymentOption, PaymentRequiredError>,
+) -> (Vec<R>, Vec<PaymentOption>, Vec<RejectedOption>) {
+    let mut kept = Vec::new();
+    let mut options = Vec::new();
+    let mut rejected = Vec::new();
+    for (index, mut entry) in accepts.into_iter().enumerate() {
+        let result = prepare(&mut entry)
+            .and_then(|()| Ok(serde_json::from_value::<R>(entry)?))
+            .and_then(|requirements| Ok((option(&requirements)?, requirements)));
+        match result {
+            Ok((option, _)) if options.contains(&option) => {}
+            Ok((option, requirements)) => {
+                options.push(option);
+                kept.push(requirements);
+            }
+            Err(error) => rejected.push(RejectedOption { index, error }),
+        }
+    }
+    (kept, options, rejected)
+}
+
+impl PaymentOption {
+    fn from_v1(requirements: &v1::PaymentRequirements) -> Result<Self, PaymentRequiredError> {
+        Ok(Self {
+            x402_version: v1::X402Version1::VALUE,
+            scheme: requirements.scheme.clone(),
+            chain_id: resolve_network(&requirements.network)?,
+            amount: parse_amount(&requirements.max_amount_required)?,
+            pay_to: parse_address("payTo", &requirements.pay_to)?,
+            asset: parse_address("asset", &requirements.asset)?,
+            max_timeout_seconds: requirements.max_timeout_seconds,
+            extra: requirements.extra.clone(),
+        })
+    }
+
+    fn from_v2(requirements: &v2::PaymentRequirements) -> Result<Self, PaymentRequiredError> {
+        Ok(Self {
+            x402_version: v2::X402Version2::VALUE,
+            scheme: requirements.scheme.clone(),
+            chain_id: requirements.network.clone(),
+            amount: parse_amount(&requirements.amount)?,
+            pay_to: parse_address("payTo", &requirements.pay_to)?,
+            asset: parse_address("asset", &requirements.asset)?,
+            max_timeout_seconds: requirements.max_timeout_seconds,
+            extra: requirements.extra.clone(),
+        })
+    }
+}
+
+/// Resolves a network name or CAIP-2 ID.
+pub fn resolve_network(network: &str) -> Result<ChainId, PaymentRequiredError> {
+    ChainId::from_network_name(network)
+        .or_else(|| network.parse().ok())
+        .ok_or_else(|| PaymentRequiredError::UnknownNetwork(network.to_string()))
+}
+
+/// Rewrites a V2 entry's network name as a CAIP-2 ID.
+fn normalize_network(entry: &mut Value) -> Result<(), PaymentRequiredError> {
+    if let Some(network) = entry.get_mut("network")
+        && let Some(name) = network.as_str()
+    {
+        *network = Value::String(resolve_network(name)?.to_string());
+    }
+    Ok(())
+}
+
+fn parse_amount(amount: &str) -> Result<U256, PaymentRequiredError> {
+    U256::from_str_radix(amount, 10)
+        .map_err(|_| PaymentRequiredError::InvalidAmount(amount.to_string()))
+}
+
+fn parse_address(field: &'static str, value: &str) -> Result<Address, PaymentRequiredError> {
+    value
+        .parse()
+        .map_err(|_| PaymentRequiredError::InvalidAddress {
+            field,
+            value: value.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn option(network: &str, amount: Value, asset: &str) -> Value {
+        json!({
+            "scheme": "exact",
+            "network": network,
+            "amount": amount,
+            "payTo": "0x1111111111111111111111111111111111111111",
+            "maxTimeoutSeconds": 60,
+            "asset": asset
+        })
+    }
+
+    #[test]
+    fn test_parse_v2() {
+        let usdc = "0x2222222222222222222222222222222222222222";
+        let parsed = ParsedPaymentRequired::from_value(json!({
+            "x402Version": 2,
+            "resource": {"description": "", "mimeType": "", "url": "https://example.com"},
+            "accepts": [
+                option("eip155:42793", json!("1000"), usdc),
+                option("etherlink", json!(1000), usdc),
+                option("eip155:42793", json!("-5"), usdc),
+                option("eip155:42793", json!("1000"), "not-an-address"),
+                option("eip155:1", json!("2000"), usdc),
+            ]
+        }))
+        .unwrap();
+
+        // The second entry names the same network and repeats the first.
+        assert_eq!(parsed.options.len(), 2);
+        assert_eq!(parsed.options[0].amount, U256::from(1000));
+        assert_eq!(parsed.options[0].asset, usdc.parse::<Address>().unwrap());
+        let rejected: Vec<usize> = parsed.rejected.iter().map(|r| r.index).collect();
+        assert_eq!(rejected, vec![2, 3]);
+        match &parsed.payment_required {
+            PaymentRequired::V2(payment_required) => {
+                assert_eq!(payment_required.accepts.len(), 2);
+                assert_eq!(payment_required.accepts[1].amount, "2000");
+            }
+            PaymentRequired::V1(_) => panic!("expected V2"),
+        }
+        let ethereum = ChainId::new("eip155", "1");
+        assert_eq!(parsed.options_on(&ethereum).count(), 1);
+
+        let header = Base64Bytes::encode(
+            serde_json::to_vec(&json!({
+                "x402Version": 2,
+                "resource": {"description": "", "mimeType": "", "url": ""},
+                "accepts": [option("eip155:42793", json!("1"), usdc)]
+            }))
+            .unwrap(),
+        );
+        assert!(ParsedPaymentRequired::from_header(header.as_ref()).is_ok());
+
+        let errors = [
+            json!({"accepts": []}),
+            json!({"x402Version": 3, "accepts": []}),
+            json!({"x402Version": 2, "resource": {"description": "", "mimeType": "", "url": ""}, "accepts": []}),
+            json!({"x402Version": 2, "resource": {"description": "", "mimeType": "", "url": ""},
+                "accepts": [option("nowhere", json!("1"), usdc)]}),
+        ];
+        let errors: Vec<String> = errors
+            .into_iter()
+            .map(|value| {
+                ParsedPaymentRequired::from_value(value)
+                    .unwrap_err()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "Missing x402Version",
+                "Unsupported x402Version 3",
+                "No valid payment options",
+                "Unknown network nowhere",
+            ]
+        );
+    }
+}
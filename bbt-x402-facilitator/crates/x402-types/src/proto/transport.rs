@@ -0,0 +1,93 @@
+//! Transports for carrying payment payloads from client to resource server.
+//!
+//! By default, a payment is sent in a request header (`X-PAYMENT` in V1,
+//! `Payment-Signature` in V2). Some proxies strip or truncate large headers, so a
+//! resource server may additionally accept the payment:
+//!
+//! - in the [`PAYMENT_QUERY_PARAM`] query parameter, or
+//! - in a JSON request body envelope, see [`PaymentEnvelope`].
+//!
+//! In every transport the value is the same base64-encoded, payer-signed payload
+//! that would otherwise be sent in the header.
+//!
+//! The server lists the transports it accepts in the `transports` field of the
+//! `402 Payment Required` challenge. A missing or empty list means header only.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Name of the query parameter carrying the payment payload.
+pub const PAYMENT_QUERY_PARAM: &str = "x402-payment";
+
+/// A way of carrying the payment payload in a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentTransport {
+    /// Request header (default).
+    Header,
+    /// The [`PAYMENT_QUERY_PARAM`] query parameter.
+    Query,
+    /// A JSON request body [`PaymentEnvelope`].
+    Body,
+}
+
+impl PaymentTransport {
+    /// Returns the transport name as used on the wire.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentTransport::Header => "header",
+            PaymentTransport::Query => "query",
+            PaymentTransport::Body => "body",
+        }
+    }
+
+    /// Returns `true` if a challenge listing `transports` accepts this transport.
+    ///
+    /// The header transport is always accepted.
+    pub fn is_accepted_by(&self, transports: &[PaymentTransport]) -> bool {
+        *self == PaymentTransport::Header || transports.contains(self)
+    }
+}
+
+impl fmt::Display for PaymentTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PaymentTransport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "header" => Ok(PaymentTransport::Header),
+            "query" => Ok(PaymentTransport::Query),
+            "body" => Ok(PaymentTransport::Body),
+            other => Err(format!("unknown payment transport: {other}")),
+        }
+    }
+}
+
+/// JSON request body wrapping the original body together with the payment payload.
+///
+/// ```json
+/// {
+///   "x402Payment": "eyJ4NDAyVmVyc2lvbiI6Mi...",
+///   "body": { "query": "original request body" }
+/// }
+/// ```
+///
+/// The resource server removes the envelope before the request reaches the
+/// protected handler, which sees `body` as the request body. A `null` or missing
+/// `body` stands for an empty request body. Bodies with any other top-level field
+/// are not envelopes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PaymentEnvelope {
+    /// Base64-encoded payment payload.
+    pub x402_payment: String,
+    /// The original JSON request body.
+    #[serde(default)]
+    pub body: serde_json::Value,
+}
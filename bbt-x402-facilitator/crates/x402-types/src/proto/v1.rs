@@ -10,7 +10,7 @@
 //! - [`PaymentRequirements`] - Payment terms set by the seller
 //! - [`PaymentRequired`] - HTTP 402 response body
 //! - [`VerifyRequest`] / [`VerifyResponse`] - Verification messages
-//! - [`SettleResponse`] - Settlement result
+//! - [`SettleResponse`] - Settlement result, with the [`SettlementReceipt`] details
 //! - [`PriceTag`] - Builder for creating payment requirements
 
 use serde::de::DeserializeOwned;
@@ -22,6 +22,7 @@ use std::sync::Arc;
 
 use crate::proto;
 use crate::proto::SupportedResponse;
+use crate::proto::transport::PaymentTransport;
 
 /// Version marker for x402 protocol version 1.
 ///
@@ -79,6 +80,7 @@ impl Display for X402Version1 {
 /// Response from a payment settlement request.
 ///
 /// Indicates whether the payment was successfully settled on-chain.
+#[allow(clippy::large_enum_variant)]
 pub enum SettleResponse {
     /// Settlement succeeded.
     Success {
@@ -88,6 +90,8 @@ pub enum SettleResponse {
         transaction: String,
         /// The network where settlement occurred.
         network: String,
+        /// Details from the transaction receipt.
+        receipt: SettlementReceipt,
     },
     /// Settlement failed.
     Error {
@@ -98,6 +102,46 @@ pub enum SettleResponse {
     },
 }
 
+/// Details of a settled payment, taken from the transaction receipt.
+///
+/// Every field is optional: schemes fill in what their chain reports, and
+/// responses from older facilitators carry none of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettlementReceipt {
+    /// Block the settlement transaction was included in.
+    pub block_number: Option<u64>,
+    /// Gas used by the settlement transaction.
+    pub gas_used: Option<u64>,
+    /// Address of the facilitator signer that submitted the settlement transaction.
+    pub signer: Option<String>,
+    /// Price paid per unit of gas, in wei. Serialized as a decimal string.
+    pub effective_gas_price: Option<u128>,
+    /// Fee the facilitator charged for the settlement, in the smallest unit of the
+    /// payment asset. Not set by facilitators that settle for free.
+    pub facilitator_fee: Option<String>,
+    /// Amount transferred to the payee, in the smallest unit of the payment asset.
+    /// Above the required amount when the requirements accept overpayment.
+    pub settled_amount: Option<String>,
+    /// Confirmations the transaction had when the facilitator responded.
+    pub confirmations: Option<u64>,
+    /// Confirmations the chain requires before a settlement counts as final.
+    pub required_confirmations: Option<u64>,
+    /// Facilitator IOU for a payment accepted for deferred settlement, set instead of
+    /// the transaction details until the payment is settled on chain.
+    pub iou: Option<serde_json::Value>,
+}
+
+impl SettlementReceipt {
+    /// Returns `true` if the transaction has fewer confirmations than required,
+    /// which happens when the payer asked not to wait for them.
+    pub fn is_pending(&self) -> bool {
+        matches!(
+            (self.confirmations, self.required_confirmations),
+            (Some(confirmations), Some(required)) if confirmations < required
+        )
+    }
+}
+
 impl From<SettleResponse> for proto::SettleResponse {
     fn from(val: SettleResponse) -> Self {
         proto::SettleResponse(
@@ -116,6 +160,51 @@ struct SettleResponseWire {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transaction: Option<String>,
     pub network: String,
+    #[serde(
+        rename = "blockNumber",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub block_number: Option<u64>,
+    #[serde(rename = "gasUsed", default, skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    #[serde(
+        rename = "effectiveGasPrice",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub effective_gas_price: Option<String>,
+    #[serde(
+        rename = "facilitatorFee",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub facilitator_fee: Option<String>,
+    #[serde(
+        rename = "settledAmount",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub settled_amount: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmations: Option<u64>,
+    #[serde(
+        rename = "requiredConfirmations",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub required_confirmations: Option<u64>,
+    /// `pending` or `confirmed`, derived from the two fields above.
+    #[serde(
+        rename = "confirmationStatus",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub confirmation_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iou: Option<serde_json::Value>,
 }
 
 impl Serialize for SettleResponse {
@@ -128,12 +217,29 @@ impl Serialize for SettleResponse {
                 payer,
                 transaction,
                 network,
+                receipt,
             } => SettleResponseWire {
                 success: true,
                 error_reason: None,
                 payer: Some(payer.clone()),
                 transaction: Some(transaction.clone()),
                 network: network.clone(),
+                block_number: receipt.block_number,
+                gas_used: receipt.gas_used,
+                signer: receipt.signer.clone(),
+                effective_gas_price: receipt.effective_gas_price.map(|price| price.to_string()),
+                facilitator_fee: receipt.facilitator_fee.clone(),
+                settled_amount: receipt.settled_amount.clone(),
+                confirmations: receipt.confirmations,
+                required_confirmations: receipt.required_confirmations,
+                confirmation_status: receipt.required_confirmations.map(|_| {
+                    if receipt.is_pending() {
+                        "pending".to_string()
+                    } else {
+                        "confirmed".to_string()
+                    }
+                }),
+                iou: receipt.iou.clone(),
             },
             SettleResponse::Error { reason, network } => SettleResponseWire {
                 success: false,
@@ -141,6 +247,16 @@ impl Serialize for SettleResponse {
                 payer: None,
                 transaction: None,
                 network: network.clone(),
+                block_number: None,
+                gas_used: None,
+                signer: None,
+                effective_gas_price: None,
+                facilitator_fee: None,
+                settled_amount: None,
+                confirmations: None,
+                required_confirmations: None,
+                confirmation_status: None,
+                iou: None,
             },
         };
         wire.serialize(serializer)
@@ -161,10 +277,26 @@ impl<'de> Deserialize<'de> for SettleResponse {
                 let transaction = wire
                     .transaction
                     .ok_or_else(|| serde::de::Error::missing_field("transaction"))?;
+                let effective_gas_price = wire
+                    .effective_gas_price
+                    .map(|price| price.parse::<u128>())
+                    .transpose()
+                    .map_err(serde::de::Error::custom)?;
                 Ok(SettleResponse::Success {
                     payer,
                     transaction,
                     network: wire.network,
+                    receipt: SettlementReceipt {
+                        block_number: wire.block_number,
+                        gas_used: wire.gas_used,
+                        signer: wire.signer,
+                        effective_gas_price,
+                        facilitator_fee: wire.facilitator_fee,
+                        settled_amount: wire.settled_amount,
+                        confirmations: wire.confirmations,
+                        required_confirmations: wire.required_confirmations,
+                        iou: wire.iou,
+                    },
                 })
             }
             false => {
@@ -303,9 +435,9 @@ where
     Self: DeserializeOwned,
 {
     pub fn from_proto(
-        request: proto::VerifyRequest,
+        request: &proto::VerifyRequest,
     ) -> Result<Self, proto::PaymentVerificationError> {
-        let deserialized: Self = serde_json::from_value(request.into_json())?;
+        let deserialized: Self = serde_json::from_str(request.as_raw().get())?;
         Ok(deserialized)
     }
 }
@@ -318,8 +450,8 @@ where
 {
     type Error = serde_json::Error;
     fn try_into(self) -> Result<proto::VerifyRequest, Self::Error> {
-        let json = serde_json::to_value(self)?;
-        Ok(proto::VerifyRequest(json))
+        let raw = serde_json::value::to_raw_value(&self)?;
+        Ok(proto::VerifyRequest::from(raw))
     }
 }
 
@@ -369,6 +501,13 @@ pub struct PaymentRequirements<
     /// The network name (e.g., "etherlink").
     pub network: String,
     /// The maximum amount required for payment.
+    #[serde(
+        with = "super::amount",
+        bound(
+            serialize = "TAmount: Display",
+            deserialize = "TAmount: FromStr, TAmount::Err: Display"
+        )
+    )]
     pub max_amount_required: TAmount,
     /// The resource URL being paid for.
     pub resource: String,
@@ -439,6 +578,9 @@ pub struct PaymentRequired {
     /// Optional error message if the request was malformed.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Transports accepted for the payment payload. Empty means header only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transports: Vec<PaymentTransport>,
 }
 
 /// Builder for creating payment requirements.
@@ -23,6 +23,7 @@
 use crate::chain::ChainId;
 use crate::proto;
 use crate::proto::SupportedResponse;
+use crate::proto::transport::PaymentTransport;
 use crate::proto::v1;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -94,10 +95,14 @@ pub type VerifyResponse = v1::VerifyResponse;
 /// V2 uses the same response format as V1.
 pub type SettleResponse = v1::SettleResponse;
 
+/// Receipt details of a V2 settlement, same as V1.
+pub type SettlementReceipt = v1::SettlementReceipt;
+
 /// Metadata about the resource being paid for.
 ///
 /// This provides human-readable information about what the buyer is paying for.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceInfo {
     /// Human-readable description of the resource.
@@ -127,9 +132,9 @@ where
     Self: DeserializeOwned,
 {
     pub fn from_proto(
-        request: proto::VerifyRequest,
+        request: &proto::VerifyRequest,
     ) -> Result<Self, proto::PaymentVerificationError> {
-        let deserialized: Self = serde_json::from_value(request.into_json())?;
+        let deserialized: Self = serde_json::from_str(request.as_raw().get())?;
         Ok(deserialized)
     }
 }
@@ -180,6 +185,13 @@ pub struct PaymentRequirements<
     /// The CAIP-2 chain ID (e.g., "eip155:42793").
     pub network: ChainId,
     /// The payment amount in token units.
+    #[serde(
+        with = "super::amount",
+        bound(
+            serialize = "TAmount: Display",
+            deserialize = "TAmount: FromStr, TAmount::Err: Display"
+        )
+    )]
     pub amount: TAmount,
     /// The recipient address for payment.
     pub pay_to: TAddress,
@@ -239,6 +251,9 @@ pub struct PaymentRequired {
     /// List of acceptable payment methods.
     #[serde(default)]
     pub accepts: Vec<PaymentRequirements>,
+    /// Transports accepted for the payment payload. Empty means header only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transports: Vec<PaymentTransport>,
 }
 
 /// Builder for creating V2 payment requirements.
@@ -16,6 +16,8 @@
 //! - [`FirstMatch`] - Takes the first available option
 //! - [`PreferChain`] - Prefers specific chains in priority order
 //! - [`MaxAmount`] - Only accepts payments up to a maximum amount
+//! - [`PaymentPreferences`] - Ranks candidates by preferred schemes, transfer
+//!   methods, chains, assets and protocol versions
 
 use alloy_primitives::U256;
 use async_trait::async_trait;
@@ -23,6 +25,7 @@ use async_trait::async_trait;
 use crate::chain::{ChainId, ChainIdPattern};
 use crate::proto;
 use crate::scheme::X402SchemeId;
+use crate::timestamp::UnixTimestamp;
 
 /// A payment option that can be signed and submitted.
 ///
@@ -42,6 +45,8 @@ pub struct PaymentCandidate {
     pub x402_version: u8,
     /// The recipient address.
     pub pay_to: String,
+    /// How the signed authorization moves the funds, e.g. `"eip3009"` or `"permit2"`.
+    pub transfer_method: Option<String>,
     /// The signer that can authorize this payment.
     pub signer: Box<dyn PaymentCandidateSigner + Send + Sync>,
 }
@@ -52,6 +57,32 @@ impl PaymentCandidate {
     pub async fn sign(&self) -> Result<String, X402Error> {
         self.signer.sign_payment().await
     }
+
+    /// Signs this payment candidate with an explicit validity window.
+    ///
+    /// See [`PaymentCandidateSigner::sign_payment_within`].
+    #[allow(dead_code)] // Public for consumption by downstream crates.
+    pub async fn sign_within(&self, window: ValidityWindow) -> Result<String, X402Error> {
+        self.signer.sign_payment_within(window).await
+    }
+}
+
+/// Time bounds of a signed payment authorization.
+///
+/// The authorization can be executed when `valid_after <= now < valid_before`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidityWindow {
+    /// The earliest time the authorization can be executed.
+    pub valid_after: UnixTimestamp,
+    /// The time the authorization expires.
+    pub valid_before: UnixTimestamp,
+}
+
+impl ValidityWindow {
+    /// Returns `true` if the window is open at `at`.
+    pub fn contains(&self, at: UnixTimestamp) -> bool {
+        self.valid_after <= at && at < self.valid_before
+    }
 }
 
 /// Trait for scheme clients that can process payment requirements.
@@ -71,6 +102,18 @@ pub trait X402SchemeClient: X402SchemeId + Send + Sync {
 pub trait PaymentCandidateSigner {
     /// Signs a payment authorization.
     async fn sign_payment(&self) -> Result<String, X402Error>;
+
+    /// Signs a payment authorization restricted to the given validity window.
+    ///
+    /// This allows authorizations to be signed ahead of time. Schemes that do not
+    /// carry time bounds in their authorizations keep the default implementation,
+    /// which returns [`X402Error::SigningError`].
+    async fn sign_payment_within(&self, window: ValidityWindow) -> Result<String, X402Error> {
+        let _ = window;
+        Err(X402Error::SigningError(
+            "scheme does not support explicit validity windows".to_string(),
+        ))
+    }
 }
 
 /// Errors that can occur during client-side payment processing.
@@ -85,6 +128,10 @@ pub enum X402Error {
     #[error("Request is not cloneable (streaming body?)")]
     RequestNotCloneable,
 
+    /// The HTTP request failed before a response was received.
+    #[error("HTTP request failed: {0}")]
+    Http(String),
+
     /// Failed to parse the 402 response body.
     #[error("Failed to parse 402 response: {0}")]
     ParseError(String),
@@ -93,11 +140,51 @@ pub enum X402Error {
     #[error("Failed to sign payment: {0}")]
     SigningError(String),
 
+    /// Paying would exceed a spend limit of the client's budget.
+    #[error(transparent)]
+    BudgetExceeded(Box<BudgetExceeded>),
+
     /// JSON serialization/deserialization error.
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
 }
 
+/// A payment refused because it would exceed a client spend limit.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "Payment of {amount} of {asset} on {chain_id} exceeds the {window} spend limit ({remaining} left)"
+)]
+pub struct BudgetExceeded {
+    /// The limit that would be exceeded.
+    pub window: SpendWindow,
+    pub chain_id: ChainId,
+    pub asset: String,
+    pub amount: U256,
+    /// What the limit still allows.
+    pub remaining: U256,
+}
+
+/// The span of a client spend limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpendWindow {
+    /// A single payment.
+    Request,
+    /// Payments of the last hour.
+    Hour,
+    /// All payments made by the client.
+    Total,
+}
+
+impl std::fmt::Display for SpendWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SpendWindow::Request => "per-request",
+            SpendWindow::Hour => "hourly",
+            SpendWindow::Total => "total",
+        })
+    }
+}
+
 // ============================================================================
 // PaymentSelector - Selection strategy
 // ============================================================================
@@ -182,3 +269,183 @@ impl PaymentSelector for MaxAmount {
         candidates.iter().find(|c| c.amount <= self.0)
     }
 }
+
+/// Selector that ranks candidates by ordered preferences.
+///
+/// Each preference list is ordered from most to least preferred. Candidates are
+/// compared on scheme first, then transfer method, chain, asset and protocol
+/// version; values missing from a list rank after all listed ones. Remaining ties
+/// go to the lower amount, then to the candidate listed first by the server.
+///
+/// # Example
+///
+/// ```rust
+/// use x402_types::scheme::client::PaymentPreferences;
+///
+/// let selector = PaymentPreferences::new()
+///     .prefer_transfer_methods(["permit2", "eip3009"])
+///     .prefer_chains(vec!["eip155:42793".parse().unwrap(), "eip155:*".parse().unwrap()])
+///     .prefer_versions([2, 1]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PaymentPreferences {
+    schemes: Vec<String>,
+    transfer_methods: Vec<String>,
+    chains: Vec<ChainIdPattern>,
+    assets: Vec<String>,
+    versions: Vec<u8>,
+    max_amount: Option<U256>,
+}
+
+impl PaymentPreferences {
+    /// Creates a selector without preferences, which picks the cheapest candidate.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefers payment schemes, such as `"exact"`, in the given order.
+    pub fn prefer_schemes<I: IntoIterator<Item = impl Into<String>>>(mut self, schemes: I) -> Self {
+        self.schemes = schemes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Prefers transfer methods, such as `"permit2"` or `"eip3009"`, in the given order.
+    pub fn prefer_transfer_methods<I: IntoIterator<Item = impl Into<String>>>(
+        mut self,
+        methods: I,
+    ) -> Self {
+        self.transfer_methods = methods.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Prefers chains matching the given patterns, in order.
+    pub fn prefer_chains<P: Into<Vec<ChainIdPattern>>>(mut self, patterns: P) -> Self {
+        self.chains = patterns.into();
+        self
+    }
+
+    /// Prefers token assets in the given order. Addresses compare case-insensitively.
+    pub fn prefer_assets<I: IntoIterator<Item = impl Into<String>>>(mut self, assets: I) -> Self {
+        self.assets = assets.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Prefers x402 protocol versions in the given order.
+    pub fn prefer_versions<I: IntoIterator<Item = u8>>(mut self, versions: I) -> Self {
+        self.versions = versions.into_iter().collect();
+        self
+    }
+
+    /// Never selects candidates above `amount`.
+    pub fn with_max_amount(mut self, amount: U256) -> Self {
+        self.max_amount = Some(amount);
+        self
+    }
+
+    fn rank(&self, candidate: &PaymentCandidate) -> (usize, usize, usize, usize, usize, U256) {
+        fn position<T>(list: &[T], matches: impl Fn(&T) -> bool) -> usize {
+            list.iter().position(matches).unwrap_or(list.len())
+        }
+        (
+            position(&self.schemes, |s| *s == candidate.scheme),
+            position(&self.transfer_methods, |m| {
+                candidate.transfer_method.as_deref() == Some(m.as_str())
+            }),
+            position(&self.chains, |p| p.matches(&candidate.chain_id)),
+            position(&self.assets, |a| a.eq_ignore_ascii_case(&candidate.asset)),
+            position(&self.versions, |v| *v == candidate.x402_version),
+            candidate.amount,
+        )
+    }
+}
+
+impl PaymentSelector for PaymentPreferences {
+    fn select<'a>(&self, candidates: &'a [PaymentCandidate]) -> Option<&'a PaymentCandidate> {
+        candidates
+            .iter()
+            .filter(|c| self.max_amount.is_none_or(|max| c.amount <= max))
+            .min_by_key(|c| self.rank(c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoopSigner;
+
+    #[async_trait]
+    impl PaymentCandidateSigner for NoopSigner {
+        async fn sign_payment(&self) -> Result<String, X402Error> {
+            Ok(String::new())
+        }
+    }
+
+    fn candidate(chain: &str, method: &str, version: u8, amount: u64) -> PaymentCandidate {
+        PaymentCandidate {
+            chain_id: chain.parse().unwrap(),
+            asset: "0xAAA0000000000000000000000000000000000001".to_string(),
+            amount: U256::from(amount),
+            scheme: "exact".to_string(),
+            x402_version: version,
+            pay_to: "0xBBB0000000000000000000000000000000000002".to_string(),
+            transfer_method: Some(method.to_string()),
+            signer: Box::new(NoopSigner),
+        }
+    }
+
+    #[test]
+    fn test_payment_preferences() {
+        let candidates = [
+            candidate("eip155:1", "eip3009", 1, 100),
+            candidate("eip155:42793", "eip3009", 2, 300),
+            candidate("eip155:42793", "permit2", 1, 200),
+            candidate("eip155:42793", "permit2", 2, 200),
+        ];
+        let select = |preferences: PaymentPreferences| {
+            let selected = preferences.select(&candidates).unwrap();
+            candidates
+                .iter()
+                .position(|c| std::ptr::eq(c, selected))
+                .unwrap()
+        };
+
+        assert_eq!(select(PaymentPreferences::new()), 0);
+        assert_eq!(
+            select(PaymentPreferences::new().prefer_transfer_methods(["permit2"])),
+            2
+        );
+        assert_eq!(
+            select(
+                PaymentPreferences::new()
+                    .prefer_transfer_methods(["permit2"])
+                    .prefer_versions([2])
+            ),
+            3
+        );
+        assert_eq!(
+            select(
+                PaymentPreferences::new()
+                    .prefer_chains(vec!["eip155:42793".parse().unwrap()])
+                    .prefer_assets(["0xaaa0000000000000000000000000000000000001"])
+                    .prefer_versions([2, 1])
+            ),
+            3
+        );
+        assert_eq!(
+            select(
+                PaymentPreferences::new()
+                    .prefer_schemes(["upto", "exact"])
+                    .prefer_chains(vec!["eip155:42793".parse().unwrap()])
+                    .with_max_amount(U256::from(150))
+            ),
+            0
+        );
+        assert!(
+            PaymentPreferences::new()
+                .with_max_amount(U256::from(50))
+                .select(&candidates)
+                .is_none()
+        );
+    }
+}
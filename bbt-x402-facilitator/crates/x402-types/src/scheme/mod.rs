@@ -39,6 +39,7 @@ use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
 use std::marker::PhantomData;
 use std::ops::Deref;
+use std::time::Duration;
 
 /// Trait for scheme handlers that process payment verification and settlement.
 ///
@@ -64,6 +65,62 @@ pub trait X402SchemeFacilitator: Send + Sync {
         request: &proto::SettleRequest,
     ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError>;
 
+    /// Runs settlement up to, but not including, broadcasting.
+    ///
+    /// Returns the transactions that [`Self::settle`] would send and their
+    /// estimated cost. Schemes without dry-run support reject the request.
+    async fn settle_dry_run(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleDryRunResponse, X402SchemeFacilitatorError> {
+        let _ = request;
+        Err(PaymentVerificationError::TransactionSimulation(
+            "settlement dry run is not supported by this scheme".to_string(),
+        )
+        .into())
+    }
+
+    /// Sends a settled payment back to its payer from a facilitator-controlled treasury.
+    ///
+    /// Schemes without refund support reject the request.
+    async fn refund(
+        &self,
+        request: &proto::RefundRequest,
+    ) -> Result<proto::RefundResponse, X402SchemeFacilitatorError> {
+        let _ = request;
+        Err(PaymentVerificationError::UnsupportedScheme.into())
+    }
+
+    /// Returns the status of the recurring payment authorization `id`.
+    ///
+    /// Schemes without recurring payments track no subscription and return `None`.
+    async fn subscription(
+        &self,
+        id: &str,
+    ) -> Result<Option<proto::SubscriptionResponse>, X402SchemeFacilitatorError> {
+        let _ = id;
+        Ok(None)
+    }
+
+    /// Explains `request` without reading the chain, for debugging integrations.
+    ///
+    /// Schemes without payload explanations return `None`.
+    async fn explain(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<Option<proto::ExplainResponse>, X402SchemeFacilitatorError> {
+        let _ = request;
+        Ok(None)
+    }
+
+    /// Settles the payments accepted for deferred settlement whose settlement is due.
+    ///
+    /// Called periodically by the facilitator. Schemes settling every payment right
+    /// away have nothing to flush.
+    async fn flush(&self) -> Result<(), X402SchemeFacilitatorError> {
+        Ok(())
+    }
+
     /// Returns the payment methods supported by this handler.
     async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError>;
 }
@@ -132,6 +189,10 @@ pub enum X402SchemeFacilitatorError {
     /// On-chain operation failed.
     #[error("Onchain error: {0}")]
     OnchainFailure(String),
+    /// The chain is settling as many payments as it is allowed to; try again after
+    /// `retry_after`.
+    #[error("Settlement capacity exhausted, retry after {}s", retry_after.as_secs())]
+    Overloaded { retry_after: Duration },
 }
 
 impl AsPaymentProblem for X402SchemeFacilitatorError {
@@ -141,6 +202,9 @@ impl AsPaymentProblem for X402SchemeFacilitatorError {
             X402SchemeFacilitatorError::OnchainFailure(e) => {
                 PaymentProblem::new(ErrorReason::UnexpectedError, e.to_string())
             }
+            X402SchemeFacilitatorError::Overloaded { .. } => {
+                PaymentProblem::new(ErrorReason::UnexpectedError, self.to_string())
+            }
         }
     }
 }
@@ -238,17 +302,62 @@ impl Debug for SchemeRegistry {
     }
 }
 
+/// A scheme handler that could not be built from its configuration.
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to build scheme {id} for {chain_id}: {source}")]
+pub struct SchemeBuildError {
+    /// The scheme id.
+    pub id: String,
+    /// The chain the handler was built for.
+    pub chain_id: ChainId,
+    /// The error returned by the scheme's builder.
+    pub source: Box<dyn std::error::Error>,
+}
+
 impl SchemeRegistry {
     /// Builds a scheme registry from blueprints and configuration.
     ///
     /// For each enabled scheme in the config, this finds the matching blueprint
-    /// and chain provider, then builds a handler.
+    /// and chain provider, then builds a handler. Handlers that fail to build are
+    /// logged and skipped; use [`SchemeRegistry::try_build`] to fail instead.
     pub fn build<P: ChainProviderOps>(
         chains: ChainRegistry<P>,
         blueprints: SchemeBlueprints<P>,
         config: &Vec<SchemeConfig>,
     ) -> Self {
+        let (registry, _errors) = Self::build_handlers(chains, blueprints, config);
+        #[cfg(feature = "telemetry")]
+        for error in _errors {
+            tracing::error!(
+                "Error building scheme handler for {}: {}",
+                error.id,
+                error.source
+            );
+        }
+        registry
+    }
+
+    /// Builds a scheme registry like [`SchemeRegistry::build`], but fails if any
+    /// handler cannot be built, e.g. because its `config` is malformed.
+    pub fn try_build<P: ChainProviderOps>(
+        chains: ChainRegistry<P>,
+        blueprints: SchemeBlueprints<P>,
+        config: &Vec<SchemeConfig>,
+    ) -> Result<Self, SchemeBuildError> {
+        let (registry, errors) = Self::build_handlers(chains, blueprints, config);
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(registry),
+        }
+    }
+
+    fn build_handlers<P: ChainProviderOps>(
+        chains: ChainRegistry<P>,
+        blueprints: SchemeBlueprints<P>,
+        config: &Vec<SchemeConfig>,
+    ) -> (Self, Vec<SchemeBuildError>) {
         let mut handlers = HashMap::with_capacity(config.len());
+        let mut errors = Vec::new();
         for config in config {
             if !config.enabled {
                 #[cfg(feature = "telemetry")]
@@ -278,13 +387,12 @@ impl SchemeRegistry {
                 let chain_id = chain_provider.chain_id();
                 let handler = match blueprint.build(chain_provider, config.config.clone()) {
                     Ok(handler) => handler,
-                    Err(_err) => {
-                        #[cfg(feature = "telemetry")]
-                        tracing::error!(
-                            "Error building scheme handler for {}: {}",
-                            config.id,
-                            _err
-                        );
+                    Err(source) => {
+                        errors.push(SchemeBuildError {
+                            id: config.id.clone(),
+                            chain_id,
+                            source,
+                        });
                         continue;
                     }
                 };
@@ -298,7 +406,7 @@ impl SchemeRegistry {
                 handlers.insert(slug, handler);
             }
         }
-        Self(handlers)
+        (Self(handlers), errors)
     }
 
     /// Gets a handler by its slug.
@@ -311,12 +419,18 @@ impl SchemeRegistry {
     pub fn values(&self) -> impl Iterator<Item = &dyn X402SchemeFacilitator> {
         self.0.values().map(|v| v.deref())
     }
+
+    /// Returns an iterator over all registered handlers and their slugs.
+    pub fn iter(&self) -> impl Iterator<Item = (&SchemeHandlerSlug, &dyn X402SchemeFacilitator)> {
+        self.0.iter().map(|(slug, v)| (slug, v.deref()))
+    }
 }
 
 /// Configuration for a specific scheme.
 ///
 /// Each scheme entry specifies which scheme to use and which chains it applies to.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SchemeConfig {
     /// Whether this scheme is enabled (defaults to true).
     #[serde(default = "scheme_config_defaults::default_enabled")]
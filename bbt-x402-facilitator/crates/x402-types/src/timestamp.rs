@@ -4,12 +4,47 @@
 //! to represent time-bounded payment authorizations. Timestamps are used in ERC-3009
 //! `transferWithAuthorization` messages and Permit2 authorizations to specify
 //! when a payment authorization becomes valid and when it expires.
+//!
+//! Integers are always read as seconds, as signed fields must be: far-future
+//! sentinels such as `253402300799` (9999-12-31) or Permit2's `2^48 - 1`
+//! expiration are valid seconds. Unsigned fields where clients plausibly send
+//! milliseconds, such as query bounds, opt into reading them with [`lenient`].
 
+use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::{Display, Formatter};
 use std::ops::Add;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::SystemTime;
 
+/// With [`lenient`], integers from here on are too large to be seconds of any
+/// plausible date (year 5138), and are read as milliseconds.
+const MILLIS_MIN: u64 = 1_000_000_000_000;
+
+/// With [`lenient`], integers from here up to [`MILLIS_MIN`] are rejected: as
+/// seconds they are after year 5138, as milliseconds before 2001.
+const SECS_MAX: u64 = 100_000_000_000;
+
+/// With [`lenient`], integers from here on are rejected: as milliseconds they are
+/// after year 5138.
+const MILLIS_MAX: u64 = SECS_MAX * 1000;
+
+/// Errors from parsing a [`UnixTimestamp`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TimestampParseError {
+    /// The integer could be seconds or milliseconds, and both are implausible.
+    #[error("timestamp {0} is ambiguous: too large for seconds, too small for milliseconds")]
+    Ambiguous(u64),
+    /// The integer is too large for seconds and for milliseconds.
+    #[error("timestamp {0} is out of range")]
+    OutOfRange(u64),
+    /// The value is neither an integer nor an ISO-8601 date-time.
+    #[error("timestamp must be a non-negative integer or an ISO-8601 date-time, got {0:?}")]
+    Invalid(String),
+}
+
 /// A Unix timestamp representing seconds since the Unix epoch (1970-01-01T00:00:00Z).
 ///
 /// This type is used throughout the x402 protocol for time-bounded payment authorizations:
@@ -26,6 +61,16 @@ use std::time::SystemTime;
 /// "1699999999"
 /// ```
 ///
+/// Deserialization accepts, as JSON strings or numbers:
+///
+/// - seconds, whatever their magnitude,
+/// - ISO-8601 date-times with an offset, such as `"2023-11-14T22:13:19Z"` or
+///   `"2023-11-15T00:13:19.250+02:00"`, truncated to seconds.
+///
+/// Fields deserialized with [`lenient`] also read milliseconds. Note that signed
+/// authorizations commit to the exact value: a payload carrying a date-time
+/// decodes, but its signature only verifies if it was made over seconds.
+///
 /// # Example
 ///
 /// ```
@@ -55,11 +100,78 @@ impl<'de> Deserialize<'de> for UnixTimestamp {
     where
         D: Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        let ts = s
-            .parse::<u64>()
-            .map_err(|_| serde::de::Error::custom("timestamp must be a non-negative integer"))?;
-        Ok(UnixTimestamp(ts))
+        deserializer.deserialize_any(UnixTimestampVisitor { millis: false })
+    }
+}
+
+/// Deserializes a [`UnixTimestamp`] that may also be given in milliseconds.
+///
+/// For fields that are not signed, where a client may send `Date.now()`: integers
+/// from `1000000000000` (year 2001) are read as milliseconds and truncated to
+/// seconds, and integers between `100000000000` (year 5138 in seconds) and that
+/// are rejected as ambiguous. Use with
+/// `#[serde(deserialize_with = "x402_types::timestamp::lenient::deserialize")]`, on
+/// a `UnixTimestamp` or an `Option<UnixTimestamp>` field with `#[serde(default)]`.
+pub mod lenient {
+    use super::{UnixTimestamp, UnixTimestampVisitor};
+    use serde::Deserializer;
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<UnixTimestamp>,
+    {
+        deserializer
+            .deserialize_any(UnixTimestampVisitor { millis: true })
+            .map(T::from)
+    }
+}
+
+/// Reads integers as seconds, or as milliseconds when large enough if `millis` is set.
+struct UnixTimestampVisitor {
+    millis: bool,
+}
+
+impl Visitor<'_> for UnixTimestampVisitor {
+    type Value = UnixTimestamp;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        if self.millis {
+            formatter
+                .write_str("a Unix timestamp in seconds or milliseconds, or an ISO-8601 date-time")
+        } else {
+            formatter.write_str("a Unix timestamp in seconds, or an ISO-8601 date-time")
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        UnixTimestamp::parse(value, self.millis).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        UnixTimestamp::from_integer(value, self.millis).map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        let value = u64::try_from(value)
+            .map_err(|_| E::custom(TimestampParseError::Invalid(value.to_string())))?;
+        self.visit_u64(value)
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        if value.fract() != 0.0 || value < 0.0 || value >= u64::MAX as f64 {
+            return Err(E::custom(TimestampParseError::Invalid(value.to_string())));
+        }
+        self.visit_u64(value as u64)
+    }
+}
+
+impl FromStr for UnixTimestamp {
+    type Err = TimestampParseError;
+
+    /// Parses seconds or an ISO-8601 date-time, see [`UnixTimestamp`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, false)
     }
 }
 
@@ -92,6 +204,30 @@ impl UnixTimestamp {
         Self(secs)
     }
 
+    /// Parses an integer, see [`UnixTimestamp::from_integer`], or an ISO-8601 date-time.
+    fn parse(s: &str, millis: bool) -> Result<Self, TimestampParseError> {
+        if let Ok(value) = s.parse::<u64>() {
+            return Self::from_integer(value, millis);
+        }
+        parse_iso8601(s)
+            .map(Self)
+            .ok_or_else(|| TimestampParseError::Invalid(s.to_string()))
+    }
+
+    /// Reads an integer as seconds or, if `millis` is set, as milliseconds when
+    /// too large for seconds.
+    fn from_integer(value: u64, millis: bool) -> Result<Self, TimestampParseError> {
+        if !millis {
+            return Ok(Self(value));
+        }
+        match value {
+            ..SECS_MAX => Ok(Self(value)),
+            SECS_MAX..MILLIS_MIN => Err(TimestampParseError::Ambiguous(value)),
+            MILLIS_MIN..MILLIS_MAX => Ok(Self(value / 1000)),
+            _ => Err(TimestampParseError::OutOfRange(value)),
+        }
+    }
+
     /// Returns the current system time as a [`UnixTimestamp`].
     ///
     /// # Panics
@@ -130,3 +266,270 @@ impl UnixTimestamp {
         self.0
     }
 }
+
+/// Source of the current time for time-bounded checks.
+///
+/// Verification reads the time through a clock rather than [`UnixTimestamp::now`],
+/// so that tests can check authorizations at chosen instants, such as the second
+/// an authorization expires.
+pub trait Clock: Send + Sync {
+    /// The current time.
+    fn now(&self) -> UnixTimestamp;
+}
+
+/// The system clock, see [`UnixTimestamp::now`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> UnixTimestamp {
+        UnixTimestamp::now()
+    }
+}
+
+/// A clock that only moves when told to, for tests.
+///
+/// # Example
+///
+/// ```
+/// use x402_types::timestamp::{Clock, ManualClock, UnixTimestamp};
+///
+/// let clock = ManualClock::new(UnixTimestamp::from_secs(1000));
+/// clock.advance(60);
+/// assert_eq!(clock.now().as_secs(), 1060);
+/// ```
+#[derive(Debug, Default)]
+pub struct ManualClock(AtomicU64);
+
+impl ManualClock {
+    pub fn new(now: UnixTimestamp) -> Self {
+        Self(AtomicU64::new(now.as_secs()))
+    }
+
+    /// Moves the clock to `now`.
+    pub fn set(&self, now: UnixTimestamp) {
+        self.0.store(now.as_secs(), Ordering::Relaxed);
+    }
+
+    /// Moves the clock `secs` seconds forward.
+    pub fn advance(&self, secs: u64) {
+        self.0.fetch_add(secs, Ordering::Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> UnixTimestamp {
+        UnixTimestamp(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// A cloneable handle to a [`Clock`], the [`SystemClock`] by default.
+///
+/// Held by scheme configurations, which are cloned into every facilitator built
+/// from them.
+#[derive(Clone)]
+pub struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        Self(Arc::new(clock))
+    }
+}
+
+impl From<Arc<dyn Clock>> for SharedClock {
+    fn from(clock: Arc<dyn Clock>) -> Self {
+        Self(clock)
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
+impl std::fmt::Debug for SharedClock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SharedClock({})", self.0.now())
+    }
+}
+
+impl Clock for SharedClock {
+    fn now(&self) -> UnixTimestamp {
+        self.0.now()
+    }
+}
+
+/// Parses an RFC 3339 date-time, the ISO-8601 profile with a mandatory offset,
+/// into seconds since the epoch. Fractional seconds are truncated.
+fn parse_iso8601(s: &str) -> Option<u64> {
+    fn number(s: &str, range: std::ops::RangeInclusive<i64>) -> Option<i64> {
+        if !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        s.parse().ok().filter(|n| range.contains(n))
+    }
+
+    let bytes = s.as_bytes();
+    if bytes.len() < 20 || ![b'T', b't', b' '].contains(&bytes[10]) {
+        return None;
+    }
+    let (date, time) = (s.get(..10)?, s.get(11..)?);
+    let (year, month, day) = match date.split('-').collect::<Vec<_>>()[..] {
+        [y, m, d] if y.len() == 4 && m.len() == 2 && d.len() == 2 => (
+            number(y, 1970..=9999)?,
+            number(m, 1..=12)?,
+            number(d, 1..=31)?,
+        ),
+        _ => return None,
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if day > month_days {
+        return None;
+    }
+
+    let (clock, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(i) => time.split_at(i),
+        None => return None,
+    };
+    let clock = clock
+        .split_once('.')
+        .map_or(Some(clock), |(clock, fraction)| {
+            (!fraction.is_empty() && fraction.bytes().all(|b| b.is_ascii_digit())).then_some(clock)
+        })?;
+    let (hour, minute, second) = match clock.split(':').collect::<Vec<_>>()[..] {
+        [h, m, s] if h.len() == 2 && m.len() == 2 && s.len() == 2 => {
+            (number(h, 0..=23)?, number(m, 0..=59)?, number(s, 0..=60)?)
+        }
+        _ => return None,
+    };
+    let offset_secs = match offset {
+        "Z" | "z" => 0,
+        _ => match offset[1..].split(':').collect::<Vec<_>>()[..] {
+            [h, m] if h.len() == 2 && m.len() == 2 => {
+                let secs = number(h, 0..=23)? * 3600 + number(m, 0..=59)? * 60;
+                if offset.starts_with('-') { -secs } else { secs }
+            }
+            _ => return None,
+        },
+    };
+
+    // Days since the epoch of a proleptic Gregorian date, counting years from March.
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let days = y * 365 + y / 4 - y / 100 + y / 400 + (153 * m + 2) / 5 + day - 1 - 719_468;
+    let secs = days * 86_400 + hour * 3600 + minute * 60 + second - offset_secs;
+    u64::try_from(secs).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(value: serde_json::Value) -> Result<u64, String> {
+        serde_json::from_value::<UnixTimestamp>(value)
+            .map(|ts| ts.as_secs())
+            .map_err(|e| e.to_string())
+    }
+
+    fn parse_lenient(value: serde_json::Value) -> Result<u64, String> {
+        #[derive(Deserialize)]
+        struct Query {
+            #[serde(default, deserialize_with = "lenient::deserialize")]
+            from: Option<UnixTimestamp>,
+        }
+        serde_json::from_value::<Query>(json!({ "from": value }))
+            .map(|query| query.from.unwrap().as_secs())
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_timestamp_formats() {
+        assert_eq!(parse(json!("1699999999")), Ok(1699999999));
+        assert_eq!(parse(json!(1699999999)), Ok(1699999999));
+        assert_eq!(parse(json!(0)), Ok(0));
+        assert_eq!(parse(json!(1699999999.0)), Ok(1699999999));
+        assert_eq!(parse(json!("2023-11-14T22:13:19Z")), Ok(1699999999));
+        assert_eq!(parse(json!("2023-11-14t22:13:19.999z")), Ok(1699999999));
+        assert_eq!(parse(json!("2023-11-15T00:13:19+02:00")), Ok(1699999999));
+        assert_eq!(parse(json!("2023-11-14 17:13:19.5-05:00")), Ok(1699999999));
+        assert_eq!(parse(json!("1970-01-01T00:00:00Z")), Ok(0));
+        assert_eq!(parse(json!("2024-02-29T00:00:00Z")), Ok(1709164800));
+
+        for invalid in [
+            json!(-1),
+            json!(1.5),
+            json!(""),
+            json!("soon"),
+            json!("2023-11-14T22:13:19"),
+            json!("2023-02-29T00:00:00Z"),
+            json!("2023-11-14T24:00:00Z"),
+            json!("2023-11-14T22:13:19.Z"),
+            json!("1969-12-31T23:59:59Z"),
+            json!(null),
+        ] {
+            assert!(parse(invalid.clone()).is_err(), "{invalid}");
+        }
+
+        let ts = UnixTimestamp::from_secs(1699999999);
+        assert_eq!(serde_json::to_value(ts).unwrap(), json!("1699999999"));
+    }
+
+    #[test]
+    fn test_far_future_sentinels_stay_seconds() {
+        // 9999-12-31T23:59:59Z, a common "no expiry" value.
+        let end_of_time = 253_402_300_799u64;
+        assert_eq!(parse(json!(end_of_time)), Ok(end_of_time));
+        assert_eq!(parse(json!(end_of_time.to_string())), Ok(end_of_time));
+        assert_eq!(parse(json!("9999-12-31T23:59:59Z")), Ok(end_of_time));
+        // The largest Permit2 `expiration`, a uint48.
+        let permit2_max = (1u64 << 48) - 1;
+        assert_eq!(parse(json!(permit2_max)), Ok(permit2_max));
+        assert_eq!(parse(json!(permit2_max.to_string())), Ok(permit2_max));
+        assert_eq!(parse(json!(u64::MAX.to_string())), Ok(u64::MAX));
+
+        let ts = UnixTimestamp::from_secs(permit2_max);
+        let json = serde_json::to_value(ts).unwrap();
+        assert_eq!(serde_json::from_value::<UnixTimestamp>(json).unwrap(), ts);
+    }
+
+    #[test]
+    fn test_lenient_timestamp_reads_milliseconds() {
+        assert_eq!(parse_lenient(json!(1699999999)), Ok(1699999999));
+        assert_eq!(parse_lenient(json!("1699999999250")), Ok(1699999999));
+        assert_eq!(parse_lenient(json!(1699999999250u64)), Ok(1699999999));
+        assert_eq!(parse_lenient(json!("2023-11-14T22:13:19Z")), Ok(1699999999));
+        assert!(
+            parse_lenient(json!(500_000_000_000u64))
+                .unwrap_err()
+                .contains("ambiguous")
+        );
+        assert!(
+            parse_lenient(json!("99999999999999999"))
+                .unwrap_err()
+                .contains("out of range")
+        );
+    }
+
+    #[test]
+    fn test_shared_clock_follows_manual_clock() {
+        let manual = Arc::new(ManualClock::new(UnixTimestamp::from_secs(1000)));
+        let clock = SharedClock::from(manual.clone() as Arc<dyn Clock>);
+        let copy = clock.clone();
+        manual.advance(5);
+        assert_eq!(copy.now(), UnixTimestamp::from_secs(1005));
+        manual.set(UnixTimestamp::from_secs(42));
+        assert_eq!(clock.now(), UnixTimestamp::from_secs(42));
+        assert!(SharedClock::default().now().as_secs() > 1577836800);
+    }
+}
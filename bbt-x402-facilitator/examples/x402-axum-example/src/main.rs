@@ -29,49 +29,49 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         eip712: Some(TokenDeploymentEip712 {
             name: "BBT".into(),
             version: "1".into(),
+            domain: Default::default(),
         }),
     };
 
     let app = Router::new()
         .route(
             "/static-price-v1",
-            get(my_handler).layer(
-                x402.with_price_tag(V1Eip155Exact::price_tag(
-                    address!("0xBAc675C310721717Cd4A37F6cbeA1F081b1C2a07"),
-                    bbt.parse("0.01")?,
-                )),
-            ),
+            get(my_handler).layer(x402.with_price_tag(V1Eip155Exact::price_tag(
+                address!("0xBAc675C310721717Cd4A37F6cbeA1F081b1C2a07"),
+                bbt.parse("0.01")?,
+            ))),
         )
         .route(
             "/static-price-v2",
-            get(my_handler).layer(
-                x402.with_price_tag(V2Eip155Exact::price_tag(
-                    address!("0xBAc675C310721717Cd4A37F6cbeA1F081b1C2a07"),
-                    bbt.amount(10u64),
-                )),
-            ),
+            get(my_handler).layer(x402.with_price_tag(V2Eip155Exact::price_tag(
+                address!("0xBAc675C310721717Cd4A37F6cbeA1F081b1C2a07"),
+                bbt.amount(10u64),
+            ))),
         )
         // Dynamic pricing: adjust price based on request parameters
         // GET /dynamic-price-v2 -> 100 units
         // GET /dynamic-price-v2?discount -> 50 units (discounted)
         .route(
             "/dynamic-price-v2",
-            get(my_handler).layer(x402.with_dynamic_price({ let bbt = bbt.clone(); move |_headers, uri, _base_url| {
-                // Check if "discount" query parameter is present (before async block)
-                let has_discount = uri.query().map(|q| q.contains("discount")).unwrap_or(false);
-                let amount: u64 = if has_discount { 50 } else { 100 };
+            get(my_handler).layer(x402.with_dynamic_price({
                 let bbt = bbt.clone();
+                move |_headers, uri, _base_url| {
+                    // Check if "discount" query parameter is present (before async block)
+                    let has_discount = uri.query().map(|q| q.contains("discount")).unwrap_or(false);
+                    let amount: u64 = if has_discount { 50 } else { 100 };
+                    let bbt = bbt.clone();
 
-                async move {
-                    vec![
-                        // V2 EIP155 (Etherlink) price tag
-                        V2Eip155Exact::price_tag(
-                            address!("0xBAc675C310721717Cd4A37F6cbeA1F081b1C2a07"),
-                            bbt.amount(amount),
-                        ),
-                    ]
+                    async move {
+                        vec![
+                            // V2 EIP155 (Etherlink) price tag
+                            V2Eip155Exact::price_tag(
+                                address!("0xBAc675C310721717Cd4A37F6cbeA1F081b1C2a07"),
+                                bbt.amount(amount),
+                            ),
+                        ]
+                    }
                 }
-            }})),
+            })),
         )
         // Conditional free access: bypass payment when "free" query parameter is present
         // GET /conditional-free-v2 -> requires payment (402)
@@ -81,28 +81,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Useful for implementing free tiers, promotional access, or conditional pricing.
         .route(
             "/conditional-free-v2",
-            get(my_handler).layer(x402.with_dynamic_price({ let bbt = bbt.clone(); move |_headers, uri, _base_url| {
-                // Check if "free" query parameter is present - if so, bypass payment
-                let is_free = uri.query().map(|q| q.contains("free")).unwrap_or(false);
+            get(my_handler).layer(x402.with_dynamic_price({
                 let bbt = bbt.clone();
+                move |_headers, uri, _base_url| {
+                    // Check if "free" query parameter is present - if so, bypass payment
+                    let is_free = uri.query().map(|q| q.contains("free")).unwrap_or(false);
+                    let bbt = bbt.clone();
 
-                async move {
-                    if is_free {
-                        // Return empty vector to bypass payment enforcement entirely.
-                        // The middleware will forward the request directly to the handler
-                        // without requiring any payment.
-                        vec![]
-                    } else {
-                        // Normal pricing - payment required
-                        vec![
-                            V2Eip155Exact::price_tag(
+                    async move {
+                        if is_free {
+                            // Return empty vector to bypass payment enforcement entirely.
+                            // The middleware will forward the request directly to the handler
+                            // without requiring any payment.
+                            vec![]
+                        } else {
+                            // Normal pricing - payment required
+                            vec![V2Eip155Exact::price_tag(
                                 address!("0xBAc675C310721717Cd4A37F6cbeA1F081b1C2a07"),
                                 bbt.amount(100u64),
-                            ),
-                        ]
+                            )]
+                        }
                     }
                 }
-            }})),
+            })),
         );
 
     tracing::info!("Using facilitator on {}", x402.facilitator_url());
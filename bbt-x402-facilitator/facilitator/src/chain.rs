@@ -30,16 +30,24 @@
 //! assert!(mainnet_chains.matches(&etherlink));
 //! ```
 
+#[cfg(feature = "dev-mode")]
+use crate::mock::MockChainProvider;
+use serde::Serialize;
 use std::collections::HashMap;
-#[cfg(any(
-    feature = "chain-eip155"
-))]
+#[cfg(feature = "chain-eip155")]
 use std::sync::Arc;
 #[cfg(feature = "chain-eip155")]
 use x402_chain_eip155::chain as eip155;
+#[cfg(feature = "chain-eip155")]
+use x402_chain_eip155::chain::Eip155MetaTransactionProvider;
+#[cfg(feature = "chain-eip155")]
+use x402_chain_eip155::chain::rpc_failover::CircuitState;
+#[cfg(feature = "chain-eip155")]
+use x402_chain_eip155::v1_eip155_exact::signature_check as eip155_signatures;
 use x402_types::chain::{ChainId, ChainProviderOps, ChainRegistry, FromConfig};
 
 use crate::config::{ChainConfig, ChainsConfig};
+use crate::readiness::ReadinessCheck;
 
 /// A blockchain provider that can interact with EVM chains.
 ///
@@ -49,11 +57,15 @@ use crate::config::{ChainConfig, ChainsConfig};
 /// # Variants
 ///
 /// - `Eip155` - Provider for EVM-compatible chains (Etherlink)
+/// - `Mock` - Mock chain settling without a chain (`dev-mode` feature)
 #[derive(Debug, Clone)]
 pub enum ChainProvider {
     /// EVM chain provider for EIP-155 compatible networks.
     #[cfg(feature = "chain-eip155")]
     Eip155(Arc<eip155::Eip155ChainProvider>),
+    /// Mock chain, see [`crate::mock`].
+    #[cfg(feature = "dev-mode")]
+    Mock(std::sync::Arc<MockChainProvider>),
 }
 
 /// Creates a new chain provider from configuration.
@@ -77,6 +89,10 @@ impl FromConfig<ChainConfig> for ChainProvider {
                 let provider = eip155::Eip155ChainProvider::from_config(config).await?;
                 ChainProvider::Eip155(Arc::new(provider))
             }
+            #[cfg(feature = "dev-mode")]
+            ChainConfig::Mock(config) => {
+                ChainProvider::Mock(std::sync::Arc::new(MockChainProvider::new(config)))
+            }
             #[allow(unreachable_patterns)] // For when no chain features enabled
             _ => unreachable!("ChainConfig variant not enabled in this build"),
         };
@@ -90,6 +106,8 @@ impl ChainProviderOps for ChainProvider {
         match self {
             #[cfg(feature = "chain-eip155")]
             ChainProvider::Eip155(provider) => provider.signer_addresses(),
+            #[cfg(feature = "dev-mode")]
+            ChainProvider::Mock(provider) => provider.signer_addresses(),
             #[allow(unreachable_patterns)] // For when no chain features enabled
             _ => unreachable!("ChainProvider variant not enabled in this build"),
         }
@@ -99,6 +117,423 @@ impl ChainProviderOps for ChainProvider {
         match self {
             #[cfg(feature = "chain-eip155")]
             ChainProvider::Eip155(provider) => provider.chain_id(),
+            #[cfg(feature = "dev-mode")]
+            ChainProvider::Mock(provider) => provider.chain_id(),
+            #[allow(unreachable_patterns)] // For when no chain features enabled
+            _ => unreachable!("ChainProvider variant not enabled in this build"),
+        }
+    }
+}
+
+/// Signer readiness of a single chain, as reported by `GET /health/signers`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainSignerHealth {
+    /// The chain the signers belong to.
+    pub chain_id: ChainId,
+    /// `false` if the chain could not be queried, every RPC endpoint is failing,
+    /// or any signer is low on funds.
+    pub healthy: bool,
+    /// Balance under which a signer is reported as low, in the native token's smallest unit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub low_balance_threshold: Option<String>,
+    /// Chain-specific status of each signer.
+    pub signers: Vec<serde_json::Value>,
+    /// Signers without native balance or below the low-balance threshold.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unfunded: Vec<String>,
+    /// Chain-specific state of each RPC endpoint, in failover order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rpc: Vec<serde_json::Value>,
+    /// Settlement transactions in flight and queued, when their number is bounded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settlement_slots: Option<serde_json::Value>,
+    /// Why the chain could not be queried.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ChainProvider {
+    /// Queries the chain for the balance and pending transactions of each signer.
+    pub async fn signer_health(&self) -> ChainSignerHealth {
+        match self {
+            #[cfg(feature = "chain-eip155")]
+            ChainProvider::Eip155(provider) => {
+                let low_balance_threshold = provider.low_balance_threshold().map(|t| t.to_string());
+                let rpc_health = provider.rpc_health();
+                let rpc_available = rpc_health
+                    .iter()
+                    .any(|endpoint| endpoint.state != CircuitState::Open);
+                let rpc = rpc_health
+                    .iter()
+                    .map(|endpoint| serde_json::to_value(endpoint).expect("serializable"))
+                    .collect();
+                let settlement_slots = provider
+                    .settlement_limiter_status()
+                    .map(|status| serde_json::to_value(status).expect("serializable"));
+                match provider.signer_status().await {
+                    Ok(statuses) => ChainSignerHealth {
+                        chain_id: provider.chain_id(),
+                        healthy: rpc_available && statuses.iter().all(|status| !status.low_balance),
+                        low_balance_threshold,
+                        signers: statuses
+                            .iter()
+                            .map(|status| serde_json::to_value(status).expect("serializable"))
+                            .collect(),
+                        unfunded: statuses
+                            .iter()
+                            .filter(|status| status.low_balance || status.balance.0.is_zero())
+                            .map(|status| status.address.to_string())
+                            .collect(),
+                        rpc,
+                        settlement_slots,
+                        error: None,
+                    },
+                    Err(e) => ChainSignerHealth {
+                        chain_id: provider.chain_id(),
+                        healthy: false,
+                        low_balance_threshold,
+                        signers: Vec::new(),
+                        unfunded: Vec::new(),
+                        rpc,
+                        settlement_slots,
+                        error: Some(e.to_string()),
+                    },
+                }
+            }
+            #[cfg(feature = "dev-mode")]
+            ChainProvider::Mock(provider) => ChainSignerHealth {
+                chain_id: provider.chain_id(),
+                healthy: true,
+                low_balance_threshold: None,
+                signers: Vec::new(),
+                unfunded: Vec::new(),
+                rpc: Vec::new(),
+                settlement_slots: None,
+                error: None,
+            },
+            #[allow(unreachable_patterns)] // For when no chain features enabled
+            _ => unreachable!("ChainProvider variant not enabled in this build"),
+        }
+    }
+}
+
+impl ChainProvider {
+    /// Probes every RPC endpoint of the chain so failing ones are taken out of
+    /// rotation, and recovered ones put back, ahead of traffic.
+    ///
+    /// Returns an error if no endpoint of the chain is taking requests.
+    pub async fn probe_rpc(&self) -> Result<(), String> {
+        match self {
+            #[cfg(feature = "chain-eip155")]
+            ChainProvider::Eip155(provider) => {
+                provider.probe_rpc().await;
+                let health = provider.rpc_health();
+                if health
+                    .iter()
+                    .all(|endpoint| endpoint.state == CircuitState::Open)
+                {
+                    let errors: Vec<String> = health
+                        .into_iter()
+                        .map(|endpoint| {
+                            let error = endpoint.last_error.unwrap_or_default();
+                            format!("{}: {error}", endpoint.endpoint)
+                        })
+                        .collect();
+                    return Err(format!(
+                        "{}: every RPC endpoint is failing ({})",
+                        provider.chain_id(),
+                        errors.join("; ")
+                    ));
+                }
+                Ok(())
+            }
+            #[cfg(feature = "dev-mode")]
+            ChainProvider::Mock(_) => Ok(()),
+            #[allow(unreachable_patterns)] // For when no chain features enabled
+            _ => unreachable!("ChainProvider variant not enabled in this build"),
+        }
+    }
+
+    /// Checks RPC connectivity, the chain ID, signer balances and contract
+    /// deployments, see [`crate::readiness::self_test`].
+    ///
+    /// Checks are named `chain:<chain id>:<check>`.
+    pub async fn self_test(&self) -> Vec<ReadinessCheck> {
+        match self {
+            #[cfg(feature = "chain-eip155")]
+            ChainProvider::Eip155(provider) => {
+                let chain_id = provider.chain_id();
+                provider
+                    .self_test()
+                    .await
+                    .into_iter()
+                    .map(|check| ReadinessCheck {
+                        name: format!("chain:{chain_id}:{}", check.name),
+                        ready: check.passed,
+                        detail: Some(check.detail),
+                    })
+                    .collect()
+            }
+            #[cfg(feature = "dev-mode")]
+            ChainProvider::Mock(provider) => vec![ReadinessCheck {
+                name: format!("chain:{}:mock", provider.chain_id()),
+                ready: true,
+                detail: Some("mock chain, settlements are not sent".to_string()),
+            }],
+            #[allow(unreachable_patterns)] // For when no chain features enabled
+            _ => unreachable!("ChainProvider variant not enabled in this build"),
+        }
+    }
+
+    /// Fetches the chain head and syncs the signer nonces ahead of the first
+    /// settlement. Returns the head block number.
+    pub async fn warm_up(&self) -> Result<u64, String> {
+        match self {
+            #[cfg(feature = "chain-eip155")]
+            ChainProvider::Eip155(provider) => provider.warm_up().await.map_err(|e| e.to_string()),
+            #[cfg(feature = "dev-mode")]
+            ChainProvider::Mock(_) => Ok(0),
+            #[allow(unreachable_patterns)] // For when no chain features enabled
+            _ => unreachable!("ChainProvider variant not enabled in this build"),
+        }
+    }
+
+    /// Reconciles the cached signer nonces with the chain, resyncing those that
+    /// drifted.
+    ///
+    /// Returns an error naming the signers with a stuck pending transaction, or if
+    /// the chain could not be queried.
+    pub async fn reconcile_nonces(&self) -> Result<(), String> {
+        match self {
+            #[cfg(feature = "chain-eip155")]
+            ChainProvider::Eip155(provider) => {
+                let chain_id = provider.chain_id();
+                let reconciliations = provider
+                    .reconcile_nonces()
+                    .await
+                    .map_err(|e| format!("{chain_id}: {e}"))?;
+                let stuck: Vec<String> = reconciliations
+                    .iter()
+                    .filter(|reconciliation| reconciliation.stuck)
+                    .map(|reconciliation| {
+                        format!(
+                            "{} at nonce {} ({} pending)",
+                            reconciliation.address,
+                            reconciliation.confirmed_nonce,
+                            reconciliation.pending_nonce - reconciliation.confirmed_nonce
+                        )
+                    })
+                    .collect();
+                if stuck.is_empty() {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{chain_id}: stuck signer nonces: {}",
+                        stuck.join(", ")
+                    ))
+                }
+            }
+            #[cfg(feature = "dev-mode")]
+            ChainProvider::Mock(_) => Ok(()),
+            #[allow(unreachable_patterns)] // For when no chain features enabled
+            _ => unreachable!("ChainProvider variant not enabled in this build"),
+        }
+    }
+
+    /// Replaces the pending transactions of `signer` with self-transfers, clearing
+    /// its stuck nonces. Returns the hashes of the replacement transactions.
+    pub async fn cancel_pending(&self, signer: &str) -> Result<Vec<String>, SignerRepairError> {
+        match self {
+            #[cfg(feature = "chain-eip155")]
+            ChainProvider::Eip155(provider) => {
+                let address = signer
+                    .parse()
+                    .ok()
+                    .map(|address: eip155::ChecksummedAddress| address.0)
+                    .filter(|address| provider.signer_addresses().contains(&address.to_string()))
+                    .ok_or_else(|| SignerRepairError::UnknownSigner(signer.to_string()))?;
+                let hashes = provider
+                    .cancel_pending(address)
+                    .await
+                    .map_err(|e| SignerRepairError::Rpc(e.to_string()))?;
+                Ok(hashes.iter().map(|hash| hash.to_string()).collect())
+            }
+            #[cfg(feature = "dev-mode")]
+            ChainProvider::Mock(_) => Err(SignerRepairError::UnknownSigner(signer.to_string())),
+            #[allow(unreachable_patterns)] // For when no chain features enabled
+            _ => unreachable!("ChainProvider variant not enabled in this build"),
+        }
+    }
+}
+
+impl ChainProvider {
+    /// The EIP-712 domains of token contracts cached by the chain.
+    pub fn eip712_domains(&self) -> Vec<serde_json::Value> {
+        match self {
+            #[cfg(feature = "chain-eip155")]
+            ChainProvider::Eip155(provider) => provider
+                .eip712_domains()
+                .into_iter()
+                .flat_map(|cache| cache.entries())
+                .map(|entry| serde_json::to_value(entry).expect("serializable"))
+                .collect(),
+            #[cfg(feature = "dev-mode")]
+            ChainProvider::Mock(_) => Vec::new(),
+            #[allow(unreachable_patterns)] // For when no chain features enabled
+            _ => unreachable!("ChainProvider variant not enabled in this build"),
+        }
+    }
+
+    /// Drops the cached EIP-712 domain of `token`, or of every token without one,
+    /// so that the next payment reads it from the contract again. Returns how many
+    /// entries were dropped, or `None` if `token` is not an address.
+    pub fn invalidate_eip712_domains(&self, token: Option<&str>) -> Option<usize> {
+        match self {
+            #[cfg(feature = "chain-eip155")]
+            ChainProvider::Eip155(provider) => {
+                let Some(cache) = provider.eip712_domains() else {
+                    return Some(0);
+                };
+                match token {
+                    Some(token) => {
+                        let token = token.parse::<eip155::ChecksummedAddress>().ok()?;
+                        Some(usize::from(cache.invalidate(token.0)))
+                    }
+                    None => Some(cache.clear()),
+                }
+            }
+            #[cfg(feature = "dev-mode")]
+            ChainProvider::Mock(_) => Some(0),
+            #[allow(unreachable_patterns)] // For when no chain features enabled
+            _ => unreachable!("ChainProvider variant not enabled in this build"),
+        }
+    }
+}
+
+/// Why the pending transactions of a signer could not be cancelled.
+#[derive(Debug, thiserror::Error)]
+pub enum SignerRepairError {
+    /// The address is not a settlement signer of the chain.
+    #[error("unknown signer: {0}")]
+    UnknownSigner(String),
+    /// The replacement transactions could not be sent.
+    #[error("rpc error: {0}")]
+    Rpc(String),
+}
+
+/// Why a settlement could not be looked up on chain.
+#[derive(Debug, thiserror::Error)]
+pub enum SettlementLookupError {
+    /// The query does not fit the chain family.
+    #[error("invalid query: {0}")]
+    InvalidQuery(String),
+    /// The (archive) RPC node could not be queried.
+    #[error("rpc error: {0}")]
+    Rpc(String),
+}
+
+impl ChainProvider {
+    /// Checks on chain whether a past transaction settled the expected payment.
+    ///
+    /// `query` holds the chain-specific transaction reference and payment fields;
+    /// the result is the chain-specific verification report.
+    pub async fn verify_settlement(
+        &self,
+        query: serde_json::Value,
+    ) -> Result<serde_json::Value, SettlementLookupError> {
+        match self {
+            #[cfg(feature = "chain-eip155")]
+            ChainProvider::Eip155(provider) => {
+                let query: eip155::SettlementQuery = serde_json::from_value(query)
+                    .map_err(|e| SettlementLookupError::InvalidQuery(e.to_string()))?;
+                let verification = provider
+                    .verify_settlement(query.transaction, &query.expected)
+                    .await
+                    .map_err(|e| SettlementLookupError::Rpc(e.to_string()))?;
+                Ok(serde_json::to_value(verification).expect("serializable"))
+            }
+            #[cfg(feature = "dev-mode")]
+            ChainProvider::Mock(_) => Err(SettlementLookupError::InvalidQuery(
+                "mock chains send no transactions".to_string(),
+            )),
+            #[allow(unreachable_patterns)] // For when no chain features enabled
+            _ => unreachable!("ChainProvider variant not enabled in this build"),
+        }
+    }
+}
+
+/// Why a chain could not be queried on behalf of a client.
+#[derive(Debug, thiserror::Error)]
+pub enum ChainQueryError {
+    /// The query does not fit the chain family.
+    #[error("invalid query: {0}")]
+    InvalidQuery(String),
+    /// The RPC node could not be queried.
+    #[error("rpc error: {0}")]
+    Rpc(String),
+}
+
+impl ChainProvider {
+    /// Reads the Permit2 nonces a client needs to build a Permit2 payload.
+    ///
+    /// `query` holds the chain-specific owner, token and spender; the result is
+    /// the chain-specific nonce report.
+    pub async fn permit2_nonces(
+        &self,
+        query: serde_json::Value,
+    ) -> Result<serde_json::Value, ChainQueryError> {
+        match self {
+            #[cfg(feature = "chain-eip155")]
+            ChainProvider::Eip155(provider) => {
+                let query: eip155::Permit2NonceQuery = serde_json::from_value(query)
+                    .map_err(|e| ChainQueryError::InvalidQuery(e.to_string()))?;
+                let nonces = provider
+                    .permit2_nonces(&query)
+                    .await
+                    .map_err(|e| ChainQueryError::Rpc(e.to_string()))?;
+                Ok(serde_json::to_value(nonces).expect("serializable"))
+            }
+            #[cfg(feature = "dev-mode")]
+            ChainProvider::Mock(_) => Err(ChainQueryError::InvalidQuery(
+                "mock chains have no Permit2 contract".to_string(),
+            )),
+            #[allow(unreachable_patterns)] // For when no chain features enabled
+            _ => unreachable!("ChainProvider variant not enabled in this build"),
+        }
+    }
+
+    /// Checks a signature over typed data the way payment verification does.
+    ///
+    /// `query` holds the chain-specific typed data, signature and signer; the
+    /// result is the chain-specific validation report.
+    pub async fn validate_signature(
+        &self,
+        query: serde_json::Value,
+    ) -> Result<serde_json::Value, ChainQueryError> {
+        match self {
+            #[cfg(feature = "chain-eip155")]
+            ChainProvider::Eip155(provider) => {
+                use x402_chain_eip155::v1_eip155_exact::Eip155ExactError;
+
+                let query: eip155_signatures::SignatureQuery = serde_json::from_value(query)
+                    .map_err(|e| ChainQueryError::InvalidQuery(e.to_string()))?;
+                let validation =
+                    provider
+                        .validate_signature(&query)
+                        .await
+                        .map_err(|e| match e {
+                            Eip155ExactError::PaymentVerification(e) => {
+                                ChainQueryError::InvalidQuery(e.to_string())
+                            }
+                            e => ChainQueryError::Rpc(e.to_string()),
+                        })?;
+                Ok(serde_json::to_value(validation).expect("serializable"))
+            }
+            #[cfg(feature = "dev-mode")]
+            ChainProvider::Mock(_) => Err(ChainQueryError::InvalidQuery(
+                "mock chains have no contracts to check signatures with".to_string(),
+            )),
             #[allow(unreachable_patterns)] // For when no chain features enabled
             _ => unreachable!("ChainProvider variant not enabled in this build"),
         }
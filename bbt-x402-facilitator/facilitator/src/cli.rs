@@ -0,0 +1,328 @@
+//! Command line of the facilitator binary.
+//!
+//! Without a subcommand, or with `serve`, the facilitator serves the x402 HTTP API, see
+//! [`crate::run`]. The other subcommands are for operational debugging: each does one
+//! thing with the configuration file given with `--config` (or `CONFIG`), prints JSON to
+//! stdout and exits, with status 1 on failure.
+//!
+//! - `validate-config` - checks the configuration file, see [`crate::validate`]
+//! - `sign-test-payment` - signs an `exact` payment and prints it as a `/verify` and
+//!   `/settle` request body
+//! - `check-chain <chain_id>` - connects to a configured chain, runs its startup
+//!   self-test and queries its signers, failing if a check fails
+//! - `settle-file <request.json>` - verifies and settles a `/verify` or `/settle` request
+//!   body with the configured chains and schemes, without going through HTTP
+//!
+//! ```bash
+//! x402-facilitator sign-test-payment --pay-to 0x... --amount 1000 > payment.json
+//! x402-facilitator --config config.json settle-file payment.json --verify-only
+//! x402-facilitator --config config.json check-chain eip155:42793
+//! ```
+//!
+//! `settle-file` uses the chains and schemes only: API keys, compliance screening,
+//! velocity limits and the guardrail of the server do not apply, and the settlement is
+//! not recorded in the ledger.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Parser, Subcommand};
+use serde_json::json;
+use x402_facilitator_local::FacilitatorLocal;
+use x402_types::chain::{ChainId, FromConfig};
+use x402_types::config::CliArgs;
+use x402_types::facilitator::Facilitator;
+use x402_types::proto;
+
+use crate::chain::ChainProvider;
+use crate::config::Config;
+use crate::run;
+
+#[derive(Debug, Parser)]
+#[command(name = "x402-facilitator", about = "x402 Facilitator HTTP server")]
+pub struct Cli {
+    #[command(flatten)]
+    pub args: CliArgs,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Serve the x402 HTTP API (the default).
+    Serve,
+    /// Check the configuration file and exit, printing where it is invalid.
+    ValidateConfig,
+    /// Sign an `exact` payment and print it as a `/verify` and `/settle` request body.
+    #[cfg(feature = "chain-eip155")]
+    SignTestPayment(Box<TestPaymentArgs>),
+    /// Connect to a configured chain, run its self-test and query its signers.
+    CheckChain {
+        /// CAIP-2 id of the chain, e.g. `eip155:42793`.
+        chain_id: ChainId,
+    },
+    /// Verify and settle the `/verify` or `/settle` request body in a file.
+    SettleFile(SettleFileArgs),
+}
+
+#[cfg(feature = "chain-eip155")]
+#[derive(Debug, Clone, Args)]
+pub struct TestPaymentArgs {
+    /// EIP-155 chain id of the payment.
+    #[arg(long, default_value_t = 42793)]
+    pub chain_id: u64,
+    /// ERC-3009 token the payment is made in.
+    #[arg(long, env = "BBT_TOKEN", default_value_t = test_payment::DEFAULT_ASSET)]
+    pub asset: alloy_primitives::Address,
+    /// EIP-712 domain name of the token.
+    #[arg(long, default_value = "BBT")]
+    pub token_name: String,
+    /// EIP-712 domain version of the token.
+    #[arg(long, default_value = "1")]
+    pub token_version: String,
+    /// Recipient of the payment.
+    #[arg(long, env = "SERVER_WALLET")]
+    pub pay_to: alloy_primitives::Address,
+    /// Amount of the payment, in the token's smallest unit.
+    #[arg(long, default_value_t = 1000)]
+    pub amount: u128,
+    /// Validity window of the authorization, in seconds.
+    #[arg(long, default_value_t = 300)]
+    pub max_timeout_seconds: u64,
+    /// Private key of the payer; a random payer signs when not given.
+    #[arg(long, env = "TEST_PAYER_PRIVATE_KEY", hide_env_values = true)]
+    pub payer_key: Option<alloy_signer_local::PrivateKeySigner>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct SettleFileArgs {
+    /// JSON file holding a `/verify` or `/settle` request body.
+    pub path: PathBuf,
+    /// Stop after verifying the payment.
+    #[arg(long)]
+    pub verify_only: bool,
+    /// Simulate the settlement without broadcasting it.
+    #[arg(long, conflicts_with = "verify_only")]
+    pub dry_run: bool,
+}
+
+impl Cli {
+    /// Runs the subcommand, serving when there is none.
+    pub async fn run(self) -> Result<(), Box<dyn Error>> {
+        run::init()?;
+        match self.command.unwrap_or(Command::Serve) {
+            Command::Serve => run::run(self.args).await,
+            Command::ValidateConfig => run::check_config(&self.args.canonical_config_path()?),
+            #[cfg(feature = "chain-eip155")]
+            Command::SignTestPayment(args) => print_json(&test_payment::sign(&args).await?),
+            Command::CheckChain { chain_id } => check_chain(&self.args, &chain_id).await,
+            Command::SettleFile(args) => settle_file(&self.args, &args).await,
+        }
+    }
+}
+
+fn print_json(value: &serde_json::Value) -> Result<(), Box<dyn Error>> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn load_config(cli_args: &CliArgs) -> Result<Config, Box<dyn Error>> {
+    Ok(Config::load_from_path(cli_args.canonical_config_path()?)?)
+}
+
+/// `check-chain`: prints the self-test and signer health of a configured chain.
+///
+/// Fails if a self-test check fails or the signers could not be queried.
+async fn check_chain(cli_args: &CliArgs, chain_id: &ChainId) -> Result<(), Box<dyn Error>> {
+    let config = load_config(cli_args)?;
+    let chain_config = config
+        .chains()
+        .iter()
+        .find(|chain| &chain.chain_id() == chain_id)
+        .ok_or_else(|| {
+            format!(
+                "{chain_id} is not configured in {}",
+                cli_args.config.display()
+            )
+        })?;
+    let provider = ChainProvider::from_config(chain_config).await?;
+    let checks = provider.self_test().await;
+    let signers = provider.signer_health().await;
+    print_json(&json!({ "chainId": chain_id, "checks": checks, "signers": signers }))?;
+
+    let mut failures: Vec<String> = checks
+        .iter()
+        .filter(|check| !check.ready)
+        .map(|check| check.name.clone())
+        .collect();
+    if let Some(error) = &signers.error {
+        failures.push(format!("signers: {error}"));
+    }
+    if !failures.is_empty() {
+        return Err(format!("{chain_id} failed: {}", failures.join(", ")).into());
+    }
+    Ok(())
+}
+
+/// `settle-file`: verifies the request body in the file, then settles it.
+///
+/// Fails if the payment is invalid or the settlement did not succeed.
+async fn settle_file(cli_args: &CliArgs, args: &SettleFileArgs) -> Result<(), Box<dyn Error>> {
+    let request = read_request(&args.path)?;
+    let config = load_config(cli_args)?;
+    let (_, scheme_registry) = run::build_registries(&config).await?;
+    let facilitator = FacilitatorLocal::new(scheme_registry);
+
+    let verify = facilitator
+        .verify(&request)
+        .await
+        .map_err(|e| format!("verification failed: {e}"))?;
+    let is_valid = verify.0["isValid"].as_bool().unwrap_or(false);
+    if args.verify_only || !is_valid {
+        print_json(&json!({ "verify": verify.0 }))?;
+    } else if args.dry_run {
+        let settle = facilitator
+            .settle_dry_run(&request)
+            .await
+            .map_err(|e| format!("settlement dry run failed: {e}"))?;
+        print_json(&json!({ "verify": verify.0, "settleDryRun": settle.0 }))?;
+    } else {
+        let settle = facilitator
+            .settle(&request)
+            .await
+            .map_err(|e| format!("settlement failed: {e}"))?;
+        print_json(&json!({ "verify": verify.0, "settle": settle.0 }))?;
+        if settle.0["success"].as_bool() != Some(true) {
+            return Err("settlement did not succeed".into());
+        }
+    }
+    if !is_valid {
+        return Err("payment is invalid".into());
+    }
+    Ok(())
+}
+
+fn read_request(path: &Path) -> Result<proto::SettleRequest, Box<dyn Error>> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    let value: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("{} is not JSON: {e}", path.display()))?;
+    Ok(proto::SettleRequest::from(value))
+}
+
+/// Signed test payments for `sign-test-payment`.
+#[cfg(feature = "chain-eip155")]
+mod test_payment {
+    use alloy_primitives::{Address, U256, address};
+    use alloy_signer_local::PrivateKeySigner;
+    use serde_json::{Value, json};
+    use x402_chain_eip155::v1_eip155_exact::client::{
+        Eip3009SigningParams, sign_erc3009_authorization,
+    };
+    use x402_chain_eip155::v1_eip155_exact::{AmountMatching, PaymentRequirementsExtra};
+
+    use super::TestPaymentArgs;
+
+    /// BBT on Etherlink.
+    pub const DEFAULT_ASSET: Address = address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6");
+
+    /// Signs an ERC-3009 `TransferWithAuthorization` and wraps it in an x402 v2 request body.
+    pub async fn sign(args: &TestPaymentArgs) -> Result<Value, String> {
+        let payer = args
+            .payer_key
+            .clone()
+            .unwrap_or_else(PrivateKeySigner::random);
+        let extra = PaymentRequirementsExtra {
+            name: args.token_name.clone(),
+            version: args.token_version.clone(),
+            domain: Default::default(),
+            receive_forwarder: None,
+            stealth_meta_address: None,
+            discounts: Vec::new(),
+            amount_matching: AmountMatching::Exact,
+            display: None,
+        };
+        let requirements = json!({
+            "scheme": "exact",
+            "network": format!("eip155:{}", args.chain_id),
+            "amount": args.amount.to_string(),
+            "payTo": args.pay_to,
+            "maxTimeoutSeconds": args.max_timeout_seconds,
+            "asset": args.asset,
+            "extra": extra,
+        });
+        let params = Eip3009SigningParams {
+            chain_id: args.chain_id,
+            asset_address: args.asset,
+            pay_to: args.pay_to,
+            amount: U256::from(args.amount),
+            max_timeout_seconds: args.max_timeout_seconds,
+            extra: Some(extra),
+            validity: None,
+        };
+        let payload = sign_erc3009_authorization(&payer, &params)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "x402Version": 2,
+                "accepted": requirements,
+                "payload": payload,
+            },
+            "paymentRequirements": requirements,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_subcommands() {
+        let cli = Cli::try_parse_from(["x402-facilitator"]).unwrap();
+        assert!(cli.command.is_none());
+
+        let cli = Cli::try_parse_from([
+            "x402-facilitator",
+            "check-chain",
+            "eip155:42793",
+            "--config",
+            "etherlink.json",
+        ])
+        .unwrap();
+        assert_eq!(cli.args.config, PathBuf::from("etherlink.json"));
+        assert!(matches!(
+            cli.command,
+            Some(Command::CheckChain { chain_id }) if chain_id == ChainId::new("eip155", "42793")
+        ));
+
+        let cli = Cli::try_parse_from([
+            "x402-facilitator",
+            "settle-file",
+            "payment.json",
+            "--dry-run",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::SettleFile(SettleFileArgs {
+                dry_run: true,
+                verify_only: false,
+                ..
+            }))
+        ));
+        assert!(
+            Cli::try_parse_from([
+                "x402-facilitator",
+                "settle-file",
+                "payment.json",
+                "--dry-run",
+                "--verify-only"
+            ])
+            .is_err()
+        );
+    }
+}
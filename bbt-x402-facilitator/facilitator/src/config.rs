@@ -26,11 +26,19 @@
 //!   }
 //! }
 //! ```
+//!
+//! With the `dev-mode` feature, an entry `{ "mock": true }` configures a mock chain of
+//! any namespace instead, see [`crate::mock`].
 
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde::de::DeserializeSeed;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::ops::Deref;
 use x402_types::chain::ChainId;
 
+#[cfg(feature = "dev-mode")]
+use crate::mock::{self, MockChainConfig, MockChainConfigInner};
 #[cfg(feature = "chain-eip155")]
 use x402_chain_eip155::chain as eip155;
 #[cfg(feature = "chain-eip155")]
@@ -52,6 +60,23 @@ pub enum ChainConfig {
     /// EVM chain configuration (for chains with "eip155:" prefix).
     #[cfg(feature = "chain-eip155")]
     Eip155(Box<Eip155ChainConfig>),
+    /// Mock chain settling without a chain (`"mock": true`, `dev-mode` feature).
+    #[cfg(feature = "dev-mode")]
+    Mock(MockChainConfig),
+}
+
+impl ChainConfig {
+    /// Returns the CAIP-2 identifier of the chain.
+    pub fn chain_id(&self) -> ChainId {
+        match self {
+            #[cfg(feature = "chain-eip155")]
+            ChainConfig::Eip155(config) => config.chain_id(),
+            #[cfg(feature = "dev-mode")]
+            ChainConfig::Mock(config) => config.chain_id.clone(),
+            #[allow(unreachable_patterns)] // For when no chain features enabled
+            _ => unreachable!("ChainConfig variant not enabled in this build"),
+        }
+    }
 }
 
 /// Configuration for chains.
@@ -87,6 +112,10 @@ impl Serialize for ChainsConfig {
                     let inner = &config.inner;
                     map.serialize_entry(&chain_id, inner)?;
                 }
+                #[cfg(feature = "dev-mode")]
+                ChainConfig::Mock(config) => {
+                    map.serialize_entry(&config.chain_id, &config.inner)?;
+                }
                 #[allow(unreachable_patterns)] // For when no chain features enabled
                 _ => unreachable!("ChainConfig variant not enabled in this build"),
             }
@@ -116,33 +145,37 @@ impl<'de> Deserialize<'de> for ChainsConfig {
             where
                 M: MapAccess<'de>,
             {
-                #[allow(unused_mut)] // For when no chain features enabled
                 let mut chains = Vec::with_capacity(access.size_hint().unwrap_or(0));
 
                 while let Some(chain_id) = access.next_key::<ChainId>()? {
-                    let namespace = chain_id.namespace();
-                    #[allow(unused_variables)] // For when no chain features enabled
-                    let config = match namespace {
-                        #[cfg(feature = "chain-eip155")]
-                        eip155::EIP155_NAMESPACE => {
-                            let inner: Eip155ChainConfigInner = access.next_value()?;
-                            let config = Eip155ChainConfig {
-                                chain_reference: chain_id
-                                    .try_into()
-                                    .map_err(|e| serde::de::Error::custom(format!("{}", e)))?,
-                                inner,
-                            };
-                            ChainConfig::Eip155(Box::new(config))
-                        }
-                        _ => {
-                            return Err(serde::de::Error::custom(format!(
-                                "Unexpected namespace: {}",
-                                namespace
-                            )));
+                    #[cfg(feature = "dev-mode")]
+                    {
+                        let value: serde_json::Value = access.next_value()?;
+                        if mock::is_mock_chain(&value) {
+                            let inner: MockChainConfigInner =
+                                serde_json::from_value(value).map_err(serde::de::Error::custom)?;
+                            chains.push(ChainConfig::Mock(MockChainConfig { chain_id, inner }));
+                        } else {
+                            // Keeps the path within the entry in the error message.
+                            let mut track = serde_path_to_error::Track::new();
+                            let deserializer =
+                                serde_path_to_error::Deserializer::new(value, &mut track);
+                            let config = ChainConfigSeed(chain_id.clone())
+                                .deserialize(deserializer)
+                                .map_err(|e| {
+                                    serde::de::Error::custom(format!(
+                                        "{}.{}: {}",
+                                        chain_id,
+                                        track.path(),
+                                        e
+                                    ))
+                                })?;
+                            chains.push(config);
                         }
-                    };
-                    #[allow(unreachable_code)] // For when no chain features enabled
-                    chains.push(config)
+                        continue;
+                    }
+                    #[allow(unreachable_code)] // With the dev-mode feature
+                    chains.push(access.next_value_seed(ChainConfigSeed(chain_id))?);
                 }
 
                 Ok(ChainsConfig(chains))
@@ -152,3 +185,75 @@ impl<'de> Deserialize<'de> for ChainsConfig {
         deserializer.deserialize_map(ChainsVisitor)
     }
 }
+
+/// Deserializes the configuration of a chain, by the namespace of its identifier.
+///
+/// The entry is read in place, so errors in it carry their path, e.g.
+/// `chains.eip155:42793.rpc[0].http`.
+struct ChainConfigSeed(ChainId);
+
+impl<'de> DeserializeSeed<'de> for ChainConfigSeed {
+    type Value = ChainConfig;
+
+    #[allow(unused_variables)] // For when no chain features enabled
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let chain_id = self.0;
+        match chain_id.namespace() {
+            #[cfg(feature = "chain-eip155")]
+            eip155::EIP155_NAMESPACE => {
+                let chain_reference = (&chain_id)
+                    .try_into()
+                    .map_err(|e| serde::de::Error::custom(format!("{}", e)))?;
+                let inner = Eip155ChainConfigInner::deserialize(deserializer)?;
+                Ok(ChainConfig::Eip155(Box::new(Eip155ChainConfig {
+                    chain_reference,
+                    inner,
+                })))
+            }
+            namespace => Err(serde::de::Error::custom(format!(
+                "Unexpected namespace: {}",
+                namespace
+            ))),
+        }
+    }
+}
+
+/// An object keyed by CAIP-2 chain identifier, with the configuration of every
+/// supported namespace. With the `dev-mode` feature, any entry may be a mock chain.
+impl JsonSchema for ChainsConfig {
+    fn schema_name() -> Cow<'static, str> {
+        "ChainsConfig".into()
+    }
+
+    #[allow(unused_variables)] // For when no chain features enabled
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        #[allow(unused_mut)] // For when no chain features enabled
+        let mut namespaces = serde_json::Map::new();
+        #[cfg(feature = "chain-eip155")]
+        namespaces.insert(
+            format!("^{}:[0-9]+$", eip155::EIP155_NAMESPACE),
+            generator
+                .subschema_for::<Eip155ChainConfigInner>()
+                .to_value(),
+        );
+        #[cfg(feature = "dev-mode")]
+        let other = {
+            let mock = generator.subschema_for::<MockChainConfigInner>().to_value();
+            for schema in namespaces.values_mut() {
+                *schema = serde_json::json!({ "anyOf": [schema.take(), mock.clone()] });
+            }
+            mock
+        };
+        #[cfg(not(feature = "dev-mode"))]
+        let other = serde_json::Value::Bool(false);
+        json_schema!({
+            "type": "object",
+            "description": "Chains to serve, keyed by CAIP-2 chain identifier.",
+            "patternProperties": namespaces,
+            "additionalProperties": other,
+        })
+    }
+}
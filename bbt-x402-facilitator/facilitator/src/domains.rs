@@ -0,0 +1,76 @@
+//! `/admin/eip712-domains`: the EIP-712 domains cached per chain and token.
+//!
+//! Payment requirements without `extra` make the facilitator read the EIP-712 name
+//! and version of EIP-3009 tokens, which each chain caches for its
+//! `eip712_cache_ttl_secs` (see `x402_chain_eip155::chain::eip712_cache`). After a
+//! token upgrade changes them, drop the stale entry rather than wait for it to expire:
+//!
+//! - `GET /admin/eip712-domains` lists the cached entries per chain
+//! - `DELETE /admin/eip712-domains/{chain_id}` drops the entries of a chain, or only
+//!   the one of `?token=0x...`
+//!
+//! Both are served behind an admin API key.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::json;
+use x402_types::chain::ChainId;
+
+use crate::signers::SignerHealth;
+
+/// Admin routes inspecting and invalidating the EIP-712 domain caches. Guard them
+/// with API key authentication.
+pub fn admin_routes() -> Router<Arc<SignerHealth>> {
+    Router::new()
+        .route("/admin/eip712-domains", get(get_eip712_domains))
+        .route(
+            "/admin/eip712-domains/{chain_id}",
+            delete(delete_eip712_domains),
+        )
+}
+
+#[derive(Debug, Deserialize)]
+struct InvalidateParams {
+    token: Option<String>,
+}
+
+/// `GET /admin/eip712-domains`: cached entries, keyed by chain id.
+async fn get_eip712_domains(State(signer_health): State<Arc<SignerHealth>>) -> Response {
+    let chains = signer_health.chains();
+    let domains: BTreeMap<String, Vec<serde_json::Value>> = chains
+        .iter()
+        .map(|(chain_id, provider)| (chain_id.to_string(), provider.eip712_domains()))
+        .collect();
+    Json(domains).into_response()
+}
+
+/// `DELETE /admin/eip712-domains/{chain_id}`: drops cached entries of the chain.
+async fn delete_eip712_domains(
+    State(signer_health): State<Arc<SignerHealth>>,
+    Path(chain_id): Path<String>,
+    Query(params): Query<InvalidateParams>,
+) -> Response {
+    let chains = signer_health.chains();
+    let provider = chain_id
+        .parse::<ChainId>()
+        .ok()
+        .and_then(|chain_id| chains.by_chain_id(chain_id));
+    let Some(provider) = provider else {
+        let body = json!({ "error": "not_found", "details": format!("unknown chain: {chain_id}") });
+        return (StatusCode::NOT_FOUND, Json(body)).into_response();
+    };
+    match provider.invalidate_eip712_domains(params.token.as_deref()) {
+        Some(invalidated) => Json(json!({ "invalidated": invalidated })).into_response(),
+        None => {
+            let body = json!({ "error": "invalid_request", "details": "token is not an address" });
+            (StatusCode::BAD_REQUEST, Json(body)).into_response()
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! `POST /settlements/verify`: checks that a past transaction settled a payment.
+//!
+//! Meant for dispute resolution: the answer comes from chain data only (read from
+//! the chain's `archive_rpc` nodes when configured), so it works for payments the
+//! facilitator has no record of. The request names the network, the transaction
+//! and the expected payment:
+//!
+//! ```json
+//! {
+//!   "network": "eip155:42793",
+//!   "transaction": "0x...",
+//!   "payer": "0x...",
+//!   "payTo": "0x...",
+//!   "asset": "0x...",
+//!   "amount": "1000"
+//! }
+//! ```
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::json;
+use x402_types::chain::ChainId;
+
+use crate::chain::SettlementLookupError;
+use crate::signers::SignerHealth;
+
+/// Routes serving settlement verification against the configured chains.
+pub fn routes() -> Router<Arc<SignerHealth>> {
+    Router::new().route("/settlements/verify", post(post_verify_settlement))
+}
+
+fn lookup_error(status: StatusCode, error: &'static str, details: String) -> Response {
+    (status, Json(json!({ "error": error, "details": details }))).into_response()
+}
+
+/// `POST /settlements/verify`: reports whether the transaction settled the payment.
+async fn post_verify_settlement(
+    State(signer_health): State<Arc<SignerHealth>>,
+    Json(query): Json<serde_json::Value>,
+) -> Response {
+    let network = match query
+        .get("network")
+        .cloned()
+        .map(serde_json::from_value::<ChainId>)
+    {
+        Some(Ok(network)) => network,
+        _ => {
+            return lookup_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_request",
+                "missing or invalid network".to_string(),
+            );
+        }
+    };
+    let chains = signer_health.chains();
+    let Some(provider) = chains.by_chain_id(network.clone()) else {
+        return lookup_error(
+            StatusCode::NOT_FOUND,
+            "unsupported_network",
+            format!("network {network} is not configured"),
+        );
+    };
+    match provider.verify_settlement(query).await {
+        Ok(verification) => Json(verification).into_response(),
+        Err(e @ SettlementLookupError::InvalidQuery(_)) => {
+            lookup_error(StatusCode::BAD_REQUEST, "invalid_request", e.to_string())
+        }
+        Err(e @ SettlementLookupError::Rpc(_)) => {
+            lookup_error(StatusCode::BAD_GATEWAY, "rpc_error", e.to_string())
+        }
+    }
+}
@@ -12,9 +12,14 @@
 //! | Module | Description |
 //! |--------|-------------|
 //! | [`chain`] | Blockchain provider abstractions for EVM/EIP-155 |
+//! | [`cli`] | Command line: `serve` and the operational debugging subcommands |
 //! | [`config`] | Configuration types and loading |
+//! | [`inspect`] | `GET /admin/config` running configuration, secrets redacted |
+//! | `mock` | Mock chains settling without a chain, for local development (`dev-mode` feature) |
 //! | [`run`] | Main server initialization and runtime |
 //! | [`schemes`] | Scheme builder implementations for supported payment schemes |
+//! | [`signers`] | `GET /health/signers` signer balance and nonce report, `GET /admin/signers` drift check |
+//! | [`validate`] | `--validate-config` and `--config-schema`: configuration checks and JSON Schema |
 //!
 //! # Running the Server
 //!
@@ -27,11 +32,25 @@
 //!
 //! # Run with custom config
 //! cargo run --package facilitator -- --config /path/to/config.json
+//!
+//! # Check a configured chain, see `cli` for the other subcommands
+//! cargo run --package facilitator -- --config /path/to/config.json check-chain eip155:42793
 //! ```
 
 pub mod chain;
+pub mod cli;
 pub mod config;
+pub mod domains;
+pub mod history;
+pub mod inspect;
+#[cfg(feature = "dev-mode")]
+pub mod mock;
+pub mod permit2;
+pub mod readiness;
 pub mod run;
 pub mod schemes;
+pub mod signatures;
+pub mod signers;
+pub mod validate;
 
 pub use run::run;
@@ -12,6 +12,15 @@
 //! # Run with custom configuration
 //! cargo run --package facilitator -- --config /path/to/config.json
 //!
+//! # Check a configuration file, or print its JSON Schema
+//! cargo run --package facilitator -- --config /path/to/config.json --validate-config
+//! cargo run --package facilitator -- --config-schema
+//!
+//! # Debug without the HTTP server, see the `cli` module
+//! cargo run --package facilitator -- check-chain eip155:42793
+//! cargo run --package facilitator -- sign-test-payment --pay-to 0x... > payment.json
+//! cargo run --package facilitator -- settle-file payment.json --verify-only
+//!
 //! # Run with telemetry enabled
 //! cargo run --package facilitator --features telemetry
 //! ```
@@ -29,22 +38,42 @@
 //!
 //! The binary is organized into modules:
 //! - [`chain`](crate::chain) - Blockchain provider abstractions
+//! - [`cli`](crate::cli) - Command line: `serve` (the default) and the debugging subcommands
 //! - [`config`](crate::config) - Configuration loading and validation
+//! - [`domains`](crate::domains) - Admin view and invalidation of cached token EIP-712 domains
+//! - [`inspect`](crate::inspect) - Admin view of the running configuration, secrets redacted
+//! - [`permit2`](crate::permit2) - Permit2 nonce lookup for clients building Permit2 payloads
 //! - [`run`](crate::run) - HTTP server initialization and request handling
 //! - [`schemes`](crate::schemes) - Payment scheme registration
+//! - [`signatures`](crate::signatures) - Signature checks without payment requirements, for wallet developers
+//! - [`signers`](crate::signers) - Signer balance and nonce health endpoint, signer drift check
+//! - [`validate`](crate::validate) - Configuration checks (`--validate-config`) and JSON Schema (`--config-schema`)
 
 mod chain;
+mod cli;
 mod config;
+mod domains;
+mod history;
+mod inspect;
+#[cfg(feature = "dev-mode")]
+mod mock;
+mod permit2;
+mod readiness;
 mod run;
 mod schemes;
+mod signatures;
+mod signers;
+mod validate;
 
 use std::process;
 
-use crate::run::run;
+use clap::Parser;
+
+use crate::cli::Cli;
 
 #[tokio::main]
 async fn main() {
-    let result = run().await;
+    let result = Cli::parse().run().await;
     if let Err(e) = result {
         println!("{e}");
         process::exit(1)
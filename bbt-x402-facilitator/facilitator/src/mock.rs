@@ -0,0 +1,268 @@
+//! Mock chains for local development (`dev-mode` feature).
+//!
+//! A chain configured with `"mock": true` is served by a [`MockChainProvider`] instead of
+//! an RPC node. Every scheme enabled on it accepts any well-formed payload, that is one
+//! naming the chain and scheme and carrying a payer, a recipient and an amount, and
+//! "settles" it by returning a fake transaction hash. No signature, balance or chain
+//! state is checked, and nothing is sent anywhere; with a settlement ledger attached,
+//! the settlements are recorded like real ones.
+//!
+//! ```json
+//! {
+//!   "chains": {
+//!     "eip155:31337": { "mock": true }
+//!   },
+//!   "schemes": [
+//!     { "id": "v2-eip155-exact", "chains": "eip155:31337" }
+//!   ]
+//! }
+//! ```
+//!
+//! `signer` sets the address advertised on `/supported` (default
+//! [`DEFAULT_MOCK_SIGNER`]). Never enable this feature in production builds.
+
+use serde::{Deserialize, Serialize};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use x402_types::chain::{ChainId, ChainProviderOps};
+use x402_types::proto::{self, PaymentVerificationError, v1};
+use x402_types::scheme::{X402SchemeFacilitator, X402SchemeFacilitatorError};
+
+/// Signer address advertised by a mock chain without `signer`.
+pub const DEFAULT_MOCK_SIGNER: &str = "0x0000000000000000000000000000000000000402";
+
+/// Configuration of a mock chain.
+#[derive(Debug, Clone)]
+pub struct MockChainConfig {
+    /// The chain payments are accepted for.
+    pub chain_id: ChainId,
+    /// The entry as configured.
+    pub inner: MockChainConfigInner,
+}
+
+/// The body of a mock chain entry in the `chains` section.
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct MockChainConfigInner {
+    /// Marks the entry as a mock chain; must be `true`.
+    pub mock: bool,
+    /// Signer address advertised on `/supported`.
+    #[serde(default = "default_mock_signer")]
+    pub signer: String,
+}
+
+fn default_mock_signer() -> String {
+    DEFAULT_MOCK_SIGNER.to_string()
+}
+
+/// Returns `true` if the chain entry `value` configures a mock chain.
+pub fn is_mock_chain(value: &serde_json::Value) -> bool {
+    value.get("mock").and_then(serde_json::Value::as_bool) == Some(true)
+}
+
+/// Chain provider that settles without a chain.
+#[derive(Debug)]
+pub struct MockChainProvider {
+    chain_id: ChainId,
+    signer: String,
+    /// Settlements so far, mixed into the fake transaction hashes.
+    settlements: AtomicU64,
+}
+
+impl MockChainProvider {
+    pub fn new(config: &MockChainConfig) -> Self {
+        Self {
+            chain_id: config.chain_id.clone(),
+            signer: config.inner.signer.clone(),
+            settlements: AtomicU64::new(0),
+        }
+    }
+
+    /// A handler accepting every well-formed payload of `scheme` in protocol `x402_version`.
+    pub fn facilitator(
+        self: &Arc<Self>,
+        x402_version: u8,
+        scheme: &str,
+    ) -> Box<dyn X402SchemeFacilitator> {
+        Box::new(MockSchemeFacilitator {
+            provider: Arc::clone(self),
+            x402_version,
+            scheme: scheme.to_string(),
+        })
+    }
+
+    /// A transaction hash that looks real and differs for every settlement.
+    fn fake_transaction_hash(&self, request: &proto::SettleRequest) -> String {
+        let settlement = self.settlements.fetch_add(1, Ordering::Relaxed);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        let words: Vec<String> = (0u8..4)
+            .map(|word| {
+                let mut hasher = DefaultHasher::new();
+                (word, settlement, now, request.as_raw().get()).hash(&mut hasher);
+                format!("{:016x}", hasher.finish())
+            })
+            .collect();
+        format!("0x{}", words.concat())
+    }
+}
+
+impl ChainProviderOps for MockChainProvider {
+    fn signer_addresses(&self) -> Vec<String> {
+        vec![self.signer.clone()]
+    }
+
+    fn chain_id(&self) -> ChainId {
+        self.chain_id.clone()
+    }
+}
+
+/// Scheme handler of a mock chain, see the [module documentation](self).
+struct MockSchemeFacilitator {
+    provider: Arc<MockChainProvider>,
+    x402_version: u8,
+    scheme: String,
+}
+
+impl MockSchemeFacilitator {
+    /// The network as written in payloads of the handler's protocol version.
+    fn network(&self) -> String {
+        match self.x402_version {
+            1 => self
+                .provider
+                .chain_id
+                .as_network_name()
+                .map(str::to_string)
+                .unwrap_or_else(|| self.provider.chain_id.to_string()),
+            _ => self.provider.chain_id.to_string(),
+        }
+    }
+
+    /// Checks that `request` is for this handler and names a payer, a recipient and
+    /// an amount. Returns the payer.
+    fn assert_well_formed(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<String, PaymentVerificationError> {
+        let slug = request.scheme_handler_slug().ok_or_else(|| {
+            PaymentVerificationError::InvalidFormat("unknown version, network or scheme".into())
+        })?;
+        if slug.chain_id != self.provider.chain_id {
+            return Err(PaymentVerificationError::ChainIdMismatch);
+        }
+        if slug.x402_version != self.x402_version || slug.name != self.scheme {
+            return Err(PaymentVerificationError::UnsupportedScheme);
+        }
+        let missing =
+            |field: &str| PaymentVerificationError::InvalidFormat(format!("missing {field}"));
+        request.payee().ok_or_else(|| missing("payTo"))?;
+        request.amount().ok_or_else(|| missing("amount"))?;
+        request.payer().ok_or_else(|| missing("payer"))
+    }
+}
+
+#[async_trait::async_trait]
+impl X402SchemeFacilitator for MockSchemeFacilitator {
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
+        let payer = self.assert_well_formed(request)?;
+        Ok(v1::VerifyResponse::Valid { payer }.into())
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let payer = self.assert_well_formed(request)?;
+        let transaction = self.provider.fake_transaction_hash(request);
+        #[cfg(feature = "telemetry")]
+        tracing::info!(chain_id = %self.provider.chain_id, %payer, %transaction, "Mock settlement");
+        Ok(v1::SettleResponse::Success {
+            payer,
+            transaction,
+            network: self.network(),
+            receipt: v1::SettlementReceipt::default(),
+        }
+        .into())
+    }
+
+    async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
+        Ok(proto::SupportedResponse {
+            kinds: vec![proto::SupportedPaymentKind {
+                x402_version: self.x402_version,
+                scheme: self.scheme.clone(),
+                network: self.network(),
+                extra: Some(serde_json::json!({ "mock": true })),
+            }],
+            extensions: Vec::new(),
+            signers: [(
+                self.provider.chain_id.clone(),
+                vec![self.provider.signer.clone()],
+            )]
+            .into_iter()
+            .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(payer: Option<&str>) -> proto::VerifyRequest {
+        let mut authorization =
+            serde_json::json!({ "to": "0x2222222222222222222222222222222222222222" });
+        if let Some(payer) = payer {
+            authorization["from"] = payer.into();
+        }
+        serde_json::json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "x402Version": 2,
+                "accepted": { "scheme": "exact", "network": "eip155:31337" },
+                "payload": { "signature": "0x00", "authorization": authorization }
+            },
+            "paymentRequirements": {
+                "scheme": "exact",
+                "network": "eip155:31337",
+                "amount": "1000",
+                "payTo": "0x2222222222222222222222222222222222222222",
+                "asset": "0x3333333333333333333333333333333333333333"
+            }
+        })
+        .into()
+    }
+
+    #[tokio::test]
+    async fn test_mock_settles_well_formed_payloads() {
+        let provider = Arc::new(MockChainProvider::new(&MockChainConfig {
+            chain_id: ChainId::new("eip155", "31337"),
+            inner: MockChainConfigInner {
+                mock: true,
+                signer: default_mock_signer(),
+            },
+        }));
+        let facilitator = provider.facilitator(2, "exact");
+        let payer = "0x1111111111111111111111111111111111111111";
+
+        let verified = facilitator.verify(&request(Some(payer))).await.unwrap();
+        assert_eq!(verified.0["isValid"], true);
+        let first = facilitator.settle(&request(Some(payer))).await.unwrap();
+        let second = facilitator.settle(&request(Some(payer))).await.unwrap();
+        assert_eq!(first.0["success"], true);
+        assert_eq!(first.0["payer"], payer);
+        assert_eq!(first.0["network"], "eip155:31337");
+        let transaction = first.0["transaction"].as_str().unwrap();
+        assert_eq!(transaction.len(), 66);
+        assert_ne!(first.0["transaction"], second.0["transaction"]);
+
+        assert!(facilitator.verify(&request(None)).await.is_err());
+        let upto = provider.facilitator(2, "upto");
+        assert!(upto.settle(&request(Some(payer))).await.is_err());
+    }
+}
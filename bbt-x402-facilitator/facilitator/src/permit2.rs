@@ -0,0 +1,71 @@
+//! `GET /permit2/nonce`: the Permit2 nonces a client needs to build a Permit2 payload.
+//!
+//! Lets clients without RPC access of their own sign valid Permit2 payloads. The
+//! nonces are read from the Permit2 contract of the chain named by `chain`:
+//!
+//! ```text
+//! GET /permit2/nonce?chain=eip155:42793&owner=0x...&token=0x...&spender=0x...&word=0
+//! ```
+//!
+//! The answer holds the `AllowanceTransfer` allowance of the owner, token and
+//! spender (one of the facilitator signers when `spender` is left out), with the
+//! nonce the next `permit2` payload must sign, and word `word` (`0` by default) of
+//! the owner's `SignatureTransfer` nonce bitmap, with its lowest unused nonce for a
+//! `permit2Authorization` payload.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::json;
+use x402_types::chain::ChainId;
+
+use crate::chain::ChainQueryError;
+use crate::signers::SignerHealth;
+
+/// Routes serving Permit2 nonces of the configured chains.
+pub fn routes() -> Router<Arc<SignerHealth>> {
+    Router::new().route("/permit2/nonce", get(get_permit2_nonce))
+}
+
+fn lookup_error(status: StatusCode, error: &'static str, details: String) -> Response {
+    (status, Json(json!({ "error": error, "details": details }))).into_response()
+}
+
+/// `GET /permit2/nonce`: reports the Permit2 nonces of `owner` on `chain`.
+async fn get_permit2_nonce(
+    State(signer_health): State<Arc<SignerHealth>>,
+    Query(mut params): Query<BTreeMap<String, String>>,
+) -> Response {
+    let Some(network) = params
+        .remove("chain")
+        .and_then(|chain| chain.parse::<ChainId>().ok())
+    else {
+        return lookup_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "missing or invalid chain".to_string(),
+        );
+    };
+    let chains = signer_health.chains();
+    let Some(provider) = chains.by_chain_id(network.clone()) else {
+        return lookup_error(
+            StatusCode::NOT_FOUND,
+            "unsupported_network",
+            format!("network {network} is not configured"),
+        );
+    };
+    match provider.permit2_nonces(json!(params)).await {
+        Ok(nonces) => Json(nonces).into_response(),
+        Err(e @ ChainQueryError::InvalidQuery(_)) => {
+            lookup_error(StatusCode::BAD_REQUEST, "invalid_request", e.to_string())
+        }
+        Err(e @ ChainQueryError::Rpc(_)) => {
+            lookup_error(StatusCode::BAD_GATEWAY, "rpc_error", e.to_string())
+        }
+    }
+}
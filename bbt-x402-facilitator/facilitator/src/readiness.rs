@@ -0,0 +1,290 @@
+//! `GET /ready`: whether the facilitator is ready to take payments.
+//!
+//! `/health` answers as soon as the server listens. `/ready` answers
+//! `503 Service Unavailable` until a warm-up has passed, so that load balancers
+//! only route payments to an instance whose first requests can succeed:
+//!
+//! - `schemes` - at least one scheme handler is built
+//! - `chain:<chain id>` - the chain head was fetched and the signer nonces synced
+//! - `compliance` - the screening provider answered a probe
+//!
+//! Failed checks are retried every [`WARM_UP_RETRY`] until they all pass. Once
+//! ready, the instance stays ready; ongoing signer and task health is reported on
+//! `/health/signers` and `/health/tasks`.
+//!
+//! # Startup Self-Test
+//!
+//! Before serving, [`self_test`] checks every chain for the misconfigurations that
+//! would otherwise only show as failing payments: an RPC that does not answer or
+//! serves another chain, signers without gas money, and missing Multicall3, Permit2
+//! or EIP-6492 validator contracts. The summary is logged and reported as `selfTest`
+//! by `/ready`. `STARTUP_SELF_TEST` selects what failed checks do:
+//!
+//! - `warn` (default) - they are logged, and the facilitator starts anyway
+//! - `strict` - the facilitator refuses to start
+//! - `off` - the self-test is skipped
+
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Serialize;
+use x402_facilitator_local::FacilitatorLocal;
+use x402_facilitator_local::compliance::ComplianceGate;
+use x402_types::facilitator::Facilitator;
+use x402_types::scheme::SchemeRegistry;
+
+use crate::chain::ChainProvider;
+use crate::signers::SignerHealth;
+use x402_types::chain::ChainRegistry;
+
+/// Delay between two warm-up attempts.
+pub const WARM_UP_RETRY: Duration = Duration::from_secs(5);
+
+/// Outcome of one warm-up check.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessCheck {
+    pub name: String,
+    pub ready: bool,
+    /// What was found, or why the check failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Readiness of the facilitator, as reported by `GET /ready`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReadinessReport {
+    /// `true` once every check has passed.
+    pub ready: bool,
+    /// Checks of the last warm-up attempt, empty before the first one.
+    pub checks: Vec<ReadinessCheck>,
+    /// Outcome of the startup self-test, unless it was skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub self_test: Option<SelfTestReport>,
+}
+
+/// Outcome of the startup [`self_test`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SelfTestReport {
+    /// `true` if every check passed.
+    pub passed: bool,
+    pub checks: Vec<ReadinessCheck>,
+}
+
+impl SelfTestReport {
+    /// The checks that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &ReadinessCheck> {
+        self.checks.iter().filter(|check| !check.ready)
+    }
+}
+
+/// What failed startup self-test checks do, from `STARTUP_SELF_TEST`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelfTestMode {
+    /// Skip the self-test.
+    Off,
+    /// Log failed checks and start anyway.
+    #[default]
+    Warn,
+    /// Refuse to start.
+    Strict,
+}
+
+impl FromStr for SelfTestMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "off" | "false" | "0" => Ok(Self::Off),
+            "warn" | "" => Ok(Self::Warn),
+            "strict" => Ok(Self::Strict),
+            other => Err(format!(
+                "invalid STARTUP_SELF_TEST {other}, expected off, warn or strict"
+            )),
+        }
+    }
+}
+
+impl SelfTestMode {
+    /// Reads `STARTUP_SELF_TEST`, defaulting to [`SelfTestMode::Warn`].
+    pub fn from_env() -> Result<Self, String> {
+        std::env::var("STARTUP_SELF_TEST").map_or(Ok(Self::default()), |value| value.parse())
+    }
+}
+
+/// Runs the self-test of every chain in `chains`, see [`ChainProvider::self_test`].
+///
+/// Without any chain configured, the report holds a single failed `chains` check.
+pub async fn self_test(chains: &ChainRegistry<ChainProvider>) -> SelfTestReport {
+    let mut providers: Vec<_> = chains.iter().collect();
+    providers.sort_by_key(|(chain_id, _)| chain_id.to_string());
+    let mut checks = Vec::new();
+    if providers.is_empty() {
+        checks.push(check(
+            "chains".to_string(),
+            Err("no chain is configured".to_string()),
+        ));
+    }
+    for (_, provider) in providers {
+        checks.extend(provider.self_test().await);
+    }
+    SelfTestReport {
+        passed: checks.iter().all(|check| check.ready),
+        checks,
+    }
+}
+
+/// Shared state of the `/ready` endpoint.
+pub struct Readiness {
+    facilitator: Arc<FacilitatorLocal<SchemeRegistry>>,
+    signer_health: Arc<SignerHealth>,
+    compliance_gate: ComplianceGate,
+    report: RwLock<ReadinessReport>,
+    self_test: Option<SelfTestReport>,
+}
+
+impl Readiness {
+    pub fn new(
+        facilitator: Arc<FacilitatorLocal<SchemeRegistry>>,
+        signer_health: Arc<SignerHealth>,
+        compliance_gate: ComplianceGate,
+    ) -> Self {
+        Self {
+            facilitator,
+            signer_health,
+            compliance_gate,
+            report: RwLock::new(ReadinessReport::default()),
+            self_test: None,
+        }
+    }
+
+    /// Reports `self_test` alongside the warm-up checks.
+    pub fn with_self_test(mut self, self_test: SelfTestReport) -> Self {
+        self.self_test = Some(self_test);
+        self
+    }
+
+    /// The report of the last warm-up attempt.
+    pub fn report(&self) -> ReadinessReport {
+        let mut report = self.report.read().expect("readiness lock poisoned").clone();
+        report.self_test = self.self_test.clone();
+        report
+    }
+
+    /// Runs the warm-up checks once and returns whether they all passed.
+    ///
+    /// Does nothing once the facilitator is ready.
+    pub async fn warm_up(&self) -> bool {
+        if self.report().ready {
+            return true;
+        }
+        let mut checks = Vec::new();
+        let schemes = match self.facilitator.supported().await {
+            Ok(supported) if !supported.kinds.is_empty() => {
+                Ok(format!("{} payment kinds", supported.kinds.len()))
+            }
+            Ok(_) => Err("no scheme handler is configured".to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        checks.push(check("schemes".to_string(), schemes));
+        let chains = self.signer_health.chains();
+        let mut providers: Vec<_> = chains.iter().collect();
+        providers.sort_by_key(|(chain_id, _)| chain_id.to_string());
+        for (chain_id, provider) in providers {
+            let head = provider.warm_up().await.map(|head| format!("head {head}"));
+            checks.push(check(format!("chain:{chain_id}"), head));
+        }
+        let compliance = self
+            .compliance_gate
+            .probe()
+            .await
+            .map(|()| "probed".to_string());
+        checks.push(check("compliance".to_string(), compliance));
+
+        let ready = checks.iter().all(|check| check.ready);
+        #[cfg(feature = "telemetry")]
+        for failed in checks.iter().filter(|check| !check.ready) {
+            tracing::warn!(check = %failed.name, detail = ?failed.detail, "Warm-up check failed");
+        }
+        *self.report.write().expect("readiness lock poisoned") = ReadinessReport {
+            ready,
+            checks,
+            self_test: None,
+        };
+        ready
+    }
+}
+
+fn check(name: String, result: Result<String, String>) -> ReadinessCheck {
+    let ready = result.is_ok();
+    let detail = result.unwrap_or_else(|error| error);
+    ReadinessCheck {
+        name,
+        ready,
+        detail: Some(detail),
+    }
+}
+
+/// Routes serving the readiness report.
+pub fn routes() -> Router<Arc<Readiness>> {
+    Router::new().route("/ready", get(get_ready))
+}
+
+/// `GET /ready`: `200 OK` once warmed up, `503 Service Unavailable` before.
+async fn get_ready(State(readiness): State<Arc<Readiness>>) -> impl IntoResponse {
+    let report = readiness.report();
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_not_ready_without_schemes() {
+        let readiness = Readiness::new(
+            Arc::new(FacilitatorLocal::new(SchemeRegistry::default())),
+            Arc::new(SignerHealth::new(ChainRegistry::new(HashMap::new()))),
+            ComplianceGate::disabled(),
+        );
+        assert!(!readiness.report().ready);
+        assert!(!readiness.warm_up().await);
+        let report = readiness.report();
+        let schemes = &report.checks[0];
+        assert_eq!(schemes.name, "schemes");
+        assert!(!schemes.ready);
+        assert!(
+            report
+                .checks
+                .iter()
+                .any(|c| c.name == "compliance" && c.ready)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_self_test_without_chains_fails() {
+        let report = self_test(&ChainRegistry::new(HashMap::new())).await;
+        assert!(!report.passed);
+        assert_eq!(report.failures().count(), 1);
+        assert_eq!(report.checks[0].name, "chains");
+    }
+
+    #[test]
+    fn test_self_test_mode() {
+        assert_eq!("strict".parse(), Ok(SelfTestMode::Strict));
+        assert_eq!(" OFF ".parse(), Ok(SelfTestMode::Off));
+        assert_eq!("warn".parse(), Ok(SelfTestMode::Warn));
+        assert!("loud".parse::<SelfTestMode>().is_err());
+    }
+}
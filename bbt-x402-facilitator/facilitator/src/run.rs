@@ -13,48 +13,149 @@
 //! | `POST` | `/settle` | Settle an accepted payment payload on-chain |
 //! | `GET` | `/supported` | List supported payment kinds (version/scheme/network) |
 //! | `GET` | `/health` | Health check endpoint |
+//! | `GET` | `/ready` | Readiness: `503` until chains, signer nonces and compliance are warmed up |
+//! | `GET` | `/events` | Live payment events as Server-Sent Events, filtered by `payer`, `payee` and `chain` |
+//! | `GET` | `/health/signers` | Signer balances, pending transactions and RPC endpoint state per chain |
+//! | `POST` | `/settlements/verify` | Check on chain that a past transaction settled a payment |
+//! | `GET` | `/health/tasks` | Status of background tasks (config watching, ...) |
+//! | `GET` | `/health/cluster` | Whether this replica is the cluster leader, and which one is |
+//! | `GET` | `/admin/signers` | Signers not advertised, not settling or not funded (admin API key) |
+//! | `GET` | `/admin/dlq` | Dead-lettered settlements (admin API key, `SETTLEMENT_DLQ_ENABLED`) |
+//! | `POST` | `/admin/dlq/{id}/requeue` | Settle a dead-lettered entry again |
+//! | `POST` | `/admin/dlq/{id}/void` | Drop a dead-lettered entry |
+//! | `GET` | `/settlements` | Recorded settlement attempts, filtered by `payer`, `payee`, `from` and `to` (admin API key, `storage` feature, `SETTLEMENT_LEDGER_ENABLED`) |
+//! | `POST` | `/refund` | Send a recorded settlement back to its payer from the chain's `refund_treasury` (admin API key, `storage` feature, `SETTLEMENT_LEDGER_ENABLED`) |
+//! | `GET` | `/admin/guardrail` | Signer spending caps, usage and pause (admin API key, `GUARDRAIL_*`) |
+//! | `POST` | `/admin/resume` | Resume settlement paused by the guardrail |
+//! | `GET` | `/admin/config` | Running configuration, secrets redacted (admin API key) |
+//! | `GET` | `/admin/chains` | Registered chains with their scheme handlers and signers (admin API key) |
+//! | `GET` | `/admin/schemes` | Registered scheme handlers with their payment kinds, asset policies and signers (admin API key) |
+//! | `POST` | `/admin/chains/{chain_id}/disable` | Stop serving payments on a chain until `/enable` (admin API key) |
+//! | `POST` | `/admin/schemes/{slug}/disable` | Stop serving payments with a scheme handler until `/enable` (admin API key) |
+//! | `POST` | `/admin/cluster/resign` | Make this replica give up cluster leadership (admin API key) |
+//! | `GET` | `/discovery/resources` | Priced resources registered by resource servers, filtered by `type`, `network`, `scheme` and `asset` (`DISCOVERY_ENABLED`) |
+//! | `POST` | `/discovery/resources` | Register a priced resource (settle API key when keys are configured) |
+//! | `POST` | `/debug/decode` | Explain a payment payload: signature kind, EIP-712 domain and digest, static checks (`DEBUG_ENDPOINTS_ENABLED`) |
+//! | `GET` | `/openapi.json` | OpenAPI spec of `/verify`, `/settle`, `/supported` and `/health` (`openapi` feature) |
+//! | `GET` | `/docs` | Swagger UI for the OpenAPI spec (`openapi` feature) |
+//!
+//! With the `grpc` feature and `GRPC_PORT` set, the `x402.facilitator.v1.Facilitator` gRPC
+//! service (`Verify`, `Settle`, `Supported`, `SettlementEvents`) is served on that port, see
+//! `x402_facilitator_local::grpc`.
 //!
 //! # Features
 //!
 //! - `Multi-chain support`: EIP-155 (EVM) networks
 //! - `OpenTelemetry` tracing (with `telemetry` feature): distributed tracing and metrics
+//! - `OpenAPI` spec and Swagger UI (with `openapi` feature): see [`x402_facilitator_local::openapi`]
 //! - `CORS` support: Cross-origin requests for browser-based clients
-//! - `Graceful shutdown`: Signal-based shutdown with cleanup
+//! - `Graceful shutdown`: on SIGTERM/SIGINT new settlements are turned away and the ones in
+//!   flight get `SETTLEMENT_DRAIN_TIMEOUT_SECS` to confirm; a second signal stops waiting
+//! - `Config reload`: chains and schemes are rebuilt from the config file on `SIGHUP`,
+//!   or whenever the file changes if `CONFIG_WATCH` is enabled
 //!
 //! # Environment Variables
 //!
 //! - `HOST` - Server bind address (default: `0.0.0.0`)
 //! - `PORT` - Server port (default: `9090`)
-//! - `CONFIG` - Path to configuration file (default: `config.json`)
+//! - `GRPC_PORT` - gRPC server port (with the `grpc` feature); the gRPC server is off when unset
+//! - `CONFIG` - Path to configuration file (default: `config.json`); check it with `--validate-config`, see [`crate::validate`]
+//! - `STARTUP_SELF_TEST` - `warn` (default), `strict` to refuse to start when a chain fails its self-test, or `off`, see [`crate::readiness`]
+//! - `CONFIG_WATCH` - reload chains and schemes when the config file changes (true/false, defaults to false)
+//! - `DEBUG_ENDPOINTS_ENABLED` - serve `POST /debug/decode`, for development only (true/false, defaults to false)
+//! - `V1_UPCONVERSION_ENABLED` - handle V1 payments with V2 schemes when their V1 scheme is not registered (true/false, defaults to false)
 //! - `X402_CORS_ALLOWED_ORIGINS` - comma-separated CORS allowlist, or `*` to allow all
 //! - COMPLIANCE_SCREENING_ENABLED - enable off-chain compliance checks (true/false, defaults to true)
 //! - `COMPLIANCE_DENY_LIST` - comma-separated list of denied addresses
 //! - `COMPLIANCE_ALLOW_LIST` - comma-separated list of allowed addresses (if set, only these are allowed)
+//! - `COMPLIANCE_PROVIDER` - `chainalysis` (default), `trm`, `elliptic` or `lists`, see [`x402_facilitator_local::compliance`]
+//! - `COMPLIANCE_AUDIT_SINKS` - where audit events go: `file`, `stdout`, `syslog`, `http`, `s3`, see [`x402_facilitator_local::compliance::audit`]
+//! - `GEO_*` - country blocking of `/verify` and `/settle`, see [`x402_facilitator_local::compliance::geo`]
+//! - `API_KEYS` - comma-separated `id:key:scope` entries guarding `/verify` and `/settle`, see [`x402_facilitator_local::auth`]
+//! - `RATE_LIMIT_*` - per-IP and per-API-key rate limits, see [`x402_facilitator_local::rate_limit`]
+//! - `VELOCITY_*` - per-payer settlement count and value limits, see [`x402_facilitator_local::velocity`]
+//! - `GUARDRAIL_*` - per-signer gas and value caps pausing settlement, see [`x402_facilitator_local::guardrail`]
+//! - `SETTLEMENT_*` - settlement retries and the dead-letter queue, see [`x402_facilitator_local::dead_letter`]
+//! - `SETTLEMENT_DRAIN_TIMEOUT_SECS`, `SETTLEMENT_JOURNAL_PATH` - draining and journaling of in-flight settlements, see [`x402_facilitator_local::in_flight`]
+//! - `SETTLEMENT_LEDGER_*` - the settlement ledger (with the `storage` feature), see `x402_facilitator_local::ledger`
+//! - `NOTIFICATION_OUTBOX_*` - guaranteed notification delivery, see [`x402_facilitator_local::outbox`]
+//! - `DISCOVERY_*` - the catalog of priced resources, see [`x402_facilitator_local::discovery`]
+//! - `PAYLOAD_LOG_*` - redacted logging of verify and settle payloads, see [`x402_facilitator_local::payload_log`]
+//! - `CLUSTER_*` - leader election for singleton background jobs, see [`x402_facilitator_local::cluster`]
+//! - `EVENT_BUS*` - settlement and compliance events to Kafka or NATS, see [`x402_facilitator_local::event_bus`]
 //! - `OTEL_*` - OpenTelemetry configuration (when `telemetry` feature enabled)
 
 use std::io;
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
-use axum::http::{HeaderValue, Method};
 use axum::Router;
+use axum::http::{HeaderValue, Method};
 use dotenvy::dotenv;
+use tokio::signal::unix::{SignalKind, signal};
 use tower_http::cors;
 
-use x402_facilitator_local::util::SigDown;
-use x402_facilitator_local::{FacilitatorLocal, handlers};
-#[cfg(feature = "chain-eip155")]
-use x402_chain_eip155::{V1Eip155Exact, V2Eip155Exact};
-use x402_types::chain::{ChainRegistry, FromConfig};
-use x402_types::scheme::{SchemeBlueprints, SchemeRegistry};
+#[cfg(feature = "openapi")]
+use utoipa::OpenApi;
+#[cfg(feature = "openapi")]
+use utoipa_swagger_ui::SwaggerUi;
+#[cfg(feature = "storage")]
+use x402_facilitator_local::SettlementLedger;
+#[cfg(feature = "grpc")]
+use x402_facilitator_local::grpc::GrpcFacilitator;
+#[cfg(feature = "openapi")]
+use x402_facilitator_local::openapi::FacilitatorApi;
 #[cfg(feature = "telemetry")]
 use x402_facilitator_local::util::Telemetry;
+use x402_facilitator_local::util::{Scheduler, SigDown};
+use x402_facilitator_local::{
+    ApiKeyAuth, Cluster, DeadLetterQueue, DiscoveryCatalog, EmbeddedFacilitator, EventBus,
+    FacilitatorLocal, GeoBlocker, InFlightSettlements, NotificationDispatcher, Outbox, PayloadLog,
+    PaymentEvents, RateLimiter, ResponseAmountFormat, SettlementGuardrail, VelocityLimiter,
+    handlers,
+};
+use x402_types::chain::{ChainRegistry, FromConfig};
+use x402_types::config::CliArgs;
+use x402_types::scheme::SchemeRegistry;
 
+use crate::chain::ChainProvider;
 use crate::config::Config;
+use crate::domains;
+use crate::history;
+use crate::inspect::{self, RunningConfig};
+use crate::permit2;
+use crate::readiness::{self, Readiness, SelfTestMode, SelfTestReport};
+use crate::schemes;
+use crate::signatures;
+use crate::signers::{self, SignerAudit, SignerHealth};
+use crate::validate;
+
+/// How often the config file is checked for changes when `CONFIG_WATCH` is enabled.
+const CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+/// Upper bound of the random delay added to each config file check.
+const CONFIG_WATCH_JITTER: Duration = Duration::from_secs(1);
+
+/// How often undelivered notifications are retried.
+const OUTBOX_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often due deferred payments are settled, see `x402_chain_eip155::v2_eip155_deferred`.
+const DEFERRED_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often every RPC endpoint is probed, see `x402_chain_eip155::chain::rpc_failover`.
+const RPC_PROBE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How often signer nonces are reconciled with the chain, see
+/// `x402_chain_eip155::chain::pending_nonce_manager`.
+const NONCE_RECONCILE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long other requests and background jobs get to finish once settlements are drained.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
 
 fn build_cors_layer() -> Result<cors::CorsLayer, io::Error> {
     let raw = std::env::var("X402_CORS_ALLOWED_ORIGINS").unwrap_or_else(|_| {
-        "http://localhost:9091,http://127.0.0.1:9091,https://exp-store.bubbletez.com"
-            .to_string()
+        "http://localhost:9091,http://127.0.0.1:9091,https://exp-store.bubbletez.com".to_string()
     });
 
     let base = cors::CorsLayer::new()
@@ -88,58 +189,724 @@ fn build_cors_layer() -> Result<cors::CorsLayer, io::Error> {
     Ok(base.allow_origin(origins))
 }
 
+fn load_rate_limiter() -> Result<Option<RateLimiter>, io::Error> {
+    RateLimiter::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Port of the gRPC server, from `GRPC_PORT`. `None` when unset.
+#[cfg(feature = "grpc")]
+fn grpc_port() -> Result<Option<u16>, io::Error> {
+    match std::env::var("GRPC_PORT") {
+        Ok(port) if !port.trim().is_empty() => port.trim().parse().map(Some).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid GRPC_PORT {port}: {e}"),
+            )
+        }),
+        _ => Ok(None),
+    }
+}
+
+fn load_dead_letter_queue() -> Result<Option<DeadLetterQueue>, io::Error> {
+    DeadLetterQueue::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Runs the startup self-test of `chains` as `STARTUP_SELF_TEST` asks, see [`readiness::self_test`].
+///
+/// Fails in strict mode if any check failed.
+async fn run_self_test(
+    chains: &ChainRegistry<ChainProvider>,
+) -> Result<Option<SelfTestReport>, io::Error> {
+    let mode =
+        SelfTestMode::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    if mode == SelfTestMode::Off {
+        return Ok(None);
+    }
+    let report = readiness::self_test(chains).await;
+    #[cfg(feature = "telemetry")]
+    for check in &report.checks {
+        if check.ready {
+            tracing::info!(check = %check.name, detail = ?check.detail, "Self-test check passed");
+        } else {
+            tracing::warn!(check = %check.name, detail = ?check.detail, "Self-test check failed");
+        }
+    }
+    if mode == SelfTestMode::Strict && !report.passed {
+        let failures: Vec<String> = report
+            .failures()
+            .map(|check| format!("{}: {}", check.name, check.detail.as_deref().unwrap_or("")))
+            .collect();
+        return Err(io::Error::other(format!(
+            "startup self-test failed ({}), set STARTUP_SELF_TEST=warn to start anyway",
+            failures.join("; ")
+        )));
+    }
+    Ok(Some(report))
+}
+
+fn load_in_flight_settlements() -> Result<InFlightSettlements, io::Error> {
+    InFlightSettlements::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Waits for the settlements in flight, until the drain timeout or a second shutdown signal.
+///
+/// Settlements still unfinished stay journaled and are dead-lettered at the next startup.
+async fn drain_settlements(in_flight: &InFlightSettlements, sig_down: &SigDown) {
+    #[cfg(feature = "telemetry")]
+    tracing::info!(
+        in_flight = in_flight.in_flight().len(),
+        timeout = ?in_flight.drain_timeout(),
+        "Draining in-flight settlements"
+    );
+    let force_token = sig_down.force_token();
+    let _unfinished = tokio::select! {
+        unfinished = in_flight.drain() => unfinished,
+        _ = force_token.cancelled() => in_flight.in_flight(),
+    };
+    #[cfg(feature = "telemetry")]
+    for settlement in &_unfinished {
+        tracing::warn!(
+            id = settlement.id,
+            started_at = settlement.started_at.as_secs(),
+            payer = ?settlement.request.payer(),
+            "Settlement still in flight at shutdown"
+        );
+    }
+}
+
+#[cfg(feature = "storage")]
+fn load_settlement_ledger() -> Result<Option<SettlementLedger>, io::Error> {
+    SettlementLedger::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+fn load_event_bus() -> Result<Option<EventBus>, io::Error> {
+    EventBus::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+fn load_outbox() -> Result<Option<Outbox>, io::Error> {
+    Outbox::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+fn load_payload_log() -> Result<Option<PayloadLog>, io::Error> {
+    PayloadLog::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+fn load_velocity_limiter() -> Result<Option<VelocityLimiter>, io::Error> {
+    VelocityLimiter::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+fn load_guardrail() -> Result<Option<SettlementGuardrail>, io::Error> {
+    SettlementGuardrail::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+fn load_discovery_catalog() -> Result<Option<DiscoveryCatalog>, io::Error> {
+    DiscoveryCatalog::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+fn load_cluster() -> Result<Cluster, io::Error> {
+    Cluster::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+fn load_notifications(config: &Config) -> Result<Option<NotificationDispatcher>, io::Error> {
+    NotificationDispatcher::from_config(config.notifications())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+fn load_api_key_auth(config: &Config) -> Result<Option<ApiKeyAuth>, io::Error> {
+    ApiKeyAuth::from_config_and_env(config.api_keys())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
 fn load_compliance_gate() -> Result<x402_facilitator_local::compliance::ComplianceGate, io::Error> {
     x402_facilitator_local::compliance::ComplianceGate::from_env()
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
 }
 
-/// Initializes the x402 facilitator server.
+fn load_geo_blocker(
+    compliance_gate: &x402_facilitator_local::compliance::ComplianceGate,
+) -> Result<Option<GeoBlocker>, io::Error> {
+    GeoBlocker::from_env(compliance_gate.clone())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+fn config_watch_enabled() -> bool {
+    std::env::var("CONFIG_WATCH")
+        .map(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+fn debug_endpoints_enabled() -> bool {
+    std::env::var("DEBUG_ENDPOINTS_ENABLED")
+        .map(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+fn v1_upconversion_enabled() -> bool {
+    std::env::var("V1_UPCONVERSION_ENABLED")
+        .map(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Serves the gRPC interface until shutdown, see [`x402_facilitator_local::grpc`].
+#[cfg(feature = "grpc")]
+fn serve_grpc(
+    scheduler: &Scheduler,
+    grpc: GrpcFacilitator<Arc<FacilitatorLocal<SchemeRegistry>>>,
+    addr: SocketAddr,
+) {
+    #[cfg(feature = "telemetry")]
+    tracing::info!("Starting gRPC server at {}", addr);
+    scheduler.spawn("grpc-server", move |cancellation_token| async move {
+        tonic::transport::Server::builder()
+            .add_service(grpc.into_service())
+            .serve_with_shutdown(addr, cancellation_token.cancelled_owned())
+            .await
+            .map_err(|e| format!("gRPC server at {addr} failed: {e}"))
+    });
+}
+
+/// The enabled scheme ids and the chain patterns they apply to, comma-separated,
+/// for the telemetry resource.
+#[cfg(feature = "telemetry")]
+fn telemetry_resource_attributes(config: &Config) -> (String, String) {
+    let mut schemes = Vec::new();
+    let mut networks = Vec::new();
+    for scheme in config.schemes().iter().filter(|scheme| scheme.enabled) {
+        if !schemes.contains(&scheme.id) {
+            schemes.push(scheme.id.clone());
+        }
+        let pattern = scheme.chains.to_string();
+        if !networks.contains(&pattern) {
+            networks.push(pattern);
+        }
+    }
+    (schemes.join(","), networks.join(","))
+}
+
+/// Settlement verification, behind API keys when they are configured.
+fn settlement_history_routes(api_key_auth: &Option<Arc<ApiKeyAuth>>) -> Router<Arc<SignerHealth>> {
+    match api_key_auth {
+        Some(api_key_auth) => {
+            handlers::authenticated_routes(history::routes(), api_key_auth.clone())
+        }
+        None => history::routes(),
+    }
+}
+
+/// Permit2 nonce lookup, behind API keys when they are configured.
+fn permit2_nonce_routes(api_key_auth: &Option<Arc<ApiKeyAuth>>) -> Router<Arc<SignerHealth>> {
+    match api_key_auth {
+        Some(api_key_auth) => {
+            handlers::authenticated_routes(permit2::routes(), api_key_auth.clone())
+        }
+        None => permit2::routes(),
+    }
+}
+
+/// Signature checks, behind API keys when they are configured.
+fn signature_routes(api_key_auth: &Option<Arc<ApiKeyAuth>>) -> Router<Arc<SignerHealth>> {
+    match api_key_auth {
+        Some(api_key_auth) => {
+            handlers::authenticated_routes(signatures::routes(), api_key_auth.clone())
+        }
+        None => signatures::routes(),
+    }
+}
+
+/// The live payment event feed, behind API keys when they are configured.
+fn payment_event_routes(api_key_auth: &Option<Arc<ApiKeyAuth>>) -> Router<Arc<PaymentEvents>> {
+    match api_key_auth {
+        Some(api_key_auth) => {
+            handlers::authenticated_routes(handlers::payment_event_routes(), api_key_auth.clone())
+        }
+        None => handlers::payment_event_routes(),
+    }
+}
+
+/// Subscription status, behind API keys when they are configured.
+fn subscription_routes(
+    api_key_auth: &Option<Arc<ApiKeyAuth>>,
+) -> Router<Arc<FacilitatorLocal<SchemeRegistry>>> {
+    match api_key_auth {
+        Some(api_key_auth) => {
+            handlers::authenticated_routes(handlers::subscription_routes(), api_key_auth.clone())
+        }
+        None => handlers::subscription_routes(),
+    }
+}
+
+/// Connects to the configured chains and builds the scheme handlers on top of them.
+pub(crate) async fn build_registries(
+    config: &Config,
+) -> Result<(ChainRegistry<ChainProvider>, SchemeRegistry), Box<dyn std::error::Error>> {
+    config.register_networks()?;
+    let chain_registry = ChainRegistry::from_config(config.chains()).await?;
+    let scheme_blueprints = schemes::scheme_blueprints();
+    // A scheme whose config is malformed fails startup (and reloads) instead of
+    // being skipped.
+    let scheme_registry =
+        SchemeRegistry::try_build(chain_registry.clone(), scheme_blueprints, config.schemes())?;
+    Ok((chain_registry, scheme_registry))
+}
+
+/// Rebuilds the chain and scheme registries from the config file.
+///
+/// On error the running registries are kept. Server address, API keys, notification
+/// rules and the other settings read once at startup are not reloaded.
+struct ConfigReloader {
+    facilitator: Arc<FacilitatorLocal<SchemeRegistry>>,
+    signer_health: Arc<SignerHealth>,
+    running_config: Arc<RunningConfig>,
+    amount_format: Arc<ResponseAmountFormat>,
+    config_path: PathBuf,
+    last_modified: Mutex<Option<SystemTime>>,
+}
+
+impl ConfigReloader {
+    fn new(
+        facilitator: Arc<FacilitatorLocal<SchemeRegistry>>,
+        signer_health: Arc<SignerHealth>,
+        running_config: Arc<RunningConfig>,
+        amount_format: Arc<ResponseAmountFormat>,
+        config_path: PathBuf,
+    ) -> Self {
+        let last_modified = Mutex::new(modified_at(&config_path));
+        Self {
+            facilitator,
+            signer_health,
+            running_config,
+            amount_format,
+            config_path,
+            last_modified,
+        }
+    }
+
+    /// Re-reads the config file and swaps in freshly built chain and scheme registries.
+    async fn reload(&self) -> Result<(), String> {
+        *self
+            .last_modified
+            .lock()
+            .expect("config watch lock poisoned") = modified_at(&self.config_path);
+        let result = self.rebuild().await;
+        #[cfg(feature = "telemetry")]
+        match &result {
+            Ok(()) => tracing::info!("Chains and schemes reloaded"),
+            Err(e) => {
+                tracing::error!(error = %e, "Config reload failed, keeping previous configuration")
+            }
+        }
+        #[cfg(not(feature = "telemetry"))]
+        if let Err(e) = &result {
+            eprintln!("Config reload failed, keeping previous configuration: {e}");
+        }
+        result
+    }
+
+    async fn rebuild(&self) -> Result<(), String> {
+        let config = Config::load_from_path(self.config_path.clone()).map_err(|e| e.to_string())?;
+        let (chain_registry, scheme_registry) =
+            build_registries(&config).await.map_err(|e| e.to_string())?;
+        self.facilitator.replace_handlers(scheme_registry);
+        self.signer_health.replace_chains(chain_registry);
+        self.running_config.replace_registries(&config);
+        self.amount_format.set(config.amount_format());
+        Ok(())
+    }
+
+    /// Reloads if the file modification time changed since the last reload.
+    async fn reload_if_changed(&self) -> Result<(), String> {
+        let modified = modified_at(&self.config_path);
+        if modified
+            == *self
+                .last_modified
+                .lock()
+                .expect("config watch lock poisoned")
+        {
+            return Ok(());
+        }
+        #[cfg(feature = "telemetry")]
+        tracing::info!(path = %self.config_path.display(), "Config file changed, reloading");
+        self.reload().await
+    }
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Schedules config reloads on `SIGHUP`, and on file changes if `watch_file` is set.
+fn schedule_config_reload(
+    scheduler: &Scheduler,
+    reloader: Arc<ConfigReloader>,
+    watch_file: bool,
+) -> Result<(), io::Error> {
+    let mut sighup = signal(SignalKind::hangup())?;
+    if watch_file {
+        let reloader = reloader.clone();
+        scheduler.every(
+            "config-watch",
+            CONFIG_WATCH_INTERVAL,
+            CONFIG_WATCH_JITTER,
+            move || {
+                let reloader = reloader.clone();
+                async move { reloader.reload_if_changed().await }
+            },
+        );
+    }
+    scheduler.spawn("config-sighup", move |cancellation_token| async move {
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => return Ok(()),
+                _ = sighup.recv() => {
+                    #[cfg(feature = "telemetry")]
+                    tracing::info!(path = %reloader.config_path.display(), "SIGHUP received, reloading config");
+                    // Failures are logged by `reload`; the listener keeps running.
+                    let _ = reloader.reload().await;
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Probes the RPC endpoints of every chain, so failing ones leave the rotation
+/// before requests hit them.
+///
+/// Chains are looked up on each run, so the probes follow config reloads.
+fn schedule_rpc_probes(scheduler: &Scheduler, signer_health: Arc<SignerHealth>) {
+    scheduler.every(
+        "rpc-probe",
+        RPC_PROBE_INTERVAL,
+        Duration::from_secs(1),
+        move || {
+            let chains = signer_health.chains();
+            async move {
+                let mut errors = Vec::new();
+                for (_, provider) in chains.iter() {
+                    if let Err(e) = provider.probe_rpc().await {
+                        errors.push(e);
+                    }
+                }
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors.join("; "))
+                }
+            }
+        },
+    );
+}
+
+/// Reconciles the signer nonces of every chain with the chain state, resyncing
+/// drifted nonce caches. A run fails while a signer has a stuck nonce, which
+/// `POST /admin/signers/{chain_id}/{address}/cancel-pending` clears.
+///
+/// Chains are looked up on each run, so reconciliation follows config reloads.
+fn schedule_nonce_reconciliation(scheduler: &Scheduler, signer_health: Arc<SignerHealth>) {
+    scheduler.every(
+        "nonce-reconcile",
+        NONCE_RECONCILE_INTERVAL,
+        Duration::from_secs(5),
+        move || {
+            let chains = signer_health.chains();
+            async move {
+                let mut errors = Vec::new();
+                for (_, provider) in chains.iter() {
+                    if let Err(e) = provider.reconcile_nonces().await {
+                        errors.push(e);
+                    }
+                }
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors.join("; "))
+                }
+            }
+        },
+    );
+}
+
+/// Keeps the leader lease of `cluster` renewed, and releases it on shutdown.
+///
+/// The first renewal happens right away, so a lone replica leads from startup.
+fn schedule_lease_renewal(scheduler: &Scheduler, cluster: Arc<Cluster>) {
+    scheduler.spawn("cluster-lease", move |cancellation_token| async move {
+        loop {
+            if let Err(_e) = cluster.renew() {
+                #[cfg(feature = "telemetry")]
+                tracing::warn!(error = %_e, "Cluster lease renewal failed");
+            }
+            tokio::select! {
+                _ = cancellation_token.cancelled() => break,
+                _ = tokio::time::sleep(cluster.renew_interval()) => {}
+            }
+        }
+        cluster.resign()
+    });
+}
+
+/// `--validate-config` and `validate-config`: prints the problems of the config file, failing if there are any.
+pub(crate) fn check_config(config_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let problems = validate::validate_config_file(config_path)?;
+    if problems.is_empty() {
+        println!("{} is valid", config_path.display());
+        return Ok(());
+    }
+    let problems: Vec<String> = problems
+        .iter()
+        .map(|problem| format!("  {problem}"))
+        .collect();
+    Err(format!(
+        "{} is invalid:\n{}",
+        config_path.display(),
+        problems.join("\n")
+    )
+    .into())
+}
+
+/// Installs the TLS crypto provider and loads `.env` variables, once per process.
+pub fn init() -> Result<(), io::Error> {
+    rustls::crypto::CryptoProvider::install_default(rustls::crypto::ring::default_provider())
+        .map_err(|e| {
+            io::Error::other(format!(
+                "failed to initialize rustls crypto provider: {e:?}"
+            ))
+        })?;
+    dotenv().ok();
+    Ok(())
+}
+
+/// Initializes the x402 facilitator server, after [`init`].
 ///
-/// - Loads `.env` variables.
 /// - Initializes OpenTelemetry tracing.
 /// - Connects to Ethereum providers for supported networks.
 /// - Starts an Axum HTTP server with the x402 protocol handlers.
+/// - Reloads chains and schemes from the config file on `SIGHUP`.
 ///
 /// Binds to the address specified by the `HOST` and `PORT` env vars.
-pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
-    rustls::crypto::CryptoProvider::install_default(rustls::crypto::ring::default_provider())
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("failed to initialize rustls crypto provider: {e:?}")))?;
-
-    // Load .env variables
-    dotenv().ok();
+pub async fn run(cli_args: CliArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if cli_args.config_schema {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&validate::config_schema())?
+        );
+        return Ok(());
+    }
+    let config_path = cli_args.canonical_config_path()?;
+    if cli_args.validate_config {
+        return check_config(&config_path);
+    }
+    let config = Config::load_from_path(config_path.clone())?;
 
     #[cfg(feature = "telemetry")]
     let telemetry_layer = {
+        let (schemes, networks) = telemetry_resource_attributes(&config);
         let telemetry = Telemetry::new()
             .with_name(env!("CARGO_PKG_NAME"))
             .with_version(env!("CARGO_PKG_VERSION"))
+            .with_attribute("x402.schemes", schemes)
+            .with_attribute("x402.networks", networks)
             .register();
         telemetry.http_tracing()
     };
+    let amount_format = Arc::new(ResponseAmountFormat::new(config.amount_format()));
+    let event_bus = load_event_bus()?.map(Arc::new);
+    let mut compliance_gate = load_compliance_gate()?;
+    if let Some(event_bus) = &event_bus {
+        compliance_gate = compliance_gate.with_audit_sink(event_bus.clone());
+    }
+    let geo_blocker = load_geo_blocker(&compliance_gate)?.map(Arc::new);
+    let rate_limiter = load_rate_limiter()?.map(Arc::new);
+    let api_key_auth = load_api_key_auth(&config)?.map(Arc::new);
+    let dead_letters = load_dead_letter_queue()?.map(Arc::new);
+    let in_flight = Arc::new(load_in_flight_settlements()?);
+    let notifications = load_notifications(&config)?.map(Arc::new);
+    let outbox = load_outbox()?.map(Arc::new);
+    let payload_log = load_payload_log()?.map(Arc::new);
+    let velocity = load_velocity_limiter()?.map(Arc::new);
+    let guardrail = load_guardrail()?.map(Arc::new);
+    let discovery = load_discovery_catalog()?.map(Arc::new);
+    #[cfg(feature = "storage")]
+    let ledger = load_settlement_ledger()?.map(Arc::new);
+    let cluster = Arc::new(load_cluster()?);
 
-    let config = Config::load()?;
-    let compliance_gate = load_compliance_gate()?;
-
-    let chain_registry = ChainRegistry::from_config(config.chains()).await?;
-    let scheme_blueprints = {
-        #[allow(unused_mut)] // For when no chain features are enabled
-        let mut scheme_blueprints = SchemeBlueprints::new();
-        #[cfg(feature = "chain-eip155")]
-        {
-            scheme_blueprints.register(V1Eip155Exact);
-            scheme_blueprints.register(V2Eip155Exact);
-        }
-        scheme_blueprints
-    };
-    let scheme_registry =
-        SchemeRegistry::build(chain_registry, scheme_blueprints, config.schemes());
+    let (chain_registry, scheme_registry) = build_registries(&config).await?;
+    let self_test = run_self_test(&chain_registry).await?;
 
-    let facilitator = FacilitatorLocal::new_with_compliance(scheme_registry, compliance_gate);
-    let axum_state = std::sync::Arc::new(facilitator);
+    let mut facilitator =
+        FacilitatorLocal::new_with_compliance(scheme_registry, compliance_gate.clone());
+    if let Some(dead_letters) = &dead_letters {
+        #[cfg(feature = "telemetry")]
+        dead_letters.register_metrics();
+        facilitator = facilitator.with_dead_letter_queue(dead_letters.clone());
+    }
+    facilitator = facilitator.with_in_flight_settlements(in_flight.clone());
+    if let Some(notifications) = &notifications {
+        facilitator = facilitator.with_notifications(notifications.clone());
+    }
+    if let Some(outbox) = &outbox {
+        facilitator = facilitator.with_outbox(outbox.clone());
+    }
+    if let Some(event_bus) = event_bus {
+        facilitator = facilitator.with_event_bus(event_bus);
+    }
+    if let Some(payload_log) = payload_log {
+        facilitator = facilitator.with_payload_log(payload_log);
+    }
+    if let Some(velocity) = velocity {
+        facilitator = facilitator.with_velocity_limits(velocity);
+    }
+    if let Some(guardrail) = &guardrail {
+        facilitator = facilitator.with_guardrail(guardrail.clone());
+    }
+    if v1_upconversion_enabled() {
+        facilitator = facilitator.with_v1_upconversion();
+    }
+    #[cfg(feature = "storage")]
+    if let Some(ledger) = &ledger {
+        facilitator = facilitator.with_settlement_ledger(ledger.clone());
+    }
+    let payment_events = Arc::new(PaymentEvents::default());
+    facilitator = facilitator.with_payment_events(payment_events.clone());
+    let _recovered = facilitator.recover_interrupted_settlements();
+    #[cfg(feature = "telemetry")]
+    if _recovered > 0 {
+        tracing::warn!(
+            count = _recovered,
+            "Settlements interrupted by the last shutdown moved to the dead-letter queue"
+        );
+    }
+    let axum_state = Arc::new(facilitator);
+    let signer_health = Arc::new(SignerHealth::new(chain_registry));
+    let running_config = Arc::new(RunningConfig::new(&config));
+    let mut readiness = Readiness::new(axum_state.clone(), signer_health.clone(), compliance_gate);
+    if let Some(self_test) = self_test {
+        readiness = readiness.with_self_test(self_test);
+    }
+    let readiness = Arc::new(readiness);
+    let sig_down = SigDown::try_new()?;
+    let scheduler = Arc::new(Scheduler::new(sig_down.cancellation_token()));
 
+    let mut facilitator_routes = EmbeddedFacilitator::new(axum_state.clone());
+    if let Some(rate_limiter) = &rate_limiter {
+        facilitator_routes = facilitator_routes.with_rate_limiter(rate_limiter.clone());
+    }
+    if let Some(api_key_auth) = &api_key_auth {
+        facilitator_routes = facilitator_routes.with_api_key_auth(api_key_auth.clone());
+    }
+    if let Some(geo_blocker) = geo_blocker {
+        facilitator_routes = facilitator_routes.with_geo_blocker(geo_blocker);
+    }
     let mut http_endpoints = Router::new()
-        .merge(handlers::routes().with_state(axum_state.clone()))
-        .merge(handlers::compliance_routes().with_state(axum_state.clone()));
+        .merge(facilitator_routes.router())
+        .merge(signers::routes().with_state(signer_health.clone()))
+        .merge(settlement_history_routes(&api_key_auth).with_state(signer_health.clone()))
+        .merge(permit2_nonce_routes(&api_key_auth).with_state(signer_health.clone()))
+        .merge(signature_routes(&api_key_auth).with_state(signer_health.clone()))
+        .merge(payment_event_routes(&api_key_auth).with_state(payment_events.clone()))
+        .merge(subscription_routes(&api_key_auth).with_state(axum_state.clone()))
+        .merge(handlers::scheduler_routes().with_state(scheduler.clone()))
+        .merge(handlers::cluster_routes().with_state(cluster.clone()))
+        .merge(readiness::routes().with_state(readiness.clone()));
+    if let Some(discovery) = discovery {
+        let discovery_routes = match &api_key_auth {
+            Some(api_key_auth) => {
+                handlers::authenticated_routes(handlers::discovery_routes(), api_key_auth.clone())
+            }
+            None => handlers::discovery_routes(),
+        };
+        http_endpoints = http_endpoints.merge(discovery_routes.with_state(discovery));
+    }
+    if debug_endpoints_enabled() {
+        let debug_routes = match &api_key_auth {
+            Some(api_key_auth) => {
+                handlers::authenticated_routes(handlers::debug_routes(), api_key_auth.clone())
+            }
+            None => handlers::debug_routes(),
+        };
+        http_endpoints = http_endpoints.merge(debug_routes.with_state(axum_state.clone()));
+    }
+    // The admin API is only served behind API keys.
+    if let Some(api_key_auth) = &api_key_auth {
+        let signer_audit = Arc::new(SignerAudit {
+            facilitator: axum_state.clone(),
+            signer_health: signer_health.clone(),
+        });
+        let admin_routes =
+            handlers::authenticated_routes(signers::admin_routes(), api_key_auth.clone());
+        http_endpoints = http_endpoints.merge(admin_routes.with_state(signer_audit));
+        let domain_admin_routes =
+            handlers::authenticated_routes(domains::admin_routes(), api_key_auth.clone());
+        http_endpoints =
+            http_endpoints.merge(domain_admin_routes.with_state(signer_health.clone()));
+        let cluster_admin_routes =
+            handlers::authenticated_routes(handlers::cluster_admin_routes(), api_key_auth.clone());
+        http_endpoints = http_endpoints.merge(cluster_admin_routes.with_state(cluster.clone()));
+        let config_routes =
+            handlers::authenticated_routes(inspect::admin_routes(), api_key_auth.clone());
+        http_endpoints = http_endpoints.merge(config_routes.with_state(running_config.clone()));
+        let registry_routes =
+            handlers::authenticated_routes(handlers::registry_routes(), api_key_auth.clone());
+        http_endpoints = http_endpoints.merge(registry_routes.with_state(axum_state.clone()));
+    }
+    match (&api_key_auth, &dead_letters) {
+        (Some(api_key_auth), Some(_)) => {
+            let admin_routes = handlers::authenticated_routes(
+                handlers::dead_letter_routes(),
+                api_key_auth.clone(),
+            );
+            http_endpoints = http_endpoints.merge(admin_routes.with_state(axum_state.clone()));
+        }
+        (None, Some(_)) => {
+            #[cfg(feature = "telemetry")]
+            tracing::warn!(
+                "SETTLEMENT_DLQ_ENABLED without API keys, the /admin/dlq API is disabled"
+            );
+        }
+        _ => {}
+    }
+    match (&api_key_auth, &guardrail) {
+        (Some(api_key_auth), Some(_)) => {
+            let guardrail_routes =
+                handlers::authenticated_routes(handlers::guardrail_routes(), api_key_auth.clone());
+            http_endpoints = http_endpoints.merge(guardrail_routes.with_state(axum_state.clone()));
+        }
+        (None, Some(_)) => {
+            #[cfg(feature = "telemetry")]
+            tracing::warn!(
+                "GUARDRAIL_* without API keys, the /admin/resume API is disabled and a pause lasts until restart"
+            );
+        }
+        _ => {}
+    }
+    #[cfg(feature = "storage")]
+    match (&api_key_auth, ledger) {
+        (Some(api_key_auth), Some(ledger)) => {
+            let ledger_routes = handlers::authenticated_routes(
+                handlers::settlement_ledger_routes(),
+                api_key_auth.clone(),
+            );
+            http_endpoints = http_endpoints.merge(ledger_routes.with_state(ledger));
+            let refund_routes =
+                handlers::authenticated_routes(handlers::refund_routes(), api_key_auth.clone());
+            http_endpoints = http_endpoints.merge(refund_routes.with_state(axum_state.clone()));
+        }
+        (None, Some(_)) => {
+            #[cfg(feature = "telemetry")]
+            tracing::warn!(
+                "SETTLEMENT_LEDGER_ENABLED without API keys, the /settlements and /refund APIs are disabled"
+            );
+        }
+        _ => {}
+    }
+    #[cfg(feature = "openapi")]
+    {
+        http_endpoints = http_endpoints
+            .merge(SwaggerUi::new("/docs").url("/openapi.json", FacilitatorApi::openapi()));
+    }
+    http_endpoints = handlers::amount_formatted_routes(http_endpoints, amount_format.clone());
     #[cfg(feature = "telemetry")]
     {
         http_endpoints = http_endpoints.layer(telemetry_layer);
@@ -155,12 +922,105 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let listener = listener.inspect_err(|e| tracing::error!("Failed to bind to {}: {}", addr, e));
     let listener = listener?;
 
-    let sig_down = SigDown::try_new()?;
+    #[cfg(feature = "grpc")]
+    if let Some(grpc_port) = grpc_port()? {
+        let mut grpc = GrpcFacilitator::new(axum_state.clone()).with_payment_events(payment_events);
+        if let Some(api_key_auth) = &api_key_auth {
+            grpc = grpc.with_api_key_auth(api_key_auth.clone());
+        }
+        if let Some(rate_limiter) = &rate_limiter {
+            grpc = grpc.with_rate_limiter(rate_limiter.clone());
+        }
+        serve_grpc(&scheduler, grpc, SocketAddr::new(config.host(), grpc_port));
+    }
+    schedule_rpc_probes(&scheduler, signer_health.clone());
+    schedule_nonce_reconciliation(&scheduler, signer_health.clone());
+    let reloader = Arc::new(ConfigReloader::new(
+        axum_state.clone(),
+        signer_health,
+        running_config,
+        amount_format,
+        config_path,
+    ));
+    schedule_config_reload(&scheduler, reloader, config_watch_enabled())?;
+    schedule_lease_renewal(&scheduler, cluster);
+    // Every replica settles the deferred payments it accepted, as tabs are not shared.
+    scheduler.every(
+        "deferred-flush",
+        DEFERRED_FLUSH_INTERVAL,
+        Duration::from_secs(1),
+        move || {
+            let facilitator = axum_state.clone();
+            async move { facilitator.flush_deferred().await }
+        },
+    );
+    match (outbox, notifications) {
+        (Some(outbox), Some(notifications)) => {
+            scheduler.every(
+                "notification-outbox",
+                OUTBOX_INTERVAL,
+                Duration::from_secs(1),
+                move || {
+                    let outbox = outbox.clone();
+                    let notifications = notifications.clone();
+                    async move { outbox.deliver_due(&notifications).await }
+                },
+            );
+        }
+        (Some(_), None) => {
+            #[cfg(feature = "telemetry")]
+            tracing::warn!(
+                "NOTIFICATION_OUTBOX_ENABLED without notification rules, the outbox is unused"
+            );
+        }
+        _ => {}
+    }
+    // Warm up while already serving, so that `/health` answers and `/ready` reports progress.
+    let warm_up_cancellation_token = sig_down.cancellation_token();
+    tokio::spawn(async move {
+        while !readiness.warm_up().await {
+            tokio::select! {
+                _ = warm_up_cancellation_token.cancelled() => return,
+                _ = tokio::time::sleep(readiness::WARM_UP_RETRY) => {}
+            }
+        }
+        #[cfg(feature = "telemetry")]
+        tracing::info!("Warm-up complete, ready to take payments");
+    });
     let axum_cancellation_token = sig_down.cancellation_token();
     let axum_graceful_shutdown = async move { axum_cancellation_token.cancelled().await };
-    axum::serve(listener, http_endpoints)
-        .with_graceful_shutdown(axum_graceful_shutdown)
-        .await?;
+    let server = axum::serve(
+        listener,
+        http_endpoints.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(axum_graceful_shutdown)
+    .into_future();
+    tokio::pin!(server);
+    let server_stopped = tokio::select! {
+        result = &mut server => {
+            result?;
+            true
+        }
+        _ = sig_down.recv() => false,
+    };
+    drain_settlements(&in_flight, &sig_down).await;
+    // Settlements are done or journaled, everything else gets a last moment to finish.
+    if !server_stopped {
+        match tokio::time::timeout(SHUTDOWN_GRACE, server).await {
+            Ok(result) => result?,
+            Err(_) => {
+                #[cfg(feature = "telemetry")]
+                tracing::warn!("Requests still open at shutdown, closing them");
+            }
+        }
+    }
+    if tokio::time::timeout(SHUTDOWN_GRACE, scheduler.shutdown())
+        .await
+        .is_err()
+    {
+        #[cfg(feature = "telemetry")]
+        tracing::warn!("Background tasks still running at shutdown, stopping them");
+    }
 
     Ok(())
 }
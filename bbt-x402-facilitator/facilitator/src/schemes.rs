@@ -11,6 +11,14 @@
 //! |--------|--------|-------------|
 //! | [`V1Eip155Exact`] | EIP-155 (EVM) | V1 protocol with exact amount on EVM |
 //! | [`V2Eip155Exact`] | EIP-155 (EVM) | V2 protocol with exact amount on EVM |
+//! | [`V2Eip155Native`] | EIP-155 (EVM) | V2 protocol with native coin payments through an escrow |
+//! | [`V2Eip155Recurring`] | EIP-155 (EVM) | V2 protocol with subscriptions pulled from a Permit2 allowance |
+//! | [`V2Eip155Upto`] | EIP-155 (EVM) | V2 protocol with metered amounts settled up to a Permit2 allowance |
+//! | [`V2Eip155Deferred`] | EIP-155 (EVM) | V2 protocol with payments accrued on a Permit2 allowance and settled in batches |
+//! | [`V2Eip155Allowance`] | EIP-155 (EVM) | V2 protocol with signed intents pulled from an ERC-20 allowance with `transferFrom` |
+//!
+//! With the `dev-mode` feature, every scheme built for a mock chain is served by the
+//! chain's mock handler instead, see [`crate::mock`].
 //!
 //! # Example
 //!
@@ -29,11 +37,34 @@
 use crate::chain::ChainProvider;
 #[allow(unused_imports)] // For when no chain features are enabled
 use std::sync::Arc;
+use x402_types::scheme::SchemeBlueprints;
+#[cfg(all(feature = "chain-eip155", feature = "dev-mode"))]
+use x402_types::scheme::X402SchemeId;
 #[allow(unused_imports)] // For when no chain features are enabled
 use x402_types::scheme::{X402SchemeFacilitator, X402SchemeFacilitatorBuilder};
 
 #[cfg(feature = "chain-eip155")]
-use x402_chain_eip155::{V1Eip155Exact, V2Eip155Exact};
+use x402_chain_eip155::{
+    V1Eip155Exact, V2Eip155Allowance, V2Eip155Deferred, V2Eip155Exact, V2Eip155Native,
+    V2Eip155Recurring, V2Eip155Upto,
+};
+/// Returns the blueprints of all supported schemes, by scheme id.
+pub fn scheme_blueprints() -> SchemeBlueprints<ChainProvider> {
+    #[allow(unused_mut)] // For when no chain features are enabled
+    let mut scheme_blueprints = SchemeBlueprints::new();
+    #[cfg(feature = "chain-eip155")]
+    {
+        scheme_blueprints.register(V1Eip155Exact);
+        scheme_blueprints.register(V2Eip155Exact);
+        scheme_blueprints.register(V2Eip155Native);
+        scheme_blueprints.register(V2Eip155Recurring);
+        scheme_blueprints.register(V2Eip155Upto);
+        scheme_blueprints.register(V2Eip155Deferred);
+        scheme_blueprints.register(V2Eip155Allowance);
+    }
+    scheme_blueprints
+}
+
 #[cfg(feature = "chain-eip155")]
 impl X402SchemeFacilitatorBuilder<&ChainProvider> for V2Eip155Exact {
     fn build(
@@ -41,6 +72,10 @@ impl X402SchemeFacilitatorBuilder<&ChainProvider> for V2Eip155Exact {
         provider: &ChainProvider,
         config: Option<serde_json::Value>,
     ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
+        #[cfg(feature = "dev-mode")]
+        if let ChainProvider::Mock(provider) = provider {
+            return Ok(provider.facilitator(self.x402_version(), self.scheme()));
+        }
         #[allow(irrefutable_let_patterns)] // For when just chain-eip155 is enabled
         let eip155_provider = if let ChainProvider::Eip155(provider) = provider {
             Arc::clone(provider)
@@ -58,6 +93,10 @@ impl X402SchemeFacilitatorBuilder<&ChainProvider> for V1Eip155Exact {
         provider: &ChainProvider,
         config: Option<serde_json::Value>,
     ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
+        #[cfg(feature = "dev-mode")]
+        if let ChainProvider::Mock(provider) = provider {
+            return Ok(provider.facilitator(self.x402_version(), self.scheme()));
+        }
         #[allow(irrefutable_let_patterns)] // For when just chain-eip155 is enabled
         let eip155_provider = if let ChainProvider::Eip155(provider) = provider {
             Arc::clone(provider)
@@ -67,3 +106,108 @@ impl X402SchemeFacilitatorBuilder<&ChainProvider> for V1Eip155Exact {
         self.build(eip155_provider, config)
     }
 }
+
+#[cfg(feature = "chain-eip155")]
+impl X402SchemeFacilitatorBuilder<&ChainProvider> for V2Eip155Native {
+    fn build(
+        &self,
+        provider: &ChainProvider,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
+        #[cfg(feature = "dev-mode")]
+        if let ChainProvider::Mock(provider) = provider {
+            return Ok(provider.facilitator(self.x402_version(), self.scheme()));
+        }
+        #[allow(irrefutable_let_patterns)] // For when just chain-eip155 is enabled
+        let eip155_provider = if let ChainProvider::Eip155(provider) = provider {
+            Arc::clone(provider)
+        } else {
+            return Err("V2Eip155Native::build: provider must be an Eip155ChainProvider".into());
+        };
+        self.build(eip155_provider, config)
+    }
+}
+
+#[cfg(feature = "chain-eip155")]
+impl X402SchemeFacilitatorBuilder<&ChainProvider> for V2Eip155Recurring {
+    fn build(
+        &self,
+        provider: &ChainProvider,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
+        #[cfg(feature = "dev-mode")]
+        if let ChainProvider::Mock(provider) = provider {
+            return Ok(provider.facilitator(self.x402_version(), self.scheme()));
+        }
+        #[allow(irrefutable_let_patterns)] // For when just chain-eip155 is enabled
+        let eip155_provider = if let ChainProvider::Eip155(provider) = provider {
+            Arc::clone(provider)
+        } else {
+            return Err("V2Eip155Recurring::build: provider must be an Eip155ChainProvider".into());
+        };
+        self.build(eip155_provider, config)
+    }
+}
+
+#[cfg(feature = "chain-eip155")]
+impl X402SchemeFacilitatorBuilder<&ChainProvider> for V2Eip155Upto {
+    fn build(
+        &self,
+        provider: &ChainProvider,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
+        #[cfg(feature = "dev-mode")]
+        if let ChainProvider::Mock(provider) = provider {
+            return Ok(provider.facilitator(self.x402_version(), self.scheme()));
+        }
+        #[allow(irrefutable_let_patterns)] // For when just chain-eip155 is enabled
+        let eip155_provider = if let ChainProvider::Eip155(provider) = provider {
+            Arc::clone(provider)
+        } else {
+            return Err("V2Eip155Upto::build: provider must be an Eip155ChainProvider".into());
+        };
+        self.build(eip155_provider, config)
+    }
+}
+
+#[cfg(feature = "chain-eip155")]
+impl X402SchemeFacilitatorBuilder<&ChainProvider> for V2Eip155Deferred {
+    fn build(
+        &self,
+        provider: &ChainProvider,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
+        #[cfg(feature = "dev-mode")]
+        if let ChainProvider::Mock(provider) = provider {
+            return Ok(provider.facilitator(self.x402_version(), self.scheme()));
+        }
+        #[allow(irrefutable_let_patterns)] // For when just chain-eip155 is enabled
+        let eip155_provider = if let ChainProvider::Eip155(provider) = provider {
+            Arc::clone(provider)
+        } else {
+            return Err("V2Eip155Deferred::build: provider must be an Eip155ChainProvider".into());
+        };
+        self.build(eip155_provider, config)
+    }
+}
+
+#[cfg(feature = "chain-eip155")]
+impl X402SchemeFacilitatorBuilder<&ChainProvider> for V2Eip155Allowance {
+    fn build(
+        &self,
+        provider: &ChainProvider,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
+        #[cfg(feature = "dev-mode")]
+        if let ChainProvider::Mock(provider) = provider {
+            return Ok(provider.facilitator(self.x402_version(), self.scheme()));
+        }
+        #[allow(irrefutable_let_patterns)] // For when just chain-eip155 is enabled
+        let eip155_provider = if let ChainProvider::Eip155(provider) = provider {
+            Arc::clone(provider)
+        } else {
+            return Err("V2Eip155Allowance::build: provider must be an Eip155ChainProvider".into());
+        };
+        self.build(eip155_provider, config)
+    }
+}
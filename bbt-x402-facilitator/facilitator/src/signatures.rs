@@ -0,0 +1,78 @@
+//! `POST /validate-signature`: checks a signature without payment requirements.
+//!
+//! Meant for wallet developers debugging smart-account signatures: only the
+//! signature path of payment verification runs (EOA recovery, ERC-1271 or
+//! EIP-6492), against the contracts of the chain named by `network`:
+//!
+//! ```json
+//! {
+//!   "network": "eip155:42793",
+//!   "token": "0x...",
+//!   "typedData": { "domain": {}, "types": {}, "primaryType": "...", "message": {} },
+//!   "signature": "0x...",
+//!   "signer": "0x..."
+//! }
+//! ```
+//!
+//! `signer` may be left out for EOA signatures; the answer holds the recovered or
+//! validated signer, or the reason the signature is not valid.
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::json;
+use x402_types::chain::ChainId;
+
+use crate::chain::ChainQueryError;
+use crate::signers::SignerHealth;
+
+/// Routes serving signature checks against the configured chains.
+pub fn routes() -> Router<Arc<SignerHealth>> {
+    Router::new().route("/validate-signature", post(post_validate_signature))
+}
+
+fn lookup_error(status: StatusCode, error: &'static str, details: String) -> Response {
+    (status, Json(json!({ "error": error, "details": details }))).into_response()
+}
+
+/// `POST /validate-signature`: reports whether the signature is valid and whose it is.
+async fn post_validate_signature(
+    State(signer_health): State<Arc<SignerHealth>>,
+    Json(query): Json<serde_json::Value>,
+) -> Response {
+    let network = match query
+        .get("network")
+        .cloned()
+        .map(serde_json::from_value::<ChainId>)
+    {
+        Some(Ok(network)) => network,
+        _ => {
+            return lookup_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_request",
+                "missing or invalid network".to_string(),
+            );
+        }
+    };
+    let chains = signer_health.chains();
+    let Some(provider) = chains.by_chain_id(network.clone()) else {
+        return lookup_error(
+            StatusCode::NOT_FOUND,
+            "unsupported_network",
+            format!("network {network} is not configured"),
+        );
+    };
+    match provider.validate_signature(query).await {
+        Ok(validation) => Json(validation).into_response(),
+        Err(e @ ChainQueryError::InvalidQuery(_)) => {
+            lookup_error(StatusCode::BAD_REQUEST, "invalid_request", e.to_string())
+        }
+        Err(e @ ChainQueryError::Rpc(_)) => {
+            lookup_error(StatusCode::BAD_GATEWAY, "rpc_error", e.to_string())
+        }
+    }
+}
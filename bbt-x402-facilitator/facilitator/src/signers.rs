@@ -0,0 +1,329 @@
+//! `GET /health/signers`: on-chain readiness of the facilitator signers.
+//!
+//! For every configured chain, reports each signer's native balance, how many of its
+//! transactions are still pending, and whether the balance is under the chain's
+//! `low_balance_threshold`, along with the circuit state of each RPC endpoint
+//! (see `x402_chain_eip155::chain::rpc_failover`). The endpoint responds with
+//! `503 Service Unavailable` when a chain cannot be queried, all of its RPC
+//! endpoints are failing, or a signer is low on funds, so it can back an alert
+//! before settlements start failing.
+//!
+//! `GET /admin/signers` checks that the signers advertised on `/supported`, which
+//! payers may name as spenders, are the ones settlements are sent from, and that
+//! each of them is funded. It is served behind an admin API key.
+//!
+//! `POST /admin/signers/{chain_id}/{address}/cancel-pending` replaces the pending
+//! transactions of a signer with self-transfers, to clear nonces reported as stuck
+//! on `/health/tasks` (see the `nonce-reconcile` task). It is served behind an
+//! admin API key as well.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, RwLock};
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Serialize;
+use serde_json::json;
+use x402_facilitator_local::FacilitatorLocal;
+use x402_types::chain::{ChainId, ChainProviderOps, ChainRegistry};
+use x402_types::facilitator::Facilitator;
+use x402_types::scheme::SchemeRegistry;
+
+use crate::chain::{ChainProvider, ChainSignerHealth, SignerRepairError};
+
+/// Signer readiness across all configured chains.
+#[derive(Debug, Clone, Serialize)]
+pub struct SignerHealthReport {
+    /// `true` if every chain is healthy.
+    pub healthy: bool,
+    /// Per-chain status, sorted by chain id.
+    pub chains: Vec<ChainSignerHealth>,
+}
+
+/// Shared state of the `/health/signers` endpoint.
+///
+/// Holds the chain registry the facilitator currently settles with; it is swapped
+/// on config reload together with the scheme handlers.
+pub struct SignerHealth {
+    chains: RwLock<Arc<ChainRegistry<ChainProvider>>>,
+}
+
+impl SignerHealth {
+    pub fn new(chains: ChainRegistry<ChainProvider>) -> Self {
+        Self {
+            chains: RwLock::new(Arc::new(chains)),
+        }
+    }
+
+    /// Replaces the chains being reported on.
+    pub fn replace_chains(&self, chains: ChainRegistry<ChainProvider>) {
+        *self.chains.write().expect("signer health lock poisoned") = Arc::new(chains);
+    }
+
+    /// The chains the facilitator currently settles with.
+    pub fn chains(&self) -> Arc<ChainRegistry<ChainProvider>> {
+        self.chains
+            .read()
+            .expect("signer health lock poisoned")
+            .clone()
+    }
+
+    /// Queries every chain for the status of its signers.
+    pub async fn report(&self) -> SignerHealthReport {
+        let chains = self.chains();
+        let mut providers: Vec<_> = chains.iter().collect();
+        providers.sort_by_key(|(chain_id, _)| chain_id.to_string());
+        let mut reports = Vec::with_capacity(providers.len());
+        for (_, provider) in providers {
+            reports.push(provider.signer_health().await);
+        }
+        SignerHealthReport {
+            healthy: reports.iter().all(|report| report.healthy),
+            chains: reports,
+        }
+    }
+}
+
+/// Routes serving the signer health report.
+pub fn routes() -> Router<Arc<SignerHealth>> {
+    Router::new().route("/health/signers", get(get_signer_health))
+}
+
+/// Signer set of one chain as advertised, as used for settlement, and where they differ.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainSignerDrift {
+    pub chain_id: ChainId,
+    /// `true` if both sets are equal and every signer is funded.
+    pub consistent: bool,
+    /// Signers listed on `/supported`.
+    pub advertised: Vec<String>,
+    /// Signers settlements are sent from.
+    pub settlement: Vec<String>,
+    /// Advertised signers that never send settlements.
+    pub not_settling: Vec<String>,
+    /// Settlement signers payers cannot name as spender.
+    pub not_advertised: Vec<String>,
+    /// Settlement signers without native balance or below the low-balance threshold.
+    pub unfunded: Vec<String>,
+    /// Why the balances could not be queried.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ChainSignerDrift {
+    fn new(
+        chain_id: ChainId,
+        advertised: Vec<String>,
+        settlement: Vec<String>,
+        health: Option<ChainSignerHealth>,
+    ) -> Self {
+        let normalized = |addresses: &[String]| -> BTreeSet<String> {
+            addresses.iter().map(|a| a.to_ascii_lowercase()).collect()
+        };
+        let advertised_set = normalized(&advertised);
+        let settlement_set = normalized(&settlement);
+        let not_settling: Vec<String> = advertised
+            .iter()
+            .filter(|a| !settlement_set.contains(&a.to_ascii_lowercase()))
+            .cloned()
+            .collect();
+        let not_advertised: Vec<String> = settlement
+            .iter()
+            .filter(|a| !advertised_set.contains(&a.to_ascii_lowercase()))
+            .cloned()
+            .collect();
+        let (unfunded, error) = match health {
+            Some(health) => (health.unfunded, health.error),
+            None => (Vec::new(), None),
+        };
+        Self {
+            chain_id,
+            consistent: not_settling.is_empty()
+                && not_advertised.is_empty()
+                && unfunded.is_empty()
+                && error.is_none(),
+            advertised,
+            settlement,
+            not_settling,
+            not_advertised,
+            unfunded,
+            error,
+        }
+    }
+}
+
+/// Signer drift across all chains.
+#[derive(Debug, Clone, Serialize)]
+pub struct SignerDriftReport {
+    /// `true` if every chain is consistent.
+    pub consistent: bool,
+    /// Per-chain drift, sorted by chain id.
+    pub chains: Vec<ChainSignerDrift>,
+}
+
+/// Shared state of the `/admin/signers` endpoint.
+pub struct SignerAudit {
+    pub facilitator: Arc<FacilitatorLocal<SchemeRegistry>>,
+    pub signer_health: Arc<SignerHealth>,
+}
+
+impl SignerAudit {
+    /// Compares the signers advertised on `/supported` with the settlement signers.
+    pub async fn report(&self) -> SignerDriftReport {
+        let mut advertised = match self.facilitator.supported().await {
+            Ok(supported) => supported.signers,
+            Err(_) => HashMap::new(),
+        };
+        let chains = self.signer_health.chains();
+        let mut chain_ids: Vec<ChainId> = advertised.keys().cloned().collect();
+        for (chain_id, _) in chains.iter() {
+            if !advertised.contains_key(chain_id) {
+                chain_ids.push(chain_id.clone());
+            }
+        }
+        chain_ids.sort_by_key(|chain_id| chain_id.to_string());
+        let mut reports = Vec::with_capacity(chain_ids.len());
+        for chain_id in chain_ids {
+            let (settlement, health) = match chains.by_chain_id(chain_id.clone()) {
+                Some(provider) => (
+                    provider.signer_addresses(),
+                    Some(provider.signer_health().await),
+                ),
+                None => (Vec::new(), None),
+            };
+            let advertised = advertised.remove(&chain_id).unwrap_or_default();
+            reports.push(ChainSignerDrift::new(
+                chain_id, advertised, settlement, health,
+            ));
+        }
+        SignerDriftReport {
+            consistent: reports.iter().all(|report| report.consistent),
+            chains: reports,
+        }
+    }
+}
+
+/// Replacement transactions sent for the pending transactions of a signer.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelledTransactions {
+    pub chain_id: ChainId,
+    pub signer: String,
+    /// Hashes of the self-transfers, one per pending nonce.
+    pub transactions: Vec<String>,
+}
+
+/// Admin routes flagging signer drift and clearing stuck nonces. Guard them with
+/// API key authentication.
+pub fn admin_routes() -> Router<Arc<SignerAudit>> {
+    Router::new()
+        .route("/admin/signers", get(get_signer_drift))
+        .route(
+            "/admin/signers/{chain_id}/{address}/cancel-pending",
+            post(post_cancel_pending),
+        )
+}
+
+/// `GET /health/signers`: reports signer balances and pending transactions per chain.
+async fn get_signer_health(State(signer_health): State<Arc<SignerHealth>>) -> impl IntoResponse {
+    let report = signer_health.report().await;
+    let status = if report.healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+/// `GET /admin/signers`: reports signers that are not advertised, not settling or not funded.
+async fn get_signer_drift(State(audit): State<Arc<SignerAudit>>) -> impl IntoResponse {
+    let report = audit.report().await;
+    let status = if report.consistent {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+/// `POST /admin/signers/{chain_id}/{address}/cancel-pending`: replaces the pending
+/// transactions of a signer with self-transfers.
+///
+/// Responds with `404 Not Found` if the chain or the signer is not configured, and
+/// with `502 Bad Gateway` if the replacements could not be sent.
+async fn post_cancel_pending(
+    State(audit): State<Arc<SignerAudit>>,
+    Path((chain_id, address)): Path<(String, String)>,
+) -> Response {
+    let not_found = |details: String| {
+        let body = json!({ "error": "not_found", "details": details });
+        (StatusCode::NOT_FOUND, Json(body)).into_response()
+    };
+    let Ok(chain_id) = chain_id.parse::<ChainId>() else {
+        return not_found(format!("unknown chain: {chain_id}"));
+    };
+    let chains = audit.signer_health.chains();
+    let Some(provider) = chains.by_chain_id(chain_id.clone()) else {
+        return not_found(format!("unknown chain: {chain_id}"));
+    };
+    match provider.cancel_pending(&address).await {
+        Ok(transactions) => Json(CancelledTransactions {
+            chain_id,
+            signer: address,
+            transactions,
+        })
+        .into_response(),
+        Err(error @ SignerRepairError::UnknownSigner(_)) => not_found(error.to_string()),
+        Err(error @ SignerRepairError::Rpc(_)) => (
+            StatusCode::BAD_GATEWAY,
+            Json(json!({ "error": "rpc_error", "details": error.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signer_drift() {
+        let chain_id: ChainId = "eip155:42793".parse().unwrap();
+        let a = "0xAAA0000000000000000000000000000000000001".to_string();
+        let b = "0xBBB0000000000000000000000000000000000002".to_string();
+        let c = "0xCCC0000000000000000000000000000000000003".to_string();
+
+        let same = ChainSignerDrift::new(
+            chain_id.clone(),
+            vec![a.clone(), b.clone()],
+            vec![b.to_lowercase(), a.clone()],
+            None,
+        );
+        assert!(same.consistent);
+
+        let health = ChainSignerHealth {
+            chain_id: chain_id.clone(),
+            healthy: true,
+            low_balance_threshold: None,
+            signers: Vec::new(),
+            unfunded: vec![c.clone()],
+            rpc: Vec::new(),
+            settlement_slots: None,
+            error: None,
+        };
+        let drift = ChainSignerDrift::new(
+            chain_id,
+            vec![a.clone(), b.clone()],
+            vec![a.clone(), c.clone()],
+            Some(health),
+        );
+        assert!(!drift.consistent);
+        assert_eq!(drift.not_settling, vec![b]);
+        assert_eq!(drift.not_advertised, vec![c.clone()]);
+        assert_eq!(drift.unfunded, vec![c]);
+    }
+}
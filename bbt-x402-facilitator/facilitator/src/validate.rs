@@ -0,0 +1,176 @@
+//! Checking a configuration file without starting the facilitator.
+//!
+//! `x402-facilitator --validate-config` loads the file given with `--config` (or
+//! `CONFIG`), prints every problem found with the path to it, and exits with status 1
+//! if there is any:
+//!
+//! ```text
+//! config.json is invalid:
+//!   schemes[1].id: unknown scheme v2-eip155-exakt
+//!   chains.eip155:42793.rpc: at least one RPC endpoint should be provided
+//! ```
+//!
+//! A value that does not match the format stops parsing, so it is the only problem
+//! reported, e.g. ``Invalid config at `chains.eip155:42793.rpc[0].http`: relative URL
+//! without a base``. Environment variable references are resolved, so the check should
+//! run with the environment the facilitator runs with.
+//!
+//! A file that parses is checked for what would fail startup or be ignored: unknown
+//! scheme ids, scheme chain patterns matching no configured chain, chains missing
+//! signers or RPC endpoints, a `refund_treasury` that is not a signer, and malformed
+//! `api_keys`, `notifications` and `networks`. Chains are not connected to, and the
+//! `config` objects of schemes are only checked at startup.
+//!
+//! `x402-facilitator --config-schema` prints the JSON Schema of the configuration
+//! file, for editors and CI pipelines.
+
+use std::fmt;
+use std::path::Path;
+
+use schemars::Schema;
+use x402_facilitator_local::{ApiKeyAuth, NotificationDispatcher};
+use x402_types::config::ConfigError;
+
+#[cfg(feature = "chain-eip155")]
+use crate::config::ChainConfig;
+use crate::config::Config;
+use crate::schemes;
+
+/// A problem found in a configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    /// Path to the offending value, e.g. `schemes[0].chains`.
+    pub path: String,
+    pub message: String,
+}
+
+impl ConfigProblem {
+    fn new(path: impl Into<String>, message: impl fmt::Display) -> Self {
+        Self {
+            path: path.into(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// The JSON Schema of the configuration file.
+pub fn config_schema() -> Schema {
+    schemars::schema_for!(Config)
+}
+
+/// Checks the configuration file at `path`, see the [module documentation](self).
+///
+/// Returns the problems found, none for a valid file. Fails if the file cannot be
+/// read or is not JSON.
+pub fn validate_config_file(path: &Path) -> Result<Vec<ConfigProblem>, ConfigError> {
+    match Config::load_from_path(path.to_path_buf()) {
+        Ok(config) => Ok(config_problems(&config)),
+        Err(ConfigError::Invalid { path, source }) => Ok(vec![ConfigProblem::new(path, source)]),
+        Err(e) => Err(e),
+    }
+}
+
+/// Problems of a configuration that parsed.
+pub fn config_problems(config: &Config) -> Vec<ConfigProblem> {
+    let mut problems = Vec::new();
+
+    let blueprints = schemes::scheme_blueprints();
+    for (index, scheme) in config.schemes().iter().enumerate() {
+        if !scheme.enabled {
+            continue;
+        }
+        if blueprints.get(&scheme.id).is_none() {
+            problems.push(ConfigProblem::new(
+                format!("schemes[{index}].id"),
+                format!("unknown scheme {}", scheme.id),
+            ));
+        }
+        let matched = config
+            .chains()
+            .iter()
+            .any(|chain| scheme.chains.matches(&chain.chain_id()));
+        if !matched {
+            problems.push(ConfigProblem::new(
+                format!("schemes[{index}].chains"),
+                format!("{} matches no chain in chains", scheme.chains),
+            ));
+        }
+    }
+
+    #[cfg(feature = "chain-eip155")]
+    for chain in config.chains().iter() {
+        #[allow(irrefutable_let_patterns)] // For when just chain-eip155 is enabled
+        if let ChainConfig::Eip155(config) = chain {
+            for (field, message) in config.inner.problems() {
+                problems.push(ConfigProblem::new(
+                    format!("chains.{}.{field}", config.chain_id()),
+                    message,
+                ));
+            }
+        }
+    }
+
+    if let Err(e) = ApiKeyAuth::from_config_and_env(config.api_keys()) {
+        problems.push(ConfigProblem::new("api_keys", e));
+    }
+    if let Err(e) = NotificationDispatcher::from_config(config.notifications()) {
+        problems.push(ConfigProblem::new("notifications", e));
+    }
+    if let Err(e) = config.register_networks() {
+        problems.push(ConfigProblem::new("networks", e));
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_problems() {
+        let config = Config::from_json(
+            r#"{
+                "chains": {
+                    "eip155:42793": {
+                        "signers": [],
+                        "rpc": [{ "http": "https://node.mainnet.etherlink.com" }]
+                    }
+                },
+                "schemes": [
+                    { "id": "v2-eip155-exakt", "chains": "eip155:42793" },
+                    { "id": "v2-eip155-exact", "chains": "eip155:1" }
+                ],
+                "notifications": { "rules": [{ "channel": "ops" }] }
+            }"#,
+        )
+        .unwrap();
+        let problems: Vec<String> = config_problems(&config)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            problems,
+            [
+                "schemes[0].id: unknown scheme v2-eip155-exakt",
+                "schemes[1].chains: eip155:1 matches no chain in chains",
+                "chains.eip155:42793.signers: at least one signer should be provided",
+                "notifications: notification rule references unknown channel ops",
+            ]
+        );
+
+        let error = Config::from_json(
+            r#"{ "chains": { "eip155:42793": { "signers": [], "rpc": [{ "http": "node" }] } } }"#,
+        )
+        .unwrap_err()
+        .to_string();
+        // With the dev-mode feature, the path within the chain is part of the message.
+        assert!(error.contains("eip155:42793"), "{error}");
+        assert!(error.contains("rpc[0].http"), "{error}");
+    }
+}
@@ -0,0 +1,61 @@
+//! Load test harness for x402 facilitators.
+//!
+//! Sends signed synthetic payments to a facilitator's `/verify` or `/settle`
+//! endpoint at a fixed rate, and reports latency percentiles and a breakdown of
+//! the errors, for capacity planning before a deployment takes real traffic.
+//!
+//! # Usage
+//!
+//! ```bash
+//! # Serve a mock chain, and point the facilitator's chain `rpc` at it
+//! cargo run -p x402-loadtest --release -- mock-chain --listen 127.0.0.1:8545
+//!
+//! # 200 verifications per second, at most 64 in flight, for a minute
+//! cargo run -p x402-loadtest --release -- run --rps 200 --concurrency 64 --duration 60
+//!
+//! # Settlements on a devnet, paid by funded accounts
+//! cargo run -p x402-loadtest --release -- run --endpoint settle --payer-keys 0x...,0x...
+//! ```
+//!
+//! The harness is organized into modules:
+//! - [`mock_chain`] - JSON-RPC endpoint standing in for an EVM node
+//! - [`payments`] - signed synthetic payment requests
+//! - [`report`] - latency percentiles and error breakdown
+//! - [`run`] - the request loop
+
+mod mock_chain;
+mod payments;
+mod report;
+mod run;
+
+use std::process;
+
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(name = "loadtest", about = "Load test harness for x402 facilitators")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Send synthetic payments to a facilitator and report latencies.
+    Run(run::RunArgs),
+    /// Serve a mock EVM JSON-RPC endpoint for the facilitator to use as its chain.
+    MockChain(mock_chain::MockChainArgs),
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Run(args) => run::run(args).await,
+        Command::MockChain(args) => mock_chain::serve(args).await,
+    };
+    if let Err(e) = result {
+        eprintln!("{e}");
+        process::exit(1)
+    }
+}
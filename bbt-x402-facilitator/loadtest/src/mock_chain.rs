@@ -0,0 +1,248 @@
+//! JSON-RPC endpoint standing in for an EVM node.
+//!
+//! Answers just enough of the Ethereum JSON-RPC API for a facilitator to start,
+//! verify and settle ERC-3009 payments without a chain:
+//!
+//! - every account holds an unlimited token balance and allowance, and every
+//!   other contract call succeeds without return data
+//! - sent transactions are mined immediately, with a successful receipt
+//! - a block is produced every second
+//!
+//! Nothing is executed, so a run against the mock chain measures the facilitator
+//! alone. Fee history is not served: configure the chain with `"eip1559": false`.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use alloy_primitives::{B256, Bytes, U256, hex, keccak256};
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use clap::Args;
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+
+/// Gas price reported by `eth_gasPrice`, in wei.
+const GAS_PRICE: u64 = 1_000_000_000;
+/// Gas reported by `eth_estimateGas`, and used by every transaction.
+const GAS_USED: u64 = 100_000;
+
+const BALANCE_OF: [u8; 4] = hex!("70a08231");
+const ALLOWANCE: [u8; 4] = hex!("dd62ed3e");
+
+#[derive(Debug, Clone, Args)]
+pub struct MockChainArgs {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:8545")]
+    pub listen: SocketAddr,
+    /// Chain id reported by `eth_chainId`.
+    #[arg(long, env = "CHAIN_ID", default_value_t = 42793)]
+    pub chain_id: u64,
+}
+
+struct MockChain {
+    chain_id: u64,
+    started: Instant,
+    /// Block each sent transaction was mined in.
+    mined: Mutex<HashMap<B256, u64>>,
+}
+
+impl MockChain {
+    fn new(chain_id: u64) -> Self {
+        Self {
+            chain_id,
+            started: Instant::now(),
+            mined: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn block_number(&self) -> u64 {
+        1 + self.started.elapsed().as_secs()
+    }
+
+    fn handle(&self, method: &str, params: &Value) -> Result<Value, String> {
+        match method {
+            "eth_chainId" => Ok(quantity(self.chain_id)),
+            "net_version" => Ok(json!(self.chain_id.to_string())),
+            "eth_blockNumber" => Ok(quantity(self.block_number())),
+            "eth_getBlockByNumber" => Ok(self.block(params)),
+            "eth_getCode" => Ok(json!("0x")),
+            "eth_getBalance" => Ok(json!(U256::from(u128::MAX))),
+            "eth_getTransactionCount" => Ok(quantity(0)),
+            "eth_gasPrice" => Ok(quantity(GAS_PRICE)),
+            "eth_estimateGas" => Ok(quantity(GAS_USED)),
+            "eth_call" => Ok(call(params)),
+            "eth_sendRawTransaction" => self.send_raw_transaction(params),
+            "eth_getTransactionReceipt" => Ok(self.receipt(params)),
+            _ => Err(format!("{method} is not served by the mock chain")),
+        }
+    }
+
+    fn send_raw_transaction(&self, params: &Value) -> Result<Value, String> {
+        let raw: Bytes = serde_json::from_value(params[0].clone())
+            .map_err(|e| format!("invalid raw transaction: {e}"))?;
+        let hash = keccak256(&raw);
+        let block = self.block_number();
+        self.mined
+            .lock()
+            .expect("mock chain lock poisoned")
+            .insert(hash, block);
+        Ok(json!(hash))
+    }
+
+    fn receipt(&self, params: &Value) -> Value {
+        let Ok(hash) = serde_json::from_value::<B256>(params[0].clone()) else {
+            return Value::Null;
+        };
+        let mined = self.mined.lock().expect("mock chain lock poisoned");
+        match mined.get(&hash) {
+            Some(&block) => receipt(hash, block),
+            None => Value::Null,
+        }
+    }
+
+    fn block(&self, params: &Value) -> Value {
+        let head = self.block_number();
+        let number = match params[0].as_str() {
+            Some(tag) if tag.starts_with("0x") => {
+                u64::from_str_radix(&tag[2..], 16).unwrap_or(head)
+            }
+            _ => head,
+        };
+        if number > head {
+            return Value::Null;
+        }
+        block(number)
+    }
+}
+
+/// Result of an `eth_call`: balances and allowances are unlimited, anything else
+/// succeeds without return data.
+fn call(params: &Value) -> Value {
+    let data = params[0]
+        .get("input")
+        .or_else(|| params[0].get("data"))
+        .and_then(|data| serde_json::from_value::<Bytes>(data.clone()).ok())
+        .unwrap_or_default();
+    match data.get(..4) {
+        Some(selector) if selector == BALANCE_OF || selector == ALLOWANCE => {
+            json!(B256::from(U256::MAX))
+        }
+        _ => json!("0x"),
+    }
+}
+
+fn quantity(value: u64) -> Value {
+    json!(format!("{value:#x}"))
+}
+
+fn block_hash(number: u64) -> B256 {
+    keccak256(number.to_be_bytes())
+}
+
+fn receipt(hash: B256, block: u64) -> Value {
+    json!({
+        "type": "0x0",
+        "status": "0x1",
+        "cumulativeGasUsed": quantity(GAS_USED),
+        "logs": [],
+        "logsBloom": Bytes::from(vec![0u8; 256]),
+        "transactionHash": hash,
+        "transactionIndex": "0x0",
+        "blockHash": block_hash(block),
+        "blockNumber": quantity(block),
+        "gasUsed": quantity(GAS_USED),
+        "effectiveGasPrice": quantity(GAS_PRICE),
+        "from": alloy_primitives::Address::ZERO,
+        "to": null,
+        "contractAddress": null,
+    })
+}
+
+fn block(number: u64) -> Value {
+    json!({
+        "hash": block_hash(number),
+        "parentHash": block_hash(number.saturating_sub(1)),
+        "sha3Uncles": B256::ZERO,
+        "miner": alloy_primitives::Address::ZERO,
+        "stateRoot": B256::ZERO,
+        "transactionsRoot": B256::ZERO,
+        "receiptsRoot": B256::ZERO,
+        "logsBloom": Bytes::from(vec![0u8; 256]),
+        "difficulty": "0x0",
+        "number": quantity(number),
+        "gasLimit": quantity(30_000_000),
+        "gasUsed": "0x0",
+        "timestamp": quantity(1_700_000_000 + number),
+        "extraData": "0x",
+        "mixHash": B256::ZERO,
+        "nonce": "0x0000000000000000",
+        "uncles": [],
+        "transactions": [],
+    })
+}
+
+async fn rpc(State(chain): State<Arc<MockChain>>, Json(body): Json<Value>) -> Json<Value> {
+    match body {
+        Value::Array(calls) => Json(Value::Array(
+            calls.iter().map(|call| respond(&chain, call)).collect(),
+        )),
+        call => Json(respond(&chain, &call)),
+    }
+}
+
+fn respond(chain: &MockChain, call: &Value) -> Value {
+    let method = call["method"].as_str().unwrap_or_default();
+    match chain.handle(method, &call["params"]) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": call["id"], "result": result }),
+        Err(message) => json!({
+            "jsonrpc": "2.0",
+            "id": call["id"],
+            "error": { "code": -32601, "message": message },
+        }),
+    }
+}
+
+pub async fn serve(args: MockChainArgs) -> Result<(), Box<dyn Error>> {
+    let chain = Arc::new(MockChain::new(args.chain_id));
+    let app = Router::new().route("/", post(rpc)).with_state(chain);
+    let listener = TcpListener::bind(args.listen).await?;
+    eprintln!(
+        "mock chain eip155:{} listening on http://{}",
+        args.chain_id,
+        listener.local_addr()?
+    );
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_rpc_types_eth::{Block, TransactionReceipt};
+
+    #[test]
+    fn test_mined_transaction_has_a_receipt() {
+        let chain = MockChain::new(42793);
+        let hash = chain
+            .handle("eth_sendRawTransaction", &json!(["0x02f8"]))
+            .unwrap();
+        let receipt = chain
+            .handle("eth_getTransactionReceipt", &json!([hash]))
+            .unwrap();
+        let receipt: TransactionReceipt = serde_json::from_value(receipt).unwrap();
+        assert!(receipt.status());
+        assert_eq!(json!(receipt.transaction_hash), hash);
+        assert_eq!(receipt.gas_used, GAS_USED);
+
+        let head = chain
+            .handle("eth_getBlockByNumber", &json!(["latest", false]))
+            .unwrap();
+        let head: Block = serde_json::from_value(head).unwrap();
+        assert!(head.header.number >= receipt.block_number.unwrap());
+        assert!(chain.handle("eth_feeHistory", &json!([])).is_err());
+    }
+}
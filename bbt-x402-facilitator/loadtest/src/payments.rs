@@ -0,0 +1,125 @@
+//! Signed synthetic payments.
+//!
+//! Every request carries a fresh ERC-3009 `TransferWithAuthorization` with a
+//! random nonce, signed by one of the payers in turn, in the x402 v2 request
+//! shape accepted by both `/verify` and `/settle`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use alloy_primitives::{Address, U256, address};
+use alloy_signer_local::PrivateKeySigner;
+use clap::Args;
+use serde_json::{Value, json};
+use x402_chain_eip155::v1_eip155_exact::client::{
+    Eip3009SigningParams, sign_erc3009_authorization,
+};
+use x402_chain_eip155::v1_eip155_exact::{AmountMatching, PaymentRequirementsExtra};
+
+/// BBT on Etherlink.
+const DEFAULT_ASSET: Address = address!("0x7EfE4bdd11237610bcFca478937658bE39F8dfd6");
+
+/// Validity window of the signed authorizations.
+const MAX_TIMEOUT_SECONDS: u64 = 300;
+
+#[derive(Debug, Clone, Args)]
+pub struct PaymentArgs {
+    /// EIP-155 chain id of the payments.
+    #[arg(long, env = "CHAIN_ID", default_value_t = 42793)]
+    pub chain_id: u64,
+    /// ERC-3009 token the payments are made in.
+    #[arg(long, env = "BBT_TOKEN", default_value_t = DEFAULT_ASSET)]
+    pub asset: Address,
+    /// EIP-712 domain name of the token.
+    #[arg(long, default_value = "BBT")]
+    pub token_name: String,
+    /// EIP-712 domain version of the token.
+    #[arg(long, default_value = "1")]
+    pub token_version: String,
+    /// Recipient of the payments.
+    #[arg(long, env = "SERVER_WALLET", default_value_t = address!("0x000000000000000000000000000000000000dEaD"))]
+    pub pay_to: Address,
+    /// Amount of each payment, in the token's smallest unit.
+    #[arg(long, default_value_t = 1000)]
+    pub amount: u128,
+    /// Number of random payer keys, used when no `--payer-keys` are given.
+    #[arg(long, default_value_t = 16)]
+    pub payers: usize,
+    /// Comma-separated private keys of funded payers, for a devnet.
+    #[arg(long, env = "LOADTEST_PAYER_KEYS", value_delimiter = ',')]
+    pub payer_keys: Vec<PrivateKeySigner>,
+}
+
+/// Builds signed payment requests, rotating through the payers.
+pub struct PaymentFactory {
+    payers: Vec<PrivateKeySigner>,
+    cursor: AtomicUsize,
+    params: Eip3009SigningParams,
+    requirements: Value,
+}
+
+impl PaymentFactory {
+    pub fn new(args: &PaymentArgs) -> Self {
+        let payers = if args.payer_keys.is_empty() {
+            (0..args.payers.max(1))
+                .map(|_| PrivateKeySigner::random())
+                .collect()
+        } else {
+            args.payer_keys.clone()
+        };
+        let extra = PaymentRequirementsExtra {
+            name: args.token_name.clone(),
+            version: args.token_version.clone(),
+            domain: Default::default(),
+            receive_forwarder: None,
+            stealth_meta_address: None,
+            discounts: Vec::new(),
+            amount_matching: AmountMatching::Exact,
+            display: None,
+        };
+        let requirements = json!({
+            "scheme": "exact",
+            "network": format!("eip155:{}", args.chain_id),
+            "amount": args.amount.to_string(),
+            "payTo": args.pay_to,
+            "maxTimeoutSeconds": MAX_TIMEOUT_SECONDS,
+            "asset": args.asset,
+            "extra": extra,
+        });
+        let params = Eip3009SigningParams {
+            chain_id: args.chain_id,
+            asset_address: args.asset,
+            pay_to: args.pay_to,
+            amount: U256::from(args.amount),
+            max_timeout_seconds: MAX_TIMEOUT_SECONDS,
+            extra: Some(extra),
+            validity: None,
+        };
+        Self {
+            payers,
+            cursor: AtomicUsize::new(0),
+            params,
+            requirements,
+        }
+    }
+
+    pub fn payer_count(&self) -> usize {
+        self.payers.len()
+    }
+
+    /// Signs a new payment and wraps it in a `/verify` or `/settle` request body.
+    pub async fn next_request(&self) -> Result<Value, String> {
+        let index = self.cursor.fetch_add(1, Ordering::Relaxed) % self.payers.len();
+        let payload = sign_erc3009_authorization(&self.payers[index], &self.params)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "x402Version": 2,
+                "accepted": self.requirements,
+                "payload": payload,
+            },
+            "paymentRequirements": self.requirements,
+        }))
+    }
+}
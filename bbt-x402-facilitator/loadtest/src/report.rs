@@ -0,0 +1,156 @@
+//! Latency percentiles and error breakdown of a run.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// How a single request ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The payment was valid, or settled.
+    Success,
+    /// The facilitator answered, but did not accept the payment.
+    Failure(String),
+}
+
+/// Collects request outcomes while the run is in progress.
+#[derive(Debug, Default)]
+pub struct Recorder {
+    latencies: Vec<Duration>,
+    succeeded: u64,
+    errors: BTreeMap<String, u64>,
+}
+
+impl Recorder {
+    pub fn record(&mut self, latency: Duration, outcome: Outcome) {
+        self.latencies.push(latency);
+        match outcome {
+            Outcome::Success => self.succeeded += 1,
+            Outcome::Failure(reason) => *self.errors.entry(reason).or_default() += 1,
+        }
+    }
+
+    pub fn finish(mut self, elapsed: Duration, target_rps: f64) -> Report {
+        self.latencies.sort_unstable();
+        let requests = self.latencies.len() as u64;
+        let total: Duration = self.latencies.iter().sum();
+        let latency_ms = LatencySummary {
+            mean: millis(total.checked_div(requests as u32).unwrap_or_default()),
+            p50: millis(percentile(&self.latencies, 50.0)),
+            p90: millis(percentile(&self.latencies, 90.0)),
+            p99: millis(percentile(&self.latencies, 99.0)),
+            max: millis(self.latencies.last().copied().unwrap_or_default()),
+        };
+        Report {
+            requests,
+            succeeded: self.succeeded,
+            failed: requests - self.succeeded,
+            elapsed_secs: elapsed.as_secs_f64(),
+            target_rps,
+            achieved_rps: requests as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+            latency_ms,
+            errors: self.errors,
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted latencies.
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Latencies of all requests, successful or not, in milliseconds.
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySummary {
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+/// Result of a run.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    pub requests: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    pub elapsed_secs: f64,
+    pub target_rps: f64,
+    /// Falls short of `target_rps` when the concurrency limit was reached.
+    pub achieved_rps: f64,
+    pub latency_ms: LatencySummary,
+    /// Failed requests by reason.
+    pub errors: BTreeMap<String, u64>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "requests:  {} ({} succeeded, {} failed) in {:.1}s",
+            self.requests, self.succeeded, self.failed, self.elapsed_secs
+        )?;
+        writeln!(
+            f,
+            "rate:      {:.1} req/s (target {:.1})",
+            self.achieved_rps, self.target_rps
+        )?;
+        let latency = &self.latency_ms;
+        writeln!(
+            f,
+            "latency:   mean {:.1}ms  p50 {:.1}ms  p90 {:.1}ms  p99 {:.1}ms  max {:.1}ms",
+            latency.mean, latency.p50, latency.p90, latency.p99, latency.max
+        )?;
+        if !self.errors.is_empty() {
+            writeln!(f, "errors:")?;
+            let mut errors: Vec<_> = self.errors.iter().collect();
+            errors.sort_by(|a, b| b.1.cmp(a.1));
+            for (reason, count) in errors {
+                writeln!(f, "  {count:>8}  {reason}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_percentiles_and_errors() {
+        let mut recorder = Recorder::default();
+        for ms in 1..=100 {
+            let outcome = if ms % 10 == 0 {
+                Outcome::Failure("HTTP 400: invalid_exact_evm_payload_signature".to_string())
+            } else {
+                Outcome::Success
+            };
+            recorder.record(Duration::from_millis(ms), outcome);
+        }
+        let report = recorder.finish(Duration::from_secs(2), 50.0);
+        assert_eq!(report.requests, 100);
+        assert_eq!(report.succeeded, 90);
+        assert_eq!(report.failed, 10);
+        assert_eq!(report.achieved_rps, 50.0);
+        assert_eq!(report.latency_ms.p50, 50.0);
+        assert_eq!(report.latency_ms.p99, 99.0);
+        assert_eq!(report.latency_ms.max, 100.0);
+        assert_eq!(
+            report.errors["HTTP 400: invalid_exact_evm_payload_signature"],
+            10
+        );
+    }
+}
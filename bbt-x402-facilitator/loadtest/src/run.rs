@@ -0,0 +1,169 @@
+//! The request loop.
+//!
+//! Requests are started at a fixed rate (open loop) with at most `concurrency`
+//! of them in flight. When the facilitator cannot keep up, starts are delayed
+//! until a slot frees up, and the report's achieved rate falls below the target.
+
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use clap::{Args, ValueEnum};
+use serde_json::Value;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio::time::{Instant, MissedTickBehavior, interval};
+
+use crate::payments::{PaymentArgs, PaymentFactory};
+use crate::report::{Outcome, Recorder};
+
+#[derive(Debug, Clone, Args)]
+pub struct RunArgs {
+    /// Base URL of the facilitator under test.
+    #[arg(long, env = "FACILITATOR_URL", default_value = "http://localhost:9090")]
+    pub facilitator_url: String,
+    /// Endpoint to send the payments to.
+    #[arg(long, value_enum, default_value_t = Endpoint::Verify)]
+    pub endpoint: Endpoint,
+    /// Requests started per second.
+    #[arg(long, default_value_t = 50.0)]
+    pub rps: f64,
+    /// Maximum number of requests in flight.
+    #[arg(long, default_value_t = 32)]
+    pub concurrency: usize,
+    /// Duration of the run, in seconds.
+    #[arg(long, default_value_t = 30)]
+    pub duration: u64,
+    /// Timeout of a single request, in seconds.
+    #[arg(long, default_value_t = 60)]
+    pub timeout: u64,
+    /// Facilitator API key, sent as a bearer token.
+    #[arg(long, env = "LOADTEST_API_KEY")]
+    pub api_key: Option<String>,
+    /// Print the report as JSON.
+    #[arg(long)]
+    pub json: bool,
+    #[command(flatten)]
+    pub payments: PaymentArgs,
+}
+
+/// Facilitator endpoint under load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Endpoint {
+    Verify,
+    Settle,
+}
+
+impl Endpoint {
+    fn path(self) -> &'static str {
+        match self {
+            Endpoint::Verify => "verify",
+            Endpoint::Settle => "settle",
+        }
+    }
+
+    /// Whether a response body accepts the payment, and the reason when it does not.
+    fn outcome(self, body: &Value) -> Outcome {
+        let (accepted, reason) = match self {
+            Endpoint::Verify => ("isValid", "invalidReason"),
+            Endpoint::Settle => ("success", "errorReason"),
+        };
+        if body.get(accepted).and_then(Value::as_bool) == Some(true) {
+            Outcome::Success
+        } else {
+            Outcome::Failure(format!("rejected: {}", reason_of(body, reason)))
+        }
+    }
+}
+
+fn reason_of(body: &Value, field: &str) -> String {
+    body.get(field)
+        .and_then(Value::as_str)
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+pub async fn run(args: RunArgs) -> Result<(), Box<dyn Error>> {
+    if !args.rps.is_finite() || args.rps <= 0.0 {
+        return Err("--rps must be a positive number".into());
+    }
+    let factory = PaymentFactory::new(&args.payments);
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(args.timeout))
+        .build()?;
+    let url = format!(
+        "{}/{}",
+        args.facilitator_url.trim_end_matches('/'),
+        args.endpoint.path()
+    );
+    eprintln!(
+        "{} {url} at {} req/s, {} in flight at most, for {}s, from {} payers",
+        args.endpoint.path(),
+        args.rps,
+        args.concurrency,
+        args.duration,
+        factory.payer_count()
+    );
+
+    let recorder = Arc::new(Mutex::new(Recorder::default()));
+    let slots = Arc::new(Semaphore::new(args.concurrency.max(1)));
+    let mut ticker = interval(Duration::from_secs_f64(1.0 / args.rps));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut tasks = JoinSet::new();
+    let started = Instant::now();
+    let deadline = started + Duration::from_secs(args.duration);
+    while ticker.tick().await < deadline {
+        let slot = slots.clone().acquire_owned().await?;
+        let body = factory.next_request().await?;
+        let request = client.post(&url).json(&body);
+        let request = match &args.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        };
+        let endpoint = args.endpoint;
+        let recorder = recorder.clone();
+        tasks.spawn(async move {
+            let sent = Instant::now();
+            let outcome = send(request, endpoint).await;
+            let latency = sent.elapsed();
+            drop(slot);
+            recorder
+                .lock()
+                .expect("recorder lock poisoned")
+                .record(latency, outcome);
+        });
+        while tasks.try_join_next().is_some() {}
+    }
+    while tasks.join_next().await.is_some() {}
+
+    let recorder = std::mem::take(&mut *recorder.lock().expect("recorder lock poisoned"));
+    let report = recorder.finish(started.elapsed(), args.rps);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{report}");
+    }
+    Ok(())
+}
+
+async fn send(request: reqwest::RequestBuilder, endpoint: Endpoint) -> Outcome {
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) if e.is_timeout() => return Outcome::Failure("transport: timeout".to_string()),
+        Err(e) if e.is_connect() => return Outcome::Failure("transport: connect".to_string()),
+        Err(_) => return Outcome::Failure("transport: other".to_string()),
+    };
+    let status = response.status();
+    let body = response.json::<Value>().await.unwrap_or(Value::Null);
+    if status.is_success() {
+        endpoint.outcome(&body)
+    } else {
+        let reason = ["invalidReason", "errorReason", "error"]
+            .iter()
+            .find_map(|field| body.get(field).and_then(Value::as_str));
+        match reason {
+            Some(reason) => Outcome::Failure(format!("HTTP {}: {reason}", status.as_u16())),
+            None => Outcome::Failure(format!("HTTP {}", status.as_u16())),
+        }
+    }
+}
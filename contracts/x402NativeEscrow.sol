@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+interface IERC1271 {
+    function isValidSignature(bytes32 hash, bytes memory signature) external view returns (bytes4 magicValue);
+}
+
+/// @title x402NativeEscrow
+/// @notice Settles x402 payments in the chain's native coin (ETH, XTZ, ...).
+/// @dev A native transfer cannot be authorized by a signature alone, so payers first
+///      deposit coin here. A payment is then an EIP-712 `NativePayment` signed by the
+///      payer, which anyone (in practice the facilitator) may submit: the escrow checks
+///      the signature, validity window and nonce, and sends `value` from the payer's
+///      deposit to `to`. Deposits can be withdrawn by their owner at any time.
+contract x402NativeEscrow {
+    error AuthorizationNotYetValid();
+    error AuthorizationExpired();
+    error AuthorizationUsed();
+    error InvalidSignature();
+    error InsufficientDeposit();
+    error TransferFailed();
+
+    event Deposited(address indexed owner, uint256 value);
+    event Withdrawn(address indexed owner, uint256 value);
+    event Paid(address indexed from, address indexed to, uint256 value, bytes32 indexed nonce);
+
+    bytes32 public constant NATIVE_PAYMENT_TYPEHASH = keccak256(
+        "NativePayment(address from,address to,uint256 value,uint256 validAfter,uint256 validBefore,bytes32 nonce)"
+    );
+    bytes32 private constant DOMAIN_TYPEHASH =
+        keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)");
+    bytes4 private constant ERC1271_MAGIC_VALUE = 0x1626ba7e;
+
+    /// @notice Native coin held for each payer.
+    mapping(address => uint256) public balanceOf;
+    /// @notice Whether a payer's nonce has been used.
+    mapping(address => mapping(bytes32 => bool)) public authorizationState;
+
+    receive() external payable {
+        deposit();
+    }
+
+    function deposit() public payable {
+        balanceOf[msg.sender] += msg.value;
+        emit Deposited(msg.sender, msg.value);
+    }
+
+    function withdraw(uint256 value) external {
+        if (balanceOf[msg.sender] < value) revert InsufficientDeposit();
+        balanceOf[msg.sender] -= value;
+        emit Withdrawn(msg.sender, value);
+        (bool ok,) = msg.sender.call{value: value}("");
+        if (!ok) revert TransferFailed();
+    }
+
+    function DOMAIN_SEPARATOR() public view returns (bytes32) {
+        return keccak256(
+            abi.encode(DOMAIN_TYPEHASH, keccak256("x402NativeEscrow"), keccak256("1"), block.chainid, address(this))
+        );
+    }
+
+    function payWithAuthorization(
+        address from,
+        address to,
+        uint256 value,
+        uint256 validAfter,
+        uint256 validBefore,
+        bytes32 nonce,
+        bytes calldata signature
+    ) external {
+        if (block.timestamp <= validAfter) revert AuthorizationNotYetValid();
+        if (block.timestamp >= validBefore) revert AuthorizationExpired();
+        if (authorizationState[from][nonce]) revert AuthorizationUsed();
+        bytes32 structHash =
+            keccak256(abi.encode(NATIVE_PAYMENT_TYPEHASH, from, to, value, validAfter, validBefore, nonce));
+        bytes32 digest = keccak256(abi.encodePacked("\x19\x01", DOMAIN_SEPARATOR(), structHash));
+        if (!_isValidSignature(from, digest, signature)) revert InvalidSignature();
+        if (balanceOf[from] < value) revert InsufficientDeposit();
+
+        authorizationState[from][nonce] = true;
+        balanceOf[from] -= value;
+        emit Paid(from, to, value, nonce);
+        (bool ok,) = to.call{value: value}("");
+        if (!ok) revert TransferFailed();
+    }
+
+    function _isValidSignature(address signer, bytes32 digest, bytes calldata signature) private view returns (bool) {
+        if (signer.code.length > 0) {
+            try IERC1271(signer).isValidSignature(digest, signature) returns (bytes4 magicValue) {
+                return magicValue == ERC1271_MAGIC_VALUE;
+            } catch {
+                return false;
+            }
+        }
+        if (signature.length != 65) return false;
+        bytes32 r = bytes32(signature[0:32]);
+        bytes32 s = bytes32(signature[32:64]);
+        uint8 v = uint8(signature[64]);
+        if (v < 27) v += 27;
+        // Reject malleable signatures (upper half of the curve order).
+        if (uint256(s) > 0x7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF5D576E7357A4501DDFE92F46681B20A0) return false;
+        address recovered = ecrecover(digest, v, r, s);
+        return recovered != address(0) && recovered == signer;
+    }
+}
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+interface IERC3009Receive {
+    function receiveWithAuthorization(
+        address from,
+        address to,
+        uint256 value,
+        uint256 validAfter,
+        uint256 validBefore,
+        bytes32 nonce,
+        bytes memory signature
+    ) external;
+
+    function transfer(address to, uint256 value) external returns (bool);
+}
+
+/// @title x402ReceiveForwarder
+/// @notice Settles ERC-3009 payments for tokens that only allow `receiveWithAuthorization`.
+/// @dev The payer signs a `ReceiveWithAuthorization` with `to` set to this contract. The
+///      first 20 bytes of the signed nonce must be the final recipient, so the relayer
+///      cannot redirect funds. Tokens are pulled into this contract and forwarded in the
+///      same transaction; nothing is held between calls.
+contract x402ReceiveForwarder {
+    error RecipientMismatch();
+    error TransferFailed();
+
+    event Forwarded(address indexed token, address indexed from, address indexed payTo, uint256 value, bytes32 nonce);
+
+    function forwardWithAuthorization(
+        address token,
+        address from,
+        address payTo,
+        uint256 value,
+        uint256 validAfter,
+        uint256 validBefore,
+        bytes32 nonce,
+        bytes calldata signature
+    ) external {
+        if (address(bytes20(nonce)) != payTo) revert RecipientMismatch();
+        IERC3009Receive(token).receiveWithAuthorization(
+            from, address(this), value, validAfter, validBefore, nonce, signature
+        );
+        if (!IERC3009Receive(token).transfer(payTo, value)) revert TransferFailed();
+        emit Forwarded(token, from, payTo, value, nonce);
+    }
+}